@@ -25,6 +25,7 @@ use common::{
 };
 use database::{
     Database,
+    InvalidationCause,
     LogReader,
     ReadSet,
     Subscription,
@@ -47,6 +48,10 @@ use keybroker::Identity;
 use model::{
     file_storage::FileStorageId,
     session_requests::types::SessionRequestIdentifier,
+    static_assets::types::{
+        StaticAsset,
+        StaticAssetMount,
+    },
 };
 use serde_json::Value as JsonValue;
 use sync_types::{
@@ -64,6 +69,8 @@ use value::{
 };
 
 use crate::{
+    identity_quotas::IdentityQuotaTracker,
+    subscription_stats::SubscriptionStatsTracker,
     Application,
     FunctionError,
     FunctionReturn,
@@ -209,6 +216,15 @@ pub trait ApplicationApi: Send + Sync {
         request_id: RequestId,
     ) -> anyhow::Result<RepeatableTimestamp>;
 
+    /// Waits until the latest timestamp includes every write committed at or
+    /// before `min_ts`, and returns it.
+    async fn wait_for_min_ts(
+        &self,
+        host: &ResolvedHostname,
+        request_id: RequestId,
+        min_ts: Timestamp,
+    ) -> anyhow::Result<Timestamp>;
+
     async fn check_store_file_authorization(
         &self,
         host: &ResolvedHostname,
@@ -248,6 +264,15 @@ pub trait ApplicationApi: Send + Sync {
         file_storage_id: FileStorageId,
     ) -> anyhow::Result<FileStream>;
 
+    /// The static asset to serve for a request to `path` on the HTTP actions
+    /// domain, along with the mount that matched it.
+    async fn resolve_static_asset(
+        &self,
+        host: &ResolvedHostname,
+        request_id: RequestId,
+        path: &str,
+    ) -> anyhow::Result<Option<(StaticAssetMount, StaticAsset)>>;
+
     // Returns a fallible subscription client. The implementation is not required to
     // recover from transient errors with the underlying connection or stream. The
     // client is responsible to Drop the client and create a new one on any system
@@ -259,6 +284,12 @@ pub trait ApplicationApi: Send + Sync {
         &self,
         host: &ResolvedHostname,
     ) -> anyhow::Result<Box<dyn SubscriptionClient>>;
+
+    /// Where sync workers record statistics on their subscribed queries.
+    fn subscription_stats(&self, host: &ResolvedHostname) -> SubscriptionStatsTracker;
+
+    /// Where sync workers count usage against per-identity quotas.
+    fn identity_quotas(&self, host: &ResolvedHostname) -> IdentityQuotaTracker;
 }
 
 // Implements ApplicationApi via Application.
@@ -285,6 +316,7 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
         ts: ExecuteQueryTimestamp,
         journal: Option<SerializedQueryJournal>,
     ) -> anyhow::Result<RedactedQueryReturn> {
+        self.fail_while_in_maintenance(&identity)?;
         anyhow::ensure!(
             caller.allowed_visibility() == AllowedVisibility::PublicOnly,
             "This method should not be used by internal callers."
@@ -316,6 +348,7 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
         ts: ExecuteQueryTimestamp,
         journal: Option<SerializedQueryJournal>,
     ) -> anyhow::Result<RedactedQueryReturn> {
+        self.fail_while_in_maintenance(&identity)?;
         anyhow::ensure!(
             path.component.is_root() || identity.is_admin() || identity.is_system(),
             "Only admin or system users can call functions on non-root components directly"
@@ -348,6 +381,7 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
         mutation_identifier: Option<SessionRequestIdentifier>,
         mutation_queue_length: Option<usize>,
     ) -> anyhow::Result<Result<RedactedMutationReturn, RedactedMutationError>> {
+        self.fail_while_in_maintenance(&identity)?;
         anyhow::ensure!(
             caller.allowed_visibility() == AllowedVisibility::PublicOnly,
             "This method should not be used by internal callers."
@@ -375,6 +409,7 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
         mutation_identifier: Option<SessionRequestIdentifier>,
         mutation_queue_length: Option<usize>,
     ) -> anyhow::Result<Result<RedactedMutationReturn, RedactedMutationError>> {
+        self.fail_while_in_maintenance(&identity)?;
         anyhow::ensure!(
             path.component.is_root() || identity.is_admin() || identity.is_system(),
             "Only admin or system users can call functions on non-root components directly"
@@ -400,6 +435,7 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
         args: Vec<JsonValue>,
         caller: FunctionCaller,
     ) -> anyhow::Result<Result<RedactedActionReturn, RedactedActionError>> {
+        self.fail_while_in_maintenance(&identity)?;
         anyhow::ensure!(
             caller.allowed_visibility() == AllowedVisibility::PublicOnly,
             "This method should not be used by internal callers."
@@ -423,6 +459,7 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
         args: Vec<JsonValue>,
         caller: FunctionCaller,
     ) -> anyhow::Result<Result<RedactedActionReturn, RedactedActionError>> {
+        self.fail_while_in_maintenance(&identity)?;
         anyhow::ensure!(
             path.component.is_root() || identity.is_admin() || identity.is_system(),
             "Only admin or system users can call functions on non-root components directly"
@@ -446,6 +483,7 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
         args: Vec<JsonValue>,
         caller: FunctionCaller,
     ) -> anyhow::Result<Result<FunctionReturn, FunctionError>> {
+        self.fail_while_in_maintenance(&identity)?;
         anyhow::ensure!(
             path.component.is_root() || identity.is_admin() || identity.is_system(),
             "Only admin or system users can call functions on non-root components directly"
@@ -461,6 +499,15 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
         Ok(self.now_ts_for_reads())
     }
 
+    async fn wait_for_min_ts(
+        &self,
+        _host: &ResolvedHostname,
+        _request_id: RequestId,
+        min_ts: Timestamp,
+    ) -> anyhow::Result<Timestamp> {
+        self.wait_for_min_ts(min_ts).await
+    }
+
     async fn execute_http_action(
         &self,
         _host: &ResolvedHostname,
@@ -470,6 +517,7 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
         caller: FunctionCaller,
         response_streamer: HttpActionResponseStreamer,
     ) -> anyhow::Result<()> {
+        self.fail_while_in_maintenance(&identity)?;
         self.http_action_udf(
             request_id,
             http_request_metadata,
@@ -535,6 +583,15 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
         self.get_file(component, file_storage_id).await
     }
 
+    async fn resolve_static_asset(
+        &self,
+        _host: &ResolvedHostname,
+        _request_id: RequestId,
+        path: &str,
+    ) -> anyhow::Result<Option<(StaticAssetMount, StaticAsset)>> {
+        self.resolve_static_asset(path).await
+    }
+
     async fn subscription_client(
         &self,
         _host: &ResolvedHostname,
@@ -543,6 +600,14 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
             database: self.database.clone(),
         }))
     }
+
+    fn subscription_stats(&self, _host: &ResolvedHostname) -> SubscriptionStatsTracker {
+        self.subscription_stats.clone()
+    }
+
+    fn identity_quotas(&self, _host: &ResolvedHostname) -> IdentityQuotaTracker {
+        self.identity_quotas.clone()
+    }
 }
 
 #[async_trait]
@@ -575,6 +640,11 @@ pub trait SubscriptionTrait: Send + Sync {
     // that extend_validity might return false even if the subscription can be
     // extended, but will never return true if it can't.
     async fn extend_validity(&self, new_ts: Timestamp) -> anyhow::Result<bool>;
+
+    /// What invalidated the subscription, if a write has invalidated it.
+    fn invalidation_cause(&self) -> Option<InvalidationCause> {
+        None
+    }
 }
 
 struct ApplicationSubscription {
@@ -593,6 +663,10 @@ impl SubscriptionTrait for ApplicationSubscription {
         self.inner.wait_for_invalidation().map(Ok).boxed()
     }
 
+    fn invalidation_cause(&self) -> Option<InvalidationCause> {
+        self.inner.invalidation_cause()
+    }
+
     #[fastrace::trace]
     async fn extend_validity(&self, new_ts: Timestamp) -> anyhow::Result<bool> {
         if new_ts < self.initial_ts {
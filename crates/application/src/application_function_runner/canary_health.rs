@@ -0,0 +1,145 @@
+//! Error rates of the canary and stable versions of the root component's
+//! functions, so a canary that fails more often than the code it's replacing
+//! is rolled back before it's promoted.
+//!
+//! Counts are kept in memory on each backend and reset when a different canary
+//! starts. They only cover mutations and actions, since queries can be served
+//! from the cache without running either version.
+
+use std::sync::Arc;
+
+use model::{
+    canary::types::CanaryDeployment,
+    source_packages::types::SourcePackageId,
+};
+use parking_lot::Mutex;
+
+#[derive(Default)]
+struct Counts {
+    calls: u64,
+    errors: u64,
+}
+
+impl Counts {
+    fn record(&mut self, failed: bool) {
+        self.calls += 1;
+        if failed {
+            self.errors += 1;
+        }
+    }
+
+    fn error_rate(&self) -> f64 {
+        if self.calls == 0 {
+            return 0.0;
+        }
+        self.errors as f64 / self.calls as f64
+    }
+}
+
+struct CanaryCounts {
+    source_package_id: SourcePackageId,
+    canary: Counts,
+    stable: Counts,
+    rolling_back: bool,
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct CanaryHealth {
+    counts: Arc<Mutex<Option<CanaryCounts>>>,
+}
+
+impl CanaryHealth {
+    /// Counts a call that ran on the canary if `on_canary` is set and on the
+    /// stable version otherwise. Returns why the canary should be rolled back
+    /// the first time it breaches its rollback policy.
+    pub(crate) fn record(
+        &self,
+        canary: &CanaryDeployment,
+        on_canary: bool,
+        failed: bool,
+    ) -> Option<String> {
+        let mut counts = self.counts.lock();
+        let counts = match &mut *counts {
+            Some(counts) if counts.source_package_id == canary.source_package_id => counts,
+            counts => counts.insert(CanaryCounts {
+                source_package_id: canary.source_package_id,
+                canary: Counts::default(),
+                stable: Counts::default(),
+                rolling_back: false,
+            }),
+        };
+        if on_canary {
+            counts.canary.record(failed);
+        } else {
+            counts.stable.record(failed);
+        }
+        let policy = &canary.rollback_policy;
+        if counts.rolling_back || counts.canary.calls < policy.min_invocations {
+            return None;
+        }
+        let canary_rate = counts.canary.error_rate();
+        let stable_rate = counts.stable.error_rate();
+        if canary_rate - stable_rate <= policy.max_error_rate_increase {
+            return None;
+        }
+        counts.rolling_back = true;
+        Some(format!(
+            "The canary's error rate of {:.1}% over {} calls exceeded the stable version's rate \
+             of {:.1}% by more than {:.1} percentage points.",
+            canary_rate * 100.0,
+            counts.canary.calls,
+            stable_rate * 100.0,
+            policy.max_error_rate_increase * 100.0,
+        ))
+    }
+
+    /// Lets the canary trigger a rollback again, after an attempt failed.
+    pub(crate) fn rollback_failed(&self, source_package_id: SourcePackageId) {
+        if let Some(counts) = &mut *self.counts.lock()
+            && counts.source_package_id == source_package_id
+        {
+            counts.rolling_back = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use model::canary::types::{
+        CanaryDeployment,
+        CanaryRollbackPolicy,
+        CanaryRouting,
+        CanaryState,
+    };
+    use value::DeveloperDocumentId;
+
+    use super::CanaryHealth;
+
+    #[test]
+    fn test_canary_health_rolls_back_once() {
+        let canary = CanaryDeployment {
+            source_package_id: DeveloperDocumentId::MIN.into(),
+            traffic_percent: 50,
+            routing: CanaryRouting::Invocation,
+            rollback_policy: CanaryRollbackPolicy {
+                min_invocations: 4,
+                max_error_rate_increase: 0.25,
+            },
+            state: CanaryState::Active,
+            started_at_ms: 0,
+        };
+        let health = CanaryHealth::default();
+        for _ in 0..4 {
+            assert_eq!(health.record(&canary, false, false), None);
+        }
+        assert_eq!(health.record(&canary, true, true), None);
+        for _ in 0..3 {
+            // One failure in four calls is within the allowed increase.
+            assert_eq!(health.record(&canary, true, false), None);
+        }
+        // Two failures in five calls isn't.
+        assert!(health.record(&canary, true, true).is_some());
+        // The rollback is only requested once.
+        assert_eq!(health.record(&canary, true, true), None);
+    }
+}
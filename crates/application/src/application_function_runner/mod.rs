@@ -4,6 +4,7 @@ use std::{
         BTreeMap,
         BTreeSet,
     },
+    num::NonZeroU32,
     sync::{
         atomic::AtomicUsize,
         Arc,
@@ -35,6 +36,7 @@ use common::{
     errors::JsError,
     execution_context::ExecutionContext,
     fastrace_helpers::EncodedSpan,
+    http::rate_limit::HttpActionRateLimiter,
     knobs::{
         APPLICATION_FUNCTION_RUNNER_SEMAPHORE_TIMEOUT,
         APPLICATION_MAX_CONCURRENT_HTTP_ACTIONS,
@@ -76,7 +78,11 @@ use common::{
 };
 use database::{
     unauthorized_error,
+    ComponentCallCounter,
+    ComponentQuotasModel,
     Database,
+    HybridSearch,
+    PublicHybridSearchQueryResult,
     Token,
     Transaction,
 };
@@ -105,11 +111,19 @@ use keybroker::{
 };
 use model::{
     backend_state::BackendStateModel,
+    canary::{
+        types::CanaryDeployment,
+        CanaryModel,
+    },
     components::handles::FunctionHandlesModel,
     config::{
         module_loader::ModuleLoader,
         types::ModuleConfig,
     },
+    deployment_audit_log::{
+        types::DeploymentAuditLogEvent,
+        DeploymentAuditLogModel,
+    },
     environment_variables::{
         types::{
             EnvVarName,
@@ -133,6 +147,10 @@ use model::{
         },
         ModuleModel,
     },
+    queues::{
+        types::LeasedQueueMessage,
+        QueueModel,
+    },
     scheduled_jobs::VirtualSchedulerModel,
     session_requests::{
         types::{
@@ -143,10 +161,14 @@ use model::{
         SessionRequestModel,
     },
     source_packages::{
-        types::SourcePackage,
+        types::{
+            SourcePackage,
+            SourcePackageId,
+        },
         SourcePackageModel,
     },
     udf_config::types::UdfConfig,
+    webhook_nonces::WebhookNonceModel,
 };
 use node_executor::{
     Actions,
@@ -190,14 +212,17 @@ use vector::{
     VectorSearch,
 };
 
-use self::metrics::{
-    function_waiter_timer,
-    log_occ_retries,
-    log_outstanding_functions,
-    log_udf_executor_result,
-    mutation_timer,
-    OutstandingFunctionState,
-    UdfExecutorResult,
+use self::{
+    canary_health::CanaryHealth,
+    metrics::{
+        function_waiter_timer,
+        log_occ_retries,
+        log_outstanding_functions,
+        log_udf_executor_result,
+        mutation_timer,
+        OutstandingFunctionState,
+        UdfExecutorResult,
+    },
 };
 use crate::{
     application_function_runner::metrics::{
@@ -221,6 +246,7 @@ use crate::{
     QueryReturn,
 };
 
+mod canary_health;
 mod http_routing;
 mod metrics;
 
@@ -587,6 +613,9 @@ pub struct ApplicationFunctionRunner<RT: Runtime> {
     cache_manager: CacheManager<RT>,
     default_system_env_vars: BTreeMap<EnvVarName, EnvVarValue>,
     node_action_limiter: Limiter,
+    component_calls: ComponentCallCounter,
+    canary_health: CanaryHealth,
+    http_rate_limiter: HttpActionRateLimiter<RT>,
 }
 
 impl<RT: Runtime> ApplicationFunctionRunner<RT> {
@@ -609,15 +638,18 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
             database.clone(),
             default_system_env_vars.clone(),
         );
+        let component_calls = ComponentCallCounter::default();
         let cache_manager = CacheManager::new(
             runtime.clone(),
             database.clone(),
             isolate_functions.clone(),
             function_log.clone(),
+            component_calls.clone(),
             cache,
         );
 
         Self {
+            http_rate_limiter: HttpActionRateLimiter::new(runtime.clone()),
             runtime,
             database,
             key_broker,
@@ -634,6 +666,8 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
                 UdfType::Action,
                 *APPLICATION_MAX_CONCURRENT_NODE_ACTIONS,
             ),
+            component_calls,
+            canary_health: CanaryHealth::default(),
         }
     }
 
@@ -1058,6 +1092,10 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
         };
 
         let path = path_and_args.path().clone();
+        ComponentQuotasModel::new(&mut tx)
+            .check_function_call(path.component, &self.component_calls)
+            .await?;
+        let canary = Self::canary_routing(&mut tx, path.component).await?;
         let (mut tx, outcome) = self
             .isolate_functions
             .execute_query_or_mutation(
@@ -1082,10 +1120,68 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
             &table_mapping,
             mutation_queue_length,
         );
+        self.record_canary_outcome(canary, outcome.result.is_err())
+            .await;
 
         Ok((tx, outcome))
     }
 
+    /// The active canary deploy and whether it runs this call, for calls to
+    /// the root component. This makes the same choice as the module lookups
+    /// made while running the function, since those see the same identity and
+    /// begin timestamp.
+    async fn canary_routing(
+        tx: &mut Transaction<RT>,
+        component: ComponentId,
+    ) -> anyhow::Result<Option<(CanaryDeployment, bool)>> {
+        if !component.is_root() {
+            return Ok(None);
+        }
+        let Some(canary) = CanaryModel::new(tx).active().await? else {
+            return Ok(None);
+        };
+        let on_canary = canary.routes_to_canary(tx.identity(), *tx.begin_timestamp());
+        Ok(Some((canary, on_canary)))
+    }
+
+    async fn record_canary_outcome(&self, canary: Option<(CanaryDeployment, bool)>, failed: bool) {
+        let Some((canary, on_canary)) = canary else {
+            return;
+        };
+        let Some(reason) = self.canary_health.record(&canary, on_canary, failed) else {
+            return;
+        };
+        if let Err(e) = self
+            .roll_back_unhealthy_canary(canary.source_package_id, reason)
+            .await
+        {
+            tracing::error!("Failed to roll back unhealthy canary: {e:?}");
+            self.canary_health.rollback_failed(canary.source_package_id);
+        }
+    }
+
+    async fn roll_back_unhealthy_canary(
+        &self,
+        source_package_id: SourcePackageId,
+        reason: String,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        // Another backend may have already rolled it back or promoted it.
+        match CanaryModel::new(&mut tx).active().await? {
+            Some(canary) if canary.source_package_id == source_package_id => {},
+            _ => return Ok(()),
+        }
+        tracing::warn!("Rolling back canary {source_package_id:?}: {reason}");
+        CanaryModel::new(&mut tx).roll_back(reason.clone()).await?;
+        DeploymentAuditLogModel::new(&mut tx)
+            .insert(vec![DeploymentAuditLogEvent::RollBackCanary { reason }])
+            .await?;
+        self.database
+            .commit_with_write_source(tx, "canary_auto_rollback")
+            .await?;
+        Ok(())
+    }
+
     #[fastrace::trace]
     pub async fn run_action(
         &self,
@@ -1242,6 +1338,9 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
         };
 
         let component = path_and_args.path().component;
+        ComponentQuotasModel::new(&mut tx)
+            .check_function_call(component, &self.component_calls)
+            .await?;
 
         // We should use table mappings from the same transaction as the output
         // validator was retrieved.
@@ -1255,6 +1354,7 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
             .get_metadata_for_function_by_id(&path)
             .await?
             .context("Missing a valid module")?;
+        let canary = Self::canary_routing(&mut tx, path.component).await?;
         let (log_line_sender, log_line_receiver) = mpsc::unbounded_channel();
 
         let inert_identity = tx.inert_identity();
@@ -1310,24 +1410,54 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
                     module_path: path.udf_path.module().clone(),
                 };
                 let component = path.component;
-                let module_metadata = match ModuleModel::new(&mut tx)
-                    .get_metadata(module_path.clone())
-                    .await?
-                {
-                    Some(r) => r,
-                    None => anyhow::bail!("Missing a valid module_version"),
-                };
+                let module_metadata =
+                    match ModuleModel::new(&mut tx).get_metadata(module_path).await? {
+                        Some(r) => r,
+                        None => anyhow::bail!("Missing a valid module_version"),
+                    };
                 let source_package = SourcePackageModel::new(&mut tx, component.into())
                     .get(module_metadata.source_package_id)
                     .await?;
-                let source_maps_callback = async {
-                    let module_version = self
-                        .module_cache
-                        .get_module_with_metadata(module_metadata, source_package)
-                        .await?;
+                let source_maps_callback = move |module_paths: BTreeSet<
+                    CanonicalizedModulePath,
+                >| async move {
+                    // Frames may also point into the chunks under `_deps/node/` that
+                    // hold code shared between Node modules, so look up whichever
+                    // modules besides this one they reference.
+                    let mut modules = vec![];
+                    let mut other_paths = vec![];
+                    for module_path in module_paths {
+                        if module_path == module_metadata.path {
+                            modules.push(module_metadata.clone());
+                        } else {
+                            other_paths.push(module_path);
+                        }
+                    }
+                    if !other_paths.is_empty() {
+                        let mut tx = self.database.begin_system().await?;
+                        for module_path in other_paths {
+                            let path = CanonicalizedComponentModulePath {
+                                component,
+                                module_path,
+                            };
+                            if let Some(metadata) =
+                                ModuleModel::new(&mut tx).get_metadata(path).await?
+                                && metadata.source_package_id == module_metadata.source_package_id
+                            {
+                                modules.push(metadata);
+                            }
+                        }
+                    }
                     let mut source_maps = BTreeMap::new();
-                    if let Some(source_map) = module_version.source_map.clone() {
-                        source_maps.insert(module_path.module_path.clone(), source_map);
+                    for metadata in modules {
+                        let path = metadata.path.clone();
+                        let module_version = self
+                            .module_cache
+                            .get_module_with_metadata(metadata, source_package.clone())
+                            .await?;
+                        if let Some(source_map) = module_version.source_map.clone() {
+                            source_maps.insert(path, source_map);
+                        }
                     }
                     Ok(source_maps)
                 };
@@ -1457,7 +1587,7 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
                 Err(anyhow::anyhow!("Attempting to run an invalid function"))
             },
         };
-        match completion_result {
+        let completion_result = match completion_result {
             Ok(c) => Ok(c),
             Err(e) if e.is_deterministic_user_error() => {
                 let outcome = ValidatedActionOutcome::from_error(
@@ -1487,7 +1617,12 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
                 })
             },
             Err(e) => Err(e),
+        };
+        if let Ok(completion) = &completion_result {
+            self.record_canary_outcome(canary, completion.outcome.result.is_err())
+                .await;
         }
+        completion_result
     }
 
     #[fastrace::trace]
@@ -2079,6 +2214,7 @@ impl<RT: Runtime> ActionCallbacks for ApplicationFunctionRunner<RT> {
         udf_args: Vec<JsonValue>,
         scheduled_ts: UnixTimestamp,
         context: ExecutionContext,
+        idempotency_key: Option<String>,
     ) -> anyhow::Result<DeveloperDocumentId> {
         let (_ts, virtual_id, _stats) = self
             .database
@@ -2090,6 +2226,7 @@ impl<RT: Runtime> ActionCallbacks for ApplicationFunctionRunner<RT> {
                     let path = scheduled_path.clone();
                     let args = udf_args.clone();
                     let context = context.clone();
+                    let idempotency_key = idempotency_key.clone();
                     async move {
                         let (path, udf_args) = validate_schedule_args(
                             path,
@@ -2103,7 +2240,7 @@ impl<RT: Runtime> ActionCallbacks for ApplicationFunctionRunner<RT> {
                         .await?;
                         let virtual_id =
                             VirtualSchedulerModel::new(tx, scheduling_component.into())
-                                .schedule(path, udf_args, scheduled_ts, context)
+                                .schedule(path, udf_args, scheduled_ts, context, idempotency_key)
                                 .await?;
                         Ok(virtual_id)
                     }
@@ -2137,6 +2274,120 @@ impl<RT: Runtime> ActionCallbacks for ApplicationFunctionRunner<RT> {
         Ok(())
     }
 
+    async fn queue_lease(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        queue: String,
+        max_messages: usize,
+        visibility_timeout: Duration,
+    ) -> anyhow::Result<Vec<LeasedQueueMessage>> {
+        let (_ts, messages, _stats) = self
+            .database
+            .execute_with_occ_retries(
+                identity,
+                FunctionUsageTracker::new(),
+                "app_funrun_queue_lease",
+                |tx| {
+                    let queue = queue.clone();
+                    async move {
+                        let now = self.runtime.unix_timestamp();
+                        QueueModel::new(tx, component.into())
+                            .lease(&queue, max_messages, visibility_timeout, now)
+                            .await
+                    }
+                    .into()
+                },
+            )
+            .await?;
+        Ok(messages)
+    }
+
+    async fn queue_ack(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        receipt: String,
+    ) -> anyhow::Result<()> {
+        self.database
+            .execute_with_occ_retries(
+                identity,
+                FunctionUsageTracker::new(),
+                "app_funrun_queue_ack",
+                |tx| {
+                    let receipt = receipt.clone();
+                    async move { QueueModel::new(tx, component.into()).ack(&receipt).await }.into()
+                },
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn queue_nack(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        receipt: String,
+        delay: Duration,
+    ) -> anyhow::Result<()> {
+        self.database
+            .execute_with_occ_retries(
+                identity,
+                FunctionUsageTracker::new(),
+                "app_funrun_queue_nack",
+                |tx| {
+                    let receipt = receipt.clone();
+                    async move {
+                        let visible_at = self.runtime.unix_timestamp() + delay;
+                        QueueModel::new(tx, component.into())
+                            .nack(&receipt, visible_at)
+                            .await
+                    }
+                    .into()
+                },
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn record_webhook_nonce(
+        &self,
+        scope: String,
+        nonce: String,
+        expires_at: UnixTimestamp,
+    ) -> anyhow::Result<bool> {
+        let expires_at_ms = expires_at.as_ms_since_epoch()? as i64;
+        let (_ts, accepted, _stats) = self
+            .database
+            .execute_with_occ_retries(
+                Identity::system(),
+                FunctionUsageTracker::new(),
+                "app_funrun_record_webhook_nonce",
+                |tx| {
+                    let scope = scope.clone();
+                    let nonce = nonce.clone();
+                    async move {
+                        let now_ms = self.runtime.unix_timestamp().as_ms_since_epoch()? as i64;
+                        WebhookNonceModel::new(tx)
+                            .record(scope, nonce, expires_at_ms, now_ms)
+                            .await
+                    }
+                    .into()
+                },
+            )
+            .await?;
+        Ok(accepted)
+    }
+
+    async fn check_http_rate_limit(
+        &self,
+        key: String,
+        requests: NonZeroU32,
+        period: Duration,
+    ) -> anyhow::Result<Option<Duration>> {
+        self.http_rate_limiter.check(&key, requests, period)
+    }
+
     async fn vector_search(
         &self,
         identity: Identity,
@@ -2149,6 +2400,18 @@ impl<RT: Runtime> ActionCallbacks for ApplicationFunctionRunner<RT> {
         self.database.vector_search(identity, query).await
     }
 
+    async fn hybrid_search(
+        &self,
+        identity: Identity,
+        query: JsonValue,
+    ) -> anyhow::Result<(Vec<PublicHybridSearchQueryResult>, FunctionUsageStats)> {
+        let query = HybridSearch::try_from(query).map_err(|e| {
+            let message = e.to_string();
+            e.context(ErrorMetadata::bad_request("InvalidHybridSearch", message))
+        })?;
+        self.database.hybrid_search(identity, query).await
+    }
+
     async fn lookup_function_handle(
         &self,
         identity: Identity,
@@ -0,0 +1,61 @@
+use std::{
+    future::Future,
+    time::Duration,
+};
+
+use common::{
+    backoff::Backoff,
+    errors::report_error,
+    runtime::Runtime,
+};
+use tokio::sync::mpsc;
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_BACKOFF: Duration = Duration::from_secs(10);
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Returns a sender for records and a worker that writes them in batches of
+/// up to `max_batch_size` with `write_batch`, retrying a failed batch with
+/// backoff a few times before dropping it. Records sent while `buffer_size`
+/// records are already waiting are up to the sender to drop.
+///
+/// `name` is the worker's name for logs, and `description` describes the
+/// records, e.g. "function runs".
+pub fn batched_writer<RT, T, F, Fut>(
+    runtime: RT,
+    name: &'static str,
+    description: &'static str,
+    buffer_size: usize,
+    max_batch_size: usize,
+    write_batch: F,
+) -> (mpsc::Sender<T>, impl Future<Output = ()> + Send)
+where
+    RT: Runtime,
+    T: Clone + Send + 'static,
+    F: Fn(Vec<T>) -> Fut + Send + 'static,
+    Fut: Future<Output = anyhow::Result<()>> + Send,
+{
+    let (tx, mut rx) = mpsc::channel(buffer_size);
+    let worker = async move {
+        tracing::info!("Starting {name}");
+        let mut batch = Vec::with_capacity(max_batch_size);
+        while rx.recv_many(&mut batch, max_batch_size).await > 0 {
+            let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+            while let Err(mut e) = write_batch(batch.clone()).await {
+                if backoff.failures() + 1 >= MAX_ATTEMPTS {
+                    report_error(&mut e.context(format!(
+                        "Dropping {} {description} after repeated failures",
+                        batch.len()
+                    )))
+                    .await;
+                    break;
+                }
+                let delay = backoff.fail(&mut runtime.rng());
+                tracing::warn!("Failed to write {description}, retrying in {delay:?}: {e:#}");
+                runtime.wait(delay).await;
+            }
+            batch.clear();
+        }
+    };
+    (tx, worker)
+}
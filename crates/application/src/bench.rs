@@ -0,0 +1,585 @@
+//! Synthetic workloads for measuring a deployment's write and subscription
+//! performance, so self-hosters can compare releases on their own hardware.
+//!
+//! A bench run writes generated documents to a scratch table straight through
+//! the database, skipping function execution, so the numbers reflect the
+//! commit pipeline rather than user code. The sequence of operations is fully
+//! determined by the seed: two runs with the same config issue the same
+//! inserts, updates, deletes, and queries, and only the timings differ.
+//!
+//! The report covers:
+//! - commit and query latency,
+//! - index write amplification, as index entries written per document write,
+//!   computed with the same index registry the committer uses,
+//! - subscription fan-out, as how many subscriptions each commit invalidates
+//!   and how long until every subscription has caught up with it.
+
+use std::{
+    collections::VecDeque,
+    time::Duration,
+};
+
+use common::{
+    components::ComponentId,
+    query::{
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+};
+use database::{
+    ResolvedQuery,
+    Subscription,
+    TableModel,
+    Transaction,
+    UserFacingModel,
+};
+use errors::ErrorMetadata;
+use keybroker::Identity;
+use rand::{
+    rngs::StdRng,
+    Rng,
+    SeedableRng,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::{
+    obj,
+    DeveloperDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+use crate::Application;
+
+const MAX_TRANSACTIONS: usize = 100_000;
+const MAX_WRITES_PER_TRANSACTION: usize = 1000;
+const MAX_QUERIES_PER_TRANSACTION: usize = 1000;
+const MAX_SUBSCRIPTIONS: usize = 10_000;
+const MAX_DOCUMENT_SIZE_BYTES: usize = 512 * 1024;
+/// Distinct values of the `group` field, so indexes on it see updates that
+/// move documents between keys.
+const NUM_GROUPS: i64 = 16;
+/// How long to wait for subscriptions to catch up with a commit before
+/// giving up on the run.
+const SUBSCRIPTION_CATCH_UP_TIMEOUT: Duration = Duration::from_secs(30);
+const CLEANUP_BATCH_SIZE: usize = 1000;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchConfigJson {
+    seed: Option<u64>,
+    table_name: Option<String>,
+    transactions: Option<usize>,
+    writes_per_transaction: Option<usize>,
+    write_mix: Option<WriteMix>,
+    document_size_bytes: Option<usize>,
+    query_shape: Option<QueryShape>,
+    queries_per_transaction: Option<usize>,
+    subscriptions: Option<usize>,
+}
+
+/// Relative weights of each kind of write. Updates and deletes fall back to
+/// inserts while the table is empty.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WriteMix {
+    pub inserts: u32,
+    pub updates: u32,
+    pub deletes: u32,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum QueryShape {
+    /// Fetch a random live document by ID.
+    PointGet,
+    /// Read the oldest `limit` documents in `_creationTime` order.
+    Scan { limit: usize },
+}
+
+#[derive(Debug)]
+pub struct BenchConfig {
+    pub seed: u64,
+    /// A table that doesn't exist yet or is empty. Tables the bench creates
+    /// are deleted afterwards; existing tables are emptied again.
+    pub table_name: TableName,
+    pub transactions: usize,
+    pub writes_per_transaction: usize,
+    pub write_mix: WriteMix,
+    pub document_size_bytes: usize,
+    pub query_shape: QueryShape,
+    /// Queries run in each write transaction before its writes.
+    pub queries_per_transaction: usize,
+    /// Long-lived subscriptions, each on one query of `query_shape`, that are
+    /// re-run whenever a commit invalidates them.
+    pub subscriptions: usize,
+}
+
+impl TryFrom<BenchConfigJson> for BenchConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(json: BenchConfigJson) -> anyhow::Result<Self> {
+        let table_name: TableName = json
+            .table_name
+            .as_deref()
+            .unwrap_or("bench_documents")
+            .parse()?;
+        if table_name.is_system() {
+            anyhow::bail!(invalid_bench_config(format!(
+                "Can't run a bench against system table {table_name}"
+            )));
+        }
+        let config = Self {
+            seed: json.seed.unwrap_or(0),
+            table_name,
+            transactions: json.transactions.unwrap_or(1000),
+            writes_per_transaction: json.writes_per_transaction.unwrap_or(10),
+            write_mix: json.write_mix.unwrap_or(WriteMix {
+                inserts: 2,
+                updates: 1,
+                deletes: 1,
+            }),
+            document_size_bytes: json.document_size_bytes.unwrap_or(256),
+            query_shape: json.query_shape.unwrap_or(QueryShape::Scan { limit: 10 }),
+            queries_per_transaction: json.queries_per_transaction.unwrap_or(1),
+            subscriptions: json.subscriptions.unwrap_or(0),
+        };
+        let limits = [
+            ("transactions", config.transactions, MAX_TRANSACTIONS),
+            (
+                "writesPerTransaction",
+                config.writes_per_transaction,
+                MAX_WRITES_PER_TRANSACTION,
+            ),
+            (
+                "queriesPerTransaction",
+                config.queries_per_transaction,
+                MAX_QUERIES_PER_TRANSACTION,
+            ),
+            ("subscriptions", config.subscriptions, MAX_SUBSCRIPTIONS),
+            (
+                "documentSizeBytes",
+                config.document_size_bytes,
+                MAX_DOCUMENT_SIZE_BYTES,
+            ),
+        ];
+        for (name, value, max) in limits {
+            if value > max {
+                anyhow::bail!(invalid_bench_config(format!(
+                    "{name} is {value}, but can be at most {max}"
+                )));
+            }
+        }
+        let WriteMix {
+            inserts,
+            updates,
+            deletes,
+        } = config.write_mix;
+        if inserts == 0 && config.writes_per_transaction > 0 {
+            anyhow::bail!(invalid_bench_config(
+                "writeMix.inserts must be positive so the table has documents to update, delete, \
+                 and query"
+                    .to_string()
+            ));
+        }
+        if inserts
+            .checked_add(updates)
+            .and_then(|w| w.checked_add(deletes))
+            .is_none()
+        {
+            anyhow::bail!(invalid_bench_config(
+                "writeMix weights are too large".to_string()
+            ));
+        }
+        Ok(config)
+    }
+}
+
+fn invalid_bench_config(msg: String) -> ErrorMetadata {
+    ErrorMetadata::bad_request("InvalidBenchConfig", msg)
+}
+
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencySummary {
+    pub count: usize,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+impl LatencySummary {
+    fn new(mut samples: Vec<Duration>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        samples.sort();
+        let percentile = |p: usize| {
+            let i = ((samples.len() * p).div_ceil(100)).saturating_sub(1);
+            samples[i].as_secs_f64() * 1000.0
+        };
+        Self {
+            count: samples.len(),
+            p50_ms: percentile(50),
+            p90_ms: percentile(90),
+            p99_ms: percentile(99),
+            max_ms: percentile(100),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchReport {
+    pub seed: u64,
+    pub inserts: usize,
+    pub updates: usize,
+    pub deletes: usize,
+    pub queries: usize,
+    pub commit_latency: LatencySummary,
+    pub query_latency: LatencySummary,
+    pub document_writes: usize,
+    pub index_writes: usize,
+    /// `index_writes / document_writes`.
+    pub index_write_amplification: f64,
+    pub subscription_invalidations: usize,
+    /// Mean subscriptions invalidated per commit.
+    pub subscription_fan_out: f64,
+    /// Time from a commit returning until every subscription was either
+    /// invalidated or advanced past it.
+    pub subscription_catch_up_latency: LatencySummary,
+    pub duration_ms: u64,
+}
+
+/// Live documents in the bench table, oldest first.
+struct LiveDocuments {
+    ids: VecDeque<DeveloperDocumentId>,
+}
+
+impl LiveDocuments {
+    fn random(&self, rng: &mut StdRng) -> Option<DeveloperDocumentId> {
+        if self.ids.is_empty() {
+            return None;
+        }
+        Some(self.ids[rng.random_range(0..self.ids.len())])
+    }
+
+    fn remove(&mut self, id: DeveloperDocumentId) {
+        if let Some(i) = self.ids.iter().position(|live| *live == id) {
+            self.ids.remove(i);
+        }
+    }
+}
+
+struct BenchSubscription {
+    subscription: Subscription,
+    /// Drawn once, so a point-get subscription keeps watching the same
+    /// document until it's deleted.
+    target: Option<DeveloperDocumentId>,
+}
+
+impl<RT: Runtime> Application<RT> {
+    pub async fn run_bench(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        config: BenchConfig,
+    ) -> anyhow::Result<BenchReport> {
+        let namespace = TableNamespace::from(component);
+        let start = self.runtime.monotonic_now();
+
+        let mut tx = self.begin(identity.clone()).await?;
+        let created_table = !TableModel::new(&mut tx).table_exists(namespace, &config.table_name);
+        if created_table {
+            TableModel::new(&mut tx)
+                .insert_table_metadata(namespace, &config.table_name)
+                .await?;
+            self.commit(tx, "bench_create_table").await?;
+        } else if tx.must_count(namespace, &config.table_name).await? > 0 {
+            anyhow::bail!(invalid_bench_config(format!(
+                "Benches can only run against new or empty tables, but {} has documents",
+                config.table_name
+            )));
+        }
+
+        let result = self.run_bench_inner(&identity, namespace, &config).await;
+
+        // Clean up even if the bench failed partway through.
+        let mut tx = self.begin(identity.clone()).await?;
+        if created_table {
+            TableModel::new(&mut tx)
+                .delete_active_table(namespace, config.table_name.clone())
+                .await?;
+            self.commit(tx, "bench_cleanup").await?;
+        } else {
+            drop(tx);
+            self.empty_bench_table(&identity, namespace, &config.table_name)
+                .await?;
+        }
+
+        let mut report = result?;
+        report.duration_ms = (self.runtime.monotonic_now() - start).as_millis() as u64;
+        Ok(report)
+    }
+
+    async fn run_bench_inner(
+        &self,
+        identity: &Identity,
+        namespace: TableNamespace,
+        config: &BenchConfig,
+    ) -> anyhow::Result<BenchReport> {
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let mut live = LiveDocuments {
+            ids: VecDeque::new(),
+        };
+        let mut report = BenchReport {
+            seed: config.seed,
+            inserts: 0,
+            updates: 0,
+            deletes: 0,
+            queries: 0,
+            commit_latency: LatencySummary::default(),
+            query_latency: LatencySummary::default(),
+            document_writes: 0,
+            index_writes: 0,
+            index_write_amplification: 0.0,
+            subscription_invalidations: 0,
+            subscription_fan_out: 0.0,
+            subscription_catch_up_latency: LatencySummary::default(),
+            duration_ms: 0,
+        };
+        let mut commit_latencies = vec![];
+        let mut query_latencies = vec![];
+        let mut catch_up_latencies = vec![];
+
+        let mut subscriptions = vec![];
+        for _ in 0..config.subscriptions {
+            let target = live.random(&mut rng);
+            subscriptions.push(
+                self.bench_subscribe(identity, namespace, config, target)
+                    .await?,
+            );
+        }
+
+        let mut seq = 0i64;
+        for _ in 0..config.transactions {
+            let mut tx = self.begin(identity.clone()).await?;
+            for _ in 0..config.queries_per_transaction {
+                let target = live.random(&mut rng);
+                let query_start = self.runtime.monotonic_now();
+                run_bench_query(&mut tx, namespace, config, target).await?;
+                query_latencies.push(self.runtime.monotonic_now() - query_start);
+                report.queries += 1;
+            }
+
+            for _ in 0..config.writes_per_transaction {
+                let WriteMix {
+                    inserts,
+                    updates,
+                    deletes,
+                } = config.write_mix;
+                let choice = rng.random_range(0..inserts + updates + deletes);
+                let target = live.random(&mut rng);
+                let group = rng.random_range(0..NUM_GROUPS);
+                let payload = random_payload(&mut rng, config.document_size_bytes);
+                let document = obj!(
+                    "seq" => seq,
+                    "group" => group,
+                    "payload" => payload,
+                )?;
+                seq += 1;
+                let mut model = UserFacingModel::new(&mut tx, namespace);
+                match target {
+                    Some(id) if choice >= inserts && choice < inserts + updates => {
+                        model.replace(id, document).await?;
+                        report.updates += 1;
+                    },
+                    Some(id) if choice >= inserts + updates => {
+                        model.delete(id).await?;
+                        live.remove(id);
+                        report.deletes += 1;
+                    },
+                    _ => {
+                        let id = model.insert(config.table_name.clone(), document).await?;
+                        live.ids.push_back(id);
+                        report.inserts += 1;
+                    },
+                }
+            }
+
+            let snapshot = self.database.latest_snapshot()?;
+            for (_, update) in tx.writes().as_flat()?.coalesced_writes() {
+                report.document_writes += 1;
+                report.index_writes += snapshot
+                    .index_registry()
+                    .index_updates(
+                        update.old_document.as_ref().map(|(document, _)| document),
+                        update.new_document.as_ref(),
+                    )
+                    .len();
+            }
+
+            let commit_start = self.runtime.monotonic_now();
+            let commit_ts = self.commit(tx, "bench").await?;
+            let committed = self.runtime.monotonic_now();
+            commit_latencies.push(committed - commit_start);
+
+            if subscriptions.is_empty() {
+                continue;
+            }
+            let mut invalidated = vec![];
+            let mut pending: Vec<_> = (0..subscriptions.len()).collect();
+            while !pending.is_empty() {
+                pending.retain(|&i| {
+                    let subscription: &BenchSubscription = &subscriptions[i];
+                    match subscription.subscription.current_ts() {
+                        None => {
+                            invalidated.push(i);
+                            false
+                        },
+                        Some(ts) => ts < commit_ts,
+                    }
+                });
+                if pending.is_empty() {
+                    break;
+                }
+                if self.runtime.monotonic_now() - committed > SUBSCRIPTION_CATCH_UP_TIMEOUT {
+                    anyhow::bail!(
+                        "{} subscriptions didn't catch up with a commit within {:?}",
+                        pending.len(),
+                        SUBSCRIPTION_CATCH_UP_TIMEOUT
+                    );
+                }
+                tokio::task::yield_now().await;
+            }
+            catch_up_latencies.push(self.runtime.monotonic_now() - committed);
+            report.subscription_invalidations += invalidated.len();
+
+            // Re-run invalidated queries like a client would, keeping the same
+            // target unless it was deleted.
+            for i in invalidated {
+                let target = match subscriptions[i].target {
+                    Some(id) if live.ids.contains(&id) => Some(id),
+                    _ => live.random(&mut rng),
+                };
+                subscriptions[i] = self
+                    .bench_subscribe(identity, namespace, config, target)
+                    .await?;
+            }
+        }
+
+        report.commit_latency = LatencySummary::new(commit_latencies);
+        report.query_latency = LatencySummary::new(query_latencies);
+        report.subscription_catch_up_latency = LatencySummary::new(catch_up_latencies);
+        if report.document_writes > 0 {
+            report.index_write_amplification =
+                report.index_writes as f64 / report.document_writes as f64;
+        }
+        if config.transactions > 0 && config.subscriptions > 0 {
+            report.subscription_fan_out =
+                report.subscription_invalidations as f64 / config.transactions as f64;
+        }
+        Ok(report)
+    }
+
+    async fn bench_subscribe(
+        &self,
+        identity: &Identity,
+        namespace: TableNamespace,
+        config: &BenchConfig,
+        target: Option<DeveloperDocumentId>,
+    ) -> anyhow::Result<BenchSubscription> {
+        let mut tx = self.begin(identity.clone()).await?;
+        run_bench_query(&mut tx, namespace, config, target).await?;
+        let subscription = self.database.subscribe(tx.into_token()?).await?;
+        Ok(BenchSubscription {
+            subscription,
+            target,
+        })
+    }
+
+    async fn empty_bench_table(
+        &self,
+        identity: &Identity,
+        namespace: TableNamespace,
+        table_name: &TableName,
+    ) -> anyhow::Result<()> {
+        loop {
+            let mut tx = self.begin(identity.clone()).await?;
+            let mut query = ResolvedQuery::new(
+                &mut tx,
+                namespace,
+                Query::full_table_scan(table_name.clone(), Order::Asc),
+            )?;
+            let mut ids = vec![];
+            while ids.len() < CLEANUP_BATCH_SIZE
+                && let Some(document) = query.next(&mut tx, None).await?
+            {
+                ids.push(document.developer_id());
+            }
+            if ids.is_empty() {
+                return Ok(());
+            }
+            for id in ids {
+                UserFacingModel::new(&mut tx, namespace).delete(id).await?;
+            }
+            self.commit(tx, "bench_cleanup").await?;
+        }
+    }
+}
+
+async fn run_bench_query<RT: Runtime>(
+    tx: &mut Transaction<RT>,
+    namespace: TableNamespace,
+    config: &BenchConfig,
+    target: Option<DeveloperDocumentId>,
+) -> anyhow::Result<()> {
+    match config.query_shape {
+        QueryShape::PointGet => {
+            if let Some(id) = target {
+                UserFacingModel::new(tx, namespace).get(id, None).await?;
+            }
+        },
+        QueryShape::Scan { limit } => {
+            let mut query = ResolvedQuery::new(
+                tx,
+                namespace,
+                Query::full_table_scan(config.table_name.clone(), Order::Asc),
+            )?;
+            for _ in 0..limit {
+                if query.next(tx, Some(limit)).await?.is_none() {
+                    break;
+                }
+            }
+        },
+    }
+    Ok(())
+}
+
+fn random_payload(rng: &mut StdRng, size: usize) -> String {
+    (0..size)
+        .map(|_| rng.random_range(b'a'..=b'z') as char)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::LatencySummary;
+
+    #[test]
+    fn test_latency_summary_percentiles() {
+        let samples = (1..=100).map(Duration::from_millis).collect();
+        let summary = LatencySummary::new(samples);
+        assert_eq!(summary.count, 100);
+        assert_eq!(summary.p50_ms, 50.0);
+        assert_eq!(summary.p90_ms, 90.0);
+        assert_eq!(summary.p99_ms, 99.0);
+        assert_eq!(summary.max_ms, 100.0);
+    }
+}
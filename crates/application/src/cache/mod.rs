@@ -44,6 +44,8 @@ use common::{
     RequestId,
 };
 use database::{
+    ComponentCallCounter,
+    ComponentQuotasModel,
     Database,
     Token,
 };
@@ -111,6 +113,7 @@ pub struct CacheManager<RT: Runtime> {
     database: Database<RT>,
     function_router: FunctionRouter<RT>,
     udf_execution: FunctionExecutionLog<RT>,
+    component_calls: ComponentCallCounter,
 
     instance_id: InstanceId,
     cache: QueryCache,
@@ -298,6 +301,7 @@ impl<RT: Runtime> CacheManager<RT> {
         database: Database<RT>,
         function_router: FunctionRouter<RT>,
         udf_execution: FunctionExecutionLog<RT>,
+        component_calls: ComponentCallCounter,
         cache: QueryCache,
     ) -> Self {
         // each `CacheManager` (for a different instance) gets its own cache key space
@@ -308,6 +312,7 @@ impl<RT: Runtime> CacheManager<RT> {
             database,
             function_router,
             udf_execution,
+            component_calls,
             instance_id,
             cache,
         }
@@ -597,6 +602,9 @@ impl<RT: Runtime> CacheManager<RT> {
                     },
                     Ok((path_and_args, returns_validator)) => {
                         let component = path_and_args.path().component;
+                        ComponentQuotasModel::new(&mut tx)
+                            .check_function_call(component, &self.component_calls)
+                            .await?;
                         let (mut tx, outcome) = self
                             .function_router
                             .execute_query_or_mutation(
@@ -182,7 +182,7 @@ impl<RT: Runtime> CronJobExecutor<RT> {
                 self.context.rt.wait(Duration::from_secs(5))
             } else {
                 metrics::log_cron_job_execution_lag(Duration::from_secs(0));
-                self.context.rt.wait(next_job_ts - now)
+                self.context.rt.wait_for_system_time(next_job_ts - now)
             })
         } else {
             metrics::log_cron_job_execution_lag(Duration::from_secs(0));
@@ -0,0 +1,227 @@
+//! Checks whether a token a client resumes a flow from is still usable, so
+//! clients can restart pagination or a streaming export up front instead of
+//! failing partway through.
+
+use common::{
+    bootstrap_model::schema::SchemaState,
+    components::ComponentPath,
+    knobs::LIST_SNAPSHOT_MAX_AGE_SECS,
+    query::SerializedCursor,
+    runtime::Runtime,
+    types::{
+        IndexDescriptor,
+        IndexName,
+        WriteTimestamp,
+    },
+};
+use database::{
+    unauthorized_error,
+    BootstrapComponentsModel,
+    IndexModel,
+    SchemaModel,
+};
+use keybroker::Identity;
+use sync_types::Timestamp;
+use value::{
+    TableName,
+    TableNamespace,
+};
+
+use crate::Application;
+
+/// A token a client holds across requests to continue reading where it left
+/// off.
+#[derive(Clone, Debug)]
+pub enum ResumptionToken {
+    /// A cursor returned by a paginated query, along with the index the query
+    /// reads. Queries without an index read `by_creation_time`.
+    PaginationCursor {
+        cursor: SerializedCursor,
+        component: ComponentPath,
+        table: TableName,
+        index: IndexDescriptor,
+    },
+    /// The snapshot a `list_snapshot` walk reads at.
+    Snapshot(Timestamp),
+    /// The cursor of a `document_deltas` walk.
+    DocumentDeltas(Timestamp),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TokenValidity {
+    Valid,
+    /// Resuming from the token would fail. `reason` is a short code for the
+    /// check that failed.
+    Invalid {
+        reason: &'static str,
+        message: String,
+    },
+}
+
+impl TokenValidity {
+    fn invalid(reason: &'static str, message: impl Into<String>) -> Self {
+        Self::Invalid {
+            reason,
+            message: message.into(),
+        }
+    }
+}
+
+impl<RT: Runtime> Application<RT> {
+    /// Reports whether resuming from `token` would succeed right now.
+    ///
+    /// Pagination cursors are checked against the index registry: the cursor
+    /// must be from this deployment and the index it reads must still be
+    /// enabled. Timestamp tokens must be within the retention window, and the
+    /// root component's schema must not have changed since the timestamp.
+    pub async fn check_resumption_token(
+        &self,
+        identity: Identity,
+        token: ResumptionToken,
+    ) -> anyhow::Result<TokenValidity> {
+        match token {
+            ResumptionToken::PaginationCursor {
+                cursor,
+                component,
+                table,
+                index,
+            } => {
+                self.check_pagination_cursor(identity, cursor, component, table, index)
+                    .await
+            },
+            ResumptionToken::Snapshot(ts) => {
+                anyhow::ensure!(
+                    identity.is_system() || identity.is_admin(),
+                    unauthorized_error("check_resumption_token")
+                );
+                let now = self.database.now_ts_for_reads();
+                if ts > *now {
+                    return Ok(TokenValidity::invalid(
+                        "SnapshotTooNew",
+                        format!("Snapshot value {ts} is in the future."),
+                    ));
+                }
+                if *now - ts > *LIST_SNAPSHOT_MAX_AGE_SECS {
+                    return Ok(TokenValidity::invalid(
+                        "SnapshotTooOld",
+                        format!("Snapshot value {ts} is too far in the past."),
+                    ));
+                }
+                self.check_schema_unchanged_since(identity, ts).await
+            },
+            ResumptionToken::DocumentDeltas(ts) => {
+                anyhow::ensure!(
+                    identity.is_system() || identity.is_admin(),
+                    unauthorized_error("check_resumption_token")
+                );
+                let now = self.database.now_ts_for_reads();
+                if ts > *now {
+                    return Ok(TokenValidity::invalid(
+                        "CursorTooNew",
+                        format!("Cursor value {ts} is in the future."),
+                    ));
+                }
+                let min_ts = self
+                    .database
+                    .retention_validator()
+                    .min_document_snapshot_ts()
+                    .await?;
+                if ts < *min_ts {
+                    return Ok(TokenValidity::invalid(
+                        "InvalidWindowToReadDocuments",
+                        format!("Timestamp {ts} is too old"),
+                    ));
+                }
+                self.check_schema_unchanged_since(identity, ts).await
+            },
+        }
+    }
+
+    async fn check_pagination_cursor(
+        &self,
+        identity: Identity,
+        cursor: SerializedCursor,
+        component: ComponentPath,
+        table: TableName,
+        index: IndexDescriptor,
+    ) -> anyhow::Result<TokenValidity> {
+        if self
+            .key_broker
+            .decrypt_cursor(cursor, self.database.persistence_version())
+            .is_err()
+        {
+            return Ok(TokenValidity::invalid(
+                "InvalidCursor",
+                "The cursor wasn't issued by this deployment or is from an unsupported version.",
+            ));
+        }
+        let mut tx = self.begin(identity).await?;
+        let Some((_, component_id)) =
+            BootstrapComponentsModel::new(&mut tx).component_path_to_ids(&component)?
+        else {
+            return Ok(TokenValidity::invalid(
+                "ComponentNotFound",
+                format!("No component at {component}."),
+            ));
+        };
+        let namespace = TableNamespace::from(component_id);
+        if !tx.table_mapping().namespace(namespace).name_exists(&table) {
+            return Ok(TokenValidity::invalid(
+                "TableNotFound",
+                format!("Table {table} doesn't exist."),
+            ));
+        }
+        let index_name = if index.is_reserved() {
+            IndexName::new_reserved(table, index)?
+        } else {
+            IndexName::new(table, index)?
+        };
+        let mut index_model = IndexModel::new(&mut tx);
+        if index_model
+            .enabled_index_metadata(namespace, &index_name)?
+            .is_some()
+        {
+            return Ok(TokenValidity::Valid);
+        }
+        if index_model
+            .pending_index_metadata(namespace, &index_name)?
+            .is_some()
+        {
+            return Ok(TokenValidity::invalid(
+                "IndexNotEnabled",
+                format!("Index {index_name} is being rebuilt and isn't enabled yet."),
+            ));
+        }
+        Ok(TokenValidity::invalid(
+            "IndexNotFound",
+            format!("Index {index_name} not found."),
+        ))
+    }
+
+    /// Invalid if the root component's active schema was written after `ts`.
+    /// A deployment without an active schema counts as unchanged.
+    async fn check_schema_unchanged_since(
+        &self,
+        identity: Identity,
+        ts: Timestamp,
+    ) -> anyhow::Result<TokenValidity> {
+        let mut tx = self.begin(identity).await?;
+        let Some((schema_id, _)) = SchemaModel::new(&mut tx, TableNamespace::root_component())
+            .get_by_state(SchemaState::Active)
+            .await?
+        else {
+            return Ok(TokenValidity::Valid);
+        };
+        let changed = match tx.get_with_ts(schema_id).await? {
+            Some((_, WriteTimestamp::Committed(written_ts))) => written_ts > ts,
+            Some((_, WriteTimestamp::Pending)) | None => true,
+        };
+        if changed {
+            return Ok(TokenValidity::invalid(
+                "SchemaChanged",
+                format!("The schema changed after {ts}."),
+            ));
+        }
+        Ok(TokenValidity::Valid)
+    }
+}
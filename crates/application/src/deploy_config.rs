@@ -27,12 +27,21 @@ use common::{
         ComponentPath,
         Resource,
     },
+    document::ParsedDocument,
     errors::JsError,
     runtime::{
         Runtime,
         UnixTimestamp,
     },
-    schemas::DatabaseSchema,
+    schemas::{
+        violations::{
+            SchemaMigrationPlan,
+            SchemaMigrationPlanJson,
+            SchemaViolationReport,
+            SerializedSchemaViolationReport,
+        },
+        DatabaseSchema,
+    },
     types::{
         EnvVarName,
         EnvVarValue,
@@ -45,6 +54,7 @@ use database::{
     BootstrapComponentsModel,
     IndexModel,
     OccRetryStats,
+    SchemaModel,
     Token,
     WriteSource,
     SCHEMAS_TABLE,
@@ -61,6 +71,18 @@ use model::{
         types::AuthDiff,
         AuthInfoModel,
     },
+    canary::{
+        types::{
+            CanaryDeployment,
+            CanaryRollbackPolicy,
+            CanaryRouting,
+        },
+        CanaryModel,
+    },
+    code_versions::{
+        types::CodeVersion,
+        CodeVersionsModel,
+    },
     components::{
         config::{
             ComponentConfigModel,
@@ -103,7 +125,10 @@ use model::{
         types::SourcePackage,
         upload_download::download_package,
     },
-    udf_config::types::UdfConfig,
+    udf_config::{
+        types::UdfConfig,
+        UdfConfigModel,
+    },
 };
 use rand::Rng;
 use serde::{
@@ -127,6 +152,10 @@ use value::{
 };
 
 use crate::{
+    index_references::{
+        find_missing_indexes,
+        missing_indexes_error,
+    },
     Application,
     ApplyConfigArgs,
     ConfigMetadataAndSchema,
@@ -218,6 +247,8 @@ impl<RT: Runtime> Application<RT> {
                 system_env_var_overrides,
             )
             .await?;
+        self.check_index_references(config, &evaluated_components)
+            .await?;
         // Build and typecheck the component tree. We don't strictly need to do this
         // before `/finish_push`, but it's better to fail fast here on errors before
         // waiting for schema backfills to complete.
@@ -263,6 +294,51 @@ impl<RT: Runtime> Application<RT> {
         Ok(resp)
     }
 
+    /// Fails the push if its queries use indexes that its schemas don't
+    /// declare, rather than leaving them to fail at runtime.
+    async fn check_index_references(
+        &self,
+        config: &ProjectConfig,
+        evaluated_components: &BTreeMap<ComponentDefinitionPath, EvaluatedComponentDefinition>,
+    ) -> anyhow::Result<()> {
+        // Only the root component has a single set of deployed indexes to
+        // compare against, since a component definition can be instantiated
+        // many times.
+        let deployed_indexes: BTreeSet<_> = {
+            let mut tx = self.begin(Identity::system()).await?;
+            IndexModel::new(&mut tx)
+                .get_application_indexes(TableNamespace::root_component())
+                .await?
+                .into_iter()
+                .map(|index| index.name.clone())
+                .collect()
+        };
+        let schema = |path: &ComponentDefinitionPath| {
+            evaluated_components
+                .get(path)
+                .and_then(|definition| definition.schema.as_ref())
+        };
+        let root = ComponentDefinitionPath::root();
+        let mut missing = find_missing_indexes(
+            &root,
+            &config.app_definition.functions,
+            schema(&root),
+            &deployed_indexes,
+        );
+        for component_def in &config.component_definitions {
+            missing.extend(find_missing_indexes(
+                &component_def.definition_path,
+                &component_def.functions,
+                schema(&component_def.definition_path),
+                &BTreeSet::new(),
+            ));
+        }
+        if !missing.is_empty() {
+            anyhow::bail!(missing_indexes_error(&missing));
+        }
+        Ok(())
+    }
+
     async fn _handle_schema_change_in_start_push(
         &self,
         app: &CheckedComponent,
@@ -464,11 +540,12 @@ impl<RT: Runtime> Application<RT> {
         identity: Identity,
         schema_change: SchemaChange,
         timeout: Duration,
+        include_migration_plan: bool,
     ) -> anyhow::Result<SchemaStatus> {
         let deadline = self.runtime().monotonic_now() + timeout;
         loop {
             let (status, token) = self
-                .load_component_schema_status(&identity, &schema_change)
+                .load_component_schema_status(&identity, &schema_change, include_migration_plan)
                 .await?;
             let now = self.runtime().monotonic_now();
             let in_progress = matches!(status, SchemaStatus::InProgress { .. });
@@ -490,6 +567,7 @@ impl<RT: Runtime> Application<RT> {
         &self,
         identity: &Identity,
         schema_change: &SchemaChange,
+        include_migration_plan: bool,
     ) -> anyhow::Result<(SchemaStatus, Token)> {
         let mut tx = self.begin(identity.clone()).await?;
         let mut components_status = BTreeMap::new();
@@ -506,23 +584,6 @@ impl<RT: Runtime> Application<RT> {
                 .get(schema_id)
                 .await?
                 .context("Missing schema document")?;
-            let SchemaMetadata { state, .. } = document.into_value().0.try_into()?;
-            let schema_validation_complete = match state {
-                SchemaState::Pending => false,
-                SchemaState::Active | SchemaState::Validated => true,
-                SchemaState::Failed { error, table_name } => {
-                    let status = SchemaStatus::Failed {
-                        error,
-                        component_path: component_path.clone(),
-                        table_name,
-                    };
-                    return Ok((status, tx.into_token()?));
-                },
-                SchemaState::Overwritten => {
-                    return Ok((SchemaStatus::RaceDetected, tx.into_token()?))
-                },
-            };
-
             let component_id = if component_path.is_root() {
                 ComponentId::Root
             } else {
@@ -537,6 +598,42 @@ impl<RT: Runtime> Application<RT> {
                 ComponentId::Child(internal_id)
             };
             let namespace = TableNamespace::from(component_id);
+            let metadata: SchemaMetadata = document.into_value().0.try_into()?;
+            let schema_validation_complete = match metadata.state {
+                SchemaState::Pending => false,
+                SchemaState::Active | SchemaState::Validated => true,
+                SchemaState::Failed {
+                    ref error,
+                    ref table_name,
+                    ref report,
+                } => {
+                    let migration_plan = match report {
+                        Some(report) if include_migration_plan => {
+                            let active_schema = SchemaModel::new(&mut tx, namespace)
+                                .get_by_state(SchemaState::Active)
+                                .await?
+                                .map(|(_id, active_schema)| active_schema);
+                            Some(report.migration_plan(
+                                active_schema.as_deref(),
+                                &metadata.database_schema()?,
+                            )?)
+                        },
+                        _ => None,
+                    };
+                    let status = SchemaStatus::Failed {
+                        error: error.clone(),
+                        component_path: component_path.clone(),
+                        table_name: table_name.clone(),
+                        report: report.clone(),
+                        migration_plan,
+                    };
+                    return Ok((status, tx.into_token()?));
+                },
+                SchemaState::Overwritten => {
+                    return Ok((SchemaStatus::RaceDetected, tx.into_token()?))
+                },
+            };
+
             let mut indexes_complete = 0;
             let mut indexes_total = 0;
             for index in IndexModel::new(&mut tx)
@@ -633,6 +730,7 @@ impl<RT: Runtime> Application<RT> {
                             modules_by_definition,
                         )
                         .await?;
+                    CodeVersionsModel::new(tx).record().await?;
 
                     let diffs = PushComponentDiffs {
                         auth_diff: auth_diff.clone(),
@@ -655,6 +753,188 @@ impl<RT: Runtime> Application<RT> {
         Ok(diff)
     }
 
+    /// Starts a canary deploy of a push's root component functions instead of
+    /// finishing the push. The push can't change anything but function code:
+    /// its schema, auth config, environment variables and UDF server version
+    /// have to match the deployment's, and neither can have child components.
+    pub async fn start_canary(
+        &self,
+        identity: Identity,
+        start_push: StartPushResponse,
+        traffic_percent: u32,
+        routing: CanaryRouting,
+        rollback_policy: CanaryRollbackPolicy,
+    ) -> anyhow::Result<()> {
+        let root = ComponentDefinitionPath::root();
+        let (Some(definition), Some(source_package), 1, 1) = (
+            start_push.analysis.get(&root),
+            start_push.component_definition_packages.get(&root),
+            start_push.analysis.len(),
+            start_push.component_definition_packages.len(),
+        ) else {
+            anyhow::bail!(canary_unsupported("the push includes components"));
+        };
+        let downloaded = download_package(
+            self.modules_storage().clone(),
+            source_package.storage_key.clone(),
+            source_package.sha256.clone(),
+        )
+        .await?;
+        let mut analyze_results = definition.functions.clone();
+        let mut modules = vec![];
+        for (module_path, module) in downloaded {
+            // Like `apply_component_definitions_diff`, fill in modules (e.g.
+            // `_deps/*`) that weren't analyzed.
+            analyze_results.entry(module_path).or_default();
+            modules.push(module);
+        }
+
+        self.execute_with_audit_log_events_and_occ_retries(identity, "start_canary", |tx| {
+            let start_push = &start_push;
+            let modules = &modules;
+            let analyze_results = &analyze_results;
+            let rollback_policy = &rollback_policy;
+            async move {
+                let environment_variables = EnvironmentVariablesModel::new(tx).get_all().await?;
+                if environment_variables != start_push.environment_variables {
+                    anyhow::bail!(ErrorMetadata::bad_request(
+                        "RaceDetected",
+                        "Environment variables have changed during push"
+                    ));
+                }
+                let definitions = BootstrapComponentsModel::new(tx)
+                    .load_all_definitions()
+                    .await?;
+                if definitions.keys().any(|path| !path.is_root()) {
+                    anyhow::bail!(canary_unsupported("the deployment has components"));
+                }
+                let active_schema = SchemaModel::new(tx, TableNamespace::root_component())
+                    .get_by_state(SchemaState::Active)
+                    .await?
+                    .map(|(_, schema)| schema);
+                if definition.schema.as_ref() != active_schema.as_deref() {
+                    anyhow::bail!(canary_unsupported("the push changes the schema"));
+                }
+                let auth_info: BTreeSet<_> = AuthInfoModel::new(tx)
+                    .get()
+                    .await?
+                    .into_iter()
+                    .map(|auth_info| auth_info.into_value())
+                    .collect();
+                if auth_info != start_push.app_auth.iter().cloned().collect() {
+                    anyhow::bail!(canary_unsupported("the push changes the auth config"));
+                }
+                let udf_config = UdfConfigModel::new(tx, TableNamespace::root_component())
+                    .get()
+                    .await?;
+                if udf_config.as_ref().map(|config| &config.server_version)
+                    != Some(&definition.udf_config.server_version)
+                {
+                    anyhow::bail!(canary_unsupported(
+                        "the push uses a different version of the convex package"
+                    ));
+                }
+                CanaryModel::new(tx)
+                    .start(
+                        modules.clone(),
+                        source_package.clone(),
+                        analyze_results.clone(),
+                        traffic_percent,
+                        routing,
+                        rollback_policy.clone(),
+                    )
+                    .await?;
+                let events = vec![DeploymentAuditLogEvent::StartCanary {
+                    traffic_percent,
+                    routing,
+                }];
+                Ok(((), events))
+            }
+            .into()
+        })
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_canary(&self, identity: Identity) -> anyhow::Result<Option<CanaryDeployment>> {
+        let mut tx = self.begin(identity).await?;
+        Ok(CanaryModel::new(&mut tx)
+            .get()
+            .await?
+            .map(|canary| canary.into_value()))
+    }
+
+    pub async fn update_canary_traffic(
+        &self,
+        identity: Identity,
+        traffic_percent: u32,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.begin(identity).await?;
+        CanaryModel::new(&mut tx)
+            .set_traffic(traffic_percent)
+            .await?;
+        self.commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::UpdateCanaryTraffic { traffic_percent }],
+            "update_canary_traffic",
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Makes the active canary's code serve all of the root component's
+    /// traffic.
+    pub async fn promote_canary(&self, identity: Identity) -> anyhow::Result<()> {
+        let mut tx = self.begin(identity).await?;
+        CanaryModel::new(&mut tx).promote().await?;
+        self.commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::PromoteCanary],
+            "promote_canary",
+        )
+        .await?;
+        Ok(())
+    }
+
+    pub async fn roll_back_canary(&self, identity: Identity, reason: String) -> anyhow::Result<()> {
+        let mut tx = self.begin(identity).await?;
+        CanaryModel::new(&mut tx).roll_back(reason.clone()).await?;
+        self.commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::RollBackCanary { reason }],
+            "roll_back_canary",
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Recorded pushes of the root component's code, newest first.
+    pub async fn list_code_versions(
+        &self,
+        identity: Identity,
+    ) -> anyhow::Result<Vec<ParsedDocument<CodeVersion>>> {
+        let mut tx = self.begin(identity).await?;
+        CodeVersionsModel::new(&mut tx).list().await
+    }
+
+    /// Switches the root component back to an earlier push's code, schema
+    /// and config in a single transaction.
+    pub async fn rollback_code_version(
+        &self,
+        identity: Identity,
+        version_id: DeveloperDocumentId,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.begin(identity).await?;
+        CodeVersionsModel::new(&mut tx).rollback(version_id).await?;
+        self.commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::RollbackCodeVersion { version_id }],
+            "rollback_code_version",
+        )
+        .await?;
+        Ok(())
+    }
+
     /// N.B.: does not check auth
     pub async fn push_config_no_components(
         &self,
@@ -1011,6 +1291,16 @@ pub fn parse_module_path(path: &str) -> anyhow::Result<ModulePath> {
     })
 }
 
+fn canary_unsupported(why: &str) -> ErrorMetadata {
+    ErrorMetadata::bad_request(
+        "CanaryUnsupported",
+        format!(
+            "This push can't be deployed as a canary because {why}. Canary deploys can only \
+             change function code. Deploy it normally instead."
+        ),
+    )
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct NodeDependencyJson {
@@ -1034,6 +1324,8 @@ pub enum SchemaStatus {
         error: String,
         component_path: ComponentPath,
         table_name: Option<String>,
+        report: Option<SchemaViolationReport>,
+        migration_plan: Option<SchemaMigrationPlan>,
     },
     RaceDetected,
     Complete,
@@ -1052,14 +1344,18 @@ pub enum SchemaStatusJson {
         error: String,
         component_path: String,
         table_name: Option<String>,
+        report: Option<SerializedSchemaViolationReport>,
+        migration_plan: Option<SchemaMigrationPlanJson>,
     },
     RaceDetected,
     Complete,
 }
 
-impl From<SchemaStatus> for SchemaStatusJson {
-    fn from(value: SchemaStatus) -> Self {
-        match value {
+impl TryFrom<SchemaStatus> for SchemaStatusJson {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SchemaStatus) -> anyhow::Result<Self> {
+        Ok(match value {
             SchemaStatus::InProgress { components } => SchemaStatusJson::InProgress {
                 components: components
                     .into_iter()
@@ -1070,14 +1366,18 @@ impl From<SchemaStatus> for SchemaStatusJson {
                 error,
                 component_path,
                 table_name,
+                report,
+                migration_plan,
             } => SchemaStatusJson::Failed {
                 error,
                 component_path: String::from(component_path),
                 table_name,
+                report: report.map(SerializedSchemaViolationReport::from),
+                migration_plan: migration_plan.map(TryInto::try_into).transpose()?,
             },
             SchemaStatus::RaceDetected => SchemaStatusJson::RaceDetected,
             SchemaStatus::Complete => SchemaStatusJson::Complete,
-        }
+        })
     }
 }
 
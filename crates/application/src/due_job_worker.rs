@@ -0,0 +1,143 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use common::{
+    backoff::Backoff,
+    errors::report_error,
+    runtime::Runtime,
+};
+use database::{
+    Database,
+    Transaction,
+};
+use futures::{
+    stream,
+    StreamExt,
+};
+use keybroker::Identity;
+
+use crate::metrics::log_worker_starting;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Jobs stored in a system table that each run once they're due, like webhook
+/// deliveries and HTTP checks.
+#[async_trait]
+pub trait DueJobs<RT: Runtime>: Send + Sync + 'static {
+    /// Used for logs, errors and the worker status metric.
+    const NAME: &'static str;
+    /// Write source for the transaction that records outcomes.
+    const WRITE_SOURCE: &'static str;
+    /// Most jobs read per pass.
+    const BATCH_SIZE: usize;
+    /// Longest the worker sleeps without rereading the table, in case the
+    /// clock moved on without any write invalidating the subscription.
+    const MAX_IDLE_WAIT: Duration;
+
+    type Job: Send + Sync;
+    type Outcome: Send;
+
+    fn concurrency(&self) -> usize;
+
+    /// Reads up to `limit` jobs that are due at `now_ms`, along with anything
+    /// else needed to run them.
+    async fn list_due(
+        &self,
+        tx: &mut Transaction<RT>,
+        now_ms: i64,
+        limit: usize,
+    ) -> anyhow::Result<Vec<Self::Job>>;
+
+    /// When the next job becomes due, if it's known. The worker wakes up then
+    /// or after `MAX_IDLE_WAIT`, whichever is sooner.
+    async fn next_due_ms(&self, _tx: &mut Transaction<RT>) -> anyhow::Result<Option<i64>> {
+        Ok(None)
+    }
+
+    /// Runs one job outside of any transaction, starting at `now_ms`.
+    async fn run(&self, job: &Self::Job, now_ms: i64) -> Self::Outcome;
+
+    /// Records a job's outcome.
+    async fn record(
+        &self,
+        tx: &mut Transaction<RT>,
+        job: Self::Job,
+        outcome: Self::Outcome,
+        now_ms: i64,
+    ) -> anyhow::Result<()>;
+}
+
+/// Runs due jobs in batches, waiting for a write to the table or for the next
+/// job to come due when there aren't any. Outcomes are recorded after the
+/// whole batch has run, so jobs are at least once: a batch interrupted by a
+/// restart runs again.
+pub struct DueJobWorker<RT: Runtime, J: DueJobs<RT>> {
+    runtime: RT,
+    database: Database<RT>,
+    jobs: J,
+}
+
+impl<RT: Runtime, J: DueJobs<RT>> DueJobWorker<RT, J> {
+    pub fn start(runtime: RT, database: Database<RT>, jobs: J) -> impl Future<Output = ()> + Send {
+        let worker = Self {
+            runtime,
+            database,
+            jobs,
+        };
+        async move {
+            tracing::info!("Starting {}", J::NAME);
+            let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+            loop {
+                if let Err(e) = worker.run().await {
+                    let delay = backoff.fail(&mut worker.runtime.rng());
+                    report_error(&mut e.context(format!("{} died", J::NAME))).await;
+                    tracing::error!("{} failed, sleeping {delay:?}", J::NAME);
+                    worker.runtime.wait(delay).await;
+                } else {
+                    backoff.reset();
+                }
+            }
+        }
+    }
+
+    async fn run(&self) -> anyhow::Result<()> {
+        let _status = log_worker_starting(J::NAME);
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let now_ms = self.runtime.unix_timestamp_ms()?;
+        let due = self.jobs.list_due(&mut tx, now_ms, J::BATCH_SIZE).await?;
+        if due.is_empty() {
+            let wait = match self.jobs.next_due_ms(&mut tx).await? {
+                Some(next_due_ms) => Duration::from_millis((next_due_ms - now_ms).max(0) as u64)
+                    .min(J::MAX_IDLE_WAIT),
+                None => J::MAX_IDLE_WAIT,
+            };
+            let token = tx.into_token()?;
+            let subscription = self.database.subscribe(token).await?;
+            tokio::select! {
+                _ = subscription.wait_for_invalidation() => {},
+                _ = self.runtime.wait(wait) => {},
+            }
+            return Ok(());
+        }
+        let outcomes: Vec<_> = stream::iter(due)
+            .map(|job| async move {
+                let started_at_ms = self.runtime.unix_timestamp_ms().unwrap_or(now_ms);
+                let outcome = self.jobs.run(&job, started_at_ms).await;
+                (job, outcome)
+            })
+            .buffer_unordered(self.jobs.concurrency())
+            .collect()
+            .await;
+
+        let now_ms = self.runtime.unix_timestamp_ms()?;
+        let mut tx = self.database.begin(Identity::system()).await?;
+        for (job, outcome) in outcomes {
+            self.jobs.record(&mut tx, job, outcome, now_ms).await?;
+        }
+        self.database
+            .commit_with_write_source(tx, J::WRITE_SOURCE)
+            .await?;
+        Ok(())
+    }
+}
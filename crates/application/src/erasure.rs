@@ -0,0 +1,121 @@
+//! Admin APIs for data subject erasure. The erasure itself runs in the
+//! background; see `erasure_worker`.
+
+use common::{
+    components::ComponentId,
+    runtime::Runtime,
+};
+use errors::ErrorMetadata;
+use keybroker::Identity;
+use model::{
+    deployment_audit_log::types::DeploymentAuditLogEvent,
+    erasure::{
+        types::{
+            ErasureJob,
+            ErasurePolicy,
+            ErasureReport,
+        },
+        ErasureModel,
+    },
+};
+use value::{
+    DeveloperDocumentId,
+    TableNamespace,
+};
+
+use crate::Application;
+
+impl<RT: Runtime> Application<RT> {
+    pub async fn get_erasure_policies(
+        &self,
+        identity: Identity,
+    ) -> anyhow::Result<Vec<ErasurePolicy>> {
+        let mut tx = self.begin(identity).await?;
+        Ok(ErasureModel::new(&mut tx)
+            .get_policies()
+            .await?
+            .into_iter()
+            .map(|policy| policy.into_value())
+            .collect())
+    }
+
+    /// Replaces the deployment's erasure policies. Jobs that haven't started
+    /// erasing yet use the new policies.
+    pub async fn update_erasure_policies(
+        &self,
+        identity: Identity,
+        policies: Vec<ErasurePolicy>,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.begin(identity).await?;
+        ErasureModel::new(&mut tx).set_policies(policies).await?;
+        self.commit(tx, "update_erasure_policies").await?;
+        Ok(())
+    }
+
+    /// Queues the erasure of `subject_id`, a document in one of `component`'s
+    /// tables, and returns the ID of the job.
+    pub async fn request_erasure(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        subject_id: DeveloperDocumentId,
+    ) -> anyhow::Result<DeveloperDocumentId> {
+        let mut tx = self.begin(identity).await?;
+        let namespace = TableNamespace::from(component);
+        let is_user_table = tx
+            .table_mapping()
+            .namespace(namespace)
+            .name_by_number_if_exists(subject_id.table())
+            .is_some_and(|table_name| !table_name.is_system());
+        let subject = if is_user_table {
+            let id = tx.resolve_developer_id(&subject_id, namespace)?;
+            tx.get(id).await?
+        } else {
+            None
+        };
+        anyhow::ensure!(
+            subject.is_some(),
+            ErrorMetadata::bad_request(
+                "ErasureSubjectNotFound",
+                format!("No document with ID {subject_id} in the component's tables"),
+            )
+        );
+        let now_ms = self
+            .runtime
+            .unix_timestamp()
+            .as_ms_since_epoch()?
+            .try_into()?;
+        let job_id = ErasureModel::new(&mut tx)
+            .request_erasure(component, subject_id, now_ms)
+            .await?;
+        let event = DeploymentAuditLogEvent::RequestErasure {
+            subject_id: subject_id.encode(),
+        };
+        self.commit_with_audit_log_events(tx, vec![event], "request_erasure")
+            .await?;
+        Ok(job_id.into())
+    }
+
+    pub async fn get_erasure_job(
+        &self,
+        identity: Identity,
+        job_id: DeveloperDocumentId,
+    ) -> anyhow::Result<Option<ErasureJob>> {
+        let mut tx = self.begin(identity).await?;
+        Ok(ErasureModel::new(&mut tx)
+            .get_job(job_id)
+            .await?
+            .map(|job| job.into_value()))
+    }
+
+    /// Whether `signature` is this deployment's signature of `report`.
+    pub fn verify_erasure_report(
+        &self,
+        report: &ErasureReport,
+        signature: &str,
+    ) -> anyhow::Result<bool> {
+        Ok(self
+            .key_broker
+            .verify_erasure_report(&report.signed_content()?, signature))
+    }
+}
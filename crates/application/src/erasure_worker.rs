@@ -0,0 +1,786 @@
+//! Processes erasure jobs. See `model::erasure`.
+//!
+//! A job is processed in three steps. First, the worker plans the erasure:
+//! it finds the fields that can reference the subject and the indexes to find
+//! their documents with. Then it erases the live data in batches, each in its
+//! own transaction: it applies the erasure policies to the documents
+//! referencing the subject and then to the subject, deletes the `_storage`
+//! entries of the files they reference, and records every changed document.
+//! Finally, once no snapshot from before the erasure can be read anymore, it
+//! deletes the files' content and every earlier revision of the changed
+//! documents, along with the index entries those revisions wrote, and
+//! completes the job with a signed report. Text and vector index segments
+//! built before the erasure keep the old values until they're rebuilt or
+//! compacted.
+
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
+    sync::Arc,
+    time::Duration,
+};
+
+use common::{
+    backoff::Backoff,
+    bootstrap_model::{
+        index::{
+            database_index::{
+                DeveloperDatabaseIndexConfig,
+                IndexedFields,
+            },
+            IndexConfig,
+        },
+        schema::SchemaState,
+    },
+    components::ComponentId,
+    document::ResolvedDocument,
+    errors::report_error,
+    index::{
+        IndexEntry,
+        SplitKey,
+    },
+    maybe_val,
+    persistence::{
+        DocumentLogEntry,
+        DocumentPrevTsQuery,
+        NoopRetentionValidator,
+        Persistence,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    schemas::DatabaseSchema,
+    sha256::Sha256,
+    types::{
+        IndexId,
+        IndexName,
+    },
+};
+use database::{
+    query::{
+        PaginationOptions,
+        TableFilter,
+    },
+    Database,
+    IndexModel,
+    ResolvedQuery,
+    SchemaModel,
+    SystemMetadataModel,
+    Transaction,
+    UserFacingModel,
+};
+use errors::{
+    ErrorMetadata,
+    ErrorMetadataAnyhowExt,
+};
+use file_storage::TransactionalFileStorage;
+use keybroker::{
+    DataMaskingKey,
+    Identity,
+    KeyBroker,
+};
+use model::{
+    erasure::{
+        types::{
+            ErasedTable,
+            ErasureAction,
+            ErasureJob,
+            ErasureJobItem,
+            ErasureJobState,
+            ErasureReport,
+            ErasureSummary,
+            ErasureTarget,
+            SkippedReference,
+            SkippedReferenceReason,
+        },
+        ErasureModel,
+    },
+    file_storage::{
+        FileStorageId,
+        FileStorageModel,
+        FILE_STORAGE_VIRTUAL_TABLE,
+    },
+};
+use value::{
+    ConvexValue,
+    DeveloperDocumentId,
+    FieldPath,
+    InternalDocumentId,
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+    TabletId,
+};
+
+use crate::metrics::log_worker_starting;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// How many documents to erase in one transaction, keeping each batch well
+/// under the transaction limits.
+const ERASE_BATCH_SIZE: usize = 128;
+
+/// How many documents' revision histories to purge at a time.
+const PURGE_BATCH_SIZE: usize = 64;
+
+/// How often to check whether the retention window has passed a purging
+/// job's erasure.
+const RETENTION_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+pub struct ErasureWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+    persistence: Arc<dyn Persistence>,
+    file_storage: TransactionalFileStorage<RT>,
+    key_broker: KeyBroker,
+}
+
+impl<RT: Runtime> ErasureWorker<RT> {
+    pub fn start(
+        runtime: RT,
+        database: Database<RT>,
+        persistence: Arc<dyn Persistence>,
+        file_storage: TransactionalFileStorage<RT>,
+        key_broker: KeyBroker,
+    ) -> impl Future<Output = ()> + Send {
+        let worker = Self {
+            runtime,
+            database,
+            persistence,
+            file_storage,
+            key_broker,
+        };
+        async move {
+            tracing::info!("Starting ErasureWorker");
+            let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+            loop {
+                if let Err(e) = worker.run().await {
+                    let delay = backoff.fail(&mut worker.runtime.rng());
+                    report_error(&mut e.context("ErasureWorker died")).await;
+                    tracing::error!("Erasure worker failed, sleeping {delay:?}");
+                    worker.runtime.wait(delay).await;
+                } else {
+                    backoff.reset();
+                }
+            }
+        }
+    }
+
+    /// Takes one step of the next unfinished job.
+    async fn run(&self) -> anyhow::Result<()> {
+        let _status = log_worker_starting("ErasureWorker");
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let Some(job) = ErasureModel::new(&mut tx).next_unfinished_job().await? else {
+            let token = tx.into_token()?;
+            let subscription = self.database.subscribe(token).await?;
+            subscription.wait_for_invalidation().await;
+            return Ok(());
+        };
+        let (id, job) = job.into_id_and_value();
+        let result = match &job.state {
+            ErasureJobState::Requested => self.plan(id, &job).await,
+            ErasureJobState::Erasing { .. } => self.erase_batch(id, &job).await,
+            ErasureJobState::Purging { .. } => self.purge_batch(id, &job).await,
+            ErasureJobState::Completed { .. } | ErasureJobState::Failed { .. } => {
+                anyhow::bail!("Erasure job {id} is already finished")
+            },
+        };
+        if let Err(e) = result {
+            // Retrying won't fix a policy the data doesn't satisfy, such as nulling
+            // out a field the schema requires.
+            if !e.is_deterministic_user_error() {
+                return Err(e);
+            }
+            tracing::warn!("Erasure job {id} failed: {e:#}");
+            let error = ErasureJobState::Failed {
+                error: e.user_facing_message(),
+            };
+            self.update_if_unchanged(id, &job, error).await?;
+        }
+        Ok(())
+    }
+
+    /// Finds the fields that can reference the subject with the current
+    /// schema and policies, and starts erasing.
+    async fn plan(&self, id: ResolvedDocumentId, job: &ErasureJob) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let state = plan_erasure(&mut tx, job.component, job.subject_id).await?;
+        self.update_if_unchanged(id, job, state).await
+    }
+
+    /// Erases up to `ERASE_BATCH_SIZE` documents found through the job's
+    /// first target, or the subject once no targets remain, in one
+    /// transaction.
+    async fn erase_batch(&self, id: ResolvedDocumentId, job: &ErasureJob) -> anyhow::Result<()> {
+        let ErasureJobState::Erasing {
+            summary,
+            subject_action,
+            targets,
+            cursor,
+        } = &job.state
+        else {
+            anyhow::bail!("Erasure job {id} isn't erasing");
+        };
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let current = ErasureModel::new(&mut tx).get_job(id.into()).await?;
+        if current.map(|current| current.into_value()).as_ref() != Some(job) {
+            return Ok(());
+        }
+        let namespace = TableNamespace::from(job.component);
+        let schema = SchemaModel::new(&mut tx, namespace)
+            .get_by_state(SchemaState::Active)
+            .await?
+            .map(|(_, schema)| schema);
+        let mut summary = summary.clone();
+        let persistence_version = self.persistence.reader().version();
+        let masking_key = self.key_broker.data_masking_key();
+        let erasing_subject = targets.is_empty();
+
+        let (targets, cursor) = if let Some(target) = targets.first() {
+            let index_name =
+                IndexName::new(target.table_name.clone(), target.index_descriptor.clone())?;
+            let query = Query::index_range(IndexRange {
+                index_name,
+                range: vec![IndexRangeExpression::Eq(
+                    target.field.clone(),
+                    maybe_val!(job.subject_id.encode()),
+                )],
+                order: Order::Asc,
+            });
+            let start_cursor = cursor
+                .clone()
+                .map(|cursor| self.key_broker.decrypt_cursor(cursor, persistence_version))
+                .transpose()?;
+            let mut query_stream = ResolvedQuery::new_bounded(
+                &mut tx,
+                namespace,
+                query,
+                PaginationOptions::ManualPagination {
+                    start_cursor,
+                    maximum_rows_read: None,
+                    maximum_bytes_read: None,
+                },
+                None,
+                TableFilter::IncludePrivateSystemTables,
+            )?;
+            let storage_fields =
+                storage_fields(schema.as_ref(), &target.table_name, &target.action);
+            let mut erased = 0;
+            let mut exhausted = false;
+            while erased < ERASE_BATCH_SIZE && !query_stream.is_approaching_data_limit() {
+                let Some(document) = query_stream.next(&mut tx, None).await? else {
+                    exhausted = true;
+                    break;
+                };
+                erase_document(
+                    &mut tx,
+                    namespace,
+                    id.into(),
+                    &target.table_name,
+                    &target.action,
+                    &masking_key,
+                    &storage_fields,
+                    document,
+                    &mut summary,
+                )
+                .await?;
+                erased += 1;
+            }
+            if exhausted {
+                (targets[1..].to_vec(), None)
+            } else {
+                let cursor = query_stream
+                    .cursor()
+                    .map(|cursor| self.key_broker.encrypt_cursor(&cursor, persistence_version));
+                (targets.clone(), cursor)
+            }
+        } else {
+            // The subject's table may have been deleted since the erasure was
+            // planned.
+            if let Ok(subject_id) = tx.resolve_developer_id(&job.subject_id, namespace)
+                && let Some(subject) = tx.get(subject_id).await?
+            {
+                let subject_table = summary.subject_table.clone();
+                let storage_fields =
+                    storage_fields(schema.as_ref(), &subject_table, subject_action);
+                erase_document(
+                    &mut tx,
+                    namespace,
+                    id.into(),
+                    &subject_table,
+                    subject_action,
+                    &masking_key,
+                    &storage_fields,
+                    subject,
+                    &mut summary,
+                )
+                .await?;
+            }
+            (vec![], None)
+        };
+        let erasing = ErasureJob {
+            state: ErasureJobState::Erasing {
+                summary: summary.clone(),
+                subject_action: subject_action.clone(),
+                targets,
+                cursor,
+            },
+            ..job.clone()
+        };
+        ErasureModel::new(&mut tx)
+            .update_job(id, erasing.clone())
+            .await?;
+        let erased_ts = self
+            .database
+            .commit_with_write_source(tx, "erasure")
+            .await?;
+        if erasing_subject {
+            // Every erasure committed at or before `erased_ts`.
+            let purging = ErasureJobState::Purging {
+                summary,
+                erased_ts,
+                revisions_purged: 0,
+            };
+            self.update_if_unchanged(id, &erasing, purging).await?;
+        }
+        Ok(())
+    }
+
+    /// Deletes the content and earlier revisions of up to `PURGE_BATCH_SIZE`
+    /// of the job's items, or completes the job once none remain.
+    async fn purge_batch(&self, id: ResolvedDocumentId, job: &ErasureJob) -> anyhow::Result<()> {
+        let ErasureJobState::Purging {
+            summary,
+            erased_ts,
+            revisions_purged,
+        } = &job.state
+        else {
+            anyhow::bail!("Erasure job {id} isn't purging");
+        };
+        // Until the retention window passes the erasure, snapshot reads from
+        // before it may still need the earlier revisions and their index
+        // entries.
+        let min_snapshot_ts = self
+            .database
+            .retention_validator()
+            .min_snapshot_ts()
+            .await?;
+        if *min_snapshot_ts <= *erased_ts {
+            self.runtime.wait(RETENTION_POLL_INTERVAL).await;
+            return Ok(());
+        }
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let items = ErasureModel::new(&mut tx)
+            .items(id.into(), PURGE_BATCH_SIZE)
+            .await?;
+        drop(tx);
+        if items.is_empty() {
+            let report = ErasureReport {
+                subject_id: job.subject_id,
+                requested_at_ms: job.requested_at_ms,
+                completed_at_ms: self
+                    .runtime
+                    .unix_timestamp()
+                    .as_ms_since_epoch()?
+                    .try_into()?,
+                summary: summary.clone(),
+                revisions_purged: *revisions_purged,
+            };
+            let signature = self
+                .key_broker
+                .sign_erasure_report(&report.signed_content()?);
+            tracing::info!(
+                "Erasure job {id} completed, purging {revisions_purged} earlier revisions"
+            );
+            return self
+                .update_if_unchanged(id, job, ErasureJobState::Completed { report, signature })
+                .await;
+        }
+
+        let mut document_ids = vec![];
+        for item in &items {
+            if let Some(object_key) = &item.object_key {
+                self.file_storage.delete_object(object_key).await?;
+            }
+            document_ids.push(item.document_id);
+        }
+        let purged = self
+            .purge_revisions(TableNamespace::from(job.component), &document_ids)
+            .await?;
+
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let mut model = ErasureModel::new(&mut tx);
+        let current = model.get_job(id.into()).await?;
+        if current.map(|current| current.into_value()).as_ref() != Some(job) {
+            return Ok(());
+        }
+        for item in items {
+            model.delete_item(item.id()).await?;
+        }
+        let purging = ErasureJob {
+            state: ErasureJobState::Purging {
+                summary: summary.clone(),
+                erased_ts: *erased_ts,
+                revisions_purged: revisions_purged + purged,
+            },
+            ..job.clone()
+        };
+        model.update_job(id, purging).await?;
+        self.database
+            .commit_with_write_source(tx, "erasure_progress")
+            .await?;
+        Ok(())
+    }
+
+    /// Deletes every revision of the documents but the latest, along with the
+    /// index entries those revisions wrote, and returns how many revisions
+    /// were deleted. Revisions that document retention has already deleted
+    /// are skipped.
+    async fn purge_revisions(
+        &self,
+        namespace: TableNamespace,
+        document_ids: &[DeveloperDocumentId],
+    ) -> anyhow::Result<u64> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        // Every revision before this timestamp, which is after the erasure
+        // committed.
+        let ts = tx.begin_timestamp().succ()?;
+        let mut latest = BTreeSet::new();
+        let mut indexes: BTreeMap<TabletId, Vec<(IndexId, IndexedFields)>> = BTreeMap::new();
+        for id in document_ids {
+            // The table may have been deleted since the erasure.
+            let Ok(id) = tx.resolve_developer_id(id, namespace) else {
+                continue;
+            };
+            if !indexes.contains_key(&id.tablet_id) {
+                let table_indexes = database_indexes(&mut tx, id.tablet_id).await?;
+                indexes.insert(id.tablet_id, table_indexes);
+            }
+            latest.insert((InternalDocumentId::from(id), ts));
+        }
+        drop(tx);
+
+        let reader = self.persistence.reader();
+        let persistence_version = reader.version();
+        let retention_validator = Arc::new(NoopRetentionValidator);
+        let mut revisions: Vec<DocumentLogEntry> = reader
+            .previous_revisions(latest, retention_validator.clone())
+            .await?
+            .into_values()
+            .collect();
+        let mut purged = 0;
+        while !revisions.is_empty() {
+            let queries = revisions
+                .iter()
+                .filter_map(|revision| {
+                    Some(DocumentPrevTsQuery {
+                        id: revision.id,
+                        ts: revision.ts,
+                        prev_ts: revision.prev_ts?,
+                    })
+                })
+                .collect();
+            let previous_revisions = reader
+                .previous_revisions_of_documents(queries, retention_validator.clone())
+                .await?;
+            let mut documents = vec![];
+            let mut index_entries = vec![];
+            for (query, previous) in &previous_revisions {
+                documents.push((previous.ts, previous.id));
+                let Some(previous_value) = &previous.value else {
+                    continue;
+                };
+                let next_value = revisions
+                    .iter()
+                    .find(|revision| revision.id == query.id && revision.ts == query.ts)
+                    .and_then(|revision| revision.value.as_ref());
+                for (index_id, fields) in &indexes[&query.id.table()] {
+                    let index_key = previous_value
+                        .index_key(fields, persistence_version)
+                        .to_bytes();
+                    let key_sha256 = Sha256::hash(&index_key);
+                    let key = SplitKey::new(index_key.clone().0);
+                    index_entries.push(IndexEntry {
+                        index_id: *index_id,
+                        key_prefix: key.prefix.clone(),
+                        key_suffix: key.suffix.clone(),
+                        key_sha256: key_sha256.to_vec(),
+                        ts: previous.ts,
+                        deleted: false,
+                    });
+                    // The next revision also wrote a tombstone for the previous
+                    // key if it changed the key or deleted the document.
+                    if let Some(next_value) = next_value
+                        && next_value.index_key(fields, persistence_version).to_bytes() == index_key
+                    {
+                        continue;
+                    }
+                    index_entries.push(IndexEntry {
+                        index_id: *index_id,
+                        key_prefix: key.prefix,
+                        key_suffix: key.suffix,
+                        key_sha256: key_sha256.to_vec(),
+                        ts: query.ts,
+                        deleted: true,
+                    });
+                }
+            }
+            // Delete the index entries first so they never point to a missing
+            // revision.
+            self.persistence.delete_index_entries(index_entries).await?;
+            purged += self.persistence.delete(documents).await? as u64;
+            revisions = previous_revisions.into_values().collect();
+        }
+        Ok(purged)
+    }
+
+    /// Moves the job to `state` unless it has changed since the worker read
+    /// `original`.
+    async fn update_if_unchanged(
+        &self,
+        id: ResolvedDocumentId,
+        original: &ErasureJob,
+        state: ErasureJobState,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let mut model = ErasureModel::new(&mut tx);
+        let current = model.get_job(id.into()).await?;
+        if current.map(|current| current.into_value()).as_ref() != Some(original) {
+            return Ok(());
+        }
+        let updated = ErasureJob {
+            state,
+            ..original.clone()
+        };
+        model.update_job(id, updated).await?;
+        self.database
+            .commit_with_write_source(tx, "erasure_progress")
+            .await?;
+        Ok(())
+    }
+}
+
+/// The database indexes on the table, including indexes that are still
+/// backfilling since they have entries too.
+async fn database_indexes<RT: Runtime>(
+    tx: &mut Transaction<RT>,
+    tablet_id: TabletId,
+) -> anyhow::Result<Vec<(IndexId, IndexedFields)>> {
+    Ok(IndexModel::new(tx)
+        .all_indexes_on_table(tablet_id)
+        .await?
+        .into_iter()
+        .filter_map(|index| match &index.config {
+            IndexConfig::Database {
+                developer_config: DeveloperDatabaseIndexConfig { fields },
+                ..
+            } => Some((index.id().internal_id(), fields.clone())),
+            _ => None,
+        })
+        .collect())
+}
+
+/// Finds the fields of the component's tables that can reference the
+/// subject, skipping those without a policy or an index to find their
+/// documents with. The subject is deleted unless its own table has a policy.
+async fn plan_erasure<RT: Runtime>(
+    tx: &mut Transaction<RT>,
+    component: ComponentId,
+    subject_id: DeveloperDocumentId,
+) -> anyhow::Result<ErasureJobState> {
+    let namespace = TableNamespace::from(component);
+    let Some(subject_table) = tx
+        .table_mapping()
+        .namespace(namespace)
+        .name_by_number_if_exists(subject_id.table())
+        .cloned()
+    else {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "ErasureSubjectTableNotFound",
+            format!("The table of {subject_id} no longer exists"),
+        ));
+    };
+    let policies: BTreeMap<TableName, ErasureAction> = ErasureModel::new(tx)
+        .get_policies()
+        .await?
+        .into_iter()
+        .map(|policy| policy.into_value())
+        .filter(|policy| policy.component == component)
+        .map(|policy| (policy.table_name, policy.action))
+        .collect();
+    let schema = SchemaModel::new(tx, namespace)
+        .get_by_state(SchemaState::Active)
+        .await?
+        .map(|(_, schema)| schema);
+
+    let mut targets = vec![];
+    let mut skipped_references = vec![];
+    for (table_name, table) in schema.iter().flat_map(|schema| &schema.tables) {
+        let Some(document_type) = &table.document_type else {
+            continue;
+        };
+        if !SystemMetadataModel::new(tx, namespace).table_exists(table_name) {
+            continue;
+        }
+        for field in document_type.reference_field_paths(&subject_table) {
+            let Some(action) = policies.get(table_name) else {
+                skipped_references.push(SkippedReference {
+                    table_name: table_name.clone(),
+                    field,
+                    reason: SkippedReferenceReason::NoPolicy,
+                });
+                continue;
+            };
+            // An index declared for one variant of a union doesn't have the
+            // documents of the other variants.
+            let mut index_descriptor = None;
+            if let Some(index) = table
+                .indexes
+                .values()
+                .find(|index| index.variant.is_none() && index.fields.first() == Some(&field))
+            {
+                let name = IndexName::new(table_name.clone(), index.index_descriptor.clone())?;
+                if IndexModel::new(tx)
+                    .enabled_index_metadata(namespace, &name)?
+                    .is_some()
+                {
+                    index_descriptor = Some(index.index_descriptor.clone());
+                }
+            }
+            let Some(index_descriptor) = index_descriptor else {
+                skipped_references.push(SkippedReference {
+                    table_name: table_name.clone(),
+                    field,
+                    reason: SkippedReferenceReason::NoIndex,
+                });
+                continue;
+            };
+            targets.push(ErasureTarget {
+                table_name: table_name.clone(),
+                field,
+                index_descriptor,
+                action: action.clone(),
+            });
+        }
+    }
+    let subject_action = policies
+        .get(&subject_table)
+        .cloned()
+        .unwrap_or(ErasureAction::Delete);
+    Ok(ErasureJobState::Erasing {
+        summary: ErasureSummary {
+            subject_table,
+            tables: vec![],
+            skipped_references,
+            files_deleted: 0,
+        },
+        subject_action,
+        targets,
+        cursor: None,
+    })
+}
+
+/// The fields of the table that reference files and that `action` erases.
+fn storage_fields(
+    schema: Option<&DatabaseSchema>,
+    table_name: &TableName,
+    action: &ErasureAction,
+) -> Vec<FieldPath> {
+    schema
+        .and_then(|schema| schema.tables.get(table_name))
+        .and_then(|table| table.document_type.as_ref())
+        .map(|document_type| document_type.reference_field_paths(&FILE_STORAGE_VIRTUAL_TABLE))
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|field| action.erases_field(field))
+        .collect()
+}
+
+/// Applies `action` to the document and deletes the files it references in
+/// `storage_fields`, recording each as an item of the job. Documents the job
+/// has already erased are skipped, since hashing a field again would change
+/// it again.
+async fn erase_document<RT: Runtime>(
+    tx: &mut Transaction<RT>,
+    namespace: TableNamespace,
+    job_id: DeveloperDocumentId,
+    table_name: &TableName,
+    action: &ErasureAction,
+    masking_key: &DataMaskingKey,
+    storage_fields: &[FieldPath],
+    document: ResolvedDocument,
+    summary: &mut ErasureSummary,
+) -> anyhow::Result<()> {
+    let document_id = document.developer_id();
+    if ErasureModel::new(tx).has_item(job_id, document_id).await? {
+        return Ok(());
+    }
+    let mut storage_ids = BTreeSet::new();
+    for field in storage_fields {
+        if let Some(ConvexValue::String(storage_id)) = document.value().get_path(field)
+            && let Ok(storage_id) = storage_id.parse::<FileStorageId>()
+        {
+            storage_ids.insert(storage_id);
+        }
+    }
+    let value = document.into_value().0.filter_system_fields();
+    match action.apply(value, masking_key)? {
+        Some(value) => {
+            UserFacingModel::new(tx, namespace)
+                .replace(document_id, value)
+                .await?;
+        },
+        None => {
+            UserFacingModel::new(tx, namespace)
+                .delete(document_id)
+                .await?;
+        },
+    }
+    ErasureModel::new(tx)
+        .insert_item(ErasureJobItem {
+            job_id,
+            document_id,
+            object_key: None,
+        })
+        .await?;
+    match summary
+        .tables
+        .iter_mut()
+        .find(|table| &table.table_name == table_name)
+    {
+        Some(table) => table.documents += 1,
+        None => summary.tables.push(ErasedTable {
+            table_name: table_name.clone(),
+            action: action.kind(),
+            documents: 1,
+        }),
+    }
+
+    // Files referenced by several erased documents are only deleted once.
+    for storage_id in storage_ids {
+        let mut file_storage = FileStorageModel::new(tx, namespace);
+        let Some(entry) = file_storage.get_file(storage_id.clone()).await? else {
+            continue;
+        };
+        file_storage
+            .delete_file(storage_id, Identity::system())
+            .await?;
+        let entry_id = entry.id().into();
+        ErasureModel::new(tx)
+            .insert_item(ErasureJobItem {
+                job_id,
+                document_id: entry_id,
+                object_key: Some(entry.into_value().storage_key),
+            })
+            .await?;
+        summary.files_deleted += 1;
+    }
+    Ok(())
+}
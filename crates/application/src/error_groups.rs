@@ -0,0 +1,55 @@
+use std::future::Future;
+
+use common::runtime::Runtime;
+use database::Database;
+use keybroker::Identity;
+use model::error_groups::{
+    types::ErrorOccurrence,
+    ErrorGroupsModel,
+};
+use tokio::sync::mpsc;
+
+use crate::batched_writer::batched_writer;
+
+/// Maximum number of occurrences buffered for writing. Occurrences raised
+/// while the buffer is full are not counted.
+const BUFFER_SIZE: usize = 1000;
+const MAX_BATCH_SIZE: usize = 64;
+
+/// Adds uncaught exceptions to their groups in `_error_groups` in batches.
+pub struct ErrorGroupsWriter;
+
+impl ErrorGroupsWriter {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new<RT: Runtime>(
+        runtime: RT,
+        database: Database<RT>,
+    ) -> (
+        mpsc::Sender<ErrorOccurrence>,
+        impl Future<Output = ()> + Send,
+    ) {
+        batched_writer(
+            runtime,
+            "ErrorGroupsWriter",
+            "error occurrences",
+            BUFFER_SIZE,
+            MAX_BATCH_SIZE,
+            move |occurrences| Self::write_batch(database.clone(), occurrences),
+        )
+    }
+
+    async fn write_batch<RT: Runtime>(
+        database: Database<RT>,
+        occurrences: Vec<ErrorOccurrence>,
+    ) -> anyhow::Result<()> {
+        let mut tx = database.begin(Identity::system()).await?;
+        let mut model = ErrorGroupsModel::new(&mut tx);
+        for occurrence in occurrences {
+            model.record(occurrence).await?;
+        }
+        database
+            .commit_with_write_source(tx, "error_groups_writer")
+            .await?;
+        Ok(())
+    }
+}
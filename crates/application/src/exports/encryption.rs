@@ -0,0 +1,29 @@
+//! Encrypting export archives at rest. See `keybroker::ArchiveEncryptor` for
+//! the format.
+
+use bytes::Bytes;
+use futures::Stream;
+use futures_async_stream::try_stream;
+use keybroker::{
+    ArchiveEncryptor,
+    ArchiveKey,
+};
+
+/// Encrypts the bytes of an export archive as they're produced, so the
+/// plaintext never reaches export storage.
+#[try_stream(ok = Bytes, error = anyhow::Error)]
+pub(crate) async fn encrypt_archive(
+    key: ArchiveKey,
+    chunks: impl Stream<Item = Bytes> + Send + 'static,
+) {
+    let mut encryptor = ArchiveEncryptor::new(&key);
+    yield Bytes::copy_from_slice(encryptor.header());
+    #[for_await]
+    for chunk in chunks {
+        let sealed = encryptor.update(&chunk)?;
+        if !sealed.is_empty() {
+            yield Bytes::from(sealed);
+        }
+    }
+    yield Bytes::from(encryptor.finish()?);
+}
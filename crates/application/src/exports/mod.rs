@@ -4,11 +4,18 @@ use anyhow::Context as _;
 use bytes::Bytes;
 use common::{
     async_compat::TokioAsyncWriteCompatExt,
-    bootstrap_model::tables::TABLES_TABLE,
+    bootstrap_model::{
+        index::{
+            IndexConfig,
+            TabletIndexMetadata,
+        },
+        tables::TABLES_TABLE,
+    },
     components::{
         ComponentId,
         ComponentPath,
     },
+    document::ParsedDocument,
     fastrace_helpers::get_sampled_span,
     persistence::LatestDocument,
     runtime::Runtime,
@@ -38,6 +45,10 @@ use itertools::Itertools;
 use keybroker::Identity;
 use maplit::btreemap;
 use model::{
+    data_masking::{
+        types::DataMaskingPolicy,
+        DataMaskingModel,
+    },
     exports::types::{
         ExportFormat,
         ExportRequestor,
@@ -65,19 +76,33 @@ use value::{
 };
 
 use crate::exports::{
+    encryption::encrypt_archive,
     export_storage::write_storage_table,
+    search_indexes::write_search_indexes,
     worker::ExportWorker,
     zip_uploader::ZipSnapshotUpload,
 };
 
+mod encryption;
 mod export_storage;
 mod metrics;
+mod search_indexes;
 #[cfg(test)]
 mod tests;
 pub mod worker;
 mod zip_uploader;
 
 pub use export_storage::FileStorageZipMetadata;
+pub use search_indexes::{
+    map_segment_keys,
+    search_index_dir,
+    SearchIndexZipMetadata,
+    SEARCH_INDEX_EXPORT_VERSION,
+};
+pub use zip_uploader::{
+    ChecksumManifest,
+    CHECKSUM_MANIFEST_PATH,
+};
 
 async fn export_inner<F, Fut, RT: Runtime>(
     worker: &mut ExportWorker<RT>,
@@ -91,9 +116,28 @@ where
 {
     let storage = &worker.storage;
     update_progress("Beginning backup".to_string()).await?;
-    let (ts, tables, component_ids_to_paths, by_id_indexes, system_tables) = {
+    let (
+        ts,
+        tables,
+        component_ids_to_paths,
+        by_id_indexes,
+        system_tables,
+        data_masking_policy,
+        search_indexes,
+    ) = {
         let mut tx = worker.database.begin(Identity::system()).await?;
         let by_id_indexes = IndexModel::new(&mut tx).by_id_indexes().await?;
+        // Cloud backups are restored as-is, so only snapshot exports and
+        // preview deployment clones are masked.
+        let data_masking_policy = match requestor {
+            ExportRequestor::SnapshotExport | ExportRequestor::PreviewDeploymentClone => Some(
+                DataMaskingModel::new(&mut tx)
+                    .get_policy(worker.data_masking_key.clone())
+                    .await?,
+            ),
+            ExportRequestor::CloudBackup => None,
+        }
+        .filter(|policy| !policy.is_empty());
         let ts = tx.begin_timestamp();
         let snapshot = worker.database.snapshot(ts)?;
         let table_summaries = snapshot.must_table_summaries()?;
@@ -118,12 +162,36 @@ where
             .iter_active_system_tables()
             .map(|(id, namespace, _, name)| ((namespace, name.clone()), id))
             .collect();
+        // Cloud backups also carry built search index segments, so restores
+        // don't have to rebuild them. They aren't useful in a user's export.
+        let mut search_indexes = BTreeMap::new();
+        if requestor == ExportRequestor::CloudBackup {
+            for tablet_id in tables.keys() {
+                let indexes: Vec<_> = IndexModel::new(&mut tx)
+                    .all_indexes_on_table(*tablet_id)
+                    .await?
+                    .into_iter()
+                    .filter(|index| {
+                        index.config.is_enabled()
+                            && matches!(
+                                index.config,
+                                IndexConfig::Text { .. } | IndexConfig::Vector { .. }
+                            )
+                    })
+                    .collect();
+                if !indexes.is_empty() {
+                    search_indexes.insert(*tablet_id, indexes);
+                }
+            }
+        }
         (
             ts,
             tables,
             component_ids_to_paths,
             by_id_indexes,
             system_tables,
+            data_masking_policy,
+            search_indexes,
         )
     };
     match format {
@@ -131,8 +199,12 @@ where
             // Start upload.
             let mut upload = storage.start_upload().await?;
             let (sender, receiver) = mpsc::channel::<Bytes>(1);
-            let uploader =
-                upload.try_write_parallel_and_hash(ReceiverStream::new(receiver).map(Ok));
+            let chunks = ReceiverStream::new(receiver);
+            let chunks = match worker.archive_key.clone() {
+                Some(key) => encrypt_archive(key, chunks).boxed(),
+                None => chunks.map(Ok).boxed(),
+            };
+            let uploader = upload.try_write_parallel_and_hash(chunks);
             let writer = ChannelWriter::new(sender, 5 * (1 << 20));
             let usage = FunctionUsageTracker::new();
 
@@ -156,9 +228,12 @@ where
                 component_ids_to_paths,
                 by_id_indexes,
                 system_tables,
+                search_indexes,
+                *ts,
                 include_storage,
                 usage.clone(),
                 requestor,
+                data_masking_policy.as_ref(),
                 update_progress,
             );
             let (_, ()) = try_join!(uploader, zipper)?;
@@ -209,6 +284,7 @@ pub async fn write_table<'a, 'b: 'a, RT: Runtime>(
     table_summary: TableSummary,
     by_id: &InternalId,
     usage: &FunctionUsageTracker,
+    data_masking_policy: Option<&DataMaskingPolicy>,
 ) -> anyhow::Result<()> {
     let mut table_upload = zip_snapshot_upload
         .start_table(path_prefix, table_name.clone())
@@ -230,6 +306,12 @@ pub async fn write_table<'a, 'b: 'a, RT: Runtime>(
             doc.size() as u64,
             false,
         );
+        let doc = match data_masking_policy {
+            Some(policy) => {
+                doc.replace_value(policy.mask_document(&table_name, doc.value().0.clone())?)?
+            },
+            None => doc,
+        };
         table_upload.write(doc).await?;
     }
 
@@ -248,9 +330,12 @@ async fn construct_zip_snapshot<F, Fut, RT: Runtime>(
     component_ids_to_paths: BTreeMap<ComponentId, ComponentPath>,
     by_id_indexes: BTreeMap<TabletId, IndexId>,
     system_tables: BTreeMap<(TableNamespace, TableName), TabletId>,
+    mut search_indexes: BTreeMap<TabletId, Vec<ParsedDocument<TabletIndexMetadata>>>,
+    snapshot_ts: Timestamp,
     include_storage: bool,
     usage: FunctionUsageTracker,
     requestor: ExportRequestor,
+    data_masking_policy: Option<&DataMaskingPolicy>,
     update_progress: F,
 ) -> anyhow::Result<()>
 where
@@ -288,7 +373,7 @@ where
     // sort tables small to large, and write them to the zip.
     let mut sorted_tables: Vec<_> = tables.iter().collect();
     sorted_tables.sort_by_key(|(_, (_, _, _, table_summary))| table_summary.total_size());
-    for (tablet_id, (namespace, _, table_name, table_summary)) in sorted_tables {
+    for (tablet_id, (namespace, table_number, table_name, table_summary)) in sorted_tables {
         let component_id: ComponentId = (*namespace).into();
         let Some(component_path) = component_ids_to_paths.get(&component_id) else {
             tracing::info!(
@@ -324,10 +409,29 @@ where
             table_summary.clone(),
             by_id,
             &usage,
+            data_masking_policy,
         )
         .in_span(root)
         .await?;
 
+        if let Some(indexes) = search_indexes.remove(tablet_id) {
+            update_progress(format!(
+                "Backing up search indexes on {table_name}{in_component_str}"
+            ))
+            .await?;
+            write_search_indexes(
+                worker,
+                &path_prefix,
+                &mut zip_snapshot_upload,
+                table_name,
+                *table_number,
+                *tablet_id,
+                indexes,
+                snapshot_ts,
+            )
+            .await?;
+        }
+
         table_iterator.unregister_table(*tablet_id)?;
     }
 
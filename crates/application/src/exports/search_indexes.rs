@@ -0,0 +1,313 @@
+//! Built text and vector index segments in cloud backups, so restoring a
+//! large deployment doesn't have to rebuild its search indexes from scratch.
+//!
+//! Each exported index is a directory next to its table's documents:
+//! `<table>/_search_indexes/<index>/index.json` describes the index, with its
+//! segments' storage keys replaced by the names of the files alongside it.
+
+use std::{
+    collections::BTreeMap,
+    num::NonZeroU32,
+    ops::Bound,
+};
+
+use anyhow::Context;
+use common::{
+    bootstrap_model::index::{
+        text_index::{
+            FragmentedTextSegment,
+            TextIndexSnapshot,
+            TextIndexSnapshotData,
+            TextIndexState,
+        },
+        vector_index::{
+            FragmentedVectorSegment,
+            VectorIndexSnapshot,
+            VectorIndexSnapshotData,
+            VectorIndexState,
+        },
+        IndexConfig,
+        TabletIndexMetadata,
+    },
+    document::ParsedDocument,
+    persistence::TimestampRange,
+    query::Order,
+    runtime::{
+        new_rate_limiter,
+        Runtime,
+    },
+    types::{
+        ObjectKey,
+        TableName,
+        Timestamp,
+    },
+};
+use futures::{
+    pin_mut,
+    TryStreamExt,
+};
+use governor::Quota;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_json::Value as JsonValue;
+use storage::StorageExt;
+use value::{
+    ConvexObject,
+    TableNumber,
+    TabletId,
+};
+
+use crate::exports::{
+    worker::ExportWorker,
+    zip_uploader::ZipSnapshotUpload,
+};
+
+/// Bumped whenever the layout of an exported search index changes. Imports
+/// rebuild indexes exported with any other version.
+pub const SEARCH_INDEX_EXPORT_VERSION: i64 = 1;
+
+/// The contents of `index.json` for an exported search index.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchIndexZipMetadata {
+    pub format_version: i64,
+    pub index_name: String,
+    /// Segments refer to documents by ID, so they can only be used if the
+    /// table is imported with the same table number.
+    pub table_number: u32,
+    /// The index's `IndexConfig`, as internal JSON.
+    pub index_config: JsonValue,
+}
+
+pub fn search_index_dir(path_prefix: &str, table_name: &TableName, index_name: &str) -> String {
+    format!("{path_prefix}{table_name}/_search_indexes/{index_name}/")
+}
+
+/// Rewrites the storage key of every file in a snapshotted text or vector
+/// index's segments.
+pub fn map_segment_keys(
+    config: IndexConfig,
+    mut f: impl FnMut(ObjectKey) -> anyhow::Result<ObjectKey>,
+) -> anyhow::Result<IndexConfig> {
+    Ok(match config {
+        IndexConfig::Text {
+            developer_config,
+            on_disk_state:
+                TextIndexState::SnapshottedAt(TextIndexSnapshot {
+                    data: TextIndexSnapshotData::MultiSegment(segments),
+                    ts,
+                    version,
+                }),
+        } => {
+            let segments = segments
+                .into_iter()
+                .map(|segment| {
+                    Ok(FragmentedTextSegment {
+                        segment_key: f(segment.segment_key)?,
+                        id_tracker_key: f(segment.id_tracker_key)?,
+                        deleted_terms_table_key: f(segment.deleted_terms_table_key)?,
+                        alive_bitset_key: f(segment.alive_bitset_key)?,
+                        ..segment
+                    })
+                })
+                .collect::<anyhow::Result<_>>()?;
+            IndexConfig::Text {
+                developer_config,
+                on_disk_state: TextIndexState::SnapshottedAt(TextIndexSnapshot {
+                    data: TextIndexSnapshotData::MultiSegment(segments),
+                    ts,
+                    version,
+                }),
+            }
+        },
+        IndexConfig::Vector {
+            developer_config,
+            on_disk_state:
+                VectorIndexState::SnapshottedAt(VectorIndexSnapshot {
+                    data: VectorIndexSnapshotData::MultiSegment(segments),
+                    ts,
+                }),
+        } => {
+            let segments = segments
+                .into_iter()
+                .map(|segment| {
+                    Ok(FragmentedVectorSegment {
+                        segment_key: f(segment.segment_key)?,
+                        id_tracker_key: f(segment.id_tracker_key)?,
+                        deleted_bitset_key: f(segment.deleted_bitset_key)?,
+                        ..segment
+                    })
+                })
+                .collect::<anyhow::Result<_>>()?;
+            IndexConfig::Vector {
+                developer_config,
+                on_disk_state: VectorIndexState::SnapshottedAt(VectorIndexSnapshot {
+                    data: VectorIndexSnapshotData::MultiSegment(segments),
+                    ts,
+                }),
+            }
+        },
+        _ => anyhow::bail!("Only snapshotted text and vector indexes have segments"),
+    })
+}
+
+fn snapshot_ts(config: &IndexConfig) -> Option<Timestamp> {
+    match config {
+        IndexConfig::Text {
+            on_disk_state: TextIndexState::SnapshottedAt(snapshot),
+            ..
+        } => Some(snapshot.ts),
+        IndexConfig::Vector {
+            on_disk_state: VectorIndexState::SnapshottedAt(snapshot),
+            ..
+        } => Some(snapshot.ts),
+        _ => None,
+    }
+}
+
+/// Writes the segments of a table's text and vector indexes to the zip.
+/// Indexes whose last snapshot is older than the table's latest write at
+/// `export_ts` are skipped, since their segments don't match the exported
+/// documents, and are rebuilt on import instead.
+pub async fn write_search_indexes<'a, 'b: 'a, RT: Runtime>(
+    worker: &ExportWorker<RT>,
+    path_prefix: &str,
+    zip_snapshot_upload: &'a mut ZipSnapshotUpload<'b>,
+    table_name: &TableName,
+    table_number: TableNumber,
+    tablet_id: TabletId,
+    indexes: Vec<ParsedDocument<TabletIndexMetadata>>,
+    export_ts: Timestamp,
+) -> anyhow::Result<()> {
+    let search_storage = worker.database.search_storage();
+    for index in indexes {
+        let index = index.into_value();
+        let index_name = index.name.descriptor().to_string();
+        let Some(index_ts) = snapshot_ts(&index.config) else {
+            continue;
+        };
+        if index_ts < export_ts
+            && table_changed_between(worker, tablet_id, index_ts, export_ts).await?
+        {
+            tracing::info!(
+                "Not exporting {table_name}.{index_name}: its snapshot is older than the table"
+            );
+            continue;
+        }
+        let mut files = BTreeMap::new();
+        let config = map_segment_keys(index.config, |key| {
+            let file_name = format!("segment-{}", files.len());
+            files.insert(file_name.clone(), key);
+            file_name.try_into()
+        })?;
+        let dir = search_index_dir(path_prefix, table_name, &index_name);
+        let metadata = SearchIndexZipMetadata {
+            format_version: SEARCH_INDEX_EXPORT_VERSION,
+            index_name,
+            table_number: table_number.into(),
+            index_config: ConvexObject::try_from(config)?.into(),
+        };
+        zip_snapshot_upload
+            .stream_full_file(
+                format!("{dir}index.json"),
+                serde_json::to_vec(&metadata)?.as_slice(),
+            )
+            .await?;
+        for (file_name, key) in files {
+            let file = search_storage
+                .get(&key)
+                .await?
+                .with_context(|| format!("search index file missing from storage: {key:?}"))?;
+            zip_snapshot_upload
+                .stream_full_file(format!("{dir}{file_name}"), file.into_tokio_reader())
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether any document in the table was written after `after_ts`, up to and
+/// including `until_ts`.
+async fn table_changed_between<RT: Runtime>(
+    worker: &ExportWorker<RT>,
+    tablet_id: TabletId,
+    after_ts: Timestamp,
+    until_ts: Timestamp,
+) -> anyhow::Result<bool> {
+    // Only the first document is read, so the rate limit never applies.
+    let rate_limiter = new_rate_limiter(worker.runtime.clone(), Quota::per_second(NonZeroU32::MIN));
+    let stream = worker.database.load_documents_in_table(
+        tablet_id,
+        TimestampRange::new((Bound::Excluded(after_ts), Bound::Included(until_ts)))?,
+        Order::Asc,
+        &rate_limiter,
+    );
+    pin_mut!(stream);
+    Ok(stream.try_next().await?.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use cmd_util::env::env_config;
+    use common::{
+        bootstrap_model::index::{
+            text_index::{
+                FragmentedTextSegment,
+                TextIndexSnapshot,
+                TextIndexSnapshotData,
+                TextIndexState,
+                TextSnapshotVersion,
+            },
+            IndexConfig,
+        },
+        types::Timestamp,
+    };
+    use must_let::must_let;
+    use proptest::prelude::*;
+
+    use super::map_segment_keys;
+
+    proptest! {
+        #![proptest_config(
+            ProptestConfig { cases: 16 * env_config("CONVEX_PROPTEST_MULTIPLIER", 1), failure_persistence: None, ..ProptestConfig::default() }
+        )]
+
+        #[test]
+        fn test_map_segment_keys_renames_every_file(
+            config in any::<IndexConfig>(),
+            segments in any::<Vec<FragmentedTextSegment>>(),
+        ) {
+            let IndexConfig::Text { developer_config, .. } = config else {
+                return Ok(());
+            };
+            let config = IndexConfig::Text {
+                developer_config,
+                on_disk_state: TextIndexState::SnapshottedAt(TextIndexSnapshot {
+                    data: TextIndexSnapshotData::MultiSegment(segments.clone()),
+                    ts: Timestamp::MIN,
+                    version: TextSnapshotVersion::V2UseStringIds,
+                }),
+            };
+            let mut num_files = 0;
+            let mapped = map_segment_keys(config, |_| {
+                num_files += 1;
+                format!("segment-{num_files}").try_into()
+            })
+            .unwrap();
+            prop_assert_eq!(num_files, segments.len() * 4);
+            must_let!(let IndexConfig::Text {
+                on_disk_state: TextIndexState::SnapshottedAt(TextIndexSnapshot {
+                    data: TextIndexSnapshotData::MultiSegment(mapped_segments),
+                    ..
+                }),
+                ..
+            } = mapped);
+            for (segment, mapped) in segments.iter().zip(mapped_segments) {
+                prop_assert_eq!(&segment.id, &mapped.id);
+                prop_assert_eq!(segment.num_indexed_documents, mapped.num_indexed_documents);
+            }
+        }
+    }
+}
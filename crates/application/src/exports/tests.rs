@@ -52,6 +52,10 @@ use model::{
 use pretty_assertions::assert_eq;
 use runtime::testing::TestRuntime;
 use serde_json::json;
+use sha2::{
+    Digest,
+    Sha256,
+};
 use storage::{
     LocalDirStorage,
     Storage,
@@ -73,6 +77,8 @@ use crate::{
         export_inner,
         get_export_path_prefix,
         zip_uploader::README_MD_CONTENTS,
+        ChecksumManifest,
+        CHECKSUM_MANIFEST_PATH,
     },
     test_helpers::ApplicationTestExt,
     tests::components::unmount_component,
@@ -196,6 +202,7 @@ async fn test_export_zip(rt: TestRuntime) -> anyhow::Result<()> {
             .await?;
         zip_entries.insert(filename, entry_contents);
     }
+    verify_manifest(&mut zip_entries)?;
     assert_eq!(zip_entries, expected_export_entries);
 
     let usage = usage.gather_user_stats();
@@ -207,6 +214,22 @@ async fn test_export_zip(rt: TestRuntime) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Removes the checksum manifest from `zip_entries`, checking that it covers
+/// every other entry.
+fn verify_manifest(zip_entries: &mut BTreeMap<String, String>) -> anyhow::Result<()> {
+    let manifest: ChecksumManifest = serde_json::from_str(
+        &zip_entries
+            .remove(CHECKSUM_MANIFEST_PATH)
+            .context("export is missing its manifest")?,
+    )?;
+    let actual: BTreeMap<_, _> = zip_entries
+        .iter()
+        .map(|(path, contents)| (path.clone(), hex::encode(Sha256::digest(contents))))
+        .collect();
+    assert_eq!(manifest.sha256, actual);
+    Ok(())
+}
+
 async fn write_test_data_in_component(
     db: &Database<TestRuntime>,
     component: ComponentId,
@@ -310,6 +333,7 @@ async fn test_export_components(rt: TestRuntime) -> anyhow::Result<()> {
             .await?;
         zip_entries.insert(filename, entry_contents);
     }
+    verify_manifest(&mut zip_entries)?;
     assert_eq!(zip_entries, expected_export_entries);
 
     let usage = usage.gather_user_stats();
@@ -367,6 +391,7 @@ async fn test_export_unmounted_components(rt: TestRuntime) -> anyhow::Result<()>
             .await?;
         zip_entries.insert(filename);
     }
+    verify_manifest(&mut zip_entries)?;
     assert_eq!(zip_entries, expected_export_entries);
 
     let usage = usage.gather_user_stats();
@@ -468,6 +493,7 @@ async fn test_export_storage(rt: TestRuntime) -> anyhow::Result<()> {
             .await?;
         zip_entries.insert(filename, entry_contents);
     }
+    verify_manifest(&mut zip_entries)?;
     assert_eq!(zip_entries, expected_export_entries);
 
     let usage = usage.gather_user_stats();
@@ -26,7 +26,11 @@ use futures::{
     Future,
     FutureExt,
 };
-use keybroker::Identity;
+use keybroker::{
+    ArchiveKey,
+    DataMaskingKey,
+    Identity,
+};
 use model::exports::{
     types::{
         Export,
@@ -68,6 +72,9 @@ pub struct ExportWorker<RT: Runtime> {
     pub(super) backoff: Backoff,
     pub(super) usage_tracking: UsageCounter,
     pub(super) instance_name: String,
+    /// When set, exports are encrypted with this key before they're uploaded.
+    pub(super) archive_key: Option<ArchiveKey>,
+    pub(super) data_masking_key: DataMaskingKey,
 }
 
 impl<RT: Runtime> ExportWorker<RT> {
@@ -79,6 +86,8 @@ impl<RT: Runtime> ExportWorker<RT> {
         file_storage: Arc<dyn Storage>,
         usage_tracking: UsageCounter,
         instance_name: String,
+        archive_key: Option<ArchiveKey>,
+        data_masking_key: DataMaskingKey,
     ) -> impl Future<Output = ()> + Send {
         let mut worker = Self {
             runtime,
@@ -88,6 +97,8 @@ impl<RT: Runtime> ExportWorker<RT> {
             backoff: Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF),
             usage_tracking,
             instance_name,
+            archive_key,
+            data_masking_key,
         };
         async move {
             loop {
@@ -110,6 +121,7 @@ impl<RT: Runtime> ExportWorker<RT> {
         file_storage: Arc<dyn Storage>,
     ) -> Self {
         use events::usage::NoOpUsageEventLogger;
+        use keybroker::KeyBroker;
 
         Self {
             runtime,
@@ -119,6 +131,8 @@ impl<RT: Runtime> ExportWorker<RT> {
             backoff: Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF),
             usage_tracking: UsageCounter::new(Arc::new(NoOpUsageEventLogger)),
             instance_name: "carnitas".to_string(),
+            archive_key: None,
+            data_masking_key: KeyBroker::dev().data_masking_key(),
         }
     }
 
@@ -313,7 +327,9 @@ impl<RT: Runtime> ExportWorker<RT> {
 
         let tag = requestor.usage_tag().to_string();
         let call_type = match requestor {
-            ExportRequestor::SnapshotExport => CallType::Export,
+            ExportRequestor::SnapshotExport | ExportRequestor::PreviewDeploymentClone => {
+                CallType::Export
+            },
             ExportRequestor::CloudBackup => CallType::CloudBackup,
         };
         // Charge file bandwidth for the upload of the snapshot to exports storage
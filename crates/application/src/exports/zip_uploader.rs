@@ -1,3 +1,14 @@
+use std::{
+    collections::BTreeMap,
+    io,
+    pin::Pin,
+    task::{
+        ready,
+        Context,
+        Poll,
+    },
+};
+
 use async_zip::{
     tokio::write::{
         EntryStreamWriter,
@@ -17,10 +28,18 @@ use futures::{
     pin_mut,
     AsyncWriteExt,
 };
+use serde::{
+    Deserialize,
+    Serialize,
+};
 use serde_json::{
     json,
     Value as JsonValue,
 };
+use sha2::{
+    Digest,
+    Sha256,
+};
 use shape_inference::{
     export_context::GeneratedSchema,
     ShapeConfig,
@@ -28,6 +47,7 @@ use shape_inference::{
 use storage::ChannelWriter;
 use tokio::io::{
     AsyncBufRead,
+    AsyncWrite,
     AsyncWriteExt as _,
 };
 use value::export::ValueFormat;
@@ -49,23 +69,70 @@ check out [the docs](https://docs.convex.dev/database/import-export/export) or
 ask us in [Discord](http://convex.dev/community).
 "#;
 
+/// Path of the checksum manifest at the root of an export.
+pub const CHECKSUM_MANIFEST_PATH: &str = "manifest.json";
+
+/// Checksums of every other file in an export, so an import can detect a
+/// corrupted archive before it writes anything.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct ChecksumManifest {
+    /// Hex-encoded SHA-256 of each file's uncompressed contents, by its path
+    /// in the zip.
+    pub sha256: BTreeMap<String, String>,
+}
+
+/// Writes through to `inner`, hashing everything written.
+struct Sha256Writer<W> {
+    inner: W,
+    hasher: Sha256,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for Sha256Writer<W> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let n = ready!(Pin::new(&mut self.inner).poll_write(cx, buf))?;
+        self.hasher.update(&buf[..n]);
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
 // 'a is lifetime of entire zip file writer.
 // 'b is lifetime of entry writer for a single table.
 pub struct ZipSnapshotTableUpload<'a, 'b> {
     entry_writer: EntryStreamWriter<'b, &'a mut ChannelWriter>,
+    path: String,
+    hasher: Sha256,
+    manifest: &'b mut ChecksumManifest,
 }
 
 impl<'a, 'b> ZipSnapshotTableUpload<'a, 'b> {
     async fn new(
         zip_writer: &'b mut ZipFileWriter<&'a mut ChannelWriter>,
+        manifest: &'b mut ChecksumManifest,
         path_prefix: &str,
         table_name: TableName,
     ) -> anyhow::Result<Self> {
-        let source_path = format!("{path_prefix}{table_name}/documents.jsonl");
-        let builder = ZipEntryBuilder::new(source_path.into(), Compression::Deflate)
+        let path = format!("{path_prefix}{table_name}/documents.jsonl");
+        let builder = ZipEntryBuilder::new(path.clone().into(), Compression::Deflate)
             .unix_permissions(ZIP_ENTRY_PERMISSIONS);
         let entry_writer = zip_writer.write_entry_stream(builder.build()).await?;
-        Ok(Self { entry_writer })
+        Ok(Self {
+            entry_writer,
+            path,
+            hasher: Sha256::new(),
+            manifest,
+        })
     }
 
     pub async fn write(&mut self, doc: ResolvedDocument) -> anyhow::Result<()> {
@@ -77,23 +144,32 @@ impl<'a, 'b> ZipSnapshotTableUpload<'a, 'b> {
         let buf = serde_json::to_vec(&json)?;
         self.entry_writer.write_all(&buf).await?;
         self.entry_writer.write_all(&AFTER_DOCUMENTS_CLEAN).await?;
+        self.hasher.update(&buf);
+        self.hasher.update(&AFTER_DOCUMENTS_CLEAN);
         Ok(())
     }
 
     pub async fn complete(self) -> anyhow::Result<()> {
         self.entry_writer.close().await?;
+        self.manifest
+            .sha256
+            .insert(self.path, hex::encode(self.hasher.finalize()));
         Ok(())
     }
 }
 
 pub struct ZipSnapshotUpload<'a> {
     writer: ZipFileWriter<&'a mut ChannelWriter>,
+    manifest: ChecksumManifest,
 }
 
 impl<'a> ZipSnapshotUpload<'a> {
     pub async fn new(out: &'a mut ChannelWriter) -> anyhow::Result<Self> {
         let writer = ZipFileWriter::with_tokio(out);
-        let mut zip_snapshot_upload = Self { writer };
+        let mut zip_snapshot_upload = Self {
+            writer,
+            manifest: ChecksumManifest::default(),
+        };
         zip_snapshot_upload
             .stream_full_file("README.md".to_owned(), README_MD_CONTENTS.as_bytes())
             .await?;
@@ -106,16 +182,23 @@ impl<'a> ZipSnapshotUpload<'a> {
         path: String,
         contents: impl AsyncBufRead,
     ) -> anyhow::Result<()> {
-        let builder = ZipEntryBuilder::new(path.into(), Compression::Deflate)
+        let builder = ZipEntryBuilder::new(path.clone().into(), Compression::Deflate)
             .unix_permissions(ZIP_ENTRY_PERMISSIONS);
-        let mut entry_writer = self
-            .writer
-            .write_entry_stream(builder.build())
-            .await?
-            .compat_write();
+        let mut entry_writer = Sha256Writer {
+            inner: self
+                .writer
+                .write_entry_stream(builder.build())
+                .await?
+                .compat_write(),
+            hasher: Sha256::new(),
+        };
         pin_mut!(contents);
         tokio::io::copy_buf(&mut contents, &mut entry_writer).await?;
-        entry_writer.into_inner().close().await?;
+        let Sha256Writer { inner, hasher } = entry_writer;
+        inner.into_inner().close().await?;
+        self.manifest
+            .sha256
+            .insert(path, hex::encode(hasher.finalize()));
         Ok(())
     }
 
@@ -124,7 +207,13 @@ impl<'a> ZipSnapshotUpload<'a> {
         path_prefix: &str,
         table_name: TableName,
     ) -> anyhow::Result<ZipSnapshotTableUpload<'a, '_>> {
-        ZipSnapshotTableUpload::new(&mut self.writer, path_prefix, table_name).await
+        ZipSnapshotTableUpload::new(
+            &mut self.writer,
+            &mut self.manifest,
+            path_prefix,
+            table_name,
+        )
+        .await
     }
 
     /// System tables have known shape, so we don't need to serialize it.
@@ -134,7 +223,13 @@ impl<'a> ZipSnapshotUpload<'a> {
         table_name: TableName,
     ) -> anyhow::Result<ZipSnapshotTableUpload<'a, '_>> {
         anyhow::ensure!(table_name.is_system());
-        ZipSnapshotTableUpload::new(&mut self.writer, path_prefix, table_name).await
+        ZipSnapshotTableUpload::new(
+            &mut self.writer,
+            &mut self.manifest,
+            path_prefix,
+            table_name,
+        )
+        .await
     }
 
     pub async fn write_generated_schema<T: ShapeConfig>(
@@ -144,13 +239,17 @@ impl<'a> ZipSnapshotUpload<'a> {
         generated_schema: GeneratedSchema<T>,
     ) -> anyhow::Result<()> {
         let generated_schema_path = format!("{path_prefix}{table_name}/generated_schema.jsonl");
-        let builder = ZipEntryBuilder::new(generated_schema_path.into(), Compression::Deflate)
-            .unix_permissions(ZIP_ENTRY_PERMISSIONS);
-        let mut entry_writer = self
-            .writer
-            .write_entry_stream(builder.build())
-            .await?
-            .compat_write();
+        let builder =
+            ZipEntryBuilder::new(generated_schema_path.clone().into(), Compression::Deflate)
+                .unix_permissions(ZIP_ENTRY_PERMISSIONS);
+        let mut entry_writer = Sha256Writer {
+            inner: self
+                .writer
+                .write_entry_stream(builder.build())
+                .await?
+                .compat_write(),
+            hasher: Sha256::new(),
+        };
         let generated_schema_str = generated_schema.inferred_shape.to_string();
         entry_writer
             .write_all(serde_json::to_string(&generated_schema_str)?.as_bytes())
@@ -164,11 +263,22 @@ impl<'a> ZipSnapshotUpload<'a> {
                 .await?;
             entry_writer.write_all(b"\n").await?;
         }
-        entry_writer.into_inner().close().await?;
+        let Sha256Writer { inner, hasher } = entry_writer;
+        inner.into_inner().close().await?;
+        self.manifest
+            .sha256
+            .insert(generated_schema_path, hex::encode(hasher.finalize()));
         Ok(())
     }
 
-    pub async fn complete(self) -> anyhow::Result<()> {
+    /// Write the checksum manifest and finish the zip.
+    pub async fn complete(mut self) -> anyhow::Result<()> {
+        let manifest = serde_json::to_vec_pretty(&self.manifest)?;
+        let builder = ZipEntryBuilder::new(CHECKSUM_MANIFEST_PATH.into(), Compression::Deflate)
+            .unix_permissions(ZIP_ENTRY_PERMISSIONS);
+        let mut entry_writer = self.writer.write_entry_stream(builder.build()).await?;
+        entry_writer.write_all(&manifest).await?;
+        entry_writer.close().await?;
         self.writer.close().await?;
         Ok(())
     }
@@ -0,0 +1,502 @@
+//! Declarative fixtures for seeding development deployments, in place of the
+//! init mutations projects otherwise write by hand. A fixture file lists
+//! documents by table, files, and functions to schedule:
+//!
+//! ```json
+//! {
+//!   "tables": {
+//!     "users": [{ "_key": "alice", "name": "Alice", "avatar": { "$file": "alice.png" } }],
+//!     "messages": [{ "_key": "hi", "author": { "$ref": "users/alice" }, "body": "Hi!" }]
+//!   },
+//!   "files": {
+//!     "alice.png": { "contentType": "image/png", "contents": { "$bytes": "iVBORw0K..." } }
+//!   },
+//!   "scheduledJobs": [{ "function": "messages:notify", "args": { "id": { "$ref": "messages/hi" } } }]
+//! }
+//! ```
+//!
+//! Values use Convex's JSON export format. `{ "$ref": "table/key" }` and
+//! `{ "$file": "key" }` are replaced by the ID of the fixture document or file
+//! with that key. Those IDs are derived from the key and the table's number,
+//! so loading the same fixtures into deployments whose tables have the same
+//! numbers, e.g. fresh deployments with the same schema, gives the same IDs.
+
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
+    time::Duration,
+};
+
+use bytes::Bytes;
+use common::{
+    components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentId,
+    },
+    document::ID_FIELD,
+    execution_context::ExecutionContext,
+    runtime::Runtime,
+};
+use database::{
+    ImportFacingModel,
+    TableModel,
+};
+use errors::ErrorMetadata;
+use futures::stream;
+use headers::{
+    ContentLength,
+    ContentType,
+};
+use keybroker::Identity;
+use model::{
+    deployment_audit_log::types::DeploymentAuditLogEvent,
+    file_storage::FILE_STORAGE_TABLE,
+    scheduled_jobs::VirtualSchedulerModel,
+};
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use sync_types::{
+    CanonicalizedUdfPath,
+    UdfPath,
+};
+use udf::validation::validate_schedule_args;
+use usage_tracking::StorageUsageTracker;
+use value::{
+    sha256::Sha256,
+    val,
+    ConvexObject,
+    ConvexValue,
+    DeveloperDocumentId,
+    InternalId,
+    TableName,
+    TableNamespace,
+};
+
+use crate::Application;
+
+const KEY_FIELD: &str = "_key";
+const REF_FIELD: &str = "$ref";
+const FILE_FIELD: &str = "$file";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct FixturesJson {
+    #[serde(default)]
+    tables: BTreeMap<String, Vec<JsonValue>>,
+    #[serde(default)]
+    files: BTreeMap<String, FixtureFileJson>,
+    #[serde(default)]
+    scheduled_jobs: Vec<FixtureScheduledJobJson>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct FixtureFileJson {
+    content_type: Option<String>,
+    /// A string for text files, or `{ "$bytes": <base64> }`.
+    contents: JsonValue,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct FixtureScheduledJobJson {
+    function: String,
+    args: Option<JsonValue>,
+    delay_ms: Option<u64>,
+}
+
+/// Validated fixtures, ready to load.
+pub struct Fixtures {
+    /// Documents by table, each with its key. References aren't resolved
+    /// until the documents are inserted.
+    tables: BTreeMap<TableName, Vec<(String, JsonValue)>>,
+    files: BTreeMap<String, FixtureFile>,
+    scheduled_jobs: Vec<FixtureScheduledJob>,
+}
+
+struct FixtureFile {
+    content_type: Option<ContentType>,
+    contents: Bytes,
+}
+
+struct FixtureScheduledJob {
+    udf_path: CanonicalizedUdfPath,
+    args: JsonValue,
+    delay: Duration,
+}
+
+fn invalid_fixtures(msg: impl Into<String>) -> ErrorMetadata {
+    ErrorMetadata::bad_request("InvalidFixtures", msg.into())
+}
+
+impl TryFrom<FixturesJson> for Fixtures {
+    type Error = anyhow::Error;
+
+    fn try_from(fixtures: FixturesJson) -> anyhow::Result<Self> {
+        let mut tables = BTreeMap::new();
+        for (table_name, documents) in fixtures.tables {
+            let table_name: TableName = table_name
+                .parse()
+                .map_err(|e| invalid_fixtures(format!("Invalid table name {table_name}: {e}")))?;
+            if table_name.is_system() {
+                anyhow::bail!(invalid_fixtures(format!(
+                    "Fixtures can't write to system table {table_name}"
+                )));
+            }
+            let mut keys = BTreeSet::new();
+            let mut parsed = Vec::with_capacity(documents.len());
+            for document in documents {
+                let JsonValue::Object(mut fields) = document else {
+                    anyhow::bail!(invalid_fixtures(format!(
+                        "Documents in {table_name} must be objects"
+                    )));
+                };
+                let Some(JsonValue::String(key)) = fields.remove(KEY_FIELD) else {
+                    anyhow::bail!(invalid_fixtures(format!(
+                        "Every document in {table_name} needs a string {KEY_FIELD}"
+                    )));
+                };
+                if fields.contains_key("_id") {
+                    anyhow::bail!(invalid_fixtures(format!(
+                        "Document {table_name}/{key} can't set _id; fixture IDs are derived from \
+                         {KEY_FIELD}"
+                    )));
+                }
+                if !keys.insert(key.clone()) {
+                    anyhow::bail!(invalid_fixtures(format!(
+                        "Duplicate document {table_name}/{key}"
+                    )));
+                }
+                parsed.push((key, JsonValue::Object(fields)));
+            }
+            tables.insert(table_name, parsed);
+        }
+        let mut files = BTreeMap::new();
+        for (key, file) in fixtures.files {
+            let content_type = file
+                .content_type
+                .map(|content_type| {
+                    content_type
+                        .parse::<ContentType>()
+                        .map_err(|_| invalid_fixtures(format!("Invalid content type for {key}")))
+                })
+                .transpose()?;
+            let contents = match ConvexValue::try_from(file.contents)? {
+                ConvexValue::String(s) => Bytes::from(String::from(s)),
+                ConvexValue::Bytes(b) => Bytes::from(Vec::from(b)),
+                _ => anyhow::bail!(invalid_fixtures(format!(
+                    "The contents of file {key} must be a string or bytes"
+                ))),
+            };
+            files.insert(
+                key,
+                FixtureFile {
+                    content_type,
+                    contents,
+                },
+            );
+        }
+        let scheduled_jobs = fixtures
+            .scheduled_jobs
+            .into_iter()
+            .map(|job| {
+                let udf_path: UdfPath = job.function.parse().map_err(|e| {
+                    invalid_fixtures(format!("Invalid function {}: {e}", job.function))
+                })?;
+                anyhow::Ok(FixtureScheduledJob {
+                    udf_path: udf_path.canonicalize(),
+                    args: job
+                        .args
+                        .unwrap_or_else(|| JsonValue::Object(Default::default())),
+                    delay: Duration::from_millis(job.delay_ms.unwrap_or(0)),
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self {
+            tables,
+            files,
+            scheduled_jobs,
+        })
+    }
+}
+
+/// What loading fixtures created, with the IDs assigned to each key.
+#[derive(Debug)]
+pub struct FixturesLoaded {
+    /// Keyed by `table/key`.
+    pub document_ids: BTreeMap<String, DeveloperDocumentId>,
+    pub file_ids: BTreeMap<String, DeveloperDocumentId>,
+    pub scheduled_jobs: usize,
+}
+
+/// The stable ID for the fixture `key` in `table_name`.
+fn fixture_internal_id(table_name: &TableName, key: &str) -> InternalId {
+    let digest = Sha256::hash(format!("{table_name}/{key}").as_bytes());
+    let mut bytes = [0; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    InternalId::from(bytes)
+}
+
+/// Replaces `$ref` and `$file` references in `value` with the IDs they refer
+/// to.
+fn resolve_references(
+    value: JsonValue,
+    document_ids: &BTreeMap<String, DeveloperDocumentId>,
+    file_ids: &BTreeMap<String, DeveloperDocumentId>,
+) -> anyhow::Result<JsonValue> {
+    Ok(match value {
+        JsonValue::Object(mut fields) => {
+            if fields.len() == 1 {
+                if let Some(JsonValue::String(key)) = fields.get(REF_FIELD) {
+                    let id = document_ids
+                        .get(key)
+                        .ok_or_else(|| invalid_fixtures(format!("Unknown document {key}")))?;
+                    return Ok(JsonValue::String(id.encode()));
+                }
+                if let Some(JsonValue::String(key)) = fields.get(FILE_FIELD) {
+                    let id = file_ids
+                        .get(key)
+                        .ok_or_else(|| invalid_fixtures(format!("Unknown file {key}")))?;
+                    return Ok(JsonValue::String(id.encode()));
+                }
+            }
+            for field in fields.values_mut() {
+                *field = resolve_references(field.take(), document_ids, file_ids)?;
+            }
+            JsonValue::Object(fields)
+        },
+        JsonValue::Array(values) => JsonValue::Array(
+            values
+                .into_iter()
+                .map(|value| resolve_references(value, document_ids, file_ids))
+                .collect::<anyhow::Result<_>>()?,
+        ),
+        value => value,
+    })
+}
+
+impl<RT: Runtime> Application<RT> {
+    /// Loads `fixtures` into a component in one transaction. The tables being
+    /// loaded, and `_storage` if there are files, must be empty, so fixtures
+    /// only ever seed a fresh deployment.
+    pub async fn load_fixtures(
+        &self,
+        identity: &Identity,
+        component: ComponentId,
+        fixtures: Fixtures,
+        context: ExecutionContext,
+    ) -> anyhow::Result<FixturesLoaded> {
+        let namespace = TableNamespace::from(component);
+
+        // Upload file contents before starting the transaction, which only
+        // records them.
+        let mut entries = BTreeMap::new();
+        for (key, file) in fixtures.files {
+            let entry = self
+                .file_storage
+                .transactional_file_storage
+                .upload_file(
+                    Some(ContentLength(file.contents.len() as u64)),
+                    file.content_type,
+                    stream::once(async move { anyhow::Ok(file.contents) }),
+                    None,
+                )
+                .await?;
+            entries.insert(key, entry);
+        }
+
+        let mut tx = self.begin(identity.clone()).await?;
+        let component_path = tx.must_component_path(component)?;
+        let mut tables_to_check: Vec<_> = fixtures.tables.keys().collect();
+        if !entries.is_empty() {
+            tables_to_check.push(&*FILE_STORAGE_TABLE);
+        }
+        for table_name in tables_to_check {
+            if tx.must_count(namespace, table_name).await? > 0 {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "FixtureTableNotEmpty",
+                    format!(
+                        "Fixtures can only be loaded into empty tables, but {table_name} has \
+                         documents"
+                    ),
+                ));
+            }
+            if !tx
+                .table_mapping()
+                .namespace(namespace)
+                .name_exists(table_name)
+            {
+                TableModel::new(&mut tx)
+                    .insert_table_metadata(namespace, table_name)
+                    .await?;
+            }
+        }
+
+        let table_mapping = tx.table_mapping().clone();
+        let mut document_ids = BTreeMap::new();
+        for (table_name, documents) in &fixtures.tables {
+            let table_number = table_mapping
+                .namespace(namespace)
+                .id(table_name)?
+                .table_number;
+            for (key, _) in documents {
+                document_ids.insert(
+                    format!("{table_name}/{key}"),
+                    DeveloperDocumentId::new(table_number, fixture_internal_id(table_name, key)),
+                );
+            }
+        }
+
+        let mut file_ids = BTreeMap::new();
+        if !entries.is_empty() {
+            let storage_table_id = table_mapping.namespace(namespace).id(&FILE_STORAGE_TABLE)?;
+            for (key, entry) in &entries {
+                let id = DeveloperDocumentId::new(
+                    storage_table_id.table_number,
+                    fixture_internal_id(&FILE_STORAGE_TABLE, key),
+                );
+                let mut object = BTreeMap::from(ConvexObject::try_from(entry.clone())?);
+                object.insert(ID_FIELD.clone().into(), val!(id));
+                ImportFacingModel::new(&mut tx)
+                    .insert(
+                        storage_table_id,
+                        &FILE_STORAGE_TABLE,
+                        ConvexObject::try_from(object)?,
+                        &table_mapping,
+                    )
+                    .await?;
+                // `_storage` IDs are the IDs of the underlying `_file_storage`
+                // documents.
+                file_ids.insert(key.clone(), id);
+            }
+        }
+
+        for (table_name, documents) in fixtures.tables {
+            let table_id = table_mapping.namespace(namespace).id(&table_name)?;
+            for (key, document) in documents {
+                let id = document_ids[&format!("{table_name}/{key}")];
+                let mut object = BTreeMap::from(ConvexObject::try_from(resolve_references(
+                    document,
+                    &document_ids,
+                    &file_ids,
+                )?)?);
+                object.insert(ID_FIELD.clone().into(), val!(id));
+                ImportFacingModel::new(&mut tx)
+                    .insert(
+                        table_id,
+                        &table_name,
+                        ConvexObject::try_from(object)?,
+                        &table_mapping,
+                    )
+                    .await?;
+            }
+        }
+
+        let now = self.runtime.unix_timestamp();
+        let scheduled_jobs = fixtures.scheduled_jobs.len();
+        for job in fixtures.scheduled_jobs {
+            let path = CanonicalizedComponentFunctionPath {
+                component: component_path.clone(),
+                udf_path: job.udf_path,
+            };
+            let args = resolve_references(job.args, &document_ids, &file_ids)?;
+            let scheduled_ts = now + job.delay;
+            let (path, args) =
+                validate_schedule_args(path, vec![args], scheduled_ts, now, &mut tx).await?;
+            VirtualSchedulerModel::new(&mut tx, namespace)
+                .schedule(path, args, scheduled_ts, context.clone(), None)
+                .await?;
+        }
+
+        self.commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::LoadFixtures {
+                documents: document_ids.len() as u64,
+                files: file_ids.len() as u64,
+                scheduled_jobs: scheduled_jobs as u64,
+            }],
+            "load_fixtures",
+        )
+        .await?;
+
+        for entry in entries.into_values() {
+            let content_type = entry
+                .content_type
+                .as_ref()
+                .map(|ct| ct.parse())
+                .transpose()?;
+            self.usage_tracking
+                .track_storage_call(
+                    component_path.clone(),
+                    "store",
+                    entry.storage_id,
+                    content_type,
+                    entry.sha256,
+                )
+                .await
+                .track_storage_ingress_size(
+                    component_path.clone(),
+                    "store".to_string(),
+                    entry.size as u64,
+                )
+                .await;
+        }
+        Ok(FixturesLoaded {
+            document_ids,
+            file_ids,
+            scheduled_jobs,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde_json::json;
+
+    use super::{
+        resolve_references,
+        Fixtures,
+        FixturesJson,
+    };
+
+    #[test]
+    fn test_parse_fixtures() -> anyhow::Result<()> {
+        let fixtures: FixturesJson = serde_json::from_value(json!({
+            "tables": {
+                "users": [{ "_key": "alice", "name": "Alice" }],
+            },
+            "files": { "hello.txt": { "contents": "hello" } },
+            "scheduledJobs": [{ "function": "users:notify" }],
+        }))?;
+        let fixtures = Fixtures::try_from(fixtures)?;
+        assert_eq!(fixtures.tables.len(), 1);
+        assert_eq!(&fixtures.files["hello.txt"].contents[..], b"hello");
+        assert_eq!(fixtures.scheduled_jobs.len(), 1);
+
+        for invalid in [
+            json!({ "tables": { "users": [{ "name": "Alice" }] } }),
+            json!({ "tables": { "users": [{ "_key": "a" }, { "_key": "a" }] } }),
+            json!({ "tables": { "_storage": [{ "_key": "a" }] } }),
+            json!({ "files": { "a": { "contents": 1 } } }),
+        ] {
+            let fixtures: FixturesJson = serde_json::from_value(invalid)?;
+            assert!(Fixtures::try_from(fixtures).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_reference() {
+        let err = resolve_references(
+            json!({ "friend": { "$ref": "users/bob" } }),
+            &BTreeMap::new(),
+            &BTreeMap::new(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("users/bob"));
+    }
+}
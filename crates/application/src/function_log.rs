@@ -27,8 +27,10 @@ use common::{
     identity::InertIdentity,
     knobs,
     log_lines::{
+        LogLevel,
         LogLine,
         LogLines,
+        SystemLogMetadata,
     },
     log_streaming::{
         self,
@@ -59,12 +61,29 @@ use http::{
     StatusCode,
 };
 use itertools::Itertools;
+use model::{
+    data_masking::types::DataMaskingPolicy,
+    error_groups::types::{
+        ErrorOccurrence,
+        ErrorSample,
+    },
+    function_runs::types::{
+        truncate_log_lines,
+        FunctionRun,
+        FunctionRunOutcome,
+    },
+    read_set_advice::types::ReadSetAdvice,
+    webhooks::types::WebhookEvent,
+};
 use parking_lot::Mutex;
 use serde_json::{
     json,
     Value as JsonValue,
 };
-use tokio::sync::oneshot;
+use tokio::sync::{
+    mpsc,
+    oneshot,
+};
 use udf::{
     validation::{
         ValidatedActionOutcome,
@@ -103,6 +122,8 @@ use value::{
     ConvexArray,
 };
 
+use crate::read_set_advisor::ReadSetAdvisor;
+
 /// A function's execution is summarized by this structure and stored in the
 /// UdfExecutionLog
 #[derive(Debug, Clone)]
@@ -186,7 +207,7 @@ impl FunctionExecution {
         }
     }
 
-    fn event_source(
+    pub(crate) fn event_source(
         &self,
         sub_function_path: Option<&CanonicalizedComponentFunctionPath>,
     ) -> FunctionEventSource {
@@ -295,6 +316,95 @@ impl FunctionExecution {
 
         Ok(events)
     }
+
+    /// Summarizes this execution for `_function_runs`, masking console output
+    /// with `data_masking_policy`.
+    fn to_function_run(
+        &self,
+        data_masking_policy: &DataMaskingPolicy,
+    ) -> anyhow::Result<FunctionRun> {
+        let source = self.event_source(None);
+        let log_lines = self
+            .log_lines
+            .iter()
+            .cloned()
+            .flat_map(LogLine::to_pretty_strings)
+            .map(|line| data_masking_policy.mask_log_message(&line))
+            .collect();
+        let (log_lines, log_lines_truncated) =
+            truncate_log_lines(log_lines, *knobs::FUNCTION_RUNS_MAX_LOG_BYTES);
+        Ok(FunctionRun {
+            component_path: source.component_path,
+            udf_path: source.udf_path,
+            udf_type: self.udf_type,
+            caller: self.caller.to_string(),
+            request_id: self.context.request_id.to_string(),
+            execution_id: self.context.execution_id.to_string(),
+            started_at_ms: self.execution_timestamp.as_ms_since_epoch()?.try_into()?,
+            duration_ms: (self.execution_time * 1000.) as i64,
+            outcome: match self.params.err() {
+                Some(e) => FunctionRunOutcome::Failure {
+                    error: e.to_string(),
+                },
+                None => FunctionRunOutcome::Success,
+            },
+            documents_read: self.usage_stats.database_read_documents.try_into()?,
+            bytes_read: self.usage_stats.database_read_bytes.try_into()?,
+            documents_written: self
+                .tables_touched
+                .values()
+                .map(|stats| stats.rows_written)
+                .sum::<u64>()
+                .try_into()?,
+            bytes_written: self.usage_stats.database_write_bytes.try_into()?,
+            log_lines,
+            log_lines_truncated,
+        })
+    }
+
+    /// The uncaught exception this execution failed with, if any, for
+    /// `_error_groups`. The message is masked with `data_masking_policy`.
+    fn to_error_occurrence(
+        &self,
+        data_masking_policy: &DataMaskingPolicy,
+    ) -> anyhow::Result<Option<ErrorOccurrence>> {
+        let Some(error) = self.params.err() else {
+            return Ok(None);
+        };
+        let source = self.event_source(None);
+        Ok(Some(ErrorOccurrence {
+            fingerprint: error.fingerprint(&source.component_path, &source.udf_path),
+            component_path: source.component_path,
+            udf_path: source.udf_path,
+            sample: ErrorSample {
+                timestamp_ms: self.unix_timestamp.as_ms_since_epoch()?.try_into()?,
+                request_id: self.context.request_id.to_string(),
+                message: data_masking_policy.mask_log_message(&error.message),
+                stack: error
+                    .frames
+                    .as_ref()
+                    .map(|frames| frames.to_string())
+                    .unwrap_or_default(),
+            },
+        }))
+    }
+
+    /// The webhook event for this execution if it failed. The error is masked
+    /// with `data_masking_policy`.
+    fn to_function_failed_event(
+        &self,
+        data_masking_policy: &DataMaskingPolicy,
+    ) -> Option<WebhookEvent> {
+        let error = self.params.err()?;
+        let source = self.event_source(None);
+        Some(WebhookEvent::FunctionFailed {
+            component_path: source.component_path,
+            udf_path: source.udf_path,
+            udf_type: self.udf_type.to_string(),
+            error: data_masking_policy.mask_log_message(&error.to_string()),
+            request_id: self.context.request_id.to_string(),
+        })
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -524,7 +634,12 @@ pub struct FunctionExecutionLog<RT: Runtime> {
 }
 
 impl<RT: Runtime> FunctionExecutionLog<RT> {
-    pub fn new(rt: RT, usage_tracking: UsageCounter, log_manager: Arc<dyn LogSender>) -> Self {
+    pub fn new(
+        rt: RT,
+        usage_tracking: UsageCounter,
+        log_manager: Arc<dyn LogSender>,
+        data_masking_policy: DataMaskingPolicy,
+    ) -> Self {
         let base_ts = rt.system_time();
         let inner = Inner {
             rt: rt.clone(),
@@ -532,6 +647,12 @@ impl<RT: Runtime> FunctionExecutionLog<RT> {
             log: WithHeapSize::default(),
             log_waiters: vec![].into(),
             log_manager,
+            data_masking_policy: Arc::new(data_masking_policy),
+            function_runs: None,
+            error_groups: None,
+            webhook_events: None,
+            read_set_advisor: ReadSetAdvisor::default(),
+            read_set_advice: None,
             metrics: MetricStore::new(
                 base_ts,
                 MetricStoreConfig {
@@ -550,6 +671,37 @@ impl<RT: Runtime> FunctionExecutionLog<RT> {
         }
     }
 
+    /// Sets the masking rules applied to console output before it is sent to
+    /// log sinks.
+    pub fn set_data_masking_policy(&self, policy: DataMaskingPolicy) {
+        self.inner.lock().data_masking_policy = Arc::new(policy);
+    }
+
+    /// Starts recording completed executions to `_function_runs` through
+    /// `sender`. See `FunctionRunsWriter`.
+    pub fn set_function_runs_sender(&self, sender: mpsc::Sender<FunctionRun>) {
+        self.inner.lock().function_runs = Some(sender);
+    }
+
+    /// Starts grouping uncaught exceptions into `_error_groups` through
+    /// `sender`. See `ErrorGroupsWriter`.
+    pub fn set_error_groups_sender(&self, sender: mpsc::Sender<ErrorOccurrence>) {
+        self.inner.lock().error_groups = Some(sender);
+    }
+
+    /// Starts sending failed executions to webhook endpoints through `sender`.
+    /// See `WebhookEventsWriter`.
+    pub fn set_webhook_events_sender(&self, sender: mpsc::Sender<WebhookEvent>) {
+        self.inner.lock().webhook_events = Some(sender);
+    }
+
+    /// Starts recording functions whose reads are trending toward the
+    /// transaction limits to `_read_set_advice` through `sender`. See
+    /// `ReadSetAdvisor`.
+    pub fn set_read_set_advice_sender(&self, sender: mpsc::Sender<ReadSetAdvice>) {
+        self.inner.lock().read_set_advice = Some(sender);
+    }
+
     pub async fn log_query(
         &self,
         outcome: &UdfOutcome,
@@ -1543,23 +1695,44 @@ struct Inner<RT: Runtime> {
     num_execution_completions: usize,
     log_waiters: WithHeapSize<Vec<oneshot::Sender<()>>>,
     log_manager: Arc<dyn LogSender>,
+    data_masking_policy: Arc<DataMaskingPolicy>,
+    function_runs: Option<mpsc::Sender<FunctionRun>>,
+    error_groups: Option<mpsc::Sender<ErrorOccurrence>>,
+    webhook_events: Option<mpsc::Sender<WebhookEvent>>,
+    read_set_advisor: ReadSetAdvisor,
+    read_set_advice: Option<mpsc::Sender<ReadSetAdvice>>,
     metrics: MetricStore,
 }
 
 impl<RT: Runtime> Inner<RT> {
     fn log_execution(
         &mut self,
-        execution: FunctionExecution,
+        mut execution: FunctionExecution,
         send_console_events: bool,
     ) -> anyhow::Result<()> {
         if let Err(e) = self.log_execution_metrics(&execution) {
             Self::log_metrics_error(e);
         };
+        if let Some(advice) = self.read_set_advisor.observe(&execution) {
+            execution.log_lines.push(LogLine::new_system_log_line(
+                LogLevel::Warn,
+                vec![advice.message()],
+                execution.unix_timestamp,
+                SystemLogMetadata {
+                    code: "warning:ReadSetTrendingTowardLimit".to_string(),
+                },
+            ));
+            if let Some(read_set_advice) = &self.read_set_advice {
+                if read_set_advice.try_send(advice).is_err() {
+                    tracing::warn!("Read set advice buffer is full, dropping advice");
+                }
+            }
+        }
         let next_time = self.next_time()?;
 
         // Gather log lines
         let mut log_events = if send_console_events {
-            execution.console_log_events()
+            self.mask_console_log_events(execution.console_log_events())
         } else {
             vec![]
         };
@@ -1576,6 +1749,40 @@ impl<RT: Runtime> Inner<RT> {
 
         self.log_manager.send_logs(log_events);
 
+        // Cached query results didn't run anything, and recording them would
+        // add a write for every cache hit.
+        if let Some(function_runs) = &self.function_runs
+            && !execution.cached_result
+        {
+            match execution.to_function_run(&self.data_masking_policy) {
+                Ok(run) => {
+                    if function_runs.try_send(run).is_err() {
+                        tracing::warn!("Function runs buffer is full, dropping function run");
+                    }
+                },
+                Err(mut e) => report_error_sync(&mut e),
+            }
+        }
+
+        if let Some(error_groups) = &self.error_groups {
+            match execution.to_error_occurrence(&self.data_masking_policy) {
+                Ok(Some(occurrence)) => {
+                    if error_groups.try_send(occurrence).is_err() {
+                        tracing::warn!("Error groups buffer is full, dropping error occurrence");
+                    }
+                },
+                Ok(None) => {},
+                Err(mut e) => report_error_sync(&mut e),
+            }
+        }
+
+        if let Some(webhook_events) = &self.webhook_events
+            && let Some(event) = execution.to_function_failed_event(&self.data_masking_policy)
+            && webhook_events.try_send(event).is_err()
+        {
+            tracing::warn!("Webhook events buffer is full, dropping function failure");
+        }
+
         self.log
             .push_back((next_time, FunctionExecutionPart::Completion(execution)));
         self.num_execution_completions += 1;
@@ -1591,6 +1798,23 @@ impl<RT: Runtime> Inner<RT> {
         Ok(())
     }
 
+    fn mask_console_log_events(&self, mut log_events: Vec<LogEvent>) -> Vec<LogEvent> {
+        if self.data_masking_policy.is_empty() {
+            return log_events;
+        }
+        for log_event in log_events.iter_mut() {
+            if let StructuredLogEvent::Console { log_line, .. } = &mut log_event.event {
+                log_line.messages = log_line
+                    .messages
+                    .iter()
+                    .map(|message| self.data_masking_policy.mask_log_message(message))
+                    .collect::<Vec<_>>()
+                    .into();
+            }
+        }
+        log_events
+    }
+
     fn log_metrics_error(error: UdfMetricsError) {
         // Only log an error to tracing and/or Sentry at most once every 10 seconds per
         // thread.
@@ -1668,7 +1892,7 @@ impl<RT: Runtime> Inner<RT> {
             function_start_timestamp,
         };
 
-        let log_events = progress.console_log_events();
+        let log_events = self.mask_console_log_events(progress.console_log_events());
         self.log_manager.send_logs(log_events);
         self.log
             .push_back((next_time, FunctionExecutionPart::Progress(progress)));
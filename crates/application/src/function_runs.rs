@@ -0,0 +1,53 @@
+use std::future::Future;
+
+use common::{
+    knobs::FUNCTION_RUNS_BUFFER_SIZE,
+    runtime::Runtime,
+};
+use database::Database;
+use keybroker::Identity;
+use model::function_runs::{
+    types::FunctionRun,
+    FunctionRunsModel,
+};
+use tokio::sync::mpsc;
+
+use crate::batched_writer::batched_writer;
+
+const MAX_BATCH_SIZE: usize = 256;
+
+/// Writes completed function executions to `_function_runs` in batches. Old
+/// runs are deleted by `SystemTableCleanupWorker`.
+pub struct FunctionRunsWriter;
+
+impl FunctionRunsWriter {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new<RT: Runtime>(
+        runtime: RT,
+        database: Database<RT>,
+    ) -> (mpsc::Sender<FunctionRun>, impl Future<Output = ()> + Send) {
+        batched_writer(
+            runtime,
+            "FunctionRunsWriter",
+            "function runs",
+            *FUNCTION_RUNS_BUFFER_SIZE,
+            MAX_BATCH_SIZE,
+            move |runs| Self::write_batch(database.clone(), runs),
+        )
+    }
+
+    async fn write_batch<RT: Runtime>(
+        database: Database<RT>,
+        runs: Vec<FunctionRun>,
+    ) -> anyhow::Result<()> {
+        let mut tx = database.begin(Identity::system()).await?;
+        let mut model = FunctionRunsModel::new(&mut tx);
+        for run in runs {
+            model.insert(run).await?;
+        }
+        database
+            .commit_with_write_source(tx, "function_runs_writer")
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+//! Dependency probes behind the `/healthz` and `/readyz` endpoints.
+
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    time::Duration,
+};
+
+use common::{
+    knobs::HEALTH_CHECK_PROBE_TIMEOUT,
+    runtime::{
+        Runtime,
+        WithTimeout,
+    },
+    types::ObjectKey,
+};
+
+use crate::Application;
+
+/// An object that's never written, so looking it up only checks that storage
+/// can be reached.
+const STORAGE_PROBE_KEY: &str = "_health_check";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HealthStatus {
+    Ok,
+    Unavailable,
+}
+
+#[derive(Clone, Debug)]
+pub struct ComponentHealth {
+    pub status: HealthStatus,
+    pub latency: Duration,
+    /// Why the component is unavailable.
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct HealthReport {
+    pub components: BTreeMap<&'static str, ComponentHealth>,
+}
+
+impl HealthReport {
+    pub fn status(&self) -> HealthStatus {
+        if self
+            .components
+            .values()
+            .all(|component| component.status == HealthStatus::Ok)
+        {
+            HealthStatus::Ok
+        } else {
+            HealthStatus::Unavailable
+        }
+    }
+}
+
+async fn probe<RT: Runtime>(
+    rt: &RT,
+    name: &'static str,
+    fut: impl Future<Output = anyhow::Result<()>> + Send,
+) -> (&'static str, ComponentHealth) {
+    let start = rt.monotonic_now();
+    let result = rt
+        .with_timeout(name, *HEALTH_CHECK_PROBE_TIMEOUT, fut)
+        .await;
+    let health = ComponentHealth {
+        status: if result.is_ok() {
+            HealthStatus::Ok
+        } else {
+            HealthStatus::Unavailable
+        },
+        latency: start.elapsed(),
+        error: result.err().map(|e| format!("{e:#}")),
+    };
+    if let Some(ref error) = health.error {
+        tracing::warn!("Health check {name} failed: {error}");
+    }
+    (name, health)
+}
+
+impl<RT: Runtime> Application<RT> {
+    /// Whether the backend is running and should be left alone. Only checks
+    /// the process itself, so an outage in a dependency doesn't get the
+    /// backend restarted.
+    pub async fn liveness(&self) -> HealthReport {
+        let committer = probe(&self.runtime, "committer", self.database.ping_committer()).await;
+        HealthReport {
+            components: BTreeMap::from([committer]),
+        }
+    }
+
+    /// Whether the backend can serve requests: its dependencies are reachable
+    /// and indexes have finished loading.
+    pub async fn readiness(&self) -> HealthReport {
+        let storage = async {
+            let key = ObjectKey::try_from(STORAGE_PROBE_KEY)?;
+            self.application_storage
+                .files_storage
+                .get_object_attributes(&key)
+                .await?;
+            anyhow::Ok(())
+        };
+        let indexes = async {
+            anyhow::ensure!(
+                self.database.has_search_and_vector_indexes_bootstrapped(),
+                "Text and vector indexes are still bootstrapping"
+            );
+            anyhow::Ok(())
+        };
+        let (committer, persistence, storage, indexes) = futures::join!(
+            probe(&self.runtime, "committer", self.database.ping_committer()),
+            probe(
+                &self.runtime,
+                "persistence",
+                self.database.ping_persistence()
+            ),
+            probe(&self.runtime, "storage", storage),
+            probe(&self.runtime, "indexes", indexes),
+        );
+        HealthReport {
+            components: BTreeMap::from([committer, persistence, storage, indexes]),
+        }
+    }
+}
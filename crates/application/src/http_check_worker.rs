@@ -0,0 +1,161 @@
+use std::{
+    sync::Arc,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use common::{
+    document::ParsedDocument,
+    http::{
+        fetch::FetchClient,
+        HttpRequest,
+    },
+    knobs::HTTP_CHECK_CONCURRENCY,
+    runtime::Runtime,
+};
+use database::{
+    Database,
+    Transaction,
+};
+use http::{
+    HeaderMap,
+    Method,
+};
+use model::http_checks::{
+    types::{
+        HttpCheck,
+        HttpCheckConfig,
+        HttpCheckMethod,
+        HttpCheckResult,
+    },
+    HttpCheckModel,
+};
+use url::Url;
+use value::DeveloperDocumentId;
+
+use crate::due_job_worker::{
+    DueJobWorker,
+    DueJobs,
+};
+
+/// Runs due checks from `_http_checks` and records their results.
+pub struct HttpCheckWorker<RT: Runtime> {
+    runtime: RT,
+    fetch_client: Arc<dyn FetchClient>,
+}
+
+impl<RT: Runtime> HttpCheckWorker<RT> {
+    pub fn start(
+        runtime: RT,
+        database: Database<RT>,
+        fetch_client: Arc<dyn FetchClient>,
+    ) -> impl Future<Output = ()> + Send {
+        let checks = Self {
+            runtime: runtime.clone(),
+            fetch_client,
+        };
+        DueJobWorker::start(runtime, database, checks)
+    }
+
+    /// Makes the check's request once. The body of the response isn't read.
+    async fn run_check(
+        &self,
+        check_id: DeveloperDocumentId,
+        config: &HttpCheckConfig,
+        started_at_ms: i64,
+    ) -> HttpCheckResult {
+        let start = self.runtime.monotonic_now();
+        let timeout = Duration::from_secs(config.timeout_secs.into());
+        let outcome = match self.request(config) {
+            Ok(request) => tokio::select! {
+                response = self.fetch_client.fetch(request.into()) => {
+                    response.map_err(|e| format!("Request failed: {e}"))
+                },
+                _ = self.runtime.wait(timeout) => {
+                    Err(format!("Request timed out after {timeout:?}"))
+                },
+            },
+            Err(e) => Err(format!("Failed to build the request: {e}")),
+        };
+        let latency_ms = (self.runtime.monotonic_now() - start).as_millis() as i64;
+        let (status, ok, error) = match outcome {
+            Ok(response) => {
+                let status = response.status.as_u16();
+                let ok = config.is_expected_status(status);
+                let error = (!ok).then(|| format!("Unexpected status {}", response.status));
+                (Some(status), ok, error)
+            },
+            Err(error) => (None, false, Some(error)),
+        };
+        if let Some(error) = &error {
+            tracing::warn!("HTTP check {} failed: {error}", config.name);
+        }
+        HttpCheckResult {
+            check_id,
+            started_at_ms,
+            latency_ms,
+            status,
+            ok,
+            error,
+        }
+    }
+
+    fn request(&self, config: &HttpCheckConfig) -> anyhow::Result<HttpRequest> {
+        let method = match config.method {
+            HttpCheckMethod::Get => Method::GET,
+            HttpCheckMethod::Head => Method::HEAD,
+        };
+        Ok(HttpRequest {
+            headers: HeaderMap::new(),
+            url: Url::parse(&config.url)?,
+            method,
+            body: None,
+        })
+    }
+}
+
+#[async_trait]
+impl<RT: Runtime> DueJobs<RT> for HttpCheckWorker<RT> {
+    type Job = ParsedDocument<HttpCheck>;
+    type Outcome = HttpCheckResult;
+
+    const BATCH_SIZE: usize = 32;
+    const MAX_IDLE_WAIT: Duration = Duration::from_secs(60);
+    const NAME: &'static str = "HttpCheckWorker";
+    const WRITE_SOURCE: &'static str = "http_checks";
+
+    fn concurrency(&self) -> usize {
+        *HTTP_CHECK_CONCURRENCY
+    }
+
+    async fn list_due(
+        &self,
+        tx: &mut Transaction<RT>,
+        now_ms: i64,
+        limit: usize,
+    ) -> anyhow::Result<Vec<Self::Job>> {
+        HttpCheckModel::new(tx).list_due(now_ms, limit).await
+    }
+
+    async fn next_due_ms(&self, tx: &mut Transaction<RT>) -> anyhow::Result<Option<i64>> {
+        HttpCheckModel::new(tx).next_due_ms().await
+    }
+
+    async fn run(&self, check: &Self::Job, now_ms: i64) -> Self::Outcome {
+        self.run_check(check.developer_id(), &check.config, now_ms)
+            .await
+    }
+
+    async fn record(
+        &self,
+        tx: &mut Transaction<RT>,
+        check: Self::Job,
+        result: Self::Outcome,
+        now_ms: i64,
+    ) -> anyhow::Result<()> {
+        let (id, check) = check.into_id_and_value();
+        HttpCheckModel::new(tx)
+            .record_result(id, &check.config, result, now_ms)
+            .await
+    }
+}
@@ -0,0 +1,265 @@
+//! Per-identity limits on concurrent subscriptions, transition bandwidth and
+//! function calls across all sync sessions, so a single misbehaving client
+//! can't monopolize the deployment. Only authenticated users are limited:
+//! admins and the system are trusted, and anonymous clients share no key.
+
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::Duration,
+};
+
+use common::knobs::{
+    SYNC_MAX_FUNCTION_CALLS_PER_IDENTITY_PER_MINUTE,
+    SYNC_MAX_SUBSCRIPTIONS_PER_IDENTITY,
+    SYNC_MAX_TRANSITION_BYTES_PER_IDENTITY_PER_MINUTE,
+};
+use errors::ErrorMetadata;
+use keybroker::Identity;
+use parking_lot::Mutex;
+use tokio::time::Instant;
+
+use crate::metrics::{
+    log_identity_quota_exceeded,
+    IdentityQuota,
+};
+
+/// Bandwidth and function calls are counted over fixed windows of this
+/// length.
+const QUOTA_WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Default)]
+struct IdentityUsage {
+    active_subscriptions: usize,
+    window_start: Option<Instant>,
+    transition_bytes: u64,
+    function_calls: u64,
+}
+
+impl IdentityUsage {
+    fn advance_window(&mut self, now: Instant) {
+        if self
+            .window_start
+            .is_none_or(|start| now.duration_since(start) >= QUOTA_WINDOW)
+        {
+            self.window_start = Some(now);
+            self.transition_bytes = 0;
+            self.function_calls = 0;
+        }
+    }
+
+    fn is_idle(&self, now: Instant) -> bool {
+        self.active_subscriptions == 0
+            && self
+                .window_start
+                .is_none_or(|start| now.duration_since(start) >= QUOTA_WINDOW)
+    }
+}
+
+struct Inner {
+    identities: BTreeMap<String, IdentityUsage>,
+    /// Prune idle identities once the map grows past this size.
+    prune_at: usize,
+}
+
+impl Inner {
+    fn usage(&mut self, key: &str, now: Instant) -> &mut IdentityUsage {
+        if self.identities.len() >= self.prune_at {
+            self.identities.retain(|_, usage| !usage.is_idle(now));
+            self.prune_at = (self.identities.len() * 2).max(1024);
+        }
+        let usage = self.identities.entry(key.to_string()).or_default();
+        usage.advance_window(now);
+        usage
+    }
+}
+
+/// Shared by every sync worker.
+#[derive(Clone)]
+pub struct IdentityQuotaTracker {
+    inner: Arc<Mutex<Inner>>,
+}
+
+fn quota_key(identity: &Identity) -> Option<&str> {
+    match identity {
+        Identity::User(user) => Some(&user.attributes.token_identifier.0),
+        _ => None,
+    }
+}
+
+impl Default for IdentityQuotaTracker {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                identities: BTreeMap::new(),
+                prune_at: 1024,
+            })),
+        }
+    }
+}
+
+impl IdentityQuotaTracker {
+    /// Tracks the subscriptions of one sync session.
+    pub fn session_subscriptions(&self) -> SessionSubscriptionQuota {
+        SessionSubscriptionQuota {
+            tracker: self.clone(),
+            current: None,
+        }
+    }
+
+    /// Counts a mutation or action call, failing if `identity` has made too
+    /// many calls in the current window.
+    pub fn check_function_call(&self, identity: &Identity, now: Instant) -> anyhow::Result<()> {
+        let Some(key) = quota_key(identity) else {
+            return Ok(());
+        };
+        let max_calls = *SYNC_MAX_FUNCTION_CALLS_PER_IDENTITY_PER_MINUTE;
+        let mut inner = self.inner.lock();
+        let usage = inner.usage(key, now);
+        if usage.function_calls >= max_calls {
+            log_identity_quota_exceeded(IdentityQuota::FunctionCalls);
+            anyhow::bail!(ErrorMetadata::rate_limited(
+                "FunctionCallQuotaExceeded",
+                format!(
+                    "Too many function calls for this user. Only up to {max_calls} mutations and \
+                     actions per minute are allowed across all of a user's connections."
+                ),
+            ));
+        }
+        usage.function_calls += 1;
+        Ok(())
+    }
+
+    /// How long to hold off sending `identity` another transition, if it has
+    /// used up its bandwidth for the current window.
+    pub fn transition_wait(&self, identity: &Identity, now: Instant) -> Option<Duration> {
+        let key = quota_key(identity)?;
+        let mut inner = self.inner.lock();
+        let usage = inner.usage(key, now);
+        if usage.transition_bytes < *SYNC_MAX_TRANSITION_BYTES_PER_IDENTITY_PER_MINUTE {
+            return None;
+        }
+        log_identity_quota_exceeded(IdentityQuota::TransitionBandwidth);
+        let window_start = usage.window_start.unwrap_or(now);
+        Some(QUOTA_WINDOW.saturating_sub(now.duration_since(window_start)))
+    }
+
+    /// Counts the bytes of a transition sent to `identity`.
+    pub fn record_transition_bytes(&self, identity: &Identity, bytes: u64, now: Instant) {
+        let Some(key) = quota_key(identity) else {
+            return;
+        };
+        self.inner.lock().usage(key, now).transition_bytes += bytes;
+    }
+}
+
+/// Counts a sync session's subscriptions toward its identity's limit until
+/// dropped.
+pub struct SessionSubscriptionQuota {
+    tracker: IdentityQuotaTracker,
+    current: Option<(String, usize)>,
+}
+
+impl SessionSubscriptionQuota {
+    /// Replaces the session's count with `num_subscriptions` for `identity`,
+    /// failing if that would take the identity over its limit. The session's
+    /// identity may have changed since the last update.
+    pub fn update(
+        &mut self,
+        identity: &Identity,
+        num_subscriptions: usize,
+        now: Instant,
+    ) -> anyhow::Result<()> {
+        let mut inner = self.tracker.inner.lock();
+        if let Some((key, count)) = self.current.take() {
+            if let Some(usage) = inner.identities.get_mut(&key) {
+                usage.active_subscriptions = usage.active_subscriptions.saturating_sub(count);
+            }
+        }
+        let Some(key) = quota_key(identity) else {
+            return Ok(());
+        };
+        let max_subscriptions = *SYNC_MAX_SUBSCRIPTIONS_PER_IDENTITY;
+        let usage = inner.usage(key, now);
+        if usage.active_subscriptions + num_subscriptions > max_subscriptions {
+            log_identity_quota_exceeded(IdentityQuota::Subscriptions);
+            anyhow::bail!(ErrorMetadata::rate_limited(
+                "SubscriptionQuotaExceeded",
+                format!(
+                    "Too many concurrent query subscriptions for this user. Only up to \
+                     {max_subscriptions} are allowed across all of a user's connections."
+                ),
+            ));
+        }
+        usage.active_subscriptions += num_subscriptions;
+        self.current = Some((key.to_string(), num_subscriptions));
+        Ok(())
+    }
+}
+
+impl Drop for SessionSubscriptionQuota {
+    fn drop(&mut self) {
+        if let Some((key, count)) = self.current.take() {
+            if let Some(usage) = self.tracker.inner.lock().identities.get_mut(&key) {
+                usage.active_subscriptions = usage.active_subscriptions.saturating_sub(count);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use common::knobs::{
+        SYNC_MAX_FUNCTION_CALLS_PER_IDENTITY_PER_MINUTE,
+        SYNC_MAX_SUBSCRIPTIONS_PER_IDENTITY,
+    };
+    use errors::ErrorMetadataAnyhowExt;
+    use keybroker::{
+        testing::TestUserIdentity,
+        Identity,
+        UserIdentity,
+    };
+    use tokio::time::Instant;
+
+    use super::IdentityQuotaTracker;
+
+    #[test]
+    fn test_subscriptions_counted_across_sessions() -> anyhow::Result<()> {
+        let tracker = IdentityQuotaTracker::default();
+        let user = Identity::user(UserIdentity::test());
+        let now = Instant::now();
+        let max = *SYNC_MAX_SUBSCRIPTIONS_PER_IDENTITY;
+
+        let mut first = tracker.session_subscriptions();
+        first.update(&user, max - 1, now)?;
+        let mut second = tracker.session_subscriptions();
+        second.update(&user, 1, now)?;
+        let err = second.update(&user, 2, now).unwrap_err();
+        assert_eq!(err.short_msg(), "SubscriptionQuotaExceeded");
+
+        // Ending a session frees its subscriptions.
+        drop(first);
+        second.update(&user, max, now)?;
+        // Admins aren't limited.
+        tracker
+            .session_subscriptions()
+            .update(&Identity::system(), max + 1, now)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_function_calls_limited_per_window() -> anyhow::Result<()> {
+        let tracker = IdentityQuotaTracker::default();
+        let user = Identity::user(UserIdentity::test());
+        let now = Instant::now();
+        for _ in 0..*SYNC_MAX_FUNCTION_CALLS_PER_IDENTITY_PER_MINUTE {
+            tracker.check_function_call(&user, now)?;
+        }
+        let err = tracker.check_function_call(&user, now).unwrap_err();
+        assert_eq!(err.short_msg(), "FunctionCallQuotaExceeded");
+        tracker.check_function_call(&user, now + Duration::from_secs(60))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,236 @@
+//! Push-time check that queries only use indexes that will exist once the push
+//! finishes. Without it, a query over an index that was never declared (or was
+//! renamed in `schema.ts`) only fails with `IndexNotFoundError` when it runs.
+//!
+//! This is a best-effort scan of the bundled source for chains like
+//! `ctx.db.query("messages").withIndex("by_channel", ...)`, where both the
+//! table and the index are string literals. Queries that build their table or
+//! index names dynamically aren't checked.
+
+use std::{
+    collections::BTreeSet,
+    fmt,
+    sync::LazyLock,
+};
+
+use common::{
+    components::ComponentDefinitionPath,
+    schemas::DatabaseSchema,
+    types::{
+        IndexDescriptor,
+        IndexName,
+        ModuleEnvironment,
+        TableName,
+    },
+};
+use errors::ErrorMetadata;
+use model::config::types::ModuleConfig;
+use regex::Regex;
+
+static INDEX_REFERENCE_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"\.query\(\s*["'`]([A-Za-z0-9_]+)["'`]\s*\)\s*\.withIndex\(\s*["'`]([A-Za-z0-9_]+)["'`]"#,
+    )
+    .unwrap()
+});
+
+/// A query in `module` that reads `index_name` but won't find it.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MissingIndex {
+    pub definition_path: ComponentDefinitionPath,
+    pub module_path: String,
+    pub index_name: IndexName,
+    /// Whether the index is currently deployed, meaning this push removes or
+    /// renames it.
+    pub deployed: bool,
+}
+
+impl fmt::Display for MissingIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.definition_path.is_root() {
+            write!(f, "{}/", &*self.definition_path)?;
+        }
+        write!(f, "{}: {}", self.module_path, self.index_name)?;
+        if self.deployed {
+            write!(f, " (deployed, but removed or renamed in schema.ts)")?;
+        } else {
+            write!(f, " (not declared in schema.ts)")?;
+        }
+        Ok(())
+    }
+}
+
+/// The `(table, index)` pairs that `source` queries by.
+pub fn find_index_references(source: &str) -> BTreeSet<(TableName, IndexDescriptor)> {
+    INDEX_REFERENCE_PATTERN
+        .captures_iter(source)
+        .filter_map(|captures| {
+            let table_name: TableName = captures[1].parse().ok()?;
+            let descriptor = IndexDescriptor::new(captures[2].to_string()).ok()?;
+            Some((table_name, descriptor))
+        })
+        .collect()
+}
+
+/// The indexes the component definition's functions query by that neither
+/// its new schema declares nor every table has. `deployed_indexes` are the
+/// component's indexes before the push, used to tell renamed indexes from
+/// ones that never existed.
+pub fn find_missing_indexes(
+    definition_path: &ComponentDefinitionPath,
+    modules: &[ModuleConfig],
+    schema: Option<&DatabaseSchema>,
+    deployed_indexes: &BTreeSet<IndexName>,
+) -> Vec<MissingIndex> {
+    let mut missing = vec![];
+    for module in modules {
+        // Node actions can't query the database directly.
+        if module.environment != ModuleEnvironment::Isolate {
+            continue;
+        }
+        for (table_name, descriptor) in find_index_references(&module.source) {
+            // System tables are read through `ctx.db.system` and have their own
+            // indexes.
+            if table_name.is_system() || descriptor.is_reserved() {
+                continue;
+            }
+            let declared = schema
+                .and_then(|schema| schema.tables.get(&table_name))
+                .is_some_and(|table| table.indexes.contains_key(&descriptor));
+            if declared {
+                continue;
+            }
+            let Ok(index_name) = IndexName::new(table_name, descriptor) else {
+                continue;
+            };
+            missing.push(MissingIndex {
+                definition_path: definition_path.clone(),
+                module_path: module.path.as_str().to_string(),
+                deployed: deployed_indexes.contains(&index_name),
+                index_name,
+            });
+        }
+    }
+    missing
+}
+
+pub fn missing_indexes_error(missing: &[MissingIndex]) -> ErrorMetadata {
+    let list = missing
+        .iter()
+        .map(|missing| format!("  {missing}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    ErrorMetadata::bad_request(
+        "MissingIndexes",
+        format!(
+            "These queries use indexes that won't exist after this push. Add them to schema.ts or \
+             update the queries:\n{list}"
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{
+        BTreeMap,
+        BTreeSet,
+    };
+
+    use common::{
+        components::ComponentDefinitionPath,
+        schemas::{
+            DatabaseSchema,
+            IndexSchema,
+            TableDefinition,
+        },
+        types::{
+            IndexDescriptor,
+            ModuleEnvironment,
+            TableName,
+        },
+    };
+    use model::config::types::ModuleConfig;
+
+    use super::{
+        find_index_references,
+        find_missing_indexes,
+    };
+
+    fn module(path: &str, source: &str) -> ModuleConfig {
+        ModuleConfig {
+            path: path.parse().unwrap(),
+            source: source.to_string(),
+            source_map: None,
+            environment: ModuleEnvironment::Isolate,
+        }
+    }
+
+    #[test]
+    fn test_find_index_references() -> anyhow::Result<()> {
+        let source = r#"
+            const a = await ctx.db.query("messages").withIndex("by_channel", (q) => q);
+            const b = await ctx.db
+              .query('users')
+              .withIndex('by_email')
+              .unique();
+            const c = await ctx.db.query(table).withIndex("by_dynamic_table");
+        "#;
+        let references: Vec<_> = find_index_references(source)
+            .into_iter()
+            .map(|(table, index)| format!("{table}.{index}"))
+            .collect();
+        assert_eq!(references, vec!["messages.by_channel", "users.by_email"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_missing_indexes() -> anyhow::Result<()> {
+        let table_name: TableName = "messages".parse()?;
+        let descriptor = IndexDescriptor::new("by_channel")?;
+        let index = IndexSchema {
+            index_descriptor: descriptor.clone(),
+            fields: vec!["channel".parse()?].try_into()?,
+            variant: None,
+        };
+        let table = TableDefinition {
+            table_name: table_name.clone(),
+            indexes: BTreeMap::from([(descriptor, index)]),
+            search_indexes: BTreeMap::new(),
+            vector_indexes: BTreeMap::new(),
+            document_type: None,
+            defaults: Default::default(),
+            mutability: Default::default(),
+            checks: vec![],
+        };
+        let schema = DatabaseSchema {
+            tables: BTreeMap::from([(table_name, table)]),
+            schema_validation: true,
+        };
+        let modules = vec![module(
+            "messages.js",
+            r#"
+                ctx.db.query("messages").withIndex("by_channel");
+                ctx.db.query("messages").withIndex("by_creation_time");
+                ctx.db.query("messages").withIndex("by_author");
+                ctx.db.query("users").withIndex("by_name");
+                ctx.db.system.query("_storage").withIndex("by_sha256");
+            "#,
+        )];
+        let deployed = BTreeSet::from(["messages.by_author".parse()?]);
+        let missing = find_missing_indexes(
+            &ComponentDefinitionPath::root(),
+            &modules,
+            Some(&schema),
+            &deployed,
+        );
+        let missing: Vec<_> = missing.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            missing,
+            vec![
+                "messages.js: messages.by_author (deployed, but removed or renamed in schema.ts)",
+                "messages.js: users.by_name (not declared in schema.ts)",
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+//! Reads a range of any enabled database index directly, so operators can
+//! inspect documents and index keys while diagnosing data issues without
+//! deploying a temporary function.
+
+use common::{
+    bootstrap_model::index::{
+        database_index::DeveloperDatabaseIndexConfig,
+        IndexConfig,
+    },
+    components::ComponentPath,
+    document::ResolvedDocument,
+    index::IndexKey,
+    paths::FieldPath,
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::{
+        IndexDescriptor,
+        IndexName,
+    },
+};
+use database::{
+    unauthorized_error,
+    BootstrapComponentsModel,
+    IndexModel,
+    ResolvedQuery,
+};
+use errors::ErrorMetadata;
+use keybroker::Identity;
+use value::{
+    TableName,
+    TableNamespace,
+};
+
+use crate::Application;
+
+/// The most documents a single scan returns.
+pub const MAX_INDEX_SCAN_LIMIT: usize = 1000;
+
+#[derive(Clone, Debug)]
+pub struct IndexScanRequest {
+    pub component: ComponentPath,
+    pub table: TableName,
+    pub index: IndexDescriptor,
+    /// Bounds on the indexed fields, in the same form as an index range in a
+    /// query.
+    pub range: Vec<IndexRangeExpression>,
+    pub order: Order,
+    pub limit: usize,
+}
+
+pub struct IndexScanResult {
+    /// The fields the index is on, in key order.
+    pub indexed_fields: Vec<FieldPath>,
+    pub entries: Vec<IndexScanEntry>,
+}
+
+pub struct IndexScanEntry {
+    /// The key the document is stored under in the index.
+    pub key: IndexKey,
+    pub document: ResolvedDocument,
+}
+
+impl<RT: Runtime> Application<RT> {
+    /// Returns up to `request.limit` documents from a range of an enabled
+    /// database index, along with their index keys. Private system tables and
+    /// the reserved `by_id` and `by_creation_time` indexes can be scanned too.
+    ///
+    /// The scan runs in a read-only transaction that is never committed.
+    pub async fn scan_index(
+        &self,
+        identity: Identity,
+        request: IndexScanRequest,
+    ) -> anyhow::Result<IndexScanResult> {
+        anyhow::ensure!(
+            identity.is_system() || identity.is_admin(),
+            unauthorized_error("scan_index")
+        );
+        let IndexScanRequest {
+            component,
+            table,
+            index,
+            range,
+            order,
+            limit,
+        } = request;
+        if limit == 0 || limit > MAX_INDEX_SCAN_LIMIT {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidIndexScanLimit",
+                format!("limit must be between 1 and {MAX_INDEX_SCAN_LIMIT}, got {limit}."),
+            ));
+        }
+        let mut tx = self.begin(identity).await?;
+        let Some((_, component_id)) =
+            BootstrapComponentsModel::new(&mut tx).component_path_to_ids(&component)?
+        else {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "ComponentNotFound",
+                format!("No component at {component}."),
+            ));
+        };
+        let namespace = TableNamespace::from(component_id);
+        if !tx.table_mapping().namespace(namespace).name_exists(&table) {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "TableNotFound",
+                format!("Table {table} doesn't exist."),
+            ));
+        }
+        let index_name = if index.is_reserved() {
+            IndexName::new_reserved(table, index)?
+        } else {
+            IndexName::new(table, index)?
+        };
+        let Some(metadata) =
+            IndexModel::new(&mut tx).enabled_index_metadata(namespace, &index_name)?
+        else {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "IndexNotFound",
+                format!("Index {index_name} not found or not enabled."),
+            ));
+        };
+        let IndexConfig::Database {
+            developer_config: DeveloperDatabaseIndexConfig { fields },
+            ..
+        } = metadata.into_value().config
+        else {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "IndexNotADatabaseIndex",
+                format!("Index {index_name} is a search index and can't be scanned by range."),
+            ));
+        };
+
+        let query = Query::index_range(IndexRange {
+            index_name,
+            range,
+            order,
+        });
+        let mut query_stream = ResolvedQuery::new(&mut tx, namespace, query)?;
+        let persistence_version = self.database.persistence_version();
+        let mut entries = Vec::new();
+        while entries.len() < limit {
+            let Some(document) = query_stream
+                .next(&mut tx, Some(limit - entries.len()))
+                .await?
+            else {
+                break;
+            };
+            entries.push(IndexScanEntry {
+                key: document.index_key(&fields, persistence_version),
+                document,
+            });
+        }
+        Ok(IndexScanResult {
+            indexed_fields: fields.into(),
+            entries,
+        })
+    }
+}
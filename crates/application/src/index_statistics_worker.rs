@@ -0,0 +1,136 @@
+//! Keeps the statistics in `_index_statistics` that `estimatedCount()` and
+//! `approxDistinct()` read up to date. An index's statistics are recomputed
+//! when they're missing, older than `INDEX_STATISTICS_INTERVAL`, or were
+//! computed when its table had a very different number of documents.
+
+use std::{
+    collections::BTreeMap,
+    time::Duration,
+};
+
+use common::{
+    backoff::Backoff,
+    bootstrap_model::index::{
+        database_index::{
+            DatabaseIndexState,
+            DeveloperDatabaseIndexConfig,
+        },
+        IndexConfig,
+    },
+    errors::report_error,
+    knobs::INDEX_STATISTICS_INTERVAL,
+    runtime::Runtime,
+};
+use database::{
+    compute_index_statistics,
+    Database,
+    IndexModel,
+    IndexStatisticsModel,
+    TableModel,
+};
+use keybroker::Identity;
+
+use crate::metrics::log_worker_starting;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// How often to look for indexes whose statistics are missing or stale.
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How far a table's count can drift from the count its indexes' statistics
+/// were computed with, as a fraction of the latter, before they're recomputed.
+const MAX_COUNT_DRIFT: f64 = 0.1;
+
+pub struct IndexStatisticsWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+}
+
+impl<RT: Runtime> IndexStatisticsWorker<RT> {
+    pub fn start(runtime: RT, database: Database<RT>) -> impl Future<Output = ()> + Send {
+        let worker = Self { runtime, database };
+        async move {
+            tracing::info!("Starting IndexStatisticsWorker");
+            let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+            loop {
+                if let Err(e) = worker.run().await {
+                    let delay = backoff.fail(&mut worker.runtime.rng());
+                    report_error(&mut e.context("IndexStatisticsWorker died")).await;
+                    tracing::error!("Index statistics worker failed, sleeping {delay:?}");
+                    worker.runtime.wait(delay).await;
+                } else {
+                    backoff.reset();
+                    worker.runtime.wait(POLL_INTERVAL).await;
+                }
+            }
+        }
+    }
+
+    async fn run(&self) -> anyhow::Result<()> {
+        let _status = log_worker_starting("IndexStatisticsWorker");
+        let now_ms = self.runtime.unix_timestamp().as_ms_since_epoch()? as i64;
+        let max_age_ms = INDEX_STATISTICS_INTERVAL.as_millis() as i64;
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let mut previous: BTreeMap<_, _> = IndexStatisticsModel::new(&mut tx)
+            .list()
+            .await?
+            .into_iter()
+            .map(|statistics| (statistics.index_id, statistics.into_value()))
+            .collect();
+        let mut stale = vec![];
+        for index in IndexModel::new(&mut tx).get_all_indexes().await? {
+            let tablet_id = *index.name.table();
+            let IndexConfig::Database {
+                developer_config: DeveloperDatabaseIndexConfig { fields },
+                on_disk_state: DatabaseIndexState::Enabled,
+            } = &index.config
+            else {
+                continue;
+            };
+            if index.name.is_by_id() || tx.table_mapping().is_system_tablet(tablet_id) {
+                continue;
+            }
+            let index_id = index.id().internal_id();
+            let is_stale = match previous.remove(&index_id) {
+                None => true,
+                Some(statistics) => {
+                    let count = TableModel::new(&mut tx).count_tablet(tablet_id).await?;
+                    now_ms - statistics.computed_at_ms >= max_age_ms
+                        || count.is_some_and(|count| {
+                            count.abs_diff(statistics.count) as f64
+                                > MAX_COUNT_DRIFT * statistics.count as f64
+                        })
+                },
+            };
+            if is_stale {
+                stale.push((tablet_id, index_id, fields.clone()));
+            }
+        }
+
+        let ts = self.database.now_ts_for_reads();
+        for (tablet_id, index_id, fields) in stale {
+            let statistics =
+                compute_index_statistics(&self.database, ts, tablet_id, index_id, fields, now_ms)
+                    .await?;
+            let mut tx = self.database.begin(Identity::system()).await?;
+            IndexStatisticsModel::new(&mut tx)
+                .record(statistics)
+                .await?;
+            self.database
+                .commit_with_write_source(tx, "index_statistics")
+                .await?;
+        }
+        // The statistics left over belong to indexes that have been dropped.
+        if !previous.is_empty() {
+            let mut tx = self.database.begin(Identity::system()).await?;
+            for index_id in previous.into_keys() {
+                IndexStatisticsModel::new(&mut tx).delete(index_id).await?;
+            }
+            self.database
+                .commit_with_write_source(tx, "index_statistics")
+                .await?;
+        }
+        Ok(())
+    }
+}
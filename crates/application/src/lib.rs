@@ -48,7 +48,9 @@ use common::{
         index::{
             database_index::IndexedFields,
             index_validation_error,
+            IndexConfig,
             IndexMetadata,
+            TabletIndexMetadata,
         },
         schema::{
             invalid_schema_id,
@@ -68,18 +70,27 @@ use common::{
     },
     document::{
         DocumentUpdate,
+        ParsedDocument,
         CREATION_TIME_FIELD_PATH,
     },
     errors::{
         report_error,
         JsError,
     },
-    http::RequestDestination,
+    http::{
+        fetch::FetchClient,
+        RequestDestination,
+    },
     knobs::{
         APPLICATION_MAX_CONCURRENT_UPLOADS,
+        ERROR_TRACKING_ENABLED,
+        FUNCTION_RUNS_RETENTION,
         MAX_JOBS_CANCEL_BATCH,
         MAX_USER_MODULES,
+        MIN_TS_MAX_WAIT,
+        READ_SET_ADVISOR_THRESHOLD,
         SNAPSHOT_LIST_LIMIT,
+        USAGE_METERING_RETENTION,
     },
     log_lines::LogLines,
     log_streaming::LogSender,
@@ -89,6 +100,7 @@ use common::{
         IndexRange,
         IndexRangeExpression,
         Order,
+        SearchSynonyms,
     },
     query_journal::QueryJournal,
     runtime::{
@@ -137,26 +149,41 @@ use cron_jobs::CronJobExecutor;
 use database::{
     unauthorized_error,
     BootstrapComponentsModel,
+    ComponentQuota,
+    ComponentQuotasModel,
     Database,
     DocumentDeltas,
     FastForwardIndexWorker,
+    HybridSearch,
     IndexModel,
     IndexWorker,
     OccRetryStats,
+    PublicHybridSearchQueryResult,
     ResolvedQuery,
     SchemaModel,
     SearchIndexWorkers,
+    SearchSynonymsModel,
     Snapshot,
     SnapshotPage,
     StreamingExportTableFilter,
     Subscription,
+    TableFreeze,
+    TableFreezesModel,
     TableModel,
+    TableSnapshot,
+    TableSnapshotsModel,
     Token,
     Transaction,
     UserFacingModel,
+    VectorEmbeddingJobModel,
+    VectorEmbeddingStatus,
+    VectorIndexMigration,
+    VectorIndexMigrationModel,
     WriteSource,
 };
 use either::Either;
+use erasure_worker::ErasureWorker;
+use error_groups::ErrorGroupsWriter;
 use errors::{
     ErrorMetadata,
     ErrorMetadataAnyhowExt,
@@ -177,19 +204,30 @@ use function_log::{
     FunctionExecutionPart,
 };
 use function_runner::FunctionRunner;
+use function_runs::FunctionRunsWriter;
 use futures::stream::BoxStream;
 use headers::{
     ContentLength,
     ContentType,
 };
+use http::StatusCode;
+use http_check_worker::HttpCheckWorker;
 use http_client::{
     cached_http_client_for,
     ClientPurpose,
 };
+use identity_quotas::IdentityQuotaTracker;
+use index_statistics_worker::IndexStatisticsWorker;
 use isolate::helpers::source_map_from_slice;
 use keybroker::{
+    ArchiveKey,
     Identity,
     KeyBroker,
+    WebhookSecret,
+};
+use maintenance_mode_worker::{
+    MaintenanceModeCache,
+    MaintenanceModeWorker,
 };
 use maplit::{
     btreemap,
@@ -206,6 +244,7 @@ use model::{
         types::CanonicalUrl,
         CanonicalUrlsModel,
     },
+    code_versions::CodeVersionsModel,
     components::{
         config::ComponentConfigModel,
         handles::FunctionHandlesModel,
@@ -222,12 +261,23 @@ use model::{
         },
         ConfigModel,
     },
+    data_masking::{
+        types::{
+            DataMaskingPolicy,
+            DataMaskingRule,
+        },
+        DataMaskingModel,
+    },
     database_globals::{
         types::StorageTagInitializer,
         DatabaseGlobalsModel,
     },
     deployment_audit_log::{
-        types::DeploymentAuditLogEvent,
+        types::{
+            AuditLogActor,
+            AuditLogRequestContext,
+            DeploymentAuditLogEvent,
+        },
         DeploymentAuditLogModel,
     },
     environment_variables::{
@@ -249,11 +299,30 @@ use model::{
         },
         ExternalPackagesModel,
     },
+    feature_flags::{
+        types::{
+            FeatureFlag,
+            FeatureFlagRule,
+        },
+        FeatureFlagsModel,
+    },
     file_storage::{
         types::FileStorageEntry,
         FileStorageId,
     },
     fivetran_import::FivetranImportModel,
+    http_checks::{
+        types::{
+            HttpCheck,
+            HttpCheckConfig,
+            HttpCheckResult,
+        },
+        HttpCheckModel,
+    },
+    maintenance_mode::{
+        types::MaintenanceMode,
+        MaintenanceModeModel,
+    },
     migrations::MigrationWorker,
     modules::{
         module_versions::{
@@ -277,14 +346,32 @@ use model::{
         upload_download::upload_package,
         SourcePackageModel,
     },
+    static_assets::{
+        types::{
+            StaticAsset,
+            StaticAssetMount,
+        },
+        validate_asset_location,
+        StaticAssetModel,
+    },
     udf_config::{
         types::UdfConfig,
         UdfConfigModel,
     },
+    usage_metering::UsageMeteringModel,
+    webhooks::{
+        types::{
+            WebhookDelivery,
+            WebhookEndpoint,
+            WebhookEventFilter,
+        },
+        WebhookModel,
+    },
 };
 use node_executor::Actions;
 use parking_lot::Mutex;
 use rand::Rng;
+use read_set_advisor::ReadSetAdviceWriter;
 use scheduled_jobs::ScheduledJobRunner;
 use schema_worker::SchemaWorker;
 use search::{
@@ -298,6 +385,7 @@ use semver::Version;
 use serde_json::Value as JsonValue;
 use short_future::ShortBoxFuture;
 use snapshot_import::{
+    backfill_and_enable_indexes_on_table,
     clear_tables,
     start_stored_import,
 };
@@ -312,6 +400,7 @@ use storage::{
     StorageUseCase,
     Upload,
 };
+use subscription_stats::SubscriptionStatsTracker;
 use sync_types::{
     AuthenticationToken,
     CanonicalizedModulePath,
@@ -319,6 +408,7 @@ use sync_types::{
     FunctionName,
     ModulePath,
     SerializedQueryJournal,
+    UdfPath,
 };
 use system_table_cleanup::SystemTableCleanupWorker;
 use table_summary_worker::{
@@ -357,11 +447,20 @@ use value::{
     Namespace,
     ResolvedDocumentId,
     TableNamespace,
+    TabletId,
 };
 use vector::{
     PublicVectorSearchQueryResult,
     VectorSearch,
 };
+use vector_embedding_worker::VectorEmbeddingWorker;
+use vector_index_stats_worker::VectorIndexStatsWorker;
+use vector_reembedding_worker::VectorReembeddingWorker;
+use webhooks::{
+    WebhookChangeFeed,
+    WebhookDeliveryWorker,
+    WebhookEventsWriter,
+};
 
 use crate::{
     application_function_runner::ApplicationFunctionRunner,
@@ -379,26 +478,54 @@ use crate::{
         RedactedLogLines,
     },
     snapshot_import::SnapshotImportWorker,
+    usage_metering::{
+        UsageReport,
+        REPORT_CHUNK_MS,
+        USAGE_WINDOW,
+    },
 };
 
 pub mod airbyte_import;
 pub mod api;
 pub mod application_function_runner;
+mod batched_writer;
+pub mod bench;
 mod cache;
 pub mod cron_jobs;
+pub mod cursor_validity;
 pub mod deploy_config;
+mod due_job_worker;
+pub mod erasure;
+mod erasure_worker;
+mod error_groups;
 mod exports;
+pub mod fixtures;
 pub mod function_log;
-pub mod log_visibility;
+mod function_runs;
+pub mod health;
+mod http_check_worker;
+pub mod identity_quotas;
+mod index_references;
+pub mod index_scan;
+mod index_statistics_worker;
+pub pub mod log_visibility;
+mod maintenance_mode_worker;
 mod metrics;
 mod module_cache;
+mod read_set_advisor;
 pub mod redaction;
 pub mod scheduled_jobs;
 mod schema_worker;
 pub mod snapshot_import;
+pub mod subscription_stats;
 mod system_table_cleanup;
 mod table_summary_worker;
+pub mod usage_metering;
 pub mod valid_identifier;
+mod vector_embedding_worker;
+mod vector_index_stats_worker;
+mod vector_reembedding_worker;
+mod webhooks;
 
 #[cfg(any(test, feature = "testing"))]
 pub mod test_helpers;
@@ -411,6 +538,9 @@ use crate::metrics::{
     log_source_package_size_bytes_total,
 };
 
+/// How often `wait_for_min_ts` checks whether its timestamp is readable yet.
+const MIN_TS_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
 pub struct ConfigMetadataAndSchema {
     pub config_metadata: ConfigMetadata,
     pub schema: Option<DatabaseSchema>,
@@ -547,7 +677,23 @@ pub struct Application<RT: Runtime> {
     snapshot_import_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
     export_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
     system_table_cleanup_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
+    vector_reembedding_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
+    vector_index_stats_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
+    vector_embedding_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
+    index_statistics_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
     migration_worker: Arc<Mutex<Option<Box<dyn SpawnHandle>>>>,
+    function_runs_writer: Arc<Mutex<Option<Box<dyn SpawnHandle>>>>,
+    error_groups_writer: Arc<Mutex<Option<Box<dyn SpawnHandle>>>>,
+    read_set_advice_writer: Arc<Mutex<Option<Box<dyn SpawnHandle>>>>,
+    webhook_events_writer: Arc<Mutex<Box<dyn SpawnHandle>>>,
+    webhook_change_feed: Arc<Mutex<Box<dyn SpawnHandle>>>,
+    webhook_delivery_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
+    http_check_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
+    erasure_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
+    maintenance_mode_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
+    maintenance_mode: MaintenanceModeCache,
+    subscription_stats: SubscriptionStatsTracker,
+    identity_quotas: IdentityQuotaTracker,
     log_sender: Arc<dyn LogSender>,
     log_visibility: Arc<dyn LogVisibility<RT>>,
     module_cache: ModuleCache<RT>,
@@ -578,7 +724,23 @@ impl<RT: Runtime> Clone for Application<RT> {
             snapshot_import_worker: self.snapshot_import_worker.clone(),
             export_worker: self.export_worker.clone(),
             system_table_cleanup_worker: self.system_table_cleanup_worker.clone(),
+            vector_reembedding_worker: self.vector_reembedding_worker.clone(),
+            vector_index_stats_worker: self.vector_index_stats_worker.clone(),
+            vector_embedding_worker: self.vector_embedding_worker.clone(),
+            index_statistics_worker: self.index_statistics_worker.clone(),
             migration_worker: self.migration_worker.clone(),
+            function_runs_writer: self.function_runs_writer.clone(),
+            error_groups_writer: self.error_groups_writer.clone(),
+            read_set_advice_writer: self.read_set_advice_writer.clone(),
+            webhook_events_writer: self.webhook_events_writer.clone(),
+            webhook_change_feed: self.webhook_change_feed.clone(),
+            webhook_delivery_worker: self.webhook_delivery_worker.clone(),
+            http_check_worker: self.http_check_worker.clone(),
+            erasure_worker: self.erasure_worker.clone(),
+            maintenance_mode_worker: self.maintenance_mode_worker.clone(),
+            maintenance_mode: self.maintenance_mode.clone(),
+            subscription_stats: self.subscription_stats.clone(),
+            identity_quotas: self.identity_quotas.clone(),
             log_sender: self.log_sender.clone(),
             log_visibility: self.log_visibility.clone(),
             module_cache: self.module_cache.clone(),
@@ -675,6 +837,8 @@ impl<RT: Runtime> Application<RT> {
         log_visibility: Arc<dyn LogVisibility<RT>>,
         app_auth: Arc<ApplicationAuth>,
         cache: QueryCache,
+        export_encryption_key: Option<ArchiveKey>,
+        fetch_client: Arc<dyn FetchClient>,
     ) -> anyhow::Result<Self> {
         let module_cache =
             ModuleCache::new(runtime.clone(), application_storage.modules_storage.clone()).await;
@@ -724,11 +888,73 @@ impl<RT: Runtime> Application<RT> {
             runtime.spawn("system_table_cleanup_worker", system_table_cleanup_worker),
         ));
 
+        let data_masking_policy = {
+            let mut tx = database.begin(Identity::system()).await?;
+            DataMaskingModel::new(&mut tx)
+                .get_policy(key_broker.data_masking_key())
+                .await?
+        };
         let function_log = FunctionExecutionLog::new(
             runtime.clone(),
             database.usage_counter(),
             log_sender.clone(),
+            data_masking_policy,
         );
+        let function_runs_writer = FUNCTION_RUNS_RETENTION.map(|_| {
+            let (sender, writer) = FunctionRunsWriter::new(runtime.clone(), database.clone());
+            function_log.set_function_runs_sender(sender);
+            runtime.spawn("function_runs_writer", writer)
+        });
+        let function_runs_writer = Arc::new(Mutex::new(function_runs_writer));
+        let error_groups_writer = ERROR_TRACKING_ENABLED.then(|| {
+            let (sender, writer) = ErrorGroupsWriter::new(runtime.clone(), database.clone());
+            function_log.set_error_groups_sender(sender);
+            runtime.spawn("error_groups_writer", writer)
+        });
+        let error_groups_writer = Arc::new(Mutex::new(error_groups_writer));
+        let read_set_advice_writer = (*READ_SET_ADVISOR_THRESHOLD > 0.0).then(|| {
+            let (sender, writer) = ReadSetAdviceWriter::new(runtime.clone(), database.clone());
+            function_log.set_read_set_advice_sender(sender);
+            runtime.spawn("read_set_advice_writer", writer)
+        });
+        let read_set_advice_writer = Arc::new(Mutex::new(read_set_advice_writer));
+        let webhook_events_writer = {
+            let (sender, writer) = WebhookEventsWriter::new(runtime.clone(), database.clone());
+            function_log.set_webhook_events_sender(sender);
+            Arc::new(Mutex::new(runtime.spawn("webhook_events_writer", writer)))
+        };
+        let webhook_change_feed = Arc::new(Mutex::new(runtime.spawn(
+            "webhook_change_feed",
+            WebhookChangeFeed::start(runtime.clone(), database.clone()),
+        )));
+        let webhook_delivery_worker = Arc::new(Mutex::new(runtime.spawn(
+            "webhook_delivery_worker",
+            WebhookDeliveryWorker::start(runtime.clone(), database.clone(), fetch_client.clone()),
+        )));
+        let http_check_worker = Arc::new(Mutex::new(runtime.spawn(
+            "http_check_worker",
+            HttpCheckWorker::start(runtime.clone(), database.clone(), fetch_client),
+        )));
+        let erasure_worker = Arc::new(Mutex::new(runtime.spawn(
+            "erasure_worker",
+            ErasureWorker::start(
+                runtime.clone(),
+                database.clone(),
+                persistence.clone(),
+                file_storage.transactional_file_storage.clone(),
+                key_broker.clone(),
+            ),
+        )));
+        let maintenance_mode = MaintenanceModeCache::load(&database).await?;
+        let maintenance_mode_worker = Arc::new(Mutex::new(runtime.spawn(
+            "maintenance_mode_worker",
+            MaintenanceModeWorker::start(
+                runtime.clone(),
+                database.clone(),
+                maintenance_mode.clone(),
+            ),
+        )));
+        let subscription_stats = SubscriptionStatsTracker::new();
         let runner = Arc::new(ApplicationFunctionRunner::new(
             runtime.clone(),
             database.clone(),
@@ -750,6 +976,7 @@ impl<RT: Runtime> Application<RT> {
             database.clone(),
             runner.clone(),
             function_log.clone(),
+            file_storage.transactional_file_storage.clone(),
         );
 
         let cron_job_executor_fut = CronJobExecutor::run(
@@ -763,6 +990,26 @@ impl<RT: Runtime> Application<RT> {
             runtime.spawn("cron_job_executor", cron_job_executor_fut),
         ));
 
+        let vector_reembedding_worker = Arc::new(Mutex::new(runtime.spawn(
+            "vector_reembedding_worker",
+            VectorReembeddingWorker::start(runtime.clone(), database.clone(), runner.clone()),
+        )));
+
+        let vector_index_stats_worker = Arc::new(Mutex::new(runtime.spawn(
+            "vector_index_stats_worker",
+            VectorIndexStatsWorker::start(runtime.clone(), database.clone()),
+        )));
+
+        let vector_embedding_worker = Arc::new(Mutex::new(runtime.spawn(
+            "vector_embedding_worker",
+            VectorEmbeddingWorker::start(runtime.clone(), database.clone(), runner.clone()),
+        )));
+
+        let index_statistics_worker = Arc::new(Mutex::new(runtime.spawn(
+            "index_statistics_worker",
+            IndexStatisticsWorker::start(runtime.clone(), database.clone()),
+        )));
+
         let export_worker = ExportWorker::new(
             runtime.clone(),
             database.clone(),
@@ -770,6 +1017,8 @@ impl<RT: Runtime> Application<RT> {
             application_storage.files_storage.clone(),
             database.usage_counter().clone(),
             instance_name.clone(),
+            export_encryption_key.clone(),
+            key_broker.data_masking_key(),
         );
         let export_worker = Arc::new(Mutex::new(runtime.spawn("export_worker", export_worker)));
 
@@ -779,6 +1028,7 @@ impl<RT: Runtime> Application<RT> {
             application_storage.snapshot_imports_storage.clone(),
             file_storage.clone(),
             database.usage_counter().clone(),
+            export_encryption_key,
         );
         let snapshot_import_worker = Arc::new(Mutex::new(
             runtime.spawn("snapshot_import_worker", snapshot_import_worker),
@@ -815,7 +1065,23 @@ impl<RT: Runtime> Application<RT> {
             export_worker,
             snapshot_import_worker,
             system_table_cleanup_worker,
+            vector_reembedding_worker,
+            vector_index_stats_worker,
+            vector_embedding_worker,
+            index_statistics_worker,
             migration_worker,
+            function_runs_writer,
+            error_groups_writer,
+            read_set_advice_writer,
+            webhook_events_writer,
+            webhook_change_feed,
+            webhook_delivery_worker,
+            http_check_worker,
+            erasure_worker,
+            maintenance_mode_worker,
+            maintenance_mode,
+            subscription_stats,
+            identity_quotas: IdentityQuotaTracker::default(),
             log_sender,
             log_visibility,
             module_cache,
@@ -852,10 +1118,83 @@ impl<RT: Runtime> Application<RT> {
         self.database.now_ts_for_reads()
     }
 
+    /// Waits until reads at the latest timestamp see every write committed at
+    /// or before `min_ts`, and returns that timestamp. Clients that don't
+    /// subscribe over a WebSocket pass their last mutation's commit timestamp
+    /// here to read their own writes.
+    pub async fn wait_for_min_ts(&self, min_ts: Timestamp) -> anyhow::Result<Timestamp> {
+        let deadline = self.runtime.monotonic_now() + *MIN_TS_MAX_WAIT;
+        loop {
+            let ts = *self.now_ts_for_reads();
+            if ts >= min_ts {
+                return Ok(ts);
+            }
+            if self.runtime.monotonic_now() >= deadline {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "MinTsUnavailable",
+                    format!(
+                        "Timed out waiting for writes up to {min_ts} to become readable. The \
+                         timestamp may come from a different deployment."
+                    ),
+                ));
+            }
+            self.runtime.wait(MIN_TS_POLL_INTERVAL).await;
+        }
+    }
+
     pub fn instance_name(&self) -> String {
         self.instance_name.clone()
     }
 
+    /// Usage rows for windows starting in `[since_ms, until_ms)` along with
+    /// current per-table storage. `since_ms` is clamped to the retention
+    /// window, since older rows have been deleted.
+    pub async fn usage_report(
+        &self,
+        identity: Identity,
+        since_ms: i64,
+        until_ms: i64,
+    ) -> anyhow::Result<UsageReport> {
+        let storage = self
+            .database
+            .get_document_and_index_storage(identity.clone())
+            .await?;
+        let mut functions = vec![];
+        let mut tables = vec![];
+        // Without retention, every row is deleted as soon as it's written.
+        let Some(retention) = *USAGE_METERING_RETENTION else {
+            return Ok(UsageReport {
+                functions,
+                tables,
+                storage,
+            });
+        };
+        // Rows are deleted by creation time, and a row is written up to a
+        // window after its window starts.
+        let now_ms = self.runtime.unix_timestamp().as_ms_since_epoch()? as i64;
+        let window_ms = USAGE_WINDOW.as_millis() as i64;
+        let oldest_ms = now_ms - (retention + USAGE_WINDOW).as_millis() as i64;
+        let since_ms = since_ms.max(oldest_ms - oldest_ms.rem_euclid(window_ms));
+        let mut chunk_start_ms = since_ms;
+        while chunk_start_ms < until_ms {
+            let chunk_end_ms = chunk_start_ms.saturating_add(REPORT_CHUNK_MS).min(until_ms);
+            let mut tx = self.begin(identity.clone()).await?;
+            let mut model = UsageMeteringModel::new(&mut tx);
+            functions.extend(
+                model
+                    .list_function_usage(chunk_start_ms, chunk_end_ms)
+                    .await?,
+            );
+            tables.extend(model.list_table_usage(chunk_start_ms, chunk_end_ms).await?);
+            chunk_start_ms = chunk_end_ms;
+        }
+        Ok(UsageReport {
+            functions,
+            tables,
+            storage,
+        })
+    }
+
     #[fastrace::trace]
     pub async fn begin(&self, identity: Identity) -> anyhow::Result<Transaction<RT>> {
         self.database.begin(identity).await
@@ -977,6 +1316,14 @@ impl<RT: Runtime> Application<RT> {
         self.database.vector_search(identity, query).await
     }
 
+    pub async fn hybrid_search(
+        &self,
+        identity: Identity,
+        query: HybridSearch,
+    ) -> anyhow::Result<(Vec<PublicHybridSearchQueryResult>, FunctionUsageStats)> {
+        self.database.hybrid_search(identity, query).await
+    }
+
     pub async fn get_source_code(
         &self,
         identity: Identity,
@@ -1728,6 +2075,298 @@ impl<RT: Runtime> Application<RT> {
         Self::reevaluate_existing_auth_config(self.runner().clone(), tx).await
     }
 
+    pub async fn get_data_masking_rules(
+        &self,
+        identity: Identity,
+    ) -> anyhow::Result<Vec<DataMaskingRule>> {
+        let mut tx = self.begin(identity).await?;
+        Ok(DataMaskingModel::new(&mut tx)
+            .get_rules()
+            .await?
+            .into_iter()
+            .map(|rule| rule.into_value())
+            .collect())
+    }
+
+    /// Replaces the deployment's data masking rules. The new rules apply to
+    /// console output immediately and to exports started after this returns.
+    pub async fn set_data_masking_rules(
+        &self,
+        identity: Identity,
+        rules: Vec<DataMaskingRule>,
+    ) -> anyhow::Result<()> {
+        let policy = DataMaskingPolicy::new(rules.clone(), self.key_broker.data_masking_key())?;
+        let fields = rules
+            .iter()
+            .map(|rule| match &rule.table_name {
+                Some(table_name) => format!("{table_name}.{}", String::from(rule.field.clone())),
+                None => String::from(rule.field.clone()),
+            })
+            .collect();
+        let mut tx = self.begin(identity).await?;
+        DataMaskingModel::new(&mut tx).set_rules(rules).await?;
+        self.commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::SetDataMaskingRules { fields }],
+            "set_data_masking_rules",
+        )
+        .await?;
+        self.function_log.set_data_masking_policy(policy);
+        Ok(())
+    }
+
+    pub async fn get_search_synonyms(
+        &self,
+        identity: Identity,
+        component_path: &ComponentPath,
+        index_name: &IndexName,
+    ) -> anyhow::Result<SearchSynonyms> {
+        let mut tx = self.begin(identity).await?;
+        let index_id = Self::text_index_id(&mut tx, component_path, index_name)?;
+        SearchSynonymsModel::new(&mut tx).synonyms(index_id).await
+    }
+
+    /// Replaces the synonyms of a text search index. Queries against the index
+    /// use the new synonyms as soon as this returns, without rebuilding it.
+    pub async fn set_search_synonyms(
+        &self,
+        identity: Identity,
+        component_path: &ComponentPath,
+        index_name: &IndexName,
+        synonyms: SearchSynonyms,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.begin(identity).await?;
+        let index_id = Self::text_index_id(&mut tx, component_path, index_name)?;
+        SearchSynonymsModel::new(&mut tx)
+            .set(index_id, synonyms)
+            .await?;
+        self.commit(tx, "set_search_synonyms").await?;
+        Ok(())
+    }
+
+    /// Asks the text index compactor to merge the index's segments as far as
+    /// possible and remove its deleted documents, regardless of the instance's
+    /// compaction policy. Compaction happens in the background after this
+    /// returns.
+    pub async fn compact_text_index(
+        &self,
+        identity: Identity,
+        component_path: &ComponentPath,
+        index_name: &IndexName,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.begin(identity).await?;
+        let index = Self::text_index(&mut tx, component_path, index_name)?;
+        self.search_worker
+            .lock()
+            .text_compaction_requests()
+            .request(index.into_value().name);
+        Ok(())
+    }
+
+    fn text_index_id(
+        tx: &mut Transaction<RT>,
+        component_path: &ComponentPath,
+        index_name: &IndexName,
+    ) -> anyhow::Result<IndexId> {
+        Ok(Self::text_index(tx, component_path, index_name)?
+            .id()
+            .internal_id())
+    }
+
+    fn text_index(
+        tx: &mut Transaction<RT>,
+        component_path: &ComponentPath,
+        index_name: &IndexName,
+    ) -> anyhow::Result<ParsedDocument<TabletIndexMetadata>> {
+        let index_not_found = || {
+            ErrorMetadata::bad_request(
+                "IndexNotFound",
+                format!("No enabled text search index named {index_name}."),
+            )
+        };
+        let (_, component_id) = BootstrapComponentsModel::new(tx)
+            .component_path_to_ids(component_path)?
+            .with_context(index_not_found)?;
+        let index = IndexModel::new(tx)
+            .enabled_index_metadata(component_id.into(), index_name)?
+            .filter(|index| index.is_text_index())
+            .with_context(index_not_found)?;
+        Ok(index)
+    }
+
+    /// Starts moving searches against `source_index_name` to
+    /// `target_index_name`, a vector index on a different field of the same
+    /// table. A background worker calls `reembed_action` with the ids of the
+    /// documents that don't have a vector in the target field yet, in batches
+    /// of `batch_size`, until the migration is cut over or canceled.
+    pub async fn start_vector_index_migration(
+        &self,
+        identity: Identity,
+        component_path: &ComponentPath,
+        source_index_name: &IndexName,
+        target_index_name: &IndexName,
+        reembed_action: String,
+        batch_size: Option<u32>,
+    ) -> anyhow::Result<VectorIndexMigration> {
+        anyhow::ensure!(
+            reembed_action.parse::<UdfPath>().is_ok(),
+            ErrorMetadata::bad_request(
+                "InvalidReembedAction",
+                format!("{reembed_action} is not a valid function path."),
+            )
+        );
+        let mut tx = self.begin(identity).await?;
+        let source = Self::vector_index(&mut tx, component_path, source_index_name)?;
+        let target = Self::vector_index(&mut tx, component_path, target_index_name)?;
+        let vector_field = |index: &ParsedDocument<TabletIndexMetadata>| match &index.config {
+            IndexConfig::Vector {
+                developer_config, ..
+            } => Some(developer_config.vector_field.clone()),
+            _ => None,
+        };
+        anyhow::ensure!(
+            source.name.table() == target.name.table()
+                && vector_field(&source) != vector_field(&target),
+            ErrorMetadata::bad_request(
+                "InvalidVectorIndexMigration",
+                format!(
+                    "{target_index_name} must index a different vector field of the same table as \
+                     {source_index_name}."
+                ),
+            )
+        );
+        let migration = VectorIndexMigrationModel::new(&mut tx)
+            .start(
+                source.id().internal_id(),
+                target.id().internal_id(),
+                reembed_action,
+                batch_size,
+            )
+            .await?;
+        self.commit(tx, "start_vector_index_migration").await?;
+        Ok(migration)
+    }
+
+    /// The vector index migrations of a component's indexes, along with the
+    /// names of their source and target indexes.
+    pub async fn list_vector_index_migrations(
+        &self,
+        identity: Identity,
+        component_path: &ComponentPath,
+    ) -> anyhow::Result<Vec<(IndexName, IndexName, VectorIndexMigration)>> {
+        let mut tx = self.begin(identity).await?;
+        let Some((_, component_id)) =
+            BootstrapComponentsModel::new(&mut tx).component_path_to_ids(component_path)?
+        else {
+            return Ok(vec![]);
+        };
+        let namespace = TableNamespace::from(component_id);
+        let mut index_names = BTreeMap::new();
+        for index in IndexModel::new(&mut tx).get_all_indexes().await? {
+            let tablet_id = *index.name.table();
+            if tx.table_mapping().tablet_namespace(tablet_id)? != namespace {
+                continue;
+            }
+            let name = index
+                .name
+                .clone()
+                .map_table(&tx.table_mapping().tablet_to_name())?;
+            index_names.insert(index.id().internal_id(), name);
+        }
+        let migrations = VectorIndexMigrationModel::new(&mut tx).list().await?;
+        Ok(migrations
+            .into_iter()
+            .filter_map(|migration| {
+                let source = index_names.get(&migration.source_index_id)?.clone();
+                let target = index_names.get(&migration.target_index_id)?.clone();
+                Some((source, target, migration.into_value()))
+            })
+            .collect())
+    }
+
+    /// How many documents of each of a component's vector indexes are waiting
+    /// for their embedder to compute their vectors, or gave up on it.
+    pub async fn vector_embedding_status(
+        &self,
+        identity: Identity,
+        component_path: &ComponentPath,
+    ) -> anyhow::Result<Vec<(IndexName, VectorEmbeddingStatus)>> {
+        let mut tx = self.begin(identity).await?;
+        let Some((_, component_id)) =
+            BootstrapComponentsModel::new(&mut tx).component_path_to_ids(component_path)?
+        else {
+            return Ok(vec![]);
+        };
+        let namespace = TableNamespace::from(component_id);
+        let mut statuses = vec![];
+        for ((tablet_id, index_descriptor), status) in
+            VectorEmbeddingJobModel::new(&mut tx).status().await?
+        {
+            if !tx.table_mapping().tablet_id_exists(tablet_id)
+                || tx.table_mapping().tablet_namespace(tablet_id)? != namespace
+            {
+                continue;
+            }
+            let table_name = tx.table_mapping().tablet_name(tablet_id)?;
+            statuses.push((IndexName::new(table_name, index_descriptor)?, status));
+        }
+        Ok(statuses)
+    }
+
+    /// Atomically switches searches against `source_index_name` to the target
+    /// index of its migration, once the backfill has reached the end of the
+    /// table.
+    pub async fn cut_over_vector_index_migration(
+        &self,
+        identity: Identity,
+        component_path: &ComponentPath,
+        source_index_name: &IndexName,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.begin(identity).await?;
+        let source = Self::vector_index(&mut tx, component_path, source_index_name)?;
+        VectorIndexMigrationModel::new(&mut tx)
+            .cut_over(source.id().internal_id())
+            .await?;
+        self.commit(tx, "cut_over_vector_index_migration").await?;
+        Ok(())
+    }
+
+    pub async fn cancel_vector_index_migration(
+        &self,
+        identity: Identity,
+        component_path: &ComponentPath,
+        source_index_name: &IndexName,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.begin(identity).await?;
+        let source = Self::vector_index(&mut tx, component_path, source_index_name)?;
+        VectorIndexMigrationModel::new(&mut tx)
+            .cancel(source.id().internal_id())
+            .await?;
+        self.commit(tx, "cancel_vector_index_migration").await?;
+        Ok(())
+    }
+
+    fn vector_index(
+        tx: &mut Transaction<RT>,
+        component_path: &ComponentPath,
+        index_name: &IndexName,
+    ) -> anyhow::Result<ParsedDocument<TabletIndexMetadata>> {
+        let index_not_found = || {
+            ErrorMetadata::bad_request(
+                "IndexNotFound",
+                format!("No enabled vector index named {index_name}."),
+            )
+        };
+        let (_, component_id) = BootstrapComponentsModel::new(tx)
+            .component_path_to_ids(component_path)?
+            .with_context(index_not_found)?;
+        let index = IndexModel::new(tx)
+            .enabled_index_metadata(component_id.into(), index_name)?
+            .filter(|index| index.is_vector_index())
+            .with_context(index_not_found)?;
+        Ok(index)
+    }
+
     pub async fn analyze(
         &self,
         udf_config: UdfConfig,
@@ -1963,6 +2602,7 @@ impl<RT: Runtime> Application<RT> {
             .await?;
 
         ComponentConfigModel::new(tx).disable_components().await?;
+        CodeVersionsModel::new(tx).record().await?;
 
         Ok((
             ConfigMetadataAndSchema {
@@ -2529,21 +3169,718 @@ impl<RT: Runtime> Application<RT> {
     ) -> anyhow::Result<u64> {
         let mut tx = self.begin(identity.clone()).await?;
         let mut count = 0;
-        for table_name in table_names {
+        for table_name in table_names.iter() {
             anyhow::ensure!(
                 !table_name.is_system(),
                 "cannot delete system table {table_name}"
             );
             let mut table_model = TableModel::new(&mut tx);
-            count += table_model.must_count(table_namespace, &table_name).await?;
+            count += table_model.must_count(table_namespace, table_name).await?;
             table_model
-                .delete_active_table(table_namespace, table_name)
+                .delete_active_table(table_namespace, table_name.clone())
                 .await?;
         }
-        self.commit(tx, "delete_tables").await?;
+        self.commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::DeleteTables { table_names }],
+            "delete_tables",
+        )
+        .await?;
         Ok(count)
     }
 
+    /// Removes all documents from a user table without deleting them one by
+    /// one. The table's documents move to a fresh, empty tablet with the same
+    /// table number and copies of its indexes, and the old tablet is deleted
+    /// asynchronously. Returns the number of documents removed.
+    pub async fn truncate_table(
+        &self,
+        identity: &Identity,
+        table_namespace: TableNamespace,
+        table_name: TableName,
+    ) -> anyhow::Result<u64> {
+        let (_, new_table_id, _) = self
+            .database
+            .execute_with_overloaded_retries(
+                identity.clone(),
+                FunctionUsageTracker::new(),
+                "truncate_table_prepare",
+                |tx| {
+                    async {
+                        let tablet_id = Self::user_tablet_id(tx, table_namespace, &table_name)?;
+                        TableFreezesModel::new(tx).check_writable(tablet_id).await?;
+                        let backfilling = IndexModel::new(tx)
+                            .all_indexes_on_table(tablet_id)
+                            .await?
+                            .into_iter()
+                            .any(|index| index.config.is_backfilling());
+                        anyhow::ensure!(
+                            !backfilling,
+                            ErrorMetadata::bad_request(
+                                "TableIndexesBackfilling",
+                                format!(
+                                    "{table_name} is still backfilling indexes, so it cannot be \
+                                     truncated. Wait for indexes to complete backfilling"
+                                ),
+                            )
+                        );
+                        // Create the replacement table as Hidden; it's swapped in for the
+                        // existing table once its indexes are enabled.
+                        let new_table_id = TableModel::new(tx)
+                            .insert_table_for_import(
+                                table_namespace,
+                                &table_name,
+                                None,
+                                &BTreeSet::new(),
+                            )
+                            .await?;
+                        IndexModel::new(tx)
+                            .copy_indexes_to_table(
+                                table_namespace,
+                                &table_name,
+                                new_table_id.tablet_id,
+                            )
+                            .await?;
+                        Ok(new_table_id)
+                    }
+                    .into()
+                },
+            )
+            .await?;
+        // The new table is empty, so its indexes should be backfilled quickly.
+        backfill_and_enable_indexes_on_table(&self.database, identity, new_table_id.tablet_id)
+            .await?;
+
+        let mut tx = self.begin(identity.clone()).await?;
+        let documents_deleted = TableModel::new(&mut tx)
+            .activate_table(
+                new_table_id.tablet_id,
+                &table_name,
+                new_table_id.table_number,
+                &BTreeSet::new(),
+            )
+            .await?;
+        self.commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::TruncateTable {
+                table_name,
+                documents_deleted,
+            }],
+            "truncate_table",
+        )
+        .await?;
+        Ok(documents_deleted)
+    }
+
+    /// Makes a user table read-only for `duration`. Writes to it fail with a
+    /// `TableFrozen` error until the freeze expires or is lifted.
+    pub async fn freeze_table(
+        &self,
+        identity: &Identity,
+        table_namespace: TableNamespace,
+        table_name: TableName,
+        reason: String,
+        duration: Duration,
+    ) -> anyhow::Result<TableFreeze> {
+        let mut tx = self.begin(identity.clone()).await?;
+        let tablet_id = Self::user_tablet_id(&mut tx, table_namespace, &table_name)?;
+        let freeze = TableFreezesModel::new(&mut tx)
+            .freeze(tablet_id, reason.clone(), duration)
+            .await?;
+        self.commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::FreezeTable {
+                table_name,
+                reason,
+                duration_secs: duration.as_secs().try_into()?,
+            }],
+            "freeze_table",
+        )
+        .await?;
+        Ok(freeze)
+    }
+
+    pub async fn get_maintenance_mode(
+        &self,
+        identity: &Identity,
+    ) -> anyhow::Result<Option<MaintenanceMode>> {
+        let mut tx = self.begin(identity.clone()).await?;
+        Ok(MaintenanceModeModel::new(&mut tx)
+            .get()
+            .await?
+            .map(|mode| mode.into_value()))
+    }
+
+    /// Rejects function calls from clients with `message` and `status_code`
+    /// until maintenance is disabled. Admins can still call functions.
+    pub async fn enable_maintenance_mode(
+        &self,
+        identity: &Identity,
+        message: String,
+        status_code: u16,
+    ) -> anyhow::Result<MaintenanceMode> {
+        let mut tx = self.begin(identity.clone()).await?;
+        let mode = MaintenanceModeModel::new(&mut tx)
+            .enable(message.clone(), status_code)
+            .await?;
+        let ts = self
+            .commit_with_audit_log_events(
+                tx,
+                vec![DeploymentAuditLogEvent::EnableMaintenanceMode {
+                    message,
+                    status_code,
+                }],
+                "enable_maintenance_mode",
+            )
+            .await?;
+        self.maintenance_mode.update(ts, Some(mode.clone()));
+        Ok(mode)
+    }
+
+    /// Returns whether the deployment was in maintenance.
+    pub async fn disable_maintenance_mode(&self, identity: &Identity) -> anyhow::Result<bool> {
+        let mut tx = self.begin(identity.clone()).await?;
+        let was_enabled = MaintenanceModeModel::new(&mut tx).disable().await?;
+        let ts = self
+            .commit_with_audit_log_events(
+                tx,
+                vec![DeploymentAuditLogEvent::DisableMaintenanceMode],
+                "disable_maintenance_mode",
+            )
+            .await?;
+        self.maintenance_mode.update(ts, None);
+        Ok(was_enabled)
+    }
+
+    /// Fails with the maintenance message and status if the deployment is in
+    /// maintenance and `identity` isn't an admin. Admins acting as a user
+    /// are let through so they can check the deployment before ending
+    /// maintenance. Checks the in-memory `MaintenanceModeCache` rather than
+    /// opening a transaction, since it runs on every request.
+    pub(crate) fn fail_while_in_maintenance(
+        &self,
+        identity: &Identity,
+    ) -> anyhow::Result<()> {
+        if identity.is_admin()
+            || identity.is_system()
+            || matches!(identity, Identity::ActingUser(..))
+        {
+            return Ok(());
+        }
+        let Some(mode) = self.maintenance_mode.get() else {
+            return Ok(());
+        };
+        let error = StatusCode::from_u16(mode.status_code)
+            .ok()
+            .and_then(|status| {
+                ErrorMetadata::from_http_status_code(
+                    status,
+                    "DeploymentInMaintenance",
+                    mode.message.clone(),
+                )
+            })
+            .unwrap_or_else(|| ErrorMetadata::overloaded("DeploymentInMaintenance", mode.message));
+        Err(error.into())
+    }
+
+    pub async fn list_feature_flags(
+        &self,
+        identity: &Identity,
+    ) -> anyhow::Result<Vec<FeatureFlag>> {
+        let mut tx = self.begin(identity.clone()).await?;
+        Ok(FeatureFlagsModel::new(&mut tx)
+            .list()
+            .await?
+            .into_iter()
+            .map(|flag| flag.into_value())
+            .collect())
+    }
+
+    /// Creates or changes a flag. Queries that read it through `ctx.flags`
+    /// rerun for their subscribers once this commits.
+    pub async fn set_feature_flag(
+        &self,
+        identity: &Identity,
+        name: String,
+        rule: FeatureFlagRule,
+    ) -> anyhow::Result<FeatureFlag> {
+        let mut tx = self.begin(identity.clone()).await?;
+        let flag = FeatureFlagsModel::new(&mut tx).set(name, rule).await?;
+        self.commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::SetFeatureFlag { flag: flag.clone() }],
+            "set_feature_flag",
+        )
+        .await?;
+        Ok(flag)
+    }
+
+    /// Returns whether the flag existed.
+    pub async fn delete_feature_flag(
+        &self,
+        identity: &Identity,
+        name: String,
+    ) -> anyhow::Result<bool> {
+        let mut tx = self.begin(identity.clone()).await?;
+        let existed = FeatureFlagsModel::new(&mut tx).delete(&name).await?;
+        self.commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::DeleteFeatureFlag { name }],
+            "delete_feature_flag",
+        )
+        .await?;
+        Ok(existed)
+    }
+
+    /// Lifts a table's freeze. Returns whether the table was frozen.
+    pub async fn unfreeze_table(
+        &self,
+        identity: &Identity,
+        table_namespace: TableNamespace,
+        table_name: TableName,
+    ) -> anyhow::Result<bool> {
+        let mut tx = self.begin(identity.clone()).await?;
+        let tablet_id = Self::user_tablet_id(&mut tx, table_namespace, &table_name)?;
+        let was_frozen = TableFreezesModel::new(&mut tx).unfreeze(tablet_id).await?;
+        self.commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::UnfreezeTable { table_name }],
+            "unfreeze_table",
+        )
+        .await?;
+        Ok(was_frozen)
+    }
+
+    /// Creates a read-only alias `name` for `table_name` as it was at
+    /// `snapshot_ts`, or as of now if no timestamp is given. Functions can
+    /// query the alias like a table while the snapshot is within document
+    /// retention.
+    pub async fn create_table_snapshot(
+        &self,
+        identity: &Identity,
+        table_namespace: TableNamespace,
+        name: TableName,
+        table_name: TableName,
+        snapshot_ts: Option<Timestamp>,
+    ) -> anyhow::Result<TableSnapshot> {
+        let mut tx = self.begin(identity.clone()).await?;
+        let snapshot = TableSnapshotsModel::new(&mut tx, table_namespace)
+            .create(name.clone(), table_name.clone(), snapshot_ts)
+            .await?;
+        self.commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::CreateTableSnapshot { name, table_name }],
+            "create_table_snapshot",
+        )
+        .await?;
+        Ok(snapshot)
+    }
+
+    /// Removes a table snapshot alias. Returns whether it existed.
+    pub async fn delete_table_snapshot(
+        &self,
+        identity: &Identity,
+        table_namespace: TableNamespace,
+        name: TableName,
+    ) -> anyhow::Result<bool> {
+        let mut tx = self.begin(identity.clone()).await?;
+        let existed = TableSnapshotsModel::new(&mut tx, table_namespace)
+            .delete(&name)
+            .await?;
+        self.commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::DeleteTableSnapshot { name }],
+            "delete_table_snapshot",
+        )
+        .await?;
+        Ok(existed)
+    }
+
+    pub async fn list_table_snapshots(
+        &self,
+        identity: &Identity,
+        table_namespace: TableNamespace,
+    ) -> anyhow::Result<Vec<TableSnapshot>> {
+        let mut tx = self.begin(identity.clone()).await?;
+        TableSnapshotsModel::new(&mut tx, table_namespace)
+            .list()
+            .await
+    }
+
+    /// Registers a webhook endpoint, returning its id and signing secret.
+    pub async fn register_webhook_endpoint(
+        &self,
+        identity: &Identity,
+        name: String,
+        url: String,
+        filters: Vec<WebhookEventFilter>,
+    ) -> anyhow::Result<(DeveloperDocumentId, WebhookSecret)> {
+        let mut tx = self.begin(identity.clone()).await?;
+        let (id, secret) = WebhookModel::new(&mut tx)
+            .register_endpoint(name.clone(), url.clone(), filters)
+            .await?;
+        self.commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::RegisterWebhookEndpoint { name, url }],
+            "register_webhook_endpoint",
+        )
+        .await?;
+        Ok((id, secret))
+    }
+
+    pub async fn delete_webhook_endpoint(
+        &self,
+        identity: &Identity,
+        name: String,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.begin(identity.clone()).await?;
+        WebhookModel::new(&mut tx).delete_endpoint(&name).await?;
+        self.commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::DeleteWebhookEndpoint { name }],
+            "delete_webhook_endpoint",
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Replaces a webhook endpoint's signing secret, returning the new one.
+    /// Requests are signed with the new secret from the next attempt on.
+    pub async fn rotate_webhook_secret(
+        &self,
+        identity: &Identity,
+        name: String,
+    ) -> anyhow::Result<WebhookSecret> {
+        let mut tx = self.begin(identity.clone()).await?;
+        let secret = WebhookModel::new(&mut tx).rotate_secret(&name).await?;
+        self.commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::RotateWebhookSecret { name }],
+            "rotate_webhook_secret",
+        )
+        .await?;
+        Ok(secret)
+    }
+
+    pub async fn list_webhook_endpoints(
+        &self,
+        identity: &Identity,
+    ) -> anyhow::Result<Vec<ParsedDocument<WebhookEndpoint>>> {
+        let mut tx = self.begin(identity.clone()).await?;
+        WebhookModel::new(&mut tx).list_endpoints().await
+    }
+
+    /// The endpoint's most recent deliveries, newest first.
+    pub async fn list_webhook_deliveries(
+        &self,
+        identity: &Identity,
+        name: &str,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ParsedDocument<WebhookDelivery>>> {
+        let mut tx = self.begin(identity.clone()).await?;
+        let mut model = WebhookModel::new(&mut tx);
+        let Some(endpoint) = model.get_endpoint_by_name(name).await? else {
+            anyhow::bail!(ErrorMetadata::not_found(
+                "WebhookEndpointNotFound",
+                format!("No webhook endpoint named {name}"),
+            ));
+        };
+        model.list_deliveries(endpoint.id().into(), limit).await
+    }
+
+    /// Sends a failed webhook delivery again.
+    pub async fn retry_webhook_delivery(
+        &self,
+        identity: &Identity,
+        id: DeveloperDocumentId,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.begin(identity.clone()).await?;
+        let now_ms = self
+            .runtime
+            .unix_timestamp()
+            .as_ms_since_epoch()?
+            .try_into()?;
+        WebhookModel::new(&mut tx)
+            .retry_delivery(id, now_ms)
+            .await?;
+        self.commit(tx, "retry_webhook_delivery").await?;
+        Ok(())
+    }
+
+    pub async fn list_static_asset_mounts(
+        &self,
+        identity: &Identity,
+    ) -> anyhow::Result<Vec<ParsedDocument<StaticAssetMount>>> {
+        let mut tx = self.begin(identity.clone()).await?;
+        StaticAssetModel::new(&mut tx).list_mounts().await
+    }
+
+    pub async fn set_static_asset_mount(
+        &self,
+        identity: &Identity,
+        mount: StaticAssetMount,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.begin(identity.clone()).await?;
+        let event = DeploymentAuditLogEvent::SetStaticAssetMount {
+            path_prefix: mount.path_prefix.clone(),
+            folder: mount.folder.clone(),
+        };
+        StaticAssetModel::new(&mut tx).set_mount(mount).await?;
+        self.commit_with_audit_log_events(tx, vec![event], "set_static_asset_mount")
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete_static_asset_mount(
+        &self,
+        identity: &Identity,
+        path_prefix: String,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.begin(identity.clone()).await?;
+        StaticAssetModel::new(&mut tx)
+            .delete_mount(&path_prefix)
+            .await?;
+        self.commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::DeleteStaticAssetMount { path_prefix }],
+            "delete_static_asset_mount",
+        )
+        .await?;
+        Ok(())
+    }
+
+    pub async fn list_static_assets(
+        &self,
+        identity: &Identity,
+        folder: &str,
+    ) -> anyhow::Result<Vec<ParsedDocument<StaticAsset>>> {
+        let mut tx = self.begin(identity.clone()).await?;
+        StaticAssetModel::new(&mut tx).list_assets(folder).await
+    }
+
+    /// Stores a file in the root component's file storage and adds it to
+    /// `folder` at `path`. The file it replaces, if any, is deleted.
+    pub async fn upload_static_asset(
+        &self,
+        identity: &Identity,
+        folder: String,
+        path: String,
+        content_type: Option<ContentType>,
+        cache_control: Option<String>,
+        body: BoxStream<'_, anyhow::Result<Bytes>>,
+    ) -> anyhow::Result<()> {
+        validate_asset_location(&folder, &path)?;
+        let storage_id = self
+            .store_file(ComponentId::Root, None, content_type, None, body)
+            .await?;
+        let entry = self
+            .get_file_entry(ComponentId::Root, FileStorageId::DocumentId(storage_id))
+            .await?;
+        let mut tx = self.begin(identity.clone()).await?;
+        let replaced = StaticAssetModel::new(&mut tx)
+            .put_asset(StaticAsset {
+                folder,
+                path,
+                storage_id,
+                sha256: entry.sha256,
+                size: entry.size,
+                content_type: entry.content_type,
+                cache_control,
+            })
+            .await?;
+        if let Some(replaced) = replaced {
+            self.file_storage
+                .transactional_file_storage
+                .delete(
+                    &mut tx,
+                    ComponentId::Root.into(),
+                    FileStorageId::DocumentId(replaced),
+                )
+                .await?;
+        }
+        self.commit(tx, "upload_static_asset").await?;
+        Ok(())
+    }
+
+    pub async fn delete_static_asset(
+        &self,
+        identity: &Identity,
+        folder: &str,
+        path: &str,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.begin(identity.clone()).await?;
+        let asset = StaticAssetModel::new(&mut tx)
+            .delete_asset(folder, path)
+            .await?;
+        self.file_storage
+            .transactional_file_storage
+            .delete(
+                &mut tx,
+                ComponentId::Root.into(),
+                FileStorageId::DocumentId(asset.storage_id),
+            )
+            .await?;
+        self.commit(tx, "delete_static_asset").await?;
+        Ok(())
+    }
+
+    /// The static asset to serve for a request to `path` on the HTTP actions
+    /// domain, if a mount matches it.
+    pub async fn resolve_static_asset(
+        &self,
+        path: &str,
+    ) -> anyhow::Result<Option<(StaticAssetMount, StaticAsset)>> {
+        let mut tx = self.begin(Identity::system()).await?;
+        StaticAssetModel::new(&mut tx).resolve(path).await
+    }
+
+    pub async fn list_http_checks(
+        &self,
+        identity: &Identity,
+    ) -> anyhow::Result<Vec<ParsedDocument<HttpCheck>>> {
+        let mut tx = self.begin(identity.clone()).await?;
+        HttpCheckModel::new(&mut tx).list_checks().await
+    }
+
+    pub async fn set_http_check(
+        &self,
+        identity: &Identity,
+        config: HttpCheckConfig,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.begin(identity.clone()).await?;
+        let event = DeploymentAuditLogEvent::SetHttpCheck {
+            name: config.name.clone(),
+            url: config.url.clone(),
+        };
+        let now_ms = self
+            .runtime
+            .unix_timestamp()
+            .as_ms_since_epoch()?
+            .try_into()?;
+        HttpCheckModel::new(&mut tx)
+            .set_check(config, now_ms)
+            .await?;
+        self.commit_with_audit_log_events(tx, vec![event], "set_http_check")
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete_http_check(&self, identity: &Identity, name: String) -> anyhow::Result<()> {
+        let mut tx = self.begin(identity.clone()).await?;
+        HttpCheckModel::new(&mut tx).delete_check(&name).await?;
+        self.commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::DeleteHttpCheck { name }],
+            "delete_http_check",
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// The check's most recent results, newest first.
+    pub async fn list_http_check_results(
+        &self,
+        identity: &Identity,
+        name: &str,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ParsedDocument<HttpCheckResult>>> {
+        let mut tx = self.begin(identity.clone()).await?;
+        HttpCheckModel::new(&mut tx).list_results(name, limit).await
+    }
+
+    /// The active freezes of the namespace's tables.
+    pub async fn list_table_freezes(
+        &self,
+        identity: &Identity,
+        table_namespace: TableNamespace,
+    ) -> anyhow::Result<Vec<(TableName, TableFreeze)>> {
+        let mut tx = self.begin(identity.clone()).await?;
+        let freezes = TableFreezesModel::new(&mut tx).list().await?;
+        let table_mapping = tx.table_mapping().namespace(table_namespace);
+        Ok(freezes
+            .into_iter()
+            .filter_map(|freeze| {
+                let table_name = table_mapping.tablet_name(freeze.tablet_id).ok()?;
+                Some((table_name, freeze))
+            })
+            .collect())
+    }
+
+    /// The component's resource quota. The root component is never limited.
+    pub async fn get_component_quota(
+        &self,
+        identity: &Identity,
+        component: ComponentId,
+    ) -> anyhow::Result<ComponentQuota> {
+        let mut tx = self.begin(identity.clone()).await?;
+        ComponentQuotasModel::new(&mut tx).quota(component).await
+    }
+
+    /// Replaces a child component's resource quota. A quota with no limits
+    /// removes it.
+    pub async fn set_component_quota(
+        &self,
+        identity: &Identity,
+        component: ComponentId,
+        quota: ComponentQuota,
+    ) -> anyhow::Result<()> {
+        let ComponentId::Child(component_id) = component else {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidComponentQuota",
+                "The root component can't be given a quota.",
+            ));
+        };
+        let mut tx = self.begin(identity.clone()).await?;
+        if BootstrapComponentsModel::new(&mut tx)
+            .load_component(component)
+            .await?
+            .is_none()
+        {
+            anyhow::bail!(ErrorMetadata::not_found(
+                "ComponentNotFound",
+                format!("Component {component_id} not found."),
+            ));
+        }
+        let component_path =
+            BootstrapComponentsModel::new(&mut tx).must_component_path(component)?;
+        ComponentQuotasModel::new(&mut tx)
+            .set(component_id, quota)
+            .await?;
+        self.commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::SetComponentQuota {
+                component: component_path,
+            }],
+            "set_component_quota",
+        )
+        .await?;
+        Ok(())
+    }
+
+    fn user_tablet_id(
+        tx: &mut Transaction<RT>,
+        table_namespace: TableNamespace,
+        table_name: &TableName,
+    ) -> anyhow::Result<TabletId> {
+        if table_name.is_system() {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidTableFreeze",
+                format!("System table {table_name} can't be frozen."),
+            ));
+        }
+        let Some(table_id) = tx
+            .table_mapping()
+            .namespace(table_namespace)
+            .id_and_number_if_exists(table_name)
+        else {
+            anyhow::bail!(ErrorMetadata::not_found(
+                "TableNotFound",
+                format!("Table {table_name} not found."),
+            ));
+        };
+        Ok(table_id.tablet_id)
+    }
+
     pub async fn delete_component(
         &self,
         identity: &Identity,
@@ -2972,21 +4309,29 @@ impl<RT: Runtime> Application<RT> {
     }
 
     /// Commit a transaction and send audit log events to the log manager if the
-    /// transaction commits successfully.
+    /// transaction commits successfully. The events record the current
+    /// request's context (see [`AuditLogRequestContext::scope`]).
     pub async fn commit_with_audit_log_events(
         &self,
         mut transaction: Transaction<RT>,
         events: Vec<DeploymentAuditLogEvent>,
         write_source: impl Into<WriteSource>,
     ) -> anyhow::Result<Timestamp> {
+        let context = AuditLogRequestContext::current();
+        let actor = AuditLogActor::from(transaction.identity());
         DeploymentAuditLogModel::new(&mut transaction)
-            .insert(events.clone())
+            .insert_with_context(events.clone(), None, &context)
             .await?;
         let ts = self.commit(transaction, write_source).await?;
         let logs = events
             .into_iter()
             .map(|event| {
-                DeploymentAuditLogEvent::to_log_event(event, UnixTimestamp::from_nanos(ts.into()))
+                DeploymentAuditLogEvent::to_log_event_with_context(
+                    event,
+                    &actor,
+                    &context,
+                    UnixTimestamp::from_nanos(ts.into()),
+                )
             })
             .try_collect()?;
 
@@ -3074,6 +4419,8 @@ impl<RT: Runtime> Application<RT> {
             -> ShortBoxFuture<'b, 'a, anyhow::Result<(T, Vec<DeploymentAuditLogEvent>)>>,
     {
         let db = self.database.clone();
+        let actor = AuditLogActor::from(&identity);
+        let context = AuditLogRequestContext::current();
         let (ts, (t, events), stats) = db
             .execute_with_occ_retries(identity, FunctionUsageTracker::new(), write_source, |tx| {
                 Self::insert_deployment_audit_log_events(tx, &f).into()
@@ -3084,7 +4431,12 @@ impl<RT: Runtime> Application<RT> {
         let logs = events
             .into_iter()
             .map(|event| {
-                DeploymentAuditLogEvent::to_log_event(event, UnixTimestamp::from_nanos(ts.into()))
+                DeploymentAuditLogEvent::to_log_event_with_context(
+                    event,
+                    &actor,
+                    &context,
+                    UnixTimestamp::from_nanos(ts.into()),
+                )
             })
             .try_collect()?;
 
@@ -3442,6 +4794,10 @@ impl<RT: Runtime> Application<RT> {
         self.log_sender.shutdown()?;
         self.table_summary_worker.shutdown().await?;
         self.system_table_cleanup_worker.lock().shutdown();
+        self.vector_reembedding_worker.lock().shutdown();
+        self.vector_index_stats_worker.lock().shutdown();
+        self.vector_embedding_worker.lock().shutdown();
+        self.index_statistics_worker.lock().shutdown();
         self.schema_worker.lock().shutdown();
         self.index_worker.lock().shutdown();
         self.search_worker.lock().shutdown();
@@ -3449,6 +4805,21 @@ impl<RT: Runtime> Application<RT> {
         self.fast_forward_worker.lock().shutdown();
         self.export_worker.lock().shutdown();
         self.snapshot_import_worker.lock().shutdown();
+        if let Some(function_runs_writer) = self.function_runs_writer.lock().as_mut() {
+            function_runs_writer.shutdown();
+        }
+        if let Some(error_groups_writer) = self.error_groups_writer.lock().as_mut() {
+            error_groups_writer.shutdown();
+        }
+        if let Some(read_set_advice_writer) = self.read_set_advice_writer.lock().as_mut() {
+            read_set_advice_writer.shutdown();
+        }
+        self.webhook_events_writer.lock().shutdown();
+        self.webhook_change_feed.lock().shutdown();
+        self.webhook_delivery_worker.lock().shutdown();
+        self.http_check_worker.lock().shutdown();
+        self.erasure_worker.lock().shutdown();
+        self.maintenance_mode_worker.lock().shutdown();
         self.runner.shutdown().await?;
         self.scheduled_job_runner.shutdown();
         self.cron_job_executor.lock().shutdown();
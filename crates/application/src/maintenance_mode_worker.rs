@@ -0,0 +1,107 @@
+use std::{
+    future::Future,
+    sync::Arc,
+    time::Duration,
+};
+
+use common::{
+    backoff::Backoff,
+    errors::report_error,
+    runtime::Runtime,
+    types::Timestamp,
+};
+use database::Database;
+use keybroker::Identity;
+use model::maintenance_mode::{
+    types::MaintenanceMode,
+    MaintenanceModeModel,
+};
+use parking_lot::Mutex;
+
+use crate::metrics::log_worker_starting;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// An in-memory copy of the deployment's maintenance mode, so checking it on
+/// every function call doesn't need a transaction. `MaintenanceModeWorker`
+/// keeps it up to date.
+#[derive(Clone)]
+pub struct MaintenanceModeCache {
+    inner: Arc<Mutex<(Timestamp, Option<MaintenanceMode>)>>,
+}
+
+impl MaintenanceModeCache {
+    pub async fn load<RT: Runtime>(database: &Database<RT>) -> anyhow::Result<Self> {
+        let mut tx = database.begin(Identity::system()).await?;
+        let mode = MaintenanceModeModel::new(&mut tx)
+            .get()
+            .await?
+            .map(|mode| mode.into_value());
+        Ok(Self {
+            inner: Arc::new(Mutex::new((*tx.begin_timestamp(), mode))),
+        })
+    }
+
+    pub fn get(&self) -> Option<MaintenanceMode> {
+        self.inner.lock().1.clone()
+    }
+
+    /// Records the maintenance mode as of `ts`, unless the cache already
+    /// holds a newer one.
+    pub fn update(&self, ts: Timestamp, mode: Option<MaintenanceMode>) {
+        let mut inner = self.inner.lock();
+        if ts > inner.0 {
+            *inner = (ts, mode);
+        }
+    }
+}
+
+pub struct MaintenanceModeWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+    cache: MaintenanceModeCache,
+}
+
+impl<RT: Runtime> MaintenanceModeWorker<RT> {
+    pub fn start(
+        runtime: RT,
+        database: Database<RT>,
+        cache: MaintenanceModeCache,
+    ) -> impl Future<Output = ()> + Send {
+        let worker = Self {
+            runtime,
+            database,
+            cache,
+        };
+        async move {
+            tracing::info!("Starting MaintenanceModeWorker");
+            let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+            loop {
+                if let Err(e) = worker.run().await {
+                    let delay = backoff.fail(&mut worker.runtime.rng());
+                    report_error(&mut e.context("MaintenanceModeWorker died")).await;
+                    tracing::error!("Maintenance mode worker failed, sleeping {delay:?}");
+                    worker.runtime.wait(delay).await;
+                } else {
+                    backoff.reset();
+                }
+            }
+        }
+    }
+
+    /// Refreshes the cache and waits for the maintenance mode to change.
+    async fn run(&self) -> anyhow::Result<()> {
+        let _status = log_worker_starting("MaintenanceModeWorker");
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let mode = MaintenanceModeModel::new(&mut tx)
+            .get()
+            .await?
+            .map(|mode| mode.into_value());
+        self.cache.update(*tx.begin_timestamp(), mode);
+        let token = tx.into_token()?;
+        let subscription = self.database.subscribe(token).await?;
+        subscription.wait_for_invalidation().await;
+        Ok(())
+    }
+}
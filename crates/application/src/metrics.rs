@@ -1,5 +1,8 @@
+use std::time::Duration;
+
 use metrics::{
     log_counter_with_labels,
+    log_distribution,
     log_distribution_with_labels,
     register_convex_counter,
     register_convex_histogram,
@@ -9,6 +12,30 @@ use metrics::{
 };
 use model::source_packages::types::PackageSize;
 
+pub enum IdentityQuota {
+    Subscriptions,
+    TransitionBandwidth,
+    FunctionCalls,
+}
+
+register_convex_counter!(
+    IDENTITY_QUOTA_EXCEEDED_TOTAL,
+    "Number of times an identity went over one of its sync quotas",
+    &["quota"],
+);
+pub fn log_identity_quota_exceeded(quota: IdentityQuota) {
+    let quota_label = match quota {
+        IdentityQuota::Subscriptions => "subscriptions",
+        IdentityQuota::TransitionBandwidth => "transition_bandwidth",
+        IdentityQuota::FunctionCalls => "function_calls",
+    };
+    log_counter_with_labels(
+        &IDENTITY_QUOTA_EXCEEDED_TOTAL,
+        1,
+        vec![StaticMetricLabel::new("quota", quota_label)],
+    );
+}
+
 register_convex_counter!(
     EXTERNAL_DEPS_PACKAGES_TOTAL,
     "Total pushes with external dependency packages",
@@ -84,3 +111,35 @@ register_convex_histogram!(
 pub fn table_summary_bootstrap_timer() -> StatusTimer {
     StatusTimer::new(&TABLE_SUMMARY_BOOTSTRAP_SECONDS)
 }
+
+register_convex_histogram!(
+    VECTOR_INDEX_SEGMENTS_TOTAL,
+    "Number of disk segments in each vector index, logged by the stats worker"
+);
+register_convex_histogram!(
+    VECTOR_INDEX_MEMORY_BYTES,
+    "Size of each vector index's in-memory vectors, logged by the stats worker"
+);
+register_convex_histogram!(
+    VECTOR_INDEX_AVERAGE_QUERY_SECONDS,
+    "Average latency of each vector index's searches since the previous stats"
+);
+pub fn log_vector_index_stats(
+    segments: usize,
+    memory_index_bytes: usize,
+    average_query_latency: Option<Duration>,
+) {
+    log_distribution(&VECTOR_INDEX_SEGMENTS_TOTAL, segments as f64);
+    log_distribution(&VECTOR_INDEX_MEMORY_BYTES, memory_index_bytes as f64);
+    if let Some(latency) = average_query_latency {
+        log_distribution(&VECTOR_INDEX_AVERAGE_QUERY_SECONDS, latency.as_secs_f64());
+    }
+}
+
+register_convex_histogram!(
+    VECTOR_INDEX_RECALL_RATIO,
+    "Fraction of the exact nearest neighbors found by a vector index's recall probe"
+);
+pub fn log_vector_index_recall(recall: f64) {
+    log_distribution(&VECTOR_INDEX_RECALL_RATIO, recall);
+}
@@ -0,0 +1,265 @@
+use std::{
+    collections::{
+        BTreeMap,
+        HashMap,
+    },
+    future::Future,
+};
+
+use common::{
+    components::ComponentPath,
+    knobs::{
+        READ_SET_ADVISOR_THRESHOLD,
+        READ_SET_ADVISOR_WARNING_INTERVAL,
+        TRANSACTION_MAX_READ_SIZE_BYTES,
+        TRANSACTION_MAX_READ_SIZE_ROWS,
+    },
+    runtime::Runtime,
+    types::{
+        TableName,
+        TableStats,
+        UdfType,
+    },
+};
+use database::Database;
+use keybroker::Identity;
+use model::read_set_advice::{
+    types::{
+        ReadSetAdvice,
+        TableReads,
+        MAX_TOP_TABLES,
+    },
+    ReadSetAdviceModel,
+};
+use tokio::sync::mpsc;
+
+use crate::{
+    batched_writer::batched_writer,
+    function_log::FunctionExecution,
+};
+
+/// Weight of the newest execution in the moving averages.
+const SMOOTHING_FACTOR: f64 = 0.1;
+
+/// Maximum number of advice records buffered for writing. Advice produced
+/// while the buffer is full is dropped; it's produced again on the function's
+/// next warning.
+const BUFFER_SIZE: usize = 100;
+const MAX_BATCH_SIZE: usize = 16;
+
+#[derive(Default)]
+struct FunctionReadStats {
+    executions: i64,
+    average_documents_read: f64,
+    average_bytes_read: f64,
+    max_documents_read: u64,
+    max_bytes_read: u64,
+    first_warned_ms: Option<i64>,
+    last_warned_ms: Option<i64>,
+}
+
+/// Tracks how much each query and mutation reads and flags the ones whose
+/// typical read set is trending toward the transaction read limits, so
+/// developers can fix them before they start failing.
+#[derive(Default)]
+pub struct ReadSetAdvisor {
+    functions: HashMap<(ComponentPath, String), FunctionReadStats>,
+}
+
+impl ReadSetAdvisor {
+    /// Records the reads of a completed execution, returning advice if the
+    /// function should be warned about.
+    pub fn observe(&mut self, execution: &FunctionExecution) -> Option<ReadSetAdvice> {
+        if !matches!(execution.udf_type, UdfType::Query | UdfType::Mutation)
+            || execution.cached_result
+        {
+            return None;
+        }
+        let source = execution.event_source(None);
+        let now_ms = execution.unix_timestamp.as_ms_since_epoch().ok()?;
+        self.record(
+            source.component_path,
+            source.udf_path,
+            &execution.tables_touched,
+            execution.usage_stats.database_read_bytes,
+            now_ms.try_into().ok()?,
+        )
+    }
+
+    fn record(
+        &mut self,
+        component_path: ComponentPath,
+        udf_path: String,
+        tables_touched: &BTreeMap<TableName, TableStats>,
+        bytes_read: u64,
+        now_ms: i64,
+    ) -> Option<ReadSetAdvice> {
+        let threshold = *READ_SET_ADVISOR_THRESHOLD;
+        if threshold <= 0.0 {
+            return None;
+        }
+        let documents_read: u64 = tables_touched
+            .iter()
+            .filter(|(table_name, _)| !table_name.is_system())
+            .map(|(_, stats)| stats.rows_read)
+            .sum();
+
+        let stats = self
+            .functions
+            .entry((component_path.clone(), udf_path.clone()))
+            .or_default();
+        if stats.executions == 0 {
+            stats.average_documents_read = documents_read as f64;
+            stats.average_bytes_read = bytes_read as f64;
+        } else {
+            stats.average_documents_read +=
+                SMOOTHING_FACTOR * (documents_read as f64 - stats.average_documents_read);
+            stats.average_bytes_read +=
+                SMOOTHING_FACTOR * (bytes_read as f64 - stats.average_bytes_read);
+        }
+        stats.executions += 1;
+        stats.max_documents_read = stats.max_documents_read.max(documents_read);
+        stats.max_bytes_read = stats.max_bytes_read.max(bytes_read);
+
+        let documents_limit = *TRANSACTION_MAX_READ_SIZE_ROWS as f64;
+        let bytes_limit = *TRANSACTION_MAX_READ_SIZE_BYTES as f64;
+        if stats.average_documents_read < threshold * documents_limit
+            && stats.average_bytes_read < threshold * bytes_limit
+        {
+            return None;
+        }
+        let interval_ms = READ_SET_ADVISOR_WARNING_INTERVAL.as_millis() as i64;
+        if stats
+            .last_warned_ms
+            .is_some_and(|last_warned_ms| now_ms - last_warned_ms < interval_ms)
+        {
+            return None;
+        }
+        stats.last_warned_ms = Some(now_ms);
+        let first_warned_ms = *stats.first_warned_ms.get_or_insert(now_ms);
+
+        let mut top_tables: Vec<_> = tables_touched
+            .iter()
+            .filter(|(table_name, stats)| !table_name.is_system() && stats.rows_read > 0)
+            .map(|(table_name, stats)| TableReads {
+                table_name: table_name.to_string(),
+                documents_read: stats.rows_read as i64,
+            })
+            .collect();
+        top_tables.sort_by(|a, b| b.documents_read.cmp(&a.documents_read));
+        top_tables.truncate(MAX_TOP_TABLES);
+
+        Some(ReadSetAdvice {
+            component_path,
+            udf_path,
+            executions: stats.executions,
+            average_documents_read: stats.average_documents_read.round() as i64,
+            average_bytes_read: stats.average_bytes_read.round() as i64,
+            max_documents_read: stats.max_documents_read as i64,
+            max_bytes_read: stats.max_bytes_read as i64,
+            documents_limit: *TRANSACTION_MAX_READ_SIZE_ROWS as i64,
+            bytes_limit: *TRANSACTION_MAX_READ_SIZE_BYTES as i64,
+            first_warned_ms,
+            last_warned_ms: now_ms,
+            top_tables,
+        })
+    }
+}
+
+/// Writes advice from the `ReadSetAdvisor` to `_read_set_advice` in batches.
+pub struct ReadSetAdviceWriter;
+
+impl ReadSetAdviceWriter {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new<RT: Runtime>(
+        runtime: RT,
+        database: Database<RT>,
+    ) -> (mpsc::Sender<ReadSetAdvice>, impl Future<Output = ()> + Send) {
+        batched_writer(
+            runtime,
+            "ReadSetAdviceWriter",
+            "read set advice records",
+            BUFFER_SIZE,
+            MAX_BATCH_SIZE,
+            move |batch| Self::write_batch(database.clone(), batch),
+        )
+    }
+
+    async fn write_batch<RT: Runtime>(
+        database: Database<RT>,
+        batch: Vec<ReadSetAdvice>,
+    ) -> anyhow::Result<()> {
+        let mut tx = database.begin(Identity::system()).await?;
+        let mut model = ReadSetAdviceModel::new(&mut tx);
+        for advice in batch {
+            model.record(advice).await?;
+        }
+        database
+            .commit_with_write_source(tx, "read_set_advice_writer")
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use common::{
+        components::ComponentPath,
+        knobs::TRANSACTION_MAX_READ_SIZE_ROWS,
+        types::TableStats,
+    };
+
+    use super::ReadSetAdvisor;
+
+    fn reads(table_rows: &[(&str, u64)]) -> BTreeMap<common::types::TableName, TableStats> {
+        table_rows
+            .iter()
+            .map(|(table, rows_read)| {
+                (
+                    table.parse().unwrap(),
+                    TableStats {
+                        rows_read: *rows_read,
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_read_set_advisor_warns_on_trend() {
+        let mut advisor = ReadSetAdvisor::default();
+        let limit = *TRANSACTION_MAX_READ_SIZE_ROWS as u64;
+        let small = reads(&[("messages", 10)]);
+        let large = reads(&[("messages", limit * 9 / 10), ("users", 5), ("_storage", 50)]);
+        let mut record = |tables, now_ms| {
+            advisor.record(
+                ComponentPath::root(),
+                "messages:list".to_string(),
+                tables,
+                0,
+                now_ms,
+            )
+        };
+
+        // A single large execution doesn't move the average past the threshold.
+        assert!(record(&small, 0).is_none());
+        assert!(record(&large, 1).is_none());
+
+        // Repeated large executions do, and the warning is rate limited.
+        let advice = (2..30)
+            .find_map(|now_ms| record(&large, now_ms))
+            .expect("expected a warning");
+        assert_eq!(advice.first_warned_ms, advice.last_warned_ms);
+        assert_eq!(advice.max_documents_read, (limit * 9 / 10 + 5) as i64);
+        let top_tables: Vec<_> = advice
+            .top_tables
+            .iter()
+            .map(|t| t.table_name.as_str())
+            .collect();
+        assert_eq!(top_tables, vec!["messages", "users"]);
+        assert!(record(&large, 100).is_none());
+    }
+}
@@ -68,6 +68,7 @@ use errors::{
     ErrorMetadataAnyhowExt,
 };
 use fastrace::future::FutureExt as _;
+use file_storage::TransactionalFileStorage;
 use futures::{
     future::Either,
     select_biased,
@@ -84,6 +85,7 @@ use model::{
         types::{
             ScheduledJob,
             ScheduledJobState,
+            StoredScheduledJobArgs,
         },
         SchedulerModel,
         COMPLETED_TS_FIELD,
@@ -98,7 +100,11 @@ use sentry::SentryFutureExt;
 use sync_types::Timestamp;
 use tokio::sync::mpsc;
 use usage_tracking::FunctionUsageTracker;
-use value::ResolvedDocumentId;
+use value::{
+    ConvexArray,
+    ResolvedDocumentId,
+    TableNamespace,
+};
 
 use crate::{
     application_function_runner::ApplicationFunctionRunner,
@@ -110,10 +116,14 @@ mod metrics;
 pub(crate) const SCHEDULED_JOB_EXECUTED: &str = "scheduled_job_executed";
 pub(crate) const SCHEDULED_JOB_COMMITTING: &str = "scheduled_job_committing";
 
+/// How many jobs' arguments to move to file storage per pass.
+const ARGS_UPLOAD_BATCH_SIZE: usize = 16;
+
 #[derive(Clone)]
 pub struct ScheduledJobRunner {
     executor: Arc<Mutex<Box<dyn SpawnHandle>>>,
     garbage_collector: Arc<Mutex<Box<dyn SpawnHandle>>>,
+    args_uploader: Arc<Mutex<Box<dyn SpawnHandle>>>,
 }
 
 impl ScheduledJobRunner {
@@ -123,6 +133,7 @@ impl ScheduledJobRunner {
         database: Database<RT>,
         runner: Arc<ApplicationFunctionRunner<RT>>,
         function_log: FunctionExecutionLog<RT>,
+        file_storage: TransactionalFileStorage<RT>,
     ) -> Self {
         let executor_fut = ScheduledJobExecutor::run(
             rt.clone(),
@@ -130,22 +141,31 @@ impl ScheduledJobRunner {
             database.clone(),
             runner,
             function_log,
+            file_storage.clone(),
         );
         let executor = Arc::new(Mutex::new(rt.spawn("scheduled_job_executor", executor_fut)));
 
-        let garbage_collector_fut = ScheduledJobGarbageCollector::start(rt.clone(), database);
+        let garbage_collector_fut =
+            ScheduledJobGarbageCollector::start(rt.clone(), database.clone(), file_storage.clone());
         let garbage_collector = Arc::new(Mutex::new(
             rt.spawn("scheduled_job_garbage_collector", garbage_collector_fut),
         ));
+
+        let args_uploader_fut = ScheduledJobArgsUploader::start(rt.clone(), database, file_storage);
+        let args_uploader = Arc::new(Mutex::new(
+            rt.spawn("scheduled_job_args_uploader", args_uploader_fut),
+        ));
         Self {
             executor,
             garbage_collector,
+            args_uploader,
         }
     }
 
     pub fn shutdown(&self) {
         self.executor.lock().shutdown();
         self.garbage_collector.lock().shutdown();
+        self.args_uploader.lock().shutdown();
     }
 }
 
@@ -167,6 +187,7 @@ pub struct ScheduledJobContext<RT: Runtime> {
     database: Database<RT>,
     runner: Arc<ApplicationFunctionRunner<RT>>,
     function_log: FunctionExecutionLog<RT>,
+    file_storage: TransactionalFileStorage<RT>,
 }
 
 impl<RT: Runtime> ScheduledJobContext<RT> {
@@ -176,12 +197,14 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
         database: Database<RT>,
         runner: Arc<ApplicationFunctionRunner<RT>>,
         function_log: FunctionExecutionLog<RT>,
+        file_storage: TransactionalFileStorage<RT>,
     ) -> Self {
         ScheduledJobContext {
             rt,
             database,
             runner,
             function_log,
+            file_storage,
         }
     }
 }
@@ -193,6 +216,7 @@ impl<RT: Runtime> ScheduledJobExecutor<RT> {
         database: Database<RT>,
         runner: Arc<ApplicationFunctionRunner<RT>>,
         function_log: FunctionExecutionLog<RT>,
+        file_storage: TransactionalFileStorage<RT>,
     ) {
         let (job_finished_tx, job_finished_rx) =
             mpsc::channel(*SCHEDULED_JOB_EXECUTION_PARALLELISM);
@@ -202,6 +226,7 @@ impl<RT: Runtime> ScheduledJobExecutor<RT> {
                 database,
                 runner,
                 function_log,
+                file_storage,
             },
             instance_name,
             running_job_ids: HashSet::new(),
@@ -260,7 +285,7 @@ impl<RT: Runtime> ScheduledJobExecutor<RT> {
                 // track how far we're behind in our metrics.
                 Duration::from_secs(5)
             });
-            Either::Left(self.context.rt.wait(wait_time))
+            Either::Left(self.context.rt.wait_for_system_time(wait_time))
         } else {
             Either::Right(std::future::pending())
         };
@@ -514,6 +539,7 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
             job_id: job_id.into(),
             component_id,
         };
+        let udf_args = self.file_storage.scheduled_job_args(&job).await?;
         let path = job.path.clone();
         let udf_type = match ModuleModel::new(&mut tx)
             .get_analyzed_function(&path)
@@ -540,7 +566,7 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
                     .log_mutation_system_error(
                         &error,
                         path,
-                        job.udf_args()?,
+                        udf_args,
                         identity,
                         self.rt.monotonic_now(),
                         caller,
@@ -557,11 +583,19 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
         // scheduling, but the modules can have been modified since scheduling.
         match udf_type {
             UdfType::Mutation => {
-                self.handle_mutation(caller, tx, job, job_id, usage_tracker, mutation_retry_count)
-                    .await?
+                self.handle_mutation(
+                    caller,
+                    tx,
+                    job,
+                    udf_args,
+                    job_id,
+                    usage_tracker,
+                    mutation_retry_count,
+                )
+                .await?
             },
             UdfType::Action => {
-                self.handle_action(caller, tx, job, job_id, usage_tracker)
+                self.handle_action(caller, tx, job, udf_args, job_id, usage_tracker)
                     .await?
             },
             udf_type => {
@@ -594,7 +628,7 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
                                 )
                                 .into(),
                                 path,
-                                job.udf_args()?,
+                                udf_args,
                                 identity,
                                 self.rt.monotonic_now(),
                                 caller,
@@ -614,7 +648,7 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
                                 )
                                 .into(),
                                 path,
-                                job.udf_args()?,
+                                udf_args,
                                 identity,
                                 self.rt.monotonic_now(),
                                 caller,
@@ -638,6 +672,7 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
         caller: FunctionCaller,
         mut tx: Transaction<RT>,
         job: ScheduledJob,
+        udf_args: ConvexArray,
         job_id: ResolvedDocumentId,
         usage_tracker: FunctionUsageTracker,
         mutation_retry_count: usize,
@@ -651,7 +686,6 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
         let path = job.path.clone();
         let pause_client = self.rt.pause_client();
 
-        let udf_args = job.udf_args()?;
         let result = self
             .runner
             .run_mutation_no_udf_log(
@@ -750,6 +784,7 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
         caller: FunctionCaller,
         tx: Transaction<RT>,
         job: ScheduledJob,
+        udf_args: ConvexArray,
         job_id: ResolvedDocumentId,
         usage_tracker: FunctionUsageTracker,
     ) -> anyhow::Result<()> {
@@ -782,7 +817,7 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
                     .runner
                     .run_action_no_udf_log(
                         PublicFunctionPath::Component(path),
-                        job.udf_args()?,
+                        udf_args,
                         identity,
                         caller,
                         usage_tracker.clone(),
@@ -841,7 +876,7 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
                     .log_action_system_error(
                         &err,
                         path,
-                        job.udf_args()?,
+                        udf_args,
                         identity.into(),
                         self.rt.monotonic_now(),
                         caller,
@@ -914,11 +949,20 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
 pub struct ScheduledJobGarbageCollector<RT: Runtime> {
     rt: RT,
     database: Database<RT>,
+    file_storage: TransactionalFileStorage<RT>,
 }
 
 impl<RT: Runtime> ScheduledJobGarbageCollector<RT> {
-    pub fn start(rt: RT, database: Database<RT>) -> impl Future<Output = ()> + Send {
-        let garbage_collector = Self { rt, database };
+    pub fn start(
+        rt: RT,
+        database: Database<RT>,
+        file_storage: TransactionalFileStorage<RT>,
+    ) -> impl Future<Output = ()> + Send {
+        let garbage_collector = Self {
+            rt,
+            database,
+            file_storage,
+        };
         async move {
             let mut backoff = Backoff::new(
                 *SCHEDULED_JOB_GARBAGE_COLLECTION_INITIAL_BACKOFF,
@@ -943,6 +987,7 @@ impl<RT: Runtime> ScheduledJobGarbageCollector<RT> {
                 .table_mapping()
                 .namespaces_for_name(&SCHEDULED_JOBS_TABLE);
             let mut deleted_jobs = false;
+            let mut stored_args_to_delete = vec![];
             let mut next_job_wait = None;
             for namespace in namespaces {
                 let now = self.rt.generate_timestamp()?;
@@ -984,6 +1029,7 @@ impl<RT: Runtime> ScheduledJobGarbageCollector<RT> {
                         break;
                     }
                     jobs_to_delete.push(job.id());
+                    stored_args_to_delete.extend(job.into_value().stored_args);
                 }
                 if !jobs_to_delete.is_empty() {
                     tracing::debug!(
@@ -1001,10 +1047,24 @@ impl<RT: Runtime> ScheduledJobGarbageCollector<RT> {
                 self.database
                     .commit_with_write_source(tx, "scheduled_job_gc")
                     .await?;
+                // The jobs are gone, so nothing references their arguments
+                // anymore. Failing to delete them only leaks storage.
+                for stored_args in stored_args_to_delete {
+                    if let Err(e) = self
+                        .file_storage
+                        .delete_scheduled_job_args(&stored_args)
+                        .await
+                    {
+                        tracing::warn!(
+                            "Failed to delete scheduled job arguments {:?}: {e:#}",
+                            stored_args.storage_key
+                        );
+                    }
+                }
                 self.rt.wait(*SCHEDULED_JOB_GARBAGE_COLLECTION_DELAY).await;
             } else {
                 let next_job_future = if let Some(next_job_wait) = next_job_wait {
-                    Either::Left(self.rt.wait(next_job_wait))
+                    Either::Left(self.rt.wait_for_system_time(next_job_wait))
                 } else {
                     Either::Right(std::future::pending())
                 };
@@ -1019,3 +1079,164 @@ impl<RT: Runtime> ScheduledJobGarbageCollector<RT> {
         }
     }
 }
+
+/// Moves large scheduled function arguments to file storage once the
+/// transactions that scheduled them have committed. Uploading from the
+/// scheduling transaction instead would leave an object behind whenever that
+/// transaction failed or was retried.
+pub struct ScheduledJobArgsUploader<RT: Runtime> {
+    rt: RT,
+    database: Database<RT>,
+    file_storage: TransactionalFileStorage<RT>,
+}
+
+impl<RT: Runtime> ScheduledJobArgsUploader<RT> {
+    pub fn start(
+        rt: RT,
+        database: Database<RT>,
+        file_storage: TransactionalFileStorage<RT>,
+    ) -> impl Future<Output = ()> + Send {
+        let uploader = Self {
+            rt,
+            database,
+            file_storage,
+        };
+        async move {
+            let mut backoff = Backoff::new(
+                *SCHEDULED_JOB_GARBAGE_COLLECTION_INITIAL_BACKOFF,
+                *SCHEDULED_JOB_GARBAGE_COLLECTION_MAX_BACKOFF,
+            );
+            while let Err(mut e) = uploader.run(&mut backoff).await {
+                let delay = backoff.fail(&mut uploader.rt.rng());
+                tracing::error!("Scheduled job args uploader failed, sleeping {delay:?}");
+                if !e.is_occ() || (backoff.failures() as usize) > *UDF_EXECUTOR_OCC_MAX_RETRIES {
+                    report_error(&mut e).await;
+                }
+                uploader.rt.wait(delay).await;
+            }
+        }
+    }
+
+    async fn run(&self, backoff: &mut Backoff) -> anyhow::Result<()> {
+        loop {
+            let mut tx = self.database.begin(Identity::system()).await?;
+            let namespaces = tx
+                .table_mapping()
+                .namespaces_for_name(&SCHEDULED_JOBS_TABLE);
+            let mut pending_jobs = vec![];
+            for namespace in namespaces {
+                let jobs = SchedulerModel::new(&mut tx, namespace)
+                    .list_args_pending_storage(ARGS_UPLOAD_BATCH_SIZE)
+                    .await?;
+                pending_jobs.extend(jobs.into_iter().map(|job| (namespace, job)));
+            }
+            if pending_jobs.is_empty() {
+                let token = tx.into_token()?;
+                let subscription = self.database.subscribe(token).await?;
+                subscription.wait_for_invalidation().await;
+            } else {
+                for (namespace, job) in pending_jobs {
+                    self.upload_args(namespace, job).await?;
+                }
+            }
+            backoff.reset();
+        }
+    }
+
+    async fn upload_args(
+        &self,
+        namespace: TableNamespace,
+        job: ParsedDocument<ScheduledJob>,
+    ) -> anyhow::Result<()> {
+        let (job_id, job) = job.into_id_and_value();
+        // Jobs that have started running already have their arguments loaded,
+        // so they're only unflagged.
+        let stored = match job.state {
+            ScheduledJobState::Pending => {
+                Some(self.file_storage.upload_scheduled_job_args(&job).await?)
+            },
+            _ => None,
+        };
+        let result = self
+            .record_stored_args(namespace, job_id, &job, stored.clone())
+            .await;
+        if let Some(stored) = stored
+            && !matches!(result, Ok(true))
+        {
+            self.delete_unreferenced_args(job_id, &stored).await;
+        }
+        result.map(|_| ())
+    }
+
+    /// Replaces the job's arguments with `stored`, or only unflags the job if
+    /// `stored` is `None`. Returns whether `stored` was recorded, which it
+    /// isn't if the job changed since it was read.
+    async fn record_stored_args(
+        &self,
+        namespace: TableNamespace,
+        job_id: ResolvedDocumentId,
+        job: &ScheduledJob,
+        stored: Option<StoredScheduledJobArgs>,
+    ) -> anyhow::Result<bool> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let Some(current) = tx.get(job_id).await? else {
+            return Ok(false);
+        };
+        let current: ParsedDocument<ScheduledJob> = current.parse()?;
+        let mut current = current.into_value();
+        // The next pass picks up jobs that are still waiting.
+        if current != *job {
+            return Ok(false);
+        }
+        let recorded = match stored {
+            Some(stored) => {
+                current.store_args(stored)?;
+                true
+            },
+            None => {
+                current.args_pending_storage = false;
+                false
+            },
+        };
+        SchedulerModel::new(&mut tx, namespace)
+            .replace(job_id, current)
+            .await?;
+        self.database
+            .commit_with_write_source(tx, "scheduled_job_args_upload")
+            .await?;
+        Ok(recorded)
+    }
+
+    /// Deletes an upload that wasn't recorded in its job. A failed commit may
+    /// still have been applied, so the job is checked first, and the object
+    /// is kept if that check fails.
+    async fn delete_unreferenced_args(
+        &self,
+        job_id: ResolvedDocumentId,
+        stored: &StoredScheduledJobArgs,
+    ) {
+        let referenced = async {
+            let mut tx = self.database.begin(Identity::system()).await?;
+            let Some(job) = tx.get(job_id).await? else {
+                return anyhow::Ok(false);
+            };
+            let job: ParsedDocument<ScheduledJob> = job.parse()?;
+            anyhow::Ok(job.stored_args.as_ref() == Some(stored))
+        };
+        match referenced.await {
+            Ok(false) => {
+                if let Err(e) = self.file_storage.delete_scheduled_job_args(stored).await {
+                    tracing::warn!(
+                        "Failed to delete scheduled job arguments {:?}: {e:#}",
+                        stored.storage_key
+                    );
+                }
+            },
+            Ok(true) => {},
+            Err(e) => tracing::warn!(
+                "Failed to check whether scheduled job arguments {:?} are used: {e:#}",
+                stored.storage_key
+            ),
+        }
+    }
+}
@@ -11,7 +11,11 @@ use common::{
     errors::report_error,
     persistence::LatestDocument,
     runtime::Runtime,
-    schemas::DatabaseSchema,
+    schemas::{
+        violations::SchemaViolationReport,
+        DatabaseSchema,
+        SchemaValidationError,
+    },
     types::{
         IndexId,
         RepeatableTimestamp,
@@ -161,6 +165,10 @@ impl<RT: Runtime> SchemaWorker<RT> {
                 },
             )?;
 
+            // Keep scanning after the first invalid document so the failure
+            // reports every violating document, not just the first.
+            let mut first_error = None;
+            let mut report = SchemaViolationReport::default();
             for table_name in tables_to_check {
                 let table_iterator = self.database.table_iterator(ts, 1000);
                 let tablet_id = table_mapping.name_to_tablet()(table_name.clone())?;
@@ -183,38 +191,48 @@ impl<RT: Runtime> SchemaWorker<RT> {
                         &table_mapping,
                         &virtual_system_mapping,
                     ) {
-                        let mut backoff = Backoff::new(INITIAL_COMMIT_BACKOFF, MAX_COMMIT_BACKOFF);
-                        while backoff.failures() < MAX_COMMIT_FAILURES {
-                            let mut tx = self.database.begin(Identity::system()).await?;
-                            SchemaModel::new(&mut tx, namespace)
-                                .mark_failed(id, schema_error.clone())
-                                .await?;
-                            if let Err(e) = self
-                                .database
-                                .commit_with_write_source(tx, "schema_worker_mark_failed")
-                                .await
-                            {
-                                if e.is_occ() {
-                                    let delay = backoff.fail(&mut self.runtime.rng());
-                                    tracing::error!(
-                                        "Schema worker failed to commit ({e}), retrying after \
-                                         {delay:?}"
-                                    );
-                                    self.runtime.wait(delay).await;
-                                } else {
-                                    return Err(e);
-                                }
-                            } else {
-                                break;
-                            }
+                        if let SchemaValidationError::ExistingDocument {
+                            validation_error,
+                            table_name,
+                            id,
+                        } = &schema_error
+                        {
+                            report.record(table_name, *id, validation_error);
                         }
-
-                        tracing::info!("Schema is invalid");
-                        timer.finish_developer_error();
-                        return Ok(());
+                        first_error.get_or_insert(schema_error);
                     }
                 }
             }
+            if let Some(schema_error) = first_error {
+                let mut backoff = Backoff::new(INITIAL_COMMIT_BACKOFF, MAX_COMMIT_BACKOFF);
+                while backoff.failures() < MAX_COMMIT_FAILURES {
+                    let mut tx = self.database.begin(Identity::system()).await?;
+                    SchemaModel::new(&mut tx, namespace)
+                        .mark_failed_with_report(id, schema_error.clone(), Some(report.clone()))
+                        .await?;
+                    if let Err(e) = self
+                        .database
+                        .commit_with_write_source(tx, "schema_worker_mark_failed")
+                        .await
+                    {
+                        if e.is_occ() {
+                            let delay = backoff.fail(&mut self.runtime.rng());
+                            tracing::error!(
+                                "Schema worker failed to commit ({e}), retrying after {delay:?}"
+                            );
+                            self.runtime.wait(delay).await;
+                        } else {
+                            return Err(e);
+                        }
+                    } else {
+                        break;
+                    }
+                }
+
+                tracing::info!("Schema is invalid");
+                timer.finish_developer_error();
+                return Ok(());
+            }
             let mut tx = self.database.begin(Identity::system()).await?;
             if let Err(error) = SchemaModel::new(&mut tx, namespace)
                 .mark_validated(id)
@@ -269,6 +287,7 @@ mod tests {
     };
     use keybroker::Identity;
     use maplit::btreemap;
+    use must_let::must_let;
     use runtime::testing::TestRuntime;
     use value::TableName;
 
@@ -289,6 +308,9 @@ mod tests {
             search_indexes: btreemap! {},
             vector_indexes: btreemap! {},
             document_type: Some(DocumentSchema::Any),
+            defaults: Default::default(),
+            mutability: Default::default(),
+            checks: vec![],
         };
         let db_schema = DatabaseSchema {
             tables: btreemap! { table_name.clone() => table_definition },
@@ -297,10 +319,13 @@ mod tests {
         let (id, _) = SchemaModel::new_root_for_test(&mut tx)
             .submit_pending(db_schema)
             .await?;
-        // Insert a document that matches the schema
+        // Insert documents that match the schema
         UserFacingModel::new_root_for_test(&mut tx)
             .insert(table_name.clone(), assert_obj!())
             .await?;
+        UserFacingModel::new_root_for_test(&mut tx)
+            .insert(table_name.clone(), assert_obj!("field" => "string"))
+            .await?;
         db.commit(tx).await?;
 
         // Check that the schema passes and is active
@@ -328,7 +353,10 @@ mod tests {
         assert_eq!(schema.state, SchemaState::Overwritten);
         let doc = tx.get(bad_schema_id).await?.unwrap();
         let schema: SchemaMetadata = doc.into_value().into_value().try_into()?;
-        assert!(matches!(schema.state, SchemaState::Failed { .. }));
+        must_let!(let SchemaState::Failed { report: Some(report), .. } = schema.state);
+        let violations = &report.tables[&table_name.to_string()];
+        assert_eq!(violations.count, 2);
+        assert_eq!(violations.sample_ids.len(), 2);
         Ok(())
     }
 }
@@ -119,8 +119,11 @@ async fn messages_to_confirm_replace<RT: Runtime>(
                     tables_missing_id_field.insert(current_component_table.clone());
                 }
             },
-            // Ignore storage file chunks and generated schemas.
-            ImportUnit::StorageFileChunk(..) | ImportUnit::GeneratedSchema(..) => {},
+            // Ignore storage file chunks, generated schemas, and search indexes.
+            ImportUnit::StorageFileChunk(..)
+            | ImportUnit::GeneratedSchema(..)
+            | ImportUnit::SearchIndex(..)
+            | ImportUnit::SearchIndexFileChunk(..) => {},
         }
     }
 
@@ -236,6 +239,7 @@ async fn messages_to_confirm_replace<RT: Runtime>(
             existing_rows_to_delete: *deleted as i64,
             existing_rows_in_table: *existing as i64,
             is_missing_id_field: *is_missing_id_field,
+            last_imported_id: None,
         });
     }
     let mut message_lines = Vec::new();
@@ -0,0 +1,84 @@
+//! Decrypting imports of encrypted export archives. See
+//! `keybroker::ArchiveDecryptor` for the format.
+
+use std::io;
+
+use bytes::{
+    Bytes,
+    BytesMut,
+};
+use futures::{
+    stream,
+    Stream,
+    StreamExt,
+};
+use futures_async_stream::try_stream;
+use keybroker::{
+    ArchiveDecryptor,
+    ArchiveKey,
+    ARCHIVE_MAGIC,
+};
+use storage::StorageGetStream;
+
+use crate::snapshot_import::import_error::ImportError;
+
+/// Transparently decrypts `reader` if it's an encrypted archive, and passes it
+/// through unchanged otherwise.
+pub(crate) async fn decrypt_import(
+    reader: StorageGetStream,
+    key: Option<&ArchiveKey>,
+) -> anyhow::Result<StorageGetStream> {
+    let StorageGetStream {
+        content_length,
+        mut stream,
+    } = reader;
+    let mut prefix = BytesMut::new();
+    while prefix.len() < ARCHIVE_MAGIC.len() {
+        match stream.next().await {
+            Some(chunk) => prefix.extend_from_slice(&chunk?),
+            None => break,
+        }
+    }
+    let prefix = prefix.freeze();
+    if !ArchiveDecryptor::is_encrypted(&prefix) {
+        return Ok(StorageGetStream {
+            content_length,
+            stream: stream::once(async move { Ok(prefix) })
+                .chain(stream)
+                .boxed(),
+        });
+    }
+    let Some(key) = key else {
+        anyhow::bail!(ImportError::EncryptedArchive);
+    };
+    let plaintext_len = ArchiveDecryptor::plaintext_len(content_length as u64)
+        .map_err(|e| ImportError::ArchiveDecryption(e.to_string()))?;
+    let sealed = stream::once(async move { Ok(prefix) }).chain(stream);
+    Ok(StorageGetStream {
+        content_length: plaintext_len as i64,
+        stream: decrypt_stream(ArchiveDecryptor::new(key), sealed).boxed(),
+    })
+}
+
+/// Decryption failures are reported as I/O errors wrapping an [`ImportError`]
+/// since that's all a `StorageGetStream` can carry.
+#[try_stream(ok = Bytes, error = io::Error)]
+async fn decrypt_stream(
+    mut decryptor: ArchiveDecryptor,
+    sealed: impl Stream<Item = io::Result<Bytes>> + Send + 'static,
+) {
+    let invalid = |e: anyhow::Error| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            ImportError::ArchiveDecryption(e.to_string()),
+        )
+    };
+    #[for_await]
+    for chunk in sealed {
+        let plaintext = decryptor.update(&chunk?).map_err(invalid)?;
+        if !plaintext.is_empty() {
+            yield Bytes::from(plaintext);
+        }
+    }
+    yield Bytes::from(decryptor.finish().map_err(invalid)?);
+}
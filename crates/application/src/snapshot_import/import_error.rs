@@ -63,6 +63,23 @@ pub enum ImportError {
 
     #[error("Not valid JSON: {0}")]
     NotJson(serde_json::Error),
+
+    #[error(
+        "This import is an encrypted archive. Start the backend with --export-encryption-key set \
+         to the key it was exported with."
+    )]
+    EncryptedArchive,
+
+    #[error("Failed to decrypt the import: {0}")]
+    ArchiveDecryption(String),
+
+    #[error(
+        "{0} doesn't match the checksum in the archive's manifest. The archive may be corrupted."
+    )]
+    ChecksumMismatch(String),
+
+    #[error("{0} is listed in the archive's manifest but isn't in the archive")]
+    ManifestFileMissing(String),
 }
 
 impl ImportError {
@@ -0,0 +1,365 @@
+//! Reuses the text and vector index segments included in cloud backups, so
+//! restoring a large deployment doesn't rebuild its search indexes from
+//! scratch. Indexes that can't be reused are rebuilt as usual.
+
+use std::{
+    collections::BTreeMap,
+    pin::Pin,
+};
+
+use anyhow::Context;
+use common::{
+    bootstrap_model::index::{
+        text_index::{
+            TextIndexSnapshot,
+            TextIndexState,
+            TextSnapshotVersion,
+        },
+        vector_index::{
+            VectorIndexSnapshot,
+            VectorIndexSnapshotData,
+            VectorIndexState,
+        },
+        IndexConfig,
+        IndexMetadata,
+        TabletIndexMetadata,
+        INDEX_TABLE,
+    },
+    document::ParsedDocument,
+    ext::PeekableExt,
+    runtime::Runtime,
+    types::{
+        IndexDescriptor,
+        ObjectKey,
+        TabletIndexName,
+        Timestamp,
+    },
+};
+use database::{
+    Database,
+    IndexModel,
+    SystemMetadataModel,
+};
+use futures::{
+    pin_mut,
+    stream::{
+        BoxStream,
+        Peekable,
+    },
+    TryStreamExt,
+};
+use keybroker::Identity;
+use usage_tracking::FunctionUsageTracker;
+use value::{
+    ConvexObject,
+    TableName,
+    TableNamespace,
+    TableNumber,
+    TabletId,
+    TabletIdAndTableNumber,
+};
+
+use crate::{
+    exports::{
+        map_segment_keys,
+        SearchIndexZipMetadata,
+        SEARCH_INDEX_EXPORT_VERSION,
+    },
+    snapshot_import::parse::ImportUnit,
+};
+
+/// A search index from the import whose segments have been uploaded to this
+/// deployment's search storage.
+#[derive(Clone, Debug)]
+pub struct ImportedSearchIndex {
+    pub descriptor: IndexDescriptor,
+    /// The table number the segments' document IDs refer to.
+    pub table_number: TableNumber,
+    /// The index's snapshotted config, pointing at the uploaded segments.
+    pub config: IndexConfig,
+}
+
+impl ImportedSearchIndex {
+    /// Whether these segments can stand in for `index` on a table imported
+    /// with `table_number`.
+    pub fn matches(&self, index: &TabletIndexMetadata, table_number: TableNumber) -> bool {
+        self.table_number == table_number
+            && *index.name.descriptor() == self.descriptor
+            && index.config.same_config(&self.config)
+    }
+}
+
+/// Uploads the files of a search index from the import, returning `None` if
+/// the exported index can't be used by this deployment.
+pub async fn import_search_index<RT: Runtime>(
+    database: &Database<RT>,
+    metadata: SearchIndexZipMetadata,
+    mut objects: Pin<&mut Peekable<BoxStream<'_, anyhow::Result<ImportUnit>>>>,
+) -> anyhow::Result<Option<ImportedSearchIndex>> {
+    let index = parse_metadata(database, metadata)?;
+    let search_storage = database.search_storage();
+    let mut uploaded: BTreeMap<String, ObjectKey> = BTreeMap::new();
+    // Always consume the index's files, even if they won't be used.
+    while let Some(Ok(ImportUnit::SearchIndexFileChunk(file_name, _))) =
+        objects.as_mut().peek().await
+    {
+        let file_name = file_name.clone();
+        let predicate_file_name = file_name.clone();
+        let chunks = objects
+            .as_mut()
+            .peeking_take_while(move |unit| match unit {
+                Ok(ImportUnit::SearchIndexFileChunk(chunk_file_name, _)) => {
+                    *chunk_file_name == predicate_file_name
+                },
+                Err(_) => true,
+                Ok(_) => false,
+            })
+            .try_filter_map(|unit| async move {
+                match unit {
+                    ImportUnit::SearchIndexFileChunk(_, chunk) => Ok(Some(chunk)),
+                    _ => Ok(None),
+                }
+            });
+        pin_mut!(chunks);
+        if index.is_none() {
+            while chunks.try_next().await?.is_some() {}
+            continue;
+        }
+        let mut upload = search_storage.start_upload().await?;
+        while let Some(chunk) = chunks.try_next().await? {
+            if !chunk.is_empty() {
+                upload.write(chunk).await?;
+            }
+        }
+        uploaded.insert(file_name, upload.complete().await?);
+    }
+    let Some(index) = index else {
+        return Ok(None);
+    };
+    let config = map_segment_keys(index.config, |file_name| {
+        let file_name = String::from(file_name);
+        uploaded
+            .get(&file_name)
+            .cloned()
+            .with_context(|| format!("missing segment file {file_name}"))
+    });
+    match config {
+        Ok(config) => Ok(Some(ImportedSearchIndex { config, ..index })),
+        Err(e) => {
+            tracing::warn!("Rebuilding search index {}: {e:#}", index.descriptor);
+            Ok(None)
+        },
+    }
+}
+
+/// Checks that an exported index was written in a format this deployment can
+/// read, so its segments can be reused.
+fn parse_metadata<RT: Runtime>(
+    database: &Database<RT>,
+    metadata: SearchIndexZipMetadata,
+) -> anyhow::Result<Option<ImportedSearchIndex>> {
+    if metadata.format_version != SEARCH_INDEX_EXPORT_VERSION {
+        tracing::info!(
+            "Search index {} was exported with format version {}, rebuilding it",
+            metadata.index_name,
+            metadata.format_version
+        );
+        return Ok(None);
+    }
+    let descriptor = IndexDescriptor::new(metadata.index_name)?;
+    let table_number = TableNumber::try_from(metadata.table_number)?;
+    let config = IndexConfig::try_from(ConvexObject::try_from(metadata.index_config)?)?;
+    let usable = match &config {
+        IndexConfig::Text {
+            on_disk_state: TextIndexState::SnapshottedAt(TextIndexSnapshot { version, .. }),
+            ..
+        } => *version == TextSnapshotVersion::new(database.persistence_version()),
+        IndexConfig::Vector {
+            on_disk_state:
+                VectorIndexState::SnapshottedAt(VectorIndexSnapshot {
+                    data: VectorIndexSnapshotData::MultiSegment(_),
+                    ..
+                }),
+            ..
+        } => true,
+        _ => false,
+    };
+    if !usable {
+        tracing::info!("Search index {descriptor} has an incompatible snapshot, rebuilding it");
+        return Ok(None);
+    }
+    Ok(Some(ImportedSearchIndex {
+        descriptor,
+        table_number,
+        config,
+    }))
+}
+
+/// Whether a newly created table's copy of `index` should be left out until
+/// the table's documents are imported, to be installed from `imported`
+/// instead of being built as documents are inserted.
+pub fn replaced_by_import(
+    index: &ParsedDocument<TabletIndexMetadata>,
+    table_number: TableNumber,
+    imported: &[ImportedSearchIndex],
+) -> bool {
+    matches!(
+        index.config,
+        IndexConfig::Text { .. } | IndexConfig::Vector { .. }
+    ) && imported
+        .iter()
+        .any(|imported| imported.matches(index, table_number))
+}
+
+/// Installs imported search indexes on a hidden table that the import has
+/// finished writing, for each index the table's active counterpart has. The
+/// indexes are created backfilling and then marked backfilled with the
+/// imported segments, as the search flusher would after building them.
+/// Returns whether any indexes were installed.
+pub async fn install_imported_search_indexes<RT: Runtime>(
+    database: &Database<RT>,
+    identity: &Identity,
+    namespace: TableNamespace,
+    table_name: &TableName,
+    table_id: TabletIdAndTableNumber,
+    imported: &[ImportedSearchIndex],
+) -> anyhow::Result<bool> {
+    let (_, installed, _) = database
+        .execute_with_overloaded_retries(
+            identity.clone(),
+            FunctionUsageTracker::new(),
+            "snapshot_import_create_imported_search_indexes",
+            |tx| {
+                async {
+                    if tx.table_mapping().is_active(table_id.tablet_id) {
+                        return Ok(vec![]);
+                    }
+                    let Some(active_tablet_id) = tx
+                        .table_mapping()
+                        .namespace(namespace)
+                        .id_if_exists(table_name)
+                    else {
+                        return Ok(vec![]);
+                    };
+                    let mut index_model = IndexModel::new(tx);
+                    let active_indexes = index_model.all_indexes_on_table(active_tablet_id).await?;
+                    let existing: Vec<_> = index_model
+                        .all_indexes_on_table(table_id.tablet_id)
+                        .await?
+                        .into_iter()
+                        .map(|index| index.name.descriptor().clone())
+                        .collect();
+                    let mut installed = vec![];
+                    for imported in imported {
+                        if existing.contains(&imported.descriptor)
+                            || !active_indexes.iter().any(|index| {
+                                index.config.is_enabled()
+                                    && imported.matches(index, table_id.table_number)
+                            })
+                        {
+                            continue;
+                        }
+                        let name = tablet_index_name(table_id.tablet_id, &imported.descriptor)?;
+                        let metadata = match &imported.config {
+                            IndexConfig::Text {
+                                developer_config, ..
+                            } => IndexMetadata::new_backfilling_text_index(
+                                name,
+                                developer_config.clone(),
+                            ),
+                            IndexConfig::Vector {
+                                developer_config, ..
+                            } => IndexMetadata::new_backfilling_vector_index(
+                                name,
+                                developer_config.clone(),
+                            ),
+                            IndexConfig::Database { .. } => continue,
+                        };
+                        SystemMetadataModel::new_global(tx)
+                            .insert_metadata(&INDEX_TABLE, metadata.try_into()?)
+                            .await?;
+                        installed.push(imported.clone());
+                    }
+                    Ok(installed)
+                }
+                .into()
+            },
+        )
+        .await?;
+    if installed.is_empty() {
+        return Ok(false);
+    }
+    database
+        .execute_with_overloaded_retries(
+            identity.clone(),
+            FunctionUsageTracker::new(),
+            "snapshot_import_install_imported_search_indexes",
+            |tx| {
+                async {
+                    // Every document in the table was written before this
+                    // transaction, and the imported segments cover all of them.
+                    let ts = *tx.begin_timestamp();
+                    for index in IndexModel::new(tx)
+                        .all_indexes_on_table(table_id.tablet_id)
+                        .await?
+                    {
+                        let Some(imported) = installed
+                            .iter()
+                            .find(|imported| *index.name.descriptor() == imported.descriptor)
+                        else {
+                            continue;
+                        };
+                        // The search flusher may have finished a backfill in
+                        // the meantime, in which case keep its index.
+                        if !index.config.is_backfilling() {
+                            continue;
+                        }
+                        let (id, index) = index.into_id_and_value();
+                        let metadata = IndexMetadata {
+                            name: index.name,
+                            config: backfilled_at(imported.config.clone(), ts)?,
+                        };
+                        SystemMetadataModel::new_global(tx)
+                            .replace(id, metadata.try_into()?)
+                            .await?;
+                    }
+                    Ok(())
+                }
+                .into()
+            },
+        )
+        .await?;
+    Ok(true)
+}
+
+fn tablet_index_name(
+    tablet_id: TabletId,
+    descriptor: &IndexDescriptor,
+) -> anyhow::Result<TabletIndexName> {
+    if descriptor.is_reserved() {
+        TabletIndexName::new_reserved(tablet_id, descriptor.clone())
+    } else {
+        TabletIndexName::new(tablet_id, descriptor.clone())
+    }
+}
+
+/// Moves a snapshotted index config to the backfilled state at `ts`.
+fn backfilled_at(config: IndexConfig, ts: Timestamp) -> anyhow::Result<IndexConfig> {
+    Ok(match config {
+        IndexConfig::Text {
+            developer_config,
+            on_disk_state: TextIndexState::SnapshottedAt(snapshot),
+        } => IndexConfig::Text {
+            developer_config,
+            on_disk_state: TextIndexState::Backfilled(TextIndexSnapshot { ts, ..snapshot }),
+        },
+        IndexConfig::Vector {
+            developer_config,
+            on_disk_state: VectorIndexState::SnapshottedAt(snapshot),
+        } => IndexConfig::Vector {
+            developer_config,
+            on_disk_state: VectorIndexState::Backfilled(VectorIndexSnapshot { ts, ..snapshot }),
+        },
+        _ => anyhow::bail!("Imported search indexes must be snapshotted"),
+    })
+}
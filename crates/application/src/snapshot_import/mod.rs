@@ -66,10 +66,16 @@ use futures::{
     StreamExt,
     TryStreamExt,
 };
-use keybroker::Identity;
+use keybroker::{
+    ArchiveKey,
+    Identity,
+};
 use model::{
     deployment_audit_log::{
-        types::DeploymentAuditLogEvent,
+        types::{
+            AuditLogRequestContext,
+            DeploymentAuditLogEvent,
+        },
         DeploymentAuditLogModel,
     },
     file_storage::{
@@ -125,11 +131,18 @@ use crate::{
     snapshot_import::{
         audit_log::make_audit_log_event,
         confirmation::info_message_for_import,
+        decryption::decrypt_import,
         import_error::{
             wrap_import_err,
             ImportError,
         },
         import_file_storage::import_storage_table,
+        import_search_indexes::{
+            import_search_index,
+            install_imported_search_indexes,
+            replaced_by_import,
+            ImportedSearchIndex,
+        },
         metrics::log_snapshot_import_age,
         parse::{
             parse_objects,
@@ -151,8 +164,10 @@ use crate::{
 
 mod audit_log;
 mod confirmation;
+mod decryption;
 mod import_error;
 mod import_file_storage;
+mod import_search_indexes;
 mod metrics;
 mod parse;
 mod prepare_component;
@@ -172,6 +187,8 @@ struct SnapshotImportExecutor<RT: Runtime> {
     file_storage: FileStorage<RT>,
     usage_tracking: UsageCounter,
     backoff: Backoff,
+    /// Key for decrypting imports of encrypted export archives.
+    archive_key: Option<ArchiveKey>,
 }
 
 impl<RT: Runtime> SnapshotImportExecutor<RT> {
@@ -374,6 +391,7 @@ impl<RT: Runtime> SnapshotImportExecutor<RT> {
             &self.usage_tracking,
             Identity::system(),
             snapshot_import.member_id,
+            AuditLogRequestContext::new(snapshot_import.source_ip),
             initial_schemas,
             table_mapping_for_import,
             usage,
@@ -410,7 +428,9 @@ impl<RT: Runtime> SnapshotImportExecutor<RT> {
                     Ok(key) => self.snapshot_imports_storage.get_fq_object(&key).await?,
                     Err(key) => self.snapshot_imports_storage.get(&key).await?,
                 };
-                reader.with_context(|| format!("Missing import object {:?}", object_key))
+                let reader =
+                    reader.with_context(|| format!("Missing import object {:?}", object_key))?;
+                decrypt_import(reader, self.archive_key.as_ref()).await
             }
         };
         let objects = parse_objects(format.clone(), component_path.clone(), body_stream).boxed();
@@ -534,6 +554,59 @@ pub async fn cancel_import<RT: Runtime>(
     Ok(())
 }
 
+/// Restart a failed import from where it stopped. Tables that were fully
+/// imported are kept, and partially imported tables continue after the last
+/// chunk that was committed.
+pub async fn resume_import<RT: Runtime>(
+    application: &Application<RT>,
+    identity: Identity,
+    import_id: DeveloperDocumentId,
+) -> anyhow::Result<()> {
+    if !identity.is_admin() {
+        anyhow::bail!(ImportError::Unauthorized);
+    }
+    application
+        .database
+        .execute_with_overloaded_retries(
+            identity,
+            FunctionUsageTracker::new(),
+            "snapshot_import_resume",
+            |tx| {
+                async {
+                    let import_id = tx.resolve_developer_id(&import_id, TableNamespace::Global)?;
+                    let mut import_model = SnapshotImportModel::new(tx);
+                    import_model.resume_import(import_id).await?;
+                    Ok(())
+                }
+                .into()
+            },
+        )
+        .await?;
+    Ok(())
+}
+
+/// The import's state along with per-table checkpoints, for showing
+/// progress.
+pub async fn import_progress<RT: Runtime>(
+    application: &Application<RT>,
+    identity: Identity,
+    import_id: DeveloperDocumentId,
+) -> anyhow::Result<SnapshotImport> {
+    if !identity.is_admin() {
+        anyhow::bail!(ImportError::Unauthorized);
+    }
+    let mut tx = application.begin(identity).await?;
+    let import_id = tx.resolve_developer_id(&import_id, TableNamespace::Global)?;
+    let snapshot_import = SnapshotImportModel::new(&mut tx)
+        .get(import_id)
+        .await?
+        .context(ErrorMetadata::not_found(
+            "ImportNotFound",
+            format!("import {import_id} not found"),
+        ))?;
+    Ok(snapshot_import.into_value())
+}
+
 async fn wait_for_import_worker<RT: Runtime>(
     application: &Application<RT>,
     identity: Identity,
@@ -677,6 +750,7 @@ pub async fn clear_tables<RT: Runtime>(
         &application.usage_tracking,
         identity.clone(),
         None,
+        AuditLogRequestContext::current(),
         initial_schemas,
         table_mapping_for_import,
         usage,
@@ -700,6 +774,7 @@ async fn import_objects<RT: Runtime>(
 ) -> anyhow::Result<(TableMappingForImport, u64)> {
     pin_mut!(objects);
     let mut generated_schemas = BTreeMap::new();
+    let mut imported_search_indexes = BTreeMap::new();
     let mut total_num_documents = 0;
 
     // In ReplaceAll mode, we want to delete all unaffected user tables
@@ -728,6 +803,7 @@ async fn import_objects<RT: Runtime>(
         mode,
         objects.as_mut(),
         &mut generated_schemas,
+        &mut imported_search_indexes,
         &mut table_mapping_for_import,
         usage.clone(),
         import_id,
@@ -772,7 +848,7 @@ async fn import_objects<RT: Runtime>(
         // For tables in the schema, clear them
         table_mapping_for_import.to_delete.remove(&tablet_id);
         let tables_affected = table_mapping_for_import.tables_affected();
-        let (table_id, component_id, _num_to_skip) = prepare_table_for_import(
+        let (table_id, component_id, _resume_position) = prepare_table_for_import(
             database,
             &identity,
             mode,
@@ -781,6 +857,7 @@ async fn import_objects<RT: Runtime>(
             None,
             &tables_affected,
             import_id,
+            &[],
         )
         .await?;
 
@@ -833,6 +910,7 @@ async fn finalize_import<RT: Runtime>(
     usage_tracking: &UsageCounter,
     identity: Identity,
     member_id_override: Option<MemberId>,
+    audit_log_context: AuditLogRequestContext,
     initial_schemas: SchemasForImport,
     table_mapping_for_import: TableMappingForImport,
     usage: FunctionUsageTracker,
@@ -904,9 +982,10 @@ async fn finalize_import<RT: Runtime>(
                             .await?;
                     }
                     DeploymentAuditLogModel::new(tx)
-                        .insert_with_member_override(
+                        .insert_with_context(
                             vec![audit_log_event.clone()],
                             member_id_override,
+                            &audit_log_context,
                         )
                         .await?;
 
@@ -945,6 +1024,7 @@ async fn import_tables_table<RT: Runtime>(
     component_path: &ComponentPath,
     import_id: Option<ResolvedDocumentId>,
     table_mapping_for_import: &mut TableMappingForImport,
+    imported_search_indexes: &BTreeMap<(ComponentPath, TableName), Vec<ImportedSearchIndex>>,
 ) -> anyhow::Result<()> {
     let mut import_tables: Vec<(TableName, TableNumber)> = vec![];
     let mut lineno = 0;
@@ -1010,6 +1090,9 @@ async fn import_tables_table<RT: Runtime>(
             Some(*table_number),
             &tables_affected,
             import_id,
+            imported_search_indexes
+                .get(&(component_path.clone(), table_name.clone()))
+                .map_or(&[], |indexes| indexes.as_slice()),
         )
         .await?;
         table_mapping_for_import.table_mapping_in_import.insert(
@@ -1032,11 +1115,24 @@ async fn import_single_table<RT: Runtime>(
         (ComponentPath, TableName),
         GeneratedSchema<ProdConfigWithOptionalFields>,
     >,
+    imported_search_indexes: &mut BTreeMap<(ComponentPath, TableName), Vec<ImportedSearchIndex>>,
     table_mapping_for_import: &mut TableMappingForImport,
     usage: FunctionUsageTracker,
     import_id: Option<ResolvedDocumentId>,
     requestor: ImportRequestor,
 ) -> anyhow::Result<Option<u64>> {
+    while let Some(ImportUnit::SearchIndex(component_path, table_name, metadata)) = objects
+        .as_mut()
+        .try_next_if(|line| matches!(line, ImportUnit::SearchIndex(..)))
+        .await?
+    {
+        if let Some(index) = import_search_index(database, metadata, objects.as_mut()).await? {
+            imported_search_indexes
+                .entry((component_path, table_name))
+                .or_default()
+                .push(index);
+        }
+    }
     while let Some(ImportUnit::GeneratedSchema(component_path, table_name, generated_schema)) =
         objects
             .as_mut()
@@ -1085,6 +1181,7 @@ async fn import_single_table<RT: Runtime>(
             component_path,
             import_id,
             table_mapping_for_import,
+            imported_search_indexes,
         )
         .await?;
         return Ok(Some(0));
@@ -1092,7 +1189,7 @@ async fn import_single_table<RT: Runtime>(
 
     let mut generated_schema = generated_schemas.get_mut(&component_and_table);
     let tables_affected = table_mapping_for_import.tables_affected();
-    let (table_id, num_to_skip) = match table_mapping_for_import
+    let (table_id, resume_position) = match table_mapping_for_import
         .table_mapping_in_import
         .namespace(component_id.into())
         .id_and_number_if_exists(table_name)
@@ -1106,10 +1203,16 @@ async fn import_single_table<RT: Runtime>(
                     .must_count_tablet(table_id.tablet_id)
                     .await?
             };
-            (table_id, num_to_skip)
+            (
+                table_id,
+                ResumePosition {
+                    num_to_skip,
+                    last_imported_id: None,
+                },
+            )
         },
         None => {
-            let (table_id, component_id, num_to_skip) = prepare_table_for_import(
+            let (table_id, component_id, resume_position) = prepare_table_for_import(
                 database,
                 identity,
                 mode,
@@ -1118,6 +1221,9 @@ async fn import_single_table<RT: Runtime>(
                 table_number_from_docs,
                 &tables_affected,
                 import_id,
+                imported_search_indexes
+                    .get(&component_and_table)
+                    .map_or(&[], |indexes| indexes.as_slice()),
             )
             .await?;
             table_mapping_for_import.table_mapping_in_import.insert(
@@ -1126,7 +1232,7 @@ async fn import_single_table<RT: Runtime>(
                 table_id.table_number,
                 table_name.clone(),
             );
-            (table_id, num_to_skip)
+            (table_id, resume_position)
         },
     };
 
@@ -1140,7 +1246,7 @@ async fn import_single_table<RT: Runtime>(
             objects.as_mut(),
             &usage,
             import_id,
-            num_to_skip,
+            resume_position.num_to_skip,
             requestor,
             &table_mapping_for_import.table_mapping_in_import,
         )
@@ -1155,17 +1261,25 @@ async fn import_single_table<RT: Runtime>(
     table_mapping_for_schema.update(table_mapping_for_import.table_mapping_in_import.clone());
     let mut objects_to_insert = vec![];
     let mut objects_to_insert_size = 0;
+    let mut last_imported_id = None;
     // Peek so we don't pop ImportUnit::NewTable items.
     while let Some(ImportUnit::Object(exported_value)) = objects
         .as_mut()
         .try_next_if(|line| matches!(line, ImportUnit::Object(_)))
         .await?
     {
-        if num_objects < num_to_skip {
+        if num_objects < resume_position.num_to_skip {
             num_objects += 1;
+            if num_objects == resume_position.num_to_skip {
+                resume_position.check_resumes_after(table_name, &exported_value)?;
+            }
             continue;
         }
         let row_number = (num_objects + 1) as usize;
+        last_imported_id = exported_value
+            .get(&**ID_FIELD)
+            .and_then(JsonValue::as_str)
+            .map(str::to_string);
         let convex_value = GeneratedSchema::<ProdConfigWithOptionalFields>::apply(
             &mut generated_schema,
             exported_value,
@@ -1176,6 +1290,7 @@ async fn import_single_table<RT: Runtime>(
         };
         objects_to_insert_size += convex_object.size();
         objects_to_insert.push(convex_object);
+        num_objects += 1;
 
         if objects_to_insert_size > *TRANSACTION_MAX_USER_WRITE_SIZE_BYTES / 2
             || objects_to_insert.len() > *TRANSACTION_MAX_NUM_USER_WRITES / 2
@@ -1188,6 +1303,12 @@ async fn import_single_table<RT: Runtime>(
                 table_id,
                 &table_mapping_for_schema,
                 usage.clone(),
+                import_id.map(|import_id| ChunkCheckpoint {
+                    import_id,
+                    component_path,
+                    num_rows_written: num_objects as i64,
+                    last_imported_id: last_imported_id.clone(),
+                }),
             )
             .await?;
             objects_to_insert = Vec::new();
@@ -1208,7 +1329,6 @@ async fn import_single_table<RT: Runtime>(
                 .await;
             }
         }
-        num_objects += 1;
     }
 
     insert_import_objects(
@@ -1219,9 +1339,29 @@ async fn import_single_table<RT: Runtime>(
         table_id,
         &table_mapping_for_schema,
         usage,
+        import_id.map(|import_id| ChunkCheckpoint {
+            import_id,
+            component_path,
+            num_rows_written: num_objects as i64,
+            last_imported_id,
+        }),
     )
     .await?;
 
+    if let Some(imported) = imported_search_indexes.get(&component_and_table)
+        && install_imported_search_indexes(
+            database,
+            identity,
+            component_id.into(),
+            table_name,
+            table_id,
+            imported,
+        )
+        .await?
+    {
+        backfill_and_enable_indexes_on_table(database, identity, table_id.tablet_id).await?;
+    }
+
     if let Some(import_id) = import_id {
         add_checkpoint_message(
             database,
@@ -1242,6 +1382,45 @@ async fn import_single_table<RT: Runtime>(
     Ok(Some(num_objects))
 }
 
+/// Where to pick up importing a table's objects after an earlier attempt.
+struct ResumePosition {
+    num_to_skip: u64,
+    /// The `_id` of the last object the earlier attempt wrote, if it had one.
+    last_imported_id: Option<String>,
+}
+
+impl ResumePosition {
+    fn check_resumes_after(
+        &self,
+        table_name: &TableName,
+        last_skipped: &JsonValue,
+    ) -> anyhow::Result<()> {
+        let Some(last_imported_id) = &self.last_imported_id else {
+            return Ok(());
+        };
+        if last_skipped.get(&**ID_FIELD).and_then(JsonValue::as_str)
+            != Some(last_imported_id.as_str())
+        {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "ImportFileChanged",
+                format!(
+                    "Can't resume importing \"{table_name}\" because the import file doesn't \
+                     match the earlier attempt. Start a new import instead."
+                )
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Import progress to record in the same transaction as a chunk of objects.
+struct ChunkCheckpoint<'a> {
+    import_id: ResolvedDocumentId,
+    component_path: &'a ComponentPath,
+    num_rows_written: i64,
+    last_imported_id: Option<String>,
+}
+
 async fn insert_import_objects<RT: Runtime>(
     database: &Database<RT>,
     identity: &Identity,
@@ -1250,6 +1429,7 @@ async fn insert_import_objects<RT: Runtime>(
     table_id: TabletIdAndTableNumber,
     table_mapping_for_schema: &TableMapping,
     usage: FunctionUsageTracker,
+    checkpoint: Option<ChunkCheckpoint<'_>>,
 ) -> anyhow::Result<()> {
     if objects_to_insert.is_empty() {
         return Ok(());
@@ -1282,6 +1462,17 @@ async fn insert_import_objects<RT: Runtime>(
                             )
                             .await?;
                     }
+                    if let Some(checkpoint) = &checkpoint {
+                        SnapshotImportModel::new(tx)
+                            .checkpoint_rows_imported(
+                                checkpoint.import_id,
+                                checkpoint.component_path,
+                                table_name,
+                                checkpoint.num_rows_written,
+                                checkpoint.last_imported_id.clone(),
+                            )
+                            .await?;
+                    }
                     Ok(())
                 }
                 .into()
@@ -1300,7 +1491,8 @@ async fn prepare_table_for_import<RT: Runtime>(
     table_number: Option<TableNumber>,
     tables_affected: &BTreeSet<(TableNamespace, TableName)>,
     import_id: Option<ResolvedDocumentId>,
-) -> anyhow::Result<(TabletIdAndTableNumber, ComponentId, u64)> {
+    imported_search_indexes: &[ImportedSearchIndex],
+) -> anyhow::Result<(TabletIdAndTableNumber, ComponentId, ResumePosition)> {
     anyhow::ensure!(
         table_name == &*FILE_STORAGE_TABLE || !table_name.is_system(),
         ErrorMetadata::bad_request(
@@ -1332,21 +1524,50 @@ async fn prepare_table_for_import<RT: Runtime>(
     let existing_checkpoint_tablet = existing_checkpoint
         .as_ref()
         .and_then(|checkpoint| checkpoint.tablet_id);
-    let (insert_into_existing_table_id, num_to_skip) = match existing_checkpoint_tablet {
+    let (insert_into_existing_table_id, resume_position) = match existing_checkpoint_tablet {
         Some(tablet_id) => {
             let table_number = tx.table_mapping().tablet_number(tablet_id)?;
             let num_to_skip = TableModel::new(&mut tx)
                 .must_count_tablet(tablet_id)
                 .await?;
+            // The table only holds documents from this import, so its count
+            // is where to resume. The checkpoint's `_id` is only meaningful
+            // if it was committed along with all of them.
+            let last_imported_id = existing_checkpoint
+                .and_then(|checkpoint| {
+                    (checkpoint.num_rows_written as u64 == num_to_skip)
+                        .then_some(checkpoint.last_imported_id)
+                })
+                .flatten();
             (
                 Some(TabletIdAndTableNumber {
                     tablet_id,
                     table_number,
                 }),
-                num_to_skip,
+                ResumePosition {
+                    num_to_skip,
+                    last_imported_id,
+                },
             )
         },
         None => {
+            // Appending writes into the existing table, so the count would
+            // include documents from before the import. Resume from the
+            // checkpoint instead, which is committed with each chunk.
+            let resume_position = match (mode, existing_checkpoint) {
+                (ImportMode::Append, Some(checkpoint))
+                    if existing_active_table_id.is_some() && checkpoint.num_rows_written > 0 =>
+                {
+                    ResumePosition {
+                        num_to_skip: checkpoint.num_rows_written as u64,
+                        last_imported_id: checkpoint.last_imported_id,
+                    }
+                },
+                _ => ResumePosition {
+                    num_to_skip: 0,
+                    last_imported_id: None,
+                },
+            };
             let tablet_id = match mode {
                 ImportMode::Append => existing_active_table_id,
                 ImportMode::RequireEmpty => {
@@ -1361,7 +1582,7 @@ async fn prepare_table_for_import<RT: Runtime>(
                 },
                 ImportMode::Replace | ImportMode::ReplaceAll => None,
             };
-            (tablet_id, 0)
+            (tablet_id, resume_position)
         },
     };
     drop(tx);
@@ -1392,6 +1613,19 @@ async fn prepare_table_for_import<RT: Runtime>(
                                 table_id.tablet_id,
                             )
                             .await?;
+                        // Search indexes with segments in the import are
+                        // installed once the table's documents are imported,
+                        // rather than built as they're inserted.
+                        let mut index_model = IndexModel::new(tx);
+                        for index in index_model.all_indexes_on_table(table_id.tablet_id).await? {
+                            if replaced_by_import(
+                                &index,
+                                table_id.table_number,
+                                imported_search_indexes,
+                            ) {
+                                index_model.drop_index(index.id()).await?;
+                            }
+                        }
                         if let Some(import_id) = import_id {
                             SnapshotImportModel::new(tx)
                                 .checkpoint_tablet_created(
@@ -1413,12 +1647,12 @@ async fn prepare_table_for_import<RT: Runtime>(
 
         table_id
     };
-    Ok((table_id, component_id, num_to_skip))
+    Ok((table_id, component_id, resume_position))
 }
 
 /// Waits for all indexes on a table to be backfilled, which may take a while
 /// for large tables. After the indexes are backfilled, enable them.
-async fn backfill_and_enable_indexes_on_table<RT: Runtime>(
+pub(crate) async fn backfill_and_enable_indexes_on_table<RT: Runtime>(
     database: &Database<RT>,
     identity: &Identity,
     tablet_id: TabletId,
@@ -1481,6 +1715,8 @@ async fn table_number_for_import(
         ImportUnit::NewTable(..) => None,
         ImportUnit::GeneratedSchema(..) => None,
         ImportUnit::StorageFileChunk(..) => None,
+        ImportUnit::SearchIndex(..) => None,
+        ImportUnit::SearchIndexFileChunk(..) => None,
     }
 }
 
@@ -1511,7 +1747,9 @@ async fn remap_empty_string_by_schema<'a, RT: Runtime>(
             .map_ok(move |object| match object {
                 unit @ ImportUnit::NewTable(..)
                 | unit @ ImportUnit::GeneratedSchema(..)
-                | unit @ ImportUnit::StorageFileChunk(..) => unit,
+                | unit @ ImportUnit::StorageFileChunk(..)
+                | unit @ ImportUnit::SearchIndex(..)
+                | unit @ ImportUnit::SearchIndexFileChunk(..) => unit,
                 ImportUnit::Object(mut object) => ImportUnit::Object({
                     remove_empty_string_optional_entries(&optional_fields, &mut object);
                     object
@@ -40,6 +40,10 @@ use serde_json::{
     json,
     Value as JsonValue,
 };
+use sha2::{
+    Digest,
+    Sha256,
+};
 use shape_inference::{
     export_context::{
         ExportContext,
@@ -50,13 +54,23 @@ use shape_inference::{
     ShapeConfig,
 };
 use storage::StorageGetStream;
-use tokio::io::AsyncBufReadExt as _;
+use tokio::io::{
+    AsyncBufReadExt as _,
+    AsyncReadExt as _,
+};
 use value::{
     id_v6::DeveloperDocumentId,
     TableName,
 };
 
-use crate::snapshot_import::import_error::ImportError;
+use crate::{
+    exports::{
+        ChecksumManifest,
+        SearchIndexZipMetadata,
+        CHECKSUM_MANIFEST_PATH,
+    },
+    snapshot_import::import_error::ImportError,
+};
 
 #[derive(Debug)]
 pub enum ImportUnit {
@@ -68,6 +82,9 @@ pub enum ImportUnit {
         GeneratedSchema<ProdConfigWithOptionalFields>,
     ),
     StorageFileChunk(DeveloperDocumentId, Bytes),
+    SearchIndex(ComponentPath, TableName, SearchIndexZipMetadata),
+    /// A chunk of the named file of the most recently yielded SearchIndex.
+    SearchIndexFileChunk(String, Bytes),
 }
 
 static COMPONENT_NAME_PATTERN: LazyLock<Regex> =
@@ -80,6 +97,65 @@ static DOCUMENTS_PATTERN: LazyLock<Regex> =
 // snapshot/_storage/(ID).png
 static STORAGE_FILE_PATTERN: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"(.*/)?_storage/([^/.]+)(?:\.[^/]+)?$").unwrap());
+// (table)/_search_indexes/(index)/index.json, next to the index's segment
+// files.
+static SEARCH_INDEX_METADATA_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(.*/)?([^/]+)/_search_indexes/([^/]+)/index\.json$").unwrap());
+
+/// Checks each file listed in the archive's checksum manifest, so a corrupted
+/// archive fails before anything is imported. Archives without a manifest,
+/// like ones built by hand, aren't checked.
+async fn verify_checksums(zip_reader: &mut ZipReader, filenames: &[String]) -> anyhow::Result<()> {
+    // The archive may have been re-zipped inside a directory.
+    let Some((manifest_index, manifest_name)) = filenames
+        .iter()
+        .enumerate()
+        .filter(|(_, name)| {
+            name.strip_suffix(CHECKSUM_MANIFEST_PATH)
+                .is_some_and(|prefix| prefix.is_empty() || prefix.ends_with('/'))
+        })
+        .min_by_key(|(_, name)| name.len())
+    else {
+        return Ok(());
+    };
+    let prefix = &manifest_name[..manifest_name.len() - CHECKSUM_MANIFEST_PATH.len()];
+    let mut manifest_bytes = vec![];
+    zip_reader
+        .by_index(manifest_index)
+        .await
+        .map_err(map_zip_error)?
+        .read()
+        .read_to_end(&mut manifest_bytes)
+        .await?;
+    let manifest: ChecksumManifest =
+        serde_json::from_slice(&manifest_bytes).map_err(ImportError::NotJson)?;
+    for (path, expected) in manifest.sha256 {
+        let path = format!("{prefix}{path}");
+        let index = filenames
+            .iter()
+            .position(|name| *name == path)
+            .ok_or_else(|| ImportError::ManifestFileMissing(path.clone()))?;
+        let mut entry_reader = zip_reader
+            .by_index(index)
+            .await
+            .map_err(map_zip_error)?
+            .read();
+        let mut hasher = Sha256::new();
+        loop {
+            let buf = entry_reader.fill_buf().await?;
+            if buf.is_empty() {
+                break;
+            }
+            hasher.update(buf);
+            let n = buf.len();
+            entry_reader.consume(n);
+        }
+        if hex::encode(hasher.finalize()) != expected {
+            anyhow::bail!(ImportError::ChecksumMismatch(path));
+        }
+    }
+    Ok(())
+}
 
 fn map_zip_error(e: anyhow::Error) -> anyhow::Error {
     if let Some(ZipError::Io(_)) = e.downcast_ref::<ZipError>() {
@@ -135,6 +211,9 @@ fn map_csv_error(e: csv_async::Error) -> anyhow::Error {
 ///    order.
 /// 4. If a table has a GeneratedSchema, the GeneratedSchema will be yielded
 ///    before any Objects in that table.
+/// 5. SearchIndexes are yielded before any NewTable, each followed by the
+///    SearchIndexFileChunks of its files, which are contiguous per file like
+///    StorageFileChunks.
 #[try_stream(ok = ImportUnit, error = anyhow::Error)]
 pub async fn parse_objects<'a, Fut>(
     format: ImportFormat,
@@ -225,11 +304,13 @@ pub async fn parse_objects<'a, Fut>(
                 .await
                 .map_err(map_zip_error)?;
             let filenames: Vec<_> = zip_reader.file_names().await?;
+            verify_checksums(&mut zip_reader, &filenames).await?;
             {
                 // First pass, all the things we can store in memory:
                 // a. _tables/documents.jsonl
                 // b. _storage/documents.jsonl
                 // c. user_table/generated_schema.jsonl
+                // d. user_table/_search_indexes/index/index.json
                 // _tables needs to be imported before user tables so we can
                 // pick table numbers correctly for schema validation.
                 // Each generated schema must be parsed before the corresponding
@@ -282,6 +363,66 @@ pub async fn parse_objects<'a, Fut>(
                             ));
                     }
                 }
+                // Search indexes come first, so their segments are uploaded
+                // before the tables they belong to are created.
+                for (i, filename) in filenames.iter().enumerate() {
+                    let Some((component_path, table_name)) = parse_table_filename(
+                        filename,
+                        &base_component_path,
+                        &SEARCH_INDEX_METADATA_PATTERN,
+                    )?
+                    else {
+                        continue;
+                    };
+                    let mut buf = Vec::new();
+                    zip_reader
+                        .by_index(i)
+                        .await
+                        .map_err(map_zip_error)?
+                        .read()
+                        .read_to_end(&mut buf)
+                        .await
+                        .map_err(map_zip_io_error)?;
+                    let metadata: SearchIndexZipMetadata =
+                        serde_json::from_slice(&buf).map_err(|e| {
+                            ErrorMetadata::bad_request(
+                                "InvalidSearchIndex",
+                                format!("{filename} is not valid search index metadata: {e}"),
+                            )
+                        })?;
+                    tracing::info!(
+                        "importing zip file containing search index {table_name}.{}",
+                        metadata.index_name
+                    );
+                    yield ImportUnit::SearchIndex(component_path, table_name, metadata);
+                    let dir = filename
+                        .strip_suffix("index.json")
+                        .expect("pattern ends with index.json");
+                    for (j, segment_filename) in filenames.iter().enumerate() {
+                        let Some(file_name) = segment_filename.strip_prefix(dir) else {
+                            continue;
+                        };
+                        if file_name.is_empty()
+                            || file_name == "index.json"
+                            || file_name.contains('/')
+                        {
+                            continue;
+                        }
+                        let mut entry_reader =
+                            zip_reader.by_index(j).await.map_err(map_zip_error)?.read();
+                        while let buf = entry_reader.fill_buf().await.map_err(map_zip_io_error)?
+                            && !buf.is_empty()
+                        {
+                            yield ImportUnit::SearchIndexFileChunk(
+                                file_name.to_string(),
+                                Bytes::copy_from_slice(buf),
+                            );
+                            let len = buf.len();
+                            entry_reader.consume(len);
+                        }
+                        yield ImportUnit::SearchIndexFileChunk(file_name.to_string(), Bytes::new());
+                    }
+                }
                 for table_unit in table_metadata.into_values().flatten() {
                     yield table_unit;
                 }
@@ -376,7 +517,19 @@ async fn copy_to_temp_file(reader: StorageGetStream) -> anyhow::Result<std::fs::
     let mut tokio_file = tokio::fs::File::from_std(file);
     tokio::io::copy_buf(&mut reader.into_tokio_reader(), &mut tokio_file)
         .await
-        .context("Failed to copy snapshot to temp file")?;
+        .map_err(|e| {
+            // Decryption failures come through as I/O errors; surface them as
+            // import errors so they're reported to the user.
+            match e
+                .get_ref()
+                .and_then(|inner| inner.downcast_ref::<ImportError>())
+            {
+                Some(ImportError::ArchiveDecryption(msg)) => {
+                    ImportError::ArchiveDecryption(msg.clone()).into()
+                },
+                _ => anyhow::Error::from(e).context("Failed to copy snapshot to temp file"),
+            }
+        })?;
     // N.B.: it's ok that this file is seeked to the end because the ZipReader is
     // immediately going to seek it anyway.
     Ok(tokio_file.into_std().await)
@@ -575,6 +728,7 @@ mod tests {
         parse_storage_filename,
         parse_table_filename,
         GENERATED_SCHEMA_PATTERN,
+        SEARCH_INDEX_METADATA_PATTERN,
     };
 
     #[test]
@@ -621,6 +775,20 @@ mod tests {
         )?
         .unwrap();
         assert_eq!(&storage_id.to_string(), "kg2ah8mk1xtg35g7zyexyc96e96yr74f");
+        let (_, table_name) = parse_table_filename(
+            "snapshot/users/_search_indexes/search_body/index.json",
+            &ComponentPath::root(),
+            &SEARCH_INDEX_METADATA_PATTERN,
+        )?
+        .unwrap();
+        assert_eq!(table_name, "users".parse()?);
+        // Segment files aren't metadata.
+        assert!(parse_table_filename(
+            "users/_search_indexes/search_body/segment-0",
+            &ComponentPath::root(),
+            &SEARCH_INDEX_METADATA_PATTERN,
+        )?
+        .is_none());
         Ok(())
     }
 
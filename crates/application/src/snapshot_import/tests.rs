@@ -5,6 +5,11 @@ use std::{
 };
 
 use anyhow::Context;
+use async_zip::{
+    tokio::write::ZipFileWriter,
+    Compression,
+    ZipEntryBuilder,
+};
 use bytes::Bytes;
 use common::{
     bootstrap_model::{
@@ -62,6 +67,7 @@ use futures::{
 };
 use keybroker::{
     AdminIdentity,
+    ArchiveKey,
     Identity,
 };
 use maplit::btreemap;
@@ -75,6 +81,10 @@ use serde_json::{
     json,
     Value as JsonValue,
 };
+use sha2::{
+    Digest,
+    Sha256,
+};
 use storage::{
     LocalDirStorage,
     Storage,
@@ -95,20 +105,29 @@ use value::{
 };
 
 use crate::{
+    exports::{
+        ChecksumManifest,
+        CHECKSUM_MANIFEST_PATH,
+    },
     snapshot_import::{
         do_import,
         do_import_from_object_key,
         import_objects,
+        import_progress,
         parse::{
             parse_objects,
             ImportUnit,
         },
+        resume_import,
         start_stored_import,
         wait_for_import_worker,
         ImportFormat,
         ImportMode,
     },
-    test_helpers::ApplicationTestExt,
+    test_helpers::{
+        ApplicationFixtureArgs,
+        ApplicationTestExt,
+    },
     Application,
 };
 
@@ -155,6 +174,8 @@ async fn run_parse_objects<RT: Runtime>(
                 Ok(super::ImportUnit::NewTable(..)) => None,
                 Ok(super::ImportUnit::GeneratedSchema(..)) => None,
                 Ok(super::ImportUnit::StorageFileChunk(..)) => None,
+                Ok(super::ImportUnit::SearchIndex(..)) => None,
+                Ok(super::ImportUnit::SearchIndexFileChunk(..)) => None,
                 Err(e) => Some(Err(e)),
             }
         })
@@ -990,6 +1011,102 @@ async fn import_zip_to_empty(rt: TestRuntime) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn import_encrypted_zip(rt: TestRuntime) -> anyhow::Result<()> {
+    let key = ArchiveKey::random();
+    let app = Application::new_for_tests_with_args(
+        &rt,
+        ApplicationFixtureArgs::with_export_encryption_key(key.clone()),
+    )
+    .await?;
+    let table_name: TableName = "table1".parse()?;
+    let identity = new_admin_id();
+
+    let mut tx = app.begin(identity.clone()).await?;
+    let mut ufm = UserFacingModel::new_root_for_test(&mut tx);
+    ufm.insert(table_name.clone(), assert_obj!("a" => 1))
+        .await?;
+    ufm.insert(table_name.clone(), assert_obj!("a" => 2))
+        .await?;
+    app.commit_test(tx).await?;
+    let export_object_key = app.export_and_wait().await?;
+
+    // A deployment without the key can't read the archive.
+    let app_without_key = Application::new_for_tests(&rt).await?;
+    let err = do_import_from_object_key(
+        &app_without_key,
+        identity.clone(),
+        ImportFormat::Zip,
+        ImportMode::RequireEmpty,
+        ComponentPath::root(),
+        export_object_key.clone(),
+    )
+    .await
+    .unwrap_err();
+    assert!(err.msg().contains("encrypted archive"), "{err:?}");
+
+    let app_with_key = Application::new_for_tests_with_args(
+        &rt,
+        ApplicationFixtureArgs::with_export_encryption_key(key),
+    )
+    .await?;
+    let rows_written = do_import_from_object_key(
+        &app_with_key,
+        identity,
+        ImportFormat::Zip,
+        ImportMode::RequireEmpty,
+        ComponentPath::root(),
+        export_object_key,
+    )
+    .await?;
+    assert_eq!(rows_written, 2);
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn import_zip_checksum_mismatch(rt: TestRuntime) -> anyhow::Result<()> {
+    let app = Application::new_for_tests(&rt).await?;
+    let documents = format!("{}\n", json!({"a": 1}));
+    let manifest = ChecksumManifest {
+        sha256: btreemap! {
+            "table1/documents.jsonl".to_string() =>
+                hex::encode(Sha256::digest(format!("{}\n", json!({"a": 2})))),
+        },
+    };
+    let mut zip = vec![];
+    let mut writer = ZipFileWriter::with_tokio(&mut zip);
+    writer
+        .write_entry_whole(
+            ZipEntryBuilder::new("table1/documents.jsonl".into(), Compression::Stored).build(),
+            documents.as_bytes(),
+        )
+        .await?;
+    writer
+        .write_entry_whole(
+            ZipEntryBuilder::new(CHECKSUM_MANIFEST_PATH.into(), Compression::Stored).build(),
+            &serde_json::to_vec(&manifest)?,
+        )
+        .await?;
+    writer.close().await?;
+
+    let err = do_import(
+        &app,
+        new_admin_id(),
+        ImportFormat::Zip,
+        ImportMode::RequireEmpty,
+        ComponentPath::root(),
+        stream::iter(vec![anyhow::Ok(Bytes::from(zip))]).boxed(),
+    )
+    .await
+    .unwrap_err();
+    assert!(
+        err.msg()
+            .contains("table1/documents.jsonl doesn't match the checksum"),
+        "{err:?}"
+    );
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn import_zip_to_same_deployment(rt: TestRuntime) -> anyhow::Result<()> {
     for (mode, expect_success) in [
@@ -1363,3 +1480,68 @@ a,b
 
     Ok(())
 }
+
+#[convex_macro::test_runtime]
+async fn test_resume_canceled_import(
+    rt: TestRuntime,
+    pause_controller: PauseController,
+) -> anyhow::Result<()> {
+    let app = Application::new_for_tests(&rt).await?;
+    let table_name = "table1";
+    let test_csv = r#"
+a,b
+"foo","bar"
+"baz","qux"
+"#;
+
+    let hold_guard = pause_controller.hold("before_finalize_import");
+    let mut import_fut = run_csv_import(&app, table_name, test_csv).boxed();
+    let import_id = select! {
+        r = import_fut.as_mut().fuse() => {
+            anyhow::bail!("import finished before pausing: {r:?}");
+        },
+        pause_guard = hold_guard.wait_for_blocked().fuse() => {
+            let pause_guard = pause_guard.unwrap();
+            let mut tx = app.begin(new_admin_id()).await?;
+            let mut import_model = model::snapshot_imports::SnapshotImportModel::new(&mut tx);
+            let snapshot_import = import_model.import_in_state(ImportState::InProgress {
+                progress_message: String::new(),
+                checkpoint_messages: vec![],
+            }).await?.context("No in-progress import found")?;
+            import_model.cancel_import(snapshot_import.id()).await?;
+            app.commit_test(tx).await?;
+            pause_guard.unpause();
+            DeveloperDocumentId::from(snapshot_import.id())
+        },
+    };
+    import_fut.await.unwrap_err();
+
+    // Every document was written before the cancellation, so resuming only
+    // needs to finalize the import.
+    let progress = import_progress(&app, new_admin_id(), import_id).await?;
+    let checkpoints = progress.checkpoints.context("missing checkpoints")?;
+    assert_eq!(checkpoints.len(), 1);
+    assert_eq!(checkpoints[0].num_rows_written, 2);
+    assert!(checkpoints[0].tablet_id.is_some());
+
+    resume_import(&app, new_admin_id(), import_id).await?;
+    let snapshot_import = wait_for_import_worker(&app, new_admin_id(), import_id).await?;
+    must_let!(let ImportState::Completed { num_rows_written, .. } = snapshot_import.state.clone());
+    assert_eq!(num_rows_written, 2);
+
+    let mut tx = app.begin(new_admin_id()).await?;
+    let table_size = tx
+        .must_count(
+            TableNamespace::test_user(),
+            &TableName::from_str(table_name)?,
+        )
+        .await?;
+    assert_eq!(table_size, 2);
+
+    // A completed import can't be resumed.
+    let err = resume_import(&app, new_admin_id(), import_id)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "CannotResumeImport");
+    Ok(())
+}
@@ -11,7 +11,10 @@ use common::{
 };
 use database::Database;
 use file_storage::FileStorage;
-use keybroker::Identity;
+use keybroker::{
+    ArchiveKey,
+    Identity,
+};
 use model::snapshot_imports::{
     types::ImportState,
     SnapshotImportModel,
@@ -39,6 +42,7 @@ impl SnapshotImportWorker {
         snapshot_imports_storage: Arc<dyn Storage>,
         file_storage: FileStorage<RT>,
         usage_tracking: UsageCounter,
+        archive_key: Option<ArchiveKey>,
     ) -> impl Future<Output = ()> + Send {
         let mut worker = SnapshotImportExecutor {
             runtime,
@@ -47,6 +51,7 @@ impl SnapshotImportWorker {
             file_storage,
             usage_tracking,
             backoff: Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF),
+            archive_key,
         };
         async move {
             loop {
@@ -0,0 +1,291 @@
+//! In-memory statistics on live query subscriptions across all sync sessions,
+//! so developers can see which queries re-execute the most and why.
+
+use std::{
+    collections::{
+        btree_map::Entry,
+        BTreeMap,
+    },
+    sync::{
+        atomic::{
+            AtomicU64,
+            Ordering,
+        },
+        Arc,
+    },
+};
+
+use common::{
+    components::{
+        ComponentId,
+        ComponentPath,
+    },
+    runtime::{
+        Runtime,
+        UnixTimestamp,
+    },
+};
+use database::InvalidationCause;
+use keybroker::Identity;
+use parking_lot::Mutex;
+use sync_types::Query;
+
+use crate::Application;
+
+/// The function a subscribed query runs.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SubscribedQueryPath {
+    /// Only set for queries on non-root components, which admins can
+    /// subscribe to directly.
+    pub component_path: Option<String>,
+    pub udf_path: String,
+}
+
+impl From<&Query> for SubscribedQueryPath {
+    fn from(query: &Query) -> Self {
+        Self {
+            component_path: query.component_path.clone(),
+            udf_path: query.udf_path.clone().canonicalize().to_string(),
+        }
+    }
+}
+
+/// Updated by the subscriptions' guards without locking the tracker.
+struct QueryStats {
+    since: UnixTimestamp,
+    executions: AtomicU64,
+    reexecutions: AtomicU64,
+    bytes_pushed: AtomicU64,
+    invalidations: Mutex<BTreeMap<InvalidationCause, u64>>,
+}
+
+struct TrackedQuery {
+    active_subscriptions: u64,
+    stats: Arc<QueryStats>,
+}
+
+/// Shared by every sync worker. A query function is tracked from when its
+/// first subscription starts until its last one ends, so the number of
+/// entries is bounded by the number of live subscriptions.
+#[derive(Clone, Default)]
+pub struct SubscriptionStatsTracker {
+    queries: Arc<Mutex<BTreeMap<SubscribedQueryPath, TrackedQuery>>>,
+}
+
+impl SubscriptionStatsTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts a query as subscribed until the returned guard is dropped.
+    /// Callers should only subscribe queries whose function exists, such as
+    /// once the query has returned a result, so clients can't add entries for
+    /// arbitrary paths.
+    pub fn subscribe(
+        &self,
+        path: SubscribedQueryPath,
+        now: UnixTimestamp,
+    ) -> ActiveSubscriptionGuard {
+        let mut queries = self.queries.lock();
+        let tracked = queries.entry(path.clone()).or_insert_with(|| TrackedQuery {
+            active_subscriptions: 0,
+            stats: Arc::new(QueryStats {
+                since: now,
+                executions: AtomicU64::new(0),
+                reexecutions: AtomicU64::new(0),
+                bytes_pushed: AtomicU64::new(0),
+                invalidations: Mutex::new(BTreeMap::new()),
+            }),
+        });
+        tracked.active_subscriptions += 1;
+        ActiveSubscriptionGuard {
+            tracker: self.clone(),
+            path,
+            stats: tracked.stats.clone(),
+        }
+    }
+}
+
+pub struct ActiveSubscriptionGuard {
+    tracker: SubscriptionStatsTracker,
+    path: SubscribedQueryPath,
+    stats: Arc<QueryStats>,
+}
+
+impl ActiveSubscriptionGuard {
+    /// Counts an execution of the subscribed query. `invalidation` is `Some`
+    /// if the query is re-executing because its previous subscription was
+    /// invalidated, holding what invalidated it if that's known.
+    pub fn record_execution(&self, invalidation: Option<Option<InvalidationCause>>) {
+        self.stats.executions.fetch_add(1, Ordering::Relaxed);
+        if let Some(cause) = invalidation {
+            self.stats.reexecutions.fetch_add(1, Ordering::Relaxed);
+            if let Some(cause) = cause {
+                *self.stats.invalidations.lock().entry(cause).or_default() += 1;
+            }
+        }
+    }
+
+    /// Counts bytes of query results sent to the client for this
+    /// subscription.
+    pub fn record_bytes_pushed(&self, bytes: u64) {
+        self.stats.bytes_pushed.fetch_add(bytes, Ordering::Relaxed);
+    }
+}
+
+impl Drop for ActiveSubscriptionGuard {
+    fn drop(&mut self) {
+        let mut queries = self.tracker.queries.lock();
+        if let Entry::Occupied(mut entry) = queries.entry(self.path.clone()) {
+            let tracked = entry.get_mut();
+            tracked.active_subscriptions = tracked.active_subscriptions.saturating_sub(1);
+            if tracked.active_subscriptions == 0 {
+                entry.remove();
+            }
+        }
+    }
+}
+
+/// A table or index whose writes invalidated a query.
+#[derive(Clone, Debug)]
+pub struct InvalidationCount {
+    pub component_path: Option<ComponentPath>,
+    /// `None` if the table has since been deleted.
+    pub table_name: Option<String>,
+    /// `None` for writes that matched a text search.
+    pub index: Option<String>,
+    pub count: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct QuerySubscriptionStats {
+    pub path: SubscribedQueryPath,
+    /// When the query's oldest live subscription started, since which the
+    /// counts are kept.
+    pub since: UnixTimestamp,
+    pub active_subscriptions: u64,
+    pub executions: u64,
+    pub reexecutions: u64,
+    pub bytes_pushed: u64,
+    /// Most frequent first.
+    pub invalidations: Vec<InvalidationCount>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SubscriptionStatsReport {
+    pub queries: Vec<QuerySubscriptionStats>,
+}
+
+impl<RT: Runtime> Application<RT> {
+    /// Live subscription statistics, with invalidation causes resolved to
+    /// table and index names as of now.
+    pub async fn subscription_stats(
+        &self,
+        identity: Identity,
+    ) -> anyhow::Result<SubscriptionStatsReport> {
+        let mut tx = self.begin(identity).await?;
+        let table_mapping = tx.table_mapping().clone();
+        let mut resolve_table = |tablet_id| -> anyhow::Result<_> {
+            let Ok(table_name) = table_mapping.tablet_name(tablet_id) else {
+                return Ok((None, None));
+            };
+            let namespace = table_mapping.tablet_namespace(tablet_id)?;
+            let component_path = tx.get_component_path(ComponentId::from(namespace));
+            Ok((component_path, Some(table_name.to_string())))
+        };
+
+        // Copy the entries out so resolving names doesn't block subscriptions.
+        let tracked: Vec<_> = self
+            .subscription_stats
+            .queries
+            .lock()
+            .iter()
+            .map(|(path, tracked)| {
+                (
+                    path.clone(),
+                    tracked.active_subscriptions,
+                    tracked.stats.clone(),
+                )
+            })
+            .collect();
+        let mut queries = Vec::with_capacity(tracked.len());
+        for (path, active_subscriptions, stats) in tracked {
+            let invalidation_counts = stats.invalidations.lock().clone();
+            let mut invalidations = vec![];
+            for (cause, count) in &invalidation_counts {
+                let (tablet_id, index) = match cause {
+                    InvalidationCause::Index(index) => {
+                        (*index.table(), Some(index.descriptor().to_string()))
+                    },
+                    InvalidationCause::TextSearch(tablet_id) => (*tablet_id, None),
+                };
+                let (component_path, table_name) = resolve_table(tablet_id)?;
+                invalidations.push(InvalidationCount {
+                    component_path,
+                    table_name,
+                    index,
+                    count: *count,
+                });
+            }
+            invalidations.sort_by(|a, b| b.count.cmp(&a.count));
+            queries.push(QuerySubscriptionStats {
+                path,
+                since: stats.since,
+                active_subscriptions,
+                executions: stats.executions.load(Ordering::Relaxed),
+                reexecutions: stats.reexecutions.load(Ordering::Relaxed),
+                bytes_pushed: stats.bytes_pushed.load(Ordering::Relaxed),
+                invalidations,
+            });
+        }
+        Ok(SubscriptionStatsReport { queries })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::runtime::UnixTimestamp;
+    use sync_types::{
+        Query,
+        QueryId,
+    };
+
+    use super::{
+        Ordering,
+        SubscribedQueryPath,
+        SubscriptionStatsTracker,
+    };
+
+    #[test]
+    fn test_active_subscriptions_tracked_by_guard() -> anyhow::Result<()> {
+        let tracker = SubscriptionStatsTracker::new();
+        let query = Query {
+            query_id: QueryId::new(0),
+            udf_path: "messages:list".parse()?,
+            args: vec![],
+            journal: None,
+            component_path: None,
+        };
+        let path = SubscribedQueryPath::from(&query);
+        let now = UnixTimestamp::from_millis(0);
+        let first = tracker.subscribe(path.clone(), now);
+        let second = tracker.subscribe(path.clone(), now);
+        first.record_execution(None);
+        second.record_execution(Some(None));
+        first.record_bytes_pushed(10);
+        drop(first);
+
+        {
+            let queries = tracker.queries.lock();
+            let tracked = &queries[&path];
+            assert_eq!(tracked.active_subscriptions, 1);
+            assert_eq!(tracked.stats.executions.load(Ordering::Relaxed), 2);
+            assert_eq!(tracked.stats.reexecutions.load(Ordering::Relaxed), 1);
+            assert_eq!(tracked.stats.bytes_pushed.load(Ordering::Relaxed), 10);
+        }
+        // The entry is evicted with its last subscription.
+        drop(second);
+        assert!(tracker.queries.lock().is_empty());
+        Ok(())
+    }
+}
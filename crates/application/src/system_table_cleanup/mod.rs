@@ -19,6 +19,9 @@ use common::{
     },
     errors::report_error,
     knobs::{
+        FUNCTION_RUNS_RETENTION,
+        HTTP_CHECK_RESULTS_RETENTION,
+        LOG_SEARCH_RETENTION,
         MAX_EXPIRED_SNAPSHOT_AGE,
         MAX_IMPORT_AGE,
         MAX_SESSION_CLEANUP_DURATION,
@@ -26,6 +29,8 @@ use common::{
         SYSTEM_TABLE_CLEANUP_CHUNK_SIZE,
         SYSTEM_TABLE_CLEANUP_FREQUENCY,
         SYSTEM_TABLE_ROWS_PER_SECOND,
+        USAGE_METERING_RETENTION,
+        WEBHOOK_DELIVERIES_RETENTION,
     },
     query::{
         Expression,
@@ -67,7 +72,19 @@ use metrics::{
 };
 use model::{
     exports::ExportsModel,
+    function_runs::FUNCTION_RUNS_TABLE,
+    http_checks::HTTP_CHECK_RESULTS_TABLE,
+    log_search::LOG_SEARCH_TERMS_TABLE,
     session_requests::SESSION_REQUESTS_TABLE,
+    usage_metering::{
+        FUNCTION_USAGE_TABLE,
+        TABLE_USAGE_TABLE,
+    },
+    webhook_nonces::{
+        WebhookNonceModel,
+        WEBHOOK_NONCES_TABLE,
+    },
+    webhooks::WEBHOOK_DELIVERIES_TABLE,
 };
 use rand::Rng;
 use storage::Storage;
@@ -130,6 +147,7 @@ impl<RT: Runtime> SystemTableCleanupWorker<RT> {
             self.cleanup_hidden_tables().await?;
             self.cleanup_orphaned_table_namespaces().await?;
             self.cleanup_expired_exports().await?;
+            self.cleanup_expired_webhook_nonces().await?;
 
             // _session_requests are used to make mutations idempotent.
             // We can delete them after they are old enough that the client that
@@ -149,6 +167,83 @@ impl<RT: Runtime> SystemTableCleanupWorker<RT> {
                 *SESSION_CLEANUP_DELETE_CONCURRENCY,
             )
             .await?;
+
+            // With retention disabled nothing new is recorded, so clear out
+            // any runs left from before.
+            let function_runs_to_delete = match *FUNCTION_RUNS_RETENTION {
+                Some(retention) => CreationTimeInterval::Before(
+                    (*self.database.now_ts_for_reads().sub(retention)?).try_into()?,
+                ),
+                None => CreationTimeInterval::All,
+            };
+            self.cleanup_system_table(
+                TableNamespace::Global,
+                &FUNCTION_RUNS_TABLE,
+                function_runs_to_delete,
+                &rate_limiter,
+                1,
+            )
+            .await?;
+
+            let log_search_terms_to_delete = match *LOG_SEARCH_RETENTION {
+                Some(retention) => CreationTimeInterval::Before(
+                    (*self.database.now_ts_for_reads().sub(retention)?).try_into()?,
+                ),
+                None => CreationTimeInterval::All,
+            };
+            self.cleanup_system_table(
+                TableNamespace::Global,
+                &LOG_SEARCH_TERMS_TABLE,
+                log_search_terms_to_delete,
+                &rate_limiter,
+                1,
+            )
+            .await?;
+
+            let usage_to_delete = match *USAGE_METERING_RETENTION {
+                Some(retention) => CreationTimeInterval::Before(
+                    (*self.database.now_ts_for_reads().sub(retention)?).try_into()?,
+                ),
+                None => CreationTimeInterval::All,
+            };
+            for table in [&*FUNCTION_USAGE_TABLE, &*TABLE_USAGE_TABLE] {
+                self.cleanup_system_table(
+                    TableNamespace::Global,
+                    table,
+                    usage_to_delete,
+                    &rate_limiter,
+                    1,
+                )
+                .await?;
+            }
+
+            let webhook_deliveries_cutoff = (*self
+                .database
+                .now_ts_for_reads()
+                .sub(*WEBHOOK_DELIVERIES_RETENTION)?)
+            .try_into()?;
+            self.cleanup_system_table(
+                TableNamespace::Global,
+                &WEBHOOK_DELIVERIES_TABLE,
+                CreationTimeInterval::Before(webhook_deliveries_cutoff),
+                &rate_limiter,
+                1,
+            )
+            .await?;
+
+            let http_check_results_cutoff = (*self
+                .database
+                .now_ts_for_reads()
+                .sub(*HTTP_CHECK_RESULTS_RETENTION)?)
+            .try_into()?;
+            self.cleanup_system_table(
+                TableNamespace::Global,
+                &HTTP_CHECK_RESULTS_TABLE,
+                CreationTimeInterval::Before(http_check_results_cutoff),
+                &rate_limiter,
+                1,
+            )
+            .await?;
         }
     }
 
@@ -261,6 +356,28 @@ impl<RT: Runtime> SystemTableCleanupWorker<RT> {
         Ok(())
     }
 
+    /// Deletes webhook nonces whose replay protection window has passed, a
+    /// chunk per transaction.
+    async fn cleanup_expired_webhook_nonces(&self) -> anyhow::Result<()> {
+        loop {
+            let mut tx = self.database.begin(Identity::system()).await?;
+            let now_ms = self.runtime.unix_timestamp().as_ms_since_epoch()? as i64;
+            let deleted = WebhookNonceModel::new(&mut tx)
+                .delete_expired(now_ms, *SYSTEM_TABLE_CLEANUP_CHUNK_SIZE)
+                .await?;
+            if deleted == 0 {
+                return Ok(());
+            }
+            self.database
+                .commit_with_write_source(tx, "system_table_cleanup")
+                .await?;
+            log_system_table_cleanup_rows(&WEBHOOK_NONCES_TABLE, deleted);
+            if deleted < *SYSTEM_TABLE_CLEANUP_CHUNK_SIZE {
+                return Ok(());
+            }
+        }
+    }
+
     async fn cleanup_system_table(
         &self,
         namespace: TableNamespace,
@@ -420,7 +537,6 @@ impl<RT: Runtime> SystemTableCleanupWorker<RT> {
 
 #[derive(Clone, Copy, Debug)]
 enum CreationTimeInterval {
-    #[allow(dead_code)]
     All,
     None,
     Before(CreationTime),
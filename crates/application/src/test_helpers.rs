@@ -24,7 +24,10 @@ use common::{
     },
     components::ComponentId,
     db_schema,
-    http::fetch::StaticFetchClient,
+    http::fetch::{
+        FetchClient,
+        StaticFetchClient,
+    },
     knobs::{
         ACTION_USER_TIMEOUT,
         UDF_CACHE_MAX_SIZE,
@@ -65,6 +68,7 @@ use isolate::{
     },
 };
 use keybroker::{
+    ArchiveKey,
     Identity,
     KeyBroker,
     DEV_INSTANCE_NAME,
@@ -72,7 +76,10 @@ use keybroker::{
 };
 use model::{
     config::{
-        types::ConfigMetadata,
+        types::{
+            ConfigMetadata,
+            ModuleConfig,
+        },
         ConfigModel,
     },
     cron_jobs::types::CronJob,
@@ -123,6 +130,10 @@ pub struct ApplicationFixtureArgs {
     pub tp: Option<TestPersistence>,
     pub event_logger: Option<Arc<dyn UsageEventLogger>>,
     pub node_executor: Option<Arc<dyn NodeExecutor>>,
+    pub export_encryption_key: Option<ArchiveKey>,
+    /// Used for webhook deliveries. Functions' `fetch` calls always use an
+    /// empty `StaticFetchClient`.
+    pub fetch_client: Option<Arc<dyn FetchClient>>,
 }
 
 impl ApplicationFixtureArgs {
@@ -139,6 +150,20 @@ impl ApplicationFixtureArgs {
             ..Default::default()
         }
     }
+
+    pub fn with_export_encryption_key(key: ArchiveKey) -> Self {
+        Self {
+            export_encryption_key: Some(key),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_fetch_client(fetch_client: Arc<dyn FetchClient>) -> Self {
+        Self {
+            fetch_client: Some(fetch_client),
+            ..Default::default()
+        }
+    }
 }
 
 #[async_trait]
@@ -156,6 +181,8 @@ pub trait ApplicationTestExt<RT: Runtime> {
     /// Load the modules from npm-packages/udf-tests
     async fn load_udf_tests_modules(&self) -> anyhow::Result<()>;
     async fn load_udf_tests_modules_with_node(&self) -> anyhow::Result<()>;
+    /// Analyze `modules` and make them the root component's functions.
+    async fn load_modules(&self, modules: Vec<ModuleConfig>) -> anyhow::Result<()>;
     /// Load the modules form npm-packages/component-tests
     async fn load_component_tests_modules(&self, layout: &str) -> anyhow::Result<()>;
     async fn run_test_push(&self, request: StartPushRequest) -> anyhow::Result<FinishPushDiff>;
@@ -273,6 +300,9 @@ impl<RT: Runtime> ApplicationTestExt<RT> for Application<RT> {
                 Arc::new(NullAccessTokenAuth),
             )),
             QueryCache::new(*UDF_CACHE_MAX_SIZE),
+            args.export_encryption_key,
+            args.fetch_client
+                .unwrap_or_else(|| Arc::new(StaticFetchClient::new())),
         )
         .await?;
 
@@ -297,6 +327,7 @@ impl<RT: Runtime> ApplicationTestExt<RT> for Application<RT> {
             self.database.clone(),
             self.runner.clone(),
             self.function_log.clone(),
+            self.file_storage.transactional_file_storage.clone(),
         );
         test_executor.execute_job(job, job_id).await;
         Ok(())
@@ -321,6 +352,36 @@ impl<RT: Runtime> ApplicationTestExt<RT> for Application<RT> {
         self.load_udf_tests_modules_inner(true).await
     }
 
+    async fn load_modules(&self, modules: Vec<ModuleConfig>) -> anyhow::Result<()> {
+        let mut tx = self.begin(Identity::system()).await?;
+        let udf_config = UdfConfig::new_for_test(&self.runtime(), "1000.0.0".parse()?);
+        // TODO(rakeeb): add external packages to udf test modules
+        let source_package = self.upload_package(&modules, None).await?;
+        let analyze_results = self
+            .analyze(
+                udf_config.clone(),
+                modules.clone(),
+                source_package.clone(),
+                BTreeMap::new(),
+                BTreeMap::new(),
+            )
+            .await??;
+        let schema_id = insert_validated_schema(&mut tx).await?;
+
+        ConfigModel::new(&mut tx, ComponentId::test_user())
+            .apply(
+                ConfigMetadata::new(),
+                modules,
+                udf_config,
+                Some(source_package),
+                analyze_results,
+                Some(schema_id),
+            )
+            .await?;
+        self.commit_test(tx).await?;
+        Ok(())
+    }
+
     async fn load_component_tests_modules(&self, layout: &str) -> anyhow::Result<()> {
         let request = Self::load_start_push_request(Path::new(layout))?;
         self.run_test_push(request).await?;
@@ -337,6 +398,7 @@ impl<RT: Runtime> ApplicationTestExt<RT> for Application<RT> {
                     Identity::system(),
                     start_push.schema_change.clone(),
                     Duration::from_secs(10),
+                    false,
                 )
                 .await?;
             match schema_status {
@@ -418,33 +480,7 @@ impl<RT: Runtime> Application<RT> {
         } else {
             TEST_SOURCE_ISOLATE_ONLY.clone()
         };
-        let mut tx = self.begin(Identity::system()).await?;
-        let udf_config = UdfConfig::new_for_test(&self.runtime(), "1000.0.0".parse()?);
-        // TODO(rakeeb): add external packages to udf test modules
-        let source_package = self.upload_package(&test_source, None).await?;
-        let analyze_results = self
-            .analyze(
-                udf_config.clone(),
-                test_source.clone(),
-                source_package.clone(),
-                BTreeMap::new(),
-                BTreeMap::new(),
-            )
-            .await??;
-        let schema_id = insert_validated_schema(&mut tx).await?;
-
-        ConfigModel::new(&mut tx, ComponentId::test_user())
-            .apply(
-                ConfigMetadata::new(),
-                test_source.clone(),
-                udf_config,
-                Some(source_package),
-                analyze_results,
-                Some(schema_id),
-            )
-            .await?;
-        self.commit_test(tx).await?;
-        Ok(())
+        self.load_modules(test_source).await
     }
 }
 
@@ -1,6 +1,7 @@
 use std::collections::BTreeSet;
 
 use common::{
+    bootstrap_model::tables::TableState,
     components::ComponentPath,
     knobs::TRANSACTION_MAX_NUM_USER_WRITES,
     query::{
@@ -9,6 +10,7 @@ use common::{
     },
 };
 use database::{
+    IndexModel,
     ResolvedQuery,
     TableModel,
 };
@@ -109,6 +111,70 @@ async fn test_delete_tables_and_import(rt: TestRuntime) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn test_truncate_table(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let table: TableName = "table".parse()?;
+    let table_namespace = TableNamespace::test_user();
+    let primary_key = PrimaryKey::try_from(vec![vec!["primary_key".to_string()]])?;
+    application
+        .add_primary_key_indexes(
+            &Identity::system(),
+            btreemap! {table.clone() => primary_key.clone()},
+        )
+        .await?;
+    application
+        .wait_for_primary_key_indexes_ready(Identity::system(), BTreeSet::from([table.clone()]))
+        .await?;
+    let records = (0..10)
+        .map(|i| {
+            AirbyteRecord::new(
+                table.clone(),
+                false,
+                assert_obj!("primary_key" => format!("key{i}")),
+            )
+        })
+        .collect();
+    let streams = btreemap! { table.clone() =>
+        ValidatedAirbyteStream::Dedup(primary_key)
+    };
+    application
+        .import_airbyte_records(&Identity::system(), records, streams)
+        .await?;
+    let mut tx = application.begin(Identity::system()).await?;
+    let old_table_id = tx.table_mapping().namespace(table_namespace).id(&table)?;
+
+    let deleted_docs = application
+        .truncate_table(&Identity::system(), table_namespace, table.clone())
+        .await?;
+    assert_eq!(deleted_docs, 10);
+
+    // The table keeps its name, number and indexes, but is backed by a new,
+    // empty tablet. The old tablet is left for the table deletion worker.
+    let mut tx = application.begin(Identity::system()).await?;
+    let new_table_id = tx.table_mapping().namespace(table_namespace).id(&table)?;
+    assert_eq!(new_table_id.table_number, old_table_id.table_number);
+    assert_ne!(new_table_id.tablet_id, old_table_id.tablet_id);
+    assert!(
+        TableModel::new(&mut tx)
+            .table_is_empty(table_namespace, &table)
+            .await?
+    );
+    assert_eq!(
+        TableModel::new(&mut tx)
+            .get_table_metadata(old_table_id.tablet_id)
+            .await?
+            .state,
+        TableState::Deleting
+    );
+    let indexes = IndexModel::new(&mut tx)
+        .all_indexes_on_table(new_table_id.tablet_id)
+        .await?;
+    assert_eq!(indexes.len(), 3);
+    assert!(indexes.iter().all(|index| index.config.is_enabled()));
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn test_dedup_import(rt: TestRuntime) -> anyhow::Result<()> {
     let application = Application::new_for_tests(&rt).await?;
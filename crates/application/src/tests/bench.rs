@@ -0,0 +1,84 @@
+use common::components::ComponentId;
+use database::{
+    TableModel,
+    UserFacingModel,
+};
+use errors::ErrorMetadataAnyhowExt;
+use keybroker::Identity;
+use runtime::testing::TestRuntime;
+use serde_json::json;
+use value::{
+    assert_obj,
+    TableName,
+    TableNamespace,
+};
+
+use crate::{
+    bench::{
+        BenchConfig,
+        BenchConfigJson,
+    },
+    test_helpers::ApplicationTestExt,
+    Application,
+};
+
+fn config() -> anyhow::Result<BenchConfig> {
+    let config: BenchConfigJson = serde_json::from_value(json!({
+        "seed": 7,
+        "transactions": 20,
+        "writesPerTransaction": 5,
+        "documentSizeBytes": 32,
+        "queryShape": { "type": "scan", "limit": 5 },
+        "subscriptions": 4,
+    }))?;
+    BenchConfig::try_from(config)
+}
+
+#[convex_macro::test_runtime]
+async fn test_run_bench(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let report = application
+        .run_bench(Identity::system(), ComponentId::Root, config()?)
+        .await?;
+    assert_eq!(report.inserts + report.updates + report.deletes, 100);
+    assert_eq!(report.queries, 20);
+    assert_eq!(report.commit_latency.count, 20);
+    assert_eq!(report.subscription_catch_up_latency.count, 20);
+    // Every write touches at least the `by_id` and `by_creation_time` indexes.
+    assert!(report.index_write_amplification >= 2.0);
+    assert!(report.subscription_invalidations > 0);
+
+    // The bench drops the table it created.
+    let table_name: TableName = "bench_documents".parse()?;
+    let mut tx = application.begin(Identity::system()).await?;
+    assert!(!TableModel::new(&mut tx).table_exists(TableNamespace::root_component(), &table_name));
+
+    // The same seed issues the same writes.
+    let rerun = application
+        .run_bench(Identity::system(), ComponentId::Root, config()?)
+        .await?;
+    assert_eq!(
+        (rerun.inserts, rerun.updates, rerun.deletes),
+        (report.inserts, report.updates, report.deletes)
+    );
+    assert_eq!(rerun.document_writes, report.document_writes);
+    assert_eq!(rerun.index_writes, report.index_writes);
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_bench_requires_empty_table(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let mut tx = application.begin(Identity::system()).await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .insert("bench_documents".parse()?, assert_obj!("existing" => true))
+        .await?;
+    application.commit_test(tx).await?;
+
+    let err = application
+        .run_bench(Identity::system(), ComponentId::Root, config()?)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "InvalidBenchConfig");
+    Ok(())
+}
@@ -105,6 +105,39 @@ async fn test_run_component_mutation(rt: TestRuntime) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn test_component_mutation_rolls_back_with_caller(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application
+        .load_component_tests_modules("with-schema")
+        .await?;
+    run_function(
+        &application,
+        "componentEntry:insert".parse()?,
+        vec![json!({"channel": "random", "text": "committed"})],
+    )
+    .await??;
+    let err = run_function(
+        &application,
+        "componentEntry:insertThenFail".parse()?,
+        vec![json!({"channel": "random", "text": "rolled back"})],
+    )
+    .await?
+    .unwrap_err();
+    assert_contains(&err.error, "failing after writing to the component");
+
+    // Only the write from the mutation that succeeded is in the component.
+    let result = run_function(&application, "componentEntry:list".parse()?, vec![]).await??;
+    must_let!(let ConvexValue::Array(messages) = result.value.unpack());
+    assert_eq!(messages.len(), 1);
+    must_let!(let ConvexValue::Object(message) = &messages[0]);
+    assert_eq!(
+        message.get("text"),
+        Some(&ConvexValue::try_from("committed")?)
+    );
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn test_run_component_action(rt: TestRuntime) -> anyhow::Result<()> {
     let application = Application::new_for_tests(&rt).await?;
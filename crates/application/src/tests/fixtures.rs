@@ -0,0 +1,99 @@
+use common::{
+    components::ComponentId,
+    execution_context::ExecutionContext,
+};
+use database::UserFacingModel;
+use errors::ErrorMetadataAnyhowExt;
+use keybroker::Identity;
+use runtime::testing::TestRuntime;
+use serde_json::json;
+use value::ConvexValue;
+
+use crate::{
+    fixtures::{
+        Fixtures,
+        FixturesJson,
+    },
+    test_helpers::ApplicationTestExt,
+    Application,
+};
+
+fn fixtures() -> anyhow::Result<Fixtures> {
+    let fixtures: FixturesJson = serde_json::from_value(json!({
+        "tables": {
+            "users": [
+                { "_key": "alice", "name": "Alice", "avatar": { "$file": "alice.txt" } },
+                { "_key": "bob", "name": "Bob" },
+            ],
+            "messages": [
+                { "_key": "hi", "author": { "$ref": "users/alice" }, "body": "Hi!" },
+            ],
+        },
+        "files": { "alice.txt": { "contentType": "text/plain", "contents": "alice" } },
+    }))?;
+    Fixtures::try_from(fixtures)
+}
+
+#[convex_macro::test_runtime]
+async fn test_load_fixtures(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let loaded = application
+        .load_fixtures(
+            &Identity::system(),
+            ComponentId::Root,
+            fixtures()?,
+            ExecutionContext::new_for_test(),
+        )
+        .await?;
+    assert_eq!(loaded.document_ids.len(), 3);
+    assert_eq!(loaded.file_ids.len(), 1);
+
+    // References resolve to the IDs of the documents and files they name.
+    let mut tx = application.begin(Identity::system()).await?;
+    let message = UserFacingModel::new_root_for_test(&mut tx)
+        .get(loaded.document_ids["messages/hi"], None)
+        .await?
+        .unwrap();
+    assert_eq!(
+        message.value().get("author"),
+        Some(&ConvexValue::try_from(
+            loaded.document_ids["users/alice"].encode()
+        )?)
+    );
+    let alice = UserFacingModel::new_root_for_test(&mut tx)
+        .get(loaded.document_ids["users/alice"], None)
+        .await?
+        .unwrap();
+    assert_eq!(
+        alice.value().get("avatar"),
+        Some(&ConvexValue::try_from(
+            loaded.file_ids["alice.txt"].encode()
+        )?)
+    );
+
+    // Fixtures only load into empty tables.
+    let err = application
+        .load_fixtures(
+            &Identity::system(),
+            ComponentId::Root,
+            fixtures()?,
+            ExecutionContext::new_for_test(),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "FixtureTableNotEmpty");
+
+    // A fresh deployment gets the same IDs.
+    let fresh = Application::new_for_tests(&rt).await?;
+    let reloaded = fresh
+        .load_fixtures(
+            &Identity::system(),
+            ComponentId::Root,
+            fixtures()?,
+            ExecutionContext::new_for_test(),
+        )
+        .await?;
+    assert_eq!(reloaded.document_ids, loaded.document_ids);
+    assert_eq!(reloaded.file_ids, loaded.file_ids);
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+use common::runtime::Runtime;
+use errors::ErrorMetadataAnyhowExt;
+use keybroker::Identity;
+use model::maintenance_mode::MaintenanceModeModel;
+use runtime::testing::TestRuntime;
+
+use crate::{
+    test_helpers::ApplicationTestExt,
+    Application,
+};
+
+#[convex_macro::test_runtime]
+async fn test_maintenance_mode_rejects_clients(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let client = Identity::Unknown(None);
+    application.fail_while_in_maintenance(&client)?;
+
+    application
+        .enable_maintenance_mode(&Identity::system(), "Back soon".to_string(), 503)
+        .await?;
+    let error = application.fail_while_in_maintenance(&client).unwrap_err();
+    assert!(error.is_overloaded());
+    assert_eq!(error.user_facing_message(), "Back soon");
+    application.fail_while_in_maintenance(&Identity::system())?;
+
+    application
+        .disable_maintenance_mode(&Identity::system())
+        .await?;
+    application.fail_while_in_maintenance(&client)?;
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_maintenance_mode_cache_follows_commits(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let client = Identity::Unknown(None);
+
+    // Enabling maintenance outside of `enable_maintenance_mode` still reaches
+    // the cache through the worker's subscription.
+    let mut tx = application.begin(Identity::system()).await?;
+    MaintenanceModeModel::new(&mut tx)
+        .enable("Back soon".to_string(), 503)
+        .await?;
+    application.commit_test(tx).await?;
+    for _ in 0..100 {
+        if application.fail_while_in_maintenance(&client).is_err() {
+            return Ok(());
+        }
+        rt.wait(Duration::from_millis(100)).await;
+    }
+    anyhow::bail!("Maintenance mode never reached the cache");
+}
@@ -2,12 +2,15 @@ mod airbyte_import;
 mod analyze;
 mod auth;
 mod auth_config;
+mod bench;
 pub mod components;
 mod cron_jobs;
 mod environment_variables;
 mod fivetran_import;
+mod fixtures;
 mod http_action;
 mod indexes;
+mod maintenance_mode;
 mod mutation;
 mod occ_retries;
 mod push;
@@ -16,8 +19,10 @@ mod returns_validation;
 mod scheduled_jobs;
 mod schema;
 mod source_package;
+mod static_assets;
 mod storage;
 mod streaming_export;
+mod webhooks;
 
 const NODE_SOURCE: &str = r#"
 var nodeFunction = () => {};
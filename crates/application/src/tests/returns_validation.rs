@@ -13,7 +13,10 @@ use keybroker::{
     UserIdentity,
 };
 use runtime::testing::TestRuntime;
-use serde_json::json;
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
 
 use crate::{
     test_helpers::ApplicationTestExt,
@@ -29,7 +32,14 @@ async fn run_zero_arg_mutation(
     application: &Application<TestRuntime>,
     name: &str,
 ) -> anyhow::Result<Result<RedactedMutationReturn, RedactedMutationError>> {
-    let obj = json!({});
+    run_mutation(application, name, json!({})).await
+}
+
+async fn run_mutation(
+    application: &Application<TestRuntime>,
+    name: &str,
+    obj: JsonValue,
+) -> anyhow::Result<Result<RedactedMutationReturn, RedactedMutationError>> {
     application
         .mutation_udf(
             RequestId::new(),
@@ -50,7 +60,14 @@ async fn run_zero_arg_query(
     application: &Application<TestRuntime>,
     name: &str,
 ) -> anyhow::Result<RedactedQueryReturn> {
-    let obj = json!({});
+    run_query(application, name, json!({})).await
+}
+
+async fn run_query(
+    application: &Application<TestRuntime>,
+    name: &str,
+    obj: JsonValue,
+) -> anyhow::Result<RedactedQueryReturn> {
     application
         .read_only_udf(
             RequestId::new(),
@@ -170,3 +187,43 @@ async fn test_action_output(rt: TestRuntime) -> anyhow::Result<()> {
     assert!(result.unwrap().value.as_str().contains("hello"));
     Ok(())
 }
+
+#[convex_macro::test_runtime]
+async fn test_run_query_bad_output(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+    let name = "returns_validation:runQueryWithBadOutput";
+    let result = run_query(&application, name, json!({ "validateReturns": true })).await?;
+    assert!(format!("{}", result.result.unwrap_err()).contains("ReturnsValidationError"));
+
+    let result = run_query(&application, name, json!({ "validateReturns": false })).await?;
+    assert_eq!(result.result.unwrap().as_str(), "1");
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_run_mutation_bad_output(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+    let name = "returns_validation:runMutationWithBadOutput";
+    let result = run_mutation(&application, name, json!({ "validateReturns": true })).await?;
+    assert!(format!("{}", result.unwrap_err()).contains("ReturnsValidationError"));
+
+    let result = run_mutation(&application, name, json!({ "validateReturns": false })).await?;
+    assert_eq!(result.unwrap().value.as_str(), "1");
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_query_returns_read_document(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+    run_zero_arg_mutation(&application, "returns_validation:insertDocument")
+        .await?
+        .unwrap();
+    // A document that was read only has the fields it was written with plus
+    // its system fields, so it passes a strict object validator.
+    let result = run_zero_arg_query(&application, "returns_validation:readDocument").await?;
+    assert!(result.result.unwrap().as_str().contains("value"));
+    Ok(())
+}
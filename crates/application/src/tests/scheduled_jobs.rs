@@ -3,6 +3,7 @@ use std::{
     time::Duration,
 };
 
+use anyhow::Context;
 use common::{
     components::{
         CanonicalizedComponentFunctionPath,
@@ -10,12 +11,17 @@ use common::{
         ComponentPath,
         PublicFunctionPath,
     },
+    document::{
+        ParseDocument,
+        ParsedDocument,
+    },
     execution_context::ExecutionContext,
     pause::{
         HoldGuard,
         PauseController,
     },
     runtime::Runtime,
+    testing::assert_contains,
     types::FunctionCaller,
     RequestId,
 };
@@ -32,7 +38,10 @@ use model::{
         BackendStateModel,
     },
     scheduled_jobs::{
-        types::ScheduledJobState,
+        types::{
+            ScheduledJob,
+            ScheduledJobState,
+        },
         SchedulerModel,
     },
 };
@@ -84,6 +93,7 @@ async fn create_scheduled_job<'a>(
             parse_udf_args(&path.udf_path, vec![JsonValue::Object(map)])?,
             rt.unix_timestamp(),
             ExecutionContext::new_for_test(),
+            None,
         )
         .await?;
     let state = model.check_status(job_id).await?.unwrap();
@@ -398,3 +408,67 @@ async fn test_scheduled_job_retry(
     assert_eq!(state, ScheduledJobState::Success);
     Ok(())
 }
+
+#[convex_macro::test_runtime]
+async fn test_large_args_moved_to_storage_after_commit(rt: TestRuntime) -> anyhow::Result<()> {
+    std::env::set_var("SCHEDULED_JOB_RETENTION", "30");
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+
+    let path = insert_object_path();
+    let mut map = serde_json::Map::new();
+    map.insert(
+        "key".to_string(),
+        serde_json::Value::String("x".repeat(100_000)),
+    );
+    let args = parse_udf_args(&path.udf_path, vec![JsonValue::Object(map)])?;
+    let mut tx = application.begin(Identity::system()).await?;
+    let job_id = SchedulerModel::new(&mut tx, TableNamespace::test_user())
+        .schedule(
+            path,
+            args.clone(),
+            rt.unix_timestamp() + Duration::from_secs(3600),
+            ExecutionContext::new_for_test(),
+            None,
+        )
+        .await?;
+    // The arguments stay in the document until the transaction commits.
+    let job: ParsedDocument<ScheduledJob> =
+        tx.get(job_id).await?.context("missing job")?.parse()?;
+    assert!(job.args_pending_storage);
+    assert!(job.stored_args.is_none());
+    application.commit_test(tx).await?;
+
+    let mut stored_job = None;
+    for _ in 0..100 {
+        let mut tx = application.begin(Identity::system()).await?;
+        let job: ParsedDocument<ScheduledJob> =
+            tx.get(job_id).await?.context("missing job")?.parse()?;
+        if job.stored_args.is_some() {
+            stored_job = Some(job.into_value());
+            break;
+        }
+        rt.wait(Duration::from_millis(100)).await;
+    }
+    let job = stored_job.context("Arguments weren't moved to storage")?;
+    assert!(!job.args_pending_storage);
+    assert!(job.udf_args().is_err());
+    let file_storage = &application.file_storage.transactional_file_storage;
+    assert_eq!(file_storage.scheduled_job_args(&job).await?, args);
+
+    // Garbage collecting the job deletes its arguments.
+    let mut tx = application.begin(Identity::system()).await?;
+    SchedulerModel::new(&mut tx, TableNamespace::test_user())
+        .cancel(job_id)
+        .await?;
+    application.commit_test(tx).await?;
+    rt.wait(Duration::from_secs(60)).await;
+    let mut tx = application.begin(Identity::system()).await?;
+    let state = SchedulerModel::new(&mut tx, TableNamespace::test_user())
+        .check_status(job_id)
+        .await?;
+    assert!(state.is_none());
+    let err = file_storage.scheduled_job_args(&job).await.unwrap_err();
+    assert_contains(&err, "missing from storage");
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use common::components::ComponentId;
+use errors::ErrorMetadataAnyhowExt;
+use futures::stream;
+use headers::ContentType;
+use keybroker::Identity;
+use model::{
+    file_storage::FileStorageId,
+    static_assets::types::StaticAssetMount,
+};
+use runtime::testing::TestRuntime;
+use value::sha256::Sha256;
+
+use crate::{
+    test_helpers::ApplicationTestExt,
+    Application,
+};
+
+async fn upload(
+    app: &Application<TestRuntime>,
+    path: &str,
+    contents: &'static str,
+) -> anyhow::Result<()> {
+    let body = Box::pin(stream::once(
+        async move { Ok(bytes::Bytes::from(contents)) },
+    ));
+    app.upload_static_asset(
+        &Identity::system(),
+        "site".to_string(),
+        path.to_string(),
+        Some(ContentType::html()),
+        None,
+        body,
+    )
+    .await
+}
+
+#[convex_macro::test_runtime]
+async fn test_upload_and_serve_static_assets(rt: TestRuntime) -> anyhow::Result<()> {
+    let app = Application::new_for_tests(&rt).await?;
+    let identity = Identity::system();
+    app.set_static_asset_mount(
+        &identity,
+        StaticAssetMount {
+            path_prefix: "/docs".to_string(),
+            folder: "site".to_string(),
+            cache_control: Some("public, max-age=60".to_string()),
+            compress: true,
+            spa_fallback: None,
+        },
+    )
+    .await?;
+
+    upload(&app, "index.html", "<h1>v1</h1>").await?;
+    let (_, first) = app
+        .resolve_static_asset("/docs/")
+        .await?
+        .expect("index.html should be served at the prefix");
+    assert_eq!(first.sha256, Sha256::hash(b"<h1>v1</h1>"));
+    assert_eq!(first.content_type.as_deref(), Some("text/html"));
+
+    // Replacing the file deletes the one it replaced from file storage.
+    upload(&app, "index.html", "<h1>v2</h1>").await?;
+    let (mount, second) = app
+        .resolve_static_asset("/docs")
+        .await?
+        .expect("index.html should still be served");
+    assert_eq!(second.sha256, Sha256::hash(b"<h1>v2</h1>"));
+    assert_eq!(mount.cache_control.as_deref(), Some("public, max-age=60"));
+    let err = app
+        .get_file(
+            ComponentId::Root,
+            FileStorageId::DocumentId(first.storage_id),
+        )
+        .await
+        .err()
+        .expect("replaced file should be deleted");
+    assert!(err.is_not_found());
+    assert_eq!(app.list_static_assets(&identity, "site").await?.len(), 1);
+
+    assert!(app.resolve_static_asset("/other/").await?.is_none());
+    assert!(upload(&app, "/index.html", "").await.is_err());
+
+    app.delete_static_asset(&identity, "site", "index.html")
+        .await?;
+    assert!(app.resolve_static_asset("/docs/").await?.is_none());
+    assert!(app
+        .get_file(
+            ComponentId::Root,
+            FileStorageId::DocumentId(second.storage_id)
+        )
+        .await
+        .is_err());
+    Ok(())
+}
@@ -0,0 +1,211 @@
+use std::{
+    sync::Arc,
+    time::Duration,
+};
+
+use common::{
+    components::ComponentPath,
+    http::{
+        fetch::StaticFetchClient,
+        HttpRequest,
+        HttpRequestStream,
+        HttpResponse,
+        HttpResponseStream,
+    },
+    runtime::Runtime,
+};
+use futures::FutureExt;
+use http::{
+    HeaderMap,
+    Method,
+    StatusCode,
+};
+use keybroker::{
+    Identity,
+    WebhookSecret,
+    WEBHOOK_ID_HEADER,
+    WEBHOOK_SIGNATURE_HEADER,
+    WEBHOOK_TIMESTAMP_HEADER,
+};
+use model::webhooks::{
+    types::{
+        CronOutcome,
+        WebhookDelivery,
+        WebhookDeliveryState,
+        WebhookEvent,
+        WebhookEventFilter,
+    },
+    WebhookModel,
+};
+use parking_lot::Mutex;
+use runtime::testing::TestRuntime;
+use serde_json::Value as JsonValue;
+
+use crate::{
+    test_helpers::{
+        ApplicationFixtureArgs,
+        ApplicationTestExt,
+    },
+    Application,
+};
+
+const OK_URL: &str = "https://example.com/ok";
+const FAILING_URL: &str = "https://example.com/failing";
+
+/// Routes `OK_URL` to a handler that records each request and responds 200,
+/// and `FAILING_URL` to one that always responds 500.
+fn fetch_client(requests: Arc<Mutex<Vec<HttpRequest>>>) -> anyhow::Result<StaticFetchClient> {
+    let mut client = StaticFetchClient::new();
+    client.register_http_route(OK_URL.parse()?, Method::POST, move |request| {
+        let requests = requests.clone();
+        async move {
+            requests.lock().push(request.into_http_request().await?);
+            Ok(response(StatusCode::OK, "ok"))
+        }
+        .boxed()
+    });
+    client.register_http_route(
+        FAILING_URL.parse()?,
+        Method::POST,
+        |_: HttpRequestStream| {
+            async move { Ok(response(StatusCode::INTERNAL_SERVER_ERROR, "boom")) }.boxed()
+        },
+    );
+    Ok(client)
+}
+
+fn response(status: StatusCode, body: &str) -> HttpResponseStream {
+    HttpResponse::new(
+        status,
+        HeaderMap::new(),
+        Some(body.as_bytes().to_vec()),
+        None,
+    )
+    .into()
+}
+
+fn cron_completed(rt: &TestRuntime) -> WebhookEvent {
+    WebhookEvent::CronCompleted {
+        component_path: ComponentPath::root(),
+        name: "nightly".to_string(),
+        udf_path: "crons:nightly".to_string(),
+        outcome: CronOutcome::Failure {
+            error: "Uncaught Error: boom".to_string(),
+        },
+        ts: rt.generate_timestamp().unwrap(),
+    }
+}
+
+async fn register(
+    application: &Application<TestRuntime>,
+    name: &str,
+    url: &str,
+) -> anyhow::Result<WebhookSecret> {
+    let (_, secret) = application
+        .register_webhook_endpoint(
+            &Identity::system(),
+            name.to_string(),
+            url.to_string(),
+            vec![WebhookEventFilter::CronOutcomes {
+                failures_only: false,
+            }],
+        )
+        .await?;
+    Ok(secret)
+}
+
+/// Waits until the endpoint's only delivery has been attempted at least once.
+async fn wait_for_attempt(
+    rt: &TestRuntime,
+    application: &Application<TestRuntime>,
+    name: &str,
+) -> anyhow::Result<WebhookDelivery> {
+    for _ in 0..100 {
+        let deliveries = application
+            .list_webhook_deliveries(&Identity::system(), name, 10)
+            .await?;
+        assert_eq!(deliveries.len(), 1);
+        let delivery = deliveries.into_iter().next().unwrap().into_value();
+        if delivery.attempts > 0 {
+            return Ok(delivery);
+        }
+        rt.wait(Duration::from_millis(100)).await;
+    }
+    anyhow::bail!("Webhook delivery to {name} was never attempted");
+}
+
+#[convex_macro::test_runtime]
+async fn test_webhook_delivery_is_signed(rt: TestRuntime) -> anyhow::Result<()> {
+    let requests = Arc::new(Mutex::new(vec![]));
+    let application = Application::new_for_tests_with_args(
+        &rt,
+        ApplicationFixtureArgs::with_fetch_client(Arc::new(fetch_client(requests.clone())?)),
+    )
+    .await?;
+    let secret = register(&application, "ok", OK_URL).await?;
+
+    let mut tx = application.begin(Identity::system()).await?;
+    let now_ms = rt.unix_timestamp().as_ms_since_epoch()?.try_into()?;
+    let queued = WebhookModel::new(&mut tx)
+        .enqueue_event(&cron_completed(&rt), now_ms)
+        .await?;
+    assert_eq!(queued, 1);
+    application.commit_test(tx).await?;
+
+    let delivery = wait_for_attempt(&rt, &application, "ok").await?;
+    assert_eq!(
+        delivery.state,
+        WebhookDeliveryState::Delivered { status: 200 }
+    );
+    let requests = requests.lock();
+    assert_eq!(requests.len(), 1);
+    let request = &requests[0];
+    let header = |name: &str| request.headers.get(name).unwrap().to_str().unwrap();
+    assert_eq!(header(WEBHOOK_ID_HEADER), delivery.event_id);
+    let body = request.body.as_deref().unwrap();
+    assert!(secret.verify(
+        header(WEBHOOK_ID_HEADER),
+        header(WEBHOOK_TIMESTAMP_HEADER).parse()?,
+        body,
+        header(WEBHOOK_SIGNATURE_HEADER),
+    ));
+    let payload: JsonValue = serde_json::from_slice(body)?;
+    assert_eq!(payload["type"], "cron.completed");
+    assert_eq!(payload["id"], delivery.event_id);
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_failed_webhook_delivery_is_retried(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests_with_args(
+        &rt,
+        ApplicationFixtureArgs::with_fetch_client(Arc::new(fetch_client(Default::default())?)),
+    )
+    .await?;
+    register(&application, "failing", FAILING_URL).await?;
+
+    let mut tx = application.begin(Identity::system()).await?;
+    let now_ms: i64 = rt.unix_timestamp().as_ms_since_epoch()?.try_into()?;
+    WebhookModel::new(&mut tx)
+        .enqueue_event(&cron_completed(&rt), now_ms)
+        .await?;
+    application.commit_test(tx).await?;
+
+    let delivery = wait_for_attempt(&rt, &application, "failing").await?;
+    assert_eq!(delivery.state, WebhookDeliveryState::Pending);
+    assert!(delivery.next_attempt_ms > now_ms);
+    let error = delivery.last_error.unwrap();
+    assert!(error.contains("500"), "{error}");
+    assert!(error.contains("boom"), "{error}");
+
+    // Retrying is only allowed once the delivery has given up.
+    let deliveries = application
+        .list_webhook_deliveries(&Identity::system(), "failing", 10)
+        .await?;
+    let id = deliveries[0].id().into();
+    assert!(application
+        .retry_webhook_delivery(&Identity::system(), id)
+        .await
+        .is_err());
+    Ok(())
+}
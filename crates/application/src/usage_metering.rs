@@ -0,0 +1,433 @@
+//! Rolls usage events up into hourly `_function_usage` and `_table_usage`
+//! rows.
+//!
+//! `UsageMeteringLogger` is handed to the database as its usage event logger,
+//! so it sees the same events that would be used for billing. Events are
+//! aggregated in memory and periodically added to the rows for the current
+//! window by `UsageMeteringLogger::flush_worker`. Old rows are deleted by
+//! `SystemTableCleanupWorker`.
+
+use std::{
+    collections::{
+        BTreeMap,
+        HashMap,
+    },
+    fmt,
+    future::Future,
+    mem,
+    sync::Arc,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use common::{
+    components::ComponentPath,
+    errors::report_error,
+    knobs::USAGE_METERING_FLUSH_INTERVAL,
+    runtime::Runtime,
+};
+use database::{
+    Database,
+    TablesUsage,
+};
+use events::usage::{
+    UsageEvent,
+    UsageEventLogger,
+};
+use keybroker::Identity;
+use model::usage_metering::{
+    types::{
+        FunctionUsageRecord,
+        TableUsageRecord,
+    },
+    UsageMeteringModel,
+};
+use parking_lot::Mutex;
+use value::TableName;
+
+/// Width of each row's window.
+pub const USAGE_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// Usage reports read this many milliseconds of rows per transaction to stay
+/// under transaction read limits.
+pub(crate) const REPORT_CHUNK_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// Maximum number of rows upserted per transaction.
+const MAX_ROWS_PER_TRANSACTION: usize = 256;
+
+type UsageKey = (i64, ComponentPath, String);
+
+/// Usage rows for a time range, along with the current size of each table.
+pub struct UsageReport {
+    pub functions: Vec<FunctionUsageRecord>,
+    pub tables: Vec<TableUsageRecord>,
+    pub storage: TablesUsage<(ComponentPath, TableName)>,
+}
+
+#[derive(Debug, Default)]
+struct PendingUsage {
+    functions: BTreeMap<UsageKey, FunctionUsageRecord>,
+    tables: BTreeMap<UsageKey, TableUsageRecord>,
+}
+
+impl PendingUsage {
+    fn function(
+        &mut self,
+        window_start_ms: i64,
+        component_path: ComponentPath,
+        udf_path: String,
+    ) -> &mut FunctionUsageRecord {
+        self.functions
+            .entry((window_start_ms, component_path.clone(), udf_path.clone()))
+            .or_insert_with(|| FunctionUsageRecord::new(window_start_ms, component_path, udf_path))
+    }
+
+    fn table(
+        &mut self,
+        window_start_ms: i64,
+        component_path: ComponentPath,
+        table_name: String,
+    ) -> &mut TableUsageRecord {
+        self.tables
+            .entry((window_start_ms, component_path.clone(), table_name.clone()))
+            .or_insert_with(|| TableUsageRecord::new(window_start_ms, component_path, table_name))
+    }
+
+    fn record(&mut self, window_start_ms: i64, events: Vec<UsageEvent>) {
+        // Bandwidth events carry the component of the data they touched, so
+        // look up the calling function's component from its `FunctionCall`
+        // event when it's in the same batch.
+        let function_components: HashMap<String, Option<String>> = events
+            .iter()
+            .filter_map(|event| match event {
+                UsageEvent::FunctionCall { fields } => {
+                    Some((fields.id.clone(), fields.component_path.clone()))
+                },
+                _ => None,
+            })
+            .collect();
+        let function_component = |id: &String, fallback: &Option<String>| {
+            let component_path = function_components.get(id).unwrap_or(fallback);
+            parse_component_path(component_path)
+        };
+
+        for event in events {
+            match event {
+                UsageEvent::FunctionCall { fields } => {
+                    if !fields.is_tracked {
+                        continue;
+                    }
+                    let Some(component_path) = parse_component_path(&fields.component_path) else {
+                        continue;
+                    };
+                    let record = self.function(window_start_ms, component_path, fields.udf_id);
+                    record.calls += 1;
+                    if fields.status != "success" {
+                        record.failures += 1;
+                    }
+                    record.duration_ms += fields.duration_millis as i64;
+                    record.memory_mb_ms +=
+                        (fields.memory_megabytes * fields.duration_millis) as i64;
+                },
+                UsageEvent::DatabaseBandwidth {
+                    id,
+                    component_path,
+                    udf_id,
+                    table_name,
+                    ingress,
+                    egress,
+                    egress_rows,
+                    ..
+                } => {
+                    if let Some(table_component) = parse_component_path(&component_path) {
+                        let record = self.table(window_start_ms, table_component, table_name);
+                        record.write_bytes += ingress as i64;
+                        record.read_bytes += egress as i64;
+                        record.documents_read += egress_rows as i64;
+                    }
+                    if !is_system_function(&udf_id)
+                        && let Some(component_path) = function_component(&id, &component_path)
+                    {
+                        let record = self.function(window_start_ms, component_path, udf_id);
+                        record.database_write_bytes += ingress as i64;
+                        record.database_read_bytes += egress as i64;
+                        record.documents_read += egress_rows as i64;
+                    }
+                },
+                UsageEvent::VectorBandwidth {
+                    id,
+                    component_path,
+                    udf_id,
+                    table_name,
+                    ingress,
+                    egress,
+                } => {
+                    if let Some(table_component) = parse_component_path(&component_path) {
+                        let record = self.table(window_start_ms, table_component, table_name);
+                        record.vector_write_bytes += ingress as i64;
+                        record.vector_read_bytes += egress as i64;
+                    }
+                    if !is_system_function(&udf_id)
+                        && let Some(component_path) = function_component(&id, &component_path)
+                    {
+                        let record = self.function(window_start_ms, component_path, udf_id);
+                        record.vector_write_bytes += ingress as i64;
+                        record.vector_read_bytes += egress as i64;
+                    }
+                },
+                UsageEvent::FunctionStorageBandwidth {
+                    id,
+                    component_path,
+                    udf_id,
+                    ingress,
+                    egress,
+                } => {
+                    if !is_system_function(&udf_id)
+                        && let Some(component_path) = function_component(&id, &component_path)
+                    {
+                        let record = self.function(window_start_ms, component_path, udf_id);
+                        record.storage_ingress_bytes += ingress as i64;
+                        record.storage_egress_bytes += egress as i64;
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+
+    /// Adds back usage that failed to be written so the next flush retries it.
+    fn merge(&mut self, other: PendingUsage) {
+        for (key, record) in other.functions {
+            self.functions
+                .entry(key)
+                .and_modify(|existing| existing.merge(&record))
+                .or_insert(record);
+        }
+        for (key, record) in other.tables {
+            self.tables
+                .entry(key)
+                .and_modify(|existing| existing.merge(&record))
+                .or_insert(record);
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.functions.is_empty() && self.tables.is_empty()
+    }
+}
+
+fn parse_component_path(component_path: &Option<String>) -> Option<ComponentPath> {
+    match ComponentPath::deserialize(component_path.as_deref()) {
+        Ok(component_path) => Some(component_path),
+        Err(e) => {
+            tracing::warn!("Ignoring usage for invalid component path {component_path:?}: {e:#}");
+            None
+        },
+    }
+}
+
+/// System UDFs and system jobs aren't attributed to a function, though the
+/// data they touch still counts toward its table's usage.
+fn is_system_function(udf_id: &str) -> bool {
+    udf_id.starts_with("_system")
+}
+
+pub struct UsageMeteringLogger<RT: Runtime> {
+    runtime: RT,
+    pending: Mutex<PendingUsage>,
+}
+
+impl<RT: Runtime> fmt::Debug for UsageMeteringLogger<RT> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UsageMeteringLogger")
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl<RT: Runtime> UsageEventLogger for UsageMeteringLogger<RT> {
+    async fn record_async(&self, events: Vec<UsageEvent>) {
+        let window_start_ms = match self.window_start_ms() {
+            Ok(window_start_ms) => window_start_ms,
+            Err(e) => {
+                tracing::error!("Dropping usage events: {e:#}");
+                return;
+            },
+        };
+        self.pending.lock().record(window_start_ms, events);
+    }
+
+    async fn shutdown(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+impl<RT: Runtime> UsageMeteringLogger<RT> {
+    pub fn new(runtime: RT) -> Arc<Self> {
+        Arc::new(Self {
+            runtime,
+            pending: Mutex::new(PendingUsage::default()),
+        })
+    }
+
+    fn window_start_ms(&self) -> anyhow::Result<i64> {
+        let now_ms = self.runtime.unix_timestamp().as_ms_since_epoch()? as i64;
+        let window_ms = USAGE_WINDOW.as_millis() as i64;
+        Ok(now_ms - now_ms % window_ms)
+    }
+
+    /// Periodically adds the usage aggregated since the last flush to the
+    /// usage tables. Must be started once the database has been loaded.
+    pub fn flush_worker(
+        self: Arc<Self>,
+        database: Database<RT>,
+    ) -> impl Future<Output = ()> + Send {
+        async move {
+            tracing::info!("Starting usage metering flush worker");
+            loop {
+                self.runtime.wait(*USAGE_METERING_FLUSH_INTERVAL).await;
+                let mut pending = mem::take(&mut *self.pending.lock());
+                if pending.is_empty() {
+                    continue;
+                }
+                if let Err(mut e) = Self::write(&database, &mut pending).await {
+                    report_error(&mut e.context("Failed to write usage metering rows")).await;
+                    self.pending.lock().merge(pending);
+                }
+            }
+        }
+    }
+
+    /// Writes `pending` a chunk at a time, removing each chunk once it has
+    /// been committed so that only unwritten usage is retried.
+    async fn write(database: &Database<RT>, pending: &mut PendingUsage) -> anyhow::Result<()> {
+        while !pending.functions.is_empty() {
+            let keys: Vec<_> = pending
+                .functions
+                .keys()
+                .take(MAX_ROWS_PER_TRANSACTION)
+                .cloned()
+                .collect();
+            let mut tx = database.begin(Identity::system()).await?;
+            let mut model = UsageMeteringModel::new(&mut tx);
+            for key in &keys {
+                model
+                    .record_function_usage(pending.functions[key].clone())
+                    .await?;
+            }
+            database
+                .commit_with_write_source(tx, "usage_metering")
+                .await?;
+            for key in keys {
+                pending.functions.remove(&key);
+            }
+        }
+        while !pending.tables.is_empty() {
+            let keys: Vec<_> = pending
+                .tables
+                .keys()
+                .take(MAX_ROWS_PER_TRANSACTION)
+                .cloned()
+                .collect();
+            let mut tx = database.begin(Identity::system()).await?;
+            let mut model = UsageMeteringModel::new(&mut tx);
+            for key in &keys {
+                model
+                    .record_table_usage(pending.tables[key].clone())
+                    .await?;
+            }
+            database
+                .commit_with_write_source(tx, "usage_metering")
+                .await?;
+            for key in keys {
+                pending.tables.remove(&key);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::components::ComponentPath;
+    use events::usage::{
+        FunctionCallUsageFields,
+        UsageEvent,
+    };
+
+    use super::PendingUsage;
+
+    fn function_call(id: &str, udf_id: &str, status: &str, is_tracked: bool) -> UsageEvent {
+        UsageEvent::FunctionCall {
+            fields: FunctionCallUsageFields {
+                id: id.to_string(),
+                request_id: "request".to_string(),
+                status: status.to_string(),
+                component_path: None,
+                udf_id: udf_id.to_string(),
+                udf_id_type: "function".to_string(),
+                tag: "action".to_string(),
+                memory_megabytes: 512,
+                duration_millis: 100,
+                environment: "node".to_string(),
+                is_tracked,
+                response_sha256: None,
+                is_occ: false,
+                occ_table_name: None,
+                occ_document_id: None,
+                occ_write_source: None,
+                occ_retry_count: None,
+            },
+        }
+    }
+
+    fn database_bandwidth(id: &str, udf_id: &str, egress: u64) -> UsageEvent {
+        UsageEvent::DatabaseBandwidth {
+            id: id.to_string(),
+            request_id: "request".to_string(),
+            component_path: None,
+            udf_id: udf_id.to_string(),
+            table_name: "messages".to_string(),
+            ingress: 0,
+            egress,
+            egress_rows: 2,
+        }
+    }
+
+    #[test]
+    fn test_aggregates_usage_by_function_and_table() {
+        let mut pending = PendingUsage::default();
+        pending.record(
+            0,
+            vec![
+                function_call("1", "messages:send", "success", true),
+                database_bandwidth("1", "messages:send", 100),
+            ],
+        );
+        pending.record(
+            0,
+            vec![
+                function_call("2", "messages:send", "failure", true),
+                database_bandwidth("2", "messages:send", 50),
+                function_call("3", "_system/cli/tableData", "success", false),
+                database_bandwidth("3", "_system/cli/tableData", 1000),
+            ],
+        );
+
+        let key = (0, ComponentPath::root(), "messages:send".to_string());
+        let function = &pending.functions[&key];
+        assert_eq!(function.calls, 2);
+        assert_eq!(function.failures, 1);
+        assert_eq!(function.duration_ms, 200);
+        assert_eq!(function.memory_mb_ms, 2 * 512 * 100);
+        assert_eq!(function.database_read_bytes, 150);
+        assert_eq!(function.documents_read, 4);
+        assert_eq!(pending.functions.len(), 1);
+
+        // System function reads still count toward the table.
+        let key = (0, ComponentPath::root(), "messages".to_string());
+        let table = &pending.tables[&key];
+        assert_eq!(table.read_bytes, 1150);
+        assert_eq!(table.documents_read, 6);
+    }
+}
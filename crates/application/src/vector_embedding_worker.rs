@@ -0,0 +1,365 @@
+//! Computes the vectors of vector indexes with an embedder, calling the
+//! embedder's action on the texts of the documents queued in
+//! `_vector_embedding_jobs` when they were written.
+
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::Duration,
+};
+
+use common::{
+    backoff::Backoff,
+    bootstrap_model::schema_state::SchemaState,
+    components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentId,
+        PublicFunctionPath,
+    },
+    document::ParsedDocument,
+    errors::report_error,
+    knobs::{
+        VECTOR_EMBEDDER_BATCH_SIZE,
+        VECTOR_EMBEDDER_MAX_ATTEMPTS,
+    },
+    runtime::Runtime,
+    schemas::VectorIndexSchema,
+    types::{
+        FunctionCaller,
+        IndexDescriptor,
+    },
+    RequestId,
+};
+use database::{
+    BootstrapComponentsModel,
+    Database,
+    DeepPatch,
+    PatchOperation,
+    SchemaModel,
+    Transaction,
+    UserFacingModel,
+    VectorEmbeddingJob,
+    VectorEmbeddingJobModel,
+    VectorEmbeddingJobState,
+};
+use keybroker::Identity;
+use serde_json::json;
+use sync_types::UdfPath;
+use value::{
+    ConvexValue,
+    TableNamespace,
+    TabletId,
+};
+
+use crate::{
+    application_function_runner::ApplicationFunctionRunner,
+    metrics::log_worker_starting,
+};
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// How long to wait before retrying a job after its first failed attempt. Each
+/// further attempt doubles the delay, up to `MAX_RETRY_DELAY`.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(5);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(600);
+
+/// How often to check for jobs whose retry delay has passed when no new jobs
+/// are queued.
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+pub struct VectorEmbeddingWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+    runner: Arc<ApplicationFunctionRunner<RT>>,
+}
+
+impl<RT: Runtime> VectorEmbeddingWorker<RT> {
+    pub fn start(
+        runtime: RT,
+        database: Database<RT>,
+        runner: Arc<ApplicationFunctionRunner<RT>>,
+    ) -> impl Future<Output = ()> + Send {
+        let worker = Self {
+            runtime,
+            database,
+            runner,
+        };
+        async move {
+            tracing::info!("Starting VectorEmbeddingWorker");
+            let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+            loop {
+                if let Err(e) = worker.run().await {
+                    let delay = backoff.fail(&mut worker.runtime.rng());
+                    report_error(&mut e.context("VectorEmbeddingWorker died")).await;
+                    tracing::error!("Vector embedding worker failed, sleeping {delay:?}");
+                    worker.runtime.wait(delay).await;
+                } else {
+                    backoff.reset();
+                }
+            }
+        }
+    }
+
+    async fn run(&self) -> anyhow::Result<()> {
+        let _status = log_worker_starting("VectorEmbeddingWorker");
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let now_ms = self.runtime.unix_timestamp_ms()?;
+        let jobs = VectorEmbeddingJobModel::new(&mut tx)
+            .list_due(now_ms, *VECTOR_EMBEDDER_BATCH_SIZE)
+            .await?;
+        if jobs.is_empty() {
+            let token = tx.into_token()?;
+            let subscription = self.database.subscribe(token).await?;
+            tokio::select! {
+                _ = subscription.wait_for_invalidation() => {},
+                _ = self.runtime.wait(IDLE_POLL_INTERVAL) => {},
+            }
+            return Ok(());
+        }
+        let mut batches = BTreeMap::<_, Vec<_>>::new();
+        for job in jobs {
+            batches
+                .entry((job.tablet_id, job.index_descriptor.clone()))
+                .or_default()
+                .push(job);
+        }
+        for ((tablet_id, index_descriptor), jobs) in batches {
+            self.embed_batch(tablet_id, index_descriptor, jobs).await?;
+        }
+        Ok(())
+    }
+
+    /// Embeds the texts of one index's queued documents and writes the vectors.
+    async fn embed_batch(
+        &self,
+        tablet_id: TabletId,
+        index_descriptor: IndexDescriptor,
+        jobs: Vec<ParsedDocument<VectorEmbeddingJob>>,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let Some((namespace, index)) =
+            Self::embedded_index(&mut tx, tablet_id, &index_descriptor).await?
+        else {
+            // The table was deleted or the schema no longer has the embedder.
+            return self.delete_jobs(jobs).await;
+        };
+        let embedder = index.embedder.as_ref().expect("checked by embedded_index");
+
+        // Documents can be queued more than once before the worker gets to
+        // them, so embed each one once and delete its other jobs along with it.
+        let mut texts = BTreeMap::new();
+        let mut stale_jobs = vec![];
+        for job in jobs {
+            let document_id = job.document_id();
+            if texts.contains_key(&document_id) {
+                stale_jobs.push(job);
+                continue;
+            }
+            let text = tx.get(document_id).await?.and_then(|document| {
+                match document.value().get_path(&embedder.source_field) {
+                    Some(ConvexValue::String(text)) => Some(text.to_string()),
+                    _ => None,
+                }
+            });
+            match text {
+                Some(text) => {
+                    texts.insert(document_id, (text, job));
+                },
+                // The document was deleted or its text was unset since.
+                None => stale_jobs.push(job),
+            }
+        }
+        if texts.is_empty() {
+            return self.delete_jobs(stale_jobs).await;
+        }
+
+        let component_id = ComponentId::from(namespace);
+        let component_path =
+            BootstrapComponentsModel::new(&mut tx).must_component_path(component_id)?;
+        let udf_path: UdfPath = embedder.action.parse()?;
+        let path = PublicFunctionPath::Component(CanonicalizedComponentFunctionPath {
+            component: component_path,
+            udf_path: udf_path.canonicalize(),
+        });
+        let args: Vec<_> = texts.values().map(|(text, _)| text.clone()).collect();
+        let result = self
+            .runner
+            .run_action(
+                RequestId::new(),
+                path,
+                vec![json!({ "texts": args })],
+                Identity::system(),
+                FunctionCaller::Action {
+                    parent_scheduled_job: None,
+                },
+            )
+            .await?;
+        let vectors = match result {
+            Ok(action_return) => parse_vectors(action_return.value.unpack()?, texts.len(), &index),
+            Err(e) => Err(format!("The embedder action failed: {}", e.error)),
+        };
+        let vectors = match vectors {
+            Ok(vectors) => vectors,
+            Err(error) => {
+                let jobs = texts.into_values().map(|(_, job)| job).collect();
+                return self.retry_jobs(jobs, error).await;
+            },
+        };
+
+        let mut tx = self.database.begin(Identity::system()).await?;
+        for ((document_id, (text, job)), vector) in texts.into_iter().zip(vectors) {
+            // Only write the vector if the text is still the one it was computed
+            // from. Otherwise the write that changed it queued another job.
+            let current = tx.get(document_id).await?;
+            if let Some(document) = current
+                && document.value().get_path(&embedder.source_field)
+                    == Some(&ConvexValue::String(text.try_into()?))
+            {
+                let patch = DeepPatch::new(vec![PatchOperation::Set {
+                    path: index.vector_field.clone(),
+                    value: vector,
+                }])?;
+                UserFacingModel::new(&mut tx, namespace)
+                    .deep_patch(document_id.developer_id, patch)
+                    .await?;
+            }
+            VectorEmbeddingJobModel::new(&mut tx)
+                .delete(job.id())
+                .await?;
+        }
+        for job in stale_jobs {
+            VectorEmbeddingJobModel::new(&mut tx)
+                .delete(job.id())
+                .await?;
+        }
+        self.database
+            .commit_with_write_source(tx, "vector_embedding")
+            .await?;
+        Ok(())
+    }
+
+    /// The namespace and schema of the index if it still has an embedder in
+    /// the active schema.
+    async fn embedded_index(
+        tx: &mut Transaction<RT>,
+        tablet_id: TabletId,
+        index_descriptor: &IndexDescriptor,
+    ) -> anyhow::Result<Option<(TableNamespace, VectorIndexSchema)>> {
+        if !tx.table_mapping().tablet_id_exists(tablet_id) {
+            return Ok(None);
+        }
+        let namespace = tx.table_mapping().tablet_namespace(tablet_id)?;
+        let table_name = tx.table_mapping().tablet_name(tablet_id)?;
+        let Some((_, schema)) = SchemaModel::new(tx, namespace)
+            .get_by_state(SchemaState::Active)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let index = schema
+            .tables
+            .get(&table_name)
+            .and_then(|table| table.vector_indexes.get(index_descriptor))
+            .filter(|index| index.embedder.is_some())
+            .cloned();
+        Ok(index.map(|index| (namespace, index)))
+    }
+
+    async fn delete_jobs(
+        &self,
+        jobs: Vec<ParsedDocument<VectorEmbeddingJob>>,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        for job in jobs {
+            VectorEmbeddingJobModel::new(&mut tx)
+                .delete(job.id())
+                .await?;
+        }
+        self.database
+            .commit_with_write_source(tx, "vector_embedding")
+            .await?;
+        Ok(())
+    }
+
+    /// Schedules another attempt at each job with exponential backoff, marking
+    /// it as failed once it has used up its attempts.
+    async fn retry_jobs(
+        &self,
+        jobs: Vec<ParsedDocument<VectorEmbeddingJob>>,
+        error: String,
+    ) -> anyhow::Result<()> {
+        tracing::warn!("Failed to embed {} documents: {error}", jobs.len());
+        let now_ms = self.runtime.unix_timestamp_ms()?;
+        let mut tx = self.database.begin(Identity::system()).await?;
+        for job in jobs {
+            let job = job.map(|mut job| {
+                job.attempts += 1;
+                if job.attempts >= *VECTOR_EMBEDDER_MAX_ATTEMPTS {
+                    job.state = VectorEmbeddingJobState::Failed {
+                        error: error.clone(),
+                    };
+                } else {
+                    let delay = INITIAL_RETRY_DELAY
+                        .saturating_mul(1 << (job.attempts - 1).min(16))
+                        .min(MAX_RETRY_DELAY);
+                    job.next_attempt_ms = now_ms + delay.as_millis() as i64;
+                }
+                Ok(job)
+            })?;
+            VectorEmbeddingJobModel::new(&mut tx).replace(job).await?;
+        }
+        self.database
+            .commit_with_write_source(tx, "vector_embedding")
+            .await?;
+        Ok(())
+    }
+}
+
+/// Checks that the embedder returned one vector of the index's dimension per
+/// text, converting them to the values to store in the vector field.
+fn parse_vectors(
+    value: ConvexValue,
+    num_texts: usize,
+    index: &VectorIndexSchema,
+) -> Result<Vec<ConvexValue>, String> {
+    let ConvexValue::Array(vectors) = value else {
+        return Err(format!(
+            "The embedder action must return an array of vectors, but it returned {}",
+            value.type_name()
+        ));
+    };
+    if vectors.len() != num_texts {
+        return Err(format!(
+            "The embedder action returned {} vectors for {num_texts} texts",
+            vectors.len()
+        ));
+    }
+    let dimension = usize::from(index.dimension);
+    vectors
+        .iter()
+        .map(|vector| {
+            let ConvexValue::Array(vector) = vector else {
+                return Err(
+                    "Each vector returned by the embedder action must be an array".to_string(),
+                );
+            };
+            if vector.len() != dimension {
+                return Err(format!(
+                    "The embedder action returned a vector of length {}, but the index has \
+                     dimension {dimension}",
+                    vector.len()
+                ));
+            }
+            if !vector
+                .iter()
+                .all(|element| matches!(element, ConvexValue::Float64(_)))
+            {
+                return Err(
+                    "Vectors returned by the embedder action must contain only numbers".to_string(),
+                );
+            }
+            Ok(ConvexValue::Array(vector.clone()))
+        })
+        .collect()
+}
@@ -0,0 +1,235 @@
+//! Periodically records each vector index's segments, memory usage, query
+//! latency and recall in `_vector_index_stats`, and logs them as metrics, so
+//! operators can tell when an index needs compacting or its search
+//! parameters need tuning.
+
+use std::{
+    collections::BTreeMap,
+    time::Duration,
+};
+
+use common::{
+    backoff::Backoff,
+    bootstrap_model::index::IndexConfig,
+    document::CREATION_TIME_FIELD_PATH,
+    errors::report_error,
+    knobs::{
+        VECTOR_INDEX_STATS_INTERVAL,
+        VECTOR_RECALL_PROBE_INTERVAL,
+        VECTOR_RECALL_PROBE_SAMPLES,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::{
+        IndexId,
+        IndexName,
+    },
+};
+use database::{
+    Database,
+    IndexModel,
+    ResolvedQuery,
+};
+use keybroker::Identity;
+use model::vector_index_stats::{
+    types::VectorIndexStats,
+    VectorIndexStatsModel,
+};
+use rand::Rng;
+use tokio::time::Instant;
+use value::ConvexValue;
+
+use crate::metrics::{
+    log_vector_index_recall,
+    log_vector_index_stats,
+    log_worker_starting,
+};
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// How many nearest neighbors each recall probe query compares.
+const RECALL_PROBE_LIMIT: u32 = 10;
+
+pub struct VectorIndexStatsWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+    last_recall_probe: Option<Instant>,
+}
+
+impl<RT: Runtime> VectorIndexStatsWorker<RT> {
+    pub fn start(runtime: RT, database: Database<RT>) -> impl Future<Output = ()> + Send {
+        let mut worker = Self {
+            runtime,
+            database,
+            last_recall_probe: None,
+        };
+        async move {
+            tracing::info!("Starting VectorIndexStatsWorker");
+            let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+            loop {
+                worker.runtime.wait(*VECTOR_INDEX_STATS_INTERVAL).await;
+                if let Err(e) = worker.run().await {
+                    let delay = backoff.fail(&mut worker.runtime.rng());
+                    report_error(&mut e.context("VectorIndexStatsWorker died")).await;
+                    tracing::error!("Vector index stats worker failed, sleeping {delay:?}");
+                    worker.runtime.wait(delay).await;
+                } else {
+                    backoff.reset();
+                }
+            }
+        }
+    }
+
+    async fn run(&mut self) -> anyhow::Result<()> {
+        let _status = log_worker_starting("VectorIndexStatsWorker");
+        let segment_stats = self.database.vector_index_segment_stats()?;
+        let query_stats = self.database.take_vector_query_stats();
+
+        let probe_recall = !VECTOR_RECALL_PROBE_INTERVAL.is_zero()
+            && self.last_recall_probe.is_none_or(|last_probe| {
+                self.runtime.monotonic_now() - last_probe >= *VECTOR_RECALL_PROBE_INTERVAL
+            });
+        let mut recalls = BTreeMap::new();
+        if probe_recall {
+            self.last_recall_probe = Some(self.runtime.monotonic_now());
+            let sampled_at_ms = self.runtime.unix_timestamp().as_ms_since_epoch()? as i64;
+            for &index_id in segment_stats.keys() {
+                let samples = self.sample_vectors(index_id).await?;
+                if let Some(recall) = self
+                    .database
+                    .vector_index_recall(index_id, samples, RECALL_PROBE_LIMIT)
+                    .await?
+                {
+                    log_vector_index_recall(recall);
+                    recalls.insert(index_id, (recall, sampled_at_ms));
+                }
+            }
+        }
+
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let mut model = VectorIndexStatsModel::new(&mut tx);
+        // Keep the previous probe's recall between probes.
+        for previous in model.list().await? {
+            if let (Some(recall), Some(sampled_at_ms)) =
+                (previous.recall, previous.recall_sampled_at_ms)
+            {
+                recalls
+                    .entry(previous.index_id)
+                    .or_insert((recall, sampled_at_ms));
+            }
+        }
+        let stats = segment_stats
+            .into_iter()
+            .map(|(index_id, segment_stats)| {
+                let queries = query_stats.get(&index_id).copied().unwrap_or_default();
+                log_vector_index_stats(
+                    segment_stats.segment_sizes.len(),
+                    segment_stats.memory_index_bytes,
+                    queries.average_latency(),
+                );
+                let recall = recalls.get(&index_id);
+                VectorIndexStats {
+                    index_id,
+                    segment_sizes: segment_stats.segment_sizes,
+                    deleted_vectors: segment_stats.num_deleted,
+                    segment_bytes: segment_stats.segment_bytes,
+                    memory_index_bytes: segment_stats.memory_index_bytes as u64,
+                    query_count: queries.count,
+                    average_query_latency_ms: queries
+                        .average_latency()
+                        .map(|latency| latency.as_secs_f64() * 1000.0),
+                    recall: recall.map(|(recall, _)| *recall),
+                    recall_sampled_at_ms: recall.map(|(_, sampled_at_ms)| *sampled_at_ms),
+                }
+            })
+            .collect();
+        model.record(stats).await?;
+        self.database
+            .commit_with_write_source(tx, "vector_index_stats")
+            .await?;
+        Ok(())
+    }
+
+    /// Picks the vectors of up to `VECTOR_RECALL_PROBE_SAMPLES` documents in
+    /// the index's table, each the first document created at or after a
+    /// random time in the table's lifetime.
+    async fn sample_vectors(&self, index_id: IndexId) -> anyhow::Result<Vec<Vec<f32>>> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let Some(index) = IndexModel::new(&mut tx)
+            .get_all_indexes()
+            .await?
+            .into_iter()
+            .find(|index| index.id().internal_id() == index_id)
+        else {
+            return Ok(vec![]);
+        };
+        let IndexConfig::Vector {
+            ref developer_config,
+            ..
+        } = index.config
+        else {
+            return Ok(vec![]);
+        };
+        let tablet_id = *index.name.table();
+        let namespace = tx.table_mapping().tablet_namespace(tablet_id)?;
+        let index_name = IndexName::by_creation_time(tx.table_mapping().tablet_name(tablet_id)?);
+        let dimensions = u32::from(developer_config.dimensions) as usize;
+
+        let query = |order, range| {
+            Query::index_range(IndexRange {
+                index_name: index_name.clone(),
+                range,
+                order,
+            })
+        };
+        let mut oldest = ResolvedQuery::new(&mut tx, namespace, query(Order::Asc, vec![]))?;
+        let Some(oldest) = oldest.next(&mut tx, Some(1)).await? else {
+            return Ok(vec![]);
+        };
+        let mut newest = ResolvedQuery::new(&mut tx, namespace, query(Order::Desc, vec![]))?;
+        let Some(newest) = newest.next(&mut tx, Some(1)).await? else {
+            return Ok(vec![]);
+        };
+        let (start, end) = (
+            f64::from(oldest.creation_time()),
+            f64::from(newest.creation_time()),
+        );
+
+        let mut samples = vec![];
+        for _ in 0..*VECTOR_RECALL_PROBE_SAMPLES {
+            let creation_time = self.runtime.rng().random_range(start..=end);
+            let range = vec![IndexRangeExpression::Gte(
+                CREATION_TIME_FIELD_PATH.clone(),
+                ConvexValue::Float64(creation_time).into(),
+            )];
+            let mut sample = ResolvedQuery::new(&mut tx, namespace, query(Order::Asc, range))?;
+            let Some(document) = sample.next(&mut tx, Some(1)).await? else {
+                continue;
+            };
+            let Some(ConvexValue::Array(array)) =
+                document.value().get_path(&developer_config.vector_field)
+            else {
+                continue;
+            };
+            let vector: Option<Vec<f32>> = array
+                .iter()
+                .map(|value| match value {
+                    ConvexValue::Float64(f) => Some(*f as f32),
+                    _ => None,
+                })
+                .collect();
+            if let Some(vector) = vector
+                && vector.len() == dimensions
+            {
+                samples.push(vector);
+            }
+        }
+        Ok(samples)
+    }
+}
@@ -0,0 +1,266 @@
+//! Runs the backfills of vector index migrations, calling each migration's
+//! re-embedding action on the documents that don't have a vector in the
+//! target index's field yet. See `database::VectorIndexMigration`.
+
+use std::{
+    sync::Arc,
+    time::Duration,
+};
+
+use common::{
+    backoff::Backoff,
+    bootstrap_model::index::IndexConfig,
+    components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentId,
+        PublicFunctionPath,
+    },
+    document::{
+        ParsedDocument,
+        CREATION_TIME_FIELD_PATH,
+    },
+    errors::report_error,
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::{
+        FunctionCaller,
+        IndexName,
+    },
+    RequestId,
+};
+use database::{
+    BootstrapComponentsModel,
+    Database,
+    IndexModel,
+    ResolvedQuery,
+    VectorIndexMigration,
+    VectorIndexMigrationModel,
+    VectorIndexMigrationState,
+};
+use keybroker::Identity;
+use serde_json::json;
+use sync_types::UdfPath;
+use value::ConvexValue;
+
+use crate::{
+    application_function_runner::ApplicationFunctionRunner,
+    metrics::log_worker_starting,
+};
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// How often to look for newly inserted documents once every migration has
+/// reached the end of its table.
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+pub struct VectorReembeddingWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+    runner: Arc<ApplicationFunctionRunner<RT>>,
+}
+
+impl<RT: Runtime> VectorReembeddingWorker<RT> {
+    pub fn start(
+        runtime: RT,
+        database: Database<RT>,
+        runner: Arc<ApplicationFunctionRunner<RT>>,
+    ) -> impl Future<Output = ()> + Send {
+        let worker = Self {
+            runtime,
+            database,
+            runner,
+        };
+        async move {
+            tracing::info!("Starting VectorReembeddingWorker");
+            let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+            loop {
+                if let Err(e) = worker.run().await {
+                    let delay = backoff.fail(&mut worker.runtime.rng());
+                    report_error(&mut e.context("VectorReembeddingWorker died")).await;
+                    tracing::error!("Vector re-embedding worker failed, sleeping {delay:?}");
+                    worker.runtime.wait(delay).await;
+                } else {
+                    backoff.reset();
+                }
+            }
+        }
+    }
+
+    async fn run(&self) -> anyhow::Result<()> {
+        let _status = log_worker_starting("VectorReembeddingWorker");
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let migrations = VectorIndexMigrationModel::new(&mut tx)
+            .list_active()
+            .await?;
+        let mut made_progress = false;
+        for migration in migrations {
+            made_progress |= self.backfill_batch(migration).await?;
+        }
+        if !made_progress {
+            let token = tx.into_token()?;
+            let subscription = self.database.subscribe(token).await?;
+            tokio::select! {
+                _ = subscription.wait_for_invalidation() => {},
+                _ = self.runtime.wait(IDLE_POLL_INTERVAL) => {},
+            }
+        }
+        Ok(())
+    }
+
+    /// Re-embeds the next batch of documents after the migration's cursor,
+    /// returning whether there were any.
+    async fn backfill_batch(
+        &self,
+        migration: ParsedDocument<VectorIndexMigration>,
+    ) -> anyhow::Result<bool> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let target_index = IndexModel::new(&mut tx)
+            .get_all_indexes()
+            .await?
+            .into_iter()
+            .find(|index| index.id().internal_id() == migration.target_index_id);
+        let vector_field = match target_index.as_ref().map(|index| &index.config) {
+            Some(IndexConfig::Vector {
+                developer_config, ..
+            }) => developer_config.vector_field.clone(),
+            _ => {
+                return self
+                    .fail(
+                        migration,
+                        "The target vector index was removed before cutover.".to_string(),
+                    )
+                    .await;
+            },
+        };
+        let tablet_id = *target_index.as_ref().expect("checked above").name.table();
+        let namespace = tx.table_mapping().tablet_namespace(tablet_id)?;
+        let table_name = tx.table_mapping().tablet_name(tablet_id)?;
+
+        let range = migration
+            .cursor
+            .map(|cursor| {
+                IndexRangeExpression::Gt(
+                    CREATION_TIME_FIELD_PATH.clone(),
+                    ConvexValue::Float64(f64::from(cursor)).into(),
+                )
+            })
+            .into_iter()
+            .collect();
+        let query = Query::index_range(IndexRange {
+            index_name: IndexName::by_creation_time(table_name),
+            range,
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(&mut tx, namespace, query)?;
+        let batch_size = migration.batch_size as usize;
+        let mut scanned = 0;
+        let mut cursor = migration.cursor;
+        let mut ids = vec![];
+        while scanned < batch_size
+            && let Some(document) = query_stream
+                .next(&mut tx, Some(batch_size - scanned))
+                .await?
+        {
+            scanned += 1;
+            cursor = Some(document.creation_time());
+            if !matches!(
+                document.value().get_path(&vector_field),
+                Some(ConvexValue::Array(_))
+            ) {
+                ids.push(String::from(document.id()));
+            }
+        }
+
+        if scanned == 0 {
+            if migration.state != VectorIndexMigrationState::Backfilling {
+                return Ok(false);
+            }
+            return self
+                .update_if_unchanged(migration, |migration| {
+                    migration.state = VectorIndexMigrationState::ReadyForCutover;
+                })
+                .await;
+        }
+
+        if !ids.is_empty() {
+            let component_id = ComponentId::from(namespace);
+            let component_path =
+                BootstrapComponentsModel::new(&mut tx).must_component_path(component_id)?;
+            let udf_path: UdfPath = migration.reembed_action.parse()?;
+            let path = PublicFunctionPath::Component(CanonicalizedComponentFunctionPath {
+                component: component_path,
+                udf_path: udf_path.canonicalize(),
+            });
+            let result = self
+                .runner
+                .run_action(
+                    RequestId::new(),
+                    path,
+                    vec![json!({ "ids": ids })],
+                    Identity::system(),
+                    FunctionCaller::Action {
+                        parent_scheduled_job: None,
+                    },
+                )
+                .await?;
+            if let Err(e) = result {
+                return self
+                    .fail(
+                        migration,
+                        format!("The re-embedding action failed: {}", e.error),
+                    )
+                    .await;
+            }
+        }
+
+        self.update_if_unchanged(migration, |migration| {
+            migration.cursor = cursor;
+            migration.documents_scanned += scanned as u64;
+            migration.documents_reembedded += ids.len() as u64;
+        })
+        .await
+    }
+
+    async fn fail(
+        &self,
+        migration: ParsedDocument<VectorIndexMigration>,
+        error: String,
+    ) -> anyhow::Result<bool> {
+        self.update_if_unchanged(migration, |migration| {
+            migration.state = VectorIndexMigrationState::Failed { error };
+        })
+        .await
+    }
+
+    /// Applies `update` to the migration unless it has changed since the worker
+    /// read `original`, e.g. because an admin canceled it or cut it over.
+    async fn update_if_unchanged(
+        &self,
+        original: ParsedDocument<VectorIndexMigration>,
+        update: impl FnOnce(&mut VectorIndexMigration),
+    ) -> anyhow::Result<bool> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let mut model = VectorIndexMigrationModel::new(&mut tx);
+        let current = model.get_active(original.source_index_id).await?;
+        if current.as_ref().map(|current| (current.id(), &**current))
+            != Some((original.id(), &*original))
+        {
+            return Ok(false);
+        }
+        let updated = original.map(|mut migration| {
+            update(&mut migration);
+            Ok(migration)
+        })?;
+        model.replace(updated).await?;
+        self.database
+            .commit_with_write_source(tx, "vector_reembedding_progress")
+            .await?;
+        Ok(true)
+    }
+}
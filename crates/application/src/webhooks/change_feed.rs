@@ -0,0 +1,237 @@
+use std::{
+    num::NonZeroU32,
+    ops::Bound,
+    time::Duration,
+};
+
+use common::{
+    backoff::Backoff,
+    components::ComponentPath,
+    document::ParsedDocument,
+    errors::report_error,
+    knobs::WEBHOOK_CHANGE_FEED_BATCH_SIZE,
+    persistence::{
+        DocumentLogEntry,
+        TimestampRange,
+    },
+    query::Order,
+    runtime::{
+        new_rate_limiter,
+        RateLimiter,
+        Runtime,
+    },
+    types::{
+        TableName,
+        Timestamp,
+    },
+};
+use database::Database;
+use futures::{
+    pin_mut,
+    TryStreamExt,
+};
+use governor::Quota;
+use keybroker::Identity;
+use model::webhooks::{
+    types::{
+        DocumentChange,
+        WebhookEndpoint,
+        WebhookEvent,
+    },
+    WebhookModel,
+};
+use value::{
+    export::ValueFormat,
+    TableNamespace,
+    TableNumber,
+    TabletId,
+};
+
+use crate::metrics::log_worker_starting;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// How often to check for new document changes once every endpoint has been
+/// sent the changes up to the latest commit.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+const ROWS_PER_SECOND: u32 = 10_000;
+
+/// Queues deliveries for the document changes that endpoints with document
+/// change filters subscribe to. Each endpoint keeps a cursor into the
+/// document log, which is advanced in the same transaction that queues the
+/// deliveries, so every change is queued exactly once. Changes are read from
+/// the document log, so an endpoint whose cursor falls out of document
+/// retention stops receiving document changes.
+pub struct WebhookChangeFeed<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+    rate_limiter: RateLimiter<RT>,
+}
+
+impl<RT: Runtime> WebhookChangeFeed<RT> {
+    pub fn start(runtime: RT, database: Database<RT>) -> impl Future<Output = ()> + Send {
+        let rate_limiter = new_rate_limiter(
+            runtime.clone(),
+            Quota::per_second(NonZeroU32::new(ROWS_PER_SECOND).unwrap()),
+        );
+        let worker = Self {
+            runtime,
+            database,
+            rate_limiter,
+        };
+        async move {
+            tracing::info!("Starting WebhookChangeFeed");
+            let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+            loop {
+                if let Err(e) = worker.run().await {
+                    let delay = backoff.fail(&mut worker.runtime.rng());
+                    report_error(&mut e.context("WebhookChangeFeed died")).await;
+                    tracing::error!("Webhook change feed failed, sleeping {delay:?}");
+                    worker.runtime.wait(delay).await;
+                } else {
+                    backoff.reset();
+                }
+            }
+        }
+    }
+
+    async fn run(&self) -> anyhow::Result<()> {
+        let _status = log_worker_starting("WebhookChangeFeed");
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let snapshot_ts = *tx.begin_timestamp();
+        let endpoints: Vec<_> = WebhookModel::new(&mut tx)
+            .list_endpoints()
+            .await?
+            .into_iter()
+            .filter(|endpoint| endpoint.document_tables().next().is_some())
+            .collect();
+        if endpoints.is_empty() {
+            let token = tx.into_token()?;
+            let subscription = self.database.subscribe(token).await?;
+            subscription.wait_for_invalidation().await;
+            return Ok(());
+        }
+        let mut caught_up = true;
+        for endpoint in endpoints {
+            if endpoint.document_cursor < snapshot_ts {
+                caught_up &= self.queue_changes(&endpoint, snapshot_ts).await?;
+            }
+        }
+        if caught_up {
+            self.runtime.wait(POLL_INTERVAL).await;
+        }
+        Ok(())
+    }
+
+    /// Queues up to a batch of the endpoint's document changes committed after
+    /// its cursor and at or before `snapshot_ts`. Returns whether the endpoint
+    /// is now caught up to `snapshot_ts`.
+    async fn queue_changes(
+        &self,
+        endpoint: &ParsedDocument<WebhookEndpoint>,
+        snapshot_ts: Timestamp,
+    ) -> anyhow::Result<bool> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let table_mapping = tx
+            .table_mapping()
+            .namespace(TableNamespace::root_component());
+        let mut tablets = vec![];
+        for table_name in endpoint.document_tables() {
+            // Tables that don't exist yet have no changes to send.
+            if let Some(tablet_id) = table_mapping.id_if_exists(table_name) {
+                let table_number = table_mapping.tablet_number(tablet_id)?;
+                tablets.push((tablet_id, table_number, table_name.clone()));
+            }
+        }
+
+        let mut upper = snapshot_ts;
+        let mut entries = vec![];
+        for (tablet_id, table_number, table_name) in tablets {
+            let (table_entries, truncated_at) = self
+                .load_changes(tablet_id, endpoint.document_cursor, snapshot_ts)
+                .await?;
+            if let Some(ts) = truncated_at {
+                upper = upper.min(ts);
+            }
+            entries.extend(
+                table_entries
+                    .into_iter()
+                    .map(|entry| (table_name.clone(), table_number, entry)),
+            );
+        }
+        entries.retain(|(.., entry)| entry.ts <= upper);
+        entries.sort_by_key(|(.., entry)| (entry.ts, entry.id));
+
+        let now_ms = self
+            .runtime
+            .unix_timestamp()
+            .as_ms_since_epoch()?
+            .try_into()?;
+        let mut model = WebhookModel::new(&mut tx);
+        for (table_name, table_number, entry) in entries {
+            let event = document_changed(table_name, table_number, entry);
+            model
+                .enqueue_for_endpoint(endpoint.id().into(), &event, now_ms)
+                .await?;
+        }
+        model.advance_document_cursor(endpoint.id(), upper).await?;
+        self.database
+            .commit_with_write_source(tx, "webhook_change_feed")
+            .await?;
+        Ok(upper == snapshot_ts)
+    }
+
+    /// Loads a table's document changes after `after_ts`, up to and including
+    /// `until_ts`. Stops after a batch, but never partway through a
+    /// timestamp, and returns the last timestamp loaded if it stopped early.
+    async fn load_changes(
+        &self,
+        tablet_id: TabletId,
+        after_ts: Timestamp,
+        until_ts: Timestamp,
+    ) -> anyhow::Result<(Vec<DocumentLogEntry>, Option<Timestamp>)> {
+        let stream = self.database.load_documents_in_table(
+            tablet_id,
+            TimestampRange::new((Bound::Excluded(after_ts), Bound::Included(until_ts)))?,
+            Order::Asc,
+            &self.rate_limiter,
+        );
+        pin_mut!(stream);
+        let mut entries: Vec<DocumentLogEntry> = vec![];
+        while let Some(entry) = stream.try_next().await? {
+            if let Some(last) = entries.last()
+                && entries.len() >= *WEBHOOK_CHANGE_FEED_BATCH_SIZE
+                && entry.ts > last.ts
+            {
+                let last_ts = last.ts;
+                return Ok((entries, Some(last_ts)));
+            }
+            entries.push(entry);
+        }
+        Ok((entries, None))
+    }
+}
+
+fn document_changed(
+    table_name: TableName,
+    table_number: TableNumber,
+    entry: DocumentLogEntry,
+) -> WebhookEvent {
+    let change = match (&entry.value, entry.prev_ts) {
+        (None, _) => DocumentChange::Delete,
+        (Some(_), None) => DocumentChange::Insert,
+        (Some(_), Some(_)) => DocumentChange::Update,
+    };
+    WebhookEvent::DocumentChanged {
+        component_path: ComponentPath::root(),
+        table_name,
+        document_id: entry.id.to_resolved(table_number).developer_id,
+        change,
+        document: entry
+            .value
+            .map(|document| document.export(ValueFormat::ConvexCleanJSON)),
+        ts: entry.ts,
+    }
+}
@@ -0,0 +1,236 @@
+use std::{
+    sync::Arc,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use common::{
+    document::ParsedDocument,
+    http::{
+        fetch::FetchClient,
+        HttpRequest,
+    },
+    knobs::{
+        WEBHOOK_DELIVERY_CONCURRENCY,
+        WEBHOOK_MAX_ATTEMPTS,
+        WEBHOOK_REQUEST_TIMEOUT,
+    },
+    runtime::Runtime,
+};
+use database::{
+    Database,
+    Transaction,
+};
+use futures::StreamExt;
+use http::{
+    header::CONTENT_TYPE,
+    HeaderMap,
+    HeaderValue,
+    Method,
+};
+use keybroker::{
+    WEBHOOK_ID_HEADER,
+    WEBHOOK_SIGNATURE_HEADER,
+    WEBHOOK_TIMESTAMP_HEADER,
+};
+use model::webhooks::{
+    types::{
+        WebhookDelivery,
+        WebhookDeliveryState,
+        WebhookEndpoint,
+    },
+    WebhookModel,
+};
+use url::Url;
+
+use crate::due_job_worker::{
+    DueJobWorker,
+    DueJobs,
+};
+
+/// How long to wait before retrying a delivery after its first failed
+/// attempt. Each further attempt doubles the delay, up to `MAX_RETRY_DELAY`.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(10);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60 * 60);
+
+/// Most bytes of an error response's body kept as the attempt's error.
+const MAX_ERROR_LEN: usize = 512;
+
+/// Sends due deliveries from `_webhook_deliveries` to their endpoints.
+/// Deliveries are at least once: a delivery sent just before the backend
+/// stops is sent again on restart, so receivers should drop duplicate
+/// `webhook-id`s.
+pub struct WebhookDeliveryWorker<RT: Runtime> {
+    runtime: RT,
+    fetch_client: Arc<dyn FetchClient>,
+}
+
+impl<RT: Runtime> WebhookDeliveryWorker<RT> {
+    pub fn start(
+        runtime: RT,
+        database: Database<RT>,
+        fetch_client: Arc<dyn FetchClient>,
+    ) -> impl Future<Output = ()> + Send {
+        let deliveries = Self {
+            runtime: runtime.clone(),
+            fetch_client,
+        };
+        DueJobWorker::start(runtime, database, deliveries)
+    }
+
+    /// Sends one signed request, returning the response status if the
+    /// endpoint accepted it.
+    async fn send(
+        &self,
+        delivery: &WebhookDelivery,
+        endpoint: &WebhookEndpoint,
+    ) -> Result<u16, String> {
+        let request = self
+            .signed_request(delivery, endpoint)
+            .map_err(|e| format!("Failed to build the request: {e}"))?;
+        let response = tokio::select! {
+            response = self.fetch_client.fetch(request.into()) => {
+                response.map_err(|e| format!("Request failed: {e}"))?
+            },
+            _ = self.runtime.wait(*WEBHOOK_REQUEST_TIMEOUT) => {
+                return Err(format!(
+                    "Request timed out after {:?}",
+                    *WEBHOOK_REQUEST_TIMEOUT
+                ));
+            },
+        };
+        let status = response.status;
+        if status.is_success() {
+            return Ok(status.as_u16());
+        }
+        let mut error = format!("Endpoint responded with {status}");
+        if let Some(mut body) = response.body {
+            let mut excerpt = vec![];
+            while excerpt.len() < MAX_ERROR_LEN
+                && let Some(Ok(chunk)) = body.next().await
+            {
+                excerpt.extend_from_slice(&chunk);
+            }
+            excerpt.truncate(MAX_ERROR_LEN);
+            if !excerpt.is_empty() {
+                error.push_str(": ");
+                error.push_str(&String::from_utf8_lossy(&excerpt));
+            }
+        }
+        Err(error)
+    }
+
+    fn signed_request(
+        &self,
+        delivery: &WebhookDelivery,
+        endpoint: &WebhookEndpoint,
+    ) -> anyhow::Result<HttpRequest> {
+        let timestamp_secs = self.runtime.unix_timestamp().as_secs();
+        let signature = endpoint.secret.sign(
+            &delivery.event_id,
+            timestamp_secs,
+            delivery.payload.as_bytes(),
+        );
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        headers.insert(WEBHOOK_ID_HEADER, delivery.event_id.parse()?);
+        headers.insert(WEBHOOK_TIMESTAMP_HEADER, timestamp_secs.into());
+        headers.insert(WEBHOOK_SIGNATURE_HEADER, signature.parse()?);
+        Ok(HttpRequest {
+            headers,
+            url: Url::parse(&endpoint.url)?,
+            method: Method::POST,
+            body: Some(delivery.payload.clone().into_bytes()),
+        })
+    }
+}
+
+#[async_trait]
+impl<RT: Runtime> DueJobs<RT> for WebhookDeliveryWorker<RT> {
+    /// The delivery and its endpoint, which is `None` if it's been deleted.
+    type Job = (ParsedDocument<WebhookDelivery>, Option<WebhookEndpoint>);
+    type Outcome = Result<u16, String>;
+
+    const BATCH_SIZE: usize = 64;
+    // Retry delays aren't tracked as a next due time, so check for deliveries
+    // whose delay has passed this often.
+    const MAX_IDLE_WAIT: Duration = Duration::from_secs(5);
+    const NAME: &'static str = "WebhookDeliveryWorker";
+    const WRITE_SOURCE: &'static str = "webhook_delivery";
+
+    fn concurrency(&self) -> usize {
+        *WEBHOOK_DELIVERY_CONCURRENCY
+    }
+
+    async fn list_due(
+        &self,
+        tx: &mut Transaction<RT>,
+        now_ms: i64,
+        limit: usize,
+    ) -> anyhow::Result<Vec<Self::Job>> {
+        let due = WebhookModel::new(tx).list_due(now_ms, limit).await?;
+        let mut jobs = Vec::with_capacity(due.len());
+        for delivery in due {
+            let endpoint = WebhookModel::new(tx)
+                .get_endpoint(delivery.endpoint_id)
+                .await?;
+            jobs.push((delivery, endpoint.map(|endpoint| endpoint.into_value())));
+        }
+        Ok(jobs)
+    }
+
+    async fn run(&self, job: &Self::Job, _now_ms: i64) -> Self::Outcome {
+        let (delivery, endpoint) = job;
+        match endpoint {
+            Some(endpoint) => self.send(delivery, endpoint).await,
+            None => Err("The webhook endpoint was deleted".to_string()),
+        }
+    }
+
+    async fn record(
+        &self,
+        tx: &mut Transaction<RT>,
+        job: Self::Job,
+        outcome: Self::Outcome,
+        now_ms: i64,
+    ) -> anyhow::Result<()> {
+        let (delivery, endpoint) = job;
+        let (id, delivery) = delivery.into_id_and_value();
+        let delivery = record_attempt(delivery, endpoint.is_some(), outcome, now_ms);
+        WebhookModel::new(tx).update_delivery(id, delivery).await
+    }
+}
+
+/// Applies the outcome of an attempt, scheduling another attempt with
+/// exponential backoff or marking the delivery as failed once it has used up
+/// its attempts.
+fn record_attempt(
+    mut delivery: WebhookDelivery,
+    endpoint_exists: bool,
+    outcome: Result<u16, String>,
+    now_ms: i64,
+) -> WebhookDelivery {
+    delivery.attempts += 1;
+    match outcome {
+        Ok(status) => {
+            delivery.state = WebhookDeliveryState::Delivered { status };
+            delivery.last_error = None;
+        },
+        Err(error) => {
+            tracing::warn!(
+                "Failed to deliver webhook event {}: {error}",
+                delivery.event_id
+            );
+            if !endpoint_exists || delivery.attempts >= *WEBHOOK_MAX_ATTEMPTS {
+                delivery.state = WebhookDeliveryState::Failed;
+            } else {
+                let delay = INITIAL_RETRY_DELAY
+                    .saturating_mul(1 << (delivery.attempts - 1).min(16))
+                    .min(MAX_RETRY_DELAY);
+                delivery.next_attempt_ms = now_ms + delay.as_millis() as i64;
+            }
+            delivery.last_error = Some(error);
+        },
+    }
+    delivery
+}
@@ -0,0 +1,56 @@
+use std::future::Future;
+
+use common::runtime::Runtime;
+use database::Database;
+use keybroker::Identity;
+use model::webhooks::{
+    types::WebhookEvent,
+    WebhookModel,
+};
+use tokio::sync::mpsc;
+
+use crate::batched_writer::batched_writer;
+
+/// Maximum number of events buffered for queueing. Events raised while the
+/// buffer is full are not sent.
+const BUFFER_SIZE: usize = 1000;
+const MAX_BATCH_SIZE: usize = 64;
+
+/// Queues webhook deliveries for events that happen outside of a transaction
+/// the delivery could be queued in, like function failures, in batches.
+pub struct WebhookEventsWriter;
+
+impl WebhookEventsWriter {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new<RT: Runtime>(
+        runtime: RT,
+        database: Database<RT>,
+    ) -> (mpsc::Sender<WebhookEvent>, impl Future<Output = ()> + Send) {
+        let write_runtime = runtime.clone();
+        batched_writer(
+            runtime,
+            "WebhookEventsWriter",
+            "webhook events",
+            BUFFER_SIZE,
+            MAX_BATCH_SIZE,
+            move |events| Self::write_batch(write_runtime.clone(), database.clone(), events),
+        )
+    }
+
+    async fn write_batch<RT: Runtime>(
+        runtime: RT,
+        database: Database<RT>,
+        events: Vec<WebhookEvent>,
+    ) -> anyhow::Result<()> {
+        let now_ms = runtime.unix_timestamp().as_ms_since_epoch()?.try_into()?;
+        let mut tx = database.begin(Identity::system()).await?;
+        let mut model = WebhookModel::new(&mut tx);
+        for event in &events {
+            model.enqueue_event(event, now_ms).await?;
+        }
+        database
+            .commit_with_write_source(tx, "webhook_events_writer")
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,12 @@
+//! Outbound webhooks: queueing deliveries for the events endpoints subscribe
+//! to and sending them. See `model::webhooks`.
+
+mod change_feed;
+mod delivery_worker;
+mod events_writer;
+
+pub use self::{
+    change_feed::WebhookChangeFeed,
+    delivery_worker::WebhookDeliveryWorker,
+    events_writer::WebhookEventsWriter,
+};
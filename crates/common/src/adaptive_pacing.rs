@@ -0,0 +1,229 @@
+//! Paces background work against live traffic. The committer reports how long
+//! persistence writes take and the persistence connection pools report how
+//! many of their connections are in use. Background workers (index backfill,
+//! retention, search compaction) scale their configured rate by
+//! [`pace_factor`], so they back off while user traffic is slow and catch up
+//! while the deployment is quiet.
+
+use std::{
+    num::NonZeroU32,
+    sync::LazyLock,
+    time::Duration,
+};
+
+use parking_lot::Mutex;
+use tokio::time::Instant;
+
+use crate::{
+    knobs::{
+        ADAPTIVE_PACING_DECAY,
+        ADAPTIVE_PACING_MAX_SPEEDUP,
+        ADAPTIVE_PACING_MIN_FACTOR,
+        ADAPTIVE_PACING_TARGET_COMMIT_LATENCY,
+        ADAPTIVE_PACING_TARGET_POOL_UTILIZATION,
+    },
+    metrics::{
+        log_adaptive_pacing_commit_latency,
+        log_adaptive_pacing_factor,
+        log_adaptive_pacing_pool_utilization,
+    },
+    runtime::Runtime,
+};
+
+/// Weight of a new sample in a signal's moving average.
+const SAMPLE_WEIGHT: f64 = 0.2;
+
+/// An exponentially weighted moving average that also decays toward zero while
+/// no samples arrive, so a burst of slow commits stops holding work back once
+/// traffic goes quiet.
+#[derive(Default)]
+struct Signal {
+    value: f64,
+    updated: Option<Instant>,
+}
+
+impl Signal {
+    fn value_at(&self, now: Instant, decay: Duration) -> f64 {
+        let Some(updated) = self.updated else {
+            return 0.0;
+        };
+        let idle = now.saturating_duration_since(updated).as_secs_f64();
+        self.value * (-idle / decay.as_secs_f64().max(f64::EPSILON)).exp()
+    }
+
+    fn record(&mut self, sample: f64, now: Instant, decay: Duration) {
+        let current = self.value_at(now, decay);
+        self.value = current + SAMPLE_WEIGHT * (sample - current);
+        self.updated = Some(now);
+    }
+}
+
+#[derive(Default)]
+struct TrafficSignals {
+    commit_latency_secs: Signal,
+    pool_utilization: Signal,
+}
+
+static TRAFFIC: LazyLock<Mutex<TrafficSignals>> =
+    LazyLock::new(|| Mutex::new(TrafficSignals::default()));
+
+/// Record how long a commit's write to persistence took.
+pub fn record_commit_latency(latency: Duration) {
+    TRAFFIC.lock().commit_latency_secs.record(
+        latency.as_secs_f64(),
+        Instant::now(),
+        *ADAPTIVE_PACING_DECAY,
+    );
+}
+
+/// Record how many of a persistence connection pool's connections are in use.
+pub fn record_pool_utilization(in_use: u64, capacity: u64) {
+    if capacity == 0 {
+        return;
+    }
+    TRAFFIC.lock().pool_utilization.record(
+        in_use as f64 / capacity as f64,
+        Instant::now(),
+        *ADAPTIVE_PACING_DECAY,
+    );
+}
+
+/// How fast background work should run relative to its configured rate right
+/// now, between `ADAPTIVE_PACING_MIN_FACTOR` and
+/// `ADAPTIVE_PACING_MAX_SPEEDUP`.
+pub fn pace_factor() -> f64 {
+    let (commit_latency, pool_utilization) = {
+        let traffic = TRAFFIC.lock();
+        let now = Instant::now();
+        (
+            Duration::from_secs_f64(
+                traffic
+                    .commit_latency_secs
+                    .value_at(now, *ADAPTIVE_PACING_DECAY),
+            ),
+            traffic
+                .pool_utilization
+                .value_at(now, *ADAPTIVE_PACING_DECAY),
+        )
+    };
+    log_adaptive_pacing_commit_latency(commit_latency);
+    log_adaptive_pacing_pool_utilization(pool_utilization);
+    pace_factor_for(commit_latency, pool_utilization)
+}
+
+/// The pace factor for the given traffic: inversely proportional to whichever
+/// of commit latency and pool utilization is furthest over its target.
+fn pace_factor_for(commit_latency: Duration, pool_utilization: f64) -> f64 {
+    let latency_pressure = commit_latency.as_secs_f64()
+        / ADAPTIVE_PACING_TARGET_COMMIT_LATENCY
+            .as_secs_f64()
+            .max(f64::EPSILON);
+    let pool_pressure =
+        pool_utilization / ADAPTIVE_PACING_TARGET_POOL_UTILIZATION.max(f64::EPSILON);
+    let min_factor = ADAPTIVE_PACING_MIN_FACTOR.clamp(f64::EPSILON, 1.0);
+    let max_speedup = ADAPTIVE_PACING_MAX_SPEEDUP.max(1.0);
+    (1.0 / latency_pressure.max(pool_pressure)).clamp(min_factor, max_speedup)
+}
+
+/// Scales a rate by the current pace factor, for work that takes its rate
+/// limit up front rather than pacing through an [`AdaptiveRateLimiter`].
+pub fn paced_rate(worker: &'static str, rate: NonZeroU32) -> NonZeroU32 {
+    let factor = pace_factor();
+    log_adaptive_pacing_factor(worker, factor);
+    let paced = (rate.get() as f64 * factor)
+        .round()
+        .clamp(1.0, u32::MAX as f64);
+    NonZeroU32::new(paced as u32).unwrap_or(NonZeroU32::MIN)
+}
+
+/// A rate limiter whose rate follows [`pace_factor`].
+pub struct AdaptiveRateLimiter<RT: Runtime> {
+    runtime: RT,
+    worker: &'static str,
+    base_per_second: f64,
+    next_available: Mutex<Option<Instant>>,
+}
+
+impl<RT: Runtime> AdaptiveRateLimiter<RT> {
+    /// Allows `base_per_second` units of work per second when traffic is at
+    /// its targets. `worker` labels the pacing metrics.
+    pub fn new(runtime: RT, worker: &'static str, base_per_second: f64) -> Self {
+        Self {
+            runtime,
+            worker,
+            base_per_second,
+            next_available: Mutex::new(None),
+        }
+    }
+
+    /// Waits until `cost` units of work are allowed. The first call returns
+    /// immediately, and each later call waits for the previous call's cost to
+    /// be paid off at the rate paced when it was made.
+    pub async fn acquire(&self, cost: u32) {
+        let factor = pace_factor();
+        log_adaptive_pacing_factor(self.worker, factor);
+        let interval = Duration::from_secs_f64(
+            cost as f64 / (self.base_per_second * factor).max(f64::EPSILON),
+        );
+        let now = self.runtime.monotonic_now();
+        let start = {
+            let mut next_available = self.next_available.lock();
+            let start = next_available.map_or(now, |next| next.max(now));
+            *next_available = Some(start + interval);
+            start
+        };
+        if start > now {
+            // NB: Wait on the runtime rather than a `tokio` timer so this works
+            // under the test runtime.
+            self.runtime.wait(start - now).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::time::Instant;
+
+    use super::{
+        pace_factor_for,
+        Signal,
+    };
+    use crate::knobs::{
+        ADAPTIVE_PACING_MAX_SPEEDUP,
+        ADAPTIVE_PACING_MIN_FACTOR,
+        ADAPTIVE_PACING_TARGET_COMMIT_LATENCY,
+    };
+
+    #[test]
+    fn test_pace_factor() {
+        let target = *ADAPTIVE_PACING_TARGET_COMMIT_LATENCY;
+        assert_eq!(
+            pace_factor_for(Duration::ZERO, 0.0),
+            *ADAPTIVE_PACING_MAX_SPEEDUP
+        );
+        assert_eq!(pace_factor_for(target, 0.0), 1.0);
+        assert_eq!(pace_factor_for(target * 2, 0.0), 0.5);
+        assert_eq!(
+            pace_factor_for(target * 1000, 0.0),
+            *ADAPTIVE_PACING_MIN_FACTOR
+        );
+        // A saturated pool slows work down even while commits are fast.
+        assert!(pace_factor_for(Duration::ZERO, 1.0) < 1.0);
+    }
+
+    #[test]
+    fn test_signal_decays_when_idle() {
+        let decay = Duration::from_secs(10);
+        let start = Instant::now();
+        let mut signal = Signal::default();
+        assert_eq!(signal.value_at(start, decay), 0.0);
+        for _ in 0..100 {
+            signal.record(1.0, start, decay);
+        }
+        assert!((signal.value_at(start, decay) - 1.0).abs() < 1e-6);
+        assert!(signal.value_at(start + decay, decay) < 0.4);
+        assert!(signal.value_at(start + decay * 10, decay) < 1e-3);
+    }
+}
@@ -250,8 +250,11 @@ mod tests {
         vector_index::{
             DeveloperVectorIndexConfig,
             FragmentedVectorSegment,
+            VectorDistanceMetric,
             VectorIndexBackfillState,
+            VectorIndexKind,
             VectorIndexState,
+            VectorQuantization,
         },
         IndexConfig,
     };
@@ -287,6 +290,10 @@ mod tests {
                     dimensions: 1536.try_into()?,
                     vector_field: "embedding.field".parse()?,
                     filter_fields: btreeset! { "filter1".parse()?, "filter2".parse()? },
+                    partition_field: None,
+                    distance_metric: VectorDistanceMetric::Cosine,
+                    index_kind: VectorIndexKind::Segmented,
+                    quantization: VectorQuantization::None,
                 },
                 on_disk_state: VectorIndexState::Backfilling(VectorIndexBackfillState {
                     cursor: None,
@@ -298,6 +305,7 @@ mod tests {
                         id: "jkl".to_string(),
                         num_vectors: 11,
                         num_deleted: 12,
+                        partitions: None,
                     }]
                 }),
             }
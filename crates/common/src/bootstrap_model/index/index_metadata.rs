@@ -1,5 +1,3 @@
-use std::collections::BTreeSet;
-
 use serde::{
     Deserialize,
     Serialize,
@@ -8,7 +6,6 @@ use sync_types::Timestamp;
 use value::{
     codegen_convex_serialization,
     ConvexValue,
-    FieldPath,
     TableName,
     TabletId,
 };
@@ -23,7 +20,6 @@ use super::{
     index_config::SerializedIndexConfig,
     vector_index::{
         DeveloperVectorIndexConfig,
-        VectorDimensions,
         VectorIndexBackfillState,
         VectorIndexState,
     },
@@ -81,33 +77,23 @@ impl<T: IndexTableIdentifier> IndexMetadata<T> {
 
     pub fn new_backfilling_text_index(
         name: GenericIndexName<T>,
-        search_field: FieldPath,
-        filter_fields: BTreeSet<FieldPath>,
+        developer_config: DeveloperTextIndexConfig,
     ) -> Self {
         Self::new_text_index(
             name,
-            DeveloperTextIndexConfig {
-                search_field,
-                filter_fields,
-            },
+            developer_config,
             TextIndexState::Backfilling(TextIndexBackfillState::new()),
         )
     }
 
     pub fn new_backfilling_vector_index(
         name: GenericIndexName<T>,
-        vector_field: FieldPath,
-        dimensions: VectorDimensions,
-        filter_fields: BTreeSet<FieldPath>,
+        developer_config: DeveloperVectorIndexConfig,
     ) -> Self {
         Self {
             name,
             config: IndexConfig::Vector {
-                developer_config: DeveloperVectorIndexConfig {
-                    dimensions,
-                    vector_field,
-                    filter_fields,
-                },
+                developer_config,
                 on_disk_state: VectorIndexState::Backfilling(VectorIndexBackfillState {
                     segments: vec![],
                     cursor: None,
@@ -2,6 +2,7 @@ use errors::ErrorMetadata;
 use value::TableName;
 
 use crate::{
+    bootstrap_model::index::MAX_SEARCH_FIELD_WEIGHT,
     paths::FieldPath,
     schemas::IndexSchema,
     types::{
@@ -154,6 +155,57 @@ pub fn too_many_filter_fields(num_fields: usize) -> ErrorMetadata {
         format!("Search indexes may have up to {num_fields} filter fields."),
     )
 }
+pub fn invalid_search_fields(reason: String) -> ErrorMetadata {
+    ErrorMetadata::bad_request(
+        "InvalidSearchFields",
+        format!("Invalid search index search fields: {reason}"),
+    )
+}
+pub fn invalid_search_field_weight(field_path: &FieldPath) -> ErrorMetadata {
+    invalid_search_fields(format!(
+        "The weight of {field_path:?} must be a whole number between 1 and \
+         {MAX_SEARCH_FIELD_WEIGHT}."
+    ))
+}
+pub fn invalid_text_analyzer(reason: String) -> ErrorMetadata {
+    ErrorMetadata::bad_request(
+        "InvalidTextAnalyzer",
+        format!("Invalid search index analyzer: {reason}"),
+    )
+}
+pub fn invalid_vector_distance_metric(reason: String) -> ErrorMetadata {
+    ErrorMetadata::bad_request(
+        "InvalidVectorDistanceMetric",
+        format!("Invalid vector index distance metric: {reason}"),
+    )
+}
+pub fn invalid_vector_index_kind(reason: String) -> ErrorMetadata {
+    ErrorMetadata::bad_request(
+        "InvalidVectorIndexKind",
+        format!("Invalid vector index kind: {reason}"),
+    )
+}
+pub fn invalid_vector_quantization(reason: String) -> ErrorMetadata {
+    ErrorMetadata::bad_request(
+        "InvalidVectorQuantization",
+        format!("Invalid vector index quantization: {reason}"),
+    )
+}
+pub fn invalid_vector_partition_field(field_path: &FieldPath) -> ErrorMetadata {
+    ErrorMetadata::bad_request(
+        "InvalidVectorPartitionField",
+        format!(
+            "Invalid vector index partition field: {field_path:?} must be one of the index's \
+             filter fields."
+        ),
+    )
+}
+pub fn invalid_vector_embedder(index: &IndexDescriptor, reason: &str) -> ErrorMetadata {
+    ErrorMetadata::bad_request(
+        "InvalidVectorEmbedder",
+        format!("Invalid embedder for vector index {index}: {reason}."),
+    )
+}
 pub fn too_many_indexes(table_name: &TableName, num_indexes: usize) -> ErrorMetadata {
     ErrorMetadata::bad_request(
         "TooManyIndexes",
@@ -40,4 +40,9 @@ pub static TABLE_ID_FIELD_PATH: LazyLock<FieldPath> =
 
 pub const MAX_INDEX_FIELDS_SIZE: usize = 16;
 pub const MAX_TEXT_INDEX_FILTER_FIELDS_SIZE: usize = 16;
+/// The most search fields a text index may have, including its `searchField`.
+pub const MAX_TEXT_INDEX_SEARCH_FIELDS_SIZE: usize = 8;
+/// The largest weight a text index may give a search field.
+pub const MAX_SEARCH_FIELD_WEIGHT: u32 = 10;
+pub const MAX_TEXT_ANALYZER_STOPWORDS: usize = 512;
 pub const MAX_VECTOR_INDEX_FILTER_FIELDS_SIZE: usize = 16;
@@ -0,0 +1,284 @@
+use std::collections::BTreeSet;
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::bootstrap_model::index::{
+    index_validation_error::invalid_text_analyzer,
+    MAX_TEXT_ANALYZER_STOPWORDS,
+};
+
+/// The longest stopword we accept. Longer words are never indexed anyway.
+const MAX_STOPWORD_LENGTH: usize = 32;
+
+/// The longest prefix of each word an autocomplete index stores.
+const MAX_AUTOCOMPLETE_GRAM: u32 = 20;
+
+/// How a text index splits its search field into terms. The same analysis is
+/// applied to documents when indexing them and to search text when compiling
+/// queries, so changing it requires rebuilding the index.
+///
+/// The default is the original analyzer: split into words of letters and
+/// numbers, drop words longer than 32 bytes and lowercase.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct TextAnalyzerConfig {
+    /// The language of the text, which picks the stemmer.
+    pub language: TextLanguage,
+    /// Reduce words to their stems so e.g. "running" matches "runs".
+    pub stemming: bool,
+    /// Lowercase words to leave out of the index and queries.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::collection::btree_set(\"[a-z]{1,8}\", 0..4)")
+    )]
+    pub stopwords: BTreeSet<String>,
+    pub segmentation: TextSegmentation,
+    /// Also index the prefixes of each word, so prefix searches can match
+    /// them exactly. Search text is never split into prefixes.
+    pub autocomplete: Option<AutocompleteConfig>,
+}
+
+impl TextAnalyzerConfig {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    strum::EnumString,
+    strum::Display,
+)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+#[strum(serialize_all = "camelCase")]
+pub enum TextLanguage {
+    Arabic,
+    Danish,
+    Dutch,
+    #[default]
+    English,
+    Finnish,
+    French,
+    German,
+    Greek,
+    Hungarian,
+    Italian,
+    Norwegian,
+    Portuguese,
+    Romanian,
+    Russian,
+    Spanish,
+    Swedish,
+    Tamil,
+    Turkish,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    strum::EnumString,
+    strum::Display,
+)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+#[strum(serialize_all = "camelCase")]
+pub enum TextSegmentation {
+    /// Split on anything that isn't a letter or number.
+    #[default]
+    Words,
+    /// Like `Words`, but runs of Chinese, Japanese or Korean characters,
+    /// which aren't separated by spaces, are split into overlapping pairs of
+    /// characters.
+    CjkBigrams,
+}
+
+/// The lengths of the word prefixes (edge n-grams) an autocomplete index
+/// stores. Whole words are always indexed too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct AutocompleteConfig {
+    #[cfg_attr(any(test, feature = "testing"), proptest(strategy = "1..=4u32"))]
+    pub min_gram: u32,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "4..=MAX_AUTOCOMPLETE_GRAM")
+    )]
+    pub max_gram: u32,
+}
+
+impl Default for AutocompleteConfig {
+    fn default() -> Self {
+        Self {
+            min_gram: 2,
+            max_gram: 15,
+        }
+    }
+}
+
+impl AutocompleteConfig {
+    pub fn new(min_gram: Option<u32>, max_gram: Option<u32>) -> anyhow::Result<Self> {
+        let default = Self::default();
+        let min_gram = min_gram.unwrap_or(default.min_gram);
+        let max_gram = max_gram.unwrap_or(default.max_gram);
+        if min_gram < 1 || min_gram > max_gram || max_gram > MAX_AUTOCOMPLETE_GRAM {
+            anyhow::bail!(invalid_text_analyzer(format!(
+                "Autocomplete prefixes must satisfy 1 <= minGram <= maxGram <= \
+                 {MAX_AUTOCOMPLETE_GRAM}, but got minGram {min_gram} and maxGram {max_gram}."
+            )));
+        }
+        Ok(Self { min_gram, max_gram })
+    }
+}
+
+/// The analyzer config in index metadata and schema JSON. Every field is
+/// optional and defaults to the default analyzer's.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct SerializedTextAnalyzerConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stemming: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stopwords: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    segmentation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    autocomplete: Option<SerializedAutocompleteConfig>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct SerializedAutocompleteConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min_gram: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_gram: Option<u32>,
+}
+
+impl From<TextAnalyzerConfig> for SerializedTextAnalyzerConfig {
+    fn from(config: TextAnalyzerConfig) -> Self {
+        Self {
+            language: Some(config.language.to_string()),
+            stemming: Some(config.stemming),
+            stopwords: Some(config.stopwords.into_iter().collect()),
+            segmentation: Some(config.segmentation.to_string()),
+            autocomplete: config
+                .autocomplete
+                .map(|autocomplete| SerializedAutocompleteConfig {
+                    min_gram: Some(autocomplete.min_gram),
+                    max_gram: Some(autocomplete.max_gram),
+                }),
+        }
+    }
+}
+
+impl TryFrom<SerializedTextAnalyzerConfig> for TextAnalyzerConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(config: SerializedTextAnalyzerConfig) -> anyhow::Result<Self> {
+        let language = match config.language {
+            Some(language) => language.parse().map_err(|_| {
+                invalid_text_analyzer(format!("{language:?} isn't a supported language."))
+            })?,
+            None => TextLanguage::default(),
+        };
+        let segmentation = match config.segmentation {
+            Some(segmentation) => segmentation.parse().map_err(|_| {
+                invalid_text_analyzer(format!(
+                    "{segmentation:?} isn't a supported segmentation. Use \"words\" or \
+                     \"cjkBigrams\"."
+                ))
+            })?,
+            None => TextSegmentation::default(),
+        };
+        let stopwords: BTreeSet<_> = config
+            .stopwords
+            .unwrap_or_default()
+            .into_iter()
+            .map(|word| word.to_lowercase())
+            .collect();
+        if stopwords.len() > MAX_TEXT_ANALYZER_STOPWORDS {
+            anyhow::bail!(invalid_text_analyzer(format!(
+                "Search indexes may have up to {MAX_TEXT_ANALYZER_STOPWORDS} stopwords."
+            )));
+        }
+        if let Some(word) = stopwords
+            .iter()
+            .find(|word| word.is_empty() || word.len() > MAX_STOPWORD_LENGTH)
+        {
+            anyhow::bail!(invalid_text_analyzer(format!(
+                "Stopword {word:?} must be between 1 and {MAX_STOPWORD_LENGTH} bytes long."
+            )));
+        }
+        let autocomplete = config
+            .autocomplete
+            .map(|autocomplete| {
+                AutocompleteConfig::new(autocomplete.min_gram, autocomplete.max_gram)
+            })
+            .transpose()?;
+        Ok(Self {
+            language,
+            stemming: config.stemming.unwrap_or_default(),
+            stopwords,
+            segmentation,
+            autocomplete,
+        })
+    }
+}
+
+impl TryFrom<pb::searchlight::TextAnalyzerConfig> for TextAnalyzerConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(proto: pb::searchlight::TextAnalyzerConfig) -> anyhow::Result<Self> {
+        SerializedTextAnalyzerConfig {
+            language: proto.language,
+            stemming: proto.stemming,
+            stopwords: Some(proto.stopwords),
+            segmentation: proto.segmentation,
+            autocomplete: proto
+                .autocomplete
+                .map(|autocomplete| SerializedAutocompleteConfig {
+                    min_gram: autocomplete.min_gram,
+                    max_gram: autocomplete.max_gram,
+                }),
+        }
+        .try_into()
+    }
+}
+
+impl From<TextAnalyzerConfig> for pb::searchlight::TextAnalyzerConfig {
+    fn from(config: TextAnalyzerConfig) -> Self {
+        pb::searchlight::TextAnalyzerConfig {
+            language: Some(config.language.to_string()),
+            stemming: Some(config.stemming),
+            stopwords: config.stopwords.into_iter().collect(),
+            segmentation: Some(config.segmentation.to_string()),
+            autocomplete: config.autocomplete.map(|autocomplete| {
+                pb::searchlight::AutocompleteConfig {
+                    min_gram: Some(autocomplete.min_gram),
+                    max_gram: Some(autocomplete.max_gram),
+                }
+            }),
+        }
+    }
+}
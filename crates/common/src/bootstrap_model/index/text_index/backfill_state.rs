@@ -21,6 +21,13 @@ pub struct TextIndexBackfillState {
     pub segments: Vec<FragmentedTextSegment>,
     // None at the start of backfill, then set after the first backfill iteration.
     pub cursor: Option<TextBackfillCursor>,
+    // The number of documents in the table as of the last backfill iteration, for reporting
+    // progress. None until the first backfill iteration.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::option::of(0u64..9223372000000000000)")
+    )]
+    pub total_documents: Option<u64>,
 }
 
 impl TextIndexBackfillState {
@@ -28,8 +35,21 @@ impl TextIndexBackfillState {
         Self {
             segments: vec![],
             cursor: None,
+            total_documents: None,
         }
     }
+
+    /// The number of documents indexed so far.
+    pub fn num_documents_indexed(&self) -> u64 {
+        self.segments
+            .iter()
+            .map(|segment| {
+                segment
+                    .num_indexed_documents
+                    .saturating_sub(segment.num_deleted_documents)
+            })
+            .sum()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -69,6 +89,7 @@ impl TryFrom<SerializedTextBackfillCursor> for TextBackfillCursor {
 pub struct SerializedTextIndexBackfillState {
     segments: Option<Vec<SerializedFragmentedTextSegment>>,
     cursor: Option<SerializedTextBackfillCursor>,
+    total_documents: Option<i64>,
 }
 
 impl TryFrom<TextIndexBackfillState> for SerializedTextIndexBackfillState {
@@ -86,6 +107,10 @@ impl TryFrom<TextIndexBackfillState> for SerializedTextIndexBackfillState {
             cursor: backfill_state
                 .cursor
                 .map(SerializedTextBackfillCursor::from),
+            total_documents: backfill_state
+                .total_documents
+                .map(i64::try_from)
+                .transpose()?,
         })
     }
 }
@@ -105,6 +130,7 @@ impl TryFrom<SerializedTextIndexBackfillState> for TextIndexBackfillState {
                 .cursor
                 .map(TextBackfillCursor::try_from)
                 .transpose()?,
+            total_documents: serialized.total_documents.map(u64::try_from).transpose()?,
         })
     }
 }
@@ -1,4 +1,7 @@
-use std::collections::BTreeSet;
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+};
 
 use serde::{
     Deserialize,
@@ -6,6 +9,10 @@ use serde::{
 };
 use value::codegen_convex_serialization;
 
+use super::{
+    SerializedTextAnalyzerConfig,
+    TextAnalyzerConfig,
+};
 use crate::paths::FieldPath;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -14,8 +21,58 @@ pub struct DeveloperTextIndexConfig {
     /// The field to index for full text search.
     pub search_field: FieldPath,
 
+    /// More fields to search along with `search_field`. Search queries name
+    /// `search_field` and match text in any of the search fields.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::collection::btree_set(proptest::arbitrary::any::<FieldPath>(), \
+                        0..4)"
+        )
+    )]
+    pub extra_search_fields: BTreeSet<FieldPath>,
+
+    /// How many times matches in each search field count when scoring
+    /// results. Search fields without a weight count once.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::collection::btree_map(proptest::arbitrary::any::<FieldPath>(), \
+                        1..=10u32, 0..4)"
+        )
+    )]
+    pub search_field_weights: BTreeMap<FieldPath, u32>,
+
     /// Other fields to index for equality filtering.
     pub filter_fields: BTreeSet<FieldPath>,
+
+    /// How to split the search field into terms.
+    pub analyzer: TextAnalyzerConfig,
+}
+
+impl DeveloperTextIndexConfig {
+    /// An index over a single search field with the default analyzer.
+    pub fn new(search_field: FieldPath, filter_fields: BTreeSet<FieldPath>) -> Self {
+        Self {
+            search_field,
+            extra_search_fields: BTreeSet::new(),
+            search_field_weights: BTreeMap::new(),
+            filter_fields,
+            analyzer: TextAnalyzerConfig::default(),
+        }
+    }
+
+    /// All of the fields the index searches, starting with `search_field`.
+    pub fn search_fields(&self) -> impl Iterator<Item = &FieldPath> {
+        std::iter::once(&self.search_field).chain(&self.extra_search_fields)
+    }
+
+    pub fn search_field_weight(&self, field_path: &FieldPath) -> u32 {
+        self.search_field_weights
+            .get(field_path)
+            .copied()
+            .unwrap_or(1)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -23,7 +80,24 @@ pub struct DeveloperTextIndexConfig {
 #[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
 pub struct SerializedDeveloperTextIndexConfig {
     search_field: String,
+    // Only present for indexes with more than one search field.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    extra_search_fields: Option<Vec<String>>,
+    // Only present for indexes that weight their search fields.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    search_field_weights: Option<Vec<SerializedSearchFieldWeight>>,
     filter_fields: Vec<String>,
+    // Only present for indexes with a non-default analyzer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    analyzer: Option<SerializedTextAnalyzerConfig>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct SerializedSearchFieldWeight {
+    field_path: String,
+    weight: i64,
 }
 
 impl TryFrom<DeveloperTextIndexConfig> for SerializedDeveloperTextIndexConfig {
@@ -32,7 +106,25 @@ impl TryFrom<DeveloperTextIndexConfig> for SerializedDeveloperTextIndexConfig {
     fn try_from(config: DeveloperTextIndexConfig) -> anyhow::Result<Self> {
         Ok(Self {
             search_field: config.search_field.into(),
+            extra_search_fields: (!config.extra_search_fields.is_empty()).then(|| {
+                config
+                    .extra_search_fields
+                    .into_iter()
+                    .map(String::from)
+                    .collect()
+            }),
+            search_field_weights: (!config.search_field_weights.is_empty()).then(|| {
+                config
+                    .search_field_weights
+                    .into_iter()
+                    .map(|(field_path, weight)| SerializedSearchFieldWeight {
+                        field_path: field_path.into(),
+                        weight: weight.into(),
+                    })
+                    .collect()
+            }),
             filter_fields: config.filter_fields.into_iter().map(String::from).collect(),
+            analyzer: (!config.analyzer.is_default()).then(|| config.analyzer.into()),
         })
     }
 }
@@ -43,11 +135,28 @@ impl TryFrom<SerializedDeveloperTextIndexConfig> for DeveloperTextIndexConfig {
     fn try_from(config: SerializedDeveloperTextIndexConfig) -> anyhow::Result<Self> {
         Ok(Self {
             search_field: config.search_field.parse()?,
+            extra_search_fields: config
+                .extra_search_fields
+                .unwrap_or_default()
+                .into_iter()
+                .map(|p| p.parse())
+                .collect::<anyhow::Result<BTreeSet<FieldPath>>>()?,
+            search_field_weights: config
+                .search_field_weights
+                .unwrap_or_default()
+                .into_iter()
+                .map(|w| anyhow::Ok((w.field_path.parse()?, u32::try_from(w.weight)?)))
+                .collect::<anyhow::Result<BTreeMap<FieldPath, u32>>>()?,
             filter_fields: config
                 .filter_fields
                 .into_iter()
                 .map(|p| p.parse())
                 .collect::<anyhow::Result<BTreeSet<FieldPath>>>()?,
+            analyzer: config
+                .analyzer
+                .map(TextAnalyzerConfig::try_from)
+                .transpose()?
+                .unwrap_or_default(),
         })
     }
 }
@@ -63,6 +172,22 @@ impl TryFrom<pb::searchlight::SearchIndexConfig> for DeveloperTextIndexConfig {
                 .search_field_path
                 .ok_or_else(|| anyhow::format_err!("Missing search_field_path"))?
                 .try_into()?,
+            extra_search_fields: proto
+                .extra_search_fields
+                .into_iter()
+                .map(|i| i.try_into())
+                .collect::<anyhow::Result<_>>()?,
+            search_field_weights: proto
+                .search_field_weights
+                .into_iter()
+                .map(|w| {
+                    let path = w
+                        .path
+                        .ok_or_else(|| anyhow::format_err!("Missing path"))?
+                        .try_into()?;
+                    anyhow::Ok((path, w.weight))
+                })
+                .collect::<anyhow::Result<_>>()?,
             filter_fields: proto
                 .filter_fields
                 .into_iter()
@@ -70,6 +195,11 @@ impl TryFrom<pb::searchlight::SearchIndexConfig> for DeveloperTextIndexConfig {
                 .collect::<Result<Vec<_>, _>>()?
                 .into_iter()
                 .collect(),
+            analyzer: proto
+                .analyzer
+                .map(TextAnalyzerConfig::try_from)
+                .transpose()?
+                .unwrap_or_default(),
         })
     }
 }
@@ -78,11 +208,25 @@ impl From<DeveloperTextIndexConfig> for pb::searchlight::SearchIndexConfig {
     fn from(config: DeveloperTextIndexConfig) -> Self {
         pb::searchlight::SearchIndexConfig {
             search_field_path: Some(config.search_field.into()),
+            extra_search_fields: config
+                .extra_search_fields
+                .into_iter()
+                .map(|f| f.into())
+                .collect(),
+            search_field_weights: config
+                .search_field_weights
+                .into_iter()
+                .map(|(path, weight)| pb::searchlight::SearchFieldWeight {
+                    path: Some(path.into()),
+                    weight,
+                })
+                .collect(),
             filter_fields: config
                 .filter_fields
                 .into_iter()
                 .map(|f| f.into())
                 .collect::<Vec<_>>(),
+            analyzer: Some(config.analyzer.into()),
         }
     }
 }
@@ -1,9 +1,17 @@
+mod analyzer_config;
 mod backfill_state;
 mod index_config;
 mod index_snapshot;
 mod index_state;
 
 pub use self::{
+    analyzer_config::{
+        AutocompleteConfig,
+        SerializedTextAnalyzerConfig,
+        TextAnalyzerConfig,
+        TextLanguage,
+        TextSegmentation,
+    },
     backfill_state::{
         TextBackfillCursor,
         TextIndexBackfillState,
@@ -0,0 +1,53 @@
+use crate::bootstrap_model::index::index_validation_error::invalid_vector_distance_metric;
+
+/// How a vector index compares vectors. Higher scores are always closer, so
+/// changing the metric changes which results come first and requires
+/// rebuilding the index.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    strum::EnumString,
+    strum::Display,
+)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+#[strum(serialize_all = "camelCase")]
+pub enum VectorDistanceMetric {
+    /// The cosine of the angle between the vectors, from -1 to 1. Vectors are
+    /// normalized to unit length before indexing and searching.
+    #[default]
+    Cosine,
+    /// The dot product of the vectors as they are, for embeddings where the
+    /// magnitude is meaningful.
+    DotProduct,
+    /// The negated squared Euclidean (L2) distance between the vectors, so
+    /// identical vectors score 0 and farther vectors score lower.
+    Euclidean,
+}
+
+impl VectorDistanceMetric {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Parses the metric from index metadata, schema JSON or protos, where a
+    /// missing metric means the original cosine metric.
+    pub fn parse(metric: Option<&str>) -> anyhow::Result<Self> {
+        let Some(metric) = metric else {
+            return Ok(Self::default());
+        };
+        metric.parse().map_err(|_| {
+            invalid_vector_distance_metric(format!(
+                "{metric:?} isn't a supported distance metric. Use \"cosine\", \"dotProduct\" or \
+                 \"euclidean\"."
+            ))
+            .into()
+        })
+    }
+}
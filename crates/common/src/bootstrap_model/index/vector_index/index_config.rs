@@ -9,7 +9,12 @@ use value::{
     FieldPath,
 };
 
-use super::VectorDimensions;
+use super::{
+    VectorDimensions,
+    VectorDistanceMetric,
+    VectorIndexKind,
+    VectorQuantization,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
@@ -22,6 +27,19 @@ pub struct DeveloperVectorIndexConfig {
 
     /// Other fields to index for equality filtering.
     pub filter_fields: BTreeSet<FieldPath>,
+
+    /// A filter field, like a tenant id, that segments track the values of so
+    /// that searches filtering on it skip segments without a matching value.
+    pub partition_field: Option<FieldPath>,
+
+    /// How to compare vectors when indexing and searching.
+    pub distance_metric: VectorDistanceMetric,
+
+    /// How to lay out the index's disk segments.
+    pub index_kind: VectorIndexKind,
+
+    /// How to compress the vectors that HNSW graphs search over.
+    pub quantization: VectorQuantization,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -33,6 +51,17 @@ pub struct SerializedDeveloperVectorIndexConfig {
     dimensions: i64,
     vector_field: String,
     filter_fields: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    partition_field: Option<String>,
+    // Only present for indexes with a non-cosine distance metric.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    distance_metric: Option<String>,
+    // Only present for indexes that aren't segmented.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    index_kind: Option<String>,
+    // Only present for quantized indexes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    quantization: Option<String>,
 }
 
 impl TryFrom<DeveloperVectorIndexConfig> for SerializedDeveloperVectorIndexConfig {
@@ -43,6 +72,12 @@ impl TryFrom<DeveloperVectorIndexConfig> for SerializedDeveloperVectorIndexConfi
             dimensions: u32::from(config.dimensions) as i64,
             vector_field: config.vector_field.into(),
             filter_fields: config.filter_fields.into_iter().map(String::from).collect(),
+            partition_field: config.partition_field.map(String::from),
+            distance_metric: (!config.distance_metric.is_default())
+                .then(|| config.distance_metric.to_string()),
+            index_kind: (!config.index_kind.is_default()).then(|| config.index_kind.to_string()),
+            quantization: (!config.quantization.is_default())
+                .then(|| config.quantization.to_string()),
         })
     }
 }
@@ -59,6 +94,10 @@ impl TryFrom<SerializedDeveloperVectorIndexConfig> for DeveloperVectorIndexConfi
                 .into_iter()
                 .map(|p| p.parse())
                 .collect::<anyhow::Result<BTreeSet<FieldPath>>>()?,
+            partition_field: config.partition_field.map(|p| p.parse()).transpose()?,
+            distance_metric: VectorDistanceMetric::parse(config.distance_metric.as_deref())?,
+            index_kind: VectorIndexKind::parse(config.index_kind.as_deref())?,
+            quantization: VectorQuantization::parse(config.quantization.as_deref())?,
         })
     }
 }
@@ -85,6 +124,13 @@ impl TryFrom<pb::searchlight::VectorIndexConfig> for DeveloperVectorIndexConfig
                 .collect::<Result<Vec<_>, _>>()?
                 .into_iter()
                 .collect(),
+            partition_field: proto
+                .partition_field_path
+                .map(|p| p.try_into())
+                .transpose()?,
+            distance_metric: VectorDistanceMetric::parse(proto.distance_metric.as_deref())?,
+            index_kind: VectorIndexKind::parse(proto.index_kind.as_deref())?,
+            quantization: VectorQuantization::parse(proto.quantization.as_deref())?,
         })
     }
 }
@@ -99,6 +145,10 @@ impl From<DeveloperVectorIndexConfig> for pb::searchlight::VectorIndexConfig {
                 .into_iter()
                 .map(|f| f.into())
                 .collect::<Vec<_>>(),
+            partition_field_path: config.partition_field.map(|f| f.into()),
+            distance_metric: Some(config.distance_metric.to_string()),
+            index_kind: Some(config.index_kind.to_string()),
+            quantization: Some(config.quantization.to_string()),
         }
     }
 }
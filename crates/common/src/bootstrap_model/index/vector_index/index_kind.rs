@@ -0,0 +1,49 @@
+use crate::bootstrap_model::index::index_validation_error::invalid_vector_index_kind;
+
+/// How a vector index lays out its disk segments. Changing the kind requires
+/// rebuilding the index.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    strum::EnumString,
+    strum::Display,
+)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+#[strum(serialize_all = "camelCase")]
+pub enum VectorIndexKind {
+    /// Small segments are searched by scanning every vector, and larger ones
+    /// get an HNSW graph that's memory-mapped from disk when searched.
+    #[default]
+    Segmented,
+    /// Every segment gets an HNSW graph, however small, and the graphs are
+    /// loaded into memory when a segment is opened. This trades memory for
+    /// lower and more predictable search latency.
+    Hnsw,
+}
+
+impl VectorIndexKind {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Parses the kind from index metadata, schema JSON or protos, where a
+    /// missing kind means the original segmented layout.
+    pub fn parse(kind: Option<&str>) -> anyhow::Result<Self> {
+        let Some(kind) = kind else {
+            return Ok(Self::default());
+        };
+        kind.parse().map_err(|_| {
+            invalid_vector_index_kind(format!(
+                "{kind:?} isn't a supported vector index kind. Use \"segmented\" or \"hnsw\"."
+            ))
+            .into()
+        })
+    }
+}
@@ -1,8 +1,11 @@
 mod backfill_state;
 mod dimensions;
+mod distance_metric;
 mod index_config;
+mod index_kind;
 mod index_snapshot;
 mod index_state;
+mod quantization;
 mod segment;
 
 pub use self::{
@@ -12,10 +15,12 @@ pub use self::{
         MAX_VECTOR_DIMENSIONS,
         MIN_VECTOR_DIMENSIONS,
     },
+    distance_metric::VectorDistanceMetric,
     index_config::{
         DeveloperVectorIndexConfig,
         SerializedDeveloperVectorIndexConfig,
     },
+    index_kind::VectorIndexKind,
     index_snapshot::{
         VectorIndexSnapshot,
         VectorIndexSnapshotData,
@@ -24,6 +29,7 @@ pub use self::{
         SerializedVectorIndexState,
         VectorIndexState,
     },
+    quantization::VectorQuantization,
     segment::FragmentedVectorSegment,
 };
 
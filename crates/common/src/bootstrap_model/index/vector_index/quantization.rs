@@ -0,0 +1,53 @@
+use crate::bootstrap_model::index::index_validation_error::invalid_vector_quantization;
+
+/// How a vector index compresses the vectors its HNSW graphs search over.
+/// Quantized searches re-rank their top candidates against the full-precision
+/// vectors, so compression mostly costs recall at the margins rather than
+/// result order. Changing the quantization requires rebuilding the index.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    strum::EnumString,
+    strum::Display,
+)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+#[strum(serialize_all = "camelCase")]
+pub enum VectorQuantization {
+    /// Search the full-precision `f32` vectors.
+    #[default]
+    None,
+    /// Store each component as an `i8`, a quarter of the memory.
+    Scalar,
+    /// Product quantization, which encodes chunks of each vector as an index
+    /// into a learned codebook. Uses a sixteenth of the memory but loses more
+    /// recall than scalar quantization.
+    Product,
+}
+
+impl VectorQuantization {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Parses the quantization from index metadata, schema JSON or protos,
+    /// where a missing quantization means full precision.
+    pub fn parse(quantization: Option<&str>) -> anyhow::Result<Self> {
+        let Some(quantization) = quantization else {
+            return Ok(Self::default());
+        };
+        quantization.parse().map_err(|_| {
+            invalid_vector_quantization(format!(
+                "{quantization:?} isn't a supported vector quantization. Use \"none\", \"scalar\" \
+                 or \"product\"."
+            ))
+            .into()
+        })
+    }
+}
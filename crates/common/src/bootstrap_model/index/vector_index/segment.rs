@@ -1,4 +1,7 @@
-use std::collections::BTreeMap;
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+};
 
 use anyhow::Context;
 use serde::{
@@ -6,6 +9,7 @@ use serde::{
     Serialize,
 };
 use value::{
+    base64,
     ConvexValue,
     FieldName,
 };
@@ -31,6 +35,10 @@ pub struct FragmentedVectorSegment {
     // A random UUID that can be used to identify a segment to determine if the
     // segment has changed during non-transactional index changes (compaction).
     pub id: String,
+    /// The encoded values of the index's partition field that the segment's
+    /// vectors had when it was built, or `None` if the index isn't partitioned
+    /// or the segment may contain vectors from any partition.
+    pub partitions: Option<BTreeSet<Vec<u8>>>,
 }
 
 impl FragmentedVectorSegment {
@@ -46,6 +54,14 @@ impl FragmentedVectorSegment {
         }
     }
 
+    /// Whether searches filtering the partition field to one of `partitions`
+    /// need to search this segment.
+    pub fn may_contain_partitions(&self, partitions: &BTreeSet<Vec<u8>>) -> bool {
+        self.partitions
+            .as_ref()
+            .is_none_or(|own| !own.is_disjoint(partitions))
+    }
+
     pub fn non_deleted_vectors(&self) -> anyhow::Result<u64> {
         let total_vectors = if self.num_vectors < self.num_deleted {
             // Some early segments have been created with num_vectors sent to the initially
@@ -123,6 +139,11 @@ impl From<FragmentedVectorSegment> for pb::searchlight::FragmentedVectorSegment
             num_vectors: value.num_vectors,
             num_deleted: value.num_deleted,
             id: value.id,
+            partitions: value.partitions.map(|partitions| {
+                pb::searchlight::VectorSegmentPartitions {
+                    partition_keys: partitions.into_iter().collect(),
+                }
+            }),
         }
     }
 }
@@ -138,6 +159,9 @@ impl TryFrom<pb::searchlight::FragmentedVectorSegment> for FragmentedVectorSegme
             num_vectors: value.num_vectors,
             num_deleted: value.num_deleted,
             id: value.id,
+            partitions: value
+                .partitions
+                .map(|partitions| partitions.partition_keys.into_iter().collect()),
         })
     }
 }
@@ -150,6 +174,8 @@ pub struct SerializedFragmentedVectorSegment {
     pub num_vectors: i64,
     pub num_deleted: i64,
     pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partitions: Option<Vec<String>>,
 }
 
 impl TryFrom<FragmentedVectorSegment> for SerializedFragmentedVectorSegment {
@@ -163,6 +189,12 @@ impl TryFrom<FragmentedVectorSegment> for SerializedFragmentedVectorSegment {
             num_vectors: value.num_vectors as i64,
             num_deleted: value.num_deleted as i64,
             id: value.id,
+            partitions: value.partitions.map(|partitions| {
+                partitions
+                    .iter()
+                    .map(|partition| base64::encode_urlsafe(partition))
+                    .collect()
+            }),
         })
     }
 }
@@ -178,6 +210,15 @@ impl TryFrom<SerializedFragmentedVectorSegment> for FragmentedVectorSegment {
             num_vectors: value.num_vectors.try_into()?,
             num_deleted: value.num_deleted.try_into()?,
             id: value.id,
+            partitions: value
+                .partitions
+                .map(|partitions| {
+                    partitions
+                        .iter()
+                        .map(|partition| base64::decode_urlsafe(partition))
+                        .collect()
+                })
+                .transpose()?,
         })
     }
 }
@@ -4,6 +4,11 @@ use serde::{
 };
 use value::codegen_convex_serialization;
 
+use crate::schemas::violations::{
+    SchemaViolationReport,
+    SerializedSchemaViolationReport,
+};
+
 /// SchemaState state machine:
 /// ```text
 /// +----------+------------------|
@@ -33,6 +38,9 @@ pub enum SchemaState {
     Failed {
         error: String,
         table_name: Option<String>,
+        /// Existing documents that don't match the schema, if it failed
+        /// validating them.
+        report: Option<SchemaViolationReport>,
     },
     Overwritten,
 }
@@ -55,6 +63,8 @@ pub enum SerializedSchemaState {
     Failed {
         error: String,
         table_name: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        report: Option<SerializedSchemaViolationReport>,
     },
     Overwritten,
 }
@@ -67,7 +77,15 @@ impl TryFrom<SchemaState> for SerializedSchemaState {
             SchemaState::Pending => Self::Pending,
             SchemaState::Validated => Self::Validated,
             SchemaState::Active => Self::Active,
-            SchemaState::Failed { error, table_name } => Self::Failed { error, table_name },
+            SchemaState::Failed {
+                error,
+                table_name,
+                report,
+            } => Self::Failed {
+                error,
+                table_name,
+                report: report.map(SerializedSchemaViolationReport::from),
+            },
             SchemaState::Overwritten => Self::Overwritten,
         })
     }
@@ -81,8 +99,14 @@ impl TryFrom<SerializedSchemaState> for SchemaState {
             SerializedSchemaState::Pending => Self::Pending,
             SerializedSchemaState::Validated => Self::Validated,
             SerializedSchemaState::Active => Self::Active,
-            SerializedSchemaState::Failed { error, table_name } => {
-                Self::Failed { error, table_name }
+            SerializedSchemaState::Failed {
+                error,
+                table_name,
+                report,
+            } => Self::Failed {
+                error,
+                table_name,
+                report: report.map(SchemaViolationReport::from),
             },
             SerializedSchemaState::Overwritten => Self::Overwritten,
         })
@@ -112,7 +136,8 @@ mod tests {
             deserialized,
             SchemaState::Failed {
                 error: "dis failed".to_string(),
-                table_name: None
+                table_name: None,
+                report: None,
             }
         );
         Ok(())
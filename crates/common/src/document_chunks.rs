@@ -0,0 +1,214 @@
+//! Storage encoding for documents with large top-level fields.
+//!
+//! Persistence backends that support chunking store each top-level field
+//! larger than [`MAX_INLINE_FIELD_SIZE`] outside of the document's row, split
+//! into [`FieldChunk`]s of serialized JSON. The document row keeps a
+//! `{"$chunked": <number of chunks>}` marker in the field's place, which can't
+//! collide with a real value since `$`-prefixed object keys are reserved by the
+//! JSON encoding.
+//!
+//! Documents without large fields encode exactly as
+//! [`ConvexObject::json_serialize`] does, so readers only need to fetch chunks
+//! for rows that contain markers.
+
+use serde_json::{
+    json,
+    Map,
+    Value as JsonValue,
+};
+use value::{
+    ConvexObject,
+    Size,
+    MAX_INLINE_FIELD_SIZE,
+};
+
+pub const CHUNKED_FIELD_KEY: &str = "$chunked";
+
+/// Bytes of serialized JSON stored per chunk row.
+pub const CHUNK_SIZE: usize = 1 << 18;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldChunk {
+    pub field: String,
+    pub index: u32,
+    pub json: String,
+}
+
+pub struct ChunkedDocument {
+    /// The document's JSON, with chunked fields replaced by markers.
+    pub json_value: String,
+    pub chunks: Vec<FieldChunk>,
+}
+
+pub fn chunk_document(document: &ConvexObject) -> anyhow::Result<ChunkedDocument> {
+    if document
+        .values()
+        .all(|value| value.size() <= MAX_INLINE_FIELD_SIZE)
+    {
+        return Ok(ChunkedDocument {
+            json_value: document.json_serialize()?,
+            chunks: vec![],
+        });
+    }
+    let mut fields = Map::new();
+    let mut chunks = vec![];
+    for (field, value) in document.iter() {
+        if value.size() <= MAX_INLINE_FIELD_SIZE {
+            fields.insert(field.to_string(), value.to_internal_json());
+            continue;
+        }
+        let pieces = split_at_char_boundaries(&value.json_serialize()?, CHUNK_SIZE);
+        fields.insert(
+            field.to_string(),
+            json!({ CHUNKED_FIELD_KEY: pieces.len() }),
+        );
+        for (index, json) in pieces.into_iter().enumerate() {
+            chunks.push(FieldChunk {
+                field: field.to_string(),
+                index: index.try_into()?,
+                json: json.to_owned(),
+            });
+        }
+    }
+    Ok(ChunkedDocument {
+        json_value: serde_json::to_string(&JsonValue::Object(fields))?,
+        chunks,
+    })
+}
+
+/// Whether a stored document's JSON has any chunked fields.
+pub fn has_chunked_fields(json_value: &JsonValue) -> bool {
+    match json_value {
+        JsonValue::Object(fields) => fields.values().any(|v| chunk_count(v).is_some()),
+        _ => false,
+    }
+}
+
+/// Replaces chunk markers in a stored document with the field values.
+/// `load_chunks` is called with a field name and its number of chunks, and
+/// must return the chunks' JSON in order. It's only called for documents that
+/// have chunked fields.
+pub fn unchunk_document(
+    json_value: JsonValue,
+    mut load_chunks: impl FnMut(&str, usize) -> anyhow::Result<Vec<String>>,
+) -> anyhow::Result<JsonValue> {
+    let JsonValue::Object(mut fields) = json_value else {
+        return Ok(json_value);
+    };
+    for (field, value) in fields.iter_mut() {
+        let Some(count) = chunk_count(value) else {
+            continue;
+        };
+        let pieces = load_chunks(field, count)?;
+        anyhow::ensure!(
+            pieces.len() == count,
+            "Expected {count} chunks for field {field}, found {}",
+            pieces.len()
+        );
+        *value = serde_json::from_str(&pieces.concat())?;
+    }
+    Ok(JsonValue::Object(fields))
+}
+
+fn chunk_count(value: &JsonValue) -> Option<usize> {
+    let JsonValue::Object(marker) = value else {
+        return None;
+    };
+    if marker.len() != 1 {
+        return None;
+    }
+    marker
+        .get(CHUNKED_FIELD_KEY)?
+        .as_u64()
+        .and_then(|count| count.try_into().ok())
+}
+
+fn split_at_char_boundaries(s: &str, max_len: usize) -> Vec<&str> {
+    let mut pieces = vec![];
+    let mut rest = s;
+    while !rest.is_empty() {
+        let mut end = max_len.min(rest.len());
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (piece, tail) = rest.split_at(end);
+        pieces.push(piece);
+        rest = tail;
+    }
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde_json::Value as JsonValue;
+    use value::{
+        assert_obj,
+        ConvexObject,
+        ConvexValue,
+        MAX_INLINE_FIELD_SIZE,
+    };
+
+    use super::{
+        chunk_document,
+        has_chunked_fields,
+        split_at_char_boundaries,
+        unchunk_document,
+        CHUNK_SIZE,
+    };
+
+    fn roundtrip(document: &ConvexObject) -> anyhow::Result<(ConvexObject, usize)> {
+        let chunked = chunk_document(document)?;
+        let mut by_field: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for chunk in &chunked.chunks {
+            let pieces = by_field.entry(chunk.field.clone()).or_default();
+            assert_eq!(pieces.len(), chunk.index as usize);
+            pieces.push(chunk.json.clone());
+        }
+        let json_value: JsonValue = serde_json::from_str(&chunked.json_value)?;
+        let json_value = unchunk_document(json_value, |field, count| {
+            let pieces = by_field.remove(field).unwrap_or_default();
+            assert_eq!(pieces.len(), count);
+            Ok(pieces)
+        })?;
+        Ok((json_value.try_into()?, chunked.chunks.len()))
+    }
+
+    #[test]
+    fn test_small_documents_are_not_chunked() -> anyhow::Result<()> {
+        let document = assert_obj!("name" => "small", "count" => 3);
+        let chunked = chunk_document(&document)?;
+        assert!(chunked.chunks.is_empty());
+        assert_eq!(chunked.json_value, document.json_serialize()?);
+        assert!(!has_chunked_fields(&serde_json::from_str(
+            &chunked.json_value
+        )?));
+        Ok(())
+    }
+
+    #[test]
+    fn test_large_fields_roundtrip() -> anyhow::Result<()> {
+        let text = "é".repeat(MAX_INLINE_FIELD_SIZE);
+        let numbers: Vec<ConvexValue> = (0..MAX_INLINE_FIELD_SIZE as i64 / 4)
+            .map(ConvexValue::from)
+            .collect();
+        let document = assert_obj!(
+            "text" => text.as_str(),
+            "numbers" => numbers,
+            "small" => "inline",
+        );
+        let (roundtripped, num_chunks) = roundtrip(&document)?;
+        assert!(num_chunks > 2);
+        assert_eq!(roundtripped, document);
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_at_char_boundaries() {
+        let s = "aé".repeat(CHUNK_SIZE);
+        let pieces = split_at_char_boundaries(&s, CHUNK_SIZE);
+        assert!(pieces.iter().all(|p| p.len() <= CHUNK_SIZE));
+        assert_eq!(pieces.concat(), s);
+    }
+}
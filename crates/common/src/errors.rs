@@ -39,7 +39,13 @@ use value::{
     ConvexValue,
 };
 
-use crate::metrics::log_errors_reported_total;
+use crate::{
+    components::ComponentPath,
+    metrics::log_errors_reported_total,
+    sha256::Sha256,
+};
+
+static DIGITS_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"[0-9]+").unwrap());
 
 // Regex to match emails from https://emailregex.com/
 pub static EMAIL_REGEX: LazyLock<Regex> = LazyLock::new(|| {
@@ -584,6 +590,38 @@ impl JsError {
         }
     }
 
+    /// Groups errors that likely share a cause: the same function failing
+    /// with the same stack shape. Line and column numbers are left out so
+    /// that unrelated edits to a file don't split a group. Errors without a
+    /// stack are grouped by their message with numbers removed.
+    pub fn fingerprint(&self, component_path: &ComponentPath, udf_path: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(String::from(component_path.clone()).as_bytes());
+        hasher.update(b"\0");
+        hasher.update(udf_path.as_bytes());
+        match &self.frames {
+            Some(frames) if !frames.0.is_empty() => {
+                for frame in frames.0.iter() {
+                    hasher.update(b"\0");
+                    hasher.update(frame.file_name.as_deref().unwrap_or_default().as_bytes());
+                    hasher.update(b":");
+                    hasher.update(
+                        frame
+                            .function_name
+                            .as_deref()
+                            .unwrap_or_default()
+                            .as_bytes(),
+                    );
+                }
+            },
+            _ => {
+                hasher.update(b"\0");
+                hasher.update(DIGITS_REGEX.replace_all(&self.message, "0").as_bytes());
+            },
+        }
+        hasher.finalize().as_hex()
+    }
+
     pub fn from_frames(
         message: String,
         frame_data: Vec<FrameData>,
@@ -758,8 +796,10 @@ mod tests {
         FrameDataProto,
         JsError,
         JsErrorProto,
+        JsFrames,
     };
     use crate::{
+        components::ComponentPath,
         errors::{
             event_from_error,
             FrameData,
@@ -773,6 +813,42 @@ mod tests {
         },
     };
 
+    #[test]
+    fn test_js_error_fingerprint() {
+        let error_at = |message: &str, line_number: u32| JsError {
+            message: message.to_string(),
+            custom_data: None,
+            frames: Some(JsFrames(
+                vec![FrameData {
+                    file_name: Some("../convex/messages.ts".to_string()),
+                    function_name: Some("handler".to_string()),
+                    line_number: Some(line_number),
+                    column_number: Some(1),
+                    ..Default::default()
+                }]
+                .into(),
+            )),
+        };
+        let root = ComponentPath::root();
+        let fingerprint = error_at("Uncaught Error: oops", 10).fingerprint(&root, "messages:send");
+        assert_eq!(
+            error_at("Uncaught Error: other", 12).fingerprint(&root, "messages:send"),
+            fingerprint
+        );
+        assert_ne!(
+            error_at("Uncaught Error: oops", 10).fingerprint(&root, "messages:list"),
+            fingerprint
+        );
+
+        // Without a stack, messages that only differ by numbers are grouped.
+        assert_eq!(
+            JsError::from_message("Document 12 not found".to_string())
+                .fingerprint(&root, "messages:send"),
+            JsError::from_message("Document 345 not found".to_string())
+                .fingerprint(&root, "messages:send"),
+        );
+    }
+
     #[test]
     fn test_js_error_conversion_into_anyhow() -> anyhow::Result<()> {
         let js_error = JsError::from_message("Big Error".into());
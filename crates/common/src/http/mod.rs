@@ -3,7 +3,10 @@ use std::{
     convert::Infallible,
     fmt,
     future::Future,
-    net::SocketAddr,
+    net::{
+        IpAddr,
+        SocketAddr,
+    },
     ops::Deref,
     pin::Pin,
     str::{
@@ -73,6 +76,7 @@ use http::{
     Uri,
 };
 use http_body_util::BodyExt;
+use ipnet::IpNet;
 use itertools::Itertools;
 use prometheus::{
     PullingGauge,
@@ -119,6 +123,7 @@ use crate::{
 pub mod extract;
 pub mod fetch;
 pub mod fork_of_axum_serve;
+pub mod rate_limit;
 
 const MAX_HTTP2_STREAMS: u32 = 1024;
 
@@ -1102,6 +1107,94 @@ where
     }
 }
 
+#[allow(clippy::declare_interior_mutable_const)]
+pub const X_FORWARDED_FOR_HEADER: HeaderName = HeaderName::from_static("x-forwarded-for");
+
+/// CIDR ranges of the reverse proxies in front of the backend. Only a proxy in
+/// one of these ranges is trusted to report the client's address in
+/// `X-Forwarded-For`. Installed as a request extension; without it,
+/// `ExtractClientIp` ignores `X-Forwarded-For` entirely.
+#[derive(Clone, Debug, Default)]
+pub struct TrustedProxies(pub Arc<Vec<IpNet>>);
+
+impl TrustedProxies {
+    fn contains(&self, ip: IpAddr) -> bool {
+        self.0.iter().any(|net| net.contains(&ip))
+    }
+}
+
+/// Treats IPv4-mapped IPv6 addresses the same as their IPv4 equivalent, so
+/// dual-stack listeners compare equal to IPv4 CIDR ranges.
+pub fn canonical_ip(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+        IpAddr::V4(_) => ip,
+    }
+}
+
+/// Resolves the client's address from the TCP peer address and the
+/// `X-Forwarded-For` header. Each proxy appends the address it received the
+/// request from, so the header is walked from the right, stopping at the first
+/// hop that isn't a trusted proxy: everything to the left of it was written by
+/// the client and can't be trusted.
+pub fn resolve_client_ip(
+    peer: Option<IpAddr>,
+    headers: &HeaderMap,
+    trusted_proxies: &TrustedProxies,
+) -> Option<IpAddr> {
+    let mut client = canonical_ip(peer?);
+    if !trusted_proxies.contains(client) {
+        return Some(client);
+    }
+    let hops = headers
+        .get_all(X_FORWARDED_FOR_HEADER)
+        .iter()
+        .flat_map(|h| h.to_str().unwrap_or_default().split(','))
+        .collect::<Vec<_>>();
+    for hop in hops.into_iter().rev() {
+        let Ok(hop) = hop.trim().parse::<IpAddr>() else {
+            break;
+        };
+        client = canonical_ip(hop);
+        if !trusted_proxies.contains(client) {
+            break;
+        }
+    }
+    Some(client)
+}
+
+/// The IP address of the client that issued the request: the peer address of
+/// the TCP connection, or the address reported in `X-Forwarded-For` by a
+/// trusted proxy (see [`TrustedProxies`]).
+pub struct ExtractClientIp(pub Option<IpAddr>);
+
+impl<S> FromRequestParts<S> for ExtractClientIp
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let peer = parts
+            .extensions
+            .get::<axum::extract::ConnectInfo<SocketAddr>>()
+            .map(|connect_info| connect_info.0.ip());
+        let trusted_proxies = parts
+            .extensions
+            .get::<TrustedProxies>()
+            .cloned()
+            .unwrap_or_default();
+        Ok(Self(resolve_client_ip(
+            peer,
+            &parts.headers,
+            &trusted_proxies,
+        )))
+    }
+}
+
 pub const TRACEPARENT_HEADER_STR: &str = "traceparent";
 pub const TRACEPARENT_HEADER: HeaderName = HeaderName::from_static(TRACEPARENT_HEADER_STR);
 
@@ -1275,6 +1368,8 @@ where
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use axum::response::IntoResponse;
     use errors::{
         ErrorMetadata,
@@ -1283,7 +1378,12 @@ mod tests {
     };
     use http::StatusCode;
 
-    use super::HttpResponseError;
+    use super::{
+        resolve_client_ip,
+        HttpResponseError,
+        TrustedProxies,
+        X_FORWARDED_FOR_HEADER,
+    };
     use crate::http::HttpError;
 
     #[tokio::test]
@@ -1350,4 +1450,58 @@ mod tests {
         assert_eq!(error.message(), msg);
         Ok(())
     }
+
+    #[test]
+    fn test_resolve_client_ip() -> anyhow::Result<()> {
+        let headers = |xff: &str| -> anyhow::Result<http::HeaderMap> {
+            let mut headers = http::HeaderMap::new();
+            headers.insert(X_FORWARDED_FOR_HEADER, xff.parse()?);
+            Ok(headers)
+        };
+        let no_proxies = TrustedProxies::default();
+        let proxies = TrustedProxies(Arc::new(vec!["10.0.0.0/8".parse()?]));
+
+        // Without trusted proxies, X-Forwarded-For is ignored.
+        assert_eq!(
+            resolve_client_ip(Some("8.8.8.8".parse()?), &headers("10.0.0.1")?, &no_proxies),
+            Some("8.8.8.8".parse()?),
+        );
+        // A peer outside the trusted ranges can't spoof its address.
+        assert_eq!(
+            resolve_client_ip(Some("8.8.8.8".parse()?), &headers("10.0.0.1")?, &proxies),
+            Some("8.8.8.8".parse()?),
+        );
+        // Behind a trusted proxy, the rightmost untrusted hop is the client, and
+        // anything the client prepended is ignored.
+        assert_eq!(
+            resolve_client_ip(
+                Some("10.0.0.2".parse()?),
+                &headers("10.0.0.1, 1.2.3.4, 10.0.0.3")?,
+                &proxies,
+            ),
+            Some("1.2.3.4".parse()?),
+        );
+        // An unparseable hop ends the walk at the last trusted address.
+        assert_eq!(
+            resolve_client_ip(
+                Some("10.0.0.2".parse()?),
+                &headers("1.2.3.4, junk")?,
+                &proxies
+            ),
+            Some("10.0.0.2".parse()?),
+        );
+        assert_eq!(
+            resolve_client_ip(
+                Some("::ffff:10.0.0.2".parse()?),
+                &headers("1.2.3.4")?,
+                &proxies
+            ),
+            Some("1.2.3.4".parse()?),
+        );
+        assert_eq!(
+            resolve_client_ip(None, &headers("1.2.3.4")?, &proxies),
+            None
+        );
+        Ok(())
+    }
 }
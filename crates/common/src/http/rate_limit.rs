@@ -0,0 +1,112 @@
+//! Rate limits for HTTP action routes, checked by the `rateLimit` router
+//! middleware. Limits are tracked in memory by the backend, so they hold
+//! across requests without writing to the database.
+
+use std::{
+    collections::BTreeMap,
+    num::NonZeroU32,
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::Context;
+use governor::Quota;
+use parking_lot::Mutex;
+
+use crate::{
+    knobs::HTTP_ACTION_RATE_LIMIT_MAX_KEYS,
+    runtime::{
+        new_keyed_rate_limiter,
+        KeyedRateLimiter,
+        Runtime,
+    },
+};
+
+/// One keyed limiter per distinct limit, since each limiter has a single
+/// quota.
+pub struct HttpActionRateLimiter<RT: Runtime> {
+    rt: RT,
+    limiters: Arc<Mutex<BTreeMap<(NonZeroU32, Duration), Arc<KeyedRateLimiter<String, RT>>>>>,
+}
+
+impl<RT: Runtime> Clone for HttpActionRateLimiter<RT> {
+    fn clone(&self) -> Self {
+        Self {
+            rt: self.rt.clone(),
+            limiters: self.limiters.clone(),
+        }
+    }
+}
+
+impl<RT: Runtime> HttpActionRateLimiter<RT> {
+    pub fn new(rt: RT) -> Self {
+        Self {
+            rt,
+            limiters: Arc::new(Mutex::new(BTreeMap::new())),
+        }
+    }
+
+    /// Counts a request against `key`, allowing `requests` per `period` with
+    /// bursts of up to `requests`. Returns how long to wait before retrying
+    /// if the request is over the limit.
+    pub fn check(
+        &self,
+        key: &str,
+        requests: NonZeroU32,
+        period: Duration,
+    ) -> anyhow::Result<Option<Duration>> {
+        let quota = Quota::with_period(period / requests.get())
+            .context("Rate limit period is too short")?
+            .allow_burst(requests);
+        let limiter = self
+            .limiters
+            .lock()
+            .entry((requests, period))
+            .or_insert_with(|| Arc::new(new_keyed_rate_limiter(self.rt.clone(), quota)))
+            .clone();
+        if limiter.len() >= *HTTP_ACTION_RATE_LIMIT_MAX_KEYS {
+            limiter.retain_recent();
+        }
+        match limiter.check_key(&key.to_string()) {
+            Ok(()) => Ok(None),
+            Err(not_until) => Ok(Some(
+                not_until.wait_time_from(self.rt.monotonic_now().into()),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        num::NonZeroU32,
+        time::Duration,
+    };
+
+    use super::HttpActionRateLimiter;
+    use crate::runtime::{
+        testing::TestDriver,
+        Runtime,
+    };
+
+    #[test]
+    fn test_rate_limit_per_key() -> anyhow::Result<()> {
+        let td = TestDriver::new();
+        let rt = td.rt();
+        td.run_until(async move {
+            let limiter = HttpActionRateLimiter::new(rt.clone());
+            let requests = NonZeroU32::new(2).unwrap();
+            let period = Duration::from_secs(60);
+            assert_eq!(limiter.check("a", requests, period)?, None);
+            assert_eq!(limiter.check("a", requests, period)?, None);
+            let retry_after = limiter.check("a", requests, period)?.unwrap();
+            assert!(retry_after <= Duration::from_secs(30));
+            // Other keys have their own limit.
+            assert_eq!(limiter.check("b", requests, period)?, None);
+
+            rt.wait(Duration::from_secs(30)).await;
+            assert_eq!(limiter.check("a", requests, period)?, None);
+            Ok(())
+        })
+    }
+}
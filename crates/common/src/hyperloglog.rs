@@ -0,0 +1,117 @@
+//! A HyperLogLog sketch, which estimates how many distinct values it has seen
+//! in a fixed 4KB regardless of how many values there are. Estimates are
+//! typically within 2% of the true count.
+
+use crate::sha256::Sha256;
+
+/// How many bits of each value's hash pick its register.
+const PRECISION: u32 = 12;
+const NUM_REGISTERS: usize = 1 << PRECISION;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HyperLogLog {
+    /// The longest run of leading zeros, plus one, among the hashes that
+    /// landed in each register.
+    registers: Vec<u8>,
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HyperLogLog {
+    pub fn new() -> Self {
+        Self {
+            registers: vec![0; NUM_REGISTERS],
+        }
+    }
+
+    pub fn insert(&mut self, value: &[u8]) {
+        let digest = Sha256::hash(value);
+        let hash = u64::from_le_bytes(digest[..8].try_into().expect("Digest has 32 bytes"));
+        let register = (hash >> (64 - PRECISION)) as usize;
+        // Setting the bit after the remaining hash bits caps the rank when
+        // they're all zero.
+        let rank = ((hash << PRECISION) | (1 << (PRECISION - 1))).leading_zeros() + 1;
+        self.registers[register] = self.registers[register].max(rank as u8);
+    }
+
+    /// The estimated number of distinct values inserted.
+    pub fn estimate(&self) -> f64 {
+        let m = NUM_REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|&register| 2f64.powi(-i32::from(register)))
+            .sum();
+        let raw = alpha * m * m / sum;
+        let empty_registers = self.registers.iter().filter(|&&r| r == 0).count();
+        if raw <= 2.5 * m && empty_registers > 0 {
+            // Linear counting is more accurate when most registers are empty.
+            m * (m / empty_registers as f64).ln()
+        } else {
+            raw
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.registers.clone()
+    }
+
+    pub fn from_bytes(registers: Vec<u8>) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            registers.len() == NUM_REGISTERS,
+            "HyperLogLog has {} registers, expected {NUM_REGISTERS}",
+            registers.len()
+        );
+        Ok(Self { registers })
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl proptest::arbitrary::Arbitrary for HyperLogLog {
+    type Parameters = ();
+
+    type Strategy = impl proptest::strategy::Strategy<Value = HyperLogLog>;
+
+    fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+        use proptest::prelude::*;
+
+        prop::collection::vec(any::<Vec<u8>>(), 0..16).prop_map(|values| {
+            let mut sketch = HyperLogLog::new();
+            for value in values {
+                sketch.insert(&value);
+            }
+            sketch
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HyperLogLog;
+
+    #[test]
+    fn test_estimate() {
+        let mut sketch = HyperLogLog::new();
+        assert_eq!(sketch.estimate(), 0.0);
+        for i in 0..100_000u32 {
+            // Duplicates don't change the estimate.
+            sketch.insert(&(i % 20_000).to_le_bytes());
+        }
+        let estimate = sketch.estimate();
+        assert!((19_000.0..21_000.0).contains(&estimate), "{estimate}");
+    }
+
+    #[test]
+    fn test_bytes_roundtrip() -> anyhow::Result<()> {
+        let mut sketch = HyperLogLog::new();
+        sketch.insert(b"convex");
+        assert_eq!(HyperLogLog::from_bytes(sketch.to_bytes())?, sketch);
+        assert!(HyperLogLog::from_bytes(vec![0; 3]).is_err());
+        Ok(())
+    }
+}
@@ -6,6 +6,7 @@ use errors::ErrorMetadata;
 mod expression;
 mod query;
 pub use expression::JsonExpression;
+pub use query::JsonIndexRangeExpression;
 use serde::{
     de::DeserializeOwned,
     Serialize,
@@ -12,7 +12,9 @@ use crate::{
     paths::FieldPath,
     query::{
         Expression,
+        FacetOptions,
         FullTableScan,
+        HighlightOptions,
         IndexRange,
         IndexRangeExpression,
         Order,
@@ -21,6 +23,7 @@ use crate::{
         QuerySource,
         Search,
         SearchFilterExpression,
+        TextSearchOptions,
         MAX_QUERY_OPERATORS,
     },
     types::{
@@ -65,7 +68,7 @@ struct JsonQueryIndexRange {
 
 #[derive(Deserialize, Serialize)]
 #[serde(tag = "type")]
-enum JsonIndexRangeExpression {
+pub enum JsonIndexRangeExpression {
     Eq(JsonFieldPathAndValue),
     Gt(JsonFieldPathAndValue),
     Gte(JsonFieldPathAndValue),
@@ -74,7 +77,7 @@ enum JsonIndexRangeExpression {
 }
 #[derive(Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
-struct JsonFieldPathAndValue {
+pub struct JsonFieldPathAndValue {
     field_path: String,
     value: JsonValue,
 }
@@ -166,8 +169,39 @@ enum JsonSearchFilterExpression {
     Search {
         field_path: String,
         value: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_typos: Option<u8>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        highlight: Option<JsonHighlightOptions>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        facets: Option<JsonFacetOptions>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        score: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        prefix_search: Option<bool>,
     },
     Eq(JsonFieldPathAndValue),
+    Gt(JsonFieldPathAndValue),
+    Gte(JsonFieldPathAndValue),
+    Lt(JsonFieldPathAndValue),
+    Lte(JsonFieldPathAndValue),
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonHighlightOptions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fragment_size: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_fragments: Option<u32>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonFacetOptions {
+    fields: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    limit: Option<u32>,
 }
 
 impl TryFrom<JsonSearchFilterExpression> for SearchFilterExpression {
@@ -175,13 +209,63 @@ impl TryFrom<JsonSearchFilterExpression> for SearchFilterExpression {
 
     fn try_from(json_filter_expression: JsonSearchFilterExpression) -> Result<Self> {
         match json_filter_expression {
-            JsonSearchFilterExpression::Search { field_path, value } => Ok(
-                SearchFilterExpression::Search(FieldPath::from_str(&field_path)?, value),
-            ),
+            JsonSearchFilterExpression::Search {
+                field_path,
+                value,
+                max_typos,
+                highlight,
+                facets,
+                score,
+                prefix_search,
+            } => {
+                let mut options = TextSearchOptions::new(max_typos.unwrap_or_default())?;
+                if let Some(JsonHighlightOptions {
+                    fragment_size,
+                    max_fragments,
+                }) = highlight
+                {
+                    options = options
+                        .with_highlight(HighlightOptions::new(fragment_size, max_fragments)?);
+                }
+                if let Some(JsonFacetOptions { fields, limit }) = facets {
+                    let fields = fields
+                        .iter()
+                        .map(|field| field.parse())
+                        .collect::<anyhow::Result<_>>()?;
+                    options = options.with_facets(FacetOptions::new(fields, limit)?);
+                }
+                if let Some(score) = score {
+                    options = options.with_score(score.parse()?);
+                }
+                if prefix_search == Some(true) {
+                    options = options.with_prefix_search();
+                }
+                Ok(SearchFilterExpression::Search(
+                    FieldPath::from_str(&field_path)?,
+                    value,
+                    options,
+                ))
+            },
             JsonSearchFilterExpression::Eq(field_and_value) => Ok(SearchFilterExpression::Eq(
                 FieldPath::from_str(&field_and_value.field_path)?,
                 MaybeValue::try_from(field_and_value.value)?.0,
             )),
+            JsonSearchFilterExpression::Gt(field_and_value) => Ok(SearchFilterExpression::Gt(
+                FieldPath::from_str(&field_and_value.field_path)?,
+                field_and_value.value.try_into()?,
+            )),
+            JsonSearchFilterExpression::Gte(field_and_value) => Ok(SearchFilterExpression::Gte(
+                FieldPath::from_str(&field_and_value.field_path)?,
+                field_and_value.value.try_into()?,
+            )),
+            JsonSearchFilterExpression::Lt(field_and_value) => Ok(SearchFilterExpression::Lt(
+                FieldPath::from_str(&field_and_value.field_path)?,
+                field_and_value.value.try_into()?,
+            )),
+            JsonSearchFilterExpression::Lte(field_and_value) => Ok(SearchFilterExpression::Lte(
+                FieldPath::from_str(&field_and_value.field_path)?,
+                field_and_value.value.try_into()?,
+            )),
         }
     }
 }
@@ -189,10 +273,21 @@ impl TryFrom<JsonSearchFilterExpression> for SearchFilterExpression {
 impl From<SearchFilterExpression> for JsonSearchFilterExpression {
     fn from(filter_expression: SearchFilterExpression) -> Self {
         match filter_expression {
-            SearchFilterExpression::Search(field_path, value) => {
+            SearchFilterExpression::Search(field_path, value, options) => {
                 JsonSearchFilterExpression::Search {
                     field_path: field_path.into(),
                     value,
+                    max_typos: (options.max_typos > 0).then_some(options.max_typos),
+                    highlight: options.highlight.map(|highlight| JsonHighlightOptions {
+                        fragment_size: Some(highlight.fragment_size),
+                        max_fragments: Some(highlight.max_fragments),
+                    }),
+                    facets: options.facets.map(|facets| JsonFacetOptions {
+                        fields: facets.fields.into_iter().map(String::from).collect(),
+                        limit: Some(facets.limit),
+                    }),
+                    score: options.score.map(String::from),
+                    prefix_search: options.prefix_search.then_some(true),
                 }
             },
             SearchFilterExpression::Eq(field_path, value) => {
@@ -201,6 +296,30 @@ impl From<SearchFilterExpression> for JsonSearchFilterExpression {
                     value: MaybeValue(value).into(),
                 })
             },
+            SearchFilterExpression::Gt(field_path, value) => {
+                JsonSearchFilterExpression::Gt(JsonFieldPathAndValue {
+                    field_path: field_path.into(),
+                    value: value.into(),
+                })
+            },
+            SearchFilterExpression::Gte(field_path, value) => {
+                JsonSearchFilterExpression::Gte(JsonFieldPathAndValue {
+                    field_path: field_path.into(),
+                    value: value.into(),
+                })
+            },
+            SearchFilterExpression::Lt(field_path, value) => {
+                JsonSearchFilterExpression::Lt(JsonFieldPathAndValue {
+                    field_path: field_path.into(),
+                    value: value.into(),
+                })
+            },
+            SearchFilterExpression::Lte(field_path, value) => {
+                JsonSearchFilterExpression::Lte(JsonFieldPathAndValue {
+                    field_path: field_path.into(),
+                    value: value.into(),
+                })
+            },
         }
     }
 }
@@ -243,24 +362,41 @@ impl TryFrom<JsonQuerySource> for QuerySource {
                     order: try_order_from_string(json_index_range.order)?,
                 })
             },
-            JsonQuerySource::Search(json_search) => {
-                let filter_expressions: Vec<SearchFilterExpression> = json_search
-                    .filters
-                    .into_iter()
-                    .map(|json_filter_expression| json_filter_expression.try_into())
-                    .collect::<anyhow::Result<Vec<_>>>()?;
+            JsonQuerySource::Search(json_search) => QuerySource::Search(json_search.try_into()?),
+        })
+    }
+}
 
-                let index_name = IndexName::from_str(&json_search.index_name)?;
-                QuerySource::Search(Search {
-                    table: index_name.table().clone(),
-                    index_name,
-                    filters: filter_expressions,
-                })
-            },
+impl TryFrom<JsonSearch> for Search {
+    type Error = anyhow::Error;
+
+    fn try_from(json_search: JsonSearch) -> Result<Self> {
+        let filter_expressions: Vec<SearchFilterExpression> = json_search
+            .filters
+            .into_iter()
+            .map(|json_filter_expression| json_filter_expression.try_into())
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let index_name = IndexName::from_str(&json_search.index_name)?;
+        Ok(Search {
+            table: index_name.table().clone(),
+            index_name,
+            filters: filter_expressions,
         })
     }
 }
 
+/// Parses a search on its own, outside of a query, as
+/// `{ indexName, filters }`.
+impl TryFrom<JsonValue> for Search {
+    type Error = anyhow::Error;
+
+    fn try_from(value: JsonValue) -> Result<Self> {
+        let json_search: JsonSearch = serde_json::from_value(value)?;
+        json_search.try_into()
+    }
+}
+
 impl From<QuerySource> for JsonQuerySource {
     fn from(query_source: QuerySource) -> Self {
         match query_source {
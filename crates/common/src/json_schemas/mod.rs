@@ -111,6 +111,45 @@ pub fn bytes(value_format: ValueFormat) -> JsonValue {
     }
 }
 
+pub fn decimal(value_format: ValueFormat) -> JsonValue {
+    match value_format {
+        ValueFormat::ConvexCleanJSON => json!({
+            "$description": "decimal string",
+            "type": "string",
+        }),
+        ValueFormat::ConvexEncodedJSON => json!({
+            "type": "object",
+            "$description": "decimal string",
+            "properties": {
+                "$decimal": {"type": "string"},
+            },
+        }),
+    }
+}
+
+pub fn datetime(value_format: ValueFormat) -> JsonValue {
+    match value_format {
+        ValueFormat::ConvexCleanJSON => json!({
+            "type": "string",
+            "format": "date-time",
+        }),
+        ValueFormat::ConvexEncodedJSON => json!({
+            "type": "object",
+            "properties": {
+                "$datetime": {"type": "string", "format": "date-time"},
+            },
+        }),
+    }
+}
+
+/// A string or boolean literal, which is its own JSON encoding in every
+/// value format.
+pub fn literal(type_schema: JsonValue, value: JsonValue) -> JsonValue {
+    let mut schema = type_schema;
+    schema["const"] = value;
+    schema
+}
+
 pub fn array(element_schema: JsonValue) -> JsonValue {
     json!({
         "type": "array",
@@ -182,3 +221,10 @@ pub fn object(fields: BTreeMap<String, FieldInfo>) -> JsonValue {
         "required": required_fields.into_iter().collect::<Vec<_>>(),
     })
 }
+
+/// An object whose fields other than `fields` are unconstrained.
+pub fn open_object(fields: BTreeMap<String, FieldInfo>) -> JsonValue {
+    let mut schema = object(fields);
+    schema["additionalProperties"] = json!(true);
+    schema
+}
@@ -244,6 +244,12 @@ pub static TRANSACTION_MAX_SYSTEM_WRITE_SIZE_BYTES: LazyLock<usize> = LazyLock::
 pub static TRANSACTION_MAX_NUM_SCHEDULED: LazyLock<usize> =
     LazyLock::new(|| env_config("TRANSACTION_MAX_NUM_SCHEDULED", 1000));
 
+/// Check the arguments of a scheduled function against its args validator
+/// when it's scheduled, so a mismatch fails the scheduling function instead of
+/// the scheduled job later. Turn off to only validate when the job runs.
+pub static VALIDATE_SCHEDULED_FUNCTION_ARGS: LazyLock<bool> =
+    LazyLock::new(|| env_config("VALIDATE_SCHEDULED_FUNCTION_ARGS", true));
+
 /// Maximum number of scheduled jobs to cancel in a single transaction.
 pub static MAX_JOBS_CANCEL_BATCH: LazyLock<usize> =
     LazyLock::new(|| env_config("MAX_JOBS_CANCEL_BATCH", 1000));
@@ -257,6 +263,12 @@ pub static TRANSACTION_MAX_SCHEDULED_TOTAL_ARGUMENT_SIZE_BYTES: LazyLock<usize>
         ) // 16 MiB
     });
 
+/// Scheduled function arguments larger than this are moved from the
+/// `_scheduled_jobs` document to file storage after the scheduling transaction
+/// commits, and loaded when the function runs.
+pub static SCHEDULED_JOB_ARGS_STORAGE_THRESHOLD_BYTES: LazyLock<usize> =
+    LazyLock::new(|| env_config("SCHEDULED_JOB_ARGS_STORAGE_THRESHOLD_BYTES", 1 << 16)); // 64 KiB
+
 /// Number of scheduled jobs that can execute in parallel.
 // Note that the current algorithm for executing ready jobs has up to
 // SCHEDULED_JOB_EXECUTION_PARALLELISM overhead for every executed job, so we
@@ -302,6 +314,17 @@ pub static SCHEDULED_JOB_RETENTION: LazyLock<Duration> = LazyLock::new(|| {
     ))
 });
 
+/// How long an idempotency key passed to `ctx.scheduler.runAfter` or
+/// `ctx.scheduler.runAt` deduplicates scheduling the same job again. Completed
+/// jobs are garbage collected after `SCHEDULED_JOB_RETENTION`, so keys can't be
+/// enforced for longer than that.
+pub static SCHEDULED_JOB_IDEMPOTENCY_WINDOW: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(env_config(
+        "SCHEDULED_JOB_IDEMPOTENCY_WINDOW_SECS",
+        60 * 60 * 24, // 1 day
+    ))
+});
+
 /// Maximum number of scheduled jobs to garbage collect in a single transaction
 pub static SCHEDULED_JOB_GARBAGE_COLLECTION_BATCH_SIZE: LazyLock<usize> =
     LazyLock::new(|| env_config("SCHEDULED_JOB_GARBAGE_COLLECTION_BATCH_SIZE", 1000));
@@ -319,6 +342,12 @@ pub static SCHEDULED_JOB_GARBAGE_COLLECTION_DELAY: LazyLock<Duration> =
 pub static MAX_SYSCALL_BATCH_SIZE: LazyLock<usize> =
     LazyLock::new(|| env_config("MAX_SYSCALL_BATCH_SIZE", 16));
 
+/// Maximum number of index ranges in a batch of reads that are fetched from
+/// persistence concurrently, e.g. when a function awaits several queries with
+/// `Promise.all`.
+pub static INDEX_RANGE_FETCH_CONCURRENCY: LazyLock<usize> =
+    LazyLock::new(|| env_config("INDEX_RANGE_FETCH_CONCURRENCY", 20));
+
 /// Maximum depth of query/mutation -> query/mutation calls within the reactor.
 /// We put a low limit on this for now so users with infinite loops won't starve
 /// all of the threads on a single node.
@@ -415,6 +444,33 @@ pub static VECTOR_INDEX_SIZE_HARD_LIMIT: LazyLock<usize> =
 pub static ENABLE_INDEX_BACKFILL: LazyLock<bool> =
     LazyLock::new(|| env_config("INDEX_BACKFILL_ENABLE", true));
 
+/// Commit latency that background work (index backfill, retention, search
+/// compaction) paces itself against. Above this, those workers slow down in
+/// proportion; below it, they may speed up to `ADAPTIVE_PACING_MAX_SPEEDUP`.
+pub static ADAPTIVE_PACING_TARGET_COMMIT_LATENCY: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_millis(env_config("ADAPTIVE_PACING_TARGET_COMMIT_LATENCY_MS", 50))
+});
+
+/// Fraction of the persistence connection pool in use that background work
+/// paces itself against, like `ADAPTIVE_PACING_TARGET_COMMIT_LATENCY`.
+pub static ADAPTIVE_PACING_TARGET_POOL_UTILIZATION: LazyLock<f64> =
+    LazyLock::new(|| env_config("ADAPTIVE_PACING_TARGET_POOL_UTILIZATION", 0.5));
+
+/// The slowest background work is paced, as a fraction of its configured rate.
+pub static ADAPTIVE_PACING_MIN_FACTOR: LazyLock<f64> =
+    LazyLock::new(|| env_config("ADAPTIVE_PACING_MIN_FACTOR", 0.1));
+
+/// How much faster than its configured rate background work may run while the
+/// deployment is quiet. Set to 1 to only ever slow down.
+pub static ADAPTIVE_PACING_MAX_SPEEDUP: LazyLock<f64> =
+    LazyLock::new(|| env_config("ADAPTIVE_PACING_MAX_SPEEDUP", 4.0));
+
+/// How quickly the traffic signals used for adaptive pacing forget old
+/// observations. After this long without a commit, a latency spike has decayed
+/// to about a third.
+pub static ADAPTIVE_PACING_DECAY: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("ADAPTIVE_PACING_DECAY_SECS", 10)));
+
 /// Number of index chunks processed per second during a backfill.
 pub static INDEX_BACKFILL_CHUNK_RATE: LazyLock<usize> =
     LazyLock::new(|| env_config("INDEX_BACKFILL_CHUNK_RATE", 8));
@@ -500,6 +556,17 @@ pub static SEARCH_INDEX_WORKER_PAGES_PER_SECOND: LazyLock<NonZeroU32> = LazyLock
 pub static DATABASE_WORKERS_MAX_CHECKPOINT_AGE: LazyLock<Duration> =
     LazyLock::new(|| Duration::from_secs(env_config("DATABASE_WORKERS_MAX_CHECKPOINT_AGE", 3600)));
 
+/// How old a backfilling search index's snapshot may get before the flusher
+/// catches the segments built so far up with the writes made since, and moves
+/// the snapshot forward. Keeps the write log to replay once the backfill
+/// finishes short for large tables.
+pub static SEARCH_INDEX_BACKFILL_MAX_SNAPSHOT_AGE: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(env_config(
+        "SEARCH_INDEX_BACKFILL_MAX_SNAPSHOT_AGE_SECS",
+        10 * 60,
+    ))
+});
+
 /// Don't fast-forward an index less than ten seconds forward so we don't
 /// amplify every commit into another write when the system is under heavy load.
 pub static DATABASE_WORKERS_POLL_INTERVAL: LazyLock<Duration> =
@@ -534,6 +601,25 @@ pub static WRITE_LOG_MAX_RETENTION_SECS: LazyLock<Duration> =
 pub static WRITE_LOG_SOFT_MAX_SIZE_BYTES: LazyLock<usize> =
     LazyLock::new(|| env_config("WRITE_LOG_SOFT_MAX_SIZE_BYTES", 50 * 1024 * 1024));
 
+/// The hard limit on the memory used by the write log. Unlike
+/// WRITE_LOG_SOFT_MAX_SIZE_BYTES, this applies within
+/// WRITE_LOG_MIN_RETENTION_SECS: the oldest entries are spilled out of memory
+/// and read back from persistence when a token refresh needs them, until they
+/// age out of WRITE_LOG_MAX_RETENTION_SECS.
+pub static WRITE_LOG_MEMORY_BUDGET_BYTES: LazyLock<usize> =
+    LazyLock::new(|| env_config("WRITE_LOG_MEMORY_BUDGET_BYTES", 200 * 1024 * 1024));
+
+/// How much of the write log that's been spilled out of memory to keep cached,
+/// either as it's spilled or once it's been read back from persistence, so
+/// tokens and commits that span it don't each rescan persistence.
+pub static WRITE_LOG_READ_BACK_CACHE_SIZE_BYTES: LazyLock<usize> =
+    LazyLock::new(|| env_config("WRITE_LOG_READ_BACK_CACHE_SIZE_BYTES", 50 * 1024 * 1024));
+
+/// Write log entries newer than this are never spilled, so commits that
+/// started recently never have to read back spilled entries.
+pub static WRITE_LOG_MIN_IN_MEMORY_SECS: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("WRITE_LOG_MIN_IN_MEMORY_SECS", 5)));
+
 /// How frequently system tables are cleaned up.
 pub static SYSTEM_TABLE_CLEANUP_FREQUENCY: LazyLock<Duration> = LazyLock::new(|| {
     Duration::from_secs(env_config(
@@ -573,6 +659,99 @@ pub static MAX_SESSION_CLEANUP_DURATION: LazyLock<Option<Duration>> = LazyLock::
 pub static SESSION_CLEANUP_DELETE_CONCURRENCY: LazyLock<usize> =
     LazyLock::new(|| env_config("SESSION_CLEANUP_DELETE_CONCURRENCY", 2));
 
+/// How long completed function executions are kept in `_function_runs`.
+/// Zero, the default, disables recording function runs, since recording one
+/// is a write for every execution.
+pub static FUNCTION_RUNS_RETENTION: LazyLock<Option<Duration>> = LazyLock::new(|| {
+    let hours = env_config("FUNCTION_RUNS_RETENTION_HOURS", 0);
+    if hours > 0 {
+        Some(Duration::from_secs(60 * 60 * hours))
+    } else {
+        None
+    }
+});
+
+/// Maximum total bytes of console output kept per row in `_function_runs`.
+pub static FUNCTION_RUNS_MAX_LOG_BYTES: LazyLock<usize> =
+    LazyLock::new(|| env_config("FUNCTION_RUNS_MAX_LOG_BYTES", 4096));
+
+/// How long function runs and audit log entries can be found by searching
+/// their text. Zero disables indexing them.
+pub static LOG_SEARCH_RETENTION: LazyLock<Option<Duration>> = LazyLock::new(|| {
+    let hours = env_config("LOG_SEARCH_RETENTION_HOURS", 7 * 24);
+    if hours > 0 {
+        Some(Duration::from_secs(60 * 60 * hours))
+    } else {
+        None
+    }
+});
+
+/// Maximum number of function runs buffered for writing to `_function_runs`.
+/// Runs completing while the buffer is full are not recorded.
+pub static FUNCTION_RUNS_BUFFER_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("FUNCTION_RUNS_BUFFER_SIZE", 10000));
+
+/// How many of the root component's most recent pushes are kept in
+/// `_code_versions` to roll back to.
+pub static CODE_VERSIONS_TO_KEEP: LazyLock<usize> =
+    LazyLock::new(|| env_config("CODE_VERSIONS_TO_KEEP", 10));
+
+/// Whether uncaught exceptions are grouped by fingerprint into
+/// `_error_groups`.
+pub static ERROR_TRACKING_ENABLED: LazyLock<bool> =
+    LazyLock::new(|| env_config("ERROR_TRACKING_ENABLED", true));
+
+/// A query or mutation is flagged in `_read_set_advice` once the moving
+/// average of the documents or bytes it reads passes this fraction of the
+/// transaction read limits. Zero disables the advisor.
+pub static READ_SET_ADVISOR_THRESHOLD: LazyLock<f64> =
+    LazyLock::new(|| env_config("READ_SET_ADVISOR_THRESHOLD", 0.5));
+
+/// Minimum time between read set warnings for the same function.
+pub static READ_SET_ADVISOR_WARNING_INTERVAL: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(env_config(
+        "READ_SET_ADVISOR_WARNING_INTERVAL_SECS",
+        60 * 60,
+    ))
+});
+
+/// How long each dependency probe behind `/healthz` and `/readyz` may take
+/// before the dependency is reported as unavailable.
+pub static HEALTH_CHECK_PROBE_TIMEOUT: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_millis(env_config("HEALTH_CHECK_PROBE_TIMEOUT_MS", 5000)));
+
+/// How long a graceful shutdown waits for sync sessions to finish their
+/// in-flight mutations and actions and for in-flight HTTP requests to complete
+/// before stopping the backend anyway.
+pub static SHUTDOWN_DRAIN_TIMEOUT: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("SHUTDOWN_DRAIN_TIMEOUT_SECS", 30)));
+
+/// How long the leader lease is valid for without renewal when running backends
+/// with leader election. A standby takes over this long after the leader stops
+/// renewing it.
+pub static LEADER_LEASE_DURATION: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("LEADER_LEASE_DURATION_SECS", 15)));
+
+/// How often a standby backend checks whether the leader lease has expired.
+pub static LEADER_LEASE_POLL_INTERVAL: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_millis(env_config("LEADER_LEASE_POLL_INTERVAL_MS", 1000)));
+
+/// How long hourly rows are kept in `_function_usage` and `_table_usage`.
+/// Zero disables usage metering.
+pub static USAGE_METERING_RETENTION: LazyLock<Option<Duration>> = LazyLock::new(|| {
+    let days = env_config("USAGE_METERING_RETENTION_DAYS", 30);
+    if days > 0 {
+        Some(Duration::from_days(days))
+    } else {
+        None
+    }
+});
+
+/// How often aggregated usage is written to `_function_usage` and
+/// `_table_usage`.
+pub static USAGE_METERING_FLUSH_INTERVAL: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("USAGE_METERING_FLUSH_INTERVAL_SECONDS", 60)));
+
 /// Snapshots that expired more than this number of days ago are purged
 /// from storage.
 pub static MAX_EXPIRED_SNAPSHOT_AGE: LazyLock<Duration> = LazyLock::new(|| {
@@ -595,6 +774,55 @@ pub static VECTOR_INDEX_SIZE_SOFT_LIMIT: LazyLock<usize> =
 pub static VECTOR_INDEX_THREADS: LazyLock<usize> =
     LazyLock::new(|| env_config("VECTOR_INDEX_THREADS", 4));
 
+/// How many times the query limit a quantized vector search fetches using the
+/// compressed vectors before re-ranking them against the full-precision ones.
+pub static VECTOR_QUANTIZATION_OVERSAMPLING: LazyLock<f64> =
+    LazyLock::new(|| env_config("VECTOR_QUANTIZATION_OVERSAMPLING", 3.0));
+
+/// Largest `efSearch` a single vector query may request. Higher values explore
+/// more of the HNSW graph, improving recall at the cost of latency.
+pub static VECTOR_MAX_EF_SEARCH: LazyLock<u32> =
+    LazyLock::new(|| env_config("VECTOR_MAX_EF_SEARCH", 1024));
+
+/// Most partitions a vector segment tracks for a partitioned vector index.
+/// Segments with vectors from more partitions are searched by every query.
+pub static VECTOR_MAX_SEGMENT_PARTITIONS: LazyLock<usize> =
+    LazyLock::new(|| env_config("VECTOR_MAX_SEGMENT_PARTITIONS", 64));
+
+/// How often to record each vector index's segments, memory usage and query
+/// latency in `_vector_index_stats`.
+pub static VECTOR_INDEX_STATS_INTERVAL: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("VECTOR_INDEX_STATS_INTERVAL_SECS", 300)));
+
+/// How often to measure each vector index's recall by comparing its searches
+/// for sampled vectors against exact searches. Zero disables recall probes.
+pub static VECTOR_RECALL_PROBE_INTERVAL: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("VECTOR_RECALL_PROBE_INTERVAL_SECS", 3600)));
+
+/// How many of an index's documents a recall probe uses as queries.
+pub static VECTOR_RECALL_PROBE_SAMPLES: LazyLock<usize> =
+    LazyLock::new(|| env_config("VECTOR_RECALL_PROBE_SAMPLES", 10));
+
+/// How often to recompute the statistics that estimate index range counts and
+/// distinct values. Indexes whose table has grown or shrunk by more than a
+/// tenth since their statistics were computed are recomputed sooner.
+pub static INDEX_STATISTICS_INTERVAL: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("INDEX_STATISTICS_INTERVAL_SECS", 3600)));
+
+/// How many index keys the statistics of an index sample at least. Range count
+/// estimates get more precise with more samples.
+pub static INDEX_STATISTICS_SAMPLES: LazyLock<usize> =
+    LazyLock::new(|| env_config("INDEX_STATISTICS_SAMPLES", 128));
+
+/// Most texts passed to a vector index embedder action in one call.
+pub static VECTOR_EMBEDDER_BATCH_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("VECTOR_EMBEDDER_BATCH_SIZE", 64));
+
+/// How many times to call a vector index embedder for a document before
+/// marking its job as failed.
+pub static VECTOR_EMBEDDER_MAX_ATTEMPTS: LazyLock<u32> =
+    LazyLock::new(|| env_config("VECTOR_EMBEDDER_MAX_ATTEMPTS", 5));
+
 /// Configures the vector and search index workers' rate limit on pages
 /// processed per second. This is the default rate limit for anything a user
 /// might be waiting on. It's initialized high enough that it effectively does
@@ -666,6 +894,37 @@ pub static ISOLATE_QUEUE_SIZE: LazyLock<usize> =
 pub static COMMITTER_QUEUE_SIZE: LazyLock<usize> =
     LazyLock::new(|| env_config("COMMITTER_QUEUE_SIZE", 128));
 
+/// Maximum number of commits admitted to the committer at once, counting
+/// both commits waiting in its queue and commits being written to
+/// persistence. Commits beyond this are rejected with an `Overloaded` error.
+/// Capped at `COMMITTER_QUEUE_SIZE`, so an admitted commit never waits for
+/// room in the queue.
+pub static COMMITTER_MAX_IN_FLIGHT_COMMITS: LazyLock<usize> = LazyLock::new(|| {
+    env_config("COMMITTER_MAX_IN_FLIGHT_COMMITS", *COMMITTER_QUEUE_SIZE).min(*COMMITTER_QUEUE_SIZE)
+});
+
+/// Maximum number of in-flight commits from user functions and requests.
+/// Keeping this below `COMMITTER_MAX_IN_FLIGHT_COMMITS` sheds user mutations
+/// first under load, reserving the rest of the queue for system commits
+/// (scheduling, index builds, retention) that keep the deployment and its
+/// subscriptions healthy. Defaults to three quarters of the queue.
+pub static COMMITTER_MAX_IN_FLIGHT_USER_COMMITS: LazyLock<usize> = LazyLock::new(|| {
+    env_config(
+        "COMMITTER_MAX_IN_FLIGHT_USER_COMMITS",
+        *COMMITTER_MAX_IN_FLIGHT_COMMITS * 3 / 4,
+    )
+    .min(*COMMITTER_MAX_IN_FLIGHT_COMMITS)
+});
+
+/// How long a user commit may wait in the committer queue before it's
+/// rejected instead of being committed. Rejected commits haven't written
+/// anything, so clients can safely back off and retry. Zero disables the
+/// budget.
+pub static COMMIT_QUEUE_TIME_BUDGET: LazyLock<Option<Duration>> = LazyLock::new(|| {
+    let millis = env_config("COMMIT_QUEUE_TIME_BUDGET_MS", 5000);
+    (millis > 0).then(|| Duration::from_millis(millis))
+});
+
 /// 0 -> default (number of cores)
 pub static V8_THREADS: LazyLock<u32> = LazyLock::new(|| env_config("V8_THREADS", 0));
 
@@ -674,6 +933,12 @@ pub static V8_THREADS: LazyLock<u32> = LazyLock::new(|| env_config("V8_THREADS",
 /// heap.
 pub static REUSE_ISOLATES: LazyLock<bool> = LazyLock::new(|| env_config("REUSE_ISOLATES", true));
 
+/// If true, run a function from its per-function bundle under
+/// `_deps/functions/` when the CLI uploaded one, so the isolate only evaluates
+/// code reachable from that function instead of its whole module.
+pub static ISOLATE_USE_FUNCTION_BUNDLES: LazyLock<bool> =
+    LazyLock::new(|| env_config("ISOLATE_USE_FUNCTION_BUNDLES", true));
+
 /// Duration in seconds before an idle isolate is recreated
 pub static ISOLATE_IDLE_TIMEOUT: LazyLock<Duration> =
     LazyLock::new(|| Duration::from_secs(env_config("ISOLATE_IDLE_TIMEOUT_SECONDS", 600)));
@@ -882,6 +1147,30 @@ pub static MAX_CONCURRENT_ACTION_OPS: LazyLock<usize> =
 pub static SYNC_MAX_SEND_TRANSITION_COUNT: LazyLock<usize> =
     LazyLock::new(|| env_config("SYNC_MAX_SEND_TRANSITION_COUNT", 2));
 
+/// Maximum total size in bytes of the query journals a sync session keeps for
+/// its queries. Past this, the largest journals of queries with up-to-date
+/// results are dropped, and those queries fail with `InvalidCursor` the next
+/// time they're invalidated so the client restarts pagination.
+pub static SYNC_MAX_SESSION_JOURNAL_BYTES: LazyLock<usize> =
+    LazyLock::new(|| env_config("SYNC_MAX_SESSION_JOURNAL_BYTES", 1 << 20));
+
+/// Maximum number of queries an authenticated user can be subscribed to at
+/// once, across all of their sync sessions. Sessions that go over are closed
+/// with a `SubscriptionQuotaExceeded` error.
+pub static SYNC_MAX_SUBSCRIPTIONS_PER_IDENTITY: LazyLock<usize> =
+    LazyLock::new(|| env_config("SYNC_MAX_SUBSCRIPTIONS_PER_IDENTITY", 5000));
+
+/// Maximum bytes of query results sent to an authenticated user per minute,
+/// across all of their sync sessions. Past this, their transitions are held
+/// back until the next minute.
+pub static SYNC_MAX_TRANSITION_BYTES_PER_IDENTITY_PER_MINUTE: LazyLock<u64> =
+    LazyLock::new(|| env_config("SYNC_MAX_TRANSITION_BYTES_PER_IDENTITY_PER_MINUTE", 1 << 30));
+
+/// Maximum number of mutations and actions an authenticated user can call per
+/// minute over the sync protocol, across all of their sessions.
+pub static SYNC_MAX_FUNCTION_CALLS_PER_IDENTITY_PER_MINUTE: LazyLock<u64> =
+    LazyLock::new(|| env_config("SYNC_MAX_FUNCTION_CALLS_PER_IDENTITY_PER_MINUTE", 6000));
+
 /// Max Axiom sink attributes. This is a knob just in case a user actually hits
 /// the limit but has an Enterprise Axiom plan that lets them use more than the
 /// limit we've configured.
@@ -912,6 +1201,35 @@ pub static MAX_COMPACTION_SEGMENTS: LazyLock<usize> =
 pub static MAX_SEGMENT_DELETED_PERCENTAGE: LazyLock<f64> =
     LazyLock::new(|| env_config("MAX_SEGMENT_DELETED_PERCENTAGE", 0.2));
 
+/// Text index segments at most this size are merged together as soon as there
+/// are TEXT_MIN_COMPACTION_SEGMENTS of them. Larger segments are only merged
+/// once there are that many larger segments that fit in one segment.
+pub static TEXT_COMPACTION_SMALL_SEGMENT_THRESHOLD_BYTES: LazyLock<u64> = LazyLock::new(|| {
+    env_config(
+        "TEXT_COMPACTION_SMALL_SEGMENT_THRESHOLD_BYTES",
+        *VECTOR_INDEX_SIZE_HARD_LIMIT as u64,
+    )
+});
+/// The maximum size that we will compact any given set of text index segments
+/// to.
+pub static TEXT_SEGMENT_MAX_SIZE_BYTES: LazyLock<u64> =
+    LazyLock::new(|| env_config("TEXT_SEGMENT_MAX_SIZE_BYTES", *SEGMENT_MAX_SIZE_BYTES));
+/// The minimum number of text index segments we will compact in one pass.
+/// Lower values merge more often, keeping fewer segments to query.
+pub static TEXT_MIN_COMPACTION_SEGMENTS: LazyLock<u64> =
+    LazyLock::new(|| env_config("TEXT_MIN_COMPACTION_SEGMENTS", *MIN_COMPACTION_SEGMENTS));
+/// The maximum number of text index segments to compact in one request.
+pub static TEXT_MAX_COMPACTION_SEGMENTS: LazyLock<usize> =
+    LazyLock::new(|| env_config("TEXT_MAX_COMPACTION_SEGMENTS", *MAX_COMPACTION_SEGMENTS));
+/// The maximum percentage of a text index segment that can be deleted before we
+/// will recompact that segment. This number must be between 0 and 1.
+pub static TEXT_MAX_SEGMENT_DELETED_PERCENTAGE: LazyLock<f64> = LazyLock::new(|| {
+    env_config(
+        "TEXT_MAX_SEGMENT_DELETED_PERCENTAGE",
+        *MAX_SEGMENT_DELETED_PERCENTAGE,
+    )
+});
+
 /// Whether to run queries, mutations, HTTP actions, and v8 actions in Funrun
 /// (true) or InProcessFunctionRunner (false).
 pub static UDF_USE_FUNRUN: LazyLock<bool> = LazyLock::new(|| env_config("UDF_USE_FUNRUN", true));
@@ -947,6 +1265,22 @@ pub static MODULE_CACHE_MAX_CONCURRENCY: LazyLock<usize> =
 pub static FUNRUN_INDEX_CACHE_SIZE: LazyLock<u64> =
     LazyLock::new(|| env_config("FUNRUN_INDEX_CACHE_SIZE", 50_000_000)); // 50 MB
 
+/// The maximum size in bytes of the backend's cache of recently read documents
+/// and index ranges, shared across transactions. 0 disables the cache, so
+/// every read that misses the in-memory indexes goes to persistence.
+pub static DOCUMENT_CACHE_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("DOCUMENT_CACHE_SIZE", 0));
+
+/// Eviction policy for the document cache: `lru` evicts the least recently
+/// read range, `lfu` the least frequently read one.
+pub static DOCUMENT_CACHE_POLICY: LazyLock<String> =
+    LazyLock::new(|| env_config("DOCUMENT_CACHE_POLICY", String::from("lru")));
+
+/// Index ranges with more documents than this aren't added to the document
+/// cache, so large scans don't evict hot point reads.
+pub static DOCUMENT_CACHE_MAX_RANGE_DOCUMENTS: LazyLock<usize> =
+    LazyLock::new(|| env_config("DOCUMENT_CACHE_MAX_RANGE_DOCUMENTS", 64));
+
 /// The maximum number of concurrent index cache requests in Funrun.
 pub static FUNRUN_INDEX_CACHE_CONCURRENCY: LazyLock<usize> =
     LazyLock::new(|| env_config("FUNRUN_INDEX_CACHE_CONCURRENCY", 100));
@@ -1006,6 +1340,23 @@ pub static SEARCHLIGHT_CLUSTER_NAME: LazyLock<String> = LazyLock::new(|| {
     )
 });
 
+/// How long to wait to connect to a searchlight process before failing the
+/// request.
+pub static SEARCHLIGHT_CONNECT_TIMEOUT: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("SEARCHLIGHT_CONNECT_TIMEOUT_SECS", 5)));
+
+/// How long a search or compaction request to searchlight may take. Compacting
+/// large segments can take minutes, so this should stay well above the
+/// expected compaction time.
+pub static SEARCHLIGHT_REQUEST_TIMEOUT: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("SEARCHLIGHT_REQUEST_TIMEOUT_SECS", 600)));
+
+/// The maximum size of a gRPC message between the backend and searchlight, in
+/// either direction. Requests only name segments, and responses are bounded by
+/// the search limits, so this only needs room for large posting list results.
+pub static SEARCHLIGHT_MAX_MESSAGE_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("SEARCHLIGHT_MAX_MESSAGE_SIZE", 1 << 26)); // 64 MiB
+
 /// The maximum number of CPU cores that can be used simultaneously by the
 /// isolates. Zero means no limit.
 pub static FUNRUN_ISOLATE_ACTIVE_THREADS: LazyLock<usize> =
@@ -1313,3 +1664,61 @@ pub static LIST_SNAPSHOT_MAX_AGE_SECS: LazyLock<Duration> = LazyLock::new(|| {
 /// messages are unread.
 pub static SUBSCRIPTIONS_WORKER_QUEUE_SIZE: LazyLock<usize> =
     LazyLock::new(|| env_config("SUBSCRIPTIONS_WORKER_QUEUE_SIZE", 10000));
+
+/// How long a query that must see writes up to a given timestamp, like the
+/// commit timestamp a mutation over the HTTP API returned, waits for that
+/// timestamp to become readable before failing.
+pub static MIN_TS_MAX_WAIT: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_millis(env_config("MIN_TS_MAX_WAIT_MS", 5000)));
+
+/// How many times a webhook delivery is attempted before it's marked as
+/// failed.
+pub static WEBHOOK_MAX_ATTEMPTS: LazyLock<u32> =
+    LazyLock::new(|| env_config("WEBHOOK_MAX_ATTEMPTS", 8));
+
+/// Most webhook deliveries sent concurrently by the delivery worker.
+pub static WEBHOOK_DELIVERY_CONCURRENCY: LazyLock<usize> =
+    LazyLock::new(|| env_config("WEBHOOK_DELIVERY_CONCURRENCY", 16).max(1));
+
+/// How long a webhook endpoint has to respond before the attempt counts as
+/// failed.
+pub static WEBHOOK_REQUEST_TIMEOUT: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("WEBHOOK_REQUEST_TIMEOUT_SECS", 15)));
+
+/// Most document changes queued for webhook endpoints per change feed
+/// transaction.
+pub static WEBHOOK_CHANGE_FEED_BATCH_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("WEBHOOK_CHANGE_FEED_BATCH_SIZE", 256).max(1));
+
+/// How long webhook deliveries are kept in `_webhook_deliveries` as the
+/// delivery log.
+pub static WEBHOOK_DELIVERIES_RETENTION: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(60 * 60 * env_config("WEBHOOK_DELIVERIES_RETENTION_HOURS", 7 * 24))
+});
+
+/// Most HTTP checks run concurrently by the check worker.
+pub static HTTP_CHECK_CONCURRENCY: LazyLock<usize> =
+    LazyLock::new(|| env_config("HTTP_CHECK_CONCURRENCY", 8).max(1));
+
+/// How long HTTP check results are kept in `_http_check_results`.
+pub static HTTP_CHECK_RESULTS_RETENTION: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(60 * 60 * env_config("HTTP_CHECK_RESULTS_RETENTION_HOURS", 7 * 24))
+});
+
+/// How far a signed webhook timestamp may be from the current time before an
+/// HTTP action route that verifies webhooks rejects the request, unless the
+/// route sets its own tolerance.
+pub static INBOUND_WEBHOOK_TOLERANCE: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("INBOUND_WEBHOOK_TOLERANCE_SECS", 5 * 60)));
+
+/// How long the nonce of a webhook without a signed timestamp is kept for
+/// replay protection. Nonces of timestamped webhooks are kept until the
+/// timestamp falls out of tolerance.
+pub static INBOUND_WEBHOOK_NONCE_TTL: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(60 * 60 * env_config("INBOUND_WEBHOOK_NONCE_TTL_HOURS", 24))
+});
+
+/// Most keys tracked by each HTTP action rate limit before keys whose limit
+/// has fully replenished are dropped.
+pub static HTTP_ACTION_RATE_LIMIT_MAX_KEYS: LazyLock<usize> =
+    LazyLock::new(|| env_config("HTTP_ACTION_RATE_LIMIT_MAX_KEYS", 100_000));
@@ -21,6 +21,7 @@
 #![feature(str_split_remainder)]
 #![feature(duration_constructors)]
 
+pub mod adaptive_pacing;
 pub mod async_compat;
 pub mod auth;
 pub mod backoff;
@@ -32,6 +33,7 @@ pub mod comparators;
 pub mod components;
 pub mod deleted_bitset;
 pub mod document;
+pub mod document_chunks;
 pub mod errors;
 pub mod execution_context;
 pub mod ext;
@@ -40,6 +42,7 @@ pub mod floating_point;
 pub mod grpc;
 pub mod heap_size;
 pub mod http;
+pub mod hyperloglog;
 pub mod id_tracker;
 pub mod identifier;
 pub mod identity;
@@ -64,6 +67,7 @@ pub mod query_journal;
 pub mod retriable_stream;
 pub mod runtime;
 pub mod schemas;
+pub mod score_expression;
 pub mod sha256;
 pub mod shapes;
 pub mod shutdown;
@@ -25,6 +25,7 @@ pub use sync_types::{
 };
 use tokio::sync::mpsc;
 use value::{
+    export::ValueFormat,
     heap_size::{
         HeapSize,
         WithHeapSize,
@@ -39,6 +40,16 @@ use value::{
     remove_vec_of_strings,
     ConvexObject,
     ConvexValue,
+    Size,
+};
+#[cfg(any(test, feature = "testing"))]
+use value::{
+    proptest::{
+        RestrictNaNs,
+        ValueBranching,
+    },
+    ExcludeSetsAndMaps,
+    FieldType,
 };
 
 use crate::{
@@ -75,6 +86,19 @@ impl Display for LogLevel {
     }
 }
 
+impl LogLevel {
+    /// Orders levels from least to most severe. `LOG` and `INFO` are
+    /// equivalent.
+    pub fn severity(&self) -> u8 {
+        match self {
+            LogLevel::Debug => 0,
+            LogLevel::Log | LogLevel::Info => 1,
+            LogLevel::Warn => 2,
+            LogLevel::Error => 3,
+        }
+    }
+}
+
 impl FromStr for LogLevel {
     type Err = anyhow::Error;
 
@@ -136,6 +160,35 @@ pub struct LogLineStructured {
     pub is_truncated: bool,
     pub timestamp: UnixTimestamp,
     pub system_metadata: Option<SystemLogMetadata>,
+    /// Fields passed to `ctx.log`, with their types preserved. `None` for
+    /// lines logged with `console`.
+    pub fields: Option<ConvexObject>,
+}
+
+/// Selects structured log lines that have a field with a given value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct LogFieldMatch {
+    pub field: String,
+    /// Numbers are compared numerically and strings as-is, so `42` matches
+    /// both `{count: 42}` and `{count: "42"}`. Other values are compared
+    /// against their clean JSON.
+    pub value: String,
+}
+
+impl FromStr for LogFieldMatch {
+    type Err = anyhow::Error;
+
+    /// Parses `field=value`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((field, value)) = s.split_once('=') else {
+            anyhow::bail!("Expected a log field filter of the form `field=value`, got {s}");
+        };
+        Ok(Self {
+            field: field.to_string(),
+            value: value.to_string(),
+        })
+    }
 }
 
 impl LogLineStructured {
@@ -146,11 +199,41 @@ impl LogLineStructured {
             is_truncated,
             timestamp: _timestamp,
             system_metadata: _system_metadata,
+            fields,
         } = self;
+        let mut message = messages.join(" ");
+        if let Some(fields) = fields {
+            if !message.is_empty() {
+                message.push(' ');
+            }
+            message.push_str(&fields.to_string());
+        }
         if *is_truncated {
-            format!("[{level}] {}{TRUNCATED_LINE_SUFFIX}", messages.join(" "))
+            format!("[{level}] {message}{TRUNCATED_LINE_SUFFIX}")
         } else {
-            format!("[{level}] {}", messages.join(" "))
+            format!("[{level}] {message}")
+        }
+    }
+
+    pub fn matches_field(&self, field_match: &LogFieldMatch) -> bool {
+        let Some(value) = self
+            .fields
+            .as_ref()
+            .and_then(|fields| fields.get(&field_match.field[..]))
+        else {
+            return false;
+        };
+        match value {
+            ConvexValue::Float64(f) => field_match.value.parse::<f64>().is_ok_and(|v| v == *f),
+            ConvexValue::Int64(i) => field_match.value.parse::<i64>().is_ok_and(|v| v == *i),
+            ConvexValue::String(s) => **s == field_match.value,
+            value => {
+                value
+                    .clone()
+                    .export(ValueFormat::ConvexCleanJSON)
+                    .to_string()
+                    == field_match.value
+            },
         }
     }
 
@@ -169,6 +252,7 @@ impl LogLineStructured {
                 is_truncated,
                 timestamp,
                 system_metadata,
+                fields,
             } = self;
             let system_metadata = if include_system_metadata {
                 system_metadata
@@ -181,6 +265,7 @@ impl LogLineStructured {
                 timestamp: timestamp.as_ms_since_epoch()?,
                 level: level.to_string(),
                 system_metadata: system_metadata.map(SystemLogMetadataJson::from),
+                fields: fields.map(JsonValue::from),
                 component_path: sub_function_path.as_ref().map(|p| p.component.to_string()),
                 udf_path: sub_function_path.map(|p| p.udf_path.to_string()),
             };
@@ -206,6 +291,7 @@ impl LogLineStructured {
                 is_truncated: false,
                 timestamp,
                 system_metadata: None,
+                fields: None,
             };
         }
         let mut total_length = 0;
@@ -228,8 +314,32 @@ impl LogLineStructured {
             is_truncated: true,
             timestamp,
             system_metadata: None,
+            fields: None,
         }
     }
+
+    /// A line logged with `ctx.log`. If the fields would take the line past
+    /// `MAX_LOG_LINE_LENGTH` they're dropped and the line is marked truncated.
+    pub fn new_structured_log_line(
+        level: LogLevel,
+        message: String,
+        fields: ConvexObject,
+        timestamp: UnixTimestamp,
+    ) -> Self {
+        let messages = if message.is_empty() {
+            vec![]
+        } else {
+            vec![message]
+        };
+        let mut log_line = Self::new_developer_log_line(level, messages, timestamp);
+        let message_length: usize = log_line.messages.iter().map(|m| m.len()).sum();
+        if message_length + fields.size() <= MAX_LOG_LINE_LENGTH {
+            log_line.fields = Some(fields);
+        } else {
+            log_line.is_truncated = true;
+        }
+        log_line
+    }
 }
 
 impl Deref for LogLines {
@@ -292,6 +402,20 @@ impl LogLines {
         self.0.is_empty()
     }
 
+    /// Keeps the lines, including those of sub-functions, that satisfy
+    /// `predicate`.
+    pub fn filtered(self, predicate: &impl Fn(&LogLineStructured) -> bool) -> Self {
+        self.into_iter()
+            .filter_map(|log_line| match log_line {
+                LogLine::Structured(line) => predicate(&line).then_some(LogLine::Structured(line)),
+                LogLine::SubFunction { path, log_lines } => {
+                    let log_lines = log_lines.filtered(predicate);
+                    (!log_lines.is_empty()).then_some(LogLine::SubFunction { path, log_lines })
+                },
+            })
+            .collect()
+    }
+
     pub fn truncated(self, mut len: usize) -> Self {
         let mut log_lines = Self::default();
         for log_line in self {
@@ -353,15 +477,23 @@ impl Arbitrary for LogLineStructured {
             any::<bool>(),
             (u64::MIN..(i64::MAX as u64)),
             any::<Option<SystemLogMetadata>>(),
+            prop::option::of(any_with::<ConvexObject>((
+                prop::collection::size_range(0..4),
+                FieldType::User,
+                ValueBranching::small(),
+                ExcludeSetsAndMaps(true),
+                RestrictNaNs(true),
+            ))),
         )
             .prop_map(
-                |(messages, level, is_truncated, timestamp_ms, system_metadata)| {
+                |(messages, level, is_truncated, timestamp_ms, system_metadata, fields)| {
                     LogLineStructured {
                         messages: messages.into(),
                         level,
                         is_truncated,
                         timestamp: UnixTimestamp::from_millis(timestamp_ms),
                         system_metadata,
+                        fields,
                     }
                 },
             )
@@ -468,6 +600,7 @@ impl LogLine {
             is_truncated: false,
             timestamp,
             system_metadata: Some(system_log_metadata),
+            fields: None,
         })
     }
 }
@@ -481,12 +614,14 @@ impl HeapSize for LogLine {
                 timestamp,
                 is_truncated,
                 system_metadata,
+                fields,
             }) => {
                 messages.heap_size()
                     + level.heap_size()
                     + timestamp.heap_size()
                     + is_truncated.heap_size()
                     + system_metadata.heap_size()
+                    + fields.heap_size()
             },
             LogLine::SubFunction { path, log_lines } => path.heap_size() + log_lines.heap_size(),
         }
@@ -511,6 +646,9 @@ impl TryFrom<ConvexValue> for LogLine {
                         let timestamp = remove_int64(&mut fields, "timestamp")?;
                         let system_metadata: Option<SystemLogMetadata> =
                             remove_nullable_object(&mut fields, "system_metadata")?;
+                        // Lines persisted before structured logging don't have fields.
+                        let log_fields: Option<ConvexObject> =
+                            remove_nullable_object(&mut fields, "fields")?;
 
                         LogLine::Structured(LogLineStructured {
                             messages: messages.clone().into(),
@@ -518,6 +656,7 @@ impl TryFrom<ConvexValue> for LogLine {
                             level: LogLevel::from_str(&level)?,
                             timestamp: UnixTimestamp::from_millis(timestamp.try_into()?),
                             system_metadata,
+                            fields: log_fields,
                         })
                     },
                     Some("SubFunction") => {
@@ -556,18 +695,24 @@ impl TryFrom<LogLine> for ConvexValue {
                 is_truncated,
                 timestamp,
                 system_metadata,
+                fields,
             }) => {
                 let timestamp_ms: i64 = timestamp.as_ms_since_epoch()?.try_into()?;
                 let system_metadata_value = match system_metadata {
                     Some(m) => ConvexValue::try_from(m)?,
                     None => ConvexValue::Null,
                 };
+                let fields_value = match fields {
+                    Some(fields) => ConvexValue::Object(fields),
+                    None => ConvexValue::Null,
+                };
                 ConvexValue::Object(obj!(
                     "messages" => messages.into_iter().map(ConvexValue::try_from).try_collect::<_, Vec<_>, _>()?,
                     "level" => level.to_string(),
                     "is_truncated" => is_truncated,
                     "timestamp" => timestamp_ms,
                     "system_metadata" => system_metadata_value,
+                    "fields" => fields_value,
                 )?)
             },
             LogLine::SubFunction { path, log_lines } => ConvexValue::Object(obj!(
@@ -589,6 +734,9 @@ pub struct LogLineJson {
     timestamp: u64,
     level: String,
     system_metadata: Option<SystemLogMetadataJson>,
+    /// Encoded as Convex JSON so the fields' types round trip.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fields: Option<JsonValue>,
     #[serde(skip_serializing_if = "Option::is_none")]
     component_path: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -649,6 +797,10 @@ impl TryFrom<JsonValue> for LogLineStructured {
             timestamp: UnixTimestamp::from_millis(log_line_json.timestamp),
             level: log_line_json.level.parse()?,
             system_metadata: log_line_json.system_metadata.map(SystemLogMetadata::from),
+            fields: log_line_json
+                .fields
+                .map(ConvexObject::try_from)
+                .transpose()?,
         })
     }
 }
@@ -670,6 +822,7 @@ impl From<LogLine> for pb::outcome::LogLine {
                 is_truncated,
                 timestamp,
                 system_metadata,
+                fields,
             }) => pb::outcome::LogLine {
                 log_type: Some(pb::outcome::log_line::LogType::Line(
                     pb::outcome::StructuredLogLine {
@@ -679,6 +832,7 @@ impl From<LogLine> for pb::outcome::LogLine {
                         timestamp: Some(timestamp.into()),
                         system_metadata: system_metadata
                             .map(|m| pb::outcome::SystemLogMetadata { code: m.code }),
+                        fields: fields.map(|fields| JsonValue::from(fields).to_string()),
                     },
                 )),
             },
@@ -715,6 +869,12 @@ impl TryFrom<pb::outcome::LogLine> for LogLine {
                     system_metadata: line
                         .system_metadata
                         .map(|m| SystemLogMetadata { code: m.code }),
+                    fields: line
+                        .fields
+                        .map(|fields| {
+                            ConvexObject::try_from(serde_json::from_str::<JsonValue>(&fields)?)
+                        })
+                        .transpose()?,
                 })
             },
             Some(pb::outcome::log_line::LogType::SubFunction(sub_function)) => {
@@ -759,14 +919,18 @@ mod tests {
     use proptest::prelude::*;
     use serde_json::Value as JsonValue;
     use value::{
+        assert_obj,
         testing::assert_roundtrips,
         ConvexValue,
     };
 
     use crate::{
         log_lines::{
+            LogFieldMatch,
+            LogLevel,
             LogLine,
             LogLineStructured,
+            LogLines,
         },
         runtime::UnixTimestamp,
     };
@@ -795,4 +959,33 @@ mod tests {
             UnixTimestamp::from_secs_f64(1733952824.).unwrap(),
         );
     }
+
+    #[test]
+    fn test_structured_log_line_fields() -> anyhow::Result<()> {
+        let timestamp = UnixTimestamp::from_millis(1000);
+        let line = LogLineStructured::new_structured_log_line(
+            LogLevel::Info,
+            "charged".to_string(),
+            assert_obj!("count" => 3.0, "user" => "alice"),
+            timestamp,
+        );
+        assert_eq!(
+            line.to_pretty_string(),
+            r#"[INFO] charged {count: 3.0, user: "alice"}"#
+        );
+        assert!(line.matches_field(&"user=alice".parse()?));
+        assert!(line.matches_field(&"count=3".parse()?));
+        assert!(line.matches_field(&"count=3.0".parse()?));
+        assert!(!line.matches_field(&"user=bob".parse()?));
+        assert!("user".parse::<LogFieldMatch>().is_err());
+
+        let log_lines = LogLines::from(vec![
+            LogLine::Structured(line.clone()),
+            LogLine::new_developer_log_line(LogLevel::Log, vec!["hi".to_string()], timestamp),
+        ]);
+        let user_match: LogFieldMatch = "user=alice".parse()?;
+        let filtered = log_lines.filtered(&|line| line.matches_field(&user_match));
+        assert_eq!(filtered, LogLines::from(vec![LogLine::Structured(line)]));
+        Ok(())
+    }
 }
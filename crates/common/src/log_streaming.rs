@@ -15,7 +15,10 @@ use serde_json::{
     json,
     Value as JsonValue,
 };
-use value::heap_size::HeapSize;
+use value::{
+    export::ValueFormat,
+    heap_size::HeapSize,
+};
 
 use crate::{
     components::ComponentPath,
@@ -352,19 +355,35 @@ impl LogEvent {
                         timestamp,
                         is_truncated,
                         system_metadata,
+                        fields,
                     } = log_line;
                     let timestamp_ms = timestamp
                         .as_ms_since_epoch()
                         .map_err(serde::ser::Error::custom)?;
-                    serialize_map!({
-                        "timestamp": timestamp_ms,
-                        "topic": "console",
-                        "function": function_source,
-                        "log_level": level.to_string(),
-                        "message": messages.join(" "),
-                        "is_truncated": is_truncated,
-                        "system_code": system_metadata.as_ref().map(|s| &s.code)
-                    })
+                    let mut map_builder = serializer.serialize_map(None)?;
+                    map_builder.serialize_entry("timestamp", &timestamp_ms)?;
+                    map_builder.serialize_entry("topic", "console")?;
+                    map_builder.serialize_entry("function", &function_source)?;
+                    map_builder.serialize_entry("log_level", &level.to_string())?;
+                    map_builder.serialize_entry("message", &messages.join(" "))?;
+                    map_builder.serialize_entry("is_truncated", is_truncated)?;
+                    map_builder.serialize_entry(
+                        "system_code",
+                        &system_metadata.as_ref().map(|s| &s.code),
+                    )?;
+                    // Lines logged with `ctx.log` also carry their fields and the
+                    // execution's id, so records from one execution can be grouped.
+                    if let Some(fields) = fields {
+                        map_builder.serialize_entry(
+                            "fields",
+                            &fields.clone().export(ValueFormat::ConvexCleanJSON),
+                        )?;
+                        map_builder.serialize_entry(
+                            "execution_id",
+                            &source.context.execution_id.to_string(),
+                        )?;
+                    }
+                    map_builder.end()
                 },
                 StructuredLogEvent::FunctionExecution {
                     source,
@@ -552,6 +571,7 @@ mod tests {
         json,
         Value as JsonValue,
     };
+    use value::assert_obj;
 
     use crate::{
         components::ComponentPath,
@@ -624,4 +644,31 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn test_serialization_of_structured_console_log_event() -> anyhow::Result<()> {
+        let timestamp = UnixTimestamp::from_millis(1000);
+        let source = FunctionEventSource::new_for_test();
+        let execution_id = source.context.execution_id.to_string();
+        let event = LogEvent {
+            timestamp,
+            event: StructuredLogEvent::Console {
+                source,
+                log_line: LogLineStructured::new_structured_log_line(
+                    LogLevel::Info,
+                    "charged".to_string(),
+                    assert_obj!("amount" => 42.5, "currency" => "usd"),
+                    timestamp,
+                ),
+            },
+        };
+        let fields = event.to_json_map(LogEventFormatVersion::default())?;
+        assert_eq!(fields["message"], "charged");
+        assert_eq!(
+            fields["fields"],
+            json!({ "amount": 42.5, "currency": "usd" })
+        );
+        assert_eq!(fields["execution_id"], execution_id);
+        Ok(())
+    }
 }
@@ -5,6 +5,7 @@ use metrics::{
     log_counter_with_labels,
     log_distribution,
     log_gauge,
+    log_gauge_with_labels,
     register_convex_counter,
     register_convex_gauge,
     register_convex_histogram,
@@ -43,6 +44,38 @@ pub fn log_codel_queue_overloaded(overloaded: bool) {
     )
 }
 
+register_convex_gauge!(
+    COMMON_ADAPTIVE_PACING_FACTOR,
+    "Rate background work runs at relative to its configured rate",
+    &["worker"]
+);
+pub fn log_adaptive_pacing_factor(worker: &'static str, factor: f64) {
+    log_gauge_with_labels(
+        &COMMON_ADAPTIVE_PACING_FACTOR,
+        factor,
+        vec![StaticMetricLabel::new("worker", worker)],
+    )
+}
+
+register_convex_gauge!(
+    COMMON_ADAPTIVE_PACING_COMMIT_LATENCY_SECONDS,
+    "Moving average of commit latency that background work is paced against"
+);
+pub fn log_adaptive_pacing_commit_latency(latency: Duration) {
+    log_gauge(
+        &COMMON_ADAPTIVE_PACING_COMMIT_LATENCY_SECONDS,
+        latency.as_secs_f64(),
+    )
+}
+
+register_convex_gauge!(
+    COMMON_ADAPTIVE_PACING_POOL_UTILIZATION,
+    "Moving average of persistence pool utilization that background work is paced against"
+);
+pub fn log_adaptive_pacing_pool_utilization(utilization: f64) {
+    log_gauge(&COMMON_ADAPTIVE_PACING_POOL_UTILIZATION, utilization)
+}
+
 // static $metric: LazyLock<IntCounter> = LazyLock::new(|| {
 // register_int_counter_with_registry!(&*$metricname, $help,
 // CONVEX_METRICS_REGISTRY).unwrap()}); ==>> register_convex_counter!($metric,
@@ -123,6 +123,11 @@ pub enum PersistenceGlobalKey {
     IndexByIdIndex,
     /// Internal id of _index table, for bootstrapping.
     IndexTabletId,
+
+    /// Holder, fencing token and expiry of the leader lease when running
+    /// several backends with leader election. Specific to the running
+    /// processes, so it should not be copied.
+    LeaderLease,
 }
 
 impl From<PersistenceGlobalKey> for String {
@@ -145,6 +150,7 @@ impl From<PersistenceGlobalKey> for String {
             // NB: For compatibility, these are referred to as "table_id"s, not "tablet_id"s.
             PersistenceGlobalKey::TablesTabletId => "tables_table_id".to_string(),
             PersistenceGlobalKey::IndexTabletId => "index_table_id".to_string(),
+            PersistenceGlobalKey::LeaderLease => "leader_lease".to_string(),
         }
     }
 }
@@ -163,6 +169,7 @@ impl FromStr for PersistenceGlobalKey {
             "tables_table_id" => Ok(Self::TablesTabletId),
             "index_by_id" => Ok(Self::IndexByIdIndex),
             "index_table_id" => Ok(Self::IndexTabletId),
+            "leader_lease" => Ok(Self::LeaderLease),
             _ => anyhow::bail!("unrecognized persistence global key"),
         }
     }
@@ -458,6 +465,13 @@ pub trait PersistenceReader: Send + Sync + 'static {
 
     fn version(&self) -> PersistenceVersion;
 
+    /// Whether this persistence stores large top-level fields out of line (see
+    /// `common::document_chunks`), so user documents may exceed
+    /// `MAX_USER_SIZE` up to `MAX_CHUNKED_DOCUMENT_SIZE`.
+    fn supports_chunked_documents(&self) -> bool {
+        false
+    }
+
     async fn table_size_stats(&self) -> anyhow::Result<Vec<PersistenceTableSize>> {
         Ok(vec![])
     }
@@ -718,6 +732,10 @@ impl PersistenceSnapshot {
     pub fn persistence(&self) -> &dyn PersistenceReader {
         self.reader.as_ref()
     }
+
+    pub fn reader(&self) -> Arc<dyn PersistenceReader> {
+        self.reader.clone()
+    }
 }
 
 /// Test-only snapshot validator that doesn't validate anything.
@@ -16,11 +16,16 @@ use prometheus::{
     VMHistogramVec,
 };
 
+use crate::adaptive_pacing::record_pool_utilization;
+
 /// Stats for a pool of connections.
 #[derive(Clone)]
 pub struct ConnectionPoolStats {
     active_count: Arc<AtomicU64>,
     max_count: Arc<AtomicU64>,
+    /// The most connections the pool allows, or 0 if it's unbounded. Bounded
+    /// pools report their utilization for adaptive pacing.
+    capacity: u64,
 
     active_count_histogram: &'static VMHistogramVec,
     max_count_gauge: &'static GaugeVec,
@@ -37,16 +42,23 @@ impl ConnectionPoolStats {
         Self {
             active_count: Arc::new(AtomicU64::new(0)),
             max_count: Arc::new(AtomicU64::new(0)),
+            capacity: 0,
             active_count_histogram,
             max_count_gauge,
             labels,
         }
     }
+
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity as u64;
+        self
+    }
 }
 
 /// Tracks a single connection.
 pub struct ConnectionTracker {
     active_count: Arc<AtomicU64>,
+    capacity: u64,
     active_count_histogram: &'static VMHistogramVec,
 
     labels: Vec<StaticMetricLabel>,
@@ -62,6 +74,7 @@ impl ConnectionTracker {
             new_count as f64,
             stats.labels.clone(),
         );
+        record_pool_utilization(new_count, stats.capacity);
 
         // Update the max count.
         let previous_max = stats.max_count.fetch_max(new_count, Ordering::SeqCst);
@@ -70,6 +83,7 @@ impl ConnectionTracker {
 
         Self {
             active_count: stats.active_count.clone(),
+            capacity: stats.capacity,
             active_count_histogram: stats.active_count_histogram,
             labels: stats.labels.clone(),
         }
@@ -86,5 +100,6 @@ impl Drop for ConnectionTracker {
             new_count as f64,
             self.labels.clone(),
         );
+        record_pool_utilization(new_count, self.capacity);
     }
 }
@@ -1,7 +1,10 @@
 //! Types for querying a database.
 
 use std::{
-    collections::BTreeMap,
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
     fmt::Display,
     io::Write,
     ops::{
@@ -36,6 +39,7 @@ use value::{
     val,
     ConvexObject,
     ConvexValue,
+    Decimal128,
     TabletId,
 };
 
@@ -50,6 +54,7 @@ use crate::{
         StartIncluded,
     },
     paths::FieldPath,
+    score_expression::ScoreExpression,
     types::{
         GenericIndexName,
         IndexName,
@@ -526,6 +531,17 @@ impl Search {
                 .into_iter()
                 .map(|f| f.to_internal())
                 .collect::<anyhow::Result<Vec<InternalSearchFilterExpression>>>()?,
+            synonyms: SearchSynonyms::default(),
+            search_after: None,
+        })
+    }
+
+    /// The expression that ranks results in place of their BM25 scores, if
+    /// the search has one.
+    pub fn score_expression(&self) -> Option<&ScoreExpression> {
+        self.filters.iter().find_map(|filter| match filter {
+            SearchFilterExpression::Search(_, _, options) => options.score.as_ref(),
+            _ => None,
         })
     }
 }
@@ -546,6 +562,83 @@ pub struct InternalSearch {
     /// index's `searchField` and any number of `Eq` expressions comparing
     /// the index's `filterFields`.
     pub filters: Vec<InternalSearchFilterExpression>,
+
+    /// The index's synonyms, which expand the terms of the search filter.
+    pub synonyms: SearchSynonyms,
+
+    /// Only return results that rank after this position, so pagination can
+    /// continue past the first `MAX_CANDIDATE_REVISIONS` results.
+    pub search_after: Option<SearchAfter>,
+}
+
+/// The index key of a text search result. Results are ordered by descending
+/// score, then descending creation time, then ascending internal ID.
+pub fn search_result_index_key(
+    score: f64,
+    creation_time: f64,
+    internal_id: &[u8],
+) -> IndexKeyBytes {
+    let index_fields = vec![
+        Some(ConvexValue::Float64(-score)),
+        Some(ConvexValue::Float64(-creation_time)),
+        Some(ConvexValue::Bytes(
+            internal_id
+                .to_vec()
+                .try_into()
+                .expect("Could not convert internal ID to value"),
+        )),
+    ];
+    IndexKeyBytes(values_to_bytes(&index_fields))
+}
+
+/// A position in the results of a text search, given by the index key of the
+/// last result returned. Index keys can't be decoded, so candidates are
+/// compared by encoding their own keys.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct SearchAfter {
+    index_key: IndexKeyBytes,
+}
+
+impl SearchAfter {
+    pub fn new(index_key: IndexKeyBytes) -> Self {
+        Self { index_key }
+    }
+
+    pub fn index_key(&self) -> &IndexKeyBytes {
+        &self.index_key
+    }
+
+    /// Whether a result with this score, creation time, and internal ID ranks
+    /// after the position.
+    pub fn admits(&self, score: f32, creation_time: f64, internal_id: &[u8]) -> bool {
+        search_result_index_key(f64::from(score), creation_time, internal_id) > self.index_key
+    }
+}
+
+/// Groups of words that text search treats as interchangeable. A query term in
+/// a group also matches the other words in the group.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct SearchSynonyms {
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::collection::vec(proptest::collection::vec(\"[a-z]{1,8}\", \
+                        2..4), 0..4)"
+        )
+    )]
+    pub groups: Vec<Vec<String>>,
+}
+
+impl SearchSynonyms {
+    pub fn new(groups: Vec<Vec<String>>) -> Self {
+        Self { groups }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
 }
 
 impl InternalSearch {
@@ -597,33 +690,344 @@ impl HeapSize for FilterValue {
     }
 }
 
+/// A range of numbers to compare a filter field with. Filter values short
+/// enough to be stored unhashed sort the same as their values, so ranges
+/// compare their bytes and never contain hashed values.
+///
+/// Both ends are bounded: comparing in one direction is bounded in the other
+/// by the end of the value's type, like index ranges, so e.g. `gt(10.0)`
+/// doesn't match int64s or strings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct FilterRange {
+    pub lower: FilterValue,
+    pub lower_inclusive: bool,
+    pub upper: FilterValue,
+    pub upper_inclusive: bool,
+}
+
+impl FilterRange {
+    pub fn gt(value: &ConvexValue) -> anyhow::Result<Self> {
+        Self::above(value, false)
+    }
+
+    pub fn gte(value: &ConvexValue) -> anyhow::Result<Self> {
+        Self::above(value, true)
+    }
+
+    pub fn lt(value: &ConvexValue) -> anyhow::Result<Self> {
+        Self::below(value, false)
+    }
+
+    pub fn lte(value: &ConvexValue) -> anyhow::Result<Self> {
+        Self::below(value, true)
+    }
+
+    fn above(value: &ConvexValue, inclusive: bool) -> anyhow::Result<Self> {
+        let (_, (upper, upper_inclusive)) = Self::type_bounds(value)?;
+        Ok(Self {
+            lower: FilterValue::from_search_value(Some(value)),
+            lower_inclusive: inclusive,
+            upper,
+            upper_inclusive,
+        })
+    }
+
+    fn below(value: &ConvexValue, inclusive: bool) -> anyhow::Result<Self> {
+        let ((lower, lower_inclusive), _) = Self::type_bounds(value)?;
+        Ok(Self {
+            lower,
+            lower_inclusive,
+            upper: FilterValue::from_search_value(Some(value)),
+            upper_inclusive: inclusive,
+        })
+    }
+
+    /// The smallest and largest filter values of `value`'s type.
+    fn type_bounds(
+        value: &ConvexValue,
+    ) -> anyhow::Result<((FilterValue, bool), (FilterValue, bool))> {
+        let bounds = match value {
+            ConvexValue::Int64(_) => (
+                (ConvexValue::Int64(i64::MIN).sort_key().into(), true),
+                (ConvexValue::Int64(i64::MAX).sort_key().into(), true),
+            ),
+            // Every float64 sort key starts with the same tag.
+            ConvexValue::Float64(_) => {
+                let tag = value.sort_key()[0];
+                ((vec![tag].into(), true), (vec![tag + 1].into(), false))
+            },
+            _ => anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidSearchRangeFilter",
+                format!(
+                    "Search filters can only compare numbers with `gt`, `gte`, `lt` and `lte`, \
+                     but got a value of type {}",
+                    value.type_name()
+                ),
+            )),
+        };
+        Ok(bounds)
+    }
+
+    pub fn contains(&self, value: &[u8]) -> bool {
+        if value.len() >= MAX_FILTER_FIELD_LENGTH {
+            return false;
+        }
+        let above_lower = if self.lower_inclusive {
+            value >= &self.lower[..]
+        } else {
+            value > &self.lower[..]
+        };
+        let below_upper = if self.upper_inclusive {
+            value <= &self.upper[..]
+        } else {
+            value < &self.upper[..]
+        };
+        above_lower && below_upper
+    }
+}
+
+impl HeapSize for FilterRange {
+    fn heap_size(&self) -> usize {
+        self.lower.heap_size() + self.upper.heap_size()
+    }
+}
+
 pub fn search_value_to_bytes(value: Option<&ConvexValue>) -> Vec<u8> {
     FilterValue::from_search_value(value).into()
 }
 
+/// The most typos a text search can tolerate in each query term.
+pub const MAX_SEARCH_TYPOS: u8 = 2;
+
+/// Options for how the text in a `Search` filter matches indexed terms.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct TextSearchOptions {
+    /// The maximum Levenshtein distance between a query term and the terms it
+    /// matches, up to [`MAX_SEARCH_TYPOS`]. Short terms tolerate fewer typos
+    /// regardless, and typos never match a different first character.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=MAX_SEARCH_TYPOS")
+    )]
+    pub max_typos: u8,
+    /// Whether to find where the search text matched in each result.
+    pub highlight: Option<HighlightOptions>,
+    /// Whether to count the values of filter fields across the results.
+    pub facets: Option<FacetOptions>,
+    /// An expression that replaces the BM25 score when ranking results.
+    pub score: Option<ScoreExpression>,
+    /// Whether to match the words of the search text as prefixes of words in
+    /// documents, using the prefixes an autocomplete index stores. Prefix
+    /// searches don't tolerate typos.
+    pub prefix_search: bool,
+}
+
+impl TextSearchOptions {
+    pub fn new(max_typos: u8) -> anyhow::Result<Self> {
+        if max_typos > MAX_SEARCH_TYPOS {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidSearchOptions",
+                format!("maxTypos must be between 0 and {MAX_SEARCH_TYPOS}, got {max_typos}"),
+            ));
+        }
+        Ok(Self {
+            max_typos,
+            highlight: None,
+            facets: None,
+            score: None,
+            prefix_search: false,
+        })
+    }
+
+    pub fn with_highlight(self, highlight: HighlightOptions) -> Self {
+        Self {
+            highlight: Some(highlight),
+            ..self
+        }
+    }
+
+    pub fn with_facets(self, facets: FacetOptions) -> Self {
+        Self {
+            facets: Some(facets),
+            ..self
+        }
+    }
+
+    pub fn with_score(self, score: ScoreExpression) -> Self {
+        Self {
+            score: Some(score),
+            ..self
+        }
+    }
+
+    pub fn with_prefix_search(self) -> Self {
+        Self {
+            prefix_search: true,
+            ..self
+        }
+    }
+}
+
+/// The most snippets a search can extract from each result.
+pub const MAX_HIGHLIGHT_FRAGMENTS: u32 = 10;
+
+/// Bounds on the length of each snippet, in characters.
+pub const MIN_HIGHLIGHT_FRAGMENT_SIZE: u32 = 10;
+pub const MAX_HIGHLIGHT_FRAGMENT_SIZE: u32 = 1000;
+
+/// Options for returning where a search matched each result, along with
+/// snippets of the search field around the matches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct HighlightOptions {
+    /// The approximate length of each snippet, in characters.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "MIN_HIGHLIGHT_FRAGMENT_SIZE..=MAX_HIGHLIGHT_FRAGMENT_SIZE")
+    )]
+    pub fragment_size: u32,
+    /// The most snippets to return for each result. With zero, only the
+    /// offsets of matches are returned.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=MAX_HIGHLIGHT_FRAGMENTS")
+    )]
+    pub max_fragments: u32,
+}
+
+impl Default for HighlightOptions {
+    fn default() -> Self {
+        Self {
+            fragment_size: 100,
+            max_fragments: 3,
+        }
+    }
+}
+
+impl HighlightOptions {
+    pub fn new(fragment_size: Option<u32>, max_fragments: Option<u32>) -> anyhow::Result<Self> {
+        let default = Self::default();
+        let fragment_size = fragment_size.unwrap_or(default.fragment_size);
+        if !(MIN_HIGHLIGHT_FRAGMENT_SIZE..=MAX_HIGHLIGHT_FRAGMENT_SIZE).contains(&fragment_size) {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidSearchOptions",
+                format!(
+                    "highlight.fragmentSize must be between {MIN_HIGHLIGHT_FRAGMENT_SIZE} and \
+                     {MAX_HIGHLIGHT_FRAGMENT_SIZE}, got {fragment_size}"
+                ),
+            ));
+        }
+        let max_fragments = max_fragments.unwrap_or(default.max_fragments);
+        if max_fragments > MAX_HIGHLIGHT_FRAGMENTS {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidSearchOptions",
+                format!(
+                    "highlight.maxFragments must be between 0 and {MAX_HIGHLIGHT_FRAGMENTS}, got \
+                     {max_fragments}"
+                ),
+            ));
+        }
+        Ok(Self {
+            fragment_size,
+            max_fragments,
+        })
+    }
+}
+
+/// The most filter fields a search can count values of.
+pub const MAX_FACET_FIELDS: usize = 8;
+
+/// The most values a search can return counts for in each facet field.
+pub const MAX_FACET_VALUES: u32 = 100;
+
+/// Options for counting how many search results have each value of some
+/// filter fields, so search UIs can offer them as filters.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct FacetOptions {
+    /// The filter fields to count values of.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::collection::btree_set(proptest::arbitrary::any::<FieldPath>(), \
+                        1..=MAX_FACET_FIELDS)"
+        )
+    )]
+    pub fields: BTreeSet<FieldPath>,
+    /// The most values to return for each field, most common first.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "1..=MAX_FACET_VALUES")
+    )]
+    pub limit: u32,
+}
+
+impl FacetOptions {
+    pub fn new(fields: BTreeSet<FieldPath>, limit: Option<u32>) -> anyhow::Result<Self> {
+        if fields.is_empty() || fields.len() > MAX_FACET_FIELDS {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidSearchOptions",
+                format!(
+                    "facets.fields must list between 1 and {MAX_FACET_FIELDS} fields, got {}",
+                    fields.len()
+                ),
+            ));
+        }
+        let limit = limit.unwrap_or(10);
+        if !(1..=MAX_FACET_VALUES).contains(&limit) {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidSearchOptions",
+                format!("facets.limit must be between 1 and {MAX_FACET_VALUES}, got {limit}"),
+            ));
+        }
+        Ok(Self { fields, limit })
+    }
+}
+
 /// Filters to apply while querying a search index.
 #[derive(Clone, Debug, PartialEq)]
 pub enum SearchFilterExpression {
-    Search(FieldPath, String),
+    Search(FieldPath, String, TextSearchOptions),
     Eq(FieldPath, Option<ConvexValue>),
+    Gt(FieldPath, ConvexValue),
+    Gte(FieldPath, ConvexValue),
+    Lt(FieldPath, ConvexValue),
+    Lte(FieldPath, ConvexValue),
 }
 
 /// Filters to apply while querying a search index.
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
 pub enum InternalSearchFilterExpression {
-    Search(FieldPath, String),
+    Search(FieldPath, String, TextSearchOptions),
     Eq(FieldPath, FilterValue),
+    Range(FieldPath, FilterRange),
 }
 
 impl SearchFilterExpression {
     pub fn to_internal(self) -> anyhow::Result<InternalSearchFilterExpression> {
         let expression = match self {
-            Self::Search(field, s) => InternalSearchFilterExpression::Search(field, s),
+            Self::Search(field, s, options) => {
+                InternalSearchFilterExpression::Search(field, s, options)
+            },
             Self::Eq(field, v) => InternalSearchFilterExpression::Eq(
                 field,
                 FilterValue::from_search_value(v.as_ref()),
             ),
+            Self::Gt(field, v) => {
+                InternalSearchFilterExpression::Range(field, FilterRange::gt(&v)?)
+            },
+            Self::Gte(field, v) => {
+                InternalSearchFilterExpression::Range(field, FilterRange::gte(&v)?)
+            },
+            Self::Lt(field, v) => {
+                InternalSearchFilterExpression::Range(field, FilterRange::lt(&v)?)
+            },
+            Self::Lte(field, v) => {
+                InternalSearchFilterExpression::Range(field, FilterRange::lte(&v)?)
+            },
         };
         Ok(expression)
     }
@@ -704,6 +1108,7 @@ mod proptest {
             Order,
             QueryOperator,
             SearchFilterExpression,
+            TextSearchOptions,
         },
         types::IndexName,
     };
@@ -736,10 +1141,21 @@ mod proptest {
 
         fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
             prop_oneof![
-                any::<(FieldPath, String)>()
-                    .prop_map(|(field_path, s)| SearchFilterExpression::Search(field_path, s)),
+                any::<(FieldPath, String, TextSearchOptions)>().prop_map(
+                    |(field_path, s, options)| SearchFilterExpression::Search(
+                        field_path, s, options
+                    )
+                ),
                 any::<(FieldPath, Option<ConvexValue>)>()
                     .prop_map(|(field_path, v)| SearchFilterExpression::Eq(field_path, v)),
+                any::<(FieldPath, ConvexValue)>()
+                    .prop_map(|(field_path, v)| SearchFilterExpression::Gt(field_path, v)),
+                any::<(FieldPath, ConvexValue)>()
+                    .prop_map(|(field_path, v)| SearchFilterExpression::Gte(field_path, v)),
+                any::<(FieldPath, ConvexValue)>()
+                    .prop_map(|(field_path, v)| SearchFilterExpression::Lt(field_path, v)),
+                any::<(FieldPath, ConvexValue)>()
+                    .prop_map(|(field_path, v)| SearchFilterExpression::Lte(field_path, v)),
             ]
         }
     }
@@ -879,6 +1295,8 @@ mod proptest {
     }
 }
 
+/// Decimal arithmetic is exact, so only the operations that can't round
+/// (`add`, `subtract`, and `multiply`) pass `do_decimals`.
 fn binary_arithmetic<I, F>(
     name: &'static str,
     environ: &ConvexObject,
@@ -886,6 +1304,7 @@ fn binary_arithmetic<I, F>(
     r_expr: &Expression,
     do_ints: I,
     do_floats: F,
+    do_decimals: Option<fn(Decimal128, Decimal128) -> anyhow::Result<Decimal128>>,
 ) -> anyhow::Result<ConvexValue>
 where
     I: FnOnce(i64, i64) -> i64,
@@ -894,13 +1313,16 @@ where
     let l = l_expr.eval(environ)?;
     let r = r_expr.eval(environ)?;
 
-    let result = match (&l.0, &r.0) {
-        (Some(ConvexValue::Int64(l)), Some(ConvexValue::Int64(r))) => {
+    let result = match (&l.0, &r.0, do_decimals) {
+        (Some(ConvexValue::Int64(l)), Some(ConvexValue::Int64(r)), _) => {
             val!(do_ints(*l, *r))
         },
-        (Some(ConvexValue::Float64(l)), Some(ConvexValue::Float64(r))) => {
+        (Some(ConvexValue::Float64(l)), Some(ConvexValue::Float64(r)), _) => {
             val!(do_floats(*l, *r))
         },
+        (Some(ConvexValue::Decimal(l)), Some(ConvexValue::Decimal(r)), Some(do_decimals)) => {
+            ConvexValue::Decimal(do_decimals(*l, *r)?)
+        },
         (..) => {
             anyhow::bail!(ErrorMetadata::bad_request(
                 "EvalError",
@@ -963,11 +1385,17 @@ impl Expression {
                 let r = comparable_value(r_expr.eval(environ)?);
                 ConvexValue::from(l >= r)
             },
-            // Arithmetic operations only work on Int64 and Float64, so we don't have to worry about
-            // mapping those from table names to table IDs.
-            Expression::Add(l_expr, r_expr) => {
-                binary_arithmetic("add", environ, l_expr, r_expr, |l, r| l + r, |l, r| l + r)?
-            },
+            // Arithmetic operations only work on Int64, Float64, and Decimal, so we don't have to
+            // worry about mapping those from table names to table IDs.
+            Expression::Add(l_expr, r_expr) => binary_arithmetic(
+                "add",
+                environ,
+                l_expr,
+                r_expr,
+                |l, r| l + r,
+                |l, r| l + r,
+                Some(Decimal128::checked_add),
+            )?,
             Expression::Sub(l_expr, r_expr) => binary_arithmetic(
                 "subtract",
                 environ,
@@ -975,6 +1403,7 @@ impl Expression {
                 r_expr,
                 |l, r| l - r,
                 |l, r| l - r,
+                Some(Decimal128::checked_sub),
             )?,
             Expression::Mul(l_expr, r_expr) => binary_arithmetic(
                 "multiply",
@@ -983,6 +1412,7 @@ impl Expression {
                 r_expr,
                 |l, r| l * r,
                 |l, r| l * r,
+                Some(Decimal128::checked_mul),
             )?,
             Expression::Div(l_expr, r_expr) => binary_arithmetic(
                 "divide",
@@ -991,15 +1421,23 @@ impl Expression {
                 r_expr,
                 |l, r| l / r,
                 |l, r| l / r,
+                None,
+            )?,
+            Expression::Mod(l_expr, r_expr) => binary_arithmetic(
+                "mod",
+                environ,
+                l_expr,
+                r_expr,
+                |l, r| l % r,
+                |l, r| l % r,
+                None,
             )?,
-            Expression::Mod(l_expr, r_expr) => {
-                binary_arithmetic("mod", environ, l_expr, r_expr, |l, r| l % r, |l, r| l % r)?
-            },
             Expression::Neg(x_expr) => {
                 let x = x_expr.eval(environ)?;
                 match &x.0 {
                     Some(ConvexValue::Int64(x)) => ConvexValue::from(-*x),
                     Some(ConvexValue::Float64(x)) => ConvexValue::from(-*x),
+                    Some(ConvexValue::Decimal(x)) => ConvexValue::from(-*x),
                     _ => anyhow::bail!(ErrorMetadata::bad_request(
                         "EvalError",
                         format!("Cannot negate {x} (type {})", x.type_name()),
@@ -1161,6 +1599,8 @@ mod tests {
         maybe_val,
         query::{
             Cursor,
+            FilterRange,
+            FilterValue,
             IndexRange,
             IndexRangeExpression,
             MaybeValue,
@@ -1262,6 +1702,25 @@ mod tests {
             ),
             val!(-6),
         )?;
+        let decimal = |s: &str| -> anyhow::Result<Box<Expression>> {
+            Ok(Box::new(Expression::Literal(MaybeValue::from(
+                ConvexValue::Decimal(s.parse()?),
+            ))))
+        };
+        test_case(
+            // -(0.1 + 0.2) * 3 = -0.9, exactly
+            Expression::Mul(
+                Box::new(Expression::Neg(Box::new(Expression::Add(
+                    decimal("0.1")?,
+                    decimal("0.2")?,
+                )))),
+                decimal("3")?,
+            ),
+            ConvexValue::Decimal("-0.9".parse()?),
+        )?;
+        assert!(Expression::Div(decimal("1")?, decimal("3")?)
+            .eval(&assert_obj!())
+            .is_err());
         test_case(
             Expression::Not(Box::new(Expression::Literal(maybe_val!(true)))),
             val!(false),
@@ -1298,6 +1757,29 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_filter_range() -> anyhow::Result<()> {
+        let contains = |range: &FilterRange, value: ConvexValue| {
+            range.contains(&FilterValue::from_search_value(Some(&value)))
+        };
+        let range = FilterRange::gte(&ConvexValue::Float64(10.))?;
+        assert!(contains(&range, ConvexValue::Float64(10.)));
+        assert!(contains(&range, ConvexValue::Float64(f64::INFINITY)));
+        assert!(!contains(&range, ConvexValue::Float64(9.5)));
+        assert!(!contains(&range, ConvexValue::Int64(11)));
+        assert!(!contains(&range, val!("11")));
+
+        let range = FilterRange::lt(&ConvexValue::Int64(0))?;
+        assert!(contains(&range, ConvexValue::Int64(i64::MIN)));
+        assert!(contains(&range, ConvexValue::Int64(-1)));
+        assert!(!contains(&range, ConvexValue::Int64(0)));
+        assert!(!contains(&range, ConvexValue::Float64(-1.)));
+
+        assert!(FilterRange::gt(&val!("a")).is_err());
+        assert!(FilterRange::lte(&ConvexValue::Null).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_eval_undefined() -> anyhow::Result<()> {
         let environ = assert_obj!(
@@ -277,6 +277,17 @@ pub trait Runtime: Clone + Sync + Send + 'static {
     /// Sleep for the given duration.
     fn wait(&self, duration: Duration) -> Pin<Box<dyn FusedFuture<Output = ()> + Send + 'static>>;
 
+    /// Sleep until `system_time()` has advanced by `duration`. Use this instead
+    /// of `wait` for deadlines computed from `system_time()`, like the next
+    /// scheduled job, since a development deployment's wall clock can be
+    /// frozen or advanced independently of real time.
+    fn wait_for_system_time(
+        &self,
+        duration: Duration,
+    ) -> Pin<Box<dyn FusedFuture<Output = ()> + Send + 'static>> {
+        self.wait(duration)
+    }
+
     /// Spawn a future on the runtime's executor.
     ///
     /// The spawned task will be canceled if the returned `SpawnHandle` is
@@ -319,6 +330,11 @@ pub trait Runtime: Clone + Sync + Send + 'static {
         )
     }
 
+    /// `unix_timestamp()` in milliseconds, as system tables store times.
+    fn unix_timestamp_ms(&self) -> anyhow::Result<i64> {
+        Ok(self.unix_timestamp().as_ms_since_epoch()?.try_into()?)
+    }
+
     /// Return (a potentially-virtualized) reading from a monotonic clock.
     fn monotonic_now(&self) -> tokio::time::Instant;
 
@@ -30,9 +30,13 @@ use super::{
         ObjectValidator,
         Validator,
     },
+    CheckConstraint,
     DatabaseSchema,
     DocumentSchema,
+    FieldDefault,
+    FieldMutability,
     IndexSchema,
+    VectorEmbedder,
     VectorIndexSchema,
 };
 use crate::{
@@ -43,9 +47,23 @@ use crate::{
             search_field_not_unique,
             vector_field_not_unique,
         },
-        vector_index::VectorDimensions,
+        text_index::{
+            SerializedTextAnalyzerConfig,
+            TextAnalyzerConfig,
+        },
+        vector_index::{
+            VectorDimensions,
+            VectorDistanceMetric,
+            VectorIndexKind,
+            VectorQuantization,
+        },
+        MAX_SEARCH_FIELD_WEIGHT,
+    },
+    json::{
+        JsonExpression,
+        JsonSerializable,
     },
-    json::JsonSerializable,
+    query::Expression,
     schemas::{
         invalid_top_level_type_in_schema,
         SearchIndexSchema,
@@ -119,6 +137,55 @@ pub struct TableDefinitionJson {
     search_indexes: Option<Vec<SearchIndexSchemaJson>>,
     vector_indexes: Option<Vec<VectorIndexSchemaJson>>,
     document_type: Option<ValidatorJson>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    defaults: Option<BTreeMap<String, FieldDefaultJson>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    immutable_fields: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    write_once_fields: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    checks: Option<Vec<CheckConstraintJson>>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckConstraintJson {
+    name: String,
+    /// A serialized filter expression, as produced by the filter builder.
+    expression: JsonValue,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+#[serde(tag = "type")]
+pub enum FieldDefaultJson {
+    Value { value: JsonValue },
+    Now,
+    TokenIdentifier,
+}
+
+impl TryFrom<FieldDefaultJson> for FieldDefault {
+    type Error = anyhow::Error;
+
+    fn try_from(j: FieldDefaultJson) -> anyhow::Result<Self> {
+        Ok(match j {
+            FieldDefaultJson::Value { value } => FieldDefault::Value(value.try_into()?),
+            FieldDefaultJson::Now => FieldDefault::Now,
+            FieldDefaultJson::TokenIdentifier => FieldDefault::TokenIdentifier,
+        })
+    }
+}
+
+impl From<FieldDefault> for FieldDefaultJson {
+    fn from(default: FieldDefault) -> Self {
+        match default {
+            FieldDefault::Value(value) => FieldDefaultJson::Value {
+                value: value.into(),
+            },
+            FieldDefault::Now => FieldDefaultJson::Now,
+            FieldDefault::TokenIdentifier => FieldDefaultJson::TokenIdentifier,
+        }
+    }
 }
 
 impl JsonSerializable for TableDefinition {
@@ -168,7 +235,8 @@ impl TryFrom<TableDefinitionJson> for TableDefinition {
         let search_indexes = j.search_indexes.unwrap_or_default();
         let vector_indexes = j.vector_indexes.unwrap_or_default();
 
-        let document_type = j.document_type.map(|t| t.try_into()).transpose()?;
+        let document_type: Option<DocumentSchema> =
+            j.document_type.map(|t| t.try_into()).transpose()?;
 
         let table_name: TableName = j
             .table_name
@@ -217,7 +285,7 @@ impl TryFrom<TableDefinitionJson> for TableDefinition {
             })?;
         validate_unique_index_fields(
             &vector_indexes,
-            |idx| (idx.vector_field.clone(), idx.dimension),
+            |idx| (idx.vector_field.clone(), idx.dimension, idx.distance_metric),
             |index1, index2| vector_field_not_unique(&table_name, index1, index2),
         )?;
 
@@ -245,12 +313,107 @@ impl TryFrom<TableDefinitionJson> for TableDefinition {
             }
         }
 
+        let defaults = j
+            .defaults
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(field_name, default)| {
+                let field_name: IdentifierFieldName = field_name.parse()?;
+                let invalid_default = |reason: &str| {
+                    ErrorMetadata::bad_request(
+                        "InvalidFieldDefault",
+                        format!(
+                            "In table \"{table_name}\" the default for `{field_name}` is invalid \
+                             because {reason}."
+                        ),
+                    )
+                };
+                anyhow::ensure!(
+                    !field_name.is_system(),
+                    invalid_default("system fields can't have defaults")
+                );
+                if let Some(document_type) = &document_type {
+                    anyhow::ensure!(
+                        document_type.can_contain_field(&FieldPath::new(vec![field_name.clone()])?),
+                        invalid_default("the field isn't in the table's schema")
+                    );
+                }
+                let default = default
+                    .try_into()
+                    .with_context(|| invalid_default("the value isn't a valid Convex value"))?;
+                anyhow::Ok((field_name, default))
+            })
+            .try_collect()?;
+
+        let mut mutability = BTreeMap::new();
+        let constrained_fields = j
+            .immutable_fields
+            .unwrap_or_default()
+            .into_iter()
+            .map(|field_name| (field_name, FieldMutability::Immutable))
+            .chain(
+                j.write_once_fields
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|field_name| (field_name, FieldMutability::WriteOnce)),
+            );
+        for (field_name, field_mutability) in constrained_fields {
+            let field_name: IdentifierFieldName = field_name.parse()?;
+            let invalid_constraint = |reason: &str| {
+                ErrorMetadata::bad_request(
+                    "InvalidFieldMutability",
+                    format!(
+                        "In table \"{table_name}\" `{field_name}` can't be {field_mutability} \
+                         because {reason}."
+                    ),
+                )
+            };
+            anyhow::ensure!(
+                !field_name.is_system(),
+                invalid_constraint("system fields are managed by Convex")
+            );
+            if let Some(document_type) = &document_type {
+                anyhow::ensure!(
+                    document_type.can_contain_field(&FieldPath::new(vec![field_name.clone()])?),
+                    invalid_constraint("the field isn't in the table's schema")
+                );
+            }
+            if let Some(existing) = mutability.insert(field_name.clone(), field_mutability) {
+                anyhow::bail!(invalid_constraint(&format!("it's already {existing}")));
+            }
+        }
+
+        let mut checks: Vec<CheckConstraint> = vec![];
+        for CheckConstraintJson { name, expression } in j.checks.unwrap_or_default() {
+            let invalid_check = |reason: &str| {
+                ErrorMetadata::bad_request(
+                    "InvalidCheckConstraint",
+                    format!(
+                        "In table \"{table_name}\" the check `{name}` is invalid because {reason}."
+                    ),
+                )
+            };
+            anyhow::ensure!(!name.is_empty(), invalid_check("it has no name"));
+            anyhow::ensure!(
+                checks.iter().all(|check| check.name != name),
+                invalid_check("another check has the same name")
+            );
+            let expression: JsonExpression = serde_json::from_value(expression)
+                .with_context(|| invalid_check("it isn't a filter expression"))?;
+            let expression = Expression::try_from(expression)
+                .with_context(|| invalid_check("it isn't a filter expression"))?;
+            checks.push(CheckConstraint { name, expression });
+        }
+
         Ok(Self {
             table_name,
             indexes,
             search_indexes,
             vector_indexes,
             document_type,
+            defaults,
+            mutability,
+            checks,
         })
     }
 }
@@ -265,6 +428,9 @@ impl TryFrom<TableDefinition> for TableDefinitionJson {
             search_indexes,
             vector_indexes,
             document_type,
+            defaults,
+            mutability,
+            checks,
         }: TableDefinition,
     ) -> anyhow::Result<Self> {
         let table_name = String::from(table_name);
@@ -285,12 +451,45 @@ impl TryFrom<TableDefinition> for TableDefinitionJson {
                 .map(VectorIndexSchemaJson::try_from)
                 .collect::<anyhow::Result<Vec<_>>>()?,
         );
+        let defaults = (!defaults.is_empty()).then(|| {
+            defaults
+                .into_iter()
+                .map(|(field_name, default)| (field_name.to_string(), default.into()))
+                .collect()
+        });
+        let fields_with = |field_mutability: FieldMutability| {
+            let fields: Vec<_> = mutability
+                .iter()
+                .filter(|(_, m)| **m == field_mutability)
+                .map(|(field_name, _)| field_name.to_string())
+                .collect();
+            (!fields.is_empty()).then_some(fields)
+        };
+        let immutable_fields = fields_with(FieldMutability::Immutable);
+        let write_once_fields = fields_with(FieldMutability::WriteOnce);
+        let checks = (!checks.is_empty())
+            .then(|| {
+                checks
+                    .into_iter()
+                    .map(|CheckConstraint { name, expression }| {
+                        anyhow::Ok(CheckConstraintJson {
+                            name,
+                            expression: serde_json::to_value(JsonExpression::from(expression))?,
+                        })
+                    })
+                    .try_collect()
+            })
+            .transpose()?;
         Ok(TableDefinitionJson {
             table_name,
             indexes,
             search_indexes,
             vector_indexes,
             document_type,
+            defaults,
+            immutable_fields,
+            write_once_fields,
+            checks,
         })
     }
 }
@@ -300,6 +499,8 @@ impl TryFrom<TableDefinition> for TableDefinitionJson {
 pub struct IndexSchemaJson {
     index_descriptor: String,
     fields: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    variant: Option<JsonValue>,
 }
 
 impl JsonSerializable for IndexSchema {
@@ -324,9 +525,20 @@ impl TryFrom<IndexSchemaJson> for IndexSchema {
             .map_err(|e: anyhow::Error| {
                 e.wrap_error_message(|s| format!("In index \"{index_descriptor}\": {s}"))
             })?;
+        let variant = j
+            .variant
+            .map(LiteralValidator::try_from)
+            .transpose()
+            .with_context(|| {
+                ErrorMetadata::bad_request(
+                    "InvalidIndexVariant",
+                    format!("In index \"{index_descriptor}\": variant must be a literal value"),
+                )
+            })?;
         Ok(Self {
             index_descriptor,
             fields,
+            variant,
         })
     }
 }
@@ -338,6 +550,7 @@ impl TryFrom<IndexSchema> for IndexSchemaJson {
         IndexSchema {
             index_descriptor,
             fields,
+            variant,
         }: IndexSchema,
     ) -> anyhow::Result<Self> {
         Ok(IndexSchemaJson {
@@ -346,6 +559,7 @@ impl TryFrom<IndexSchema> for IndexSchemaJson {
                 .into_iter()
                 .map(String::from)
                 .collect::<Vec<_>>(),
+            variant: variant.map(JsonValue::try_from).transpose()?,
         })
     }
 }
@@ -358,6 +572,23 @@ pub struct VectorIndexSchemaJson {
     dimensions: Option<u32>,
     dimension: Option<u32>,
     filter_fields: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    partition_field: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    distance_metric: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    index_kind: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    quantization: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    embedder: Option<VectorEmbedderJson>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct VectorEmbedderJson {
+    source_field: String,
+    action: String,
 }
 
 impl JsonSerializable for VectorIndexSchema {
@@ -381,6 +612,14 @@ impl TryFrom<VectorIndexSchemaJson> for VectorIndexSchema {
                 })
             })
             .collect::<anyhow::Result<BTreeSet<_>>>()?;
+        let partition_field = j
+            .partition_field
+            .map(|f| {
+                f.parse().with_context(|| {
+                    index_validation_error::invalid_index_field(&index_descriptor, &f)
+                })
+            })
+            .transpose()?;
         let dimension: VectorDimensions = match j.dimensions {
             Some(d) => d.try_into()?,
             // Support legacy alpha users
@@ -389,7 +628,34 @@ impl TryFrom<VectorIndexSchemaJson> for VectorIndexSchema {
                 None => anyhow::bail!("Missing dimensions field"),
             },
         };
-        Self::new(index_descriptor, vector_field, dimension, filter_fields)
+        let distance_metric = VectorDistanceMetric::parse(j.distance_metric.as_deref())?;
+        let index_kind = VectorIndexKind::parse(j.index_kind.as_deref())?;
+        let quantization = VectorQuantization::parse(j.quantization.as_deref())?;
+        let embedder = j
+            .embedder
+            .map(|embedder| -> anyhow::Result<_> {
+                Ok(VectorEmbedder {
+                    source_field: embedder.source_field.parse().with_context(|| {
+                        index_validation_error::invalid_index_field(
+                            &index_descriptor,
+                            &embedder.source_field,
+                        )
+                    })?,
+                    action: embedder.action,
+                })
+            })
+            .transpose()?;
+        Self::new(
+            index_descriptor,
+            vector_field,
+            dimension,
+            filter_fields,
+            partition_field,
+            distance_metric,
+            index_kind,
+            quantization,
+            embedder,
+        )
     }
 }
 
@@ -402,6 +668,11 @@ impl TryFrom<VectorIndexSchema> for VectorIndexSchemaJson {
             vector_field,
             dimension,
             filter_fields,
+            partition_field,
+            distance_metric,
+            index_kind,
+            quantization,
+            embedder,
             ..
         }: VectorIndexSchema,
     ) -> anyhow::Result<Self> {
@@ -414,6 +685,14 @@ impl TryFrom<VectorIndexSchema> for VectorIndexSchemaJson {
                 .into_iter()
                 .map(String::from)
                 .collect::<Vec<_>>(),
+            partition_field: partition_field.map(String::from),
+            distance_metric: (!distance_metric.is_default()).then(|| distance_metric.to_string()),
+            index_kind: (!index_kind.is_default()).then(|| index_kind.to_string()),
+            quantization: (!quantization.is_default()).then(|| quantization.to_string()),
+            embedder: embedder.map(|embedder| VectorEmbedderJson {
+                source_field: String::from(embedder.source_field),
+                action: embedder.action,
+            }),
         })
     }
 }
@@ -423,7 +702,13 @@ impl TryFrom<VectorIndexSchema> for VectorIndexSchemaJson {
 pub struct SearchIndexSchemaJson {
     index_descriptor: String,
     search_field: String,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    extra_search_fields: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    search_field_weights: BTreeMap<String, f64>,
     filter_fields: BTreeSet<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    analyzer: Option<SerializedTextAnalyzerConfig>,
 }
 
 impl JsonSerializable for SearchIndexSchema {
@@ -438,6 +723,30 @@ impl TryFrom<SearchIndexSchemaJson> for SearchIndexSchema {
         let search_field = j.search_field.parse().with_context(|| {
             index_validation_error::invalid_index_field(&index_descriptor, &j.search_field)
         })?;
+        let parse_field = |f: &String| {
+            f.parse::<FieldPath>()
+                .with_context(|| index_validation_error::invalid_index_field(&index_descriptor, f))
+        };
+        let extra_search_fields = j
+            .extra_search_fields
+            .iter()
+            .map(parse_field)
+            .collect::<anyhow::Result<BTreeSet<_>>>()?;
+        let search_field_weights = j
+            .search_field_weights
+            .iter()
+            .map(|(f, &weight)| {
+                let field_path = parse_field(f)?;
+                if weight.fract() != 0.0
+                    || !(1.0..=MAX_SEARCH_FIELD_WEIGHT as f64).contains(&weight)
+                {
+                    anyhow::bail!(index_validation_error::invalid_search_field_weight(
+                        &field_path
+                    ));
+                }
+                Ok((field_path, weight as u32))
+            })
+            .collect::<anyhow::Result<BTreeMap<_, _>>>()?;
         let filter_fields = j
             .filter_fields
             .into_iter()
@@ -447,8 +756,20 @@ impl TryFrom<SearchIndexSchemaJson> for SearchIndexSchema {
                 })
             })
             .collect::<anyhow::Result<BTreeSet<_>>>()?;
+        let analyzer = j
+            .analyzer
+            .map(TextAnalyzerConfig::try_from)
+            .transpose()?
+            .unwrap_or_default();
 
-        Self::new(index_descriptor, search_field, filter_fields)
+        Self::new(
+            index_descriptor,
+            search_field,
+            extra_search_fields,
+            search_field_weights,
+            filter_fields,
+            analyzer,
+        )
     }
 }
 
@@ -459,17 +780,26 @@ impl TryFrom<SearchIndexSchema> for SearchIndexSchemaJson {
         SearchIndexSchema {
             index_descriptor,
             search_field,
+            extra_search_fields,
+            search_field_weights,
             filter_fields,
+            analyzer,
             ..
         }: SearchIndexSchema,
     ) -> anyhow::Result<Self> {
         Ok(SearchIndexSchemaJson {
             index_descriptor: index_descriptor.to_string(),
             search_field: String::from(search_field),
+            extra_search_fields: extra_search_fields.into_iter().map(String::from).collect(),
+            search_field_weights: search_field_weights
+                .into_iter()
+                .map(|(f, weight)| (String::from(f), f64::from(weight)))
+                .collect(),
             filter_fields: filter_fields
                 .into_iter()
                 .map(String::from)
                 .collect::<BTreeSet<_>>(),
+            analyzer: (!analyzer.is_default()).then(|| analyzer.into()),
         })
     }
 }
@@ -572,6 +902,8 @@ pub enum ValidatorJson {
     Boolean,
     String,
     Bytes,
+    Decimal,
+    Datetime,
     Any,
     Literal {
         value: JsonValue,
@@ -617,6 +949,8 @@ impl TryFrom<ValidatorJson> for Validator {
             ValidatorJson::Boolean => Ok(Validator::Boolean),
             ValidatorJson::String => Ok(Validator::String),
             ValidatorJson::Bytes => Ok(Validator::Bytes),
+            ValidatorJson::Decimal => Ok(Validator::Decimal),
+            ValidatorJson::Datetime => Ok(Validator::DateTime),
             ValidatorJson::Any => Ok(Validator::Any),
             ValidatorJson::Literal { value } => Ok(Validator::Literal(value.try_into()?)),
             ValidatorJson::Id { table_name } => Ok(Validator::Id(table_name.parse()?)),
@@ -683,6 +1017,8 @@ impl TryFrom<Validator> for ValidatorJson {
             Validator::Boolean => ValidatorJson::Boolean,
             Validator::String => ValidatorJson::String,
             Validator::Bytes => ValidatorJson::Bytes,
+            Validator::Decimal => ValidatorJson::Decimal,
+            Validator::DateTime => ValidatorJson::Datetime,
             Validator::Literal(literal) => ValidatorJson::Literal {
                 value: literal.try_into()?,
             },
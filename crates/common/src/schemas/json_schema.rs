@@ -0,0 +1,113 @@
+//! Exports a schema's document types as JSON Schema or OpenAPI components, so
+//! that services outside Convex can validate or generate code for documents.
+
+use std::collections::BTreeMap;
+
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+use value::export::ValueFormat;
+
+use super::{
+    validator::AddTopLevelFields,
+    DatabaseSchema,
+    DocumentSchema,
+    TableDefinition,
+};
+use crate::{
+    document::{
+        CREATION_TIME_FIELD,
+        ID_FIELD,
+    },
+    json_schemas,
+};
+
+const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+/// OpenAPI 3.1 schema objects are JSON Schema 2020-12.
+const OPENAPI_VERSION: &str = "3.1.0";
+
+impl TableDefinition {
+    /// JSON Schema for the documents in this table, including `_id` and
+    /// `_creationTime`.
+    pub fn document_json_schema(&self, value_format: ValueFormat) -> JsonValue {
+        let table_name = &self.table_name;
+        match &self.document_type {
+            None | Some(DocumentSchema::Any) => {
+                let mut fields = BTreeMap::new();
+                for (field_name, schema) in [
+                    (&*ID_FIELD, json_schemas::id(table_name)),
+                    (
+                        &*CREATION_TIME_FIELD,
+                        json_schemas::float64(false, value_format),
+                    ),
+                ] {
+                    fields.insert(
+                        field_name.to_string(),
+                        json_schemas::FieldInfo {
+                            schema,
+                            optional: false,
+                        },
+                    );
+                }
+                json_schemas::open_object(fields)
+            },
+            Some(DocumentSchema::Union(variants)) => {
+                let mut schemas: Vec<_> = variants
+                    .iter()
+                    .map(|variant| {
+                        variant.to_json_schema(
+                            AddTopLevelFields::True(table_name.clone()),
+                            value_format,
+                        )
+                    })
+                    .collect();
+                if schemas.len() == 1 {
+                    schemas.remove(0)
+                } else {
+                    json_schemas::union(schemas)
+                }
+            },
+        }
+    }
+}
+
+impl DatabaseSchema {
+    /// A JSON Schema document with a definition under `$defs` for each table.
+    pub fn to_json_schema(&self, value_format: ValueFormat) -> JsonValue {
+        json!({
+            "$schema": JSON_SCHEMA_DIALECT,
+            "$defs": self.document_json_schemas(value_format),
+        })
+    }
+
+    /// An OpenAPI document with a schema component for each table.
+    pub fn to_openapi_components(&self, value_format: ValueFormat) -> JsonValue {
+        json!({
+            "openapi": OPENAPI_VERSION,
+            "info": {
+                "title": "Convex tables",
+                "version": "1",
+            },
+            "paths": {},
+            "components": {
+                "schemas": self.document_json_schemas(value_format),
+            },
+        })
+    }
+
+    fn document_json_schemas(
+        &self,
+        value_format: ValueFormat,
+    ) -> serde_json::Map<String, JsonValue> {
+        self.tables
+            .iter()
+            .map(|(table_name, table_definition)| {
+                (
+                    table_name.to_string(),
+                    table_definition.document_json_schema(value_format),
+                )
+            })
+            .collect()
+    }
+}
@@ -21,18 +21,22 @@ use shape_inference::{
     ShapeConfig,
     ShapeCounter,
 };
+use sync_types::UdfPath;
 #[cfg(any(test, feature = "testing"))]
 use value::TableType;
 use value::{
     id_v6::DeveloperDocumentId,
     ConvexObject,
     ConvexValue,
+    FieldName,
     IdentifierFieldName,
     Namespace,
     NamespacedTableMapping,
 };
 
 use self::validator::{
+    objects_discriminant,
+    LiteralValidator,
     ObjectValidator,
     ValidationError,
     Validator,
@@ -41,23 +45,39 @@ use crate::{
     bootstrap_model::index::{
         database_index::IndexedFields,
         index_validation_error,
-        vector_index::VectorDimensions,
+        text_index::{
+            DeveloperTextIndexConfig,
+            TextAnalyzerConfig,
+        },
+        vector_index::{
+            DeveloperVectorIndexConfig,
+            VectorDimensions,
+            VectorDistanceMetric,
+            VectorIndexKind,
+            VectorQuantization,
+        },
+        MAX_SEARCH_FIELD_WEIGHT,
         MAX_TEXT_INDEX_FILTER_FIELDS_SIZE,
+        MAX_TEXT_INDEX_SEARCH_FIELDS_SIZE,
         MAX_VECTOR_INDEX_FILTER_FIELDS_SIZE,
     },
     document::ResolvedDocument,
     paths::FieldPath,
+    query::Expression,
     types::{
         IndexDescriptor,
+        MaybeValue,
         TableName,
     },
     virtual_system_mapping::VirtualSystemMapping,
 };
 
 pub mod json;
+pub mod json_schema;
 #[cfg(test)]
 mod tests;
 pub mod validator;
+pub mod violations;
 
 pub const MAX_INDEXES_PER_TABLE: usize = 64;
 #[derive(derive_more::Display, Debug, Clone, PartialEq)]
@@ -122,6 +142,24 @@ impl SchemaEnforcementError {
     }
 }
 
+#[derive(derive_more::Display, Debug, Clone, PartialEq)]
+#[display(
+    "Failed to update document with ID \"{id}\" in table \"{table_name}\" because field      \
+     \"{field_name}\" is {mutability} in the schema"
+)]
+pub struct ImmutableFieldError {
+    pub table_name: TableName,
+    pub id: DeveloperDocumentId,
+    pub field_name: FieldName,
+    pub mutability: FieldMutability,
+}
+
+impl ImmutableFieldError {
+    pub fn to_error_metadata(self) -> ErrorMetadata {
+        ErrorMetadata::bad_request("ImmutableFieldError", self.to_string())
+    }
+}
+
 impl From<SchemaEnforcementError> for SchemaValidationError {
     fn from(value: SchemaEnforcementError) -> Self {
         match value {
@@ -172,6 +210,9 @@ macro_rules! db_schema {
                         search_indexes: Default::default(),
                         vector_indexes: Default::default(),
                         document_type: Some($document_schema),
+                        defaults: Default::default(),
+                        mutability: Default::default(),
+                        checks: vec![],
                     };
                     tables.insert(table_name, table_def);
                 )*
@@ -203,6 +244,9 @@ macro_rules! db_schema_not_validated {
                         search_indexes: Default::default(),
                         vector_indexes: Default::default(),
                         document_type: Some($document_schema),
+                        defaults: Default::default(),
+                        mutability: Default::default(),
+                        checks: vec![],
                     };
                     tables.insert(table_name, table_def);
                 )*
@@ -247,6 +291,11 @@ macro_rules! db_schema_with_vector_indexes {
                                 value::FieldPath::from_str($vector_field)?,
                                 1536u32.try_into()?,
                                 Default::default(),
+                                None,
+                                Default::default(),
+                                Default::default(),
+                                Default::default(),
+                                None,
                             )?,
                         );
                     )*
@@ -256,6 +305,9 @@ macro_rules! db_schema_with_vector_indexes {
                         search_indexes: Default::default(),
                         vector_indexes,
                         document_type: Some($document_schema),
+                        defaults: Default::default(),
+                        mutability: Default::default(),
+                        checks: vec![],
                     };
                     tables.insert(table_name, table_def);
                 )*
@@ -309,6 +361,23 @@ impl DatabaseSchema {
         virtual_system_mapping: &VirtualSystemMapping,
         table_shape: &Option<Shape<C, S>>,
     ) -> anyhow::Result<bool> {
+        // New check constraints have to be checked against every document.
+        let enforced_checks = match active_schema {
+            Some(active_schema) if active_schema.schema_validation => active_schema
+                .tables
+                .get(table_name)
+                .map(|table| &table.checks[..])
+                .unwrap_or_default(),
+            _ => &[],
+        };
+        if table_definition
+            .checks
+            .iter()
+            .any(|check| !enforced_checks.contains(check))
+        {
+            return Ok(true);
+        }
+
         let next_schema = table_definition.document_type.clone();
         let next_schema_validator: Validator = next_schema.into();
 
@@ -353,13 +422,16 @@ impl DatabaseSchema {
     ) -> Result<(), ValidationError> {
         if self.schema_validation
             && let Ok(table_name) = table_mapping.tablet_name(doc.id().tablet_id)
-            && let Some(document_schema) = self.schema_for_table(&table_name)
+            && let Some(table_definition) = self.tables.get(&table_name)
         {
-            return document_schema.check_value(
-                &doc.value().0,
-                table_mapping,
-                virtual_system_mapping,
-            );
+            if let Some(document_schema) = &table_definition.document_type {
+                document_schema.check_value(
+                    &doc.value().0,
+                    table_mapping,
+                    virtual_system_mapping,
+                )?;
+            }
+            return table_definition.check_constraints(&doc.value().0);
         }
         Ok(())
     }
@@ -393,6 +465,40 @@ impl DatabaseSchema {
             })
     }
 
+    /// Like `check_new_document`, but only checks `field_names` because the
+    /// document's other fields are unchanged from a version that matched.
+    pub fn check_patched_document(
+        &self,
+        doc: &ResolvedDocument,
+        table_name: TableName,
+        field_names: &BTreeSet<IdentifierFieldName>,
+        table_mapping: &NamespacedTableMapping,
+        virtual_system_mapping: &VirtualSystemMapping,
+    ) -> Result<(), SchemaEnforcementError> {
+        if !self.schema_validation {
+            return Ok(());
+        }
+        let Some(table_definition) = self.tables.get(&table_name) else {
+            return Ok(());
+        };
+        let result: Result<(), ValidationError> = try {
+            if let Some(document_schema) = &table_definition.document_type {
+                document_schema.check_fields(
+                    &doc.value().0,
+                    field_names,
+                    table_mapping,
+                    virtual_system_mapping,
+                )?;
+            }
+            // Constraints can relate any fields, so check the whole document.
+            table_definition.check_constraints(&doc.value().0)?;
+        };
+        result.map_err(|validation_error| SchemaEnforcementError::Document {
+            validation_error,
+            table_name,
+        })
+    }
+
     fn contains_table_as_reference(&self, table_name: &TableName) -> Option<TableName> {
         for table_schema in self.tables.values() {
             if let Some(document_schema) = &table_schema.document_type {
@@ -451,6 +557,17 @@ impl DatabaseSchema {
                 ));
             }
 
+            for index_schema in table_definition.indexes.values() {
+                if let Some(discriminant) = &index_schema.variant {
+                    Self::check_variant_index(
+                        table_name,
+                        table_definition,
+                        index_schema,
+                        discriminant,
+                    )?;
+                }
+            }
+
             if let Some((index_descriptor, field_path)) =
                 table_definition.vector_fields().find(|(_, vector_field)| {
                     !Self::is_vector_index_eligible(&table_definition.document_type, vector_field)
@@ -470,6 +587,60 @@ impl DatabaseSchema {
         Ok(())
     }
 
+    /// An index declared for one variant of a discriminated union must start
+    /// with the discriminant field, so it can only be queried for that
+    /// variant, and may only reference fields of that variant.
+    fn check_variant_index(
+        table_name: &TableName,
+        table_definition: &TableDefinition,
+        index_schema: &IndexSchema,
+        discriminant: &LiteralValidator,
+    ) -> anyhow::Result<()> {
+        let index_descriptor = &index_schema.index_descriptor;
+        let invalid = |reason: String| {
+            ErrorMetadata::bad_request(
+                "SchemaDefinitionError",
+                format!(
+                    "In table \"{table_name}\" the index \"{index_descriptor}\" is invalid \
+                     because {reason}."
+                ),
+            )
+        };
+        let Some((field_name, variant)) = table_definition
+            .document_type
+            .as_ref()
+            .and_then(|document_schema| document_schema.variant(discriminant))
+        else {
+            anyhow::bail!(invalid(
+                "it is declared for a variant, but the table's documents aren't a discriminated \
+                 union"
+                    .to_string()
+            ));
+        };
+        let Some(variant) = variant else {
+            anyhow::bail!(invalid(format!(
+                "no variant has `{field_name}` equal to {discriminant}"
+            )));
+        };
+        let fields: &[FieldPath] = &index_schema.fields;
+        if !matches!(fields.first().map(FieldPath::fields), Some([first]) if first == field_name) {
+            anyhow::bail!(invalid(format!(
+                "an index for a variant must start with the discriminant field `{field_name}`"
+            )));
+        }
+        let variant = Validator::Object(variant.clone());
+        if let Some(field_path) = fields
+            .iter()
+            .find(|field_path| !variant.can_contain_field(field_path))
+        {
+            anyhow::bail!(invalid(format!(
+                "it references the field {field_path} that does not exist in the variant with \
+                 `{field_name}` equal to {discriminant}"
+            )));
+        }
+        Ok(())
+    }
+
     fn is_vector_index_eligible(
         document_schema: &Option<DocumentSchema>,
         vector_field: &FieldPath,
@@ -537,9 +708,129 @@ pub struct TableDefinition {
     pub document_type: Option<DocumentSchema>, /* FIXME: `Option` could be removed here, since
                                                 * `None` is handled the same way as
                                                 * `Some(DocumentSchema::Any)`. */
+    /// Values filled in for top-level fields that an inserted document
+    /// doesn't set.
+    pub defaults: BTreeMap<IdentifierFieldName, FieldDefault>,
+    /// Constraints on changing top-level fields after a document is inserted.
+    pub mutability: BTreeMap<IdentifierFieldName, FieldMutability>,
+    /// Invariants checked on every write to the table.
+    pub checks: Vec<CheckConstraint>,
+}
+
+/// A named boolean expression over a document's fields that every document
+/// in the table must satisfy, e.g. `quantity >= 0`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CheckConstraint {
+    pub name: String,
+    pub expression: Expression,
+}
+
+/// How a field may change once its document is inserted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, derive_more::Display)]
+pub enum FieldMutability {
+    /// The field keeps the value it was inserted with, or stays unset.
+    #[display("immutable")]
+    Immutable,
+    /// The field can be set once, and then keeps that value.
+    #[display("write-once")]
+    WriteOnce,
+}
+
+/// A value the write path fills in for a field that a new document leaves
+/// unset.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FieldDefault {
+    Value(ConvexValue),
+    /// The time of the write, which is the document's `_creationTime`.
+    Now,
+    /// The `tokenIdentifier` of the user making the write. The field is left
+    /// unset if the write isn't made by a user.
+    TokenIdentifier,
+}
+
+/// Values for the server-populated defaults of a write.
+pub struct ServerDefaults<'a> {
+    pub now: f64,
+    pub token_identifier: Option<&'a str>,
 }
 
 impl TableDefinition {
+    /// Fills in the defaults for fields that `value` doesn't set.
+    pub fn apply_defaults(
+        &self,
+        value: ConvexObject,
+        server_defaults: &ServerDefaults,
+    ) -> anyhow::Result<ConvexObject> {
+        if self
+            .defaults
+            .keys()
+            .all(|field_name| value.contains_key(&FieldName::from(field_name.clone())))
+        {
+            return Ok(value);
+        }
+        let mut fields = BTreeMap::from(value);
+        for (field_name, default) in &self.defaults {
+            let field_name = FieldName::from(field_name.clone());
+            if fields.contains_key(&field_name) {
+                continue;
+            }
+            let value = match default {
+                FieldDefault::Value(value) => value.clone(),
+                FieldDefault::Now => ConvexValue::Float64(server_defaults.now),
+                FieldDefault::TokenIdentifier => match server_defaults.token_identifier {
+                    Some(token_identifier) => ConvexValue::try_from(token_identifier.to_string())?,
+                    None => continue,
+                },
+            };
+            fields.insert(field_name, value);
+        }
+        fields.try_into()
+    }
+
+    /// Checks that updating a document from `old` to `new` doesn't change any
+    /// field the table declares immutable or write-once.
+    pub fn check_mutability(
+        &self,
+        old: &ResolvedDocument,
+        new: &ResolvedDocument,
+    ) -> Result<(), ImmutableFieldError> {
+        for (field_name, mutability) in &self.mutability {
+            let field_name = FieldName::from(field_name.clone());
+            let old_value = old.value().get(&field_name);
+            if old_value == new.value().get(&field_name) {
+                continue;
+            }
+            if *mutability == FieldMutability::WriteOnce && old_value.is_none() {
+                continue;
+            }
+            return Err(ImmutableFieldError {
+                table_name: self.table_name.clone(),
+                id: new.developer_id(),
+                field_name,
+                mutability: *mutability,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks that `object` satisfies every check constraint. A constraint is
+    /// only satisfied if its expression evaluates to `true`.
+    pub fn check_constraints(&self, object: &ConvexObject) -> Result<(), ValidationError> {
+        for check in &self.checks {
+            let satisfied = matches!(
+                check.expression.eval(object),
+                Ok(MaybeValue(Some(ConvexValue::Boolean(true))))
+            );
+            if !satisfied {
+                return Err(ValidationError::CheckConstraintViolation {
+                    object: object.clone(),
+                    constraint_name: check.name.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
     pub fn fields_referenced_in_indexes(
         &self,
     ) -> impl Iterator<Item = (&IndexDescriptor, &FieldPath)> {
@@ -556,8 +847,10 @@ impl TableDefinition {
         let search_index_fields =
             self.search_indexes
                 .iter()
-                .map(|(index_descriptor, search_index_schema)| {
-                    (index_descriptor, (&search_index_schema.search_field))
+                .flat_map(|(index_descriptor, search_index_schema)| {
+                    std::iter::once(&search_index_schema.search_field)
+                        .chain(&search_index_schema.extra_search_fields)
+                        .map(move |field_path| (index_descriptor, field_path))
                 });
 
         let search_index_filter_fields =
@@ -632,6 +925,9 @@ impl proptest::arbitrary::Arbitrary for TableDefinition {
                                 .map(|i| (i.index_descriptor.clone(), i))
                                 .collect(),
                             document_type,
+                            defaults: BTreeMap::new(),
+                            mutability: BTreeMap::new(),
+                            checks: vec![],
                         })
                     } else {
                         None
@@ -646,6 +942,10 @@ impl proptest::arbitrary::Arbitrary for TableDefinition {
 pub struct IndexSchema {
     pub index_descriptor: IndexDescriptor,
     pub fields: IndexedFields,
+    /// For a table whose documents are a discriminated union, the discriminant
+    /// of the variant this index is declared for.
+    #[cfg_attr(any(test, feature = "testing"), proptest(value = "None"))]
+    pub variant: Option<LiteralValidator>,
 }
 
 impl Display for IndexSchema {
@@ -659,11 +959,21 @@ impl Display for IndexSchema {
 pub struct SearchIndexSchema {
     pub index_descriptor: IndexDescriptor,
     pub search_field: FieldPath,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "prop::collection::btree_set(any::<FieldPath>(), 0..4)")
+    )]
+    pub extra_search_fields: BTreeSet<FieldPath>,
+    /// Weights for any of the search fields. Search fields without a weight
+    /// have weight 1.
+    #[cfg_attr(any(test, feature = "testing"), proptest(value = "BTreeMap::new()"))]
+    pub search_field_weights: BTreeMap<FieldPath, u32>,
     #[cfg_attr(
         any(test, feature = "testing"),
         proptest(strategy = "prop::collection::btree_set(any::<FieldPath>(), 0..8)")
     )]
     pub filter_fields: BTreeSet<FieldPath>,
+    pub analyzer: TextAnalyzerConfig,
 
     // Private field to force all creations to go through the constructor.
     _pd: PhantomData<()>,
@@ -673,20 +983,58 @@ impl SearchIndexSchema {
     pub fn new(
         index_descriptor: IndexDescriptor,
         search_field: FieldPath,
+        extra_search_fields: BTreeSet<FieldPath>,
+        search_field_weights: BTreeMap<FieldPath, u32>,
         filter_fields: BTreeSet<FieldPath>,
+        analyzer: TextAnalyzerConfig,
     ) -> anyhow::Result<Self> {
         if filter_fields.len() > MAX_TEXT_INDEX_FILTER_FIELDS_SIZE {
             anyhow::bail!(index_validation_error::too_many_filter_fields(
                 MAX_TEXT_INDEX_FILTER_FIELDS_SIZE
             ));
         }
+        if extra_search_fields.len() + 1 > MAX_TEXT_INDEX_SEARCH_FIELDS_SIZE {
+            anyhow::bail!(index_validation_error::invalid_search_fields(format!(
+                "Search indexes may have up to {MAX_TEXT_INDEX_SEARCH_FIELDS_SIZE} search fields."
+            )));
+        }
+        if extra_search_fields.contains(&search_field) {
+            anyhow::bail!(index_validation_error::invalid_search_fields(format!(
+                "{search_field:?} is both the `searchField` and one of the `extraSearchFields`."
+            )));
+        }
+        for (field_path, weight) in &search_field_weights {
+            if *field_path != search_field && !extra_search_fields.contains(field_path) {
+                anyhow::bail!(index_validation_error::invalid_search_fields(format!(
+                    "{field_path:?} has a weight but isn't one of the index's search fields."
+                )));
+            }
+            if !(1..=MAX_SEARCH_FIELD_WEIGHT).contains(weight) {
+                anyhow::bail!(index_validation_error::invalid_search_field_weight(
+                    field_path
+                ));
+            }
+        }
         Ok(Self {
             index_descriptor,
             search_field,
+            extra_search_fields,
+            search_field_weights,
             filter_fields,
+            analyzer,
             _pd: PhantomData,
         })
     }
+
+    pub fn developer_config(&self) -> DeveloperTextIndexConfig {
+        DeveloperTextIndexConfig {
+            search_field: self.search_field.clone(),
+            extra_search_fields: self.extra_search_fields.clone(),
+            search_field_weights: self.search_field_weights.clone(),
+            filter_fields: self.filter_fields.clone(),
+            analyzer: self.analyzer.clone(),
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -700,31 +1048,94 @@ pub struct VectorIndexSchema {
         proptest(strategy = "prop::collection::btree_set(any::<FieldPath>(), 0..8)")
     )]
     pub filter_fields: BTreeSet<FieldPath>,
+    #[cfg_attr(any(test, feature = "testing"), proptest(value = "None"))]
+    pub partition_field: Option<FieldPath>,
+    pub distance_metric: VectorDistanceMetric,
+    pub index_kind: VectorIndexKind,
+    pub quantization: VectorQuantization,
+    /// Computes the vector field from a text field when it changes. Unlike the
+    /// rest of the index's configuration, changing this doesn't rebuild the
+    /// index.
+    #[cfg_attr(any(test, feature = "testing"), proptest(value = "None"))]
+    pub embedder: Option<VectorEmbedder>,
 
     // Private field to force all creations to go through the constructor.
     _pd: PhantomData<()>,
 }
 
+/// An action that the backend calls to compute a vector index's vectors,
+/// whenever a document's source text field is set to a new string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VectorEmbedder {
+    pub source_field: FieldPath,
+    /// A path like `embeddings:embed` to an action in the index's component,
+    /// called with `{ texts }` and returning one vector per text.
+    pub action: String,
+}
+
 impl VectorIndexSchema {
     pub fn new(
         index_descriptor: IndexDescriptor,
         vector_field: FieldPath,
         dimension: VectorDimensions,
         filter_fields: BTreeSet<FieldPath>,
+        partition_field: Option<FieldPath>,
+        distance_metric: VectorDistanceMetric,
+        index_kind: VectorIndexKind,
+        quantization: VectorQuantization,
+        embedder: Option<VectorEmbedder>,
     ) -> anyhow::Result<Self> {
         if filter_fields.len() > MAX_VECTOR_INDEX_FILTER_FIELDS_SIZE {
             anyhow::bail!(index_validation_error::too_many_filter_fields(
                 MAX_VECTOR_INDEX_FILTER_FIELDS_SIZE
             ));
         }
+        if let Some(ref partition_field) = partition_field
+            && !filter_fields.contains(partition_field)
+        {
+            anyhow::bail!(index_validation_error::invalid_vector_partition_field(
+                partition_field
+            ));
+        }
+        if let Some(ref embedder) = embedder {
+            if embedder.source_field == vector_field {
+                anyhow::bail!(index_validation_error::invalid_vector_embedder(
+                    &index_descriptor,
+                    "its source field can't be the vector field"
+                ));
+            }
+            if embedder.action.parse::<UdfPath>().is_err() {
+                anyhow::bail!(index_validation_error::invalid_vector_embedder(
+                    &index_descriptor,
+                    &format!("{:?} isn't a valid action path", embedder.action)
+                ));
+            }
+        }
         Ok(Self {
             index_descriptor,
             vector_field,
             dimension,
             filter_fields,
+            partition_field,
+            distance_metric,
+            index_kind,
+            quantization,
+            embedder,
             _pd: PhantomData,
         })
     }
+
+    pub fn developer_config(&self) -> DeveloperVectorIndexConfig {
+        DeveloperVectorIndexConfig {
+            dimensions: self.dimension,
+            vector_field: self.vector_field.clone(),
+            filter_fields: self.filter_fields.clone(),
+            partition_field: self.partition_field.clone(),
+            distance_metric: self.distance_metric,
+            index_kind: self.index_kind,
+            quantization: self.quantization,
+        }
+    }
 }
 
 /// [`DocumentSchema`] corresponds to the `DocumentSchema` TS type in
@@ -773,6 +1184,23 @@ impl DocumentSchema {
         Ok(())
     }
 
+    /// Checks only the given top-level fields of `value`. A union of several
+    /// object types is checked in full, since changing any field can change
+    /// which variant the document matches.
+    fn check_fields(
+        &self,
+        value: &ConvexObject,
+        field_names: &BTreeSet<IdentifierFieldName>,
+        table_mapping: &NamespacedTableMapping,
+        virtual_system_mapping: &VirtualSystemMapping,
+    ) -> Result<(), ValidationError> {
+        match self {
+            DocumentSchema::Union(validators) if validators.len() == 1 => validators[0]
+                .check_fields(value, field_names, table_mapping, virtual_system_mapping),
+            _ => self.check_value(value, table_mapping, virtual_system_mapping),
+        }
+    }
+
     /// Returns `true` when it is sometimes possible to have a field with the
     /// given path on the document if this table definition is enforced, or
     /// `false` when it is never possible.
@@ -790,6 +1218,22 @@ impl DocumentSchema {
         }
     }
 
+    /// If the documents are a discriminated union, the discriminant field and
+    /// the variant selected by `discriminant`, if there is one.
+    pub fn variant(
+        &self,
+        discriminant: &LiteralValidator,
+    ) -> Option<(&IdentifierFieldName, Option<&ObjectValidator>)> {
+        let DocumentSchema::Union(validators) = self else {
+            return None;
+        };
+        let field_name = objects_discriminant(&validators.iter().collect::<Vec<_>>())?;
+        let variant = validators
+            .iter()
+            .find(|validator| validator.literal_field(field_name) == Some(discriminant));
+        Some((field_name, variant))
+    }
+
     pub fn has_validator_for_system_field(&self) -> bool {
         match &self {
             DocumentSchema::Any => false,
@@ -848,6 +1292,19 @@ impl DocumentSchema {
             },
         }
     }
+
+    /// The paths of fields that hold an ID into `table`, possibly as one
+    /// option of a union. IDs nested in arrays, sets, records or maps aren't
+    /// included since they can't be indexed.
+    pub fn reference_field_paths(&self, table: &TableName) -> BTreeSet<FieldPath> {
+        let mut paths = BTreeSet::new();
+        if let Self::Union(options) = self {
+            for option in options {
+                option.collect_reference_field_paths(table, &mut vec![], &mut paths);
+            }
+        }
+        paths
+    }
 }
 
 const SEE_SCHEMA_DOCS: &str =
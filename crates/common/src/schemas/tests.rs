@@ -1,31 +1,57 @@
+use std::collections::BTreeSet;
+
 use cmd_util::env::env_config;
+use errors::ErrorMetadataAnyhowExt;
+use maplit::btreemap;
+use must_let::must_let;
 use proptest::prelude::*;
 use serde_json::json;
 use value::{
     assert_obj,
+    export::ValueFormat,
     ConvexObject,
+    ConvexValue,
     FieldName,
+    IdentifierFieldName,
     NamespacedTableMapping,
     TableMapping,
+    TableName,
     TableNamespace,
 };
 
 use crate::{
+    db_schema,
     db_schema_with_vector_indexes,
+    document::{
+        CreationTime,
+        ResolvedDocument,
+    },
     json::JsonSerializable,
     object_validator,
     schemas::{
         json::DatabaseSchemaJson,
         validator::{
             FieldValidator,
+            LiteralValidator,
             ValidationContext,
             ValidationError,
         },
+        violations::{
+            MigrationStep,
+            SchemaViolationReport,
+            TableViolations,
+        },
         DatabaseSchema,
         DocumentSchema,
+        FieldMutability,
+        SchemaEnforcementError,
+        ServerDefaults,
         Validator,
     },
-    testing::assert_roundtrips,
+    testing::{
+        assert_roundtrips,
+        TestIdGenerator,
+    },
     virtual_system_mapping::VirtualSystemMapping,
 };
 
@@ -139,6 +165,375 @@ fn test_document_schema_extra_field() -> anyhow::Result<()> {
     Ok(())
 }
 
+fn shapes_schema() -> serde_json::Value {
+    let field = |field_type: serde_json::Value| json!({"fieldType": field_type, "optional": false});
+    json!({
+        "type": "union",
+        "value": [
+            {
+                "type": "object",
+                "value": {
+                    "kind": field(json!({"type": "literal", "value": "circle"})),
+                    "radius": field(json!({"type": "number"})),
+                },
+            },
+            {
+                "type": "object",
+                "value": {
+                    "kind": field(json!({"type": "literal", "value": "square"})),
+                    "side": field(json!({"type": "number"})),
+                },
+            },
+        ],
+    })
+}
+
+#[test]
+fn test_discriminated_union_checks_matching_variant() -> anyhow::Result<()> {
+    let validator = Validator::json_deserialize_value(shapes_schema())?;
+    let check = |object: ConvexObject| {
+        validator.check_value(
+            &ConvexValue::Object(object),
+            &empty_table_mapping(),
+            &VirtualSystemMapping::default(),
+        )
+    };
+    check(assert_obj!("kind" => "circle", "radius" => 1.0))?;
+
+    // The error comes from the variant the discriminant selects rather than
+    // the union as a whole.
+    let err = check(assert_obj!("kind" => "square", "radius" => 1.0)).unwrap_err();
+    assert!(
+        matches!(&err, ValidationError::MissingRequiredField { field_name, .. } if field_name.to_string() == "side"),
+        "{err}"
+    );
+    let err = check(assert_obj!("kind" => "triangle")).unwrap_err();
+    assert!(
+        matches!(err, ValidationError::NoMatchingVariant { .. }),
+        "{err}"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_variant_index() -> anyhow::Result<()> {
+    let schema_with_index = |fields: serde_json::Value, variant: &str| {
+        DatabaseSchema::json_deserialize_value(json!({
+            "tables": [
+                {
+                    "tableName": "shapes",
+                    "documentType": shapes_schema(),
+                    "indexes": [
+                        {"indexDescriptor": "by_size", "fields": fields, "variant": variant},
+                    ],
+                    "searchIndexes": [],
+                },
+            ],
+            "schemaValidation": true,
+        }))
+    };
+    schema_with_index(json!(["kind", "radius"]), "circle")?.check_index_references()?;
+
+    let err = schema_with_index(json!(["kind", "side"]), "circle")?
+        .check_index_references()
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("does not exist in the variant"),
+        "{err}"
+    );
+    let err = schema_with_index(json!(["radius"]), "circle")?
+        .check_index_references()
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("must start with the discriminant"),
+        "{err}"
+    );
+    let err = schema_with_index(json!(["kind"]), "triangle")?
+        .check_index_references()
+        .unwrap_err();
+    assert!(err.to_string().contains("no variant"), "{err}");
+    Ok(())
+}
+
+#[test]
+fn test_document_json_schema() -> anyhow::Result<()> {
+    let message = object_validator!(
+        "kind" => FieldValidator::required_field_type(Validator::Literal(LiteralValidator::String("text".try_into()?))),
+        "body" => FieldValidator::optional_field_type(Validator::String)
+    );
+    let schema = db_schema!("messages" => DocumentSchema::Union(vec![message]));
+    let json_schema = schema.to_json_schema(ValueFormat::ConvexCleanJSON);
+    let messages = jsonschema::validator_for(&json_schema["$defs"]["messages"])?;
+    assert!(messages.is_valid(&json!({
+        "_id": "abc",
+        "_creationTime": 1.0,
+        "kind": "text",
+        "body": "hi",
+    })));
+    // The literal is exported as a constant and system fields are required.
+    assert!(!messages.is_valid(&json!({"_id": "abc", "_creationTime": 1.0, "kind": "image"})));
+    assert!(!messages.is_valid(&json!({"kind": "text"})));
+
+    let openapi = schema.to_openapi_components(ValueFormat::ConvexCleanJSON);
+    assert_eq!(
+        openapi["components"]["schemas"]["messages"],
+        json_schema["$defs"]["messages"]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_migration_plan() -> anyhow::Result<()> {
+    let name = object_validator!("name" => FieldValidator::required_field_type(Validator::String));
+    let name_and_age = object_validator!(
+        "name" => FieldValidator::required_field_type(Validator::String),
+        "age" => FieldValidator::required_field_type(Validator::Int64)
+    );
+    let active = db_schema!("users" => DocumentSchema::Union(vec![name.clone()]));
+    let pending = db_schema!("users" => DocumentSchema::Union(vec![name_and_age.clone()]));
+    let report = SchemaViolationReport {
+        tables: btreemap! {
+            "users".to_string() => TableViolations {
+                count: 3,
+                sample_ids: vec![],
+                first_error: "missing age".to_string(),
+            },
+        },
+    };
+    let plan = report.migration_plan(Some(&active), &pending)?;
+    let widened =
+        db_schema!("users" => DocumentSchema::Union(vec![name.clone(), name_and_age.clone()]));
+    assert_eq!(
+        plan.steps,
+        vec![
+            MigrationStep::WidenSchema { schema: widened },
+            MigrationStep::Backfill {
+                table_name: "users".parse()?,
+                document_count: 3,
+            },
+            MigrationStep::NarrowSchema { schema: pending },
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_check_mutability() -> anyhow::Result<()> {
+    let schema = DatabaseSchema::json_deserialize_value(json!({
+        "tables": [
+            {
+                "tableName": "posts",
+                "indexes": [],
+                "immutableFields": ["ownerId"],
+                "writeOnceFields": ["publishedAt"],
+            },
+        ],
+        "schemaValidation": true,
+    }))?;
+    let table = &schema.tables[&"posts".parse()?];
+    let mut id_generator = TestIdGenerator::new();
+    let id = id_generator.user_generate(&"posts".parse()?);
+    let doc = |value| ResolvedDocument::new(id, CreationTime::ONE, value);
+    let old = doc(assert_obj!("ownerId" => "a", "title" => "draft"))?;
+
+    // Other fields can change, and write-once fields can be set once.
+    let new = doc(assert_obj!("ownerId" => "a", "title" => "final", "publishedAt" => 1.0))?;
+    table.check_mutability(&old, &new)?;
+    let newer = doc(assert_obj!("ownerId" => "a", "title" => "final", "publishedAt" => 2.0))?;
+    let err = table.check_mutability(&new, &newer).unwrap_err();
+    assert_eq!(err.mutability, FieldMutability::WriteOnce);
+
+    let err = table
+        .check_mutability(&old, &doc(assert_obj!("ownerId" => "b"))?)
+        .unwrap_err();
+    assert_eq!(err.mutability, FieldMutability::Immutable);
+    let err = table
+        .check_mutability(&old, &doc(assert_obj!("title" => "draft"))?)
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("\"ownerId\" is immutable"),
+        "{err}"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_check_patched_document() -> anyhow::Result<()> {
+    let field = |field_type: serde_json::Value, optional: bool| json!({"fieldType": field_type, "optional": optional});
+    let schema = DatabaseSchema::json_deserialize_value(json!({
+        "tables": [
+            {
+                "tableName": "profiles",
+                "documentType": {
+                    "type": "object",
+                    "value": {
+                        "name": field(json!({"type": "string"}), false),
+                        "bio": field(json!({"type": "string"}), true),
+                        "settings": field(json!({
+                            "type": "object",
+                            "value": { "theme": field(json!({"type": "string"}), false) },
+                        }), false),
+                    },
+                },
+                "indexes": [],
+            },
+        ],
+        "schemaValidation": true,
+    }))?;
+    let table_name: TableName = "profiles".parse()?;
+    let mut id_generator = TestIdGenerator::new();
+    let id = id_generator.user_generate(&table_name);
+    let check = |value: ConvexObject, touched: &[&str]| {
+        let doc = ResolvedDocument::new(id, CreationTime::ONE, value)?;
+        let touched = touched
+            .iter()
+            .map(|field| field.parse())
+            .collect::<anyhow::Result<BTreeSet<IdentifierFieldName>>>()?;
+        anyhow::Ok(schema.check_patched_document(
+            &doc,
+            table_name.clone(),
+            &touched,
+            &empty_table_mapping(),
+            &VirtualSystemMapping::default(),
+        ))
+    };
+
+    // Only the touched fields are checked, so an untouched field that no
+    // longer matches doesn't fail the patch.
+    let valid = assert_obj!("name" => "emma", "settings" => {"theme" => "dark"});
+    assert_eq!(check(valid.clone(), &["settings"])?, Ok(()));
+    assert_eq!(check(valid, &["bio"])?, Ok(()));
+    assert_eq!(
+        check(
+            assert_obj!("name" => 1.0, "settings" => {"theme" => "light"}),
+            &["settings"],
+        )?,
+        Ok(())
+    );
+
+    let validation_error = |result: Result<(), SchemaEnforcementError>| {
+        must_let!(let Err(SchemaEnforcementError::Document { validation_error, .. }) = result);
+        validation_error
+    };
+    let err = validation_error(check(
+        assert_obj!("name" => "emma", "settings" => {"theme" => 1.0}),
+        &["settings"],
+    )?);
+    assert!(err.to_string().contains("settings.theme"), "{err}");
+    let err = validation_error(check(
+        assert_obj!("settings" => {"theme" => "dark"}),
+        &["name"],
+    )?);
+    assert!(
+        matches!(&err, ValidationError::MissingRequiredField { field_name, .. } if field_name.to_string() == "name"),
+        "{err}"
+    );
+    let err = validation_error(check(
+        assert_obj!("name" => "emma", "settings" => {"theme" => "dark"}, "extra" => true),
+        &["extra"],
+    )?);
+    assert!(
+        matches!(&err, ValidationError::ExtraField { field_name, .. } if field_name.to_string() == "extra"),
+        "{err}"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_check_constraints() -> anyhow::Result<()> {
+    let schema_json = json!({
+        "tables": [
+            {
+                "tableName": "events",
+                "indexes": [],
+                "checks": [
+                    {
+                        "name": "endsAfterStart",
+                        "expression": {
+                            "$gt": [{ "$field": "endTime" }, { "$field": "startTime" }],
+                        },
+                    },
+                    {
+                        "name": "nonNegativeQuantity",
+                        "expression": {
+                            "$gte": [{ "$field": "quantity" }, { "$literal": 0.0 }],
+                        },
+                    },
+                ],
+            },
+        ],
+        "schemaValidation": true,
+    });
+    let schema = DatabaseSchema::json_deserialize_value(schema_json.clone())?;
+    assert_eq!(
+        DatabaseSchema::json_deserialize(&schema.clone().json_serialize()?)?,
+        schema
+    );
+    let table = &schema.tables[&"events".parse()?];
+
+    table
+        .check_constraints(&assert_obj!("startTime" => 1.0, "endTime" => 2.0, "quantity" => 0.0))?;
+    let err = table
+        .check_constraints(&assert_obj!("startTime" => 2.0, "endTime" => 1.0, "quantity" => 0.0))
+        .unwrap_err();
+    assert!(err.to_string().contains("`endsAfterStart`"), "{err}");
+    let err = table
+        .check_constraints(&assert_obj!("startTime" => 1.0, "endTime" => 2.0, "quantity" => -1.0))
+        .unwrap_err();
+    assert!(err.to_string().contains("`nonNegativeQuantity`"), "{err}");
+    // A missing field doesn't satisfy a comparison.
+    table
+        .check_constraints(&assert_obj!("startTime" => 1.0, "endTime" => 2.0))
+        .unwrap_err();
+
+    let mut duplicate = schema_json;
+    duplicate["tables"][0]["checks"][1]["name"] = json!("endsAfterStart");
+    let err = DatabaseSchema::json_deserialize_value(duplicate).unwrap_err();
+    assert!(err.to_string().contains("same name"), "{err}");
+    Ok(())
+}
+
+#[test]
+fn test_apply_defaults() -> anyhow::Result<()> {
+    let schema = DatabaseSchema::json_deserialize_value(json!({
+        "tables": [
+            {
+                "tableName": "posts",
+                "indexes": [],
+                "defaults": {
+                    "status": {"type": "value", "value": "draft"},
+                    "publishedAt": {"type": "now"},
+                    "author": {"type": "tokenIdentifier"},
+                },
+            },
+        ],
+        "schemaValidation": true,
+    }))?;
+    let table = &schema.tables[&"posts".parse()?];
+    let server_defaults = ServerDefaults {
+        now: 1234.0,
+        token_identifier: None,
+    };
+    let value = table.apply_defaults(assert_obj!("status" => "live"), &server_defaults)?;
+    // Fields the document sets are kept, and the token identifier is skipped
+    // without a user.
+    assert_eq!(
+        value,
+        assert_obj!("status" => "live", "publishedAt" => 1234.0)
+    );
+
+    let server_defaults = ServerDefaults {
+        now: 1234.0,
+        token_identifier: Some("issuer|user"),
+    };
+    let value = table.apply_defaults(assert_obj!(), &server_defaults)?;
+    assert_eq!(
+        value,
+        assert_obj!("author" => "issuer|user", "publishedAt" => 1234.0, "status" => "draft")
+    );
+    Ok(())
+}
+
 #[test]
 fn test_nonexistent_table_name_reference() {
     // This schema has an ID that references "otherTable" but never defines
@@ -655,6 +1050,47 @@ fn test_json_backwards_compatibility() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_vector_index_embedder() -> anyhow::Result<()> {
+    let schema_json = |embedder: serde_json::Value| {
+        json!({
+            "tables": [
+                {
+                    "tableName": "documents",
+                    "indexes": [],
+                    "searchIndexes": [],
+                    "vectorIndexes": [
+                        {
+                            "indexDescriptor": "by_embedding",
+                            "vectorField": "embedding",
+                            "dimensions": 4,
+                            "filterFields": [],
+                            "embedder": embedder,
+                        },
+                    ],
+                },
+            ],
+            "schemaValidation": false,
+        })
+    };
+    let schema = DatabaseSchema::json_deserialize_value(schema_json(json!({
+        "sourceField": "text",
+        "action": "embeddings:embed",
+    })))?;
+    let index = &schema.tables[&"documents".parse()?].vector_indexes[&"by_embedding".parse()?];
+    let embedder = index.embedder.as_ref().unwrap();
+    assert_eq!(embedder.source_field, "text".parse()?);
+    assert_eq!(embedder.action, "embeddings:embed");
+
+    let error = DatabaseSchema::json_deserialize_value(schema_json(json!({
+        "sourceField": "embedding",
+        "action": "embeddings:embed",
+    })))
+    .unwrap_err();
+    assert_eq!(error.short_msg(), "InvalidVectorEmbedder");
+    Ok(())
+}
+
 fn empty_table_mapping() -> NamespacedTableMapping {
     TableMapping::new().namespace(TableNamespace::test_user())
 }
@@ -1609,3 +2045,51 @@ mod can_contain_field {
         Ok(())
     }
 }
+
+mod reference_field_paths {
+    use std::str::FromStr;
+
+    use maplit::btreeset;
+    use value::{
+        FieldPath,
+        TableName,
+    };
+
+    use crate::{
+        object_validator,
+        schemas::{
+            validator::FieldValidator,
+            DocumentSchema,
+            Validator,
+        },
+    };
+
+    #[test]
+    fn finds_id_fields_in_unions_and_nested_objects() -> anyhow::Result<()> {
+        let users = TableName::from_str("users")?;
+        let schema = DocumentSchema::Union(vec![object_validator!(
+            "author" => FieldValidator::required_field_type(Validator::Id(users.clone())),
+            "editor" => FieldValidator::optional_field_type(Validator::Union(vec![
+                Validator::Id(users.clone()),
+                Validator::Null,
+            ])),
+            "meta" => FieldValidator::required_field_type(Validator::Object(
+                object_validator!("owner" => FieldValidator::required_field_type(Validator::Id(users.clone())))
+            )),
+            "readers" => FieldValidator::required_field_type(Validator::Array(Box::new(
+                Validator::Id(users.clone())
+            ))),
+            "channel" => FieldValidator::required_field_type(Validator::Id("channels".parse()?)),
+        )]);
+        assert_eq!(
+            schema.reference_field_paths(&users),
+            btreeset! {
+                FieldPath::from_str("author")?,
+                FieldPath::from_str("editor")?,
+                FieldPath::from_str("meta.owner")?,
+            }
+        );
+        assert!(DocumentSchema::Any.reference_field_paths(&users).is_empty());
+        Ok(())
+    }
+}
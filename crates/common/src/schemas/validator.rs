@@ -1,8 +1,9 @@
-#[cfg(any(test, feature = "testing"))]
-use std::collections::BTreeSet;
 use std::{
     borrow::Borrow,
-    collections::BTreeMap,
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
     fmt::{
         self,
         Display,
@@ -11,6 +12,7 @@ use std::{
 };
 
 use errors::ErrorMetadata;
+use itertools::Itertools;
 #[cfg(any(test, feature = "testing"))]
 use proptest::prelude::*;
 use serde_json::{
@@ -67,6 +69,8 @@ pub enum Validator {
     Boolean,
     String,
     Bytes,
+    Decimal,
+    DateTime,
     Literal(LiteralValidator),
     Array(Box<Validator>),
     Set(Box<Validator>),
@@ -101,6 +105,8 @@ impl proptest::arbitrary::Arbitrary for Validator {
             Just(Validator::Boolean),
             Just(Validator::String),
             Just(Validator::Bytes),
+            Just(Validator::Decimal),
+            Just(Validator::DateTime),
             any::<LiteralValidator>().prop_map(Validator::Literal),
             Just(Validator::Any),
         ];
@@ -138,6 +144,8 @@ impl Display for Validator {
             Validator::Boolean => write!(f, "v.boolean()"),
             Validator::String => write!(f, "v.string()"),
             Validator::Bytes => write!(f, "v.bytes()"),
+            Validator::Decimal => write!(f, "v.decimal()"),
+            Validator::DateTime => write!(f, "v.datetime()"),
             Validator::Literal(literal) => write!(f, "v.literal({literal})"),
             Validator::Array(validator) => write!(f, "v.array({validator})"),
             Validator::Set(validator) => write!(f, "v.set({validator})"),
@@ -207,7 +215,9 @@ impl Validator {
             | (Validator::Int64, ConvexValue::Int64(_))
             | (Validator::Boolean, ConvexValue::Boolean(_))
             | (Validator::String, ConvexValue::String(_))
-            | (Validator::Bytes, ConvexValue::Bytes(_)) => return Ok(()),
+            | (Validator::Bytes, ConvexValue::Bytes(_))
+            | (Validator::Decimal, ConvexValue::Decimal(_))
+            | (Validator::DateTime, ConvexValue::DateTime(_)) => return Ok(()),
             (Validator::Literal(literal), value) => {
                 let literal_as_value: ConvexValue = literal.clone().into();
                 if value != &literal_as_value {
@@ -302,6 +312,34 @@ impl Validator {
                     );
                 }
 
+                // For a discriminated union, only the variant with the object's
+                // discriminant can match, so report that variant's error.
+                if let ConvexValue::Object(object) = value
+                    && let Some(field_name) = union_discriminant(validators)
+                    && let Some(discriminant) = object.get::<str>(field_name.borrow())
+                {
+                    let variant = validators.iter().find(|variant| {
+                        variant_discriminant(variant, field_name).is_some_and(|literal| {
+                            ConvexValue::from(literal.clone()) == *discriminant
+                        })
+                    });
+                    return match variant {
+                        Some(variant) => {
+                            variant.check_value_internal(value, all_tables_number_to_name, context)
+                        },
+                        None => Err(ValidationError::NoMatchingVariant {
+                            discriminant: discriminant.clone(),
+                            field_name: field_name.clone(),
+                            expected: validators
+                                .iter()
+                                .filter_map(|variant| variant_discriminant(variant, field_name))
+                                .map(|literal| literal.to_string())
+                                .join(", "),
+                            context: context.with(format!(".{field_name}")),
+                        }),
+                    };
+                }
+
                 // TODO: This is dropping the error messages from the individual
                 // validators. Maybe we should combine them if this fails?
                 for t in validators {
@@ -359,6 +397,8 @@ impl Validator {
             ShapeEnum::FieldName => Self::String,
             ShapeEnum::String => Self::String,
             ShapeEnum::Bytes => Self::Bytes,
+            ShapeEnum::Decimal => Self::Decimal,
+            ShapeEnum::DateTime => Self::DateTime,
             ShapeEnum::Array(array_type) => Self::Array(Box::new(Self::from_shape(
                 array_type.element(),
                 table_mapping,
@@ -504,6 +544,8 @@ impl Validator {
             | Validator::Boolean
             | Validator::String
             | Validator::Bytes
+            | Validator::Decimal
+            | Validator::DateTime
             | Validator::Array(_)
             | Validator::Set(_)
             | Validator::Record(..)
@@ -605,6 +647,8 @@ impl Validator {
             | Validator::Boolean
             | Validator::String
             | Validator::Bytes
+            | Validator::Decimal
+            | Validator::DateTime
             | Validator::Literal(_)
             // Values that map to `any`
             | Validator::Record(_, _)
@@ -655,11 +699,17 @@ impl Validator {
             Validator::Boolean => json_schemas::boolean(),
             Validator::String => json_schemas::string(),
             Validator::Bytes => json_schemas::bytes(value_format),
+            Validator::Decimal => json_schemas::decimal(value_format),
+            Validator::DateTime => json_schemas::datetime(value_format),
             Validator::Literal(literal_validator) => match literal_validator {
                 LiteralValidator::Float64(_) => json_schemas::float64(true, value_format),
                 LiteralValidator::Int64(_) => json_schemas::int64(value_format),
-                LiteralValidator::Boolean(_) => json_schemas::boolean(),
-                LiteralValidator::String(_) => json_schemas::string(),
+                LiteralValidator::Boolean(b) => {
+                    json_schemas::literal(json_schemas::boolean(), (*b).into())
+                },
+                LiteralValidator::String(s) => {
+                    json_schemas::literal(json_schemas::string(), JsonValue::from(&**s))
+                },
             },
             Validator::Array(element_validator) => {
                 json_schemas::array(element_validator.to_json_schema(value_format))
@@ -690,6 +740,28 @@ impl Validator {
         json_schema
     }
 
+    fn collect_reference_field_paths(
+        &self,
+        table: &TableName,
+        prefix: &mut Vec<IdentifierFieldName>,
+        paths: &mut BTreeSet<FieldPath>,
+    ) {
+        match self {
+            Self::Id(table_name) if table_name == table => {
+                if let Ok(path) = FieldPath::new(prefix.clone()) {
+                    paths.insert(path);
+                }
+            },
+            Self::Object(object) => object.collect_reference_field_paths(table, prefix, paths),
+            Self::Union(options) => {
+                for option in options {
+                    option.collect_reference_field_paths(table, prefix, paths);
+                }
+            },
+            _ => {},
+        }
+    }
+
     pub fn foreign_keys<'a>(&'a self) -> Box<dyn Iterator<Item = &'a TableName> + 'a> {
         Box::new(iter::from_coroutine(
             #[coroutine]
@@ -721,6 +793,8 @@ impl Validator {
                 Self::Any
                 | Self::Boolean
                 | Self::Bytes
+                | Self::Decimal
+                | Self::DateTime
                 | Self::String
                 | Self::Literal(_)
                 | Self::Null
@@ -739,6 +813,8 @@ impl Validator {
             | Self::Boolean
             | Self::String
             | Self::Bytes
+            | Self::Decimal
+            | Self::DateTime
             | Self::Literal(_)
             | Self::Any => false,
             Self::Set(_) | Self::Map(..) => true,
@@ -759,6 +835,8 @@ impl Validator {
             | Validator::Boolean
             | Validator::String
             | Validator::Bytes
+            | Validator::Decimal
+            | Validator::DateTime
             | Validator::Literal(_)
             | Validator::Array(_)
             | Validator::Set(_)
@@ -985,6 +1063,120 @@ impl ObjectValidator {
             .values()
             .flat_map(|field| field.validator.foreign_keys())
     }
+
+    pub(crate) fn collect_reference_field_paths(
+        &self,
+        table: &TableName,
+        prefix: &mut Vec<IdentifierFieldName>,
+        paths: &mut BTreeSet<FieldPath>,
+    ) {
+        for (field_name, field) in &self.0 {
+            prefix.push(field_name.clone());
+            field
+                .validator
+                .collect_reference_field_paths(table, prefix, paths);
+            prefix.pop();
+        }
+    }
+}
+
+impl ObjectValidator {
+    /// The value of a required literal field, which can discriminate this
+    /// object from the other variants of a union.
+    pub fn literal_field(&self, field_name: &IdentifierFieldName) -> Option<&LiteralValidator> {
+        match self.0.get(field_name) {
+            Some(FieldValidator {
+                validator: Validator::Literal(literal),
+                optional: false,
+            }) => Some(literal),
+            _ => None,
+        }
+    }
+
+    /// Checks only the given top-level fields of `object`, for updates that
+    /// leave its other fields unchanged.
+    pub fn check_fields<'a>(
+        &self,
+        object: &ConvexObject,
+        field_names: impl IntoIterator<Item = &'a IdentifierFieldName>,
+        table_mapping: &NamespacedTableMapping,
+        virtual_system_mapping: &VirtualSystemMapping,
+    ) -> Result<(), ValidationError> {
+        let all_tables_number_to_name =
+            all_tables_number_to_name(table_mapping, virtual_system_mapping);
+        let context = ValidationContext::new();
+        for field_name in field_names {
+            match (
+                self.0.get(field_name),
+                object.get::<str>(field_name.borrow()),
+            ) {
+                (Some(field_type), Some(value)) => field_type.validator.check_value_internal(
+                    value,
+                    &all_tables_number_to_name,
+                    context.with(format!(".{field_name}")),
+                )?,
+                (Some(field_type), None) if !field_type.optional => {
+                    return Err(ValidationError::MissingRequiredField {
+                        object: object.clone(),
+                        field_name: field_name.clone(),
+                        object_validator: self.clone(),
+                        context,
+                    });
+                },
+                (None, Some(_)) => {
+                    return Err(ValidationError::ExtraField {
+                        object: object.clone(),
+                        field_name: field_name.clone().into(),
+                        object_validator: self.clone(),
+                        context,
+                    });
+                },
+                (Some(_), None) | (None, None) => {},
+            }
+        }
+        Ok(())
+    }
+}
+
+fn variant_discriminant<'a>(
+    variant: &'a Validator,
+    field_name: &IdentifierFieldName,
+) -> Option<&'a LiteralValidator> {
+    match variant {
+        Validator::Object(object) => object.literal_field(field_name),
+        _ => None,
+    }
+}
+
+/// If every variant of a union is an object with a required literal field of
+/// the same name and the literals are distinct, returns that field's name. A
+/// value of the union is then validated against the single variant its
+/// discriminant selects.
+pub fn union_discriminant(variants: &[Validator]) -> Option<&IdentifierFieldName> {
+    let objects: Vec<_> = variants
+        .iter()
+        .map(|variant| match variant {
+            Validator::Object(object) => Some(object),
+            _ => None,
+        })
+        .collect::<Option<_>>()?;
+    objects_discriminant(&objects)
+}
+
+pub fn objects_discriminant<'a>(
+    objects: &[&'a ObjectValidator],
+) -> Option<&'a IdentifierFieldName> {
+    let [first, _, ..] = objects else {
+        return None;
+    };
+    first.0.keys().find(|field_name| {
+        let mut literals = BTreeSet::new();
+        objects.iter().all(|object| {
+            object
+                .literal_field(field_name)
+                .is_some_and(|literal| literals.insert(literal))
+        })
+    })
 }
 
 /// Object fields can be optional.
@@ -1102,6 +1294,25 @@ Validator: {validator}"
         validator: Validator,
         context: ValidationContext,
     },
+    #[display(
+        "`{discriminant}` does not match any variant of the union by its `{field_name}` field. \
+         Expected one of: {expected}.
+{context}"
+    )]
+    NoMatchingVariant {
+        discriminant: ConvexValue,
+        field_name: IdentifierFieldName,
+        expected: String,
+        context: ValidationContext,
+    },
+    #[display(
+        "Object violates the check constraint `{constraint_name}` of the table.
+Object: {object}"
+    )]
+    CheckConstraintViolation {
+        object: ConvexObject,
+        constraint_name: String,
+    },
 }
 
 #[cfg(test)]
@@ -1191,6 +1402,8 @@ mod tests {
             Validator::Boolean => assert_val!(false),
             Validator::String => assert_val!(""),
             Validator::Bytes => ConvexValue::Bytes(vec![1, 2, 3].try_into()?),
+            Validator::Decimal => ConvexValue::Decimal("1.5".parse()?),
+            Validator::DateTime => ConvexValue::DateTime("2024-01-01T00:00:00Z".parse()?),
             Validator::Literal(literal) => literal.into(),
             Validator::Array(v) => {
                 assert_val!([value_from_validator(*v, id_generator)?])
@@ -0,0 +1,241 @@
+//! Reports on existing documents that don't match a pushed schema, and staged
+//! plans for migrating them.
+
+use std::collections::BTreeMap;
+
+use itertools::Itertools;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::id_v6::DeveloperDocumentId;
+
+use super::{
+    json::DatabaseSchemaJson,
+    validator::ValidationError,
+    DatabaseSchema,
+    DocumentSchema,
+};
+use crate::types::TableName;
+
+/// Number of violating document IDs kept per table.
+pub const MAX_SAMPLE_IDS: usize = 10;
+
+/// Existing documents that failed validation against a pending schema, grouped
+/// by table.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct SchemaViolationReport {
+    pub tables: BTreeMap<String, TableViolations>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct TableViolations {
+    /// Number of documents in the table that don't match the schema.
+    pub count: i64,
+    /// IDs of the first violating documents in `_id` order.
+    pub sample_ids: Vec<String>,
+    /// Why the first violating document doesn't match the schema.
+    pub first_error: String,
+}
+
+impl SchemaViolationReport {
+    pub fn record(
+        &mut self,
+        table_name: &TableName,
+        id: DeveloperDocumentId,
+        validation_error: &ValidationError,
+    ) {
+        let violations = self
+            .tables
+            .entry(table_name.to_string())
+            .or_insert_with(|| TableViolations {
+                count: 0,
+                sample_ids: vec![],
+                first_error: validation_error.to_string(),
+            });
+        violations.count += 1;
+        if violations.sample_ids.len() < MAX_SAMPLE_IDS {
+            violations.sample_ids.push(id.encode());
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    /// Plans a migration from `active` to `pending` that never leaves
+    /// documents violating the enforced schema: first push a schema that
+    /// accepts both shapes, then rewrite the violating documents, then push
+    /// `pending`.
+    pub fn migration_plan(
+        &self,
+        active: Option<&DatabaseSchema>,
+        pending: &DatabaseSchema,
+    ) -> anyhow::Result<SchemaMigrationPlan> {
+        let mut widened = pending.clone();
+        let mut backfills = vec![];
+        for (table_name, violations) in &self.tables {
+            let table_name: TableName = table_name.parse()?;
+            let Some(table_definition) = widened.tables.get_mut(&table_name) else {
+                continue;
+            };
+            let active_document_type = active
+                .filter(|active| active.schema_validation)
+                .and_then(|active| active.schema_for_table(&table_name).cloned());
+            table_definition.document_type =
+                widen(active_document_type, table_definition.document_type.take());
+            backfills.push(MigrationStep::Backfill {
+                table_name,
+                document_count: violations.count,
+            });
+        }
+        let mut steps = vec![MigrationStep::WidenSchema { schema: widened }];
+        steps.extend(backfills);
+        steps.push(MigrationStep::NarrowSchema {
+            schema: pending.clone(),
+        });
+        Ok(SchemaMigrationPlan { steps })
+    }
+}
+
+/// A document type accepting every document that matches either `a` or `b`.
+fn widen(a: Option<DocumentSchema>, b: Option<DocumentSchema>) -> Option<DocumentSchema> {
+    match (a?, b?) {
+        (DocumentSchema::Union(mut variants), DocumentSchema::Union(b)) => {
+            for variant in b {
+                if !variants.contains(&variant) {
+                    variants.push(variant);
+                }
+            }
+            Some(DocumentSchema::Union(variants))
+        },
+        _ => Some(DocumentSchema::Any),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchemaMigrationPlan {
+    pub steps: Vec<MigrationStep>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MigrationStep {
+    /// Push a schema that accepts both the existing and the migrated
+    /// documents.
+    WidenSchema { schema: DatabaseSchema },
+    /// Rewrite the documents in a table that don't match the new schema,
+    /// e.g. with a migration that patches each document.
+    Backfill {
+        table_name: TableName,
+        document_count: i64,
+    },
+    /// Push the new schema once every document matches it.
+    NarrowSchema { schema: DatabaseSchema },
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedSchemaViolationReport {
+    tables: Vec<SerializedTableViolations>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedTableViolations {
+    table_name: String,
+    count: i64,
+    sample_ids: Vec<String>,
+    first_error: String,
+}
+
+impl From<SchemaViolationReport> for SerializedSchemaViolationReport {
+    fn from(report: SchemaViolationReport) -> Self {
+        Self {
+            tables: report
+                .tables
+                .into_iter()
+                .map(|(table_name, violations)| SerializedTableViolations {
+                    table_name,
+                    count: violations.count,
+                    sample_ids: violations.sample_ids,
+                    first_error: violations.first_error,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<SerializedSchemaViolationReport> for SchemaViolationReport {
+    fn from(report: SerializedSchemaViolationReport) -> Self {
+        Self {
+            tables: report
+                .tables
+                .into_iter()
+                .map(|violations| {
+                    (
+                        violations.table_name,
+                        TableViolations {
+                            count: violations.count,
+                            sample_ids: violations.sample_ids,
+                            first_error: violations.first_error,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaMigrationPlanJson {
+    steps: Vec<MigrationStepJson>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum MigrationStepJson {
+    WidenSchema {
+        schema: DatabaseSchemaJson,
+    },
+    #[serde(rename_all = "camelCase")]
+    Backfill {
+        table_name: String,
+        document_count: i64,
+    },
+    NarrowSchema {
+        schema: DatabaseSchemaJson,
+    },
+}
+
+impl TryFrom<SchemaMigrationPlan> for SchemaMigrationPlanJson {
+    type Error = anyhow::Error;
+
+    fn try_from(plan: SchemaMigrationPlan) -> anyhow::Result<Self> {
+        Ok(Self {
+            steps: plan
+                .steps
+                .into_iter()
+                .map(|step| {
+                    anyhow::Ok(match step {
+                        MigrationStep::WidenSchema { schema } => MigrationStepJson::WidenSchema {
+                            schema: schema.try_into()?,
+                        },
+                        MigrationStep::Backfill {
+                            table_name,
+                            document_count,
+                        } => MigrationStepJson::Backfill {
+                            table_name: table_name.to_string(),
+                            document_count,
+                        },
+                        MigrationStep::NarrowSchema { schema } => MigrationStepJson::NarrowSchema {
+                            schema: schema.try_into()?,
+                        },
+                    })
+                })
+                .try_collect()?,
+        })
+    }
+}
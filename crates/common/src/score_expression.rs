@@ -0,0 +1,439 @@
+//! Expressions that compute the score of a text search result from its BM25
+//! relevance and the numeric fields of its document, like
+//! `bm25 * log(1 + popularity)`.
+
+use std::{
+    fmt::{
+        self,
+        Display,
+    },
+    str::FromStr,
+};
+
+use errors::ErrorMetadata;
+use value::{
+    ConvexObject,
+    ConvexValue,
+};
+
+use crate::paths::FieldPath;
+
+/// The longest score expression a search can use, in bytes.
+pub const MAX_SCORE_EXPRESSION_LENGTH: usize = 1024;
+
+/// How deeply a score expression's parentheses and function calls can nest.
+const MAX_SCORE_EXPRESSION_DEPTH: usize = 32;
+
+/// A parsed score expression. Expressions compare equal if their source text
+/// does.
+#[derive(Clone, Debug)]
+pub struct ScoreExpression {
+    source: String,
+    root: ScoreNode,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum ScoreNode {
+    Bm25,
+    Constant(f64),
+    Field(FieldPath),
+    Negate(Box<ScoreNode>),
+    Binary(BinaryOp, Box<ScoreNode>, Box<ScoreNode>),
+    Call(Function, Vec<ScoreNode>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Function {
+    Log,
+    Log10,
+    Sqrt,
+    Abs,
+    Exp,
+    Pow,
+    Min,
+    Max,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        let function = match name {
+            "log" => Self::Log,
+            "log10" => Self::Log10,
+            "sqrt" => Self::Sqrt,
+            "abs" => Self::Abs,
+            "exp" => Self::Exp,
+            "pow" => Self::Pow,
+            "min" => Self::Min,
+            "max" => Self::Max,
+            _ => return None,
+        };
+        Some(function)
+    }
+
+    fn accepts(&self, num_args: usize) -> bool {
+        match self {
+            Self::Log | Self::Log10 | Self::Sqrt | Self::Abs | Self::Exp => num_args == 1,
+            Self::Pow => num_args == 2,
+            Self::Min | Self::Max => num_args >= 1,
+        }
+    }
+}
+
+impl ScoreExpression {
+    /// Computes a result's score. Fields that are missing or aren't numbers
+    /// count as 0, and a score that isn't a number (like `sqrt(-1)`) is 0.
+    pub fn evaluate(&self, bm25: f64, document: &ConvexObject) -> f64 {
+        let score = self.root.evaluate(bm25, document);
+        if score.is_nan() {
+            0.
+        } else {
+            score
+        }
+    }
+}
+
+impl ScoreNode {
+    fn evaluate(&self, bm25: f64, document: &ConvexObject) -> f64 {
+        match self {
+            Self::Bm25 => bm25,
+            Self::Constant(value) => *value,
+            Self::Field(field_path) => match document.get_path(field_path) {
+                Some(ConvexValue::Float64(value)) => *value,
+                Some(ConvexValue::Int64(value)) => *value as f64,
+                _ => 0.,
+            },
+            Self::Negate(node) => -node.evaluate(bm25, document),
+            Self::Binary(op, left, right) => {
+                let left = left.evaluate(bm25, document);
+                let right = right.evaluate(bm25, document);
+                match op {
+                    BinaryOp::Add => left + right,
+                    BinaryOp::Subtract => left - right,
+                    BinaryOp::Multiply => left * right,
+                    BinaryOp::Divide => left / right,
+                }
+            },
+            Self::Call(function, args) => {
+                let args: Vec<f64> = args.iter().map(|a| a.evaluate(bm25, document)).collect();
+                match function {
+                    Function::Log => args[0].ln(),
+                    Function::Log10 => args[0].log10(),
+                    Function::Sqrt => args[0].sqrt(),
+                    Function::Abs => args[0].abs(),
+                    Function::Exp => args[0].exp(),
+                    Function::Pow => args[0].powf(args[1]),
+                    Function::Min => args.into_iter().fold(f64::INFINITY, f64::min),
+                    Function::Max => args.into_iter().fold(f64::NEG_INFINITY, f64::max),
+                }
+            },
+        }
+    }
+}
+
+impl PartialEq for ScoreExpression {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Eq for ScoreExpression {}
+
+impl Display for ScoreExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl From<ScoreExpression> for String {
+    fn from(expression: ScoreExpression) -> Self {
+        expression.source
+    }
+}
+
+impl FromStr for ScoreExpression {
+    type Err = anyhow::Error;
+
+    fn from_str(source: &str) -> anyhow::Result<Self> {
+        if source.len() > MAX_SCORE_EXPRESSION_LENGTH {
+            anyhow::bail!(invalid_score_expression(
+                source,
+                format!("it's longer than {MAX_SCORE_EXPRESSION_LENGTH} bytes")
+            ));
+        }
+        let tokens = tokenize(source).map_err(|e| invalid_score_expression(source, e))?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            depth: 0,
+        };
+        let root = parser
+            .parse()
+            .map_err(|e| invalid_score_expression(source, e))?;
+        Ok(Self {
+            source: source.to_string(),
+            root,
+        })
+    }
+}
+
+fn invalid_score_expression(source: &str, reason: String) -> anyhow::Error {
+    ErrorMetadata::bad_request(
+        "InvalidScoreExpression",
+        format!("Invalid score expression {source:?}: {reason}."),
+    )
+    .into()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Identifier(String),
+    Operator(char),
+    OpenParen,
+    CloseParen,
+    Comma,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = source.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek()
+                && (c.is_ascii_digit() || c == '.')
+            {
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            let number = &source[start..end];
+            let value = number
+                .parse()
+                .map_err(|_| format!("{number:?} isn't a number"))?;
+            tokens.push(Token::Number(value));
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek()
+                && (c.is_ascii_alphanumeric() || c == '_' || c == '.')
+            {
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            tokens.push(Token::Identifier(source[start..end].to_string()));
+        } else {
+            let token = match c {
+                '+' | '-' | '*' | '/' => Token::Operator(c),
+                '(' => Token::OpenParen,
+                ')' => Token::CloseParen,
+                ',' => Token::Comma,
+                _ => return Err(format!("unexpected character {c:?}")),
+            };
+            tokens.push(token);
+            chars.next();
+        }
+    }
+    Ok(tokens)
+}
+
+/// A recursive descent parser for the grammar:
+///
+/// ```text
+/// expression := term (("+" | "-") term)*
+/// term       := unary (("*" | "/") unary)*
+/// unary      := "-" unary | primary
+/// primary    := number | "bm25" | field | function "(" expression ("," expression)* ")"
+///             | "(" expression ")"
+/// ```
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn parse(&mut self) -> Result<ScoreNode, String> {
+        let node = self.expression()?;
+        match self.peek() {
+            None => Ok(node),
+            Some(token) => Err(format!("unexpected {token:?}")),
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(format!("expected {expected:?} but found {token:?}")),
+            None => Err(format!("expected {expected:?} but the expression ended")),
+        }
+    }
+
+    fn expression(&mut self) -> Result<ScoreNode, String> {
+        self.depth += 1;
+        if self.depth > MAX_SCORE_EXPRESSION_DEPTH {
+            return Err(format!(
+                "it's nested more than {MAX_SCORE_EXPRESSION_DEPTH} levels deep"
+            ));
+        }
+        let mut node = self.term()?;
+        while let Some(Token::Operator(op @ ('+' | '-'))) = self.peek() {
+            let op = if *op == '+' {
+                BinaryOp::Add
+            } else {
+                BinaryOp::Subtract
+            };
+            self.next();
+            node = ScoreNode::Binary(op, Box::new(node), Box::new(self.term()?));
+        }
+        self.depth -= 1;
+        Ok(node)
+    }
+
+    fn term(&mut self) -> Result<ScoreNode, String> {
+        let mut node = self.unary()?;
+        while let Some(Token::Operator(op @ ('*' | '/'))) = self.peek() {
+            let op = if *op == '*' {
+                BinaryOp::Multiply
+            } else {
+                BinaryOp::Divide
+            };
+            self.next();
+            node = ScoreNode::Binary(op, Box::new(node), Box::new(self.unary()?));
+        }
+        Ok(node)
+    }
+
+    fn unary(&mut self) -> Result<ScoreNode, String> {
+        if let Some(Token::Operator('-')) = self.peek() {
+            self.next();
+            return Ok(ScoreNode::Negate(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<ScoreNode, String> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(ScoreNode::Constant(value)),
+            Some(Token::OpenParen) => {
+                let node = self.expression()?;
+                self.expect(Token::CloseParen)?;
+                Ok(node)
+            },
+            Some(Token::Identifier(name)) if self.peek() == Some(&Token::OpenParen) => {
+                let function = Function::from_name(&name)
+                    .ok_or_else(|| format!("{name:?} isn't a supported function"))?;
+                self.next();
+                let mut args = vec![self.expression()?];
+                while self.peek() == Some(&Token::Comma) {
+                    self.next();
+                    args.push(self.expression()?);
+                }
+                self.expect(Token::CloseParen)?;
+                if !function.accepts(args.len()) {
+                    return Err(format!("{name} doesn't take {} arguments", args.len()));
+                }
+                Ok(ScoreNode::Call(function, args))
+            },
+            Some(Token::Identifier(name)) if name == "bm25" => Ok(ScoreNode::Bm25),
+            Some(Token::Identifier(name)) => {
+                let field_path = name
+                    .parse()
+                    .map_err(|_| format!("{name:?} isn't a valid field path"))?;
+                Ok(ScoreNode::Field(field_path))
+            },
+            Some(token) => Err(format!("unexpected {token:?}")),
+            None => Err("the expression ended unexpectedly".to_string()),
+        }
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl proptest::arbitrary::Arbitrary for ScoreExpression {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+        use proptest::prelude::*;
+        prop_oneof![
+            Just("bm25"),
+            Just("bm25 * log(1 + popularity)"),
+            Just("bm25 + 0.5 * max(likes, 0) - age / 10"),
+            Just("pow(bm25, 2) * -sqrt(a.b)"),
+        ]
+        .prop_map(|source| source.parse().unwrap())
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use errors::ErrorMetadataAnyhowExt;
+    use value::assert_obj;
+
+    use super::ScoreExpression;
+
+    fn evaluate(source: &str, bm25: f64) -> f64 {
+        let expression: ScoreExpression = source.parse().unwrap();
+        let document = assert_obj!(
+            "popularity" => 9.,
+            "likes" => 4,
+            "nested" => { "weight" => 2. },
+            "name" => "ada",
+        );
+        expression.evaluate(bm25, &document)
+    }
+
+    #[test]
+    fn test_evaluate() {
+        assert_eq!(evaluate("bm25", 1.5), 1.5);
+        assert_eq!(evaluate("bm25 * log(1 + popularity)", 2.), 2. * 10f64.ln());
+        assert_eq!(evaluate("1 + 2 * 3 - 4 / 2", 0.), 5.);
+        assert_eq!(evaluate("(1 + 2) * -3", 0.), -9.);
+        assert_eq!(evaluate("likes * nested.weight", 0.), 8.);
+        assert_eq!(evaluate("max(likes, popularity, 1)", 0.), 9.);
+        assert_eq!(evaluate("pow(2, likes) + sqrt(popularity)", 0.), 19.);
+        // Missing and non-numeric fields count as 0.
+        assert_eq!(evaluate("bm25 + missing + name", 1.), 1.);
+        assert_eq!(evaluate("sqrt(-1)", 0.), 0.);
+    }
+
+    #[test]
+    fn test_invalid_expressions() {
+        for source in [
+            "",
+            "bm25 +",
+            "(bm25",
+            "bm25)",
+            "log(1, 2)",
+            "pow(2)",
+            "unknown(bm25)",
+            "bm25 % 2",
+            "1.2.3",
+            "a..b",
+        ] {
+            let err = source.parse::<ScoreExpression>().unwrap_err();
+            assert_eq!(err.short_msg(), "InvalidScoreExpression", "{source}");
+        }
+        let nested = format!("{}bm25{}", "(".repeat(40), ")".repeat(40));
+        assert!(nested.parse::<ScoreExpression>().is_err());
+    }
+}
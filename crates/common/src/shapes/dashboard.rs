@@ -39,6 +39,8 @@ pub fn dashboard_shape_json(
         ReducedShape::Boolean => json!({"type": "Boolean"}),
         ReducedShape::String => json!({"type": "String"}),
         ReducedShape::Bytes => json!({"type": "Bytes"}),
+        ReducedShape::Decimal => json!({"type": "Decimal"}),
+        ReducedShape::DateTime => json!({"type": "DateTime"}),
         ReducedShape::Object(fields) => {
             let field_json = fields
                 .iter()
@@ -155,6 +157,8 @@ mod tests {
             Boolean,
             String,
             Bytes,
+            Decimal,
+            DateTime,
             #[serde(rename_all = "camelCase")]
             Object {
                 fields: Vec<FieldPair>,
@@ -195,6 +199,8 @@ mod tests {
             ShapeEnumJson::Boolean => ReducedShape::Boolean,
             ShapeEnumJson::String => ReducedShape::String,
             ShapeEnumJson::Bytes => ReducedShape::Bytes,
+            ShapeEnumJson::Decimal => ReducedShape::Decimal,
+            ShapeEnumJson::DateTime => ReducedShape::DateTime,
             ShapeEnumJson::Object { fields } => {
                 let field_shapes = fields
                     .into_iter()
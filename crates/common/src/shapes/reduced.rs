@@ -39,6 +39,8 @@ pub enum ReducedShape {
     Boolean,
     String,
     Bytes,
+    Decimal,
+    DateTime,
     Object(BTreeMap<FieldName, ReducedField>),
     Array(Box<ReducedShape>),
     Set(Box<ReducedShape>),
@@ -107,6 +109,8 @@ impl ReducedShape {
             ShapeEnum::FieldName => ReducedShape::String,
             ShapeEnum::String => ReducedShape::String,
             ShapeEnum::Bytes => ReducedShape::Bytes,
+            ShapeEnum::Decimal => ReducedShape::Decimal,
+            ShapeEnum::DateTime => ReducedShape::DateTime,
             ShapeEnum::Array(array_type) => ReducedShape::Array(Box::new(ReducedShape::from_type(
                 array_type.element(),
                 table_exists,
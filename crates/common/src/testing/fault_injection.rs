@@ -0,0 +1,588 @@
+//! Fault injection for resilience tests.
+//!
+//! A [`FaultSchedule`] decides, call by call, whether a persistence or search
+//! call should be delayed, fail, or partially fail. Decisions come from a
+//! seeded RNG, so a test that drives calls in a deterministic order (e.g. on
+//! `TestRuntime`) sees the same faults on every run, and a failing seed can be
+//! replayed. [`FaultInjectingPersistence`] applies a schedule to a
+//! `Persistence`; the search crate has the equivalent wrapper for searchers.
+
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
+    fmt,
+    future::Future,
+    sync::Arc,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use futures::{
+    future,
+    stream::{
+        self,
+        BoxStream,
+    },
+    StreamExt,
+};
+use parking_lot::Mutex;
+use rand::{
+    Rng,
+    SeedableRng,
+};
+use rand_chacha::ChaCha12Rng;
+use serde_json::Value as JsonValue;
+use value::{
+    InternalDocumentId,
+    TabletId,
+};
+
+use crate::{
+    index::{
+        IndexEntry,
+        IndexKey,
+    },
+    interval::Interval,
+    persistence::{
+        ConflictStrategy,
+        DocumentLogEntry,
+        DocumentPrevTsQuery,
+        DocumentStream,
+        IndexStream,
+        LatestDocument,
+        Persistence,
+        PersistenceGlobalKey,
+        PersistenceReader,
+        PersistenceTableSize,
+        RetentionValidator,
+        TimestampRange,
+    },
+    query::Order,
+    runtime::Runtime,
+    types::{
+        DatabaseIndexUpdate,
+        IndexId,
+        PersistenceVersion,
+        Timestamp,
+    },
+};
+
+/// The kinds of calls a schedule can target.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FaultOp {
+    /// Document and index writes, e.g. from the committer.
+    PersistenceWrite,
+    /// Point reads such as previous revisions and persistence globals.
+    PersistenceRead,
+    /// Streaming reads: document log loads, index scans, and index chunks.
+    PersistenceStream,
+    /// Deletes of old revisions and index entries, e.g. from retention.
+    PersistenceDelete,
+    /// Text and vector queries against the searcher.
+    SearchQuery,
+    /// Text and vector segment compactions, e.g. from index backfill.
+    SearchCompaction,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fault {
+    /// Wait this long, then make the call.
+    Delay(Duration),
+    /// Fail without making the call.
+    Error,
+    /// Make the call but fail anyway: writes and deletes are applied but
+    /// reported as failed, and streams fail after their first item.
+    PartialFailure,
+}
+
+#[derive(Clone, Debug)]
+pub struct FaultRule {
+    pub op: FaultOp,
+    /// Chance that each matching call gets `fault`.
+    pub probability: f64,
+    pub fault: Fault,
+    /// Stop injecting after this many faults from this rule.
+    pub max_faults: Option<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InjectedFault {
+    /// Index of the call among all calls with this op.
+    pub call: usize,
+    pub op: FaultOp,
+    pub fault: Fault,
+}
+
+#[derive(Debug)]
+pub struct InjectedFaultError {
+    pub op: FaultOp,
+    pub fault: Fault,
+}
+
+impl fmt::Display for InjectedFaultError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Injected {:?} fault on {:?}", self.fault, self.op)
+    }
+}
+
+impl std::error::Error for InjectedFaultError {}
+
+#[derive(Clone)]
+pub struct FaultSchedule {
+    inner: Arc<Mutex<ScheduleState>>,
+}
+
+struct ScheduleState {
+    rng: ChaCha12Rng,
+    rules: Vec<(FaultRule, usize)>,
+    enabled: bool,
+    calls: BTreeMap<FaultOp, usize>,
+    injected: Vec<InjectedFault>,
+}
+
+impl FaultSchedule {
+    /// A schedule with no rules, which never injects faults.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(ScheduleState {
+                rng: ChaCha12Rng::seed_from_u64(seed),
+                rules: vec![],
+                enabled: true,
+                calls: BTreeMap::new(),
+                injected: vec![],
+            })),
+        }
+    }
+
+    pub fn with_rule(self, rule: FaultRule) -> Self {
+        self.inner.lock().rules.push((rule, 0));
+        self
+    }
+
+    /// Stop or resume injecting faults, e.g. to check that workers recover
+    /// once the faults stop. Calls made while disabled don't draw from the
+    /// RNG.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.inner.lock().enabled = enabled;
+    }
+
+    /// Every fault injected so far, in order.
+    pub fn injected(&self) -> Vec<InjectedFault> {
+        self.inner.lock().injected.clone()
+    }
+
+    /// Decide what happens to the next call with `op`. The first matching
+    /// rule that fires wins, but every matching rule draws from the RNG so
+    /// the sequence doesn't depend on which rules fired earlier.
+    pub fn next_fault(&self, op: FaultOp) -> Option<Fault> {
+        let mut state = self.inner.lock();
+        if !state.enabled {
+            return None;
+        }
+        let ScheduleState {
+            rng,
+            rules,
+            calls,
+            injected,
+            ..
+        } = &mut *state;
+        let call = calls.entry(op).or_default();
+        let call_index = *call;
+        *call += 1;
+        let mut result = None;
+        for (rule, count) in rules.iter_mut().filter(|(rule, _)| rule.op == op) {
+            let fires = rng.random_bool(rule.probability);
+            if fires
+                && result.is_none()
+                && rule.max_faults.is_none_or(|max_faults| *count < max_faults)
+            {
+                *count += 1;
+                result = Some(rule.fault);
+            }
+        }
+        if let Some(fault) = result {
+            injected.push(InjectedFault {
+                call: call_index,
+                op,
+                fault,
+            });
+        }
+        result
+    }
+}
+
+/// Applies a schedule's faults to calls that return a value: delays before
+/// the call, errors instead of it, and partial failures after it.
+pub async fn with_fault<RT: Runtime, T>(
+    rt: &RT,
+    schedule: &FaultSchedule,
+    op: FaultOp,
+    call: impl Future<Output = anyhow::Result<T>>,
+) -> anyhow::Result<T> {
+    match schedule.next_fault(op) {
+        None => call.await,
+        Some(Fault::Delay(delay)) => {
+            rt.wait(delay).await;
+            call.await
+        },
+        Some(fault @ Fault::Error) => Err(InjectedFaultError { op, fault }.into()),
+        Some(fault @ Fault::PartialFailure) => {
+            call.await?;
+            Err(InjectedFaultError { op, fault }.into())
+        },
+    }
+}
+
+fn with_stream_fault<'a, RT: Runtime, T: Send + 'a>(
+    rt: &RT,
+    schedule: &FaultSchedule,
+    op: FaultOp,
+    stream: BoxStream<'a, anyhow::Result<T>>,
+) -> BoxStream<'a, anyhow::Result<T>> {
+    match schedule.next_fault(op) {
+        None => stream,
+        Some(Fault::Delay(delay)) => {
+            let wait = rt.wait(delay);
+            stream::once(async move {
+                wait.await;
+                stream
+            })
+            .flatten()
+            .boxed()
+        },
+        Some(fault @ Fault::Error) => {
+            stream::once(future::ready(Err(InjectedFaultError { op, fault }.into()))).boxed()
+        },
+        Some(fault @ Fault::PartialFailure) => stream
+            .take(1)
+            .chain(stream::once(future::ready(Err(InjectedFaultError {
+                op,
+                fault,
+            }
+            .into()))))
+            .boxed(),
+    }
+}
+
+/// A `Persistence` that injects faults from a [`FaultSchedule`] into the
+/// calls it forwards.
+pub struct FaultInjectingPersistence<RT: Runtime> {
+    inner: Arc<dyn Persistence>,
+    rt: RT,
+    schedule: FaultSchedule,
+}
+
+impl<RT: Runtime> FaultInjectingPersistence<RT> {
+    pub fn new(inner: Arc<dyn Persistence>, rt: RT, schedule: FaultSchedule) -> Self {
+        Self {
+            inner,
+            rt,
+            schedule,
+        }
+    }
+}
+
+#[async_trait]
+impl<RT: Runtime> Persistence for FaultInjectingPersistence<RT> {
+    fn is_fresh(&self) -> bool {
+        self.inner.is_fresh()
+    }
+
+    fn reader(&self) -> Arc<dyn PersistenceReader> {
+        Arc::new(FaultInjectingPersistenceReader {
+            inner: self.inner.reader(),
+            rt: self.rt.clone(),
+            schedule: self.schedule.clone(),
+        })
+    }
+
+    async fn write(
+        &self,
+        documents: Vec<DocumentLogEntry>,
+        indexes: BTreeSet<(Timestamp, DatabaseIndexUpdate)>,
+        conflict_strategy: ConflictStrategy,
+    ) -> anyhow::Result<()> {
+        with_fault(
+            &self.rt,
+            &self.schedule,
+            FaultOp::PersistenceWrite,
+            self.inner.write(documents, indexes, conflict_strategy),
+        )
+        .await
+    }
+
+    async fn set_read_only(&self, read_only: bool) -> anyhow::Result<()> {
+        self.inner.set_read_only(read_only).await
+    }
+
+    async fn write_persistence_global(
+        &self,
+        key: PersistenceGlobalKey,
+        value: JsonValue,
+    ) -> anyhow::Result<()> {
+        with_fault(
+            &self.rt,
+            &self.schedule,
+            FaultOp::PersistenceWrite,
+            self.inner.write_persistence_global(key, value),
+        )
+        .await
+    }
+
+    async fn load_index_chunk(
+        &self,
+        cursor: Option<IndexEntry>,
+        chunk_size: usize,
+    ) -> anyhow::Result<Vec<IndexEntry>> {
+        with_fault(
+            &self.rt,
+            &self.schedule,
+            FaultOp::PersistenceStream,
+            self.inner.load_index_chunk(cursor, chunk_size),
+        )
+        .await
+    }
+
+    async fn delete_index_entries(&self, entries: Vec<IndexEntry>) -> anyhow::Result<usize> {
+        with_fault(
+            &self.rt,
+            &self.schedule,
+            FaultOp::PersistenceDelete,
+            self.inner.delete_index_entries(entries),
+        )
+        .await
+    }
+
+    async fn delete(
+        &self,
+        documents: Vec<(Timestamp, InternalDocumentId)>,
+    ) -> anyhow::Result<usize> {
+        with_fault(
+            &self.rt,
+            &self.schedule,
+            FaultOp::PersistenceDelete,
+            self.inner.delete(documents),
+        )
+        .await
+    }
+
+    async fn shutdown(&self) -> anyhow::Result<()> {
+        self.inner.shutdown().await
+    }
+}
+
+struct FaultInjectingPersistenceReader<RT: Runtime> {
+    inner: Arc<dyn PersistenceReader>,
+    rt: RT,
+    schedule: FaultSchedule,
+}
+
+#[async_trait]
+impl<RT: Runtime> PersistenceReader for FaultInjectingPersistenceReader<RT> {
+    fn load_documents(
+        &self,
+        range: TimestampRange,
+        order: Order,
+        page_size: u32,
+        retention_validator: Arc<dyn RetentionValidator>,
+    ) -> DocumentStream<'_> {
+        with_stream_fault(
+            &self.rt,
+            &self.schedule,
+            FaultOp::PersistenceStream,
+            self.inner
+                .load_documents(range, order, page_size, retention_validator),
+        )
+    }
+
+    fn load_documents_from_table(
+        &self,
+        tablet_id: TabletId,
+        range: TimestampRange,
+        order: Order,
+        page_size: u32,
+        retention_validator: Arc<dyn RetentionValidator>,
+    ) -> DocumentStream<'_> {
+        with_stream_fault(
+            &self.rt,
+            &self.schedule,
+            FaultOp::PersistenceStream,
+            self.inner.load_documents_from_table(
+                tablet_id,
+                range,
+                order,
+                page_size,
+                retention_validator,
+            ),
+        )
+    }
+
+    async fn previous_revisions(
+        &self,
+        ids: BTreeSet<(InternalDocumentId, Timestamp)>,
+        retention_validator: Arc<dyn RetentionValidator>,
+    ) -> anyhow::Result<BTreeMap<(InternalDocumentId, Timestamp), DocumentLogEntry>> {
+        with_fault(
+            &self.rt,
+            &self.schedule,
+            FaultOp::PersistenceRead,
+            self.inner.previous_revisions(ids, retention_validator),
+        )
+        .await
+    }
+
+    async fn previous_revisions_of_documents(
+        &self,
+        ids: BTreeSet<DocumentPrevTsQuery>,
+        retention_validator: Arc<dyn RetentionValidator>,
+    ) -> anyhow::Result<BTreeMap<DocumentPrevTsQuery, DocumentLogEntry>> {
+        with_fault(
+            &self.rt,
+            &self.schedule,
+            FaultOp::PersistenceRead,
+            self.inner
+                .previous_revisions_of_documents(ids, retention_validator),
+        )
+        .await
+    }
+
+    fn index_scan(
+        &self,
+        index_id: IndexId,
+        tablet_id: TabletId,
+        read_timestamp: Timestamp,
+        range: &Interval,
+        order: Order,
+        size_hint: usize,
+        retention_validator: Arc<dyn RetentionValidator>,
+    ) -> IndexStream<'_> {
+        with_stream_fault(
+            &self.rt,
+            &self.schedule,
+            FaultOp::PersistenceStream,
+            self.inner.index_scan(
+                index_id,
+                tablet_id,
+                read_timestamp,
+                range,
+                order,
+                size_hint,
+                retention_validator,
+            ),
+        )
+    }
+
+    async fn get_persistence_global(
+        &self,
+        key: PersistenceGlobalKey,
+    ) -> anyhow::Result<Option<JsonValue>> {
+        with_fault(
+            &self.rt,
+            &self.schedule,
+            FaultOp::PersistenceRead,
+            self.inner.get_persistence_global(key),
+        )
+        .await
+    }
+
+    async fn index_get(
+        &self,
+        index_id: IndexId,
+        tablet_id: TabletId,
+        read_timestamp: Timestamp,
+        key: IndexKey,
+        retention_validator: Arc<dyn RetentionValidator>,
+    ) -> anyhow::Result<Option<LatestDocument>> {
+        with_fault(
+            &self.rt,
+            &self.schedule,
+            FaultOp::PersistenceRead,
+            self.inner.index_get(
+                index_id,
+                tablet_id,
+                read_timestamp,
+                key,
+                retention_validator,
+            ),
+        )
+        .await
+    }
+
+    async fn max_ts(&self) -> anyhow::Result<Option<Timestamp>> {
+        with_fault(
+            &self.rt,
+            &self.schedule,
+            FaultOp::PersistenceRead,
+            self.inner.max_ts(),
+        )
+        .await
+    }
+
+    fn version(&self) -> PersistenceVersion {
+        self.inner.version()
+    }
+
+    fn supports_chunked_documents(&self) -> bool {
+        self.inner.supports_chunked_documents()
+    }
+
+    async fn table_size_stats(&self) -> anyhow::Result<Vec<PersistenceTableSize>> {
+        self.inner.table_size_stats().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{
+        Fault,
+        FaultOp,
+        FaultRule,
+        FaultSchedule,
+    };
+
+    fn schedule(seed: u64) -> FaultSchedule {
+        FaultSchedule::new(seed)
+            .with_rule(FaultRule {
+                op: FaultOp::PersistenceWrite,
+                probability: 0.3,
+                fault: Fault::Error,
+                max_faults: None,
+            })
+            .with_rule(FaultRule {
+                op: FaultOp::PersistenceWrite,
+                probability: 0.3,
+                fault: Fault::Delay(Duration::from_secs(1)),
+                max_faults: Some(2),
+            })
+    }
+
+    #[test]
+    fn test_schedule_is_reproducible() {
+        let draw = |schedule: &FaultSchedule| {
+            (0..100)
+                .map(|_| schedule.next_fault(FaultOp::PersistenceWrite))
+                .collect::<Vec<_>>()
+        };
+        let a = schedule(42);
+        let b = schedule(42);
+        assert_eq!(draw(&a), draw(&b));
+        assert_eq!(a.injected(), b.injected());
+        assert!(a.injected().iter().any(|f| f.fault == Fault::Error));
+        assert_eq!(
+            a.injected()
+                .iter()
+                .filter(|f| matches!(f.fault, Fault::Delay(_)))
+                .count(),
+            2
+        );
+        // Other ops are untouched.
+        assert_eq!(a.next_fault(FaultOp::PersistenceRead), None);
+
+        a.set_enabled(false);
+        assert!(draw(&a).iter().all(Option::is_none));
+    }
+}
@@ -1,4 +1,5 @@
 //! Test helpers for types defined in this crate
+pub mod fault_injection;
 #[cfg(test)]
 mod schema;
 mod test_id_generator;
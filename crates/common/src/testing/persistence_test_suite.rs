@@ -104,6 +104,13 @@ macro_rules! run_persistence_test_suite {
             persistence_test_suite::overwrite_document(::std::sync::Arc::new(p)).await
         }
 
+        #[tokio::test]
+        async fn test_persistence_write_and_load_large_document() -> anyhow::Result<()> {
+            let $db = $create_db;
+            let p = $create_persistence;
+            persistence_test_suite::write_and_load_large_document(::std::sync::Arc::new(p)).await
+        }
+
         #[tokio::test]
         async fn test_persistence_overwrite_index() -> anyhow::Result<()> {
             let $db = $create_db;
@@ -622,6 +629,67 @@ pub async fn overwrite_document<P: Persistence>(p: Arc<P>) -> anyhow::Result<()>
     Ok(())
 }
 
+/// Documents with large top-level fields may be chunked across rows. Check
+/// they read back intact after being overwritten and through revisions.
+pub async fn write_and_load_large_document<P: Persistence>(p: Arc<P>) -> anyhow::Result<()> {
+    let mut id_generator = TestIdGenerator::new();
+    let table: TableName = str::parse("table")?;
+    let doc_id = id_generator.user_generate(&table);
+
+    let doc = |text: String| {
+        ResolvedDocument::new(
+            doc_id,
+            CreationTime::ONE,
+            assert_obj!("text" => text, "small" => "inline"),
+        )
+    };
+    let long = doc("é".repeat(1 << 20))?;
+    let shorter = doc("x".repeat(1 << 19))?;
+    let entry = |ts, doc: &ResolvedDocument, prev_ts| DocumentLogEntry {
+        ts: Timestamp::must(ts),
+        id: doc.id_with_table_id(),
+        value: Some(doc.clone()),
+        prev_ts: prev_ts.map(Timestamp::must),
+    };
+
+    p.write(
+        vec![entry(1, &long, None), entry(2, &long, Some(1))],
+        BTreeSet::new(),
+        ConflictStrategy::Error,
+    )
+    .await?;
+    // Overwriting with a value that has fewer chunks must not read stale ones.
+    p.write(
+        vec![entry(2, &shorter, Some(1))],
+        BTreeSet::new(),
+        ConflictStrategy::Overwrite,
+    )
+    .await?;
+    id_generator.write_tables(p.clone()).await?;
+
+    test_load_documents(
+        &p,
+        &id_generator,
+        TimestampRange::all(),
+        Order::Asc,
+        vec![entry(1, &long, None), entry(2, &shorter, Some(1))],
+    )
+    .await?;
+
+    let revisions = p
+        .reader()
+        .previous_revisions(
+            btreeset![(long.id_with_table_id(), Timestamp::must(2))],
+            Arc::new(NoopRetentionValidator),
+        )
+        .await?;
+    assert_eq!(
+        revisions.into_values().collect::<Vec<_>>(),
+        vec![entry(1, &long, None)]
+    );
+    Ok(())
+}
+
 pub async fn overwrite_index<P: Persistence>(p: Arc<P>) -> anyhow::Result<()> {
     let mut id_generator = TestIdGenerator::new();
     let index_id = id_generator.system_generate(&INDEX_TABLE);
@@ -6,6 +6,7 @@ use serde_json::{
     json,
     Value as JsonValue,
 };
+use value::TableName;
 
 use crate::{
     bootstrap_model::index::{
@@ -18,6 +19,7 @@ use crate::{
         DatabaseSchema,
         MAX_INDEXES_PER_TABLE,
     },
+    types::IndexDescriptor,
 };
 
 static TOO_MANY_INDEXES: LazyLock<Vec<JsonValue>> = LazyLock::new(|| {
@@ -815,6 +817,53 @@ fn test_too_many_search_filter_fields() {
     );
 }
 
+#[test]
+fn test_invalid_extra_search_fields() {
+    let search_index = |extra_search_fields: JsonValue, search_field_weights: JsonValue| {
+        json!({
+            "tables": [
+                {
+                    "tableName": "test",
+                    "indexes": [],
+                    "searchIndexes": [{
+                        "indexDescriptor": "search_index",
+                        "searchField": "title",
+                        "extraSearchFields": extra_search_fields,
+                        "searchFieldWeights": search_field_weights,
+                        "filterFields": [],
+                    }]
+                },
+            ],
+            "schemaValidation": true,
+        })
+    };
+    for (extra_search_fields, search_field_weights) in [
+        (json!(["title"]), json!({})),
+        (json!(["body"]), json!({"tags": 2})),
+        (json!(["body"]), json!({"body": 0})),
+        (json!(["body"]), json!({"body": 1.5})),
+        (json!(["body"]), json!({"title": 11})),
+        (json!(["a", "b", "c", "d", "e", "f", "g", "h"]), json!({})),
+    ] {
+        let err = index_validation_test(search_index(extra_search_fields, search_field_weights));
+        assert_eq!(
+            err.short_msg, "InvalidSearchFields",
+            "<{err}> does not match expected error type"
+        );
+    }
+    let schema = DatabaseSchema::json_deserialize_value(search_index(
+        json!(["body", "tags"]),
+        json!({"title": 3, "tags": 2}),
+    ))
+    .unwrap();
+    let table_name: TableName = "test".parse().unwrap();
+    let index_descriptor = IndexDescriptor::new("search_index").unwrap();
+    let config = schema.tables[&table_name].search_indexes[&index_descriptor].developer_config();
+    assert_eq!(config.search_fields().count(), 3);
+    assert_eq!(config.search_field_weight(&"title".parse().unwrap()), 3);
+    assert_eq!(config.search_field_weight(&"body".parse().unwrap()), 1);
+}
+
 #[test]
 fn test_too_many_indexes() {
     let value = json!({
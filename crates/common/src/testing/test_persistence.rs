@@ -420,6 +420,11 @@ impl PersistenceReader for TestPersistence {
     fn version(&self) -> PersistenceVersion {
         PersistenceVersion::default()
     }
+
+    fn supports_chunked_documents(&self) -> bool {
+        // Documents are kept in memory whole, so any size fits.
+        true
+    }
 }
 
 struct Inner {
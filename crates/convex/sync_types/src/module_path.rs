@@ -17,6 +17,8 @@ use crate::path::{
 
 pub const SYSTEM_UDF_DIR: &str = "_system";
 pub const DEPS_DIR: &str = "_deps";
+/// Per-function bundles live at `_deps/functions/<module>/<function>.js`.
+pub const FUNCTION_BUNDLES_DIR: &str = "functions";
 pub const ACTIONS_DIR: &str = "actions";
 pub const HTTP_PATH: &str = "http.js";
 pub const CRON_PATH: &str = "crons.js";
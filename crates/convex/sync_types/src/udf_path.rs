@@ -9,6 +9,8 @@ use proptest::prelude::*;
 use super::module_path::{
     CanonicalizedModulePath,
     ModulePath,
+    DEPS_DIR,
+    FUNCTION_BUNDLES_DIR,
 };
 use crate::function_name::FunctionName;
 
@@ -150,6 +152,19 @@ impl CanonicalizedUdfPath {
         &self.function
     }
 
+    /// Path of the bundle containing only the code reachable from this
+    /// function, if the CLI uploaded one alongside the module. `None` if the
+    /// function's name can't be used as a path component.
+    pub fn function_bundle_path(&self) -> Option<CanonicalizedModulePath> {
+        let module = self.module.clone().strip();
+        let path = format!(
+            "{DEPS_DIR}/{FUNCTION_BUNDLES_DIR}/{}/{}.js",
+            module.as_str(),
+            &*self.function,
+        );
+        path.parse().ok()
+    }
+
     pub fn strip(self) -> UdfPath {
         let function = if self.function.is_default_export() {
             None
@@ -235,4 +250,14 @@ mod tests {
         let stripped = canonicalized.strip();
         assert_eq!(String::from(stripped), "test:function");
     }
+
+    #[test]
+    fn test_function_bundle_path() {
+        let p = UdfPath::from_str("messages/send.js:create")
+            .unwrap()
+            .canonicalize();
+        let bundle = p.function_bundle_path().unwrap();
+        assert_eq!(bundle.as_str(), "_deps/functions/messages/send/create.js");
+        assert!(bundle.is_deps());
+    }
 }
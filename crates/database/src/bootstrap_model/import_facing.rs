@@ -10,13 +10,11 @@ use common::{
 };
 use errors::ErrorMetadata;
 use value::{
-    check_user_size,
     ConvexObject,
     ConvexValue,
     DeveloperDocumentId,
     FieldName,
     ResolvedDocumentId,
-    Size,
     TableMapping,
     TableName,
     TabletIdAndTableNumber,
@@ -77,7 +75,7 @@ impl<'a, RT: Runtime> ImportFacingModel<'a, RT> {
         }
 
         if !table_name.is_system() {
-            check_user_size(value.size())?;
+            self.tx.check_user_document_size(&value)?;
         }
         self.tx.retention_validator.fail_if_falling_behind()?;
         let id_field = FieldName::from(ID_FIELD.clone());
@@ -159,7 +157,7 @@ impl<'a, RT: Runtime> ImportFacingModel<'a, RT> {
         }
 
         if !table_name.is_system() {
-            check_user_size(value.size())?;
+            self.tx.check_user_document_size(&value)?;
         }
         let id_field = FieldName::from(ID_FIELD.clone());
         let developer_id = if let Some(ConvexValue::String(s)) = value.get(&id_field) {
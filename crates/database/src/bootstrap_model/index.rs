@@ -1,7 +1,10 @@
-use std::collections::{
-    BTreeMap,
-    BTreeSet,
-    HashMap,
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+        HashMap,
+    },
+    sync::Arc,
 };
 
 use anyhow::Context;
@@ -13,14 +16,8 @@ use common::{
             IndexedFields,
         },
         index_validation_error,
-        text_index::{
-            DeveloperTextIndexConfig,
-            TextIndexState,
-        },
-        vector_index::{
-            DeveloperVectorIndexConfig,
-            VectorIndexState,
-        },
+        text_index::TextIndexState,
+        vector_index::VectorIndexState,
         DeveloperIndexConfig,
         DeveloperIndexMetadata,
         IndexConfig,
@@ -46,6 +43,7 @@ use common::{
         TableName,
         TabletIndexName,
     },
+    virtual_system_mapping::VirtualSystemDocMapper,
 };
 use errors::ErrorMetadata;
 use indexing::{
@@ -62,6 +60,11 @@ use value::{
     TabletId,
 };
 
+use super::index_virtual_table::{
+    indexes_virtual_indexes,
+    IndexesDocMapper,
+    INDEXES_VIRTUAL_TABLE,
+};
 use crate::{
     query::TableFilter,
     reads::TransactionReadSet,
@@ -88,6 +91,18 @@ impl SystemTable for IndexTable {
     fn indexes() -> Vec<SystemIndex<Self>> {
         vec![]
     }
+
+    fn virtual_table() -> Option<(
+        &'static TableName,
+        BTreeMap<IndexName, IndexName>,
+        Arc<dyn VirtualSystemDocMapper>,
+    )> {
+        Some((
+            &INDEXES_VIRTUAL_TABLE,
+            indexes_virtual_indexes(),
+            Arc::new(IndexesDocMapper),
+        ))
+    }
 }
 
 pub struct IndexModel<'a, RT: Runtime> {
@@ -420,17 +435,14 @@ impl<'a, RT: Runtime> IndexModel<'a, RT> {
                 let index_name = IndexName::new(table_name.clone(), index_descriptor.clone())?;
                 indexes_in_schema.push(IndexMetadata::new_backfilling_text_index(
                     index_name.clone(),
-                    index_schema.search_field.clone(),
-                    index_schema.filter_fields.clone(),
+                    index_schema.developer_config(),
                 ))
             }
             for (index_descriptor, index_schema) in &table_schema.vector_indexes {
                 let index_name = IndexName::new(table_name.clone(), index_descriptor.clone())?;
                 indexes_in_schema.push(IndexMetadata::new_backfilling_vector_index(
                     index_name.clone(),
-                    index_schema.vector_field.clone(),
-                    index_schema.dimension,
-                    index_schema.filter_fields.clone(),
+                    index_schema.developer_config(),
                 ));
             }
         }
@@ -963,31 +975,11 @@ impl<'a, RT: Runtime> IndexModel<'a, RT> {
                     ..
                 } => IndexMetadata::new_backfilling(*self.tx.begin_timestamp(), index_name, fields),
                 IndexConfig::Text {
-                    developer_config:
-                        DeveloperTextIndexConfig {
-                            search_field,
-                            filter_fields,
-                        },
-                    ..
-                } => IndexMetadata::new_backfilling_text_index(
-                    index_name,
-                    search_field,
-                    filter_fields,
-                ),
+                    developer_config, ..
+                } => IndexMetadata::new_backfilling_text_index(index_name, developer_config),
                 IndexConfig::Vector {
-                    developer_config:
-                        DeveloperVectorIndexConfig {
-                            dimensions,
-                            vector_field,
-                            filter_fields,
-                        },
-                    ..
-                } => IndexMetadata::new_backfilling_vector_index(
-                    index_name,
-                    vector_field,
-                    dimensions,
-                    filter_fields,
-                ),
+                    developer_config, ..
+                } => IndexMetadata::new_backfilling_vector_index(index_name, developer_config),
             };
             SystemMetadataModel::new_global(self.tx)
                 .insert_metadata(&INDEX_TABLE, metadata.try_into()?)
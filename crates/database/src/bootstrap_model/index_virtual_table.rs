@@ -0,0 +1,276 @@
+//! `_indexes`, a read-only virtual table over the index registry in `_index`.
+//! It lets functions list a deployment's indexes, with their state and, for
+//! text and vector indexes, how many documents they've indexed so far.
+//!
+//! `_index` only has a `by_id` index, so both of the virtual table's indexes
+//! read from it and documents come back ordered by ID.
+
+use std::{
+    collections::BTreeMap,
+    sync::LazyLock,
+};
+
+use common::{
+    bootstrap_model::index::{
+        database_index::DatabaseIndexState,
+        text_index::{
+            FragmentedTextSegment,
+            TextIndexSnapshotData,
+            TextIndexState,
+        },
+        vector_index::{
+            VectorIndexSnapshotData,
+            VectorIndexState,
+        },
+        IndexConfig,
+        TabletIndexMetadata,
+        INDEX_TABLE,
+    },
+    components::ComponentId,
+    document::{
+        DeveloperDocument,
+        ParseDocument,
+        ParsedDocument,
+        ResolvedDocument,
+        CREATION_TIME_FIELD,
+        ID_FIELD,
+    },
+    types::{
+        GenericIndexName,
+        IndexName,
+        TableName,
+    },
+    virtual_system_mapping::{
+        VirtualSystemDocMapper,
+        VirtualSystemMapping,
+    },
+};
+use maplit::btreemap;
+use semver::Version;
+use value::{
+    ConvexObject,
+    ConvexValue,
+    FieldName,
+    FieldPath,
+    TableMapping,
+};
+
+pub static INDEXES_VIRTUAL_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_indexes"
+        .parse()
+        .expect("_indexes is not a valid virtual table name")
+});
+
+static INDEX_INDEX_BY_ID: LazyLock<IndexName> =
+    LazyLock::new(|| GenericIndexName::by_id(INDEX_TABLE.clone()));
+static INDEXES_VIRTUAL_INDEX_BY_ID: LazyLock<IndexName> =
+    LazyLock::new(|| GenericIndexName::by_id(INDEXES_VIRTUAL_TABLE.clone()));
+static INDEXES_VIRTUAL_INDEX_BY_CREATION_TIME: LazyLock<IndexName> =
+    LazyLock::new(|| GenericIndexName::by_creation_time(INDEXES_VIRTUAL_TABLE.clone()));
+
+pub fn indexes_virtual_indexes() -> BTreeMap<IndexName, IndexName> {
+    btreemap! {
+        INDEXES_VIRTUAL_INDEX_BY_CREATION_TIME.clone() => INDEX_INDEX_BY_ID.clone(),
+        INDEXES_VIRTUAL_INDEX_BY_ID.clone() => INDEX_INDEX_BY_ID.clone(),
+    }
+}
+
+pub struct IndexesDocMapper;
+
+impl VirtualSystemDocMapper for IndexesDocMapper {
+    fn system_to_virtual_doc(
+        &self,
+        virtual_system_mapping: &VirtualSystemMapping,
+        doc: ResolvedDocument,
+        table_mapping: &TableMapping,
+        _version: Version,
+    ) -> anyhow::Result<DeveloperDocument> {
+        let metadata: ParsedDocument<TabletIndexMetadata> = (&doc).parse()?;
+        let metadata = metadata.into_value();
+        let tablet_id = *metadata.name.table();
+        let public_index = PublicIndex {
+            table: table_mapping.tablet_name(tablet_id)?.to_string(),
+            name: metadata.name.descriptor().to_string(),
+            component_id: ComponentId::from(table_mapping.tablet_namespace(tablet_id)?)
+                .serialize_to_string(),
+            stats: IndexStats::new(&metadata.config)?,
+            config: metadata.config,
+        };
+        let mut public_index_resolved: ConvexObject = public_index.try_into()?;
+
+        let virtual_developer_id =
+            virtual_system_mapping.system_resolved_id_to_virtual_developer_id(doc.id())?;
+
+        let mut fields: BTreeMap<_, _> = public_index_resolved.into();
+        fields.insert(ID_FIELD.to_owned().into(), virtual_developer_id.into());
+        fields.insert(
+            CREATION_TIME_FIELD.to_owned().into(),
+            ConvexValue::from(f64::from(doc.creation_time())),
+        );
+        public_index_resolved = fields.try_into()?;
+
+        Ok(DeveloperDocument::new(
+            virtual_developer_id,
+            doc.creation_time(),
+            public_index_resolved,
+        ))
+    }
+}
+
+struct PublicIndex {
+    table: String,
+    name: String,
+    component_id: Option<String>,
+    config: IndexConfig,
+    stats: IndexStats,
+}
+
+/// Size of a text or vector index, summed over its segments. Database indexes
+/// don't track their size in the registry.
+#[derive(Default)]
+struct IndexStats {
+    documents_indexed: Option<u64>,
+    size_bytes: Option<u64>,
+}
+
+impl IndexStats {
+    fn new(config: &IndexConfig) -> anyhow::Result<Self> {
+        let stats = match config {
+            IndexConfig::Database { .. } => Self::default(),
+            IndexConfig::Text { on_disk_state, .. } => {
+                let segments: &[FragmentedTextSegment] = match on_disk_state {
+                    TextIndexState::Backfilling(backfill_state) => &backfill_state.segments,
+                    TextIndexState::Backfilled(snapshot)
+                    | TextIndexState::SnapshottedAt(snapshot) => match &snapshot.data {
+                        TextIndexSnapshotData::MultiSegment(segments) => segments,
+                        TextIndexSnapshotData::Unknown(_) => return Ok(Self::default()),
+                    },
+                };
+                Self {
+                    documents_indexed: Some(
+                        segments
+                            .iter()
+                            .map(|segment| {
+                                segment
+                                    .num_indexed_documents
+                                    .saturating_sub(segment.num_deleted_documents)
+                            })
+                            .sum(),
+                    ),
+                    size_bytes: Some(segments.iter().map(|s| s.size_bytes_total).sum()),
+                }
+            },
+            IndexConfig::Vector {
+                on_disk_state,
+                developer_config,
+            } => {
+                if let VectorIndexState::Backfilled(snapshot)
+                | VectorIndexState::SnapshottedAt(snapshot) = on_disk_state
+                    && let VectorIndexSnapshotData::Unknown(_) = snapshot.data
+                {
+                    return Ok(Self::default());
+                }
+                let segments = on_disk_state.segments()?;
+                let mut documents_indexed = 0;
+                let mut size_bytes = 0;
+                for segment in segments {
+                    documents_indexed += segment.non_deleted_vectors()?;
+                    size_bytes += segment.non_deleted_size_bytes(developer_config.dimensions)?;
+                }
+                Self {
+                    documents_indexed: Some(documents_indexed),
+                    size_bytes: Some(size_bytes),
+                }
+            },
+        };
+        Ok(stats)
+    }
+}
+
+fn field_paths_value<'a>(
+    fields: impl IntoIterator<Item = &'a FieldPath>,
+) -> anyhow::Result<ConvexValue> {
+    let fields: Vec<ConvexValue> = fields
+        .into_iter()
+        .map(|field| ConvexValue::try_from(String::from(field.clone())))
+        .collect::<anyhow::Result<_>>()?;
+    Ok(ConvexValue::Array(fields.try_into()?))
+}
+
+fn optional_f64(value: Option<u64>) -> ConvexValue {
+    match value {
+        Some(value) => ConvexValue::Float64(value as f64),
+        None => ConvexValue::Null,
+    }
+}
+
+impl TryFrom<PublicIndex> for ConvexObject {
+    type Error = anyhow::Error;
+
+    fn try_from(index: PublicIndex) -> anyhow::Result<Self> {
+        let (index_type, fields, state) = match &index.config {
+            IndexConfig::Database {
+                developer_config,
+                on_disk_state,
+            } => (
+                "database",
+                field_paths_value(developer_config.fields.iter())?,
+                match on_disk_state {
+                    DatabaseIndexState::Backfilling(_) => "backfilling",
+                    DatabaseIndexState::Backfilled => "backfilled",
+                    DatabaseIndexState::Enabled => "enabled",
+                },
+            ),
+            IndexConfig::Text {
+                developer_config,
+                on_disk_state,
+            } => (
+                "search",
+                field_paths_value(
+                    std::iter::once(&developer_config.search_field)
+                        .chain(&developer_config.extra_search_fields)
+                        .chain(&developer_config.filter_fields),
+                )?,
+                match on_disk_state {
+                    TextIndexState::Backfilling(_) => "backfilling",
+                    TextIndexState::Backfilled(_) => "backfilled",
+                    TextIndexState::SnapshottedAt(_) => "enabled",
+                },
+            ),
+            IndexConfig::Vector {
+                developer_config,
+                on_disk_state,
+            } => (
+                "vector",
+                field_paths_value(
+                    std::iter::once(&developer_config.vector_field)
+                        .chain(&developer_config.filter_fields),
+                )?,
+                match on_disk_state {
+                    VectorIndexState::Backfilling(_) => "backfilling",
+                    VectorIndexState::Backfilled(_) => "backfilled",
+                    VectorIndexState::SnapshottedAt(_) => "enabled",
+                },
+            ),
+        };
+        let mut obj: BTreeMap<FieldName, ConvexValue> = BTreeMap::new();
+        obj.insert("table".parse()?, index.table.try_into()?);
+        obj.insert("name".parse()?, index.name.try_into()?);
+        obj.insert(
+            "componentId".parse()?,
+            match index.component_id {
+                Some(component_id) => component_id.try_into()?,
+                None => ConvexValue::Null,
+            },
+        );
+        obj.insert("type".parse()?, index_type.try_into()?);
+        obj.insert("fields".parse()?, fields);
+        obj.insert("state".parse()?, state.try_into()?);
+        obj.insert(
+            "documentsIndexed".parse()?,
+            optional_f64(index.stats.documents_indexed),
+        );
+        obj.insert("sizeBytes".parse()?, optional_f64(index.stats.size_bytes));
+        ConvexObject::try_from(obj)
+    }
+}
@@ -6,6 +6,7 @@ pub mod components;
 pub mod defaults;
 pub mod import_facing;
 pub mod index;
+pub mod index_virtual_table;
 pub mod index_workers;
 pub mod schema;
 pub mod system_metadata;
@@ -3,6 +3,7 @@ mod tests;
 pub mod types;
 
 use std::{
+    collections::BTreeSet,
     sync::{
         Arc,
         LazyLock,
@@ -28,6 +29,7 @@ use common::{
     },
     runtime::Runtime,
     schemas::{
+        violations::SchemaViolationReport,
         DatabaseSchema,
         SchemaValidationError,
     },
@@ -35,6 +37,7 @@ use common::{
 use errors::ErrorMetadata;
 use value::{
     FieldPath,
+    IdentifierFieldName,
     NamespacedTableMapping,
     ResolvedDocumentId,
     TableName,
@@ -136,6 +139,29 @@ impl<'a, RT: Runtime> SchemaModel<'a, RT> {
             .await
     }
 
+    /// Rejects updates that change a field the active schema declares
+    /// immutable or write-once.
+    pub async fn enforce_mutability(
+        &mut self,
+        old_document: &ResolvedDocument,
+        new_document: &ResolvedDocument,
+    ) -> anyhow::Result<()> {
+        let schema_table_mapping = self.tx.table_mapping().namespace(self.namespace);
+        if schema_table_mapping.is_system_tablet(new_document.id().tablet_id) {
+            return Ok(());
+        }
+        let Some((_id, active_schema)) = self.get_by_state(SchemaState::Active).await? else {
+            return Ok(());
+        };
+        let table_name = schema_table_mapping.tablet_name(new_document.id().tablet_id)?;
+        if let Some(table_definition) = active_schema.tables.get(&table_name)
+            && let Err(error) = table_definition.check_mutability(old_document, new_document)
+        {
+            anyhow::bail!(error.to_error_metadata());
+        }
+        Ok(())
+    }
+
     pub async fn enforce_table_deletion(
         &mut self,
         active_table_to_delete: TableName,
@@ -166,6 +192,21 @@ impl<'a, RT: Runtime> SchemaModel<'a, RT> {
         Ok(())
     }
 
+    /// Like `enforce`, but the active schema only checks `field_names` since
+    /// the document's other fields didn't change.
+    pub async fn enforce_patch(
+        &mut self,
+        document: &ResolvedDocument,
+        field_names: &BTreeSet<IdentifierFieldName>,
+    ) -> anyhow::Result<()> {
+        let schema_table_mapping = self.tx.table_mapping().namespace(self.namespace);
+        if schema_table_mapping.is_system_tablet(document.id().tablet_id) {
+            return Ok(());
+        }
+        self.enforce_inner(document, &schema_table_mapping, Some(field_names))
+            .await
+    }
+
     /// You probably want to use `enforce`.
     /// enforce_with_table_mapping allows schema validation to use a custom
     /// TableMapping for validating foreign references, which is useful for
@@ -175,18 +216,40 @@ impl<'a, RT: Runtime> SchemaModel<'a, RT> {
         &mut self,
         document: &ResolvedDocument,
         table_mapping_for_schema: &NamespacedTableMapping,
+    ) -> anyhow::Result<()> {
+        self.enforce_inner(document, table_mapping_for_schema, None)
+            .await
+    }
+
+    async fn enforce_inner(
+        &mut self,
+        document: &ResolvedDocument,
+        table_mapping_for_schema: &NamespacedTableMapping,
+        patched_fields: Option<&BTreeSet<IdentifierFieldName>>,
     ) -> anyhow::Result<()> {
         let table_name = table_mapping_for_schema.tablet_name(document.id().tablet_id)?;
         if let Some((_id, active_schema)) = self.get_by_state(SchemaState::Active).await? {
-            if let Err(schema_error) = active_schema.check_new_document(
-                document,
-                table_name.clone(),
-                table_mapping_for_schema,
-                self.tx.virtual_system_mapping(),
-            ) {
+            let result = match patched_fields {
+                Some(field_names) => active_schema.check_patched_document(
+                    document,
+                    table_name.clone(),
+                    field_names,
+                    table_mapping_for_schema,
+                    self.tx.virtual_system_mapping(),
+                ),
+                None => active_schema.check_new_document(
+                    document,
+                    table_name.clone(),
+                    table_mapping_for_schema,
+                    self.tx.virtual_system_mapping(),
+                ),
+            };
+            if let Err(schema_error) = result {
                 anyhow::bail!(schema_error.to_error_metadata());
             }
         }
+        // Pending schemas haven't checked existing documents yet, so they check
+        // the whole document.
         let pending_schema = self.get_by_state(SchemaState::Pending).await?;
         let validated_schema = self.get_by_state(SchemaState::Validated).await?;
         match (pending_schema, validated_schema) {
@@ -370,6 +433,17 @@ impl<'a, RT: Runtime> SchemaModel<'a, RT> {
         &mut self,
         document_id: ResolvedDocumentId,
         error: SchemaValidationError,
+    ) -> anyhow::Result<()> {
+        self.mark_failed_with_report(document_id, error, None).await
+    }
+
+    /// Marks a schema as failed, recording every existing document that
+    /// doesn't match it in `report`.
+    pub async fn mark_failed_with_report(
+        &mut self,
+        document_id: ResolvedDocumentId,
+        error: SchemaValidationError,
+        report: Option<SchemaViolationReport>,
     ) -> anyhow::Result<()> {
         let doc = self
             .tx
@@ -395,7 +469,8 @@ impl<'a, RT: Runtime> SchemaModel<'a, RT> {
                             "state" => Some(
                                 SchemaState::Failed {
                                     error: error_message,
-                                    table_name: Some(table_name.to_string())
+                                    table_name: Some(table_name.to_string()),
+                                    report,
                                 }.try_into()?
                             )
                         )?,
@@ -47,6 +47,13 @@ impl<'a, RT: Runtime> SystemMetadataModel<'a, RT> {
         }
     }
 
+    /// Whether `table` exists in this namespace. A system table added by a
+    /// migration doesn't exist until the deployment has been migrated past
+    /// it, so models check this first and treat a missing table as empty.
+    pub fn table_exists(&mut self, table: &TableName) -> bool {
+        TableModel::new(self.tx).table_exists(self.namespace, table)
+    }
+
     /// Creates a new document with given value in the specified table,
     /// enforcing that the transaction's identity is system or admin.
     #[fastrace::trace]
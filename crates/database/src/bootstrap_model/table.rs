@@ -761,7 +761,7 @@ mod tests {
             .await?
             .is_none());
         let schema = ParseDocument::<SchemaMetadata>::parse(tx.get(schema_id).await?.unwrap())?;
-        must_let!(let SchemaState::Failed { error, table_name } = &schema.state);
+        must_let!(let SchemaState::Failed { error, table_name, .. } = &schema.state);
         assert_eq!(table_name, &Some("my_table".to_string()));
         assert!(
             error
@@ -5,16 +5,26 @@ use std::{
 
 use anyhow::Context;
 use common::{
-    bootstrap_model::components::ComponentState,
+    bootstrap_model::{
+        components::ComponentState,
+        schema::SchemaState,
+    },
     components::ComponentId,
     document::{
+        CreationTime,
         DeveloperDocument,
         ResolvedDocument,
     },
     query::CursorPosition,
     runtime::Runtime,
+    schemas::{
+        ServerDefaults,
+        VectorEmbedder,
+    },
     types::{
+        IndexDescriptor,
         StableIndexName,
+        Timestamp,
         WriteTimestamp,
     },
     version::Version,
@@ -24,14 +34,16 @@ use indexing::backend_in_memory_indexes::{
     BatchKey,
     RangeRequest,
 };
+use keybroker::Identity;
 use value::{
-    check_user_size,
     ConvexObject,
+    ConvexValue,
     DeveloperDocumentId,
     ResolvedDocumentId,
     Size,
     TableName,
     TableNamespace,
+    TabletId,
 };
 
 use crate::{
@@ -43,14 +55,19 @@ use crate::{
         DeveloperIndexRangeResponse,
         IndexRangeResponse,
     },
+    table_freezes::TableFreezesModel,
     transaction::{
         IndexRangeRequest,
         MAX_PAGE_SIZE,
     },
     unauthorized_error,
+    vector_embedding_jobs::VectorEmbeddingJobModel,
     virtual_tables::VirtualTable,
     BootstrapComponentsModel,
+    ComponentQuotasModel,
+    DeepPatch,
     PatchValue,
+    SchemaModel,
     TableModel,
     Transaction,
 };
@@ -190,7 +207,6 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
             ));
         }
 
-        check_user_size(value.size())?;
         self.tx.retention_validator.fail_if_falling_behind()?;
         let internal_id = self.tx.id_generator.generate_internal();
 
@@ -202,6 +218,10 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
                 format!("Invalid table name {table} starts with metadata prefix '_'")
             ));
         }
+        let value = self
+            .apply_schema_defaults(&table, value, creation_time)
+            .await?;
+        self.tx.check_user_document_size(&value)?;
 
         // Note that the index and document store updates within `self.insert_document`
         // below are fallible, and since the layers above still have access to
@@ -223,6 +243,12 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
             .table_mapping()
             .namespace(self.namespace)
             .name_to_id_user_input()(table)?;
+        TableFreezesModel::new(self.tx)
+            .check_writable(table_id.tablet_id)
+            .await?;
+        ComponentQuotasModel::new(self.tx)
+            .check_document_quota(self.namespace, true)
+            .await?;
         let document = ResolvedDocument::new(
             ResolvedDocumentId::new(
                 table_id.tablet_id,
@@ -231,11 +257,107 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
             creation_time,
             value,
         )?;
-        let document_id = self.tx.insert_document(document).await?;
+        let embedders = self.vector_embedders(table_id.tablet_id).await?;
+        let document_id = self.tx.insert_document(document.clone()).await?;
+        self.queue_embeddings(embedders, None, &document).await?;
 
         Ok(document_id.into())
     }
 
+    /// The embedders of the active schema's vector indexes on the table.
+    async fn vector_embedders(
+        &mut self,
+        tablet_id: TabletId,
+    ) -> anyhow::Result<Vec<(IndexDescriptor, VectorEmbedder)>> {
+        let Some((_, schema)) = SchemaModel::new(self.tx, self.namespace)
+            .get_by_state(SchemaState::Active)
+            .await?
+        else {
+            return Ok(vec![]);
+        };
+        let table = self.tx.table_mapping().tablet_name(tablet_id)?;
+        let Some(table_definition) = schema.tables.get(&table) else {
+            return Ok(vec![]);
+        };
+        Ok(table_definition
+            .vector_indexes
+            .iter()
+            .filter_map(|(descriptor, index)| Some((descriptor.clone(), index.embedder.clone()?)))
+            .collect())
+    }
+
+    /// Queues the document for each embedder whose source field the write set
+    /// to a new string. `previous` is the document's value before the write.
+    async fn queue_embeddings(
+        &mut self,
+        embedders: Vec<(IndexDescriptor, VectorEmbedder)>,
+        previous: Option<&ConvexObject>,
+        document: &ResolvedDocument,
+    ) -> anyhow::Result<()> {
+        for (index_descriptor, embedder) in embedders {
+            let source = document.value().get_path(&embedder.source_field);
+            if !matches!(source, Some(ConvexValue::String(_)))
+                || previous
+                    .is_some_and(|previous| previous.get_path(&embedder.source_field) == source)
+            {
+                continue;
+            }
+            let now_ms = self.tx.runtime().unix_timestamp().as_ms_since_epoch()? as i64;
+            VectorEmbeddingJobModel::new(self.tx)
+                .enqueue(document.id(), index_descriptor, now_ms)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Reads the document's current value if the write needs to compare it
+    /// with the new one to decide whether to queue embeddings.
+    async fn previous_for_embedders(
+        &mut self,
+        id: ResolvedDocumentId,
+        embedders: &[(IndexDescriptor, VectorEmbedder)],
+    ) -> anyhow::Result<Option<ConvexObject>> {
+        if embedders.is_empty() {
+            return Ok(None);
+        }
+        Ok(self
+            .tx
+            .get(id)
+            .await?
+            .map(|document| document.value().0.clone()))
+    }
+
+    /// Fills in the defaults the active schema declares for fields a new
+    /// document doesn't set.
+    async fn apply_schema_defaults(
+        &mut self,
+        table: &TableName,
+        value: ConvexObject,
+        creation_time: CreationTime,
+    ) -> anyhow::Result<ConvexObject> {
+        let Some((_, schema)) = SchemaModel::new(self.tx, self.namespace)
+            .get_by_state(SchemaState::Active)
+            .await?
+        else {
+            return Ok(value);
+        };
+        let Some(table_definition) = schema.tables.get(table) else {
+            return Ok(value);
+        };
+        let token_identifier = match self.tx.identity() {
+            Identity::User(user) => Some(&user.attributes.token_identifier),
+            Identity::ActingUser(_, attributes) => Some(&attributes.token_identifier),
+            _ => None,
+        };
+        table_definition.apply_defaults(
+            value,
+            &ServerDefaults {
+                now: creation_time.into(),
+                token_identifier: token_identifier.map(|identifier| identifier.0.as_str()),
+            },
+        )
+    }
+
     /// Merges the existing document with the given object. Will overwrite any
     /// conflicting fields.
     #[fastrace::trace]
@@ -254,18 +376,66 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
         self.tx.retention_validator.fail_if_falling_behind()?;
 
         let id_ = self.tx.resolve_developer_id(&id, self.namespace)?;
+        TableFreezesModel::new(self.tx)
+            .check_writable(id_.tablet_id)
+            .await?;
+        ComponentQuotasModel::new(self.tx)
+            .check_document_quota(self.namespace, false)
+            .await?;
+        let embedders = self.vector_embedders(id_.tablet_id).await?;
+        let previous = self.previous_for_embedders(id_, &embedders).await?;
 
         let new_document = self.tx.patch_inner(id_, value).await?;
 
         // Check the size of the patched document.
         if !self.tx.is_system(self.namespace, id.table()) {
-            check_user_size(new_document.size())?;
+            self.tx.check_user_document_size(&new_document.value())?;
         }
+        self.queue_embeddings(embedders, previous.as_ref(), &new_document)
+            .await?;
 
         let developer_document = new_document.to_developer();
         Ok(developer_document)
     }
 
+    /// Updates nested fields of the document in place. Only the top-level
+    /// fields the patch touches are checked against the schema.
+    #[fastrace::trace]
+    #[convex_macro::instrument_future]
+    pub async fn deep_patch(
+        &mut self,
+        id: DeveloperDocumentId,
+        patch: DeepPatch,
+    ) -> anyhow::Result<DeveloperDocument> {
+        if self.tx.is_system(self.namespace, id.table())
+            && !(self.tx.identity.is_admin() || self.tx.identity.is_system())
+        {
+            anyhow::bail!(unauthorized_error("patch"))
+        }
+        self.require_active_component().await?;
+        self.tx.retention_validator.fail_if_falling_behind()?;
+
+        let id_ = self.tx.resolve_developer_id(&id, self.namespace)?;
+        TableFreezesModel::new(self.tx)
+            .check_writable(id_.tablet_id)
+            .await?;
+        ComponentQuotasModel::new(self.tx)
+            .check_document_quota(self.namespace, false)
+            .await?;
+        let embedders = self.vector_embedders(id_.tablet_id).await?;
+        let previous = self.previous_for_embedders(id_, &embedders).await?;
+
+        let new_document = self.tx.deep_patch_inner(id_, patch).await?;
+
+        if !self.tx.is_system(self.namespace, id.table()) {
+            self.tx.check_user_document_size(&new_document.value())?;
+        }
+        self.queue_embeddings(embedders, previous.as_ref(), &new_document)
+            .await?;
+
+        Ok(new_document.to_developer())
+    }
+
     /// Replace the document with the given value.
     #[fastrace::trace]
     #[convex_macro::instrument_future]
@@ -281,16 +451,67 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
         }
         self.require_active_component().await?;
         if !self.tx.is_system(self.namespace, id.table()) {
-            check_user_size(value.size())?;
+            self.tx.check_user_document_size(&value)?;
         }
         self.tx.retention_validator.fail_if_falling_behind()?;
         let id_ = self.tx.resolve_developer_id(&id, self.namespace)?;
+        TableFreezesModel::new(self.tx)
+            .check_writable(id_.tablet_id)
+            .await?;
+        ComponentQuotasModel::new(self.tx)
+            .check_document_quota(self.namespace, false)
+            .await?;
+        let embedders = self.vector_embedders(id_.tablet_id).await?;
+        let previous = self.previous_for_embedders(id_, &embedders).await?;
 
         let new_document = self.tx.replace_inner(id_, value).await?;
+        self.queue_embeddings(embedders, previous.as_ref(), &new_document)
+            .await?;
         let developer_document = new_document.to_developer();
         Ok(developer_document)
     }
 
+    /// The timestamp of the document's latest write, which increases every
+    /// time it's written. `None` if the document doesn't exist or was written
+    /// earlier in this transaction, since it has no timestamp until commit.
+    #[fastrace::trace]
+    #[convex_macro::instrument_future]
+    pub async fn get_version(
+        &mut self,
+        id: DeveloperDocumentId,
+    ) -> anyhow::Result<Option<Timestamp>> {
+        Ok(match self.get_with_ts(id, None).await? {
+            Some((_, WriteTimestamp::Committed(ts))) => Some(ts),
+            _ => None,
+        })
+    }
+
+    /// Replace the document with the given value if its version, from
+    /// [`Self::get_version`], is still `version`. Returns whether the document
+    /// was replaced.
+    #[fastrace::trace]
+    #[convex_macro::instrument_future]
+    pub async fn replace_if_version(
+        &mut self,
+        id: DeveloperDocumentId,
+        version: Timestamp,
+        value: ConvexObject,
+    ) -> anyhow::Result<bool> {
+        // Reading the document puts it in the read set, so a concurrent write
+        // makes this transaction retry and see the newer version.
+        let Some((_, ts)) = self.get_with_ts(id, None).await? else {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "NonexistentDocument",
+                format!("Update on nonexistent document ID {id}"),
+            ));
+        };
+        if ts != WriteTimestamp::Committed(version) {
+            return Ok(false);
+        }
+        self.replace(id, value).await?;
+        Ok(true)
+    }
+
     /// Delete the document at the given path -- called from user facing APIs
     /// (e.g. syscalls)
     #[fastrace::trace]
@@ -305,6 +526,9 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
         self.tx.retention_validator.fail_if_falling_behind()?;
 
         let id_ = self.tx.resolve_developer_id(&id, self.namespace)?;
+        TableFreezesModel::new(self.tx)
+            .check_writable(id_.tablet_id)
+            .await?;
         let document = self.tx.delete_inner(id_).await?;
         Ok(document.to_developer())
     }
@@ -0,0 +1,158 @@
+//! Admission control in front of the committer. Bounds the number of
+//! in-flight commits and sheds user commits before system commits so an
+//! overloaded deployment keeps its background work and subscriptions
+//! running while clients back off.
+
+use std::sync::{
+    atomic::{
+        AtomicUsize,
+        Ordering,
+    },
+    Arc,
+};
+
+use common::knobs::{
+    COMMITTER_MAX_IN_FLIGHT_COMMITS,
+    COMMITTER_MAX_IN_FLIGHT_USER_COMMITS,
+};
+use errors::ErrorMetadata;
+use keybroker::Identity;
+
+use crate::metrics;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommitPriority {
+    /// Commits from user functions and requests, shed first.
+    User,
+    /// Commits made by the backend itself.
+    System,
+}
+
+impl CommitPriority {
+    pub fn for_identity(identity: &Identity) -> Self {
+        if identity.is_system() {
+            Self::System
+        } else {
+            Self::User
+        }
+    }
+
+    pub fn as_label(&self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::System => "system",
+        }
+    }
+}
+
+#[derive(Default)]
+struct InFlightCounts {
+    total: AtomicUsize,
+    user: AtomicUsize,
+}
+
+#[derive(Clone)]
+pub struct CommitAdmission {
+    in_flight: Arc<InFlightCounts>,
+    max_in_flight: usize,
+    max_in_flight_user: usize,
+}
+
+impl CommitAdmission {
+    pub fn new() -> Self {
+        Self::with_limits(
+            *COMMITTER_MAX_IN_FLIGHT_COMMITS,
+            *COMMITTER_MAX_IN_FLIGHT_USER_COMMITS,
+        )
+    }
+
+    fn with_limits(max_in_flight: usize, max_in_flight_user: usize) -> Self {
+        Self {
+            in_flight: Arc::new(InFlightCounts::default()),
+            max_in_flight,
+            max_in_flight_user,
+        }
+    }
+
+    /// Admits a commit, or returns an `Overloaded` error if there are already
+    /// too many commits in flight at its priority. The commit counts as in
+    /// flight until the permit is dropped.
+    pub fn try_admit(&self, priority: CommitPriority) -> Result<CommitPermit, ErrorMetadata> {
+        let admit = |count: &AtomicUsize, limit: usize| {
+            count
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                    (n < limit).then_some(n + 1)
+                })
+                .is_ok()
+        };
+        if priority == CommitPriority::User && !admit(&self.in_flight.user, self.max_in_flight_user)
+        {
+            return Err(metrics::too_many_commits_in_flight_error(priority));
+        }
+        if !admit(&self.in_flight.total, self.max_in_flight) {
+            if priority == CommitPriority::User {
+                self.in_flight.user.fetch_sub(1, Ordering::SeqCst);
+            }
+            return Err(metrics::too_many_commits_in_flight_error(priority));
+        }
+        let permit = CommitPermit {
+            in_flight: self.in_flight.clone(),
+            priority,
+        };
+        permit.log_in_flight();
+        Ok(permit)
+    }
+}
+
+pub struct CommitPermit {
+    in_flight: Arc<InFlightCounts>,
+    priority: CommitPriority,
+}
+
+impl CommitPermit {
+    pub fn priority(&self) -> CommitPriority {
+        self.priority
+    }
+
+    fn log_in_flight(&self) {
+        let total = self.in_flight.total.load(Ordering::SeqCst);
+        let user = self.in_flight.user.load(Ordering::SeqCst);
+        metrics::log_commits_in_flight(CommitPriority::User, user);
+        metrics::log_commits_in_flight(CommitPriority::System, total.saturating_sub(user));
+    }
+}
+
+impl Drop for CommitPermit {
+    fn drop(&mut self) {
+        if self.priority == CommitPriority::User {
+            self.in_flight.user.fetch_sub(1, Ordering::SeqCst);
+        }
+        self.in_flight.total.fetch_sub(1, Ordering::SeqCst);
+        self.log_in_flight();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CommitAdmission,
+        CommitPriority,
+    };
+
+    #[test]
+    fn test_user_commits_shed_first() {
+        let admission = CommitAdmission::with_limits(3, 2);
+        let user1 = admission.try_admit(CommitPriority::User).unwrap();
+        let _user2 = admission.try_admit(CommitPriority::User).unwrap();
+        // User commits are at their limit, but system commits still have room.
+        let err = admission.try_admit(CommitPriority::User).unwrap_err();
+        assert!(err.is_overloaded());
+        let _system = admission.try_admit(CommitPriority::System).unwrap();
+        assert!(admission.try_admit(CommitPriority::System).is_err());
+
+        // Finishing a user commit frees a slot for either priority.
+        drop(user1);
+        let _user3 = admission.try_admit(CommitPriority::User).unwrap();
+        assert!(admission.try_admit(CommitPriority::System).is_err());
+    }
+}
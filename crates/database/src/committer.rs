@@ -12,6 +12,7 @@ use ::metrics::{
 };
 use anyhow::Context as _;
 use common::{
+    adaptive_pacing::record_commit_latency,
     backoff::Backoff,
     bootstrap_model::tables::{
         TableMetadata,
@@ -38,11 +39,13 @@ use common::{
     },
     knobs::{
         COMMITTER_QUEUE_SIZE,
+        COMMIT_QUEUE_TIME_BUDGET,
         COMMIT_TRACE_THRESHOLD,
         MAX_REPEATABLE_TIMESTAMP_COMMIT_DELAY,
         MAX_REPEATABLE_TIMESTAMP_IDLE_FREQUENCY,
         TRANSACTION_WARN_READ_SET_INTERVALS,
     },
+    pause::PauseClient,
     persistence::{
         ConflictStrategy,
         DocumentLogEntry,
@@ -112,6 +115,10 @@ use vector::DocInVectorIndex;
 
 use crate::{
     bootstrap_model::defaults::BootstrapTableIds,
+    commit_admission::{
+        CommitAdmission,
+        CommitPriority,
+    },
     database::ConflictingReadWithWriteSource,
     metrics::{
         self,
@@ -131,10 +138,12 @@ use crate::{
     },
     transaction::FinalTransaction,
     write_log::{
+        LogReader,
         LogWriter,
         PackedDocumentUpdate,
         PendingWriteHandle,
         PendingWrites,
+        SpillPin,
         WriteSource,
     },
     writes::DocumentWrite,
@@ -170,6 +179,11 @@ impl PersistenceWrite {
 }
 
 pub const AFTER_PENDING_WRITE_SNAPSHOT: &str = "after_pending_write_snapshot";
+pub const AFTER_SPILLED_CONFLICTS_CHECK: &str = "after_spilled_conflicts_check";
+
+/// Pings waiting for the committer loop beyond this many block until it
+/// catches up.
+const PING_QUEUE_SIZE: usize = 16;
 
 pub struct Committer<RT: Runtime> {
     // Internal staged commits for conflict checking.
@@ -199,7 +213,11 @@ impl<RT: Runtime> Committer<RT> {
     ) -> CommitterClient {
         let persistence_reader = persistence.reader();
         let conflict_checker = PendingWrites::new(persistence_reader.version());
+        let log_reader = log.reader();
         let (tx, rx) = mpsc::channel(*COMMITTER_QUEUE_SIZE);
+        // Pings go on their own channel so a full commit queue doesn't make
+        // a busy committer look unhealthy.
+        let (ping_tx, ping_rx) = mpsc::channel(PING_QUEUE_SIZE);
         let snapshot_reader = snapshot_manager.reader();
         let committer = Self {
             pending_writes: conflict_checker,
@@ -212,7 +230,7 @@ impl<RT: Runtime> Committer<RT> {
             retention_validator: retention_validator.clone(),
         };
         let handle = runtime.spawn("committer", async move {
-            if let Err(err) = committer.go(rx).await {
+            if let Err(err) = committer.go(rx, ping_rx).await {
                 // Committer hit a fatal error. This should only happen if a
                 // persistence write fails or in case of unrecoverable logic
                 // errors.
@@ -223,13 +241,21 @@ impl<RT: Runtime> Committer<RT> {
         CommitterClient {
             handle: Arc::new(Mutex::new(handle)),
             sender: tx,
+            ping_sender: ping_tx,
+            admission: CommitAdmission::new(),
+            log: log_reader,
             persistence_reader,
             retention_validator,
             snapshot_reader,
+            pause_client: runtime.pause_client(),
         }
     }
 
-    async fn go(mut self, mut rx: mpsc::Receiver<CommitterMessage>) -> anyhow::Result<()> {
+    async fn go(
+        mut self,
+        mut rx: mpsc::Receiver<CommitterMessage>,
+        mut ping_rx: mpsc::Receiver<oneshot::Sender<()>>,
+    ) -> anyhow::Result<()> {
         let mut last_bumped_repeatable_ts = self.runtime.monotonic_now();
         // Assume there were commits just before the backend restarted, so first do a
         // quick bump.
@@ -337,7 +363,20 @@ impl<RT: Runtime> Committer<RT> {
                             result,
                             write_source,
                             parent_trace,
+                            priority,
                         }) => {
+                            // Shed user commits that waited too long rather than
+                            // falling further behind. Nothing has been written yet,
+                            // so the caller can retry.
+                            if priority == CommitPriority::User
+                                && let Some(budget) = *COMMIT_QUEUE_TIME_BUDGET
+                                && queue_timer.elapsed() > budget
+                            {
+                                let _ = result.send(Err(
+                                    metrics::commit_queue_time_exceeded_error(priority).into(),
+                                ));
+                                continue;
+                            }
 
                             let parent_span = initialize_root_from_parent("handle_commit_message", parent_trace.clone())
                                 .with_property(|| ("time_in_queue_ms", format!("{}", queue_timer.elapsed().as_secs_f64() * 1000.0)));
@@ -393,7 +432,14 @@ impl<RT: Runtime> Committer<RT> {
                         }) => {
                             let response = self.load_indexes_into_memory(tables).await;
                             let _ = result.send(response);
-                        }
+                        },
+                    }
+                },
+                maybe_ping = ping_rx.recv().fuse() => {
+                    // The ping channel closes along with the commit channel,
+                    // which is checked first and shuts the committer down.
+                    if let Some(result) = maybe_ping {
+                        let _ = result.send(());
                     }
                 },
             }
@@ -673,7 +719,7 @@ impl<RT: Runtime> Committer<RT> {
         let timer = metrics::commit_is_stale_timer();
         if let Some(conflicting_read) = self.commit_has_conflict(
             transaction.reads.read_set(),
-            *transaction.begin_timestamp,
+            transaction.conflicts_checked_ts,
             commit_ts,
         )? {
             anyhow::bail!(conflicting_read.into_error(&transaction.table_mapping, &write_source));
@@ -807,6 +853,7 @@ impl<RT: Runtime> Committer<RT> {
             .write(document_writes, index_writes, ConflictStrategy::Error)
             .await?;
 
+        record_commit_latency(timer.elapsed());
         timer.finish();
         Ok(())
     }
@@ -1044,9 +1091,13 @@ struct ValidatedDocumentWrite {
 pub struct CommitterClient {
     handle: Arc<Mutex<Box<dyn SpawnHandle>>>,
     sender: mpsc::Sender<CommitterMessage>,
+    ping_sender: mpsc::Sender<oneshot::Sender<()>>,
+    admission: CommitAdmission,
+    log: LogReader,
     persistence_reader: Arc<dyn PersistenceReader>,
     retention_validator: Arc<dyn RetentionValidator>,
     snapshot_reader: Reader<SnapshotManager>,
+    pause_client: PauseClient,
 }
 
 impl CommitterClient {
@@ -1113,8 +1164,19 @@ impl CommitterClient {
         self.check_generated_ids(&transaction).await?;
 
         // Finish reading everything from persistence.
-        let transaction = transaction.finalize(self.snapshot_reader.clone()).await?;
+        let mut transaction = transaction.finalize(self.snapshot_reader.clone()).await?;
+        // Held until the commit finishes, so the committer can check the rest
+        // of the log in memory.
+        let spill_pin = self
+            .check_spilled_conflicts(&mut transaction, &write_source)
+            .await?;
+        self.pause_client.wait(AFTER_SPILLED_CONFLICTS_CHECK).await;
 
+        // Held until the commit finishes, so it counts as in flight while it's
+        // queued and while it's being written.
+        let permit = self
+            .admission
+            .try_admit(CommitPriority::for_identity(transaction.identity()))?;
         let queue_timer = metrics::commit_queue_timer();
         let (tx, rx) = oneshot::channel();
         let message = CommitterMessage::Commit {
@@ -1123,6 +1185,7 @@ impl CommitterClient {
             result: tx,
             write_source,
             parent_trace: EncodedSpan::from_parent(),
+            priority: permit.priority(),
         };
         self.sender.try_send(message).map_err(|e| match e {
             TrySendError::Full(..) => metrics::committer_full_error().into(),
@@ -1131,6 +1194,8 @@ impl CommitterClient {
         let Ok(result) = rx.await else {
             anyhow::bail!(metrics::shutdown_error());
         };
+        drop(permit);
+        drop(spill_pin);
         if let Err(e) = result {
             // For OCC and other known commit failure error types,
             // replace the committer's stacktrace with the caller's stack trace as
@@ -1147,6 +1212,19 @@ impl CommitterClient {
         self.handle.lock().shutdown();
     }
 
+    /// Waits for the committer loop to handle a ping, to check that it's
+    /// still running and isn't stuck. Pings don't queue behind commits, so
+    /// this succeeds while the commit queue is full as long as the loop is
+    /// making progress.
+    pub async fn ping(&self) -> anyhow::Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.ping_sender
+            .send(tx)
+            .await
+            .map_err(|_| metrics::shutdown_error())?;
+        rx.await.map_err(|_| metrics::shutdown_error())
+    }
+
     #[cfg(any(test, feature = "testing"))]
     pub async fn bump_max_repeatable_ts(&self) -> anyhow::Result<Timestamp> {
         let (tx, rx) = oneshot::channel();
@@ -1157,6 +1235,35 @@ impl CommitterClient {
         Ok(rx.await?)
     }
 
+    /// The committer only checks for conflicts against the write log it has in
+    /// memory, so check against any of it that's been spilled here, before
+    /// queueing the commit. The returned pin keeps the log after the checked
+    /// timestamp from being spilled before the committer gets to it.
+    async fn check_spilled_conflicts(
+        &self,
+        transaction: &mut FinalTransaction,
+        write_source: &WriteSource,
+    ) -> anyhow::Result<SpillPin> {
+        let persistence = RepeatablePersistence::new(
+            self.persistence_reader.clone(),
+            self.snapshot_reader.lock().latest_ts(),
+            self.retention_validator.clone(),
+        );
+        let (conflict, spill_pin) = self
+            .log
+            .check_spilled(
+                transaction.reads.read_set(),
+                transaction.conflicts_checked_ts,
+                &persistence,
+            )
+            .await?;
+        if let Some(conflicting_read) = conflict {
+            anyhow::bail!(conflicting_read.into_error(&transaction.table_mapping, write_source));
+        }
+        transaction.conflicts_checked_ts = spill_pin.ts();
+        Ok(spill_pin)
+    }
+
     async fn check_generated_ids<RT: Runtime>(
         &self,
         transaction: &Transaction<RT>,
@@ -1224,6 +1331,7 @@ enum CommitterMessage {
         result: oneshot::Sender<anyhow::Result<Timestamp>>,
         write_source: WriteSource,
         parent_trace: EncodedSpan,
+        priority: CommitPriority,
     },
     #[cfg(any(test, feature = "testing"))]
     BumpMaxRepeatableTs { result: oneshot::Sender<Timestamp> },
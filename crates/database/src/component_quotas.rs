@@ -0,0 +1,420 @@
+//! Resource quotas for components, so a buggy third-party component can't use
+//! up the whole deployment. These live in the database crate rather than the
+//! model crate because `UserFacingModel` checks them on writes.
+//!
+//! Document counts and sizes come from running totals kept per component in
+//! the table summaries of the transaction's snapshot rather than from reading
+//! the component's tables, so checking a quota doesn't make a write conflict
+//! with every other write to the component. Concurrent writes can overshoot a
+//! quota by a few documents.
+
+use std::{
+    collections::BTreeMap,
+    sync::{
+        Arc,
+        LazyLock,
+    },
+};
+
+use common::{
+    components::ComponentId,
+    document::{
+        ParseDocument,
+        ParsedDocument,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+};
+use errors::ErrorMetadata;
+use parking_lot::Mutex;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::{
+    codegen_convex_serialization,
+    ConvexValue,
+    DeveloperDocumentId,
+    FieldPath,
+    TableName,
+    TableNamespace,
+    TabletId,
+};
+
+use crate::{
+    system_tables::{
+        SystemIndex,
+        SystemTable,
+    },
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+
+pub static COMPONENT_QUOTAS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_component_quotas"
+        .parse()
+        .expect("Invalid built-in table name")
+});
+
+static COMPONENT_ID_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "componentId".parse().expect("Invalid built-in field"));
+
+pub static COMPONENT_QUOTAS_BY_COMPONENT_ID: LazyLock<SystemIndex<ComponentQuotasTable>> =
+    LazyLock::new(|| SystemIndex::new("by_component_id", [&COMPONENT_ID_FIELD]).unwrap());
+
+pub struct ComponentQuotasTable;
+
+impl SystemTable for ComponentQuotasTable {
+    type Metadata = ComponentQuotaRecord;
+
+    fn table_name() -> &'static TableName {
+        &COMPONENT_QUOTAS_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![COMPONENT_QUOTAS_BY_COMPONENT_ID.clone()]
+    }
+}
+
+/// Limits on what a component can use. Unset limits are unlimited.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ComponentQuota {
+    /// How many documents the component's tables can hold in total.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::option::of(0..=i64::MAX as u64)")
+    )]
+    pub max_documents: Option<u64>,
+    /// How many bytes of documents the component's tables can hold in total.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::option::of(0..=i64::MAX as u64)")
+    )]
+    pub max_document_bytes: Option<u64>,
+    /// How many of the component's functions can start executing each minute.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::option::of(0..=i64::MAX as u64)")
+    )]
+    pub max_function_calls_per_minute: Option<u64>,
+    /// How many scheduled jobs the component can have in `_scheduled_jobs`,
+    /// including finished jobs that haven't been cleaned up yet.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::option::of(0..=i64::MAX as u64)")
+    )]
+    pub max_scheduled_jobs: Option<u64>,
+}
+
+impl ComponentQuota {
+    pub fn is_unlimited(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ComponentQuotaRecord {
+    pub component_id: DeveloperDocumentId,
+    pub quota: ComponentQuota,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedComponentQuotaRecord {
+    component_id: String,
+    max_documents: Option<i64>,
+    max_document_bytes: Option<i64>,
+    max_function_calls_per_minute: Option<i64>,
+    max_scheduled_jobs: Option<i64>,
+}
+
+impl TryFrom<ComponentQuotaRecord> for SerializedComponentQuotaRecord {
+    type Error = anyhow::Error;
+
+    fn try_from(record: ComponentQuotaRecord) -> anyhow::Result<Self> {
+        let quota = record.quota;
+        Ok(Self {
+            component_id: record.component_id.encode(),
+            max_documents: quota.max_documents.map(i64::try_from).transpose()?,
+            max_document_bytes: quota.max_document_bytes.map(i64::try_from).transpose()?,
+            max_function_calls_per_minute: quota
+                .max_function_calls_per_minute
+                .map(i64::try_from)
+                .transpose()?,
+            max_scheduled_jobs: quota.max_scheduled_jobs.map(i64::try_from).transpose()?,
+        })
+    }
+}
+
+impl TryFrom<SerializedComponentQuotaRecord> for ComponentQuotaRecord {
+    type Error = anyhow::Error;
+
+    fn try_from(record: SerializedComponentQuotaRecord) -> anyhow::Result<Self> {
+        Ok(Self {
+            component_id: DeveloperDocumentId::decode(&record.component_id)?,
+            quota: ComponentQuota {
+                max_documents: record.max_documents.map(u64::try_from).transpose()?,
+                max_document_bytes: record.max_document_bytes.map(u64::try_from).transpose()?,
+                max_function_calls_per_minute: record
+                    .max_function_calls_per_minute
+                    .map(u64::try_from)
+                    .transpose()?,
+                max_scheduled_jobs: record.max_scheduled_jobs.map(u64::try_from).transpose()?,
+            },
+        })
+    }
+}
+
+codegen_convex_serialization!(ComponentQuotaRecord, SerializedComponentQuotaRecord);
+
+fn quota_exceeded(component_id: DeveloperDocumentId, msg: String) -> ErrorMetadata {
+    ErrorMetadata::bad_request(
+        "ComponentQuotaExceeded",
+        format!("Component {component_id} exceeded its quota: {msg}"),
+    )
+}
+
+/// Counts how many functions each component has started in the current
+/// minute. Shared by everything that executes functions in a deployment.
+#[derive(Clone, Default)]
+pub struct ComponentCallCounter {
+    windows: Arc<Mutex<BTreeMap<DeveloperDocumentId, (u64, u64)>>>,
+}
+
+impl ComponentCallCounter {
+    /// Counts a call in the minute `minute`, and returns how many calls the
+    /// component has made in that minute including this one.
+    fn increment(&self, component_id: DeveloperDocumentId, minute: u64) -> u64 {
+        let mut windows = self.windows.lock();
+        let (window_minute, calls) = windows.entry(component_id).or_insert((minute, 0));
+        if *window_minute != minute {
+            *window_minute = minute;
+            *calls = 0;
+        }
+        *calls += 1;
+        *calls
+    }
+}
+
+pub struct ComponentQuotasModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> ComponentQuotasModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    async fn get(
+        &mut self,
+        component_id: DeveloperDocumentId,
+    ) -> anyhow::Result<Option<ParsedDocument<ComponentQuotaRecord>>> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&COMPONENT_QUOTAS_TABLE) {
+            return Ok(None);
+        }
+        let range = vec![IndexRangeExpression::Eq(
+            COMPONENT_ID_FIELD.clone(),
+            ConvexValue::String(component_id.encode().try_into()?).into(),
+        )];
+        let query = Query::index_range(IndexRange {
+            index_name: COMPONENT_QUOTAS_BY_COMPONENT_ID.name(),
+            range,
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParseDocument::<ComponentQuotaRecord>::parse)
+            .transpose()
+    }
+
+    /// The component's quota. The root app doesn't have one.
+    pub async fn quota(&mut self, component: ComponentId) -> anyhow::Result<ComponentQuota> {
+        let ComponentId::Child(component_id) = component else {
+            return Ok(ComponentQuota::default());
+        };
+        Ok(self
+            .get(component_id)
+            .await?
+            .map(|record| record.into_value().quota)
+            .unwrap_or_default())
+    }
+
+    /// Replaces the component's quota. Setting no limits removes it.
+    pub async fn set(
+        &mut self,
+        component_id: DeveloperDocumentId,
+        quota: ComponentQuota,
+    ) -> anyhow::Result<()> {
+        let existing = self.get(component_id).await?;
+        if quota.is_unlimited() {
+            if let Some(existing) = existing {
+                SystemMetadataModel::new_global(self.tx)
+                    .delete(existing.id())
+                    .await?;
+            }
+            return Ok(());
+        }
+        let record = ComponentQuotaRecord {
+            component_id,
+            quota,
+        };
+        match existing {
+            Some(existing) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), record.try_into()?)
+                    .await?;
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&COMPONENT_QUOTAS_TABLE, record.try_into()?)
+                    .await?;
+            },
+        }
+        Ok(())
+    }
+
+    /// The number of documents in the table as of the transaction's snapshot
+    /// plus its own writes, without taking a read dependency on the table.
+    async fn snapshot_count(&mut self, tablet_id: TabletId) -> anyhow::Result<u64> {
+        let count = self
+            .tx
+            .count_snapshot
+            .count(tablet_id)
+            .await?
+            .unwrap_or_default();
+        let delta = self
+            .tx
+            .table_count_deltas
+            .get(&tablet_id)
+            .copied()
+            .unwrap_or_default();
+        Ok(count.saturating_add_signed(delta))
+    }
+
+    /// The number and total size of the documents in the namespace's user
+    /// tables as of the transaction's snapshot plus its own writes, without
+    /// taking a read dependency on them.
+    async fn snapshot_usage(&mut self, namespace: TableNamespace) -> anyhow::Result<(u64, u64)> {
+        let (documents, bytes) = self
+            .tx
+            .count_snapshot
+            .namespace_usage(namespace)
+            .await?
+            .unwrap_or_default();
+        let (documents_delta, bytes_delta) = self
+            .tx
+            .namespace_usage_deltas
+            .get(&namespace)
+            .copied()
+            .unwrap_or_default();
+        Ok((
+            documents.saturating_add_signed(documents_delta),
+            bytes.saturating_add_signed(bytes_delta),
+        ))
+    }
+
+    /// Fails with a `ComponentQuotaExceeded` error if the component's tables
+    /// already hold as many documents or bytes as its quota allows.
+    /// `inserting` is whether the write adds a document.
+    pub async fn check_document_quota(
+        &mut self,
+        namespace: TableNamespace,
+        inserting: bool,
+    ) -> anyhow::Result<()> {
+        let TableNamespace::ByComponent(component_id) = namespace else {
+            return Ok(());
+        };
+        let quota = self.quota(ComponentId::Child(component_id)).await?;
+        if quota.max_documents.is_none() && quota.max_document_bytes.is_none() {
+            return Ok(());
+        }
+        let (documents, bytes) = self.snapshot_usage(namespace).await?;
+        if inserting
+            && let Some(max_documents) = quota.max_documents
+            && documents >= max_documents
+        {
+            anyhow::bail!(quota_exceeded(
+                component_id,
+                format!("its tables can hold at most {max_documents} documents.")
+            ));
+        }
+        if let Some(max_document_bytes) = quota.max_document_bytes
+            && bytes >= max_document_bytes
+        {
+            anyhow::bail!(quota_exceeded(
+                component_id,
+                format!(
+                    "its tables can hold at most {max_document_bytes} bytes of documents. Delete \
+                     documents to free up space."
+                )
+            ));
+        }
+        Ok(())
+    }
+
+    /// Fails with a `ComponentQuotaExceeded` error if the component can't
+    /// schedule another job.
+    pub async fn check_scheduled_jobs_quota(
+        &mut self,
+        namespace: TableNamespace,
+        scheduled_jobs_table: &TableName,
+    ) -> anyhow::Result<()> {
+        let TableNamespace::ByComponent(component_id) = namespace else {
+            return Ok(());
+        };
+        let quota = self.quota(ComponentId::Child(component_id)).await?;
+        let Some(max_scheduled_jobs) = quota.max_scheduled_jobs else {
+            return Ok(());
+        };
+        let Some(tablet_id) = self
+            .tx
+            .table_mapping()
+            .namespace(namespace)
+            .id_if_exists(scheduled_jobs_table)
+        else {
+            return Ok(());
+        };
+        if self.snapshot_count(tablet_id).await? >= max_scheduled_jobs {
+            anyhow::bail!(quota_exceeded(
+                component_id,
+                format!("it can have at most {max_scheduled_jobs} scheduled jobs.")
+            ));
+        }
+        Ok(())
+    }
+
+    /// Counts a call to one of the component's functions, failing with a
+    /// `ComponentQuotaExceeded` error if it has already made as many calls
+    /// this minute as its quota allows.
+    pub async fn check_function_call(
+        &mut self,
+        component: ComponentId,
+        counter: &ComponentCallCounter,
+    ) -> anyhow::Result<()> {
+        let ComponentId::Child(component_id) = component else {
+            return Ok(());
+        };
+        let quota = self.quota(component).await?;
+        let Some(max_calls) = quota.max_function_calls_per_minute else {
+            return Ok(());
+        };
+        let minute = self.tx.runtime().unix_timestamp().as_secs() / 60;
+        if counter.increment(component_id, minute) > max_calls {
+            anyhow::bail!(quota_exceeded(
+                component_id,
+                format!("it can run at most {max_calls} functions per minute.")
+            ));
+        }
+        Ok(())
+    }
+}
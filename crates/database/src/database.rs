@@ -4,6 +4,7 @@ use std::{
         BTreeMap,
         BTreeSet,
     },
+    future::Future,
     ops::Bound,
     sync::{
         atomic::{
@@ -31,6 +32,8 @@ use common::{
         components::ComponentMetadata,
         index::{
             database_index::IndexedFields,
+            vector_index::DeveloperVectorIndexConfig,
+            IndexConfig,
             IndexMetadata,
             TabletIndexMetadata,
             INDEX_TABLE,
@@ -75,7 +78,10 @@ use common::{
         RetentionValidator,
         TimestampRange,
     },
-    query::Order,
+    query::{
+        Order,
+        SearchVersion,
+    },
     runtime::{
         RateLimiter,
         Runtime,
@@ -122,7 +128,10 @@ use indexing::{
         BackendInMemoryIndexes,
         DatabaseIndexSnapshot,
     },
-    index_registry::IndexRegistry,
+    index_registry::{
+        index_not_found_error,
+        IndexRegistry,
+    },
 };
 use itertools::Itertools;
 use keybroker::Identity;
@@ -149,9 +158,13 @@ use value::{
     TableNumber,
 };
 use vector::{
+    InternalVectorSearch,
     PublicVectorSearchQueryResult,
+    QdrantSchema,
     VectorIndexManager,
+    VectorIndexSegmentStats,
     VectorSearch,
+    DEFAULT_VECTOR_LIMIT,
 };
 
 use crate::{
@@ -167,12 +180,19 @@ use crate::{
         bootstrap_system_tables,
         DEFAULT_BOOTSTRAP_TABLE_NUMBERS,
     },
+    document_cache::DocumentCache,
+    hybrid_search::{
+        HybridSearch,
+        PublicHybridSearchQueryResult,
+        HYBRID_SEARCH_CANDIDATES,
+    },
     metrics::{
         self,
         load_indexes_into_memory_timer,
         vector::vector_search_with_retries_timer,
         verify_invariants_timer,
     },
+    query::TableFilter,
     retention::LeaderRetentionManager,
     schema_registry::SchemaRegistry,
     search_index_bootstrap::SearchIndexBootstrapWorker,
@@ -200,6 +220,10 @@ use crate::{
         TextIndexManagerSnapshot,
         TransactionIndex,
     },
+    vector_query_stats::{
+        VectorQueryStats,
+        VectorQueryStatsTracker,
+    },
     write_log::{
         new_write_log,
         LogReader,
@@ -208,9 +232,11 @@ use crate::{
     BootstrapComponentsModel,
     ComponentRegistry,
     FollowerRetentionManager,
+    IndexModel,
     TableIterator,
     Transaction,
     TransactionReadSet,
+    VectorIndexMigrationModel,
     COMPONENTS_TABLE,
     SCHEMAS_TABLE,
 };
@@ -266,6 +292,7 @@ pub struct Database<RT: Runtime> {
     pub searcher: Arc<dyn Searcher>,
     pub search_storage: Arc<OnceLock<Arc<dyn Storage>>>,
     usage_counter: UsageCounter,
+    vector_query_stats: VectorQueryStatsTracker,
     virtual_system_mapping: VirtualSystemMapping,
     pub bootstrap_metadata: BootstrapMetadata,
     // Caches of snapshot TableMapping and by_id index ids, which are used repeatedly by
@@ -281,6 +308,7 @@ pub struct Database<RT: Runtime> {
             )>,
         >,
     >,
+    document_cache: Option<Arc<DocumentCache>>,
 }
 
 #[derive(PartialEq, Eq)]
@@ -843,6 +871,7 @@ impl<RT: Runtime> Database<RT> {
         let component_paths_snapshot_cache =
             AsyncLru::new(runtime.clone(), 10, 2, "component_paths_snapshot");
         let list_snapshot_table_iterator_cache = Arc::new(Mutex::new(None));
+        let document_cache = DocumentCache::from_knobs(log_reader.clone())?.map(Arc::new);
         let database = Self {
             committer,
             subscriptions,
@@ -855,12 +884,14 @@ impl<RT: Runtime> Database<RT> {
             searcher,
             search_storage: Arc::new(OnceLock::new()),
             usage_counter,
+            vector_query_stats: VectorQueryStatsTracker::default(),
             virtual_system_mapping,
             bootstrap_metadata,
             table_mapping_snapshot_cache,
             by_id_indexes_snapshot_cache,
             component_paths_snapshot_cache,
             list_snapshot_table_iterator_cache,
+            document_cache,
         };
 
         Ok(database)
@@ -1478,19 +1509,23 @@ impl<RT: Runtime> Database<RT> {
         let begin_ts = cmp::max(latest_ts.succ()?, self.runtime.generate_timestamp()?);
         let creation_time = CreationTime::try_from(begin_ts)?;
         let id_generator = TransactionIdGenerator::new(&self.runtime)?;
+        let mut index_snapshot = DatabaseIndexSnapshot::new(
+            snapshot.index_registry.clone(),
+            Arc::new(snapshot.in_memory_indexes),
+            snapshot.table_registry.table_mapping().clone(),
+            RepeatablePersistence::new(
+                self.reader.clone(),
+                repeatable_ts,
+                Arc::new(self.retention_manager.clone()),
+            )
+            .read_snapshot(repeatable_ts)?,
+        );
+        if let Some(document_cache) = &self.document_cache {
+            index_snapshot = index_snapshot.with_shared_cache(document_cache.clone());
+        }
         let transaction_index = TransactionIndex::new(
             snapshot.index_registry.clone(),
-            DatabaseIndexSnapshot::new(
-                snapshot.index_registry.clone(),
-                Arc::new(snapshot.in_memory_indexes),
-                snapshot.table_registry.table_mapping().clone(),
-                RepeatablePersistence::new(
-                    self.reader.clone(),
-                    repeatable_ts,
-                    Arc::new(self.retention_manager.clone()),
-                )
-                .read_snapshot(repeatable_ts)?,
-            ),
+            index_snapshot,
             Arc::new(TextIndexManagerSnapshot::new(
                 snapshot.index_registry,
                 snapshot.text_indexes,
@@ -1557,6 +1592,22 @@ impl<RT: Runtime> Database<RT> {
         self.committer.load_indexes_into_memory(tables).await
     }
 
+    /// Shares a document cache between this database's transactions, whatever
+    /// `DOCUMENT_CACHE_SIZE` says.
+    #[cfg(test)]
+    pub fn with_document_cache(
+        mut self,
+        max_size: usize,
+        policy: crate::document_cache::DocumentCachePolicy,
+    ) -> Self {
+        self.document_cache = Some(Arc::new(DocumentCache::new(
+            self.log.clone(),
+            max_size,
+            policy,
+        )));
+        self
+    }
+
     #[cfg(any(test, feature = "testing"))]
     pub async fn bump_max_repeatable_ts(&self) -> anyhow::Result<Timestamp> {
         self.committer.bump_max_repeatable_ts().await
@@ -1566,8 +1617,13 @@ impl<RT: Runtime> Database<RT> {
         self.write_commits_since_load.load(Ordering::SeqCst)
     }
 
-    // TODO: consider making this function non-async
     pub async fn subscribe(&self, token: Token) -> anyhow::Result<Subscription> {
+        // The subscription worker only has the write log in memory, so refresh
+        // the token through anything that's been spilled first.
+        let token = match self.refresh_token(token, self.log.max_ts()).await? {
+            Some(token) => token,
+            None => return Ok(Subscription::invalid()),
+        };
         self.subscriptions.subscribe(token)
     }
 
@@ -1885,14 +1941,22 @@ impl<RT: Runtime> Database<RT> {
 
     /// Attempt to pull a token forward to a given timestamp, returning `None`
     /// if there have been overlapping writes between the token's original
-    /// timestamp and `ts`.
+    /// timestamp and `ts`. Write log entries that were spilled out of memory
+    /// are read back from persistence.
     pub async fn refresh_token(
         &self,
         token: Token,
         ts: Timestamp,
     ) -> anyhow::Result<Option<Token>> {
         let _timer = metrics::refresh_token_timer();
-        self.log.refresh_token(token, ts)
+        let persistence = RepeatablePersistence::new(
+            self.reader.clone(),
+            self.now_ts_for_reads(),
+            self.retention_validator(),
+        );
+        self.log
+            .refresh_token_with_read_back(token, ts, &persistence)
+            .await
     }
 
     pub fn log(&self) -> &LogReader {
@@ -1982,6 +2046,26 @@ impl<RT: Runtime> Database<RT> {
             .is_some()
     }
 
+    /// Whether text and vector indexes have finished bootstrapping, so
+    /// searches can be served.
+    pub fn has_search_and_vector_indexes_bootstrapped(&self) -> bool {
+        let snapshot = self.snapshot_manager.lock().latest_snapshot();
+        !snapshot.text_indexes.is_bootstrapping() && !snapshot.vector_indexes.is_bootstrapping()
+    }
+
+    /// Checks that the committer loop is still processing messages.
+    pub async fn ping_committer(&self) -> anyhow::Result<()> {
+        self.committer.ping().await
+    }
+
+    /// Makes a cheap read from persistence to check that it's reachable.
+    pub async fn ping_persistence(&self) -> anyhow::Result<()> {
+        self.reader
+            .get_persistence_global(PersistenceGlobalKey::MaxRepeatableTimestamp)
+            .await?;
+        Ok(())
+    }
+
     pub async fn get_document_and_index_storage(
         &self,
         identity: Identity,
@@ -2034,12 +2118,90 @@ impl<RT: Runtime> Database<RT> {
         _identity: Identity,
         query: VectorSearch,
     ) -> anyhow::Result<(Vec<PublicVectorSearchQueryResult>, FunctionUsageStats)> {
+        let timer = vector_search_with_retries_timer();
+        let result = self
+            .retry_vector_search(|ts| self.vector_search_at_ts(query.clone(), ts))
+            .await;
+        timer.finish(result.is_ok());
+        result
+    }
+
+    /// Runs a text search and a vector search on the same table at the same
+    /// timestamp, and fuses their rankings into one list of results.
+    pub async fn hybrid_search(
+        &self,
+        identity: Identity,
+        query: HybridSearch,
+    ) -> anyhow::Result<(Vec<PublicHybridSearchQueryResult>, FunctionUsageStats)> {
+        self.retry_vector_search(|ts| self.hybrid_search_at_ts(identity.clone(), query.clone(), ts))
+            .await
+    }
+
+    pub async fn hybrid_search_at_ts(
+        &self,
+        identity: Identity,
+        query: HybridSearch,
+        ts: RepeatableTimestamp,
+    ) -> anyhow::Result<(Vec<PublicHybridSearchQueryResult>, FunctionUsageStats)> {
+        let usage = FunctionUsageTracker::new();
+        let namespace = TableNamespace::from(query.component_id);
+        let mut tx = self
+            .begin_with_repeatable_ts(identity, ts, usage.clone())
+            .await?;
+        let table_mapping = tx.table_mapping().namespace(namespace);
+        if !table_mapping.name_exists(query.text.index_name.table()) {
+            return Ok((vec![], usage.gather_user_stats()));
+        }
+        let table_number = table_mapping
+            .id(query.text.index_name.table())?
+            .table_number;
+        let stable_index_name = IndexModel::new(&mut tx).stable_index_name(
+            namespace,
+            &query.text.index_name,
+            TableFilter::ExcludePrivateSystemTables,
+        )?;
+        if let Err(missing_index_name) = stable_index_name.tablet_index_name_or_missing() {
+            anyhow::bail!(index_not_found_error(missing_index_name));
+        }
+        let text_ranking = tx
+            .search(&stable_index_name, &query.text, SearchVersion::V2, None)
+            .await?
+            .into_iter()
+            .take(HYBRID_SEARCH_CANDIDATES)
+            .map(|(candidate, _)| {
+                (
+                    DeveloperDocumentId::new(table_number, candidate.id),
+                    f64::from(candidate.score),
+                )
+            })
+            .collect();
+        let (vector_results, vector_usage) = self.vector_search_at_ts(query.vector, ts).await?;
+        usage.add(vector_usage);
+        let vector_ranking = vector_results
+            .into_iter()
+            .sorted_by(|a, b| b.cmp(a))
+            .map(|result| (result.id, f64::from(result.score)))
+            .collect();
+        let results = query
+            .fusion
+            .fuse(text_ranking, vector_ranking, query.limit as usize);
+        Ok((results, usage.gather_user_stats()))
+    }
+
+    /// Retries searches that read vector indexes until the backend has loaded
+    /// them into memory.
+    async fn retry_vector_search<T, Fut>(
+        &self,
+        search: impl Fn(RepeatableTimestamp) -> Fut,
+    ) -> anyhow::Result<T>
+    where
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
         let mut last_error = None;
         let mut backoff = Backoff::new(INITIAL_VECTOR_BACKOFF, MAX_VECTOR_BACKOFF);
-        let timer = vector_search_with_retries_timer();
         while backoff.failures() < MAX_VECTOR_ATTEMPTS {
             let ts = self.now_ts_for_reads();
-            match self.vector_search_at_ts(query.clone(), ts).await {
+            match search(ts).await {
                 Err(e) => {
                     // If backend hasn't loaded the in-memory index yet, it returns
                     // overloaded. We want to retry those.
@@ -2056,28 +2218,33 @@ impl<RT: Runtime> Database<RT> {
                         self.runtime.wait(delay).await;
                         continue;
                     } else {
-                        timer.finish(false);
                         return Err(e);
                     }
                 },
                 Ok(result) => {
-                    timer.finish(true);
                     return Ok(result);
                 },
             }
         }
         let last_error = last_error.expect("Exited vector_search() loop without any failure");
-        timer.finish(false);
         Err(last_error)
     }
 
     pub async fn vector_search_at_ts(
         &self,
-        query: VectorSearch,
+        mut query: VectorSearch,
         ts: RepeatableTimestamp,
     ) -> anyhow::Result<(Vec<PublicVectorSearchQueryResult>, FunctionUsageStats)> {
         let timer = metrics::vector::vector_search_timer();
         let usage = FunctionUsageTracker::new();
+        // Re-ranking by MMR fetches more nearest neighbors than the query returns.
+        let limit = query.limit.unwrap_or(DEFAULT_VECTOR_LIMIT);
+        let mmr = query.mmr.take();
+        if let Some(ref mmr) = mmr {
+            query.limit = Some(mmr.fetch_limit(limit)?);
+        }
+        let include_vectors = query.include_vectors;
+        let query_vector = query.vector.clone();
         let snapshot = self.snapshot(ts)?;
         let component_id = query.component_id;
         let table_mapping = snapshot
@@ -2087,16 +2254,37 @@ impl<RT: Runtime> Database<RT> {
             return Ok((vec![], usage.gather_user_stats()));
         }
         let table_number = table_mapping.id(query.index_name.table())?.table_number;
-        let index_name = query
+        let mut index_name = query
             .index_name
             .clone()
             .to_resolved(table_mapping.name_to_tablet())?;
-        let index = snapshot
+        let mut index = snapshot
             .index_registry
             .require_enabled(&index_name, &query.index_name)?;
+        // Searches against the source index of a re-embedding migration that
+        // has cut over run against its target index instead.
+        let mut tx = self
+            .begin_with_repeatable_ts(Identity::system(), ts, usage.clone())
+            .await?;
+        let search_index_id = VectorIndexMigrationModel::new(&mut tx)
+            .search_index_id(index.id().internal_id())
+            .await?;
+        if search_index_id != index.id().internal_id()
+            && let Some(target) = snapshot
+                .index_registry
+                .enabled_index_by_index_id(&search_index_id)
+        {
+            query.index_name = IndexName::new(
+                query.index_name.table().clone(),
+                target.name.descriptor().clone(),
+            )?;
+            index_name = target.name.clone();
+            index = target.clone();
+        }
         let resolved: vector::InternalVectorSearch = query.resolve(&table_mapping)?;
         let search_storage = self.search_storage();
-        let results: Vec<_> = snapshot
+        let search_start = self.runtime.monotonic_now();
+        let mut results: Vec<_> = snapshot
             .vector_indexes
             .vector_search(
                 &index,
@@ -2108,6 +2296,50 @@ impl<RT: Runtime> Database<RT> {
             .into_iter()
             .map(|r| r.to_public(table_number))
             .collect();
+        self.vector_query_stats
+            .record(index.id(), self.runtime.monotonic_now() - search_start);
+        if include_vectors || mmr.is_some() {
+            let IndexConfig::Vector {
+                ref developer_config,
+                ..
+            } = index.config
+            else {
+                anyhow::bail!("{index_name:?} isn't a vector index");
+            };
+            results = Self::load_result_vectors(
+                &mut tx,
+                *index_name.table(),
+                developer_config,
+                &query_vector,
+                results,
+            )
+            .await?;
+            if let Some(mmr) = mmr {
+                let order = {
+                    let vectors: Vec<&[f32]> = results
+                        .iter()
+                        .map(|r| r.vector.as_deref().unwrap_or_default())
+                        .collect();
+                    vector::maximal_marginal_relevance(
+                        &query_vector,
+                        &vectors,
+                        mmr.lambda,
+                        limit as usize,
+                    )
+                };
+                let mut results_by_index: Vec<_> = results.into_iter().map(Some).collect();
+                results = order
+                    .into_iter()
+                    .filter_map(|i| results_by_index[i].take())
+                    .collect();
+            }
+            if !include_vectors {
+                for result in &mut results {
+                    result.vector = None;
+                    result.distance = None;
+                }
+            }
+        }
         let size: u64 = results.iter().map(|row| row.size() as u64).sum();
         let component_path = snapshot
             .component_registry
@@ -2123,6 +2355,129 @@ impl<RT: Runtime> Database<RT> {
         Ok((results, usage.gather_user_stats()))
     }
 
+    /// Returns the searches against each vector index since this was last
+    /// called.
+    pub fn take_vector_query_stats(&self) -> BTreeMap<IndexId, VectorQueryStats> {
+        self.vector_query_stats.take()
+    }
+
+    /// The segments of each enabled vector index that has finished
+    /// backfilling, as of the latest snapshot. Empty until the backend has
+    /// loaded its vector indexes.
+    pub fn vector_index_segment_stats(
+        &self,
+    ) -> anyhow::Result<BTreeMap<IndexId, VectorIndexSegmentStats>> {
+        let snapshot = self.latest_snapshot()?;
+        let mut stats = BTreeMap::new();
+        if snapshot.vector_indexes.is_bootstrapping() {
+            return Ok(stats);
+        }
+        for metadata in snapshot.index_registry.all_vector_indexes() {
+            let Some(index) = snapshot
+                .index_registry
+                .enabled_index_by_index_id(&metadata.id().internal_id())
+            else {
+                continue;
+            };
+            if let Some(index_stats) = snapshot.vector_indexes.segment_stats(index)? {
+                stats.insert(index.id(), index_stats);
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Measures a vector index's recall: the fraction of the exact `limit`
+    /// nearest neighbors of each sample vector that the index's approximate
+    /// search finds. Returns `None` if the index isn't an enabled vector index
+    /// or no sample has any neighbors.
+    pub async fn vector_index_recall(
+        &self,
+        index_id: IndexId,
+        samples: Vec<Vec<f32>>,
+        limit: u32,
+    ) -> anyhow::Result<Option<f64>> {
+        let snapshot = self.latest_snapshot()?;
+        let Some(index) = snapshot.index_registry.enabled_index_by_index_id(&index_id) else {
+            return Ok(None);
+        };
+        let original_table_name = snapshot
+            .table_mapping()
+            .tablet_name(*index.name().table())?;
+        let search_storage = self.search_storage();
+        let mut expected = 0;
+        let mut found = 0;
+        for vector in samples {
+            let search = |exact| InternalVectorSearch {
+                index_name: index.name(),
+                limit: Some(limit),
+                ef_search: None,
+                vector: vector.clone(),
+                expressions: vec![],
+                original_table_name: original_table_name.clone(),
+                exact,
+            };
+            let approximate: BTreeSet<_> = snapshot
+                .vector_indexes
+                .vector_search(
+                    index,
+                    search(false),
+                    self.searcher.clone(),
+                    search_storage.clone(),
+                )
+                .await?
+                .into_iter()
+                .map(|result| result.id)
+                .collect();
+            let exact = snapshot
+                .vector_indexes
+                .vector_search(
+                    index,
+                    search(true),
+                    self.searcher.clone(),
+                    search_storage.clone(),
+                )
+                .await?;
+            expected += exact.len();
+            found += exact
+                .iter()
+                .filter(|result| approximate.contains(&result.id))
+                .count();
+        }
+        Ok((expected > 0).then(|| found as f64 / expected as f64))
+    }
+
+    /// Reads each result's document to fill in its stored vector and exact
+    /// distance from `query_vector`. Results whose document no longer has a
+    /// vector are dropped.
+    async fn load_result_vectors(
+        tx: &mut Transaction<RT>,
+        tablet_id: TabletId,
+        config: &DeveloperVectorIndexConfig,
+        query_vector: &[f32],
+        results: Vec<PublicVectorSearchQueryResult>,
+    ) -> anyhow::Result<Vec<PublicVectorSearchQueryResult>> {
+        let schema = QdrantSchema::new(config);
+        let mut loaded = Vec::with_capacity(results.len());
+        for mut result in results {
+            let id = ResolvedDocumentId::new(tablet_id, result.id);
+            let Some(document) = tx.get(id).await? else {
+                continue;
+            };
+            let Some(qdrant_document) = schema.index(&document) else {
+                continue;
+            };
+            let vector = Vec::from(qdrant_document.vector);
+            result.distance = Some(vector::vector_distance(
+                config.distance_metric,
+                query_vector,
+                &vector,
+            ));
+            result.vector = Some(vector);
+            loaded.push(result);
+        }
+        Ok(loaded)
+    }
+
     pub async fn search_with_compiled_query(
         &self,
         index_id: IndexId,
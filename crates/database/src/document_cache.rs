@@ -0,0 +1,392 @@
+//! A size-bounded cache of recently read documents and index ranges, shared
+//! by every transaction on this backend. Without it, a read that misses the
+//! in-memory indexes and the transaction's own cache always goes to
+//! persistence, even if the same document was read a moment ago.
+//!
+//! Each entry holds every document in an index interval as of the timestamp
+//! it was read at, along with a [`Token`] for that read. An entry is served at
+//! a later timestamp only after the write log confirms nothing was written to
+//! the interval in between, the same way subscriptions are refreshed, so the
+//! cache never needs to be invalidated by the committer.
+
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
+    str::FromStr,
+    sync::Arc,
+};
+
+use common::{
+    bootstrap_model::index::IndexConfig,
+    document::PackedDocument,
+    index::IndexKeyBytes,
+    interval::Interval,
+    knobs::{
+        DOCUMENT_CACHE_POLICY,
+        DOCUMENT_CACHE_SIZE,
+    },
+    types::{
+        IndexId,
+        RepeatableTimestamp,
+        Timestamp,
+    },
+};
+use indexing::{
+    backend_in_memory_indexes::SharedIndexCache,
+    index_registry::Index,
+};
+use parking_lot::Mutex;
+use value::{
+    heap_size::HeapSize,
+    TableName,
+};
+
+use crate::{
+    metrics::{
+        log_document_cache_eviction,
+        log_document_cache_query,
+        log_document_cache_size,
+    },
+    write_log::LogReader,
+    Token,
+    TransactionReadSet,
+};
+
+/// Which entry to evict when the cache is full.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DocumentCachePolicy {
+    /// Evict the entry read least recently.
+    Lru,
+    /// Evict the entry read least often, breaking ties by recency.
+    Lfu,
+}
+
+impl FromStr for DocumentCachePolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "lru" => Ok(Self::Lru),
+            "lfu" => Ok(Self::Lfu),
+            _ => anyhow::bail!("Unknown document cache policy {s:?}, expected `lru` or `lfu`"),
+        }
+    }
+}
+
+type CachedDocuments = Arc<Vec<(IndexKeyBytes, Timestamp, PackedDocument)>>;
+
+struct CacheEntry {
+    key: (IndexId, Interval),
+    /// The read of `key`'s interval, at the latest timestamp the documents
+    /// are known to be current at.
+    token: Token,
+    documents: CachedDocuments,
+    size: usize,
+    last_used: u64,
+    num_uses: u64,
+}
+
+impl CacheEntry {
+    fn rank(&self, policy: DocumentCachePolicy) -> (u64, u64) {
+        match policy {
+            DocumentCachePolicy::Lru => (self.last_used, 0),
+            DocumentCachePolicy::Lfu => (self.num_uses, self.last_used),
+        }
+    }
+}
+
+struct Inner {
+    policy: DocumentCachePolicy,
+    max_size: usize,
+    size: usize,
+    next_entry_id: u64,
+    clock: u64,
+    entry_ids: BTreeMap<(IndexId, Interval), u64>,
+    entries: BTreeMap<u64, CacheEntry>,
+    /// Entry IDs ordered by eviction priority, lowest first.
+    eviction_order: BTreeSet<((u64, u64), u64)>,
+}
+
+impl Inner {
+    fn new(max_size: usize, policy: DocumentCachePolicy) -> Self {
+        Self {
+            policy,
+            max_size,
+            size: 0,
+            next_entry_id: 0,
+            clock: 0,
+            entry_ids: BTreeMap::new(),
+            entries: BTreeMap::new(),
+            eviction_order: BTreeSet::new(),
+        }
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn remove(&mut self, entry_id: u64) -> Option<CacheEntry> {
+        let entry = self.entries.remove(&entry_id)?;
+        self.entry_ids.remove(&entry.key);
+        self.eviction_order
+            .remove(&(entry.rank(self.policy), entry_id));
+        self.size -= entry.size;
+        Some(entry)
+    }
+
+    fn insert(&mut self, entry: CacheEntry) {
+        if let Some(existing) = self.entry_ids.get(&entry.key).copied() {
+            self.remove(existing);
+        }
+        let entry_id = self.next_entry_id;
+        self.next_entry_id += 1;
+        self.size += entry.size;
+        self.entry_ids.insert(entry.key.clone(), entry_id);
+        self.eviction_order
+            .insert((entry.rank(self.policy), entry_id));
+        self.entries.insert(entry_id, entry);
+        // Never evict the new entry, which would otherwise always go first
+        // under LFU.
+        while self.size > self.max_size {
+            let Some(evicted) = self
+                .eviction_order
+                .iter()
+                .map(|(_, id)| *id)
+                .find(|id| *id != entry_id)
+            else {
+                break;
+            };
+            self.remove(evicted);
+            log_document_cache_eviction("size");
+        }
+    }
+
+    /// Records a read of the entry, advancing its token if `token` is newer.
+    fn touch(&mut self, entry_id: u64, token: Token) {
+        let now = self.tick();
+        let Some(entry) = self.entries.get_mut(&entry_id) else {
+            return;
+        };
+        self.eviction_order
+            .remove(&(entry.rank(self.policy), entry_id));
+        entry.last_used = now;
+        entry.num_uses += 1;
+        if token.ts() > entry.token.ts() {
+            entry.token = token;
+        }
+        self.eviction_order
+            .insert((entry.rank(self.policy), entry_id));
+    }
+}
+
+pub struct DocumentCache {
+    log: LogReader,
+    inner: Mutex<Inner>,
+}
+
+impl DocumentCache {
+    /// Returns `None` if the cache is disabled by `DOCUMENT_CACHE_SIZE`.
+    pub fn from_knobs(log: LogReader) -> anyhow::Result<Option<Self>> {
+        if *DOCUMENT_CACHE_SIZE == 0 {
+            return Ok(None);
+        }
+        let policy = DOCUMENT_CACHE_POLICY.parse()?;
+        Ok(Some(Self::new(log, *DOCUMENT_CACHE_SIZE, policy)))
+    }
+
+    pub fn new(log: LogReader, max_size: usize, policy: DocumentCachePolicy) -> Self {
+        Self {
+            log,
+            inner: Mutex::new(Inner::new(max_size, policy)),
+        }
+    }
+
+    fn lookup(
+        &self,
+        index_id: IndexId,
+        interval: &Interval,
+        ts: Timestamp,
+    ) -> Option<CachedDocuments> {
+        let (entry_id, token, documents) = {
+            let inner = self.inner.lock();
+            let entry_id = *inner.entry_ids.get(&(index_id, interval.clone()))?;
+            let entry = &inner.entries[&entry_id];
+            (entry_id, entry.token.clone(), entry.documents.clone())
+        };
+        // Documents deleted before the entry's timestamp aren't in the entry,
+        // so it can't be used to read at an earlier timestamp.
+        if ts < token.ts() {
+            return None;
+        }
+        // Checking the write log can take a while, so don't hold the lock.
+        let token = match self.log.refresh_token(token, ts) {
+            Ok(Some(token)) => token,
+            Ok(None) => {
+                if self.inner.lock().remove(entry_id).is_some() {
+                    log_document_cache_eviction("stale");
+                }
+                return None;
+            },
+            Err(e) => {
+                tracing::warn!("Failed to refresh document cache entry: {e:#}");
+                return None;
+            },
+        };
+        self.inner.lock().touch(entry_id, token);
+        Some(documents)
+    }
+
+    fn report_size(&self) {
+        let (size, num_entries) = {
+            let inner = self.inner.lock();
+            (inner.size, inner.entries.len())
+        };
+        log_document_cache_size(size, num_entries);
+    }
+}
+
+impl SharedIndexCache for DocumentCache {
+    fn get(
+        &self,
+        index_id: IndexId,
+        interval: &Interval,
+        ts: RepeatableTimestamp,
+        table_name: &TableName,
+    ) -> Option<CachedDocuments> {
+        let result = self.lookup(index_id, interval, *ts);
+        log_document_cache_query(table_name, result.is_some());
+        result
+    }
+
+    fn populate(
+        &self,
+        index: &Index,
+        interval: Interval,
+        ts: RepeatableTimestamp,
+        _table_name: &TableName,
+        documents: Vec<(IndexKeyBytes, Timestamp, PackedDocument)>,
+    ) {
+        let IndexConfig::Database {
+            developer_config, ..
+        } = &index.metadata().config
+        else {
+            return;
+        };
+        let size = interval.start.heap_size()
+            + interval.end.heap_size()
+            + documents
+                .iter()
+                .map(|(key, _, document)| key.len() + document.heap_size())
+                .sum::<usize>();
+        let key = (index.id(), interval);
+        {
+            let mut inner = self.inner.lock();
+            if size > inner.max_size / 2 {
+                return;
+            }
+            // Don't replace an entry that's already current at a later
+            // timestamp.
+            if let Some(existing) = inner.entry_ids.get(&key)
+                && inner.entries[existing].token.ts() >= *ts
+            {
+                return;
+            }
+            let mut reads = TransactionReadSet::new();
+            reads.record_indexed_derived(
+                index.name(),
+                developer_config.fields.clone(),
+                key.1.clone(),
+            );
+            let now = inner.tick();
+            inner.insert(CacheEntry {
+                key,
+                token: Token::new(Arc::new(reads.into_read_set()), *ts),
+                documents: Arc::new(documents),
+                size,
+                last_used: now,
+                num_uses: 0,
+            });
+        }
+        self.report_size();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use common::{
+        interval::Interval,
+        types::{
+            IndexId,
+            Timestamp,
+        },
+    };
+
+    use super::{
+        CacheEntry,
+        DocumentCachePolicy,
+        Inner,
+    };
+    use crate::Token;
+
+    fn entry(inner: &mut Inner, key: u8) -> CacheEntry {
+        CacheEntry {
+            key: (IndexId::MIN, Interval::prefix(vec![key].into())),
+            token: Token::empty(Timestamp::MIN),
+            documents: Arc::new(vec![]),
+            size: 10,
+            last_used: inner.tick(),
+            num_uses: 0,
+        }
+    }
+
+    fn cached_keys(inner: &Inner) -> Vec<u8> {
+        inner
+            .entries
+            .values()
+            .map(|entry| entry.key.1.start.0[0])
+            .collect()
+    }
+
+    /// Fills a cache with room for two entries, reading the first entry twice
+    /// and then the second once, and then adds a third.
+    fn fill(policy: DocumentCachePolicy) -> Inner {
+        let mut inner = Inner::new(20, policy);
+        for key in [1, 2] {
+            let entry = entry(&mut inner, key);
+            inner.insert(entry);
+        }
+        let ids: Vec<_> = inner.entries.keys().copied().collect();
+        for id in [ids[0], ids[0], ids[1]] {
+            inner.touch(id, Token::empty(Timestamp::MIN));
+        }
+        let entry = entry(&mut inner, 3);
+        inner.insert(entry);
+        assert_eq!(inner.size, 20);
+        inner
+    }
+
+    #[test]
+    fn test_eviction_policies() {
+        assert_eq!(cached_keys(&fill(DocumentCachePolicy::Lru)), vec![2, 3]);
+        assert_eq!(cached_keys(&fill(DocumentCachePolicy::Lfu)), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_parse_policy() -> anyhow::Result<()> {
+        assert_eq!(
+            "lru".parse::<DocumentCachePolicy>()?,
+            DocumentCachePolicy::Lru
+        );
+        assert_eq!(
+            "LFU".parse::<DocumentCachePolicy>()?,
+            DocumentCachePolicy::Lfu
+        );
+        assert!("fifo".parse::<DocumentCachePolicy>().is_err());
+        Ok(())
+    }
+}
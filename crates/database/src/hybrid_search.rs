@@ -0,0 +1,399 @@
+use std::{
+    cmp,
+    collections::BTreeMap,
+};
+
+use common::{
+    components::ComponentId,
+    query::{
+        Search,
+        SearchFilterExpression,
+    },
+};
+use errors::ErrorMetadata;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+use value::{
+    id_v6::DeveloperDocumentId,
+    Size,
+};
+use vector::{
+    VectorSearch,
+    MAX_VECTOR_RESULTS,
+};
+
+/// The number of top results each of the text and vector searches contributes
+/// to the fused ranking.
+pub const HYBRID_SEARCH_CANDIDATES: usize = MAX_VECTOR_RESULTS;
+
+const DEFAULT_HYBRID_SEARCH_LIMIT: u32 = 10;
+
+/// The `k` constant of reciprocal rank fusion, from the original paper. Larger
+/// values flatten the difference between the top ranks.
+const DEFAULT_RRF_K: f64 = 60.0;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HybridSearchRequest {
+    pub query: JsonValue,
+}
+
+/// A text search and a vector search against indexes on the same table, run
+/// at the same timestamp with their rankings fused into one list of results.
+#[derive(Clone, Debug)]
+pub struct HybridSearch {
+    pub component_id: ComponentId,
+    pub text: Search,
+    pub vector: VectorSearch,
+    pub limit: u32,
+    pub fusion: RankFusion,
+}
+
+/// How to combine the text and vector rankings of a [`HybridSearch`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RankFusion {
+    /// Scores each result by the sum of `1 / (k + rank)` over the rankings it
+    /// appears in, ignoring the searches' own scores.
+    ReciprocalRank { k: f64 },
+    /// Scales each search's scores to between 0 and 1 and adds them up with
+    /// the given weights. Results missing from a ranking score 0 in it.
+    WeightedScore {
+        text_weight: f64,
+        vector_weight: f64,
+    },
+}
+
+impl Default for RankFusion {
+    fn default() -> Self {
+        Self::ReciprocalRank { k: DEFAULT_RRF_K }
+    }
+}
+
+impl RankFusion {
+    /// Fuses two rankings of `(id, score)`, each ordered from the best result,
+    /// into the `limit` best results, with each document appearing once.
+    pub fn fuse(
+        &self,
+        text_ranking: Vec<(DeveloperDocumentId, f64)>,
+        vector_ranking: Vec<(DeveloperDocumentId, f64)>,
+        limit: usize,
+    ) -> Vec<PublicHybridSearchQueryResult> {
+        let mut scores: BTreeMap<DeveloperDocumentId, f64> = BTreeMap::new();
+        match *self {
+            Self::ReciprocalRank { k } => {
+                for ranking in [text_ranking, vector_ranking] {
+                    for (rank, (id, _)) in ranking.into_iter().enumerate() {
+                        *scores.entry(id).or_default() += 1.0 / (k + (rank + 1) as f64);
+                    }
+                }
+            },
+            Self::WeightedScore {
+                text_weight,
+                vector_weight,
+            } => {
+                for (ranking, weight) in
+                    [(text_ranking, text_weight), (vector_ranking, vector_weight)]
+                {
+                    let (min, max) = ranking.iter().fold(
+                        (f64::INFINITY, f64::NEG_INFINITY),
+                        |(min, max), (_, score)| (min.min(*score), max.max(*score)),
+                    );
+                    for (id, score) in ranking {
+                        let normalized = if max > min {
+                            (score - min) / (max - min)
+                        } else {
+                            1.0
+                        };
+                        *scores.entry(id).or_default() += weight * normalized;
+                    }
+                }
+            },
+        }
+        let mut results: Vec<_> = scores
+            .into_iter()
+            .map(|(id, score)| PublicHybridSearchQueryResult { id, score })
+            .collect();
+        results.sort_by(|a, b| b.cmp(a));
+        results.truncate(limit);
+        results
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HybridSearchJson {
+    component_id: Option<String>,
+    text: JsonValue,
+    vector: JsonValue,
+    limit: Option<u32>,
+    fusion: Option<RankFusionJson>,
+}
+
+impl HybridSearchJson {
+    /// Inject the component_id into the [HybridSearchJson], like
+    /// [`vector::VectorSearchJson::insert_component_id`].
+    pub fn insert_component_id(&mut self, component_id: ComponentId) {
+        self.component_id = component_id.serialize_to_string();
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum RankFusionJson {
+    Rrf {
+        k: Option<f64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Weighted {
+        text_weight: Option<f64>,
+        vector_weight: Option<f64>,
+    },
+}
+
+fn invalid_hybrid_search(message: String) -> anyhow::Error {
+    ErrorMetadata::bad_request("InvalidHybridSearch", message).into()
+}
+
+impl TryFrom<RankFusionJson> for RankFusion {
+    type Error = anyhow::Error;
+
+    fn try_from(value: RankFusionJson) -> Result<Self, Self::Error> {
+        match value {
+            RankFusionJson::Rrf { k } => {
+                let k = k.unwrap_or(DEFAULT_RRF_K);
+                if !k.is_finite() || k <= 0.0 {
+                    return Err(invalid_hybrid_search(format!(
+                        "fusion.k must be a positive number, got {k}"
+                    )));
+                }
+                Ok(Self::ReciprocalRank { k })
+            },
+            RankFusionJson::Weighted {
+                text_weight,
+                vector_weight,
+            } => {
+                let text_weight = text_weight.unwrap_or(0.5);
+                let vector_weight = vector_weight.unwrap_or(0.5);
+                for weight in [text_weight, vector_weight] {
+                    if !weight.is_finite() || weight < 0.0 {
+                        return Err(invalid_hybrid_search(format!(
+                            "Fusion weights must be non-negative numbers, got {weight}"
+                        )));
+                    }
+                }
+                if text_weight == 0.0 && vector_weight == 0.0 {
+                    return Err(invalid_hybrid_search(
+                        "At least one fusion weight must be positive".to_string(),
+                    ));
+                }
+                Ok(Self::WeightedScore {
+                    text_weight,
+                    vector_weight,
+                })
+            },
+        }
+    }
+}
+
+impl TryFrom<JsonValue> for HybridSearch {
+    type Error = anyhow::Error;
+
+    fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
+        let json: HybridSearchJson = serde_json::from_value(value)?;
+        let component_id = ComponentId::deserialize_from_string(json.component_id.as_deref())?;
+        let text = Search::try_from(json.text)?;
+        let mut vector = VectorSearch::try_from(json.vector)?;
+        vector.component_id = component_id;
+        vector.limit = Some(HYBRID_SEARCH_CANDIDATES as u32);
+        if vector.include_vectors || vector.mmr.is_some() {
+            return Err(invalid_hybrid_search(
+                "Hybrid searches don't support the includeVectors or mmr vector search options"
+                    .to_string(),
+            ));
+        }
+        if text.index_name.table() != vector.index_name.table() {
+            return Err(invalid_hybrid_search(format!(
+                "The text index {} and the vector index {} must be on the same table",
+                text.index_name, vector.index_name
+            )));
+        }
+        for filter in &text.filters {
+            if let SearchFilterExpression::Search(_, _, options) = filter
+                && (options.highlight.is_some()
+                    || options.facets.is_some()
+                    || options.score.is_some())
+            {
+                return Err(invalid_hybrid_search(
+                    "Hybrid searches don't support the highlight, facets or score search options"
+                        .to_string(),
+                ));
+            }
+        }
+        let limit = json.limit.unwrap_or(DEFAULT_HYBRID_SEARCH_LIMIT);
+        if limit == 0 || limit as usize > MAX_VECTOR_RESULTS {
+            return Err(invalid_hybrid_search(format!(
+                "limit must be between 1 and {MAX_VECTOR_RESULTS}, got {limit}"
+            )));
+        }
+        let fusion = json
+            .fusion
+            .map(RankFusion::try_from)
+            .transpose()?
+            .unwrap_or_default();
+        Ok(Self {
+            component_id,
+            text,
+            vector,
+            limit,
+            fusion,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PublicHybridSearchQueryResult {
+    pub score: f64,
+    pub id: DeveloperDocumentId,
+}
+
+impl Size for PublicHybridSearchQueryResult {
+    fn size(&self) -> usize {
+        self.id.size() + std::mem::size_of::<f64>()
+    }
+
+    fn nesting(&self) -> usize {
+        0
+    }
+}
+
+impl From<PublicHybridSearchQueryResult> for JsonValue {
+    fn from(value: PublicHybridSearchQueryResult) -> Self {
+        json!({
+            "_id": String::from(value.id),
+            "_score": value.score,
+        })
+    }
+}
+
+impl Eq for PublicHybridSearchQueryResult {}
+
+impl PartialEq for PublicHybridSearchQueryResult {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id && self.score.total_cmp(&other.score).is_eq()
+    }
+}
+
+impl Ord for PublicHybridSearchQueryResult {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.score
+            .total_cmp(&other.score)
+            .then(self.id.cmp(&other.id))
+    }
+}
+
+impl PartialOrd for PublicHybridSearchQueryResult {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use value::{
+        id_v6::DeveloperDocumentId,
+        InternalId,
+        TableNumber,
+    };
+
+    use super::{
+        HybridSearch,
+        RankFusion,
+    };
+
+    fn id(i: u8) -> DeveloperDocumentId {
+        DeveloperDocumentId::new(
+            TableNumber::try_from(1000).unwrap(),
+            InternalId::from([i; 16]),
+        )
+    }
+
+    #[test]
+    fn test_reciprocal_rank_fusion_prefers_results_in_both_rankings() {
+        let fusion = RankFusion::ReciprocalRank { k: 60.0 };
+        let results = fusion.fuse(
+            vec![(id(1), 9.0), (id(2), 5.0)],
+            vec![(id(3), 0.9), (id(2), 0.8), (id(4), 0.1)],
+            10,
+        );
+        let ids: Vec<_> = results.iter().map(|r| r.id).collect();
+        assert_eq!(ids[0], id(2));
+        assert_eq!(ids.len(), 4);
+        assert_eq!(results[0].score, 1.0 / 62.0 + 1.0 / 62.0);
+
+        let results = fusion.fuse(vec![(id(1), 9.0), (id(2), 5.0)], vec![], 1);
+        assert_eq!(
+            results.iter().map(|r| r.id).collect::<Vec<_>>(),
+            vec![id(1)]
+        );
+    }
+
+    #[test]
+    fn test_weighted_score_fusion_normalizes_scores() {
+        let fusion = RankFusion::WeightedScore {
+            text_weight: 0.25,
+            vector_weight: 0.75,
+        };
+        // BM25 scores are on a different scale from vector similarities, so
+        // only their positions between the best and worst result count.
+        let results = fusion.fuse(
+            vec![(id(1), 100.0), (id(2), 50.0), (id(3), 0.0)],
+            vec![(id(3), 0.8), (id(1), 0.5), (id(2), 0.2)],
+            10,
+        );
+        let scores: Vec<_> = results.iter().map(|r| (r.id, r.score)).collect();
+        assert_eq!(
+            scores,
+            vec![
+                (id(3), 0.75),
+                (id(1), 0.25 + 0.75 * 0.5),
+                (id(2), 0.25 * 0.5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hybrid_search_requires_same_table() -> anyhow::Result<()> {
+        let query = |vector_index: &str| {
+            json!({
+                "text": {
+                    "indexName": "messages.search_body",
+                    "filters": [{ "type": "Search", "fieldPath": "body", "value": "hello" }],
+                },
+                "vector": { "indexName": vector_index, "vector": [1.0, 2.0] },
+                "fusion": { "type": "weighted", "textWeight": 1 },
+            })
+        };
+        let search = HybridSearch::try_from(query("messages.by_embedding"))?;
+        assert_eq!(
+            search.fusion,
+            RankFusion::WeightedScore {
+                text_weight: 1.0,
+                vector_weight: 0.5
+            }
+        );
+        assert_eq!(search.limit, 10);
+        let err = HybridSearch::try_from(query("users.by_embedding")).unwrap_err();
+        assert!(
+            format!("{err}").contains("must be on the same table"),
+            "{err}"
+        );
+        Ok(())
+    }
+}
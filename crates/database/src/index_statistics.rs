@@ -0,0 +1,470 @@
+//! Statistics about the contents of database indexes, which let queries
+//! estimate how many documents an index range has and how many distinct values
+//! an indexed field has without scanning the index. These live in the database
+//! crate rather than the model crate because UDFs read them while executing.
+//!
+//! Each index's statistics hold an evenly spaced sample of its keys and a
+//! HyperLogLog sketch of each indexed field's values. The index statistics
+//! worker recomputes them by scanning the index in the background, so they can
+//! lag behind writes. Range estimates scale the fraction of sampled keys in
+//! the range by the table's current count, which is always up to date.
+
+use std::{
+    collections::BTreeMap,
+    str::FromStr,
+    sync::LazyLock,
+};
+
+use anyhow::Context;
+use common::{
+    bootstrap_model::index::database_index::IndexedFields,
+    document::{
+        ParseDocument,
+        ParsedDocument,
+    },
+    hyperloglog::HyperLogLog,
+    knobs::INDEX_STATISTICS_SAMPLES,
+    persistence::LatestDocument,
+    query::{
+        FullTableScan,
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+        QueryOperator,
+        QuerySource,
+    },
+    runtime::Runtime,
+    types::{
+        IndexId,
+        IndexName,
+        RepeatableTimestamp,
+    },
+};
+use errors::ErrorMetadata;
+use futures::{
+    pin_mut,
+    TryStreamExt,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_bytes::ByteBuf;
+use value::{
+    codegen_convex_serialization,
+    sorting::values_to_bytes,
+    ConvexValue,
+    FieldPath,
+    InternalId,
+    TableName,
+    TableNamespace,
+    TabletId,
+};
+
+use crate::{
+    query::TableFilter,
+    system_tables::{
+        SystemIndex,
+        SystemTable,
+    },
+    table_summary::table_summary_bootstrapping_error,
+    Database,
+    IndexModel,
+    ResolvedQuery,
+    SystemMetadataModel,
+    TableModel,
+    Transaction,
+};
+
+/// Longest prefix of an index key to keep as a sample. Truncating long keys
+/// bounds the size of the statistics at the cost of slightly less accurate
+/// estimates for ranges that split keys sharing the prefix.
+const MAX_SAMPLE_KEY_BYTES: usize = 1024;
+
+pub static INDEX_STATISTICS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_index_statistics"
+        .parse()
+        .expect("Invalid built-in table name")
+});
+
+static INDEX_ID_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "indexId".parse().expect("Invalid built-in field"));
+
+pub static INDEX_STATISTICS_BY_INDEX_ID: LazyLock<SystemIndex<IndexStatisticsTable>> =
+    LazyLock::new(|| SystemIndex::new("by_index_id", [&INDEX_ID_FIELD]).unwrap());
+
+pub struct IndexStatisticsTable;
+
+impl SystemTable for IndexStatisticsTable {
+    type Metadata = IndexStatistics;
+
+    fn table_name() -> &'static TableName {
+        &INDEX_STATISTICS_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![INDEX_STATISTICS_BY_INDEX_ID.clone()]
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct IndexStatistics {
+    pub index_id: IndexId,
+    /// How many documents the index had when the statistics were computed.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=i64::MAX as u64")
+    )]
+    pub count: u64,
+    /// Index keys spaced evenly through the index, in index order.
+    pub samples: Vec<Vec<u8>>,
+    /// A sketch of the distinct values of each indexed field.
+    pub distinct: BTreeMap<FieldPath, HyperLogLog>,
+    pub computed_at_ms: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedIndexStatistics {
+    index_id: String,
+    count: i64,
+    samples: Vec<ByteBuf>,
+    distinct: Vec<SerializedDistinctSketch>,
+    computed_at_ms: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedDistinctSketch {
+    field: String,
+    sketch: ByteBuf,
+}
+
+impl TryFrom<IndexStatistics> for SerializedIndexStatistics {
+    type Error = anyhow::Error;
+
+    fn try_from(statistics: IndexStatistics) -> anyhow::Result<Self> {
+        Ok(Self {
+            index_id: statistics.index_id.to_string(),
+            count: statistics.count.try_into()?,
+            samples: statistics.samples.into_iter().map(ByteBuf::from).collect(),
+            distinct: statistics
+                .distinct
+                .into_iter()
+                .map(|(field, sketch)| SerializedDistinctSketch {
+                    field: String::from(field),
+                    sketch: ByteBuf::from(sketch.to_bytes()),
+                })
+                .collect(),
+            computed_at_ms: statistics.computed_at_ms,
+        })
+    }
+}
+
+impl TryFrom<SerializedIndexStatistics> for IndexStatistics {
+    type Error = anyhow::Error;
+
+    fn try_from(statistics: SerializedIndexStatistics) -> anyhow::Result<Self> {
+        Ok(Self {
+            index_id: InternalId::from_str(&statistics.index_id)?,
+            count: statistics.count.try_into()?,
+            samples: statistics
+                .samples
+                .into_iter()
+                .map(ByteBuf::into_vec)
+                .collect(),
+            distinct: statistics
+                .distinct
+                .into_iter()
+                .map(|sketch| {
+                    Ok((
+                        sketch.field.parse()?,
+                        HyperLogLog::from_bytes(sketch.sketch.into_vec())?,
+                    ))
+                })
+                .collect::<anyhow::Result<_>>()?,
+            computed_at_ms: statistics.computed_at_ms,
+        })
+    }
+}
+
+codegen_convex_serialization!(IndexStatistics, SerializedIndexStatistics);
+
+/// Computes the statistics of a database index by scanning it at `ts`. The scan
+/// runs outside of a transaction, so it isn't bound by transaction limits.
+pub async fn compute_index_statistics<RT: Runtime>(
+    database: &Database<RT>,
+    ts: RepeatableTimestamp,
+    tablet_id: TabletId,
+    index_id: IndexId,
+    fields: IndexedFields,
+    computed_at_ms: i64,
+) -> anyhow::Result<IndexStatistics> {
+    let mut distinct: BTreeMap<_, _> = fields
+        .iter()
+        .map(|field| (field.clone(), HyperLogLog::new()))
+        .collect();
+    // Sample every `stride`th key, doubling the stride and dropping every other
+    // sample whenever there are twice as many as needed, so the samples stay
+    // evenly spaced without knowing the index's size up front.
+    let mut stride = 1;
+    let mut samples = vec![];
+    let mut count = 0;
+    let stream = database
+        .table_iterator(ts, 1000)
+        .stream_documents_in_table_by_index(tablet_id, index_id, fields, None);
+    pin_mut!(stream);
+    while let Some((
+        key,
+        LatestDocument {
+            value: document, ..
+        },
+    )) = stream.try_next().await?
+    {
+        if count % stride == 0 {
+            let mut key = key.0;
+            key.truncate(MAX_SAMPLE_KEY_BYTES);
+            samples.push(key);
+            if samples.len() >= 2 * *INDEX_STATISTICS_SAMPLES {
+                samples = samples.into_iter().step_by(2).collect();
+                stride *= 2;
+            }
+        }
+        count += 1;
+        for (field, sketch) in &mut distinct {
+            sketch.insert(&values_to_bytes(&[document
+                .value()
+                .get_path(field)
+                .cloned()]));
+        }
+    }
+    Ok(IndexStatistics {
+        index_id,
+        count,
+        samples,
+        distinct,
+        computed_at_ms,
+    })
+}
+
+fn statistics_unavailable_error(index_name: &IndexName) -> ErrorMetadata {
+    ErrorMetadata::bad_request(
+        "IndexStatisticsUnavailable",
+        format!(
+            "Statistics for index {index_name} haven't been computed yet. They're computed in the \
+             background shortly after an index is created."
+        ),
+    )
+}
+
+pub struct IndexStatisticsModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> IndexStatisticsModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    pub async fn get(
+        &mut self,
+        index_id: IndexId,
+    ) -> anyhow::Result<Option<ParsedDocument<IndexStatistics>>> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&INDEX_STATISTICS_TABLE) {
+            return Ok(None);
+        }
+        let range = vec![IndexRangeExpression::Eq(
+            INDEX_ID_FIELD.clone(),
+            ConvexValue::String(index_id.to_string().try_into()?).into(),
+        )];
+        let query = Query::index_range(IndexRange {
+            index_name: INDEX_STATISTICS_BY_INDEX_ID.name(),
+            range,
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParseDocument::<IndexStatistics>::parse)
+            .transpose()
+    }
+
+    pub async fn list(&mut self) -> anyhow::Result<Vec<ParsedDocument<IndexStatistics>>> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&INDEX_STATISTICS_TABLE) {
+            return Ok(vec![]);
+        }
+        let query = Query::full_table_scan(INDEX_STATISTICS_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut statistics = vec![];
+        while let Some(document) = query_stream.next(self.tx, None).await? {
+            statistics.push(document.parse()?);
+        }
+        Ok(statistics)
+    }
+
+    /// Replaces the statistics of `statistics.index_id`.
+    pub async fn record(&mut self, statistics: IndexStatistics) -> anyhow::Result<()> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&INDEX_STATISTICS_TABLE) {
+            return Ok(());
+        }
+        match self.get(statistics.index_id).await? {
+            Some(existing) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), statistics.try_into()?)
+                    .await?;
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&INDEX_STATISTICS_TABLE, statistics.try_into()?)
+                    .await?;
+            },
+        }
+        Ok(())
+    }
+
+    pub async fn delete(&mut self, index_id: IndexId) -> anyhow::Result<()> {
+        if let Some(existing) = self.get(index_id).await? {
+            SystemMetadataModel::new_global(self.tx)
+                .delete(existing.id())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Estimates how many documents `query` would return without running it.
+    /// Full table scans use the table's exact count, and index ranges scale it
+    /// by the fraction of the index's sampled keys within the range.
+    pub async fn estimated_count(
+        &mut self,
+        namespace: TableNamespace,
+        query: Query,
+        table_filter: TableFilter,
+    ) -> anyhow::Result<u64> {
+        let mut limit = None;
+        for operator in &query.operators {
+            match operator {
+                QueryOperator::Filter(_) => anyhow::bail!(ErrorMetadata::bad_request(
+                    "InvalidEstimatedCountQuery",
+                    "estimatedCount() can't be used after filter(), since statistics only cover \
+                     index ranges.",
+                )),
+                QueryOperator::Limit(n) => {
+                    limit = Some(limit.map_or(*n, |limit: usize| limit.min(*n)));
+                },
+            }
+        }
+        let estimate = match query.source {
+            QuerySource::FullTableScan(FullTableScan { table_name, .. }) => self
+                .tx
+                .count(namespace, &table_name)
+                .await?
+                .ok_or_else(|| {
+                    table_summary_bootstrapping_error(Some(
+                        "Table count unavailable while bootstrapping",
+                    ))
+                })?,
+            QuerySource::IndexRange(index_range) => {
+                self.estimated_range_count(namespace, index_range, table_filter)
+                    .await?
+            },
+            QuerySource::Search(_) => anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidEstimatedCountQuery",
+                "estimatedCount() isn't supported for search queries.",
+            )),
+        };
+        Ok(limit.map_or(estimate, |limit| estimate.min(limit as u64)))
+    }
+
+    async fn estimated_range_count(
+        &mut self,
+        namespace: TableNamespace,
+        index_range: IndexRange,
+        table_filter: TableFilter,
+    ) -> anyhow::Result<u64> {
+        let index_name = index_range.index_name.clone();
+        let stable_index_name =
+            IndexModel::new(self.tx).stable_index_name(namespace, &index_name, table_filter)?;
+        let fields = IndexModel::new(self.tx).indexed_fields(&stable_index_name, &index_name)?;
+        let tablet_id = *stable_index_name
+            .tablet_index_name()
+            .context("Indexed fields found for a missing index")?
+            .table();
+        let count = TableModel::new(self.tx)
+            .must_count_tablet(tablet_id)
+            .await?;
+        if index_range.range.is_empty() {
+            return Ok(count);
+        }
+        let interval = index_range.compile(fields)?;
+        let statistics = self.index_statistics(namespace, &index_name).await?;
+        if statistics.samples.is_empty() {
+            return Ok(0);
+        }
+        let matching = statistics
+            .samples
+            .iter()
+            .filter(|sample| interval.contains(sample))
+            .count();
+        Ok((count as f64 * matching as f64 / statistics.samples.len() as f64).round() as u64)
+    }
+
+    /// Estimates how many distinct values `field` has across a whole index.
+    /// `query` must be an index range query without a range or operators.
+    pub async fn approx_distinct(
+        &mut self,
+        namespace: TableNamespace,
+        query: Query,
+        field: FieldPath,
+        table_filter: TableFilter,
+    ) -> anyhow::Result<u64> {
+        let QuerySource::IndexRange(index_range) = query.source else {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidApproxDistinctQuery",
+                "approxDistinct() must be called on a query using withIndex().",
+            ));
+        };
+        if !index_range.range.is_empty() || !query.operators.is_empty() {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidApproxDistinctQuery",
+                "approxDistinct() counts the distinct values across a whole index, so the query \
+                 can't have an index range, filter() or limit().",
+            ));
+        }
+        let index_name = index_range.index_name;
+        let stable_index_name =
+            IndexModel::new(self.tx).stable_index_name(namespace, &index_name, table_filter)?;
+        let fields = IndexModel::new(self.tx).indexed_fields(&stable_index_name, &index_name)?;
+        if !fields.contains(&field) {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidApproxDistinctField",
+                format!("{field} isn't one of the fields of index {index_name}: {fields}"),
+            ));
+        }
+        let statistics = self.index_statistics(namespace, &index_name).await?;
+        let sketch = statistics
+            .distinct
+            .get(&field)
+            .ok_or_else(|| statistics_unavailable_error(&index_name))?;
+        Ok(sketch.estimate().round() as u64)
+    }
+
+    async fn index_statistics(
+        &mut self,
+        namespace: TableNamespace,
+        index_name: &IndexName,
+    ) -> anyhow::Result<IndexStatistics> {
+        let index_id = IndexModel::new(self.tx)
+            .enabled_index_metadata(namespace, index_name)?
+            .with_context(|| statistics_unavailable_error(index_name))?
+            .id()
+            .internal_id();
+        Ok(self
+            .get(index_id)
+            .await?
+            .ok_or_else(|| statistics_unavailable_error(index_name))?
+            .into_value())
+    }
+}
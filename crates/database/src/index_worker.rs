@@ -14,6 +14,7 @@ use std::{
 };
 
 use common::{
+    adaptive_pacing::AdaptiveRateLimiter,
     backoff::Backoff,
     bootstrap_model::index::{
         database_index::{
@@ -55,11 +56,7 @@ use common::{
         Order,
         Query,
     },
-    runtime::{
-        new_rate_limiter,
-        RateLimiter,
-        Runtime,
-    },
+    runtime::Runtime,
     types::{
         DatabaseIndexUpdate,
         IndexId,
@@ -83,7 +80,6 @@ use futures::{
     StreamExt,
     TryStreamExt,
 };
-use governor::Quota;
 use indexing::index_registry::IndexRegistry;
 use keybroker::Identity;
 use maplit::btreeset;
@@ -134,7 +130,7 @@ pub struct IndexWriter<RT: Runtime> {
     // Reader must have by_id index fully populated.
     reader: Arc<dyn PersistenceReader>,
     retention_validator: Arc<dyn RetentionValidator>,
-    rate_limiter: Arc<RateLimiter<RT>>,
+    rate_limiter: Arc<AdaptiveRateLimiter<RT>>,
     runtime: RT,
 }
 
@@ -610,9 +606,10 @@ impl<RT: Runtime> IndexWriter<RT> {
             persistence,
             reader,
             retention_validator,
-            rate_limiter: Arc::new(new_rate_limiter(
+            rate_limiter: Arc::new(AdaptiveRateLimiter::new(
                 runtime.clone(),
-                Quota::per_second(*ENTRIES_PER_SECOND),
+                "index_backfill",
+                ENTRIES_PER_SECOND.get() as f64,
             )),
             runtime,
         }
@@ -958,17 +955,7 @@ impl<RT: Runtime> IndexWriter<RT> {
         let mut num_entries_written = 0;
 
         while !updates.is_terminated() {
-            // There are potentially more document revisions, so start a new chunk. First,
-            // check with the rate limiter upfront to ensure we're allowed to
-            // continue.
-            while let Err(not_until) = self.rate_limiter.check() {
-                // NB: We can't use `RateLimiter`'s async API since it internally relies on
-                // `futures-timer`. These timers will never get satisfied under our test
-                // runtime.
-                let delay = not_until.wait_time_from(self.runtime.monotonic_now().into());
-                self.runtime.wait(delay).await;
-            }
-
+            // There are potentially more document revisions, so start a new chunk.
             // Try to fill up a full chunk until we exhaust the stream or fill the chunk.
             let mut chunk = BTreeSet::new();
             while chunk.len() < *INDEX_BACKFILL_CHUNK_SIZE {
@@ -982,6 +969,9 @@ impl<RT: Runtime> IndexWriter<RT> {
                 chunk.insert((ts, update));
             }
             if !chunk.is_empty() {
+                // Pace writes against live traffic so backfills don't slow down
+                // the deployment's own commits.
+                self.rate_limiter.acquire(chunk.len() as u32).await;
                 num_entries_written += chunk.len();
                 self.persistence
                     .write(vec![], chunk, ConflictStrategy::Overwrite)
@@ -165,6 +165,7 @@ pub struct BackfillState<T: SearchIndex> {
     pub segments: Vec<T::Segment>,
     pub cursor: Option<InternalId>,
     pub backfill_snapshot_ts: Option<Timestamp>,
+    pub total_documents: Option<u64>,
 }
 
 pub enum SearchOnDiskState<T: SearchIndex> {
@@ -202,7 +203,19 @@ impl<T: SearchIndex> SearchOnDiskState<T> {
             data: SnapshotData::MultiSegment(segments),
         };
         match self {
-            Self::Backfilling(_) => anyhow::bail!("Can't update backfilling index!"),
+            // A backfilling index's segments were caught up to `ts` with the writes made since
+            // its backfill snapshot, so the backfill continues from `ts`.
+            Self::Backfilling(backfill) => {
+                anyhow::ensure!(
+                    backfill.cursor.is_some(),
+                    "Can't update backfilling index without a cursor!"
+                );
+                Ok(Self::Backfilling(BackfillState {
+                    segments: snapshot.data.segments(),
+                    backfill_snapshot_ts: Some(ts),
+                    ..backfill
+                }))
+            },
             Self::Backfilled(_) => Ok(Self::Backfilled(snapshot)),
             Self::SnapshottedAt(_) => Ok(Self::SnapshottedAt(snapshot)),
         }
@@ -57,4 +57,7 @@ pub async fn timeout_with_jitter<RT: Runtime>(rt: &RT, duration: Duration) {
 pub struct MultiSegmentBackfillResult {
     pub new_cursor: Option<ResolvedDocumentId>,
     pub is_backfill_complete: bool,
+    /// The number of documents in the table when the segment was built, used
+    /// to report the backfill's progress.
+    pub total_documents: Option<u64>,
 }
@@ -1,16 +1,25 @@
 use std::{
-    collections::BTreeMap,
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
     sync::Arc,
 };
 
 use anyhow::Context;
 use common::{
+    adaptive_pacing::paced_rate,
     knobs::{
         MAX_COMPACTION_SEGMENTS,
         MAX_SEGMENT_DELETED_PERCENTAGE,
         MIN_COMPACTION_SEGMENTS,
         SEARCH_WORKER_PASSIVE_PAGES_PER_SECOND,
         SEGMENT_MAX_SIZE_BYTES,
+        TEXT_COMPACTION_SMALL_SEGMENT_THRESHOLD_BYTES,
+        TEXT_MAX_COMPACTION_SEGMENTS,
+        TEXT_MAX_SEGMENT_DELETED_PERCENTAGE,
+        TEXT_MIN_COMPACTION_SEGMENTS,
+        TEXT_SEGMENT_MAX_SIZE_BYTES,
         VECTOR_INDEX_SIZE_HARD_LIMIT,
     },
     runtime::Runtime,
@@ -18,13 +27,17 @@ use common::{
 };
 use itertools::Itertools;
 use keybroker::Identity;
+use parking_lot::Mutex;
 use rand::seq::SliceRandom;
 use search::{
     metrics::SearchType,
     Searcher,
 };
 use storage::Storage;
-use tokio::task;
+use tokio::{
+    sync::Notify,
+    task,
+};
 use value::ResolvedDocumentId;
 
 use crate::{
@@ -57,6 +70,7 @@ pub struct SearchIndexCompactor<RT: Runtime, T: SearchIndex> {
     search_storage: Arc<dyn Storage>,
     config: CompactionConfig,
     writer: SearchIndexMetadataWriter<RT, T>,
+    requests: CompactionRequests,
 }
 
 impl<RT: Runtime, T: SearchIndex> SearchIndexCompactor<RT, T> {
@@ -73,9 +87,16 @@ impl<RT: Runtime, T: SearchIndex> SearchIndexCompactor<RT, T> {
             search_storage,
             config,
             writer,
+            requests: CompactionRequests::default(),
         }
     }
 
+    /// Returns a handle for requesting that this compactor compact an index
+    /// regardless of its compaction policy.
+    pub(crate) fn compaction_requests(&self) -> CompactionRequests {
+        self.requests.clone()
+    }
+
     fn search_type() -> SearchType {
         T::search_type()
     }
@@ -120,7 +141,7 @@ impl<RT: Runtime, T: SearchIndex> SearchIndexCompactor<RT, T> {
             };
             let name = index_metadata.name;
 
-            let maybe_segments_to_compact = match &config.on_disk_state {
+            let segments = match &config.on_disk_state {
                 SearchOnDiskState::Backfilling(BackfillState {
                     segments,
                     backfill_snapshot_ts,
@@ -129,11 +150,7 @@ impl<RT: Runtime, T: SearchIndex> SearchIndexCompactor<RT, T> {
                     if backfill_snapshot_ts.is_none() {
                         continue;
                     } else {
-                        Self::find_segments_to_compact(
-                            segments,
-                            &config.developer_config,
-                            &self.config,
-                        )?
+                        segments
                     }
                 },
                 SearchOnDiskState::SnapshottedAt(SearchSnapshot {
@@ -143,11 +160,7 @@ impl<RT: Runtime, T: SearchIndex> SearchIndexCompactor<RT, T> {
                 | SearchOnDiskState::Backfilled(SearchSnapshot {
                     data: SnapshotData::MultiSegment(segments),
                     ..
-                }) => Self::find_segments_to_compact(
-                    segments,
-                    &config.developer_config,
-                    &self.config,
-                )?,
+                }) => segments,
                 _ => {
                     tracing::info!(
                         "Skipping {:?} index for compaction: {name:?} because it is not \
@@ -157,6 +170,22 @@ impl<RT: Runtime, T: SearchIndex> SearchIndexCompactor<RT, T> {
                     continue;
                 },
             };
+            let mut maybe_segments_to_compact =
+                Self::find_segments_to_compact(segments, &config.developer_config, &self.config)?;
+            if maybe_segments_to_compact.is_none() && self.requests.contains(&name) {
+                maybe_segments_to_compact = Self::find_segments_to_force_compact(
+                    segments,
+                    &config.developer_config,
+                    &self.config,
+                )?;
+                if maybe_segments_to_compact.is_none() {
+                    tracing::info!(
+                        "Finished requested compaction of {:?} index: {name:?}",
+                        Self::search_type()
+                    );
+                    self.requests.complete(&name);
+                }
+            }
             if let Some((mut segments_to_compact, compaction_reason)) = maybe_segments_to_compact {
                 tracing::info!(
                     "Queueing {:?} index for compaction: {name:?} for reason: \
@@ -167,11 +196,11 @@ impl<RT: Runtime, T: SearchIndex> SearchIndexCompactor<RT, T> {
                 segments_to_compact.shuffle(&mut rand::rng());
                 tracing::info!(
                     "Compacting {} segments out of {} that need compaction for reason: {:?}",
-                    *MAX_COMPACTION_SEGMENTS,
+                    self.config.max_compaction_segments,
                     segments_to_compact.len(),
                     compaction_reason,
                 );
-                segments_to_compact.truncate(*MAX_COMPACTION_SEGMENTS);
+                segments_to_compact.truncate(self.config.max_compaction_segments);
                 let job = CompactionJob {
                     index_id,
                     index_name: name.clone(),
@@ -223,7 +252,7 @@ impl<RT: Runtime, T: SearchIndex> SearchIndexCompactor<RT, T> {
                 snapshot_ts,
                 segments_to_compact.clone(),
                 new_segment.clone(),
-                *SEARCH_WORKER_PASSIVE_PAGES_PER_SECOND,
+                paced_rate("search_compaction", *SEARCH_WORKER_PASSIVE_PAGES_PER_SECOND),
                 T::new_schema(&job.developer_config),
             )
             .await?;
@@ -381,6 +410,35 @@ impl<RT: Runtime, T: SearchIndex> SearchIndexCompactor<RT, T> {
         Ok(None)
     }
 
+    /// Finds segments to compact for an index that was explicitly requested to
+    /// be compacted, ignoring the thresholds of the compaction policy: as many
+    /// of the smallest segments as fit in one segment, or otherwise any single
+    /// segment with deleted documents.
+    fn find_segments_to_force_compact(
+        segments: &Vec<T::Segment>,
+        developer_config: &T::DeveloperConfig,
+        compaction_config: &CompactionConfig,
+    ) -> anyhow::Result<Option<(Vec<T::Segment>, CompactionReason)>> {
+        let forced_config = CompactionConfig {
+            min_compaction_segments: 2,
+            ..compaction_config.clone()
+        };
+        if let Some(compactable) = Self::get_compactable_segments(
+            segments.iter().collect(),
+            developer_config,
+            &forced_config,
+        )? {
+            return Ok(Some((
+                compactable.into_iter().cloned().collect(),
+                CompactionReason::Requested,
+            )));
+        }
+        let with_deletes = segments
+            .iter()
+            .try_find(|segment| anyhow::Ok(segment.statistics()?.num_deleted_documents() > 0))?;
+        Ok(with_deletes.map(|segment| (vec![segment.clone()], CompactionReason::Requested)))
+    }
+
     async fn compact(
         &self,
         developer_config: &T::DeveloperConfig,
@@ -438,11 +496,26 @@ pub struct CompactionConfig {
     // want to compact yet.
     pub min_compaction_segments: u64,
     pub max_segment_size_bytes: u64,
+    // Don't compact more than N segments at once, even if more need compaction.
+    pub max_compaction_segments: usize,
+}
+
+impl CompactionConfig {
+    /// The compaction policy for text indexes, configured by the `TEXT_*`
+    /// compaction knobs.
+    pub fn text() -> Self {
+        Self {
+            max_deleted_percentage: *TEXT_MAX_SEGMENT_DELETED_PERCENTAGE,
+            small_segment_threshold_bytes: *TEXT_COMPACTION_SMALL_SEGMENT_THRESHOLD_BYTES,
+            min_compaction_segments: *TEXT_MIN_COMPACTION_SEGMENTS,
+            max_segment_size_bytes: *TEXT_SEGMENT_MAX_SIZE_BYTES,
+            max_compaction_segments: *TEXT_MAX_COMPACTION_SEGMENTS,
+        }
+    }
 }
 
-// TODO(sam): These defaults are reasonable for vector, but maybe not text.
-// Remove this Default impl and replace it with variants for text an vector
-// search.
+// TODO(sam): These defaults are reasonable for vector. Text indexes use
+// `CompactionConfig::text` outside of tests.
 impl Default for CompactionConfig {
     fn default() -> Self {
         Self {
@@ -452,10 +525,42 @@ impl Default for CompactionConfig {
             small_segment_threshold_bytes: *VECTOR_INDEX_SIZE_HARD_LIMIT as u64,
             min_compaction_segments: *MIN_COMPACTION_SEGMENTS,
             max_segment_size_bytes: *SEGMENT_MAX_SIZE_BYTES,
+            max_compaction_segments: *MAX_COMPACTION_SEGMENTS,
         }
     }
 }
 
+/// Indexes that have been requested to be compacted regardless of the
+/// compaction policy, shared between a compactor and whoever requests
+/// compactions from it.
+#[derive(Clone, Default)]
+pub struct CompactionRequests {
+    requested: Arc<Mutex<BTreeSet<TabletIndexName>>>,
+    notify: Arc<Notify>,
+}
+
+impl CompactionRequests {
+    /// Asks the compactor to merge the index's segments as far as possible,
+    /// and wakes it up to do so.
+    pub fn request(&self, index_name: TabletIndexName) {
+        self.requested.lock().insert(index_name);
+        self.notify.notify_one();
+    }
+
+    fn contains(&self, index_name: &TabletIndexName) -> bool {
+        self.requested.lock().contains(index_name)
+    }
+
+    fn complete(&self, index_name: &TabletIndexName) {
+        self.requested.lock().remove(index_name);
+    }
+
+    /// Waits until a new compaction is requested.
+    pub(crate) async fn wait_for_request(&self) {
+        self.notify.notified().await
+    }
+}
+
 struct CompactionJob<T: SearchIndex> {
     index_id: ResolvedDocumentId,
     index_name: TabletIndexName,
@@ -17,6 +17,7 @@ use common::{
     knobs::{
         DATABASE_WORKERS_MAX_CHECKPOINT_AGE,
         DEFAULT_DOCUMENTS_PAGE_SIZE,
+        SEARCH_INDEX_BACKFILL_MAX_SNAPSHOT_AGE,
         VECTOR_INDEX_WORKER_PAGE_SIZE,
     },
     persistence::{
@@ -83,6 +84,7 @@ use crate::{
     },
     Database,
     IndexModel,
+    TableModel,
     Token,
 };
 
@@ -330,6 +332,7 @@ impl<RT: Runtime, T: SearchIndex + 'static> SearchFlusher<RT, T> {
         let tablet_id = *job.index_name.table();
         let table_number = tx.table_mapping().tablet_number(tablet_id)?;
         let mut new_ts = tx.begin_timestamp();
+        let mut total_documents = None;
         let (previous_segments, build_type) = match job.index_config.on_disk_state {
             SearchOnDiskState::Backfilling(ref backfill_state) => {
                 let backfill_snapshot_ts = backfill_state
@@ -337,24 +340,49 @@ impl<RT: Runtime, T: SearchIndex + 'static> SearchFlusher<RT, T> {
                     .map(|ts| new_ts.prior_ts(ts))
                     .transpose()?
                     .unwrap_or(new_ts);
-                // For backfilling indexes, the snapshot timestamp we return is the backfill
-                // snapshot timestamp
-                new_ts = backfill_snapshot_ts;
-
-                let cursor = backfill_state.cursor;
-
-                (
-                    backfill_state.segments.clone(),
-                    MultipartBuildType::IncrementalComplete {
-                        cursor: cursor.map(|cursor| {
-                            ResolvedDocumentId::new(
-                                tablet_id,
-                                DeveloperDocumentId::new(table_number, cursor),
-                            )
-                        }),
-                        backfill_snapshot_ts,
+                total_documents = TableModel::new(&mut tx).count_tablet(tablet_id).await?;
+
+                let cursor = backfill_state.cursor.map(|cursor| {
+                    ResolvedDocumentId::new(
+                        tablet_id,
+                        DeveloperDocumentId::new(table_number, cursor),
+                    )
+                });
+                let snapshot_age = *new_ts - *backfill_snapshot_ts;
+
+                match cursor {
+                    // The backfill has been running long enough that replaying the writes made
+                    // since its snapshot would be expensive once it finishes. Catch the documents
+                    // indexed so far up to `new_ts` instead, and continue the backfill from
+                    // there.
+                    Some(cursor) if snapshot_age >= *SEARCH_INDEX_BACKFILL_MAX_SNAPSHOT_AGE => {
+                        tracing::info!(
+                            "Catching up backfilling {} index {:?} from {:?} ago",
+                            self.index_type_name(),
+                            job.index_name,
+                            snapshot_age
+                        );
+                        (
+                            backfill_state.segments.clone(),
+                            MultipartBuildType::BackfillCatchUp {
+                                cursor,
+                                backfill_snapshot_ts,
+                            },
+                        )
                     },
-                )
+                    _ => {
+                        // For backfilling indexes, the snapshot timestamp we return is the
+                        // backfill snapshot timestamp
+                        new_ts = backfill_snapshot_ts;
+                        (
+                            backfill_state.segments.clone(),
+                            MultipartBuildType::IncrementalComplete {
+                                cursor,
+                                backfill_snapshot_ts,
+                            },
+                        )
+                    },
+                }
             },
             SearchOnDiskState::Backfilled(ref snapshot)
             | SearchOnDiskState::SnapshottedAt(ref snapshot) => {
@@ -392,6 +420,10 @@ impl<RT: Runtime, T: SearchIndex + 'static> SearchFlusher<RT, T> {
             )
             .await?;
 
+        let backfill_result = backfill_result.map(|result| MultiSegmentBackfillResult {
+            total_documents,
+            ..result
+        });
         let new_segment = if let Some(new_segment) = new_segment {
             Some(self.upload_new_segment(new_segment).await?)
         } else {
@@ -511,6 +543,31 @@ impl<RT: Runtime, T: SearchIndex + 'static> SearchFlusher<RT, T> {
                     previous_segments,
                 )
             },
+            MultipartBuildType::BackfillCatchUp {
+                cursor,
+                backfill_snapshot_ts,
+            } => {
+                lower_bound_ts = Some(*backfill_snapshot_ts);
+                // Documents after the cursor aren't indexed yet, and the rest of the backfill
+                // will read them as of `snapshot_ts`.
+                let cursor = cursor.internal_id();
+                (
+                    params
+                        .database
+                        .load_documents_in_table(
+                            *index_name.table(),
+                            TimestampRange::new((
+                                Bound::Excluded(*backfill_snapshot_ts),
+                                Bound::Included(*snapshot_ts),
+                            ))?,
+                            T::partial_document_order(),
+                            &row_rate_limiter,
+                        )
+                        .try_filter(move |entry| future::ready(entry.id.internal_id() <= cursor))
+                        .boxed(),
+                    previous_segments,
+                )
+            },
             MultipartBuildType::IncrementalComplete {
                 cursor,
                 backfill_snapshot_ts,
@@ -589,6 +646,7 @@ impl<RT: Runtime, T: SearchIndex + 'static> SearchFlusher<RT, T> {
                 Some(MultiSegmentBackfillResult {
                     new_cursor,
                     is_backfill_complete,
+                    total_documents: None,
                 })
             } else {
                 None
@@ -655,4 +713,10 @@ pub enum MultipartBuildType {
         cursor: Option<ResolvedDocumentId>,
         backfill_snapshot_ts: RepeatableTimestamp,
     },
+    // Update the parts of a backfilling index built so far, which cover the documents up to and
+    // including `cursor`, with the writes made since `backfill_snapshot_ts`
+    BackfillCatchUp {
+        cursor: ResolvedDocumentId,
+        backfill_snapshot_ts: RepeatableTimestamp,
+    },
 }
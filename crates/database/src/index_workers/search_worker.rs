@@ -18,7 +18,10 @@ use common::{
     types::TabletIndexName,
 };
 use futures::{
-    future::BoxFuture,
+    future::{
+        self,
+        BoxFuture,
+    },
     pin_mut,
     select_biased,
     FutureExt,
@@ -36,7 +39,10 @@ use crate::{
             retry_loop_expect_occs_and_overloaded,
             RetriableWorker,
         },
-        search_compactor::CompactionConfig,
+        search_compactor::{
+            CompactionConfig,
+            CompactionRequests,
+        },
         timeout_with_jitter,
         writer::SearchIndexMetadataWriter,
     },
@@ -68,6 +74,7 @@ use crate::{
 /// Builds and compacts text/vector search indexes.
 pub struct SearchIndexWorkers {
     handles: Vec<Box<dyn SpawnHandle>>,
+    text_compaction_requests: CompactionRequests,
 }
 
 enum SearchIndexWorker<RT: Runtime> {
@@ -161,18 +168,20 @@ impl SearchIndexWorkers {
             text_flusher,
         );
 
+        let text_compactor = new_text_compactor(
+            database.clone(),
+            searcher,
+            search_storage,
+            CompactionConfig::text(),
+            text_index_metadata_writer,
+        );
+        let text_compaction_requests = text_compactor.compaction_requests();
         let text_compact = retry_loop_expect_occs_and_overloaded(
             "TextCompactor",
             runtime.clone(),
-            database.clone(),
+            database,
             Duration::ZERO,
-            SearchIndexWorker::TextCompactor(new_text_compactor(
-                database,
-                searcher,
-                search_storage,
-                CompactionConfig::default(),
-                text_index_metadata_writer,
-            )),
+            SearchIndexWorker::TextCompactor(text_compactor),
         );
 
         let vector_flush_handle = runtime.spawn("vector_flush", vector_flush);
@@ -186,9 +195,15 @@ impl SearchIndexWorkers {
                 text_flush_handle,
                 text_compact_handle,
             ],
+            text_compaction_requests,
         }
     }
 
+    /// Requests compactions of text indexes from the text compactor.
+    pub fn text_compaction_requests(&self) -> CompactionRequests {
+        self.text_compaction_requests.clone()
+    }
+
     pub fn shutdown(&mut self) {
         self.handles.iter_mut().for_each(|handle| handle.shutdown())
     }
@@ -206,6 +221,14 @@ impl<RT: Runtime> SearchIndexWorker<RT> {
         }
     }
 
+    fn compaction_requests(&self) -> Option<CompactionRequests> {
+        match self {
+            Self::VectorCompactor(compactor) => Some(compactor.compaction_requests()),
+            Self::TextCompactor(compactor) => Some(compactor.compaction_requests()),
+            Self::VectorFlusher(_) | Self::TextFlusher(_) => None,
+        }
+    }
+
     async fn work_and_wait_for_changes(
         &mut self,
         name: &'static str,
@@ -229,6 +252,15 @@ impl<RT: Runtime> SearchIndexWorker<RT> {
             //    indexes
             // 2. Our soft index size is exceeded so we need to flush to disk - Implement
             //    via polling
+            // Compactors also wake up when a compaction is explicitly requested.
+            let compaction_requests = self.compaction_requests();
+            let compaction_requested = async {
+                match &compaction_requests {
+                    Some(requests) => requests.wait_for_request().await,
+                    None => future::pending().await,
+                }
+            };
+            pin_mut!(compaction_requested);
             let poll = timeout_with_jitter(rt, *DATABASE_WORKERS_POLL_INTERVAL);
             pin_mut!(poll);
             let subscription = db.subscribe(token).await?;
@@ -240,6 +272,9 @@ impl<RT: Runtime> SearchIndexWorker<RT> {
                         "{name} resuming after index subscription notification"
                     );
                 }
+                _ = compaction_requested.fuse() => {
+                    tracing::info!("{name} resuming after compaction request");
+                }
                 _ = poll.fuse() => {
                     tracing::debug!("{name} starting background checks");
                 }
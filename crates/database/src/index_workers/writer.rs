@@ -454,6 +454,7 @@ impl<RT: Runtime, T: SearchIndex> Inner<RT, T> {
                         .new_cursor
                         .map(|cursor| cursor.internal_id()),
                     backfill_snapshot_ts: Some(*backfill_complete_ts),
+                    total_documents: backfill_result.total_documents,
                 })
             },
         )
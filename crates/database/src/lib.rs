@@ -14,9 +14,14 @@
 #![feature(once_cell_try)]
 
 mod bootstrap_model;
+mod commit_admission;
 mod committer;
+mod component_quotas;
 mod database;
+mod document_cache;
 mod execution_size;
+mod hybrid_search;
+mod index_statistics;
 mod index_worker;
 mod index_workers;
 mod metrics;
@@ -27,19 +32,25 @@ pub mod query;
 pub mod reads;
 mod retention;
 mod search_index_bootstrap;
+mod search_synonyms;
 mod snapshot_manager;
 mod stack_traces;
 pub mod streaming_export_selection;
 pub mod subscription;
 pub mod system_tables;
+mod table_freezes;
 mod table_registry;
+mod table_snapshots;
 pub mod table_summary;
 mod table_usage;
 mod token;
 mod transaction;
 mod transaction_id_generator;
 mod transaction_index;
+mod vector_embedding_jobs;
+mod vector_index_migrations;
 pub mod vector_index_worker;
+mod vector_query_stats;
 mod virtual_tables;
 mod write_limits;
 #[cfg(any(test, feature = "testing"))]
@@ -63,7 +74,11 @@ pub use index_workers::{
     fast_forward::FastForwardIndexWorker,
     search_worker::SearchIndexWorkers,
 };
-pub use patch::PatchValue;
+pub use patch::{
+    DeepPatch,
+    PatchOperation,
+    PatchValue,
+};
 pub use preloaded::PreloadedIndexRange;
 pub use reads::{
     ReadSet,
@@ -142,6 +157,14 @@ pub use self::{
         },
         user_facing::UserFacingModel,
     },
+    component_quotas::{
+        ComponentCallCounter,
+        ComponentQuota,
+        ComponentQuotasModel,
+        ComponentQuotasTable,
+        COMPONENT_QUOTAS_BY_COMPONENT_ID,
+        COMPONENT_QUOTAS_TABLE,
+    },
     database::{
         unauthorized_error,
         BootstrapMetadata,
@@ -153,13 +176,32 @@ pub use self::{
         StreamingExportTableFilter,
         MAX_OCC_FAILURES,
     },
+    hybrid_search::{
+        HybridSearch,
+        HybridSearchJson,
+        HybridSearchRequest,
+        PublicHybridSearchQueryResult,
+        RankFusion,
+    },
+    index_statistics::{
+        compute_index_statistics,
+        IndexStatistics,
+        IndexStatisticsModel,
+        IndexStatisticsTable,
+        INDEX_STATISTICS_BY_INDEX_ID,
+        INDEX_STATISTICS_TABLE,
+    },
     index_worker::{
         IndexSelector,
         IndexWriter,
     },
     query::{
+        group_by_batch,
         soft_data_limit,
+        Aggregate,
         DeveloperQuery,
+        Group,
+        GroupByQuery,
         ResolvedQuery,
     },
     retention::{
@@ -168,15 +210,39 @@ pub use self::{
         LeaderRetentionManager,
         RetentionType,
     },
+    search_synonyms::{
+        SearchSynonymsModel,
+        SearchSynonymsTable,
+        SEARCH_SYNONYMS_BY_INDEX_ID,
+        SEARCH_SYNONYMS_TABLE,
+    },
     snapshot_manager::{
         Snapshot,
         TableSummaries,
     },
-    subscription::Subscription,
+    subscription::{
+        InvalidationCause,
+        Subscription,
+    },
+    table_freezes::{
+        TableFreeze,
+        TableFreezesModel,
+        TableFreezesTable,
+        MAX_TABLE_FREEZE_DURATION,
+        TABLE_FREEZES_BY_TABLET_ID,
+        TABLE_FREEZES_TABLE,
+    },
     table_iteration::{
         MultiTableIterator,
         TableIterator,
     },
+    table_snapshots::{
+        TableSnapshot,
+        TableSnapshotsModel,
+        TableSnapshotsTable,
+        TABLE_SNAPSHOTS_BY_NAME,
+        TABLE_SNAPSHOTS_TABLE,
+    },
     table_summary::{
         TableSummary,
         TableSummaryWriter,
@@ -188,6 +254,25 @@ pub use self::{
     transaction::DEFAULT_PAGE_SIZE,
     transaction_id_generator::TransactionIdGenerator,
     transaction_index::TransactionIndex,
+    vector_embedding_jobs::{
+        VectorEmbeddingJob,
+        VectorEmbeddingJobModel,
+        VectorEmbeddingJobState,
+        VectorEmbeddingJobsTable,
+        VectorEmbeddingStatus,
+        VECTOR_EMBEDDING_JOBS_BY_STATE_AND_NEXT_ATTEMPT,
+        VECTOR_EMBEDDING_JOBS_TABLE,
+    },
+    vector_index_migrations::{
+        SerializedVectorIndexMigration,
+        VectorIndexMigration,
+        VectorIndexMigrationModel,
+        VectorIndexMigrationState,
+        VectorIndexMigrationsTable,
+        VECTOR_INDEX_MIGRATIONS_BY_SOURCE_INDEX_ID,
+        VECTOR_INDEX_MIGRATIONS_TABLE,
+    },
+    vector_query_stats::VectorQueryStats,
 };
 #[cfg(any(test, feature = "testing"))]
 pub use crate::bootstrap_model::test_facing::TestFacingModel;
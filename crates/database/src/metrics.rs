@@ -14,7 +14,10 @@ use metrics::{
     log_counter_with_labels,
     log_distribution,
     log_distribution_with_labels,
+    log_gauge,
+    log_gauge_with_labels,
     register_convex_counter,
+    register_convex_gauge,
     register_convex_histogram,
     IntoLabel,
     StaticMetricLabel,
@@ -26,8 +29,10 @@ use prometheus::{
     VMHistogram,
     VMHistogramVec,
 };
+use value::TableName;
 
 use crate::{
+    commit_admission::CommitPriority,
     transaction::FinalTransaction,
     RetentionType,
     Transaction,
@@ -149,6 +154,27 @@ pub fn subscription_timer() -> Timer<VMHistogram> {
     Timer::new(&DATABASE_SUBSCRIPTION_SECONDS)
 }
 
+register_convex_counter!(
+    DATABASE_SUBSCRIPTION_SHARED_TOTAL,
+    "Number of subscriptions that joined an existing subscriber with the same read set"
+);
+pub fn log_subscription_shared() {
+    log_counter(&DATABASE_SUBSCRIPTION_SHARED_TOTAL, 1);
+}
+
+register_convex_gauge!(
+    DATABASE_SUBSCRIBERS_TOTAL,
+    "Number of distinct read sets the subscription worker is tracking"
+);
+register_convex_gauge!(
+    DATABASE_SUBSCRIPTIONS_TOTAL,
+    "Number of subscriptions the subscription worker is tracking"
+);
+pub fn log_subscribers(num_subscribers: usize, num_subscriptions: usize) {
+    log_gauge(&DATABASE_SUBSCRIBERS_TOTAL, num_subscribers as f64);
+    log_gauge(&DATABASE_SUBSCRIPTIONS_TOTAL, num_subscriptions as f64);
+}
+
 register_convex_histogram!(
     DATABASE_REFRESH_TOKEN_SECONDS,
     "time taken to refresh a database token"
@@ -313,6 +339,55 @@ pub fn committer_full_error() -> ErrorMetadata {
     )
 }
 
+register_convex_gauge!(
+    DATABASE_COMMITS_IN_FLIGHT_TOTAL,
+    "Number of commits admitted to the committer and not yet finished",
+    &["priority"]
+);
+pub fn log_commits_in_flight(priority: CommitPriority, count: usize) {
+    log_gauge_with_labels(
+        &DATABASE_COMMITS_IN_FLIGHT_TOTAL,
+        count as f64,
+        vec![StaticMetricLabel::new("priority", priority.as_label())],
+    );
+}
+
+register_convex_counter!(
+    DATABASE_COMMITS_SHED_TOTAL,
+    "Count of commits rejected by committer admission control",
+    &["priority", "reason"]
+);
+
+pub fn too_many_commits_in_flight_error(priority: CommitPriority) -> ErrorMetadata {
+    log_counter_with_labels(
+        &DATABASE_COMMITS_SHED_TOTAL,
+        1,
+        vec![
+            StaticMetricLabel::new("priority", priority.as_label()),
+            StaticMetricLabel::new("reason", "in_flight_limit"),
+        ],
+    );
+    ErrorMetadata::overloaded(
+        "TooManyConcurrentCommits",
+        "Too many concurrent commits, backoff and try again",
+    )
+}
+
+pub fn commit_queue_time_exceeded_error(priority: CommitPriority) -> ErrorMetadata {
+    log_counter_with_labels(
+        &DATABASE_COMMITS_SHED_TOTAL,
+        1,
+        vec![
+            StaticMetricLabel::new("priority", priority.as_label()),
+            StaticMetricLabel::new("reason", "queue_time_budget"),
+        ],
+    );
+    ErrorMetadata::overloaded(
+        "CommitQueueTimeExceeded",
+        "Commit waited too long to be processed and was not applied, backoff and try again",
+    )
+}
+
 register_convex_counter!(
     SUBSCRIPTIONS_WORKER_FULL_TOTAL,
     "Count of subscription worker full errors"
@@ -643,6 +718,59 @@ pub fn log_read_set_age(seconds: f64) {
     log_distribution(&DATABASE_READS_REFRESH_AGE_SECONDS, seconds);
 }
 
+register_convex_gauge!(
+    DATABASE_WRITE_LOG_SIZE_BYTES,
+    "Size of the entries held in memory by the write log"
+);
+register_convex_gauge!(
+    DATABASE_WRITE_LOG_ENTRIES_TOTAL,
+    "Number of commits held in memory by the write log"
+);
+pub fn log_write_log_size(size: usize, num_entries: usize) {
+    log_gauge(&DATABASE_WRITE_LOG_SIZE_BYTES, size as f64);
+    log_gauge(&DATABASE_WRITE_LOG_ENTRIES_TOTAL, num_entries as f64);
+}
+
+register_convex_counter!(
+    DATABASE_WRITE_LOG_SPILLED_ENTRIES_TOTAL,
+    "Number of write log entries spilled out of memory to stay under the memory budget"
+);
+register_convex_counter!(
+    DATABASE_WRITE_LOG_SPILLED_BYTES_TOTAL,
+    "Size of the write log entries spilled out of memory"
+);
+pub fn log_write_log_spill(num_bytes: usize) {
+    log_counter(&DATABASE_WRITE_LOG_SPILLED_ENTRIES_TOTAL, 1);
+    log_counter(&DATABASE_WRITE_LOG_SPILLED_BYTES_TOTAL, num_bytes as u64);
+}
+
+register_convex_histogram!(
+    DATABASE_WRITE_LOG_READ_BACK_SECONDS,
+    "Time to read spilled write log entries back from persistence"
+);
+pub fn write_log_read_back_timer() -> Timer<VMHistogram> {
+    Timer::new(&DATABASE_WRITE_LOG_READ_BACK_SECONDS)
+}
+
+register_convex_histogram!(
+    DATABASE_WRITE_LOG_READ_BACK_ENTRIES_TOTAL,
+    "Number of spilled write log entries read back from persistence to check reads against"
+);
+pub fn log_write_log_read_back(num_entries: usize) {
+    log_distribution(
+        &DATABASE_WRITE_LOG_READ_BACK_ENTRIES_TOTAL,
+        num_entries as f64,
+    );
+}
+
+register_convex_gauge!(
+    DATABASE_WRITE_LOG_READ_BACK_CACHE_SIZE_BYTES,
+    "Size of the spilled write log entries kept in memory after being spilled or read back"
+);
+pub fn log_write_log_read_back_cache_size(size: usize) {
+    log_gauge(&DATABASE_WRITE_LOG_READ_BACK_CACHE_SIZE_BYTES, size as f64);
+}
+
 register_convex_counter!(
     VIRTUAL_TABLE_GET_REQUESTS_TOTAL,
     "Number of times virtual table get path is called"
@@ -872,6 +1000,7 @@ pub enum CompactionReason {
     SmallSegments,
     LargeSegments,
     Deletes,
+    Requested,
 }
 
 impl CompactionReason {
@@ -880,6 +1009,7 @@ impl CompactionReason {
             CompactionReason::SmallSegments => "small",
             CompactionReason::LargeSegments => "large",
             CompactionReason::Deletes => "deletes",
+            CompactionReason::Requested => "requested",
         };
         StaticMetricLabel::new(COMPACTION_REASON_LABEL, label)
     }
@@ -1039,3 +1169,45 @@ register_convex_histogram!(
 pub fn log_list_snapshot_page_documents(num_docs: usize) {
     log_distribution(&LIST_SNAPSHOT_PAGE_DOCUMENTS, num_docs as f64);
 }
+
+register_convex_counter!(
+    DATABASE_DOCUMENT_CACHE_QUERIES_TOTAL,
+    "Number of index range reads looked up in the document cache",
+    &["hit", "table"]
+);
+pub fn log_document_cache_query(table_name: &TableName, hit: bool) {
+    log_counter_with_labels(
+        &DATABASE_DOCUMENT_CACHE_QUERIES_TOTAL,
+        1,
+        vec![
+            StaticMetricLabel::new("hit", hit.as_label()),
+            StaticMetricLabel::new("table", table_name.to_string()),
+        ],
+    );
+}
+
+register_convex_counter!(
+    DATABASE_DOCUMENT_CACHE_EVICTIONS_TOTAL,
+    "Number of entries evicted from the document cache",
+    &["reason"]
+);
+pub fn log_document_cache_eviction(reason: &'static str) {
+    log_counter_with_labels(
+        &DATABASE_DOCUMENT_CACHE_EVICTIONS_TOTAL,
+        1,
+        vec![StaticMetricLabel::new("reason", reason)],
+    );
+}
+
+register_convex_gauge!(
+    DATABASE_DOCUMENT_CACHE_SIZE_BYTES,
+    "Size of the documents in the document cache"
+);
+register_convex_gauge!(
+    DATABASE_DOCUMENT_CACHE_ENTRIES_TOTAL,
+    "Number of index ranges in the document cache"
+);
+pub fn log_document_cache_size(size: usize, num_entries: usize) {
+    log_gauge(&DATABASE_DOCUMENT_CACHE_SIZE_BYTES, size as f64);
+    log_gauge(&DATABASE_DOCUMENT_CACHE_ENTRIES_TOTAL, num_entries as f64);
+}
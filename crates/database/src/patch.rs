@@ -1,13 +1,28 @@
-use std::collections::BTreeMap;
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+};
 
+use anyhow::Context;
 use common::{
+    json::JsonExpression,
+    paths::FieldPath,
+    query::Expression,
     types::MaybeValue,
     value::{
         ConvexObject,
         FieldName,
     },
 };
+use errors::ErrorMetadata;
+use serde::Deserialize;
 use serde_json::Value as JsonValue;
+use value::{
+    ConvexArray,
+    ConvexValue,
+    IdentifierFieldName,
+    Namespace,
+};
 
 /// A object used in patch. Similar to GenericObject but also allows top level
 /// undefined fields.
@@ -69,6 +84,246 @@ impl From<ConvexObject> for PatchValue {
     }
 }
 
+/// One update to a possibly nested field of a document.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PatchOperation {
+    /// Sets the field, creating any missing objects along the path.
+    Set { path: FieldPath, value: ConvexValue },
+    /// Removes the field if it exists.
+    Unset { path: FieldPath },
+    /// Appends values to the array at the field, creating the array if the
+    /// field is unset.
+    Push {
+        path: FieldPath,
+        values: Vec<ConvexValue>,
+    },
+    /// Removes the elements of the array at the field that match.
+    Pull {
+        path: FieldPath,
+        predicate: PullPredicate,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PullPredicate {
+    Equals(ConvexValue),
+    /// A filter expression evaluated with the element as the document. Only
+    /// object elements can match.
+    Filter(Expression),
+}
+
+impl PullPredicate {
+    fn matches(&self, element: &ConvexValue) -> anyhow::Result<bool> {
+        match (self, element) {
+            (PullPredicate::Equals(value), element) => Ok(value == element),
+            (PullPredicate::Filter(expression), ConvexValue::Object(object)) => {
+                Ok(expression.eval(object)? == MaybeValue(Some(ConvexValue::from(true))))
+            },
+            (PullPredicate::Filter(_), _) => Ok(false),
+        }
+    }
+}
+
+/// A patch that updates nested fields without the caller rebuilding the
+/// enclosing objects. The whole document is still read and rewritten, but only
+/// the top-level fields the patch touches are validated against the schema.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeepPatch {
+    operations: Vec<PatchOperation>,
+}
+
+impl DeepPatch {
+    pub fn new(operations: Vec<PatchOperation>) -> anyhow::Result<Self> {
+        for operation in &operations {
+            let first = &operation.path().fields()[0];
+            anyhow::ensure!(
+                !first.is_system(),
+                invalid_deep_patch(format!("System field `{first}` can't be patched"))
+            );
+        }
+        Ok(Self { operations })
+    }
+
+    pub fn apply(self, original: ConvexObject) -> anyhow::Result<ConvexObject> {
+        self.operations
+            .into_iter()
+            .try_fold(original, |object, operation| operation.apply(object))
+    }
+
+    /// The top-level fields the patch can change. Only these need to be
+    /// validated against the schema.
+    pub fn touched_fields(&self) -> BTreeSet<IdentifierFieldName> {
+        self.operations
+            .iter()
+            .map(|operation| operation.path().fields()[0].clone())
+            .collect()
+    }
+}
+
+impl PatchOperation {
+    fn path(&self) -> &FieldPath {
+        match self {
+            PatchOperation::Set { path, .. }
+            | PatchOperation::Unset { path }
+            | PatchOperation::Push { path, .. }
+            | PatchOperation::Pull { path, .. } => path,
+        }
+    }
+
+    fn apply(self, object: ConvexObject) -> anyhow::Result<ConvexObject> {
+        match self {
+            PatchOperation::Set { path, value } => {
+                update_path(object, path.fields(), &mut |_| Ok(Some(value.clone())))
+            },
+            PatchOperation::Unset { path } => {
+                if object.get_path(&path).is_none() {
+                    return Ok(object);
+                }
+                update_path(object, path.fields(), &mut |_| Ok(None))
+            },
+            PatchOperation::Push { path, values } => {
+                update_path(object, path.fields(), &mut |current| {
+                    let mut elements = match current {
+                        None => vec![],
+                        Some(ConvexValue::Array(array)) => Vec::from(array),
+                        Some(value) => anyhow::bail!(not_an_array(&path, &value)),
+                    };
+                    elements.extend(values.iter().cloned());
+                    Ok(Some(ConvexValue::Array(ConvexArray::try_from(elements)?)))
+                })
+            },
+            PatchOperation::Pull { path, predicate } => {
+                if object.get_path(&path).is_none() {
+                    return Ok(object);
+                }
+                update_path(object, path.fields(), &mut |current| {
+                    let array = match current {
+                        Some(ConvexValue::Array(array)) => array,
+                        Some(value) => anyhow::bail!(not_an_array(&path, &value)),
+                        None => return Ok(None),
+                    };
+                    let mut elements = vec![];
+                    for element in array {
+                        if !predicate.matches(&element)? {
+                            elements.push(element);
+                        }
+                    }
+                    Ok(Some(ConvexValue::Array(ConvexArray::try_from(elements)?)))
+                })
+            },
+        }
+    }
+}
+
+/// Replaces the value at `path` with `update(current value)`, creating objects
+/// for missing fields along the path.
+fn update_path(
+    object: ConvexObject,
+    path: &[IdentifierFieldName],
+    update: &mut impl FnMut(Option<ConvexValue>) -> anyhow::Result<Option<ConvexValue>>,
+) -> anyhow::Result<ConvexObject> {
+    let (first, rest) = path.split_first().context("Empty field path")?;
+    let field_name = FieldName::from(first.clone());
+    let mut fields = BTreeMap::from(object);
+    let current = fields.remove(&field_name);
+    let updated = if rest.is_empty() {
+        update(current)?
+    } else {
+        let nested = match current {
+            None => ConvexObject::empty(),
+            Some(ConvexValue::Object(nested)) => nested,
+            Some(value) => anyhow::bail!(invalid_deep_patch(format!(
+                "Can't update a field inside `{first}` because it is a {}, not an object",
+                value.type_name()
+            ))),
+        };
+        Some(ConvexValue::Object(update_path(nested, rest, update)?))
+    };
+    if let Some(updated) = updated {
+        fields.insert(field_name, updated);
+    }
+    fields.try_into()
+}
+
+fn not_an_array(path: &FieldPath, value: &ConvexValue) -> ErrorMetadata {
+    invalid_deep_patch(format!(
+        "Can't push to or pull from `{path}` because it is a {}, not an array",
+        value.type_name()
+    ))
+}
+
+fn invalid_deep_patch(msg: String) -> ErrorMetadata {
+    ErrorMetadata::bad_request("InvalidDeepPatch", msg)
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+enum PatchOperationJson {
+    Set {
+        path: String,
+        value: JsonValue,
+    },
+    Unset {
+        path: String,
+    },
+    Push {
+        path: String,
+        values: Vec<JsonValue>,
+    },
+    Pull {
+        path: String,
+        value: Option<JsonValue>,
+        filter: Option<JsonExpression>,
+    },
+}
+
+impl TryFrom<JsonValue> for DeepPatch {
+    type Error = anyhow::Error;
+
+    fn try_from(json_value: JsonValue) -> anyhow::Result<Self> {
+        let operations: Vec<PatchOperationJson> = serde_json::from_value(json_value)?;
+        let operations = operations
+            .into_iter()
+            .map(|operation| {
+                anyhow::Ok(match operation {
+                    PatchOperationJson::Set { path, value } => PatchOperation::Set {
+                        path: path.parse()?,
+                        value: value.try_into()?,
+                    },
+                    PatchOperationJson::Unset { path } => PatchOperation::Unset {
+                        path: path.parse()?,
+                    },
+                    PatchOperationJson::Push { path, values } => PatchOperation::Push {
+                        path: path.parse()?,
+                        values: values
+                            .into_iter()
+                            .map(ConvexValue::try_from)
+                            .collect::<anyhow::Result<_>>()?,
+                    },
+                    PatchOperationJson::Pull {
+                        path,
+                        value,
+                        filter,
+                    } => {
+                        let predicate = match (value, filter) {
+                            (Some(value), None) => PullPredicate::Equals(value.try_into()?),
+                            (None, Some(filter)) => PullPredicate::Filter(filter.try_into()?),
+                            _ => anyhow::bail!(invalid_deep_patch(
+                                "A pull needs exactly one of `value` or `filter`".to_string()
+                            )),
+                        };
+                        PatchOperation::Pull {
+                            path: path.parse()?,
+                            predicate,
+                        }
+                    },
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Self::new(operations)
+    }
+}
+
 #[macro_export]
 /// Create an patch object from field/value pairs.
 macro_rules! patch_value {
@@ -92,11 +347,14 @@ macro_rules! patch_value {
 #[cfg(test)]
 mod tests {
     use common::assert_obj;
+    use serde_json::json;
     use value::{
         assert_val,
         ConvexObject,
     };
 
+    use super::DeepPatch;
+
     #[test]
     fn test_apply() -> anyhow::Result<()> {
         // Overwrite duplicate fields instead of merging sub-fields.
@@ -148,4 +406,51 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_deep_patch() -> anyhow::Result<()> {
+        let original: ConvexObject = assert_obj!(
+            "profile" => {
+                "name" => "Mr Fantastik",
+                "settings" => { "theme" => "light" },
+            },
+            "tags" => ["a", "b", "a"],
+            "items" => [{ "done" => true }, { "done" => false }],
+        );
+        let patch = DeepPatch::try_from(json!([
+            {"op": "set", "path": "profile.settings.theme", "value": "dark"},
+            {"op": "set", "path": "profile.address.city", "value": "Metropolis"},
+            {"op": "unset", "path": "profile.name"},
+            {"op": "push", "path": "tags", "values": ["c"]},
+            {"op": "pull", "path": "tags", "value": "a"},
+            {"op": "pull", "path": "items", "filter": {"$eq": [{"$field": "done"}, {"$literal": true}]}},
+            {"op": "push", "path": "history", "values": [1]},
+        ]))?;
+        assert_eq!(
+            patch.touched_fields().len(),
+            4,
+            "{:?}",
+            patch.touched_fields()
+        );
+        let expected = assert_obj!(
+            "history" => [1.0],
+            "items" => [{ "done" => false }],
+            "profile" => {
+                "address" => { "city" => "Metropolis" },
+                "settings" => { "theme" => "dark" },
+            },
+            "tags" => ["b", "c"],
+        );
+        assert_eq!(patch.apply(original.clone())?, expected);
+
+        // Pushing to a field that isn't an array fails.
+        let patch = DeepPatch::try_from(json!([
+            {"op": "push", "path": "profile.name", "values": ["c"]},
+        ]))?;
+        assert!(patch.apply(original).is_err());
+
+        // System fields can't be patched.
+        assert!(DeepPatch::try_from(json!([{"op": "unset", "path": "_id"}])).is_err());
+        Ok(())
+    }
 }
@@ -10,6 +10,10 @@ use common::{
         TabletIndexName,
     },
 };
+use search::{
+    Highlighter,
+    SearchFacets,
+};
 
 use super::{
     DeveloperIndexRangeResponse,
@@ -17,7 +21,10 @@ use super::{
     QueryStream,
     QueryStreamNext,
 };
-use crate::Transaction;
+use crate::{
+    transaction::IndexRangeRequest,
+    Transaction,
+};
 
 // We can likely be smarter here, like start with a medium limit
 // and then dynamically adjust it up or down depending on latency and data
@@ -82,4 +89,16 @@ impl QueryStream for Filter {
     fn printable_index_name(&self) -> &IndexName {
         self.inner.printable_index_name()
     }
+
+    fn highlighter(&self) -> Option<&Highlighter> {
+        self.inner.highlighter()
+    }
+
+    fn facets(&self) -> Option<&SearchFacets> {
+        self.inner.facets()
+    }
+
+    fn prefetch_request(&self, max_rows: usize) -> Option<IndexRangeRequest> {
+        self.inner.prefetch_request(max_rows)
+    }
 }
@@ -0,0 +1,458 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use common::{
+    bootstrap_model::index::database_index::IndexedFields,
+    components::{
+        ComponentId,
+        ComponentPath,
+    },
+    interval::Interval,
+    query::{
+        IndexRange,
+        Order,
+        Query,
+        QuerySource,
+    },
+    runtime::Runtime,
+    types::{
+        IndexName,
+        StableIndexName,
+        TabletIndexName,
+    },
+};
+use errors::ErrorMetadata;
+use indexing::backend_in_memory_indexes::BatchKey;
+use maplit::btreemap;
+use value::{
+    ConvexObject,
+    ConvexValue,
+    Decimal128,
+    FieldPath,
+    Size,
+    TableNamespace,
+};
+
+use super::{
+    DeveloperIndexRangeResponse,
+    TableFilter,
+    MAX_QUERY_FETCH,
+};
+use crate::{
+    bootstrap_model::user_facing::index_range_batch,
+    transaction::IndexRangeRequest,
+    IndexModel,
+    Transaction,
+};
+
+/// An aggregate computed over each group's documents.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Aggregate {
+    Count,
+    /// The sum of the field's numeric values. Documents where the field is
+    /// missing or isn't a number are skipped.
+    Sum(FieldPath),
+    /// The smallest value of the field, in Convex's value ordering. Documents
+    /// where the field is missing are skipped.
+    Min(FieldPath),
+    Max(FieldPath),
+}
+
+/// The documents of an index range that share the same values for the
+/// grouped fields.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Group {
+    /// The values of the grouped fields, or `None` where they're missing.
+    pub key: Vec<Option<ConvexValue>>,
+    /// One value per aggregate, or `None` if no document in the group had a
+    /// value for it.
+    pub values: Vec<Option<ConvexValue>>,
+}
+
+impl Group {
+    fn size(&self) -> usize {
+        self.key
+            .iter()
+            .chain(&self.values)
+            .map(|value| value.as_ref().map_or(1, |value| value.size()))
+            .sum()
+    }
+}
+
+/// Groups the documents of an index range by a prefix of the index's fields
+/// and aggregates each group in a single scan of the index. Since the grouped
+/// fields are a prefix of the index, each group's documents are contiguous
+/// and the scan only needs to hold one group at a time.
+///
+/// The documents scanned don't count towards the transaction's read limits.
+/// Each returned group counts as one document read instead, so summarizing a
+/// large table is limited by how many groups it has rather than its size.
+pub struct GroupByQuery {
+    namespace: TableNamespace,
+    stable_index_name: StableIndexName,
+    printable_index_name: IndexName,
+    indexed_fields: IndexedFields,
+    interval: Interval,
+    order: Order,
+    group_fields: Vec<FieldPath>,
+    aggregates: Vec<Aggregate>,
+}
+
+impl GroupByQuery {
+    pub fn new<RT: Runtime>(
+        tx: &mut Transaction<RT>,
+        namespace: TableNamespace,
+        query: Query,
+        group_fields: Vec<FieldPath>,
+        aggregates: Vec<Aggregate>,
+        table_filter: TableFilter,
+    ) -> anyhow::Result<Self> {
+        if !query.operators.is_empty() {
+            anyhow::bail!(invalid_group_by(
+                "groupBy() aggregates whole index ranges, so it can't be used after filter() or \
+                 limit()."
+                    .to_string()
+            ));
+        }
+        let (index_name, range, order) = match query.source {
+            QuerySource::FullTableScan(full_table_scan) => (
+                IndexName::by_creation_time(full_table_scan.table_name),
+                vec![],
+                full_table_scan.order,
+            ),
+            QuerySource::IndexRange(index_range) => {
+                (index_range.index_name, index_range.range, index_range.order)
+            },
+            QuerySource::Search(_) => anyhow::bail!(invalid_group_by(
+                "groupBy() isn't supported for search queries.".to_string()
+            )),
+        };
+        let stable_index_name =
+            IndexModel::new(tx).stable_index_name(namespace, &index_name, table_filter)?;
+        let indexed_fields = IndexModel::new(tx).indexed_fields(&stable_index_name, &index_name)?;
+        if group_fields.len() > indexed_fields.len()
+            || group_fields
+                .iter()
+                .zip(indexed_fields.iter())
+                .any(|(a, b)| a != b)
+        {
+            anyhow::bail!(invalid_group_by(format!(
+                "groupBy() fields must be a prefix of the fields of index {index_name}: \
+                 {indexed_fields}"
+            )));
+        }
+        let interval = IndexRange {
+            index_name: index_name.clone(),
+            range,
+            order,
+        }
+        .compile(indexed_fields.clone())?;
+        Ok(Self {
+            namespace,
+            stable_index_name,
+            printable_index_name: index_name,
+            indexed_fields,
+            interval,
+            order,
+            group_fields,
+            aggregates,
+        })
+    }
+
+    pub async fn execute<RT: Runtime>(
+        self,
+        tx: &mut Transaction<RT>,
+    ) -> anyhow::Result<Vec<Group>> {
+        group_by_batch(btreemap! { 0 => self }, tx)
+            .await
+            .remove(&0)
+            .context("batch_key missing")?
+    }
+
+    fn finish_group<RT: Runtime>(
+        &self,
+        tx: &mut Transaction<RT>,
+        component_path: &ComponentPath,
+        (key, accumulators): (Vec<Option<ConvexValue>>, Vec<Accumulator>),
+        is_virtual_table: bool,
+    ) -> anyhow::Result<Group> {
+        let group = Group {
+            key,
+            values: accumulators.into_iter().map(Accumulator::finish).collect(),
+        };
+        tx.reads.record_read_document(
+            component_path.clone(),
+            self.printable_index_name.table().clone(),
+            group.size(),
+            &tx.usage_tracker,
+            is_virtual_table,
+        )?;
+        Ok(group)
+    }
+}
+
+/// The state of one query's scan in `group_by_batch`.
+struct GroupByScan {
+    query: GroupByQuery,
+    tablet_index_name: TabletIndexName,
+    component_path: ComponentPath,
+    is_virtual_table: bool,
+    is_system_table: bool,
+    groups: Vec<Group>,
+    current: Option<(Vec<Option<ConvexValue>>, Vec<Accumulator>)>,
+    unfetched_interval: Interval,
+}
+
+impl GroupByScan {
+    fn new<RT: Runtime>(
+        tx: &mut Transaction<RT>,
+        query: GroupByQuery,
+    ) -> anyhow::Result<Option<Self>> {
+        let Some(tablet_index_name) = query.stable_index_name.tablet_index_name().cloned() else {
+            // The table doesn't exist, so it has no groups.
+            return Ok(None);
+        };
+        let table_name = query.printable_index_name.table();
+        let component_path = tx.must_component_path(ComponentId::from(query.namespace))?;
+        let is_virtual_table = tx.virtual_system_mapping().is_virtual_table(table_name);
+        let is_system_table = table_name.is_system() && !is_virtual_table;
+        let unfetched_interval = query.interval.clone();
+        Ok(Some(Self {
+            query,
+            tablet_index_name,
+            component_path,
+            is_virtual_table,
+            is_system_table,
+            groups: vec![],
+            current: None,
+            unfetched_interval,
+        }))
+    }
+
+    fn request(&self) -> IndexRangeRequest {
+        IndexRangeRequest {
+            stable_index_name: self.query.stable_index_name.clone(),
+            interval: self.unfetched_interval.clone(),
+            order: self.query.order,
+            max_rows: MAX_QUERY_FETCH,
+            version: None,
+        }
+    }
+
+    fn process_page<RT: Runtime>(
+        &mut self,
+        tx: &mut Transaction<RT>,
+        DeveloperIndexRangeResponse { page, cursor }: DeveloperIndexRangeResponse,
+    ) -> anyhow::Result<()> {
+        let mut scanned_bytes = 0;
+        for (_, document, _) in page {
+            scanned_bytes += document.size();
+            let value = document.value();
+            let key: Vec<_> = self
+                .query
+                .group_fields
+                .iter()
+                .map(|field| value.get_path(field).cloned())
+                .collect();
+            if self
+                .current
+                .as_ref()
+                .is_none_or(|(current_key, _)| *current_key != key)
+            {
+                if let Some(group) = self.current.take() {
+                    let group = self.query.finish_group(
+                        tx,
+                        &self.component_path,
+                        group,
+                        self.is_virtual_table,
+                    )?;
+                    self.groups.push(group);
+                }
+                let accumulators = self.query.aggregates.iter().map(Accumulator::new).collect();
+                self.current = Some((key, accumulators));
+            }
+            let (_, accumulators) = self.current.as_mut().expect("Current group was just set");
+            for accumulator in accumulators {
+                accumulator.add(value)?;
+            }
+        }
+        // Documents scanned are billed as bandwidth even though they only
+        // count towards read limits as groups.
+        tx.usage_tracker.track_database_egress_size(
+            self.component_path.clone(),
+            self.query.printable_index_name.table().to_string(),
+            scanned_bytes as u64,
+            self.is_system_table,
+        );
+        (_, self.unfetched_interval) = self.unfetched_interval.split(cursor, self.query.order);
+        Ok(())
+    }
+
+    fn finish<RT: Runtime>(mut self, tx: &mut Transaction<RT>) -> anyhow::Result<Vec<Group>> {
+        if let Some(group) = self.current.take() {
+            let group =
+                self.query
+                    .finish_group(tx, &self.component_path, group, self.is_virtual_table)?;
+            self.groups.push(group);
+        }
+        tx.reads.record_indexed_directly(
+            self.tablet_index_name,
+            self.query.indexed_fields.clone(),
+            self.query.interval.clone(),
+        )?;
+        Ok(self.groups)
+    }
+}
+
+/// Runs several group-by queries together. Each round fetches the next page
+/// of every unfinished scan in one `index_range_batch`, so the scans read
+/// from persistence concurrently instead of one after another. Pages are
+/// processed in batch key order, so the reads recorded are deterministic.
+pub async fn group_by_batch<RT: Runtime>(
+    batch: BTreeMap<BatchKey, GroupByQuery>,
+    tx: &mut Transaction<RT>,
+) -> BTreeMap<BatchKey, anyhow::Result<Vec<Group>>> {
+    let batch_size = batch.len();
+    let mut results = BTreeMap::new();
+    let mut scans = BTreeMap::new();
+    for (batch_key, query) in batch {
+        match GroupByScan::new(tx, query) {
+            Ok(Some(scan)) if scan.unfetched_interval.is_empty() => {
+                results.insert(batch_key, scan.finish(tx));
+            },
+            Ok(Some(scan)) => {
+                scans.insert(batch_key, scan);
+            },
+            Ok(None) => {
+                results.insert(batch_key, Ok(vec![]));
+            },
+            Err(e) => {
+                results.insert(batch_key, Err(e));
+            },
+        }
+    }
+    while !scans.is_empty() {
+        let requests = scans
+            .iter()
+            .map(|(batch_key, scan)| (*batch_key, scan.request()))
+            .collect();
+        let mut responses = index_range_batch(tx, requests).await;
+        let mut next_scans = BTreeMap::new();
+        for (batch_key, mut scan) in scans {
+            let result: anyhow::Result<_> = try {
+                let response = responses
+                    .remove(&batch_key)
+                    .context("batch_key missing")??;
+                scan.process_page(tx, response)?;
+            };
+            match result {
+                Err(e) => {
+                    results.insert(batch_key, Err(e));
+                },
+                Ok(()) if scan.unfetched_interval.is_empty() => {
+                    results.insert(batch_key, scan.finish(tx));
+                },
+                Ok(()) => {
+                    next_scans.insert(batch_key, scan);
+                },
+            }
+        }
+        scans = next_scans;
+    }
+    assert_eq!(results.len(), batch_size);
+    results
+}
+
+enum Accumulator {
+    Count(u64),
+    Sum(FieldPath, Option<Sum>),
+    Min(FieldPath, Option<ConvexValue>),
+    Max(FieldPath, Option<ConvexValue>),
+}
+
+enum Sum {
+    Int64(i64),
+    Float64(f64),
+    Decimal(Decimal128),
+}
+
+impl Accumulator {
+    fn new(aggregate: &Aggregate) -> Self {
+        match aggregate {
+            Aggregate::Count => Self::Count(0),
+            Aggregate::Sum(field) => Self::Sum(field.clone(), None),
+            Aggregate::Min(field) => Self::Min(field.clone(), None),
+            Aggregate::Max(field) => Self::Max(field.clone(), None),
+        }
+    }
+
+    fn add(&mut self, document: &ConvexObject) -> anyhow::Result<()> {
+        match self {
+            Self::Count(count) => *count += 1,
+            Self::Sum(field, sum) => {
+                let Some(value) = document.get_path(field) else {
+                    return Ok(());
+                };
+                *sum = match (sum.take(), value) {
+                    (None, ConvexValue::Int64(n)) => Some(Sum::Int64(*n)),
+                    (None, ConvexValue::Float64(n)) => Some(Sum::Float64(*n)),
+                    (None, ConvexValue::Decimal(n)) => Some(Sum::Decimal(*n)),
+                    (Some(Sum::Int64(a)), ConvexValue::Int64(b)) => {
+                        Some(Sum::Int64(a.checked_add(*b).ok_or_else(|| {
+                            invalid_group_by(format!("The sum of {field} overflowed int64."))
+                        })?))
+                    },
+                    (Some(Sum::Int64(a)), ConvexValue::Float64(b)) => {
+                        Some(Sum::Float64(a as f64 + b))
+                    },
+                    (Some(Sum::Float64(a)), ConvexValue::Int64(b)) => {
+                        Some(Sum::Float64(a + *b as f64))
+                    },
+                    (Some(Sum::Float64(a)), ConvexValue::Float64(b)) => Some(Sum::Float64(a + b)),
+                    (Some(Sum::Decimal(a)), ConvexValue::Decimal(b)) => {
+                        Some(Sum::Decimal(a.checked_add(*b)?))
+                    },
+                    (Some(Sum::Decimal(_)), ConvexValue::Int64(_) | ConvexValue::Float64(_))
+                    | (Some(Sum::Int64(_) | Sum::Float64(_)), ConvexValue::Decimal(_)) => {
+                        anyhow::bail!(invalid_group_by(format!(
+                            "Can't sum {field} since it mixes decimals with other numbers."
+                        )))
+                    },
+                    // Values that aren't numbers are skipped.
+                    (sum, _) => sum,
+                };
+            },
+            Self::Min(field, min) => {
+                if let Some(value) = document.get_path(field)
+                    && min.as_ref().is_none_or(|min| value < min)
+                {
+                    *min = Some(value.clone());
+                }
+            },
+            Self::Max(field, max) => {
+                if let Some(value) = document.get_path(field)
+                    && max.as_ref().is_none_or(|max| value > max)
+                {
+                    *max = Some(value.clone());
+                }
+            },
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Option<ConvexValue> {
+        match self {
+            Self::Count(count) => Some(ConvexValue::from(count as f64)),
+            Self::Sum(_, sum) => sum.map(|sum| match sum {
+                Sum::Int64(n) => ConvexValue::from(n),
+                Sum::Float64(n) => ConvexValue::from(n),
+                Sum::Decimal(n) => ConvexValue::from(n),
+            }),
+            Self::Min(_, value) | Self::Max(_, value) => value,
+        }
+    }
+}
+
+fn invalid_group_by(msg: String) -> ErrorMetadata {
+    ErrorMetadata::bad_request("InvalidGroupByQuery", msg)
+}
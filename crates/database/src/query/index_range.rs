@@ -27,6 +27,10 @@ use common::{
     },
     version::Version,
 };
+use search::{
+    Highlighter,
+    SearchFacets,
+};
 use tokio::task;
 use value::TableNamespace;
 
@@ -328,6 +332,30 @@ impl QueryStream for IndexRange {
     fn printable_index_name(&self) -> &IndexName {
         &self.printable_index_name
     }
+
+    fn highlighter(&self) -> Option<&Highlighter> {
+        None
+    }
+
+    fn facets(&self) -> Option<&SearchFacets> {
+        None
+    }
+
+    fn prefetch_request(&self, max_rows: usize) -> Option<IndexRangeRequest> {
+        if !self.page.is_empty()
+            || self.unfetched_interval.is_empty()
+            || self.tablet_index_name().is_none()
+        {
+            return None;
+        }
+        Some(IndexRangeRequest {
+            stable_index_name: self.stable_index_name.clone(),
+            interval: self.unfetched_interval.clone(),
+            order: self.order,
+            max_rows: max_rows.clamp(1, MAX_QUERY_FETCH),
+            version: self.version.clone(),
+        })
+    }
 }
 
 impl Drop for IndexRange {
@@ -345,6 +373,7 @@ impl Drop for IndexRange {
 }
 
 /// An interval between two optional cursors.
+#[derive(Clone)]
 pub struct CursorInterval {
     pub curr_exclusive: Option<CursorPosition>,
     pub end_inclusive: Option<CursorPosition>,
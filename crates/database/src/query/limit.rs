@@ -9,6 +9,10 @@ use common::{
         TabletIndexName,
     },
 };
+use search::{
+    Highlighter,
+    SearchFacets,
+};
 
 use super::{
     DeveloperIndexRangeResponse,
@@ -17,7 +21,10 @@ use super::{
     QueryStreamNext,
     DEFAULT_QUERY_PREFETCH,
 };
-use crate::Transaction;
+use crate::{
+    transaction::IndexRangeRequest,
+    Transaction,
+};
 
 /// See Query.limit().
 pub(super) struct Limit {
@@ -80,4 +87,20 @@ impl QueryStream for Limit {
     fn printable_index_name(&self) -> &IndexName {
         self.inner.printable_index_name()
     }
+
+    fn highlighter(&self) -> Option<&Highlighter> {
+        self.inner.highlighter()
+    }
+
+    fn facets(&self) -> Option<&SearchFacets> {
+        self.inner.facets()
+    }
+
+    fn prefetch_request(&self, max_rows: usize) -> Option<IndexRangeRequest> {
+        let remaining = self.limit.saturating_sub(self.rows_emitted);
+        if remaining == 0 {
+            return None;
+        }
+        self.inner.prefetch_request(cmp::min(max_rows, remaining))
+    }
 }
@@ -1,6 +1,7 @@
 use std::{
     collections::BTreeMap,
     marker::PhantomData,
+    mem,
     ops::Deref,
 };
 
@@ -29,6 +30,7 @@ use common::{
     runtime::Runtime,
     types::{
         IndexName,
+        StableIndexName,
         TabletIndexName,
         WriteTimestamp,
     },
@@ -41,6 +43,11 @@ use futures::{
 };
 use indexing::backend_in_memory_indexes::BatchKey;
 use maplit::btreemap;
+use search::{
+    Highlighter,
+    SearchFacets,
+    SearchHighlights,
+};
 use value::{
     val,
     TableNamespace,
@@ -54,6 +61,7 @@ use self::{
     },
     limit::Limit,
     search_query::SearchQuery,
+    table_snapshot::TableSnapshotScan,
 };
 use crate::{
     bootstrap_model::user_facing::index_range_batch,
@@ -63,11 +71,24 @@ use crate::{
 };
 
 mod filter;
+mod group_by;
 mod index_range;
 mod limit;
+mod sample;
 mod search_query;
+mod table_snapshot;
 
+pub use group_by::{
+    group_by_batch,
+    Aggregate,
+    Group,
+    GroupByQuery,
+};
 pub use index_range::soft_data_limit;
+pub use sample::{
+    sample_documents,
+    MAX_SAMPLE_SIZE,
+};
 
 // Even in the presence of large prefetch hints, we should never fetch too much
 // data at once.
@@ -124,6 +145,19 @@ trait QueryStream: Send {
 
     /// For logging. All queries have an index name.
     fn printable_index_name(&self) -> &IndexName;
+
+    /// Finds where a search query matched its results, if the query asked for
+    /// highlights and has started returning results.
+    fn highlighter(&self) -> Option<&Highlighter>;
+
+    /// The facet counts of a search query that asked for them, once it has
+    /// started returning results.
+    fn facets(&self) -> Option<&SearchFacets>;
+
+    /// The index range `next` would fetch first, for warming the
+    /// transaction's index cache before the query runs. `None` if the query
+    /// doesn't start by fetching an index range.
+    fn prefetch_request(&self, max_rows: usize) -> Option<IndexRangeRequest>;
 }
 
 pub struct DeveloperIndexRangeResponse {
@@ -416,19 +450,38 @@ impl<RT: Runtime> DeveloperQuery<RT> {
         };
 
         let mut cur_node = match query.source {
-            QuerySource::FullTableScan(full_table_scan) => QueryNode::IndexRange(IndexRange::new(
-                namespace,
-                stable_index_name,
-                index_name,
-                Interval::all(),
-                full_table_scan.order,
-                indexed_fields,
-                cursor_interval,
-                maximum_rows_read,
-                maximum_bytes_read,
-                should_compute_split_cursor,
-                version,
-            )),
+            QuerySource::FullTableScan(full_table_scan) => {
+                // Tables that don't exist might be snapshot aliases, which can
+                // only be read with a full table scan.
+                let may_be_snapshot = matches!(stable_index_name, StableIndexName::Missing(_))
+                    && !index_name.table().is_system();
+                let index_range = IndexRange::new(
+                    namespace,
+                    stable_index_name,
+                    index_name.clone(),
+                    Interval::all(),
+                    full_table_scan.order,
+                    indexed_fields,
+                    cursor_interval.clone(),
+                    maximum_rows_read,
+                    maximum_bytes_read,
+                    should_compute_split_cursor,
+                    version,
+                );
+                if may_be_snapshot {
+                    QueryNode::TableSnapshot(Box::new(TableSnapshotScan::new(
+                        namespace,
+                        index_name,
+                        full_table_scan.order,
+                        cursor_interval,
+                        maximum_rows_read,
+                        maximum_bytes_read,
+                        index_range,
+                    )))
+                } else {
+                    QueryNode::IndexRange(index_range)
+                }
+            },
             QuerySource::IndexRange(index_range) => {
                 let order = index_range.order;
                 let interval = index_range.compile(indexed_fields.clone())?;
@@ -534,6 +587,19 @@ impl<RT: Runtime> DeveloperQuery<RT> {
     pub fn printable_index_name(&self) -> &IndexName {
         self.root.printable_index_name()
     }
+
+    /// Where the search matched a document this query returned, if it's a
+    /// search query that asked for highlights.
+    pub fn search_highlights(&self, document: &DeveloperDocument) -> Option<SearchHighlights> {
+        let highlighter = self.root.highlighter()?;
+        Some(highlighter.highlight(document.value()))
+    }
+
+    /// The facet counts of this query's results, if it's a search query that
+    /// asked for them.
+    pub fn search_facets(&self) -> Option<&SearchFacets> {
+        self.root.facets()
+    }
 }
 
 impl<RT: Runtime> ResolvedQuery<RT> {
@@ -580,21 +646,48 @@ pub fn query_batch_next<'a, RT: Runtime>(
     tx: &'a mut Transaction<RT>,
 ) -> BoxFuture<'a, BTreeMap<BatchKey, anyhow::Result<Option<(DeveloperDocument, WriteTimestamp)>>>>
 {
-    query_batch_next_(batch, tx).boxed()
+    query_batch_next_(batch, vec![], tx).boxed()
+}
+
+/// Like `query_batch_next`, but also fetches the first page of each query in
+/// `prefetches`, sized by its prefetch hint, alongside the batch's first round
+/// of reads, so the batch waits on them too. The prefetched documents aren't
+/// returned, and the prefetched ranges aren't recorded in the read set: they
+/// only warm the transaction's index cache, so later reads of the same ranges
+/// are cache hits, and those reads are what's recorded.
+pub fn query_batch_next_with_prefetches<'a, RT: Runtime>(
+    batch: BTreeMap<BatchKey, (&'a mut DeveloperQuery<RT>, Option<usize>)>,
+    prefetches: Vec<(DeveloperQuery<RT>, Option<usize>)>,
+    tx: &'a mut Transaction<RT>,
+) -> BoxFuture<'a, BTreeMap<BatchKey, anyhow::Result<Option<(DeveloperDocument, WriteTimestamp)>>>>
+{
+    query_batch_next_(batch, prefetches, tx).boxed()
 }
 
 pub async fn query_batch_next_<RT: Runtime>(
     mut batch: BTreeMap<BatchKey, (&mut DeveloperQuery<RT>, Option<usize>)>,
+    prefetches: Vec<(DeveloperQuery<RT>, Option<usize>)>,
     tx: &mut Transaction<RT>,
 ) -> BTreeMap<BatchKey, anyhow::Result<Option<(DeveloperDocument, WriteTimestamp)>>> {
     let batch_size = batch.len();
+    // Prefetches use batch keys after the batch's own, and their responses
+    // are dropped once fetched.
+    let first_prefetch_key = batch.keys().next_back().map_or(0, |key| key + 1);
+    let mut prefetches: Vec<_> = prefetches
+        .into_iter()
+        .filter_map(|(query, prefetch_hint)| {
+            query
+                .root
+                .prefetch_request(prefetch_hint.unwrap_or(DEFAULT_QUERY_PREFETCH))
+        })
+        .collect();
     // Algorithm overview:
     // Call `next` on every query.
     // Accumulate fetch (IO) requests and perform them all in a batch.
     // Call `feed` on the queries with the responses from the fetch requests.
     // Repeat until all queries have returned Ready from `next`.
     let mut results = BTreeMap::new();
-    while !batch.is_empty() {
+    while !batch.is_empty() || !prefetches.is_empty() {
         let mut batch_to_feed = BTreeMap::new();
         let mut requests = BTreeMap::new();
         for (batch_key, (query, prefetch_hint)) in batch {
@@ -622,6 +715,7 @@ pub async fn query_batch_next_<RT: Runtime>(
                 },
             }
         }
+        requests.extend((first_prefetch_key..).zip(mem::take(&mut prefetches)));
         let mut responses = if requests.is_empty() {
             BTreeMap::new()
         } else {
@@ -701,6 +795,7 @@ enum QueryNode {
     Search(SearchQuery),
     Filter(Box<Filter>),
     Limit(Box<Limit>),
+    TableSnapshot(Box<TableSnapshotScan>),
 }
 
 #[async_trait]
@@ -711,6 +806,7 @@ impl QueryStream for QueryNode {
             QueryNode::Search(r) => r.cursor_position(),
             QueryNode::Filter(r) => r.cursor_position(),
             QueryNode::Limit(r) => r.cursor_position(),
+            QueryNode::TableSnapshot(r) => r.cursor_position(),
         }
     }
 
@@ -720,6 +816,7 @@ impl QueryStream for QueryNode {
             QueryNode::Search(r) => r.split_cursor_position(),
             QueryNode::Filter(r) => r.split_cursor_position(),
             QueryNode::Limit(r) => r.split_cursor_position(),
+            QueryNode::TableSnapshot(r) => r.split_cursor_position(),
         }
     }
 
@@ -729,6 +826,7 @@ impl QueryStream for QueryNode {
             Self::Search(r) => r.is_approaching_data_limit(),
             Self::Filter(r) => r.is_approaching_data_limit(),
             Self::Limit(r) => r.is_approaching_data_limit(),
+            Self::TableSnapshot(r) => r.is_approaching_data_limit(),
         }
     }
 
@@ -742,6 +840,7 @@ impl QueryStream for QueryNode {
             QueryNode::Search(r) => r.next(tx, prefetch_hint).await,
             QueryNode::Filter(r) => r.next(tx, prefetch_hint).await,
             QueryNode::Limit(r) => r.next(tx, prefetch_hint).await,
+            QueryNode::TableSnapshot(r) => r.next(tx, prefetch_hint).await,
         }
     }
 
@@ -751,6 +850,7 @@ impl QueryStream for QueryNode {
             QueryNode::Search(r) => r.feed(index_range_response),
             QueryNode::Filter(r) => r.feed(index_range_response),
             QueryNode::Limit(r) => r.feed(index_range_response),
+            QueryNode::TableSnapshot(r) => r.feed(index_range_response),
         }
     }
 
@@ -760,6 +860,7 @@ impl QueryStream for QueryNode {
             QueryNode::Search(r) => r.tablet_index_name(),
             QueryNode::Filter(r) => r.tablet_index_name(),
             QueryNode::Limit(r) => r.tablet_index_name(),
+            QueryNode::TableSnapshot(r) => r.tablet_index_name(),
         }
     }
 
@@ -769,6 +870,37 @@ impl QueryStream for QueryNode {
             QueryNode::Search(r) => r.printable_index_name(),
             QueryNode::Filter(r) => r.printable_index_name(),
             QueryNode::Limit(r) => r.printable_index_name(),
+            QueryNode::TableSnapshot(r) => r.printable_index_name(),
+        }
+    }
+
+    fn highlighter(&self) -> Option<&Highlighter> {
+        match self {
+            QueryNode::IndexRange(r) => r.highlighter(),
+            QueryNode::Search(r) => r.highlighter(),
+            QueryNode::Filter(r) => r.highlighter(),
+            QueryNode::Limit(r) => r.highlighter(),
+            QueryNode::TableSnapshot(r) => r.highlighter(),
+        }
+    }
+
+    fn facets(&self) -> Option<&SearchFacets> {
+        match self {
+            QueryNode::IndexRange(r) => r.facets(),
+            QueryNode::Search(r) => r.facets(),
+            QueryNode::Filter(r) => r.facets(),
+            QueryNode::Limit(r) => r.facets(),
+            QueryNode::TableSnapshot(r) => r.facets(),
+        }
+    }
+
+    fn prefetch_request(&self, max_rows: usize) -> Option<IndexRangeRequest> {
+        match self {
+            QueryNode::IndexRange(r) => r.prefetch_request(max_rows),
+            QueryNode::Search(r) => r.prefetch_request(max_rows),
+            QueryNode::Filter(r) => r.prefetch_request(max_rows),
+            QueryNode::Limit(r) => r.prefetch_request(max_rows),
+            QueryNode::TableSnapshot(r) => r.prefetch_request(max_rows),
         }
     }
 }
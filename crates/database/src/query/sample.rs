@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+
+use common::{
+    document::{
+        DeveloperDocument,
+        ID_FIELD_PATH,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+        QueryOperator,
+        QuerySource,
+    },
+    runtime::Runtime,
+    types::{
+        IndexName,
+        MaybeValue,
+    },
+};
+use errors::ErrorMetadata;
+use rand::Rng;
+use value::{
+    id_v6::DeveloperDocumentId,
+    InternalId,
+    TableName,
+    TableNamespace,
+};
+
+use super::{
+    query_batch_next,
+    DeveloperQuery,
+    TableFilter,
+};
+use crate::Transaction;
+
+/// The most documents a single sample can return.
+pub const MAX_SAMPLE_SIZE: usize = 1024;
+
+/// How many rounds of draws to make before settling for a smaller sample.
+/// Draws only come up empty or repeat a document when the table is small
+/// relative to the sample, so a few rounds are almost always enough.
+const MAX_SAMPLE_ROUNDS: usize = 8;
+
+/// Selects up to `n` distinct documents from a table at random without
+/// scanning it.
+///
+/// Document IDs are mostly random bytes, so the `by_id` index spreads a
+/// table's documents evenly across its key space. Each draw picks a random ID
+/// and reads the first document at or after it, which only touches a single
+/// row of the index. A document is slightly more likely to be drawn the bigger
+/// the gap before its ID, but for samples this bias is negligible.
+///
+/// Tables with at most `n` documents are returned in full.
+pub async fn sample_documents<RT: Runtime>(
+    tx: &mut Transaction<RT>,
+    namespace: TableNamespace,
+    table_name: TableName,
+    n: usize,
+    rng: &mut impl Rng,
+    table_filter: TableFilter,
+) -> anyhow::Result<Vec<DeveloperDocument>> {
+    if n > MAX_SAMPLE_SIZE {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "InvalidSampleSize",
+            format!("sample() can return at most {MAX_SAMPLE_SIZE} documents, but asked for {n}."),
+        ));
+    }
+    if tx.virtual_system_mapping().is_virtual_table(&table_name) {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "InvalidSampleQuery",
+            format!("sample() isn't supported for system table {table_name}."),
+        ));
+    }
+    let Some(table_number) = tx
+        .table_mapping()
+        .namespace(namespace)
+        .id_and_number_if_exists(&table_name)
+        .map(|id| id.table_number)
+    else {
+        return Ok(vec![]);
+    };
+    if n == 0 {
+        return Ok(vec![]);
+    }
+    if tx
+        .count(namespace, &table_name)
+        .await?
+        .is_some_and(|count| count <= n as u64)
+    {
+        let query = Query::full_table_scan(table_name, Order::Asc);
+        let mut query = DeveloperQuery::new(tx, namespace, query, table_filter)?;
+        let mut documents = vec![];
+        while let Some(document) = query.next(tx, Some(n)).await? {
+            documents.push(document);
+        }
+        return Ok(documents);
+    }
+
+    let mut sampled = BTreeMap::new();
+    for _ in 0..MAX_SAMPLE_ROUNDS {
+        let remaining = n - sampled.len();
+        if remaining == 0 {
+            break;
+        }
+        let mut draws = BTreeMap::new();
+        for i in 0..remaining {
+            let start = DeveloperDocumentId::new(table_number, InternalId(rng.random()));
+            let query = Query {
+                source: QuerySource::IndexRange(IndexRange {
+                    index_name: IndexName::by_id(table_name.clone()),
+                    range: vec![IndexRangeExpression::Gte(
+                        ID_FIELD_PATH.clone(),
+                        MaybeValue(Some(start.into())),
+                    )],
+                    order: Order::Asc,
+                }),
+                operators: vec![QueryOperator::Limit(1)],
+            };
+            draws.insert(i, DeveloperQuery::new(tx, namespace, query, table_filter)?);
+        }
+        let batch = draws
+            .iter_mut()
+            .map(|(i, query)| (*i, (query, Some(1))))
+            .collect();
+        for (_, result) in query_batch_next(batch, tx).await {
+            // A draw past the table's last ID finds nothing, and is retried in
+            // the next round.
+            if let Some((document, _)) = result? {
+                sampled.entry(document.id()).or_insert(document);
+            }
+        }
+    }
+    Ok(sampled.into_values().collect())
+}
@@ -6,6 +6,7 @@ use common::{
     query::{
         CursorPosition,
         Search,
+        SearchAfter,
         SearchVersion,
     },
     runtime::Runtime,
@@ -24,6 +25,8 @@ use errors::ErrorMetadata;
 use indexing::index_registry::index_not_found_error;
 use search::{
     CandidateRevision,
+    Highlighter,
+    SearchFacets,
     MAX_CANDIDATE_REVISIONS,
 };
 use tokio::task;
@@ -44,6 +47,7 @@ use super::{
 };
 use crate::{
     metrics,
+    transaction::IndexRangeRequest,
     Transaction,
     UserFacingModel,
 };
@@ -58,6 +62,10 @@ pub struct SearchQuery {
     query: Search,
     // Results are generated on the first call to SearchQuery::next.
     results: Option<SearchResultIterator>,
+    // Set along with `results` if the query asked for highlights.
+    highlighter: Option<Highlighter>,
+    // Set along with `results` if the query asked for facets.
+    facets: Option<SearchFacets>,
 
     /// The interval defined by the optional start and end cursors.
     /// The start cursor will move as we produce results.
@@ -76,6 +84,8 @@ impl SearchQuery {
             stable_index_name,
             query,
             results: None,
+            highlighter: None,
+            facets: None,
             cursor_interval,
             version,
         }
@@ -91,15 +101,29 @@ impl SearchQuery {
     async fn search<RT: Runtime>(
         &self,
         tx: &mut Transaction<RT>,
-    ) -> anyhow::Result<SearchResultIterator> {
+    ) -> anyhow::Result<(SearchResultIterator, Option<SearchFacets>)> {
         let search_version = self.get_cli_gated_search_version();
-        let revisions = tx
-            .search(&self.stable_index_name, &self.query, search_version)
+        let mut facet_counter = tx.search_facet_counter(&self.stable_index_name, &self.query)?;
+        let score = self.query.score_expression();
+        // Searching after the start cursor lets pagination continue past the
+        // best `MAX_CANDIDATE_REVISIONS` results. Facets and score expressions
+        // need every result on each page, so they search from the beginning.
+        let search_after = match &self.cursor_interval.curr_exclusive {
+            Some(CursorPosition::After(index_key))
+                if facet_counter.is_none() && score.is_none() =>
+            {
+                Some(SearchAfter::new(index_key.clone()))
+            },
+            _ => None,
+        };
+        let mut revisions = tx
+            .search(
+                &self.stable_index_name,
+                &self.query,
+                search_version,
+                search_after,
+            )
             .await?;
-        let revisions_in_range = revisions
-            .into_iter()
-            .filter(|(_, index_key)| self.cursor_interval.contains(index_key))
-            .collect();
         let (namespace, table_number) = match self.stable_index_name.tablet_index_name_or_missing()
         {
             Ok(index_name) => {
@@ -111,12 +135,42 @@ impl SearchQuery {
                 anyhow::bail!(index_not_found_error(missing_index_name));
             },
         };
-        Ok(SearchResultIterator::new(
+        // Facets count every result, not just the ones in the cursor interval,
+        // so they're the same on every page. Likewise, a score expression
+        // reorders all of the results before we look for the cursor.
+        if facet_counter.is_some() || score.is_some() {
+            for (candidate, index_key) in &mut revisions {
+                let id = DeveloperDocumentId::new(table_number, candidate.id);
+                let Some(document) = UserFacingModel::new(tx, namespace)
+                    .get(id, self.version.clone())
+                    .await?
+                else {
+                    continue;
+                };
+                if let Some(counter) = &mut facet_counter {
+                    counter.count(document.value());
+                }
+                if let Some(score) = score {
+                    let score = score.evaluate(f64::from(candidate.score), document.value());
+                    *index_key = candidate.index_key(score);
+                }
+            }
+            if score.is_some() {
+                revisions.sort_by(|(_, a), (_, b)| a.cmp(b));
+            }
+        }
+        let facets = facet_counter.map(|counter| counter.into_facets());
+        let revisions_in_range = revisions
+            .into_iter()
+            .filter(|(_, index_key)| self.cursor_interval.contains(index_key))
+            .collect();
+        let iterator = SearchResultIterator::new(
             revisions_in_range,
             namespace,
             table_number,
             self.version.clone(),
-        ))
+        );
+        Ok((iterator, facets))
     }
 
     #[convex_macro::instrument_future]
@@ -126,7 +180,18 @@ impl SearchQuery {
     ) -> anyhow::Result<Option<(DeveloperDocument, WriteTimestamp)>> {
         let iterator = match &mut self.results {
             Some(results) => results,
-            None => self.results.get_or_insert(self.search(tx).await?),
+            None => {
+                let (results, facets) = self.search(tx).await?;
+                self.facets = facets;
+                self.highlighter = tx
+                    .search_highlighter(
+                        &self.stable_index_name,
+                        &self.query,
+                        self.get_cli_gated_search_version(),
+                    )
+                    .await?;
+                self.results.get_or_insert(results)
+            },
         };
 
         Ok(match iterator.next(tx).await? {
@@ -188,6 +253,18 @@ impl QueryStream for SearchQuery {
     fn printable_index_name(&self) -> &IndexName {
         &self.query.index_name
     }
+
+    fn highlighter(&self) -> Option<&Highlighter> {
+        self.highlighter.as_ref()
+    }
+
+    fn facets(&self) -> Option<&SearchFacets> {
+        self.facets.as_ref()
+    }
+
+    fn prefetch_request(&self, _max_rows: usize) -> Option<IndexRangeRequest> {
+        None
+    }
 }
 
 #[derive(Clone)]
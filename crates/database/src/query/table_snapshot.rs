@@ -0,0 +1,321 @@
+use std::collections::VecDeque;
+
+use async_trait::async_trait;
+use common::{
+    bootstrap_model::index::database_index::IndexedFields,
+    document::DeveloperDocument,
+    index::IndexKeyBytes,
+    knobs::{
+        TRANSACTION_MAX_READ_SIZE_BYTES,
+        TRANSACTION_MAX_READ_SIZE_ROWS,
+    },
+    query::{
+        CursorPosition,
+        Order,
+    },
+    runtime::Runtime,
+    types::{
+        IndexId,
+        IndexName,
+        RepeatableTimestamp,
+        TabletIndexName,
+        WriteTimestamp,
+    },
+};
+use errors::ErrorMetadata;
+use futures::{
+    pin_mut,
+    TryStreamExt,
+};
+use search::{
+    Highlighter,
+    SearchFacets,
+};
+use tokio::task;
+use value::TableNamespace;
+
+use super::{
+    index_range::{
+        CursorInterval,
+        IndexRange,
+    },
+    query_scanned_too_many_documents_error,
+    query_scanned_too_much_data,
+    soft_data_limit,
+    DeveloperIndexRangeResponse,
+    QueryStream,
+    QueryStreamNext,
+    DEFAULT_QUERY_PREFETCH,
+    MAX_QUERY_FETCH,
+};
+use crate::{
+    transaction::IndexRangeRequest,
+    TableIterator,
+    TableSnapshotsModel,
+    Transaction,
+    UserFacingModel,
+};
+
+/// A `QueryStream` for a full table scan of a table that doesn't exist, which
+/// might be a snapshot alias. Snapshots are looked up when the query first
+/// runs: if the table name is an alias, this walks the source table as of the
+/// snapshot's timestamp in creation time order, and otherwise it defers to
+/// `fallback`, which scans the missing table like any other.
+///
+/// The documents of a snapshot never change, so only the alias itself is
+/// recorded in the read set.
+pub struct TableSnapshotScan {
+    namespace: TableNamespace,
+    printable_index_name: IndexName,
+    order: Order,
+    state: SnapshotScanState,
+    fallback: IndexRange,
+
+    cursor_interval: CursorInterval,
+    page: VecDeque<(IndexKeyBytes, DeveloperDocument, WriteTimestamp)>,
+    fetched_all: bool,
+    rows_read: usize,
+    returned_bytes: usize,
+    maximum_rows_read: Option<usize>,
+    maximum_bytes_read: Option<usize>,
+    soft_maximum_rows_read: usize,
+    soft_maximum_bytes_read: usize,
+}
+
+enum SnapshotScanState {
+    Unresolved,
+    NotASnapshot,
+    Snapshot(SnapshotSource),
+}
+
+struct SnapshotSource {
+    by_creation_time: TabletIndexName,
+    index_id: IndexId,
+    snapshot_ts: RepeatableTimestamp,
+}
+
+impl TableSnapshotScan {
+    pub fn new(
+        namespace: TableNamespace,
+        printable_index_name: IndexName,
+        order: Order,
+        cursor_interval: CursorInterval,
+        maximum_rows_read: Option<usize>,
+        maximum_bytes_read: Option<usize>,
+        fallback: IndexRange,
+    ) -> Self {
+        Self {
+            namespace,
+            printable_index_name,
+            order,
+            state: SnapshotScanState::Unresolved,
+            fallback,
+            cursor_interval,
+            page: VecDeque::new(),
+            fetched_all: false,
+            rows_read: 0,
+            returned_bytes: 0,
+            maximum_rows_read,
+            maximum_bytes_read,
+            soft_maximum_rows_read: soft_data_limit(
+                maximum_rows_read
+                    .unwrap_or(*TRANSACTION_MAX_READ_SIZE_ROWS)
+                    .min(*TRANSACTION_MAX_READ_SIZE_ROWS),
+            ),
+            soft_maximum_bytes_read: soft_data_limit(
+                maximum_bytes_read
+                    .unwrap_or(*TRANSACTION_MAX_READ_SIZE_BYTES)
+                    .min(*TRANSACTION_MAX_READ_SIZE_BYTES),
+            ),
+        }
+    }
+
+    async fn resolve<RT: Runtime>(
+        &self,
+        tx: &mut Transaction<RT>,
+    ) -> anyhow::Result<SnapshotScanState> {
+        let alias = self.printable_index_name.table();
+        let Some(snapshot) = TableSnapshotsModel::new(tx, self.namespace)
+            .get(alias)
+            .await?
+        else {
+            return Ok(SnapshotScanState::NotASnapshot);
+        };
+        let snapshot = snapshot.into_value();
+        if self.order != Order::Asc {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidTableSnapshotQuery",
+                format!("Snapshot {alias} can only be read in ascending order."),
+            ));
+        }
+        let by_creation_time = TabletIndexName::by_creation_time(snapshot.tablet_id);
+        let Some(index) = tx.index.index_registry().get_enabled(&by_creation_time) else {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "TableSnapshotSourceDeleted",
+                format!(
+                    "Snapshot {alias} can't be read because its table {} has since been deleted \
+                     or replaced.",
+                    snapshot.table_name
+                ),
+            ));
+        };
+        Ok(SnapshotScanState::Snapshot(SnapshotSource {
+            index_id: index.id(),
+            by_creation_time,
+            snapshot_ts: tx.begin_timestamp().prior_ts(snapshot.snapshot_ts)?,
+        }))
+    }
+
+    async fn fetch<RT: Runtime>(
+        &mut self,
+        tx: &mut Transaction<RT>,
+        max_rows: usize,
+    ) -> anyhow::Result<()> {
+        let SnapshotScanState::Snapshot(ref source) = self.state else {
+            anyhow::bail!("Fetching from a table that isn't a snapshot");
+        };
+        let iterator = TableIterator::new(
+            tx.runtime().clone(),
+            source.snapshot_ts,
+            tx.persistence_reader(),
+            tx.retention_validator.clone(),
+            max_rows,
+        );
+        let stream = iterator.stream_documents_in_table_by_index(
+            *source.by_creation_time.table(),
+            source.index_id,
+            IndexedFields::creation_time(),
+            self.cursor_interval.curr_exclusive.clone(),
+        );
+        pin_mut!(stream);
+        let mut fetched = 0;
+        while fetched < max_rows {
+            let Some((index_key, revision)) = stream.try_next().await? else {
+                self.fetched_all = true;
+                break;
+            };
+            if !self.cursor_interval.contains(&index_key) {
+                self.fetched_all = true;
+                break;
+            }
+            self.page.push_back((
+                index_key,
+                revision.value.to_developer(),
+                WriteTimestamp::Committed(revision.ts),
+            ));
+            fetched += 1;
+        }
+        self.rows_read += fetched;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl QueryStream for TableSnapshotScan {
+    fn cursor_position(&self) -> &Option<CursorPosition> {
+        match self.state {
+            SnapshotScanState::Snapshot(_) => &self.cursor_interval.curr_exclusive,
+            _ => self.fallback.cursor_position(),
+        }
+    }
+
+    fn split_cursor_position(&self) -> Option<&CursorPosition> {
+        match self.state {
+            SnapshotScanState::Snapshot(_) => None,
+            _ => self.fallback.split_cursor_position(),
+        }
+    }
+
+    fn is_approaching_data_limit(&self) -> bool {
+        match self.state {
+            SnapshotScanState::Snapshot(_) => {
+                self.rows_read > self.soft_maximum_rows_read
+                    || self.returned_bytes > self.soft_maximum_bytes_read
+            },
+            _ => self.fallback.is_approaching_data_limit(),
+        }
+    }
+
+    async fn next<RT: Runtime>(
+        &mut self,
+        tx: &mut Transaction<RT>,
+        prefetch_hint: Option<usize>,
+    ) -> anyhow::Result<QueryStreamNext> {
+        task::consume_budget().await;
+        if let SnapshotScanState::Unresolved = self.state {
+            self.state = self.resolve(tx).await?;
+        }
+        if let SnapshotScanState::NotASnapshot = self.state {
+            return self.fallback.next(tx, prefetch_hint).await;
+        }
+        let enforce_limits = self.cursor_interval.end_inclusive.is_none();
+        loop {
+            if enforce_limits
+                && let Some(maximum_bytes_read) = self.maximum_bytes_read
+                && self.returned_bytes >= maximum_bytes_read
+            {
+                return Err(query_scanned_too_much_data(self.returned_bytes).into());
+            }
+            if let Some((index_key, document, ts)) = self.page.pop_front() {
+                self.cursor_interval.curr_exclusive = Some(CursorPosition::After(index_key));
+                UserFacingModel::new(tx, self.namespace)
+                    .record_read_document(&document, self.printable_index_name.table())?;
+                self.returned_bytes += document.size();
+                return Ok(QueryStreamNext::Ready(Some((document, ts))));
+            }
+            if self.fetched_all
+                || matches!(
+                    self.cursor_interval.curr_exclusive,
+                    Some(CursorPosition::End)
+                )
+            {
+                self.cursor_interval.curr_exclusive = Some(
+                    self.cursor_interval
+                        .end_inclusive
+                        .clone()
+                        .unwrap_or(CursorPosition::End),
+                );
+                return Ok(QueryStreamNext::Ready(None));
+            }
+            let mut max_rows = prefetch_hint
+                .unwrap_or(DEFAULT_QUERY_PREFETCH)
+                .clamp(1, MAX_QUERY_FETCH);
+            if enforce_limits && let Some(maximum_rows_read) = self.maximum_rows_read {
+                if self.rows_read >= maximum_rows_read {
+                    return Err(query_scanned_too_many_documents_error(self.rows_read).into());
+                }
+                max_rows = max_rows.min(maximum_rows_read - self.rows_read);
+            }
+            self.fetch(tx, max_rows).await?;
+        }
+    }
+
+    fn feed(&mut self, index_range_response: DeveloperIndexRangeResponse) -> anyhow::Result<()> {
+        self.fallback.feed(index_range_response)
+    }
+
+    fn tablet_index_name(&self) -> Option<&TabletIndexName> {
+        match self.state {
+            SnapshotScanState::Snapshot(ref source) => Some(&source.by_creation_time),
+            _ => self.fallback.tablet_index_name(),
+        }
+    }
+
+    fn printable_index_name(&self) -> &IndexName {
+        &self.printable_index_name
+    }
+
+    fn highlighter(&self) -> Option<&Highlighter> {
+        None
+    }
+
+    fn facets(&self) -> Option<&SearchFacets> {
+        None
+    }
+
+    fn prefetch_request(&self, _max_rows: usize) -> Option<IndexRangeRequest> {
+        // Whether the table is a snapshot alias is only resolved when the
+        // scan runs.
+        None
+    }
+}
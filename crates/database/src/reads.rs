@@ -1,6 +1,11 @@
 //! Read set tracking for an active transaction
 use std::{
     collections::BTreeMap,
+    hash::{
+        DefaultHasher,
+        Hash,
+        Hasher,
+    },
     sync::LazyLock,
 };
 
@@ -119,6 +124,35 @@ impl ReadSet {
         self.search.iter()
     }
 
+    /// A hash of the indexes read and the number of intervals read in each,
+    /// for finding read sets that may have [`ReadSet::same_reads`].
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for (index_name, reads) in self.indexed.iter() {
+            index_name.hash(&mut hasher);
+            reads.intervals.len().hash(&mut hasher);
+        }
+        self.search.len().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Whether `self` and `other` read exactly the same index ranges, so
+    /// they're invalidated by exactly the same writes. Search reads aren't
+    /// compared, so a read set with any isn't the same as any other.
+    pub fn same_reads(&self, other: &ReadSet) -> bool {
+        self.search.is_empty()
+            && other.search.is_empty()
+            && self.indexed.len() == other.indexed.len()
+            && self.indexed.iter().zip(other.indexed.iter()).all(
+                |((index_name, reads), (other_index_name, other_reads))| {
+                    index_name == other_index_name
+                        && reads.fields == other_reads.fields
+                        && reads.intervals.len() == other_reads.intervals.len()
+                        && reads.intervals.iter().eq(other_reads.intervals.iter())
+                },
+            )
+    }
+
     pub fn consume(
         self,
     ) -> (
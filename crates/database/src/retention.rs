@@ -18,6 +18,7 @@ use std::{
 use anyhow::Context;
 use async_trait::async_trait;
 use common::{
+    adaptive_pacing::AdaptiveRateLimiter,
     backoff::Backoff,
     bootstrap_model::index::{
         database_index::{
@@ -77,7 +78,6 @@ use common::{
     },
     query::Order,
     runtime::{
-        new_rate_limiter,
         shutdown_and_join,
         Runtime,
         SpawnHandle,
@@ -115,7 +115,6 @@ use futures::{
     TryStreamExt,
 };
 use futures_async_stream::try_stream;
-use governor::Quota;
 use parking_lot::Mutex;
 use rand::Rng;
 use tokio::sync::watch::{
@@ -1252,9 +1251,10 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
         let mut min_document_snapshot_ts = RepeatableTimestamp::MIN;
         let mut is_working = false;
 
-        let rate_limiter = new_rate_limiter(
+        let rate_limiter = AdaptiveRateLimiter::new(
             rt.clone(),
-            Quota::with_period(*DOCUMENT_RETENTION_BATCH_INTERVAL_SECONDS).unwrap(),
+            "document_retention",
+            1.0 / DOCUMENT_RETENTION_BATCH_INTERVAL_SECONDS.as_secs_f64(),
         );
 
         loop {
@@ -1273,11 +1273,9 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
                 is_working = true;
             }
 
-            // Rate limit so we don't overload the database
-            while let Err(not_until) = rate_limiter.check() {
-                let delay = not_until.wait_time_from(rt.monotonic_now().into());
-                rt.wait(delay).await;
-            }
+            // Rate limit so we don't overload the database, backing off further
+            // while user traffic is slow.
+            rate_limiter.acquire(1).await;
 
             tracing::trace!(
                 "go_delete_documents: running, is_working: {is_working}, current_bounds: \
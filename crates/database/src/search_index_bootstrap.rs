@@ -143,7 +143,10 @@ impl IndexesToBootstrap {
                     let vector_index_bootstrap_data = VectorIndexBootstrapData {
                         index_id: index_id.internal_id(),
                         on_disk_state,
-                        memory_index: MemoryVectorIndex::new(WriteTimestamp::Committed(ts.succ()?)),
+                        memory_index: MemoryVectorIndex::new(
+                            WriteTimestamp::Committed(ts.succ()?),
+                            developer_config.distance_metric,
+                        ),
                         qdrant_schema,
                     };
                     if let Some(vector_indexes) =
@@ -537,7 +540,11 @@ mod tests {
 
     use common::{
         bootstrap_model::index::{
-            text_index::TextIndexState,
+            text_index::{
+                DeveloperTextIndexConfig,
+                TextIndexState,
+            },
+            vector_index::DeveloperVectorIndexConfig,
             IndexConfig,
             IndexMetadata,
             TabletIndexMetadata,
@@ -877,7 +884,10 @@ mod tests {
             component_id: ComponentId::Root,
             vector: vec![0.; 2],
             limit: None,
+            ef_search: None,
             expressions: btreeset![],
+            include_vectors: false,
+            mmr: None,
         };
         let (results, _usage_stats) = db.vector_search(Identity::system(), query).await?;
         Ok(results)
@@ -924,9 +934,15 @@ mod tests {
         let filter_field: FieldPath = "channel".parse()?;
         let metadata = IndexMetadata::new_backfilling_vector_index(
             index_name,
-            vector_field,
-            (2u32).try_into()?,
-            btreeset![filter_field],
+            DeveloperVectorIndexConfig {
+                dimensions: (2u32).try_into()?,
+                vector_field,
+                filter_fields: btreeset![filter_field],
+                partition_field: None,
+                distance_metric: Default::default(),
+                index_kind: Default::default(),
+                quantization: Default::default(),
+            },
         );
         Ok(metadata)
     }
@@ -1156,8 +1172,10 @@ mod tests {
             .await?;
         let index = IndexMetadata::new_backfilling_text_index(
             "test.by_text".parse()?,
-            "searchField".parse()?,
-            btreeset! {"filterField".parse()?},
+            DeveloperTextIndexConfig::new(
+                "searchField".parse()?,
+                btreeset! {"filterField".parse()?},
+            ),
         );
         IndexModel::new(&mut tx)
             .add_application_index(TableNamespace::test_user(), index)
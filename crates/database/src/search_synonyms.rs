@@ -0,0 +1,224 @@
+//! Synonyms for text search indexes. These live in the database crate rather
+//! than the model crate because text search reads them while executing
+//! queries.
+//!
+//! Synonyms are applied at query time, so changing them takes effect without
+//! rebuilding the index, and queries that read them rerun when they change.
+
+use std::{
+    str::FromStr,
+    sync::LazyLock,
+};
+
+use common::{
+    document::{
+        ParseDocument,
+        ParsedDocument,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+        SearchSynonyms,
+    },
+    runtime::Runtime,
+    types::IndexId,
+};
+use errors::ErrorMetadata;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::{
+    codegen_convex_serialization,
+    ConvexValue,
+    FieldPath,
+    InternalId,
+    TableName,
+    TableNamespace,
+};
+
+use crate::{
+    system_tables::{
+        SystemIndex,
+        SystemTable,
+    },
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+
+/// How many synonym groups can an index have?
+pub const MAX_SYNONYM_GROUPS: usize = 1024;
+
+/// How many words can be in a synonym group?
+pub const MAX_SYNONYM_GROUP_SIZE: usize = 32;
+
+pub static SEARCH_SYNONYMS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_search_synonyms"
+        .parse()
+        .expect("Invalid built-in table name")
+});
+
+static INDEX_ID_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "indexId".parse().expect("Invalid built-in field"));
+
+pub static SEARCH_SYNONYMS_BY_INDEX_ID: LazyLock<SystemIndex<SearchSynonymsTable>> =
+    LazyLock::new(|| SystemIndex::new("by_index_id", [&INDEX_ID_FIELD]).unwrap());
+
+pub struct SearchSynonymsTable;
+
+impl SystemTable for SearchSynonymsTable {
+    type Metadata = SearchSynonymsRecord;
+
+    fn table_name() -> &'static TableName {
+        &SEARCH_SYNONYMS_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![SEARCH_SYNONYMS_BY_INDEX_ID.clone()]
+    }
+}
+
+/// The synonyms of a single text search index.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct SearchSynonymsRecord {
+    pub index_id: InternalId,
+    pub synonyms: SearchSynonyms,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedSearchSynonymsRecord {
+    index_id: String,
+    synonyms: Vec<Vec<String>>,
+}
+
+impl From<SearchSynonymsRecord> for SerializedSearchSynonymsRecord {
+    fn from(record: SearchSynonymsRecord) -> Self {
+        Self {
+            index_id: record.index_id.to_string(),
+            synonyms: record.synonyms.groups,
+        }
+    }
+}
+
+impl TryFrom<SerializedSearchSynonymsRecord> for SearchSynonymsRecord {
+    type Error = anyhow::Error;
+
+    fn try_from(record: SerializedSearchSynonymsRecord) -> anyhow::Result<Self> {
+        Ok(Self {
+            index_id: InternalId::from_str(&record.index_id)?,
+            synonyms: SearchSynonyms::new(record.synonyms),
+        })
+    }
+}
+
+codegen_convex_serialization!(SearchSynonymsRecord, SerializedSearchSynonymsRecord);
+
+/// Checks that every group has at least two non-empty words and that there
+/// aren't too many groups or words.
+pub fn validate_search_synonyms(synonyms: &SearchSynonyms) -> anyhow::Result<()> {
+    if synonyms.groups.len() > MAX_SYNONYM_GROUPS {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "TooManySynonymGroups",
+            format!(
+                "A search index can have at most {MAX_SYNONYM_GROUPS} synonym groups, but {} were \
+                 given.",
+                synonyms.groups.len()
+            ),
+        ));
+    }
+    for group in &synonyms.groups {
+        if group.len() < 2 || group.len() > MAX_SYNONYM_GROUP_SIZE {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidSynonymGroup",
+                format!(
+                    "Synonym groups must have between 2 and {MAX_SYNONYM_GROUP_SIZE} words, but \
+                     {group:?} has {}.",
+                    group.len()
+                ),
+            ));
+        }
+        if group.iter().any(|word| word.trim().is_empty()) {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidSynonymGroup",
+                format!("Synonym group {group:?} contains an empty word."),
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub struct SearchSynonymsModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> SearchSynonymsModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    pub async fn get(
+        &mut self,
+        index_id: IndexId,
+    ) -> anyhow::Result<Option<ParsedDocument<SearchSynonymsRecord>>> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&SEARCH_SYNONYMS_TABLE) {
+            return Ok(None);
+        }
+        let range = vec![IndexRangeExpression::Eq(
+            INDEX_ID_FIELD.clone(),
+            ConvexValue::String(index_id.to_string().try_into()?).into(),
+        )];
+        let query = Query::index_range(IndexRange {
+            index_name: SEARCH_SYNONYMS_BY_INDEX_ID.name(),
+            range,
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParseDocument::<SearchSynonymsRecord>::parse)
+            .transpose()
+    }
+
+    /// The synonyms of an index, or no synonyms if none have been set.
+    pub async fn synonyms(&mut self, index_id: IndexId) -> anyhow::Result<SearchSynonyms> {
+        Ok(self
+            .get(index_id)
+            .await?
+            .map(|record| record.into_value().synonyms)
+            .unwrap_or_default())
+    }
+
+    /// Replaces the synonyms of an index. Setting no synonyms removes them.
+    pub async fn set(&mut self, index_id: IndexId, synonyms: SearchSynonyms) -> anyhow::Result<()> {
+        validate_search_synonyms(&synonyms)?;
+        let existing = self.get(index_id).await?;
+        if synonyms.is_empty() {
+            if let Some(existing) = existing {
+                SystemMetadataModel::new_global(self.tx)
+                    .delete(existing.id())
+                    .await?;
+            }
+            return Ok(());
+        }
+        let record = SearchSynonymsRecord { index_id, synonyms };
+        match existing {
+            Some(existing) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), record.try_into()?)
+                    .await?;
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&SEARCH_SYNONYMS_TABLE, record.try_into()?)
+                    .await?;
+            },
+        }
+        Ok(())
+    }
+}
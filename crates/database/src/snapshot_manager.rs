@@ -83,6 +83,9 @@ pub struct TableSummaries {
     pub tables: OrdMap<TabletId, TableSummary>,
     pub num_user_documents: u64,
     pub user_size: u64,
+    /// The user document count and size of each namespace, so component
+    /// quotas don't have to add up every table in the component.
+    pub user_usage_by_namespace: OrdMap<TableNamespace, (u64, u64)>,
 }
 
 #[async_trait]
@@ -100,6 +103,19 @@ impl TableCountSnapshot for Option<TableSummaries> {
         };
         Ok(result)
     }
+
+    async fn namespace_usage(
+        &self,
+        namespace: TableNamespace,
+    ) -> anyhow::Result<Option<(u64, u64)>> {
+        Ok(self.as_ref().map(|table_summaries| {
+            table_summaries
+                .user_usage_by_namespace
+                .get(&namespace)
+                .copied()
+                .unwrap_or_default()
+        }))
+    }
 }
 
 impl TableSummaries {
@@ -122,10 +138,22 @@ impl TableSummaries {
                     acc_size + summary.total_size(),
                 )
             });
+        let mut user_usage_by_namespace = OrdMap::new();
+        for (table_id, summary) in tables.iter() {
+            if table_mapping.is_system_tablet(*table_id) {
+                continue;
+            }
+            if let Ok(namespace) = table_mapping.tablet_namespace(*table_id) {
+                let usage: &mut (u64, u64) = user_usage_by_namespace.entry(namespace).or_default();
+                usage.0 += summary.num_values();
+                usage.1 += summary.total_size();
+            }
+        }
         Self {
             tables,
             num_user_documents,
             user_size,
+            user_usage_by_namespace,
         }
     }
 
@@ -160,9 +188,9 @@ impl TableSummaries {
             table_summary = table_summary.insert(&new_value.value().0);
         }
         if let Some(TableUpdate {
-            namespace: _,
+            namespace,
             table_id_and_number,
-            table_name: _,
+            table_name,
             state: _,
             mode,
         }) = table_update
@@ -176,7 +204,15 @@ impl TableSummaries {
                 },
                 TableUpdateMode::Activate => {},
                 TableUpdateMode::Drop => {
-                    self.tables.remove(&table_id_and_number.tablet_id);
+                    if let Some(dropped) = self.tables.remove(&table_id_and_number.tablet_id)
+                        && !table_name.is_system()
+                    {
+                        self.update_namespace_usage(
+                            *namespace,
+                            -(dropped.num_values() as i64),
+                            -(dropped.total_size() as i64),
+                        );
+                    }
                 },
             }
         }
@@ -189,12 +225,23 @@ impl TableSummaries {
                         self.num_user_documents + new_info_num_values - old_summary.num_values();
                     self.user_size =
                         self.user_size + new_info_total_size - old_summary.total_size();
+                    self.update_namespace_usage(
+                        table_mapping.tablet_namespace(document_id.tablet_id)?,
+                        new_info_num_values as i64 - old_summary.num_values() as i64,
+                        new_info_total_size as i64 - old_summary.total_size() as i64,
+                    );
                 }
             },
             None => panic!("Applying update for non-existent table!"),
         }
         Ok(())
     }
+
+    fn update_namespace_usage(&mut self, namespace: TableNamespace, documents: i64, size: i64) {
+        let usage = self.user_usage_by_namespace.entry(namespace).or_default();
+        usage.0 = usage.0.saturating_add_signed(documents);
+        usage.1 = usage.1.saturating_add_signed(size);
+    }
 }
 /// A snapshot of the database indexes and metadata at a certain timestamp.
 #[derive(Clone)]
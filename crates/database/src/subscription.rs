@@ -5,6 +5,7 @@ use std::{
     collections::{
         BTreeMap,
         BTreeSet,
+        HashMap,
     },
     future::Future,
     sync::{
@@ -56,6 +57,7 @@ use tokio::sync::{
     },
     watch,
 };
+use value::TabletId;
 
 use crate::{
     metrics,
@@ -107,15 +109,29 @@ impl SubscriptionsClient {
 pub struct SubscriptionSender {
     valid_ts: Arc<AtomicI64>,
     valid_tx: watch::Sender<SubscriptionState>,
+    /// Set when a write invalidates the subscription, just before it's
+    /// dropped.
+    invalidation_cause: Option<InvalidationCause>,
 }
 
 impl Drop for SubscriptionSender {
     fn drop(&mut self) {
         self.valid_ts.store(-1, Ordering::SeqCst);
-        _ = self.valid_tx.send(SubscriptionState::Invalid);
+        _ = self
+            .valid_tx
+            .send(SubscriptionState::Invalid(self.invalidation_cause.take()));
     }
 }
 
+/// What a write overlapped with to invalidate a subscription.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum InvalidationCause {
+    /// The write was in a range the subscription read from this index.
+    Index(TabletIndexName),
+    /// The write matched a text search the subscription ran on this table.
+    TextSearch(TabletId),
+}
+
 enum SubscriptionRequest {
     Subscribe {
         token: Token,
@@ -185,6 +201,19 @@ pub struct SubscriptionManager {
     subscribers: Slab<Subscriber>,
     subscriptions: SubscriptionMap,
     next_seq: Sequence,
+    /// Subscribers by [`ReadSet::fingerprint`]. Subscriptions with the same
+    /// reads share one subscriber, whether they come from one query cache
+    /// entry or separate executions: its reads are tracked and checked
+    /// against the log once, and invalidations fan out to every session.
+    /// Two queries only have the same reads if they're invalidated by the
+    /// same writes, so sharing never changes when a subscription fires.
+    ///
+    /// Re-running the query after an invalidation is deduplicated by the
+    /// query cache, which makes concurrent requests for the same query wait
+    /// on one execution. Transitions are still built per session, since each
+    /// carries that client's own query set and versions.
+    by_reads: HashMap<u64, Vec<SubscriberId>>,
+    num_subscriptions: usize,
 
     closed_subscriptions: FuturesUnordered<BoxFuture<'static, SubscriptionKey>>,
 
@@ -202,8 +231,10 @@ pub struct SubscriptionManager {
 
 struct Subscriber {
     reads: Arc<ReadSet>,
-    sender: SubscriptionSender,
-    seq: Sequence,
+    fingerprint: u64,
+    /// Every subscription to `reads`, by the sequence number in its
+    /// `SubscriptionKey`.
+    senders: BTreeMap<Sequence, SubscriptionSender>,
 }
 
 impl SubscriptionManager {
@@ -222,6 +253,8 @@ impl SubscriptionManager {
             subscribers: Slab::new(),
             subscriptions: SubscriptionMap::new(),
             next_seq: 0,
+            by_reads: HashMap::new(),
+            num_subscriptions: 0,
             closed_subscriptions: FuturesUnordered::new(),
             log,
             processed_ts,
@@ -253,23 +286,47 @@ impl SubscriptionManager {
         }
         assert!(token.ts() >= self.processed_ts);
 
-        let entry = self.subscribers.vacant_entry();
-        let subscriber_id = entry.key();
-
-        self.subscriptions.insert(subscriber_id, token.reads());
-
         let seq: usize = self.next_seq;
+        self.next_seq += 1;
+        let valid_tx = sender.valid_tx.clone();
+        let reads = token.reads_owned();
+        // Every subscriber is valid through `processed_ts`, so a new
+        // subscription with the same reads can join an existing one.
+        let fingerprint = reads.fingerprint();
+        let existing = self.by_reads.get(&fingerprint).and_then(|ids| {
+            ids.iter().copied().find(|&id| {
+                let existing = &self.subscribers[id].reads;
+                Arc::ptr_eq(existing, &reads) || existing.same_reads(&reads)
+            })
+        });
+        let subscriber_id = match existing {
+            Some(subscriber_id) => {
+                metrics::log_subscription_shared();
+                self.subscribers[subscriber_id].senders.insert(seq, sender);
+                subscriber_id
+            },
+            None => {
+                let entry = self.subscribers.vacant_entry();
+                let subscriber_id = entry.key();
+                self.subscriptions.insert(subscriber_id, &reads);
+                self.by_reads
+                    .entry(fingerprint)
+                    .or_default()
+                    .push(subscriber_id);
+                entry.insert(Subscriber {
+                    reads,
+                    fingerprint,
+                    senders: BTreeMap::from([(seq, sender)]),
+                });
+                subscriber_id
+            },
+        };
+        self.num_subscriptions += 1;
+        metrics::log_subscribers(self.subscribers.len(), self.num_subscriptions);
         let key = SubscriptionKey {
             id: subscriber_id,
             seq,
         };
-        self.next_seq += 1;
-        let valid_tx = sender.valid_tx.clone();
-        entry.insert(Subscriber {
-            reads: token.reads_owned(),
-            sender,
-            seq,
-        });
         self.closed_subscriptions.push(
             async move {
                 valid_tx.closed().await;
@@ -295,7 +352,7 @@ impl SubscriptionManager {
         block_in_place(|| {
             let from_ts = self.processed_ts.succ()?;
 
-            let mut to_notify = BTreeSet::new();
+            let mut to_notify = BTreeMap::new();
             let mut buffer = IndexKeyBuffer::new();
             self.log.for_each(from_ts, next_ts, |_, writes| {
                 for (_, document_change) in writes {
@@ -324,17 +381,22 @@ impl SubscriptionManager {
 
             // First, do a pass where we advance all of the valid subscriptions.
             for (subscriber_id, subscriber) in &mut self.subscribers {
-                if !to_notify.contains(&subscriber_id) {
-                    subscriber
-                        .sender
-                        .valid_ts
-                        .store(i64::from(next_ts), Ordering::SeqCst);
+                if !to_notify.contains_key(&subscriber_id) {
+                    for sender in subscriber.senders.values() {
+                        sender.valid_ts.store(i64::from(next_ts), Ordering::SeqCst);
+                    }
                 }
             }
             // Then, invalidate all the remaining subscriptions.
-            for subscriber_id in to_notify {
+            for (subscriber_id, cause) in to_notify {
+                if let Some(subscriber) = self.subscribers.get_mut(subscriber_id) {
+                    for sender in subscriber.senders.values_mut() {
+                        sender.invalidation_cause = Some(cause.clone());
+                    }
+                }
                 self._remove(subscriber_id);
             }
+            metrics::log_subscribers(self.subscribers.len(), self.num_subscriptions);
 
             assert!(self.processed_ts <= next_ts);
             self.processed_ts = next_ts;
@@ -356,18 +418,22 @@ impl SubscriptionManager {
     ) {
         use common::document::IndexKeyBuffer;
 
+        let mut causes = BTreeMap::new();
         self.overlapping(
             document,
-            to_notify,
+            &mut causes,
             persistence_version,
             &mut IndexKeyBuffer::new(),
         );
+        to_notify.extend(causes.into_keys());
     }
 
+    /// Adds subscribers whose reads overlap `document` to `to_notify`, along
+    /// with the first overlap found for each.
     fn overlapping(
         &self,
         document: &PackedDocument,
-        to_notify: &mut BTreeSet<SubscriberId>,
+        to_notify: &mut BTreeMap<SubscriberId, InvalidationCause>,
         persistence_version: PersistenceVersion,
         buffer: &mut IndexKeyBuffer,
     ) {
@@ -375,42 +441,59 @@ impl SubscriptionManager {
             if *index.table() == document.id().tablet_id {
                 let index_key = document.index_key(fields, persistence_version, buffer);
                 for subscriber_id in range_map.query(index_key) {
-                    to_notify.insert(subscriber_id);
+                    to_notify
+                        .entry(subscriber_id)
+                        .or_insert_with(|| InvalidationCause::Index(index.clone()));
                 }
             }
         }
-        self.subscriptions.search.add_matches(document, to_notify);
-    }
-
-    fn get_subscriber(&self, key: SubscriptionKey) -> Option<&Subscriber> {
-        let entry = self.subscribers.get(key.id)?;
-        if entry.seq > key.seq {
-            return None;
+        let mut search_matches = BTreeSet::new();
+        self.subscriptions
+            .search
+            .add_matches(document, &mut search_matches);
+        for subscriber_id in search_matches {
+            to_notify
+                .entry(subscriber_id)
+                .or_insert(InvalidationCause::TextSearch(document.id().tablet_id));
         }
-        assert_eq!(entry.seq, key.seq);
-        Some(entry)
     }
 
-    /// Remove the given subscription if it exists.
+    /// Remove the given subscription if it exists, and its subscriber if no
+    /// other subscriptions share it.
     fn remove(&mut self, key: SubscriptionKey) {
-        // Don't remove anything if `key` is no longer valid.
-        if self.get_subscriber(key).is_none() {
+        // Don't remove anything if `key` is no longer valid. The slab may have
+        // reused `key.id` for a subscriber that doesn't contain `key.seq`.
+        let Some(entry) = self.subscribers.get_mut(key.id) else {
             return;
+        };
+        if entry.senders.remove(&key.seq).is_none() {
+            return;
+        }
+        self.num_subscriptions -= 1;
+        if entry.senders.is_empty() {
+            self._remove(key.id);
         }
-        self._remove(key.id);
+        metrics::log_subscribers(self.subscribers.len(), self.num_subscriptions);
     }
 
     fn _remove(&mut self, id: SubscriberId) {
         let entry = self.subscribers.remove(id);
         self.subscriptions.remove(id, &entry.reads);
-        // dropping `entry.sender` will invalidate the subscription
+        if let Some(ids) = self.by_reads.get_mut(&entry.fingerprint) {
+            ids.retain(|&other| other != id);
+            if ids.is_empty() {
+                self.by_reads.remove(&entry.fingerprint);
+            }
+        }
+        self.num_subscriptions -= entry.senders.len();
+        // dropping `entry.senders` will invalidate the subscriptions
     }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Clone)]
 enum SubscriptionState {
     Valid,
-    Invalid,
+    Invalid(Option<InvalidationCause>),
 }
 
 /// A subscription on a set of read keys from a prior read-only transaction.
@@ -430,11 +513,18 @@ impl Subscription {
             valid: valid_rx,
             _timer: metrics::subscription_timer(),
         };
-        (subscription, SubscriptionSender { valid_ts, valid_tx })
+        (
+            subscription,
+            SubscriptionSender {
+                valid_ts,
+                valid_tx,
+                invalidation_cause: None,
+            },
+        )
     }
 
-    fn invalid() -> Self {
-        let (_, receiver) = watch::channel(SubscriptionState::Invalid);
+    pub(crate) fn invalid() -> Self {
+        let (_, receiver) = watch::channel(SubscriptionState::Invalid(None));
         Subscription {
             valid_ts: Arc::new(AtomicI64::new(-1)),
             valid: receiver,
@@ -457,11 +547,19 @@ impl Subscription {
         let span = fastrace::Span::enter_with_local_parent("wait_for_invalidation");
         async move {
             let _: Result<_, _> = valid
-                .wait_for(|state| matches!(state, SubscriptionState::Invalid))
+                .wait_for(|state| matches!(state, SubscriptionState::Invalid(_)))
                 .await;
         }
         .in_span(span)
     }
+
+    /// What invalidated the subscription, if it was invalidated by a write.
+    pub fn invalidation_cause(&self) -> Option<InvalidationCause> {
+        match &*self.valid.borrow() {
+            SubscriptionState::Valid => None,
+            SubscriptionState::Invalid(cause) => cause.clone(),
+        }
+    }
 }
 
 /// Tracks every subscriber for a given read-set.
@@ -1001,4 +1099,62 @@ mod tests {
         assert!(subscription_manager.subscribers.get(id).is_none());
         assert!(subscription_manager.subscribers.is_empty());
     }
+
+    fn index_token(tablet_id: TabletId, index: &str) -> anyhow::Result<Token> {
+        let mut read_set = crate::TransactionReadSet::new();
+        read_set.record_indexed_directly(
+            GenericIndexName::new(tablet_id, IndexDescriptor::new(index)?)?,
+            common::bootstrap_model::index::database_index::IndexedFields::by_id(),
+            common::interval::Interval::all(),
+        )?;
+        Ok(Token::new_for_testing(
+            read_set.into_read_set(),
+            Timestamp::MIN,
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_shares_subscriptions_with_same_reads() -> anyhow::Result<()> {
+        let mut id_generator = TestIdGenerator::new();
+        let tablet_id = id_generator
+            .user_table_id(&id_generator.generate_table_name())
+            .tablet_id;
+        let mut subscription_manager = SubscriptionManager::new_for_testing();
+        let token = index_token(tablet_id, "by_channel")?;
+        let (first, id) = subscription_manager.subscribe_for_testing(token.clone())?;
+        let (second, second_id) = subscription_manager.subscribe_for_testing(token)?;
+        assert_eq!(id, second_id);
+        // A token with equal but separately computed reads is shared too.
+        let (third, third_id) =
+            subscription_manager.subscribe_for_testing(index_token(tablet_id, "by_channel")?)?;
+        assert_eq!(id, third_id);
+        assert_eq!(subscription_manager.subscribers.len(), 1);
+        // Different reads get their own subscriber.
+        let (_other, other_id) =
+            subscription_manager.subscribe_for_testing(index_token(tablet_id, "by_author")?)?;
+        assert_ne!(id, other_id);
+
+        // The subscriber stays until every subscription sharing it is dropped.
+        drop(first);
+        subscription_manager.run_worker(disconnected_rx()).await;
+        assert_eq!(subscription_manager.subscribers[id].senders.len(), 2);
+        assert!(second.current_ts().is_some());
+        drop(second);
+        drop(third);
+        subscription_manager.run_worker(disconnected_rx()).await;
+        subscription_manager.run_worker(disconnected_rx()).await;
+        assert!(subscription_manager.subscribers.get(id).is_none());
+        assert_eq!(subscription_manager.num_subscriptions, 1);
+        assert_eq!(subscription_manager.by_reads.len(), 1);
+
+        // Invalidating a shared subscriber invalidates every subscription.
+        let (first, id) =
+            subscription_manager.subscribe_for_testing(index_token(tablet_id, "by_channel")?)?;
+        let (second, _) =
+            subscription_manager.subscribe_for_testing(index_token(tablet_id, "by_channel")?)?;
+        subscription_manager._remove(id);
+        assert!(first.current_ts().is_none());
+        assert!(second.current_ts().is_none());
+        Ok(())
+    }
 }
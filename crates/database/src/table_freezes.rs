@@ -0,0 +1,274 @@
+//! Freezes that make a user table read-only for a while, e.g. during a
+//! migration or export. These live in the database crate rather than the model
+//! crate because `UserFacingModel` checks them on every write.
+//!
+//! A freeze ends at `expires_at_ms` without anyone lifting it, so a crashed
+//! maintenance script can't leave a table read-only forever. Expired freezes
+//! are ignored by writes and deleted the next time the table is frozen or
+//! unfrozen.
+
+use std::{
+    str::FromStr,
+    sync::LazyLock,
+    time::Duration,
+};
+
+use common::{
+    document::{
+        ParseDocument,
+        ParsedDocument,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+};
+use errors::ErrorMetadata;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::{
+    codegen_convex_serialization,
+    ConvexValue,
+    FieldPath,
+    TableName,
+    TableNamespace,
+    TabletId,
+};
+
+use crate::{
+    system_tables::{
+        SystemIndex,
+        SystemTable,
+    },
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+
+/// The longest a table can be frozen for at once.
+pub const MAX_TABLE_FREEZE_DURATION: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// The longest reason a freeze can be given.
+const MAX_REASON_LENGTH: usize = 1024;
+
+pub static TABLE_FREEZES_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_table_freezes"
+        .parse()
+        .expect("Invalid built-in table name")
+});
+
+static TABLET_ID_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "tabletId".parse().expect("Invalid built-in field"));
+
+pub static TABLE_FREEZES_BY_TABLET_ID: LazyLock<SystemIndex<TableFreezesTable>> =
+    LazyLock::new(|| SystemIndex::new("by_tablet_id", [&TABLET_ID_FIELD]).unwrap());
+
+pub struct TableFreezesTable;
+
+impl SystemTable for TableFreezesTable {
+    type Metadata = TableFreeze;
+
+    fn table_name() -> &'static TableName {
+        &TABLE_FREEZES_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![TABLE_FREEZES_BY_TABLET_ID.clone()]
+    }
+}
+
+/// A freeze of the user table `tablet_id`, rejecting writes to it until
+/// `expires_at_ms`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct TableFreeze {
+    pub tablet_id: TabletId,
+    pub reason: String,
+    pub frozen_at_ms: i64,
+    pub expires_at_ms: i64,
+}
+
+impl TableFreeze {
+    pub fn is_expired(&self, now_ms: i64) -> bool {
+        self.expires_at_ms <= now_ms
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedTableFreeze {
+    tablet_id: String,
+    reason: String,
+    frozen_at_ms: i64,
+    expires_at_ms: i64,
+}
+
+impl From<TableFreeze> for SerializedTableFreeze {
+    fn from(freeze: TableFreeze) -> Self {
+        Self {
+            tablet_id: freeze.tablet_id.to_string(),
+            reason: freeze.reason,
+            frozen_at_ms: freeze.frozen_at_ms,
+            expires_at_ms: freeze.expires_at_ms,
+        }
+    }
+}
+
+impl TryFrom<SerializedTableFreeze> for TableFreeze {
+    type Error = anyhow::Error;
+
+    fn try_from(freeze: SerializedTableFreeze) -> anyhow::Result<Self> {
+        Ok(Self {
+            tablet_id: TabletId::from_str(&freeze.tablet_id)?,
+            reason: freeze.reason,
+            frozen_at_ms: freeze.frozen_at_ms,
+            expires_at_ms: freeze.expires_at_ms,
+        })
+    }
+}
+
+codegen_convex_serialization!(TableFreeze, SerializedTableFreeze);
+
+pub struct TableFreezesModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> TableFreezesModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    async fn get(
+        &mut self,
+        tablet_id: TabletId,
+    ) -> anyhow::Result<Option<ParsedDocument<TableFreeze>>> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&TABLE_FREEZES_TABLE) {
+            return Ok(None);
+        }
+        let range = vec![IndexRangeExpression::Eq(
+            TABLET_ID_FIELD.clone(),
+            ConvexValue::String(tablet_id.to_string().try_into()?).into(),
+        )];
+        let query = Query::index_range(IndexRange {
+            index_name: TABLE_FREEZES_BY_TABLET_ID.name(),
+            range,
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParseDocument::<TableFreeze>::parse)
+            .transpose()
+    }
+
+    /// The table's freeze, unless it isn't frozen or its freeze has expired.
+    pub async fn active_freeze(
+        &mut self,
+        tablet_id: TabletId,
+    ) -> anyhow::Result<Option<TableFreeze>> {
+        let now_ms = self.tx.runtime().unix_timestamp_ms()?;
+        Ok(self
+            .get(tablet_id)
+            .await?
+            .map(ParsedDocument::into_value)
+            .filter(|freeze| !freeze.is_expired(now_ms)))
+    }
+
+    /// Fails with a `TableFrozen` error if the table is frozen. Reading the
+    /// freeze is recorded, so a write racing with a freeze conflicts with it
+    /// rather than slipping through.
+    pub async fn check_writable(&mut self, tablet_id: TabletId) -> anyhow::Result<()> {
+        if let Some(freeze) = self.active_freeze(tablet_id).await? {
+            let table_name = self.tx.table_mapping().tablet_name(tablet_id)?;
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "TableFrozen",
+                format!(
+                    "Table {table_name} is read-only until {} ms since the epoch: {}",
+                    freeze.expires_at_ms, freeze.reason
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Freezes the table for `duration`, replacing any existing freeze.
+    pub async fn freeze(
+        &mut self,
+        tablet_id: TabletId,
+        reason: String,
+        duration: Duration,
+    ) -> anyhow::Result<TableFreeze> {
+        if duration.is_zero() || duration > MAX_TABLE_FREEZE_DURATION {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidTableFreeze",
+                format!(
+                    "A table can be frozen for between 1 and {} seconds, but {} were given.",
+                    MAX_TABLE_FREEZE_DURATION.as_secs(),
+                    duration.as_secs()
+                ),
+            ));
+        }
+        if reason.len() > MAX_REASON_LENGTH {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidTableFreeze",
+                format!("A freeze's reason can be at most {MAX_REASON_LENGTH} bytes."),
+            ));
+        }
+        let now_ms = self.tx.runtime().unix_timestamp_ms()?;
+        let freeze = TableFreeze {
+            tablet_id,
+            reason,
+            frozen_at_ms: now_ms,
+            expires_at_ms: now_ms + duration.as_millis() as i64,
+        };
+        match self.get(tablet_id).await? {
+            Some(existing) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), freeze.clone().try_into()?)
+                    .await?;
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&TABLE_FREEZES_TABLE, freeze.clone().try_into()?)
+                    .await?;
+            },
+        }
+        Ok(freeze)
+    }
+
+    /// Lifts the table's freeze. Returns whether it was frozen.
+    pub async fn unfreeze(&mut self, tablet_id: TabletId) -> anyhow::Result<bool> {
+        let now_ms = self.tx.runtime().unix_timestamp_ms()?;
+        let Some(existing) = self.get(tablet_id).await? else {
+            return Ok(false);
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .delete(existing.id())
+            .await?;
+        Ok(!existing.is_expired(now_ms))
+    }
+
+    /// Every active freeze.
+    pub async fn list(&mut self) -> anyhow::Result<Vec<TableFreeze>> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&TABLE_FREEZES_TABLE) {
+            return Ok(vec![]);
+        }
+        let now_ms = self.tx.runtime().unix_timestamp_ms()?;
+        let query = Query::full_table_scan(TABLE_FREEZES_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut freezes = vec![];
+        while let Some(document) = query_stream.next(self.tx, None).await? {
+            let freeze = ParseDocument::<TableFreeze>::parse(document)?.into_value();
+            if !freeze.is_expired(now_ms) {
+                freezes.push(freeze);
+            }
+        }
+        Ok(freezes)
+    }
+}
@@ -0,0 +1,271 @@
+//! Snapshot aliases: read-only views of a user table as of a past timestamp,
+//! queryable under a table name of their own. These live in the database
+//! crate rather than the model crate because queries resolve them while
+//! executing.
+//!
+//! A snapshot doesn't copy any documents. Reading it walks the source table
+//! and the document log written since the snapshot, so it only stays readable
+//! while that log is within document retention.
+
+use std::{
+    str::FromStr,
+    sync::LazyLock,
+};
+
+use anyhow::Context;
+use common::{
+    components::ComponentId,
+    document::{
+        ParseDocument,
+        ParsedDocument,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::Timestamp,
+};
+use errors::ErrorMetadata;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::{
+    codegen_convex_serialization,
+    ConvexValue,
+    FieldPath,
+    TableName,
+    TableNamespace,
+    TabletId,
+};
+
+use crate::{
+    system_tables::{
+        SystemIndex,
+        SystemTable,
+    },
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+
+pub static TABLE_SNAPSHOTS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_table_snapshots"
+        .parse()
+        .expect("Invalid built-in table name")
+});
+
+static COMPONENT_ID_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "componentId".parse().expect("Invalid built-in field"));
+static NAME_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "name".parse().expect("Invalid built-in field"));
+
+pub static TABLE_SNAPSHOTS_BY_NAME: LazyLock<SystemIndex<TableSnapshotsTable>> =
+    LazyLock::new(|| SystemIndex::new("by_name", [&COMPONENT_ID_FIELD, &NAME_FIELD]).unwrap());
+
+pub struct TableSnapshotsTable;
+
+impl SystemTable for TableSnapshotsTable {
+    type Metadata = TableSnapshot;
+
+    fn table_name() -> &'static TableName {
+        &TABLE_SNAPSHOTS_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![TABLE_SNAPSHOTS_BY_NAME.clone()]
+    }
+}
+
+/// An alias `name` for the user table `tablet_id` as it was at `snapshot_ts`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TableSnapshot {
+    pub namespace: TableNamespace,
+    pub name: TableName,
+    pub table_name: TableName,
+    pub tablet_id: TabletId,
+    pub snapshot_ts: Timestamp,
+    pub created_at_ms: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedTableSnapshot {
+    component_id: Option<String>,
+    name: String,
+    table_name: String,
+    tablet_id: String,
+    snapshot_ts: i64,
+    created_at_ms: i64,
+}
+
+impl From<TableSnapshot> for SerializedTableSnapshot {
+    fn from(snapshot: TableSnapshot) -> Self {
+        Self {
+            component_id: ComponentId::from(snapshot.namespace).serialize_to_string(),
+            name: snapshot.name.to_string(),
+            table_name: snapshot.table_name.to_string(),
+            tablet_id: snapshot.tablet_id.to_string(),
+            snapshot_ts: snapshot.snapshot_ts.into(),
+            created_at_ms: snapshot.created_at_ms,
+        }
+    }
+}
+
+impl TryFrom<SerializedTableSnapshot> for TableSnapshot {
+    type Error = anyhow::Error;
+
+    fn try_from(snapshot: SerializedTableSnapshot) -> anyhow::Result<Self> {
+        Ok(Self {
+            namespace: ComponentId::deserialize_from_string(snapshot.component_id.as_deref())?
+                .into(),
+            name: snapshot.name.parse()?,
+            table_name: snapshot.table_name.parse()?,
+            tablet_id: TabletId::from_str(&snapshot.tablet_id)?,
+            snapshot_ts: snapshot.snapshot_ts.try_into()?,
+            created_at_ms: snapshot.created_at_ms,
+        })
+    }
+}
+
+codegen_convex_serialization!(TableSnapshot, SerializedTableSnapshot);
+
+pub struct TableSnapshotsModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+    namespace: TableNamespace,
+}
+
+impl<'a, RT: Runtime> TableSnapshotsModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>, namespace: TableNamespace) -> Self {
+        Self { tx, namespace }
+    }
+
+    /// The snapshot aliased as `name`, if any.
+    pub async fn get(
+        &mut self,
+        name: &TableName,
+    ) -> anyhow::Result<Option<ParsedDocument<TableSnapshot>>> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&TABLE_SNAPSHOTS_TABLE) {
+            return Ok(None);
+        }
+        let component_id = match ComponentId::from(self.namespace).serialize_to_string() {
+            Some(component_id) => ConvexValue::String(component_id.try_into()?),
+            None => ConvexValue::Null,
+        };
+        let range = vec![
+            IndexRangeExpression::Eq(COMPONENT_ID_FIELD.clone(), component_id.into()),
+            IndexRangeExpression::Eq(
+                NAME_FIELD.clone(),
+                ConvexValue::String(name.to_string().try_into()?).into(),
+            ),
+        ];
+        let query = Query::index_range(IndexRange {
+            index_name: TABLE_SNAPSHOTS_BY_NAME.name(),
+            range,
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParseDocument::<TableSnapshot>::parse)
+            .transpose()
+    }
+
+    /// Aliases `table_name` as it was at `snapshot_ts`, or as of this
+    /// transaction if no timestamp is given, under the table name `name`.
+    pub async fn create(
+        &mut self,
+        name: TableName,
+        table_name: TableName,
+        snapshot_ts: Option<Timestamp>,
+    ) -> anyhow::Result<TableSnapshot> {
+        if name.is_system() || table_name.is_system() {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidTableSnapshot",
+                "Only user tables can be snapshotted, and snapshots can't have system table names.",
+            ));
+        }
+        let table_mapping = self.tx.table_mapping().namespace(self.namespace);
+        if table_mapping.name_exists(&name) {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "TableSnapshotNameTaken",
+                format!("Can't create snapshot {name}: a table with that name already exists."),
+            ));
+        }
+        let Some(tablet_id) = table_mapping.id_if_exists(&table_name) else {
+            anyhow::bail!(ErrorMetadata::not_found(
+                "TableNotFound",
+                format!("Table {table_name} not found."),
+            ));
+        };
+        if self.get(&name).await?.is_some() {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "TableSnapshotNameTaken",
+                format!("Can't create snapshot {name}: a snapshot with that name already exists."),
+            ));
+        }
+        let begin_ts = self.tx.begin_timestamp();
+        let snapshot_ts = snapshot_ts.unwrap_or(*begin_ts);
+        if snapshot_ts > *begin_ts {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidTableSnapshot",
+                format!("Can't snapshot {table_name} at {snapshot_ts}, which is in the future."),
+            ));
+        }
+        self.tx
+            .retention_validator
+            .validate_document_snapshot(snapshot_ts)
+            .await
+            .context(ErrorMetadata::bad_request(
+                "InvalidTableSnapshot",
+                format!(
+                    "Can't snapshot {table_name} at {snapshot_ts}, which is outside of document \
+                     retention."
+                ),
+            ))?;
+        let snapshot = TableSnapshot {
+            namespace: self.namespace,
+            name,
+            table_name,
+            tablet_id,
+            snapshot_ts,
+            created_at_ms: self.tx.runtime().unix_timestamp().as_ms_since_epoch()? as i64,
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .insert(&TABLE_SNAPSHOTS_TABLE, snapshot.clone().try_into()?)
+            .await?;
+        Ok(snapshot)
+    }
+
+    /// Removes the snapshot aliased as `name`. Returns whether it existed.
+    pub async fn delete(&mut self, name: &TableName) -> anyhow::Result<bool> {
+        let Some(existing) = self.get(name).await? else {
+            return Ok(false);
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .delete(existing.id())
+            .await?;
+        Ok(true)
+    }
+
+    /// Every snapshot in the namespace.
+    pub async fn list(&mut self) -> anyhow::Result<Vec<TableSnapshot>> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&TABLE_SNAPSHOTS_TABLE) {
+            return Ok(vec![]);
+        }
+        let query = Query::full_table_scan(TABLE_SNAPSHOTS_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut snapshots = vec![];
+        while let Some(document) = query_stream.next(self.tx, None).await? {
+            let snapshot = ParseDocument::<TableSnapshot>::parse(document)?.into_value();
+            if snapshot.namespace == self.namespace {
+                snapshots.push(snapshot);
+            }
+        }
+        Ok(snapshots)
+    }
+}
@@ -8,7 +8,10 @@ use value::{
 };
 
 use crate::{
-    committer::AFTER_PENDING_WRITE_SNAPSHOT,
+    committer::{
+        AFTER_PENDING_WRITE_SNAPSHOT,
+        AFTER_SPILLED_CONFLICTS_CHECK,
+    },
     test_helpers::{
         DbFixtures,
         DbFixturesArgs,
@@ -72,3 +75,49 @@ async fn test_table_summary_recompute_pending_writes(
     assert_eq!(count, 2);
     Ok(())
 }
+
+#[convex_macro::test_runtime]
+async fn test_spill_between_conflict_checks(
+    rt: TestRuntime,
+    pause: PauseController,
+) -> anyhow::Result<()> {
+    let DbFixtures { db, .. } = DbFixtures::new(&rt).await?;
+    let table_name: TableName = "test_table".parse()?;
+    let mut tx = db.begin(Identity::system()).await?;
+    TestFacingModel::new(&mut tx)
+        .insert(&table_name, assert_obj!())
+        .await?;
+    db.commit(tx).await?;
+
+    // `tx2` commits after `tx1` begins, so the committer has to check `tx1`
+    // against it.
+    let mut tx1 = db.begin(Identity::system()).await?;
+    TestFacingModel::new(&mut tx1)
+        .insert(&table_name, assert_obj!())
+        .await?;
+    let mut tx2 = db.begin(Identity::system()).await?;
+    TestFacingModel::new(&mut tx2)
+        .insert(&table_name, assert_obj!())
+        .await?;
+    db.commit(tx2).await?;
+
+    let db_clone = db.clone();
+    let commit_fut = async move {
+        db_clone.commit(tx1).await?;
+        anyhow::Ok(())
+    };
+    let hold_guard = pause.hold(AFTER_SPILLED_CONFLICTS_CHECK);
+    let db_clone = db.clone();
+    let spill_fut = async move {
+        let pause_guard = hold_guard.wait_for_blocked().await;
+        // Spilling everything without caching any of it would leave the
+        // committer without `tx2`'s commit to check `tx1` against.
+        db_clone.log().spill_for_testing(0);
+        if let Some(pause_guard) = pause_guard {
+            pause_guard.unpause();
+        }
+        anyhow::Ok(())
+    };
+    futures::try_join!(commit_fut, spill_fut)?;
+    Ok(())
+}
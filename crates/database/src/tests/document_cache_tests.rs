@@ -0,0 +1,143 @@
+use std::sync::Arc;
+
+use common::{
+    query::{
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    testing::{
+        fault_injection::{
+            Fault,
+            FaultInjectingPersistence,
+            FaultOp,
+            FaultRule,
+            FaultSchedule,
+        },
+        TestPersistence,
+    },
+};
+use keybroker::Identity;
+use runtime::testing::TestRuntime;
+use value::{
+    assert_obj,
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+use crate::{
+    document_cache::DocumentCachePolicy,
+    query::ResolvedQuery,
+    test_helpers::{
+        DbFixtures,
+        DbFixturesArgs,
+    },
+    Database,
+    TestFacingModel,
+    Transaction,
+};
+
+/// Returns a database with a shared document cache, and a schedule that fails
+/// every fetch from persistence while it's enabled.
+async fn database_with_document_cache(
+    rt: &TestRuntime,
+) -> anyhow::Result<(Database<TestRuntime>, FaultSchedule)> {
+    let schedule = FaultSchedule::new(0).with_rule(FaultRule {
+        op: FaultOp::PersistenceStream,
+        probability: 1.0,
+        fault: Fault::Error,
+        max_faults: None,
+    });
+    schedule.set_enabled(false);
+    let tp = FaultInjectingPersistence::new(
+        Arc::new(TestPersistence::new()),
+        rt.clone(),
+        schedule.clone(),
+    );
+    let DbFixtures { db, .. } = DbFixtures::new_with_args(
+        rt,
+        DbFixturesArgs {
+            tp: Some(Arc::new(tp)),
+            ..Default::default()
+        },
+    )
+    .await?;
+    Ok((
+        db.with_document_cache(1 << 20, DocumentCachePolicy::Lru),
+        schedule,
+    ))
+}
+
+async fn insert<RT: Runtime>(
+    database: &Database<RT>,
+    table_name: &TableName,
+) -> anyhow::Result<ResolvedDocumentId> {
+    let mut tx = database.begin(Identity::system()).await?;
+    let id = TestFacingModel::new(&mut tx)
+        .insert(table_name, assert_obj!())
+        .await?;
+    database.commit(tx).await?;
+    Ok(id)
+}
+
+/// Reads the whole table in one page, so the range can be cached.
+async fn scan<RT: Runtime>(
+    tx: &mut Transaction<RT>,
+    table_name: &TableName,
+) -> anyhow::Result<Vec<ResolvedDocumentId>> {
+    let query = Query::full_table_scan(table_name.clone(), Order::Asc);
+    let mut query_stream = ResolvedQuery::new(tx, TableNamespace::test_user(), query)?;
+    let mut results = vec![];
+    while let Some(document) = query_stream.next(tx, None).await? {
+        results.push(document.id());
+    }
+    Ok(results)
+}
+
+#[convex_macro::test_runtime]
+async fn test_document_cache_misses_after_write(rt: TestRuntime) -> anyhow::Result<()> {
+    let (database, schedule) = database_with_document_cache(&rt).await?;
+    let table_name: TableName = "table".parse()?;
+    let first = insert(&database, &table_name).await?;
+    let mut tx = database.begin(Identity::system()).await?;
+    assert_eq!(scan(&mut tx, &table_name).await?, vec![first]);
+
+    // Nothing has been written to the range since, so a later transaction
+    // reads it from the cache.
+    schedule.set_enabled(true);
+    let mut tx = database.begin(Identity::system()).await?;
+    assert_eq!(scan(&mut tx, &table_name).await?, vec![first]);
+    schedule.set_enabled(false);
+
+    // After a write to the range, the cached read is stale and has to go to
+    // persistence.
+    let second = insert(&database, &table_name).await?;
+    schedule.set_enabled(true);
+    let mut tx = database.begin(Identity::system()).await?;
+    assert!(scan(&mut tx, &table_name).await.is_err());
+    schedule.set_enabled(false);
+    let mut tx = database.begin(Identity::system()).await?;
+    assert_eq!(scan(&mut tx, &table_name).await?, vec![first, second]);
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_document_cache_misses_before_entry_ts(rt: TestRuntime) -> anyhow::Result<()> {
+    let (database, schedule) = database_with_document_cache(&rt).await?;
+    let table_name: TableName = "table".parse()?;
+    let first = insert(&database, &table_name).await?;
+    let mut old_tx = database.begin(Identity::system()).await?;
+    let second = insert(&database, &table_name).await?;
+    let mut tx = database.begin(Identity::system()).await?;
+    assert_eq!(scan(&mut tx, &table_name).await?, vec![first, second]);
+    schedule.set_enabled(true);
+    let mut tx = database.begin(Identity::system()).await?;
+    assert_eq!(scan(&mut tx, &table_name).await?, vec![first, second]);
+    schedule.set_enabled(false);
+
+    // The cached range includes a document inserted after `old_tx` began, so
+    // `old_tx` reads from persistence instead.
+    assert_eq!(scan(&mut old_tx, &table_name).await?, vec![first]);
+    Ok(())
+}
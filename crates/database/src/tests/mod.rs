@@ -24,6 +24,7 @@ use common::{
         },
         schema::SchemaState,
     },
+    components::ComponentId,
     db_schema,
     document::{
         CreationTime,
@@ -58,6 +59,16 @@ use common::{
         TableDefinition,
         MAX_INDEXES_PER_TABLE,
     },
+    testing::{
+        fault_injection::{
+            Fault,
+            FaultInjectingPersistence,
+            FaultOp,
+            FaultRule,
+            FaultSchedule,
+        },
+        TestPersistence,
+    },
     types::{
         unchecked_repeatable_ts,
         IndexDescriptor,
@@ -87,6 +98,8 @@ use keybroker::Identity;
 use must_let::must_let;
 use pretty_assertions::assert_eq;
 use proptest::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
 use runtime::testing::TestRuntime;
 use sync_types::backoff::Backoff;
 use value::{
@@ -96,21 +109,32 @@ use value::{
     val,
     FieldPath,
     ResolvedDocumentId,
+    Size,
     TableMapping,
     TableNamespace,
     TabletIdAndTableNumber,
 };
 
 use crate::{
+    compute_index_statistics,
     index_worker::{
         IndexSelector,
         IndexWriter,
     },
     query::{
+        group_by_batch,
+        query_batch_next_with_prefetches,
+        sample_documents,
+        Aggregate,
+        DeveloperQuery,
+        Group,
+        GroupByQuery,
         PaginationOptions,
         ResolvedQuery,
         TableFilter,
+        MAX_SAMPLE_SIZE,
     },
+    system_tables::ErasedSystemTable,
     table_summary::{
         write_snapshot,
         TableSummary,
@@ -122,20 +146,36 @@ use crate::{
         DbFixturesArgs,
     },
     write_log::WriteSource,
+    ComponentCallCounter,
+    ComponentQuota,
+    ComponentQuotasModel,
+    ComponentQuotasTable,
     Database,
     DatabaseSnapshot,
     ImportFacingModel,
     IndexModel,
+    IndexStatisticsModel,
+    IndexStatisticsTable,
     IndexWorker,
     SchemaModel,
     SystemMetadataModel,
+    TableFreezesModel,
+    TableFreezesTable,
     TableModel,
+    TableSnapshotsModel,
+    TableSnapshotsTable,
     TestFacingModel,
+    Token,
     Transaction,
     UserFacingModel,
+    COMPONENT_QUOTAS_TABLE,
+    INDEX_STATISTICS_TABLE,
+    TABLE_FREEZES_TABLE,
+    TABLE_SNAPSHOTS_TABLE,
 };
 
 mod committer_race_tests;
+mod document_cache_tests;
 mod randomized_search_tests;
 mod streaming_export_tests;
 mod usage_tracking;
@@ -172,6 +212,14 @@ async fn test_load_database(rt: TestRuntime) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn test_ping_committer(rt: TestRuntime) -> anyhow::Result<()> {
+    let DbFixtures { db, .. } = DbFixtures::new(&rt).await?;
+    // Many concurrent pings all get answered, even beyond the ping queue.
+    futures::future::try_join_all((0..64).map(|_| db.ping_committer())).await?;
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn test_load_from_table_summary_snapshot(rt: TestRuntime) -> anyhow::Result<()> {
     let DbFixtures { db, tp, .. } = DbFixtures::new(&rt).await?;
@@ -265,6 +313,7 @@ async fn test_build_indexes(rt: TestRuntime) -> anyhow::Result<()> {
         IndexSchema {
             index_descriptor: index_name1.descriptor().clone(),
             fields: vec![str::parse("a")?, str::parse("b")?].try_into()?,
+            variant: None,
         },
     );
     indexes.insert(
@@ -272,6 +321,7 @@ async fn test_build_indexes(rt: TestRuntime) -> anyhow::Result<()> {
         IndexSchema {
             index_descriptor: index_name2.descriptor().clone(),
             fields: vec![str::parse("c")?, str::parse("d")?].try_into()?,
+            variant: None,
         },
     );
 
@@ -284,6 +334,9 @@ async fn test_build_indexes(rt: TestRuntime) -> anyhow::Result<()> {
             search_indexes: BTreeMap::new(),
             vector_indexes: BTreeMap::new(),
             document_type: None,
+            defaults: Default::default(),
+            mutability: Default::default(),
+            checks: vec![],
         },
     );
     let schema = DatabaseSchema {
@@ -321,6 +374,7 @@ async fn test_build_indexes(rt: TestRuntime) -> anyhow::Result<()> {
         IndexSchema {
             index_descriptor: index_name2.descriptor().clone(),
             fields: vec![str::parse("c")?].try_into()?,
+            variant: None,
         },
     );
     indexes.insert(
@@ -328,6 +382,7 @@ async fn test_build_indexes(rt: TestRuntime) -> anyhow::Result<()> {
         IndexSchema {
             index_descriptor: index_name3.descriptor().clone(),
             fields: vec![str::parse("e")?, str::parse("f")?].try_into()?,
+            variant: None,
         },
     );
 
@@ -340,6 +395,9 @@ async fn test_build_indexes(rt: TestRuntime) -> anyhow::Result<()> {
             search_indexes: BTreeMap::new(),
             vector_indexes: BTreeMap::new(),
             document_type: None,
+            defaults: Default::default(),
+            mutability: Default::default(),
+            checks: vec![],
         },
     );
     let schema = DatabaseSchema {
@@ -440,6 +498,116 @@ async fn test_delete_conflict(rt: TestRuntime) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn test_spilled_write_log(rt: TestRuntime) -> anyhow::Result<()> {
+    let database = new_test_database(rt).await;
+    let mut tx = database.begin(Identity::system()).await?;
+    let id = TestFacingModel::new(&mut tx)
+        .insert(&"key".parse()?, ConvexObject::empty())
+        .await?;
+    database.commit(tx).await?;
+
+    let mut tx1 = database.begin(Identity::system()).await?;
+    assert!(tx1.get(id).await?.is_some());
+    TestFacingModel::new(&mut tx1)
+        .insert(&"key".parse()?, ConvexObject::empty())
+        .await?;
+    let mut tx2 = database.begin(Identity::system()).await?;
+    TestFacingModel::new(&mut tx2)
+        .insert(&"key".parse()?, ConvexObject::empty())
+        .await?;
+    let mut tx3 = database.begin(Identity::system()).await?;
+    assert!(tx3.get(id).await?.is_some());
+    let token = tx3.into_token()?;
+    let empty_token = Token::empty(token.ts());
+
+    let mut tx = database.begin(Identity::system()).await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .delete(id.into())
+        .await?;
+    database.commit(tx).await?;
+
+    // Spill the whole write log without caching any of it.
+    database.log().spill_for_testing(0);
+    let ts = database.log().max_ts();
+    assert!(database
+        .log()
+        .refresh_token(empty_token.clone(), ts)?
+        .is_none());
+
+    // Reading the spilled commits back finds the conflict, and caches them.
+    assert!(database.refresh_token(token, ts).await?.is_none());
+    assert!(database.log().refresh_token(empty_token, ts)?.is_some());
+
+    // Commits are checked against the spilled commits too.
+    must_let!(let Err(e) = database.commit(tx1).await);
+    assert!(e.is_occ(), "{e:?}");
+    database.log().spill_for_testing(0);
+    database.commit(tx2).await?;
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_prefetch_warms_transaction_cache(rt: TestRuntime) -> anyhow::Result<()> {
+    let schedule = FaultSchedule::new(0).with_rule(FaultRule {
+        op: FaultOp::PersistenceStream,
+        probability: 1.0,
+        fault: Fault::Error,
+        max_faults: None,
+    });
+    schedule.set_enabled(false);
+    let tp = FaultInjectingPersistence::new(
+        Arc::new(TestPersistence::new()),
+        rt.clone(),
+        schedule.clone(),
+    );
+    let DbFixtures { db: database, .. } = DbFixtures::new_with_args(
+        &rt,
+        DbFixturesArgs {
+            tp: Some(Arc::new(tp)),
+            ..Default::default()
+        },
+    )
+    .await?;
+    let table_name: TableName = "table".parse()?;
+    let mut tx = database.begin(Identity::system()).await?;
+    let prefetched_id = UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table_name.clone(), assert_obj!("a" => 1))
+        .await?;
+    let other_id = UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table_name.clone(), assert_obj!("a" => 2))
+        .await?;
+    database.commit(tx).await?;
+
+    let get = |tx: &mut Transaction<TestRuntime>, id| {
+        DeveloperQuery::new(
+            tx,
+            TableNamespace::test_user(),
+            Query::get(table_name.clone(), id),
+            TableFilter::ExcludePrivateSystemTables,
+        )
+    };
+    let mut tx = database.begin(Identity::system()).await?;
+    let num_intervals = tx.reads.num_intervals();
+    let prefetch = get(&mut tx, prefetched_id)?;
+    let results =
+        query_batch_next_with_prefetches(BTreeMap::new(), vec![(prefetch, Some(1))], &mut tx).await;
+    assert!(results.is_empty());
+    // Prefetched ranges aren't part of the read set.
+    assert_eq!(tx.reads.num_intervals(), num_intervals);
+
+    // Every fetch from persistence fails from here on, so only the prefetched
+    // document can still be read.
+    schedule.set_enabled(true);
+    let mut query = get(&mut tx, prefetched_id)?;
+    must_let!(let Some(document) = query.next(&mut tx, None).await?);
+    assert_eq!(document.id(), prefetched_id);
+    assert!(tx.reads.num_intervals() > num_intervals);
+    let mut query = get(&mut tx, other_id)?;
+    assert!(query.next(&mut tx, None).await.is_err());
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn test_creation_time_success(rt: TestRuntime) -> anyhow::Result<()> {
     let database = new_test_database(rt.clone()).await;
@@ -962,33 +1130,57 @@ async fn test_query_cursor_reuse(rt: TestRuntime) -> anyhow::Result<()> {
 #[convex_macro::test_runtime]
 async fn test_too_large_values(rt: TestRuntime) -> anyhow::Result<()> {
     let huge_obj = assert_obj!("huge" => vec![0; 1 << 22]);
+    let too_huge_obj = assert_obj!("huge" => vec![0; 1 << 25]);
     let smol_obj = assert_obj!("huge" => vec![0; 1 << 12]);
+    // No single field is large enough to be chunked, so all 1.6MB is inline.
+    let wide_obj = ConvexObject::try_from(
+        (0..8)
+            .map(|i| {
+                Ok((
+                    format!("field{i}").parse()?,
+                    ConvexValue::try_from(vec![0; 200 << 10])?,
+                ))
+            })
+            .collect::<anyhow::Result<BTreeMap<_, _>>>()?,
+    )?;
 
     let database = new_test_database(rt).await;
     let table_name: TableName = "table".parse()?;
 
     let mut tx = database.begin(Identity::system()).await?;
-    let err = UserFacingModel::new_root_for_test(&mut tx)
-        .insert(table_name.clone(), huge_obj.clone())
-        .await
-        .unwrap_err();
-    assert!(format!("{err}").contains("Value is too large"));
+    for obj in [&too_huge_obj, &wide_obj] {
+        let err = UserFacingModel::new_root_for_test(&mut tx)
+            .insert(table_name.clone(), obj.clone())
+            .await
+            .unwrap_err();
+        assert!(format!("{err}").contains("Value is too large"));
+    }
 
     let doc_id = UserFacingModel::new_root_for_test(&mut tx)
-        .insert(table_name, smol_obj)
+        .insert(table_name.clone(), smol_obj)
         .await?;
 
-    let err = UserFacingModel::new_root_for_test(&mut tx)
-        .patch(doc_id, huge_obj.clone().into())
-        .await
-        .unwrap_err();
-    assert!(format!("{err}").contains("Value is too large"), "{err}");
+    for obj in [&too_huge_obj, &wide_obj] {
+        let err = UserFacingModel::new_root_for_test(&mut tx)
+            .patch(doc_id, obj.clone().into())
+            .await
+            .unwrap_err();
+        assert!(format!("{err}").contains("Value is too large"), "{err}");
 
-    let err = UserFacingModel::new_root_for_test(&mut tx)
-        .replace(doc_id, huge_obj.clone())
-        .await
-        .unwrap_err();
-    assert!(format!("{err}").contains("Value is too large"));
+        let err = UserFacingModel::new_root_for_test(&mut tx)
+            .replace(doc_id, obj.clone())
+            .await
+            .unwrap_err();
+        assert!(format!("{err}").contains("Value is too large"));
+    }
+
+    // A single 4MB field is chunked, so the document is allowed.
+    let huge_id = UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table_name, huge_obj.clone())
+        .await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .replace(huge_id, huge_obj.clone())
+        .await?;
 
     // Check that inserting a 4MB value to a system table works.
     let table_name = "_test_table".parse()?;
@@ -1003,6 +1195,45 @@ async fn test_too_large_values(rt: TestRuntime) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn test_indexed_fields_must_be_inline(rt: TestRuntime) -> anyhow::Result<()> {
+    let database = new_test_database(rt).await;
+    let table_name: TableName = "table".parse()?;
+    let namespace = TableNamespace::test_user();
+
+    let mut tx = database.begin(Identity::system()).await?;
+    IndexModel::new(&mut tx)
+        .add_application_index(
+            namespace,
+            IndexMetadata::new_enabled(
+                IndexName::new(table_name.clone(), IndexDescriptor::new("by_body")?)?,
+                vec![str::parse("body.title")?].try_into()?,
+            ),
+        )
+        .await?;
+    database.commit(tx).await?;
+
+    let mut tx = database.begin(Identity::system()).await?;
+    let err = UserFacingModel::new_root_for_test(&mut tx)
+        .insert(
+            table_name.clone(),
+            assert_obj!("body" => {"title" => "a", "text" => "x".repeat(1 << 20)}),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "IndexedFieldTooLarge");
+
+    // Large fields that aren't indexed are fine.
+    UserFacingModel::new_root_for_test(&mut tx)
+        .insert(
+            table_name,
+            assert_obj!("body" => {"title" => "a"}, "text" => "x".repeat(1 << 20)),
+        )
+        .await?;
+    database.commit(tx).await?;
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn test_too_nested_values(rt: TestRuntime) -> anyhow::Result<()> {
     let mut deeply_nested_but_still_ok = assert_val!(false);
@@ -2517,3 +2748,670 @@ async fn test_schema_registry_takes_read_dependency(rt: TestRuntime) -> anyhow::
     );
     Ok(())
 }
+
+#[convex_macro::test_runtime]
+async fn test_index_statistics_estimates(rt: TestRuntime) -> anyhow::Result<()> {
+    let database = new_test_database(rt).await;
+    let table_name: TableName = "table".parse()?;
+    let namespace = TableNamespace::test_user();
+    let index_name = IndexName::new(table_name.clone(), IndexDescriptor::new("by_a")?)?;
+
+    let mut tx = database.begin(Identity::system()).await?;
+    tx.create_system_table_testing(TableNamespace::Global, &INDEX_STATISTICS_TABLE, None)
+        .await?;
+    for index in ErasedSystemTable::indexes(&IndexStatisticsTable) {
+        IndexModel::new(&mut tx)
+            .add_system_index(
+                TableNamespace::Global,
+                IndexMetadata::new_enabled(index.name, index.fields),
+            )
+            .await?;
+    }
+    IndexModel::new(&mut tx)
+        .add_application_index(
+            namespace,
+            IndexMetadata::new_enabled(index_name.clone(), vec![str::parse("a")?].try_into()?),
+        )
+        .await?;
+    database.commit(tx).await?;
+
+    let mut tx = database.begin(Identity::system()).await?;
+    for i in 0..1000i64 {
+        TestFacingModel::new(&mut tx)
+            .insert(&table_name, assert_obj!("a" => i % 100))
+            .await?;
+    }
+    database.commit(tx).await?;
+
+    let by_a = |range| Query {
+        source: QuerySource::IndexRange(IndexRange {
+            index_name: index_name.clone(),
+            range,
+            order: Order::Asc,
+        }),
+        operators: vec![],
+    };
+
+    // Range estimates need statistics, but full table scans don't.
+    let mut tx = database.begin(Identity::system()).await?;
+    let full_scan = Query::full_table_scan(table_name.clone(), Order::Asc);
+    assert_eq!(
+        IndexStatisticsModel::new(&mut tx)
+            .estimated_count(
+                namespace,
+                full_scan,
+                TableFilter::IncludePrivateSystemTables
+            )
+            .await?,
+        1000
+    );
+    let err = IndexStatisticsModel::new(&mut tx)
+        .estimated_count(
+            namespace,
+            by_a(vec![IndexRangeExpression::Lt("a".parse()?, maybe_val!(50))]),
+            TableFilter::IncludePrivateSystemTables,
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "IndexStatisticsUnavailable");
+
+    let index = IndexModel::new(&mut tx)
+        .enabled_index_metadata(namespace, &index_name)?
+        .unwrap();
+    must_let!(let IndexConfig::Database {
+        developer_config: DeveloperDatabaseIndexConfig { fields },
+        ..
+    } = index.config.clone());
+    let statistics = compute_index_statistics(
+        &database,
+        database.now_ts_for_reads(),
+        *index.name.table(),
+        index.id().internal_id(),
+        fields,
+        0,
+    )
+    .await?;
+    assert_eq!(statistics.count, 1000);
+    IndexStatisticsModel::new(&mut tx)
+        .record(statistics)
+        .await?;
+    database.commit(tx).await?;
+
+    let mut tx = database.begin(Identity::system()).await?;
+    let estimate = IndexStatisticsModel::new(&mut tx)
+        .estimated_count(
+            namespace,
+            by_a(vec![IndexRangeExpression::Lt("a".parse()?, maybe_val!(50))]),
+            TableFilter::IncludePrivateSystemTables,
+        )
+        .await?;
+    assert!((400..=600).contains(&estimate), "{estimate}");
+    let distinct = IndexStatisticsModel::new(&mut tx)
+        .approx_distinct(
+            namespace,
+            by_a(vec![]),
+            "a".parse()?,
+            TableFilter::IncludePrivateSystemTables,
+        )
+        .await?;
+    assert!((95..=105).contains(&distinct), "{distinct}");
+    let err = IndexStatisticsModel::new(&mut tx)
+        .approx_distinct(
+            namespace,
+            by_a(vec![]),
+            "b".parse()?,
+            TableFilter::IncludePrivateSystemTables,
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "InvalidApproxDistinctField");
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_group_by_index_prefix(rt: TestRuntime) -> anyhow::Result<()> {
+    let database = new_test_database(rt).await;
+    let table_name: TableName = "table".parse()?;
+    let namespace = TableNamespace::test_user();
+    let index_name = IndexName::new(table_name.clone(), IndexDescriptor::new("by_a_b")?)?;
+
+    let mut tx = database.begin(Identity::system()).await?;
+    IndexModel::new(&mut tx)
+        .add_application_index(
+            namespace,
+            IndexMetadata::new_enabled(
+                index_name.clone(),
+                vec![str::parse("a")?, str::parse("b")?].try_into()?,
+            ),
+        )
+        .await?;
+    database.commit(tx).await?;
+
+    let mut tx = database.begin(Identity::system()).await?;
+    for i in 0..3000i64 {
+        TestFacingModel::new(&mut tx)
+            .insert(&table_name, assert_obj!("a" => i % 3, "b" => i))
+            .await?;
+    }
+    TestFacingModel::new(&mut tx)
+        .insert(&table_name, assert_obj!("b" => 1.5))
+        .await?;
+    database.commit(tx).await?;
+
+    let query = |range| Query {
+        source: QuerySource::IndexRange(IndexRange {
+            index_name: index_name.clone(),
+            range,
+            order: Order::Asc,
+        }),
+        operators: vec![],
+    };
+    let aggregates = vec![
+        Aggregate::Count,
+        Aggregate::Sum("b".parse()?),
+        Aggregate::Min("b".parse()?),
+        Aggregate::Max("b".parse()?),
+    ];
+    let mut tx = database.begin(Identity::system()).await?;
+    let groups = GroupByQuery::new(
+        &mut tx,
+        namespace,
+        query(vec![]),
+        vec!["a".parse()?],
+        aggregates.clone(),
+        TableFilter::IncludePrivateSystemTables,
+    )?
+    .execute(&mut tx)
+    .await?;
+    let sum = |a: i64| (0..3000i64).filter(|i| i % 3 == a).sum::<i64>();
+    assert_eq!(
+        groups,
+        vec![
+            Group {
+                key: vec![None],
+                values: vec![
+                    Some(val!(1.)),
+                    Some(val!(1.5)),
+                    Some(val!(1.5)),
+                    Some(val!(1.5))
+                ],
+            },
+            Group {
+                key: vec![Some(val!(0))],
+                values: vec![
+                    Some(val!(1000.)),
+                    Some(val!(sum(0))),
+                    Some(val!(0)),
+                    Some(val!(2997))
+                ],
+            },
+            Group {
+                key: vec![Some(val!(1))],
+                values: vec![
+                    Some(val!(1000.)),
+                    Some(val!(sum(1))),
+                    Some(val!(1)),
+                    Some(val!(2998))
+                ],
+            },
+            Group {
+                key: vec![Some(val!(2))],
+                values: vec![
+                    Some(val!(1000.)),
+                    Some(val!(sum(2))),
+                    Some(val!(2)),
+                    Some(val!(2999))
+                ],
+            },
+        ]
+    );
+    // Each group counts as one read, rather than each document scanned.
+    assert_eq!(tx.reads.user_tx_size().total_document_count, 4);
+
+    // Index ranges restrict the documents grouped.
+    let groups = GroupByQuery::new(
+        &mut tx,
+        namespace,
+        query(vec![IndexRangeExpression::Eq("a".parse()?, maybe_val!(1))]),
+        vec![],
+        vec![Aggregate::Count],
+        TableFilter::IncludePrivateSystemTables,
+    )?
+    .execute(&mut tx)
+    .await?;
+    assert_eq!(
+        groups,
+        vec![Group {
+            key: vec![],
+            values: vec![Some(val!(1000.))],
+        }]
+    );
+
+    let err = GroupByQuery::new(
+        &mut tx,
+        namespace,
+        query(vec![]),
+        vec!["b".parse()?],
+        aggregates,
+        TableFilter::IncludePrivateSystemTables,
+    )
+    .err()
+    .unwrap();
+    assert_eq!(err.short_msg(), "InvalidGroupByQuery");
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_group_by_batch(rt: TestRuntime) -> anyhow::Result<()> {
+    let database = new_test_database(rt).await;
+    let namespace = TableNamespace::test_user();
+    let tables: Vec<TableName> = vec!["small".parse()?, "large".parse()?, "missing".parse()?];
+
+    let mut tx = database.begin(Identity::system()).await?;
+    for i in 0..10i64 {
+        TestFacingModel::new(&mut tx)
+            .insert(&tables[0], assert_obj!("a" => i))
+            .await?;
+    }
+    // Spans several fetches, so the scans finish in different rounds.
+    for i in 0..3000i64 {
+        TestFacingModel::new(&mut tx)
+            .insert(&tables[1], assert_obj!("a" => i))
+            .await?;
+    }
+    database.commit(tx).await?;
+
+    let mut tx = database.begin(Identity::system()).await?;
+    let mut batch = BTreeMap::new();
+    for (batch_key, table_name) in tables.iter().enumerate() {
+        let query = GroupByQuery::new(
+            &mut tx,
+            namespace,
+            Query::full_table_scan(table_name.clone(), Order::Asc),
+            vec![],
+            vec![Aggregate::Count, Aggregate::Max("a".parse()?)],
+            TableFilter::IncludePrivateSystemTables,
+        )?;
+        batch.insert(batch_key, query);
+    }
+    let mut results = group_by_batch(batch, &mut tx).await;
+    assert_eq!(
+        results.remove(&0).unwrap()?,
+        vec![Group {
+            key: vec![],
+            values: vec![Some(val!(10.)), Some(val!(9))],
+        }]
+    );
+    assert_eq!(
+        results.remove(&1).unwrap()?,
+        vec![Group {
+            key: vec![],
+            values: vec![Some(val!(3000.)), Some(val!(2999))],
+        }]
+    );
+    assert_eq!(results.remove(&2).unwrap()?, vec![]);
+    // The reads of every query in the batch are recorded in the transaction.
+    assert_eq!(tx.reads.user_tx_size().total_document_count, 2);
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_sample_documents(rt: TestRuntime) -> anyhow::Result<()> {
+    let database = new_test_database(rt).await;
+    let table_name: TableName = "table".parse()?;
+    let namespace = TableNamespace::test_user();
+    let mut rng = ChaCha12Rng::seed_from_u64(0);
+
+    let mut tx = database.begin(Identity::system()).await?;
+    for i in 0..500i64 {
+        TestFacingModel::new(&mut tx)
+            .insert(&table_name, assert_obj!("i" => i))
+            .await?;
+    }
+    database.commit(tx).await?;
+
+    let mut tx = database.begin(Identity::system()).await?;
+    let sample = sample_documents(
+        &mut tx,
+        namespace,
+        table_name.clone(),
+        20,
+        &mut rng,
+        TableFilter::IncludePrivateSystemTables,
+    )
+    .await?;
+    assert_eq!(sample.len(), 20);
+    let ids: BTreeSet<_> = sample.iter().map(|document| document.id()).collect();
+    assert_eq!(ids.len(), 20);
+    // Each draw reads a single document.
+    assert!(tx.reads.user_tx_size().total_document_count < 40);
+
+    // Tables no bigger than the sample are returned in full.
+    let sample = sample_documents(
+        &mut tx,
+        namespace,
+        table_name.clone(),
+        500,
+        &mut rng,
+        TableFilter::IncludePrivateSystemTables,
+    )
+    .await?;
+    assert_eq!(sample.len(), 500);
+
+    let sample = sample_documents(
+        &mut tx,
+        namespace,
+        "missing".parse()?,
+        10,
+        &mut rng,
+        TableFilter::IncludePrivateSystemTables,
+    )
+    .await?;
+    assert!(sample.is_empty());
+
+    let err = sample_documents(
+        &mut tx,
+        namespace,
+        table_name,
+        MAX_SAMPLE_SIZE + 1,
+        &mut rng,
+        TableFilter::IncludePrivateSystemTables,
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(err.short_msg(), "InvalidSampleSize");
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_table_freeze_rejects_writes(rt: TestRuntime) -> anyhow::Result<()> {
+    let database = new_test_database(rt.clone()).await;
+    let table_name: TableName = "table".parse()?;
+
+    let mut tx = database.begin(Identity::system()).await?;
+    tx.create_system_table_testing(TableNamespace::Global, &TABLE_FREEZES_TABLE, None)
+        .await?;
+    for index in ErasedSystemTable::indexes(&TableFreezesTable) {
+        IndexModel::new(&mut tx)
+            .add_system_index(
+                TableNamespace::Global,
+                IndexMetadata::new_enabled(index.name, index.fields),
+            )
+            .await?;
+    }
+    let id = UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table_name.clone(), assert_obj!("a" => 1))
+        .await?;
+    database.commit(tx).await?;
+
+    let mut tx = database.begin(Identity::system()).await?;
+    let tablet_id = tx
+        .table_mapping()
+        .namespace(TableNamespace::test_user())
+        .id(&table_name)?
+        .tablet_id;
+    TableFreezesModel::new(&mut tx)
+        .freeze(tablet_id, "migration".to_string(), Duration::from_secs(60))
+        .await?;
+    database.commit(tx).await?;
+
+    let mut tx = database.begin(Identity::system()).await?;
+    let err = UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table_name.clone(), assert_obj!("a" => 2))
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "TableFrozen");
+    let err = UserFacingModel::new_root_for_test(&mut tx)
+        .delete(id)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "TableFrozen");
+    // Reads are unaffected.
+    assert!(UserFacingModel::new_root_for_test(&mut tx)
+        .get(id, None)
+        .await?
+        .is_some());
+    assert_eq!(TableFreezesModel::new(&mut tx).list().await?.len(), 1);
+
+    let mut tx = database.begin(Identity::system()).await?;
+    assert!(TableFreezesModel::new(&mut tx).unfreeze(tablet_id).await?);
+    UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table_name.clone(), assert_obj!("a" => 2))
+        .await?;
+    database.commit(tx).await?;
+
+    // Freezes lift on their own once they expire.
+    let mut tx = database.begin(Identity::system()).await?;
+    TableFreezesModel::new(&mut tx)
+        .freeze(tablet_id, "export".to_string(), Duration::from_secs(60))
+        .await?;
+    database.commit(tx).await?;
+    rt.advance_time(Duration::from_secs(61)).await;
+    let mut tx = database.begin(Identity::system()).await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table_name.clone(), assert_obj!("a" => 3))
+        .await?;
+    assert!(TableFreezesModel::new(&mut tx).list().await?.is_empty());
+    database.commit(tx).await?;
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_table_snapshot_reads_past_contents(rt: TestRuntime) -> anyhow::Result<()> {
+    let database = new_test_database(rt).await;
+    let namespace = TableNamespace::test_user();
+    let table_name: TableName = "table".parse()?;
+    let snapshot_name: TableName = "table_before".parse()?;
+
+    async fn read_a(
+        tx: &mut Transaction<TestRuntime>,
+        table_name: &TableName,
+        order: Order,
+    ) -> anyhow::Result<Vec<ConvexValue>> {
+        let query = Query::full_table_scan(table_name.clone(), order);
+        let mut query_stream = DeveloperQuery::new(
+            tx,
+            TableNamespace::test_user(),
+            query,
+            TableFilter::ExcludePrivateSystemTables,
+        )?;
+        let mut values = vec![];
+        while let Some(document) = query_stream.next(tx, None).await? {
+            values.push(document.value().0.get("a").unwrap().clone());
+        }
+        Ok(values)
+    }
+
+    let mut tx = database.begin(Identity::system()).await?;
+    tx.create_system_table_testing(TableNamespace::Global, &TABLE_SNAPSHOTS_TABLE, None)
+        .await?;
+    for index in ErasedSystemTable::indexes(&TableSnapshotsTable) {
+        IndexModel::new(&mut tx)
+            .add_system_index(
+                TableNamespace::Global,
+                IndexMetadata::new_enabled(index.name, index.fields),
+            )
+            .await?;
+    }
+    let id = UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table_name.clone(), assert_obj!("a" => 1))
+        .await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table_name.clone(), assert_obj!("a" => 2))
+        .await?;
+    database.commit(tx).await?;
+
+    let mut tx = database.begin(Identity::system()).await?;
+    TableSnapshotsModel::new(&mut tx, namespace)
+        .create(snapshot_name.clone(), table_name.clone(), None)
+        .await?;
+    let err = TableSnapshotsModel::new(&mut tx, namespace)
+        .create(table_name.clone(), table_name.clone(), None)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "TableSnapshotNameTaken");
+    database.commit(tx).await?;
+
+    let mut tx = database.begin(Identity::system()).await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .delete(id)
+        .await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table_name.clone(), assert_obj!("a" => 3))
+        .await?;
+    database.commit(tx).await?;
+
+    // The snapshot still has the table's contents from before the writes.
+    let mut tx = database.begin(Identity::system()).await?;
+    assert_eq!(
+        read_a(&mut tx, &table_name, Order::Asc).await?,
+        vec![val!(2), val!(3)]
+    );
+    assert_eq!(
+        read_a(&mut tx, &snapshot_name, Order::Asc).await?,
+        vec![val!(1), val!(2)]
+    );
+    let err = read_a(&mut tx, &snapshot_name, Order::Desc)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "InvalidTableSnapshotQuery");
+
+    let mut tx = database.begin(Identity::system()).await?;
+    assert!(
+        TableSnapshotsModel::new(&mut tx, namespace)
+            .delete(&snapshot_name)
+            .await?
+    );
+    database.commit(tx).await?;
+    let mut tx = database.begin(Identity::system()).await?;
+    assert!(read_a(&mut tx, &snapshot_name, Order::Asc)
+        .await?
+        .is_empty());
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_component_function_call_quota(rt: TestRuntime) -> anyhow::Result<()> {
+    let database = new_test_database(rt.clone()).await;
+    let component_id = DeveloperDocumentId::MIN;
+    let component = ComponentId::Child(component_id);
+    let counter = ComponentCallCounter::default();
+
+    let mut tx = database.begin(Identity::system()).await?;
+    tx.create_system_table_testing(TableNamespace::Global, &COMPONENT_QUOTAS_TABLE, None)
+        .await?;
+    for index in ErasedSystemTable::indexes(&ComponentQuotasTable) {
+        IndexModel::new(&mut tx)
+            .add_system_index(
+                TableNamespace::Global,
+                IndexMetadata::new_enabled(index.name, index.fields),
+            )
+            .await?;
+    }
+    let quota = ComponentQuota {
+        max_function_calls_per_minute: Some(2),
+        ..Default::default()
+    };
+    ComponentQuotasModel::new(&mut tx)
+        .set(component_id, quota.clone())
+        .await?;
+    database.commit(tx).await?;
+
+    let mut tx = database.begin(Identity::system()).await?;
+    assert_eq!(
+        ComponentQuotasModel::new(&mut tx).quota(component).await?,
+        quota
+    );
+    for _ in 0..2 {
+        ComponentQuotasModel::new(&mut tx)
+            .check_function_call(component, &counter)
+            .await?;
+    }
+    let err = ComponentQuotasModel::new(&mut tx)
+        .check_function_call(component, &counter)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "ComponentQuotaExceeded");
+    // The root component is never limited.
+    ComponentQuotasModel::new(&mut tx)
+        .check_function_call(ComponentId::Root, &counter)
+        .await?;
+
+    // The count starts over each minute.
+    rt.advance_time(Duration::from_secs(60)).await;
+    let mut tx = database.begin(Identity::system()).await?;
+    ComponentQuotasModel::new(&mut tx)
+        .check_function_call(component, &counter)
+        .await?;
+
+    // Setting no limits removes the quota.
+    ComponentQuotasModel::new(&mut tx)
+        .set(component_id, ComponentQuota::default())
+        .await?;
+    database.commit(tx).await?;
+    let mut tx = database.begin(Identity::system()).await?;
+    assert!(ComponentQuotasModel::new(&mut tx)
+        .quota(component)
+        .await?
+        .is_unlimited());
+    for _ in 0..3 {
+        ComponentQuotasModel::new(&mut tx)
+            .check_function_call(component, &counter)
+            .await?;
+    }
+    Ok(())
+}
+
+/// The user document count and size of the test namespace in the latest
+/// snapshot.
+fn namespace_usage(database: &Database<TestRuntime>) -> anyhow::Result<(u64, u64)> {
+    let table_summaries = database
+        .latest_snapshot()?
+        .table_summaries
+        .expect("Table summaries should be bootstrapped");
+    Ok(table_summaries
+        .user_usage_by_namespace
+        .get(&TableNamespace::test_user())
+        .copied()
+        .unwrap_or_default())
+}
+
+#[convex_macro::test_runtime]
+async fn test_namespace_usage_running_totals(rt: TestRuntime) -> anyhow::Result<()> {
+    let database = new_test_database(rt).await;
+    let table_name: TableName = "table".parse()?;
+    assert_eq!(namespace_usage(&database)?, (0, 0));
+
+    let mut tx = database.begin(Identity::system()).await?;
+    let first = TestFacingModel::new(&mut tx)
+        .insert(&table_name, assert_obj!("a" => 1))
+        .await?;
+    let second = TestFacingModel::new(&mut tx)
+        .insert(&"other".parse()?, assert_obj!("b" => "hello"))
+        .await?;
+    let first_size = tx.get(first).await?.unwrap().value().0.size() as u64;
+    let second_size = tx.get(second).await?.unwrap().value().0.size() as u64;
+    // The transaction's own writes are tracked as deltas until it commits.
+    assert_eq!(
+        tx.namespace_usage_deltas
+            .get(&TableNamespace::test_user())
+            .copied(),
+        Some((2, (first_size + second_size) as i64))
+    );
+    database.commit(tx).await?;
+    assert_eq!(namespace_usage(&database)?, (2, first_size + second_size));
+
+    let mut tx = database.begin(Identity::system()).await?;
+    tx.delete_inner(second).await?;
+    database.commit(tx).await?;
+    assert_eq!(namespace_usage(&database)?, (1, first_size));
+
+    // Dropping a table frees up its documents.
+    let mut tx = database.begin(Identity::system()).await?;
+    TableModel::new(&mut tx)
+        .delete_active_table(TableNamespace::test_user(), table_name)
+        .await?;
+    database.commit(tx).await?;
+    assert_eq!(namespace_usage(&database)?, (0, 0));
+    Ok(())
+}
@@ -16,10 +16,20 @@ use async_trait::async_trait;
 use cmd_util::env::env_config;
 use common::{
     bootstrap_model::index::{
-        text_index::FragmentedTextSegment,
+        text_index::{
+            AutocompleteConfig,
+            DeveloperTextIndexConfig,
+            FragmentedTextSegment,
+            TextAnalyzerConfig,
+        },
         vector_index::FragmentedVectorSegment,
         IndexMetadata,
     },
+    document::{
+        CreationTime,
+        PackedDocument,
+        ResolvedDocument,
+    },
     floating_point::assert_approx_equal,
     knobs::DATABASE_WORKERS_MAX_CHECKPOINT_AGE,
     pause::PauseController,
@@ -31,11 +41,14 @@ use common::{
         QuerySource,
         Search,
         SearchFilterExpression,
+        SearchSynonyms,
         SearchVersion,
+        TextSearchOptions,
     },
     types::{
         IndexDescriptor,
         IndexName,
+        PersistenceVersion,
         Timestamp,
     },
     value::{
@@ -57,7 +70,10 @@ use futures::{
     FutureExt,
 };
 use keybroker::Identity;
-use maplit::btreeset;
+use maplit::{
+    btreemap,
+    btreeset,
+};
 use must_let::must_let;
 use pb::searchlight::FragmentedVectorSegmentPaths;
 use proptest::prelude::*;
@@ -84,6 +100,8 @@ use storage::Storage;
 use usage_tracking::FunctionUsageTracker;
 use value::{
     assert_obj,
+    val,
+    FieldPath,
     TableNamespace,
 };
 use vector::{
@@ -104,6 +122,12 @@ use crate::{
         TableFilter,
     },
     search_index_bootstrap::FINISHED_BOOTSTRAP_UPDATES,
+    search_synonyms::{
+        SearchSynonymsModel,
+        SearchSynonymsTable,
+        SEARCH_SYNONYMS_TABLE,
+    },
+    system_tables::ErasedSystemTable,
     test_helpers::{
         DbFixtures,
         DbFixturesArgs,
@@ -148,6 +172,21 @@ impl Scenario {
     }
 
     async fn new_with_searcher(rt: TestRuntime, searcher: impl Searcher) -> anyhow::Result<Self> {
+        Self::new_with_config(rt, searcher, Self::index_config()?).await
+    }
+
+    fn index_config() -> anyhow::Result<DeveloperTextIndexConfig> {
+        Ok(DeveloperTextIndexConfig::new(
+            "searchField".parse()?,
+            btreeset! {"filterField".parse()?},
+        ))
+    }
+
+    async fn new_with_config(
+        rt: TestRuntime,
+        searcher: impl Searcher,
+        index_config: DeveloperTextIndexConfig,
+    ) -> anyhow::Result<Self> {
         let DbFixtures {
             db: database,
             search_storage,
@@ -170,11 +209,8 @@ impl Scenario {
         TableModel::new(&mut tx)
             .insert_table_metadata_for_test(TableNamespace::test_user(), &table_name)
             .await?;
-        let index = IndexMetadata::new_backfilling_text_index(
-            "test.by_text".parse()?,
-            "searchField".parse()?,
-            btreeset! {"filterField".parse()?},
-        );
+        let index =
+            IndexMetadata::new_backfilling_text_index("test.by_text".parse()?, index_config);
         let index_id = IndexModel::new(&mut tx)
             .add_application_index(namespace, index)
             .await?;
@@ -283,19 +319,16 @@ impl Scenario {
         &self,
         tx: &mut Transaction<TestRuntime>,
         query_string: S,
-        filter: Option<String>,
+        options: TextSearchOptions,
+        filter: Option<SearchFilterExpression>,
         version: SearchVersion,
     ) -> anyhow::Result<Vec<(ResolvedDocumentId, f64)>> {
         let mut filters = vec![SearchFilterExpression::Search(
             "searchField".parse()?,
             query_string.into(),
+            options,
         )];
-        if let Some(filter_field) = filter {
-            filters.push(SearchFilterExpression::Eq(
-                "filterField".parse()?,
-                Some(filter_field.try_into()?),
-            ));
-        }
+        filters.extend(filter);
         let search = Search {
             index_name: "test.by_text".parse()?,
             table: self.table_name.clone(),
@@ -358,8 +391,54 @@ impl Scenario {
             .database
             .begin_with_ts(Identity::system(), ts, FunctionUsageTracker::new())
             .await?;
-        self.query_in_tx(&mut tx, query_string, filter, version)
-            .await
+        let filter = filter
+            .map(|filter_field| {
+                anyhow::Ok(SearchFilterExpression::Eq(
+                    "filterField".parse()?,
+                    Some(filter_field.try_into()?),
+                ))
+            })
+            .transpose()?;
+        self.query_in_tx(
+            &mut tx,
+            query_string,
+            TextSearchOptions::default(),
+            filter,
+            version,
+        )
+        .await
+    }
+
+    async fn query_with_filter(
+        &self,
+        query_string: &str,
+        filter: SearchFilterExpression,
+    ) -> anyhow::Result<Vec<(ResolvedDocumentId, f64)>> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        self.query_in_tx(
+            &mut tx,
+            query_string,
+            TextSearchOptions::default(),
+            Some(filter),
+            SearchVersion::V2,
+        )
+        .await
+    }
+
+    async fn query_with_typos(
+        &self,
+        query_string: &str,
+        max_typos: u8,
+    ) -> anyhow::Result<Vec<(ResolvedDocumentId, f64)>> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        self.query_in_tx(
+            &mut tx,
+            query_string,
+            TextSearchOptions::new(max_typos)?,
+            None,
+            SearchVersion::V2,
+        )
+        .await
     }
 
     async fn query_with_scores(
@@ -467,6 +546,32 @@ impl Scenario {
         Ok((document_id, ts))
     }
 
+    async fn set_synonyms(&self, groups: Vec<Vec<&str>>) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let is_new = tx
+            .create_system_table_testing(TableNamespace::Global, &SEARCH_SYNONYMS_TABLE, None)
+            .await?;
+        if is_new {
+            for index in ErasedSystemTable::indexes(&SearchSynonymsTable) {
+                IndexModel::new(&mut tx)
+                    .add_system_index(
+                        TableNamespace::Global,
+                        IndexMetadata::new_enabled(index.name, index.fields),
+                    )
+                    .await?;
+            }
+        }
+        let groups = groups
+            .into_iter()
+            .map(|group| group.into_iter().map(String::from).collect())
+            .collect();
+        SearchSynonymsModel::new(&mut tx)
+            .set(self.index_id.internal_id(), SearchSynonyms::new(groups))
+            .await?;
+        self.database.commit(tx).await?;
+        Ok(())
+    }
+
     async fn execute(&mut self, action: TestAction) -> anyhow::Result<()> {
         match action {
             TestAction::Backfill => {
@@ -899,6 +1004,512 @@ async fn test_prefix_mem(rt: TestRuntime) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn test_typo_tolerance(rt: TestRuntime) -> anyhow::Result<()> {
+    let mut scenario = Scenario::new(rt).await?;
+    let (elephant, _) = scenario
+        ._patch("a", "elephant crossing the river", "test")
+        .await?;
+    let (international, _) = scenario
+        ._patch("b", "international shipping", "test")
+        .await?;
+    let (misspelled, _) = scenario._patch("c", "elephent sanctuary", "test").await?;
+    let ids = |results: Vec<(ResolvedDocumentId, f64)>| {
+        results.into_iter().map(|(id, _)| id).collect::<Vec<_>>()
+    };
+
+    for backfill in [false, true] {
+        if backfill {
+            scenario.backfill().await?;
+        }
+        // Typos aren't tolerated unless the query asks for them.
+        let results = scenario.query_with_typos("elephant zzzz", 0).await?;
+        assert_eq!(ids(results), vec![elephant]);
+
+        // Exact matches rank above matches with typos.
+        let results = scenario.query_with_typos("elephant zzzz", 1).await?;
+        assert_eq!(ids(results), vec![elephant, misspelled]);
+
+        // Short tokens never tolerate typos.
+        assert!(scenario.query_with_typos("rivr zzzz", 2).await?.is_empty());
+
+        // Two typos need a long token and a query that allows them.
+        let results = scenario.query_with_typos("internatoinal zzzz", 1).await?;
+        assert!(results.is_empty());
+        let results = scenario.query_with_typos("internatoinal zzzz", 2).await?;
+        assert_eq!(ids(results), vec![international]);
+
+        // Typos can't change the first character.
+        let results = scenario.query_with_typos("xnternational zzzz", 2).await?;
+        assert!(results.is_empty());
+    }
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_phrase_and_proximity(rt: TestRuntime) -> anyhow::Result<()> {
+    let mut scenario = Scenario::new(rt).await?;
+    let (in_order, _) = scenario
+        ._patch("a", "the quick brown fox jumps", "test")
+        .await?;
+    let (reversed, _) = scenario._patch("b", "brown quick fox", "test").await?;
+    let (apart, _) = scenario._patch("c", "quick red brown fox", "test").await?;
+    let ids = |results: Vec<(ResolvedDocumentId, f64)>| {
+        results
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect::<BTreeSet<_>>()
+    };
+
+    for backfill in [false, true] {
+        if backfill {
+            scenario.backfill().await?;
+        }
+        let results = scenario.query_with_typos("quick brown", 0).await?;
+        assert_eq!(ids(results), btreeset! {in_order, reversed, apart});
+
+        // Phrases match consecutive terms in order.
+        let results = scenario.query_with_typos("\"quick brown\"", 0).await?;
+        assert_eq!(ids(results), btreeset! {in_order});
+        let results = scenario.query_with_typos("\"brown quick\" fox", 0).await?;
+        assert_eq!(ids(results), btreeset! {reversed});
+        let results = scenario.query_with_typos("\"brown fox\"", 0).await?;
+        assert_eq!(ids(results), btreeset! {in_order, apart});
+
+        // Proximity matches terms in either order.
+        let results = scenario.query_with_typos("quick NEAR/1 brown", 0).await?;
+        assert_eq!(ids(results), btreeset! {in_order, reversed});
+
+        // Closer matches rank higher.
+        let results = scenario.query_with_typos("quick NEAR/2 brown", 0).await?;
+        assert_eq!(results.last().map(|(id, _)| *id), Some(apart));
+        assert_eq!(ids(results), btreeset! {in_order, reversed, apart});
+
+        // Phrase terms must match exactly, even as the last token.
+        assert!(scenario
+            .query_with_typos("\"quick bro\"", 2)
+            .await?
+            .is_empty());
+    }
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_stemming_and_stopwords(rt: TestRuntime) -> anyhow::Result<()> {
+    let index_config = DeveloperTextIndexConfig {
+        analyzer: TextAnalyzerConfig {
+            stemming: true,
+            stopwords: btreeset! {"the".to_string()},
+            ..Default::default()
+        },
+        ..Scenario::index_config()?
+    };
+    let searcher = InProcessSearcher::new(rt.clone()).await?;
+    let mut scenario = Scenario::new_with_config(rt, searcher, index_config).await?;
+    let (runs, _) = scenario._patch("a", "she runs daily", "test").await?;
+    let (ran, _) = scenario._patch("b", "the race was ran", "test").await?;
+
+    for backfill in [false, true] {
+        if backfill {
+            scenario.backfill().await?;
+        }
+        let results = scenario.query_with_typos("running", 0).await?;
+        assert_eq!(
+            results.into_iter().map(|(id, _)| id).collect::<Vec<_>>(),
+            vec![runs]
+        );
+        // Stopwords are dropped from both documents and search text.
+        assert!(scenario.query_with_typos("the", 0).await?.is_empty());
+        let results = scenario.query_with_typos("\"the race\"", 0).await?;
+        assert_eq!(
+            results.into_iter().map(|(id, _)| id).collect::<Vec<_>>(),
+            vec![ran]
+        );
+    }
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_search_reads_only_overlap_matching_writes(rt: TestRuntime) -> anyhow::Result<()> {
+    let mut scenario = Scenario::new(rt).await?;
+    let (id, _) = scenario._patch("a", "unrelated", "test").await?;
+
+    let mut tx = scenario.database.begin(Identity::system()).await?;
+    let filter = SearchFilterExpression::Eq("filterField".parse()?, Some("test".try_into()?));
+    let results = scenario
+        .query_in_tx(
+            &mut tx,
+            "comfortable",
+            TextSearchOptions::new(1)?,
+            Some(filter),
+            SearchVersion::V2,
+        )
+        .await?;
+    assert!(results.is_empty());
+    let token = tx.into_token()?;
+
+    let overlaps = |search_field: &str, filter_field: &str| -> anyhow::Result<bool> {
+        let document = ResolvedDocument::new(
+            id,
+            CreationTime::ONE,
+            assert_obj!("searchField" => search_field, "filterField" => filter_field),
+        )?;
+        Ok(token
+            .reads()
+            .overlaps_document_for_test(
+                &PackedDocument::pack(&document),
+                PersistenceVersion::default(),
+            )
+            .is_some())
+    };
+    assert!(overlaps("a comfortable couch", "test")?);
+    // Writes within the search's typo tolerance could be results too.
+    assert!(overlaps("a comfortible couch", "test")?);
+    // Writes that don't match both the terms and the filters can't be results.
+    assert!(!overlaps("a comfy couch", "test")?);
+    assert!(!overlaps("a comfortable couch", "other")?);
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_prefix_search(rt: TestRuntime) -> anyhow::Result<()> {
+    let index_config = DeveloperTextIndexConfig {
+        analyzer: TextAnalyzerConfig {
+            autocomplete: Some(AutocompleteConfig::default()),
+            ..Default::default()
+        },
+        ..Scenario::index_config()?
+    };
+    let searcher = InProcessSearcher::new(rt.clone()).await?;
+    let mut scenario = Scenario::new_with_config(rt.clone(), searcher, index_config).await?;
+    let (couch, _) = scenario._patch("a", "comfy couch", "test").await?;
+    let (count, _) = scenario._patch("b", "count the ways", "test").await?;
+
+    for backfill in [false, true] {
+        if backfill {
+            scenario.backfill().await?;
+        }
+        let mut tx = scenario.database.begin(Identity::system()).await?;
+        let options = TextSearchOptions::default().with_prefix_search();
+        let results = scenario
+            .query_in_tx(&mut tx, "cou", options.clone(), None, SearchVersion::V2)
+            .await?;
+        let ids: BTreeSet<_> = results.into_iter().map(|(id, _)| id).collect();
+        assert_eq!(ids, btreeset! {couch, count});
+        let results = scenario
+            .query_in_tx(&mut tx, "comfy cou", options, None, SearchVersion::V2)
+            .await?;
+        assert_eq!(results[0].0, couch);
+        // Ordinary searches still only match whole words.
+        assert!(scenario.query_with_typos("cou", 0).await?.is_empty());
+    }
+
+    // Indexes without autocomplete don't store prefixes to search.
+    let scenario = Scenario::new(rt).await?;
+    let mut tx = scenario.database.begin(Identity::system()).await?;
+    let err = scenario
+        .query_in_tx(
+            &mut tx,
+            "cou",
+            TextSearchOptions::default().with_prefix_search(),
+            None,
+            SearchVersion::V2,
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "PrefixSearchRequiresAutocompleteIndex");
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_multiple_search_fields(rt: TestRuntime) -> anyhow::Result<()> {
+    let index_config = DeveloperTextIndexConfig {
+        extra_search_fields: btreeset! {"title".parse()?},
+        search_field_weights: btreemap! {"title".parse()? => 3},
+        ..Scenario::index_config()?
+    };
+    let searcher = InProcessSearcher::new(rt.clone()).await?;
+    let mut scenario = Scenario::new_with_config(rt, searcher, index_config).await?;
+    let mut tx = scenario.database.begin(Identity::system()).await?;
+    let in_title = TestFacingModel::new(&mut tx)
+        .insert(
+            &scenario.table_name,
+            assert_obj!("title" => "rust search", "searchField" => "notes on indexing"),
+        )
+        .await?;
+    let in_body = TestFacingModel::new(&mut tx)
+        .insert(
+            &scenario.table_name,
+            assert_obj!("title" => "guide", "searchField" => "an overview of rust engines"),
+        )
+        .await?;
+    scenario.database.commit(tx).await?;
+
+    for backfill in [false, true] {
+        if backfill {
+            scenario.backfill().await?;
+        }
+        // Matches in either search field count, but the title's weight makes
+        // its match score higher.
+        let results = scenario.query_with_typos("rust", 0).await?;
+        assert_eq!(
+            results.into_iter().map(|(id, _)| id).collect::<Vec<_>>(),
+            vec![in_title, in_body]
+        );
+        let results = scenario.query_with_typos("guide indexing", 0).await?;
+        assert_eq!(results.len(), 2);
+        // Phrases don't span search fields.
+        assert!(scenario
+            .query_with_typos("\"indexing rust\"", 0)
+            .await?
+            .is_empty());
+    }
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_range_filters(rt: TestRuntime) -> anyhow::Result<()> {
+    let mut scenario = Scenario::new(rt).await?;
+    let mut ids = vec![];
+    for filter_value in [
+        val!(5.),
+        val!(10.),
+        val!(25.),
+        val!(50.),
+        val!(10),
+        val!("10"),
+    ] {
+        let mut tx = scenario.database.begin(Identity::system()).await?;
+        let id = TestFacingModel::new(&mut tx)
+            .insert(
+                &scenario.table_name,
+                assert_obj!("searchField" => "price", "filterField" => filter_value),
+            )
+            .await?;
+        scenario.database.commit(tx).await?;
+        ids.push(id);
+    }
+    let filter_field: FieldPath = "filterField".parse()?;
+    let matching = |results: Vec<(ResolvedDocumentId, f64)>| {
+        results
+            .into_iter()
+            .map(|(id, _)| ids.iter().position(|i| *i == id).unwrap())
+            .collect::<BTreeSet<_>>()
+    };
+
+    for backfill in [false, true] {
+        if backfill {
+            scenario.backfill().await?;
+        }
+        let results = scenario
+            .query_with_filter(
+                "price",
+                SearchFilterExpression::Gte(filter_field.clone(), val!(10.)),
+            )
+            .await?;
+        assert_eq!(matching(results), btreeset! {1, 2, 3});
+        let results = scenario
+            .query_with_filter(
+                "price",
+                SearchFilterExpression::Lt(filter_field.clone(), val!(25.)),
+            )
+            .await?;
+        assert_eq!(matching(results), btreeset! {0, 1});
+        // Ranges only match values of the same type.
+        let results = scenario
+            .query_with_filter(
+                "price",
+                SearchFilterExpression::Gt(filter_field.clone(), val!(0)),
+            )
+            .await?;
+        assert_eq!(matching(results), btreeset! {4});
+
+        let err = scenario
+            .query_with_filter(
+                "price",
+                SearchFilterExpression::Gt(filter_field.clone(), val!("10")),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.short_msg(), "InvalidSearchRangeFilter");
+    }
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_synonyms(rt: TestRuntime) -> anyhow::Result<()> {
+    let mut scenario = Scenario::new(rt).await?;
+    let (couch, _) = scenario._patch("a", "a comfy couch", "test").await?;
+    let (sofa, _) = scenario._patch("b", "a leather sofa", "test").await?;
+    let matching = |results: Vec<(ResolvedDocumentId, f64)>| {
+        results
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect::<BTreeSet<_>>()
+    };
+    assert_eq!(
+        matching(scenario.query_with_typos("sofa", 0).await?),
+        btreeset! {sofa}
+    );
+
+    // Synonyms take effect without rebuilding the index, and are analyzed
+    // like the search text.
+    scenario
+        .set_synonyms(vec![vec!["Couch", "sofa", "settee"]])
+        .await?;
+    for backfill in [false, true] {
+        if backfill {
+            scenario.backfill().await?;
+        }
+        assert_eq!(
+            matching(scenario.query_with_typos("sofa", 0).await?),
+            btreeset! {couch, sofa}
+        );
+        assert_eq!(
+            matching(scenario.query_with_typos("settee", 0).await?),
+            btreeset! {couch, sofa}
+        );
+        // Words in phrases have to match exactly.
+        assert!(scenario
+            .query_with_typos("\"leather couch\"", 0)
+            .await?
+            .is_empty());
+    }
+
+    scenario.set_synonyms(vec![]).await?;
+    assert_eq!(
+        matching(scenario.query_with_typos("sofa", 0).await?),
+        btreeset! {sofa}
+    );
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_score_expression(rt: TestRuntime) -> anyhow::Result<()> {
+    let mut scenario = Scenario::new(rt).await?;
+    let mut tx = scenario.database.begin(Identity::system()).await?;
+    let mut ids = vec![];
+    for popularity in [1., 100., 10.] {
+        let id = TestFacingModel::new(&mut tx)
+            .insert(
+                &scenario.table_name,
+                assert_obj!(
+                    "searchField" => "a comfy couch",
+                    "filterField" => "test",
+                    "popularity" => popularity,
+                ),
+            )
+            .await?;
+        ids.push(id);
+    }
+    scenario.database.commit(tx).await?;
+
+    for backfill in [false, true] {
+        if backfill {
+            scenario.backfill().await?;
+        }
+        // Every document has the same BM25 score, so without an expression
+        // the newest document comes first.
+        let results = scenario.query_with_typos("couch", 0).await?;
+        let ranked: Vec<_> = results.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ranked, vec![ids[2], ids[1], ids[0]]);
+        let bm25 = results[0].1;
+
+        let mut tx = scenario.database.begin(Identity::system()).await?;
+        let options =
+            TextSearchOptions::default().with_score("bm25 * log(1 + popularity)".parse()?);
+        let results = scenario
+            .query_in_tx(&mut tx, "couch", options, None, SearchVersion::V2)
+            .await?;
+        let ranked: Vec<_> = results.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ranked, vec![ids[1], ids[2], ids[0]]);
+        assert!((results[0].1 - bm25 * 101f64.ln()).abs() < 1e-6);
+    }
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_paginate_past_candidate_limit(rt: TestRuntime) -> anyhow::Result<()> {
+    let mut scenario = Scenario::new(rt).await?;
+    let num_documents = MAX_CANDIDATE_REVISIONS + 100;
+    for i in 0..num_documents {
+        scenario
+            ._patch(format!("{i:}"), "rakeeb wuz here", "test")
+            .await?;
+    }
+    for backfill in [false, true] {
+        if backfill {
+            scenario.backfill().await?;
+        }
+        let mut ids = BTreeSet::new();
+        let mut cursor = None;
+        loop {
+            let mut tx = scenario.database.begin(Identity::system()).await?;
+            let query = Query {
+                source: QuerySource::Search(Search {
+                    index_name: "test.by_text".parse()?,
+                    table: scenario.table_name.clone(),
+                    filters: vec![SearchFilterExpression::Search(
+                        "searchField".parse()?,
+                        "rakeeb".to_string(),
+                        TextSearchOptions::default(),
+                    )],
+                }),
+                operators: vec![QueryOperator::Limit(500)],
+            };
+            let mut query_stream = ResolvedQuery::new_bounded(
+                &mut tx,
+                scenario.namespace,
+                query,
+                PaginationOptions::ManualPagination {
+                    start_cursor: cursor,
+                    maximum_rows_read: None,
+                    maximum_bytes_read: None,
+                },
+                Some(MIN_NPM_VERSION_FOR_FUZZY_SEARCH.clone()),
+                TableFilter::ExcludePrivateSystemTables,
+            )?;
+            let mut page_size = 0;
+            while let Some(document) = query_stream.next(&mut tx, None).await? {
+                // Each page picks up where the last one stopped.
+                assert!(ids.insert(document.id()));
+                page_size += 1;
+            }
+            cursor = query_stream.cursor();
+            if page_size == 0 {
+                break;
+            }
+        }
+        assert_eq!(ids.len(), num_documents);
+    }
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_changing_synonyms_invalidates_subscriptions(rt: TestRuntime) -> anyhow::Result<()> {
+    let scenario = Scenario::new(rt).await?;
+    scenario.insert("a comfy couch", "a").await?;
+    scenario.set_synonyms(vec![vec!["couch", "sofa"]]).await?;
+
+    let mut tx = scenario.database.begin_system().await?;
+    let results = scenario
+        .query_in_tx(
+            &mut tx,
+            "sofa",
+            TextSearchOptions::default(),
+            None,
+            SearchVersion::V2,
+        )
+        .await?;
+    assert_eq!(results.len(), 1);
+    let token = tx.into_token()?;
+
+    scenario.set_synonyms(vec![vec!["couch", "settee"]]).await?;
+    let ts = *scenario.database.now_ts_for_reads();
+    assert!(scenario.database.refresh_token(token, ts).await?.is_none());
+    Ok(())
+}
+
 // Previous regression
 #[convex_macro::test_runtime]
 async fn test_fuzzy_disk_snapshot_shortlist_ids_valid_with_empty_memory_index(
@@ -1428,7 +2039,13 @@ async fn test_flushing_does_not_invalidate_subscriptions(rt: TestRuntime) -> any
 
     let mut tx = scenario.database.begin_system().await?;
     let results = scenario
-        .query_in_tx(&mut tx, "existing", None, SearchVersion::V2)
+        .query_in_tx(
+            &mut tx,
+            "existing",
+            TextSearchOptions::default(),
+            None,
+            SearchVersion::V2,
+        )
         .await?;
     assert_eq!(results.len(), 1);
     let token = tx.into_token()?;
@@ -4,6 +4,7 @@ use anyhow::Context;
 use common::{
     bootstrap_model::index::{
         text_index::{
+            DeveloperTextIndexConfig,
             FragmentedTextSegment,
             TextIndexSnapshot,
             TextIndexSnapshotData,
@@ -25,6 +26,7 @@ use common::{
         QuerySource,
         Search,
         SearchFilterExpression,
+        TextSearchOptions,
     },
     runtime::testing::TestRuntime,
     types::{
@@ -325,6 +327,7 @@ impl TextFixtures {
         let filters = vec![SearchFilterExpression::Search(
             SEARCH_FIELD.parse()?,
             query_string.into(),
+            TextSearchOptions::default(),
         )];
         let search = Search {
             table: index_name.table().clone(),
@@ -390,8 +393,7 @@ pub fn backfilling_text_index() -> anyhow::Result<IndexMetadata<TableName>> {
     let filter_field: FieldPath = "channel".parse()?;
     let metadata = IndexMetadata::new_backfilling_text_index(
         index_name,
-        search_field,
-        btreeset![filter_field],
+        DeveloperTextIndexConfig::new(search_field, btreeset![filter_field]),
     );
     Ok(metadata)
 }
@@ -241,8 +241,11 @@ async fn vector_query_counts_bandwidth(rt: TestRuntime) -> anyhow::Result<()> {
                 index_name: index_name.clone(),
                 component_id: ComponentId::Root,
                 limit: Some(10),
+                ef_search: None,
                 vector: vec![0.; 2],
                 expressions: btreeset![],
+                include_vectors: false,
+                mmr: None,
             },
         )
         .await?;
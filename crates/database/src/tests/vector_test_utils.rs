@@ -6,11 +6,14 @@ use common::{
     bootstrap_model::index::{
         text_index::FragmentedTextSegment,
         vector_index::{
+            DeveloperVectorIndexConfig,
             FragmentedVectorSegment,
             VectorIndexBackfillState,
+            VectorIndexKind,
             VectorIndexSnapshot,
             VectorIndexSnapshotData,
             VectorIndexState,
+            VectorQuantization,
         },
         IndexConfig,
         IndexMetadata,
@@ -211,6 +214,16 @@ impl VectorFixtures {
         backfilling_vector_index(&self.db).await
     }
 
+    pub async fn backfilling_vector_index_with_options(
+        &self,
+        index_kind: VectorIndexKind,
+        quantization: VectorQuantization,
+        partition_field: Option<FieldPath>,
+    ) -> anyhow::Result<IndexData> {
+        backfilling_vector_index_with_options(&self.db, index_kind, quantization, partition_field)
+            .await
+    }
+
     pub async fn add_document_vec_array(
         &self,
         table_name: &TableName,
@@ -434,16 +447,26 @@ pub struct IndexData {
     pub namespace: TableNamespace,
 }
 
-fn new_backfilling_vector_index() -> anyhow::Result<IndexMetadata<TableName>> {
+fn new_backfilling_vector_index(
+    index_kind: VectorIndexKind,
+    quantization: VectorQuantization,
+    partition_field: Option<FieldPath>,
+) -> anyhow::Result<IndexMetadata<TableName>> {
     let table_name: TableName = "table".parse()?;
     let index_name = IndexName::new(table_name, IndexDescriptor::new("vector_index")?)?;
     let vector_field: FieldPath = "vector".parse()?;
     let filter_field: FieldPath = "channel".parse()?;
     let metadata = IndexMetadata::new_backfilling_vector_index(
         index_name,
-        vector_field,
-        (2u32).try_into()?,
-        btreeset![filter_field],
+        DeveloperVectorIndexConfig {
+            dimensions: (2u32).try_into()?,
+            vector_field,
+            filter_fields: btreeset![filter_field],
+            partition_field,
+            distance_metric: Default::default(),
+            index_kind,
+            quantization,
+        },
     );
     Ok(metadata)
 }
@@ -461,7 +484,16 @@ pub async fn backfilled_vector_index(
 }
 
 pub async fn backfilling_vector_index(db: &Database<TestRuntime>) -> anyhow::Result<IndexData> {
-    let index_metadata = new_backfilling_vector_index()?;
+    backfilling_vector_index_with_options(db, Default::default(), Default::default(), None).await
+}
+
+pub async fn backfilling_vector_index_with_options(
+    db: &Database<TestRuntime>,
+    index_kind: VectorIndexKind,
+    quantization: VectorQuantization,
+    partition_field: Option<FieldPath>,
+) -> anyhow::Result<IndexData> {
+    let index_metadata = new_backfilling_vector_index(index_kind, quantization, partition_field)?;
     let index_name = &index_metadata.name;
     let mut tx = db.begin_system().await?;
     let namespace = TableNamespace::test_user();
@@ -3,14 +3,17 @@ use std::{
         BTreeMap,
         BTreeSet,
     },
+    net::SocketAddr,
     sync::Arc,
 };
 
+use async_trait::async_trait;
 use cmd_util::env::env_config;
 use common::{
     bootstrap_model::index::{
         vector_index::{
             DeveloperVectorIndexConfig,
+            VectorDistanceMetric,
             VectorIndexBackfillState,
             VectorIndexSnapshot,
             VectorIndexSnapshotData,
@@ -20,12 +23,20 @@ use common::{
         IndexMetadata,
     },
     components::ComponentId,
+    grpc::ConvexGrpcService,
     knobs::{
         MULTI_SEGMENT_FULL_SCAN_THRESHOLD_KB,
         VECTOR_INDEX_SIZE_SOFT_LIMIT,
     },
     persistence::PersistenceReader,
     runtime::Runtime,
+    schemas::{
+        DatabaseSchema,
+        DocumentSchema,
+        TableDefinition,
+        VectorEmbedder,
+        VectorIndexSchema,
+    },
     types::{
         unchecked_repeatable_ts,
         IndexDescriptor,
@@ -39,6 +50,10 @@ use maplit::{
     btreeset,
 };
 use must_let::must_let;
+use pb::searchlight::{
+    searchlight_server::SearchlightServer,
+    StorageType,
+};
 use proptest::prelude::*;
 use proptest_derive::Arbitrary;
 use qdrant_segment::types::VECTOR_ELEMENT_SIZE;
@@ -51,9 +66,20 @@ use runtime::{
 };
 use search::searcher::{
     InProcessSearcher,
+    SearchStorageResolver,
     Searcher,
+    SearcherImpl,
+    SearchlightClient,
+    SearchlightService,
+};
+use storage::{
+    LocalDirStorage,
+    Storage,
+};
+use tokio::{
+    net::TcpStream,
+    sync::oneshot,
 };
-use storage::Storage;
 use value::{
     assert_obj,
     ConvexObject,
@@ -64,12 +90,19 @@ use value::{
 };
 use vector::{
     cosine_similarity,
+    vector_distance,
+    vector_similarity,
     PublicVectorSearchQueryResult,
+    VectorFilterBound,
+    VectorFilterRange,
+    VectorMmr,
     VectorSearch,
     VectorSearchExpression,
+    VectorSearcher,
 };
 
 use crate::{
+    system_tables::ErasedSystemTable,
     test_helpers::{
         vector_utils::{
             random_vector,
@@ -93,8 +126,13 @@ use crate::{
     },
     Database,
     IndexModel,
+    SchemaModel,
     TableModel,
     UserFacingModel,
+    VectorEmbeddingJobModel,
+    VectorEmbeddingJobsTable,
+    VectorEmbeddingStatus,
+    VECTOR_EMBEDDING_JOBS_TABLE,
 };
 
 const TABLE_NAME: &str = "test";
@@ -119,6 +157,16 @@ struct Scenario<RT: Runtime> {
 
 impl<RT: Runtime> Scenario<RT> {
     async fn new(rt: RT, vector_index_state: ScenarioIndexState) -> anyhow::Result<Self> {
+        let searcher = Arc::new(InProcessSearcher::new(rt.clone()).await?);
+        Self::new_with_searcher(rt, vector_index_state, searcher, None).await
+    }
+
+    async fn new_with_searcher(
+        rt: RT,
+        vector_index_state: ScenarioIndexState,
+        searcher: Arc<dyn Searcher>,
+        search_storage: Option<Arc<dyn Storage>>,
+    ) -> anyhow::Result<Self> {
         let DbFixtures {
             tp,
             db,
@@ -128,7 +176,8 @@ impl<RT: Runtime> Scenario<RT> {
         } = DbFixtures::new_with_args(
             &rt,
             DbFixturesArgs {
-                searcher: Some(Arc::new(InProcessSearcher::new(rt.clone()).await?)),
+                searcher: Some(searcher),
+                search_storage,
                 ..Default::default()
             },
         )
@@ -151,6 +200,15 @@ impl<RT: Runtime> Scenario<RT> {
     }
 
     async fn add_vector_index(&self, should_backfill: bool) -> anyhow::Result<()> {
+        self.add_vector_index_with_metric(should_backfill, VectorDistanceMetric::default())
+            .await
+    }
+
+    async fn add_vector_index_with_metric(
+        &self,
+        should_backfill: bool,
+        distance_metric: VectorDistanceMetric,
+    ) -> anyhow::Result<()> {
         let table_name: TableName = TABLE_NAME.parse()?;
         let mut tx = self.database.begin(Identity::system()).await?;
         let namespace = TableNamespace::test_user();
@@ -159,9 +217,15 @@ impl<RT: Runtime> Scenario<RT> {
             .await?;
         let index = IndexMetadata::new_backfilling_vector_index(
             INDEX_NAME.parse()?,
-            INDEXED_FIELD.parse()?,
-            DIMENSIONS.try_into()?,
-            FILTER_FIELDS.iter().map(|f| f.parse()).try_collect()?,
+            DeveloperVectorIndexConfig {
+                dimensions: DIMENSIONS.try_into()?,
+                vector_field: INDEXED_FIELD.parse()?,
+                filter_fields: FILTER_FIELDS.iter().map(|f| f.parse()).try_collect()?,
+                partition_field: None,
+                distance_metric,
+                index_kind: Default::default(),
+                quantization: Default::default(),
+            },
         );
         IndexModel::new(&mut tx)
             .add_application_index(namespace, index)
@@ -251,7 +315,10 @@ impl<RT: Runtime> Scenario<RT> {
                     component_id: ComponentId::Root,
                     vector,
                     limit,
+                    ef_search: None,
                     expressions: filter_expressions,
+                    include_vectors: false,
+                    mmr: None,
                 },
             )
             .await?;
@@ -429,7 +496,10 @@ impl<RT: Runtime> RandomizedTest<RT> {
                     component_id: ComponentId::Root,
                     vector: test_query.vector.clone(),
                     limit: Some(test_query.limit),
+                    ef_search: None,
                     expressions,
+                    include_vectors: false,
+                    mmr: None,
                 };
                 let (returned_results, _usage_stats) = self
                     .scenario
@@ -453,7 +523,12 @@ impl<RT: Runtime> RandomizedTest<RT> {
                         continue;
                     }
                     let score = cosine_similarity(&test_query.vector, &update.vector);
-                    expected_results.push(PublicVectorSearchQueryResult { id: *id, score });
+                    expected_results.push(PublicVectorSearchQueryResult {
+                        id: *id,
+                        score,
+                        vector: None,
+                        distance: None,
+                    });
                 }
                 expected_results.sort_by(|a, b| a.cmp(b).reverse());
                 expected_results.truncate(test_query.limit as usize);
@@ -512,6 +587,33 @@ async fn test_vector_search(rt: TestRuntime) -> anyhow::Result<()> {
             .await?;
         assert_eq!(results.len(), 0);
 
+        // Check that excluding 1017 only gets the second vector.
+        let exclude_first =
+            VectorSearchExpression::NotIn("A".parse()?, btreeset![Some(ConvexValue::Int64(1017))]);
+        let results = scenario
+            .search(vec![0.; 4], btreeset![exclude_first])
+            .await?;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id.internal_id(), id2.internal_id());
+
+        // Check that a range over int64s only gets the first vector.
+        let in_range = VectorSearchExpression::Range(
+            "A".parse()?,
+            VectorFilterRange {
+                lower: Some(VectorFilterBound {
+                    value: ConvexValue::Int64(1000),
+                    inclusive: true,
+                }),
+                upper: Some(VectorFilterBound {
+                    value: ConvexValue::Int64(1017),
+                    inclusive: true,
+                }),
+            },
+        );
+        let results = scenario.search(vec![0.; 4], btreeset![in_range]).await?;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id.internal_id(), id1.internal_id());
+
         // Backfill and repeat once to check the disk index.
         scenario.backfill().await?;
     }
@@ -677,8 +779,11 @@ async fn test_concurrent_index_version_searches(rt: ProdRuntime) -> anyhow::Resu
                         index_name: INDEX_NAME.parse().unwrap(),
                         component_id: ComponentId::Root,
                         limit: Some(10),
+                        ef_search: None,
                         vector: vec![0.; 4],
                         expressions: btreeset![],
+                        include_vectors: false,
+                        mmr: None,
                     },
                     unchecked_repeatable_ts(timestamp),
                 )
@@ -705,6 +810,87 @@ async fn test_concurrent_index_version_searches(rt: ProdRuntime) -> anyhow::Resu
     Ok(())
 }
 
+struct LocalSearchStorageResolver(Arc<dyn Storage>);
+
+#[async_trait]
+impl SearchStorageResolver for LocalSearchStorageResolver {
+    async fn resolve(&self, _storage_type: StorageType) -> anyhow::Result<Arc<dyn Storage>> {
+        Ok(self.0.clone())
+    }
+}
+
+#[convex_macro::prod_rt_test]
+async fn test_vector_search_through_searchlight(rt: ProdRuntime) -> anyhow::Result<()> {
+    let storage_dir = tempfile::tempdir()?;
+    let cache_dir = tempfile::tempdir()?;
+    let search_storage: Arc<dyn Storage> = Arc::new(LocalDirStorage::new_at_path(
+        rt.clone(),
+        storage_dir.path().to_owned(),
+    )?);
+    let searcher = SearcherImpl::new(cache_dir.path(), 1 << 30, 100, false, rt.clone()).await?;
+    let service = SearchlightService::new(
+        Arc::new(searcher),
+        Arc::new(LocalSearchStorageResolver(search_storage.clone())),
+        "secret",
+    )?;
+    let port = portpicker::pick_unused_port().expect("No ports free");
+    let addr: SocketAddr = format!("127.0.0.1:{port}").parse()?;
+    let (shutdown_tx, shutdown_rx) = oneshot::channel();
+    let server = tokio::spawn(
+        ConvexGrpcService::new()
+            .add_service(SearchlightServer::new(service))
+            .serve(addr, async move {
+                let _ = shutdown_rx.await;
+            }),
+    );
+    // Can take a moment after the server spawn to accept connections.
+    while TcpStream::connect(addr).await.is_err() {
+        tokio::task::yield_now().await;
+    }
+
+    let client = Arc::new(SearchlightClient::new(
+        vec![format!("http://{addr}")],
+        "secret",
+    )?);
+    let mut scenario = Scenario::new_with_searcher(
+        rt.clone(),
+        ScenarioIndexState::Some,
+        client,
+        Some(search_storage.clone()),
+    )
+    .await?;
+    // Build two segments and compact them through the searchlight.
+    let mut ids = scenario.seed_table_with_vector_data(3).await?;
+    scenario.backfill().await?;
+    ids.extend(scenario.seed_table_with_vector_data(3).await?);
+    scenario.backfill().await?;
+    scenario.compact().await?;
+
+    let results = scenario.search(vec![0.; 4], btreeset![]).await?;
+    assert_eq!(
+        results
+            .into_iter()
+            .map(|result| result.id.internal_id())
+            .sorted()
+            .collect_vec(),
+        ids.iter().map(|id| id.internal_id()).sorted().collect_vec()
+    );
+
+    let unauthenticated = SearchlightClient::new(vec![format!("http://{addr}")], "wrong")?;
+    let err = unauthenticated
+        .execute_vector_compaction(search_storage, vec![], DIMENSIONS as usize)
+        .await
+        .unwrap_err();
+    assert!(
+        format!("{err:?}").contains("Missing or invalid searchlight secret"),
+        "{err:?}"
+    );
+
+    shutdown_tx.send(()).unwrap();
+    server.await??;
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn test_vector_search_compaction_with_deletes(rt: TestRuntime) -> anyhow::Result<()> {
     let mut scenario = Scenario::new(rt.clone(), ScenarioIndexState::Some).await?;
@@ -911,6 +1097,68 @@ async fn test_empty_multi_segment(rt: TestRuntime) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn test_include_vectors_and_mmr(rt: TestRuntime) -> anyhow::Result<()> {
+    let scenario = Scenario::new(rt.clone(), ScenarioIndexState::Some).await?;
+    let near = vec![1., 0.1, 0., 0.];
+    let near_duplicate = vec![1., 0.11, 0., 0.];
+    let different = vec![0.7, -0.7, 0., 0.];
+    let mut tx = scenario.database.begin(Identity::system()).await?;
+    let mut ids = vec![];
+    for vector in [&near, &near_duplicate, &different] {
+        let obj = assert_obj!(INDEXED_FIELD => vector_to_value(vector.clone()));
+        let id = UserFacingModel::new_root_for_test(&mut tx)
+            .insert(TABLE_NAME.parse()?, obj)
+            .await?;
+        ids.push(id);
+    }
+    scenario.database.commit(tx).await?;
+
+    let query = vec![1., 0., 0., 0.];
+    let search = |include_vectors, mmr| VectorSearch {
+        index_name: INDEX_NAME.parse().unwrap(),
+        component_id: ComponentId::Root,
+        vector: query.clone(),
+        limit: Some(2),
+        ef_search: None,
+        expressions: btreeset![],
+        include_vectors,
+        mmr,
+    };
+    for _ in 0..2 {
+        // The nearest neighbors come back with their stored vectors.
+        let (results, _) = scenario
+            .database
+            .vector_search(Identity::system(), search(true, None))
+            .await?;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].id, ids[0].developer_id);
+        assert_eq!(results[0].vector.as_ref(), Some(&near));
+        assert_eq!(
+            results[0].distance,
+            Some(vector_distance(VectorDistanceMetric::Cosine, &query, &near))
+        );
+        assert_eq!(results[1].id, ids[1].developer_id);
+
+        // Re-ranking skips the near duplicate for the more diverse vector.
+        let mmr = VectorMmr {
+            lambda: 0.5,
+            fetch_limit: Some(3),
+        };
+        let (results, _) = scenario
+            .database
+            .vector_search(Identity::system(), search(false, Some(mmr)))
+            .await?;
+        let result_ids: Vec<_> = results.iter().map(|r| r.id).collect();
+        assert_eq!(result_ids, vec![ids[0].developer_id, ids[2].developer_id]);
+        assert!(results.iter().all(|r| r.vector.is_none()));
+
+        // Backfill and repeat once to check the disk index.
+        scenario.backfill().await?;
+    }
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn test_recall_multi_segment(rt: TestRuntime) -> anyhow::Result<()> {
     let scenario = Scenario::new(rt.clone(), ScenarioIndexState::Some).await?;
@@ -940,6 +1188,8 @@ async fn test_recall_multi_segment(rt: TestRuntime) -> anyhow::Result<()> {
         .map(|(id, vector)| PublicVectorSearchQueryResult {
             id: DeveloperDocumentId::new(table_number, *id),
             score: cosine_similarity(&query, vector),
+            vector: None,
+            distance: None,
         })
         .collect();
     expected.sort_by(|a, b| a.cmp(b).reverse());
@@ -958,6 +1208,92 @@ async fn test_recall_multi_segment(rt: TestRuntime) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn test_vector_index_stats(rt: TestRuntime) -> anyhow::Result<()> {
+    let scenario = Scenario::new(rt.clone(), ScenarioIndexState::Some).await?;
+    scenario.seed_table_with_vector_data(20).await?;
+    scenario.backfill().await?;
+
+    let segment_stats = scenario.database.vector_index_segment_stats()?;
+    assert_eq!(segment_stats.len(), 1);
+    let (&index_id, stats) = segment_stats.iter().next().unwrap();
+    assert_eq!(stats.segment_sizes.iter().sum::<u64>(), 20);
+    assert_eq!(stats.num_deleted, 0);
+
+    let query = random_vector(&mut rt.rng());
+    scenario.search(query.clone(), btreeset![]).await?;
+    let query_stats = scenario.database.take_vector_query_stats();
+    assert_eq!(query_stats[&index_id].count, 1);
+    assert!(scenario.database.take_vector_query_stats().is_empty());
+
+    let recall = scenario
+        .database
+        .vector_index_recall(index_id, vec![query], 10)
+        .await?
+        .unwrap();
+    assert!((0.9..=1.0).contains(&recall));
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_vector_search_distance_metrics(rt: TestRuntime) -> anyhow::Result<()> {
+    for distance_metric in [
+        VectorDistanceMetric::DotProduct,
+        VectorDistanceMetric::Euclidean,
+    ] {
+        let scenario = Scenario::new(rt.clone(), ScenarioIndexState::None).await?;
+        scenario
+            .add_vector_index_with_metric(true, distance_metric)
+            .await?;
+        let mut tx = scenario.database.begin(Identity::system()).await?;
+        let table_number = tx
+            .table_mapping()
+            .namespace(TABLE_NAMESPACE)
+            .name_to_number_user_input()(TABLE_NAME.parse()?)?;
+
+        // Scale the vectors so their magnitudes differ, which only matters
+        // for the non-cosine metrics.
+        let mut rng = rt.rng();
+        let mut by_id = BTreeMap::new();
+        for i in 0..20 {
+            let vector: Vec<f32> = random_vector(&mut rng)
+                .into_iter()
+                .map(|x| x * (i + 1) as f32)
+                .collect();
+            let obj = assert_obj!(INDEXED_FIELD => vector_to_value(vector.clone()));
+            let id = UserFacingModel::new_root_for_test(&mut tx)
+                .insert(TABLE_NAME.parse()?, obj)
+                .await?;
+            by_id.insert(id.internal_id(), vector);
+        }
+        scenario.database.commit(tx).await?;
+
+        let limit = 5u32;
+        let query = random_vector(&mut rng);
+        let mut expected: Vec<_> = by_id
+            .iter()
+            .map(|(id, vector)| PublicVectorSearchQueryResult {
+                id: DeveloperDocumentId::new(table_number, *id),
+                score: vector_similarity(distance_metric, &query, vector),
+                vector: None,
+                distance: None,
+            })
+            .collect();
+        expected.sort_by(|a, b| a.cmp(b).reverse());
+        expected.truncate(limit as usize);
+
+        // Search the memory index, then the disk index built by the backfill.
+        for _ in 0..2 {
+            let results = scenario
+                .search_with_limit(query.clone(), btreeset![], Some(limit))
+                .await?;
+            assert_eq!(results, expected, "{distance_metric}");
+            scenario.backfill().await?;
+        }
+    }
+    Ok(())
+}
+
 proptest! {
     #![proptest_config(ProptestConfig {
         cases: 32 * env_config("CONVEX_PROPTEST_MULTIPLIER", 1),
@@ -1014,7 +1350,10 @@ async fn test_multi_segment_search_obeys_sorted_order(rt: TestRuntime) -> anyhow
                 component_id: ComponentId::Root,
                 vector: [6f64, 7f64].into_iter().map(|value| value as f32).collect(),
                 limit: Some(3),
+                ef_search: None,
                 expressions: btreeset![],
+                include_vectors: false,
+                mmr: None,
             },
         )
         .await?;
@@ -1034,3 +1373,90 @@ async fn test_multi_segment_search_obeys_sorted_order(rt: TestRuntime) -> anyhow
 
     Ok(())
 }
+
+#[convex_macro::test_runtime]
+async fn test_vector_embedder_queues_changed_text(rt: TestRuntime) -> anyhow::Result<()> {
+    let DbFixtures { db, .. } = DbFixtures::new(&rt).await?;
+    let mut tx = db.begin(Identity::system()).await?;
+    tx.create_system_table_testing(TableNamespace::Global, &VECTOR_EMBEDDING_JOBS_TABLE, None)
+        .await?;
+    for index in ErasedSystemTable::indexes(&VectorEmbeddingJobsTable) {
+        IndexModel::new(&mut tx)
+            .add_system_index(
+                TableNamespace::Global,
+                IndexMetadata::new_enabled(index.name, index.fields),
+            )
+            .await?;
+    }
+    let table_name: TableName = TABLE_NAME.parse()?;
+    let index_descriptor = IndexDescriptor::new(INDEX_DESCRIPTOR)?;
+    let index = VectorIndexSchema::new(
+        index_descriptor.clone(),
+        INDEXED_FIELD.parse()?,
+        DIMENSIONS.try_into()?,
+        Default::default(),
+        None,
+        Default::default(),
+        Default::default(),
+        Default::default(),
+        Some(VectorEmbedder {
+            source_field: "text".parse()?,
+            action: "embeddings:embed".to_string(),
+        }),
+    )?;
+    let schema = DatabaseSchema {
+        tables: btreemap! {
+            table_name.clone() => TableDefinition {
+                table_name: table_name.clone(),
+                indexes: Default::default(),
+                search_indexes: Default::default(),
+                vector_indexes: btreemap! { index_descriptor.clone() => index },
+                document_type: Some(DocumentSchema::Any),
+                defaults: Default::default(),
+                mutability: Default::default(),
+                checks: vec![],
+            },
+        },
+        schema_validation: true,
+    };
+    let mut schema_model = SchemaModel::new_root_for_test(&mut tx);
+    let (schema_id, _) = schema_model.submit_pending(schema).await?;
+    schema_model.mark_validated(schema_id).await?;
+    schema_model.mark_active(schema_id).await?;
+    db.commit(tx).await?;
+
+    let mut tx = db.begin(Identity::system()).await?;
+    let mut model = UserFacingModel::new_root_for_test(&mut tx);
+    let id = model
+        .insert(table_name.clone(), assert_obj!("text" => "hello"))
+        .await?;
+    // Documents without a text and writes that keep the text aren't queued.
+    model
+        .insert(table_name.clone(), assert_obj!("other" => "hello"))
+        .await?;
+    model.patch(id, assert_obj!("other" => 1.).into()).await?;
+    model
+        .patch(id, assert_obj!("text" => "goodbye").into())
+        .await?;
+    db.commit(tx).await?;
+
+    let mut tx = db.begin(Identity::system()).await?;
+    let tablet_id = tx
+        .table_mapping()
+        .namespace(TABLE_NAMESPACE)
+        .id(&table_name)?
+        .tablet_id;
+    let status = VectorEmbeddingJobModel::new(&mut tx).status().await?;
+    must_let!(let Some(VectorEmbeddingStatus { pending: 2, failed: 0, .. }) =
+        status.get(&(tablet_id, index_descriptor)));
+    assert_eq!(
+        VectorEmbeddingJobModel::new(&mut tx)
+            .list_due(i64::MAX, 10)
+            .await?
+            .into_iter()
+            .map(|job| job.document_id().developer_id)
+            .collect_vec(),
+        vec![id, id],
+    );
+    Ok(())
+}
@@ -117,6 +117,52 @@ mod tests {
         Ok(())
     }
 
+    #[convex_macro::test_runtime]
+    async fn requested_compaction_merges_segments_below_the_policy_threshold(
+        rt: TestRuntime,
+    ) -> anyhow::Result<()> {
+        let fixtures = TextFixtures::new(rt.clone()).await?;
+        let index_data = fixtures.enabled_text_index().await?;
+        assert!(CompactionConfig::default().min_compaction_segments > 2);
+
+        for _ in 0..2 {
+            fixtures.add_document("horse").await?;
+            fixtures.backfill().await?;
+        }
+
+        let compactor = fixtures.new_compactor();
+        let (metrics, _) = compactor.step().await?;
+        assert_eq!(metrics, btreemap! {});
+
+        compactor
+            .compaction_requests()
+            .request(index_data.resolved_index_name.clone());
+        let (metrics, _) = compactor.step().await?;
+        assert_eq!(
+            metrics,
+            btreemap! { index_data.resolved_index_name.clone() => 2 }
+        );
+        let segments = fixtures
+            .get_segments_metadata(index_data.index_name.clone())
+            .await?;
+        assert_eq!(segments.len(), 1);
+
+        // Once there's nothing left to merge, the request is done and the
+        // compaction policy applies again.
+        let (metrics, _) = compactor.step().await?;
+        assert_eq!(metrics, btreemap! {});
+        fixtures.add_document("horse").await?;
+        fixtures.backfill().await?;
+        let (metrics, _) = compactor.step().await?;
+        assert_eq!(metrics, btreemap! {});
+        let segments = fixtures
+            .get_segments_metadata(index_data.index_name)
+            .await?;
+        assert_eq!(segments.len(), 2);
+
+        Ok(())
+    }
+
     #[convex_macro::test_runtime]
     async fn compact_test(rt: TestRuntime) -> anyhow::Result<()> {
         let fixtures = TextFixtures::new(rt.clone()).await?;
@@ -585,6 +585,76 @@ mod tests {
         Ok(())
     }
 
+    #[convex_macro::test_runtime]
+    async fn backfill_catches_up_with_writes_after_old_snapshot(
+        rt: TestRuntime,
+    ) -> anyhow::Result<()> {
+        let fixtures = TextFixtures::new(rt.clone()).await?;
+        let IndexData { index_name, .. } = fixtures.insert_backfilling_text_index().await?;
+
+        let mut doc_ids = vec![];
+        for text in ["cat", "dog", "fish"] {
+            doc_ids.push(fixtures.add_document(text).await?);
+        }
+
+        let mut flusher = fixtures
+            .new_search_flusher_builder()
+            .set_incremental_multipart_threshold_bytes(0)
+            .build();
+        flusher.step().await?;
+        let metadata = fixtures.get_index_metadata(index_name.clone()).await?;
+        must_let!(let IndexConfig::Text { on_disk_state, .. } = &metadata.config);
+        must_let!(let TextIndexState::Backfilling(backfill_state) = on_disk_state);
+        assert_eq!(backfill_state.num_documents_indexed(), 1);
+        assert_eq!(backfill_state.total_documents, Some(3));
+        let first_snapshot_ts = backfill_state.cursor.as_ref().unwrap().backfill_snapshot_ts;
+
+        // Rewrite every document, including the one already indexed, and let the
+        // backfill's snapshot get old.
+        for (doc_id, text) in doc_ids.iter().zip(["lion", "wolf", "shark"]) {
+            fixtures.replace_document(*doc_id, text).await?;
+        }
+        rt.advance_time(Duration::from_secs(3600)).await;
+        let mut tx = fixtures.db.begin_system().await?;
+        TestFacingModel::new(&mut tx)
+            .insert(&"unrelated".parse()?, assert_obj!("wise" => "ambience"))
+            .await?;
+        fixtures.db.commit(tx).await?;
+
+        // The next step catches the indexed document up and moves the snapshot forward
+        // without advancing the cursor.
+        flusher.step().await?;
+        let metadata = fixtures.get_index_metadata(index_name.clone()).await?;
+        must_let!(let IndexConfig::Text { on_disk_state, .. } = &metadata.config);
+        must_let!(let TextIndexState::Backfilling(caught_up) = on_disk_state);
+        let caught_up_cursor = caught_up.cursor.as_ref().unwrap();
+        assert!(caught_up_cursor.backfill_snapshot_ts > first_snapshot_ts);
+        assert_eq!(
+            caught_up_cursor.cursor,
+            backfill_state.cursor.as_ref().unwrap().cursor
+        );
+        assert_eq!(caught_up.num_documents_indexed(), 1);
+
+        for _ in 0..3 {
+            flusher.step().await?;
+        }
+        fixtures.assert_backfilled(&index_name).await?;
+        let segments = fixtures.get_segments_metadata(index_name.clone()).await?;
+        let num_documents: u64 = segments
+            .iter()
+            .map(|segment| segment.num_indexed_documents - segment.num_deleted_documents)
+            .sum();
+        assert_eq!(num_documents, 3);
+
+        fixtures.enable_index(&index_name).await?;
+        assert!(fixtures.search(index_name.clone(), "cat").await?.is_empty());
+        for text in ["lion", "wolf", "shark"] {
+            assert_eq!(fixtures.search(index_name.clone(), text).await?.len(), 1);
+        }
+
+        Ok(())
+    }
+
     #[convex_macro::test_runtime]
     async fn backfill_with_empty_index_adds_no_segments(rt: TestRuntime) -> anyhow::Result<()> {
         let fixtures = TextFixtures::new(rt).await?;
@@ -221,7 +221,9 @@ impl SearchIndex for TextSearchIndex {
         multipart_build_type: MultipartBuildType,
     ) -> anyhow::Result<Option<Self::NewSegment>> {
         let revision_stream = match multipart_build_type {
-            MultipartBuildType::Partial(_) => Box::pin(stream_revision_pairs(documents, &reader)),
+            MultipartBuildType::Partial(_) | MultipartBuildType::BackfillCatchUp { .. } => {
+                Box::pin(stream_revision_pairs(documents, &reader))
+            },
             // Create a fake revision stream for complete builds because we are building from
             // scratch so we don't need to look up previous revisions. We know there are no deletes.
             MultipartBuildType::IncrementalComplete { .. } => documents
@@ -448,6 +450,7 @@ impl From<TextIndexBackfillState> for BackfillState<TextSearchIndex> {
             segments: value.segments,
             cursor: value.cursor.clone().map(|value| value.cursor),
             backfill_snapshot_ts: value.cursor.map(|value| value.backfill_snapshot_ts),
+            total_documents: value.total_documents,
         }
     }
 }
@@ -467,6 +470,7 @@ impl From<BackfillState<TextSearchIndex>> for TextIndexBackfillState {
         Self {
             segments: value.segments,
             cursor,
+            total_documents: value.total_documents,
         }
     }
 }
@@ -44,11 +44,16 @@ use common::{
         TEXT_INDEX_SIZE_HARD_LIMIT,
         VECTOR_INDEX_SIZE_HARD_LIMIT,
     },
-    persistence::RetentionValidator,
+    persistence::{
+        PersistenceReader,
+        RetentionValidator,
+    },
     query::{
         CursorPosition,
+        InternalSearch,
         Order,
         Search,
+        SearchAfter,
         SearchVersion,
     },
     runtime::Runtime,
@@ -84,7 +89,11 @@ use keybroker::{
     UserIdentityAttributes,
 };
 use maplit::btreemap;
-use search::CandidateRevision;
+use search::{
+    CandidateRevision,
+    FacetCounter,
+    Highlighter,
+};
 use sync_types::{
     AuthenticationToken,
     Timestamp,
@@ -92,9 +101,13 @@ use sync_types::{
 use tokio::task;
 use usage_tracking::FunctionUsageTracker;
 use value::{
+    check_user_document_size,
+    check_user_size,
     TableNamespace,
     TableNumber,
     TabletId,
+    MAX_CHUNKED_DOCUMENT_SIZE,
+    MAX_USER_SIZE,
 };
 
 use crate::{
@@ -106,9 +119,13 @@ use crate::{
         },
     },
     committer::table_dependency_sort_key,
+    database::unauthorized_error,
     execution_size::FunctionExecutionSize,
     metrics,
-    patch::PatchValue,
+    patch::{
+        DeepPatch,
+        PatchValue,
+    },
     preloaded::PreloadedIndexRange,
     query::{
         IndexRangeResponse,
@@ -116,6 +133,7 @@ use crate::{
     },
     reads::TransactionReadSet,
     schema_registry::SchemaRegistry,
+    search_synonyms::SearchSynonymsModel,
     snapshot_manager::{
         Snapshot,
         SnapshotManager,
@@ -167,6 +185,9 @@ pub struct Transaction<RT: Runtime> {
     /// this transaction. If there is no entry for a table, assume deltas
     /// are zero.
     pub(crate) table_count_deltas: BTreeMap<TabletId, i64>,
+    /// The change in the number and total size of user documents in each
+    /// namespace that has had writes in this transaction.
+    pub(crate) namespace_usage_deltas: BTreeMap<TableNamespace, (i64, i64)>,
 
     pub(crate) stats: BTreeMap<TabletId, TableStats>,
 
@@ -193,6 +214,13 @@ pub trait TableCountSnapshot: Send + Sync + 'static {
     /// Returns the number of documents in the table at the timestamp of the
     /// snapshot.
     async fn count(&self, table: TabletId) -> anyhow::Result<Option<u64>>;
+
+    /// Returns the number and total size in bytes of the user documents in
+    /// the namespace at the timestamp of the snapshot.
+    async fn namespace_usage(
+        &self,
+        namespace: TableNamespace,
+    ) -> anyhow::Result<Option<(u64, u64)>>;
 }
 
 pub struct SubtransactionToken {
@@ -231,6 +259,7 @@ impl<RT: Runtime> Transaction<RT> {
             component_registry: NestedWrites::new(component_registry),
             count_snapshot: count,
             table_count_deltas: BTreeMap::new(),
+            namespace_usage_deltas: BTreeMap::new(),
             stats: BTreeMap::new(),
             runtime,
             retention_validator,
@@ -245,6 +274,26 @@ impl<RT: Runtime> Transaction<RT> {
         self.index.index_registry().persistence_version()
     }
 
+    /// Checks a user document against the size limit of this deployment's
+    /// persistence. Documents can only grow past `MAX_USER_SIZE` where large
+    /// fields are chunked out of line.
+    pub fn check_user_document_size(&self, value: &ConvexObject) -> anyhow::Result<()> {
+        if self.index.supports_chunked_documents() {
+            check_user_document_size(value)
+        } else {
+            check_user_size(value.size())
+        }
+    }
+
+    /// The largest a user document can be on this deployment's persistence.
+    pub fn max_user_document_size(&self) -> usize {
+        if self.index.supports_chunked_documents() {
+            MAX_CHUNKED_DOCUMENT_SIZE
+        } else {
+            MAX_USER_SIZE
+        }
+    }
+
     pub fn table_mapping(&mut self) -> &TableMapping {
         self.take_table_mapping_dep();
         self.metadata.table_mapping()
@@ -312,6 +361,15 @@ impl<RT: Runtime> Transaction<RT> {
         &self.identity
     }
 
+    /// Fails unless the transaction runs as the system or a deployment admin,
+    /// for system tables that only they can read or write.
+    pub fn require_admin(&self, operation: &'static str) -> anyhow::Result<()> {
+        if !(self.identity.is_system() || self.identity.is_admin()) {
+            anyhow::bail!(unauthorized_error(operation));
+        }
+        Ok(())
+    }
+
     pub fn inert_identity(&self) -> InertIdentity {
         self.identity.clone().into()
     }
@@ -332,6 +390,12 @@ impl<RT: Runtime> Transaction<RT> {
         self.index.base_snapshot().timestamp()
     }
 
+    /// Reader for persistence at any timestamp, for reads that can't be served
+    /// from this transaction's snapshot.
+    pub(crate) fn persistence_reader(&self) -> Arc<dyn PersistenceReader> {
+        self.index.base_snapshot().persistence().reader()
+    }
+
     pub fn is_readonly(&self) -> bool {
         self.writes.is_empty()
     }
@@ -411,6 +475,7 @@ impl<RT: Runtime> Transaction<RT> {
             Some(BiggestDocumentWrites {
                 max_size: ((*biggest_document_id).into(), max_size),
                 max_nesting: ((*most_nested_document_id).into(), max_nesting),
+                max_size_limit: self.max_user_document_size(),
             })
         } else {
             None
@@ -564,8 +629,44 @@ impl<RT: Runtime> Transaction<RT> {
                 .apply(old_document.value().clone().into_value())?;
             old_document.replace_value(patched_value)?
         };
-        SchemaModel::new(self, namespace)
-            .enforce(&new_document)
+        let mut schema_model = SchemaModel::new(self, namespace);
+        schema_model
+            .enforce_mutability(&old_document, &new_document)
+            .await?;
+        schema_model.enforce(&new_document).await?;
+
+        self.apply_validated_write(id, Some((old_document, old_ts)), Some(new_document.clone()))?;
+        Ok(new_document)
+    }
+
+    #[convex_macro::instrument_future]
+    pub(crate) async fn deep_patch_inner(
+        &mut self,
+        id: ResolvedDocumentId,
+        patch: DeepPatch,
+    ) -> anyhow::Result<ResolvedDocument> {
+        task::consume_budget().await;
+
+        let table_name = self.table_mapping().tablet_name(id.tablet_id)?;
+        let namespace = self.table_mapping().tablet_namespace(id.tablet_id)?;
+
+        let (old_document, old_ts) =
+            self.get_inner(id, table_name.clone())
+                .await?
+                .context(ErrorMetadata::bad_request(
+                    "NonexistentDocument",
+                    format!("Update on nonexistent document ID {id}"),
+                ))?;
+
+        let touched_fields = patch.touched_fields();
+        let new_document =
+            old_document.replace_value(patch.apply(old_document.value().clone().into_value())?)?;
+        let mut schema_model = SchemaModel::new(self, namespace);
+        schema_model
+            .enforce_mutability(&old_document, &new_document)
+            .await?;
+        schema_model
+            .enforce_patch(&new_document, &touched_fields)
             .await?;
 
         self.apply_validated_write(id, Some((old_document, old_ts)), Some(new_document.clone()))?;
@@ -602,9 +703,11 @@ impl<RT: Runtime> Transaction<RT> {
         // Replace document.
         let new_document = old_document.replace_value(value)?;
 
-        SchemaModel::new(self, namespace)
-            .enforce(&new_document)
+        let mut schema_model = SchemaModel::new(self, namespace);
+        schema_model
+            .enforce_mutability(&old_document, &new_document)
             .await?;
+        schema_model.enforce(&new_document).await?;
 
         self.apply_validated_write(
             new_document.id(),
@@ -983,6 +1086,15 @@ impl<RT: Runtime> Transaction<RT> {
             old_document.map(|d| d.value().deref()),
             new_document.as_ref().map(|d| d.value().deref()),
         )?;
+        let namespace = if is_system_document {
+            None
+        } else {
+            Some(self.table_mapping().tablet_namespace(id.tablet_id)?)
+        };
+        let size_delta = new_document
+            .as_ref()
+            .map_or(0, |d| d.value().0.size() as i64)
+            - old_document.map_or(0, |d| d.value().0.size() as i64);
         let stats = self.stats.entry(id.tablet_id).or_default();
         let mut delta = 0;
         match (old_document, new_document.as_ref()) {
@@ -1022,6 +1134,11 @@ impl<RT: Runtime> Transaction<RT> {
         component_update.apply();
 
         *self.table_count_deltas.entry(id.tablet_id).or_default() += delta;
+        if let Some(namespace) = namespace {
+            let usage = self.namespace_usage_deltas.entry(namespace).or_default();
+            usage.0 += delta;
+            usage.1 += size_delta;
+        }
         Ok(())
     }
 
@@ -1043,16 +1160,68 @@ impl<RT: Runtime> Transaction<RT> {
         stable_index_name: &StableIndexName,
         search: &Search,
         version: SearchVersion,
+        search_after: Option<SearchAfter>,
     ) -> anyhow::Result<Vec<(CandidateRevision, IndexKeyBytes)>> {
         let Some(tablet_index_name) = stable_index_name.tablet_index_name() else {
             return Ok(vec![]);
         };
-        let search = search.clone().to_internal(tablet_index_name.clone())?;
+        let mut search = self
+            .internal_search_with_synonyms(search, tablet_index_name)
+            .await?;
+        search.search_after = search_after;
         self.index
             .search(&mut self.reads, &search, tablet_index_name.clone(), version)
             .await
     }
 
+    pub async fn search_highlighter(
+        &mut self,
+        stable_index_name: &StableIndexName,
+        search: &Search,
+        version: SearchVersion,
+    ) -> anyhow::Result<Option<Highlighter>> {
+        let Some(tablet_index_name) = stable_index_name.tablet_index_name() else {
+            return Ok(None);
+        };
+        let search = self
+            .internal_search_with_synonyms(search, tablet_index_name)
+            .await?;
+        self.index
+            .search_highlighter(&search, tablet_index_name, version)
+    }
+
+    /// Converts a search to run against `tablet_index_name` with the index's
+    /// synonyms. Reading the synonyms is recorded, so the transaction is
+    /// invalidated when they change.
+    async fn internal_search_with_synonyms(
+        &mut self,
+        search: &Search,
+        tablet_index_name: &TabletIndexName,
+    ) -> anyhow::Result<InternalSearch> {
+        let mut internal_search = search.clone().to_internal(tablet_index_name.clone())?;
+        let index_id = self
+            .index
+            .index_registry()
+            .get_enabled(tablet_index_name)
+            .map(|index| index.id());
+        if let Some(index_id) = index_id {
+            internal_search.synonyms = SearchSynonymsModel::new(self).synonyms(index_id).await?;
+        }
+        Ok(internal_search)
+    }
+
+    pub fn search_facet_counter(
+        &self,
+        stable_index_name: &StableIndexName,
+        search: &Search,
+    ) -> anyhow::Result<Option<FacetCounter>> {
+        let Some(tablet_index_name) = stable_index_name.tablet_index_name() else {
+            return Ok(None);
+        };
+        let search = search.clone().to_internal(tablet_index_name.clone())?;
+        self.index.search_facet_counter(&search, tablet_index_name)
+    }
+
     // TODO(lee) Make this private.
     // We ideally want the transaction to call this internally so caller doesn't
     // have to call this. However, this is currently hard since the query layer
@@ -1138,6 +1307,9 @@ pub struct FinalTransaction {
     pub(crate) component_registry: ComponentRegistry,
 
     pub(crate) reads: TransactionReadSet,
+    /// `reads` have already been checked for conflicts with commits up to
+    /// this timestamp, which is at least `begin_timestamp`.
+    pub(crate) conflicts_checked_ts: Timestamp,
     pub(crate) writes: Writes,
 
     pub(crate) usage_tracker: FunctionUsageTracker,
@@ -1168,6 +1340,7 @@ impl FinalTransaction {
             table_mapping,
             component_registry,
             reads: transaction.reads,
+            conflicts_checked_ts: *begin_timestamp,
             writes: transaction.writes.into_flat()?,
             usage_tracker: transaction.usage_tracker.clone(),
         })
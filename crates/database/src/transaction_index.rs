@@ -61,8 +61,11 @@ use maplit::btreemap;
 use search::{
     query::RevisionWithKeys,
     CandidateRevision,
+    FacetCounter,
+    Highlighter,
     QueryResults,
     Searcher,
+    TantivySearchIndexSchema,
     TextIndexManager,
 };
 use storage::Storage;
@@ -303,6 +306,36 @@ impl TransactionIndex {
         Ok(results.revisions_with_keys)
     }
 
+    /// Builds a highlighter for a search's results if it asked for highlights.
+    /// Like `search`, this doesn't record a read of the index metadata.
+    pub fn search_highlighter(
+        &self,
+        query: &InternalSearch,
+        index_name: &TabletIndexName,
+        version: SearchVersion,
+    ) -> anyhow::Result<Option<Highlighter>> {
+        let printable_index_name = query.printable_index_name()?;
+        let index = self
+            .index_registry
+            .require_enabled(index_name, &printable_index_name)?;
+        TantivySearchIndexSchema::new_for_index(&index, &printable_index_name)?
+            .highlighter(query, version)
+    }
+
+    /// Builds a counter for a search's facets if it asked for them. Like
+    /// `search`, this doesn't record a read of the index metadata.
+    pub fn search_facet_counter(
+        &self,
+        query: &InternalSearch,
+        index_name: &TabletIndexName,
+    ) -> anyhow::Result<Option<FacetCounter>> {
+        let printable_index_name = query.printable_index_name()?;
+        let index = self
+            .index_registry
+            .require_enabled(index_name, &printable_index_name)?;
+        TantivySearchIndexSchema::new_for_index(&index, &printable_index_name)?.facet_counter(query)
+    }
+
     /// Fetch a batch of index ranges. This method does not update the read set,
     /// since we might be fetching more documents than the caller actually needs
     /// due to filtering.
@@ -451,6 +484,12 @@ impl TransactionIndex {
         old_document: Option<ResolvedDocument>,
         new_document: Option<ResolvedDocument>,
     ) -> anyhow::Result<Update<'_>> {
+        if let Some(new_document) = &new_document
+            && self.supports_chunked_documents()
+        {
+            self.index_registry
+                .verify_indexed_fields_inline(new_document)?;
+        }
         let mut registry = self.index_registry.clone();
         registry.update(old_document.as_ref(), new_document.as_ref())?;
 
@@ -588,6 +627,15 @@ impl TransactionIndex {
 
     /// Returns the snapshot the transaction is based on ignoring any pending
     /// updates.
+    /// Whether documents written in this transaction may have chunked fields.
+    /// See `PersistenceReader::supports_chunked_documents`.
+    pub fn supports_chunked_documents(&self) -> bool {
+        self.database_index_snapshot
+            .persistence()
+            .persistence()
+            .supports_chunked_documents()
+    }
+
     pub fn base_snapshot(&self) -> &DatabaseIndexSnapshot {
         &self.database_index_snapshot
     }
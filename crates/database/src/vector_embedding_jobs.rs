@@ -0,0 +1,288 @@
+//! A queue of documents whose vector index embedders need to compute a new
+//! vector, because a write set the embedder's source field to a new string.
+//! These live in the database crate rather than the model crate because
+//! `UserFacingModel` queues them in the writing transaction.
+//!
+//! Pending jobs are due at `next_attempt_ms`. The embedding worker retries a
+//! job with backoff when the embedder's action fails, and leaves it `Failed`
+//! after too many attempts. A job is deleted once its vector is written, so
+//! the jobs of an index are exactly its stale documents.
+
+use std::{
+    collections::BTreeMap,
+    str::FromStr,
+    sync::LazyLock,
+};
+
+use common::{
+    document::{
+        ParseDocument,
+        ParsedDocument,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::IndexDescriptor,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::{
+    codegen_convex_serialization,
+    ConvexValue,
+    DeveloperDocumentId,
+    FieldPath,
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+    TabletId,
+};
+
+use crate::{
+    system_tables::{
+        SystemIndex,
+        SystemTable,
+    },
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+
+pub static VECTOR_EMBEDDING_JOBS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_vector_embedding_jobs"
+        .parse()
+        .expect("Invalid built-in table name")
+});
+
+static STATE_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "state".parse().expect("Invalid built-in field"));
+static NEXT_ATTEMPT_MS_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "nextAttemptMs".parse().expect("Invalid built-in field"));
+
+pub static VECTOR_EMBEDDING_JOBS_BY_STATE_AND_NEXT_ATTEMPT: LazyLock<
+    SystemIndex<VectorEmbeddingJobsTable>,
+> = LazyLock::new(|| {
+    SystemIndex::new(
+        "by_state_and_next_attempt",
+        [&STATE_FIELD, &NEXT_ATTEMPT_MS_FIELD],
+    )
+    .unwrap()
+});
+
+pub struct VectorEmbeddingJobsTable;
+
+impl SystemTable for VectorEmbeddingJobsTable {
+    type Metadata = VectorEmbeddingJob;
+
+    fn table_name() -> &'static TableName {
+        &VECTOR_EMBEDDING_JOBS_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![VECTOR_EMBEDDING_JOBS_BY_STATE_AND_NEXT_ATTEMPT.clone()]
+    }
+}
+
+/// A document that the embedder of the vector index `index_descriptor` on
+/// its table needs to embed.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct VectorEmbeddingJob {
+    pub tablet_id: TabletId,
+    pub document_id: DeveloperDocumentId,
+    pub index_descriptor: IndexDescriptor,
+    pub attempts: u32,
+    pub next_attempt_ms: i64,
+    pub state: VectorEmbeddingJobState,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum VectorEmbeddingJobState {
+    Pending,
+    Failed { error: String },
+}
+
+impl VectorEmbeddingJob {
+    pub fn document_id(&self) -> ResolvedDocumentId {
+        ResolvedDocumentId::new(self.tablet_id, self.document_id)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedVectorEmbeddingJob {
+    tablet_id: String,
+    document_id: String,
+    index_descriptor: String,
+    attempts: i64,
+    next_attempt_ms: i64,
+    state: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl From<VectorEmbeddingJob> for SerializedVectorEmbeddingJob {
+    fn from(job: VectorEmbeddingJob) -> Self {
+        let (state, error) = match job.state {
+            VectorEmbeddingJobState::Pending => ("pending", None),
+            VectorEmbeddingJobState::Failed { error } => ("failed", Some(error)),
+        };
+        Self {
+            tablet_id: job.tablet_id.to_string(),
+            document_id: job.document_id.encode(),
+            index_descriptor: job.index_descriptor.to_string(),
+            attempts: job.attempts as i64,
+            next_attempt_ms: job.next_attempt_ms,
+            state: state.to_string(),
+            error,
+        }
+    }
+}
+
+impl TryFrom<SerializedVectorEmbeddingJob> for VectorEmbeddingJob {
+    type Error = anyhow::Error;
+
+    fn try_from(job: SerializedVectorEmbeddingJob) -> anyhow::Result<Self> {
+        let state = match (job.state.as_str(), job.error) {
+            ("pending", None) => VectorEmbeddingJobState::Pending,
+            ("failed", Some(error)) => VectorEmbeddingJobState::Failed { error },
+            (state, _) => anyhow::bail!("Invalid vector embedding job state {state}"),
+        };
+        Ok(Self {
+            tablet_id: TabletId::from_str(&job.tablet_id)?,
+            document_id: DeveloperDocumentId::decode(&job.document_id)?,
+            index_descriptor: IndexDescriptor::new(job.index_descriptor)?,
+            attempts: job.attempts.try_into()?,
+            next_attempt_ms: job.next_attempt_ms,
+            state,
+        })
+    }
+}
+
+codegen_convex_serialization!(VectorEmbeddingJob, SerializedVectorEmbeddingJob);
+
+/// The stale documents of one vector index with an embedder.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VectorEmbeddingStatus {
+    pub pending: u64,
+    pub failed: u64,
+    /// When the oldest pending job was queued.
+    pub oldest_pending_ms: Option<f64>,
+}
+
+pub struct VectorEmbeddingJobModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> VectorEmbeddingJobModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    /// Queues `document_id` for the embedder of `index_descriptor`, due now.
+    pub async fn enqueue(
+        &mut self,
+        document_id: ResolvedDocumentId,
+        index_descriptor: IndexDescriptor,
+        now_ms: i64,
+    ) -> anyhow::Result<()> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&VECTOR_EMBEDDING_JOBS_TABLE) {
+            return Ok(());
+        }
+        let job = VectorEmbeddingJob {
+            tablet_id: document_id.tablet_id,
+            document_id: document_id.developer_id,
+            index_descriptor,
+            attempts: 0,
+            next_attempt_ms: now_ms,
+            state: VectorEmbeddingJobState::Pending,
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .insert(&VECTOR_EMBEDDING_JOBS_TABLE, job.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    /// The pending jobs due by `now_ms`, oldest first.
+    pub async fn list_due(
+        &mut self,
+        now_ms: i64,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ParsedDocument<VectorEmbeddingJob>>> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&VECTOR_EMBEDDING_JOBS_TABLE) {
+            return Ok(vec![]);
+        }
+        let range = vec![
+            IndexRangeExpression::Eq(
+                STATE_FIELD.clone(),
+                ConvexValue::String("pending".try_into()?).into(),
+            ),
+            IndexRangeExpression::Lte(
+                NEXT_ATTEMPT_MS_FIELD.clone(),
+                ConvexValue::Int64(now_ms).into(),
+            ),
+        ];
+        let query = Query::index_range(IndexRange {
+            index_name: VECTOR_EMBEDDING_JOBS_BY_STATE_AND_NEXT_ATTEMPT.name(),
+            range,
+            order: Order::Asc,
+        })
+        .limit(limit);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut jobs = vec![];
+        while let Some(document) = query_stream.next(self.tx, None).await? {
+            jobs.push(document.parse()?);
+        }
+        Ok(jobs)
+    }
+
+    /// Counts the stale documents of each index with queued jobs.
+    pub async fn status(
+        &mut self,
+    ) -> anyhow::Result<BTreeMap<(TabletId, IndexDescriptor), VectorEmbeddingStatus>> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&VECTOR_EMBEDDING_JOBS_TABLE) {
+            return Ok(BTreeMap::new());
+        }
+        let query = Query::full_table_scan(VECTOR_EMBEDDING_JOBS_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut status = BTreeMap::<_, VectorEmbeddingStatus>::new();
+        while let Some(document) = query_stream.next(self.tx, None).await? {
+            let creation_time = f64::from(document.creation_time());
+            let job: ParsedDocument<VectorEmbeddingJob> = document.parse()?;
+            let index_status = status
+                .entry((job.tablet_id, job.index_descriptor.clone()))
+                .or_default();
+            match job.state {
+                VectorEmbeddingJobState::Pending => {
+                    index_status.pending += 1;
+                    index_status.oldest_pending_ms = Some(
+                        index_status
+                            .oldest_pending_ms
+                            .map_or(creation_time, |oldest| oldest.min(creation_time)),
+                    );
+                },
+                VectorEmbeddingJobState::Failed { .. } => index_status.failed += 1,
+            }
+        }
+        Ok(status)
+    }
+
+    pub async fn replace(&mut self, job: ParsedDocument<VectorEmbeddingJob>) -> anyhow::Result<()> {
+        let (id, job) = job.into_id_and_value();
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, job.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete(&mut self, id: ResolvedDocumentId) -> anyhow::Result<()> {
+        SystemMetadataModel::new_global(self.tx).delete(id).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,414 @@
+//! Workflows that move a vector index's searches over to a new index on a
+//! re-embedded field, e.g. to change embedding models or dimensions. These
+//! live in the database crate rather than the model crate because vector
+//! search reads them to route searches after cutover.
+//!
+//! A migration goes through these states:
+//! 1. `Backfilling`: the re-embedding worker pages through the table in
+//!    creation order, calling the user's re-embedding action on documents that
+//!    don't have a vector in the target index's field yet.
+//! 2. `ReadyForCutover`: the backfill has reached the end of the table. The
+//!    worker keeps re-embedding documents inserted after that, so both indexes
+//!    stay populated until cutover. Mutations that update existing documents'
+//!    vectors should write both fields during this window.
+//! 3. `CutOver`: searches against the source index run against the target index
+//!    instead, starting atomically at the commit that cut over.
+//!
+//! A migration stops in `Failed` if the re-embedding action throws, or in
+//! `Canceled` if an admin cancels it before cutover.
+
+use std::{
+    str::FromStr,
+    sync::LazyLock,
+};
+
+use common::{
+    document::{
+        CreationTime,
+        ParseDocument,
+        ParsedDocument,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::IndexId,
+};
+use errors::ErrorMetadata;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::{
+    codegen_convex_serialization,
+    ConvexValue,
+    FieldPath,
+    InternalId,
+    TableName,
+    TableNamespace,
+};
+
+use crate::{
+    system_tables::{
+        SystemIndex,
+        SystemTable,
+    },
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+
+/// How many documents can the re-embedding action be called with at once?
+pub const MAX_REEMBED_BATCH_SIZE: u32 = 512;
+
+pub const DEFAULT_REEMBED_BATCH_SIZE: u32 = 64;
+
+pub static VECTOR_INDEX_MIGRATIONS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_vector_index_migrations"
+        .parse()
+        .expect("Invalid built-in table name")
+});
+
+static SOURCE_INDEX_ID_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "sourceIndexId".parse().expect("Invalid built-in field"));
+
+pub static VECTOR_INDEX_MIGRATIONS_BY_SOURCE_INDEX_ID: LazyLock<
+    SystemIndex<VectorIndexMigrationsTable>,
+> = LazyLock::new(|| SystemIndex::new("by_source_index_id", [&SOURCE_INDEX_ID_FIELD]).unwrap());
+
+pub struct VectorIndexMigrationsTable;
+
+impl SystemTable for VectorIndexMigrationsTable {
+    type Metadata = VectorIndexMigration;
+
+    fn table_name() -> &'static TableName {
+        &VECTOR_INDEX_MIGRATIONS_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![VECTOR_INDEX_MIGRATIONS_BY_SOURCE_INDEX_ID.clone()]
+    }
+}
+
+/// Moves searches from one vector index to another on the same table once a
+/// user-provided action has re-embedded every document into the target
+/// index's vector field.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct VectorIndexMigration {
+    pub source_index_id: IndexId,
+    pub target_index_id: IndexId,
+    /// The action called with `{ ids }` to write new vectors for a batch of
+    /// documents, as a path like `embeddings:reembed` in the index's
+    /// component.
+    pub reembed_action: String,
+    pub batch_size: u32,
+    pub state: VectorIndexMigrationState,
+    /// The creation time of the last document the backfill has looked at.
+    pub cursor: Option<CreationTime>,
+    /// How many documents the backfill has looked at.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=(i64::MAX as u64)")
+    )]
+    pub documents_scanned: u64,
+    /// How many documents the backfill has passed to the re-embedding action.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=(i64::MAX as u64)")
+    )]
+    pub documents_reembedded: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum VectorIndexMigrationState {
+    Backfilling,
+    ReadyForCutover,
+    CutOver,
+    Failed { error: String },
+    Canceled,
+}
+
+impl VectorIndexMigrationState {
+    /// Whether the worker is still re-embedding documents for the migration.
+    pub fn is_active(&self) -> bool {
+        matches!(self, Self::Backfilling | Self::ReadyForCutover)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedVectorIndexMigration {
+    source_index_id: String,
+    target_index_id: String,
+    reembed_action: String,
+    batch_size: i64,
+    state: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    cursor: Option<f64>,
+    documents_scanned: i64,
+    documents_reembedded: i64,
+}
+
+impl From<VectorIndexMigration> for SerializedVectorIndexMigration {
+    fn from(migration: VectorIndexMigration) -> Self {
+        let (state, error) = match migration.state {
+            VectorIndexMigrationState::Backfilling => ("backfilling", None),
+            VectorIndexMigrationState::ReadyForCutover => ("readyForCutover", None),
+            VectorIndexMigrationState::CutOver => ("cutOver", None),
+            VectorIndexMigrationState::Failed { error } => ("failed", Some(error)),
+            VectorIndexMigrationState::Canceled => ("canceled", None),
+        };
+        Self {
+            source_index_id: migration.source_index_id.to_string(),
+            target_index_id: migration.target_index_id.to_string(),
+            reembed_action: migration.reembed_action,
+            batch_size: migration.batch_size as i64,
+            state: state.to_string(),
+            error,
+            cursor: migration.cursor.map(f64::from),
+            documents_scanned: migration.documents_scanned as i64,
+            documents_reembedded: migration.documents_reembedded as i64,
+        }
+    }
+}
+
+impl TryFrom<SerializedVectorIndexMigration> for VectorIndexMigration {
+    type Error = anyhow::Error;
+
+    fn try_from(migration: SerializedVectorIndexMigration) -> anyhow::Result<Self> {
+        let state = match (migration.state.as_str(), migration.error) {
+            ("backfilling", None) => VectorIndexMigrationState::Backfilling,
+            ("readyForCutover", None) => VectorIndexMigrationState::ReadyForCutover,
+            ("cutOver", None) => VectorIndexMigrationState::CutOver,
+            ("failed", Some(error)) => VectorIndexMigrationState::Failed { error },
+            ("canceled", None) => VectorIndexMigrationState::Canceled,
+            (state, _) => anyhow::bail!("Invalid vector index migration state {state}"),
+        };
+        Ok(Self {
+            source_index_id: InternalId::from_str(&migration.source_index_id)?,
+            target_index_id: InternalId::from_str(&migration.target_index_id)?,
+            reembed_action: migration.reembed_action,
+            batch_size: migration.batch_size.try_into()?,
+            state,
+            cursor: migration.cursor.map(CreationTime::try_from).transpose()?,
+            documents_scanned: migration.documents_scanned.try_into()?,
+            documents_reembedded: migration.documents_reembedded.try_into()?,
+        })
+    }
+}
+
+codegen_convex_serialization!(VectorIndexMigration, SerializedVectorIndexMigration);
+
+pub struct VectorIndexMigrationModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> VectorIndexMigrationModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    pub async fn list(&mut self) -> anyhow::Result<Vec<ParsedDocument<VectorIndexMigration>>> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&VECTOR_INDEX_MIGRATIONS_TABLE) {
+            return Ok(vec![]);
+        }
+        let query = Query::full_table_scan(VECTOR_INDEX_MIGRATIONS_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut migrations = vec![];
+        while let Some(document) = query_stream.next(self.tx, None).await? {
+            migrations.push(document.parse()?);
+        }
+        Ok(migrations)
+    }
+
+    /// The migrations from `source_index_id`, oldest first.
+    async fn list_from(
+        &mut self,
+        source_index_id: IndexId,
+    ) -> anyhow::Result<Vec<ParsedDocument<VectorIndexMigration>>> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&VECTOR_INDEX_MIGRATIONS_TABLE) {
+            return Ok(vec![]);
+        }
+        let range = vec![IndexRangeExpression::Eq(
+            SOURCE_INDEX_ID_FIELD.clone(),
+            ConvexValue::String(source_index_id.to_string().try_into()?).into(),
+        )];
+        let query = Query::index_range(IndexRange {
+            index_name: VECTOR_INDEX_MIGRATIONS_BY_SOURCE_INDEX_ID.name(),
+            range,
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut migrations = vec![];
+        while let Some(document) = query_stream.next(self.tx, None).await? {
+            migrations.push(document.parse()?);
+        }
+        Ok(migrations)
+    }
+
+    /// The migrations the re-embedding worker is still working on.
+    pub async fn list_active(
+        &mut self,
+    ) -> anyhow::Result<Vec<ParsedDocument<VectorIndexMigration>>> {
+        Ok(self
+            .list()
+            .await?
+            .into_iter()
+            .filter(|migration| migration.state.is_active())
+            .collect())
+    }
+
+    /// The index that searches against `index_id` should run against, which
+    /// is the index itself unless a migration from it has cut over.
+    pub async fn search_index_id(&mut self, index_id: IndexId) -> anyhow::Result<IndexId> {
+        let cut_over = self
+            .list_from(index_id)
+            .await?
+            .into_iter()
+            .find(|migration| migration.state == VectorIndexMigrationState::CutOver);
+        Ok(cut_over.map_or(index_id, |migration| migration.target_index_id))
+    }
+
+    /// Starts re-embedding documents for a migration from `source_index_id` to
+    /// `target_index_id`. Callers are responsible for checking that both are
+    /// enabled vector indexes on the same table.
+    pub async fn start(
+        &mut self,
+        source_index_id: IndexId,
+        target_index_id: IndexId,
+        reembed_action: String,
+        batch_size: Option<u32>,
+    ) -> anyhow::Result<VectorIndexMigration> {
+        let batch_size = batch_size.unwrap_or(DEFAULT_REEMBED_BATCH_SIZE);
+        anyhow::ensure!(
+            (1..=MAX_REEMBED_BATCH_SIZE).contains(&batch_size),
+            ErrorMetadata::bad_request(
+                "InvalidReembedBatchSize",
+                format!(
+                    "The re-embedding batch size must be between 1 and {MAX_REEMBED_BATCH_SIZE}, \
+                     got {batch_size}."
+                ),
+            )
+        );
+        for existing in self.list().await? {
+            let involved = [existing.source_index_id, existing.target_index_id];
+            if (existing.state.is_active() || existing.state == VectorIndexMigrationState::CutOver)
+                && (involved.contains(&source_index_id) || involved.contains(&target_index_id))
+            {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "VectorIndexMigrationExists",
+                    "One of these indexes is already part of a vector index migration. Cancel it \
+                     or remove the cut over index from your schema first.",
+                ));
+            }
+        }
+        let migration = VectorIndexMigration {
+            source_index_id,
+            target_index_id,
+            reembed_action,
+            batch_size,
+            state: VectorIndexMigrationState::Backfilling,
+            cursor: None,
+            documents_scanned: 0,
+            documents_reembedded: 0,
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .insert(
+                &VECTOR_INDEX_MIGRATIONS_TABLE,
+                migration.clone().try_into()?,
+            )
+            .await?;
+        Ok(migration)
+    }
+
+    /// The migration from `source_index_id` that hasn't finished, if any.
+    pub async fn get_active(
+        &mut self,
+        source_index_id: IndexId,
+    ) -> anyhow::Result<Option<ParsedDocument<VectorIndexMigration>>> {
+        Ok(self
+            .list_from(source_index_id)
+            .await?
+            .into_iter()
+            .find(|migration| migration.state.is_active()))
+    }
+
+    pub async fn replace(
+        &mut self,
+        migration: ParsedDocument<VectorIndexMigration>,
+    ) -> anyhow::Result<()> {
+        let (id, migration) = migration.into_id_and_value();
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, migration.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    /// Routes searches against the source index to the target index, as of
+    /// this transaction's commit.
+    pub async fn cut_over(&mut self, source_index_id: IndexId) -> anyhow::Result<()> {
+        let migration = self
+            .get_active(source_index_id)
+            .await?
+            .ok_or_else(no_active_migration_error)?;
+        anyhow::ensure!(
+            migration.state == VectorIndexMigrationState::ReadyForCutover,
+            ErrorMetadata::bad_request(
+                "VectorIndexMigrationNotReady",
+                "The re-embedding backfill hasn't reached the end of the table yet.",
+            )
+        );
+        let (id, mut migration) = migration.into_id_and_value();
+        migration.state = VectorIndexMigrationState::CutOver;
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, migration.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn cancel(&mut self, source_index_id: IndexId) -> anyhow::Result<()> {
+        let migration = self
+            .get_active(source_index_id)
+            .await?
+            .ok_or_else(no_active_migration_error)?;
+        let (id, mut migration) = migration.into_id_and_value();
+        migration.state = VectorIndexMigrationState::Canceled;
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, migration.try_into()?)
+            .await?;
+        Ok(())
+    }
+}
+
+fn no_active_migration_error() -> ErrorMetadata {
+    ErrorMetadata::bad_request(
+        "NoVectorIndexMigration",
+        "There's no vector index migration in progress from this index.",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use value::{
+        testing::assert_roundtrips,
+        ConvexObject,
+    };
+
+    use super::VectorIndexMigration;
+
+    proptest! {
+        #![proptest_config(ProptestConfig { failure_persistence: None, ..ProptestConfig::default() })]
+
+        #[test]
+        fn test_vector_index_migration_roundtrips(migration in any::<VectorIndexMigration>()) {
+            assert_roundtrips::<VectorIndexMigration, ConvexObject>(migration);
+        }
+    }
+}
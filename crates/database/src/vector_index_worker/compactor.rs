@@ -598,7 +598,10 @@ mod tests {
                     component_id: ComponentId::Root,
                     vector: vec![0f32, 0f32],
                     limit: Some(10),
+                    ef_search: None,
                     expressions: btreeset![],
+                    include_vectors: false,
+                    mmr: None,
                 },
             )
             .await?;
@@ -114,8 +114,10 @@ mod tests {
         bootstrap_model::index::{
             vector_index::{
                 FragmentedVectorSegment,
+                VectorIndexKind,
                 VectorIndexSnapshot,
                 VectorIndexState,
+                VectorQuantization,
             },
             IndexConfig,
             IndexMetadata,
@@ -127,6 +129,7 @@ mod tests {
         },
         pause::PauseController,
         persistence::PersistenceReader,
+        query::search_value_to_bytes,
         runtime::Runtime,
         types::{
             IndexId,
@@ -139,7 +142,13 @@ mod tests {
         btreeset,
     };
     use must_let::must_let;
-    use qdrant_segment::vector_storage::VectorStorage;
+    use qdrant_segment::{
+        types::{
+            Indexes,
+            QuantizationConfig,
+        },
+        vector_storage::VectorStorage,
+    };
     use runtime::testing::TestRuntime;
     use storage::LocalDirStorage;
     use value::{
@@ -149,6 +158,7 @@ mod tests {
         ResolvedDocumentId,
     };
     use vector::{
+        qdrant_segments::DEFAULT_VECTOR_NAME,
         PublicVectorSearchQueryResult,
         QdrantExternalId,
         VectorSearch,
@@ -172,6 +182,7 @@ mod tests {
         Database,
         IndexModel,
         SystemMetadataModel,
+        TestFacingModel,
         UserFacingModel,
     };
 
@@ -352,6 +363,109 @@ mod tests {
         Ok(())
     }
 
+    #[convex_macro::test_runtime]
+    async fn worker_for_hnsw_index_kind_uses_in_memory_hnsw_under_threshold(
+        rt: TestRuntime,
+    ) -> anyhow::Result<()> {
+        let fixtures = VectorFixtures::new(rt.clone()).await?;
+
+        let IndexData { index_name, .. } = fixtures
+            .backfilling_vector_index_with_options(
+                VectorIndexKind::Hnsw,
+                VectorQuantization::None,
+                None,
+            )
+            .await?;
+        fixtures
+            .add_document_vec_array(index_name.table(), [3f64, 4f64])
+            .await?;
+        let mut worker = fixtures.new_index_flusher_with_full_scan_threshold(1000000)?;
+        worker.step().await?;
+
+        let segments = fixtures.get_segments_metadata(index_name).await?;
+        assert_eq!(segments.len(), 1);
+        let segment = segments.first().unwrap();
+
+        let segment = fixtures.load_segment(segment).await?;
+        assert!(segment.segment_config.is_any_vector_indexed());
+        must_let!(let Indexes::Hnsw(hnsw_config) =
+            &segment.segment_config.vector_data[DEFAULT_VECTOR_NAME].index);
+        assert_eq!(hnsw_config.on_disk, Some(false));
+
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn worker_for_scalar_quantization_builds_quantized_vectors(
+        rt: TestRuntime,
+    ) -> anyhow::Result<()> {
+        let fixtures = VectorFixtures::new(rt.clone()).await?;
+
+        let IndexData { index_name, .. } = fixtures
+            .backfilling_vector_index_with_options(
+                VectorIndexKind::Hnsw,
+                VectorQuantization::Scalar,
+                None,
+            )
+            .await?;
+        for i in 0..10 {
+            fixtures
+                .add_document_vec_array(index_name.table(), [i as f64, 1f64])
+                .await?;
+        }
+        let mut worker = fixtures.new_index_flusher()?;
+        worker.step().await?;
+
+        let segments = fixtures.get_segments_metadata(index_name).await?;
+        assert_eq!(segments.len(), 1);
+        let segment = segments.first().unwrap();
+
+        let segment = fixtures.load_segment(segment).await?;
+        let vector_data = &segment.vector_data[DEFAULT_VECTOR_NAME];
+        assert!(vector_data.quantized_vectors.borrow().is_some());
+        must_let!(let Some(QuantizationConfig::Scalar(_)) =
+            &segment.segment_config.vector_data[DEFAULT_VECTOR_NAME].quantization_config);
+
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn worker_for_partitioned_index_records_segment_partitions(
+        rt: TestRuntime,
+    ) -> anyhow::Result<()> {
+        let fixtures = VectorFixtures::new(rt.clone()).await?;
+
+        let IndexData { index_name, .. } = fixtures
+            .backfilling_vector_index_with_options(
+                Default::default(),
+                Default::default(),
+                Some("channel".parse()?),
+            )
+            .await?;
+        let mut tx = fixtures.db.begin_system().await?;
+        for channel in ["#general", "#random", "#general"] {
+            TestFacingModel::new(&mut tx)
+                .insert(
+                    index_name.table(),
+                    assert_obj!("vector" => assert_val!([1f64, 2f64]), "channel" => channel),
+                )
+                .await?;
+        }
+        fixtures.db.commit(tx).await?;
+        let mut worker = fixtures.new_index_flusher()?;
+        worker.step().await?;
+
+        let segments = fixtures.get_segments_metadata(index_name).await?;
+        assert_eq!(segments.len(), 1);
+        let general = search_value_to_bytes(Some(&ConvexValue::try_from("#general")?));
+        let random = search_value_to_bytes(Some(&ConvexValue::try_from("#random")?));
+        let other = search_value_to_bytes(Some(&ConvexValue::try_from("#other")?));
+        assert_eq!(segments[0].partitions, Some(btreeset! { general, random }));
+        assert!(!segments[0].may_contain_partitions(&btreeset! { other }));
+
+        Ok(())
+    }
+
     #[convex_macro::test_runtime]
     async fn worker_with_deleted_vector_does_not_include_deleted_vector_in_segment(
         rt: TestRuntime,
@@ -1070,7 +1184,10 @@ mod tests {
                     component_id: ComponentId::Root,
                     vector: vector.into_iter().map(|value| value as f32).collect(),
                     limit: Some(1),
+                    ef_search: None,
                     expressions: btreeset![],
+                    include_vectors: false,
+                    mmr: None,
                 },
             )
             .await?
@@ -22,6 +22,7 @@ use common::{
         ParsedDocument,
         ResolvedDocument,
     },
+    knobs::VECTOR_MAX_SEGMENT_PARTITIONS,
     persistence::{
         DocumentStream,
         RepeatablePersistence,
@@ -269,13 +270,28 @@ impl SearchIndex for VectorSearchIndex {
         config: &Self::DeveloperConfig,
         segments: Vec<Self::Segment>,
     ) -> anyhow::Result<Self::Segment> {
+        // The merged segment contains the vectors of every partition its inputs
+        // did.
+        let partitions = segments
+            .iter()
+            .map(|segment| segment.partitions.clone())
+            .reduce(|lhs, rhs| {
+                let mut partitions = lhs?;
+                partitions.extend(rhs?);
+                (partitions.len() <= *VECTOR_MAX_SEGMENT_PARTITIONS).then_some(partitions)
+            })
+            .flatten();
         let protos: Vec<pb::searchlight::FragmentedVectorSegmentPaths> = segments
             .into_iter()
             .map(|segment| segment.to_paths_proto())
             .collect::<anyhow::Result<Vec<_>>>()?;
-        searcher
+        let segment = searcher
             .execute_vector_compaction(search_storage, protos, config.dimensions.into())
-            .await
+            .await?;
+        Ok(FragmentedVectorSegment {
+            partitions,
+            ..segment
+        })
     }
 
     async fn merge_deletes(
@@ -326,6 +342,8 @@ impl From<VectorIndexBackfillState> for BackfillState<VectorSearchIndex> {
             segments: value.segments,
             cursor: value.cursor,
             backfill_snapshot_ts: value.backfill_snapshot_ts,
+            // Vector index backfills don't report progress.
+            total_documents: None,
         }
     }
 }
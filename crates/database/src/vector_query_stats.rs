@@ -0,0 +1,65 @@
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::Duration,
+};
+
+use common::types::IndexId;
+use parking_lot::Mutex;
+
+/// The searches against one vector index since its stats were last taken.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct VectorQueryStats {
+    pub count: u64,
+    pub total_latency: Duration,
+}
+
+impl VectorQueryStats {
+    pub fn average_latency(&self) -> Option<Duration> {
+        (self.count > 0).then(|| self.total_latency / self.count as u32)
+    }
+}
+
+/// Accumulates the latency of each vector index's searches on this backend
+/// until the vector index stats worker takes them.
+#[derive(Clone, Default)]
+pub struct VectorQueryStatsTracker {
+    stats: Arc<Mutex<BTreeMap<IndexId, VectorQueryStats>>>,
+}
+
+impl VectorQueryStatsTracker {
+    pub fn record(&self, index_id: IndexId, latency: Duration) {
+        let mut stats = self.stats.lock();
+        let index_stats = stats.entry(index_id).or_default();
+        index_stats.count += 1;
+        index_stats.total_latency += latency;
+    }
+
+    pub fn take(&self) -> BTreeMap<IndexId, VectorQueryStats> {
+        std::mem::take(&mut *self.stats.lock())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use value::InternalId;
+
+    use super::VectorQueryStatsTracker;
+
+    #[test]
+    fn test_take_resets_stats() {
+        let tracker = VectorQueryStatsTracker::default();
+        let index_id = InternalId::MIN;
+        tracker.record(index_id, Duration::from_millis(10));
+        tracker.record(index_id, Duration::from_millis(30));
+        let stats = tracker.take();
+        assert_eq!(stats[&index_id].count, 2);
+        assert_eq!(
+            stats[&index_id].average_latency(),
+            Some(Duration::from_millis(20))
+        );
+        assert!(tracker.take().is_empty());
+    }
+}
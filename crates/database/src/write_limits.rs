@@ -4,4 +4,6 @@ use value::DeveloperDocumentId;
 pub struct BiggestDocumentWrites {
     pub max_size: (DeveloperDocumentId, usize),
     pub max_nesting: (DeveloperDocumentId, usize),
+    /// The largest a document can be on this deployment's persistence.
+    pub max_size_limit: usize,
 }
@@ -1,5 +1,6 @@
 use std::{
     borrow::Cow,
+    cmp,
     collections::{
         BTreeMap,
         VecDeque,
@@ -15,9 +16,18 @@ use common::{
     },
     knobs::{
         WRITE_LOG_MAX_RETENTION_SECS,
+        WRITE_LOG_MEMORY_BUDGET_BYTES,
+        WRITE_LOG_MIN_IN_MEMORY_SECS,
         WRITE_LOG_MIN_RETENTION_SECS,
+        WRITE_LOG_READ_BACK_CACHE_SIZE_BYTES,
         WRITE_LOG_SOFT_MAX_SIZE_BYTES,
     },
+    persistence::{
+        RepeatablePersistence,
+        TimestampRange,
+    },
+    persistence_helpers::stream_revision_pairs,
+    query::Order,
     runtime::block_in_place,
     types::{
         PersistenceVersion,
@@ -29,7 +39,10 @@ use errors::{
     ErrorMetadata,
     ErrorMetadataAnyhowExt,
 };
-use futures::Future;
+use futures::{
+    Future,
+    TryStreamExt,
+};
 use imbl::Vector;
 use indexing::index_registry::{
     DocumentIndexKeys,
@@ -183,13 +196,33 @@ impl HeapSize for WriteSource {
 struct WriteLogManager {
     log: WriteLog,
     waiters: VecDeque<(Timestamp, oneshot::Sender<()>)>,
+    /// Counts of the [`SpillPin`]s at each timestamp. Nothing after the
+    /// earliest of them is spilled.
+    spill_pins: BTreeMap<Timestamp, usize>,
 }
 
 impl WriteLogManager {
     fn new(initial_timestamp: Timestamp, persistence_version: PersistenceVersion) -> Self {
         let log = WriteLog::new(initial_timestamp, persistence_version);
         let waiters = VecDeque::new();
-        Self { log, waiters }
+        Self {
+            log,
+            waiters,
+            spill_pins: BTreeMap::new(),
+        }
+    }
+
+    fn pin_spill(&mut self, ts: Timestamp) {
+        *self.spill_pins.entry(ts).or_default() += 1;
+    }
+
+    fn unpin_spill(&mut self, ts: Timestamp) {
+        if let Some(count) = self.spill_pins.get_mut(&ts) {
+            *count -= 1;
+            if *count == 0 {
+                self.spill_pins.remove(&ts);
+            }
+        }
     }
 
     fn notify_waiters(&mut self) {
@@ -248,6 +281,14 @@ impl WriteLogManager {
         let target_ts = current_ts
             .sub(*WRITE_LOG_MAX_RETENTION_SECS)
             .unwrap_or(Timestamp::MIN);
+        // Spilled entries don't take up memory, so only their age limits them.
+        if self.log.spilled_ts > self.log.purged_ts {
+            let spilled_purge_ts = cmp::min(
+                self.log.spilled_ts,
+                target_ts.pred().unwrap_or(Timestamp::MIN),
+            );
+            self.log.purged_ts = cmp::max(self.log.purged_ts, spilled_purge_ts);
+        }
         while let Some((ts, ..)) = self.log.by_ts.front().map(|entry| &**entry) {
             let ts = *ts;
 
@@ -263,18 +304,80 @@ impl WriteLogManager {
             }
 
             self.log.purged_ts = ts;
+            self.log.spilled_ts = ts;
             self.log.by_ts.pop_front();
         }
+        self.log.purge_read_back();
+
+        self.spill(current_ts, *WRITE_LOG_MEMORY_BUDGET_BYTES);
+    }
+
+    /// If the log is over its memory budget, spills the oldest entries that are
+    /// old enough that few commits should still need them. Spilled entries
+    /// move to the read-back cache until it's full, and are read back from
+    /// persistence after that. Entries after a [`SpillPin`] stay in memory.
+    fn spill(&mut self, current_ts: Timestamp, budget_bytes: usize) {
+        let mut spill_ts = current_ts
+            .sub(*WRITE_LOG_MIN_IN_MEMORY_SECS)
+            .unwrap_or(Timestamp::MIN);
+        if let Some((&pinned_ts, _)) = self.spill_pins.first_key_value() {
+            spill_ts = cmp::min(spill_ts, pinned_ts.succ().unwrap_or(Timestamp::MAX));
+        }
+        while self.log.by_ts.heap_size() > budget_bytes {
+            let Some(ts) = self.log.by_ts.front().map(|entry| entry.0) else {
+                break;
+            };
+            if ts >= spill_ts {
+                break;
+            }
+            let size_before = self.log.by_ts.heap_size();
+            self.log.spilled_ts = ts;
+            let entry = self.log.by_ts.pop_front().expect("checked above");
+            self.log.read_back.push_back(entry);
+            metrics::log_write_log_spill(size_before - self.log.by_ts.heap_size());
+        }
+        self.log
+            .trim_read_back(*WRITE_LOG_READ_BACK_CACHE_SIZE_BYTES);
+        metrics::log_write_log_size(self.log.by_ts.heap_size(), self.log.by_ts.len());
+    }
+
+    /// Caches entries in `(read_back_ts, to]` that were read back from
+    /// persistence, if they still extend the read-back cache and fit in it.
+    fn cache_read_back(
+        &mut self,
+        read_back_ts: Timestamp,
+        to: Timestamp,
+        entries: &[WriteLogEntry],
+    ) {
+        if self.log.read_back_ts != to || read_back_ts < self.log.purged_ts {
+            return;
+        }
+        let size: usize = entries.iter().map(|entry| entry.heap_size()).sum();
+        if self.log.read_back.heap_size() + size > *WRITE_LOG_READ_BACK_CACHE_SIZE_BYTES {
+            return;
+        }
+        self.log.prepend_read_back(read_back_ts, entries);
     }
 }
 
+type WriteLogEntry = Arc<(Timestamp, OrderedDocumentWrites, WriteSource)>;
+
 /// WriteLog holds recent commits that have been written to persistence and
 /// snapshot manager. These commits may cause OCC aborts for new commits, and
 /// they may trigger subscriptions.
+///
+/// Commits in `(purged_ts, spilled_ts]` have been spilled out of memory to
+/// stay under `WRITE_LOG_MEMORY_BUDGET_BYTES`: they're still within retention,
+/// but those in `(purged_ts, read_back_ts]` must be read back from
+/// persistence. The rest are in `read_back`, which is capped at
+/// `WRITE_LOG_READ_BACK_CACHE_SIZE_BYTES`.
 #[derive(Clone)]
 struct WriteLog {
-    by_ts: WithHeapSize<Vector<Arc<(Timestamp, OrderedDocumentWrites, WriteSource)>>>,
+    by_ts: WithHeapSize<Vector<WriteLogEntry>>,
+    read_back: WithHeapSize<Vector<WriteLogEntry>>,
+    read_back_ts: Timestamp,
     purged_ts: Timestamp,
+    spilled_ts: Timestamp,
     persistence_version: PersistenceVersion,
 }
 
@@ -282,7 +385,10 @@ impl WriteLog {
     fn new(initial_timestamp: Timestamp, persistence_version: PersistenceVersion) -> Self {
         Self {
             by_ts: WithHeapSize::default(),
+            read_back: WithHeapSize::default(),
+            read_back_ts: initial_timestamp,
             purged_ts: initial_timestamp,
+            spilled_ts: initial_timestamp,
             persistence_version,
         }
     }
@@ -290,7 +396,7 @@ impl WriteLog {
     fn max_ts(&self) -> Timestamp {
         match self.by_ts.back() {
             Some(entry) => entry.0,
-            None => self.purged_ts,
+            None => self.spilled_ts,
         }
     }
 
@@ -309,17 +415,65 @@ impl WriteLog {
             )
             .context(ErrorMetadata::out_of_retention())
         );
-        let start = match self.by_ts.binary_search_by_key(&from, |entry| entry.0) {
-            Ok(i) => i,
-            Err(i) => i,
-        };
-        let iter = self.by_ts.focus().narrow(start..).into_iter();
+        anyhow::ensure!(
+            from > self.read_back_ts,
+            anyhow::anyhow!(
+                "Timestamp {from} has been spilled out of the in-memory write log (minimum \
+                 timestamp {})",
+                self.read_back_ts
+            )
+            .context(ErrorMetadata::out_of_retention())
+        );
+        let iter =
+            Self::entries_from(&self.read_back, from).chain(Self::entries_from(&self.by_ts, from));
         Ok(iter
             .map(|entry| &**entry)
             .take_while(move |(t, ..)| *t <= to)
             .map(|(ts, writes, write_source)| (ts, writes.iter(), write_source)))
     }
 
+    fn entries_from(
+        entries: &Vector<WriteLogEntry>,
+        from: Timestamp,
+    ) -> impl Iterator<Item = &WriteLogEntry> {
+        let start = match entries.binary_search_by_key(&from, |entry| entry.0) {
+            Ok(i) => i,
+            Err(i) => i,
+        };
+        entries.focus().narrow(start..).into_iter()
+    }
+
+    /// Drops read-back entries that have aged out of retention.
+    fn purge_read_back(&mut self) {
+        while let Some(ts) = self.read_back.front().map(|entry| entry.0) {
+            if ts > self.purged_ts {
+                break;
+            }
+            self.read_back.pop_front();
+        }
+        self.read_back_ts = cmp::max(self.read_back_ts, self.purged_ts);
+    }
+
+    /// Drops the oldest read-back entries until they fit in `budget_bytes`.
+    fn trim_read_back(&mut self, budget_bytes: usize) {
+        while self.read_back.heap_size() > budget_bytes {
+            let Some(entry) = self.read_back.pop_front() else {
+                break;
+            };
+            self.read_back_ts = entry.0;
+        }
+        metrics::log_write_log_read_back_cache_size(self.read_back.heap_size());
+    }
+
+    /// Extends the read-back entries down to `read_back_ts` with `entries`,
+    /// which must be every commit in `(read_back_ts, self.read_back_ts]`.
+    fn prepend_read_back(&mut self, read_back_ts: Timestamp, entries: &[WriteLogEntry]) {
+        for entry in entries.iter().rev() {
+            self.read_back.push_front(entry.clone());
+        }
+        self.read_back_ts = read_back_ts;
+    }
+
     #[fastrace::trace]
     fn is_stale(
         &self,
@@ -445,6 +599,127 @@ impl LogReader {
         })
     }
 
+    /// Like [`Self::refresh_token`], but if part of the range has been
+    /// spilled out of memory and isn't in the read-back cache, reads it back
+    /// from `persistence` instead of treating the token as out of retention.
+    pub async fn refresh_token_with_read_back(
+        &self,
+        token: Token,
+        ts: Timestamp,
+        persistence: &RepeatablePersistence,
+    ) -> anyhow::Result<Option<Token>> {
+        if token.ts() == ts {
+            return Ok(Some(token));
+        }
+        let snapshot = self.snapshot_from(token.ts().succ()?, persistence).await?;
+        block_in_place(|| {
+            let max_ts = snapshot.max_ts();
+            anyhow::ensure!(
+                ts <= max_ts,
+                "Can't refresh token to newer timestamp {ts} than max ts {max_ts}"
+            );
+            snapshot.refresh_token(token, ts)
+        })
+    }
+
+    /// Checks `reads` against the commits after `reads_ts` that have been
+    /// spilled out of memory, reading them back from `persistence` if they
+    /// aren't cached. Returns the first conflict, if any, along with a pin at
+    /// the timestamp `reads` have been checked through, which keeps the
+    /// commits after it in memory until it's dropped.
+    pub async fn check_spilled(
+        &self,
+        reads: &ReadSet,
+        reads_ts: Timestamp,
+        persistence: &RepeatablePersistence,
+    ) -> anyhow::Result<(Option<ConflictingReadWithWriteSource>, SpillPin)> {
+        // Pin before taking the snapshot, so everything after the snapshot's
+        // `spilled_ts` is still in memory when the committer checks it.
+        let mut pin = SpillPin::new(self.inner.clone(), reads_ts);
+        if reads_ts >= self.inner.lock().log.spilled_ts {
+            return Ok((None, pin));
+        }
+        let snapshot = self.snapshot_from(reads_ts.succ()?, persistence).await?;
+        let conflict = snapshot.is_stale(reads, reads_ts, snapshot.spilled_ts)?;
+        pin.advance(snapshot.spilled_ts);
+        Ok((conflict, pin))
+    }
+
+    /// Returns a snapshot of the log that can be iterated from `from`, reading
+    /// back any commits after `from` that have been spilled out of memory and
+    /// aren't in the read-back cache. The read-back entries are cached if
+    /// they fit.
+    async fn snapshot_from(
+        &self,
+        from: Timestamp,
+        persistence: &RepeatablePersistence,
+    ) -> anyhow::Result<WriteLog> {
+        let mut snapshot = { self.inner.lock().log.clone() };
+        if from <= snapshot.purged_ts || from > snapshot.read_back_ts {
+            return Ok(snapshot);
+        }
+        let read_back_to = snapshot.read_back_ts;
+        anyhow::ensure!(
+            read_back_to <= *persistence.upper_bound(),
+            "Spilled write log entries up to {read_back_to} are newer than {}",
+            *persistence.upper_bound()
+        );
+        let timer = metrics::write_log_read_back_timer();
+        let revision_pairs: Vec<_> = stream_revision_pairs(
+            persistence.load_documents(TimestampRange::new(from..=read_back_to)?, Order::Asc),
+            persistence,
+        )
+        .try_collect()
+        .await?;
+        let mut spilled: Vec<(Timestamp, OrderedDocumentWrites)> = vec![];
+        for revision_pair in revision_pairs {
+            let Some(id) = revision_pair
+                .document()
+                .or(revision_pair.prev_document())
+                .map(|document| document.id())
+            else {
+                continue;
+            };
+            let update = PackedDocumentUpdate {
+                id,
+                old_document: revision_pair.prev_document().map(PackedDocument::pack),
+                new_document: revision_pair.document().map(PackedDocument::pack),
+            };
+            match spilled.last_mut() {
+                Some((last_ts, writes)) if *last_ts == revision_pair.ts() => {
+                    writes.push((id, update))
+                },
+                _ => spilled.push((revision_pair.ts(), vec![(id, update)].into())),
+            }
+        }
+        metrics::log_write_log_read_back(spilled.len());
+        drop(timer);
+
+        let entries: Vec<WriteLogEntry> = spilled
+            .into_iter()
+            .map(|(ts, writes)| Arc::new((ts, writes, WriteSource::unknown())))
+            .collect();
+        let read_back_ts = from.pred()?;
+        self.inner
+            .lock()
+            .cache_read_back(read_back_ts, read_back_to, &entries);
+        snapshot.prepend_read_back(read_back_ts, &entries);
+        Ok(snapshot)
+    }
+
+    pub fn max_ts(&self) -> Timestamp {
+        self.inner.lock().log.max_ts()
+    }
+
+    /// Spills every entry out of memory, keeping at most
+    /// `read_back_cache_bytes` of them in the read-back cache.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn spill_for_testing(&self, read_back_cache_bytes: usize) {
+        let mut manager = self.inner.lock();
+        manager.spill(Timestamp::MAX, 0);
+        manager.log.trim_read_back(read_back_cache_bytes);
+    }
+
     pub fn refresh_reads_until_max_ts(&self, token: Token) -> anyhow::Result<Option<Token>> {
         let snapshot = { self.inner.lock().log.clone() };
         block_in_place(|| {
@@ -454,12 +729,50 @@ impl LogReader {
     }
 }
 
+/// Keeps the write log from spilling any commits after `ts` while it's held,
+/// so a commit whose reads have been checked through `ts` can be checked
+/// against the rest of the log in memory.
+pub struct SpillPin {
+    inner: Arc<Mutex<WriteLogManager>>,
+    ts: Timestamp,
+}
+
+impl SpillPin {
+    fn new(inner: Arc<Mutex<WriteLogManager>>, ts: Timestamp) -> Self {
+        inner.lock().pin_spill(ts);
+        Self { inner, ts }
+    }
+
+    pub fn ts(&self) -> Timestamp {
+        self.ts
+    }
+
+    fn advance(&mut self, ts: Timestamp) {
+        let mut manager = self.inner.lock();
+        manager.pin_spill(ts);
+        manager.unpin_spill(self.ts);
+        self.ts = ts;
+    }
+}
+
+impl Drop for SpillPin {
+    fn drop(&mut self) {
+        self.inner.lock().unpin_spill(self.ts);
+    }
+}
+
 /// LogWriter can append to the log.
 pub struct LogWriter {
     inner: Arc<Mutex<WriteLogManager>>,
 }
 
 impl LogWriter {
+    pub fn reader(&self) -> LogReader {
+        LogReader {
+            inner: self.inner.clone(),
+        }
+    }
+
     // N.B.: `writes` is `OrderedWrites` because that's what the committer
     // already has, but the write log doesn't actually care about the ordering.
     pub fn append(
@@ -592,6 +905,8 @@ impl PendingWriteHandle {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use common::{
         assert_obj,
         document::{
@@ -606,7 +921,10 @@ mod tests {
             Interval,
             StartIncluded,
         },
-        knobs::WRITE_LOG_MAX_RETENTION_SECS,
+        knobs::{
+            WRITE_LOG_MAX_RETENTION_SECS,
+            WRITE_LOG_MIN_IN_MEMORY_SECS,
+        },
         testing::TestIdGenerator,
         types::{
             IndexDescriptor,
@@ -617,8 +935,12 @@ mod tests {
         value::FieldPath,
     };
     use convex_macro::test_runtime;
+    use errors::ErrorMetadataAnyhowExt;
     use runtime::testing::TestRuntime;
-    use value::val;
+    use value::{
+        heap_size::HeapSize,
+        val,
+    };
 
     use crate::{
         reads::{
@@ -710,6 +1032,106 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_spill() -> anyhow::Result<()> {
+        let mut id_generator = TestIdGenerator::new();
+        let mut log_manager =
+            WriteLogManager::new(Timestamp::must(1000), PersistenceVersion::default());
+        let id = id_generator.user_generate(&"t".parse()?);
+        let doc = ResolvedDocument::new(id, CreationTime::ONE, assert_obj!("k" => 5))?;
+        for ts in (1002..=1010).step_by(2) {
+            log_manager.append(
+                Timestamp::must(ts),
+                vec![(
+                    id,
+                    PackedDocumentUpdate::pack(&DocumentUpdate {
+                        id,
+                        old_document: None,
+                        new_document: Some(doc.clone()),
+                    }),
+                )]
+                .into(),
+                WriteSource::unknown(),
+            );
+        }
+        let entry_size = log_manager.log.by_ts.heap_size() / 5;
+
+        // Recent entries are never spilled, even over budget.
+        log_manager.spill(Timestamp::must(1010), 0);
+        assert_eq!(log_manager.log.spilled_ts, Timestamp::must(1000));
+
+        let current_ts = Timestamp::must(1010).add(*WRITE_LOG_MIN_IN_MEMORY_SECS)?;
+        log_manager.spill(current_ts, 2 * entry_size);
+        assert_eq!(log_manager.log.purged_ts, Timestamp::must(1000));
+        assert_eq!(log_manager.log.spilled_ts, Timestamp::must(1006));
+        assert_eq!(log_manager.log.max_ts(), Timestamp::must(1010));
+        assert_eq!(log_manager.log.by_ts.len(), 2);
+
+        // Spilled entries stay in the read-back cache while they fit.
+        assert_eq!(
+            log_manager
+                .log
+                .iter(Timestamp::must(1003), Timestamp::must(1010))?
+                .map(|(ts, ..)| *ts)
+                .collect::<Vec<_>>(),
+            (1004..=1010)
+                .step_by(2)
+                .map(Timestamp::must)
+                .collect::<Vec<_>>()
+        );
+        log_manager.log.trim_read_back(entry_size);
+        assert_eq!(log_manager.log.read_back_ts, Timestamp::must(1004));
+        assert!(log_manager
+            .log
+            .iter(Timestamp::must(1003), Timestamp::must(1010))
+            .err()
+            .unwrap()
+            .is_out_of_retention());
+        assert_eq!(
+            log_manager
+                .log
+                .iter(Timestamp::must(1005), Timestamp::must(1010))?
+                .map(|(ts, ..)| *ts)
+                .collect::<Vec<_>>(),
+            vec![
+                Timestamp::must(1006),
+                Timestamp::must(1008),
+                Timestamp::must(1010)
+            ]
+        );
+
+        // Entries read back from persistence extend the cache if they fit.
+        let read_back: Vec<_> = (1002..=1004)
+            .step_by(2)
+            .map(|ts| Arc::new((Timestamp::must(ts), vec![].into(), WriteSource::unknown())))
+            .collect();
+        log_manager.cache_read_back(Timestamp::must(1001), Timestamp::must(1003), &read_back);
+        assert_eq!(log_manager.log.read_back_ts, Timestamp::must(1004));
+        log_manager.cache_read_back(Timestamp::must(1001), Timestamp::must(1004), &read_back);
+        assert_eq!(log_manager.log.read_back_ts, Timestamp::must(1001));
+        assert_eq!(
+            log_manager
+                .log
+                .iter(Timestamp::must(1002), Timestamp::must(1010))?
+                .map(|(ts, ..)| *ts)
+                .collect::<Vec<_>>(),
+            (1002..=1010)
+                .step_by(2)
+                .map(Timestamp::must)
+                .collect::<Vec<_>>()
+        );
+
+        // Spilled entries are still purged once they're past max retention.
+        log_manager
+            .enforce_retention_policy(Timestamp::must(1005).add(*WRITE_LOG_MAX_RETENTION_SECS)?);
+        assert_eq!(log_manager.log.purged_ts, Timestamp::must(1004));
+        assert_eq!(log_manager.log.spilled_ts, Timestamp::must(1006));
+        assert_eq!(log_manager.log.read_back_ts, Timestamp::must(1004));
+        assert_eq!(log_manager.log.read_back.len(), 1);
+        assert_eq!(log_manager.log.by_ts.len(), 2);
+        Ok(())
+    }
+
     #[test_runtime]
     async fn test_is_stale(_rt: TestRuntime) -> anyhow::Result<()> {
         let mut id_generator = TestIdGenerator::new();
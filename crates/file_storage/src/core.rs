@@ -20,6 +20,7 @@ use common::{
     sha256::Sha256Digest,
     types::{
         ConvexOrigin,
+        ObjectKey,
         StorageUuid,
     },
 };
@@ -55,6 +56,16 @@ use model::{
         FileStorageId,
         FileStorageModel,
     },
+    scheduled_jobs::{
+        args::{
+            scheduled_job_args,
+            upload_scheduled_job_args,
+        },
+        types::{
+            ScheduledJob,
+            StoredScheduledJobArgs,
+        },
+    },
 };
 use storage::{
     Storage,
@@ -68,6 +79,7 @@ use usage_tracking::{
 };
 use value::{
     id_v6::DeveloperDocumentId,
+    ConvexArray,
     TableNamespace,
 };
 
@@ -443,6 +455,43 @@ impl<RT: Runtime> TransactionalFileStorage<RT> {
 
         Ok(virtual_id)
     }
+
+    /// Uploads the arguments kept in a committed scheduled job, so they can be
+    /// moved out of `_scheduled_jobs`.
+    pub async fn upload_scheduled_job_args(
+        &self,
+        job: &ScheduledJob,
+    ) -> anyhow::Result<StoredScheduledJobArgs> {
+        upload_scheduled_job_args(job, &self.storage).await
+    }
+
+    pub async fn scheduled_job_args(&self, job: &ScheduledJob) -> anyhow::Result<ConvexArray> {
+        scheduled_job_args(job, &self.storage).await
+    }
+
+    pub async fn delete_scheduled_job_args(
+        &self,
+        stored: &StoredScheduledJobArgs,
+    ) -> anyhow::Result<()> {
+        self.storage.delete_object(&stored.storage_key).await
+    }
+
+    /// Deletes a file's content from the underlying storage. Deleting a file's
+    /// `_file_storage` entry leaves its content in place, so callers that need
+    /// the content gone delete it with this once the entry's deletion has
+    /// committed. Deleting content that's already gone does nothing, so
+    /// callers can retry.
+    pub async fn delete_object(&self, storage_key: &ObjectKey) -> anyhow::Result<()> {
+        if self
+            .storage
+            .get_object_attributes(storage_key)
+            .await?
+            .is_none()
+        {
+            return Ok(());
+        }
+        self.storage.delete_object(storage_key).await
+    }
 }
 
 impl<RT: Runtime> FileStorage<RT> {
@@ -292,6 +292,9 @@ fn roundtrip_converted_value(
         (ConvexValue::Float64(v), FivetranDataType::Int) => FivetranValue::Int(v as i32),
         (ConvexValue::Int64(v), FivetranDataType::Long) => FivetranValue::Long(v),
         (ConvexValue::String(v), FivetranDataType::Decimal) => FivetranValue::Decimal(v.into()),
+        (ConvexValue::Decimal(v), FivetranDataType::Decimal) => {
+            FivetranValue::Decimal(v.to_string())
+        },
         (ConvexValue::Float64(v), FivetranDataType::Float) => FivetranValue::Float(v as f32),
         (ConvexValue::Float64(v), FivetranDataType::Double) => FivetranValue::Double(v),
         (ConvexValue::String(v), FivetranDataType::NaiveTime) => {
@@ -323,6 +326,13 @@ fn roundtrip_converted_value(
                 nanos: dt.timestamp_subsec_nanos() as i32,
             })
         },
+        (ConvexValue::DateTime(v), FivetranDataType::UtcDatetime) => {
+            let nanos = v.nanos_since_epoch();
+            FivetranValue::UtcDatetime(Timestamp {
+                seconds: nanos.div_euclid(1_000_000_000),
+                nanos: nanos.rem_euclid(1_000_000_000) as i32,
+            })
+        },
         (ConvexValue::Float64(v), FivetranDataType::UtcDatetime) => {
             FivetranValue::UtcDatetime(timestamp_from_ms(v))
         },
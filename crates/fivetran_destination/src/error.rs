@@ -347,6 +347,7 @@ mod tests {
             ]
             .try_into()
             .unwrap(),
+            variant: None,
         };
 
         assert_eq!(
@@ -364,13 +365,15 @@ mod tests {
                     index_descriptor: IndexDescriptor::new("by_name").unwrap(),
                     fields: vec![
                         "name".parse().unwrap()
-                    ].try_into().unwrap()
+                    ].try_into().unwrap(),
+                    variant: None,
                 },
                 IndexDescriptor::new("by_email").unwrap() => IndexSchema {
                     index_descriptor: IndexDescriptor::new("by_email").unwrap(),
                     fields: vec![
                         "email".parse().unwrap()
-                    ].try_into().unwrap()
+                    ].try_into().unwrap(),
+                    variant: None,
                 }
             },
             document_type: Some(DocumentSchema::Union(vec![object_validator!(
@@ -379,6 +382,9 @@ mod tests {
             )])),
             search_indexes: Default::default(),
             vector_indexes: Default::default(),
+            defaults: Default::default(),
+            mutability: Default::default(),
+            checks: vec![],
         };
 
         assert_eq!(
@@ -209,6 +209,9 @@ impl FivetranTableSchema {
             indexes,
             search_indexes: Default::default(),
             vector_indexes: Default::default(),
+            defaults: Default::default(),
+            mutability: Default::default(),
+            checks: vec![],
         })
     }
 
@@ -249,6 +252,7 @@ impl FivetranTableSchema {
         Ok(IndexSchema {
             index_descriptor: FIVETRAN_PRIMARY_KEY_INDEX_DESCRIPTOR.clone(),
             fields,
+            variant: None,
         })
     }
 
@@ -260,6 +264,7 @@ impl FivetranTableSchema {
             } else {
                 FIVETRAN_SYNC_INDEX_WITHOUT_SOFT_DELETE_FIELDS.clone()
             },
+            variant: None,
         }
     }
 
@@ -664,6 +669,9 @@ impl FivetranTableSchema {
             search_indexes: BTreeMap::new(),
             vector_indexes: BTreeMap::new(),
             document_type: Some(document_schema),
+            defaults: Default::default(),
+            mutability: Default::default(),
+            checks: vec![],
         })
     }
 }
@@ -936,6 +944,8 @@ fn recognize_fivetran_type(validator: &Validator) -> anyhow::Result<FivetranData
         Validator::Boolean => Ok(FivetranDataType::Boolean),
         Validator::String => Ok(FivetranDataType::String),
         Validator::Bytes => Ok(FivetranDataType::Binary),
+        Validator::Decimal => Ok(FivetranDataType::Decimal),
+        Validator::DateTime => Ok(FivetranDataType::UtcDatetime),
         Validator::Object(_) | Validator::Array(_) => Ok(FivetranDataType::Json),
 
         // Allow nullable types
@@ -1084,6 +1094,9 @@ mod tests {
                     .collect(),
             )])),
             indexes: convex_indexes(indexes),
+            defaults: Default::default(),
+            mutability: Default::default(),
+            checks: vec![],
         }
     }
 
@@ -1099,6 +1112,7 @@ mod tests {
                     IndexSchema {
                         index_descriptor,
                         fields: IndexedFields::try_from(index_fields).unwrap(),
+                        variant: None,
                     },
                 )
             })
@@ -1884,7 +1898,8 @@ mod tests {
                             "fivetran.deleted".parse()?,
                             "fivetran.synced".parse()?,
                             "_creationTime".parse()?,
-                        ].try_into()?
+                        ].try_into()?,
+                        variant: None,
                     },
                     FIVETRAN_PRIMARY_KEY_INDEX_DESCRIPTOR.clone() => IndexSchema {
                         index_descriptor: FIVETRAN_PRIMARY_KEY_INDEX_DESCRIPTOR.clone(),
@@ -1893,7 +1908,8 @@ mod tests {
                             "fivetran.id".parse()?,
                             "fivetran.columns.key".parse()?,
                             "slug".parse()?,
-                        ].try_into()?
+                        ].try_into()?,
+                        variant: None,
                     }
                 },
                 document_type: Some(DocumentSchema::Union(vec![object_validator!(
@@ -1921,6 +1937,9 @@ mod tests {
                 )])),
                 search_indexes: Default::default(),
                 vector_indexes: Default::default(),
+                defaults: Default::default(),
+                mutability: Default::default(),
+                checks: vec![],
             },
         );
         Ok(())
@@ -33,6 +33,10 @@ use common::{
         IntervalSet,
         StartIncluded,
     },
+    knobs::{
+        DOCUMENT_CACHE_MAX_RANGE_DOCUMENTS,
+        INDEX_RANGE_FETCH_CONCURRENCY,
+    },
     persistence::PersistenceSnapshot,
     query::{
         CursorPosition,
@@ -75,7 +79,10 @@ use value::{
 };
 
 use crate::{
-    index_registry::IndexRegistry,
+    index_registry::{
+        Index,
+        IndexRegistry,
+    },
     metrics::log_transaction_cache_query,
 };
 
@@ -94,6 +101,33 @@ pub trait InMemoryIndexes: Send + Sync {
     ) -> anyhow::Result<Option<Vec<(IndexKeyBytes, Timestamp, LazyDocument)>>>;
 }
 
+/// A cache of index ranges shared by every transaction, so repeated reads of
+/// hot documents don't go to persistence. Unlike the per-transaction cache,
+/// an entry may be served at a later timestamp than the one it was read at,
+/// so implementations must check that the range hasn't been written since.
+pub trait SharedIndexCache: Send + Sync {
+    /// Returns every document in `interval` of the index as of `ts`, in
+    /// ascending index key order, if the range is cached.
+    fn get(
+        &self,
+        index_id: IndexId,
+        interval: &Interval,
+        ts: RepeatableTimestamp,
+        table_name: &TableName,
+    ) -> Option<Arc<Vec<(IndexKeyBytes, Timestamp, PackedDocument)>>>;
+
+    /// Caches every document in `interval` of the index as of `ts`, in
+    /// ascending index key order.
+    fn populate(
+        &self,
+        index: &Index,
+        interval: Interval,
+        ts: RepeatableTimestamp,
+        table_name: &TableName,
+        documents: Vec<(IndexKeyBytes, Timestamp, PackedDocument)>,
+    );
+}
+
 /// [`BackendInMemoryIndexes`] maintains in-memory database indexes. With the
 /// exception of the table scan index, newly created indexes are not initially
 /// loaded in memory. A post-commit, asynchronous backfill job is responsible
@@ -402,6 +436,8 @@ pub struct DatabaseIndexSnapshot {
     // Cache results reads from the snapshot. The snapshot is immutable and thus
     // we don't have to do any invalidation.
     cache: DatabaseIndexSnapshotCache,
+
+    shared_cache: Option<Arc<dyn SharedIndexCache>>,
 }
 
 impl DatabaseIndexSnapshot {
@@ -417,9 +453,17 @@ impl DatabaseIndexSnapshot {
             table_mapping: ReadOnly::new(table_mapping),
             persistence: persistence_snapshot,
             cache: DatabaseIndexSnapshotCache::new(),
+            shared_cache: None,
         }
     }
 
+    /// Serve ranges from, and add complete ranges fetched from persistence to,
+    /// `shared_cache`.
+    pub fn with_shared_cache(mut self, shared_cache: Arc<dyn SharedIndexCache>) -> Self {
+        self.shared_cache = Some(shared_cache);
+        self
+    }
+
     async fn start_range_fetch<'a>(
         &self,
         range_request: &'a RangeRequest,
@@ -493,6 +537,48 @@ impl DatabaseIndexSnapshot {
             return Ok(Ok((range, CursorPosition::End)));
         }
 
+        // Then the cache shared with other transactions.
+        let shared_documents = self.shared_cache.as_ref().and_then(|shared_cache| {
+            shared_cache.get(
+                index.id(),
+                &range_request.interval,
+                self.persistence.timestamp(),
+                range_request.printable_index_name.table(),
+            )
+        });
+        if let Some(documents) = shared_documents {
+            Self::log_start_range_fetch(
+                range_request.printable_index_name.table(),
+                1,
+                0,
+                range_request.max_size,
+            );
+            let to_range_entry =
+                |(key, ts, document): &(IndexKeyBytes, Timestamp, PackedDocument)| {
+                    (key.clone(), *ts, LazyDocument::from(document.clone()))
+                };
+            let range: Vec<_> = match range_request.order {
+                Order::Asc => documents
+                    .iter()
+                    .take(range_request.max_size)
+                    .map(to_range_entry)
+                    .collect(),
+                Order::Desc => documents
+                    .iter()
+                    .rev()
+                    .take(range_request.max_size)
+                    .map(to_range_entry)
+                    .collect(),
+            };
+            let cursor = match range.last() {
+                Some((last_key, ..)) if documents.len() > range_request.max_size => {
+                    CursorPosition::After(last_key.clone())
+                },
+                _ => CursorPosition::End,
+            };
+            return Ok(Ok((range, cursor)));
+        }
+
         // Next, try the transaction cache.
         let cache_results =
             self.cache
@@ -597,7 +683,7 @@ impl DatabaseIndexSnapshot {
                 }
             },
         ))
-        .buffer_unordered(20)
+        .buffer_unordered(*INDEX_RANGE_FETCH_CONCURRENCY)
         .collect();
         let fetch_results: Vec<_> = assert_send(f).await;
 
@@ -620,6 +706,9 @@ impl DatabaseIndexSnapshot {
                 // being populated.
                 self.cache
                     .record_interval_populated(index_id, interval_read);
+                if cursor == CursorPosition::End {
+                    self.populate_shared_cache(index_id, range_request, &fetch_result_vec);
+                }
                 (fetch_result_vec, cursor)
             };
             results.insert(batch_key, result);
@@ -628,6 +717,39 @@ impl DatabaseIndexSnapshot {
         results
     }
 
+    /// Adds a range that was read in full to the shared cache, if it's small
+    /// enough.
+    fn populate_shared_cache(
+        &self,
+        index_id: IndexId,
+        range_request: &RangeRequest,
+        results: &[(IndexKeyBytes, Timestamp, LazyDocument)],
+    ) {
+        let Some(shared_cache) = &self.shared_cache else {
+            return;
+        };
+        if results.len() > *DOCUMENT_CACHE_MAX_RANGE_DOCUMENTS {
+            return;
+        }
+        let Some(index) = self.index_registry.enabled_index_by_index_id(&index_id) else {
+            return;
+        };
+        let mut documents: Vec<_> = results
+            .iter()
+            .map(|(key, ts, document)| (key.clone(), *ts, document.pack()))
+            .collect();
+        if range_request.order == Order::Desc {
+            documents.reverse();
+        }
+        shared_cache.populate(
+            index,
+            range_request.interval.clone(),
+            self.persistence.timestamp(),
+            range_request.printable_index_name.table(),
+            documents,
+        );
+    }
+
     async fn fetch_cache_misses(
         persistence: PersistenceSnapshot,
         index_id: IndexId,
@@ -690,6 +812,10 @@ impl DatabaseIndexSnapshot {
     pub fn timestamp(&self) -> RepeatableTimestamp {
         self.persistence.timestamp()
     }
+
+    pub fn persistence(&self) -> &PersistenceSnapshot {
+        &self.persistence
+    }
 }
 
 const MAX_TRANSACTION_CACHE_SIZE: usize = 10 * (1 << 20); // 10 MiB
@@ -1120,4 +1246,11 @@ impl LazyDocument {
             LazyDocument::Packed(doc) => doc.unpack(),
         }
     }
+
+    pub fn pack(&self) -> PackedDocument {
+        match self {
+            LazyDocument::Resolved(doc) => PackedDocument::pack(doc),
+            LazyDocument::Packed(doc) => doc.clone(),
+        }
+    }
 }
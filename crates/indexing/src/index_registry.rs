@@ -61,13 +61,16 @@ use value::{
     },
     ConvexString,
     ConvexValue,
+    FieldName,
     FieldPath,
     InternalId,
     ResolvedDocumentId,
+    Size,
     TableMapping,
     TableNamespace,
     TableNumber,
     TabletId,
+    MAX_INLINE_FIELD_SIZE,
 };
 
 /// [`IndexRegistry`] maintains the metadata for indexes, indicating
@@ -260,6 +263,7 @@ impl IndexRegistry {
                             DeveloperTextIndexConfig {
                                 search_field,
                                 filter_fields,
+                                ..
                             },
                         ..
                     } => {
@@ -389,6 +393,37 @@ impl IndexRegistry {
         Ok(())
     }
 
+    /// Large top-level fields are chunked out of line by persistence backends
+    /// that support it, so they can't be part of an index key there.
+    pub fn verify_indexed_fields_inline(&self, document: &ResolvedDocument) -> anyhow::Result<()> {
+        for index in self.indexes_by_table(document.id().tablet_id) {
+            let IndexConfig::Database {
+                developer_config: DeveloperDatabaseIndexConfig { fields },
+                ..
+            } = &index.metadata.config
+            else {
+                continue;
+            };
+            for field_path in fields.iter() {
+                let field = FieldName::from(field_path.fields()[0].clone());
+                if let Some(value) = document.value().get(&field)
+                    && value.size() > MAX_INLINE_FIELD_SIZE
+                {
+                    anyhow::bail!(ErrorMetadata::bad_request(
+                        "IndexedFieldTooLarge",
+                        format!(
+                            "Field \"{field}\" is indexed by {} but is {} bytes. Indexed fields \
+                             must be at most {MAX_INLINE_FIELD_SIZE} bytes.",
+                            index.name().descriptor(),
+                            value.size(),
+                        )
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn verify_index_state(
         enabled_index: Option<&Index>,
         pending_index: Option<&Index>,
@@ -845,7 +880,9 @@ pub enum DocumentIndexKeyValue {
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SearchIndexKeyValue {
     /// These are values for the fields present in the must
-    /// clauses of the search index.
+    /// clauses of the search index. Values short enough to be stored
+    /// unhashed keep their sort order, so numbers can also match range
+    /// filters.
     pub filter_values: WithHeapSize<BTreeMap<FieldPath, SearchFilterValue>>,
     pub search_field: FieldPath,
     pub search_field_value: Option<ConvexString>,
@@ -916,10 +953,10 @@ mod tests {
             IndexMetadata::new_enabled(by_name.clone(), vec!["name".parse()?].try_into()?),
             IndexMetadata::new_text_index(
                 by_content.clone(),
-                DeveloperTextIndexConfig {
-                    search_field: FieldPath::from_str("content")?,
-                    filter_fields: vec![FieldPath::from_str("author")?].into_iter().collect(),
-                },
+                DeveloperTextIndexConfig::new(
+                    FieldPath::from_str("content")?,
+                    vec![FieldPath::from_str("author")?].into_iter().collect(),
+                ),
                 TextIndexState::SnapshottedAt(TextIndexSnapshot {
                     data: TextIndexSnapshotData::MultiSegment(vec![]),
                     ts: Timestamp::MIN,
@@ -6,6 +6,7 @@ use std::{
         VecDeque,
     },
     env,
+    num::NonZeroU32,
     sync::{
         Arc,
         Once,
@@ -78,6 +79,7 @@ use common::{
 };
 use database::{
     shutdown_error,
+    PublicHybridSearchQueryResult,
     Transaction,
 };
 use deno_core::{
@@ -119,6 +121,7 @@ use model::{
         ModuleSource,
         SourceMap,
     },
+    queues::types::LeasedQueueMessage,
     udf_config::types::UdfConfig,
 };
 use parking_lot::Mutex;
@@ -289,6 +292,7 @@ pub trait ActionCallbacks: Send + Sync {
         udf_args: Vec<JsonValue>,
         scheduled_ts: UnixTimestamp,
         context: ExecutionContext,
+        idempotency_key: Option<String>,
     ) -> anyhow::Result<DeveloperDocumentId>;
 
     async fn cancel_job(
@@ -297,6 +301,49 @@ pub trait ActionCallbacks: Send + Sync {
         virtual_id: DeveloperDocumentId,
     ) -> anyhow::Result<()>;
 
+    // Queues
+    async fn queue_lease(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        queue: String,
+        max_messages: usize,
+        visibility_timeout: Duration,
+    ) -> anyhow::Result<Vec<LeasedQueueMessage>>;
+
+    async fn queue_ack(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        receipt: String,
+    ) -> anyhow::Result<()>;
+
+    async fn queue_nack(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        receipt: String,
+        delay: Duration,
+    ) -> anyhow::Result<()>;
+
+    // Webhook replay protection. Records a verified webhook's nonce on
+    // `scope` until `expires_at`, returning false if it was already recorded.
+    async fn record_webhook_nonce(
+        &self,
+        scope: String,
+        nonce: String,
+        expires_at: UnixTimestamp,
+    ) -> anyhow::Result<bool>;
+
+    // HTTP action rate limits. Counts a request against `key`, returning how
+    // long to wait before retrying if it's over the limit.
+    async fn check_http_rate_limit(
+        &self,
+        key: String,
+        requests: NonZeroU32,
+        period: Duration,
+    ) -> anyhow::Result<Option<Duration>>;
+
     // Vector Search
     async fn vector_search(
         &self,
@@ -304,6 +351,13 @@ pub trait ActionCallbacks: Send + Sync {
         query: JsonValue,
     ) -> anyhow::Result<(Vec<PublicVectorSearchQueryResult>, FunctionUsageStats)>;
 
+    // Hybrid Search
+    async fn hybrid_search(
+        &self,
+        identity: Identity,
+        query: JsonValue,
+    ) -> anyhow::Result<(Vec<PublicHybridSearchQueryResult>, FunctionUsageStats)>;
+
     // Components
     async fn lookup_function_handle(
         &self,
@@ -1,5 +1,11 @@
 #![allow(non_snake_case)]
 
+use std::{
+    collections::BTreeMap,
+    num::NonZeroU32,
+    time::Duration,
+};
+
 use anyhow::Context;
 use common::{
     bootstrap_model::components::handles::FunctionHandle,
@@ -7,15 +13,27 @@ use common::{
         ComponentId,
         Reference,
     },
+    knobs::{
+        INBOUND_WEBHOOK_NONCE_TTL,
+        INBOUND_WEBHOOK_TOLERANCE,
+    },
     runtime::{
         Runtime,
         UnixTimestamp,
     },
 };
+use database::{
+    HybridSearchJson,
+    HybridSearchRequest,
+};
 use errors::{
     ErrorMetadata,
     ErrorMetadataAnyhowExt,
 };
+use keybroker::{
+    verify_webhook,
+    WebhookScheme,
+};
 use model::{
     components::auth::propagate_component_auth,
     file_storage::FileStorageId,
@@ -39,10 +57,18 @@ use vector::{
 
 use super::task_executor::TaskExecutor;
 use crate::{
-    environment::helpers::{
-        remove_rejected_before_execution,
-        with_argument_error,
-        ArgName,
+    environment::{
+        helpers::{
+            remove_rejected_before_execution,
+            with_argument_error,
+            ArgName,
+        },
+        queues::{
+            leased_messages_to_json,
+            AckArgs,
+            LeaseArgs,
+            NackArgs,
+        },
     },
     helpers::UdfArgsJson,
     metrics::async_syscall_timer,
@@ -66,7 +92,11 @@ impl<RT: Runtime> TaskExecutor<RT> {
                 "1.0/actions/action" => self.async_syscall_actions_runAction(args).await?.into(),
                 "1.0/actions/schedule" => self.async_syscall_schedule(args).await?.into(),
                 "1.0/actions/cancel_job" => self.async_syscall_cancel_job(args).await?.into(),
+                "1.0/actions/queue/lease" => self.async_syscall_queue_lease(args).await?.into(),
+                "1.0/actions/queue/ack" => self.async_syscall_queue_ack(args).await?.into(),
+                "1.0/actions/queue/nack" => self.async_syscall_queue_nack(args).await?.into(),
                 "1.0/actions/vectorSearch" => self.async_syscall_vectorSearch(args).await?.into(),
+                "1.0/actions/hybridSearch" => self.async_syscall_hybridSearch(args).await?.into(),
                 "1.0/getUserIdentity" => self.async_syscall_getUserIdentity(args).await?.into(),
                 "1.0/storageDelete" => self.async_syscall_storageDelete(args).await?.into(),
                 "1.0/storageGetMetadata" => {
@@ -80,6 +110,8 @@ impl<RT: Runtime> TaskExecutor<RT> {
                 "1.0/createFunctionHandle" => {
                     self.async_syscall_createFunctionHandle(args).await?.into()
                 },
+                "1.0/actions/verifyWebhook" => self.async_syscall_verifyWebhook(args).await?.into(),
+                "1.0/actions/rateLimit" => self.async_syscall_rateLimit(args).await?.into(),
                 _ => {
                     anyhow::bail!(ErrorMetadata::bad_request(
                         "UnknownAsyncOperation",
@@ -269,6 +301,7 @@ impl<RT: Runtime> TaskExecutor<RT> {
             function_handle: Option<String>,
             ts: f64,
             args: UdfArgsJson,
+            idempotency_key: Option<String>,
         }
 
         let ScheduleArgs {
@@ -277,6 +310,7 @@ impl<RT: Runtime> TaskExecutor<RT> {
             function_handle,
             ts,
             args,
+            idempotency_key,
         }: ScheduleArgs = with_argument_error("scheduler", || Ok(serde_json::from_value(args)?))?;
         let path = match function_handle {
             Some(h) => {
@@ -300,6 +334,7 @@ impl<RT: Runtime> TaskExecutor<RT> {
                 args.into_arg_vec(),
                 scheduled_ts,
                 self.context.clone(),
+                idempotency_key,
             )
             .await?;
 
@@ -325,6 +360,161 @@ impl<RT: Runtime> TaskExecutor<RT> {
         Ok(JsonValue::Null)
     }
 
+    /// Verifies the signature on a webhook sent to an HTTP action route, and
+    /// with replay protection on, records its nonce so the same request is
+    /// only accepted once. Returns `{ status: "verified" | "invalid" |
+    /// "replayed" }`, with a `reason` for invalid requests.
+    #[convex_macro::instrument_future]
+    async fn async_syscall_verifyWebhook(&self, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct VerifyWebhookArgs {
+            scheme: String,
+            secret: String,
+            route: String,
+            headers: Vec<(String, String)>,
+            body: String,
+            tolerance_seconds: Option<f64>,
+            replay_protection: bool,
+        }
+        let (args, scheme, body, tolerance) = with_argument_error("verifyWebhook", || {
+            let args: VerifyWebhookArgs = serde_json::from_value(args)?;
+            let scheme: WebhookScheme = args.scheme.parse().context(ArgName("scheme"))?;
+            let body = base64::decode(&args.body).context(ArgName("body"))?;
+            let tolerance = match args.tolerance_seconds {
+                Some(secs) => {
+                    Duration::try_from_secs_f64(secs).context(ArgName("toleranceSeconds"))?
+                },
+                None => *INBOUND_WEBHOOK_TOLERANCE,
+            };
+            Ok((args, scheme, body, tolerance))
+        })?;
+        let headers: BTreeMap<_, _> = args
+            .headers
+            .into_iter()
+            .map(|(name, value)| (name.to_ascii_lowercase(), value))
+            .collect();
+        let now = self.rt.unix_timestamp();
+        let verified = match verify_webhook(
+            scheme,
+            &args.secret,
+            &headers,
+            &body,
+            now.as_secs(),
+            tolerance,
+        ) {
+            Ok(verified) => verified,
+            Err(e) => return Ok(json!({ "status": "invalid", "reason": e.to_string() })),
+        };
+        if args.replay_protection {
+            // Timestamped requests are rejected once they're out of
+            // tolerance, so their nonces only need to outlive that.
+            let expires_at = match verified.timestamp_secs {
+                Some(timestamp_secs) => {
+                    UnixTimestamp::from_secs_f64(timestamp_secs as f64)? + tolerance
+                },
+                None => now + *INBOUND_WEBHOOK_NONCE_TTL,
+            };
+            let component = self
+                .component_id()
+                .serialize_to_string()
+                .unwrap_or_else(|| "root".to_string());
+            let accepted = self
+                .action_callbacks
+                .record_webhook_nonce(
+                    format!("{component}:{}", args.route),
+                    verified.nonce,
+                    expires_at,
+                )
+                .await?;
+            if !accepted {
+                return Ok(json!({ "status": "replayed" }));
+            }
+        }
+        Ok(json!({ "status": "verified" }))
+    }
+
+    /// Counts a request to an HTTP action route against its `rateLimit`
+    /// middleware's limit. Returns `{ ok: true }`, or `{ ok: false,
+    /// retryAfterMs }` if the request is over the limit.
+    #[convex_macro::instrument_future]
+    async fn async_syscall_rateLimit(&self, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct RateLimitArgs {
+            route: String,
+            key: String,
+            requests: u32,
+            period_seconds: f64,
+        }
+        let (args, requests, period) = with_argument_error("rateLimit", || {
+            let args: RateLimitArgs = serde_json::from_value(args)?;
+            let requests = NonZeroU32::new(args.requests)
+                .context("requests must be positive")
+                .context(ArgName("requests"))?;
+            let period = Duration::try_from_secs_f64(args.period_seconds)
+                .context(ArgName("periodSeconds"))?;
+            anyhow::ensure!(!period.is_zero(), "periodSeconds must be positive");
+            Ok((args, requests, period))
+        })?;
+        let component = self
+            .component_id()
+            .serialize_to_string()
+            .unwrap_or_else(|| "root".to_string());
+        let retry_after = self
+            .action_callbacks
+            .check_http_rate_limit(
+                format!("{component}:{}:{}", args.route, args.key),
+                requests,
+                period,
+            )
+            .await?;
+        Ok(match retry_after {
+            None => json!({ "ok": true }),
+            Some(retry_after) => {
+                json!({ "ok": false, "retryAfterMs": retry_after.as_millis() as u64 })
+            },
+        })
+    }
+
+    #[convex_macro::instrument_future]
+    async fn async_syscall_queue_lease(&self, args: JsonValue) -> anyhow::Result<JsonValue> {
+        let LeaseArgs {
+            queue,
+            max_messages,
+            visibility_timeout,
+        } = LeaseArgs::parse(args)?;
+        let messages = self
+            .action_callbacks
+            .queue_lease(
+                self.identity.clone(),
+                self.component_id(),
+                queue,
+                max_messages,
+                visibility_timeout,
+            )
+            .await?;
+        Ok(leased_messages_to_json(messages))
+    }
+
+    #[convex_macro::instrument_future]
+    async fn async_syscall_queue_ack(&self, args: JsonValue) -> anyhow::Result<JsonValue> {
+        let AckArgs { receipt } = AckArgs::parse(args)?;
+        self.action_callbacks
+            .queue_ack(self.identity.clone(), self.component_id(), receipt)
+            .await?;
+        Ok(JsonValue::Null)
+    }
+
+    #[convex_macro::instrument_future]
+    async fn async_syscall_queue_nack(&self, args: JsonValue) -> anyhow::Result<JsonValue> {
+        let NackArgs { receipt, delay } = NackArgs::parse(args)?;
+        self.action_callbacks
+            .queue_nack(self.identity.clone(), self.component_id(), receipt, delay)
+            .await?;
+        Ok(JsonValue::Null)
+    }
+
     #[convex_macro::instrument_future]
     async fn async_syscall_vectorSearch(&self, args: JsonValue) -> anyhow::Result<JsonValue> {
         let VectorSearchRequest { query } = serde_json::from_value(args)?;
@@ -344,6 +534,25 @@ impl<RT: Runtime> TaskExecutor<RT> {
         Ok(json!({ "results": results }))
     }
 
+    #[convex_macro::instrument_future]
+    async fn async_syscall_hybridSearch(&self, args: JsonValue) -> anyhow::Result<JsonValue> {
+        let HybridSearchRequest { query } = serde_json::from_value(args)?;
+        let component_id = self.component_id();
+        let mut hybrid_search_query: HybridSearchJson = serde_json::from_value(query)?;
+        hybrid_search_query.insert_component_id(component_id);
+
+        let (results, usage_stats) = self
+            .action_callbacks
+            .hybrid_search(
+                self.identity.clone(),
+                serde_json::to_value(hybrid_search_query)?,
+            )
+            .await?;
+        self.usage_tracker.add(usage_stats);
+        let results: Vec<_> = results.into_iter().map(JsonValue::from).collect();
+        Ok(json!({ "results": results }))
+    }
+
     #[convex_macro::instrument_future]
     async fn async_syscall_getUserIdentity(&self, _args: JsonValue) -> anyhow::Result<JsonValue> {
         self.user_identity()
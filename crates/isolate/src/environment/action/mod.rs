@@ -33,6 +33,7 @@ use common::{
     log_lines::{
         LogLevel,
         LogLine,
+        LogLineStructured,
         SystemLogMetadata,
     },
     runtime::{
@@ -103,6 +104,7 @@ use udf::{
 use value::{
     heap_size::HeapSize,
     ConvexArray,
+    ConvexObject,
     JsonPackedValue,
     NamespacedTableMapping,
     Size,
@@ -756,14 +758,8 @@ impl<RT: Runtime> ActionEnvironment<RT> {
         }
 
         // First, load the user's module and find the specified function.
-        let module_path = path.udf_path.module();
-        let Ok(module_specifier) = module_specifier_from_path(module_path) else {
-            let message = format!("Invalid module path: {module_path:?}");
-            return Ok(Err(JsError::from_message(message)));
-        };
-
         let module = match scope
-            .eval_user_module(UdfType::Action, false, &module_specifier)
+            .eval_udf_module(UdfType::Action, &path.udf_path)
             .await?
         {
             Ok(id) => id,
@@ -1319,22 +1315,15 @@ impl<RT: Runtime> ActionEnvironment<RT> {
         ))?;
         Ok(())
     }
-}
 
-impl<RT: Runtime> IsolateEnvironment<RT> for ActionEnvironment<RT> {
-    fn trace(&mut self, level: LogLevel, messages: Vec<String>) -> anyhow::Result<()> {
+    fn send_developer_log_line(&mut self, log_line: LogLine) {
         // - 1 to reserve for the [ERROR] log line
-
         match self.total_log_lines.cmp(&(MAX_LOG_LINES - 1)) {
             // We are explicitly dropping errors in actions in case the log line sender goes away.
             // We should throw errors again once we correctly handle clients going away in HTTP
             // actions.
             Ordering::Less => {
-                let _ = self.log_line_sender.send(LogLine::new_developer_log_line(
-                    level,
-                    messages,
-                    self.rt.unix_timestamp(),
-                ));
+                let _ = self.log_line_sender.send(log_line);
                 self.total_log_lines += 1;
             },
             Ordering::Equal => {
@@ -1350,6 +1339,29 @@ impl<RT: Runtime> IsolateEnvironment<RT> for ActionEnvironment<RT> {
             },
             Ordering::Greater => (),
         };
+    }
+}
+
+impl<RT: Runtime> IsolateEnvironment<RT> for ActionEnvironment<RT> {
+    fn trace(&mut self, level: LogLevel, messages: Vec<String>) -> anyhow::Result<()> {
+        let log_line = LogLine::new_developer_log_line(level, messages, self.rt.unix_timestamp());
+        self.send_developer_log_line(log_line);
+        Ok(())
+    }
+
+    fn trace_structured(
+        &mut self,
+        level: LogLevel,
+        message: String,
+        fields: ConvexObject,
+    ) -> anyhow::Result<()> {
+        let log_line = LogLine::Structured(LogLineStructured::new_structured_log_line(
+            level,
+            message,
+            fields,
+            self.rt.unix_timestamp(),
+        ));
+        self.send_developer_log_line(log_line);
         Ok(())
     }
 
@@ -19,6 +19,7 @@ pub mod auth_config;
 pub mod component_definitions;
 pub mod crypto_rng;
 pub mod helpers;
+pub mod queues;
 pub mod schema;
 pub mod udf;
 pub mod warnings;
@@ -34,7 +35,10 @@ use common::{
 use deno_core::v8;
 use rand_chacha::ChaCha12Rng;
 use serde_json::Value as JsonValue;
-use value::NamespacedTableMapping;
+use value::{
+    ConvexObject,
+    NamespacedTableMapping,
+};
 
 pub use self::async_op::AsyncOpRequest;
 use crate::{
@@ -78,6 +82,16 @@ pub trait IsolateEnvironment<RT: Runtime>: 'static {
     ) -> anyhow::Result<()>;
 
     fn trace(&mut self, level: LogLevel, messages: Vec<String>) -> anyhow::Result<()>;
+    /// Logs a line from `ctx.log`. Environments that don't keep log lines
+    /// treat it like `trace`.
+    fn trace_structured(
+        &mut self,
+        level: LogLevel,
+        message: String,
+        fields: ConvexObject,
+    ) -> anyhow::Result<()> {
+        self.trace(level, vec![message, fields.to_string()])
+    }
     fn rng(&mut self) -> anyhow::Result<&mut ChaCha12Rng>;
     fn crypto_rng(&mut self) -> anyhow::Result<CryptoRng>;
     fn unix_timestamp(&mut self) -> anyhow::Result<UnixTimestamp>;
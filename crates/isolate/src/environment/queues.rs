@@ -0,0 +1,142 @@
+//! Argument parsing for the work queue syscalls, shared between mutations and
+//! actions.
+
+use std::time::Duration;
+
+use anyhow::Context;
+use model::queues::{
+    types::LeasedQueueMessage,
+    validate_visibility_timeout,
+    DEFAULT_MAX_ATTEMPTS,
+    DEFAULT_VISIBILITY_TIMEOUT,
+};
+use serde::Deserialize;
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+use value::ConvexValue;
+
+use super::helpers::{
+    with_argument_error,
+    ArgName,
+};
+
+pub struct EnqueueArgs {
+    pub queue: String,
+    pub payload: ConvexValue,
+    pub delay: Duration,
+    pub max_attempts: u32,
+}
+
+impl EnqueueArgs {
+    pub fn parse(args: JsonValue) -> anyhow::Result<Self> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct EnqueueArgsJson {
+            queue: String,
+            payload: JsonValue,
+            delay_ms: Option<f64>,
+            max_attempts: Option<u32>,
+        }
+        with_argument_error("queue.enqueue", || {
+            let args: EnqueueArgsJson = serde_json::from_value(args)?;
+            Ok(Self {
+                queue: args.queue,
+                payload: ConvexValue::try_from(args.payload).context(ArgName("payload"))?,
+                delay: duration_from_ms(args.delay_ms, ArgName("delayMs"))?.unwrap_or_default(),
+                max_attempts: args.max_attempts.unwrap_or(DEFAULT_MAX_ATTEMPTS),
+            })
+        })
+    }
+}
+
+pub struct LeaseArgs {
+    pub queue: String,
+    pub max_messages: usize,
+    pub visibility_timeout: Duration,
+}
+
+impl LeaseArgs {
+    pub fn parse(args: JsonValue) -> anyhow::Result<Self> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct LeaseArgsJson {
+            queue: String,
+            max_messages: Option<usize>,
+            visibility_timeout_ms: Option<f64>,
+        }
+        let args = with_argument_error("queue.lease", || {
+            let args: LeaseArgsJson = serde_json::from_value(args)?;
+            Ok(Self {
+                queue: args.queue,
+                max_messages: args.max_messages.unwrap_or(1),
+                visibility_timeout: duration_from_ms(
+                    args.visibility_timeout_ms,
+                    ArgName("visibilityTimeoutMs"),
+                )?
+                .unwrap_or(DEFAULT_VISIBILITY_TIMEOUT),
+            })
+        })?;
+        validate_visibility_timeout(args.visibility_timeout)?;
+        Ok(args)
+    }
+}
+
+pub fn leased_messages_to_json(messages: Vec<LeasedQueueMessage>) -> JsonValue {
+    messages
+        .into_iter()
+        .map(|message| {
+            json!({
+                "receipt": message.receipt,
+                "payload": message.payload.to_internal_json(),
+                "attempts": message.attempts,
+            })
+        })
+        .collect()
+}
+
+pub struct AckArgs {
+    pub receipt: String,
+}
+
+impl AckArgs {
+    pub fn parse(args: JsonValue) -> anyhow::Result<Self> {
+        #[derive(Deserialize)]
+        struct AckArgsJson {
+            receipt: String,
+        }
+        with_argument_error("queue.ack", || {
+            let AckArgsJson { receipt } = serde_json::from_value(args)?;
+            Ok(Self { receipt })
+        })
+    }
+}
+
+pub struct NackArgs {
+    pub receipt: String,
+    pub delay: Duration,
+}
+
+impl NackArgs {
+    pub fn parse(args: JsonValue) -> anyhow::Result<Self> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct NackArgsJson {
+            receipt: String,
+            delay_ms: Option<f64>,
+        }
+        with_argument_error("queue.nack", || {
+            let args: NackArgsJson = serde_json::from_value(args)?;
+            Ok(Self {
+                receipt: args.receipt,
+                delay: duration_from_ms(args.delay_ms, ArgName("delayMs"))?.unwrap_or_default(),
+            })
+        })
+    }
+}
+
+fn duration_from_ms(ms: Option<f64>, name: ArgName) -> anyhow::Result<Option<Duration>> {
+    ms.map(|ms| Duration::try_from_secs_f64(ms / 1000.0).context(name))
+        .transpose()
+}
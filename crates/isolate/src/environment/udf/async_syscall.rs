@@ -35,6 +35,7 @@ use common::{
     types::{
         AllowedVisibility,
         PersistenceVersion,
+        Timestamp,
         UdfType,
     },
     value::ConvexValue,
@@ -42,14 +43,20 @@ use common::{
 };
 use database::{
     query::{
-        query_batch_next,
+        group_by_batch,
+        query_batch_next_with_prefetches,
+        sample_documents,
         PaginationOptions,
         TableFilter,
     },
     soft_data_limit,
     table_summary::table_summary_bootstrapping_error,
+    Aggregate,
     BootstrapComponentsModel,
+    DeepPatch,
     DeveloperQuery,
+    GroupByQuery,
+    IndexStatisticsModel,
     PatchValue,
     Transaction,
     UserFacingModel,
@@ -66,14 +73,22 @@ use model::{
         handles::FunctionHandlesModel,
         ComponentsModel,
     },
+    feature_flags::FeatureFlagsModel,
     file_storage::{
         types::FileStorageEntry,
         BatchKey,
         FileStorageId,
     },
+    queues::QueueModel,
     scheduled_jobs::VirtualSchedulerModel,
     virtual_system_mapping,
 };
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use search::{
+    SearchFacets,
+    SearchHighlights,
+};
 use serde::{
     Deserialize,
     Serialize,
@@ -95,6 +110,7 @@ use value::{
     id_v6::DeveloperDocumentId,
     ConvexArray,
     ConvexObject,
+    FieldPath,
     TableName,
 };
 
@@ -109,6 +125,13 @@ use crate::{
             with_argument_error,
             ArgName,
         },
+        queues::{
+            leased_messages_to_json,
+            AckArgs,
+            EnqueueArgs,
+            LeaseArgs,
+            NackArgs,
+        },
     },
     helpers::UdfArgsJson,
     isolate2::client::QueryId,
@@ -148,6 +171,9 @@ pub fn system_table_guard(name: &TableName, expect_system_table: bool) -> anyhow
     Ok(())
 }
 
+/// Most documents a single `db.prefetch()` call can warm the cache with.
+const MAX_PREFETCH_IDS: usize = 1024;
+
 /// A batch of async syscalls that can run "in parallel", where they actually
 /// execute in a batch for determinism, but as far as the js promises are
 /// concerned, they're running in parallel.
@@ -161,6 +187,7 @@ pub fn system_table_guard(name: &TableName, expect_system_table: bool) -> anyhow
 pub enum AsyncSyscallBatch {
     Reads(Vec<AsyncRead>),
     StorageGetUrls(Vec<JsonValue>),
+    GroupBys(Vec<JsonValue>),
     Unbatched { name: String, args: JsonValue },
 }
 
@@ -168,6 +195,7 @@ pub enum AsyncSyscallBatch {
 pub enum AsyncRead {
     Get(JsonValue),
     QueryStreamNext(JsonValue),
+    Prefetch(JsonValue),
 }
 
 impl AsyncSyscallBatch {
@@ -175,7 +203,9 @@ impl AsyncSyscallBatch {
         match &*name {
             "1.0/get" => Self::Reads(vec![AsyncRead::Get(args)]),
             "1.0/queryStreamNext" => Self::Reads(vec![AsyncRead::QueryStreamNext(args)]),
+            "1.0/prefetch" => Self::Reads(vec![AsyncRead::Prefetch(args)]),
             "1.0/storageGetUrl" => Self::StorageGetUrls(vec![args]),
+            "1.0/groupBy" => Self::GroupBys(vec![args]),
             _ => Self::Unbatched { name, args },
         }
     }
@@ -187,9 +217,12 @@ impl AsyncSyscallBatch {
         match (self, name) {
             (Self::Reads(_), "1.0/get") => true,
             (Self::Reads(_), "1.0/queryStreamNext") => true,
+            (Self::Reads(_), "1.0/prefetch") => true,
             (Self::Reads(_), _) => false,
             (Self::StorageGetUrls(_), "1.0/storageGetUrl") => true,
             (Self::StorageGetUrls(_), _) => false,
+            (Self::GroupBys(_), "1.0/groupBy") => true,
+            (Self::GroupBys(_), _) => false,
             (Self::Unbatched { .. }, _) => false,
         }
     }
@@ -200,9 +233,13 @@ impl AsyncSyscallBatch {
             (Self::Reads(batch_args), "1.0/queryStreamNext") => {
                 batch_args.push(AsyncRead::QueryStreamNext(args))
             },
+            (Self::Reads(batch_args), "1.0/prefetch") => batch_args.push(AsyncRead::Prefetch(args)),
             (Self::StorageGetUrls(batch_args), "1.0/storageGetUrl") => {
                 batch_args.push(args);
             },
+            (Self::GroupBys(batch_args), "1.0/groupBy") => {
+                batch_args.push(args);
+            },
             _ => anyhow::bail!("cannot push {name} onto {self:?}"),
         }
         Ok(())
@@ -213,6 +250,7 @@ impl AsyncSyscallBatch {
             // 1.0/get is grouped in with 1.0/queryStreamNext.
             Self::Reads(_) => "1.0/queryStreamNext",
             Self::StorageGetUrls(_) => "1.0/storageGetUrl",
+            Self::GroupBys(_) => "1.0/groupBy",
             Self::Unbatched { name, .. } => name,
         }
     }
@@ -221,6 +259,7 @@ impl AsyncSyscallBatch {
         match self {
             Self::Reads(args) => args.len(),
             Self::StorageGetUrls(args) => args.len(),
+            Self::GroupBys(args) => args.len(),
             Self::Unbatched { .. } => 1,
         }
     }
@@ -313,11 +352,15 @@ pub trait AsyncSyscallProvider<RT: Runtime> {
         storage_id: FileStorageId,
     ) -> anyhow::Result<Option<FileStorageEntry>>;
 
+    /// Runs a query or mutation in this transaction. The called function's
+    /// arguments are always validated, and its return value is validated
+    /// unless `validate_returns` is false.
     async fn run_udf(
         &mut self,
         udf_type: UdfType,
         path: ResolvedComponentFunctionPath,
         args: ConvexObject,
+        validate_returns: bool,
     ) -> anyhow::Result<ConvexValue>;
 
     async fn create_function_handle(
@@ -476,6 +519,7 @@ impl<RT: Runtime> AsyncSyscallProvider<RT> for DatabaseUdfEnvironment<RT> {
         udf_type: UdfType,
         path: ResolvedComponentFunctionPath,
         args: ConvexObject,
+        validate_returns: bool,
     ) -> anyhow::Result<ConvexValue> {
         match (self.udf_type, udf_type) {
             // Queries can call other queries.
@@ -526,6 +570,8 @@ impl<RT: Runtime> AsyncSyscallProvider<RT> for DatabaseUdfEnvironment<RT> {
             0
         };
 
+        // The called function runs in this transaction, even if it's in another
+        // component, so its writes commit or roll back atomically with ours.
         let mut tx = self.phase.take_tx()?;
         let tokens = tx.begin_subtransaction();
 
@@ -612,12 +658,14 @@ impl<RT: Runtime> AsyncSyscallProvider<RT> for DatabaseUdfEnvironment<RT> {
                 anyhow::bail!(e);
             },
         };
-        let tx = self.phase.tx()?;
-        let table_mapping = tx.table_mapping().namespace(called_component_id.into());
-        if let Some(e) =
-            returns_validator.check_output(&result, &table_mapping, virtual_system_mapping())
-        {
-            anyhow::bail!(ErrorMetadata::bad_request("InvalidReturnValue", e.message));
+        if validate_returns {
+            let tx = self.phase.tx()?;
+            let table_mapping = tx.table_mapping().namespace(called_component_id.into());
+            if let Some(e) =
+                returns_validator.check_output(&result, &table_mapping, virtual_system_mapping())
+            {
+                anyhow::bail!(ErrorMetadata::bad_request("InvalidReturnValue", e.message));
+            }
         }
         Ok(result)
     }
@@ -681,19 +729,32 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
             AsyncSyscallBatch::StorageGetUrls(batch_args) => {
                 Self::storage_get_url_batch(provider, batch_args).await
             },
+            AsyncSyscallBatch::GroupBys(batch_args) => {
+                Self::group_by_batch(provider, batch_args).await
+            },
             AsyncSyscallBatch::Unbatched { name, args } => {
                 let result = match &name[..] {
                     // Database
                     "1.0/count" => Box::pin(Self::count(provider, args)).await,
+                    "1.0/estimatedCount" => Box::pin(Self::estimated_count(provider, args)).await,
+                    "1.0/approxDistinct" => Box::pin(Self::approx_distinct(provider, args)).await,
+                    "1.0/sample" => Box::pin(Self::sample(provider, args)).await,
                     "1.0/insert" => Box::pin(Self::insert(provider, args)).await,
                     "1.0/shallowMerge" => Box::pin(Self::shallow_merge(provider, args)).await,
+                    "1.0/deepPatch" => Box::pin(Self::deep_patch(provider, args)).await,
                     "1.0/replace" => Box::pin(Self::replace(provider, args)).await,
+                    "1.0/getVersion" => Box::pin(Self::get_version(provider, args)).await,
+                    "1.0/replaceIfVersion" => {
+                        Box::pin(Self::replace_if_version(provider, args)).await
+                    },
                     "1.0/remove" => Box::pin(Self::remove(provider, args)).await,
                     "1.0/queryPage" => Box::pin(Self::query_page(provider, args)).await,
                     // Auth
                     "1.0/getUserIdentity" => {
                         Box::pin(Self::get_user_identity(provider, args)).await
                     },
+                    // Feature flags
+                    "1.0/getFeatureFlag" => Box::pin(Self::get_feature_flag(provider, args)).await,
                     // Storage
                     "1.0/storageDelete" => Box::pin(Self::storage_delete(provider, args)).await,
                     "1.0/storageGetMetadata" => {
@@ -705,6 +766,12 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
                     // Scheduling
                     "1.0/schedule" => Box::pin(Self::schedule(provider, args)).await,
                     "1.0/cancel_job" => Box::pin(Self::cancel_job(provider, args)).await,
+                    // Queues
+                    "1.0/queue/enqueue" => Box::pin(Self::queue_enqueue(provider, args)).await,
+                    "1.0/queue/lease" => Box::pin(Self::queue_lease(provider, args)).await,
+                    "1.0/queue/ack" => Box::pin(Self::queue_ack(provider, args)).await,
+                    "1.0/queue/nack" => Box::pin(Self::queue_nack(provider, args)).await,
+                    "1.0/queue/redrive" => Box::pin(Self::queue_redrive(provider, args)).await,
 
                     // Components
                     "1.0/runUdf" => Box::pin(Self::run_udf(provider, args)).await,
@@ -770,6 +837,191 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
         Ok(ConvexValue::from(result).to_internal_json())
     }
 
+    #[convex_macro::instrument_future]
+    async fn estimated_count(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct EstimatedCountArgs {
+            query: JsonValue,
+        }
+        let query = with_argument_error("estimatedCount", || {
+            let args: EstimatedCountArgs = serde_json::from_value(args)?;
+            Query::try_from(args.query).context(ArgName("query"))
+        })?;
+        let table_filter = provider.table_filter();
+        let component = provider.component()?;
+        let tx = provider.tx()?;
+        let result = IndexStatisticsModel::new(tx)
+            .estimated_count(component.into(), query, table_filter)
+            .await?;
+        Ok(ConvexValue::from(result as f64).to_internal_json())
+    }
+
+    #[convex_macro::instrument_future]
+    async fn approx_distinct(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ApproxDistinctArgs {
+            query: JsonValue,
+            field: String,
+        }
+        let (query, field) = with_argument_error("approxDistinct", || {
+            let args: ApproxDistinctArgs = serde_json::from_value(args)?;
+            let query = Query::try_from(args.query).context(ArgName("query"))?;
+            let field: FieldPath = args.field.parse().context(ArgName("field"))?;
+            Ok((query, field))
+        })?;
+        let table_filter = provider.table_filter();
+        let component = provider.component()?;
+        let tx = provider.tx()?;
+        let result = IndexStatisticsModel::new(tx)
+            .approx_distinct(component.into(), query, field, table_filter)
+            .await?;
+        Ok(ConvexValue::from(result as f64).to_internal_json())
+    }
+
+    /// Runs `db.query(...).groupBy(...)` calls awaited together in one
+    /// `group_by_batch`, so their index scans read from persistence
+    /// concurrently.
+    #[convex_macro::instrument_future]
+    async fn group_by_batch(
+        provider: &mut P,
+        batch_args: Vec<JsonValue>,
+    ) -> Vec<anyhow::Result<JsonValue>> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct GroupByArgs {
+            query: JsonValue,
+            group_by: Vec<String>,
+            aggregates: Vec<AggregateArg>,
+        }
+        #[derive(Deserialize)]
+        #[serde(tag = "op", rename_all = "camelCase")]
+        enum AggregateArg {
+            Count,
+            Sum { field: String },
+            Min { field: String },
+            Max { field: String },
+        }
+        let table_filter = provider.table_filter();
+        let batch_size = batch_args.len();
+        let tx_and_component: anyhow::Result<_> = try {
+            let component = provider.component()?;
+            (provider.tx()?, component)
+        };
+        let (tx, component) = match tx_and_component {
+            Ok(tx_and_component) => tx_and_component,
+            Err(e) => {
+                return (0..batch_size).map(|_| Err(e.clone_error())).collect_vec();
+            },
+        };
+        let mut results = BTreeMap::new();
+        let mut queries = BTreeMap::new();
+        for (idx, args) in batch_args.into_iter().enumerate() {
+            let query: anyhow::Result<_> = try {
+                let (query, group_fields, aggregates) = with_argument_error("groupBy", || {
+                    let args: GroupByArgs = serde_json::from_value(args)?;
+                    let query = Query::try_from(args.query).context(ArgName("query"))?;
+                    let group_fields = args
+                        .group_by
+                        .into_iter()
+                        .map(|field| field.parse())
+                        .collect::<anyhow::Result<Vec<FieldPath>>>()
+                        .context(ArgName("groupBy"))?;
+                    let aggregates = args
+                        .aggregates
+                        .into_iter()
+                        .map(|aggregate| {
+                            anyhow::Ok(match aggregate {
+                                AggregateArg::Count => Aggregate::Count,
+                                AggregateArg::Sum { field } => Aggregate::Sum(field.parse()?),
+                                AggregateArg::Min { field } => Aggregate::Min(field.parse()?),
+                                AggregateArg::Max { field } => Aggregate::Max(field.parse()?),
+                            })
+                        })
+                        .collect::<anyhow::Result<Vec<_>>>()
+                        .context(ArgName("aggregates"))?;
+                    Ok((query, group_fields, aggregates))
+                })?;
+                GroupByQuery::new(
+                    tx,
+                    component.into(),
+                    query,
+                    group_fields,
+                    aggregates,
+                    table_filter,
+                )?
+            };
+            match query {
+                Ok(query) => {
+                    queries.insert(idx, query);
+                },
+                Err(e) => {
+                    assert!(results.insert(idx, Err(e)).is_none());
+                },
+            }
+        }
+        if !queries.is_empty() {
+            // Missing key fields and aggregates without any values become null.
+            let to_json = |values: Vec<Option<ConvexValue>>| {
+                values
+                    .into_iter()
+                    .map(|value| value.unwrap_or(ConvexValue::Null).to_internal_json())
+                    .collect::<Vec<_>>()
+            };
+            for (batch_key, groups) in group_by_batch(queries, tx).await {
+                let result = groups.map(|groups| {
+                    let groups: Vec<_> = groups
+                        .into_iter()
+                        .map(|group| {
+                            json!({ "key": to_json(group.key), "values": to_json(group.values) })
+                        })
+                        .collect();
+                    JsonValue::Array(groups)
+                });
+                assert!(results.insert(batch_key, result).is_none());
+            }
+        }
+        assert_eq!(results.len(), batch_size);
+        results.into_values().collect()
+    }
+
+    #[convex_macro::instrument_future]
+    async fn sample(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct SampleArgs {
+            table: String,
+            n: usize,
+            /// Drawn from the function's seeded `Math.random()`, so a sample
+            /// is as deterministic as the rest of the function.
+            seed: u64,
+        }
+        let (table_name, n, seed) = with_argument_error("sample", || {
+            let args: SampleArgs = serde_json::from_value(args)?;
+            let table_name: TableName = args.table.parse().context(ArgName("table"))?;
+            Ok((table_name, args.n, args.seed))
+        })?;
+        let table_filter = provider.table_filter();
+        let component = provider.component()?;
+        let tx = provider.tx()?;
+        let documents = sample_documents(
+            tx,
+            component.into(),
+            table_name,
+            n,
+            &mut ChaCha12Rng::seed_from_u64(seed),
+            table_filter,
+        )
+        .await?;
+        Ok(JsonValue::Array(
+            documents
+                .into_iter()
+                .map(|document| document.to_internal_json())
+                .collect(),
+        ))
+    }
+
     #[convex_macro::instrument_future]
     async fn get_user_identity(provider: &mut P, _args: JsonValue) -> anyhow::Result<JsonValue> {
         provider.observe_identity()?;
@@ -786,6 +1038,29 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
         Ok(JsonValue::Null)
     }
 
+    /// Reads the flag through the transaction, so queries that check a flag
+    /// rerun when it's changed. Unknown flags are off.
+    #[convex_macro::instrument_future]
+    async fn get_feature_flag(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        struct GetFeatureFlagArgs {
+            name: String,
+        }
+        let name = with_argument_error("flags.get", || {
+            let GetFeatureFlagArgs { name } = serde_json::from_value(args)?;
+            Ok(name)
+        })?;
+        let tx = provider.tx()?;
+        let Some(flag) = FeatureFlagsModel::new(tx).get(&name).await? else {
+            return Ok(JsonValue::Bool(false));
+        };
+        if flag.rule.depends_on_identity() {
+            provider.observe_identity()?;
+        }
+        let user_identity = provider.tx()?.user_identity();
+        Ok(JsonValue::Bool(flag.evaluate(user_identity.as_ref())))
+    }
+
     #[convex_macro::instrument_future]
     async fn storage_generate_upload_url(
         provider: &mut P,
@@ -900,6 +1175,7 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
             function_handle: Option<String>,
             ts: f64,
             args: UdfArgsJson,
+            idempotency_key: Option<String>,
         }
 
         let ScheduleArgs {
@@ -908,6 +1184,7 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
             function_handle,
             ts,
             args,
+            idempotency_key,
         }: ScheduleArgs = with_argument_error("scheduler", || Ok(serde_json::from_value(args)?))?;
 
         let path = match function_handle {
@@ -945,7 +1222,7 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
         let context = provider.context().clone();
         let tx = provider.tx()?;
         let virtual_id = VirtualSchedulerModel::new(tx, scheduling_component.into())
-            .schedule(path, udf_args, scheduled_ts, context)
+            .schedule(path, udf_args, scheduled_ts, context, idempotency_key)
             .await?;
 
         Ok(JsonValue::from(virtual_id))
@@ -974,6 +1251,74 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
         Ok(JsonValue::Null)
     }
 
+    #[convex_macro::instrument_future]
+    async fn queue_enqueue(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        let EnqueueArgs {
+            queue,
+            payload,
+            delay,
+            max_attempts,
+        } = EnqueueArgs::parse(args)?;
+        let visible_at = provider.rt().unix_timestamp() + delay;
+        let component = provider.component()?;
+        QueueModel::new(provider.tx()?, component.into())
+            .enqueue(queue, payload, visible_at, max_attempts)
+            .await?;
+        Ok(JsonValue::Null)
+    }
+
+    #[convex_macro::instrument_future]
+    async fn queue_lease(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        let LeaseArgs {
+            queue,
+            max_messages,
+            visibility_timeout,
+        } = LeaseArgs::parse(args)?;
+        let now = provider.rt().unix_timestamp();
+        let component = provider.component()?;
+        let messages = QueueModel::new(provider.tx()?, component.into())
+            .lease(&queue, max_messages, visibility_timeout, now)
+            .await?;
+        Ok(leased_messages_to_json(messages))
+    }
+
+    #[convex_macro::instrument_future]
+    async fn queue_ack(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        let AckArgs { receipt } = AckArgs::parse(args)?;
+        let component = provider.component()?;
+        QueueModel::new(provider.tx()?, component.into())
+            .ack(&receipt)
+            .await?;
+        Ok(JsonValue::Null)
+    }
+
+    #[convex_macro::instrument_future]
+    async fn queue_nack(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        let NackArgs { receipt, delay } = NackArgs::parse(args)?;
+        let visible_at = provider.rt().unix_timestamp() + delay;
+        let component = provider.component()?;
+        QueueModel::new(provider.tx()?, component.into())
+            .nack(&receipt, visible_at)
+            .await?;
+        Ok(JsonValue::Null)
+    }
+
+    #[convex_macro::instrument_future]
+    async fn queue_redrive(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        struct RedriveArgs {
+            queue: String,
+        }
+        let RedriveArgs { queue } =
+            with_argument_error("queue.redrive", || Ok(serde_json::from_value(args)?))?;
+        let now = provider.rt().unix_timestamp();
+        let component = provider.component()?;
+        let count = QueueModel::new(provider.tx()?, component.into())
+            .redrive(&queue, now)
+            .await?;
+        Ok(JsonValue::from(count))
+    }
+
     #[fastrace::trace]
     #[convex_macro::instrument_future]
     async fn insert(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
@@ -1036,6 +1381,38 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
         Ok(document.to_internal_json())
     }
 
+    #[fastrace::trace]
+    #[convex_macro::instrument_future]
+    async fn deep_patch(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct DeepPatchArgs {
+            id: String,
+            operations: JsonValue,
+        }
+        let table_filter = provider.table_filter();
+        let component = provider.component()?;
+        let tx = provider.tx()?;
+        let (id, patch, table_name) = with_argument_error("db.deepPatch", || {
+            let args: DeepPatchArgs = serde_json::from_value(args)?;
+
+            let id = DeveloperDocumentId::decode(&args.id).context(ArgName("id"))?;
+            let table_name = tx
+                .resolve_idv6(id, component.into(), table_filter)
+                .context(ArgName("id"))?;
+
+            let patch = DeepPatch::try_from(args.operations).context(ArgName("operations"))?;
+            Ok((id, patch, table_name))
+        })?;
+
+        system_table_guard(&table_name, false)?;
+
+        let document = UserFacingModel::new(tx, component.into())
+            .deep_patch(id, patch)
+            .await?;
+        Ok(document.to_internal_json())
+    }
+
     #[fastrace::trace]
     #[convex_macro::instrument_future]
     async fn replace(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
@@ -1068,6 +1445,79 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
         Ok(document.to_internal_json())
     }
 
+    #[convex_macro::instrument_future]
+    async fn get_version(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct GetVersionArgs {
+            id: String,
+        }
+        let table_filter = provider.table_filter();
+        let component = provider.component()?;
+        let tx = provider.tx()?;
+        let (id, table_name) = with_argument_error("db.getVersion", || {
+            let args: GetVersionArgs = serde_json::from_value(args)?;
+            let id = DeveloperDocumentId::decode(&args.id).context(ArgName("id"))?;
+            let table_name = tx
+                .resolve_idv6(id, component.into(), table_filter)
+                .context(ArgName("id"))?;
+            Ok((id, table_name))
+        })?;
+        system_table_guard(&table_name, false)?;
+        let version = UserFacingModel::new(tx, component.into())
+            .get_version(id)
+            .await?;
+        let value = match version {
+            Some(ts) => ConvexValue::Int64(ts.into()),
+            None => ConvexValue::Null,
+        };
+        Ok(value.into())
+    }
+
+    #[convex_macro::instrument_future]
+    async fn replace_if_version(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ReplaceIfVersionArgs {
+            id: String,
+            version: JsonValue,
+            value: JsonValue,
+        }
+        let table_filter = provider.table_filter();
+        let component = provider.component()?;
+        let tx = provider.tx()?;
+        let (id, version, value, table_name) = with_argument_error("db.replaceIfVersion", || {
+            let args: ReplaceIfVersionArgs = serde_json::from_value(args)?;
+
+            let id = DeveloperDocumentId::decode(&args.id).context(ArgName("id"))?;
+            let table_name = tx
+                .resolve_idv6(id, component.into(), table_filter)
+                .context(ArgName("id"))?;
+
+            let version = match ConvexValue::try_from(args.version).context(ArgName("version"))? {
+                ConvexValue::Int64(version) => {
+                    Timestamp::try_from(version).context(ArgName("version"))?
+                },
+                v => Err(anyhow::anyhow!("Expected a bigint, got {v}"))
+                    .context(ArgName("version"))?,
+            };
+            let value = ConvexValue::try_from(args.value).context(ArgName("value"))?;
+            Ok((
+                id,
+                version,
+                value.try_into().context(ArgName("value"))?,
+                table_name,
+            ))
+        })?;
+
+        system_table_guard(&table_name, false)?;
+
+        let replaced = UserFacingModel::new(tx, component.into())
+            .replace_if_version(id, version, value)
+            .await?;
+        Ok(JsonValue::from(replaced))
+    }
+
     #[fastrace::trace]
     #[convex_macro::instrument_future]
     async fn query_batch(
@@ -1088,9 +1538,22 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
         struct QueryStreamNextArgs {
             query_id: u32,
         }
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct PrefetchArgs {
+            #[serde(default)]
+            ids: Vec<String>,
+            #[serde(default)]
+            is_system: bool,
+            query: Option<JsonValue>,
+            num_items: Option<usize>,
+            #[serde(default)]
+            version: Option<String>,
+        }
 
         let table_filter = provider.table_filter();
         let mut queries_to_fetch = BTreeMap::new();
+        let mut prefetches = vec![];
         let mut results = BTreeMap::new();
         let batch_size = batch_args.len();
         for (idx, args) in batch_args.into_iter().enumerate() {
@@ -1161,6 +1624,60 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
                             },
                         }
                     },
+                    AsyncRead::Prefetch(args) => {
+                        let component = provider.component()?;
+                        let tx = provider.tx()?;
+                        let args = with_argument_error("db.prefetch", || {
+                            let args: PrefetchArgs = serde_json::from_value(args)?;
+                            anyhow::ensure!(
+                                args.ids.len() <= MAX_PREFETCH_IDS,
+                                ErrorMetadata::bad_request(
+                                    "TooManyPrefetchIds",
+                                    format!(
+                                        "db.prefetch() can prefetch at most {MAX_PREFETCH_IDS} \
+                                         documents at once"
+                                    ),
+                                )
+                            );
+                            Ok(args)
+                        })?;
+                        let version = parse_version(args.version)?;
+                        for id in args.ids {
+                            let id = with_argument_error("db.prefetch", || {
+                                DeveloperDocumentId::decode(&id).context(ArgName("ids"))
+                            })?;
+                            // Like `db.get`, ids of tables that don't exist
+                            // have nothing to fetch.
+                            let Ok(table_name) =
+                                tx.resolve_idv6(id, component.into(), table_filter)
+                            else {
+                                continue;
+                            };
+                            system_table_guard(&table_name, args.is_system)?;
+                            let query = DeveloperQuery::new_with_version(
+                                tx,
+                                component.into(),
+                                Query::get(table_name, id),
+                                version.clone(),
+                                table_filter,
+                            )?;
+                            prefetches.push((query, Some(1)));
+                        }
+                        if let Some(query) = args.query {
+                            let query = with_argument_error("query.prefetch", || {
+                                Query::try_from(query).context(ArgName("query"))
+                            })?;
+                            let query = DeveloperQuery::new_with_version(
+                                tx,
+                                component.into(),
+                                query,
+                                version,
+                                table_filter,
+                            )?;
+                            prefetches.push((query, args.num_items));
+                        }
+                        None
+                    },
                 }
             };
             match result {
@@ -1185,11 +1702,12 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
             },
         };
 
-        let mut fetch_results = query_batch_next(
+        let mut fetch_results = query_batch_next_with_prefetches(
             queries_to_fetch
                 .iter_mut()
                 .map(|(idx, (_, local_query))| (*idx, (local_query, None)))
                 .collect(),
+            prefetches,
             tx,
         )
         .await;
@@ -1198,16 +1716,28 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
         struct QueryStreamNextResult {
             value: JsonValue,
             done: bool,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            highlights: Option<SearchHighlights>,
+            // Sent with the last result, once the search has counted them.
+            #[serde(skip_serializing_if = "Option::is_none")]
+            facets: Option<SearchFacets>,
         }
 
         for (batch_key, (query_id, local_query)) in queries_to_fetch {
             let result: anyhow::Result<_> = try {
+                let next = fetch_results.remove(&batch_key);
+                let highlights = match &next {
+                    Some(Ok(Some((doc, _)))) => local_query.search_highlights(doc),
+                    _ => None,
+                };
+                let facets = match &next {
+                    Some(Ok(None)) => local_query.search_facets().cloned(),
+                    _ => None,
+                };
                 if let Some(query_id) = query_id {
                     provider.insert_query(query_id, local_query);
                 }
-                let maybe_next = fetch_results
-                    .remove(&batch_key)
-                    .context("batch_key missing")??;
+                let maybe_next = next.context("batch_key missing")??;
 
                 let done = maybe_next.is_none();
                 let value = match maybe_next {
@@ -1222,6 +1752,8 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
                     serde_json::to_value(QueryStreamNextResult {
                         value: value.into(),
                         done,
+                        highlights,
+                        facets,
                     })?
                 } else {
                     value.into()
@@ -1278,6 +1810,7 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
             reference: Option<String>,
             function_handle: Option<String>,
             args: JsonValue,
+            validate_returns: Option<bool>,
         }
         let RunUdfArgs {
             udf_type,
@@ -1285,6 +1818,7 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
             reference,
             function_handle,
             args,
+            validate_returns,
         } = with_argument_error("runUdf", || Ok(serde_json::from_value(args)?))?;
         let (udf_type, args) = with_argument_error("runUdf", || {
             let udf_type: UdfType = udf_type.parse().context(ArgName("udfType"))?;
@@ -1332,7 +1866,9 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
                 }
             },
         };
-        let value = provider.run_udf(udf_type, path, args).await?;
+        let value = provider
+            .run_udf(udf_type, path, args, validate_returns.unwrap_or(true))
+            .await?;
         Ok(value.into())
     }
 
@@ -1409,7 +1945,7 @@ struct QueryPageMetadata {
 
 impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsShared<RT, P> {
     async fn read_page_from_query(
-        mut query: DeveloperQuery<RT>,
+        query: &mut DeveloperQuery<RT>,
         tx: &mut Transaction<RT>,
         page_size: usize,
     ) -> anyhow::Result<(Vec<DeveloperDocument>, QueryPageMetadata)> {
@@ -1536,13 +2072,15 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsShared<RT, P> {
 
         let (
             page,
+            page_highlights,
+            facets,
             QueryPageMetadata {
                 cursor,
                 split_cursor,
                 page_status,
             },
         ) = {
-            let query = DeveloperQuery::new_bounded(
+            let mut query = DeveloperQuery::new_bounded(
                 tx,
                 component.into(),
                 parsed_query,
@@ -1555,9 +2093,22 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsShared<RT, P> {
                 version,
                 table_filter,
             )?;
-            let (page, metadata) = Self::read_page_from_query(query, tx, page_size).await?;
+            let (page, metadata) = Self::read_page_from_query(&mut query, tx, page_size).await?;
+            let highlights: Vec<_> = page
+                .iter()
+                .map(|doc| query.search_highlights(doc))
+                .collect();
+            // Only search queries that asked for highlights have them.
+            let page_highlights: Option<Vec<SearchHighlights>> =
+                highlights.iter().any(Option::is_some).then(|| {
+                    highlights
+                        .into_iter()
+                        .map(Option::unwrap_or_default)
+                        .collect()
+                });
+            let facets = query.search_facets().cloned();
             let page = page.into_iter().map(|doc| doc.to_internal_json()).collect();
-            (page, metadata)
+            (page, page_highlights, facets, metadata)
         };
 
         let page_status = page_status.map(|s| s.as_str());
@@ -1599,6 +2150,10 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsShared<RT, P> {
             continue_cursor: String,
             split_cursor: Option<String>,
             page_status: Option<&'static str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            page_highlights: Option<Vec<SearchHighlights>>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            facets: Option<SearchFacets>,
         }
         let result = QueryPageResult {
             page,
@@ -1606,6 +2161,8 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsShared<RT, P> {
             continue_cursor,
             split_cursor,
             page_status,
+            page_highlights,
+            facets,
         };
         Ok(serde_json::to_value(result)?)
     }
@@ -56,6 +56,7 @@ use common::{
     log_lines::{
         LogLevel,
         LogLine,
+        LogLineStructured,
         LogLines,
     },
     query_journal::QueryJournal,
@@ -94,11 +95,11 @@ use value::{
         HeapSize,
         WithHeapSize,
     },
+    ConvexObject,
     JsonPackedValue,
     NamespacedTableMapping,
     Size,
     MAX_DOCUMENT_NESTING,
-    MAX_USER_SIZE,
     VALUE_TOO_LARGE_SHORT_MSG,
 };
 
@@ -130,7 +131,6 @@ use crate::{
     concurrency_limiter::ConcurrencyPermit,
     environment::{
         helpers::{
-            module_loader::module_specifier_from_path,
             resolve_promise,
             MAX_LOG_LINES,
         },
@@ -220,6 +220,23 @@ impl<RT: Runtime> IsolateEnvironment<RT> for DatabaseUdfEnvironment<RT> {
         Ok(())
     }
 
+    fn trace_structured(
+        &mut self,
+        level: LogLevel,
+        message: String,
+        fields: ConvexObject,
+    ) -> anyhow::Result<()> {
+        self.emit_log_line(LogLine::Structured(
+            LogLineStructured::new_structured_log_line(
+                level,
+                message,
+                fields,
+                self.rt.unix_timestamp(),
+            ),
+        ));
+        Ok(())
+    }
+
     fn rng(&mut self) -> anyhow::Result<&mut ChaCha12Rng> {
         self.phase.rng()
     }
@@ -542,16 +559,7 @@ impl<RT: Runtime> DatabaseUdfEnvironment<RT> {
         }
 
         // First, load the user's module and find the specified function.
-        let module_path = udf_path.module().clone();
-        let Ok(module_specifier) = module_specifier_from_path(&module_path) else {
-            let message = format!("Invalid module path: {module_path:?}");
-            return Ok(Err(JsError::from_message(message)));
-        };
-
-        let module = match scope
-            .eval_user_module(udf_type, false, &module_specifier)
-            .await?
-        {
+        let module = match scope.eval_udf_module(udf_type, &udf_path).await? {
             Ok(id) => id,
             Err(e) => return Ok(Err(e)),
         };
@@ -952,7 +960,7 @@ impl<RT: Runtime> DatabaseUdfEnvironment<RT> {
             let (max_size_document_id, max_size) = biggest_writes.max_size;
             if let Some(warning) = approaching_limit_warning(
                 max_size,
-                MAX_USER_SIZE,
+                biggest_writes.max_size_limit,
                 VALUE_TOO_LARGE_SHORT_MSG,
                 || format!("Large document written with ID \"{max_size_document_id}\""),
                 None,
@@ -16,6 +16,7 @@ use anyhow::{
 use async_recursion::async_recursion;
 use common::{
     errors::JsError,
+    knobs::ISOLATE_USE_FUNCTION_BUNDLES,
     runtime::Runtime,
     static_span,
     types::UdfType,
@@ -34,12 +35,14 @@ use model::modules::{
     },
 };
 use serde_json::Value as JsonValue;
+use sync_types::CanonicalizedUdfPath;
 use value::heap_size::HeapSize;
 
 use crate::{
     array_buffer_allocator::ArrayBufferMemoryLimit,
     bundled_js::system_udf_file,
     environment::{
+        helpers::module_loader::module_specifier_from_path,
         IsolateEnvironment,
         ModuleCodeCacheResult,
     },
@@ -271,28 +274,63 @@ impl<'a, 'b: 'a, RT: Runtime, E: IsolateEnvironment<RT>> ExecutionScope<'a, 'b,
         let timer = metrics::eval_user_module_timer(udf_type, is_dynamic);
         let module = match self.eval_module(name).await {
             Ok(id) => id,
-            Err(e) => {
-                // TODO: It's a bit awkward that we're calling these "JsError"s, since they
-                // don't originate from JavaScript.
-                if let Some(e) = e.downcast_ref::<ModuleNotFoundError>() {
-                    return Ok(Err(JsError::from_message(format!("{e}"))));
-                }
-                if let Some(e) = e.downcast_ref::<SystemModuleNotFoundError>() {
-                    return Ok(Err(JsError::from_message(format!("{e}"))));
-                }
-                if let Some(e) = e.downcast_ref::<ModuleResolutionError>() {
-                    return Ok(Err(JsError::from_message(format!("{e}"))));
-                }
-                match e.downcast::<JsError>() {
-                    Ok(e) => return Ok(Err(e)),
-                    Err(e) => return Err(e),
-                }
-            },
+            Err(e) => return Self::module_error_to_js(e),
         };
         timer.finish();
         Ok(Ok(module))
     }
 
+    fn module_error_to_js<T>(e: anyhow::Error) -> anyhow::Result<Result<T, JsError>> {
+        // TODO: It's a bit awkward that we're calling these "JsError"s, since they
+        // don't originate from JavaScript.
+        if let Some(e) = e.downcast_ref::<ModuleNotFoundError>() {
+            return Ok(Err(JsError::from_message(format!("{e}"))));
+        }
+        if let Some(e) = e.downcast_ref::<SystemModuleNotFoundError>() {
+            return Ok(Err(JsError::from_message(format!("{e}"))));
+        }
+        if let Some(e) = e.downcast_ref::<ModuleResolutionError>() {
+            return Ok(Err(JsError::from_message(format!("{e}"))));
+        }
+        match e.downcast::<JsError>() {
+            Ok(e) => Ok(Err(e)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Evaluates the module to run `udf_path` from: the function's own
+    /// bundle if one was uploaded, otherwise the module that defines it.
+    pub async fn eval_udf_module(
+        &mut self,
+        udf_type: UdfType,
+        udf_path: &CanonicalizedUdfPath,
+    ) -> anyhow::Result<Result<v8::Local<'a, v8::Module>, JsError>> {
+        if *ISOLATE_USE_FUNCTION_BUNDLES
+            && !udf_path.is_system()
+            && let Some(bundle_path) = udf_path.function_bundle_path()
+        {
+            let bundle_specifier = module_specifier_from_path(&bundle_path)?;
+            match self.eval_module(&bundle_specifier).await {
+                Ok(module) => {
+                    metrics::log_function_bundle_used(true);
+                    return Ok(Ok(module));
+                },
+                // Most deployments don't upload function bundles, so fall back
+                // to the defining module.
+                Err(e) if e.is::<ModuleNotFoundError>() => {
+                    metrics::log_function_bundle_used(false);
+                },
+                Err(e) => return Self::module_error_to_js(e),
+            }
+        }
+        let Ok(module_specifier) = module_specifier_from_path(udf_path.module()) else {
+            let message = format!("Invalid module path: {:?}", udf_path.module());
+            return Ok(Err(JsError::from_message(message)));
+        };
+        self.eval_user_module(udf_type, false, &module_specifier)
+            .await
+    }
+
     #[fastrace::trace]
     pub async fn eval_module(
         &mut self,
@@ -938,6 +938,7 @@ impl<RT: Runtime> AsyncSyscallProvider<RT> for Isolate2SyscallProvider<'_, RT> {
         _udf_type: UdfType,
         _path: ResolvedComponentFunctionPath,
         _args: ConvexObject,
+        _validate_returns: bool,
     ) -> anyhow::Result<ConvexValue> {
         todo!();
     }
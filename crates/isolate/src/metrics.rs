@@ -200,6 +200,19 @@ pub fn eval_user_module_timer(udf_type: UdfType, is_dynamic: bool) -> StatusTime
     t
 }
 
+register_convex_counter!(
+    UDF_ISOLATE_FUNCTION_BUNDLE_TOTAL,
+    "Number of functions run from their own bundle vs. their whole module",
+    &["found"],
+);
+pub fn log_function_bundle_used(found: bool) {
+    log_counter_with_labels(
+        &UDF_ISOLATE_FUNCTION_BUNDLE_TOTAL,
+        1,
+        vec![StaticMetricLabel::new("found", found.as_label())],
+    );
+}
+
 register_convex_histogram!(
     UDF_ISOLATE_LOOKUP_SOURCE_SECONDS,
     "Time to load a single module's source",
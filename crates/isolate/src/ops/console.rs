@@ -6,6 +6,8 @@ use common::{
     },
     log_lines::LogLevel,
 };
+use serde_json::Value as JsonValue;
+use value::ConvexObject;
 
 use super::{
     metrics,
@@ -26,6 +28,21 @@ pub fn op_console_message<'b, P: OpProvider<'b>>(
     Ok(())
 }
 
+/// Logs a line from `ctx.log`, with `fields` encoded as Convex JSON.
+#[convex_macro::v8_op]
+pub fn op_console_structured_message<'b, P: OpProvider<'b>>(
+    provider: &mut P,
+    level: String,
+    message: String,
+    fields: JsonValue,
+) -> anyhow::Result<()> {
+    let fields = ConvexObject::try_from(fields).context("Invalid log fields")?;
+    tracing::trace!("console structured message: {message:?} {fields}");
+    metrics::log_log_line(&message);
+    provider.trace_structured(level.parse()?, message, fields)?;
+    Ok(())
+}
+
 #[convex_macro::v8_op]
 pub fn op_console_trace<'b, P: OpProvider<'b>>(
     provider: &mut P,
@@ -53,6 +53,7 @@ use uuid::Uuid;
 use validate_returns::op_validate_returns;
 use value::{
     heap_size::WithHeapSize,
+    ConvexObject,
     NamespacedTableMapping,
 };
 
@@ -64,6 +65,7 @@ use self::{
     },
     console::{
         op_console_message,
+        op_console_structured_message,
         op_console_time_end,
         op_console_time_log,
         op_console_time_start,
@@ -165,6 +167,16 @@ pub trait OpProvider<'b> {
         specifier: &ModuleSpecifier,
     ) -> anyhow::Result<Option<SourceMap>>;
     fn trace(&mut self, level: LogLevel, messages: Vec<String>) -> anyhow::Result<()>;
+    /// Logs a line from `ctx.log`. Providers without structured log lines
+    /// fall back to logging the fields as a message.
+    fn trace_structured(
+        &mut self,
+        level: LogLevel,
+        message: String,
+        fields: ConvexObject,
+    ) -> anyhow::Result<()> {
+        self.trace(level, vec![message, fields.to_string()])
+    }
     fn console_timers(
         &mut self,
     ) -> anyhow::Result<&mut WithHeapSize<BTreeMap<String, UnixTimestamp>>>;
@@ -233,6 +245,17 @@ impl<'a, 'b: 'a, RT: Runtime, E: IsolateEnvironment<RT>> OpProvider<'b>
         Ok(())
     }
 
+    fn trace_structured(
+        &mut self,
+        level: LogLevel,
+        message: String,
+        fields: ConvexObject,
+    ) -> anyhow::Result<()> {
+        let state = self.state_mut()?;
+        state.environment.trace_structured(level, message, fields)?;
+        Ok(())
+    }
+
     fn console_timers(
         &mut self,
     ) -> anyhow::Result<&mut WithHeapSize<BTreeMap<String, UnixTimestamp>>> {
@@ -370,6 +393,7 @@ pub fn run_op<'b, P: OpProvider<'b>>(
             op_throw_uncatchable_developer_error(provider, args, rv)?
         },
         "console/message" => op_console_message(provider, args, rv)?,
+        "console/structuredMessage" => op_console_structured_message(provider, args, rv)?,
         "console/trace" => op_console_trace(provider, args, rv)?,
         "console/timeStart" => op_console_time_start(provider, args, rv)?,
         "console/timeLog" => op_console_time_log(provider, args, rv)?,
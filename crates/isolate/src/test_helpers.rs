@@ -3,6 +3,7 @@ use std::{
     collections::BTreeMap,
     fs::File,
     io::Read,
+    num::NonZeroU32,
     sync::{
         Arc,
         Exclusive,
@@ -33,6 +34,7 @@ use common::{
     fastrace_helpers::EncodedSpan,
     http::{
         fetch::ProxiedFetchClient,
+        rate_limit::HttpActionRateLimiter,
         RoutedHttpPath,
     },
     log_lines::LogLines,
@@ -62,8 +64,10 @@ use database::{
     vector_index_worker::flusher::backfill_vector_indexes,
     Database,
     FollowerRetentionManager,
+    HybridSearch,
     IndexModel,
     IndexWorker,
+    PublicHybridSearchQueryResult,
     Token,
     Transaction,
 };
@@ -98,6 +102,10 @@ use model::{
         types::FileStorageEntry,
         FileStorageId,
     },
+    queues::{
+        types::LeasedQueueMessage,
+        QueueModel,
+    },
     scheduled_jobs::VirtualSchedulerModel,
     source_packages::{
         types::SourcePackage,
@@ -109,6 +117,7 @@ use model::{
         UdfConfigModel,
     },
     virtual_system_mapping,
+    webhook_nonces::WebhookNonceModel,
 };
 use rand::Rng;
 use search::searcher::InProcessSearcher;
@@ -276,6 +285,7 @@ pub struct UdfTest<RT: Runtime, P: Persistence> {
     search_storage: Arc<dyn Storage>,
     file_storage: TransactionalFileStorage<RT>,
     environment_data: EnvironmentData<RT>,
+    http_rate_limiter: HttpActionRateLimiter<RT>,
 
     isolate_v2_enabled: bool,
 }
@@ -292,6 +302,7 @@ impl<RT: Runtime, P: Persistence> Clone for UdfTest<RT, P> {
             search_storage: self.search_storage.clone(),
             file_storage: self.file_storage.clone(),
             environment_data: self.environment_data.clone(),
+            http_rate_limiter: self.http_rate_limiter.clone(),
             isolate_v2_enabled: self.isolate_v2_enabled,
         }
     }
@@ -404,6 +415,7 @@ impl<RT: Runtime, P: Persistence> UdfTest<RT, P> {
             database,
             isolate,
             persistence,
+            http_rate_limiter: HttpActionRateLimiter::new(rt.clone()),
             rt,
             key_broker,
             search_storage,
@@ -1381,6 +1393,7 @@ impl<RT: Runtime, P: Persistence> ActionCallbacks for UdfTest<RT, P> {
         udf_args: Vec<JsonValue>,
         scheduled_ts: UnixTimestamp,
         context: ExecutionContext,
+        idempotency_key: Option<String>,
     ) -> anyhow::Result<DeveloperDocumentId> {
         let mut tx: database::Transaction<RT> = self.database.begin(identity).await?;
         let (scheduled_path, udf_args) = validate_schedule_args(
@@ -1395,7 +1408,13 @@ impl<RT: Runtime, P: Persistence> ActionCallbacks for UdfTest<RT, P> {
         .await?;
 
         let virtual_id = VirtualSchedulerModel::new(&mut tx, scheduling_component.into())
-            .schedule(scheduled_path, udf_args, scheduled_ts, context)
+            .schedule(
+                scheduled_path,
+                udf_args,
+                scheduled_ts,
+                context,
+                idempotency_key,
+            )
             .await?;
         self.database.commit(tx).await?;
 
@@ -1415,6 +1434,77 @@ impl<RT: Runtime, P: Persistence> ActionCallbacks for UdfTest<RT, P> {
         Ok(())
     }
 
+    async fn queue_lease(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        queue: String,
+        max_messages: usize,
+        visibility_timeout: Duration,
+    ) -> anyhow::Result<Vec<LeasedQueueMessage>> {
+        let mut tx = self.database.begin(identity).await?;
+        let now = self.rt.unix_timestamp();
+        let messages = QueueModel::new(&mut tx, component.into())
+            .lease(&queue, max_messages, visibility_timeout, now)
+            .await?;
+        self.database.commit(tx).await?;
+        Ok(messages)
+    }
+
+    async fn queue_ack(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        receipt: String,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(identity).await?;
+        QueueModel::new(&mut tx, component.into())
+            .ack(&receipt)
+            .await?;
+        self.database.commit(tx).await?;
+        Ok(())
+    }
+
+    async fn queue_nack(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        receipt: String,
+        delay: Duration,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(identity).await?;
+        let visible_at = self.rt.unix_timestamp() + delay;
+        QueueModel::new(&mut tx, component.into())
+            .nack(&receipt, visible_at)
+            .await?;
+        self.database.commit(tx).await?;
+        Ok(())
+    }
+
+    async fn record_webhook_nonce(
+        &self,
+        scope: String,
+        nonce: String,
+        expires_at: UnixTimestamp,
+    ) -> anyhow::Result<bool> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let now_ms = self.rt.unix_timestamp().as_ms_since_epoch()? as i64;
+        let accepted = WebhookNonceModel::new(&mut tx)
+            .record(scope, nonce, expires_at.as_ms_since_epoch()? as i64, now_ms)
+            .await?;
+        self.database.commit(tx).await?;
+        Ok(accepted)
+    }
+
+    async fn check_http_rate_limit(
+        &self,
+        key: String,
+        requests: NonZeroU32,
+        period: Duration,
+    ) -> anyhow::Result<Option<Duration>> {
+        self.http_rate_limiter.check(&key, requests, period)
+    }
+
     async fn vector_search(
         &self,
         identity: Identity,
@@ -1424,6 +1514,15 @@ impl<RT: Runtime, P: Persistence> ActionCallbacks for UdfTest<RT, P> {
         self.database.vector_search(identity, query).await
     }
 
+    async fn hybrid_search(
+        &self,
+        identity: Identity,
+        query: JsonValue,
+    ) -> anyhow::Result<(Vec<PublicHybridSearchQueryResult>, FunctionUsageStats)> {
+        let query = HybridSearch::try_from(query)?;
+        self.database.hybrid_search(identity, query).await
+    }
+
     async fn lookup_function_handle(
         &self,
         identity: Identity,
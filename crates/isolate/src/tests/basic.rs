@@ -381,6 +381,98 @@ async fn test_replace(rt: TestRuntime) -> anyhow::Result<()> {
     .await
 }
 
+#[convex_macro::test_runtime]
+async fn test_deep_patch(rt: TestRuntime) -> anyhow::Result<()> {
+    UdfTest::run_test_with_isolate2(rt, async move |t: UdfTestType| {
+        must_let!(let ConvexValue::Object(obj) = t.mutation(
+            "basic:insertObject",
+            assert_obj!(
+                "profile" => {"name" => "emma", "settings" => {"theme" => "light", "size" => 1.}},
+                "tags" => ["a", "b"],
+                "items" => [{"n" => 1., "done" => true}, {"n" => 2., "done" => false}],
+                "stale" => true,
+                "field" => "value",
+            ),
+        ).await?);
+        must_let!(let Some(id) = obj.get("_id"));
+        must_let!(let Some(ConvexValue::Float64(creation_time)) = obj.get("_creationTime"));
+
+        must_let!(let ConvexValue::Object(obj) = t.mutation(
+            "basic:deepPatchObject",
+            assert_obj!("id" => id.clone()),
+        ).await?);
+        // Fields beside the patched paths are left as they were.
+        let expected = assert_obj!(
+            "_id" => id.clone(),
+            "_creationTime" => *creation_time,
+            "profile" => {"name" => "emma", "settings" => {"theme" => "dark", "size" => 1.}},
+            "tags" => ["a", "b", "c"],
+            "items" => [{"n" => 2., "done" => false}],
+            "field" => "value",
+        );
+        assert_eq!(obj, expected);
+
+        let e = t
+            .mutation_js_error(
+                "basic:deepPatchCreationTime",
+                assert_obj!("id" => id.clone()),
+            )
+            .await?;
+        assert_contains(&e, "`_creationTime` can't be patched");
+        Ok(())
+    })
+    .await
+}
+
+#[convex_macro::test_runtime]
+async fn test_replace_if_version(rt: TestRuntime) -> anyhow::Result<()> {
+    UdfTest::run_test_with_isolate2(rt, async move |t: UdfTestType| {
+        must_let!(let ConvexValue::Object(obj) = t.mutation(
+            "basic:insertObject",
+            assert_obj!("field" => "value1"),
+        ).await?);
+        must_let!(let Some(id) = obj.get("_id"));
+        // Reading a document doesn't include its version.
+        assert!(obj.get("_version").is_none());
+
+        must_let!(let ConvexValue::Int64(version) = t.query("basic:getObjectVersion", assert_obj!("id" => id.clone())).await?);
+
+        t.mutation("basic:rewriteObject", assert_obj!("id" => id.clone()))
+            .await?;
+        must_let!(let ConvexValue::Int64(new_version) = t.query("basic:getObjectVersion", assert_obj!("id" => id.clone())).await?);
+        assert!(new_version > version);
+
+        // The document was written since `version`, so it isn't replaced.
+        let replaced = t
+            .mutation(
+                "basic:replaceObjectIfVersion",
+                assert_obj!(
+                    "id" => id.clone(),
+                    "version" => version,
+                    "obj" => {"field" => "value2"},
+                ),
+            )
+            .await?;
+        assert_eq!(replaced, ConvexValue::Boolean(false));
+
+        let replaced = t
+            .mutation(
+                "basic:replaceObjectIfVersion",
+                assert_obj!(
+                    "id" => id.clone(),
+                    "version" => new_version,
+                    "obj" => {"field" => "value2"},
+                ),
+            )
+            .await?;
+        assert_eq!(replaced, ConvexValue::Boolean(true));
+        must_let!(let ConvexValue::Object(obj) = t.query("basic:getObject", assert_obj!("id" => id.clone())).await?);
+        assert_eq!(obj.get("field"), Some(&ConvexValue::try_from("value2")?));
+        Ok(())
+    })
+    .await
+}
+
 #[convex_macro::test_runtime]
 async fn test_query_missing_table(rt: TestRuntime) -> anyhow::Result<()> {
     UdfTest::run_test_with_isolate2(rt, async move |t: UdfTestType| {
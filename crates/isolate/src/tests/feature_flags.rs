@@ -0,0 +1,84 @@
+use common::{
+    assert_obj,
+    value::ConvexValue,
+};
+use keybroker::{
+    testing::TestUserIdentity,
+    Identity,
+    UserIdentity,
+};
+use model::feature_flags::{
+    types::FeatureFlagRule,
+    FeatureFlagsModel,
+};
+use runtime::testing::TestRuntime;
+
+use crate::test_helpers::{
+    UdfTest,
+    UdfTestType,
+};
+
+async fn set_flag(t: &UdfTestType, name: &str, rule: FeatureFlagRule) -> anyhow::Result<()> {
+    let mut tx = t.database.begin(Identity::system()).await?;
+    FeatureFlagsModel::new(&mut tx)
+        .set(name.to_string(), rule)
+        .await?;
+    t.database.commit(tx).await?;
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_get_feature_flag(rt: TestRuntime) -> anyhow::Result<()> {
+    UdfTest::run_test_with_isolate(rt, async |t| {
+        // Flags that don't exist are off.
+        let value = t
+            .query("featureFlags:isEnabled", assert_obj!("name" => "beta"))
+            .await?;
+        assert_eq!(value, ConvexValue::Boolean(false));
+
+        set_flag(&t, "beta", FeatureFlagRule::Boolean { enabled: true }).await?;
+        let (value, outcome) = t
+            .query_outcome(
+                "featureFlags:isEnabled",
+                assert_obj!("name" => "beta"),
+                Identity::system(),
+            )
+            .await?;
+        assert_eq!(value, ConvexValue::Boolean(true));
+        // Boolean flags are the same for everyone, so the result can be
+        // cached across users.
+        assert!(!outcome.observed_identity);
+        let value = t
+            .mutation(
+                "featureFlags:isEnabledInMutation",
+                assert_obj!("name" => "beta"),
+            )
+            .await?;
+        assert_eq!(value, ConvexValue::Boolean(true));
+
+        set_flag(
+            &t,
+            "beta",
+            FeatureFlagRule::Targeted {
+                claim: "email".to_string(),
+                values: vec!["foo@bar.com".to_string()],
+            },
+        )
+        .await?;
+        let (value, outcome) = t
+            .query_outcome(
+                "featureFlags:isEnabled",
+                assert_obj!("name" => "beta"),
+                Identity::user(UserIdentity::test()),
+            )
+            .await?;
+        assert_eq!(value, ConvexValue::Boolean(true));
+        assert!(outcome.observed_identity);
+        let value = t
+            .query("featureFlags:isEnabled", assert_obj!("name" => "beta"))
+            .await?;
+        assert_eq!(value, ConvexValue::Boolean(false));
+        Ok(())
+    })
+    .await
+}
@@ -1,5 +1,6 @@
 use std::{
     assert_matches::assert_matches,
+    collections::HashSet,
     time::Duration,
 };
 
@@ -24,9 +25,15 @@ use http::{
 };
 use itertools::Itertools;
 use keybroker::Identity;
-use model::scheduled_jobs::{
-    types::ScheduledJobState,
-    virtual_table::PublicScheduledJob,
+use model::{
+    environment_variables::{
+        types::EnvironmentVariable,
+        EnvironmentVariablesModel,
+    },
+    scheduled_jobs::{
+        types::ScheduledJobState,
+        virtual_table::PublicScheduledJob,
+    },
 };
 use must_let::must_let;
 use runtime::{
@@ -198,6 +205,23 @@ async fn test_http_echo(rt: TestRuntime) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn test_http_serve_stored_file(rt: TestRuntime) -> anyhow::Result<()> {
+    let t = http_action_udf_test(rt).await?;
+
+    let response = t
+        .http_action(
+            "http_action",
+            http_post_request("store_and_serve", "stored".as_bytes().to_vec()),
+            Identity::system(),
+        )
+        .await?;
+
+    must_let!(let Some(value) = response.body().clone());
+    assert_eq!(std::str::from_utf8(&value)?, "stored");
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn test_http_scheduler(rt: TestRuntime) -> anyhow::Result<()> {
     let t = http_action_udf_test(rt.clone()).await?;
@@ -432,3 +456,125 @@ async fn test_http_streaming(rt: TestRuntime) -> anyhow::Result<()> {
     );
     Ok(())
 }
+
+#[convex_macro::test_runtime]
+async fn test_http_webhook_verification(rt: TestRuntime) -> anyhow::Result<()> {
+    let t = http_action_udf_test(rt).await?;
+    let mut tx = t.database.begin(Identity::system()).await?;
+    EnvironmentVariablesModel::new(&mut tx)
+        .create(
+            EnvironmentVariable::new(
+                "GITHUB_WEBHOOK_SECRET".parse()?,
+                "It's a Secret to Everybody".parse()?,
+            ),
+            &HashSet::new(),
+        )
+        .await?;
+    t.database.commit(tx).await?;
+
+    // The signature from GitHub's documentation for this secret and body.
+    let signed_request = |signature: &str, delivery: &str| {
+        let mut request = http_post_request("githubWebhook", b"Hello, World!".to_vec());
+        let headers = &mut request.head.headers;
+        headers.insert("x-hub-signature-256", signature.parse().unwrap());
+        headers.insert("x-github-delivery", delivery.parse().unwrap());
+        request
+    };
+    let delivery = "72d3162e-cc78-11e3-81ab-4c9367dc0958";
+    let valid = "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+
+    let response = t
+        .http_action(
+            "http_action",
+            http_post_request("githubWebhook", b"Hello, World!".to_vec()),
+            Identity::system(),
+        )
+        .await?;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+
+    let response = t
+        .http_action(
+            "http_action",
+            signed_request(&valid.replace("757", "000"), delivery),
+            Identity::system(),
+        )
+        .await?;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+
+    let response = t
+        .http_action(
+            "http_action",
+            signed_request(valid, delivery),
+            Identity::system(),
+        )
+        .await?;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.body().as_deref(),
+        Some(&b"Received Hello, World!"[..])
+    );
+
+    // Redelivering the same webhook doesn't run the handler again, even with
+    // a different (unsigned) delivery ID.
+    let response = t
+        .http_action(
+            "http_action",
+            signed_request(valid, "a-new-delivery-id"),
+            Identity::system(),
+        )
+        .await?;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.body().as_deref(),
+        Some(&b"Webhook already received"[..])
+    );
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_http_path_params_and_middleware(rt: TestRuntime) -> anyhow::Result<()> {
+    let t = http_action_udf_test(rt).await?;
+    let request = |method: Method| {
+        let mut request = http_request("items/a%20b");
+        request.head.method = method;
+        let headers = &mut request.head.headers;
+        headers.insert("origin", "https://example.com".parse().unwrap());
+        request
+    };
+
+    let response = t
+        .http_action("http_action", request(Method::GET), Identity::system())
+        .await?;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body().as_deref(), Some(&b"Item a b"[..]));
+    assert_eq!(
+        response.headers.get("access-control-allow-origin").unwrap(),
+        "https://example.com"
+    );
+
+    // Preflight requests are answered by the GET route's `cors` middleware,
+    // before they count against its rate limit.
+    let mut preflight = request(Method::OPTIONS);
+    preflight
+        .head
+        .headers
+        .insert("access-control-request-method", "GET".parse().unwrap());
+    let response = t
+        .http_action("http_action", preflight, Identity::system())
+        .await?;
+    assert_eq!(response.status, StatusCode::NO_CONTENT);
+    assert!(response
+        .headers
+        .contains_key("access-control-allow-methods"));
+
+    let response = t
+        .http_action("http_action", request(Method::GET), Identity::system())
+        .await?;
+    assert_eq!(response.status, StatusCode::OK);
+    let response = t
+        .http_action("http_action", request(Method::GET), Identity::system())
+        .await?;
+    assert_eq!(response.status, StatusCode::TOO_MANY_REQUESTS);
+    assert!(response.headers.contains_key("retry-after"));
+    Ok(())
+}
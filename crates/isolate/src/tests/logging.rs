@@ -45,6 +45,27 @@ async fn test_log_string(rt: TestRuntime) -> anyhow::Result<()> {
     .await
 }
 
+#[convex_macro::test_runtime]
+async fn test_log_structured(rt: TestRuntime) -> anyhow::Result<()> {
+    let t = UdfTest::default(rt).await?;
+    let log_lines = t
+        .query_log_lines("logging:logStructured", assert_obj!())
+        .await?;
+    let log_lines = log_lines.into_iter().collect_vec();
+    must_let!(let [LogLine::Structured(charged), LogLine::Structured(retries)] = &log_lines[..]);
+    assert_eq!(
+        charged.fields,
+        Some(assert_obj!("amount" => 42.0, "user" => "alice"))
+    );
+    assert_eq!(
+        charged.to_pretty_string(),
+        r#"[INFO] charged {amount: 42.0, user: "alice"}"#
+    );
+    assert!(retries.messages.is_empty());
+    assert_eq!(retries.fields, Some(assert_obj!("retries" => 3i64)));
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn test_log_number(rt: TestRuntime) -> anyhow::Result<()> {
     UdfTest::run_test_with_isolate2(rt, async move |t: UdfTestType| {
@@ -9,6 +9,7 @@ mod basic;
 mod creation_time;
 mod custom_errors;
 mod environment_variables;
+mod feature_flags;
 mod fetch;
 mod globals;
 mod http_action;
@@ -20,6 +21,7 @@ mod js_builtins;
 mod logging;
 mod module_loader;
 mod query;
+mod queues;
 mod scheduler;
 mod schema;
 mod search;
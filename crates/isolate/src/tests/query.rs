@@ -136,6 +136,66 @@ async fn test_query_parallel(rt: TestRuntime) -> anyhow::Result<()> {
     }).await
 }
 
+#[convex_macro::test_runtime]
+async fn test_prefetch(rt: TestRuntime) -> anyhow::Result<()> {
+    UdfTest::run_test_with_isolate2(rt, async move |t: UdfTestType| {
+        let mut ids = Vec::new();
+        for i in 1..5 {
+            ids.push(
+                t.mutation("query:insert", assert_obj!("number" => i))
+                    .await?,
+            );
+        }
+        must_let!(let ConvexValue::Array(r) = t.query("query:prefetchThenGet", assert_obj!("ids" => ids)).await?);
+        let returned_numbers = r
+            .iter()
+            .map(|v| {
+                must_let!(let ConvexValue::Object(o) = v);
+                must_let!(let ConvexValue::Int64(i) = o.get("hello").unwrap());
+                *i
+            })
+            .collect_vec();
+        assert_eq!(returned_numbers, (1..5).collect_vec());
+        Ok(())
+    }).await
+}
+
+#[convex_macro::test_runtime]
+async fn test_group_by_parallel(rt: TestRuntime) -> anyhow::Result<()> {
+    UdfTest::run_test_with_isolate2(rt, async move |t: UdfTestType| {
+        for i in [1, 1, 3, 5, 5, 5] {
+            t.mutation("query:insert", assert_obj!("number" => i))
+                .await?;
+        }
+        let (outcome, token) = t
+            .raw_query("query:parallelGroupBy", vec![], Identity::system(), None)
+            .await?;
+        let group = |hello: i64, count: f64| {
+            assert_val!({ "key" => [hello], "values" => { "count" => count } })
+        };
+        assert_eq!(
+            outcome.result.unwrap().unpack(),
+            assert_val!([[group(1, 2.)], [group(5, 3.)]]),
+        );
+
+        // Both groupBys are awaited together, so they run as one batch...
+        assert_eq!(
+            outcome.syscall_trace.async_syscalls["1.0/groupBy"].invocations,
+            1
+        );
+        // ...and each of their ranges is still in the read set.
+        let by_hello = token
+            .reads()
+            .iter_indexed()
+            .filter(|(index, _)| index.descriptor().to_string() == "by_hello")
+            .map(|(_, reads)| reads.intervals.len())
+            .collect_vec();
+        assert_eq!(by_hello, vec![2]);
+        Ok(())
+    })
+    .await
+}
+
 #[convex_macro::test_runtime]
 async fn test_boolean_value_filters(rt: TestRuntime) -> anyhow::Result<()> {
     UdfTest::run_test_with_isolate2(rt, async move |t: UdfTestType| {
@@ -920,3 +980,31 @@ async fn test_query_with_pending_deletes(rt: TestRuntime) -> anyhow::Result<()>
     })
     .await
 }
+
+#[convex_macro::test_runtime]
+async fn test_indexes_virtual_table(rt: TestRuntime) -> anyhow::Result<()> {
+    UdfTest::run_test_with_isolate2(rt, async move |t: UdfTestType| {
+        add_index(&t).await?;
+        let find_index = |indexes: ConvexArray| {
+            indexes.into_iter().find_map(|index| {
+                must_let!(let ConvexValue::Object(index) = index);
+                (ConvexObject::get(&index, "table") == Some(&assert_val!("myTable"))
+                    && ConvexObject::get(&index, "name") == Some(&assert_val!("by_a_b")))
+                .then_some(index)
+            })
+        };
+
+        must_let!(let ConvexValue::Array(r) = t.query("indexing:listIndexes", assert_obj!()).await?);
+        let index = find_index(r).expect("myTable.by_a_b missing from _indexes");
+        assert_eq!(ConvexObject::get(&index, "type"), Some(&assert_val!("database")));
+        assert_eq!(ConvexObject::get(&index, "fields"), Some(&assert_val!(["a", "b"])));
+        assert_eq!(ConvexObject::get(&index, "state"), Some(&assert_val!("backfilling")));
+        assert_eq!(ConvexObject::get(&index, "documentsIndexed"), Some(&ConvexValue::Null));
+
+        t.backfill_indexes().await?;
+        must_let!(let ConvexValue::Array(r) = t.query("indexing:listIndexes", assert_obj!()).await?);
+        let index = find_index(r).expect("myTable.by_a_b missing from _indexes");
+        assert_ne!(ConvexObject::get(&index, "state"), Some(&assert_val!("backfilling")));
+        Ok(())
+    }).await
+}
@@ -0,0 +1,125 @@
+use common::{
+    assert_obj,
+    testing::assert_contains,
+    value::ConvexValue,
+};
+use must_let::must_let;
+use runtime::testing::TestRuntime;
+
+use crate::{
+    test_helpers::{
+        UdfTest,
+        UdfTestType,
+    },
+    tests::action::action_udf_test,
+};
+
+/// Leases from `queue` and returns the `(receipt, payload, attempts)` of each
+/// leased message.
+async fn lease(t: &UdfTestType, queue: &str) -> anyhow::Result<Vec<(String, ConvexValue, f64)>> {
+    must_let!(let ConvexValue::Array(messages) = t
+        .mutation("queues:lease", assert_obj!("queue" => queue, "maxMessages" => 10.0))
+        .await?);
+    let mut leased = vec![];
+    for message in messages.iter() {
+        must_let!(let ConvexValue::Object(message) = message);
+        must_let!(let Some(ConvexValue::String(receipt)) = message.get("receipt"));
+        must_let!(let Some(ConvexValue::Float64(attempts)) = message.get("attempts"));
+        let payload = message.get("payload").unwrap().clone();
+        leased.push((receipt.to_string(), payload, *attempts));
+    }
+    Ok(leased)
+}
+
+#[convex_macro::test_runtime]
+async fn test_queue_lease_ack_nack(rt: TestRuntime) -> anyhow::Result<()> {
+    UdfTest::run_test_with_isolate(rt, async |t| {
+        t.mutation(
+            "queues:enqueue",
+            assert_obj!("queue" => "emails", "payload" => "hello"),
+        )
+        .await?;
+        // Messages on other queues aren't leased.
+        assert!(lease(&t, "sms").await?.is_empty());
+
+        let leased = lease(&t, "emails").await?;
+        assert_eq!(leased.len(), 1);
+        let (receipt, payload, attempts) = leased[0].clone();
+        assert_eq!(payload, ConvexValue::try_from("hello")?);
+        assert_eq!(attempts, 1.0);
+        // The message is hidden while it's leased.
+        assert!(lease(&t, "emails").await?.is_empty());
+
+        // Nacking makes it visible again, with a fresh receipt.
+        t.mutation("queues:nack", assert_obj!("receipt" => receipt.clone()))
+            .await?;
+        let leased = lease(&t, "emails").await?;
+        assert_eq!(leased.len(), 1);
+        let (new_receipt, _, attempts) = leased[0].clone();
+        assert_eq!(attempts, 2.0);
+        assert_ne!(receipt, new_receipt);
+
+        // The old receipt can't be used to ack the redelivered message.
+        let e = t
+            .mutation_js_error("queues:ack", assert_obj!("receipt" => receipt))
+            .await?;
+        assert_contains(&e, "lease has expired");
+
+        t.mutation("queues:ack", assert_obj!("receipt" => new_receipt))
+            .await?;
+        t.mutation("queues:redrive", assert_obj!("queue" => "emails"))
+            .await?;
+        assert!(lease(&t, "emails").await?.is_empty());
+        Ok(())
+    })
+    .await
+}
+
+#[convex_macro::test_runtime]
+async fn test_queue_dead_letter_and_redrive(rt: TestRuntime) -> anyhow::Result<()> {
+    UdfTest::run_test_with_isolate(rt, async |t| {
+        t.mutation(
+            "queues:enqueue",
+            assert_obj!("queue" => "jobs", "payload" => 1.0, "maxAttempts" => 1.0),
+        )
+        .await?;
+        let leased = lease(&t, "jobs").await?;
+        assert_eq!(leased.len(), 1);
+        t.mutation("queues:nack", assert_obj!("receipt" => leased[0].0.clone()))
+            .await?;
+
+        // The message has used up its attempts, so it's dead lettered rather
+        // than leased again.
+        assert!(lease(&t, "jobs").await?.is_empty());
+
+        let redriven = t
+            .mutation("queues:redrive", assert_obj!("queue" => "jobs"))
+            .await?;
+        assert_eq!(redriven, ConvexValue::Float64(1.0));
+        let leased = lease(&t, "jobs").await?;
+        assert_eq!(leased.len(), 1);
+        assert_eq!(leased[0].1, ConvexValue::Float64(1.0));
+        assert_eq!(leased[0].2, 1.0);
+        Ok(())
+    })
+    .await
+}
+
+#[convex_macro::test_runtime]
+async fn test_queue_lease_in_action(rt: TestRuntime) -> anyhow::Result<()> {
+    let t = action_udf_test(rt).await?;
+    for i in 0..3 {
+        t.mutation(
+            "queues:enqueue",
+            assert_obj!("queue" => "jobs", "payload" => i as f64),
+        )
+        .await?;
+    }
+    must_let!(let ConvexValue::Array(payloads) = t
+        .action("queues:leaseAndAckInAction", assert_obj!("queue" => "jobs"))
+        .await?);
+    assert_eq!(payloads.len(), 3);
+    // Acked messages aren't redelivered.
+    assert!(lease(&t, "jobs").await?.is_empty());
+    Ok(())
+}
@@ -196,6 +196,25 @@ async fn test_schedule_missing_function(rt: TestRuntime) -> anyhow::Result<()> {
     .await
 }
 
+#[convex_macro::test_runtime]
+async fn test_schedule_invalid_args(rt: TestRuntime) -> anyhow::Result<()> {
+    UdfTest::run_test_with_isolate2(rt, async move |t: UdfTestType| {
+        let err = t
+            .mutation_js_error("scheduler:scheduleWithInvalidArgs", assert_obj!())
+            .await?;
+        assert_contains(
+            &err,
+            "Attempted to schedule scheduler:incrementBy with invalid arguments. \
+             ArgumentValidationError",
+        );
+        let result = t.query("scheduler:getScheduledJobs", assert_obj!()).await?;
+        must_let!(let ConvexValue::Array(scheduled_jobs) = result);
+        assert!(scheduled_jobs.is_empty());
+        Ok(())
+    })
+    .await
+}
+
 #[convex_macro::test_runtime]
 async fn test_schedule_too_many(rt: TestRuntime) -> anyhow::Result<()> {
     UdfTest::run_test_with_isolate2(rt, async move |t: UdfTestType| {
@@ -223,6 +242,48 @@ async fn test_schedule_too_many(rt: TestRuntime) -> anyhow::Result<()> {
     .await
 }
 
+#[convex_macro::test_runtime]
+async fn test_schedule_with_idempotency_key(rt: TestRuntime) -> anyhow::Result<()> {
+    UdfTest::run_test_with_isolate2(rt, async move |t: UdfTestType| {
+        let first = t
+            .mutation(
+                "scheduler:scheduleWithIdempotencyKey",
+                assert_obj!("idempotencyKey" => "key", "obj" => {"a" => 1.0}),
+            )
+            .await?;
+        // Retrying with the same key returns the same job.
+        let retried = t
+            .mutation(
+                "scheduler:scheduleWithIdempotencyKey",
+                assert_obj!("idempotencyKey" => "key", "obj" => {"a" => 1.0}),
+            )
+            .await?;
+        assert_eq!(first, retried);
+        let other = t
+            .mutation(
+                "scheduler:scheduleWithIdempotencyKey",
+                assert_obj!("idempotencyKey" => "other key", "obj" => {"a" => 1.0}),
+            )
+            .await?;
+        assert_ne!(first, other);
+
+        let result = t.query("scheduler:getScheduledJobs", assert_obj!()).await?;
+        must_let!(let ConvexValue::Array(scheduled_jobs) = result);
+        assert_eq!(scheduled_jobs.len(), 2);
+
+        // Reusing a key with different arguments is an error.
+        let err = t
+            .mutation_js_error(
+                "scheduler:scheduleWithIdempotencyKey",
+                assert_obj!("idempotencyKey" => "key", "obj" => {"a" => 2.0}),
+            )
+            .await?;
+        assert_contains(&err, "was already used to schedule");
+        Ok(())
+    })
+    .await
+}
+
 #[convex_macro::test_runtime]
 async fn test_schedule_many(rt: TestRuntime) -> anyhow::Result<()> {
     UdfTest::run_test_with_isolate2(rt, async move |t: UdfTestType| {
@@ -270,7 +270,10 @@ async fn test_eval_schema(rt: TestRuntime) -> anyhow::Result<()> {
                     "union" => FieldValidator::required_field_type(Validator::Union(vec![Validator::String, Validator::Float64])),
                     "object" => FieldValidator::required_field_type(Validator::Object(object_validator!("a" => FieldValidator::optional_field_type(Validator::Any))))
                   )
-                ]))
+                ])),
+                defaults: Default::default(),
+                mutability: Default::default(),
+                checks: vec![],
             },
             name2.clone() => TableDefinition {
                 table_name: name2,
@@ -278,15 +281,20 @@ async fn test_eval_schema(rt: TestRuntime) -> anyhow::Result<()> {
                     by_email.clone() => IndexSchema {
                         index_descriptor: by_email,
                         fields: vec!["email".parse()?].try_into()?,
+                        variant: None,
                     },
                     by_creation_deleted.clone() => IndexSchema {
                         index_descriptor: by_creation_deleted,
                         fields: vec!["creation".parse()?, "deleted".parse()?].try_into()?,
+                        variant: None,
                     },
                 ),
                 search_indexes: btreemap!(),
                 vector_indexes: btreemap!(),
                 document_type: None,
+                defaults: Default::default(),
+                mutability: Default::default(),
+                checks: vec![],
             },
             name3.clone() => TableDefinition {
               table_name: name3,
@@ -295,11 +303,17 @@ async fn test_eval_schema(rt: TestRuntime) -> anyhow::Result<()> {
                 search_index.clone() => SearchIndexSchema::new(
                   search_index,
                   "title".parse()?,
-                  btreeset!{"is_deleted".parse()?, "workspace_id".parse()?}
+                  btreeset!{},
+                  btreemap!{},
+                  btreeset!{"is_deleted".parse()?, "workspace_id".parse()?},
+                  Default::default(),
                 )?
                },
                vector_indexes: btreemap!(),
                document_type: None,
+              defaults: Default::default(),
+              mutability: Default::default(),
+              checks: vec![],
           }
         ),
         schema_validation: true,
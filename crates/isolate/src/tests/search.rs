@@ -5,7 +5,10 @@ use std::{
 
 use common::{
     assert_obj,
-    bootstrap_model::index::IndexMetadata,
+    bootstrap_model::index::{
+        text_index::DeveloperTextIndexConfig,
+        IndexMetadata,
+    },
     testing::{
         assert_contains,
         TestPersistence,
@@ -38,8 +41,7 @@ use crate::{
 async fn add_text_index(t: &UdfTest<TestRuntime, TestPersistence>) -> anyhow::Result<()> {
     t.add_index(IndexMetadata::new_backfilling_text_index(
         "messages.by_body".parse()?,
-        "body".parse()?,
-        btreeset! { "filterField".parse()?},
+        DeveloperTextIndexConfig::new("body".parse()?, btreeset! { "filterField".parse()?}),
     ))
     .await
 }
@@ -31,6 +31,45 @@ async fn test_storage_store_get(rt: TestRuntime) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn test_storage_copy(rt: TestRuntime) -> anyhow::Result<()> {
+    let t = action_udf_test(rt).await?;
+
+    let data = ConvexValue::Bytes("data".as_bytes().to_vec().try_into()?);
+    let id = t
+        .action("storage:storeFile", assert_obj!("data" => data.clone()))
+        .await?;
+    let copy_id = t
+        .action("storage:copyFile", assert_obj!("id" => id.clone()))
+        .await?;
+    assert_ne!(id, copy_id);
+
+    let retrieved = t
+        .action("storage:getFile", assert_obj!("id" => copy_id))
+        .await?;
+    assert_eq!(data, retrieved);
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_storage_blob_passed_by_reference(rt: TestRuntime) -> anyhow::Result<()> {
+    let t = action_udf_test(rt).await?;
+
+    let data = ConvexValue::Bytes("data".as_bytes().to_vec().try_into()?);
+    let id = t
+        .action("storage:storeFile", assert_obj!("data" => data))
+        .await?;
+    let err = t
+        .action_js_error("storage:readCopiedBlob", assert_obj!("id" => id))
+        .await?;
+    assert!(
+        err.message.contains("can no longer be read"),
+        "{}",
+        err.message
+    );
+    Ok(())
+}
+
 async fn check_storage_url(
     t: &UdfTest<TestRuntime, TestPersistence>,
     url: &ConvexValue,
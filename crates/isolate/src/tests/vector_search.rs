@@ -1,7 +1,11 @@
 use common::{
     assert_obj,
     bootstrap_model::index::{
-        vector_index::VectorDimensions,
+        text_index::DeveloperTextIndexConfig,
+        vector_index::{
+            DeveloperVectorIndexConfig,
+            VectorDimensions,
+        },
         IndexMetadata,
     },
     testing::{
@@ -28,9 +32,15 @@ async fn add_vector_index(t: &UdfTest<TestRuntime, TestPersistence>) -> anyhow::
     let mut tx = t.database.begin(Identity::system()).await?;
     let index = IndexMetadata::new_backfilling_vector_index(
         "vectorTable.vector".parse()?,
-        "vector".parse()?,
-        VectorDimensions::try_from(4)?,
-        btreeset! { "filterA".parse()?, "filterB".parse()? },
+        DeveloperVectorIndexConfig {
+            dimensions: VectorDimensions::try_from(4)?,
+            vector_field: "vector".parse()?,
+            filter_fields: btreeset! { "filterA".parse()?, "filterB".parse()? },
+            partition_field: None,
+            distance_metric: Default::default(),
+            index_kind: Default::default(),
+            quantization: Default::default(),
+        },
     );
     IndexModel::new(&mut tx)
         .add_application_index(TableNamespace::test_user(), index)
@@ -121,3 +131,25 @@ async fn test_no_filter(rt: TestRuntime) -> anyhow::Result<()> {
     assert_eq!(String::from(r), "success".to_string());
     Ok(())
 }
+
+#[convex_macro::test_runtime]
+async fn test_hybrid_search(rt: TestRuntime) -> anyhow::Result<()> {
+    common::testing::init_test_logging();
+
+    let t = action_udf_test(rt).await?;
+
+    add_and_backfill_vector_index(&t).await?;
+    t.add_index(IndexMetadata::new_backfilling_text_index(
+        "vectorTable.text".parse()?,
+        DeveloperTextIndexConfig::new("id".parse()?, btreeset! { "filterA".parse()? }),
+    ))
+    .await?;
+    t.backfill_text_indexes().await?;
+    t.mutation("vector_search:populate", assert_obj!()).await?;
+
+    must_let!(let ConvexValue::String(r) = t.action("vector_search:hybridSearch", assert_obj!()).await?);
+    assert_eq!(String::from(r), "success".to_string());
+    must_let!(let ConvexValue::String(r) = t.action("vector_search:hybridSearchWeighted", assert_obj!()).await?);
+    assert_eq!(String::from(r), "success".to_string());
+    Ok(())
+}
@@ -0,0 +1,357 @@
+//! Streaming encryption for snapshot export archives.
+//!
+//! Archives can be many gigabytes, so they're sealed in fixed-size chunks with
+//! AES-256-GCM rather than as a single message. Each chunk's nonce is a random
+//! per-archive prefix, the chunk's index, and whether it's the final chunk, so
+//! chunks can't be reordered, dropped, or truncated without failing to
+//! decrypt.
+//!
+//! Layout: `MAGIC || nonce prefix || chunk*`, where every chunk but the last
+//! holds `ARCHIVE_CHUNK_SIZE` bytes of plaintext and the last holds fewer
+//! (possibly zero). Each chunk is followed by its tag.
+
+use std::{
+    fmt,
+    str::FromStr,
+};
+
+use anyhow::Context;
+use aws_lc_rs::{
+    aead,
+    rand::{
+        SecureRandom,
+        SystemRandom,
+    },
+};
+
+const AEAD_ALGORITHM: &aead::Algorithm = &aead::AES_256_GCM;
+const KEY_LEN: usize = 32;
+const NONCE_PREFIX_LEN: usize = 7;
+const TAG_LEN: usize = 16;
+
+/// Identifies an encrypted archive, so imports can tell them apart from
+/// plain zip files.
+pub const ARCHIVE_MAGIC: &[u8; 8] = b"CVXENC1\0";
+pub const ARCHIVE_HEADER_LEN: usize = ARCHIVE_MAGIC.len() + NONCE_PREFIX_LEN;
+pub const ARCHIVE_CHUNK_SIZE: usize = 64 * 1024;
+const SEALED_CHUNK_SIZE: usize = ARCHIVE_CHUNK_SIZE + TAG_LEN;
+
+#[test]
+fn test_lens() {
+    assert_eq!(KEY_LEN, AEAD_ALGORITHM.key_len());
+    assert_eq!(TAG_LEN, AEAD_ALGORITHM.tag_len());
+    assert_eq!(NONCE_PREFIX_LEN + 4 + 1, aead::NONCE_LEN);
+}
+
+/// A deployment-provided key for export archives, written as 64 hex
+/// characters.
+#[derive(Clone)]
+pub struct ArchiveKey([u8; KEY_LEN]);
+
+impl ArchiveKey {
+    pub fn random() -> Self {
+        let mut key = [0; KEY_LEN];
+        SystemRandom::new()
+            .fill(&mut key)
+            .expect("SystemRandom failed");
+        Self(key)
+    }
+
+    fn aead_key(&self) -> aead::LessSafeKey {
+        aead::LessSafeKey::new(
+            aead::UnboundKey::new(AEAD_ALGORITHM, &self.0)
+                .expect("KEY_LEN == AEAD_ALGORITHM.key_len()"),
+        )
+    }
+}
+
+impl FromStr for ArchiveKey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let key = hex::decode(s.trim()).context("Archive key must be hex encoded")?;
+        let key: [u8; KEY_LEN] = key
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("Archive key must be {KEY_LEN} bytes"))?;
+        Ok(Self(key))
+    }
+}
+
+impl fmt::Display for ArchiveKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
+
+impl fmt::Debug for ArchiveKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ArchiveKey(..)")
+    }
+}
+
+fn chunk_nonce(prefix: &[u8; NONCE_PREFIX_LEN], index: u32, last: bool) -> aead::Nonce {
+    let mut nonce = [0; aead::NONCE_LEN];
+    nonce[..NONCE_PREFIX_LEN].copy_from_slice(prefix);
+    nonce[NONCE_PREFIX_LEN..NONCE_PREFIX_LEN + 4].copy_from_slice(&index.to_be_bytes());
+    nonce[aead::NONCE_LEN - 1] = last as u8;
+    aead::Nonce::assume_unique_for_key(nonce)
+}
+
+pub struct ArchiveEncryptor {
+    key: aead::LessSafeKey,
+    nonce_prefix: [u8; NONCE_PREFIX_LEN],
+    header: Vec<u8>,
+    next_index: u32,
+    buffer: Vec<u8>,
+}
+
+impl ArchiveEncryptor {
+    pub fn new(key: &ArchiveKey) -> Self {
+        let mut nonce_prefix = [0; NONCE_PREFIX_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce_prefix)
+            .expect("SystemRandom failed");
+        let mut header = ARCHIVE_MAGIC.to_vec();
+        header.extend_from_slice(&nonce_prefix);
+        Self {
+            key: key.aead_key(),
+            nonce_prefix,
+            header,
+            next_index: 0,
+            buffer: Vec::with_capacity(SEALED_CHUNK_SIZE),
+        }
+    }
+
+    /// The bytes that start the archive. Write these before any output from
+    /// `update`.
+    pub fn header(&self) -> &[u8] {
+        &self.header
+    }
+
+    /// Encrypt more of the archive, returning any chunks that are complete.
+    pub fn update(&mut self, mut data: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let mut out = vec![];
+        while !data.is_empty() {
+            // Only seal a full chunk once more data arrives, since a full
+            // chunk at the end of the archive is followed by an empty final
+            // chunk.
+            if self.buffer.len() == ARCHIVE_CHUNK_SIZE {
+                self.seal_chunk(false, &mut out)?;
+            }
+            let n = (ARCHIVE_CHUNK_SIZE - self.buffer.len()).min(data.len());
+            self.buffer.extend_from_slice(&data[..n]);
+            data = &data[n..];
+        }
+        Ok(out)
+    }
+
+    /// Seal the final chunk.
+    pub fn finish(mut self) -> anyhow::Result<Vec<u8>> {
+        let mut out = vec![];
+        if self.buffer.len() == ARCHIVE_CHUNK_SIZE {
+            self.seal_chunk(false, &mut out)?;
+        }
+        self.seal_chunk(true, &mut out)?;
+        Ok(out)
+    }
+
+    fn seal_chunk(&mut self, last: bool, out: &mut Vec<u8>) -> anyhow::Result<()> {
+        let nonce = chunk_nonce(&self.nonce_prefix, self.next_index, last);
+        self.next_index = self
+            .next_index
+            .checked_add(1)
+            .context("Archive has too many chunks")?;
+        let tag = self
+            .key
+            .seal_in_place_separate_tag(nonce, aead::Aad::from(&self.header), &mut self.buffer)
+            .map_err(|_| anyhow::anyhow!("Failed to encrypt archive chunk"))?;
+        out.extend_from_slice(&self.buffer);
+        out.extend_from_slice(tag.as_ref());
+        self.buffer.clear();
+        Ok(())
+    }
+}
+
+pub struct ArchiveDecryptor {
+    key: aead::LessSafeKey,
+    header: Vec<u8>,
+    next_index: u32,
+    buffer: Vec<u8>,
+}
+
+impl ArchiveDecryptor {
+    pub fn new(key: &ArchiveKey) -> Self {
+        Self {
+            key: key.aead_key(),
+            header: Vec::with_capacity(ARCHIVE_HEADER_LEN),
+            next_index: 0,
+            buffer: Vec::with_capacity(SEALED_CHUNK_SIZE),
+        }
+    }
+
+    /// Whether `prefix`, the start of a file, looks like an encrypted archive.
+    pub fn is_encrypted(prefix: &[u8]) -> bool {
+        prefix.starts_with(ARCHIVE_MAGIC)
+    }
+
+    /// The plaintext size of an encrypted archive that's `len` bytes long.
+    pub fn plaintext_len(len: u64) -> anyhow::Result<u64> {
+        let body = len
+            .checked_sub(ARCHIVE_HEADER_LEN as u64)
+            .context("Encrypted archive is truncated")?;
+        let full_chunks = body / SEALED_CHUNK_SIZE as u64;
+        let last_chunk = body % SEALED_CHUNK_SIZE as u64;
+        anyhow::ensure!(
+            last_chunk >= TAG_LEN as u64,
+            "Encrypted archive is truncated"
+        );
+        Ok(full_chunks * ARCHIVE_CHUNK_SIZE as u64 + last_chunk - TAG_LEN as u64)
+    }
+
+    /// Decrypt more of the archive, returning plaintext for any chunks that
+    /// are complete. Plaintext is only returned once its chunk is
+    /// authenticated.
+    pub fn update(&mut self, mut data: &[u8]) -> anyhow::Result<Vec<u8>> {
+        if self.header.len() < ARCHIVE_HEADER_LEN {
+            let n = (ARCHIVE_HEADER_LEN - self.header.len()).min(data.len());
+            self.header.extend_from_slice(&data[..n]);
+            data = &data[n..];
+            if self.header.len() >= ARCHIVE_MAGIC.len() {
+                anyhow::ensure!(Self::is_encrypted(&self.header), "Not an encrypted archive");
+            }
+        }
+        let mut out = vec![];
+        while !data.is_empty() {
+            let n = (SEALED_CHUNK_SIZE - self.buffer.len()).min(data.len());
+            self.buffer.extend_from_slice(&data[..n]);
+            data = &data[n..];
+            // The final chunk is always shorter than a sealed full chunk, so a
+            // full buffer can't be the end of the archive.
+            if self.buffer.len() == SEALED_CHUNK_SIZE {
+                self.open_chunk(false, &mut out)?;
+            }
+        }
+        Ok(out)
+    }
+
+    /// Decrypt the final chunk, failing if the archive was truncated.
+    pub fn finish(mut self) -> anyhow::Result<Vec<u8>> {
+        anyhow::ensure!(
+            self.header.len() == ARCHIVE_HEADER_LEN,
+            "Encrypted archive is truncated"
+        );
+        let mut out = vec![];
+        self.open_chunk(true, &mut out)?;
+        Ok(out)
+    }
+
+    fn open_chunk(&mut self, last: bool, out: &mut Vec<u8>) -> anyhow::Result<()> {
+        let nonce_prefix: [u8; NONCE_PREFIX_LEN] = self.header[ARCHIVE_MAGIC.len()..]
+            .try_into()
+            .context("Encrypted archive is truncated")?;
+        let nonce = chunk_nonce(&nonce_prefix, self.next_index, last);
+        self.next_index = self
+            .next_index
+            .checked_add(1)
+            .context("Archive has too many chunks")?;
+        let plaintext = self
+            .key
+            .open_in_place(nonce, aead::Aad::from(&self.header), &mut self.buffer)
+            .map_err(|_| {
+                anyhow::anyhow!("Failed to decrypt archive: wrong key, or the archive is corrupted")
+            })?;
+        out.extend_from_slice(plaintext);
+        self.buffer.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::testing::assert_contains;
+
+    use super::{
+        ArchiveDecryptor,
+        ArchiveEncryptor,
+        ArchiveKey,
+        ARCHIVE_CHUNK_SIZE,
+    };
+
+    fn encrypt(key: &ArchiveKey, plaintext: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let mut encryptor = ArchiveEncryptor::new(key);
+        let mut out = encryptor.header().to_vec();
+        // Feed uneven pieces to exercise buffering.
+        for piece in plaintext.chunks(1000) {
+            out.extend(encryptor.update(piece)?);
+        }
+        out.extend(encryptor.finish()?);
+        Ok(out)
+    }
+
+    fn decrypt(key: &ArchiveKey, ciphertext: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let mut decryptor = ArchiveDecryptor::new(key);
+        let mut out = vec![];
+        for piece in ciphertext.chunks(777) {
+            out.extend(decryptor.update(piece)?);
+        }
+        out.extend(decryptor.finish()?);
+        Ok(out)
+    }
+
+    #[test]
+    fn test_round_trip() -> anyhow::Result<()> {
+        let key = ArchiveKey::random();
+        for len in [
+            0,
+            1,
+            ARCHIVE_CHUNK_SIZE - 1,
+            ARCHIVE_CHUNK_SIZE,
+            ARCHIVE_CHUNK_SIZE + 1,
+            3 * ARCHIVE_CHUNK_SIZE,
+        ] {
+            let plaintext: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            let ciphertext = encrypt(&key, &plaintext)?;
+            assert!(ArchiveDecryptor::is_encrypted(&ciphertext));
+            assert_eq!(
+                ArchiveDecryptor::plaintext_len(ciphertext.len() as u64)?,
+                len as u64
+            );
+            assert_eq!(decrypt(&key, &ciphertext)?, plaintext);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_tampering_is_detected() -> anyhow::Result<()> {
+        let key = ArchiveKey::random();
+        let plaintext = vec![7; 2 * ARCHIVE_CHUNK_SIZE + 10];
+        let ciphertext = encrypt(&key, &plaintext)?;
+
+        assert_contains(
+            &decrypt(&ArchiveKey::random(), &ciphertext).unwrap_err(),
+            "Failed to decrypt archive",
+        );
+
+        let mut flipped = ciphertext.clone();
+        flipped[ciphertext.len() / 2] ^= 1;
+        assert_contains(
+            &decrypt(&key, &flipped).unwrap_err(),
+            "Failed to decrypt archive",
+        );
+
+        // Dropping the final chunk leaves a full chunk at the end, which
+        // can't open as the last one.
+        let truncated = &ciphertext[..ciphertext.len() - 10 - 16];
+        assert!(decrypt(&key, truncated).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_key() -> anyhow::Result<()> {
+        let key = ArchiveKey::random();
+        let parsed: ArchiveKey = key.to_string().parse()?;
+        assert_eq!(parsed.to_string(), key.to_string());
+        assert!("abcd".parse::<ArchiveKey>().is_err());
+        Ok(())
+    }
+}
@@ -97,6 +97,7 @@ use sync_types::{
 #[cfg(any(test, feature = "testing"))]
 use crate::testing::TestUserIdentity;
 use crate::{
+    data_masking_key::DataMaskingKey,
     encryptor::{
         DeterministicEncryptor,
         Purpose,
@@ -107,6 +108,7 @@ use crate::{
         log_actions_token_expired,
         log_store_file_auth_expired,
     },
+    report_signature::ReportSigner,
     secret::InstanceSecret,
 };
 
@@ -128,6 +130,8 @@ pub struct KeyBroker {
     cursor_encryptor: DeterministicEncryptor,
     journal_encryptor: RandomEncryptor,
     store_file_encryptor: RandomEncryptor,
+    erasure_report_signer: ReportSigner,
+    data_masking_key: DataMaskingKey,
 }
 
 // This enum encodes a successful authentication decision, and its nontrivial
@@ -803,6 +807,11 @@ impl KeyBroker {
                 &instance_secret,
                 Purpose::STORE_FILE_AUTHORIZATION,
             )?,
+            erasure_report_signer: ReportSigner::derive_from_secret(
+                &instance_secret,
+                "erasure report",
+            )?,
+            data_masking_key: DataMaskingKey::derive_from_secret(&instance_secret)?,
         })
     }
 
@@ -822,6 +831,20 @@ impl KeyBroker {
         .unwrap()
     }
 
+    /// Signs the canonical encoding of a data erasure completion report.
+    pub fn sign_erasure_report(&self, content: &[u8]) -> String {
+        self.erasure_report_signer.sign(content)
+    }
+
+    pub fn verify_erasure_report(&self, content: &[u8], signature: &str) -> bool {
+        self.erasure_report_signer.verify(content, signature)
+    }
+
+    /// The key for the `hash` data masking strategy.
+    pub fn data_masking_key(&self) -> DataMaskingKey {
+        self.data_masking_key.clone()
+    }
+
     pub fn issue_admin_key(&self, member_id: MemberId) -> AdminKey {
         AdminKey::new(self.issue_key(Some(member_id), false))
     }
@@ -0,0 +1,53 @@
+//! The key for the `hash` data masking strategy. Masked values are
+//! HMAC-SHA256 tags keyed by a secret derived from the instance secret, so
+//! equal inputs still hash equally within a deployment but low-entropy values
+//! like emails or phone numbers can't be recovered by hashing guesses.
+
+use crate::{
+    report_signature::ReportSigner,
+    Secret,
+};
+
+#[derive(Clone)]
+pub struct DataMaskingKey {
+    signer: ReportSigner,
+}
+
+impl DataMaskingKey {
+    pub fn derive_from_secret(secret: &Secret) -> anyhow::Result<Self> {
+        Ok(Self {
+            signer: ReportSigner::derive_from_secret(secret, "data masking")?,
+        })
+    }
+
+    /// The hex-encoded keyed hash of `content`.
+    pub fn hash(&self, content: &[u8]) -> String {
+        self.signer.sign(content)
+    }
+}
+
+impl std::fmt::Debug for DataMaskingKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DataMaskingKey").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DataMaskingKey;
+    use crate::{
+        InstanceSecret,
+        DEV_SECRET,
+    };
+
+    #[test]
+    fn test_hash_is_keyed() -> anyhow::Result<()> {
+        let key = DataMaskingKey::derive_from_secret(&InstanceSecret::try_from(DEV_SECRET)?)?;
+        assert_eq!(key.hash(b"ada@example.com"), key.hash(b"ada@example.com"));
+        assert_ne!(key.hash(b"ada@example.com"), key.hash(b"bob@example.com"));
+
+        let other = DataMaskingKey::derive_from_secret(&InstanceSecret::random())?;
+        assert_ne!(key.hash(b"ada@example.com"), other.hash(b"ada@example.com"));
+        Ok(())
+    }
+}
@@ -2,20 +2,31 @@
 #![feature(type_alias_impl_trait)]
 #![feature(impl_trait_in_assoc_type)]
 
+mod archive_encryption;
 mod broker;
+mod data_masking_key;
 mod encryptor;
 mod legacy_encryptor;
 mod metrics;
+mod report_signature;
 mod secret;
 #[cfg(any(test, feature = "testing"))]
 pub mod testing;
 
 #[cfg(test)]
 mod tests;
+mod webhook_signature;
+mod webhook_verification;
 
 pub use sync_types::UserIdentityAttributes;
 
 pub use self::{
+    archive_encryption::{
+        ArchiveDecryptor,
+        ArchiveEncryptor,
+        ArchiveKey,
+        ARCHIVE_MAGIC,
+    },
     broker::{
         AdminIdentity,
         AdminIdentityPrincipal,
@@ -28,11 +39,24 @@ pub use self::{
         SystemKey,
         UserIdentity,
     },
+    data_masking_key::DataMaskingKey,
     legacy_encryptor::LegacyEncryptor,
     secret::{
         InstanceSecret,
         Secret,
     },
+    webhook_signature::{
+        WebhookSecret,
+        WEBHOOK_ID_HEADER,
+        WEBHOOK_SIGNATURE_HEADER,
+        WEBHOOK_TIMESTAMP_HEADER,
+    },
+    webhook_verification::{
+        verify_webhook,
+        VerifiedWebhook,
+        WebhookScheme,
+        WebhookVerificationError,
+    },
 };
 
 pub const DEV_INSTANCE_NAME: &str = include_str!("../dev/instance_name.txt");
@@ -0,0 +1,74 @@
+//! HMAC-SHA256 signatures on reports the backend issues about its own
+//! actions, such as data erasure completion reports, so a report can later be
+//! checked as issued by this deployment and unmodified. The key is derived
+//! from the instance secret, so only this deployment can sign or verify.
+
+use anyhow::Context;
+use aws_lc_rs::{
+    hmac,
+    kdf,
+};
+
+use crate::Secret;
+
+const KEY_LEN: usize = 32;
+
+const KDF_ALGORITHM: &kdf::KbkdfCtrHmacAlgorithm =
+    kdf::get_kbkdf_ctr_hmac_algorithm(kdf::KbkdfCtrHmacAlgorithmId::Sha256).unwrap();
+
+#[derive(Clone)]
+pub struct ReportSigner {
+    key: hmac::Key,
+}
+
+impl ReportSigner {
+    /// `purpose` must be distinct for each kind of report.
+    pub fn derive_from_secret(secret: &Secret, purpose: &'static str) -> anyhow::Result<Self> {
+        let mut derived_key = [0; KEY_LEN];
+        kdf::kbkdf_ctr_hmac(
+            KDF_ALGORITHM,
+            secret.as_bytes(),
+            purpose.as_bytes(),
+            &mut derived_key,
+        )
+        .context("KBKDF failed")?;
+        Ok(Self {
+            key: hmac::Key::new(hmac::HMAC_SHA256, &derived_key),
+        })
+    }
+
+    /// The hex-encoded signature of `content`.
+    pub fn sign(&self, content: &[u8]) -> String {
+        hex::encode(hmac::sign(&self.key, content).as_ref())
+    }
+
+    pub fn verify(&self, content: &[u8], signature: &str) -> bool {
+        let Ok(tag) = hex::decode(signature) else {
+            return false;
+        };
+        hmac::verify(&self.key, content, &tag).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReportSigner;
+    use crate::{
+        InstanceSecret,
+        DEV_SECRET,
+    };
+
+    #[test]
+    fn test_sign_and_verify() -> anyhow::Result<()> {
+        let secret = InstanceSecret::try_from(DEV_SECRET)?;
+        let signer = ReportSigner::derive_from_secret(&secret, "testing")?;
+        let signature = signer.sign(b"report");
+        assert!(signer.verify(b"report", &signature));
+        assert!(!signer.verify(b"tampered report", &signature));
+        assert!(!signer.verify(b"report", "not hex"));
+
+        let other = ReportSigner::derive_from_secret(&secret, "testing2")?;
+        assert!(!other.verify(b"report", &signature));
+        Ok(())
+    }
+}
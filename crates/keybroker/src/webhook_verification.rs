@@ -0,0 +1,412 @@
+//! Verifies signatures on webhooks sent to HTTP actions by third parties.
+//! Each scheme signs the request body (and usually a timestamp) with
+//! HMAC-SHA256 under a secret shared with the sender, and comparisons are
+//! constant time.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    str::FromStr,
+    time::Duration,
+};
+
+use aws_lc_rs::hmac;
+
+use crate::WebhookSecret;
+
+const STRIPE_SIGNATURE_HEADER: &str = "stripe-signature";
+const GITHUB_SIGNATURE_HEADER: &str = "x-hub-signature-256";
+const SVIX_ID_HEADER: &str = "svix-id";
+const SVIX_TIMESTAMP_HEADER: &str = "svix-timestamp";
+const SVIX_SIGNATURE_HEADER: &str = "svix-signature";
+
+/// How a webhook sender signs its requests.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WebhookScheme {
+    /// `Stripe-Signature: t=<timestamp>,v1=<hex>` over `{timestamp}.{body}`,
+    /// keyed by the whole `whsec_...` secret.
+    Stripe,
+    /// `X-Hub-Signature-256: sha256=<hex>` over the body. GitHub doesn't sign
+    /// a timestamp or `X-GitHub-Delivery`, so replays are caught by the
+    /// signature, which is the same for every delivery of the same body.
+    Github,
+    /// Standard Webhooks with `svix-` headers, as sent by Svix, Clerk and
+    /// Resend. Also accepts the `webhook-` headers our own webhooks send.
+    Svix,
+}
+
+impl FromStr for WebhookScheme {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "stripe" => Ok(Self::Stripe),
+            "github" => Ok(Self::Github),
+            "svix" => Ok(Self::Svix),
+            _ => anyhow::bail!("Unknown webhook scheme {s:?}, expected stripe, github or svix"),
+        }
+    }
+}
+
+/// A request whose signature checked out.
+#[derive(Debug, PartialEq, Eq)]
+pub struct VerifiedWebhook {
+    /// Identifies this delivery, for replay protection.
+    pub nonce: String,
+    /// When the sender signed the request, for schemes that sign one.
+    pub timestamp_secs: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum WebhookVerificationError {
+    MissingHeader(&'static str),
+    MalformedHeader(&'static str),
+    InvalidSecret,
+    TimestampOutOfTolerance,
+    InvalidSignature,
+}
+
+impl fmt::Display for WebhookVerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingHeader(name) => write!(f, "Missing {name} header"),
+            Self::MalformedHeader(name) => write!(f, "Malformed {name} header"),
+            Self::InvalidSecret => write!(f, "The webhook secret isn't valid for this scheme"),
+            Self::TimestampOutOfTolerance => {
+                write!(f, "The signed timestamp is too far from the current time")
+            },
+            Self::InvalidSignature => write!(f, "No signature matched the request"),
+        }
+    }
+}
+
+impl std::error::Error for WebhookVerificationError {}
+
+/// Verifies a request's signature. `headers` is keyed by lowercase header
+/// name. Signed timestamps more than `tolerance` from `now_secs` are
+/// rejected.
+pub fn verify_webhook(
+    scheme: WebhookScheme,
+    secret: &str,
+    headers: &BTreeMap<String, String>,
+    body: &[u8],
+    now_secs: u64,
+    tolerance: Duration,
+) -> Result<VerifiedWebhook, WebhookVerificationError> {
+    let header = |name: &'static str| {
+        headers
+            .get(name)
+            .map(String::as_str)
+            .ok_or(WebhookVerificationError::MissingHeader(name))
+    };
+    let check_timestamp = |timestamp_secs: u64| {
+        if now_secs.abs_diff(timestamp_secs) > tolerance.as_secs() {
+            return Err(WebhookVerificationError::TimestampOutOfTolerance);
+        }
+        Ok(())
+    };
+    match scheme {
+        WebhookScheme::Stripe => {
+            let mut timestamp = None;
+            let mut signatures = vec![];
+            for part in header(STRIPE_SIGNATURE_HEADER)?.split(',') {
+                match part.trim().split_once('=') {
+                    Some(("t", t)) => timestamp = t.parse::<u64>().ok(),
+                    Some(("v1", signature)) => signatures.push(signature),
+                    _ => {},
+                }
+            }
+            let timestamp_secs = timestamp.ok_or(WebhookVerificationError::MalformedHeader(
+                STRIPE_SIGNATURE_HEADER,
+            ))?;
+            check_timestamp(timestamp_secs)?;
+            let mut content = format!("{timestamp_secs}.").into_bytes();
+            content.extend_from_slice(body);
+            let signature = first_valid_hex(secret.as_bytes(), &content, &signatures)?;
+            Ok(VerifiedWebhook {
+                nonce: signature.to_ascii_lowercase(),
+                timestamp_secs: Some(timestamp_secs),
+            })
+        },
+        WebhookScheme::Github => {
+            let signature = header(GITHUB_SIGNATURE_HEADER)?
+                .strip_prefix("sha256=")
+                .ok_or(WebhookVerificationError::MalformedHeader(
+                    GITHUB_SIGNATURE_HEADER,
+                ))?;
+            let signature = first_valid_hex(secret.as_bytes(), body, &[signature])?;
+            Ok(VerifiedWebhook {
+                nonce: signature.to_ascii_lowercase(),
+                timestamp_secs: None,
+            })
+        },
+        WebhookScheme::Svix => {
+            let secret: WebhookSecret = secret
+                .parse()
+                .map_err(|_| WebhookVerificationError::InvalidSecret)?;
+            let (id, timestamp, signatures) = match header(SVIX_ID_HEADER) {
+                Ok(id) => (
+                    id,
+                    header(SVIX_TIMESTAMP_HEADER)?,
+                    header(SVIX_SIGNATURE_HEADER)?,
+                ),
+                Err(_) if headers.contains_key(crate::WEBHOOK_ID_HEADER) => (
+                    header(crate::WEBHOOK_ID_HEADER)?,
+                    header(crate::WEBHOOK_TIMESTAMP_HEADER)?,
+                    header(crate::WEBHOOK_SIGNATURE_HEADER)?,
+                ),
+                Err(e) => return Err(e),
+            };
+            let timestamp_secs = timestamp
+                .parse()
+                .map_err(|_| WebhookVerificationError::MalformedHeader(SVIX_TIMESTAMP_HEADER))?;
+            check_timestamp(timestamp_secs)?;
+            if !secret.verify(id, timestamp_secs, body, signatures) {
+                return Err(WebhookVerificationError::InvalidSignature);
+            }
+            Ok(VerifiedWebhook {
+                nonce: id.to_string(),
+                timestamp_secs: Some(timestamp_secs),
+            })
+        },
+    }
+}
+
+/// Returns the first hex-encoded HMAC-SHA256 tag in `signatures` that's
+/// valid for `content`. Callers lowercase it before using it as a nonce, so
+/// the same tag in a different case is still caught as a replay.
+fn first_valid_hex<'a>(
+    key: &[u8],
+    content: &[u8],
+    signatures: &[&'a str],
+) -> Result<&'a str, WebhookVerificationError> {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, key);
+    signatures
+        .iter()
+        .copied()
+        .find(|signature| {
+            hex::decode(signature).is_ok_and(|tag| hmac::verify(&key, content, &tag).is_ok())
+        })
+        .ok_or(WebhookVerificationError::InvalidSignature)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeMap,
+        time::Duration,
+    };
+
+    use aws_lc_rs::hmac;
+
+    use super::{
+        verify_webhook,
+        WebhookScheme,
+        WebhookVerificationError,
+    };
+    use crate::WebhookSecret;
+
+    const NOW: u64 = 1_700_000_000;
+    const TOLERANCE: Duration = Duration::from_secs(300);
+    const BODY: &[u8] = br#"{"id":"evt_1"}"#;
+
+    fn hex_tag(key: &[u8], content: &[u8]) -> String {
+        hex::encode(hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, key), content))
+    }
+
+    fn headers<const N: usize>(pairs: [(&str, String); N]) -> BTreeMap<String, String> {
+        pairs
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect()
+    }
+
+    #[test]
+    fn test_stripe() {
+        let secret = "whsec_test";
+        let mut content = format!("{NOW}.").into_bytes();
+        content.extend_from_slice(BODY);
+        let tag = hex_tag(secret.as_bytes(), &content);
+        let valid = headers([("stripe-signature", format!("t={NOW},v1=00ff,v1={tag}"))]);
+        let verified = verify_webhook(
+            WebhookScheme::Stripe,
+            secret,
+            &valid,
+            BODY,
+            NOW + 10,
+            TOLERANCE,
+        )
+        .unwrap();
+        assert_eq!(verified.nonce, tag);
+        assert_eq!(verified.timestamp_secs, Some(NOW));
+
+        assert_eq!(
+            verify_webhook(
+                WebhookScheme::Stripe,
+                "whsec_other",
+                &valid,
+                BODY,
+                NOW,
+                TOLERANCE
+            ),
+            Err(WebhookVerificationError::InvalidSignature)
+        );
+        assert_eq!(
+            verify_webhook(WebhookScheme::Stripe, secret, &valid, b"{}", NOW, TOLERANCE),
+            Err(WebhookVerificationError::InvalidSignature)
+        );
+        assert_eq!(
+            verify_webhook(
+                WebhookScheme::Stripe,
+                secret,
+                &valid,
+                BODY,
+                NOW + 301,
+                TOLERANCE
+            ),
+            Err(WebhookVerificationError::TimestampOutOfTolerance)
+        );
+        assert_eq!(
+            verify_webhook(
+                WebhookScheme::Stripe,
+                secret,
+                &headers([("stripe-signature", format!("v1={tag}"))]),
+                BODY,
+                NOW,
+                TOLERANCE
+            ),
+            Err(WebhookVerificationError::MalformedHeader(
+                "stripe-signature"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_github() {
+        let secret = "It's a Secret to Everybody";
+        // From GitHub's documentation on validating webhook deliveries.
+        let valid = headers([
+            (
+                "x-hub-signature-256",
+                "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17"
+                    .to_string(),
+            ),
+            (
+                "x-github-delivery",
+                "72d3162e-cc78-11e3-81ab-4c9367dc0958".to_string(),
+            ),
+        ]);
+        let verified = verify_webhook(
+            WebhookScheme::Github,
+            secret,
+            &valid,
+            b"Hello, World!",
+            NOW,
+            TOLERANCE,
+        )
+        .unwrap();
+        assert_eq!(
+            verified.nonce,
+            "757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17"
+        );
+        assert_eq!(verified.timestamp_secs, None);
+        // The delivery ID isn't signed, so it can't distinguish replays.
+        let mut redelivered = valid.clone();
+        redelivered.insert("x-github-delivery".to_string(), "forged".to_string());
+        assert_eq!(
+            verify_webhook(
+                WebhookScheme::Github,
+                secret,
+                &redelivered,
+                b"Hello, World!",
+                NOW,
+                TOLERANCE,
+            ),
+            Ok(verified)
+        );
+        assert_eq!(
+            verify_webhook(
+                WebhookScheme::Github,
+                secret,
+                &valid,
+                b"Hello",
+                NOW,
+                TOLERANCE
+            ),
+            Err(WebhookVerificationError::InvalidSignature)
+        );
+        assert_eq!(
+            verify_webhook(
+                WebhookScheme::Github,
+                secret,
+                &BTreeMap::new(),
+                b"Hello, World!",
+                NOW,
+                TOLERANCE
+            ),
+            Err(WebhookVerificationError::MissingHeader(
+                "x-hub-signature-256"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_svix() {
+        let secret = WebhookSecret::random();
+        let signature = secret.sign("msg_1", NOW, BODY);
+        let valid = headers([
+            ("svix-id", "msg_1".to_string()),
+            ("svix-timestamp", NOW.to_string()),
+            ("svix-signature", signature.clone()),
+        ]);
+        let verified = verify_webhook(
+            WebhookScheme::Svix,
+            &secret.to_string(),
+            &valid,
+            BODY,
+            NOW,
+            TOLERANCE,
+        )
+        .unwrap();
+        assert_eq!(verified.nonce, "msg_1");
+
+        // Our own outbound webhooks use the unprefixed headers.
+        let standard = headers([
+            ("webhook-id", "msg_1".to_string()),
+            ("webhook-timestamp", NOW.to_string()),
+            ("webhook-signature", signature),
+        ]);
+        assert!(verify_webhook(
+            WebhookScheme::Svix,
+            &secret.to_string(),
+            &standard,
+            BODY,
+            NOW,
+            TOLERANCE
+        )
+        .is_ok());
+
+        assert_eq!(
+            verify_webhook(
+                WebhookScheme::Svix,
+                &WebhookSecret::random().to_string(),
+                &valid,
+                BODY,
+                NOW,
+                TOLERANCE
+            ),
+            Err(WebhookVerificationError::InvalidSignature)
+        );
+        assert_eq!(
+            verify_webhook(
+                WebhookScheme::Svix,
+                "not base64!",
+                &valid,
+                BODY,
+                NOW,
+                TOLERANCE
+            ),
+            Err(WebhookVerificationError::InvalidSecret)
+        );
+    }
+}
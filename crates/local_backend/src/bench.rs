@@ -0,0 +1,55 @@
+//! Running synthetic benchmark workloads against this deployment. See
+//! `application::bench` for what is measured.
+
+use application::bench::{
+    BenchConfig,
+    BenchConfigJson,
+};
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    components::ComponentId,
+    http::{
+        extract::Json,
+        HttpResponseError,
+    },
+};
+use serde::Deserialize;
+
+use crate::{
+    admin::must_be_admin_with_write_access,
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunBenchArgs {
+    component_id: Option<String>,
+    config: BenchConfigJson,
+}
+
+/// Runs a bench to completion and responds with its report. The bench only
+/// writes to a table that is new or empty, and empties it again afterwards.
+#[debug_handler]
+pub async fn run_bench(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(RunBenchArgs {
+        component_id,
+        config,
+    }): Json<RunBenchArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let component = ComponentId::deserialize_from_string(component_id.as_deref())?;
+    let config = BenchConfig::try_from(config)?;
+    tracing::info!("Running bench {config:?}");
+    let report = st
+        .application
+        .run_bench(identity, component, config)
+        .await?;
+    Ok(Json(report))
+}
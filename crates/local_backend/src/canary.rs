@@ -0,0 +1,194 @@
+//! Canary deploys, which serve a share of the root component's traffic from a
+//! new push until it's promoted or rolled back.
+//!
+//! With `invocation` routing, each function call picks a version on its own,
+//! so the functions an action calls may run on either version. With
+//! `identity` routing, every call from the same user runs on the same version,
+//! and calls without a user run on the same version as each other.
+
+use anyhow::Context;
+use application::deploy_config::StartPushResponse;
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::http::{
+    extract::Json,
+    HttpResponseError,
+};
+use errors::ErrorMetadata;
+use http::StatusCode;
+use model::canary::types::{
+    CanaryDeployment,
+    CanaryRollbackPolicy,
+    CanaryRouting,
+    CanaryState,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::DeveloperDocumentId;
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_from_key_with_write_access,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    deploy_config2::SerializedStartPushResponse,
+    LocalAppState,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartCanaryRequest {
+    admin_key: String,
+    start_push: SerializedStartPushResponse,
+    traffic_percent: u32,
+    routing: Option<String>,
+    min_invocations: Option<u64>,
+    max_error_rate_increase: Option<f64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateCanaryTrafficArgs {
+    traffic_percent: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RollBackCanaryArgs {
+    reason: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CanaryJson {
+    source_package_id: String,
+    traffic_percent: u32,
+    routing: String,
+    min_invocations: u64,
+    max_error_rate_increase: f64,
+    state: String,
+    rolled_back_reason: Option<String>,
+    started_at_ms: i64,
+}
+
+impl From<CanaryDeployment> for CanaryJson {
+    fn from(canary: CanaryDeployment) -> Self {
+        let (state, rolled_back_reason) = match canary.state {
+            CanaryState::Active => ("active".to_string(), None),
+            CanaryState::RolledBack { reason } => ("rolledBack".to_string(), Some(reason)),
+        };
+        Self {
+            source_package_id: DeveloperDocumentId::from(canary.source_package_id).encode(),
+            traffic_percent: canary.traffic_percent,
+            routing: canary.routing.to_string(),
+            min_invocations: canary.rollback_policy.min_invocations,
+            max_error_rate_increase: canary.rollback_policy.max_error_rate_increase,
+            state,
+            rolled_back_reason,
+            started_at_ms: canary.started_at_ms,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetCanaryResponse {
+    canary: Option<CanaryJson>,
+}
+
+/// Deploys the functions from a `start_push` as a canary instead of finishing
+/// the push.
+#[debug_handler]
+pub async fn start_canary(
+    State(st): State<LocalAppState>,
+    Json(req): Json<StartCanaryRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let identity = must_be_admin_from_key_with_write_access(
+        st.application.app_auth(),
+        st.instance_name.clone(),
+        req.admin_key.clone(),
+    )
+    .await?;
+    let start_push = StartPushResponse::try_from(req.start_push)?;
+    let routing = match req.routing {
+        Some(routing) => routing.parse().context(ErrorMetadata::bad_request(
+            "InvalidCanaryRouting",
+            "Canary routing must be \"invocation\" or \"identity\".",
+        ))?,
+        None => CanaryRouting::Invocation,
+    };
+    let default_policy = CanaryRollbackPolicy::default();
+    let rollback_policy = CanaryRollbackPolicy {
+        min_invocations: req
+            .min_invocations
+            .unwrap_or(default_policy.min_invocations),
+        max_error_rate_increase: req
+            .max_error_rate_increase
+            .unwrap_or(default_policy.max_error_rate_increase),
+    };
+    st.application
+        .start_canary(
+            identity,
+            start_push,
+            req.traffic_percent,
+            routing,
+            rollback_policy,
+        )
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+/// Returns the current canary deploy, or the last one if it was rolled back.
+#[debug_handler]
+pub async fn get_canary(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let canary = st.application.get_canary(identity).await?;
+    Ok(Json(GetCanaryResponse {
+        canary: canary.map(CanaryJson::from),
+    }))
+}
+
+#[debug_handler]
+pub async fn update_canary_traffic(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(UpdateCanaryTrafficArgs { traffic_percent }): Json<UpdateCanaryTrafficArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    st.application
+        .update_canary_traffic(identity, traffic_percent)
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[debug_handler]
+pub async fn promote_canary(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    st.application.promote_canary(identity).await?;
+    Ok(StatusCode::OK)
+}
+
+#[debug_handler]
+pub async fn roll_back_canary(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(RollBackCanaryArgs { reason }): Json<RollBackCanaryArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let reason = reason.unwrap_or_else(|| "Rolled back from the dashboard.".to_string());
+    st.application.roll_back_canary(identity, reason).await?;
+    Ok(StatusCode::OK)
+}
@@ -0,0 +1,98 @@
+//! Earlier pushes of the root component's code, which the deployment can be
+//! switched back to without pushing them again.
+
+use anyhow::Context;
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    document::ParsedDocument,
+    http::{
+        extract::Json,
+        HttpResponseError,
+    },
+};
+use errors::ErrorMetadata;
+use http::StatusCode;
+use model::code_versions::types::CodeVersion;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::DeveloperDocumentId;
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RollbackCodeVersionArgs {
+    version_id: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeVersionJson {
+    id: String,
+    source_package_id: String,
+    has_schema: bool,
+    udf_server_version: String,
+    pushed_at_ms: i64,
+}
+
+impl From<ParsedDocument<CodeVersion>> for CodeVersionJson {
+    fn from(version: ParsedDocument<CodeVersion>) -> Self {
+        let id = version.developer_id().encode();
+        let version = version.into_value();
+        Self {
+            id,
+            source_package_id: DeveloperDocumentId::from(version.source_package_id).encode(),
+            has_schema: version.raw_schema.is_some(),
+            udf_server_version: version.udf_server_version.to_string(),
+            pushed_at_ms: version.pushed_at_ms,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListCodeVersionsResponse {
+    versions: Vec<CodeVersionJson>,
+}
+
+/// Lists the root component's recorded pushes, newest first.
+#[debug_handler]
+pub async fn list_code_versions(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let versions = st.application.list_code_versions(identity).await?;
+    Ok(Json(ListCodeVersionsResponse {
+        versions: versions.into_iter().map(CodeVersionJson::from).collect(),
+    }))
+}
+
+#[debug_handler]
+pub async fn rollback_code_version(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(RollbackCodeVersionArgs { version_id }): Json<RollbackCodeVersionArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let version_id = DeveloperDocumentId::decode(&version_id).context(
+        ErrorMetadata::bad_request("InvalidCodeVersionId", "Invalid code version id."),
+    )?;
+    st.application
+        .rollback_code_version(identity, version_id)
+        .await?;
+    Ok(StatusCode::OK)
+}
@@ -6,7 +6,9 @@ use common::types::{
     ConvexOrigin,
     ConvexSite,
 };
+use ipnet::IpNet;
 use keybroker::{
+    ArchiveKey,
     InstanceSecret,
     KeyBroker,
     DEV_INSTANCE_NAME,
@@ -122,6 +124,83 @@ pub struct LocalConfig {
     /// reach the client for debugging purposes.
     #[clap(long, default_value = "false")]
     pub redact_logs_to_client: bool,
+
+    /// Comma-separated CIDR ranges (e.g. `10.0.0.0/8,192.168.1.4/32`) allowed
+    /// to call the admin API. Requests from other addresses are rejected with
+    /// a 403. If unset, the admin API accepts requests from any address.
+    #[clap(long, value_delimiter = ',')]
+    pub admin_ip_allowlist: Vec<IpNet>,
+
+    /// Comma-separated CIDR ranges of the reverse proxies in front of the
+    /// backend. `X-Forwarded-For` is only used to find the client's address
+    /// when the request comes from one of these ranges; otherwise the address
+    /// of the TCP connection is used.
+    #[clap(long, value_delimiter = ',')]
+    pub trusted_proxy_cidrs: Vec<IpNet>,
+
+    /// Comma-separated origins (e.g. `https://app.example.com`) allowed to make
+    /// cross-origin requests to the client API and HTTP actions. If unset,
+    /// any origin is allowed.
+    #[clap(long, value_delimiter = ',')]
+    pub cors_allowed_origins: Option<Vec<String>>,
+
+    /// Base URL of an OpenTelemetry collector's OTLP/HTTP endpoint (e.g.
+    /// `http://localhost:4318`). If set, sampled traces and server metrics
+    /// are exported to `/v1/traces` and `/v1/metrics` under it.
+    #[clap(long)]
+    pub otlp_endpoint: Option<Url>,
+
+    /// Comma-separated `key=value` headers sent with every OTLP export, in the
+    /// format of `OTEL_EXPORTER_OTLP_HEADERS`. Values may be URL-encoded.
+    #[clap(long, value_delimiter = ',', requires = "otlp_endpoint")]
+    pub otlp_headers: Vec<String>,
+
+    /// Fraction of requests to trace, between 0 and 1. Overrides the default
+    /// fraction in `REQUEST_TRACE_SAMPLE_CONFIG`.
+    #[clap(long, requires = "otlp_endpoint")]
+    pub otlp_trace_sample_ratio: Option<f64>,
+
+    /// How often to push metrics to the OTLP endpoint, in seconds. Must be
+    /// at least 1.
+    #[clap(long, default_value = "60", value_parser = clap::value_parser!(u64).range(1..))]
+    pub otlp_metrics_interval_secs: u64,
+
+    /// If set, this backend runs as a standby while another backend holds the
+    /// leader lease in the same database, and takes over once the lease
+    /// expires. Only supported with Postgres and MySQL.
+    #[clap(long)]
+    pub leader_election: bool,
+
+    /// Comma-separated URLs of `convex-searchlight` processes (e.g.
+    /// `http://10.0.0.5:8100`) to run text and vector searches and
+    /// compactions on, instead of in this process. Requests are balanced
+    /// across them. Each searchlight must be able to read this backend's
+    /// search index storage.
+    #[clap(long, value_delimiter = ',')]
+    pub searchlight_urls: Vec<String>,
+
+    /// Shared secret sent with every request to the searchlights. Must match
+    /// their `--secret`. Required with `--searchlight-urls`.
+    #[clap(long, requires = "searchlight_urls")]
+    pub searchlight_secret: Option<String>,
+
+    /// If set, admins can seed empty tables from declarative fixtures with
+    /// `/api/load_fixtures`. Only set this on development deployments.
+    #[clap(long)]
+    pub enable_fixture_loading: bool,
+
+    /// If set, the backend clock can be frozen and advanced through
+    /// `/api/simulated_time` so scheduled functions and crons can be tested
+    /// without waiting. Only set this on development deployments.
+    #[clap(long)]
+    pub enable_simulated_time: bool,
+
+    /// If set, snapshot exports are encrypted with this key (64 hex
+    /// characters) before they're stored, and imports of archives encrypted
+    /// with it are decrypted. Keep it somewhere safe: encrypted exports can't
+    /// be restored without it.
+    #[clap(long)]
+    pub export_encryption_key: Option<ArchiveKey>,
 }
 
 impl fmt::Debug for LocalConfig {
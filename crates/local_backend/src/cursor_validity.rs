@@ -0,0 +1,185 @@
+use std::str::FromStr;
+
+use anyhow::Context;
+use application::cursor_validity::{
+    ResumptionToken,
+    TokenValidity,
+};
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    components::ComponentPath,
+    http::{
+        extract::Json,
+        HttpResponseError,
+    },
+    types::{
+        IndexDescriptor,
+        INDEX_BY_CREATION_TIME_DESCRIPTOR,
+    },
+};
+use errors::ErrorMetadata;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use sync_types::Timestamp;
+use value::TableName;
+
+use crate::{
+    admin::must_be_admin,
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ResumptionTokenJson {
+    #[serde(rename_all = "camelCase")]
+    PaginationCursor {
+        cursor: String,
+        component_path: Option<String>,
+        table: String,
+        /// Defaults to `by_creation_time`.
+        index: Option<String>,
+    },
+    Snapshot {
+        snapshot: i64,
+    },
+    DocumentDeltas {
+        cursor: i64,
+    },
+}
+
+impl TryFrom<ResumptionTokenJson> for ResumptionToken {
+    type Error = anyhow::Error;
+
+    fn try_from(token: ResumptionTokenJson) -> anyhow::Result<Self> {
+        let token = match token {
+            ResumptionTokenJson::PaginationCursor {
+                cursor,
+                component_path,
+                table,
+                index,
+            } => {
+                let index = match index {
+                    Some(index) => {
+                        IndexDescriptor::new(index.clone()).context(ErrorMetadata::bad_request(
+                            "InvalidIndexName",
+                            format!("Invalid index name {index}"),
+                        ))?
+                    },
+                    None => INDEX_BY_CREATION_TIME_DESCRIPTOR.clone(),
+                };
+                ResumptionToken::PaginationCursor {
+                    cursor,
+                    component: ComponentPath::deserialize(component_path.as_deref())?,
+                    table: TableName::from_str(&table).context(ErrorMetadata::bad_request(
+                        "InvalidTableName",
+                        format!("Invalid table name {table}"),
+                    ))?,
+                    index,
+                }
+            },
+            ResumptionTokenJson::Snapshot { snapshot } => {
+                ResumptionToken::Snapshot(Timestamp::try_from(snapshot)?)
+            },
+            ResumptionTokenJson::DocumentDeltas { cursor } => {
+                ResumptionToken::DocumentDeltas(Timestamp::try_from(cursor)?)
+            },
+        };
+        Ok(token)
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckResumptionTokenResponse {
+    valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+}
+
+impl From<TokenValidity> for CheckResumptionTokenResponse {
+    fn from(validity: TokenValidity) -> Self {
+        match validity {
+            TokenValidity::Valid => Self {
+                valid: true,
+                reason: None,
+                message: None,
+            },
+            TokenValidity::Invalid { reason, message } => Self {
+                valid: false,
+                reason: Some(reason.to_string()),
+                message: Some(message),
+            },
+        }
+    }
+}
+
+/// Reports whether a pagination cursor, `list_snapshot` snapshot or
+/// `document_deltas` cursor can still be resumed from, so a client can
+/// restart from the beginning before it fails partway through.
+#[debug_handler]
+pub async fn check_resumption_token(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(token): Json<ResumptionTokenJson>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let validity = st
+        .application
+        .check_resumption_token(identity, token.try_into()?)
+        .await?;
+    Ok(Json(CheckResumptionTokenResponse::from(validity)))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum_extra::headers::authorization::Credentials;
+    use http::Request;
+    use runtime::prod::ProdRuntime;
+    use serde_json::{
+        json,
+        Value as JsonValue,
+    };
+
+    use crate::test_helpers::setup_backend_for_test;
+
+    #[convex_macro::prod_rt_test]
+    async fn test_check_resumption_token(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let check = |body: JsonValue| {
+            Request::builder()
+                .uri("/api/check_resumption_token")
+                .method("POST")
+                .header("Authorization", backend.admin_auth_header.0.encode())
+                .header("Content-Type", "application/json")
+                .body(axum::body::Body::from(serde_json::to_vec(&body).unwrap()))
+        };
+
+        let result: JsonValue = backend
+            .expect_success(check(json!({
+                "type": "paginationCursor",
+                "cursor": "not a cursor",
+                "table": "messages",
+            }))?)
+            .await?;
+        assert_eq!(result["valid"], false);
+        assert_eq!(result["reason"], "InvalidCursor");
+
+        let result: JsonValue = backend
+            .expect_success(check(
+                json!({ "type": "documentDeltas", "cursor": i64::MAX }),
+            )?)
+            .await?;
+        assert_eq!(result["valid"], false);
+        assert_eq!(result["reason"], "CursorTooNew");
+        Ok(())
+    }
+}
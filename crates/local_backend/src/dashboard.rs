@@ -1,3 +1,9 @@
+use std::time::{
+    Duration,
+    SystemTime,
+    UNIX_EPOCH,
+};
+
 use anyhow::Context;
 use application::{
     deploy_config::ModuleJson,
@@ -9,7 +15,10 @@ use axum::{
     response::IntoResponse,
 };
 use common::{
-    components::ComponentId,
+    components::{
+        ComponentId,
+        ComponentPath,
+    },
     http::{
         extract::{
             Json,
@@ -23,14 +32,33 @@ use common::{
         dashboard_shape_json,
         reduced::ReducedShape,
     },
-    types::FunctionCaller,
+    types::{
+        FunctionCaller,
+        Timestamp,
+    },
+};
+use database::{
+    ComponentQuota,
+    IndexModel,
+    TableFreeze,
+    TableSnapshot,
 };
-use database::IndexModel;
 use errors::ErrorMetadata;
 use http::StatusCode;
 use isolate::UdfArgsJson;
 use model::{
     config::types::ModuleConfig,
+    deployment_audit_log::DeploymentAuditLogModel,
+    error_groups::ErrorGroupsModel,
+    function_runs::{
+        FunctionRunsFilter,
+        FunctionRunsModel,
+    },
+    log_search::{
+        LogSearchModel,
+        LogSearchQuery,
+    },
+    schema_history::SchemaHistoryModel,
     virtual_system_mapping,
 };
 use serde::{
@@ -39,12 +67,14 @@ use serde::{
 };
 use serde_json::json;
 use value::{
+    export::ValueFormat,
     TableName,
     TableNamespace,
 };
 
 use crate::{
     admin::{
+        must_be_admin,
         must_be_admin_from_key,
         must_be_admin_member,
         must_be_admin_member_with_write_access,
@@ -133,6 +163,347 @@ pub async fn delete_tables(
     Ok(StatusCode::OK)
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TruncateTableArgs {
+    table_name: String,
+    component_id: Option<String>,
+}
+
+/// Removes all of a table's documents by swapping in a fresh, empty tablet,
+/// rather than deleting them one page at a time.
+#[debug_handler]
+pub async fn truncate_table(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(TruncateTableArgs {
+        table_name,
+        component_id,
+    }): Json<TruncateTableArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let table_name = table_name.parse::<ValidIdentifier<TableName>>()?.0;
+    let table_namespace = TableNamespace::from(ComponentId::deserialize_from_string(
+        component_id.as_deref(),
+    )?);
+    let documents_deleted = st
+        .application
+        .truncate_table(&identity, table_namespace, table_name)
+        .await?;
+    Ok(Json(json!({ "documentsDeleted": documents_deleted })))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FreezeTableArgs {
+    table_name: String,
+    component_id: Option<String>,
+    reason: String,
+    duration_seconds: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnfreezeTableArgs {
+    table_name: String,
+    component_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListTableFreezesArgs {
+    component_id: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableFreezeJson {
+    table_name: String,
+    reason: String,
+    frozen_at_ms: i64,
+    expires_at_ms: i64,
+}
+
+impl TableFreezeJson {
+    fn new(table_name: TableName, freeze: TableFreeze) -> Self {
+        Self {
+            table_name: table_name.to_string(),
+            reason: freeze.reason,
+            frozen_at_ms: freeze.frozen_at_ms,
+            expires_at_ms: freeze.expires_at_ms,
+        }
+    }
+}
+
+/// Makes a table read-only, e.g. while a migration or export runs. The freeze
+/// expires on its own after `durationSeconds`.
+#[debug_handler]
+pub async fn freeze_table(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(FreezeTableArgs {
+        table_name,
+        component_id,
+        reason,
+        duration_seconds,
+    }): Json<FreezeTableArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let table_name = table_name.parse::<ValidIdentifier<TableName>>()?.0;
+    let table_namespace = TableNamespace::from(ComponentId::deserialize_from_string(
+        component_id.as_deref(),
+    )?);
+    let freeze = st
+        .application
+        .freeze_table(
+            &identity,
+            table_namespace,
+            table_name.clone(),
+            reason,
+            Duration::from_secs(duration_seconds),
+        )
+        .await?;
+    Ok(Json(TableFreezeJson::new(table_name, freeze)))
+}
+
+#[debug_handler]
+pub async fn unfreeze_table(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(UnfreezeTableArgs {
+        table_name,
+        component_id,
+    }): Json<UnfreezeTableArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let table_name = table_name.parse::<ValidIdentifier<TableName>>()?.0;
+    let table_namespace = TableNamespace::from(ComponentId::deserialize_from_string(
+        component_id.as_deref(),
+    )?);
+    let was_frozen = st
+        .application
+        .unfreeze_table(&identity, table_namespace, table_name)
+        .await?;
+    Ok(Json(json!({ "wasFrozen": was_frozen })))
+}
+
+#[debug_handler]
+pub async fn list_table_freezes(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(ListTableFreezesArgs { component_id }): Query<ListTableFreezesArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member(&identity)?;
+    let table_namespace = TableNamespace::from(ComponentId::deserialize_from_string(
+        component_id.as_deref(),
+    )?);
+    let freezes: Vec<_> = st
+        .application
+        .list_table_freezes(&identity, table_namespace)
+        .await?
+        .into_iter()
+        .map(|(table_name, freeze)| TableFreezeJson::new(table_name, freeze))
+        .collect();
+    Ok(Json(json!({ "freezes": freezes })))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateTableSnapshotArgs {
+    name: String,
+    table_name: String,
+    component_id: Option<String>,
+    /// Defaults to now.
+    snapshot_time_ms: Option<u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteTableSnapshotArgs {
+    name: String,
+    component_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListTableSnapshotsArgs {
+    component_id: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableSnapshotJson {
+    name: String,
+    table_name: String,
+    snapshot_time_ms: u64,
+    created_at_ms: i64,
+}
+
+impl TryFrom<TableSnapshot> for TableSnapshotJson {
+    type Error = anyhow::Error;
+
+    fn try_from(snapshot: TableSnapshot) -> anyhow::Result<Self> {
+        let snapshot_time = SystemTime::from(snapshot.snapshot_ts).duration_since(UNIX_EPOCH)?;
+        Ok(Self {
+            name: snapshot.name.to_string(),
+            table_name: snapshot.table_name.to_string(),
+            snapshot_time_ms: snapshot_time.as_millis() as u64,
+            created_at_ms: snapshot.created_at_ms,
+        })
+    }
+}
+
+/// Creates a read-only alias `name` for `tableName` as it was at
+/// `snapshotTimeMs`. Functions can query the alias like any other table while
+/// the snapshot is within document retention.
+#[debug_handler]
+pub async fn create_table_snapshot(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(CreateTableSnapshotArgs {
+        name,
+        table_name,
+        component_id,
+        snapshot_time_ms,
+    }): Json<CreateTableSnapshotArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let name = name.parse::<ValidIdentifier<TableName>>()?.0;
+    let table_name = table_name.parse::<ValidIdentifier<TableName>>()?.0;
+    let table_namespace = TableNamespace::from(ComponentId::deserialize_from_string(
+        component_id.as_deref(),
+    )?);
+    let snapshot_ts = snapshot_time_ms
+        .map(|ms| Timestamp::try_from(UNIX_EPOCH + Duration::from_millis(ms)))
+        .transpose()
+        .context(ErrorMetadata::bad_request(
+            "InvalidTableSnapshot",
+            "snapshotTimeMs is out of range",
+        ))?;
+    let snapshot = st
+        .application
+        .create_table_snapshot(&identity, table_namespace, name, table_name, snapshot_ts)
+        .await?;
+    Ok(Json(TableSnapshotJson::try_from(snapshot)?))
+}
+
+#[debug_handler]
+pub async fn delete_table_snapshot(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(DeleteTableSnapshotArgs { name, component_id }): Json<DeleteTableSnapshotArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let name = name.parse::<ValidIdentifier<TableName>>()?.0;
+    let table_namespace = TableNamespace::from(ComponentId::deserialize_from_string(
+        component_id.as_deref(),
+    )?);
+    let existed = st
+        .application
+        .delete_table_snapshot(&identity, table_namespace, name)
+        .await?;
+    Ok(Json(json!({ "existed": existed })))
+}
+
+#[debug_handler]
+pub async fn list_table_snapshots(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(ListTableSnapshotsArgs { component_id }): Query<ListTableSnapshotsArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member(&identity)?;
+    let table_namespace = TableNamespace::from(ComponentId::deserialize_from_string(
+        component_id.as_deref(),
+    )?);
+    let snapshots = st
+        .application
+        .list_table_snapshots(&identity, table_namespace)
+        .await?
+        .into_iter()
+        .map(TableSnapshotJson::try_from)
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    Ok(Json(json!({ "snapshots": snapshots })))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetComponentQuotaArgs {
+    component_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentQuotaJson {
+    max_documents: Option<u64>,
+    max_document_bytes: Option<u64>,
+    max_function_calls_per_minute: Option<u64>,
+    max_scheduled_jobs: Option<u64>,
+}
+
+impl From<ComponentQuota> for ComponentQuotaJson {
+    fn from(quota: ComponentQuota) -> Self {
+        Self {
+            max_documents: quota.max_documents,
+            max_document_bytes: quota.max_document_bytes,
+            max_function_calls_per_minute: quota.max_function_calls_per_minute,
+            max_scheduled_jobs: quota.max_scheduled_jobs,
+        }
+    }
+}
+
+impl From<ComponentQuotaJson> for ComponentQuota {
+    fn from(quota: ComponentQuotaJson) -> Self {
+        Self {
+            max_documents: quota.max_documents,
+            max_document_bytes: quota.max_document_bytes,
+            max_function_calls_per_minute: quota.max_function_calls_per_minute,
+            max_scheduled_jobs: quota.max_scheduled_jobs,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetComponentQuotaArgs {
+    component_id: String,
+    #[serde(flatten)]
+    quota: ComponentQuotaJson,
+}
+
+#[debug_handler]
+pub async fn get_component_quota(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(GetComponentQuotaArgs { component_id }): Query<GetComponentQuotaArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member(&identity)?;
+    let component_id = ComponentId::deserialize_from_string(component_id.as_deref())?;
+    let quota = st
+        .application
+        .get_component_quota(&identity, component_id)
+        .await?;
+    Ok(Json(ComponentQuotaJson::from(quota)))
+}
+
+/// Limits the resources a component can use. Omitted limits are removed, so
+/// setting no limits lifts the component's quota entirely.
+#[debug_handler]
+pub async fn set_component_quota(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(SetComponentQuotaArgs {
+        component_id,
+        quota,
+    }): Json<SetComponentQuotaArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let component_id = ComponentId::deserialize_from_string(Some(&component_id))?;
+    st.application
+        .set_component_quota(&identity, component_id, quota.into())
+        .await?;
+    Ok(StatusCode::OK)
+}
+
 #[debug_handler]
 pub async fn delete_component(
     State(st): State<LocalAppState>,
@@ -179,6 +550,208 @@ pub async fn get_indexes(
     }))
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListDeploymentAuditLogArgs {
+    action: Option<String>,
+    limit: Option<usize>,
+}
+
+const DEFAULT_AUDIT_LOG_LIMIT: usize = 100;
+const MAX_AUDIT_LOG_LIMIT: usize = 1000;
+
+#[debug_handler]
+pub async fn list_deployment_audit_log(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(ListDeploymentAuditLogArgs { action, limit }): Query<ListDeploymentAuditLogArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let limit = limit
+        .unwrap_or(DEFAULT_AUDIT_LOG_LIMIT)
+        .min(MAX_AUDIT_LOG_LIMIT);
+    let mut tx = st.application.begin(identity).await?;
+    let entries = DeploymentAuditLogModel::new(&mut tx)
+        .list_recent(action.as_deref(), limit)
+        .await?;
+    Ok(Json(json!({
+        "entries": entries
+            .into_iter()
+            .map(|doc| doc.export(ValueFormat::ConvexCleanJSON))
+            .collect::<Vec<_>>(),
+    })))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListFunctionRunsArgs {
+    udf_path: Option<String>,
+    outcome: Option<String>,
+    limit: Option<usize>,
+}
+
+const DEFAULT_FUNCTION_RUNS_LIMIT: usize = 100;
+const MAX_FUNCTION_RUNS_LIMIT: usize = 1000;
+
+#[debug_handler]
+pub async fn list_function_runs(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(ListFunctionRunsArgs {
+        udf_path,
+        outcome,
+        limit,
+    }): Query<ListFunctionRunsArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let limit = limit
+        .unwrap_or(DEFAULT_FUNCTION_RUNS_LIMIT)
+        .min(MAX_FUNCTION_RUNS_LIMIT);
+    let mut tx = st.application.begin(identity).await?;
+    let runs = FunctionRunsModel::new(&mut tx)
+        .list_recent(FunctionRunsFilter { udf_path, outcome }, limit)
+        .await?;
+    Ok(Json(json!({
+        "runs": runs
+            .into_iter()
+            .map(|doc| doc.export(ValueFormat::ConvexCleanJSON))
+            .collect::<Vec<_>>(),
+    })))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchLogsArgs {
+    query: String,
+    /// `"functionRun"` or `"auditLog"`.
+    source: Option<String>,
+    #[serde(default)]
+    failures_only: bool,
+    since_ms: Option<i64>,
+    until_ms: Option<i64>,
+    limit: Option<usize>,
+}
+
+/// Searches the text of recorded function runs and audit log entries, newest
+/// first, e.g. for failed runs mentioning `ECONNRESET` in the last day.
+#[debug_handler]
+pub async fn search_logs(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(SearchLogsArgs {
+        query,
+        source,
+        failures_only,
+        since_ms,
+        until_ms,
+        limit,
+    }): Query<SearchLogsArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let source =
+        source
+            .map(|source| source.parse())
+            .transpose()
+            .context(ErrorMetadata::bad_request(
+                "InvalidLogSearchSource",
+                "Expected source to be \"functionRun\" or \"auditLog\".",
+            ))?;
+    let limit = limit
+        .unwrap_or(DEFAULT_FUNCTION_RUNS_LIMIT)
+        .min(MAX_FUNCTION_RUNS_LIMIT);
+    let mut tx = st.application.begin(identity).await?;
+    let hits = LogSearchModel::new(&mut tx)
+        .search(LogSearchQuery {
+            text: query,
+            source,
+            failures_only,
+            since_ms: since_ms.unwrap_or(0),
+            until_ms,
+            limit,
+        })
+        .await?;
+    Ok(Json(json!({
+        "hits": hits
+            .into_iter()
+            .map(|hit| json!({
+                "source": hit.source.to_string(),
+                "tsMs": hit.ts_ms,
+                "record": hit.record.export(ValueFormat::ConvexCleanJSON),
+            }))
+            .collect::<Vec<_>>(),
+    })))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListErrorGroupsArgs {
+    limit: Option<usize>,
+}
+
+const DEFAULT_ERROR_GROUPS_LIMIT: usize = 100;
+const MAX_ERROR_GROUPS_LIMIT: usize = 1000;
+
+/// Lists groups of uncaught exceptions, most recently seen first.
+#[debug_handler]
+pub async fn list_error_groups(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(ListErrorGroupsArgs { limit }): Query<ListErrorGroupsArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let limit = limit
+        .unwrap_or(DEFAULT_ERROR_GROUPS_LIMIT)
+        .min(MAX_ERROR_GROUPS_LIMIT);
+    let mut tx = st.application.begin(identity).await?;
+    let groups = ErrorGroupsModel::new(&mut tx).list_recent(limit).await?;
+    Ok(Json(json!({
+        "groups": groups
+            .into_iter()
+            .map(|doc| doc.export(ValueFormat::ConvexCleanJSON))
+            .collect::<Vec<_>>(),
+    })))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListSchemaHistoryArgs {
+    component_path: Option<String>,
+    limit: Option<usize>,
+}
+
+const DEFAULT_SCHEMA_HISTORY_LIMIT: usize = 50;
+const MAX_SCHEMA_HISTORY_LIMIT: usize = 500;
+
+/// Lists pushes that changed a schema or its indexes, newest first, with the
+/// tables, validators and indexes each one changed.
+#[debug_handler]
+pub async fn list_schema_history(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(ListSchemaHistoryArgs {
+        component_path,
+        limit,
+    }): Query<ListSchemaHistoryArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let component_path = component_path
+        .map(|path| ComponentPath::deserialize(Some(&path)))
+        .transpose()?;
+    let limit = limit
+        .unwrap_or(DEFAULT_SCHEMA_HISTORY_LIMIT)
+        .min(MAX_SCHEMA_HISTORY_LIMIT);
+    let mut tx = st.application.begin(identity).await?;
+    let entries = SchemaHistoryModel::new(&mut tx)
+        .list_recent(component_path.as_ref(), limit)
+        .await?;
+    Ok(Json(json!({
+        "entries": entries
+            .into_iter()
+            .map(|doc| doc.export(ValueFormat::ConvexCleanJSON))
+            .collect::<Vec<_>>(),
+    })))
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GetSourceCodeArgs {
@@ -258,6 +831,7 @@ pub async fn run_test_function(
         Ok(result) => UdfResponse::Success {
             value: export_value(result.value.unpack(), value_format, client_version)?,
             log_lines: result.log_lines,
+            ts: None,
         },
         Err(error) => {
             UdfResponse::error(error.error, error.log_lines, value_format, client_version)?
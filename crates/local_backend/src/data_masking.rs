@@ -0,0 +1,121 @@
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::http::{
+    extract::Json,
+    HttpResponseError,
+};
+use errors::ErrorMetadata;
+use http::StatusCode;
+use model::data_masking::types::{
+    DataMaskingRule,
+    SerializedDataMaskingRule,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataMaskingRules {
+    rules: Vec<SerializedDataMaskingRule>,
+}
+
+#[debug_handler]
+pub async fn get_data_masking_rules(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let rules = st.application.get_data_masking_rules(identity).await?;
+    Ok(Json(DataMaskingRules {
+        rules: rules
+            .into_iter()
+            .map(SerializedDataMaskingRule::from)
+            .collect(),
+    }))
+}
+
+#[debug_handler]
+pub async fn update_data_masking_rules(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(request): Json<DataMaskingRules>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let rules = request
+        .rules
+        .into_iter()
+        .map(DataMaskingRule::try_from)
+        .collect::<anyhow::Result<Vec<_>>>()
+        .map_err(|e| {
+            anyhow::anyhow!(ErrorMetadata::bad_request(
+                "InvalidDataMaskingRule",
+                format!("Invalid data masking rule: {e}"),
+            ))
+        })?;
+    st.application
+        .set_data_masking_rules(identity, rules)
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum_extra::headers::authorization::Credentials;
+    use http::Request;
+    use runtime::prod::ProdRuntime;
+    use serde_json::{
+        json,
+        Value as JsonValue,
+    };
+
+    use crate::test_helpers::setup_backend_for_test;
+
+    #[convex_macro::prod_rt_test]
+    async fn test_update_data_masking_rules(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let rules = json!({
+            "rules": [
+                {
+                    "tableName": "users",
+                    "field": "email",
+                    "strategy": { "type": "redact" },
+                },
+                {
+                    "tableName": null,
+                    "field": "phone",
+                    "strategy": { "type": "partial", "visibleChars": 4 },
+                },
+            ],
+        });
+        let req = Request::builder()
+            .uri("/api/update_data_masking_rules")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .header("Authorization", backend.admin_auth_header.0.encode())
+            .body(axum::body::Body::from(serde_json::to_vec(&rules)?))?;
+        let () = backend.expect_success(req).await?;
+
+        let req = Request::builder()
+            .uri("/api/get_data_masking_rules")
+            .method("GET")
+            .header("Authorization", backend.admin_auth_header.0.encode())
+            .body(axum::body::Body::empty())?;
+        let result: JsonValue = backend.expect_success(req).await?;
+        assert_eq!(result, rules);
+        Ok(())
+    }
+}
@@ -217,6 +217,9 @@ pub struct WaitForSchemaRequest {
     schema_change: SerializedSchemaChange,
     dry_run: bool,
     timeout_ms: Option<u32>,
+    /// Plan a staged migration if existing documents don't match the schema.
+    #[serde(default)]
+    include_migration_plan: bool,
 }
 
 #[derive(Serialize)]
@@ -251,14 +254,14 @@ pub async fn wait_for_schema(
     // in a `start_push` dry run. Just return immediately.
     if req.dry_run {
         tracing::info!("Skipping wait_for_schema in dry run");
-        return Ok(Json(SchemaStatusJson::from(SchemaStatus::Complete)));
+        return Ok(Json(SchemaStatusJson::try_from(SchemaStatus::Complete)?));
     }
 
     let resp = st
         .application
-        .wait_for_schema(identity, schema_change, timeout)
+        .wait_for_schema(identity, schema_change, timeout, req.include_migration_plan)
         .await?;
-    Ok(Json(SchemaStatusJson::from(resp)))
+    Ok(Json(SchemaStatusJson::try_from(resp)?))
 }
 
 #[derive(Deserialize)]
@@ -0,0 +1,107 @@
+//! State for draining the backend before it shuts down: once draining starts,
+//! new requests and sync sessions are turned away while existing sessions
+//! finish their in-flight work and close.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::State,
+    response::Response,
+};
+use common::http::HttpResponseError;
+use errors::ErrorMetadata;
+use tokio::sync::watch;
+
+#[derive(Clone)]
+pub struct DrainSignal {
+    draining: Arc<watch::Sender<bool>>,
+    open_sessions: Arc<watch::Sender<usize>>,
+}
+
+impl Default for DrainSignal {
+    fn default() -> Self {
+        Self {
+            draining: Arc::new(watch::Sender::new(false)),
+            open_sessions: Arc::new(watch::Sender::new(0)),
+        }
+    }
+}
+
+impl DrainSignal {
+    /// Starts draining. Idempotent.
+    pub fn start(&self) {
+        self.draining.send_replace(true);
+    }
+
+    pub fn is_draining(&self) -> bool {
+        *self.draining.borrow()
+    }
+
+    /// Resolves once draining has started.
+    pub async fn wait(&self) {
+        let mut rx = self.draining.subscribe();
+        // The sender lives as long as `self`, so this can't fail.
+        let _ = rx.wait_for(|draining| *draining).await;
+    }
+
+    /// Counts a sync session as open until the returned guard is dropped.
+    pub fn open_session(&self) -> OpenSessionGuard {
+        self.open_sessions.send_modify(|count| *count += 1);
+        OpenSessionGuard {
+            open_sessions: self.open_sessions.clone(),
+        }
+    }
+
+    /// Resolves once every sync session has closed.
+    pub async fn wait_for_sessions(&self) {
+        let mut rx = self.open_sessions.subscribe();
+        let _ = rx.wait_for(|count| *count == 0).await;
+    }
+}
+
+pub struct OpenSessionGuard {
+    open_sessions: Arc<watch::Sender<usize>>,
+}
+
+impl Drop for OpenSessionGuard {
+    fn drop(&mut self) {
+        self.open_sessions.send_modify(|count| *count -= 1);
+    }
+}
+
+/// Rejects new requests while the backend is draining, so load balancers
+/// retry them on another backend.
+pub async fn reject_while_draining_middleware(
+    State(drain): State<DrainSignal>,
+    req: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> Result<Response, HttpResponseError> {
+    if drain.is_draining() {
+        return Err(anyhow::anyhow!(ErrorMetadata::rejected_before_execution(
+            "BackendShuttingDown",
+            "This backend is shutting down. Retry the request.",
+        ))
+        .into());
+    }
+    Ok(next.run(req).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DrainSignal;
+
+    #[tokio::test]
+    async fn test_wait_for_sessions() {
+        let drain = DrainSignal::default();
+        let session = drain.open_session();
+        assert!(!drain.is_draining());
+        drain.start();
+        drain.wait().await;
+
+        let wait = drain.wait_for_sessions();
+        tokio::pin!(wait);
+        assert!(futures::poll!(&mut wait).is_pending());
+        drop(session);
+        wait.await;
+    }
+}
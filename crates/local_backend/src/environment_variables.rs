@@ -85,8 +85,12 @@ fn validate_env_var(name: &String, value: &String) -> anyhow::Result<Environment
 
 #[cfg(test)]
 mod tests {
-    use std::collections::BTreeMap;
+    use std::{
+        collections::BTreeMap,
+        net::SocketAddr,
+    };
 
+    use axum::extract::ConnectInfo;
     use axum_extra::headers::authorization::Credentials;
     use common::types::{
         EnvVarName,
@@ -95,9 +99,13 @@ mod tests {
     use http::Request;
     use keybroker::Identity;
     use maplit::btreemap;
-    use model::environment_variables::EnvironmentVariablesModel;
+    use model::{
+        deployment_audit_log::DeploymentAuditLogModel,
+        environment_variables::EnvironmentVariablesModel,
+    };
     use runtime::prod::ProdRuntime;
     use serde_json::json;
+    use value::ConvexValue;
 
     use crate::test_helpers::{
         setup_backend_for_test,
@@ -206,4 +214,31 @@ mod tests {
         );
         Ok(())
     }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_audit_log_records_client_ip(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let json_body = json!({"changes": [{"name": "name1", "value": "value1"}]});
+        let mut req = Request::builder()
+            .uri("/api/update_environment_variables")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .header("Authorization", backend.admin_auth_header.0.encode())
+            // Without a trusted proxy, a client-supplied header is ignored.
+            .header("X-Forwarded-For", "10.0.0.1")
+            .body(axum::body::Body::from(serde_json::to_vec(&json_body)?))?;
+        req.extensions_mut()
+            .insert(ConnectInfo("203.0.113.7:4321".parse::<SocketAddr>()?));
+        let () = backend.expect_success(req).await?;
+
+        let mut tx = backend.st.application.begin(Identity::system()).await?;
+        let entries = DeploymentAuditLogModel::new(&mut tx)
+            .list_recent(Some("create_environment_variable"), 1)
+            .await?;
+        assert_eq!(
+            entries[0].value().get("source_ip"),
+            Some(&ConvexValue::try_from("203.0.113.7".to_string())?)
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,257 @@
+//! Data subject erasure: per-table erasure policies, erasure requests and the
+//! signed reports of completed erasures.
+
+use anyhow::Context;
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    components::ComponentId,
+    http::{
+        extract::{
+            Json,
+            Query,
+        },
+        HttpResponseError,
+    },
+};
+use errors::ErrorMetadata;
+use http::StatusCode;
+use model::erasure::types::{
+    ErasurePolicy,
+    ErasureReport,
+    SerializedErasureJob,
+    SerializedErasurePolicy,
+    SerializedErasureReport,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::DeveloperDocumentId;
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErasurePolicies {
+    policies: Vec<SerializedErasurePolicy>,
+}
+
+#[debug_handler]
+pub async fn get_erasure_policies(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let policies = st.application.get_erasure_policies(identity).await?;
+    Ok(Json(ErasurePolicies {
+        policies: policies
+            .into_iter()
+            .map(SerializedErasurePolicy::from)
+            .collect(),
+    }))
+}
+
+#[debug_handler]
+pub async fn update_erasure_policies(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(request): Json<ErasurePolicies>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let policies = request
+        .policies
+        .into_iter()
+        .map(ErasurePolicy::try_from)
+        .collect::<anyhow::Result<Vec<_>>>()
+        .map_err(|e| {
+            anyhow::anyhow!(ErrorMetadata::bad_request(
+                "InvalidErasurePolicy",
+                format!("Invalid erasure policy: {e}"),
+            ))
+        })?;
+    st.application
+        .update_erasure_policies(identity, policies)
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestErasureArgs {
+    /// The component the subject is in. Defaults to the root component.
+    component_id: Option<String>,
+    /// The ID of the document to erase, in one of the component's tables.
+    subject_id: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestErasureResponse {
+    job_id: String,
+}
+
+/// Queues the erasure of a document and the data referencing it. Poll
+/// `/get_erasure_job` for the job's progress and report.
+#[debug_handler]
+pub async fn request_erasure(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(RequestErasureArgs {
+        component_id,
+        subject_id,
+    }): Json<RequestErasureArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let component = ComponentId::deserialize_from_string(component_id.as_deref())?;
+    let subject_id =
+        DeveloperDocumentId::decode(&subject_id).context(ErrorMetadata::bad_request(
+            "InvalidErasureSubjectId",
+            format!("Invalid document ID {subject_id}"),
+        ))?;
+    let job_id = st
+        .application
+        .request_erasure(identity, component, subject_id)
+        .await?;
+    Ok(Json(RequestErasureResponse {
+        job_id: job_id.encode(),
+    }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetErasureJobArgs {
+    job_id: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetErasureJobResponse {
+    /// `null` if there's no job with the ID.
+    job: Option<SerializedErasureJob>,
+}
+
+#[debug_handler]
+pub async fn get_erasure_job(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(GetErasureJobArgs { job_id }): Query<GetErasureJobArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let job_id = DeveloperDocumentId::decode(&job_id).context(ErrorMetadata::bad_request(
+        "InvalidErasureJobId",
+        format!("Invalid erasure job ID {job_id}"),
+    ))?;
+    let job = st.application.get_erasure_job(identity, job_id).await?;
+    Ok(Json(GetErasureJobResponse {
+        job: job.map(SerializedErasureJob::from),
+    }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyErasureReportArgs {
+    report: SerializedErasureReport,
+    signature: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyErasureReportResponse {
+    valid: bool,
+}
+
+/// Checks that a completed job's report was signed by this deployment and
+/// hasn't been modified.
+#[debug_handler]
+pub async fn verify_erasure_report(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(VerifyErasureReportArgs { report, signature }): Json<VerifyErasureReportArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let report = ErasureReport::try_from(report).map_err(|e| {
+        anyhow::anyhow!(ErrorMetadata::bad_request(
+            "InvalidErasureReport",
+            format!("Invalid erasure report: {e}"),
+        ))
+    })?;
+    let valid = st.application.verify_erasure_report(&report, &signature)?;
+    Ok(Json(VerifyErasureReportResponse { valid }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum_extra::headers::authorization::Credentials;
+    use http::{
+        Request,
+        StatusCode,
+    };
+    use runtime::prod::ProdRuntime;
+    use serde_json::{
+        json,
+        Value as JsonValue,
+    };
+
+    use crate::test_helpers::setup_backend_for_test;
+
+    #[convex_macro::prod_rt_test]
+    async fn test_erasure_policies_and_requests(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let post = |uri: &str, body: &JsonValue| {
+            Request::builder()
+                .uri(uri)
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .header("Authorization", backend.admin_auth_header.0.encode())
+                .body(axum::body::Body::from(serde_json::to_vec(body).unwrap()))
+        };
+
+        let policies = json!({
+            "policies": [
+                { "tableName": "messages", "action": { "type": "hash", "fields": ["author"] } },
+                { "tableName": "sessions", "action": { "type": "delete" } },
+            ],
+        });
+        let () = backend
+            .expect_success(post("/api/update_erasure_policies", &policies)?)
+            .await?;
+        let req = Request::builder()
+            .uri("/api/get_erasure_policies")
+            .method("GET")
+            .header("Authorization", backend.admin_auth_header.0.encode())
+            .body(axum::body::Body::empty())?;
+        let result: JsonValue = backend.expect_success(req).await?;
+        assert_eq!(result, policies);
+
+        let system_table_policy = json!({
+            "policies": [{ "tableName": "_storage", "action": { "type": "delete" } }],
+        });
+        backend
+            .expect_error(
+                post("/api/update_erasure_policies", &system_table_policy)?,
+                StatusCode::BAD_REQUEST,
+                "InvalidErasurePolicy",
+            )
+            .await?;
+
+        backend
+            .expect_error(
+                post("/api/request_erasure", &json!({ "subjectId": "not an id" }))?,
+                StatusCode::BAD_REQUEST,
+                "InvalidErasureSubjectId",
+            )
+            .await?;
+        Ok(())
+    }
+}
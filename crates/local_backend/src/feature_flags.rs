@@ -0,0 +1,137 @@
+//! Feature flags, which functions check with `ctx.flags.get()`. Queries that
+//! check a flag rerun when it's changed, so clients see the change live.
+
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::http::{
+    extract::Json,
+    HttpResponseError,
+};
+use model::feature_flags::types::{
+    FeatureFlag,
+    FeatureFlagRule,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum FeatureFlagRuleJson {
+    Boolean { enabled: bool },
+    Percentage { percent: u32 },
+    Targeted { claim: String, values: Vec<String> },
+}
+
+impl From<FeatureFlagRuleJson> for FeatureFlagRule {
+    fn from(rule: FeatureFlagRuleJson) -> Self {
+        match rule {
+            FeatureFlagRuleJson::Boolean { enabled } => Self::Boolean { enabled },
+            FeatureFlagRuleJson::Percentage { percent } => Self::Percentage { percent },
+            FeatureFlagRuleJson::Targeted { claim, values } => Self::Targeted { claim, values },
+        }
+    }
+}
+
+impl From<FeatureFlagRule> for FeatureFlagRuleJson {
+    fn from(rule: FeatureFlagRule) -> Self {
+        match rule {
+            FeatureFlagRule::Boolean { enabled } => Self::Boolean { enabled },
+            FeatureFlagRule::Percentage { percent } => Self::Percentage { percent },
+            FeatureFlagRule::Targeted { claim, values } => Self::Targeted { claim, values },
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeatureFlagJson {
+    name: String,
+    rule: FeatureFlagRuleJson,
+    updated_at_ms: i64,
+}
+
+impl From<FeatureFlag> for FeatureFlagJson {
+    fn from(flag: FeatureFlag) -> Self {
+        Self {
+            name: flag.name,
+            rule: flag.rule.into(),
+            updated_at_ms: flag.updated_at_ms,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListFeatureFlagsResponse {
+    flags: Vec<FeatureFlagJson>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetFeatureFlagArgs {
+    name: String,
+    rule: FeatureFlagRuleJson,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteFeatureFlagArgs {
+    name: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteFeatureFlagResponse {
+    existed: bool,
+}
+
+#[debug_handler]
+pub async fn list_feature_flags(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let flags = st.application.list_feature_flags(&identity).await?;
+    Ok(Json(ListFeatureFlagsResponse {
+        flags: flags.into_iter().map(FeatureFlagJson::from).collect(),
+    }))
+}
+
+#[debug_handler]
+pub async fn set_feature_flag(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(SetFeatureFlagArgs { name, rule }): Json<SetFeatureFlagArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let flag = st
+        .application
+        .set_feature_flag(&identity, name, rule.into())
+        .await?;
+    Ok(Json(FeatureFlagJson::from(flag)))
+}
+
+#[debug_handler]
+pub async fn delete_feature_flag(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(DeleteFeatureFlagArgs { name }): Json<DeleteFeatureFlagArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let existed = st.application.delete_feature_flag(&identity, name).await?;
+    Ok(Json(DeleteFeatureFlagResponse { existed }))
+}
@@ -0,0 +1,96 @@
+//! Loading declarative fixtures into a fresh development deployment. See
+//! `application::fixtures` for the fixture format.
+
+use std::collections::BTreeMap;
+
+use application::fixtures::{
+    Fixtures,
+    FixturesJson,
+};
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    components::ComponentId,
+    execution_context::ExecutionContext,
+    http::{
+        extract::Json,
+        ExtractClientVersion,
+        ExtractRequestId,
+        HttpResponseError,
+    },
+    types::FunctionCaller,
+};
+use errors::ErrorMetadata;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    admin::must_be_admin_with_write_access,
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadFixturesArgs {
+    component_id: Option<String>,
+    fixtures: FixturesJson,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadFixturesResponse {
+    /// Keyed by `table/key`.
+    document_ids: BTreeMap<String, String>,
+    file_ids: BTreeMap<String, String>,
+    scheduled_jobs: usize,
+}
+
+/// Loads fixtures atomically into empty tables. Only available on backends
+/// started with `--enable-fixture-loading`.
+#[debug_handler]
+pub async fn load_fixtures(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    ExtractRequestId(request_id): ExtractRequestId,
+    ExtractClientVersion(client_version): ExtractClientVersion,
+    Json(LoadFixturesArgs {
+        component_id,
+        fixtures,
+    }): Json<LoadFixturesArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    if !st.enable_fixture_loading {
+        return Err(anyhow::anyhow!(ErrorMetadata::forbidden(
+            "FixtureLoadingDisabled",
+            "Fixtures can only be loaded into development deployments. Start the backend with \
+             --enable-fixture-loading to allow it.",
+        ))
+        .into());
+    }
+    let component = ComponentId::deserialize_from_string(component_id.as_deref())?;
+    let fixtures = Fixtures::try_from(fixtures)?;
+    let context = ExecutionContext::new(request_id, &FunctionCaller::HttpApi(client_version));
+    let loaded = st
+        .application
+        .load_fixtures(&identity, component, fixtures, context)
+        .await?;
+    Ok(Json(LoadFixturesResponse {
+        document_ids: loaded
+            .document_ids
+            .into_iter()
+            .map(|(key, id)| (key, id.encode()))
+            .collect(),
+        file_ids: loaded
+            .file_ids
+            .into_iter()
+            .map(|(key, id)| (key, id.encode()))
+            .collect(),
+        scheduled_jobs: loaded.scheduled_jobs,
+    }))
+}
@@ -0,0 +1,129 @@
+use std::{
+    collections::BTreeMap,
+    time::Duration,
+};
+
+use application::health::{
+    ComponentHealth,
+    HealthReport,
+    HealthStatus,
+};
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::http::extract::Json;
+use http::StatusCode;
+use serde::Serialize;
+
+use crate::LocalAppState;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HealthStatusJson {
+    Ok,
+    Unavailable,
+}
+
+impl From<HealthStatus> for HealthStatusJson {
+    fn from(status: HealthStatus) -> Self {
+        match status {
+            HealthStatus::Ok => Self::Ok,
+            HealthStatus::Unavailable => Self::Unavailable,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentHealthJson {
+    status: HealthStatusJson,
+    latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl From<ComponentHealth> for ComponentHealthJson {
+    fn from(health: ComponentHealth) -> Self {
+        Self {
+            status: health.status.into(),
+            latency_ms: health.latency.as_millis() as u64,
+            error: health.error,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthResponse {
+    status: HealthStatusJson,
+    components: BTreeMap<&'static str, ComponentHealthJson>,
+}
+
+/// Responds with 200 if every component is healthy and 503 otherwise, so load
+/// balancers and probes can act on the status code alone.
+fn health_response(report: HealthReport) -> impl IntoResponse {
+    let status = report.status();
+    let code = match status {
+        HealthStatus::Ok => StatusCode::OK,
+        HealthStatus::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+    };
+    let body = HealthResponse {
+        status: status.into(),
+        components: report
+            .components
+            .into_iter()
+            .map(|(name, health)| (name, health.into()))
+            .collect(),
+    };
+    (code, Json(body))
+}
+
+/// Liveness probe: fails only if the backend itself is wedged and should be
+/// restarted.
+#[debug_handler]
+pub async fn healthz(State(st): State<LocalAppState>) -> impl IntoResponse {
+    health_response(st.application.liveness().await)
+}
+
+/// Readiness probe: fails while persistence or storage is unreachable,
+/// indexes are still loading, or the backend is draining before shutdown, so
+/// traffic is routed elsewhere.
+#[debug_handler]
+pub async fn readyz(State(st): State<LocalAppState>) -> impl IntoResponse {
+    let mut report = st.application.readiness().await;
+    if st.drain.is_draining() {
+        report.components.insert(
+            "shutdown",
+            ComponentHealth {
+                status: HealthStatus::Unavailable,
+                latency: Duration::ZERO,
+                error: Some("Backend is shutting down".to_string()),
+            },
+        );
+    }
+    health_response(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use http::Request;
+    use runtime::prod::ProdRuntime;
+    use serde_json::Value as JsonValue;
+
+    use crate::test_helpers::setup_backend_for_test;
+
+    #[convex_macro::prod_rt_test]
+    async fn test_healthz(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let req = Request::builder()
+            .uri("/healthz")
+            .method("GET")
+            .body(axum::body::Body::empty())?;
+        let result: JsonValue = backend.expect_success(req).await?;
+        assert_eq!(result["status"], "ok");
+        assert_eq!(result["components"]["committer"]["status"], "ok");
+        Ok(())
+    }
+}
@@ -67,6 +67,7 @@ use url::Url;
 
 use crate::{
     authentication::TryExtractIdentity,
+    static_assets::serve_static_asset,
     RouterState,
 };
 
@@ -159,7 +160,15 @@ pub async fn http_any_method(
     ExtractRequestId(request_id): ExtractRequestId,
     ExtractResolvedHostname(host): ExtractResolvedHostname,
     ExtractHttpRequestMetadata(http_request_metadata): ExtractHttpRequestMetadata,
-) -> Result<impl IntoResponse, HttpResponseError> {
+) -> Result<Response, HttpResponseError> {
+    // Static assets take precedence over HTTP actions for the paths they serve.
+    let head = &http_request_metadata.head;
+    if (head.method == Method::GET || head.method == Method::HEAD)
+        && let Some(response) = serve_static_asset(&st.api, &host, request_id.clone(), head).await?
+    {
+        return Ok(response);
+    }
+
     // The `Authorization` header for the request may contain a token corresponding
     // to Convex auth, or it could be something separate managed by the developer.
     // Try to extract the identity based on the Convex auth, but allow the request
@@ -202,7 +211,8 @@ pub async fn http_any_method(
         headers: response_head.headers,
         content_length,
         body: peek_body,
-    })
+    }
+    .into_response())
 }
 
 #[try_stream(ok=HttpActionResponsePart, error=anyhow::Error, boxed)]
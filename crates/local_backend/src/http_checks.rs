@@ -0,0 +1,215 @@
+//! Scheduled HTTP checks, which request a URL on an interval to check that a
+//! dependency is up and alert webhook endpoints when it goes down or recovers.
+
+use anyhow::Context;
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    document::ParsedDocument,
+    http::{
+        extract::{
+            Json,
+            Query,
+        },
+        HttpResponseError,
+    },
+};
+use errors::ErrorMetadata;
+use model::http_checks::types::{
+    HttpCheck,
+    HttpCheckConfig,
+    HttpCheckMethod,
+    HttpCheckResult,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+/// Results listed when the request doesn't set a limit.
+const DEFAULT_RESULTS_LIMIT: usize = 100;
+const MAX_RESULTS_LIMIT: usize = 1000;
+
+const DEFAULT_TIMEOUT_SECS: u32 = 10;
+const DEFAULT_FAILURE_THRESHOLD: u32 = 1;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpCheckJson {
+    id: String,
+    name: String,
+    url: String,
+    method: String,
+    interval_secs: u32,
+    timeout_secs: u32,
+    expected_status: Option<u16>,
+    failure_threshold: u32,
+    next_run_ms: i64,
+    consecutive_failures: u32,
+    healthy: bool,
+}
+
+impl From<ParsedDocument<HttpCheck>> for HttpCheckJson {
+    fn from(check: ParsedDocument<HttpCheck>) -> Self {
+        let (id, check) = check.into_id_and_value();
+        Self {
+            id: id.to_string(),
+            name: check.config.name,
+            url: check.config.url,
+            method: check.config.method.as_ref().to_string(),
+            interval_secs: check.config.interval_secs,
+            timeout_secs: check.config.timeout_secs,
+            expected_status: check.config.expected_status,
+            failure_threshold: check.config.failure_threshold,
+            next_run_ms: check.next_run_ms,
+            consecutive_failures: check.consecutive_failures,
+            healthy: check.healthy,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpCheckResultJson {
+    started_at_ms: i64,
+    latency_ms: i64,
+    status: Option<u16>,
+    ok: bool,
+    error: Option<String>,
+}
+
+impl From<ParsedDocument<HttpCheckResult>> for HttpCheckResultJson {
+    fn from(result: ParsedDocument<HttpCheckResult>) -> Self {
+        let result = result.into_value();
+        Self {
+            started_at_ms: result.started_at_ms,
+            latency_ms: result.latency_ms,
+            status: result.status,
+            ok: result.ok,
+            error: result.error,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListHttpChecksResponse {
+    checks: Vec<HttpCheckJson>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetHttpCheckArgs {
+    name: String,
+    url: String,
+    /// `GET` or `HEAD`. Defaults to `GET`.
+    method: Option<String>,
+    interval_secs: u32,
+    timeout_secs: Option<u32>,
+    /// Any 2xx status counts as healthy if unset.
+    expected_status: Option<u16>,
+    failure_threshold: Option<u32>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpCheckNameArgs {
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListHttpCheckResultsArgs {
+    name: String,
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListHttpCheckResultsResponse {
+    results: Vec<HttpCheckResultJson>,
+}
+
+#[debug_handler]
+pub async fn list_http_checks(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let checks = st.application.list_http_checks(&identity).await?;
+    Ok(Json(ListHttpChecksResponse {
+        checks: checks.into_iter().map(HttpCheckJson::from).collect(),
+    }))
+}
+
+#[debug_handler]
+pub async fn set_http_check(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(args): Json<SetHttpCheckArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let method = match args.method {
+        Some(method) => method
+            .to_uppercase()
+            .parse()
+            .context(ErrorMetadata::bad_request(
+                "InvalidHttpCheckMethod",
+                format!("HTTP checks must use GET or HEAD, not {method}"),
+            ))?,
+        None => HttpCheckMethod::Get,
+    };
+    let config = HttpCheckConfig {
+        name: args.name,
+        url: args.url,
+        method,
+        interval_secs: args.interval_secs,
+        timeout_secs: args.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS),
+        expected_status: args.expected_status,
+        failure_threshold: args.failure_threshold.unwrap_or(DEFAULT_FAILURE_THRESHOLD),
+    };
+    st.application.set_http_check(&identity, config).await?;
+    Ok(())
+}
+
+#[debug_handler]
+pub async fn delete_http_check(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(HttpCheckNameArgs { name }): Json<HttpCheckNameArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    st.application.delete_http_check(&identity, name).await?;
+    Ok(())
+}
+
+#[debug_handler]
+pub async fn list_http_check_results(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(ListHttpCheckResultsArgs { name, limit }): Query<ListHttpCheckResultsArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let limit = limit
+        .unwrap_or(DEFAULT_RESULTS_LIMIT)
+        .min(MAX_RESULTS_LIMIT);
+    let results = st
+        .application
+        .list_http_check_results(&identity, &name, limit)
+        .await?;
+    Ok(Json(ListHttpCheckResultsResponse {
+        results: results.into_iter().map(HttpCheckResultJson::from).collect(),
+    }))
+}
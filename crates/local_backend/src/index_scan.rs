@@ -0,0 +1,226 @@
+use std::str::FromStr;
+
+use anyhow::Context;
+use application::index_scan::{
+    IndexScanRequest,
+    IndexScanResult,
+};
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    components::ComponentPath,
+    http::{
+        extract::Json,
+        HttpResponseError,
+    },
+    json::JsonIndexRangeExpression,
+    query::{
+        IndexRangeExpression,
+        Order,
+    },
+    types::{
+        IndexDescriptor,
+        INDEX_BY_CREATION_TIME_DESCRIPTOR,
+    },
+};
+use errors::ErrorMetadata;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_json::Value as JsonValue;
+use value::{
+    export::ValueFormat,
+    TableName,
+};
+
+use crate::{
+    admin::must_be_admin,
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+const DEFAULT_INDEX_SCAN_LIMIT: usize = 100;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanIndexArgs {
+    component_path: Option<String>,
+    table: String,
+    /// Defaults to `by_creation_time`.
+    index: Option<String>,
+    /// Bounds on the indexed fields, in the same form as an index range in a
+    /// query. Defaults to the whole index.
+    #[serde(default)]
+    range: Vec<JsonIndexRangeExpression>,
+    /// `"asc"` or `"desc"`. Defaults to `"asc"`.
+    order: Option<String>,
+    limit: Option<usize>,
+}
+
+impl TryFrom<ScanIndexArgs> for IndexScanRequest {
+    type Error = anyhow::Error;
+
+    fn try_from(args: ScanIndexArgs) -> anyhow::Result<Self> {
+        let index = match args.index {
+            Some(index) => {
+                IndexDescriptor::new(index.clone()).context(ErrorMetadata::bad_request(
+                    "InvalidIndexName",
+                    format!("Invalid index name {index}"),
+                ))?
+            },
+            None => INDEX_BY_CREATION_TIME_DESCRIPTOR.clone(),
+        };
+        let range = args
+            .range
+            .into_iter()
+            .map(IndexRangeExpression::try_from)
+            .collect::<anyhow::Result<Vec<_>>>()
+            .context(ErrorMetadata::bad_request(
+                "InvalidIndexRange",
+                "range must be a list of index range expressions",
+            ))?;
+        let order = match args.order.as_deref() {
+            None | Some("asc") => Order::Asc,
+            Some("desc") => Order::Desc,
+            Some(order) => anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidOrder",
+                format!("order must be \"asc\" or \"desc\", got {order:?}"),
+            )),
+        };
+        Ok(IndexScanRequest {
+            component: ComponentPath::deserialize(args.component_path.as_deref())?,
+            table: TableName::from_str(&args.table).context(ErrorMetadata::bad_request(
+                "InvalidTableName",
+                format!("Invalid table name {}", args.table),
+            ))?,
+            index,
+            range,
+            order,
+            limit: args.limit.unwrap_or(DEFAULT_INDEX_SCAN_LIMIT),
+        })
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanIndexResponse {
+    entries: Vec<IndexScanEntryJson>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexScanEntryJson {
+    /// The indexed values in key order, followed by the document's `_id`.
+    key: Vec<IndexKeyValueJson>,
+    /// The hex encoding of the key's bytes, which sort in index order.
+    key_bytes: String,
+    document: JsonValue,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexKeyValueJson {
+    field_path: String,
+    /// Omitted when the document doesn't have the field.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<JsonValue>,
+}
+
+impl From<IndexScanResult> for ScanIndexResponse {
+    fn from(result: IndexScanResult) -> Self {
+        let entries = result
+            .entries
+            .into_iter()
+            .map(|entry| {
+                let mut key: Vec<_> = result
+                    .indexed_fields
+                    .iter()
+                    .zip(entry.key.indexed_values())
+                    .map(|(field_path, value)| IndexKeyValueJson {
+                        field_path: String::from(field_path.clone()),
+                        value: value.as_ref().map(|value| value.to_internal_json()),
+                    })
+                    .collect();
+                key.push(IndexKeyValueJson {
+                    field_path: "_id".to_string(),
+                    value: Some(entry.document.developer_id().into()),
+                });
+                IndexScanEntryJson {
+                    key,
+                    key_bytes: hex::encode(entry.key.to_bytes().0),
+                    document: entry.document.export(ValueFormat::ConvexEncodedJSON),
+                }
+            })
+            .collect();
+        Self { entries }
+    }
+}
+
+/// Reads a range of any enabled database index, including the indexes of
+/// system tables, and returns the raw documents along with their index keys.
+/// Documents and key values are in the internal Convex JSON encoding so their
+/// types are preserved.
+#[debug_handler]
+pub async fn scan_index(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(args): Json<ScanIndexArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let result = st
+        .application
+        .scan_index(identity, args.try_into()?)
+        .await?;
+    Ok(Json(ScanIndexResponse::from(result)))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum_extra::headers::authorization::Credentials;
+    use http::Request;
+    use runtime::prod::ProdRuntime;
+    use serde_json::{
+        json,
+        Value as JsonValue,
+    };
+
+    use crate::test_helpers::setup_backend_for_test;
+
+    #[convex_macro::prod_rt_test]
+    async fn test_scan_index(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let scan = |body: JsonValue| {
+            Request::builder()
+                .uri("/api/scan_index")
+                .method("POST")
+                .header("Authorization", backend.admin_auth_header.0.encode())
+                .header("Content-Type", "application/json")
+                .body(axum::body::Body::from(serde_json::to_vec(&body).unwrap()))
+        };
+
+        let result: JsonValue = backend
+            .expect_success(scan(
+                json!({ "table": "_tables", "index": "by_id", "limit": 2 }),
+            )?)
+            .await?;
+        let entries = result["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        let key = entries[0]["key"].as_array().unwrap();
+        assert_eq!(key.last().unwrap()["fieldPath"], "_id");
+        assert_eq!(key.last().unwrap()["value"], entries[0]["document"]["_id"]);
+        assert!(entries[0]["keyBytes"].as_str().is_some());
+
+        backend
+            .expect_error(
+                scan(json!({ "table": "_tables", "index": "by_missing" }))?,
+                http::StatusCode::BAD_REQUEST,
+                "IndexNotFound",
+            )
+            .await?;
+        Ok(())
+    }
+}
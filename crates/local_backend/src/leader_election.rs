@@ -0,0 +1,322 @@
+//! Lease-based leader election for running several backends against one
+//! persistence layer. The leader holds a lease in the `leader_lease`
+//! persistence global and renews it while it runs. Standbys poll the lease and
+//! take over once it expires.
+//!
+//! Taking over opens persistence for writing, which steals the persistence
+//! lease. Any write by the previous leader after that fails with
+//! `LeaseLostError`, so a leader that stalled past its lease can't write
+//! alongside its successor. The fencing token in the leader lease counts
+//! takeovers so a leader can also notice it was replaced before it next
+//! writes.
+//!
+//! Standbys are warm spares, not read replicas: they don't open persistence
+//! for writing, load modules or build indexes until they take over, so they
+//! serve no queries. Clients retry against the leader.
+
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::{
+    routing::get,
+    Router,
+};
+use common::{
+    errors::report_error,
+    http::HttpResponseError,
+    knobs::{
+        LEADER_LEASE_DURATION,
+        LEADER_LEASE_POLL_INTERVAL,
+    },
+    persistence::{
+        Persistence,
+        PersistenceGlobalKey,
+        PersistenceReader,
+    },
+    runtime::Runtime,
+    shutdown::ShutdownSignal,
+};
+use errors::ErrorMetadata;
+use http::StatusCode;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderLease {
+    /// Identifies the backend process holding the lease.
+    pub holder: String,
+    /// Incremented every time a new backend becomes leader.
+    pub fencing_token: u64,
+    pub expires_at_ms: i64,
+}
+
+impl LeaderLease {
+    fn is_expired<RT: Runtime>(&self, rt: &RT) -> anyhow::Result<bool> {
+        Ok(rt.unix_timestamp_ms()? >= self.expires_at_ms)
+    }
+}
+
+async fn read_lease(reader: &dyn PersistenceReader) -> anyhow::Result<Option<LeaderLease>> {
+    reader
+        .get_persistence_global(PersistenceGlobalKey::LeaderLease)
+        .await?
+        .map(serde_json::from_value)
+        .transpose()
+        .context("Invalid leader lease")
+}
+
+/// Waits until no other backend holds an unexpired leader lease. Returns the
+/// last lease seen, which the new leader's fencing token follows.
+pub async fn wait_for_leadership<RT: Runtime>(
+    rt: &RT,
+    reader: &dyn PersistenceReader,
+    holder: &str,
+) -> anyhow::Result<Option<LeaderLease>> {
+    loop {
+        let lease = read_lease(reader).await?;
+        match &lease {
+            Some(lease) if lease.holder != holder && !lease.is_expired(rt)? => {
+                tracing::debug!("Standing by while {} holds the leader lease", lease.holder);
+            },
+            _ => return Ok(lease),
+        }
+        rt.wait(*LEADER_LEASE_POLL_INTERVAL).await;
+    }
+}
+
+/// Takes the leader lease. `persistence` must have been opened after
+/// `wait_for_leadership` returned, so the previous leader is already fenced
+/// out.
+pub async fn acquire_leadership<RT: Runtime>(
+    rt: &RT,
+    persistence: &dyn Persistence,
+    holder: String,
+    previous: Option<LeaderLease>,
+) -> anyhow::Result<LeaderLease> {
+    let lease = LeaderLease {
+        holder,
+        fencing_token: previous.map_or(1, |lease| lease.fencing_token + 1),
+        expires_at_ms: rt.unix_timestamp_ms()? + LEADER_LEASE_DURATION.as_millis() as i64,
+    };
+    write_lease(persistence, &lease).await?;
+    tracing::info!(
+        "Acquired the leader lease with fencing token {}",
+        lease.fencing_token
+    );
+    Ok(lease)
+}
+
+async fn write_lease(persistence: &dyn Persistence, lease: &LeaderLease) -> anyhow::Result<()> {
+    persistence
+        .write_persistence_global(
+            PersistenceGlobalKey::LeaderLease,
+            serde_json::to_value(lease)?,
+        )
+        .await
+}
+
+/// Renews the leader lease until it is lost, then signals the backend to shut
+/// down. Renews three times per lease duration so a single slow write doesn't
+/// let the lease expire.
+pub async fn renew_leadership<RT: Runtime>(
+    rt: RT,
+    persistence: Arc<dyn Persistence>,
+    mut lease: LeaderLease,
+    shutdown_signal: ShutdownSignal,
+) {
+    let result: anyhow::Result<()> = try {
+        loop {
+            rt.wait(*LEADER_LEASE_DURATION / 3).await;
+            let current = read_lease(persistence.reader().as_ref()).await?;
+            if current.as_ref().map(|current| current.fencing_token) != Some(lease.fencing_token) {
+                Err(anyhow::anyhow!(
+                    "Leader lease was taken over by {:?}",
+                    current.map(|current| current.holder)
+                ))?;
+            }
+            lease.expires_at_ms =
+                rt.unix_timestamp_ms()? + LEADER_LEASE_DURATION.as_millis() as i64;
+            write_lease(persistence.as_ref(), &lease).await?;
+        }
+    };
+    if let Err(mut e) = result {
+        report_error(&mut e).await;
+        shutdown_signal.signal(e.context("Lost the leader lease"));
+    }
+}
+
+/// Routes served while standing by. Only health checks succeed, and `/readyz`
+/// fails so load balancers send traffic to the leader. Every other request,
+/// including read-only queries, is rejected with a retryable error.
+pub fn standby_router(version: String) -> Router {
+    Router::new()
+        .route("/instance_version", get(|| async move { version }))
+        .route("/healthz", get(|| async { "Standing by" }))
+        .route(
+            "/readyz",
+            get(|| async { (StatusCode::SERVICE_UNAVAILABLE, "Standing by") }),
+        )
+        .fallback(|| async {
+            HttpResponseError::from(anyhow::anyhow!(ErrorMetadata::rejected_before_execution(
+                "BackendStandingBy",
+                "This backend is standing by for another backend. Retry the request.",
+            )))
+        })
+}
+
+/// Picks a holder ID that is unique across restarts of this process.
+pub fn new_holder_id(instance_name: &str) -> String {
+    format!("{instance_name}-{:016x}", rand::random::<u64>())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::body::Body;
+    use common::{
+        knobs::LEADER_LEASE_DURATION,
+        persistence::Persistence,
+        runtime::Runtime,
+        shutdown::ShutdownSignal,
+        testing::{
+            assert_contains,
+            TestPersistence,
+        },
+    };
+    use futures::FutureExt;
+    use http::{
+        Request,
+        StatusCode,
+    };
+    use runtime::testing::TestRuntime;
+    use tokio::sync::oneshot;
+    use tower::ServiceExt;
+
+    use super::{
+        acquire_leadership,
+        read_lease,
+        renew_leadership,
+        standby_router,
+        wait_for_leadership,
+    };
+
+    #[convex_macro::test_runtime]
+    async fn test_takeover_after_expiry(rt: TestRuntime) -> anyhow::Result<()> {
+        let persistence = Arc::new(TestPersistence::new());
+        let reader = persistence.reader();
+        assert_eq!(wait_for_leadership(&rt, reader.as_ref(), "a").await?, None);
+        let lease = acquire_leadership(&rt, persistence.as_ref(), "a".to_string(), None).await?;
+        assert_eq!(lease.fencing_token, 1);
+
+        // The standby only takes over once the lease expires.
+        let previous = wait_for_leadership(&rt, reader.as_ref(), "b").await?;
+        assert_eq!(previous.as_ref(), Some(&lease));
+        assert!(previous.as_ref().unwrap().is_expired(&rt)?);
+        let lease =
+            acquire_leadership(&rt, persistence.as_ref(), "b".to_string(), previous).await?;
+        assert_eq!(lease.fencing_token, 2);
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_holder_reclaims_own_lease(rt: TestRuntime) -> anyhow::Result<()> {
+        let persistence = Arc::new(TestPersistence::new());
+        let lease = acquire_leadership(&rt, persistence.as_ref(), "a".to_string(), None).await?;
+        // A restarted leader with the same holder doesn't wait out its own lease.
+        let previous = wait_for_leadership(&rt, persistence.reader().as_ref(), "a")
+            .now_or_never()
+            .expect("Shouldn't wait for its own lease")?;
+        assert_eq!(previous, Some(lease));
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_renewal_keeps_lease(rt: TestRuntime) -> anyhow::Result<()> {
+        let persistence: Arc<dyn Persistence> = Arc::new(TestPersistence::new());
+        let lease = acquire_leadership(&rt, persistence.as_ref(), "a".to_string(), None).await?;
+        let (shutdown_tx, mut shutdown_rx) = oneshot::channel();
+        let _renewer = rt.spawn(
+            "leader_lease_renewer",
+            renew_leadership(
+                rt.clone(),
+                persistence.clone(),
+                lease.clone(),
+                ShutdownSignal::new(shutdown_tx),
+            ),
+        );
+        rt.wait(*LEADER_LEASE_DURATION * 3).await;
+        let current = read_lease(persistence.reader().as_ref())
+            .await?
+            .expect("Lease should exist");
+        assert_eq!(current.fencing_token, lease.fencing_token);
+        assert!(!current.is_expired(&rt)?);
+        assert!(shutdown_rx.try_recv().is_err());
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_renewer_shuts_down_after_takeover(rt: TestRuntime) -> anyhow::Result<()> {
+        let persistence: Arc<dyn Persistence> = Arc::new(TestPersistence::new());
+        let lease = acquire_leadership(&rt, persistence.as_ref(), "a".to_string(), None).await?;
+        acquire_leadership(
+            &rt,
+            persistence.as_ref(),
+            "b".to_string(),
+            Some(lease.clone()),
+        )
+        .await?;
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+        renew_leadership(
+            rt.clone(),
+            persistence.clone(),
+            lease,
+            ShutdownSignal::new(shutdown_tx),
+        )
+        .await;
+        let error = shutdown_rx.await?;
+        assert_contains(&error, "Lost the leader lease");
+        // The renewer must not overwrite its successor's lease.
+        let current = read_lease(persistence.reader().as_ref())
+            .await?
+            .expect("Lease should exist");
+        assert_eq!(current.holder, "b");
+        assert_eq!(current.fencing_token, 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_standby_router() -> anyhow::Result<()> {
+        let get = |uri: &str| {
+            Request::builder()
+                .uri(uri)
+                .method("GET")
+                .body(Body::empty())
+        };
+        let router = standby_router("1.0.0".to_string());
+        let response = router.clone().oneshot(get("/healthz")?).await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = router.clone().oneshot(get("/readyz")?).await?;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let response = router.clone().oneshot(get("/instance_version")?).await?;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Queries are rejected like every other request.
+        let response = router
+            .oneshot(
+                Request::builder()
+                    .uri("/api/query")
+                    .method("POST")
+                    .header("Content-Type", "application/json")
+                    .body(Body::from(r#"{"path":"messages:list","args":{}}"#))?,
+            )
+            .await?;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+        assert_contains(&String::from_utf8(body.to_vec())?, "BackendStandingBy");
+        Ok(())
+    }
+}
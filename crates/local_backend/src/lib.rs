@@ -14,10 +14,12 @@ use ::authentication::{
     access_token_auth::NullAccessTokenAuth,
     application_auth::ApplicationAuth,
 };
+use anyhow::Context;
 use application::{
     self,
     api::ApplicationApi,
     log_visibility::RedactLogsToClient,
+    usage_metering::UsageMeteringLogger,
     Application,
     QueryCache,
 };
@@ -30,8 +32,8 @@ use common::{
     knobs::{
         ACTION_USER_TIMEOUT,
         UDF_CACHE_MAX_SIZE,
+        USAGE_METERING_RETENTION,
     },
-    log_streaming::NoopLogSender,
     persistence::Persistence,
     runtime::Runtime,
     shutdown::ShutdownSignal,
@@ -42,7 +44,11 @@ use common::{
 };
 use config::LocalConfig;
 use database::Database;
-use events::usage::NoOpUsageEventLogger;
+use drain::DrainSignal;
+use events::usage::{
+    NoOpUsageEventLogger,
+    UsageEventLogger,
+};
 use file_storage::{
     FileStorage,
     TransactionalFileStorage,
@@ -52,17 +58,22 @@ use function_runner::{
     server::InstanceStorage,
     FunctionRunner,
 };
+use log_sinks::LocalLogManager;
 use model::{
     initialize_application_system_tables,
     virtual_system_mapping,
 };
+use network_policy::NetworkPolicy;
 use node_executor::{
     local::LocalNodeExecutor,
     Actions,
 };
 use runtime::prod::ProdRuntime;
 use search::{
-    searcher::InProcessSearcher,
+    searcher::{
+        InProcessSearcher,
+        SearchlightClient,
+    },
     Searcher,
     SegmentTermMetadataFetcher,
 };
@@ -73,29 +84,55 @@ mod app_metrics;
 mod args_structs;
 pub mod authentication;
 pub mod beacon;
+pub mod bench;
+pub mod canary;
 pub mod canonical_urls;
+pub mod code_versions;
 pub mod config;
+pub mod cursor_validity;
 pub mod custom_headers;
 pub mod dashboard;
+pub mod data_masking;
 pub mod deploy_config;
 pub mod deploy_config2;
+pub mod drain;
 pub mod environment_variables;
+pub mod erasure;
+pub mod feature_flags;
+pub mod fixtures;
+pub mod health;
 pub mod http_actions;
+pub mod http_checks;
+pub mod index_scan;
+pub mod leader_election;
+pub mod log_sinks;
 pub mod logs;
+pub mod maintenance_mode;
+pub mod network_policy;
 pub mod node_action_callbacks;
+pub mod otel;
 pub mod parse;
 pub mod proxy;
 pub mod public_api;
 pub mod router;
 pub mod scheduling;
 pub mod schema;
+pub mod search_compaction;
+pub mod search_synonyms;
+pub mod searchlight;
+pub mod simulated_time;
 pub mod snapshot_export;
 pub mod snapshot_import;
+pub mod static_assets;
 pub mod storage;
 pub mod streaming_import;
 pub mod subs;
+pub mod subscription_stats;
 #[cfg(test)]
 mod test_helpers;
+pub mod usage_metering;
+pub mod vector_index_migrations;
+pub mod webhooks;
 
 pub const MAX_CONCURRENT_REQUESTS: usize = 128;
 
@@ -109,6 +146,10 @@ pub struct LocalAppState {
     pub instance_name: String,
     pub application: Application<ProdRuntime>,
     pub zombify_rx: async_broadcast::Receiver<()>,
+    pub network_policy: Arc<NetworkPolicy>,
+    pub drain: DrainSignal,
+    /// Whether `/load_fixtures` may seed this deployment.
+    pub enable_fixture_loading: bool,
 }
 
 impl LocalAppState {
@@ -126,6 +167,7 @@ impl LocalAppState {
 pub struct RouterState {
     pub api: Arc<dyn ApplicationApi>,
     pub runtime: ProdRuntime,
+    pub drain: DrainSignal,
 }
 
 #[derive(Serialize)]
@@ -139,20 +181,46 @@ pub async fn make_app(
     preempt_tx: ShutdownSignal,
 ) -> anyhow::Result<LocalAppState> {
     let key_broker = config.key_broker()?;
-    let in_process_searcher = InProcessSearcher::new(runtime.clone()).await?;
-    let searcher: Arc<dyn Searcher> = Arc::new(in_process_searcher.clone());
     // TODO(CX-6572) Separate `SegmentMetadataFetcher` from `SearcherImpl`
-    let segment_metadata_fetcher: Arc<dyn SegmentTermMetadataFetcher> =
-        Arc::new(in_process_searcher);
+    let (searcher, segment_metadata_fetcher): (
+        Arc<dyn Searcher>,
+        Arc<dyn SegmentTermMetadataFetcher>,
+    ) = if config.searchlight_urls.is_empty() {
+        let in_process_searcher = InProcessSearcher::new(runtime.clone()).await?;
+        (
+            Arc::new(in_process_searcher.clone()),
+            Arc::new(in_process_searcher),
+        )
+    } else {
+        tracing::info!("Using searchlight at {}", config.searchlight_urls.join(","));
+        let secret = config
+            .searchlight_secret
+            .as_deref()
+            .context("--searchlight-secret is required with --searchlight-urls")?;
+        let client = SearchlightClient::new(config.searchlight_urls.clone(), secret)?;
+        (Arc::new(client.clone()), Arc::new(client))
+    };
+    let usage_metering =
+        USAGE_METERING_RETENTION.map(|_| UsageMeteringLogger::new(runtime.clone()));
+    let usage_events: Arc<dyn UsageEventLogger> = match &usage_metering {
+        Some(logger) => logger.clone(),
+        None => Arc::new(NoOpUsageEventLogger),
+    };
     let database = Database::load(
         persistence.clone(),
         runtime.clone(),
         searcher.clone(),
         preempt_tx,
         virtual_system_mapping().clone(),
-        Arc::new(NoOpUsageEventLogger),
+        usage_events,
     )
     .await?;
+    if let Some(logger) = usage_metering {
+        runtime.spawn_background(
+            "usage_metering_flush_worker",
+            logger.flush_worker(database.clone()),
+        );
+    }
     initialize_application_system_tables(&database).await?;
     let application_storage = Application::initialize_storage(
         runtime.clone(),
@@ -202,7 +270,7 @@ pub async fn make_app(
                 modules_storage: application_storage.modules_storage.clone(),
             },
             database.clone(),
-            fetch_client,
+            fetch_client.clone(),
         )
         .await?,
     );
@@ -222,13 +290,15 @@ pub async fn make_app(
         segment_metadata_fetcher,
         persistence,
         actions,
-        Arc::new(NoopLogSender),
+        LocalLogManager::start(runtime.clone(), database.clone()),
         Arc::new(RedactLogsToClient::new(config.redact_logs_to_client)),
         Arc::new(ApplicationAuth::new(
             key_broker.clone(),
             Arc::new(NullAccessTokenAuth),
         )),
         QueryCache::new(*UDF_CACHE_MAX_SIZE),
+        config.export_encryption_key.clone(),
+        fetch_client,
     )
     .await?;
 
@@ -251,6 +321,9 @@ pub async fn make_app(
         instance_name,
         application,
         zombify_rx,
+        network_policy: Arc::new(NetworkPolicy::from_config(&config)?),
+        drain: DrainSignal::default(),
+        enable_fixture_loading: config.enable_fixture_loading,
     };
 
     Ok(app_state)
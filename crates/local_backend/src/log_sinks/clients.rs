@@ -0,0 +1,485 @@
+//! HTTP (and file) clients for each supported log sink type. Each client
+//! sends one batch of events per call; batching and retries live in
+//! `super::sink`.
+
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use common::log_streaming::{
+    LogEvent,
+    LogEventFormatVersion,
+    StructuredLogEvent,
+};
+use flate2::{
+    write::GzEncoder,
+    Compression,
+};
+use http::header::{
+    CONTENT_ENCODING,
+    CONTENT_TYPE,
+};
+use model::log_sinks::types::{
+    datadog::DatadogConfig,
+    loki::LokiConfig,
+    sentry::SentryConfig,
+    webhook::WebhookConfig,
+    SinkConfig,
+};
+use sentry::{
+    protocol::{
+        Event,
+        Exception,
+        Frame,
+        Level,
+        Stacktrace,
+        User,
+    },
+    types::Dsn,
+};
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+use tokio::io::AsyncWriteExt;
+
+#[async_trait]
+pub trait SinkClient: Send + Sync {
+    async fn send(&self, events: &[LogEvent]) -> anyhow::Result<()>;
+}
+
+pub fn sink_client(
+    config: &SinkConfig,
+    http_client: reqwest::Client,
+) -> anyhow::Result<Arc<dyn SinkClient>> {
+    Ok(match config {
+        SinkConfig::Local(path) => Arc::new(LocalFileClient { path: path.clone() }),
+        SinkConfig::Datadog(config) => Arc::new(DatadogClient {
+            config: config.clone(),
+            http_client,
+        }),
+        SinkConfig::Loki(config) => Arc::new(LokiClient {
+            config: config.clone(),
+            http_client,
+        }),
+        SinkConfig::Webhook(config) => Arc::new(WebhookClient {
+            config: config.clone(),
+            http_client,
+        }),
+        SinkConfig::Sentry(config) => Arc::new(SentryClient {
+            config: config.clone(),
+            http_client,
+        }),
+        config => anyhow::bail!(
+            "{:?} log sinks are not supported by this backend",
+            config.sink_type()
+        ),
+    })
+}
+
+fn gzip_json(body: &JsonValue) -> anyhow::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    serde_json::to_writer(&mut encoder, body)?;
+    Ok(encoder.finish()?)
+}
+
+fn to_json_lines(
+    events: &[LogEvent],
+    format: LogEventFormatVersion,
+) -> anyhow::Result<Vec<serde_json::Map<String, JsonValue>>> {
+    events
+        .iter()
+        .map(|event| event.to_json_map(format))
+        .collect()
+}
+
+struct DatadogClient {
+    config: DatadogConfig,
+    http_client: reqwest::Client,
+}
+
+#[async_trait]
+impl SinkClient for DatadogClient {
+    async fn send(&self, events: &[LogEvent]) -> anyhow::Result<()> {
+        let ddtags = self.config.dd_tags.join(",");
+        let service = self.config.service.as_deref().unwrap_or("convex");
+        let body: Vec<JsonValue> = to_json_lines(events, self.config.version)?
+            .into_iter()
+            .map(|mut fields| {
+                fields.insert("ddsource".to_string(), json!("convex"));
+                fields.insert("ddtags".to_string(), json!(ddtags));
+                fields.insert("service".to_string(), json!(service));
+                JsonValue::Object(fields)
+            })
+            .collect();
+        self.http_client
+            .post(self.config.site_location.get_logging_endpoint()?)
+            .header("DD-API-KEY", &self.config.dd_api_key.0)
+            .header(CONTENT_TYPE, "application/json")
+            .header(CONTENT_ENCODING, "gzip")
+            .body(gzip_json(&JsonValue::Array(body))?)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+struct LokiClient {
+    config: LokiConfig,
+    http_client: reqwest::Client,
+}
+
+impl LokiClient {
+    /// Resolves the push endpoint under the configured URL, keeping any base
+    /// path even if it doesn't end in a slash.
+    fn push_url(&self) -> anyhow::Result<reqwest::Url> {
+        let mut url = self.config.url.clone();
+        if !url.path().ends_with('/') {
+            url.set_path(&format!("{}/", url.path()));
+        }
+        Ok(url.join("loki/api/v1/push")?)
+    }
+
+    /// Groups events into one Loki stream per topic.
+    fn push_request(&self, events: &[LogEvent]) -> anyhow::Result<JsonValue> {
+        let mut streams: BTreeMap<String, Vec<JsonValue>> = BTreeMap::new();
+        for event in events {
+            let fields = event.to_json_map(LogEventFormatVersion::V2)?;
+            let topic = fields
+                .get("topic")
+                .and_then(|topic| topic.as_str())
+                .unwrap_or("unknown")
+                .to_string();
+            streams.entry(topic).or_default().push(json!([
+                event.timestamp.as_nanos().to_string(),
+                JsonValue::Object(fields).to_string(),
+            ]));
+        }
+        let streams: Vec<_> = streams
+            .into_iter()
+            .map(|(topic, values)| {
+                let mut labels = self.config.labels.clone();
+                labels.insert("source".to_string(), "convex".to_string());
+                labels.insert("topic".to_string(), topic);
+                json!({ "stream": labels, "values": values })
+            })
+            .collect();
+        Ok(json!({ "streams": streams }))
+    }
+}
+
+#[async_trait]
+impl SinkClient for LokiClient {
+    async fn send(&self, events: &[LogEvent]) -> anyhow::Result<()> {
+        let mut request = self
+            .http_client
+            .post(self.push_url()?)
+            .header(CONTENT_TYPE, "application/json")
+            .header(CONTENT_ENCODING, "gzip")
+            .body(gzip_json(&self.push_request(events)?)?);
+        if let Some(tenant_id) = &self.config.tenant_id {
+            request = request.header("X-Scope-OrgID", tenant_id);
+        }
+        request.send().await?.error_for_status()?;
+        Ok(())
+    }
+}
+
+struct WebhookClient {
+    config: WebhookConfig,
+    http_client: reqwest::Client,
+}
+
+#[async_trait]
+impl SinkClient for WebhookClient {
+    async fn send(&self, events: &[LogEvent]) -> anyhow::Result<()> {
+        // Webhooks receive uncompressed JSON since we can't assume the
+        // receiver handles `Content-Encoding`.
+        self.http_client
+            .post(self.config.url.clone())
+            .json(&to_json_lines(events, LogEventFormatVersion::V2)?)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Forwards uncaught exceptions to a Sentry-compatible store endpoint. Other
+/// events, including the verification event, are dropped since they aren't
+/// errors.
+struct SentryClient {
+    config: SentryConfig,
+    http_client: reqwest::Client,
+}
+
+impl SentryClient {
+    fn sentry_event(&self, event: &LogEvent) -> Option<Event<'static>> {
+        let StructuredLogEvent::Exception {
+            error,
+            user_identifier,
+            source,
+            udf_server_version,
+        } = &event.event
+        else {
+            return None;
+        };
+        // Frames are already source-mapped. Sentry expects the outermost
+        // frame first.
+        let mut frames: Vec<_> = error
+            .frames
+            .iter()
+            .flat_map(|frames| frames.0.iter().cloned())
+            .map(Frame::from)
+            .collect();
+        frames.reverse();
+        let (ty, value) = exception_type_and_value(&error.message);
+        let mut tags: BTreeMap<String, String> = self
+            .config
+            .tags
+            .iter()
+            .flatten()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect();
+        tags.insert("func".to_string(), source.udf_path.clone());
+        tags.insert(
+            "func_type".to_string(),
+            source.udf_type.to_lowercase_string().to_string(),
+        );
+        tags.insert(
+            "func_runtime".to_string(),
+            source.module_environment.to_string(),
+        );
+        tags.insert(
+            "request_id".to_string(),
+            source.context.request_id.to_string(),
+        );
+        if !source.component_path.is_root() {
+            tags.insert(
+                "component".to_string(),
+                String::from(source.component_path.clone()),
+            );
+        }
+        Some(Event {
+            level: Level::Error,
+            // Group the same way as `_error_groups` so the two line up.
+            fingerprint: Cow::Owned(vec![Cow::Owned(
+                error.fingerprint(&source.component_path, &source.udf_path),
+            )]),
+            transaction: Some(source.udf_path.clone()),
+            platform: "javascript".into(),
+            timestamp: event.timestamp.as_system_time(),
+            release: udf_server_version
+                .as_ref()
+                .map(|version| version.to_string().into()),
+            user: user_identifier.as_ref().map(|user| User {
+                id: Some(user.0.clone()),
+                ..Default::default()
+            }),
+            exception: vec![Exception {
+                ty,
+                value: Some(value),
+                stacktrace: (!frames.is_empty()).then(|| Stacktrace {
+                    frames,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }]
+            .into(),
+            tags,
+            ..Default::default()
+        })
+    }
+}
+
+/// Splits `Uncaught TypeError: x is undefined` into the error's type and
+/// message, falling back to `Error` when the message doesn't name its type.
+fn exception_type_and_value(message: &str) -> (String, String) {
+    let message = message.strip_prefix("Uncaught ").unwrap_or(message);
+    match message.split_once(": ") {
+        Some((ty, value)) if !ty.is_empty() && !ty.contains(char::is_whitespace) => {
+            (ty.to_string(), value.to_string())
+        },
+        _ => ("Error".to_string(), message.to_string()),
+    }
+}
+
+#[async_trait]
+impl SinkClient for SentryClient {
+    async fn send(&self, events: &[LogEvent]) -> anyhow::Result<()> {
+        let dsn: &Dsn = &self.config.dsn;
+        let mut auth = format!(
+            "Sentry sentry_version=7, sentry_client=convex-backend, sentry_key={}",
+            dsn.public_key()
+        );
+        if let Some(secret_key) = dsn.secret_key() {
+            auth.push_str(&format!(", sentry_secret={secret_key}"));
+        }
+        // The store endpoint takes one event per request.
+        for event in events.iter().filter_map(|event| self.sentry_event(event)) {
+            self.http_client
+                .post(dsn.store_api_url())
+                .header("X-Sentry-Auth", &auth)
+                .json(&event)
+                .send()
+                .await?
+                .error_for_status()?;
+        }
+        Ok(())
+    }
+}
+
+/// Appends events as JSON lines to a file on the backend's disk.
+struct LocalFileClient {
+    path: String,
+}
+
+#[async_trait]
+impl SinkClient for LocalFileClient {
+    async fn send(&self, events: &[LogEvent]) -> anyhow::Result<()> {
+        let mut buf = vec![];
+        for fields in to_json_lines(events, LogEventFormatVersion::V2)? {
+            serde_json::to_writer(&mut buf, &fields)?;
+            buf.push(b'\n');
+        }
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(&buf).await?;
+        file.flush().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use common::{
+        log_streaming::{
+            LogEvent,
+            StructuredLogEvent,
+        },
+        runtime::UnixTimestamp,
+    };
+    use model::log_sinks::types::{
+        loki::LokiConfig,
+        sentry::{
+            ExceptionFormatVersion,
+            SentryConfig,
+            TEST_DSN,
+        },
+    };
+    use runtime::testing::TestRuntime;
+    use sentry::types::Dsn;
+    use serde_json::json;
+
+    use super::{
+        exception_type_and_value,
+        LokiClient,
+        SentryClient,
+    };
+
+    #[test]
+    fn test_loki_push_request() -> anyhow::Result<()> {
+        let client = LokiClient {
+            config: LokiConfig {
+                url: "http://loki:3100".parse()?,
+                tenant_id: None,
+                labels: BTreeMap::from([("env".to_string(), "prod".to_string())]),
+            },
+            http_client: reqwest::Client::new(),
+        };
+        let request = client.push_request(&[LogEvent {
+            timestamp: UnixTimestamp::from_millis(1700000000000),
+            event: StructuredLogEvent::Verification,
+        }])?;
+        assert_eq!(
+            request["streams"][0]["stream"],
+            json!({ "env": "prod", "source": "convex", "topic": "verification" })
+        );
+        let [timestamp, line] = &request["streams"][0]["values"][0].as_array().unwrap()[..] else {
+            panic!("Expected [timestamp, line] pair");
+        };
+        assert_eq!(timestamp, "1700000000000000000");
+        let line: serde_json::Value = serde_json::from_str(line.as_str().unwrap())?;
+        assert_eq!(line["message"], "Convex connection test");
+        Ok(())
+    }
+
+    #[test]
+    fn test_loki_push_url() -> anyhow::Result<()> {
+        let push_url = |url: &str| -> anyhow::Result<String> {
+            let client = LokiClient {
+                config: LokiConfig {
+                    url: url.parse()?,
+                    tenant_id: None,
+                    labels: BTreeMap::new(),
+                },
+                http_client: reqwest::Client::new(),
+            };
+            Ok(client.push_url()?.to_string())
+        };
+        assert_eq!(
+            push_url("http://loki:3100")?,
+            "http://loki:3100/loki/api/v1/push"
+        );
+        assert_eq!(
+            push_url("https://grafana.example.com/logs")?,
+            "https://grafana.example.com/logs/loki/api/v1/push"
+        );
+        assert_eq!(
+            push_url("https://grafana.example.com/logs/")?,
+            "https://grafana.example.com/logs/loki/api/v1/push"
+        );
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_sentry_event(rt: TestRuntime) -> anyhow::Result<()> {
+        let client = SentryClient {
+            config: SentryConfig {
+                dsn: TEST_DSN.parse::<Dsn>()?.into(),
+                tags: None,
+                version: ExceptionFormatVersion::V2,
+            },
+            http_client: reqwest::Client::new(),
+        };
+        assert!(client
+            .sentry_event(&LogEvent::default_for_verification(&rt)?)
+            .is_none());
+
+        let event = client
+            .sentry_event(&LogEvent::sample_exception(&rt)?)
+            .unwrap();
+        assert_eq!(event.fingerprint.len(), 1);
+        assert_eq!(event.transaction.as_deref(), Some("test"));
+        assert_eq!(event.tags["func"], "test");
+        assert_eq!(event.user.unwrap().id.as_deref(), Some("test|user"));
+        let exception = &event.exception.values[0];
+        assert_eq!(exception.ty, "Error");
+        assert_eq!(exception.value.as_deref(), Some("test_message"));
+        let frames = &exception.stacktrace.as_ref().unwrap().frames;
+        assert_eq!(frames[0].filename.as_deref(), Some("test_frame_2"));
+        assert_eq!(frames[1].filename.as_deref(), Some("test_frame_1"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_exception_type_and_value() {
+        assert_eq!(
+            exception_type_and_value("Uncaught TypeError: x is undefined"),
+            ("TypeError".to_string(), "x is undefined".to_string())
+        );
+        assert_eq!(
+            exception_type_and_value("Server Error: oops"),
+            ("Error".to_string(), "Server Error: oops".to_string())
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::http::{
+    extract::Json,
+    HttpResponseError,
+};
+use errors::ErrorMetadata;
+use http::StatusCode;
+use model::log_sinks::{
+    types::{
+        filter::{
+            LogSinkFilter,
+            SerializedLogSinkFilter,
+        },
+        SerializedSinkConfig,
+        SerializedSinkState,
+        SinkConfig,
+        SinkType,
+    },
+    LogSinksModel,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+/// A configured sink, without its config since that includes credentials.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogSinkSummary {
+    sink_type: SinkType,
+    status: SerializedSinkState,
+    filter: SerializedLogSinkFilter,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListLogSinksResponse {
+    sinks: Vec<LogSinkSummary>,
+}
+
+#[debug_handler]
+pub async fn list_log_sinks(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let mut tx = st.application.begin(identity).await?;
+    let sinks = LogSinksModel::new(&mut tx)
+        .get_all()
+        .await?
+        .into_iter()
+        .map(|row| {
+            let row = row.into_value();
+            LogSinkSummary {
+                sink_type: row.config.sink_type(),
+                status: row.status.into(),
+                filter: row.filter.into(),
+            }
+        })
+        .collect();
+    Ok(Json(ListLogSinksResponse { sinks }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddLogSinkRequest {
+    config: SerializedSinkConfig,
+    #[serde(default)]
+    filter: SerializedLogSinkFilter,
+}
+
+/// Adds a sink, replacing any existing sink of the same type. The sink starts
+/// out `pending` until a verification event has been delivered to it.
+#[debug_handler]
+pub async fn add_log_sink(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(AddLogSinkRequest { config, filter }): Json<AddLogSinkRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let config = SinkConfig::try_from(config).map_err(|e| {
+        anyhow::anyhow!(ErrorMetadata::bad_request(
+            "InvalidLogSinkConfig",
+            format!("Invalid log sink config: {e}"),
+        ))
+    })?;
+    // Local sinks write to a path on the backend's filesystem, so only the
+    // operator can set them up.
+    if let SinkConfig::Local(_) = config {
+        return Err(anyhow::anyhow!(ErrorMetadata::bad_request(
+            "InvalidLogSinkConfig",
+            "Local file log sinks can't be added over HTTP",
+        ))
+        .into());
+    }
+    let filter = LogSinkFilter::try_from(filter).map_err(|e| {
+        anyhow::anyhow!(ErrorMetadata::bad_request(
+            "InvalidLogSinkFilter",
+            format!("Invalid log sink filter: {e}"),
+        ))
+    })?;
+    let mut tx = st.application.begin(identity).await?;
+    LogSinksModel::new(&mut tx)
+        .add_or_update(config, filter)
+        .await?;
+    st.application.commit(tx, "add_log_sink").await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveLogSinkRequest {
+    sink_type: SinkType,
+}
+
+#[debug_handler]
+pub async fn remove_log_sink(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(RemoveLogSinkRequest { sink_type }): Json<RemoveLogSinkRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let mut tx = st.application.begin(identity).await?;
+    LogSinksModel::new(&mut tx).remove(sink_type).await?;
+    st.application.commit(tx, "remove_log_sink").await?;
+    Ok(StatusCode::OK)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum_extra::headers::authorization::Credentials;
+    use http::{
+        Request,
+        StatusCode,
+    };
+    use runtime::prod::ProdRuntime;
+    use serde_json::{
+        json,
+        Value as JsonValue,
+    };
+
+    use crate::test_helpers::setup_backend_for_test;
+
+    #[convex_macro::prod_rt_test]
+    async fn test_add_and_remove_log_sink(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let req = Request::builder()
+            .uri("/api/add_log_sink")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .header("Authorization", backend.admin_auth_header.0.encode())
+            .body(axum::body::Body::from(serde_json::to_vec(&json!({
+                "config": { "type": "webhook", "url": "http://127.0.0.1:1/logs" },
+                "filter": { "functionPathPrefixes": ["messages:"], "minLogLevel": "warn" },
+            }))?))?;
+        let () = backend.expect_success(req).await?;
+
+        let req = Request::builder()
+            .uri("/api/list_log_sinks")
+            .method("GET")
+            .header("Authorization", backend.admin_auth_header.0.encode())
+            .body(axum::body::Body::empty())?;
+        let result: JsonValue = backend.expect_success(req).await?;
+        let sinks = result["sinks"].as_array().unwrap();
+        assert_eq!(sinks.len(), 1);
+        assert_eq!(sinks[0]["sinkType"], "webhook");
+        assert_eq!(
+            sinks[0]["filter"],
+            json!({ "functionPathPrefixes": ["messages:"], "minLogLevel": "WARN" })
+        );
+
+        let req = Request::builder()
+            .uri("/api/remove_log_sink")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .header("Authorization", backend.admin_auth_header.0.encode())
+            .body(axum::body::Body::from(serde_json::to_vec(
+                &json!({ "sinkType": "webhook" }),
+            )?))?;
+        let () = backend.expect_success(req).await?;
+        Ok(())
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_add_local_log_sink_rejected(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let req = Request::builder()
+            .uri("/api/add_log_sink")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .header("Authorization", backend.admin_auth_header.0.encode())
+            .body(axum::body::Body::from(serde_json::to_vec(&json!({
+                "config": { "type": "local", "path": "/etc/passwd" },
+            }))?))?;
+        backend
+            .expect_error(req, StatusCode::BAD_REQUEST, "InvalidLogSinkConfig")
+            .await?;
+        Ok(())
+    }
+}
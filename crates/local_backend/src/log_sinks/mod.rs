@@ -0,0 +1,222 @@
+//! Streams function logs to the sinks configured in the `_log_sinks` table.
+//!
+//! `LocalLogManager` watches the table and keeps one background task per
+//! active sink. New sinks start out `Pending`: we send them a verification
+//! event and mark them `Active` or `Failed` depending on whether it was
+//! delivered. Removed sinks are marked `Tombstoned` and deleted here once
+//! their task has been stopped.
+
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::Duration,
+};
+
+use common::{
+    backoff::Backoff,
+    document::ParsedDocument,
+    errors::report_error,
+    log_streaming::{
+        LogEvent,
+        LogSender,
+    },
+    runtime::Runtime,
+};
+use database::Database;
+use keybroker::Identity;
+use model::log_sinks::{
+    types::{
+        filter::LogSinkFilter,
+        LogSinksRow,
+        SinkState,
+    },
+    LogSinksModel,
+};
+use parking_lot::RwLock;
+use tokio::sync::mpsc;
+use value::ResolvedDocumentId;
+
+use self::{
+    clients::sink_client,
+    sink::{
+        run_sink,
+        send_with_retries,
+        SINK_BUFFER_SIZE,
+    },
+};
+
+mod clients;
+pub mod handlers;
+mod sink;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+struct RunningSink {
+    name: String,
+    filter: LogSinkFilter,
+    sender: mpsc::Sender<LogEvent>,
+}
+
+pub struct LocalLogManager {
+    sinks: RwLock<BTreeMap<ResolvedDocumentId, RunningSink>>,
+}
+
+impl LogSender for LocalLogManager {
+    fn send_logs(&self, logs: Vec<LogEvent>) {
+        let sinks = self.sinks.read();
+        for sink in sinks.values() {
+            for event in logs.iter().filter(|event| sink.filter.matches(event)) {
+                if sink.sender.try_send(event.clone()).is_err() {
+                    tracing::warn!("{} log sink is backed up, dropping log events", sink.name);
+                    break;
+                }
+            }
+        }
+    }
+
+    fn shutdown(&self) -> anyhow::Result<()> {
+        // Dropping the senders lets each sink flush its buffered events and
+        // exit.
+        self.sinks.write().clear();
+        Ok(())
+    }
+}
+
+impl LocalLogManager {
+    pub fn start<RT: Runtime>(rt: RT, database: Database<RT>) -> Arc<Self> {
+        let manager = Arc::new(Self {
+            sinks: RwLock::new(BTreeMap::new()),
+        });
+        let worker = manager.clone();
+        rt.clone().spawn_background("log_sink_worker", async move {
+            let http_client = reqwest::Client::new();
+            let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+            loop {
+                if let Err(e) = worker.run_once(&rt, &database, &http_client).await {
+                    report_error(&mut e.context("LocalLogManager died")).await;
+                    let delay = backoff.fail(&mut rt.rng());
+                    rt.wait(delay).await;
+                } else {
+                    backoff.reset();
+                }
+            }
+        });
+        manager
+    }
+
+    /// Reconciles running sinks with the `_log_sinks` table, then waits for
+    /// the table to change.
+    async fn run_once<RT: Runtime>(
+        &self,
+        rt: &RT,
+        database: &Database<RT>,
+        http_client: &reqwest::Client,
+    ) -> anyhow::Result<()> {
+        let mut tx = database.begin(Identity::system()).await?;
+        let rows = LogSinksModel::new(&mut tx).get_all().await?;
+        let token = tx.into_token()?;
+
+        // Stop sinks whose rows were deleted or are no longer running.
+        self.sinks.write().retain(|id, _| {
+            rows.iter().any(|row| {
+                row.id() == *id && matches!(row.status, SinkState::Pending | SinkState::Active)
+            })
+        });
+        for row in rows {
+            match row.status {
+                SinkState::Tombstoned => {
+                    let mut tx = database.begin(Identity::system()).await?;
+                    LogSinksModel::new(&mut tx)
+                        .delete_tombstoned(row.id())
+                        .await?;
+                    database
+                        .commit_with_write_source(tx, "log_sink_worker")
+                        .await?;
+                },
+                SinkState::Failed { .. } => {},
+                SinkState::Pending | SinkState::Active => {
+                    if !self.sinks.read().contains_key(&row.id()) {
+                        self.start_sink(rt, database, http_client, row).await?;
+                    }
+                },
+            }
+        }
+
+        let subscription = database.subscribe(token).await?;
+        subscription.wait_for_invalidation().await;
+        Ok(())
+    }
+
+    async fn start_sink<RT: Runtime>(
+        &self,
+        rt: &RT,
+        database: &Database<RT>,
+        http_client: &reqwest::Client,
+        row: ParsedDocument<LogSinksRow>,
+    ) -> anyhow::Result<()> {
+        let id = row.id();
+        let name = format!("{:?}", row.config.sink_type());
+        let client = match sink_client(&row.config, http_client.clone()) {
+            Ok(client) => client,
+            Err(e) => {
+                return self
+                    .set_status(
+                        database,
+                        id,
+                        SinkState::Failed {
+                            reason: e.to_string(),
+                        },
+                    )
+                    .await;
+            },
+        };
+        if row.status == SinkState::Pending {
+            tracing::info!("Verifying {name} log sink");
+            let verification = LogEvent::default_for_verification(rt)?;
+            if let Err(e) = send_with_retries(rt, client.as_ref(), &[verification]).await {
+                tracing::warn!("Failed to verify {name} log sink: {e:#}");
+                return self
+                    .set_status(
+                        database,
+                        id,
+                        SinkState::Failed {
+                            reason: format!("{e:#}"),
+                        },
+                    )
+                    .await;
+            }
+            self.set_status(database, id, SinkState::Active).await?;
+        }
+
+        let (sender, receiver) = mpsc::channel(SINK_BUFFER_SIZE);
+        rt.spawn_background(
+            "log_sink",
+            run_sink(rt.clone(), name.clone(), client, receiver),
+        );
+        tracing::info!("Started {name} log sink");
+        self.sinks.write().insert(
+            id,
+            RunningSink {
+                name,
+                filter: row.into_value().filter,
+                sender,
+            },
+        );
+        Ok(())
+    }
+
+    async fn set_status<RT: Runtime>(
+        &self,
+        database: &Database<RT>,
+        id: ResolvedDocumentId,
+        status: SinkState,
+    ) -> anyhow::Result<()> {
+        let mut tx = database.begin(Identity::system()).await?;
+        LogSinksModel::new(&mut tx).patch_status(id, status).await?;
+        database
+            .commit_with_write_source(tx, "log_sink_worker")
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+use std::{
+    sync::Arc,
+    time::Duration,
+};
+
+use common::{
+    backoff::Backoff,
+    log_streaming::LogEvent,
+    runtime::Runtime,
+};
+use futures::FutureExt;
+use tokio::sync::mpsc;
+
+use super::clients::SinkClient;
+
+/// Events buffered per sink before new events are dropped.
+pub const SINK_BUFFER_SIZE: usize = 10_000;
+const MAX_BATCH_SIZE: usize = 1000;
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+const MAX_ATTEMPTS: u32 = 5;
+const INITIAL_BACKOFF: Duration = Duration::from_millis(250);
+const MAX_BACKOFF: Duration = Duration::from_secs(10);
+
+/// Sends events from `rx` to `client` in batches of up to `MAX_BATCH_SIZE`,
+/// flushing at least every `FLUSH_INTERVAL`. Returns once every sender has
+/// been dropped and the remaining events have been flushed.
+pub async fn run_sink<RT: Runtime>(
+    rt: RT,
+    name: String,
+    client: Arc<dyn SinkClient>,
+    mut rx: mpsc::Receiver<LogEvent>,
+) {
+    let mut batch = Vec::with_capacity(MAX_BATCH_SIZE);
+    loop {
+        // Wait for the first event, then keep filling the batch until it's
+        // full or the flush interval passes.
+        let mut closed = rx.recv_many(&mut batch, MAX_BATCH_SIZE).await == 0;
+        let mut deadline = rt.wait(FLUSH_INTERVAL);
+        while !closed && batch.len() < MAX_BATCH_SIZE {
+            let remaining = MAX_BATCH_SIZE - batch.len();
+            futures::select_biased! {
+                n = rx.recv_many(&mut batch, remaining).fuse() => closed = n == 0,
+                _ = deadline => break,
+            }
+        }
+        if !batch.is_empty() {
+            if let Err(e) = send_with_retries(&rt, client.as_ref(), &batch).await {
+                tracing::error!("Dropping {} log events for {name} sink: {e:#}", batch.len());
+            }
+            batch.clear();
+        }
+        if closed {
+            tracing::info!("Stopped {name} log sink");
+            return;
+        }
+    }
+}
+
+/// Sends a batch, retrying with backoff on network errors, 429s, and 5xx
+/// responses.
+pub async fn send_with_retries<RT: Runtime>(
+    rt: &RT,
+    client: &dyn SinkClient,
+    events: &[LogEvent],
+) -> anyhow::Result<()> {
+    let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+    loop {
+        let e = match client.send(events).await {
+            Ok(()) => return Ok(()),
+            Err(e) => e,
+        };
+        if !is_retryable(&e) || backoff.failures() + 1 >= MAX_ATTEMPTS {
+            return Err(e);
+        }
+        let delay = backoff.fail(&mut rt.rng());
+        tracing::warn!("Failed to send log events, retrying in {delay:?}: {e:#}");
+        rt.wait(delay).await;
+    }
+}
+
+fn is_retryable(e: &anyhow::Error) -> bool {
+    let Some(e) = e.downcast_ref::<reqwest::Error>() else {
+        return true;
+    };
+    match e.status() {
+        Some(status) => status.is_server_error() || status.as_u16() == 429,
+        None => true,
+    }
+}
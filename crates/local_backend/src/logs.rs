@@ -19,6 +19,11 @@ use common::{
         ExtractClientVersion,
         HttpResponseError,
     },
+    log_lines::{
+        LogFieldMatch,
+        LogLevel,
+        LogLineStructured,
+    },
     version::ClientType,
     RequestId,
 };
@@ -125,6 +130,11 @@ pub struct StreamFunctionLogs {
     cursor: f64,
     session_id: Option<String>,
     client_request_counter: Option<u32>,
+    /// Only return log lines at or above this level.
+    min_log_level: Option<String>,
+    /// Only return log lines logged with `ctx.log` that have a field with this
+    /// value, written as `field=value`.
+    log_field: Option<String>,
 }
 // Streams log lines + function completion events.
 // Log lines can either appear in the completion (mutations, queries) or as
@@ -132,6 +142,7 @@ pub struct StreamFunctionLogs {
 //
 // If (session_id, client_request_counter) is provided, the results will be
 // filtered to events from the root execution of the corresponding request.
+// `min_log_level` and `log_field` filter the log lines within each event.
 pub async fn stream_function_logs(
     State(st): State<LocalAppState>,
     ExtractIdentity(identity): ExtractIdentity,
@@ -149,6 +160,30 @@ pub async fn stream_function_logs(
         )),
         _ => None,
     };
+    let min_log_level: Option<LogLevel> = query_args
+        .min_log_level
+        .map(|level| level.to_uppercase().parse())
+        .transpose()
+        .context(ErrorMetadata::bad_request(
+            "InvalidLogLevel",
+            "Invalid minimum log level",
+        ))?;
+    let log_field: Option<LogFieldMatch> = query_args
+        .log_field
+        .map(|log_field| log_field.parse())
+        .transpose()
+        .context(ErrorMetadata::bad_request(
+            "InvalidLogField",
+            "Log field filters must be of the form `field=value`",
+        ))?;
+    let log_line_filter = |line: &LogLineStructured| {
+        min_log_level
+            .as_ref()
+            .is_none_or(|min_log_level| line.level.severity() >= min_log_level.severity())
+            && log_field
+                .as_ref()
+                .is_none_or(|log_field| line.matches_field(log_field))
+    };
     // As of writing, this endpoint is only used by the CLI and dashboard, both of
     // which support either unstructured `string` log lines or structured log
     // lines.
@@ -188,10 +223,12 @@ pub async fn stream_function_logs(
                 })
                 .map(|e| {
                     let json = match e {
-                        FunctionExecutionPart::Completion(c) => {
+                        FunctionExecutionPart::Completion(mut c) => {
+                            c.log_lines = c.log_lines.filtered(&log_line_filter);
                             execution_to_json(c, supports_structured_log_lines)?
                         },
-                        FunctionExecutionPart::Progress(c) => {
+                        FunctionExecutionPart::Progress(mut c) => {
+                            c.log_lines = c.log_lines.filtered(&log_line_filter);
                             FunctionExecutionJson::Progress {
                                 udf_type: c.event_source.udf_type.to_string(),
                                 component_path: c.event_source.component_path.serialize(),
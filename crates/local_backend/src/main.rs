@@ -3,15 +3,20 @@
 use std::time::Duration;
 
 use clap::Parser;
+use clusters::DbDriverTag;
 use cmd_util::env::config_service;
 use common::{
     errors::MainError,
     http::ConvexHttpService,
+    knobs::SHUTDOWN_DRAIN_TIMEOUT,
     runtime::Runtime,
     shutdown::ShutdownSignal,
     version::SERVER_VERSION_STR,
 };
-use db_connection::connect_persistence;
+use db_connection::{
+    connect_persistence,
+    connect_persistence_reader,
+};
 use futures::{
     future::{
         self,
@@ -21,7 +26,15 @@ use futures::{
 };
 use local_backend::{
     config::LocalConfig,
+    leader_election::{
+        acquire_leadership,
+        new_holder_id,
+        renew_leadership,
+        standby_router,
+        wait_for_leadership,
+    },
     make_app,
+    otel::start_otlp_exporter,
     proxy::dev_site_proxy,
     router::router,
     HttpActionRouteMapper,
@@ -31,6 +44,7 @@ use runtime::prod::ProdRuntime;
 use tokio::{
     signal::{
         self,
+        unix::SignalKind,
     },
     sync::oneshot,
 };
@@ -72,7 +86,11 @@ fn main() -> Result<(), MainError> {
     }
 
     let tokio = ProdRuntime::init_tokio()?;
-    let runtime = ProdRuntime::new(&tokio);
+    let mut runtime = ProdRuntime::new(&tokio);
+    if config.enable_simulated_time {
+        tracing::warn!("Simulated time is enabled. The backend clock can be frozen and advanced.");
+        runtime = runtime.with_simulated_clock();
+    }
 
     let runtime_ = runtime.clone();
     let server_future = async {
@@ -98,6 +116,68 @@ async fn run_server(runtime: ProdRuntime, config: LocalConfig) -> anyhow::Result
 }
 
 async fn run_server_inner(runtime: ProdRuntime, config: LocalConfig) -> anyhow::Result<()> {
+    start_otlp_exporter(runtime.clone(), &config)?;
+    let mut sigterm = signal::unix::signal(SignalKind::terminate())?;
+
+    let leader_holder = config
+        .leader_election
+        .then(|| new_holder_id(&config.name()));
+    let previous_leader = match &leader_holder {
+        Some(holder) => {
+            anyhow::ensure!(
+                !matches!(config.db, DbDriverTag::Sqlite),
+                "--leader-election requires Postgres or MySQL"
+            );
+            let reader = connect_persistence_reader(
+                config.db,
+                &config.db_spec,
+                !config.do_not_require_ssl,
+                true, /* db_should_be_leader */
+                &config.name(),
+                runtime.clone(),
+            )
+            .await?;
+            // Answer health checks while another backend holds the lease, so
+            // this one isn't restarted for failing them.
+            let standby_service = ConvexHttpService::new(
+                standby_router(SERVER_VERSION_STR.to_string()),
+                "backend_standby",
+                SERVER_VERSION_STR.to_string(),
+                MAX_CONCURRENT_REQUESTS,
+                Duration::from_secs(125),
+                HttpActionRouteMapper,
+            );
+            let (standby_shutdown_tx, standby_shutdown_rx) = oneshot::channel::<()>();
+            let standby_future = standby_service
+                .serve(config.http_bind_address().into(), async move {
+                    let _ = standby_shutdown_rx.await;
+                })
+                .fuse();
+            futures::pin_mut!(standby_future);
+            tracing::info!("Standing by until the leader lease is free...");
+            let previous = futures::select! {
+                r = wait_for_leadership(&runtime, reader.as_ref(), holder).fuse() => r?,
+                r = standby_future => {
+                    r?;
+                    panic!("Standby server stopped unexpectedly!")
+                },
+                r = signal::ctrl_c().fuse() => {
+                    r?;
+                    tracing::info!("Received Ctrl-C signal while standing by");
+                    return Ok(());
+                },
+                _ = sigterm.recv().fuse() => {
+                    tracing::info!("Received SIGTERM signal while standing by");
+                    return Ok(());
+                },
+            };
+            let _ = standby_shutdown_tx.send(());
+            standby_future.await?;
+            previous
+        },
+        None => None,
+    };
+
     // Used to receive fatal errors from the database or /preempt endpoint.
     let (preempt_tx, preempt_rx) = oneshot::channel();
     let preempt_signal = ShutdownSignal::new(preempt_tx);
@@ -113,6 +193,24 @@ async fn run_server_inner(runtime: ProdRuntime, config: LocalConfig) -> anyhow::
         preempt_signal.clone(),
     )
     .await?;
+    // Opening persistence above took the persistence lease, so the previous
+    // leader can no longer write.
+    let _leader_renewer = match leader_holder {
+        Some(holder) => {
+            let lease =
+                acquire_leadership(&runtime, persistence.as_ref(), holder, previous_leader).await?;
+            Some(runtime.spawn(
+                "leader_lease_renewer",
+                renew_leadership(
+                    runtime.clone(),
+                    persistence.clone(),
+                    lease,
+                    preempt_signal.clone(),
+                ),
+            ))
+        },
+        None => None,
+    };
     let st = make_app(
         runtime.clone(),
         config.clone(),
@@ -144,8 +242,9 @@ async fn run_server_inner(runtime: ProdRuntime, config: LocalConfig) -> anyhow::
     futures::pin_mut!(serve_future);
 
     // Start shutdown when we get a manual shutdown signal or with the first
-    // ctrl-c.
+    // ctrl-c or SIGTERM.
     let mut force_exit_duration = None;
+    let mut drain = false;
     futures::select! {
         r = serve_future => {
             r?;
@@ -160,18 +259,41 @@ async fn run_server_inner(runtime: ProdRuntime, config: LocalConfig) -> anyhow::
         r = signal::ctrl_c().fuse() => {
             tracing::info!("Received Ctrl-C signal!");
             r?;
-            let _: Result<_, _> = shutdown_tx.broadcast(()).await;
+            drain = true;
+        },
+        _ = sigterm.recv().fuse() => {
+            tracing::info!("Received SIGTERM signal!");
+            drain = true;
         },
     }
 
+    let runtime_ = runtime.clone();
     let shutdown = async move {
+        if drain {
+            // Turn away new requests and sync sessions, and give open sessions
+            // time to finish their in-flight functions before closing them.
+            tracing::info!("Shutdown initiated, draining sync sessions...");
+            st.drain.start();
+            futures::select! {
+                _ = st.drain.wait_for_sessions().fuse() => (),
+                _ = runtime_.wait(*SHUTDOWN_DRAIN_TIMEOUT).fuse() => {
+                    tracing::warn!("Timed out draining sync sessions");
+                },
+            }
+            let _: Result<_, _> = shutdown_tx.broadcast(()).await;
+        }
+
         // First, drain all in-progress requests;
         tracing::info!("Shutdown initiated, draining existing requests...");
         serve_future.await?;
 
-        // Next, shutdown all of our asynchronous workers.
+        // Next, shutdown all of our asynchronous workers. Workers persist their
+        // progress as they go, so they resume from their last checkpoint.
         tracing::info!("Shutting down application...");
         st.shutdown().await?;
+        // The OTLP trace exporter sends spans with a blocking HTTP client, so
+        // flush them off the async executor.
+        tokio::task::spawn_blocking(fastrace::flush).await?;
 
         Ok::<_, anyhow::Error>(())
     }
@@ -200,12 +322,16 @@ async fn run_server_inner(runtime: ProdRuntime, config: LocalConfig) -> anyhow::
                 tracing::info!("Cool down expired. Shutting down");
                 break;
             }
-            // Forcibly shutdown with second ctrl-c.
+            // Forcibly shutdown with second ctrl-c or SIGTERM.
             r = signal::ctrl_c().fuse() => {
                 r?;
                 tracing::warn!("Forcibly shutting down!");
                 break;
             },
+            _ = sigterm.recv().fuse() => {
+                tracing::warn!("Forcibly shutting down!");
+                break;
+            },
         }
     }
 
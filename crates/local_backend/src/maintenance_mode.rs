@@ -0,0 +1,113 @@
+//! Maintenance mode, which rejects function calls from clients with a message
+//! and HTTP status of the admin's choosing while admins keep full access to
+//! the deployment, e.g. to run fixes from the dashboard or pause it.
+
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::http::{
+    extract::Json,
+    HttpResponseError,
+};
+use model::maintenance_mode::{
+    types::MaintenanceMode,
+    DEFAULT_MAINTENANCE_MESSAGE,
+    DEFAULT_MAINTENANCE_STATUS_CODE,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnableMaintenanceModeArgs {
+    message: Option<String>,
+    status_code: Option<u16>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceModeJson {
+    message: String,
+    status_code: u16,
+    started_at_ms: i64,
+}
+
+impl From<MaintenanceMode> for MaintenanceModeJson {
+    fn from(mode: MaintenanceMode) -> Self {
+        Self {
+            message: mode.message,
+            status_code: mode.status_code,
+            started_at_ms: mode.started_at_ms,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetMaintenanceModeResponse {
+    maintenance_mode: Option<MaintenanceModeJson>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisableMaintenanceModeResponse {
+    was_enabled: bool,
+}
+
+#[debug_handler]
+pub async fn get_maintenance_mode(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let mode = st.application.get_maintenance_mode(&identity).await?;
+    Ok(Json(GetMaintenanceModeResponse {
+        maintenance_mode: mode.map(MaintenanceModeJson::from),
+    }))
+}
+
+/// Starts rejecting client function calls, or updates the message and status
+/// they're rejected with.
+#[debug_handler]
+pub async fn enable_maintenance_mode(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(EnableMaintenanceModeArgs {
+        message,
+        status_code,
+    }): Json<EnableMaintenanceModeArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let mode = st
+        .application
+        .enable_maintenance_mode(
+            &identity,
+            message.unwrap_or_else(|| DEFAULT_MAINTENANCE_MESSAGE.to_string()),
+            status_code.unwrap_or(DEFAULT_MAINTENANCE_STATUS_CODE),
+        )
+        .await?;
+    Ok(Json(MaintenanceModeJson::from(mode)))
+}
+
+#[debug_handler]
+pub async fn disable_maintenance_mode(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let was_enabled = st.application.disable_maintenance_mode(&identity).await?;
+    Ok(Json(DisableMaintenanceModeResponse { was_enabled }))
+}
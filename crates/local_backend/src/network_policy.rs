@@ -0,0 +1,273 @@
+//! Deployment-level network policy: which client addresses may use the admin
+//! API, and which browser origins may call the client API and HTTP actions.
+
+use std::{
+    net::IpAddr,
+    sync::Arc,
+    time::Duration,
+};
+
+use axum::{
+    extract::State,
+    response::Response,
+};
+use common::http::{
+    canonical_ip,
+    ExtractClientIp,
+    HttpResponseError,
+    TrustedProxies,
+};
+use errors::ErrorMetadata;
+use http::{
+    HeaderValue,
+    Method,
+};
+use ipnet::IpNet;
+use model::deployment_audit_log::types::AuditLogRequestContext;
+use tower_http::cors::{
+    AllowHeaders,
+    AllowOrigin,
+    CorsLayer,
+};
+
+use crate::config::LocalConfig;
+
+#[derive(Clone, Debug, Default)]
+pub struct NetworkPolicy {
+    /// CIDR ranges allowed to reach the admin API. Empty means unrestricted.
+    admin_ip_allowlist: Vec<IpNet>,
+    /// CIDR ranges of reverse proxies trusted to report the client address in
+    /// `X-Forwarded-For`.
+    trusted_proxies: TrustedProxies,
+    /// Origins allowed to make cross-origin requests to the client API and
+    /// HTTP actions. `None` mirrors the request origin (i.e. allows any).
+    cors_allowed_origins: Option<Vec<HeaderValue>>,
+}
+
+impl NetworkPolicy {
+    pub fn new(
+        admin_ip_allowlist: Vec<IpNet>,
+        trusted_proxies: Vec<IpNet>,
+        cors_allowed_origins: Option<Vec<String>>,
+    ) -> anyhow::Result<Self> {
+        let cors_allowed_origins = cors_allowed_origins
+            .map(|origins| {
+                origins
+                    .into_iter()
+                    .map(|origin| {
+                        let origin = origin.trim_end_matches('/');
+                        HeaderValue::from_str(origin).map_err(|_| {
+                            anyhow::anyhow!("Invalid CORS origin in network policy: {origin}")
+                        })
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()
+            })
+            .transpose()?;
+        Ok(Self {
+            admin_ip_allowlist,
+            trusted_proxies: TrustedProxies(Arc::new(trusted_proxies)),
+            cors_allowed_origins,
+        })
+    }
+
+    pub fn from_config(config: &LocalConfig) -> anyhow::Result<Self> {
+        Self::new(
+            config.admin_ip_allowlist.clone(),
+            config.trusted_proxy_cidrs.clone(),
+            config.cors_allowed_origins.clone(),
+        )
+    }
+
+    pub fn is_admin_ip_allowed(&self, ip: Option<IpAddr>) -> bool {
+        if self.admin_ip_allowlist.is_empty() {
+            return true;
+        }
+        let Some(ip) = ip else {
+            return false;
+        };
+        let ip = canonical_ip(ip);
+        self.admin_ip_allowlist.iter().any(|net| net.contains(&ip))
+    }
+
+    /// Installed as a request extension so `ExtractClientIp` knows which
+    /// peers' `X-Forwarded-For` headers to believe.
+    pub fn trusted_proxies(&self) -> TrustedProxies {
+        self.trusted_proxies.clone()
+    }
+
+    pub fn has_cors_allowlist(&self) -> bool {
+        self.cors_allowed_origins.is_some()
+    }
+
+    pub fn cors(&self) -> CorsLayer {
+        let allow_origin = match &self.cors_allowed_origins {
+            Some(origins) => AllowOrigin::list(origins.clone()),
+            None => AllowOrigin::mirror_request(),
+        };
+        CorsLayer::new()
+            .allow_headers(AllowHeaders::mirror_request())
+            .allow_credentials(true)
+            .allow_methods(vec![
+                Method::GET,
+                Method::POST,
+                Method::OPTIONS,
+                Method::PATCH,
+                Method::DELETE,
+                Method::PUT,
+            ])
+            .allow_origin(allow_origin)
+            .max_age(Duration::from_secs(86400))
+    }
+}
+
+/// Rejects requests to the admin API from addresses outside the configured
+/// allowlist.
+pub async fn admin_ip_allowlist_middleware(
+    State(policy): State<Arc<NetworkPolicy>>,
+    ExtractClientIp(client_ip): ExtractClientIp,
+    req: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> Result<Response, HttpResponseError> {
+    if !policy.is_admin_ip_allowed(client_ip) {
+        let client = client_ip
+            .map(|ip| ip.to_string())
+            .unwrap_or_else(|| "an unknown address".to_string());
+        return Err(anyhow::anyhow!(ErrorMetadata::forbidden(
+            "IpNotAllowed",
+            format!(
+                "Requests from {client} are not allowed to access the admin API of this \
+                 deployment. Add the address to --admin-ip-allowlist to allow it."
+            ),
+        ))
+        .into());
+    }
+    Ok(next.run(req).await)
+}
+
+/// Attributes the deployment audit log events recorded while handling an
+/// admin request to the client's address.
+pub async fn audit_log_context_middleware(
+    ExtractClientIp(client_ip): ExtractClientIp,
+    req: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> Response {
+    AuditLogRequestContext::new(client_ip)
+        .scope(next.run(req))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::SocketAddr,
+        sync::Arc,
+    };
+
+    use axum::{
+        extract::ConnectInfo,
+        routing::get,
+        Extension,
+        Router,
+    };
+    use common::http::X_FORWARDED_FOR_HEADER;
+    use http::{
+        Request,
+        StatusCode,
+    };
+    use tower::ServiceExt;
+
+    use super::{
+        admin_ip_allowlist_middleware,
+        NetworkPolicy,
+    };
+
+    async fn admin_status(
+        policy: &Arc<NetworkPolicy>,
+        peer: &str,
+        forwarded_for: Option<&str>,
+    ) -> anyhow::Result<StatusCode> {
+        let router = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn_with_state(
+                policy.clone(),
+                admin_ip_allowlist_middleware,
+            ))
+            .layer(Extension(policy.trusted_proxies()));
+        let mut req = Request::builder().uri("/");
+        if let Some(forwarded_for) = forwarded_for {
+            req = req.header(X_FORWARDED_FOR_HEADER, forwarded_for);
+        }
+        let mut req = req.body(axum::body::Body::empty())?;
+        req.extensions_mut()
+            .insert(ConnectInfo(peer.parse::<SocketAddr>()?));
+        Ok(router.oneshot(req).await?.status())
+    }
+
+    #[test]
+    fn test_admin_ip_allowlist() -> anyhow::Result<()> {
+        let unrestricted = NetworkPolicy::default();
+        assert!(unrestricted.is_admin_ip_allowed(None));
+        assert!(unrestricted.is_admin_ip_allowed(Some("8.8.8.8".parse()?)));
+
+        let policy = NetworkPolicy::new(
+            vec!["10.0.0.0/8".parse()?, "::1/128".parse()?],
+            vec![],
+            None,
+        )?;
+        assert!(policy.is_admin_ip_allowed(Some("10.1.2.3".parse()?)));
+        assert!(policy.is_admin_ip_allowed(Some("::ffff:10.1.2.3".parse()?)));
+        assert!(policy.is_admin_ip_allowed(Some("::1".parse()?)));
+        assert!(!policy.is_admin_ip_allowed(Some("192.168.0.1".parse()?)));
+        assert!(!policy.is_admin_ip_allowed(None));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_admin_ip_allowlist_ignores_spoofed_forwarded_for() -> anyhow::Result<()> {
+        let policy = Arc::new(NetworkPolicy::new(
+            vec!["10.0.0.0/8".parse()?],
+            vec![],
+            None,
+        )?);
+        assert_eq!(
+            admin_status(&policy, "10.1.1.1:1234", None).await?,
+            StatusCode::OK
+        );
+        assert_eq!(
+            admin_status(&policy, "8.8.8.8:1234", Some("10.0.0.1")).await?,
+            StatusCode::FORBIDDEN
+        );
+
+        // Behind a trusted proxy, only the hop the proxy appended counts.
+        let policy = Arc::new(NetworkPolicy::new(
+            vec!["10.0.0.0/8".parse()?],
+            vec!["192.168.0.0/16".parse()?],
+            None,
+        )?);
+        assert_eq!(
+            admin_status(&policy, "192.168.0.5:1234", Some("10.0.0.1")).await?,
+            StatusCode::OK
+        );
+        assert_eq!(
+            admin_status(&policy, "192.168.0.5:1234", Some("10.0.0.1, 8.8.8.8")).await?,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            admin_status(&policy, "8.8.8.8:1234", Some("10.0.0.1")).await?,
+            StatusCode::FORBIDDEN
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_cors_origin() {
+        assert!(NetworkPolicy::new(vec![], vec![], Some(vec!["bad\norigin".to_string()])).is_err());
+        assert!(NetworkPolicy::new(
+            vec![],
+            vec![],
+            Some(vec!["https://app.example.com/".to_string()])
+        )
+        .unwrap()
+        .has_cors_allowlist());
+    }
+}
@@ -41,10 +41,20 @@ use common::{
     },
     RequestId,
 };
+use database::{
+    HybridSearch,
+    HybridSearchRequest,
+};
 use errors::ErrorMetadata;
 use fastrace::future::FutureExt;
 use http::HeaderMap;
 use isolate::{
+    environment::queues::{
+        leased_messages_to_json,
+        AckArgs,
+        LeaseArgs,
+        NackArgs,
+    },
     ActionCallbacks,
     UdfArgsJson,
 };
@@ -61,7 +71,10 @@ use sync_types::{
     AuthenticationToken,
     CanonicalizedUdfPath,
 };
-use usage_tracking::FunctionUsageTracker;
+use usage_tracking::{
+    FunctionUsageStats,
+    FunctionUsageTracker,
+};
 use value::{
     export::ValueFormat,
     id_v6::DeveloperDocumentId,
@@ -137,6 +150,7 @@ pub async fn internal_query_post(
         Ok(value) => UdfResponse::Success {
             value: export_value(value.unpack(), value_format, client_version)?,
             log_lines: udf_return.log_lines,
+            ts: None,
         },
         Err(error) => {
             UdfResponse::nested_error(error, udf_return.log_lines, value_format, client_version)?
@@ -193,6 +207,7 @@ pub async fn internal_mutation_post(
         Ok(write_return) => UdfResponse::Success {
             value: export_value(write_return.value.unpack(), value_format, client_version)?,
             log_lines: write_return.log_lines,
+            ts: None,
         },
         Err(write_error) => UdfResponse::nested_error(
             write_error.error,
@@ -250,6 +265,7 @@ pub async fn internal_action_post(
         Ok(action_return) => UdfResponse::Success {
             value: export_value(action_return.value.unpack(), value_format, client_version)?,
             log_lines: action_return.log_lines,
+            ts: None,
         },
         Err(action_error) => UdfResponse::nested_error(
             action_error.error,
@@ -269,6 +285,7 @@ pub struct ScheduleJobRequest {
     udf_path: Option<String>,
     udf_args: UdfArgsJson,
     scheduled_ts: f64,
+    idempotency_key: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -315,6 +332,7 @@ pub async fn schedule_job(
             udf_args,
             scheduled_ts,
             context,
+            req.idempotency_key,
         )
         .await?;
     Ok(Json(ScheduleJobResponse {
@@ -348,6 +366,68 @@ pub async fn cancel_developer_job(
     Ok(Json(json!(null)))
 }
 
+#[debug_handler]
+pub async fn queue_lease(
+    State(st): State<LocalAppState>,
+    ExtractActionIdentity {
+        identity,
+        component_id,
+    }: ExtractActionIdentity,
+    Json(req): Json<JsonValue>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let LeaseArgs {
+        queue,
+        max_messages,
+        visibility_timeout,
+    } = LeaseArgs::parse(req)?;
+    let messages = st
+        .application
+        .runner()
+        .queue_lease(
+            identity,
+            component_id,
+            queue,
+            max_messages,
+            visibility_timeout,
+        )
+        .await?;
+    Ok(Json(leased_messages_to_json(messages)))
+}
+
+#[debug_handler]
+pub async fn queue_ack(
+    State(st): State<LocalAppState>,
+    ExtractActionIdentity {
+        identity,
+        component_id,
+    }: ExtractActionIdentity,
+    Json(req): Json<JsonValue>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let AckArgs { receipt } = AckArgs::parse(req)?;
+    st.application
+        .runner()
+        .queue_ack(identity, component_id, receipt)
+        .await?;
+    Ok(Json(json!(null)))
+}
+
+#[debug_handler]
+pub async fn queue_nack(
+    State(st): State<LocalAppState>,
+    ExtractActionIdentity {
+        identity,
+        component_id,
+    }: ExtractActionIdentity,
+    Json(req): Json<JsonValue>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let NackArgs { receipt, delay } = NackArgs::parse(req)?;
+    st.application
+        .runner()
+        .queue_nack(identity, component_id, receipt, delay)
+        .await?;
+    Ok(Json(json!(null)))
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateFunctionHandleRequest {
@@ -413,12 +493,67 @@ pub async fn vector_search(
         .application
         .vector_search(identity.clone(), query)
         .await?;
+    track_search_usage(
+        &st,
+        identity,
+        component_id,
+        action_name,
+        context,
+        usage_stats,
+    )
+    .await?;
+
+    let results: Vec<_> = results.into_iter().map(JsonValue::from).collect();
+    Ok(Json(json!({ "results": results })))
+}
+
+#[debug_handler]
+pub async fn hybrid_search(
+    State(st): State<LocalAppState>,
+    ExtractActionIdentity {
+        identity,
+        component_id,
+    }: ExtractActionIdentity,
+    ExtractActionName(action_name): ExtractActionName,
+    ExtractExecutionContext(context): ExtractExecutionContext,
+    Json(req): Json<HybridSearchRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let HybridSearchRequest { query } = req;
+    let query = HybridSearch::try_from(query).map_err(|e| {
+        let message = e.to_string();
+        e.context(ErrorMetadata::bad_request("InvalidHybridSearch", message))
+    })?;
+    let (results, usage_stats) = st
+        .application
+        .hybrid_search(identity.clone(), query)
+        .await?;
+    track_search_usage(
+        &st,
+        identity,
+        component_id,
+        action_name,
+        context,
+        usage_stats,
+    )
+    .await?;
 
+    let results: Vec<_> = results.into_iter().map(JsonValue::from).collect();
+    Ok(Json(json!({ "results": results })))
+}
+
+async fn track_search_usage(
+    st: &LocalAppState,
+    identity: Identity,
+    component_id: ComponentId,
+    action_name: Option<String>,
+    context: ExecutionContext,
+    usage_stats: FunctionUsageStats,
+) -> anyhow::Result<()> {
     // This is a workaround. The correct way to track usage is to return in the
     // response, and then Node.js should aggregate it and then send it back to
     // the backend alongside the action result, which is how Funrun actions
     // work. Since we don't have that pipeline working in Node.js/Typescript, we
-    // report vector usage directly here.
+    // report vector and hybrid search usage directly here.
     if let Some(action_name) = action_name {
         let usage = FunctionUsageTracker::new();
         usage.add(usage_stats);
@@ -447,9 +582,7 @@ pub async fn vector_search(
             )
             .await;
     }
-
-    let results: Vec<_> = results.into_iter().map(JsonValue::from).collect();
-    Ok(Json(json!({ "results": results })))
+    Ok(())
 }
 
 #[debug_handler]
@@ -0,0 +1,369 @@
+//! Exports traces and server metrics to an OpenTelemetry collector over
+//! OTLP/HTTP.
+//!
+//! Traces come from the existing fastrace instrumentation: any span sampled by
+//! `REQUEST_TRACE_SAMPLE_CONFIG` (or `--otlp-trace-sample-ratio`) is reported
+//! to the collector. Metrics are the same Prometheus registry served at
+//! `/metrics`, pushed periodically as cumulative OTLP metrics.
+
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    time::{
+        Duration,
+        SystemTime,
+        UNIX_EPOCH,
+    },
+};
+
+use anyhow::Context as _;
+use common::{
+    fastrace_helpers::set_sampling_config,
+    runtime::Runtime,
+};
+use fastrace_opentelemetry::OpenTelemetryReporter;
+use metrics::{
+    CONVEX_METRICS_REGISTRY,
+    SERVER_VERSION_STR,
+};
+use opentelemetry::{
+    InstrumentationScope,
+    KeyValue,
+};
+use opentelemetry_otlp::{
+    WithExportConfig,
+    WithHttpConfig,
+};
+use opentelemetry_proto::tonic::{
+    collector::metrics::v1::ExportMetricsServiceRequest,
+    common::v1::{
+        any_value,
+        AnyValue,
+        InstrumentationScope as OtlpInstrumentationScope,
+        KeyValue as OtlpKeyValue,
+    },
+    metrics::v1::{
+        metric,
+        number_data_point,
+        AggregationTemporality,
+        Gauge,
+        Histogram,
+        HistogramDataPoint,
+        Metric,
+        NumberDataPoint,
+        ResourceMetrics,
+        ScopeMetrics,
+        Sum,
+    },
+    resource::v1::Resource as OtlpResource,
+};
+use opentelemetry_sdk::Resource;
+use prometheus::proto::{
+    LabelPair,
+    MetricFamily,
+    MetricType,
+};
+use prost::Message;
+use url::Url;
+
+use crate::config::LocalConfig;
+
+const SERVICE_NAME: &str = "convex-backend";
+
+pub struct OtlpConfig {
+    endpoint: Url,
+    headers: HashMap<String, String>,
+    metrics_interval: Duration,
+    instance_name: String,
+}
+
+impl OtlpConfig {
+    pub fn from_config(config: &LocalConfig) -> anyhow::Result<Option<Self>> {
+        let Some(endpoint) = config.otlp_endpoint.clone() else {
+            return Ok(None);
+        };
+        Ok(Some(Self {
+            endpoint,
+            headers: parse_otlp_headers(&config.otlp_headers)?,
+            metrics_interval: Duration::from_secs(config.otlp_metrics_interval_secs),
+            instance_name: config.name(),
+        }))
+    }
+
+    fn signal_url(&self, signal: &str) -> anyhow::Result<Url> {
+        let mut endpoint = self.endpoint.clone();
+        if !endpoint.path().ends_with('/') {
+            endpoint.set_path(&format!("{}/", endpoint.path()));
+        }
+        Ok(endpoint.join(&format!("v1/{signal}"))?)
+    }
+}
+
+/// Parses `key=value` pairs in the format of `OTEL_EXPORTER_OTLP_HEADERS`.
+fn parse_otlp_headers(headers: &[String]) -> anyhow::Result<HashMap<String, String>> {
+    headers
+        .iter()
+        .map(|header| {
+            let (key, value) = header
+                .split_once('=')
+                .with_context(|| format!("Invalid OTLP header {header:?}, expected key=value"))?;
+            Ok((
+                urlencoding::decode(key.trim())?.into_owned(),
+                urlencoding::decode(value.trim())?.into_owned(),
+            ))
+        })
+        .collect()
+}
+
+/// Starts exporting traces and metrics if `--otlp-endpoint` is set. Call
+/// `fastrace::flush()` on shutdown to send any buffered spans, from a blocking
+/// thread: the span exporter uses a blocking HTTP client, since fastrace
+/// reports spans synchronously from its own collector thread.
+pub fn start_otlp_exporter<RT: Runtime>(rt: RT, config: &LocalConfig) -> anyhow::Result<()> {
+    let Some(otlp_config) = OtlpConfig::from_config(config)? else {
+        return Ok(());
+    };
+    if let Some(ratio) = config.otlp_trace_sample_ratio {
+        anyhow::ensure!(
+            (0.0..=1.0).contains(&ratio),
+            "--otlp-trace-sample-ratio must be between 0 and 1, got {ratio}"
+        );
+        set_sampling_config(&format!(r#"{{"defaultFraction":{ratio}}}"#));
+    }
+
+    let span_exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(otlp_config.signal_url("traces")?.to_string())
+        .with_headers(otlp_config.headers.clone())
+        .build()?;
+    let reporter = OpenTelemetryReporter::new(
+        span_exporter,
+        Cow::Owned(
+            Resource::builder()
+                .with_service_name(SERVICE_NAME)
+                .with_attribute(KeyValue::new(
+                    "service.instance.id",
+                    otlp_config.instance_name.clone(),
+                ))
+                .build(),
+        ),
+        InstrumentationScope::builder(SERVICE_NAME)
+            .with_version(SERVER_VERSION_STR.clone())
+            .build(),
+    );
+    fastrace::set_reporter(reporter, fastrace::collector::Config::default());
+
+    tracing::info!(
+        "Exporting traces and metrics over OTLP to {}",
+        otlp_config.endpoint
+    );
+    rt.clone().spawn_background(
+        "otlp_metrics_exporter",
+        export_metrics_loop(rt, otlp_config),
+    );
+    Ok(())
+}
+
+async fn export_metrics_loop<RT: Runtime>(rt: RT, config: OtlpConfig) {
+    let client = reqwest::Client::new();
+    let start_time_unix_nano = unix_nanos(rt.system_time());
+    loop {
+        rt.wait(config.metrics_interval).await;
+        if let Err(e) = export_metrics(&client, &config, start_time_unix_nano, &rt).await {
+            tracing::warn!("Failed to export metrics over OTLP: {e:#}");
+        }
+    }
+}
+
+async fn export_metrics<RT: Runtime>(
+    client: &reqwest::Client,
+    config: &OtlpConfig,
+    start_time_unix_nano: u64,
+    rt: &RT,
+) -> anyhow::Result<()> {
+    let now = unix_nanos(rt.system_time());
+    let metrics = CONVEX_METRICS_REGISTRY
+        .gather()
+        .iter()
+        .filter_map(|family| to_otlp_metric(family, start_time_unix_nano, now))
+        .collect();
+    let request = ExportMetricsServiceRequest {
+        resource_metrics: vec![ResourceMetrics {
+            resource: Some(OtlpResource {
+                attributes: vec![
+                    string_attribute("service.name", SERVICE_NAME),
+                    string_attribute("service.instance.id", &config.instance_name),
+                ],
+                ..Default::default()
+            }),
+            scope_metrics: vec![ScopeMetrics {
+                scope: Some(OtlpInstrumentationScope {
+                    name: SERVICE_NAME.to_string(),
+                    version: SERVER_VERSION_STR.clone(),
+                    ..Default::default()
+                }),
+                metrics,
+                ..Default::default()
+            }],
+            ..Default::default()
+        }],
+    };
+    let mut builder = client
+        .post(config.signal_url("metrics")?)
+        .header(http::header::CONTENT_TYPE, "application/x-protobuf")
+        .body(request.encode_to_vec());
+    for (key, value) in &config.headers {
+        builder = builder.header(key, value);
+    }
+    builder.send().await?.error_for_status()?;
+    Ok(())
+}
+
+fn to_otlp_metric(family: &MetricFamily, start_time_unix_nano: u64, now: u64) -> Option<Metric> {
+    let number_points = |value: fn(&prometheus::proto::Metric) -> f64| {
+        family
+            .get_metric()
+            .iter()
+            .map(|m| NumberDataPoint {
+                attributes: attributes(m.get_label()),
+                start_time_unix_nano,
+                time_unix_nano: now,
+                value: Some(number_data_point::Value::AsDouble(value(m))),
+                ..Default::default()
+            })
+            .collect()
+    };
+    let data = match family.get_field_type() {
+        MetricType::COUNTER => metric::Data::Sum(Sum {
+            data_points: number_points(|m| m.get_counter().get_value()),
+            aggregation_temporality: AggregationTemporality::Cumulative as i32,
+            is_monotonic: true,
+        }),
+        MetricType::GAUGE | MetricType::UNTYPED => metric::Data::Gauge(Gauge {
+            data_points: number_points(|m| m.get_gauge().get_value()),
+        }),
+        MetricType::HISTOGRAM => metric::Data::Histogram(Histogram {
+            data_points: family
+                .get_metric()
+                .iter()
+                .map(|m| {
+                    let h = m.get_histogram();
+                    let (explicit_bounds, bucket_counts) = bucket_counts(
+                        h.get_bucket()
+                            .iter()
+                            .map(|b| (b.get_upper_bound(), b.get_cumulative_count())),
+                        h.get_sample_count(),
+                    );
+                    HistogramDataPoint {
+                        attributes: attributes(m.get_label()),
+                        start_time_unix_nano,
+                        time_unix_nano: now,
+                        count: h.get_sample_count(),
+                        sum: Some(h.get_sample_sum()),
+                        bucket_counts,
+                        explicit_bounds,
+                        ..Default::default()
+                    }
+                })
+                .collect(),
+            aggregation_temporality: AggregationTemporality::Cumulative as i32,
+        }),
+        // We don't register any summaries.
+        MetricType::SUMMARY => return None,
+    };
+    Some(Metric {
+        name: family.get_name().to_string(),
+        description: family.get_help().to_string(),
+        data: Some(data),
+        ..Default::default()
+    })
+}
+
+/// Converts Prometheus' cumulative `(upper_bound, count)` buckets into OTLP's
+/// explicit bounds and per-bucket counts, which include a final overflow
+/// bucket.
+fn bucket_counts(cumulative: impl Iterator<Item = (f64, u64)>, total: u64) -> (Vec<f64>, Vec<u64>) {
+    let mut explicit_bounds = vec![];
+    let mut bucket_counts = vec![];
+    let mut prev = 0;
+    for (upper_bound, count) in cumulative {
+        if upper_bound.is_infinite() {
+            break;
+        }
+        explicit_bounds.push(upper_bound);
+        bucket_counts.push(count.saturating_sub(prev));
+        prev = count;
+    }
+    bucket_counts.push(total.saturating_sub(prev));
+    (explicit_bounds, bucket_counts)
+}
+
+fn attributes(labels: &[LabelPair]) -> Vec<OtlpKeyValue> {
+    labels
+        .iter()
+        .map(|label| string_attribute(label.get_name(), label.get_value()))
+        .collect()
+}
+
+fn string_attribute(key: &str, value: &str) -> OtlpKeyValue {
+    OtlpKeyValue {
+        key: key.to_string(),
+        value: Some(AnyValue {
+            value: Some(any_value::Value::StringValue(value.to_string())),
+        }),
+    }
+}
+
+fn unix_nanos(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::{
+        bucket_counts,
+        parse_otlp_headers,
+    };
+    use crate::config::LocalConfig;
+
+    #[test]
+    fn test_metrics_interval_must_be_positive() {
+        let parse = |interval: &str| {
+            LocalConfig::try_parse_from([
+                "convex-local-backend",
+                "--otlp-endpoint",
+                "http://localhost:4318",
+                "--otlp-metrics-interval-secs",
+                interval,
+            ])
+        };
+        assert!(parse("0").is_err());
+        assert_eq!(parse("1").unwrap().otlp_metrics_interval_secs, 1);
+    }
+
+    #[test]
+    fn test_parse_otlp_headers() -> anyhow::Result<()> {
+        let headers = parse_otlp_headers(&[
+            "x-honeycomb-team=abc123".to_string(),
+            "Authorization=Basic%20dXNlcjpwYXNz".to_string(),
+        ])?;
+        assert_eq!(headers["x-honeycomb-team"], "abc123");
+        assert_eq!(headers["Authorization"], "Basic dXNlcjpwYXNz");
+        assert!(parse_otlp_headers(&["missing-value".to_string()]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_bucket_counts() {
+        let (bounds, counts) = bucket_counts(
+            [(0.1, 2), (0.5, 5), (1.0, 5), (f64::INFINITY, 7)].into_iter(),
+            7,
+        );
+        assert_eq!(bounds, vec![0.1, 0.5, 1.0]);
+        assert_eq!(counts, vec![2, 3, 0, 2]);
+    }
+}
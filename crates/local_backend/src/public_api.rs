@@ -24,9 +24,11 @@ use common::{
         ExtractRequestId,
         ExtractResolvedHostname,
         HttpResponseError,
+        ResolvedHostname,
     },
     types::FunctionCaller,
     version::ClientVersion,
+    RequestId,
 };
 use errors::ErrorMetadata;
 use isolate::UdfArgsJson;
@@ -58,6 +60,10 @@ pub struct UdfPostRequest {
     pub args: UdfArgsJson,
 
     pub format: Option<String>,
+    /// For queries, a commit timestamp from an earlier mutation whose writes
+    /// the query must see.
+    #[serde(default)]
+    pub min_ts: Option<SerializedTs>,
 }
 
 #[derive(Serialize)]
@@ -76,7 +82,7 @@ pub struct UdfPostWithTsRequest {
     pub format: Option<String>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct SerializedTs(String);
 
 impl From<Timestamp> for SerializedTs {
@@ -100,11 +106,14 @@ impl TryFrom<SerializedTs> for Timestamp {
 }
 
 #[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct UdfArgsQuery {
     pub path: String,
     pub args: UdfArgsJson,
 
     pub format: Option<String>,
+    #[serde(default)]
+    pub min_ts: Option<SerializedTs>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -117,6 +126,10 @@ pub enum UdfResponse {
 
         #[serde(skip_serializing_if = "RedactedLogLines::is_empty")]
         log_lines: RedactedLogLines,
+        /// The commit timestamp of a mutation, which later queries can pass as
+        /// `minTs` to read its writes.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ts: Option<SerializedTs>,
     },
     #[serde(rename_all = "camelCase")]
     Error {
@@ -220,6 +233,7 @@ pub async fn public_function_post(
         Ok(write_return) => UdfResponse::Success {
             value: export_value(write_return.value.unpack(), value_format, client_version)?,
             log_lines: write_return.log_lines,
+            ts: None,
         },
         Err(write_error) => UdfResponse::error(
             write_error.error,
@@ -301,6 +315,7 @@ pub async fn public_function_post_with_path(
         Ok(write_return) => UdfResponse::Success {
             value: export_value(write_return.value.unpack(), value_format, client_version)?,
             log_lines: write_return.log_lines,
+            ts: None,
         },
         Err(write_error) => UdfResponse::error(
             write_error.error,
@@ -345,6 +360,7 @@ pub async fn public_query_get(
         .api
         .authenticate(&host, request_id.clone(), auth_token)
         .await?;
+    let ts = query_timestamp(&st, &host, request_id.clone(), req.min_ts).await?;
     let query_result = st
         .api
         .execute_public_query(
@@ -354,7 +370,7 @@ pub async fn public_query_get(
             export_path,
             args,
             FunctionCaller::HttpApi(client_version.clone()),
-            ExecuteQueryTimestamp::Latest,
+            ts,
             journal,
         )
         .await?;
@@ -364,6 +380,7 @@ pub async fn public_query_get(
         Ok(value) => UdfResponse::Success {
             value: export_value(value.unpack(), value_format, client_version)?,
             log_lines,
+            ts: None,
         },
         Err(error) => UdfResponse::error(error, log_lines, value_format, client_version)?,
     };
@@ -389,6 +406,7 @@ pub async fn public_query_post(
         .api
         .authenticate(&host, request_id.clone(), auth_token)
         .await?;
+    let ts = query_timestamp(&st, &host, request_id.clone(), req.min_ts).await?;
     let query_return = st
         .api
         .execute_public_query(
@@ -398,7 +416,7 @@ pub async fn public_query_post(
             udf_path,
             req.args.into_arg_vec(),
             FunctionCaller::HttpApi(client_version.clone()),
-            ExecuteQueryTimestamp::Latest,
+            ts,
             journal,
         )
         .await?;
@@ -407,6 +425,7 @@ pub async fn public_query_post(
         Ok(value) => UdfResponse::Success {
             value: export_value(value.unpack(), value_format, client_version)?,
             log_lines: query_return.log_lines,
+            ts: None,
         },
         Err(error) => {
             UdfResponse::error(error, query_return.log_lines, value_format, client_version)?
@@ -415,6 +434,22 @@ pub async fn public_query_post(
     Ok(Json(response))
 }
 
+/// Picks the timestamp for a query, waiting for `min_ts` to become readable if
+/// the client passed one.
+async fn query_timestamp(
+    st: &RouterState,
+    host: &ResolvedHostname,
+    request_id: RequestId,
+    min_ts: Option<SerializedTs>,
+) -> anyhow::Result<ExecuteQueryTimestamp> {
+    let Some(min_ts) = min_ts else {
+        return Ok(ExecuteQueryTimestamp::Latest);
+    };
+    let min_ts = Timestamp::try_from(min_ts)?;
+    let ts = st.api.wait_for_min_ts(host, request_id, min_ts).await?;
+    Ok(ExecuteQueryTimestamp::At(ts))
+}
+
 pub async fn public_get_query_ts(
     ExtractResolvedHostname(host): ExtractResolvedHostname,
     ExtractRequestId(request_id): ExtractRequestId,
@@ -462,6 +497,7 @@ pub async fn public_query_at_ts_post(
         Ok(value) => UdfResponse::Success {
             value: export_value(value.unpack(), value_format, client_version)?,
             log_lines: query_return.log_lines,
+            ts: None,
         },
         Err(error) => {
             UdfResponse::error(error, query_return.log_lines, value_format, client_version)?
@@ -489,8 +525,24 @@ pub async fn public_query_batch_post(
     Json(req_batch): Json<QueryBatchArgs>,
 ) -> Result<impl IntoResponse, HttpResponseError> {
     let mut results = vec![];
-    // All queries execute at the same timestamp.
-    let ts = st.api.latest_timestamp(&host, request_id.clone()).await?;
+    // All queries execute at the same timestamp, which includes the writes of
+    // the latest `minTs` any of them passed.
+    let min_ts = req_batch
+        .queries
+        .iter()
+        .filter_map(|req| req.min_ts.as_ref())
+        .map(|min_ts| Timestamp::try_from(min_ts.clone()))
+        .try_fold(None, |max, min_ts| {
+            anyhow::Ok(std::cmp::max(max, Some(min_ts?)))
+        })?;
+    let ts = match min_ts {
+        Some(min_ts) => {
+            st.api
+                .wait_for_min_ts(&host, request_id.clone(), min_ts)
+                .await?
+        },
+        None => *st.api.latest_timestamp(&host, request_id.clone()).await?,
+    };
     let identity = st
         .api
         .authenticate(&host, request_id.clone(), auth_token)
@@ -507,7 +559,7 @@ pub async fn public_query_batch_post(
                 export_path,
                 req.args.into_arg_vec(),
                 FunctionCaller::HttpApi(client_version.clone()),
-                ExecuteQueryTimestamp::At(*ts),
+                ExecuteQueryTimestamp::At(ts),
                 None,
             )
             .await?;
@@ -515,6 +567,7 @@ pub async fn public_query_batch_post(
             Ok(value) => UdfResponse::Success {
                 value: export_value(value.unpack(), value_format, client_version.clone())?,
                 log_lines: udf_return.log_lines,
+                ts: None,
             },
             Err(error) => UdfResponse::error(
                 error,
@@ -564,6 +617,7 @@ pub async fn public_mutation_post(
         Ok(write_return) => UdfResponse::Success {
             value: export_value(write_return.value.unpack(), value_format, client_version)?,
             log_lines: write_return.log_lines,
+            ts: Some(write_return.ts.into()),
         },
         Err(write_error) => UdfResponse::error(
             write_error.error,
@@ -610,6 +664,7 @@ pub async fn public_action_post(
         Ok(action_return) => UdfResponse::Success {
             value: export_value(action_return.value.unpack(), value_format, client_version)?,
             log_lines: action_return.log_lines,
+            ts: None,
         },
         Err(action_error) => UdfResponse::error(
             action_error.error,
@@ -634,7 +689,9 @@ mod tests {
         json,
         Value as JsonValue,
     };
+    use sync_types::Timestamp;
 
+    use super::SerializedTs;
     use crate::test_helpers::setup_backend_for_test;
 
     async fn http_format_tester(
@@ -663,7 +720,14 @@ mod tests {
             .body(body)?;
         match expected {
             Ok(expected) => {
-                let result: JsonValue = backend.expect_success(req).await?;
+                let mut result: JsonValue = backend.expect_success(req).await?;
+                // Mutations also return their commit timestamp.
+                if uri == "/api/mutation" {
+                    let ts = result
+                        .as_object_mut()
+                        .and_then(|result| result.remove("ts"));
+                    assert!(ts.is_some_and(|ts| ts.is_string()));
+                }
                 assert_eq!(
                     result,
                     json!({
@@ -775,6 +839,50 @@ mod tests {
         .await
     }
 
+    #[convex_macro::prod_rt_test]
+    async fn test_http_query_reads_mutation_ts(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        backend.st.application.load_udf_tests_modules().await?;
+        let post = |uri: &str, body: JsonValue| -> anyhow::Result<Request<Body>> {
+            Ok(Request::builder()
+                .uri(uri)
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .header("Host", "localhost")
+                .body(Body::from(serde_json::to_vec(&body)?))?)
+        };
+        let result: JsonValue = backend
+            .expect_success(post(
+                "/api/mutation",
+                json!({"path": "basic:insertObject", "args": {"x": 1}}),
+            )?)
+            .await?;
+        let ts = result["ts"].clone();
+        assert!(ts.is_string());
+
+        let result: JsonValue = backend
+            .expect_success(post(
+                "/api/query",
+                json!({"path": "basic:count", "args": {}, "minTs": ts}),
+            )?)
+            .await?;
+        assert_eq!(result, json!({"status": "success", "value": 1}));
+
+        // A timestamp far past anything committed can't be waited for.
+        let future_ts = SerializedTs::from(Timestamp::MAX);
+        backend
+            .expect_error(
+                post(
+                    "/api/query",
+                    json!({"path": "basic:count", "args": {}, "minTs": future_ts}),
+                )?,
+                StatusCode::BAD_REQUEST,
+                "MinTsUnavailable",
+            )
+            .await?;
+        Ok(())
+    }
+
     #[convex_macro::prod_rt_test]
     async fn test_http_query_legacy_list_args(rt: ProdRuntime) -> anyhow::Result<()> {
         http_format_tester(
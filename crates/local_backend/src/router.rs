@@ -1,7 +1,6 @@
 use std::{
     convert::Infallible,
     sync::Arc,
-    time::Duration,
 };
 
 use axum::{
@@ -28,18 +27,11 @@ use common::{
         MAX_PUSH_BYTES,
     },
 };
-use http::{
-    Method,
-    StatusCode,
-};
+use http::StatusCode;
 use metrics::SERVER_VERSION_STR;
 use tower::ServiceBuilder;
 use tower_http::{
-    cors::{
-        AllowHeaders,
-        AllowOrigin,
-        CorsLayer,
-    },
+    cors::CorsLayer,
     decompression::RequestDecompressionLayer,
 };
 use udf::HTTP_ACTION_BODY_LIMIT;
@@ -54,15 +46,37 @@ use crate::{
         table_rate,
         udf_rate,
     },
+    bench::run_bench,
+    canary,
     canonical_urls::update_canonical_url,
+    code_versions,
+    cursor_validity::check_resumption_token,
     dashboard::{
         check_admin_key,
+        create_table_snapshot,
         delete_component,
+        delete_table_snapshot,
         delete_tables,
+        freeze_table,
+        get_component_quota,
         get_indexes,
         get_source_code,
+        list_deployment_audit_log,
+        list_error_groups,
+        list_function_runs,
+        list_schema_history,
+        list_table_freezes,
+        list_table_snapshots,
         run_test_function,
+        search_logs,
+        set_component_quota,
         shapes2,
+        truncate_table,
+        unfreeze_table,
+    },
+    data_masking::{
+        get_data_masking_rules,
+        update_data_masking_rules,
     },
     deploy_config::{
         get_config,
@@ -70,19 +84,44 @@ use crate::{
         push_config,
     },
     deploy_config2,
+    drain::reject_while_draining_middleware,
     environment_variables::update_environment_variables,
+    erasure,
+    feature_flags,
+    fixtures::load_fixtures,
+    health::{
+        healthz,
+        readyz,
+    },
     http_actions::http_action_handler,
+    http_checks,
+    index_scan::scan_index,
+    log_sinks::handlers::{
+        add_log_sink,
+        list_log_sinks,
+        remove_log_sink,
+    },
     logs::{
         stream_function_logs,
         stream_udf_execution,
     },
+    maintenance_mode,
+    network_policy::{
+        admin_ip_allowlist_middleware,
+        audit_log_context_middleware,
+        NetworkPolicy,
+    },
     node_action_callbacks::{
         action_callbacks_middleware,
         cancel_developer_job,
         create_function_handle,
+        hybrid_search,
         internal_action_post,
         internal_mutation_post,
         internal_query_post,
+        queue_ack,
+        queue_lease,
+        queue_nack,
         schedule_job,
         storage_delete,
         storage_generate_upload_url,
@@ -106,9 +145,21 @@ use crate::{
         cancel_job,
     },
     schema::{
+        export_schema,
         prepare_schema,
         schema_state,
     },
+    search_compaction::compact_text_index,
+    search_synonyms::{
+        get_search_synonyms,
+        update_search_synonyms,
+    },
+    simulated_time::{
+        advance_simulated_time,
+        freeze_simulated_time,
+        get_simulated_time,
+        resume_simulated_time,
+    },
     snapshot_export::{
         cancel_export,
         get_zip_export,
@@ -119,10 +170,13 @@ use crate::{
         cancel_import,
         import,
         import_finish_upload,
+        import_progress,
         import_start_upload,
         import_upload_part,
         perform_import,
+        resume_import,
     },
+    static_assets,
     storage::{
         storage_get,
         storage_upload,
@@ -139,6 +193,16 @@ use crate::{
         replace_tables,
     },
     subs::sync,
+    subscription_stats::subscription_stats,
+    usage_metering::usage_report,
+    vector_index_migrations::{
+        cancel_vector_index_migration,
+        cut_over_vector_index_migration,
+        list_vector_index_migrations,
+        start_vector_index_migration,
+        vector_embedding_status,
+    },
+    webhooks,
     LocalAppState,
     RouterState,
 };
@@ -168,6 +232,47 @@ pub fn router(st: LocalAppState) -> Router {
         .route("/update_environment_variables", post(update_environment_variables))
         // Canonical URL routes
         .route("/update_canonical_url", post(update_canonical_url))
+        // Data masking routes
+        .route("/get_data_masking_rules", get(get_data_masking_rules))
+        .route("/update_data_masking_rules", post(update_data_masking_rules))
+        // Erasure routes
+        .route("/get_erasure_policies", get(erasure::get_erasure_policies))
+        .route(
+            "/update_erasure_policies",
+            post(erasure::update_erasure_policies),
+        )
+        .route("/request_erasure", post(erasure::request_erasure))
+        .route("/get_erasure_job", get(erasure::get_erasure_job))
+        .route("/verify_erasure_report", post(erasure::verify_erasure_report))
+        // Search synonym routes
+        .route("/get_search_synonyms", get(get_search_synonyms))
+        .route("/update_search_synonyms", post(update_search_synonyms))
+        // Search compaction routes
+        .route("/compact_text_index", post(compact_text_index))
+        // Vector index migration routes
+        .route(
+            "/list_vector_index_migrations",
+            get(list_vector_index_migrations),
+        )
+        .route(
+            "/start_vector_index_migration",
+            post(start_vector_index_migration),
+        )
+        .route(
+            "/cut_over_vector_index_migration",
+            post(cut_over_vector_index_migration),
+        )
+        .route(
+            "/cancel_vector_index_migration",
+            post(cancel_vector_index_migration),
+        )
+        .route("/vector_embedding_status", get(vector_embedding_status))
+        // Log sink routes
+        .route("/list_log_sinks", get(list_log_sinks))
+        .route("/add_log_sink", post(add_log_sink))
+        .route("/remove_log_sink", post(remove_log_sink))
+        // Usage metering routes
+        .route("/usage_report", get(usage_report))
         // Local-only route to check if the admin key is valid
         .route("/check_admin_key", get(check_admin_key))
         .layer(ServiceBuilder::new());
@@ -182,6 +287,7 @@ pub fn router(st: LocalAppState) -> Router {
             post(deploy_config2::wait_for_schema),
         )
         .route("/deploy2/finish_push", post(deploy_config2::finish_push))
+        .route("/deploy2/start_canary", post(canary::start_canary))
         .route(
             "/deploy2/report_push_completed",
             post(deploy_config2::report_push_completed_handler),
@@ -197,6 +303,7 @@ pub fn router(st: LocalAppState) -> Router {
         .route("/get_config", post(get_config))
         .route("/get_config_hashes", post(get_config_hashes))
         .route("/schema_state/{schema_id}", get(schema_state))
+        .route("/export_schema", get(export_schema))
         .route("/stream_udf_execution", get(stream_udf_execution))
         .route("/stream_function_logs", get(stream_function_logs))
         .merge(import_routes())
@@ -211,41 +318,69 @@ pub fn router(st: LocalAppState) -> Router {
     let api_routes = Router::new()
         .merge(cli_routes)
         .merge(dashboard_routes)
+        .nest("/export", snapshot_export_routes)
+        .nest("/streaming_import", streaming_import_routes())
+        // Admin routes above this line are subject to the admin IP allowlist,
+        // record the client's address in audit log events, and are rejected
+        // while the backend drains. Action callbacks below
+        // are not, so in-flight actions can finish.
+        .layer(axum::middleware::from_fn_with_state(
+            st.network_policy.clone(),
+            admin_ip_allowlist_middleware,
+        ))
+        .layer(axum::middleware::from_fn(audit_log_context_middleware))
+        .layer(axum::middleware::from_fn_with_state(
+            st.drain.clone(),
+            reject_while_draining_middleware,
+        ))
         .nest(
             "/actions",
             action_callback_routes().layer(axum::middleware::map_request_with_state(
                 st.clone(),
                 add_extension::<LocalAppState, _>,
             )),
-        )
-        .nest("/export", snapshot_export_routes)
-        .nest("/streaming_import", streaming_import_routes());
+        );
 
     // Endpoints migrated to use the RouterState trait instead of application.
     let migrated_api_routes = Router::new()
         .merge(browser_routes)
         .merge(public_api_routes())
         .nest("/storage", storage_api_routes());
+    // HTTP actions set their own CORS headers unless the deployment restricts
+    // allowed origins.
+    let mut http_routes = http_action_routes();
+    if st.network_policy.has_cors_allowlist() {
+        http_routes = http_routes.layer(st.network_policy.cors());
+    }
     let migrated = Router::new()
         .nest("/api", migrated_api_routes)
-        .layer(cors())
+        .layer(st.network_policy.cors())
         // Order matters. Layers only apply to routes above them.
         // Notably, any layers added here won't apply to common routes
         // added inside `serve_http`
-        .nest("/http/", http_action_routes())
+        .nest("/http/", http_routes)
+        .layer(axum::middleware::from_fn_with_state(
+            st.drain.clone(),
+            reject_while_draining_middleware,
+        ))
         .with_state(RouterState {
             api: Arc::new(st.application.clone()),
             runtime: st.application.runtime().clone(),
+            drain: st.drain.clone(),
         });
 
     let version = SERVER_VERSION_STR.to_string();
+    let cors = st.network_policy.cors();
+    let trusted_proxies = st.network_policy.trusted_proxies();
 
     Router::new()
         .nest("/api", api_routes)
         .merge(health_check_routes(version))
-        .layer(cors())
+        .layer(cors)
         .with_state(st)
         .merge(migrated)
+        // Lets `ExtractClientIp` resolve addresses behind trusted proxies.
+        .layer(axum::Extension(trusted_proxies))
 }
 
 pub fn public_api_routes() -> Router<RouterState> {
@@ -283,7 +418,11 @@ where
         .route("/action", post(internal_action_post))
         .route("/schedule_job", post(schedule_job))
         .route("/vector_search", post(vector_search))
+        .route("/hybrid_search", post(hybrid_search))
         .route("/cancel_job", post(cancel_developer_job))
+        .route("/queue_lease", post(queue_lease))
+        .route("/queue_ack", post(queue_ack))
+        .route("/queue_nack", post(queue_nack))
         .route("/create_function_handle", post(create_function_handle))
         // file storage endpoints
         .route("/storage_generate_upload_url", post(storage_generate_upload_url))
@@ -307,6 +446,8 @@ where
         .route("/import/finish_upload", post(import_finish_upload))
         .route("/perform_import", post(perform_import))
         .route("/cancel_import", post(cancel_import))
+        .route("/resume_import", post(resume_import))
+        .route("/import_progress", get(import_progress))
 }
 
 pub fn http_action_routes() -> Router<RouterState> {
@@ -346,8 +487,116 @@ where
         .route("/shapes2", get(shapes2))
         .route("/get_indexes", get(get_indexes))
         .route("/delete_tables", post(delete_tables))
+        .route("/truncate_table", post(truncate_table))
+        .route("/freeze_table", post(freeze_table))
+        .route("/unfreeze_table", post(unfreeze_table))
+        .route("/list_table_freezes", get(list_table_freezes))
+        .route("/create_table_snapshot", post(create_table_snapshot))
+        .route("/delete_table_snapshot", post(delete_table_snapshot))
+        .route("/list_table_snapshots", get(list_table_snapshots))
+        .route("/load_fixtures", post(load_fixtures))
+        .route("/run_bench", post(run_bench))
+        .route("/simulated_time", get(get_simulated_time))
+        .route("/simulated_time/freeze", post(freeze_simulated_time))
+        .route("/simulated_time/resume", post(resume_simulated_time))
+        .route("/simulated_time/advance", post(advance_simulated_time))
+        .route("/get_component_quota", get(get_component_quota))
+        .route("/set_component_quota", post(set_component_quota))
+        .route("/get_canary", get(canary::get_canary))
+        .route("/update_canary_traffic", post(canary::update_canary_traffic))
+        .route("/promote_canary", post(canary::promote_canary))
+        .route("/roll_back_canary", post(canary::roll_back_canary))
+        .route("/list_code_versions", get(code_versions::list_code_versions))
+        .route(
+            "/rollback_code_version",
+            post(code_versions::rollback_code_version),
+        )
+        .route(
+            "/get_maintenance_mode",
+            get(maintenance_mode::get_maintenance_mode),
+        )
+        .route(
+            "/enable_maintenance_mode",
+            post(maintenance_mode::enable_maintenance_mode),
+        )
+        .route(
+            "/disable_maintenance_mode",
+            post(maintenance_mode::disable_maintenance_mode),
+        )
+        .route(
+            "/list_feature_flags",
+            get(feature_flags::list_feature_flags),
+        )
+        .route("/set_feature_flag", post(feature_flags::set_feature_flag))
+        .route(
+            "/delete_feature_flag",
+            post(feature_flags::delete_feature_flag),
+        )
+        .route(
+            "/list_webhook_endpoints",
+            get(webhooks::list_webhook_endpoints),
+        )
+        .route(
+            "/register_webhook_endpoint",
+            post(webhooks::register_webhook_endpoint),
+        )
+        .route(
+            "/delete_webhook_endpoint",
+            post(webhooks::delete_webhook_endpoint),
+        )
+        .route(
+            "/rotate_webhook_secret",
+            post(webhooks::rotate_webhook_secret),
+        )
+        .route(
+            "/list_webhook_deliveries",
+            get(webhooks::list_webhook_deliveries),
+        )
+        .route(
+            "/retry_webhook_delivery",
+            post(webhooks::retry_webhook_delivery),
+        )
+        .route("/list_http_checks", get(http_checks::list_http_checks))
+        .route("/set_http_check", post(http_checks::set_http_check))
+        .route("/delete_http_check", post(http_checks::delete_http_check))
+        .route(
+            "/list_http_check_results",
+            get(http_checks::list_http_check_results),
+        )
+        .route(
+            "/list_static_asset_mounts",
+            get(static_assets::list_static_asset_mounts),
+        )
+        .route(
+            "/set_static_asset_mount",
+            post(static_assets::set_static_asset_mount),
+        )
+        .route(
+            "/delete_static_asset_mount",
+            post(static_assets::delete_static_asset_mount),
+        )
+        .route(
+            "/list_static_assets",
+            get(static_assets::list_static_assets),
+        )
+        .route(
+            "/upload_static_asset",
+            post(static_assets::upload_static_asset),
+        )
+        .route(
+            "/delete_static_asset",
+            post(static_assets::delete_static_asset),
+        )
         .route("/delete_component", post(delete_component))
         .route("/get_source_code", get(get_source_code))
+        .route("/list_deployment_audit_log", get(list_deployment_audit_log))
+        .route("/list_function_runs", get(list_function_runs))
+        .route("/search_logs", get(search_logs))
+        .route("/list_error_groups", get(list_error_groups))
+        .route("/list_schema_history", get(list_schema_history))
+        .route("/subscription_stats", get(subscription_stats))
+        .route("/check_resumption_token", post(check_resumption_token))
+        .route("/scan_index", post(scan_index))
         // Metrics routes
         .nest("/app_metrics", app_metrics_routes())
 }
@@ -363,6 +612,8 @@ where
             get(|State(st): State<LocalAppState>| async move { st.instance_name.clone() }),
         )
         .route("/instance_version", get(|| async move { version }))
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
         .route(
             "/",
             get(|| async { "This Convex deployment is running. See https://docs.convex.dev/." }),
@@ -405,17 +656,5 @@ where
 }
 
 pub fn cors() -> CorsLayer {
-    CorsLayer::new()
-        .allow_headers(AllowHeaders::mirror_request())
-        .allow_credentials(true)
-        .allow_methods(vec![
-            Method::GET,
-            Method::POST,
-            Method::OPTIONS,
-            Method::PATCH,
-            Method::DELETE,
-            Method::PUT,
-        ])
-        .allow_origin(AllowOrigin::mirror_request())
-        .max_age(Duration::from_secs(86400))
+    NetworkPolicy::default().cors()
 }
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use anyhow::Context;
 use application::deploy_config::ModuleJson;
 use axum::{
@@ -14,6 +16,7 @@ use common::{
             },
             text_index::{
                 DeveloperTextIndexConfig,
+                SerializedTextAnalyzerConfig,
                 TextIndexState,
             },
             vector_index::{
@@ -30,13 +33,16 @@ use common::{
             SchemaState,
         },
     },
+    components::ComponentId,
     http::{
         extract::{
             Json,
             Path,
+            Query,
         },
         HttpResponseError,
     },
+    schemas::violations::SerializedSchemaViolationReport,
 };
 use database::{
     IndexModel,
@@ -53,6 +59,7 @@ use serde_json::{
     Value as JsonValue,
 };
 use value::{
+    export::ValueFormat,
     ConvexValue,
     ResolvedDocumentId,
     TableName,
@@ -99,6 +106,16 @@ impl TryFrom<LegacyIndexDiff> for BuildIndexesResponse {
 #[serde(rename_all = "camelCase")]
 struct BackfillResponse {
     state: String,
+    // Set for backfilling text indexes once the first batch of documents is indexed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    progress: Option<BackfillProgressResponse>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BackfillProgressResponse {
+    num_docs_indexed: u64,
+    total_docs: u64,
 }
 
 #[derive(Serialize)]
@@ -107,7 +124,8 @@ pub struct IndexMetadataResponse {
     table: String,
     name: String,
     // Either an array of fields (`string[]`) for a database index or an object of
-    // `{ searchField: string, filterFields: string }` for a search index.
+    // `{ searchField: string, filterFields: string, analyzer?: object }` for a
+    // search index.
     fields: JsonValue,
     backfill: BackfillResponse,
 }
@@ -141,6 +159,7 @@ impl TryFrom<IndexMetadata<TableName>> for IndexMetadataResponse {
                     fields: JsonValue::from(ConvexValue::try_from(fields)?),
                     backfill: BackfillResponse {
                         state: backfill_state,
+                        progress: None,
                     },
                 }
             },
@@ -149,9 +168,21 @@ impl TryFrom<IndexMetadata<TableName>> for IndexMetadataResponse {
                 developer_config:
                     DeveloperTextIndexConfig {
                         search_field,
+                        extra_search_fields,
+                        search_field_weights,
                         filter_fields,
+                        analyzer,
                     },
             } => {
+                let progress = match &on_disk_state {
+                    TextIndexState::Backfilling(backfill_state) => backfill_state
+                        .total_documents
+                        .map(|total_docs| BackfillProgressResponse {
+                            num_docs_indexed: backfill_state.num_documents_indexed(),
+                            total_docs,
+                        }),
+                    TextIndexState::SnapshottedAt(_) | TextIndexState::Backfilled(_) => None,
+                };
                 let backfill_state = match on_disk_state {
                     TextIndexState::Backfilling(_) => "in_progress".to_string(),
                     // TODO(CX-3851): The result of this is used to poll for state in the CLI and
@@ -162,15 +193,33 @@ impl TryFrom<IndexMetadata<TableName>> for IndexMetadataResponse {
                         "done".to_string()
                     },
                 };
+                let mut fields = json!({
+                    "searchField":  String::from(search_field),
+                    "filterFields": filter_fields.into_iter().map(String::from).collect::<Vec<_>>()
+                });
+                if !extra_search_fields.is_empty() {
+                    fields["extraSearchFields"] = json!(extra_search_fields
+                        .into_iter()
+                        .map(String::from)
+                        .collect::<Vec<_>>());
+                }
+                if !search_field_weights.is_empty() {
+                    fields["searchFieldWeights"] = json!(search_field_weights
+                        .into_iter()
+                        .map(|(field_path, weight)| (String::from(field_path), weight))
+                        .collect::<BTreeMap<_, _>>());
+                }
+                if !analyzer.is_default() {
+                    fields["analyzer"] =
+                        serde_json::to_value(SerializedTextAnalyzerConfig::from(analyzer))?;
+                }
                 IndexMetadataResponse {
                     table,
                     name,
-                    fields: json!({
-                        "searchField":  String::from(search_field),
-                        "filterFields": filter_fields.into_iter().map(String::from).collect::<Vec<_>>()
-                    }),
+                    fields,
                     backfill: BackfillResponse {
                         state: backfill_state,
+                        progress,
                     },
                 }
             },
@@ -180,6 +229,10 @@ impl TryFrom<IndexMetadata<TableName>> for IndexMetadataResponse {
                         dimensions,
                         vector_field,
                         filter_fields,
+                        partition_field,
+                        distance_metric,
+                        index_kind,
+                        quantization,
                     },
                 on_disk_state,
             } => {
@@ -189,16 +242,30 @@ impl TryFrom<IndexMetadata<TableName>> for IndexMetadataResponse {
                         "done".to_string()
                     },
                 };
+                let mut fields = json!({
+                    "dimensions": u32::from(dimensions),
+                    "vectorField": String::from(vector_field),
+                    "filterFields": filter_fields.into_iter().map(String::from).collect::<Vec<_>>()
+                });
+                if !distance_metric.is_default() {
+                    fields["distanceMetric"] = json!(distance_metric.to_string());
+                }
+                if !index_kind.is_default() {
+                    fields["indexKind"] = json!(index_kind.to_string());
+                }
+                if !quantization.is_default() {
+                    fields["quantization"] = json!(quantization.to_string());
+                }
+                if let Some(partition_field) = partition_field {
+                    fields["partitionField"] = json!(String::from(partition_field));
+                }
                 IndexMetadataResponse {
                     table,
                     name,
-                    fields: json!({
-                        "dimensions": u32::from(dimensions),
-                        "vectorField": String::from(vector_field),
-                        "filterFields": filter_fields.into_iter().map(String::from).collect::<Vec<_>>()
-                    }),
+                    fields,
                     backfill: BackfillResponse {
                         state: backfill_state,
+                        progress: None,
                     },
                 }
             },
@@ -335,6 +402,7 @@ enum SchemaStateJson {
     Failed {
         error: String,
         table_name: Option<String>,
+        report: Option<SerializedSchemaViolationReport>,
     },
     Overwritten,
 }
@@ -345,8 +413,14 @@ impl From<SchemaState> for SchemaStateJson {
             SchemaState::Pending => SchemaStateJson::Pending,
             SchemaState::Validated => SchemaStateJson::Validated,
             SchemaState::Active => SchemaStateJson::Active,
-            SchemaState::Failed { error, table_name } => {
-                SchemaStateJson::Failed { error, table_name }
+            SchemaState::Failed {
+                error,
+                table_name,
+                report,
+            } => SchemaStateJson::Failed {
+                error,
+                table_name,
+                report: report.map(SerializedSchemaViolationReport::from),
             },
             SchemaState::Overwritten => SchemaStateJson::Overwritten,
         }
@@ -384,3 +458,57 @@ pub async fn schema_state(
         schema_state: state.into(),
     }))
 }
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum SchemaExportFormat {
+    #[default]
+    JsonSchema,
+    Openapi,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportSchemaArgs {
+    component: Option<String>,
+    #[serde(default)]
+    format: SchemaExportFormat,
+    /// How values are encoded in the described documents, e.g. `json` for
+    /// clean JSON exports.
+    value_format: Option<String>,
+}
+
+/// Converts the active schema into JSON Schema or OpenAPI component
+/// definitions for the documents in each table.
+#[debug_handler]
+pub async fn export_schema(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(ExportSchemaArgs {
+        component,
+        format,
+        value_format,
+    }): Query<ExportSchemaArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let component = ComponentId::deserialize_from_string(component.as_deref())?;
+    let value_format = value_format
+        .as_deref()
+        .map(str::parse)
+        .transpose()?
+        .unwrap_or(ValueFormat::ConvexCleanJSON);
+    let schema = st
+        .application
+        .get_schema(component.into(), &identity)
+        .await?
+        .ok_or_else(|| {
+            anyhow::anyhow!(ErrorMetadata::not_found(
+                "SchemaNotFound",
+                "This deployment doesn't have a schema. Push a schema.ts to export it."
+            ))
+        })?;
+    Ok(Json(match format {
+        SchemaExportFormat::JsonSchema => schema.to_json_schema(value_format),
+        SchemaExportFormat::Openapi => schema.to_openapi_components(value_format),
+    }))
+}
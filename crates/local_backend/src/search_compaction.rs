@@ -0,0 +1,74 @@
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::http::{
+    extract::Json,
+    HttpResponseError,
+};
+use http::StatusCode;
+use serde::Deserialize;
+
+use crate::{
+    admin::must_be_admin_with_write_access,
+    authentication::ExtractIdentity,
+    search_synonyms::parse_index_name,
+    LocalAppState,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompactTextIndexArgs {
+    component_path: Option<String>,
+    index_name: String,
+}
+
+/// Compacts a text search index in the background, merging its segments as
+/// far as possible regardless of the instance's compaction policy.
+#[debug_handler]
+pub async fn compact_text_index(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(CompactTextIndexArgs {
+        component_path,
+        index_name,
+    }): Json<CompactTextIndexArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let (component_path, index_name) = parse_index_name(component_path, &index_name)?;
+    st.application
+        .compact_text_index(identity, &component_path, &index_name)
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum_extra::headers::authorization::Credentials;
+    use http::{
+        Request,
+        StatusCode,
+    };
+    use runtime::prod::ProdRuntime;
+    use serde_json::json;
+
+    use crate::test_helpers::setup_backend_for_test;
+
+    #[convex_macro::prod_rt_test]
+    async fn test_compact_text_index_requires_text_index(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let req = Request::builder()
+            .uri("/api/compact_text_index")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .header("Authorization", backend.admin_auth_header.0.encode())
+            .body(axum::body::Body::from(serde_json::to_vec(
+                &json!({ "indexName": "messages.search_body" }),
+            )?))?;
+        backend
+            .expect_error(req, StatusCode::BAD_REQUEST, "IndexNotFound")
+            .await?;
+        Ok(())
+    }
+}
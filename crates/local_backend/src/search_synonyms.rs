@@ -0,0 +1,160 @@
+use anyhow::Context;
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    components::ComponentPath,
+    http::{
+        extract::{
+            Json,
+            Query,
+        },
+        HttpResponseError,
+    },
+    query::SearchSynonyms,
+    types::IndexName,
+};
+use errors::ErrorMetadata;
+use http::StatusCode;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetSearchSynonymsArgs {
+    component_path: Option<String>,
+    index_name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateSearchSynonymsArgs {
+    component_path: Option<String>,
+    index_name: String,
+    synonyms: Vec<Vec<String>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchSynonymsResponse {
+    synonyms: Vec<Vec<String>>,
+}
+
+pub(crate) fn parse_index_name(
+    component_path: Option<String>,
+    index_name: &str,
+) -> anyhow::Result<(ComponentPath, IndexName)> {
+    let component_path = ComponentPath::deserialize(component_path.as_deref())?;
+    let index_name = index_name.parse().with_context(|| {
+        ErrorMetadata::bad_request(
+            "InvalidIndexName",
+            format!("Invalid index name {index_name:?}. Expected \"table.index\"."),
+        )
+    })?;
+    Ok((component_path, index_name))
+}
+
+/// Returns the synonyms of a text search index, as groups of words that
+/// searches treat as interchangeable.
+#[debug_handler]
+pub async fn get_search_synonyms(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(GetSearchSynonymsArgs {
+        component_path,
+        index_name,
+    }): Query<GetSearchSynonymsArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let (component_path, index_name) = parse_index_name(component_path, &index_name)?;
+    let synonyms = st
+        .application
+        .get_search_synonyms(identity, &component_path, &index_name)
+        .await?;
+    Ok(Json(SearchSynonymsResponse {
+        synonyms: synonyms.groups,
+    }))
+}
+
+/// Replaces the synonyms of a text search index. Searches use them right away,
+/// without rebuilding the index.
+#[debug_handler]
+pub async fn update_search_synonyms(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(UpdateSearchSynonymsArgs {
+        component_path,
+        index_name,
+        synonyms,
+    }): Json<UpdateSearchSynonymsArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let (component_path, index_name) = parse_index_name(component_path, &index_name)?;
+    st.application
+        .set_search_synonyms(
+            identity,
+            &component_path,
+            &index_name,
+            SearchSynonyms::new(synonyms),
+        )
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum_extra::headers::authorization::Credentials;
+    use http::{
+        Request,
+        StatusCode,
+    };
+    use runtime::prod::ProdRuntime;
+    use serde_json::json;
+
+    use crate::test_helpers::setup_backend_for_test;
+
+    #[convex_macro::prod_rt_test]
+    async fn test_update_search_synonyms_requires_text_index(
+        rt: ProdRuntime,
+    ) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let update = |body: serde_json::Value| {
+            Request::builder()
+                .uri("/api/update_search_synonyms")
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .header("Authorization", backend.admin_auth_header.0.encode())
+                .body(axum::body::Body::from(serde_json::to_vec(&body).unwrap()))
+        };
+
+        let req = update(json!({
+            "indexName": "messages.search_body",
+            "synonyms": [["couch", "sofa"]],
+        }))?;
+        backend
+            .expect_error(req, StatusCode::BAD_REQUEST, "IndexNotFound")
+            .await?;
+
+        let req = update(json!({
+            "indexName": "not an index",
+            "synonyms": [],
+        }))?;
+        backend
+            .expect_error(req, StatusCode::BAD_REQUEST, "InvalidIndexName")
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,220 @@
+//! `convex-searchlight`: runs text and vector searches and compactions for one
+//! or more backends started with `--searchlight-urls`, caching the segments it
+//! fetches from search storage on local disk. Run several behind one backend to
+//! scale search independently of the committer.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    future::Future,
+    net::SocketAddr,
+    path::PathBuf,
+    sync::Arc,
+};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use aws_s3::storage::{
+    s3_bucket_name,
+    S3Storage,
+};
+use clap::Parser;
+use common::{
+    grpc::ConvexGrpcService,
+    knobs::SEARCHLIGHT_MAX_MESSAGE_SIZE,
+    runtime::Runtime,
+};
+use metrics::SERVER_VERSION_STR;
+use parking_lot::Mutex;
+use pb::searchlight::{
+    searchlight_server::SearchlightServer,
+    storage_type,
+    StorageType,
+};
+use search::searcher::{
+    SearchStorageResolver,
+    SearcherImpl,
+    SearchlightService,
+};
+use storage::{
+    LocalDirStorage,
+    Storage,
+    StorageUseCase,
+};
+
+#[derive(Parser, Clone)]
+#[clap(version = &**SERVER_VERSION_STR, author = "Convex, Inc. <no-reply@convex.dev>")]
+pub struct SearchlightConfig {
+    /// Address to serve gRPC on. Bind to an address reachable from the
+    /// backends only, since searches return document IDs from any table.
+    #[clap(long, default_value = "127.0.0.1:8100")]
+    pub bind_address: SocketAddr,
+
+    /// Shared secret every request must carry. Backends send it with
+    /// `--searchlight-secret`.
+    #[clap(long)]
+    pub secret: String,
+
+    /// Directory to cache fetched segments in.
+    #[clap(long, default_value = "convex_searchlight_cache")]
+    pub cache_dir: PathBuf,
+
+    /// Maximum size of the segment cache, in MiB.
+    #[clap(long, default_value = "10240")]
+    pub max_cache_size_mb: u64,
+
+    /// Vector queries slower than this are logged.
+    #[clap(long, default_value = "100")]
+    pub slow_vector_query_threshold_millis: u64,
+
+    /// The `--local-storage` directory of the backends this searchlight
+    /// serves, if they use local storage. Only segments under it are read.
+    #[clap(long)]
+    pub local_storage: Option<PathBuf>,
+
+    /// Serve backends using S3 storage. Only segments in the bucket named by
+    /// `S3_STORAGE_SEARCH_INDEXES_BUCKET` are read.
+    #[clap(long)]
+    pub s3_storage: bool,
+}
+
+impl fmt::Debug for SearchlightConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SearchlightConfig")
+            .field("bind_address", &self.bind_address)
+            .field("cache_dir", &self.cache_dir)
+            .field("max_cache_size_mb", &self.max_cache_size_mb)
+            .field("local_storage", &self.local_storage)
+            .field("s3_storage", &self.s3_storage)
+            .finish()
+    }
+}
+
+/// Only resolves the search storage of the backends this searchlight was
+/// configured for, so a caller can't use it to read arbitrary files or
+/// buckets.
+pub struct SearchlightStorageResolver<RT: Runtime> {
+    runtime: RT,
+    local_storage: Option<PathBuf>,
+    s3_bucket: Option<String>,
+    storages: Mutex<BTreeMap<ResolvedStorage, Arc<dyn Storage>>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum ResolvedStorage {
+    Local(PathBuf),
+    S3 { bucket: String, prefix: String },
+}
+
+impl<RT: Runtime> SearchlightStorageResolver<RT> {
+    pub fn new(runtime: RT, config: &SearchlightConfig) -> anyhow::Result<Self> {
+        let local_storage = config
+            .local_storage
+            .as_ref()
+            .map(|dir| {
+                dir.canonicalize()
+                    .with_context(|| format!("Invalid --local-storage {}", dir.display()))
+            })
+            .transpose()?;
+        let s3_bucket = config
+            .s3_storage
+            .then(|| s3_bucket_name(&StorageUseCase::SearchIndexes))
+            .transpose()?;
+        anyhow::ensure!(
+            local_storage.is_some() || s3_bucket.is_some(),
+            "One of --local-storage or --s3-storage is required"
+        );
+        Ok(Self {
+            runtime,
+            local_storage,
+            s3_bucket,
+            storages: Mutex::new(BTreeMap::new()),
+        })
+    }
+
+    fn allowed(&self, storage_type: StorageType) -> anyhow::Result<ResolvedStorage> {
+        match storage_type.storage_type.context("Missing storage_type")? {
+            storage_type::StorageType::Local(local) => {
+                let root = self
+                    .local_storage
+                    .as_ref()
+                    .context("This searchlight doesn't serve local storage")?;
+                let path = PathBuf::from(&local.path)
+                    .canonicalize()
+                    .with_context(|| format!("Invalid local storage path {}", local.path))?;
+                anyhow::ensure!(
+                    path.starts_with(root),
+                    "Local storage path {} is outside {}",
+                    path.display(),
+                    root.display()
+                );
+                Ok(ResolvedStorage::Local(path))
+            },
+            storage_type::StorageType::S3(s3) => {
+                anyhow::ensure!(
+                    self.s3_bucket.as_ref() == Some(&s3.bucket),
+                    "This searchlight doesn't serve S3 bucket {}",
+                    s3.bucket
+                );
+                Ok(ResolvedStorage::S3 {
+                    bucket: s3.bucket,
+                    prefix: s3.prefix,
+                })
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl<RT: Runtime> SearchStorageResolver for SearchlightStorageResolver<RT> {
+    async fn resolve(&self, storage_type: StorageType) -> anyhow::Result<Arc<dyn Storage>> {
+        let resolved = self.allowed(storage_type)?;
+        if let Some(storage) = self.storages.lock().get(&resolved) {
+            return Ok(storage.clone());
+        }
+        let storage: Arc<dyn Storage> = match resolved.clone() {
+            ResolvedStorage::Local(path) => {
+                Arc::new(LocalDirStorage::new_at_path(self.runtime.clone(), path)?)
+            },
+            ResolvedStorage::S3 { bucket, prefix } => {
+                Arc::new(S3Storage::new_with_prefix(bucket, prefix, self.runtime.clone()).await?)
+            },
+        };
+        Ok(self
+            .storages
+            .lock()
+            .entry(resolved)
+            .or_insert(storage)
+            .clone())
+    }
+}
+
+/// Serves searches until `shutdown` resolves.
+pub async fn run_searchlight<RT: Runtime>(
+    runtime: RT,
+    config: SearchlightConfig,
+    shutdown: impl Future<Output = ()>,
+) -> anyhow::Result<()> {
+    let storage_resolver = SearchlightStorageResolver::new(runtime.clone(), &config)?;
+    let searcher = SearcherImpl::new(
+        &config.cache_dir,
+        bytesize::mib(config.max_cache_size_mb),
+        config.slow_vector_query_threshold_millis,
+        false,
+        runtime,
+    )
+    .await?;
+    let service = SearchlightService::new(
+        Arc::new(searcher),
+        Arc::new(storage_resolver),
+        &config.secret,
+    )?;
+    ConvexGrpcService::new()
+        .add_service(
+            SearchlightServer::new(service)
+                .max_decoding_message_size(*SEARCHLIGHT_MAX_MESSAGE_SIZE)
+                .max_encoding_message_size(*SEARCHLIGHT_MAX_MESSAGE_SIZE),
+        )
+        .serve(config.bind_address, shutdown)
+        .await
+}
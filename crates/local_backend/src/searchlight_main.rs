@@ -0,0 +1,36 @@
+use clap::Parser;
+use cmd_util::env::config_service;
+use common::errors::MainError;
+use futures::FutureExt;
+use local_backend::searchlight::{
+    run_searchlight,
+    SearchlightConfig,
+};
+use runtime::prod::ProdRuntime;
+use tokio::signal::{
+    self,
+    unix::SignalKind,
+};
+
+fn main() -> Result<(), MainError> {
+    let _guard = config_service();
+    let config = SearchlightConfig::parse();
+    tracing::info!("Starting searchlight");
+
+    let tokio = ProdRuntime::init_tokio()?;
+    let runtime = ProdRuntime::new(&tokio);
+    let runtime_ = runtime.clone();
+    runtime.block_on("main", async move {
+        let mut sigterm = signal::unix::signal(SignalKind::terminate())?;
+        let shutdown = async move {
+            futures::select! {
+                _ = signal::ctrl_c().fuse() => tracing::info!("Received Ctrl-C signal"),
+                _ = sigterm.recv().fuse() => tracing::info!("Received SIGTERM signal"),
+            }
+        };
+        run_searchlight(runtime_, config, shutdown).await?;
+        tracing::info!("Searchlight shut down");
+        anyhow::Ok(())
+    })?;
+    Ok(())
+}
@@ -0,0 +1,218 @@
+//! Freezing and advancing the backend clock on development deployments, so
+//! scheduled functions and crons can be tested without waiting for them. See
+//! `runtime::simulated_clock`.
+
+use std::time::{
+    Duration,
+    UNIX_EPOCH,
+};
+
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::http::{
+    extract::Json,
+    HttpResponseError,
+};
+use errors::ErrorMetadata;
+use runtime::simulated_clock::{
+    SimulatedClock,
+    SimulatedClockStatus,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdvanceSimulatedTimeArgs {
+    duration_ms: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedTimeResponse {
+    now_ms: u64,
+    frozen: bool,
+}
+
+impl TryFrom<SimulatedClockStatus> for SimulatedTimeResponse {
+    type Error = anyhow::Error;
+
+    fn try_from(status: SimulatedClockStatus) -> anyhow::Result<Self> {
+        Ok(Self {
+            now_ms: status.now.duration_since(UNIX_EPOCH)?.as_millis() as u64,
+            frozen: status.frozen,
+        })
+    }
+}
+
+fn simulated_clock(st: &LocalAppState) -> anyhow::Result<SimulatedClock> {
+    st.application
+        .runtime()
+        .simulated_clock()
+        .cloned()
+        .ok_or_else(|| {
+            anyhow::anyhow!(ErrorMetadata::forbidden(
+                "SimulatedTimeDisabled",
+                "The clock can only be controlled on development deployments. Start the backend \
+                 with --enable-simulated-time to allow it.",
+            ))
+        })
+}
+
+#[debug_handler]
+pub async fn get_simulated_time(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let clock = simulated_clock(&st)?;
+    Ok(Json(SimulatedTimeResponse::try_from(clock.status())?))
+}
+
+/// Stops the clock. Scheduled functions, crons, and anything else waiting on
+/// the runtime stay pending until the clock is advanced or resumed.
+#[debug_handler]
+pub async fn freeze_simulated_time(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let clock = simulated_clock(&st)?;
+    let status = clock.freeze();
+    tracing::info!("Froze simulated time at {:?}", status.now);
+    Ok(Json(SimulatedTimeResponse::try_from(status)?))
+}
+
+#[debug_handler]
+pub async fn resume_simulated_time(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let clock = simulated_clock(&st)?;
+    let status = clock.resume();
+    tracing::info!("Resumed simulated time from {:?}", status.now);
+    Ok(Json(SimulatedTimeResponse::try_from(status)?))
+}
+
+/// Jumps the clock forward. Anything that came due in the skipped interval,
+/// such as scheduled functions and cron runs, starts running right away.
+#[debug_handler]
+pub async fn advance_simulated_time(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(AdvanceSimulatedTimeArgs { duration_ms }): Json<AdvanceSimulatedTimeArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let clock = simulated_clock(&st)?;
+    let status = clock.advance(Duration::from_millis(duration_ms));
+    tracing::info!(
+        "Advanced simulated time by {duration_ms}ms to {:?}",
+        status.now
+    );
+    Ok(Json(SimulatedTimeResponse::try_from(status)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use common::runtime::Runtime;
+    use http::{
+        Request,
+        StatusCode,
+    };
+    use runtime::prod::ProdRuntime;
+    use serde_json::{
+        json,
+        Value as JsonValue,
+    };
+
+    use crate::test_helpers::{
+        setup_backend_for_test,
+        TestLocalBackend,
+    };
+
+    fn post(
+        backend: &TestLocalBackend,
+        path: &str,
+        body: JsonValue,
+    ) -> anyhow::Result<Request<axum::body::Body>> {
+        Ok(Request::builder()
+            .uri(format!("/api/simulated_time{path}"))
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .header("Authorization", backend.admin_auth_header.0.encode())
+            .body(axum::body::Body::from(serde_json::to_vec(&body)?))?)
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_advance_runs_waiters(rt: ProdRuntime) -> anyhow::Result<()> {
+        let rt = rt.with_simulated_clock();
+        let backend = setup_backend_for_test(rt.clone()).await?;
+
+        let frozen: JsonValue = backend
+            .expect_success(post(&backend, "/freeze", json!({}))?)
+            .await?;
+        let frozen_ms = frozen["nowMs"].as_u64().unwrap();
+        assert_eq!(frozen["frozen"], json!(true));
+
+        let day = Duration::from_secs(24 * 60 * 60);
+        let mut wait = rt.wait_for_system_time(3 * day);
+        // Real time passing doesn't move a frozen clock.
+        tokio::time::timeout(Duration::from_millis(50), &mut wait)
+            .await
+            .unwrap_err();
+
+        let advanced: JsonValue = backend
+            .expect_success(post(
+                &backend,
+                "/advance",
+                json!({ "durationMs": (3 * day).as_millis() as u64 }),
+            )?)
+            .await?;
+        assert_eq!(
+            advanced["nowMs"].as_u64().unwrap(),
+            frozen_ms + (3 * day).as_millis() as u64
+        );
+        tokio::time::timeout(Duration::from_secs(5), wait).await?;
+        Ok(())
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_frozen_clock_keeps_real_timeouts(rt: ProdRuntime) -> anyhow::Result<()> {
+        let rt = rt.with_simulated_clock();
+        rt.simulated_clock().unwrap().freeze();
+        // Function timeouts and heartbeats still fire while the clock is
+        // frozen.
+        tokio::time::timeout(Duration::from_secs(5), rt.wait(Duration::from_millis(10))).await?;
+        Ok(())
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_simulated_time_disabled(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        backend
+            .expect_error(
+                post(&backend, "/freeze", json!({}))?,
+                StatusCode::FORBIDDEN,
+                "SimulatedTimeDisabled",
+            )
+            .await?;
+        Ok(())
+    }
+}
@@ -57,6 +57,10 @@ pub struct RequestZipExport {
     #[serde(default)]
     pub include_storage: bool,
     pub component: Option<String>,
+    /// Set when the export seeds a preview deployment, so the deployment's
+    /// data masking rules are applied.
+    #[serde(default)]
+    pub for_preview_deployment: bool,
 }
 
 #[fastrace::trace]
@@ -66,16 +70,22 @@ pub async fn request_zip_export(
     Query(RequestZipExport {
         include_storage,
         component,
+        for_preview_deployment,
     }): Query<RequestZipExport>,
 ) -> Result<impl IntoResponse, HttpResponseError> {
     must_be_admin_with_write_access(&identity)?;
     let component = ComponentId::deserialize_from_string(component.as_deref())?;
+    let requestor = if for_preview_deployment {
+        ExportRequestor::PreviewDeploymentClone
+    } else {
+        ExportRequestor::SnapshotExport
+    };
     st.application
         .request_export(
             identity,
             ExportFormat::Zip { include_storage },
             component,
-            ExportRequestor::SnapshotExport,
+            requestor,
             None,
         )
         .await?;
@@ -29,6 +29,7 @@ use futures::{
 use model::snapshot_imports::types::{
     ImportFormat,
     ImportMode,
+    SerializedImportState,
 };
 use serde::{
     Deserialize,
@@ -44,7 +45,10 @@ use value::{
 };
 
 use crate::{
-    admin::must_be_admin_with_write_access,
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
     authentication::ExtractIdentity,
     LocalAppState,
 };
@@ -289,3 +293,77 @@ pub async fn cancel_import(
     snapshot_import::cancel_import(&st.application, identity, import_id).await?;
     Ok(())
 }
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResumeImportArgs {
+    pub import_id: String,
+}
+
+/// Resume a failed import from its last checkpoint instead of starting over.
+pub async fn resume_import(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(ResumeImportArgs { import_id }): Json<ResumeImportArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let import_id = DeveloperDocumentId::decode(&import_id).context(ErrorMetadata::bad_request(
+        "InvalidImport",
+        format!("invalid import id {import_id}"),
+    ))?;
+    snapshot_import::resume_import(&st.application, identity, import_id).await?;
+    Ok(())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportProgressArgs {
+    pub import_id: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ImportTableProgress {
+    component_path: String,
+    table_name: String,
+    num_rows_written: i64,
+    total_num_rows_to_write: i64,
+    last_imported_id: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ImportProgressResponse {
+    state: SerializedImportState,
+    tables: Vec<ImportTableProgress>,
+}
+
+pub async fn import_progress(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(ImportProgressArgs { import_id }): Query<ImportProgressArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let import_id = DeveloperDocumentId::decode(&import_id).context(ErrorMetadata::bad_request(
+        "InvalidImport",
+        format!("invalid import id {import_id}"),
+    ))?;
+    let snapshot_import =
+        snapshot_import::import_progress(&st.application, identity, import_id).await?;
+    let tables = snapshot_import
+        .checkpoints
+        .unwrap_or_default()
+        .into_iter()
+        .map(|checkpoint| ImportTableProgress {
+            component_path: String::from(checkpoint.component_path),
+            table_name: checkpoint.display_table_name.to_string(),
+            num_rows_written: checkpoint.num_rows_written,
+            total_num_rows_to_write: checkpoint.total_num_rows_to_write,
+            last_imported_id: checkpoint.last_imported_id,
+        })
+        .collect();
+    Ok(Json(ImportProgressResponse {
+        state: snapshot_import.state.into(),
+        tables,
+    }))
+}
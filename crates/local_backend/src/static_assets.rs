@@ -0,0 +1,526 @@
+//! Static asset hosting: admin endpoints for uploading files into folders and
+//! mounting folders under URL prefixes, and serving mounted files on the HTTP
+//! actions domain with ETags, Cache-Control and optional compression.
+
+use std::sync::Arc;
+
+use anyhow::Context;
+use application::api::ApplicationApi;
+use async_compression::{
+    tokio::bufread::{
+        BrotliEncoder,
+        GzipEncoder,
+    },
+    Level,
+};
+use axum::{
+    body::Body,
+    debug_handler,
+    extract::State,
+    response::{
+        IntoResponse,
+        Response,
+    },
+};
+use axum_extra::{
+    headers::{
+        ContentType,
+        ETag,
+        HeaderMapExt,
+        IfNoneMatch,
+    },
+    typed_header::TypedHeaderRejection,
+    TypedHeader,
+};
+use common::{
+    components::ComponentId,
+    document::ParsedDocument,
+    http::{
+        extract::{
+            Json,
+            Query,
+        },
+        HttpResponseError,
+        ResolvedHostname,
+    },
+    types::ConvexOrigin,
+    RequestId,
+};
+use file_storage::FileStream;
+use futures::{
+    stream::BoxStream,
+    StreamExt,
+};
+use http::{
+    header::{
+        CACHE_CONTROL,
+        CONTENT_ENCODING,
+        VARY,
+    },
+    HeaderMap,
+    HeaderValue,
+    StatusCode,
+};
+use model::{
+    file_storage::FileStorageId,
+    static_assets::types::{
+        StaticAsset,
+        StaticAssetMount,
+    },
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use tokio_util::io::{
+    ReaderStream,
+    StreamReader,
+};
+use udf::HttpActionRequestHead;
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    storage::map_header_err,
+    LocalAppState,
+};
+
+/// Browsers revalidate files without a configured `Cache-Control` on every
+/// use, which is cheap with ETags and never serves a stale deploy.
+const DEFAULT_CACHE_CONTROL: &str = "no-cache";
+/// Smaller files aren't worth compressing.
+const MIN_COMPRESSED_SIZE: i64 = 1024;
+/// Brotli's default quality is too slow to compress on every request.
+const BROTLI_QUALITY: i32 = 5;
+
+#[derive(Clone, Copy)]
+enum ContentEncoding {
+    Brotli,
+    Gzip,
+}
+
+impl ContentEncoding {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ContentEncoding::Brotli => "br",
+            ContentEncoding::Gzip => "gzip",
+        }
+    }
+}
+
+/// Serves the static asset mounted at the request's path, if there is one.
+/// Returns `None` so the request falls through to HTTP actions otherwise.
+pub async fn serve_static_asset(
+    api: &Arc<dyn ApplicationApi>,
+    host: &ResolvedHostname,
+    request_id: RequestId,
+    head: &HttpActionRequestHead,
+) -> anyhow::Result<Option<Response>> {
+    let Ok(path) = urlencoding::decode(head.url.path()) else {
+        return Ok(None);
+    };
+    let Some((mount, asset)) = api
+        .resolve_static_asset(host, request_id.clone(), &path)
+        .await?
+    else {
+        return Ok(None);
+    };
+    let encoding = if mount.compress
+        && asset.size >= MIN_COMPRESSED_SIZE
+        && asset.content_type.as_deref().is_some_and(is_compressible)
+    {
+        preferred_encoding(&head.headers)
+    } else {
+        None
+    };
+    // Compressed responses have different bytes, so they need their own ETags.
+    let etag: ETag = match encoding {
+        Some(encoding) => format!("\"{}-{}\"", asset.sha256.as_hex(), encoding.as_str()),
+        None => format!("\"{}\"", asset.sha256.as_hex()),
+    }
+    .parse()?;
+
+    let mut headers = HeaderMap::new();
+    headers.typed_insert(etag.clone());
+    let cache_control = asset
+        .cache_control
+        .as_deref()
+        .or(mount.cache_control.as_deref())
+        .unwrap_or(DEFAULT_CACHE_CONTROL);
+    headers.insert(CACHE_CONTROL, HeaderValue::from_str(cache_control)?);
+    if mount.compress {
+        headers.insert(VARY, HeaderValue::from_static("Accept-Encoding"));
+    }
+    if let Some(if_none_match) = head.headers.typed_get::<IfNoneMatch>()
+        && !if_none_match.precondition_passes(&etag)
+    {
+        return Ok(Some((StatusCode::NOT_MODIFIED, headers).into_response()));
+    }
+
+    let origin = ConvexOrigin::from(&head.url[url::Position::BeforeHost..url::Position::AfterPort]);
+    let FileStream {
+        content_length,
+        content_type,
+        stream,
+        ..
+    } = api
+        .get_file(
+            host,
+            request_id,
+            origin,
+            ComponentId::Root,
+            FileStorageId::DocumentId(asset.storage_id),
+        )
+        .await?;
+    if let Some(content_type) = content_type {
+        headers.typed_insert(content_type);
+    }
+    let body: BoxStream<'static, std::io::Result<_>> = match encoding {
+        Some(encoding) => {
+            headers.insert(
+                CONTENT_ENCODING,
+                HeaderValue::from_static(encoding.as_str()),
+            );
+            let reader = StreamReader::new(stream);
+            match encoding {
+                ContentEncoding::Brotli => ReaderStream::new(BrotliEncoder::with_quality(
+                    reader,
+                    Level::Precise(BROTLI_QUALITY),
+                ))
+                .boxed(),
+                ContentEncoding::Gzip => ReaderStream::new(GzipEncoder::new(reader)).boxed(),
+            }
+        },
+        None => {
+            headers.typed_insert(content_length);
+            stream
+        },
+    };
+    Ok(Some(
+        (StatusCode::OK, headers, Body::from_stream(body)).into_response(),
+    ))
+}
+
+fn is_compressible(content_type: &str) -> bool {
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    mime.starts_with("text/")
+        || mime.ends_with("+json")
+        || mime.ends_with("+xml")
+        || matches!(
+            mime.as_str(),
+            "application/javascript" | "application/json" | "application/wasm" | "application/xml"
+        )
+}
+
+/// The encoding to compress with, preferring brotli, based on the request's
+/// `Accept-Encoding` header.
+fn preferred_encoding(headers: &HeaderMap) -> Option<ContentEncoding> {
+    let accepted: Vec<String> = headers
+        .get_all(http::header::ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|item| {
+            let mut parts = item.split(';');
+            let name = parts.next()?.trim().to_ascii_lowercase();
+            let refused = parts.any(|param| {
+                param
+                    .trim()
+                    .strip_prefix("q=")
+                    .and_then(|q| q.parse::<f32>().ok())
+                    == Some(0.0)
+            });
+            (!refused).then_some(name)
+        })
+        .collect();
+    if accepted.iter().any(|name| name == "br") {
+        Some(ContentEncoding::Brotli)
+    } else if accepted.iter().any(|name| name == "gzip") {
+        Some(ContentEncoding::Gzip)
+    } else {
+        None
+    }
+}
+
+/// The content type browsers need for common web files, for uploads that
+/// don't set one.
+fn content_type_for_path(path: &str) -> Option<&'static str> {
+    let (_, extension) = path.rsplit_once('.')?;
+    let content_type = match extension.to_ascii_lowercase().as_str() {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" | "mjs" => "text/javascript; charset=utf-8",
+        "json" | "map" => "application/json",
+        "webmanifest" => "application/manifest+json",
+        "txt" => "text/plain; charset=utf-8",
+        "xml" => "application/xml",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "wasm" => "application/wasm",
+        "pdf" => "application/pdf",
+        _ => return None,
+    };
+    Some(content_type)
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StaticAssetMountJson {
+    path_prefix: String,
+    folder: String,
+    cache_control: Option<String>,
+    #[serde(default)]
+    compress: bool,
+    spa_fallback: Option<String>,
+}
+
+impl From<StaticAssetMountJson> for StaticAssetMount {
+    fn from(mount: StaticAssetMountJson) -> Self {
+        Self {
+            path_prefix: mount.path_prefix,
+            folder: mount.folder,
+            cache_control: mount.cache_control,
+            compress: mount.compress,
+            spa_fallback: mount.spa_fallback,
+        }
+    }
+}
+
+impl From<ParsedDocument<StaticAssetMount>> for StaticAssetMountJson {
+    fn from(mount: ParsedDocument<StaticAssetMount>) -> Self {
+        let mount = mount.into_value();
+        Self {
+            path_prefix: mount.path_prefix,
+            folder: mount.folder,
+            cache_control: mount.cache_control,
+            compress: mount.compress,
+            spa_fallback: mount.spa_fallback,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StaticAssetJson {
+    path: String,
+    storage_id: String,
+    sha256: String,
+    size: i64,
+    content_type: Option<String>,
+    cache_control: Option<String>,
+}
+
+impl From<ParsedDocument<StaticAsset>> for StaticAssetJson {
+    fn from(asset: ParsedDocument<StaticAsset>) -> Self {
+        let asset = asset.into_value();
+        Self {
+            path: asset.path,
+            storage_id: asset.storage_id.to_string(),
+            sha256: asset.sha256.as_hex(),
+            size: asset.size,
+            content_type: asset.content_type,
+            cache_control: asset.cache_control,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListStaticAssetMountsResponse {
+    mounts: Vec<StaticAssetMountJson>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteStaticAssetMountArgs {
+    path_prefix: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListStaticAssetsArgs {
+    folder: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListStaticAssetsResponse {
+    assets: Vec<StaticAssetJson>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadStaticAssetArgs {
+    folder: String,
+    path: String,
+    cache_control: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteStaticAssetArgs {
+    folder: String,
+    path: String,
+}
+
+#[debug_handler]
+pub async fn list_static_asset_mounts(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let mounts = st.application.list_static_asset_mounts(&identity).await?;
+    Ok(Json(ListStaticAssetMountsResponse {
+        mounts: mounts.into_iter().map(StaticAssetMountJson::from).collect(),
+    }))
+}
+
+#[debug_handler]
+pub async fn set_static_asset_mount(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(mount): Json<StaticAssetMountJson>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    st.application
+        .set_static_asset_mount(&identity, mount.into())
+        .await?;
+    Ok(())
+}
+
+#[debug_handler]
+pub async fn delete_static_asset_mount(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(DeleteStaticAssetMountArgs { path_prefix }): Json<DeleteStaticAssetMountArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    st.application
+        .delete_static_asset_mount(&identity, path_prefix)
+        .await?;
+    Ok(())
+}
+
+#[debug_handler]
+pub async fn list_static_assets(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(ListStaticAssetsArgs { folder }): Query<ListStaticAssetsArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let assets = st
+        .application
+        .list_static_assets(&identity, &folder)
+        .await?;
+    Ok(Json(ListStaticAssetsResponse {
+        assets: assets.into_iter().map(StaticAssetJson::from).collect(),
+    }))
+}
+
+/// Uploads the request body as the file at `path` in `folder`. Files without
+/// a `Content-Type` header get one from their extension.
+#[debug_handler]
+pub async fn upload_static_asset(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(UploadStaticAssetArgs {
+        folder,
+        path,
+        cache_control,
+    }): Query<UploadStaticAssetArgs>,
+    content_type: Result<TypedHeader<ContentType>, TypedHeaderRejection>,
+    body: Body,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let content_type = match map_header_err(content_type)? {
+        Some(content_type) => Some(content_type),
+        None => content_type_for_path(&path)
+            .map(str::parse)
+            .transpose()
+            .context("Invalid content type")?,
+    };
+    let body = body
+        .into_data_stream()
+        .map(|r| r.context("Error parsing body"))
+        .boxed();
+    st.application
+        .upload_static_asset(&identity, folder, path, content_type, cache_control, body)
+        .await?;
+    Ok(())
+}
+
+#[debug_handler]
+pub async fn delete_static_asset(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(DeleteStaticAssetArgs { folder, path }): Json<DeleteStaticAssetArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    st.application
+        .delete_static_asset(&identity, &folder, &path)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use http::{
+        header::ACCEPT_ENCODING,
+        HeaderMap,
+        HeaderValue,
+    };
+
+    use super::{
+        content_type_for_path,
+        is_compressible,
+        preferred_encoding,
+    };
+
+    fn encoding(accept_encoding: &str) -> Option<&'static str> {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            ACCEPT_ENCODING,
+            HeaderValue::from_str(accept_encoding).unwrap(),
+        );
+        preferred_encoding(&headers).map(|encoding| encoding.as_str())
+    }
+
+    #[test]
+    fn test_preferred_encoding() {
+        assert_eq!(encoding("gzip, deflate, br"), Some("br"));
+        assert_eq!(encoding("gzip"), Some("gzip"));
+        assert_eq!(encoding("br;q=0, gzip;q=0.5"), Some("gzip"));
+        assert_eq!(encoding("identity"), None);
+        assert_eq!(
+            preferred_encoding(&HeaderMap::new()).map(|e| e.as_str()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_content_types() {
+        assert_eq!(
+            content_type_for_path("assets/main.JS"),
+            Some("text/javascript; charset=utf-8")
+        );
+        assert_eq!(content_type_for_path("LICENSE"), None);
+        assert!(is_compressible("text/html; charset=utf-8"));
+        assert!(is_compressible("image/svg+xml"));
+        assert!(!is_compressible("image/png"));
+    }
+}
@@ -63,7 +63,7 @@ const MAX_CACHE_AGE: Duration = Duration::from_secs(60 * 60 * 24 * 30);
 
 const STORE_FILE_AUTHORIZATION_VALIDITY: Duration = Duration::from_secs(60 * 60);
 
-fn map_header_err<T: Header>(
+pub(crate) fn map_header_err<T: Header>(
     r: Result<TypedHeader<T>, TypedHeaderRejection>,
 ) -> anyhow::Result<Option<T>> {
     r.map(|t| Some(t.0)).or_else(|e| match e.reason() {
@@ -12,6 +12,7 @@ use axum::{
     body::Bytes,
     extract::{
         ws::{
+            close_code,
             CloseFrame,
             Message,
             WebSocket,
@@ -139,6 +140,7 @@ async fn run_sync_socket(
     on_connect: Box<dyn FnOnce(SessionId) + Send>,
 ) {
     let _drop_token = SyncSocketDropToken::new();
+    let _open_session = st.drain.open_session();
 
     let (mut tx, mut rx) = socket.split();
 
@@ -148,7 +150,15 @@ async fn run_sync_socket(
     let (client_tx, client_rx) = mpsc::unbounded_channel();
     let receive_messages = async {
         let _receive_message_drop_token = DebugSyncSocketDropToken::new("receive_message");
-        while let Some(message_r) = rx.next().await {
+        loop {
+            let message_r = select_biased! {
+                // Stop handling new messages once the backend starts draining.
+                _ = st.drain.wait().fuse() => break,
+                message_r = rx.next().fuse() => match message_r {
+                    Some(message_r) => message_r,
+                    None => break,
+                },
+            };
             let message = match message_r {
                 Ok(message) => message,
                 Err(e) if is_connection_closed_error(&e) => {
@@ -241,7 +251,12 @@ async fn run_sync_socket(
             server_tx,
             on_connect,
         );
-        let r = sync_worker.go().await;
+        let r = match sync_worker.go().await {
+            // The worker exits cleanly when we stop handling messages to drain,
+            // but the client is still waiting on the operations it sent.
+            Ok(()) if st.drain.is_draining() => sync_worker.finish_pending_operations().await,
+            r => r,
+        };
         identity_version = Some(sync_worker.identity_version());
         // Explicit drop for emphasis: dropping triggers send_messages to complete.
         drop(sync_worker);
@@ -255,6 +270,11 @@ async fn run_sync_socket(
     let mut socket = tx.reunite(rx).expect("Mixed up WebSocket halves?");
 
     let close_msg = match result {
+        // Tell the client to reconnect, which will reach another backend.
+        Ok(..) if st.drain.is_draining() => Some(Message::Close(Some(CloseFrame {
+            code: close_code::RESTART,
+            reason: "ServerRestarting".into(),
+        }))),
         Ok(..) => None,
         Err(mut err) => {
             // Send a message on the WebSocket before closing it if the sync
@@ -0,0 +1,180 @@
+use application::subscription_stats::{
+    InvalidationCount,
+    QuerySubscriptionStats,
+};
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    http::{
+        extract::{
+            Json,
+            Query,
+        },
+        HttpResponseError,
+    },
+    runtime::Runtime,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    admin::must_be_admin,
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+const DEFAULT_SUBSCRIPTION_STATS_LIMIT: usize = 100;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionStatsArgs {
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvalidationCountJson {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    component_path: Option<String>,
+    /// `null` if the table has since been deleted.
+    table_name: Option<String>,
+    /// `null` for writes that matched a text search.
+    index: Option<String>,
+    count: u64,
+}
+
+impl From<InvalidationCount> for InvalidationCountJson {
+    fn from(count: InvalidationCount) -> Self {
+        Self {
+            component_path: count
+                .component_path
+                .filter(|path| !path.is_root())
+                .map(String::from),
+            table_name: count.table_name,
+            index: count.index,
+            count: count.count,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuerySubscriptionStatsJson {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    component_path: Option<String>,
+    udf_path: String,
+    /// When the query's oldest live subscription started, in milliseconds
+    /// since the epoch. Counts are kept since then.
+    since: u64,
+    active_subscriptions: u64,
+    executions: u64,
+    reexecutions: u64,
+    reexecutions_per_minute: f64,
+    bytes_pushed: u64,
+    /// This query's share of all bytes pushed, from 0 to 1.
+    bytes_pushed_fraction: f64,
+    invalidations: Vec<InvalidationCountJson>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionStatsResponse {
+    active_subscriptions: u64,
+    bytes_pushed: u64,
+    /// Queries that pushed the most bytes first.
+    queries: Vec<QuerySubscriptionStatsJson>,
+}
+
+/// Statistics on live query subscriptions, to find
+/// the queries responsible for most re-executions and bandwidth.
+#[debug_handler]
+pub async fn subscription_stats(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(SubscriptionStatsArgs { limit }): Query<SubscriptionStatsArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let report = st.application.subscription_stats(identity).await?;
+    let now = st
+        .application
+        .runtime()
+        .unix_timestamp()
+        .as_ms_since_epoch()?;
+    let active_subscriptions = report
+        .queries
+        .iter()
+        .map(|query| query.active_subscriptions)
+        .sum();
+    let bytes_pushed: u64 = report.queries.iter().map(|query| query.bytes_pushed).sum();
+
+    let mut queries = report.queries;
+    queries.sort_by(|a, b| b.bytes_pushed.cmp(&a.bytes_pushed));
+    queries.truncate(limit.unwrap_or(DEFAULT_SUBSCRIPTION_STATS_LIMIT));
+    let queries = queries
+        .into_iter()
+        .map(
+            |QuerySubscriptionStats {
+                 path,
+                 since,
+                 active_subscriptions,
+                 executions,
+                 reexecutions,
+                 bytes_pushed: query_bytes_pushed,
+                 invalidations,
+             }| {
+                let since = since.as_ms_since_epoch()?;
+                let minutes = (now.saturating_sub(since) as f64 / 60_000.0).max(1.0);
+                anyhow::Ok(QuerySubscriptionStatsJson {
+                    component_path: path.component_path,
+                    udf_path: path.udf_path,
+                    since,
+                    active_subscriptions,
+                    executions,
+                    reexecutions,
+                    reexecutions_per_minute: reexecutions as f64 / minutes,
+                    bytes_pushed: query_bytes_pushed,
+                    bytes_pushed_fraction: if bytes_pushed > 0 {
+                        query_bytes_pushed as f64 / bytes_pushed as f64
+                    } else {
+                        0.0
+                    },
+                    invalidations: invalidations.into_iter().map(Into::into).collect(),
+                })
+            },
+        )
+        .collect::<anyhow::Result<_>>()?;
+    Ok(Json(SubscriptionStatsResponse {
+        active_subscriptions,
+        bytes_pushed,
+        queries,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum_extra::headers::authorization::Credentials;
+    use http::Request;
+    use runtime::prod::ProdRuntime;
+    use serde_json::Value as JsonValue;
+
+    use crate::test_helpers::setup_backend_for_test;
+
+    #[convex_macro::prod_rt_test]
+    async fn test_subscription_stats(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let req = Request::builder()
+            .uri("/api/subscription_stats")
+            .method("GET")
+            .header("Authorization", backend.admin_auth_header.0.encode())
+            .body(axum::body::Body::empty())?;
+        let result: JsonValue = backend.expect_success(req).await?;
+        assert_eq!(result["activeSubscriptions"], 0);
+        assert!(result["queries"].is_array());
+        Ok(())
+    }
+}
@@ -0,0 +1,287 @@
+use std::collections::BTreeMap;
+
+use application::usage_metering::USAGE_WINDOW;
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    components::ComponentPath,
+    http::{
+        extract::{
+            Json,
+            Query,
+        },
+        HttpResponseError,
+    },
+    runtime::Runtime,
+};
+use errors::ErrorMetadata;
+use model::usage_metering::types::{
+    FunctionUsageRecord,
+    TableUsageRecord,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    admin::must_be_admin,
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+const DEFAULT_REPORT_RANGE_MS: i64 = 24 * 60 * 60 * 1000;
+
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum UsageGranularity {
+    /// One row per function or table, summed over the whole range.
+    #[default]
+    Total,
+    /// One row per function or table per hour.
+    Hour,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReportArgs {
+    /// Start of the range in milliseconds since the epoch. Defaults to a day
+    /// before `until`.
+    since: Option<i64>,
+    /// End of the range in milliseconds since the epoch. Defaults to now.
+    until: Option<i64>,
+    #[serde(default)]
+    granularity: UsageGranularity,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionUsageJson {
+    /// Only set for hourly reports.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    window_start_ms: Option<i64>,
+    component_path: String,
+    udf_path: String,
+    calls: i64,
+    failures: i64,
+    duration_ms: i64,
+    gb_seconds: f64,
+    database_read_bytes: i64,
+    database_write_bytes: i64,
+    documents_read: i64,
+    storage_ingress_bytes: i64,
+    storage_egress_bytes: i64,
+    vector_read_bytes: i64,
+    vector_write_bytes: i64,
+}
+
+impl FunctionUsageJson {
+    fn new(record: FunctionUsageRecord, granularity: UsageGranularity) -> Self {
+        Self {
+            window_start_ms: (granularity == UsageGranularity::Hour)
+                .then_some(record.window_start_ms),
+            component_path: record.component_path.into(),
+            udf_path: record.udf_path,
+            calls: record.calls,
+            failures: record.failures,
+            duration_ms: record.duration_ms,
+            gb_seconds: record.memory_mb_ms as f64 / (1024.0 * 1000.0),
+            database_read_bytes: record.database_read_bytes,
+            database_write_bytes: record.database_write_bytes,
+            documents_read: record.documents_read,
+            storage_ingress_bytes: record.storage_ingress_bytes,
+            storage_egress_bytes: record.storage_egress_bytes,
+            vector_read_bytes: record.vector_read_bytes,
+            vector_write_bytes: record.vector_write_bytes,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableUsageJson {
+    /// Only set for hourly reports.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    window_start_ms: Option<i64>,
+    component_path: String,
+    table_name: String,
+    read_bytes: i64,
+    write_bytes: i64,
+    documents_read: i64,
+    vector_read_bytes: i64,
+    vector_write_bytes: i64,
+}
+
+impl TableUsageJson {
+    fn new(record: TableUsageRecord, granularity: UsageGranularity) -> Self {
+        Self {
+            window_start_ms: (granularity == UsageGranularity::Hour)
+                .then_some(record.window_start_ms),
+            component_path: record.component_path.into(),
+            table_name: record.table_name,
+            read_bytes: record.read_bytes,
+            write_bytes: record.write_bytes,
+            documents_read: record.documents_read,
+            vector_read_bytes: record.vector_read_bytes,
+            vector_write_bytes: record.vector_write_bytes,
+        }
+    }
+}
+
+/// Current size of a table. Storage isn't metered over time, so this is
+/// always as of the report.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableStorageJson {
+    component_path: String,
+    table_name: String,
+    document_bytes: u64,
+    index_bytes: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReportResponse {
+    since: i64,
+    until: i64,
+    functions: Vec<FunctionUsageJson>,
+    tables: Vec<TableUsageJson>,
+    storage: Vec<TableStorageJson>,
+}
+
+/// Exports metered usage per function and per table, for attributing the
+/// cost of a shared deployment.
+#[debug_handler]
+pub async fn usage_report(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(UsageReportArgs {
+        since,
+        until,
+        granularity,
+    }): Query<UsageReportArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let until = match until {
+        Some(until) => until,
+        None => st
+            .application
+            .runtime()
+            .unix_timestamp()
+            .as_ms_since_epoch()? as i64,
+    };
+    let since = since.unwrap_or(until - DEFAULT_REPORT_RANGE_MS);
+    if since >= until {
+        return Err(anyhow::anyhow!(ErrorMetadata::bad_request(
+            "InvalidUsageReportRange",
+            format!("`since` ({since}) must be before `until` ({until})"),
+        ))
+        .into());
+    }
+    // Rows are keyed by the start of their window, so include the window
+    // that `since` falls in.
+    let window_ms = USAGE_WINDOW.as_millis() as i64;
+    let report = st
+        .application
+        .usage_report(identity, since - since.rem_euclid(window_ms), until)
+        .await?;
+
+    let (functions, tables) = match granularity {
+        UsageGranularity::Hour => (report.functions, report.tables),
+        UsageGranularity::Total => {
+            let mut functions: BTreeMap<(ComponentPath, String), FunctionUsageRecord> =
+                BTreeMap::new();
+            for record in report.functions {
+                functions
+                    .entry((record.component_path.clone(), record.udf_path.clone()))
+                    .and_modify(|total| total.merge(&record))
+                    .or_insert(record);
+            }
+            let mut tables: BTreeMap<(ComponentPath, String), TableUsageRecord> = BTreeMap::new();
+            for record in report.tables {
+                tables
+                    .entry((record.component_path.clone(), record.table_name.clone()))
+                    .and_modify(|total| total.merge(&record))
+                    .or_insert(record);
+            }
+            (
+                functions.into_values().collect(),
+                tables.into_values().collect(),
+            )
+        },
+    };
+    let storage = report
+        .storage
+        .0
+        .into_iter()
+        .map(|((component_path, table_name), usage)| TableStorageJson {
+            component_path: component_path.into(),
+            table_name: table_name.to_string(),
+            document_bytes: usage.document_size,
+            index_bytes: usage.index_size + usage.system_index_size,
+        })
+        .collect();
+    Ok(Json(UsageReportResponse {
+        since,
+        until,
+        functions: functions
+            .into_iter()
+            .map(|record| FunctionUsageJson::new(record, granularity))
+            .collect(),
+        tables: tables
+            .into_iter()
+            .map(|record| TableUsageJson::new(record, granularity))
+            .collect(),
+        storage,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum_extra::headers::authorization::Credentials;
+    use http::Request;
+    use runtime::prod::ProdRuntime;
+    use serde_json::Value as JsonValue;
+
+    use crate::test_helpers::setup_backend_for_test;
+
+    #[convex_macro::prod_rt_test]
+    async fn test_usage_report(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let req = Request::builder()
+            .uri("/api/usage_report?granularity=hour")
+            .method("GET")
+            .header("Authorization", backend.admin_auth_header.0.encode())
+            .body(axum::body::Body::empty())?;
+        let result: JsonValue = backend.expect_success(req).await?;
+        assert!(result["functions"].is_array());
+        assert!(result["tables"].is_array());
+        assert!(result["storage"].is_array());
+
+        // Starts from the retention window rather than scanning from the epoch.
+        let req = Request::builder()
+            .uri("/api/usage_report?since=0&granularity=total")
+            .method("GET")
+            .header("Authorization", backend.admin_auth_header.0.encode())
+            .body(axum::body::Body::empty())?;
+        let result: JsonValue = backend.expect_success(req).await?;
+        assert!(result["functions"].is_array());
+
+        let req = Request::builder()
+            .uri("/api/usage_report?since=10&until=5")
+            .method("GET")
+            .header("Authorization", backend.admin_auth_header.0.encode())
+            .body(axum::body::Body::empty())?;
+        backend
+            .expect_error(
+                req,
+                http::StatusCode::BAD_REQUEST,
+                "InvalidUsageReportRange",
+            )
+            .await?;
+        Ok(())
+    }
+}
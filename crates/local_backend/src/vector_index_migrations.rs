@@ -0,0 +1,255 @@
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    components::ComponentPath,
+    http::{
+        extract::{
+            Json,
+            Query,
+        },
+        HttpResponseError,
+    },
+};
+use database::SerializedVectorIndexMigration;
+use http::StatusCode;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    search_synonyms::parse_index_name,
+    LocalAppState,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListVectorIndexMigrationsArgs {
+    component_path: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartVectorIndexMigrationArgs {
+    component_path: Option<String>,
+    source_index_name: String,
+    target_index_name: String,
+    reembed_action: String,
+    batch_size: Option<u32>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VectorIndexMigrationArgs {
+    component_path: Option<String>,
+    source_index_name: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VectorIndexMigrationResponse {
+    source_index_name: String,
+    target_index_name: String,
+    #[serde(flatten)]
+    migration: SerializedVectorIndexMigration,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListVectorIndexMigrationsResponse {
+    migrations: Vec<VectorIndexMigrationResponse>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VectorEmbeddingStatusResponse {
+    index_name: String,
+    pending: u64,
+    failed: u64,
+    oldest_pending_ms: Option<f64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListVectorEmbeddingStatusResponse {
+    indexes: Vec<VectorEmbeddingStatusResponse>,
+}
+
+/// Lists the vector index migrations of a component, including finished ones,
+/// with their backfill progress.
+#[debug_handler]
+pub async fn list_vector_index_migrations(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(ListVectorIndexMigrationsArgs { component_path }): Query<ListVectorIndexMigrationsArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let component_path = ComponentPath::deserialize(component_path.as_deref())?;
+    let migrations = st
+        .application
+        .list_vector_index_migrations(identity, &component_path)
+        .await?
+        .into_iter()
+        .map(
+            |(source_index_name, target_index_name, migration)| VectorIndexMigrationResponse {
+                source_index_name: source_index_name.to_string(),
+                target_index_name: target_index_name.to_string(),
+                migration: migration.into(),
+            },
+        )
+        .collect();
+    Ok(Json(ListVectorIndexMigrationsResponse { migrations }))
+}
+
+/// Starts re-embedding the documents of a vector index's table into the
+/// vector field of another index. Searches keep using the source index until
+/// the migration is cut over.
+#[debug_handler]
+pub async fn start_vector_index_migration(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(StartVectorIndexMigrationArgs {
+        component_path,
+        source_index_name,
+        target_index_name,
+        reembed_action,
+        batch_size,
+    }): Json<StartVectorIndexMigrationArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let (component_path, source) = parse_index_name(component_path, &source_index_name)?;
+    let (_, target) = parse_index_name(None, &target_index_name)?;
+    let migration = st
+        .application
+        .start_vector_index_migration(
+            identity,
+            &component_path,
+            &source,
+            &target,
+            reembed_action,
+            batch_size,
+        )
+        .await?;
+    Ok(Json(VectorIndexMigrationResponse {
+        source_index_name,
+        target_index_name,
+        migration: migration.into(),
+    }))
+}
+
+/// Switches searches against a vector index to its migration's target index.
+/// Fails unless the backfill has reached the end of the table.
+#[debug_handler]
+pub async fn cut_over_vector_index_migration(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(VectorIndexMigrationArgs {
+        component_path,
+        source_index_name,
+    }): Json<VectorIndexMigrationArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let (component_path, source) = parse_index_name(component_path, &source_index_name)?;
+    st.application
+        .cut_over_vector_index_migration(identity, &component_path, &source)
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+/// Reports the documents of each vector index with an embedder whose vectors
+/// are out of date, either because their jobs are still queued or because the
+/// embedder failed on them too many times.
+#[debug_handler]
+pub async fn vector_embedding_status(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(ListVectorIndexMigrationsArgs { component_path }): Query<ListVectorIndexMigrationsArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let component_path = ComponentPath::deserialize(component_path.as_deref())?;
+    let indexes = st
+        .application
+        .vector_embedding_status(identity, &component_path)
+        .await?
+        .into_iter()
+        .map(|(index_name, status)| VectorEmbeddingStatusResponse {
+            index_name: index_name.to_string(),
+            pending: status.pending,
+            failed: status.failed,
+            oldest_pending_ms: status.oldest_pending_ms,
+        })
+        .collect();
+    Ok(Json(ListVectorEmbeddingStatusResponse { indexes }))
+}
+
+#[debug_handler]
+pub async fn cancel_vector_index_migration(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(VectorIndexMigrationArgs {
+        component_path,
+        source_index_name,
+    }): Json<VectorIndexMigrationArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let (component_path, source) = parse_index_name(component_path, &source_index_name)?;
+    st.application
+        .cancel_vector_index_migration(identity, &component_path, &source)
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum_extra::headers::authorization::Credentials;
+    use http::{
+        Request,
+        StatusCode,
+    };
+    use runtime::prod::ProdRuntime;
+    use serde_json::json;
+
+    use crate::test_helpers::setup_backend_for_test;
+
+    #[convex_macro::prod_rt_test]
+    async fn test_start_vector_index_migration_requires_vector_indexes(
+        rt: ProdRuntime,
+    ) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let req = Request::builder()
+            .uri("/api/start_vector_index_migration")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .header("Authorization", backend.admin_auth_header.0.encode())
+            .body(axum::body::Body::from(serde_json::to_vec(&json!({
+                "sourceIndexName": "documents.by_embedding",
+                "targetIndexName": "documents.by_new_embedding",
+                "reembedAction": "embeddings:reembed",
+            }))?))?;
+        backend
+            .expect_error(req, StatusCode::BAD_REQUEST, "IndexNotFound")
+            .await?;
+        Ok(())
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_vector_embedding_status_empty(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let req = Request::builder()
+            .uri("/api/vector_embedding_status")
+            .method("GET")
+            .header("Authorization", backend.admin_auth_header.0.encode())
+            .body(axum::body::Body::empty())?;
+        let response: serde_json::Value = backend.expect_success(req).await?;
+        assert_eq!(response, json!({ "indexes": [] }));
+        Ok(())
+    }
+}
@@ -0,0 +1,312 @@
+//! Outbound webhooks, which send signed requests to registered endpoints when
+//! documents change, functions fail or cron jobs finish.
+
+use anyhow::Context;
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    document::ParsedDocument,
+    http::{
+        extract::{
+            Json,
+            Query,
+        },
+        HttpResponseError,
+    },
+};
+use errors::ErrorMetadata;
+use model::webhooks::types::{
+    SerializedWebhookEventFilter,
+    WebhookDelivery,
+    WebhookDeliveryState,
+    WebhookEndpoint,
+    WebhookEventFilter,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::DeveloperDocumentId;
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+/// Deliveries listed when the request doesn't set a limit.
+const DEFAULT_DELIVERIES_LIMIT: usize = 100;
+const MAX_DELIVERIES_LIMIT: usize = 1000;
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum WebhookEventFilterJson {
+    #[serde(rename_all = "camelCase")]
+    DocumentChanges {
+        table_names: Vec<String>,
+    },
+    FunctionFailures,
+    #[serde(rename_all = "camelCase")]
+    CronOutcomes {
+        failures_only: bool,
+    },
+    HttpCheckAlerts,
+}
+
+impl TryFrom<WebhookEventFilterJson> for WebhookEventFilter {
+    type Error = anyhow::Error;
+
+    fn try_from(filter: WebhookEventFilterJson) -> anyhow::Result<Self> {
+        let serialized = match filter {
+            WebhookEventFilterJson::DocumentChanges { table_names } => {
+                SerializedWebhookEventFilter::DocumentChanges { table_names }
+            },
+            WebhookEventFilterJson::FunctionFailures => {
+                SerializedWebhookEventFilter::FunctionFailures
+            },
+            WebhookEventFilterJson::CronOutcomes { failures_only } => {
+                SerializedWebhookEventFilter::CronOutcomes { failures_only }
+            },
+            WebhookEventFilterJson::HttpCheckAlerts => {
+                SerializedWebhookEventFilter::HttpCheckAlerts
+            },
+        };
+        serialized.try_into().context(ErrorMetadata::bad_request(
+            "InvalidWebhookFilter",
+            "Webhook filters must name valid tables",
+        ))
+    }
+}
+
+impl From<WebhookEventFilter> for WebhookEventFilterJson {
+    fn from(filter: WebhookEventFilter) -> Self {
+        match filter {
+            WebhookEventFilter::DocumentChanges { table_names } => Self::DocumentChanges {
+                table_names: table_names.iter().map(|name| name.to_string()).collect(),
+            },
+            WebhookEventFilter::FunctionFailures => Self::FunctionFailures,
+            WebhookEventFilter::CronOutcomes { failures_only } => {
+                Self::CronOutcomes { failures_only }
+            },
+            WebhookEventFilter::HttpCheckAlerts => Self::HttpCheckAlerts,
+        }
+    }
+}
+
+/// An endpoint as listed to admins. The signing secret is only returned when
+/// it's created.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookEndpointJson {
+    id: String,
+    name: String,
+    url: String,
+    filters: Vec<WebhookEventFilterJson>,
+}
+
+impl From<ParsedDocument<WebhookEndpoint>> for WebhookEndpointJson {
+    fn from(endpoint: ParsedDocument<WebhookEndpoint>) -> Self {
+        let (id, endpoint) = endpoint.into_id_and_value();
+        Self {
+            id: id.to_string(),
+            name: endpoint.name,
+            url: endpoint.url,
+            filters: endpoint.filters.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDeliveryJson {
+    id: String,
+    event_id: String,
+    event_type: String,
+    state: &'static str,
+    status: Option<u16>,
+    attempts: u32,
+    next_attempt_ms: i64,
+    last_error: Option<String>,
+    payload: String,
+}
+
+impl From<ParsedDocument<WebhookDelivery>> for WebhookDeliveryJson {
+    fn from(delivery: ParsedDocument<WebhookDelivery>) -> Self {
+        let (id, delivery) = delivery.into_id_and_value();
+        let status = match delivery.state {
+            WebhookDeliveryState::Delivered { status } => Some(status),
+            WebhookDeliveryState::Pending | WebhookDeliveryState::Failed => None,
+        };
+        Self {
+            id: id.to_string(),
+            event_id: delivery.event_id,
+            event_type: delivery.event_type,
+            state: delivery.state.as_str(),
+            status,
+            attempts: delivery.attempts,
+            next_attempt_ms: delivery.next_attempt_ms,
+            last_error: delivery.last_error,
+            payload: delivery.payload,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListWebhookEndpointsResponse {
+    endpoints: Vec<WebhookEndpointJson>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterWebhookEndpointArgs {
+    name: String,
+    url: String,
+    filters: Vec<WebhookEventFilterJson>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterWebhookEndpointResponse {
+    id: String,
+    secret: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookEndpointNameArgs {
+    name: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RotateWebhookSecretResponse {
+    secret: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListWebhookDeliveriesArgs {
+    name: String,
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListWebhookDeliveriesResponse {
+    deliveries: Vec<WebhookDeliveryJson>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryWebhookDeliveryArgs {
+    id: String,
+}
+
+#[debug_handler]
+pub async fn list_webhook_endpoints(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let endpoints = st.application.list_webhook_endpoints(&identity).await?;
+    Ok(Json(ListWebhookEndpointsResponse {
+        endpoints: endpoints
+            .into_iter()
+            .map(WebhookEndpointJson::from)
+            .collect(),
+    }))
+}
+
+#[debug_handler]
+pub async fn register_webhook_endpoint(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(RegisterWebhookEndpointArgs { name, url, filters }): Json<RegisterWebhookEndpointArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let filters = filters
+        .into_iter()
+        .map(WebhookEventFilter::try_from)
+        .collect::<anyhow::Result<_>>()?;
+    let (id, secret) = st
+        .application
+        .register_webhook_endpoint(&identity, name, url, filters)
+        .await?;
+    Ok(Json(RegisterWebhookEndpointResponse {
+        id: id.to_string(),
+        secret: secret.to_string(),
+    }))
+}
+
+#[debug_handler]
+pub async fn delete_webhook_endpoint(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(WebhookEndpointNameArgs { name }): Json<WebhookEndpointNameArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    st.application
+        .delete_webhook_endpoint(&identity, name)
+        .await?;
+    Ok(())
+}
+
+#[debug_handler]
+pub async fn rotate_webhook_secret(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(WebhookEndpointNameArgs { name }): Json<WebhookEndpointNameArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let secret = st
+        .application
+        .rotate_webhook_secret(&identity, name)
+        .await?;
+    Ok(Json(RotateWebhookSecretResponse {
+        secret: secret.to_string(),
+    }))
+}
+
+#[debug_handler]
+pub async fn list_webhook_deliveries(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(ListWebhookDeliveriesArgs { name, limit }): Query<ListWebhookDeliveriesArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let limit = limit
+        .unwrap_or(DEFAULT_DELIVERIES_LIMIT)
+        .min(MAX_DELIVERIES_LIMIT);
+    let deliveries = st
+        .application
+        .list_webhook_deliveries(&identity, &name, limit)
+        .await?;
+    Ok(Json(ListWebhookDeliveriesResponse {
+        deliveries: deliveries
+            .into_iter()
+            .map(WebhookDeliveryJson::from)
+            .collect(),
+    }))
+}
+
+#[debug_handler]
+pub async fn retry_webhook_delivery(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(RetryWebhookDeliveryArgs { id }): Json<RetryWebhookDeliveryArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let id = DeveloperDocumentId::decode(&id).context(ErrorMetadata::bad_request(
+        "InvalidWebhookDelivery",
+        format!("invalid webhook delivery id {id}"),
+    ))?;
+    st.application.retry_webhook_delivery(&identity, id).await?;
+    Ok(())
+}
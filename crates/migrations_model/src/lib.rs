@@ -29,7 +29,7 @@ pub type DatabaseVersion = i64;
 // migrations unless explicitly dropping support.
 // Add a user name next to the version when you make a change to highlight merge
 // conflicts.
-pub const DATABASE_VERSION: DatabaseVersion = 119; // nipunn
+pub const DATABASE_VERSION: DatabaseVersion = 148; // agent
 
 pub struct MigrationExecutor<RT: Runtime> {
     pub db: Database<RT>,
@@ -62,6 +62,74 @@ impl<RT: Runtime> MigrationExecutor<RT> {
                     .await?;
                 MigrationCompletionCriterion::MigrationComplete(to_version)
             },
+            // Empty migration for 120 - represents creation of the by_action index on
+            // _deployment_audit_log
+            120 => MigrationCompletionCriterion::MigrationComplete(to_version),
+            // Empty migration for 121 - represents creation of _data_masking_rules table
+            121 => MigrationCompletionCriterion::MigrationComplete(to_version),
+            // Empty migration for 122 - represents creation of _function_runs table
+            122 => MigrationCompletionCriterion::MigrationComplete(to_version),
+            // Empty migration for 123 - represents creation of _function_usage and
+            // _table_usage tables
+            123 => MigrationCompletionCriterion::MigrationComplete(to_version),
+            // Empty migration for 124 - represents creation of _error_groups table
+            124 => MigrationCompletionCriterion::MigrationComplete(to_version),
+            // Empty migration for 125 - represents creation of _schema_history table
+            125 => MigrationCompletionCriterion::MigrationComplete(to_version),
+            // Empty migration for 126 - represents creation of _search_synonyms table
+            126 => MigrationCompletionCriterion::MigrationComplete(to_version),
+            // Empty migration for 127 - represents creation of _vector_index_migrations table
+            127 => MigrationCompletionCriterion::MigrationComplete(to_version),
+            // Empty migration for 128 - represents creation of _vector_index_stats table
+            128 => MigrationCompletionCriterion::MigrationComplete(to_version),
+            // Empty migration for 129 - represents creation of _vector_embedding_jobs table
+            129 => MigrationCompletionCriterion::MigrationComplete(to_version),
+            // Empty migration for 130 - represents creation of _index_statistics table
+            130 => MigrationCompletionCriterion::MigrationComplete(to_version),
+            // Empty migration for 131 - represents creation of _table_freezes table
+            131 => MigrationCompletionCriterion::MigrationComplete(to_version),
+            // Empty migration for 132 - represents creation of _component_quotas table
+            132 => MigrationCompletionCriterion::MigrationComplete(to_version),
+            // Empty migration for 133 - represents creation of _canary_deployments and
+            // _canary_modules tables
+            133 => MigrationCompletionCriterion::MigrationComplete(to_version),
+            // Empty migration for 134 - represents creation of _code_versions and
+            // _code_version_modules tables
+            134 => MigrationCompletionCriterion::MigrationComplete(to_version),
+            // Empty migration for 135 - represents creation of _maintenance_mode table
+            135 => MigrationCompletionCriterion::MigrationComplete(to_version),
+            // Empty migration for 136 - represents creation of _feature_flags table
+            136 => MigrationCompletionCriterion::MigrationComplete(to_version),
+            // Empty migration for 137 - represents creation of _log_search_terms table
+            137 => MigrationCompletionCriterion::MigrationComplete(to_version),
+            // Empty migration for 138 - represents creation of the by_idempotency_key
+            // index on _scheduled_jobs
+            138 => MigrationCompletionCriterion::MigrationComplete(to_version),
+            // Empty migration for 139 - represents creation of _queue_messages table
+            139 => MigrationCompletionCriterion::MigrationComplete(to_version),
+            // Empty migration for 140 - represents creation of _read_set_advice table
+            140 => MigrationCompletionCriterion::MigrationComplete(to_version),
+            // Empty migration for 141 - represents creation of _table_snapshots table
+            141 => MigrationCompletionCriterion::MigrationComplete(to_version),
+            // Empty migration for 142 - represents creation of _webhook_endpoints and
+            // _webhook_deliveries tables
+            142 => MigrationCompletionCriterion::MigrationComplete(to_version),
+            // Empty migration for 143 - represents creation of _webhook_nonces table
+            143 => MigrationCompletionCriterion::MigrationComplete(to_version),
+            // Empty migration for 144 - represents creation of _static_asset_mounts and
+            // _static_assets tables
+            144 => MigrationCompletionCriterion::MigrationComplete(to_version),
+            // Empty migration for 145 - represents creation of _http_checks and
+            // _http_check_results tables
+            145 => MigrationCompletionCriterion::MigrationComplete(to_version),
+            // Empty migration for 146 - represents creation of _erasure_policies and
+            // _erasure_jobs tables
+            146 => MigrationCompletionCriterion::MigrationComplete(to_version),
+            // Empty migration for 147 - represents creation of _erasure_job_items table
+            147 => MigrationCompletionCriterion::MigrationComplete(to_version),
+            // Empty migration for 148 - represents creation of the
+            // by_args_pending_storage index on _scheduled_jobs
+            148 => MigrationCompletionCriterion::MigrationComplete(to_version),
             // NOTE: Make sure to increase DATABASE_VERSION when adding new migrations.
             _ => anyhow::bail!("Version did not define a migration! {}", to_version),
         };
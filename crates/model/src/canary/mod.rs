@@ -0,0 +1,358 @@
+//! Canary deploys: a second version of the root component's functions that
+//! serves a share of its traffic until it's promoted or rolled back.
+//!
+//! The canary's module metadata lives in `_canary_modules`, next to `_modules`,
+//! and its source package is stored like any other. Module lookups for the
+//! root component check the active canary and read from `_canary_modules` for
+//! calls routed to it, so every function runner picks the same version for a
+//! call without any extra state.
+
+use std::{
+    collections::BTreeMap,
+    sync::LazyLock,
+};
+
+use anyhow::Context;
+use common::{
+    components::ComponentId,
+    document::{
+        ParseDocument,
+        ParsedDocument,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+};
+use database::{
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use sync_types::CanonicalizedModulePath;
+use value::{
+    ConvexValue,
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::{
+    CanaryDeployment,
+    CanaryRollbackPolicy,
+    CanaryRouting,
+    CanaryState,
+};
+use crate::{
+    components::handles::FunctionHandlesModel,
+    config::types::ModuleConfig,
+    cron_jobs::CronModel,
+    modules::{
+        hash_module_source,
+        module_versions::AnalyzedModule,
+        types::ModuleMetadata,
+        ModuleModel,
+    },
+    source_packages::{
+        types::{
+            SourcePackage,
+            SourcePackageId,
+        },
+        SourcePackageModel,
+    },
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static CANARY_DEPLOYMENTS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_canary_deployments"
+        .parse()
+        .expect("Invalid built-in canary_deployments table")
+});
+
+pub static CANARY_MODULES_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_canary_modules"
+        .parse()
+        .expect("Invalid built-in canary_modules table")
+});
+
+static PATH_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "path".parse().expect("Invalid built-in field"));
+
+pub static CANARY_MODULES_INDEX_BY_PATH: LazyLock<SystemIndex<CanaryModulesTable>> =
+    LazyLock::new(|| SystemIndex::new("by_path", [&PATH_FIELD]).unwrap());
+
+pub struct CanaryDeploymentsTable;
+impl SystemTable for CanaryDeploymentsTable {
+    type Metadata = CanaryDeployment;
+
+    fn table_name() -> &'static TableName {
+        &CANARY_DEPLOYMENTS_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![]
+    }
+}
+
+pub struct CanaryModulesTable;
+impl SystemTable for CanaryModulesTable {
+    type Metadata = ModuleMetadata;
+
+    fn table_name() -> &'static TableName {
+        &CANARY_MODULES_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![CANARY_MODULES_INDEX_BY_PATH.clone()]
+    }
+}
+
+fn no_active_canary() -> ErrorMetadata {
+    ErrorMetadata::bad_request("NoActiveCanary", "There is no active canary deploy.")
+}
+
+pub struct CanaryModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> CanaryModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    fn tables_exist(&mut self) -> bool {
+        let mut model = SystemMetadataModel::new_global(self.tx);
+        model.table_exists(&CANARY_DEPLOYMENTS_TABLE) && model.table_exists(&CANARY_MODULES_TABLE)
+    }
+
+    /// The most recent canary, including one that was rolled back.
+    pub async fn get(&mut self) -> anyhow::Result<Option<ParsedDocument<CanaryDeployment>>> {
+        if !self.tables_exist() {
+            return Ok(None);
+        }
+        let query = Query::full_table_scan(CANARY_DEPLOYMENTS_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParseDocument::<CanaryDeployment>::parse)
+            .transpose()
+    }
+
+    /// The canary that's currently serving traffic, if any.
+    pub async fn active(&mut self) -> anyhow::Result<Option<CanaryDeployment>> {
+        Ok(self
+            .get()
+            .await?
+            .map(|canary| canary.into_value())
+            .filter(|canary| canary.is_active()))
+    }
+
+    async fn require_active(&mut self) -> anyhow::Result<ParsedDocument<CanaryDeployment>> {
+        self.get()
+            .await?
+            .filter(|canary| canary.is_active())
+            .ok_or_else(|| no_active_canary().into())
+    }
+
+    /// The canary's metadata for the root component module at `path`.
+    pub async fn module_metadata(
+        &mut self,
+        path: &CanonicalizedModulePath,
+    ) -> anyhow::Result<Option<ParsedDocument<ModuleMetadata>>> {
+        let index_range = IndexRange {
+            index_name: CANARY_MODULES_INDEX_BY_PATH.name(),
+            range: vec![IndexRangeExpression::Eq(
+                PATH_FIELD.clone(),
+                ConvexValue::try_from(path.as_str())?.into(),
+            )],
+            order: Order::Asc,
+        };
+        let mut query_stream = ResolvedQuery::new(
+            self.tx,
+            TableNamespace::Global,
+            Query::index_range(index_range),
+        )?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParseDocument::<ModuleMetadata>::parse)
+            .transpose()
+    }
+
+    async fn all_module_metadata(&mut self) -> anyhow::Result<Vec<ParsedDocument<ModuleMetadata>>> {
+        let query = Query::full_table_scan(CANARY_MODULES_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut modules = vec![];
+        while let Some(document) = query_stream.next(self.tx, None).await? {
+            modules.push(document.parse()?);
+        }
+        Ok(modules)
+    }
+
+    async fn clear_modules(&mut self) -> anyhow::Result<()> {
+        for module in self.all_module_metadata().await? {
+            SystemMetadataModel::new_global(self.tx)
+                .delete(module.id())
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn put_deployment(
+        &mut self,
+        existing: Option<ParsedDocument<CanaryDeployment>>,
+        canary: CanaryDeployment,
+    ) -> anyhow::Result<()> {
+        match existing {
+            Some(existing) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), canary.try_into()?)
+                    .await?;
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&CANARY_DEPLOYMENTS_TABLE, canary.try_into()?)
+                    .await?;
+            },
+        }
+        Ok(())
+    }
+
+    fn check_identity(&self, operation: &'static str) -> anyhow::Result<()> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error(operation));
+        }
+        Ok(())
+    }
+
+    /// Starts serving `traffic_percent` of the root component's calls from
+    /// `modules`. Replaces a canary that was rolled back.
+    pub async fn start(
+        &mut self,
+        modules: Vec<ModuleConfig>,
+        source_package: SourcePackage,
+        mut analyze_results: BTreeMap<CanonicalizedModulePath, AnalyzedModule>,
+        traffic_percent: u32,
+        routing: CanaryRouting,
+        rollback_policy: CanaryRollbackPolicy,
+    ) -> anyhow::Result<SourcePackageId> {
+        self.check_identity("start_canary")?;
+        anyhow::ensure!(
+            self.tables_exist(),
+            "Canary tables don't exist yet. Wait for the deployment to finish migrating."
+        );
+        validate_traffic_percent(traffic_percent)?;
+        let existing = self.get().await?;
+        if existing.as_ref().is_some_and(|canary| canary.is_active()) {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "CanaryAlreadyActive",
+                "A canary deploy is already active. Promote or roll it back first."
+            ));
+        }
+        if modules.iter().any(|c| c.path.is_system()) {
+            anyhow::bail!("You cannot push functions under the '_system/' directory.");
+        }
+        let source_package_id = SourcePackageModel::new(self.tx, ComponentId::Root.into())
+            .put(source_package)
+            .await?;
+        self.clear_modules().await?;
+        for module in modules {
+            let path = module.path.canonicalize();
+            let analyze_result = if !path.is_deps() {
+                Some(analyze_results.remove(&path).context(format!(
+                    "Missing analyze result for module {}",
+                    path.as_str()
+                ))?)
+            } else {
+                None
+            };
+            let metadata = ModuleMetadata {
+                sha256: hash_module_source(&module.source, module.source_map.as_ref()),
+                path,
+                source_package_id,
+                environment: module.environment,
+                analyze_result,
+            };
+            SystemMetadataModel::new_global(self.tx)
+                .insert(&CANARY_MODULES_TABLE, metadata.try_into()?)
+                .await?;
+        }
+        let canary = CanaryDeployment {
+            source_package_id,
+            traffic_percent,
+            routing,
+            rollback_policy,
+            state: CanaryState::Active,
+            started_at_ms: self.tx.runtime().unix_timestamp().as_ms_since_epoch()? as i64,
+        };
+        self.put_deployment(existing, canary).await?;
+        Ok(source_package_id)
+    }
+
+    pub async fn set_traffic(&mut self, traffic_percent: u32) -> anyhow::Result<()> {
+        self.check_identity("update_canary")?;
+        validate_traffic_percent(traffic_percent)?;
+        let existing = self.require_active().await?;
+        let mut canary = existing.clone().into_value();
+        canary.traffic_percent = traffic_percent;
+        self.put_deployment(Some(existing), canary).await
+    }
+
+    /// Stops routing calls to the canary and drops its modules. The canary
+    /// stays around, with `reason`, until the next one starts.
+    pub async fn roll_back(&mut self, reason: String) -> anyhow::Result<()> {
+        self.check_identity("roll_back_canary")?;
+        let existing = self.require_active().await?;
+        let mut canary = existing.clone().into_value();
+        canary.state = CanaryState::RolledBack { reason };
+        self.put_deployment(Some(existing), canary).await?;
+        self.clear_modules().await
+    }
+
+    /// Makes the canary's modules the root component's modules for all
+    /// traffic, updating its crons and function handles like a push would.
+    pub async fn promote(&mut self) -> anyhow::Result<SourcePackageId> {
+        self.check_identity("promote_canary")?;
+        let existing = self.require_active().await?;
+        let source_package_id = existing.source_package_id;
+        let modules = self
+            .all_module_metadata()
+            .await?
+            .into_iter()
+            .map(|module| module.into_value())
+            .collect();
+        let analyze_results = ModuleModel::new(self.tx)
+            .replace_application_metadata(ComponentId::Root, modules)
+            .await?;
+        self.clear_modules().await?;
+        CronModel::new(self.tx, ComponentId::Root)
+            .apply(&analyze_results)
+            .await?;
+        FunctionHandlesModel::new(self.tx)
+            .apply_config_diff(ComponentId::Root, Some(&analyze_results))
+            .await?;
+        SystemMetadataModel::new_global(self.tx)
+            .delete(existing.id())
+            .await?;
+        Ok(source_package_id)
+    }
+}
+
+fn validate_traffic_percent(traffic_percent: u32) -> anyhow::Result<()> {
+    if traffic_percent > 100 {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "InvalidCanaryTraffic",
+            format!("Canary traffic must be between 0 and 100 percent, not {traffic_percent}.")
+        ));
+    }
+    Ok(())
+}
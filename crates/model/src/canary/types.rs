@@ -0,0 +1,255 @@
+use std::{
+    fmt,
+    str::FromStr,
+};
+
+use common::types::Timestamp;
+use keybroker::Identity;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::{
+    codegen_convex_serialization,
+    sha256::Sha256,
+    DeveloperDocumentId,
+};
+
+use crate::source_packages::types::SourcePackageId;
+
+/// How a canary decides which function calls run its code.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum CanaryRouting {
+    /// Each function call is routed on its own, so an action that calls
+    /// several functions may run some of them on each version.
+    Invocation,
+    /// Every call made by the same user runs on the same version. Calls
+    /// without a user identity all share one cohort.
+    Identity,
+}
+
+impl fmt::Display for CanaryRouting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invocation => write!(f, "invocation"),
+            Self::Identity => write!(f, "identity"),
+        }
+    }
+}
+
+impl FromStr for CanaryRouting {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "invocation" => Ok(Self::Invocation),
+            "identity" => Ok(Self::Identity),
+            _ => anyhow::bail!("Invalid canary routing: {s}"),
+        }
+    }
+}
+
+/// When to roll a canary back without anyone asking.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct CanaryRollbackPolicy {
+    /// How many calls the canary has to serve before its error rate is
+    /// compared against the stable version's.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=i64::MAX as u64")
+    )]
+    pub min_invocations: u64,
+    /// How much higher the canary's error rate, as a fraction of calls, can
+    /// be than the stable version's before it's rolled back.
+    #[cfg_attr(any(test, feature = "testing"), proptest(strategy = "0.0..=1.0f64"))]
+    pub max_error_rate_increase: f64,
+}
+
+impl Default for CanaryRollbackPolicy {
+    fn default() -> Self {
+        Self {
+            min_invocations: 100,
+            max_error_rate_increase: 0.05,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum CanaryState {
+    Active,
+    RolledBack { reason: String },
+}
+
+/// A second version of the root component's functions that serves a share of
+/// its traffic. Its modules live in `_canary_modules`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct CanaryDeployment {
+    pub source_package_id: SourcePackageId,
+    /// The percentage of calls, between 0 and 100, that run the canary.
+    #[cfg_attr(any(test, feature = "testing"), proptest(strategy = "0..=100u32"))]
+    pub traffic_percent: u32,
+    pub routing: CanaryRouting,
+    pub rollback_policy: CanaryRollbackPolicy,
+    pub state: CanaryState,
+    pub started_at_ms: i64,
+}
+
+impl CanaryDeployment {
+    pub fn is_active(&self) -> bool {
+        self.state == CanaryState::Active
+    }
+
+    /// Whether a call made by `identity` in a transaction starting at `ts`
+    /// runs the canary. This only depends on what the function runner also
+    /// knows about the transaction, so it makes the same choice as the
+    /// backend that validated the call.
+    pub fn routes_to_canary(&self, identity: &Identity, ts: Timestamp) -> bool {
+        if !self.is_active() {
+            return false;
+        }
+        let cohort = match identity {
+            Identity::User(user) => user.attributes.token_identifier.0.as_str(),
+            Identity::ActingUser(_, attributes) => attributes.token_identifier.0.as_str(),
+            _ => "",
+        };
+        let key = match self.routing {
+            CanaryRouting::Identity => cohort.to_string(),
+            CanaryRouting::Invocation => format!("{cohort}|{}", u64::from(ts)),
+        };
+        let digest = Sha256::hash(key.as_bytes());
+        let bucket = u64::from_le_bytes(digest[..8].try_into().expect("Digest too short")) % 100;
+        bucket < self.traffic_percent as u64
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedCanaryRollbackPolicy {
+    min_invocations: i64,
+    max_error_rate_increase: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SerializedCanaryState {
+    Active,
+    #[serde(rename_all = "camelCase")]
+    RolledBack {
+        reason: String,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedCanaryDeployment {
+    source_package_id: String,
+    traffic_percent: i64,
+    routing: String,
+    rollback_policy: SerializedCanaryRollbackPolicy,
+    state: SerializedCanaryState,
+    started_at_ms: i64,
+}
+
+impl TryFrom<CanaryDeployment> for SerializedCanaryDeployment {
+    type Error = anyhow::Error;
+
+    fn try_from(canary: CanaryDeployment) -> anyhow::Result<Self> {
+        Ok(Self {
+            source_package_id: DeveloperDocumentId::from(canary.source_package_id).encode(),
+            traffic_percent: canary.traffic_percent.into(),
+            routing: canary.routing.to_string(),
+            rollback_policy: SerializedCanaryRollbackPolicy {
+                min_invocations: canary.rollback_policy.min_invocations.try_into()?,
+                max_error_rate_increase: canary.rollback_policy.max_error_rate_increase,
+            },
+            state: match canary.state {
+                CanaryState::Active => SerializedCanaryState::Active,
+                CanaryState::RolledBack { reason } => SerializedCanaryState::RolledBack { reason },
+            },
+            started_at_ms: canary.started_at_ms,
+        })
+    }
+}
+
+impl TryFrom<SerializedCanaryDeployment> for CanaryDeployment {
+    type Error = anyhow::Error;
+
+    fn try_from(canary: SerializedCanaryDeployment) -> anyhow::Result<Self> {
+        Ok(Self {
+            source_package_id: DeveloperDocumentId::decode(&canary.source_package_id)?.into(),
+            traffic_percent: canary.traffic_percent.try_into()?,
+            routing: canary.routing.parse()?,
+            rollback_policy: CanaryRollbackPolicy {
+                min_invocations: canary.rollback_policy.min_invocations.try_into()?,
+                max_error_rate_increase: canary.rollback_policy.max_error_rate_increase,
+            },
+            state: match canary.state {
+                SerializedCanaryState::Active => CanaryState::Active,
+                SerializedCanaryState::RolledBack { reason } => CanaryState::RolledBack { reason },
+            },
+            started_at_ms: canary.started_at_ms,
+        })
+    }
+}
+
+codegen_convex_serialization!(CanaryDeployment, SerializedCanaryDeployment);
+
+#[cfg(test)]
+mod tests {
+    use common::types::Timestamp;
+    use keybroker::Identity;
+    use value::DeveloperDocumentId;
+
+    use super::{
+        CanaryDeployment,
+        CanaryRollbackPolicy,
+        CanaryRouting,
+        CanaryState,
+    };
+
+    fn canary(traffic_percent: u32, routing: CanaryRouting) -> CanaryDeployment {
+        CanaryDeployment {
+            source_package_id: DeveloperDocumentId::MIN.into(),
+            traffic_percent,
+            routing,
+            rollback_policy: CanaryRollbackPolicy::default(),
+            state: CanaryState::Active,
+            started_at_ms: 0,
+        }
+    }
+
+    #[test]
+    fn test_routes_to_canary() {
+        let identity = Identity::system();
+        let timestamps: Vec<_> = (0..200).map(Timestamp::must).collect();
+        for ts in &timestamps {
+            assert!(!canary(0, CanaryRouting::Invocation).routes_to_canary(&identity, *ts));
+            assert!(canary(100, CanaryRouting::Invocation).routes_to_canary(&identity, *ts));
+        }
+
+        // Invocation routing splits the same caller's calls between versions.
+        let invocation = canary(50, CanaryRouting::Invocation);
+        let routed = timestamps
+            .iter()
+            .filter(|ts| invocation.routes_to_canary(&identity, **ts))
+            .count();
+        assert!(routed > 0 && routed < timestamps.len());
+
+        // Identity routing keeps sending them to the same one.
+        let sticky = canary(50, CanaryRouting::Identity);
+        let first = sticky.routes_to_canary(&identity, timestamps[0]);
+        assert!(timestamps
+            .iter()
+            .all(|ts| sticky.routes_to_canary(&identity, *ts) == first));
+
+        let mut rolled_back = canary(100, CanaryRouting::Identity);
+        rolled_back.state = CanaryState::RolledBack {
+            reason: "test".to_string(),
+        };
+        assert!(!rolled_back.routes_to_canary(&identity, timestamps[0]));
+    }
+}
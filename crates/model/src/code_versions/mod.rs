@@ -0,0 +1,373 @@
+//! The root component's recently pushed code, kept so a deployment can be
+//! switched back to an earlier push without re-pushing it.
+//!
+//! Each push records its modules' metadata, schema, auth config and UDF
+//! server version. Source packages are never deleted, so rolling back only
+//! has to point `_modules` back at the recorded package, along with the
+//! schema and config the push applied.
+
+use std::{
+    collections::BTreeMap,
+    sync::LazyLock,
+};
+
+use common::{
+    bootstrap_model::schema::SchemaState,
+    components::ComponentId,
+    document::{
+        ParseDocument,
+        ParsedDocument,
+    },
+    json::JsonSerializable,
+    knobs::CODE_VERSIONS_TO_KEEP,
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    schemas::DatabaseSchema,
+};
+use database::{
+    unauthorized_error,
+    BootstrapComponentsModel,
+    IndexModel,
+    ResolvedQuery,
+    SchemaModel,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use rand::Rng;
+use value::{
+    ConvexValue,
+    DeveloperDocumentId,
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::{
+    schema_is_compatible,
+    CodeVersion,
+    CodeVersionModule,
+};
+use crate::{
+    auth::AuthInfoModel,
+    canary::CanaryModel,
+    components::handles::FunctionHandlesModel,
+    cron_jobs::CronModel,
+    modules::ModuleModel,
+    udf_config::{
+        types::UdfConfig,
+        UdfConfigModel,
+    },
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static CODE_VERSIONS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_code_versions"
+        .parse()
+        .expect("Invalid built-in code_versions table")
+});
+
+pub static CODE_VERSION_MODULES_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_code_version_modules"
+        .parse()
+        .expect("Invalid built-in code_version_modules table")
+});
+
+static VERSION_ID_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "versionId".parse().expect("Invalid built-in field"));
+
+pub static CODE_VERSION_MODULES_INDEX_BY_VERSION: LazyLock<SystemIndex<CodeVersionModulesTable>> =
+    LazyLock::new(|| SystemIndex::new("by_version", [&VERSION_ID_FIELD]).unwrap());
+
+pub struct CodeVersionsTable;
+impl SystemTable for CodeVersionsTable {
+    type Metadata = CodeVersion;
+
+    fn table_name() -> &'static TableName {
+        &CODE_VERSIONS_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![]
+    }
+}
+
+pub struct CodeVersionModulesTable;
+impl SystemTable for CodeVersionModulesTable {
+    type Metadata = CodeVersionModule;
+
+    fn table_name() -> &'static TableName {
+        &CODE_VERSION_MODULES_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![CODE_VERSION_MODULES_INDEX_BY_VERSION.clone()]
+    }
+}
+
+fn rollback_unsupported(why: &str) -> ErrorMetadata {
+    ErrorMetadata::bad_request(
+        "RollbackUnsupported",
+        format!("Can't roll back to an earlier push because {why}."),
+    )
+}
+
+pub struct CodeVersionsModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> CodeVersionsModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    fn tables_exist(&mut self) -> bool {
+        let mut model = SystemMetadataModel::new_global(self.tx);
+        model.table_exists(&CODE_VERSIONS_TABLE) && model.table_exists(&CODE_VERSION_MODULES_TABLE)
+    }
+
+    fn check_identity(&self, operation: &'static str) -> anyhow::Result<()> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error(operation));
+        }
+        Ok(())
+    }
+
+    async fn has_components(&mut self) -> anyhow::Result<bool> {
+        Ok(BootstrapComponentsModel::new(self.tx)
+            .load_all_definitions()
+            .await?
+            .keys()
+            .any(|path| !path.is_root()))
+    }
+
+    /// Records the root component's current code as a new version, dropping
+    /// the oldest versions past `CODE_VERSIONS_TO_KEEP`. Must be called at the
+    /// end of a push, after its modules, schema and config have been applied.
+    /// Deployments with components aren't recorded, since they can't be
+    /// rolled back.
+    pub async fn record(&mut self) -> anyhow::Result<()> {
+        self.check_identity("record_code_version")?;
+        if !self.tables_exist() || self.has_components().await? {
+            return Ok(());
+        }
+        let modules = ModuleModel::new(self.tx)
+            .get_application_metadata(ComponentId::Root)
+            .await?;
+        let Some(source_package_id) = modules.first().map(|module| module.source_package_id) else {
+            return Ok(());
+        };
+        let Some(udf_config) = UdfConfigModel::new(self.tx, TableNamespace::root_component())
+            .get()
+            .await?
+        else {
+            return Ok(());
+        };
+        let raw_schema = SchemaModel::new(self.tx, TableNamespace::root_component())
+            .get_by_state(SchemaState::Active)
+            .await?
+            .map(|(_, schema)| DatabaseSchema::clone(&schema).json_serialize())
+            .transpose()?;
+        let auth_info = AuthInfoModel::new(self.tx)
+            .get()
+            .await?
+            .into_iter()
+            .map(|auth_info| auth_info.into_value())
+            .collect();
+        let version = CodeVersion {
+            source_package_id,
+            raw_schema,
+            auth_info,
+            udf_server_version: udf_config.into_value().server_version,
+            pushed_at_ms: self.tx.runtime().unix_timestamp().as_ms_since_epoch()? as i64,
+        };
+        let version_id = SystemMetadataModel::new_global(self.tx)
+            .insert(&CODE_VERSIONS_TABLE, version.try_into()?)
+            .await?;
+        for module in modules {
+            let module = CodeVersionModule {
+                version_id: version_id.into(),
+                module: module.into_value(),
+            };
+            SystemMetadataModel::new_global(self.tx)
+                .insert(&CODE_VERSION_MODULES_TABLE, module.try_into()?)
+                .await?;
+        }
+
+        let versions = self.list().await?;
+        for version in versions.into_iter().skip(*CODE_VERSIONS_TO_KEEP) {
+            for module in self.modules(version.developer_id()).await? {
+                SystemMetadataModel::new_global(self.tx)
+                    .delete(module.id())
+                    .await?;
+            }
+            SystemMetadataModel::new_global(self.tx)
+                .delete(version.id())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Recorded versions, newest first.
+    pub async fn list(&mut self) -> anyhow::Result<Vec<ParsedDocument<CodeVersion>>> {
+        self.check_identity("list_code_versions")?;
+        if !self.tables_exist() {
+            return Ok(vec![]);
+        }
+        let query = Query::full_table_scan(CODE_VERSIONS_TABLE.clone(), Order::Desc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut versions = vec![];
+        while let Some(document) = query_stream.next(self.tx, None).await? {
+            versions.push(document.parse()?);
+        }
+        Ok(versions)
+    }
+
+    async fn modules(
+        &mut self,
+        version_id: DeveloperDocumentId,
+    ) -> anyhow::Result<Vec<ParsedDocument<CodeVersionModule>>> {
+        let index_range = IndexRange {
+            index_name: CODE_VERSION_MODULES_INDEX_BY_VERSION.name(),
+            range: vec![IndexRangeExpression::Eq(
+                VERSION_ID_FIELD.clone(),
+                ConvexValue::try_from(version_id.encode())?.into(),
+            )],
+            order: Order::Asc,
+        };
+        let mut query_stream = ResolvedQuery::new(
+            self.tx,
+            TableNamespace::Global,
+            Query::index_range(index_range),
+        )?;
+        let mut modules = vec![];
+        while let Some(document) = query_stream.next(self.tx, None).await? {
+            modules.push(document.parse()?);
+        }
+        Ok(modules)
+    }
+
+    /// Switches the root component back to the code, schema and config of a
+    /// recorded version. Fails without changing anything if the version's
+    /// schema could reject existing documents or needs indexes that aren't
+    /// built yet.
+    pub async fn rollback(&mut self, version_id: DeveloperDocumentId) -> anyhow::Result<()> {
+        self.check_identity("rollback_code_version")?;
+        if self.has_components().await? {
+            anyhow::bail!(rollback_unsupported("the deployment has components"));
+        }
+        if CanaryModel::new(self.tx).active().await?.is_some() {
+            anyhow::bail!(rollback_unsupported(
+                "a canary deploy is active. Promote or roll it back first"
+            ));
+        }
+        let version = if self.tables_exist() {
+            let id = self
+                .tx
+                .resolve_developer_id(&version_id, TableNamespace::Global)?;
+            self.tx.get(id).await?
+        } else {
+            None
+        };
+        let Some(version) = version else {
+            anyhow::bail!(ErrorMetadata::not_found(
+                "CodeVersionNotFound",
+                format!("Code version {} doesn't exist.", version_id.encode()),
+            ));
+        };
+        let version: ParsedDocument<CodeVersion> = version.parse()?;
+        let version = version.into_value();
+
+        let namespace = TableNamespace::root_component();
+        let target_schema = version
+            .raw_schema
+            .as_deref()
+            .map(DatabaseSchema::json_deserialize)
+            .transpose()?;
+        let active_schema = SchemaModel::new(self.tx, namespace)
+            .get_by_state(SchemaState::Active)
+            .await?
+            .map(|(_, schema)| schema);
+        if active_schema.as_deref() != target_schema.as_ref() {
+            if !schema_is_compatible(active_schema.as_deref(), target_schema.as_ref()) {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "RollbackSchemaIncompatible",
+                    "Can't roll back because the earlier push's schema validates documents that \
+                     the current schema doesn't. Push the earlier code instead so its schema is \
+                     checked against existing documents."
+                ));
+            }
+            let empty = BTreeMap::new();
+            let target_tables = target_schema
+                .as_ref()
+                .map(|schema| &schema.tables)
+                .unwrap_or(&empty);
+            let index_diff = IndexModel::new(self.tx)
+                .get_index_diff(namespace, target_tables)
+                .await?;
+            if !index_diff.added.is_empty()
+                || index_diff
+                    .identical
+                    .iter()
+                    .any(|index| index.config.is_backfilling())
+            {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "RollbackRequiresIndexBackfill",
+                    "Can't roll back because the earlier push's schema has indexes that would \
+                     need to be backfilled. Push the earlier code instead."
+                ));
+            }
+            let schema_id = match target_schema {
+                Some(schema) => {
+                    let mut schema_model = SchemaModel::new(self.tx, namespace);
+                    let (schema_id, state) = schema_model.submit_pending(schema).await?;
+                    if state == SchemaState::Pending {
+                        schema_model.mark_validated(schema_id).await?;
+                    }
+                    Some(schema_id)
+                },
+                None => None,
+            };
+            let (_, next_schema) = SchemaModel::new(self.tx, namespace)
+                .apply(schema_id)
+                .await?;
+            IndexModel::new(self.tx)
+                .apply(namespace, &next_schema)
+                .await?;
+        }
+
+        let modules = self
+            .modules(version_id)
+            .await?
+            .into_iter()
+            .map(|module| module.into_value().module)
+            .collect();
+        let analyze_results = ModuleModel::new(self.tx)
+            .replace_application_metadata(ComponentId::Root, modules)
+            .await?;
+        CronModel::new(self.tx, ComponentId::Root)
+            .apply(&analyze_results)
+            .await?;
+        FunctionHandlesModel::new(self.tx)
+            .apply_config_diff(ComponentId::Root, Some(&analyze_results))
+            .await?;
+        AuthInfoModel::new(self.tx).put(version.auth_info).await?;
+        let udf_config = UdfConfig {
+            server_version: version.udf_server_version,
+            import_phase_rng_seed: self.tx.runtime().rng().random(),
+            import_phase_unix_timestamp: self.tx.runtime().unix_timestamp(),
+        };
+        UdfConfigModel::new(self.tx, namespace)
+            .set(udf_config)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,195 @@
+use common::{
+    auth::{
+        AuthInfo,
+        SerializedAuthInfo,
+    },
+    schemas::{
+        DatabaseSchema,
+        DocumentSchema,
+    },
+    version::Version,
+};
+#[cfg(any(test, feature = "testing"))]
+use proptest::strategy::Strategy;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::{
+    codegen_convex_serialization,
+    DeveloperDocumentId,
+};
+
+use crate::{
+    modules::types::{
+        ModuleMetadata,
+        SerializedModuleMetadata,
+    },
+    source_packages::types::SourcePackageId,
+};
+
+/// The state of the root component's code after a push, as recorded in
+/// `_code_versions`. Its modules are in `_code_version_modules`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct CodeVersion {
+    pub source_package_id: SourcePackageId,
+    /// The active schema's JSON definition, or `None` if there wasn't one.
+    pub raw_schema: Option<String>,
+    pub auth_info: Vec<AuthInfo>,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "(0..5u64, 0..100u64, 0..4u64).prop_map(|(major, minor, patch)| \
+                        Version::new(major, minor, patch))"
+        )
+    )]
+    pub udf_server_version: Version,
+    pub pushed_at_ms: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedCodeVersion {
+    source_package_id: String,
+    raw_schema: Option<String>,
+    auth_info: Vec<SerializedAuthInfo>,
+    udf_server_version: String,
+    pushed_at_ms: i64,
+}
+
+impl TryFrom<CodeVersion> for SerializedCodeVersion {
+    type Error = anyhow::Error;
+
+    fn try_from(version: CodeVersion) -> anyhow::Result<Self> {
+        Ok(Self {
+            source_package_id: DeveloperDocumentId::from(version.source_package_id).encode(),
+            raw_schema: version.raw_schema,
+            auth_info: version
+                .auth_info
+                .into_iter()
+                .map(SerializedAuthInfo::try_from)
+                .collect::<anyhow::Result<_>>()?,
+            udf_server_version: version.udf_server_version.to_string(),
+            pushed_at_ms: version.pushed_at_ms,
+        })
+    }
+}
+
+impl TryFrom<SerializedCodeVersion> for CodeVersion {
+    type Error = anyhow::Error;
+
+    fn try_from(version: SerializedCodeVersion) -> anyhow::Result<Self> {
+        Ok(Self {
+            source_package_id: DeveloperDocumentId::decode(&version.source_package_id)?.into(),
+            raw_schema: version.raw_schema,
+            auth_info: version
+                .auth_info
+                .into_iter()
+                .map(AuthInfo::try_from)
+                .collect::<anyhow::Result<_>>()?,
+            udf_server_version: version.udf_server_version.parse()?,
+            pushed_at_ms: version.pushed_at_ms,
+        })
+    }
+}
+
+codegen_convex_serialization!(CodeVersion, SerializedCodeVersion);
+
+/// One of the root component's modules at a recorded code version.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct CodeVersionModule {
+    pub version_id: DeveloperDocumentId,
+    pub module: ModuleMetadata,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedCodeVersionModule {
+    version_id: String,
+    module: SerializedModuleMetadata,
+}
+
+impl TryFrom<CodeVersionModule> for SerializedCodeVersionModule {
+    type Error = anyhow::Error;
+
+    fn try_from(module: CodeVersionModule) -> anyhow::Result<Self> {
+        Ok(Self {
+            version_id: module.version_id.encode(),
+            module: module.module.try_into()?,
+        })
+    }
+}
+
+impl TryFrom<SerializedCodeVersionModule> for CodeVersionModule {
+    type Error = anyhow::Error;
+
+    fn try_from(module: SerializedCodeVersionModule) -> anyhow::Result<Self> {
+        Ok(Self {
+            version_id: DeveloperDocumentId::decode(&module.version_id)?,
+            module: module.module.try_into()?,
+        })
+    }
+}
+
+codegen_convex_serialization!(CodeVersionModule, SerializedCodeVersionModule);
+
+/// Whether documents that satisfy the `active` schema also satisfy `target`,
+/// so switching to `target` can't leave documents that don't match it.
+pub fn schema_is_compatible(
+    active: Option<&DatabaseSchema>,
+    target: Option<&DatabaseSchema>,
+) -> bool {
+    let Some(target) = target else {
+        return true;
+    };
+    if !target.schema_validation {
+        return true;
+    }
+    let validated_active = active.filter(|schema| schema.schema_validation);
+    target.tables.iter().all(|(table_name, table)| {
+        matches!(table.document_type, None | Some(DocumentSchema::Any))
+            || validated_active
+                .and_then(|schema| schema.tables.get(table_name))
+                .is_some_and(|active_table| active_table.document_type == table.document_type)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use common::{
+        db_schema,
+        object_validator,
+        schemas::{
+            validator::{
+                FieldValidator,
+                Validator,
+            },
+            DocumentSchema,
+        },
+    };
+
+    use super::schema_is_compatible;
+
+    #[test]
+    fn test_schema_is_compatible() -> anyhow::Result<()> {
+        let any = db_schema!("messages" => DocumentSchema::Any);
+        let message_validator = object_validator!(
+            "body" => FieldValidator::required_field_type(Validator::String),
+        );
+        let typed = db_schema!("messages" => DocumentSchema::Union(vec![message_validator]));
+        let mut unvalidated = typed.clone();
+        unvalidated.schema_validation = false;
+
+        assert!(schema_is_compatible(Some(&typed), Some(&typed)));
+        assert!(schema_is_compatible(Some(&typed), None));
+        assert!(schema_is_compatible(Some(&typed), Some(&unvalidated)));
+        assert!(schema_is_compatible(None, Some(&any)));
+        // Existing documents were never checked against the validator.
+        assert!(!schema_is_compatible(Some(&any), Some(&typed)));
+        assert!(!schema_is_compatible(Some(&unvalidated), Some(&typed)));
+        assert!(!schema_is_compatible(None, Some(&typed)));
+        Ok(())
+    }
+}
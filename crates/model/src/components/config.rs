@@ -74,6 +74,7 @@ use crate::{
         module_versions::AnalyzedModule,
         ModuleModel,
     },
+    schema_history::SchemaHistoryModel,
     source_packages::{
         types::SourcePackage,
         SourcePackageModel,
@@ -460,6 +461,7 @@ impl<'a, RT: Runtime> ComponentConfigModel<'a, RT> {
                         .context("Missing allocated component ID")?;
                     let schema_id = self.schema_id_from_schema_change(schema_change, &path)?;
                     self.create_component(
+                        &path,
                         internal_id,
                         new_metadata,
                         &modules_by_definition,
@@ -472,6 +474,7 @@ impl<'a, RT: Runtime> ComponentConfigModel<'a, RT> {
                 (Some(existing_node), Some(new_metadata)) => {
                     let schema_id = self.schema_id_from_schema_change(schema_change, &path)?;
                     self.modify_component(
+                        &path,
                         existing_node,
                         new_metadata,
                         &modules_by_definition,
@@ -508,6 +511,7 @@ impl<'a, RT: Runtime> ComponentConfigModel<'a, RT> {
     #[fastrace::trace]
     pub async fn create_component(
         &mut self,
+        path: &ComponentPath,
         id: DeveloperDocumentId,
         metadata: ComponentMetadata,
         modules_by_definition: &BTreeMap<DeveloperDocumentId, NewModules>,
@@ -558,6 +562,15 @@ impl<'a, RT: Runtime> ComponentConfigModel<'a, RT> {
         IndexModel::new(self.tx)
             .apply(component_id.into(), &next_schema)
             .await?;
+        SchemaHistoryModel::new(self.tx)
+            .record(
+                path.clone(),
+                component_id,
+                schema_id,
+                schema_diff.as_ref(),
+                &index_diff,
+            )
+            .await?;
         Ok((
             id,
             ComponentDiff {
@@ -574,6 +587,7 @@ impl<'a, RT: Runtime> ComponentConfigModel<'a, RT> {
     #[fastrace::trace]
     async fn modify_component(
         &mut self,
+        path: &ComponentPath,
         existing: &ParsedDocument<ComponentMetadata>,
         new_metadata: ComponentMetadata,
         modules_by_definition: &BTreeMap<DeveloperDocumentId, NewModules>,
@@ -625,6 +639,15 @@ impl<'a, RT: Runtime> ComponentConfigModel<'a, RT> {
         IndexModel::new(self.tx)
             .apply(component_id.into(), &next_schema)
             .await?;
+        SchemaHistoryModel::new(self.tx)
+            .record(
+                path.clone(),
+                component_id,
+                schema_id,
+                schema_diff.as_ref(),
+                &index_diff,
+            )
+            .await?;
 
         let diff_type = if existing.state == ComponentState::Unmounted {
             ComponentDiffType::Remount
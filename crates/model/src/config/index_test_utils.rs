@@ -94,6 +94,7 @@ macro_rules! db_schema_with_indexes {
                             common::schemas::IndexSchema {
                                 index_descriptor: index_name.descriptor().clone(),
                                 fields: field_paths.try_into()?,
+                                variant: None,
                             },
                         );
                     )*
@@ -103,6 +104,9 @@ macro_rules! db_schema_with_indexes {
                         search_indexes: Default::default(),
                         vector_indexes: Default::default(),
                         document_type: None,
+                        defaults: Default::default(),
+                        mutability: Default::default(),
+                        checks: vec![],
                     };
                     tables.insert(table_name, table_def);
                 )*
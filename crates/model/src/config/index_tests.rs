@@ -82,6 +82,9 @@ macro_rules! db_schema_with_search_indexes {
                                 index_name.descriptor().clone(),
                                 field_path.try_into()?,
                                 BTreeSet::new(),
+                                Default::default(),
+                                BTreeSet::new(),
+                                Default::default(),
                             )?,
                         );
                     )*
@@ -91,6 +94,9 @@ macro_rules! db_schema_with_search_indexes {
                         search_indexes,
                         vector_indexes: Default::default(),
                         document_type: None,
+                        defaults: Default::default(),
+                        mutability: Default::default(),
+                        checks: vec![],
                     };
                     tables.insert(table_name, table_def);
                 )*
@@ -662,7 +668,7 @@ async fn apply_config_with_backfilling_search_index_throws(rt: TestRuntime) -> a
     assert_root_cause_contains(
         result,
         "Expected backfilled index, but found: Backfilling(TextIndexBackfillState { segments: [], \
-         cursor: None }) for \"index\"",
+         cursor: None, total_documents: None }) for \"index\"",
     );
 
     Ok(())
@@ -14,6 +14,7 @@ pub mod types;
 
 use std::collections::BTreeMap;
 
+use anyhow::Context;
 use common::{
     components::ComponentId,
     document::ParsedDocument,
@@ -45,6 +46,7 @@ use crate::{
         types::ModuleMetadata,
         ModuleModel,
     },
+    schema_history::SchemaHistoryModel,
     source_packages::{
         types::SourcePackage,
         SourcePackageModel,
@@ -100,6 +102,19 @@ impl<'a, RT: Runtime> ConfigModel<'a, RT> {
         let index_diff = IndexModel::new(self.tx)
             .apply(self.component.into(), &next_schema)
             .await?;
+        let component_path = self
+            .tx
+            .get_component_path(self.component)
+            .context("Missing component path")?;
+        SchemaHistoryModel::new(self.tx)
+            .record(
+                component_path,
+                self.component,
+                schema_id,
+                schema_diff.as_ref(),
+                &index_diff.clone().into(),
+            )
+            .await?;
 
         let module_diff = ModuleModel::new(self.tx)
             .apply(self.component, modules, source_package_id, analyze_results)
@@ -5,7 +5,10 @@ use std::{
 
 use anyhow::Context;
 use common::{
-    components::ComponentId,
+    components::{
+        ComponentId,
+        ComponentPath,
+    },
     document::{
         ParseDocument,
         ParsedDocument,
@@ -53,6 +56,13 @@ use crate::{
         },
     },
     modules::module_versions::AnalyzedModule,
+    webhooks::{
+        types::{
+            CronOutcome,
+            WebhookEvent,
+        },
+        WebhookModel,
+    },
     SystemIndex,
     SystemTable,
 };
@@ -337,6 +347,13 @@ impl<'a, RT: Runtime> CronModel<'a, RT> {
         log_lines: CronJobLogLines,
         execution_time: f64,
     ) -> anyhow::Result<()> {
+        let outcome = match &status {
+            CronJobStatus::Success(_) => CronOutcome::Success,
+            CronJobStatus::Err(error) => CronOutcome::Failure {
+                error: error.clone(),
+            },
+            CronJobStatus::Canceled { .. } => CronOutcome::Canceled,
+        };
         let cron_job_log = CronJobLog {
             name: job.name.clone(),
             ts: job.next_ts,
@@ -351,6 +368,21 @@ impl<'a, RT: Runtime> CronModel<'a, RT> {
             .await?;
         self.apply_job_log_retention(job.name.clone(), MAX_LOGS_PER_CRON)
             .await?;
+        // Queued in this transaction so the outcome is sent iff it's recorded.
+        let event = WebhookEvent::CronCompleted {
+            component_path: self
+                .tx
+                .get_component_path(self.component)
+                .unwrap_or_else(ComponentPath::root),
+            name: job.name.to_string(),
+            udf_path: job.cron_spec.udf_path.to_string(),
+            outcome,
+            ts: job.next_ts,
+        };
+        let now_ms = self.tx.runtime().unix_timestamp().as_ms_since_epoch()?;
+        WebhookModel::new(self.tx)
+            .enqueue_event(&event, now_ms.try_into()?)
+            .await?;
         Ok(())
     }
 
@@ -0,0 +1,98 @@
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParseDocument,
+        ParsedDocument,
+    },
+    query::{
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::TableName,
+};
+use database::{
+    system_tables::SystemIndex,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use keybroker::DataMaskingKey;
+use value::TableNamespace;
+
+use self::types::{
+    validate_rules,
+    DataMaskingPolicy,
+    DataMaskingRule,
+};
+use crate::SystemTable;
+
+pub mod types;
+
+pub static DATA_MASKING_RULES_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_data_masking_rules"
+        .parse()
+        .expect("Invalid built-in table name")
+});
+
+pub struct DataMaskingRulesTable;
+
+impl SystemTable for DataMaskingRulesTable {
+    type Metadata = DataMaskingRule;
+
+    fn table_name() -> &'static TableName {
+        &DATA_MASKING_RULES_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![]
+    }
+}
+
+pub struct DataMaskingModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> DataMaskingModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    pub async fn get_rules(&mut self) -> anyhow::Result<Vec<ParsedDocument<DataMaskingRule>>> {
+        let query = Query::full_table_scan(DATA_MASKING_RULES_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut rules = vec![];
+        while let Some(document) = query_stream.next(self.tx, None).await? {
+            rules.push(ParseDocument::<DataMaskingRule>::parse(document)?);
+        }
+        Ok(rules)
+    }
+
+    pub async fn get_policy(&mut self, key: DataMaskingKey) -> anyhow::Result<DataMaskingPolicy> {
+        let rules = self
+            .get_rules()
+            .await?
+            .into_iter()
+            .map(|rule| rule.into_value())
+            .collect();
+        DataMaskingPolicy::new(rules, key)
+    }
+
+    /// Replaces the deployment's masking rules with `rules`.
+    pub async fn set_rules(&mut self, rules: Vec<DataMaskingRule>) -> anyhow::Result<()> {
+        // Validate the rules compile before touching the table.
+        validate_rules(&rules)?;
+        for existing in self.get_rules().await? {
+            SystemMetadataModel::new_global(self.tx)
+                .delete(existing.id())
+                .await?;
+        }
+        for rule in rules {
+            SystemMetadataModel::new_global(self.tx)
+                .insert(&DATA_MASKING_RULES_TABLE, rule.try_into()?)
+                .await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,387 @@
+use std::{
+    borrow::Borrow,
+    collections::BTreeMap,
+};
+
+use keybroker::DataMaskingKey;
+use regex::{
+    Captures,
+    Regex,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::{
+    codegen_convex_serialization,
+    ConvexObject,
+    ConvexValue,
+    FieldPath,
+    IdentifierFieldName,
+    TableName,
+};
+
+pub const REDACTED: &str = "[REDACTED]";
+
+/// How a masked field's value is rewritten.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum MaskingStrategy {
+    /// Replace the value with a fixed placeholder.
+    Redact,
+    /// Replace the value with the hex HMAC-SHA256 of its contents, keyed by
+    /// the deployment's [`DataMaskingKey`]. Equal inputs produce equal
+    /// outputs, so masked data can still be joined on.
+    Hash,
+    /// Keep the last `visible_chars` characters of a string and replace the
+    /// rest with `*`. Non-string values are redacted.
+    Partial { visible_chars: u32 },
+}
+
+impl MaskingStrategy {
+    pub fn mask_value(
+        &self,
+        value: &ConvexValue,
+        key: &DataMaskingKey,
+    ) -> anyhow::Result<ConvexValue> {
+        let masked = match (self, value) {
+            (MaskingStrategy::Hash, ConvexValue::String(s)) => self.mask_str(s, key),
+            (MaskingStrategy::Hash, value) => self.mask_str(&value.json_serialize()?, key),
+            (MaskingStrategy::Partial { .. }, ConvexValue::String(s)) => self.mask_str(s, key),
+            (MaskingStrategy::Redact | MaskingStrategy::Partial { .. }, _) => REDACTED.to_string(),
+        };
+        masked.try_into()
+    }
+
+    pub fn mask_str(&self, s: &str, key: &DataMaskingKey) -> String {
+        match self {
+            MaskingStrategy::Redact => REDACTED.to_string(),
+            MaskingStrategy::Hash => key.hash(s.as_bytes()),
+            MaskingStrategy::Partial { visible_chars } => {
+                let num_chars = s.chars().count();
+                let num_hidden = num_chars.saturating_sub(*visible_chars as usize);
+                "*".repeat(num_hidden) + &s.chars().skip(num_hidden).collect::<String>()
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum SerializedMaskingStrategy {
+    Redact,
+    Hash,
+    #[serde(rename_all = "camelCase")]
+    Partial {
+        visible_chars: i64,
+    },
+}
+
+impl From<MaskingStrategy> for SerializedMaskingStrategy {
+    fn from(value: MaskingStrategy) -> Self {
+        match value {
+            MaskingStrategy::Redact => Self::Redact,
+            MaskingStrategy::Hash => Self::Hash,
+            MaskingStrategy::Partial { visible_chars } => Self::Partial {
+                visible_chars: visible_chars as i64,
+            },
+        }
+    }
+}
+
+impl TryFrom<SerializedMaskingStrategy> for MaskingStrategy {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedMaskingStrategy) -> Result<Self, Self::Error> {
+        Ok(match value {
+            SerializedMaskingStrategy::Redact => Self::Redact,
+            SerializedMaskingStrategy::Hash => Self::Hash,
+            SerializedMaskingStrategy::Partial { visible_chars } => Self::Partial {
+                visible_chars: visible_chars.try_into()?,
+            },
+        })
+    }
+}
+
+/// A single masking rule. A rule without a table applies to the field in
+/// every table. Every rule also applies to `field: value` pairs matching the
+/// last component of its field path in captured console output.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct DataMaskingRule {
+    pub table_name: Option<TableName>,
+    pub field: FieldPath,
+    pub strategy: MaskingStrategy,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedDataMaskingRule {
+    table_name: Option<String>,
+    field: String,
+    strategy: SerializedMaskingStrategy,
+}
+
+impl From<DataMaskingRule> for SerializedDataMaskingRule {
+    fn from(value: DataMaskingRule) -> Self {
+        Self {
+            table_name: value.table_name.map(|t| t.to_string()),
+            field: value.field.into(),
+            strategy: value.strategy.into(),
+        }
+    }
+}
+
+impl TryFrom<SerializedDataMaskingRule> for DataMaskingRule {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedDataMaskingRule) -> Result<Self, Self::Error> {
+        Ok(Self {
+            table_name: value.table_name.map(|t| t.parse()).transpose()?,
+            field: value.field.parse()?,
+            strategy: value.strategy.try_into()?,
+        })
+    }
+}
+
+codegen_convex_serialization!(DataMaskingRule, SerializedDataMaskingRule);
+
+/// The compiled set of masking rules for a deployment.
+#[derive(Clone, Debug)]
+pub struct DataMaskingPolicy {
+    rules: Vec<DataMaskingRule>,
+    key: DataMaskingKey,
+    /// Matches `field: value` and `"field": "value"` pairs for any masked
+    /// field name in log output. `None` if there are no rules.
+    log_pattern: Option<Regex>,
+    log_strategies: BTreeMap<String, MaskingStrategy>,
+}
+
+/// Fails if `rules` can't be compiled into a [`DataMaskingPolicy`].
+pub fn validate_rules(rules: &[DataMaskingRule]) -> anyhow::Result<()> {
+    compile_log_pattern(rules)?;
+    Ok(())
+}
+
+fn compile_log_pattern(
+    rules: &[DataMaskingRule],
+) -> anyhow::Result<(Option<Regex>, BTreeMap<String, MaskingStrategy>)> {
+    let mut log_strategies = BTreeMap::new();
+    for rule in rules {
+        let Some(last) = rule.field.fields().last() else {
+            continue;
+        };
+        let name: &str = last.borrow();
+        log_strategies
+            .entry(name.to_string())
+            .or_insert_with(|| rule.strategy.clone());
+    }
+    let log_pattern = if log_strategies.is_empty() {
+        None
+    } else {
+        let names = log_strategies
+            .keys()
+            .map(|name| regex::escape(name))
+            .collect::<Vec<_>>()
+            .join("|");
+        Some(Regex::new(&format!(
+            r#"(?P<key>["']?\b(?P<field>{names})\b["']?\s*[:=]\s*)(?:"(?P<dq>[^"]*)"|'(?P<sq>[^']*)'|(?P<bare>[^\s,}}\]]+))"#
+        ))?)
+    };
+    Ok((log_pattern, log_strategies))
+}
+
+impl DataMaskingPolicy {
+    pub fn new(rules: Vec<DataMaskingRule>, key: DataMaskingKey) -> anyhow::Result<Self> {
+        let (log_pattern, log_strategies) = compile_log_pattern(&rules)?;
+        Ok(Self {
+            rules,
+            key,
+            log_pattern,
+            log_strategies,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn rules(&self) -> &[DataMaskingRule] {
+        &self.rules
+    }
+
+    /// Applies every rule matching `table_name` to `object`. Fields that are
+    /// missing, or whose parent is not an object, are left untouched.
+    pub fn mask_document(
+        &self,
+        table_name: &TableName,
+        mut object: ConvexObject,
+    ) -> anyhow::Result<ConvexObject> {
+        for rule in &self.rules {
+            if rule
+                .table_name
+                .as_ref()
+                .is_some_and(|rule_table| rule_table != table_name)
+            {
+                continue;
+            }
+            object = rewrite_at_path(object, rule.field.fields(), &|value| {
+                rule.strategy.mask_value(&value, &self.key)
+            })?;
+        }
+        Ok(object)
+    }
+
+    /// Masks the values of `field: value` pairs for masked field names in a
+    /// single line of console output.
+    pub fn mask_log_message(&self, message: &str) -> String {
+        let Some(log_pattern) = &self.log_pattern else {
+            return message.to_string();
+        };
+        log_pattern
+            .replace_all(message, |captures: &Captures| {
+                let strategy = &self.log_strategies[&captures["field"]];
+                let key = &captures["key"];
+                if let Some(v) = captures.name("dq") {
+                    format!("{key}\"{}\"", strategy.mask_str(v.as_str(), &self.key))
+                } else if let Some(v) = captures.name("sq") {
+                    format!("{key}'{}'", strategy.mask_str(v.as_str(), &self.key))
+                } else {
+                    format!("{key}{}", strategy.mask_str(&captures["bare"], &self.key))
+                }
+            })
+            .into_owned()
+    }
+}
+
+/// Replaces the value at `path` in `object` with `rewrite` of it. Fields that
+/// are missing, or whose parent is not an object, are left untouched.
+pub fn rewrite_at_path(
+    object: ConvexObject,
+    path: &[IdentifierFieldName],
+    rewrite: &impl Fn(ConvexValue) -> anyhow::Result<ConvexValue>,
+) -> anyhow::Result<ConvexObject> {
+    let Some((first, rest)) = path.split_first() else {
+        return Ok(object);
+    };
+    let mut fields = BTreeMap::from(object);
+    let Some((field_name, value)) = fields.remove_entry::<str>(first.borrow()) else {
+        return fields.try_into();
+    };
+    let rewritten = match (rest.is_empty(), value) {
+        (true, value) => rewrite(value)?,
+        (false, ConvexValue::Object(inner)) => {
+            ConvexValue::Object(rewrite_at_path(inner, rest, rewrite)?)
+        },
+        (false, value) => value,
+    };
+    fields.insert(field_name, rewritten);
+    fields.try_into()
+}
+
+#[cfg(test)]
+mod tests {
+    use keybroker::{
+        DataMaskingKey,
+        KeyBroker,
+    };
+    use value::{
+        assert_obj,
+        TableName,
+    };
+
+    use super::{
+        DataMaskingPolicy,
+        DataMaskingRule,
+        MaskingStrategy,
+    };
+
+    fn users() -> TableName {
+        "users".parse().unwrap()
+    }
+
+    fn key() -> DataMaskingKey {
+        KeyBroker::dev().data_masking_key()
+    }
+
+    #[test]
+    fn test_mask_document() -> anyhow::Result<()> {
+        let policy = DataMaskingPolicy::new(
+            vec![
+                DataMaskingRule {
+                    table_name: Some(users()),
+                    field: "email".parse()?,
+                    strategy: MaskingStrategy::Redact,
+                },
+                DataMaskingRule {
+                    table_name: None,
+                    field: "address.phone".parse()?,
+                    strategy: MaskingStrategy::Partial { visible_chars: 4 },
+                },
+                DataMaskingRule {
+                    table_name: Some("orders".parse()?),
+                    field: "name".parse()?,
+                    strategy: MaskingStrategy::Hash,
+                },
+            ],
+            key(),
+        )?;
+        let masked = policy.mask_document(
+            &users(),
+            assert_obj!(
+                "name" => "Ada",
+                "email" => "ada@example.com",
+                "address" => { "phone" => "5551234567" },
+            ),
+        )?;
+        assert_eq!(
+            masked,
+            assert_obj!(
+                "name" => "Ada",
+                "email" => "[REDACTED]",
+                "address" => { "phone" => "******4567" },
+            )
+        );
+        let masked = policy.mask_document(
+            &"orders".parse()?,
+            assert_obj!("name" => "Ada", "total" => 10.0),
+        )?;
+        assert_eq!(
+            masked,
+            assert_obj!("name" => key().hash(b"Ada"), "total" => 10.0)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_mask_log_message() -> anyhow::Result<()> {
+        let policy = DataMaskingPolicy::new(
+            vec![
+                DataMaskingRule {
+                    table_name: Some(users()),
+                    field: "email".parse()?,
+                    strategy: MaskingStrategy::Redact,
+                },
+                DataMaskingRule {
+                    table_name: None,
+                    field: "ssn".parse()?,
+                    strategy: MaskingStrategy::Partial { visible_chars: 2 },
+                },
+            ],
+            key(),
+        )?;
+        assert_eq!(
+            policy.mask_log_message("{ email: 'ada@example.com', ssn: 123456789 }"),
+            "{ email: '[REDACTED]', ssn: *******89 }"
+        );
+        assert_eq!(
+            policy.mask_log_message(r#"{"email":"ada@example.com","emailVerified":true}"#),
+            r#"{"email":"[REDACTED]","emailVerified":true}"#
+        );
+        assert_eq!(
+            DataMaskingPolicy::new(vec![], key())?.mask_log_message("email: ada@example.com"),
+            "email: ada@example.com"
+        );
+        Ok(())
+    }
+}
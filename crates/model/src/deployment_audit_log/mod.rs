@@ -4,9 +4,13 @@ use common::{
     document::{
         ParseDocument,
         ParsedDocument,
+        ResolvedDocument,
+        CREATION_TIME_FIELD_PATH,
     },
     obj,
     query::{
+        IndexRange,
+        IndexRangeExpression,
         Order,
         Query,
     },
@@ -14,7 +18,6 @@ use common::{
     types::MemberId,
 };
 use database::{
-    unauthorized_error,
     ResolvedQuery,
     SystemMetadataModel,
     Transaction,
@@ -22,6 +25,7 @@ use database::{
 use futures_async_stream::try_stream;
 use value::{
     ConvexObject,
+    ConvexValue,
     FieldPath,
     ResolvedDocumentId,
     TableName,
@@ -30,9 +34,18 @@ use value::{
 
 pub mod types;
 
-use types::DeploymentAuditLogEvent;
+use types::{
+    AuditLogActor,
+    AuditLogRequestContext,
+    DeploymentAuditLogEvent,
+};
 
 use crate::{
+    log_search::{
+        audit_log_text,
+        types::LogSearchSource,
+        LogSearchModel,
+    },
     SystemIndex,
     SystemTable,
 };
@@ -46,6 +59,11 @@ pub static DEPLOYMENT_AUDIT_LOG_TABLE: LazyLock<TableName> = LazyLock::new(|| {
 pub static ACTION_FIELD: LazyLock<FieldPath> =
     LazyLock::new(|| "action".parse().expect("invalid action field"));
 
+pub static DEPLOYMENT_AUDIT_LOG_BY_ACTION_INDEX: LazyLock<SystemIndex<DeploymentAuditLogsTable>> =
+    LazyLock::new(|| {
+        SystemIndex::new("by_action", [&ACTION_FIELD, &CREATION_TIME_FIELD_PATH]).unwrap()
+    });
+
 pub struct DeploymentAuditLogsTable;
 impl SystemTable for DeploymentAuditLogsTable {
     type Metadata = DeploymentAuditLogEvent;
@@ -55,7 +73,7 @@ impl SystemTable for DeploymentAuditLogsTable {
     }
 
     fn indexes() -> Vec<SystemIndex<Self>> {
-        vec![]
+        vec![DEPLOYMENT_AUDIT_LOG_BY_ACTION_INDEX.clone()]
     }
 }
 
@@ -83,9 +101,23 @@ impl<'a, RT: Runtime> DeploymentAuditLogModel<'a, RT> {
         events: Vec<DeploymentAuditLogEvent>,
         member_id_override: Option<MemberId>,
     ) -> anyhow::Result<Vec<ResolvedDocumentId>> {
-        if !(self.tx.identity().is_system() || self.tx.identity().is_admin()) {
-            anyhow::bail!(unauthorized_error("insert_deployment_audit_log_event"));
-        }
+        self.insert_with_context(
+            events,
+            member_id_override,
+            &AuditLogRequestContext::current(),
+        )
+        .await
+    }
+
+    /// Insert events attributed to the transaction's identity, recording where
+    /// the request originated from.
+    pub async fn insert_with_context(
+        &mut self,
+        events: Vec<DeploymentAuditLogEvent>,
+        member_id_override: Option<MemberId>,
+        context: &AuditLogRequestContext,
+    ) -> anyhow::Result<Vec<ResolvedDocumentId>> {
+        self.tx.require_admin("insert_deployment_audit_log_event")?;
         let member_id = member_id_override.or_else(|| self.tx.identity().member_id());
         let member_id_value = member_id
             .map(|member_id| {
@@ -93,6 +125,12 @@ impl<'a, RT: Runtime> DeploymentAuditLogModel<'a, RT> {
                 i64::try_from(member_id_u64)
             })
             .transpose()?;
+        let actor = AuditLogActor::from(self.tx.identity()).to_string();
+        let source_ip = match context.source_ip {
+            Some(source_ip) => ConvexValue::try_from(source_ip.to_string())?,
+            None => ConvexValue::Null,
+        };
+        let now_ms = self.tx.runtime().unix_timestamp().as_ms_since_epoch()? as i64;
         let mut deployment_audit_log_ids = vec![];
         for event in events {
             let event_object: ConvexObject = event.try_into()?;
@@ -100,8 +138,16 @@ impl<'a, RT: Runtime> DeploymentAuditLogModel<'a, RT> {
                 Some(member_id) => event_object.shallow_merge(obj!("member_id" => member_id)?)?,
                 None => event_object.shallow_merge(obj!("member_id" => null)?)?,
             };
+            let event_object_with_context = event_object_with_member_id.shallow_merge(obj!(
+                "actor" => actor.clone(),
+                "source_ip" => source_ip.clone(),
+            )?)?;
+            let text = audit_log_text(&event_object_with_context);
             let id = SystemMetadataModel::new_global(self.tx)
-                .insert_metadata(&DEPLOYMENT_AUDIT_LOG_TABLE, event_object_with_member_id)
+                .insert_metadata(&DEPLOYMENT_AUDIT_LOG_TABLE, event_object_with_context)
+                .await?;
+            LogSearchModel::new(self.tx)
+                .index(LogSearchSource::AuditLog, id.into(), now_ms, &text)
                 .await?;
             deployment_audit_log_ids.push(id);
         }
@@ -126,4 +172,35 @@ impl<'a, RT: Runtime> DeploymentAuditLogModel<'a, RT> {
             yield row;
         }
     }
+
+    /// Most recent audit log entries, newest first, optionally restricted to a
+    /// single action. Entries are returned as raw documents so that callers
+    /// can see the actor and source IP recorded next to each event.
+    pub async fn list_recent(
+        &mut self,
+        action: Option<&str>,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ResolvedDocument>> {
+        self.tx.require_admin("list_deployment_audit_log")?;
+        let query = match action {
+            Some(action) => Query::index_range(IndexRange {
+                index_name: DEPLOYMENT_AUDIT_LOG_BY_ACTION_INDEX.name(),
+                range: vec![IndexRangeExpression::Eq(
+                    ACTION_FIELD.clone(),
+                    ConvexValue::try_from(action.to_string())?.into(),
+                )],
+                order: Order::Desc,
+            }),
+            None => Query::full_table_scan(DEPLOYMENT_AUDIT_LOG_TABLE.clone(), Order::Desc),
+        };
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut result = vec![];
+        while result.len() < limit {
+            let Some(doc) = query_stream.next(self.tx, None).await? else {
+                break;
+            };
+            result.push(doc);
+        }
+        Ok(result)
+    }
 }
@@ -1,5 +1,6 @@
 use std::{
     collections::BTreeMap,
+    net::IpAddr,
     str::FromStr,
     sync::LazyLock,
 };
@@ -25,6 +26,10 @@ use common::{
     },
 };
 use database::LegacyIndexDiff;
+use keybroker::{
+    AdminIdentityPrincipal,
+    Identity,
+};
 #[cfg(any(test, feature = "testing"))]
 use proptest::prelude::*;
 use serde::{
@@ -44,18 +49,21 @@ use value::{
     val,
     ConvexObject,
     ConvexValue,
+    DeveloperDocumentId,
     TableName,
 };
 
 use crate::{
     auth::types::AuthDiff,
     backend_state::types::BackendState,
+    canary::types::CanaryRouting,
     components::config::{
         ComponentDiff,
         SerializedComponentDiff,
     },
     config::types::ConfigDiff,
     environment_variables::types::EnvVarName,
+    feature_flags::types::FeatureFlag,
     snapshot_imports::types::{
         ImportFormat,
         ImportMode,
@@ -160,6 +168,93 @@ pub enum DeploymentAuditLogEvent {
     // TODO: consider adding table names once this is logged for more places
     // and we have a story about limiting size.
     ClearTables,
+    DeleteTables {
+        table_names: Vec<TableName>,
+    },
+    FreezeTable {
+        table_name: TableName,
+        reason: String,
+        duration_secs: u32,
+    },
+    UnfreezeTable {
+        table_name: TableName,
+    },
+    TruncateTable {
+        table_name: TableName,
+        documents_deleted: u64,
+    },
+    CreateTableSnapshot {
+        name: TableName,
+        table_name: TableName,
+    },
+    DeleteTableSnapshot {
+        name: TableName,
+    },
+    LoadFixtures {
+        documents: u64,
+        files: u64,
+        scheduled_jobs: u64,
+    },
+    SetComponentQuota {
+        component: ComponentPath,
+    },
+    StartCanary {
+        traffic_percent: u32,
+        routing: CanaryRouting,
+    },
+    UpdateCanaryTraffic {
+        traffic_percent: u32,
+    },
+    PromoteCanary,
+    RollBackCanary {
+        reason: String,
+    },
+    RollbackCodeVersion {
+        version_id: DeveloperDocumentId,
+    },
+    EnableMaintenanceMode {
+        message: String,
+        status_code: u16,
+    },
+    DisableMaintenanceMode,
+    SetFeatureFlag {
+        flag: FeatureFlag,
+    },
+    DeleteFeatureFlag {
+        name: String,
+    },
+    RegisterWebhookEndpoint {
+        name: String,
+        url: String,
+    },
+    DeleteWebhookEndpoint {
+        name: String,
+    },
+    RotateWebhookSecret {
+        name: String,
+    },
+    SetStaticAssetMount {
+        path_prefix: String,
+        folder: String,
+    },
+    DeleteStaticAssetMount {
+        path_prefix: String,
+    },
+    SetHttpCheck {
+        name: String,
+        url: String,
+    },
+    DeleteHttpCheck {
+        name: String,
+    },
+    RequestErasure {
+        subject_id: String,
+    },
+    /// `fields` are the masked fields, as `table.field` or just `field` for
+    /// rules that apply to every table.
+    SetDataMaskingRules {
+        fields: Vec<String>,
+    },
     SnapshotImport {
         table_names: BTreeMap<ComponentPath, Vec<TableName>>,
         table_count: u64,
@@ -222,6 +317,32 @@ impl DeploymentAuditLogEvent {
             DeploymentAuditLogEvent::ChangeDeploymentState { .. } => "change_deployment_state",
             DeploymentAuditLogEvent::SnapshotImport { .. } => "snapshot_import",
             DeploymentAuditLogEvent::ClearTables => "clear_tables",
+            DeploymentAuditLogEvent::DeleteTables { .. } => "delete_tables",
+            DeploymentAuditLogEvent::FreezeTable { .. } => "freeze_table",
+            DeploymentAuditLogEvent::UnfreezeTable { .. } => "unfreeze_table",
+            DeploymentAuditLogEvent::TruncateTable { .. } => "truncate_table",
+            DeploymentAuditLogEvent::CreateTableSnapshot { .. } => "create_table_snapshot",
+            DeploymentAuditLogEvent::DeleteTableSnapshot { .. } => "delete_table_snapshot",
+            DeploymentAuditLogEvent::LoadFixtures { .. } => "load_fixtures",
+            DeploymentAuditLogEvent::SetComponentQuota { .. } => "set_component_quota",
+            DeploymentAuditLogEvent::StartCanary { .. } => "start_canary",
+            DeploymentAuditLogEvent::UpdateCanaryTraffic { .. } => "update_canary_traffic",
+            DeploymentAuditLogEvent::PromoteCanary => "promote_canary",
+            DeploymentAuditLogEvent::RollBackCanary { .. } => "roll_back_canary",
+            DeploymentAuditLogEvent::RollbackCodeVersion { .. } => "rollback_code_version",
+            DeploymentAuditLogEvent::EnableMaintenanceMode { .. } => "enable_maintenance_mode",
+            DeploymentAuditLogEvent::DisableMaintenanceMode => "disable_maintenance_mode",
+            DeploymentAuditLogEvent::SetFeatureFlag { .. } => "set_feature_flag",
+            DeploymentAuditLogEvent::DeleteFeatureFlag { .. } => "delete_feature_flag",
+            DeploymentAuditLogEvent::RegisterWebhookEndpoint { .. } => "register_webhook_endpoint",
+            DeploymentAuditLogEvent::DeleteWebhookEndpoint { .. } => "delete_webhook_endpoint",
+            DeploymentAuditLogEvent::RotateWebhookSecret { .. } => "rotate_webhook_secret",
+            DeploymentAuditLogEvent::SetStaticAssetMount { .. } => "set_static_asset_mount",
+            DeploymentAuditLogEvent::DeleteStaticAssetMount { .. } => "delete_static_asset_mount",
+            DeploymentAuditLogEvent::SetHttpCheck { .. } => "set_http_check",
+            DeploymentAuditLogEvent::DeleteHttpCheck { .. } => "delete_http_check",
+            DeploymentAuditLogEvent::RequestErasure { .. } => "request_erasure",
+            DeploymentAuditLogEvent::SetDataMaskingRules { .. } => "set_data_masking_rules",
         }
     }
 
@@ -350,6 +471,119 @@ impl DeploymentAuditLogEvent {
                 )
             },
             DeploymentAuditLogEvent::ClearTables => obj!(),
+            DeploymentAuditLogEvent::DeleteTables { table_names } => {
+                let table_names: Vec<_> = table_names
+                    .into_iter()
+                    .map(|table_name| {
+                        anyhow::Ok(ConvexValue::String(table_name.to_string().try_into()?))
+                    })
+                    .try_collect()?;
+                obj!("table_names" => table_names)
+            },
+            DeploymentAuditLogEvent::FreezeTable {
+                table_name,
+                reason,
+                duration_secs,
+            } => {
+                obj!(
+                    "table_name" => table_name.to_string(),
+                    "reason" => reason,
+                    "duration_secs" => duration_secs as i64,
+                )
+            },
+            DeploymentAuditLogEvent::UnfreezeTable { table_name } => {
+                obj!("table_name" => table_name.to_string())
+            },
+            DeploymentAuditLogEvent::TruncateTable {
+                table_name,
+                documents_deleted,
+            } => {
+                obj!(
+                    "table_name" => table_name.to_string(),
+                    "documents_deleted" => documents_deleted as i64,
+                )
+            },
+            DeploymentAuditLogEvent::CreateTableSnapshot { name, table_name } => {
+                obj!(
+                    "name" => name.to_string(),
+                    "table_name" => table_name.to_string(),
+                )
+            },
+            DeploymentAuditLogEvent::DeleteTableSnapshot { name } => {
+                obj!("name" => name.to_string())
+            },
+            DeploymentAuditLogEvent::LoadFixtures {
+                documents,
+                files,
+                scheduled_jobs,
+            } => {
+                obj!(
+                    "documents" => documents as i64,
+                    "files" => files as i64,
+                    "scheduled_jobs" => scheduled_jobs as i64,
+                )
+            },
+            DeploymentAuditLogEvent::SetComponentQuota { component } => {
+                let component: ConvexValue = component.serialize().try_into()?;
+                obj!("component" => component)
+            },
+            DeploymentAuditLogEvent::StartCanary {
+                traffic_percent,
+                routing,
+            } => {
+                obj!(
+                    "traffic_percent" => traffic_percent as i64,
+                    "routing" => routing.to_string(),
+                )
+            },
+            DeploymentAuditLogEvent::UpdateCanaryTraffic { traffic_percent } => {
+                obj!("traffic_percent" => traffic_percent as i64)
+            },
+            DeploymentAuditLogEvent::PromoteCanary => obj!(),
+            DeploymentAuditLogEvent::RollBackCanary { reason } => {
+                obj!("reason" => reason)
+            },
+            DeploymentAuditLogEvent::RollbackCodeVersion { version_id } => {
+                obj!("version_id" => version_id.encode())
+            },
+            DeploymentAuditLogEvent::EnableMaintenanceMode {
+                message,
+                status_code,
+            } => {
+                obj!(
+                    "message" => message,
+                    "status_code" => status_code as i64,
+                )
+            },
+            DeploymentAuditLogEvent::DisableMaintenanceMode => obj!(),
+            DeploymentAuditLogEvent::SetFeatureFlag { flag } => ConvexObject::try_from(flag),
+            DeploymentAuditLogEvent::DeleteFeatureFlag { name } => obj!("name" => name),
+            DeploymentAuditLogEvent::RegisterWebhookEndpoint { name, url } => {
+                obj!("name" => name, "url" => url)
+            },
+            DeploymentAuditLogEvent::DeleteWebhookEndpoint { name }
+            | DeploymentAuditLogEvent::RotateWebhookSecret { name } => obj!("name" => name),
+            DeploymentAuditLogEvent::SetStaticAssetMount {
+                path_prefix,
+                folder,
+            } => obj!("path_prefix" => path_prefix, "folder" => folder),
+            DeploymentAuditLogEvent::DeleteStaticAssetMount { path_prefix } => {
+                obj!("path_prefix" => path_prefix)
+            },
+            DeploymentAuditLogEvent::SetHttpCheck { name, url } => {
+                obj!("name" => name, "url" => url)
+            },
+            DeploymentAuditLogEvent::DeleteHttpCheck { name } => obj!("name" => name),
+            DeploymentAuditLogEvent::RequestErasure { subject_id } => {
+                obj!("subject_id" => subject_id)
+            },
+            DeploymentAuditLogEvent::SetDataMaskingRules { fields } => {
+                let fields: Vec<_> = fields
+                    .into_iter()
+                    .map(|field| anyhow::Ok(ConvexValue::String(field.try_into()?)))
+                    .try_collect()?;
+                obj!("fields" => fields)
+            },
         }
     }
 
@@ -369,6 +603,90 @@ impl DeploymentAuditLogEvent {
             },
         })
     }
+
+    /// Like [`Self::to_log_event`], but also attributes the event to the actor
+    /// and source address that performed it.
+    pub fn to_log_event_with_context(
+        event: DeploymentAuditLogEvent,
+        actor: &AuditLogActor,
+        context: &AuditLogRequestContext,
+        timestamp: UnixTimestamp,
+    ) -> anyhow::Result<LogEvent> {
+        let mut log_event = Self::to_log_event(event, timestamp)?;
+        if let StructuredLogEvent::DeploymentAuditLog { metadata, .. } = &mut log_event.event {
+            metadata.insert("actor".to_string(), actor.to_string().into());
+            if let Some(source_ip) = context.source_ip {
+                metadata.insert("source_ip".to_string(), source_ip.to_string().into());
+            }
+        }
+        Ok(log_event)
+    }
+}
+
+/// The principal responsible for a privileged operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditLogActor {
+    Member(u64),
+    Team(u64),
+    System,
+    Unknown,
+}
+
+impl From<&Identity> for AuditLogActor {
+    fn from(identity: &Identity) -> Self {
+        match identity {
+            Identity::InstanceAdmin(admin_identity) | Identity::ActingUser(admin_identity, _) => {
+                match admin_identity.principal() {
+                    AdminIdentityPrincipal::Member(member_id) => Self::Member(member_id.0),
+                    AdminIdentityPrincipal::Team(team_id) => Self::Team(team_id.0),
+                }
+            },
+            Identity::System(_) => Self::System,
+            Identity::User(_) | Identity::Unknown(_) => Self::Unknown,
+        }
+    }
+}
+
+impl std::fmt::Display for AuditLogActor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuditLogActor::Member(member_id) => write!(f, "member:{member_id}"),
+            AuditLogActor::Team(team_id) => write!(f, "team:{team_id}"),
+            AuditLogActor::System => write!(f, "system"),
+            AuditLogActor::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Details about the request that triggered a privileged operation, recorded
+/// alongside its audit log events.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditLogRequestContext {
+    pub source_ip: Option<IpAddr>,
+}
+
+tokio::task_local! {
+    static REQUEST_CONTEXT: AuditLogRequestContext;
+}
+
+impl AuditLogRequestContext {
+    pub fn new(source_ip: Option<IpAddr>) -> Self {
+        Self { source_ip }
+    }
+
+    /// Runs `f` as part of the request described by `self`. Audit log events
+    /// recorded by `f` (but not by tasks it spawns) carry this context.
+    pub async fn scope<F: Future>(self, f: F) -> F::Output {
+        REQUEST_CONTEXT.scope(self, f).await
+    }
+
+    /// The context of the request being handled, or the default context
+    /// outside of a request (e.g. in background workers).
+    pub fn current() -> Self {
+        REQUEST_CONTEXT
+            .try_with(|context| context.clone())
+            .unwrap_or_default()
+    }
 }
 
 impl TryFrom<DeploymentAuditLogEvent> for ConvexObject {
@@ -444,6 +762,99 @@ impl TryFrom<ConvexObject> for DeploymentAuditLogEvent {
                 new_state: remove_string(&mut fields, "new_state")?.parse()?,
             },
             "clear_tables" => DeploymentAuditLogEvent::ClearTables,
+            "delete_tables" => DeploymentAuditLogEvent::DeleteTables {
+                table_names: remove_vec_of_strings(&mut fields, "table_names")?
+                    .iter()
+                    .map(|s| TableName::from_str(s))
+                    .try_collect()?,
+            },
+            "freeze_table" => DeploymentAuditLogEvent::FreezeTable {
+                table_name: remove_string(&mut fields, "table_name")?.parse()?,
+                reason: remove_string(&mut fields, "reason")?,
+                duration_secs: remove_int64(&mut fields, "duration_secs")?.try_into()?,
+            },
+            "unfreeze_table" => DeploymentAuditLogEvent::UnfreezeTable {
+                table_name: remove_string(&mut fields, "table_name")?.parse()?,
+            },
+            "truncate_table" => DeploymentAuditLogEvent::TruncateTable {
+                table_name: remove_string(&mut fields, "table_name")?.parse()?,
+                documents_deleted: remove_int64(&mut fields, "documents_deleted")? as u64,
+            },
+            "create_table_snapshot" => DeploymentAuditLogEvent::CreateTableSnapshot {
+                name: remove_string(&mut fields, "name")?.parse()?,
+                table_name: remove_string(&mut fields, "table_name")?.parse()?,
+            },
+            "delete_table_snapshot" => DeploymentAuditLogEvent::DeleteTableSnapshot {
+                name: remove_string(&mut fields, "name")?.parse()?,
+            },
+            "load_fixtures" => DeploymentAuditLogEvent::LoadFixtures {
+                documents: remove_int64(&mut fields, "documents")? as u64,
+                files: remove_int64(&mut fields, "files")? as u64,
+                scheduled_jobs: remove_int64(&mut fields, "scheduled_jobs")? as u64,
+            },
+            "set_component_quota" => DeploymentAuditLogEvent::SetComponentQuota {
+                component: ComponentPath::deserialize(
+                    remove_nullable_string(&mut fields, "component")?.as_deref(),
+                )?,
+            },
+            "start_canary" => DeploymentAuditLogEvent::StartCanary {
+                traffic_percent: remove_int64(&mut fields, "traffic_percent")?.try_into()?,
+                routing: remove_string(&mut fields, "routing")?.parse()?,
+            },
+            "update_canary_traffic" => DeploymentAuditLogEvent::UpdateCanaryTraffic {
+                traffic_percent: remove_int64(&mut fields, "traffic_percent")?.try_into()?,
+            },
+            "promote_canary" => DeploymentAuditLogEvent::PromoteCanary,
+            "roll_back_canary" => DeploymentAuditLogEvent::RollBackCanary {
+                reason: remove_string(&mut fields, "reason")?,
+            },
+            "rollback_code_version" => DeploymentAuditLogEvent::RollbackCodeVersion {
+                version_id: DeveloperDocumentId::decode(&remove_string(
+                    &mut fields,
+                    "version_id",
+                )?)?,
+            },
+            "enable_maintenance_mode" => DeploymentAuditLogEvent::EnableMaintenanceMode {
+                message: remove_string(&mut fields, "message")?,
+                status_code: remove_int64(&mut fields, "status_code")?.try_into()?,
+            },
+            "disable_maintenance_mode" => DeploymentAuditLogEvent::DisableMaintenanceMode,
+            "set_feature_flag" => DeploymentAuditLogEvent::SetFeatureFlag {
+                flag: ConvexObject::try_from(fields)?.try_into()?,
+            },
+            "delete_feature_flag" => DeploymentAuditLogEvent::DeleteFeatureFlag {
+                name: remove_string(&mut fields, "name")?,
+            },
+            "register_webhook_endpoint" => DeploymentAuditLogEvent::RegisterWebhookEndpoint {
+                name: remove_string(&mut fields, "name")?,
+                url: remove_string(&mut fields, "url")?,
+            },
+            "delete_webhook_endpoint" => DeploymentAuditLogEvent::DeleteWebhookEndpoint {
+                name: remove_string(&mut fields, "name")?,
+            },
+            "rotate_webhook_secret" => DeploymentAuditLogEvent::RotateWebhookSecret {
+                name: remove_string(&mut fields, "name")?,
+            },
+            "set_static_asset_mount" => DeploymentAuditLogEvent::SetStaticAssetMount {
+                path_prefix: remove_string(&mut fields, "path_prefix")?,
+                folder: remove_string(&mut fields, "folder")?,
+            },
+            "delete_static_asset_mount" => DeploymentAuditLogEvent::DeleteStaticAssetMount {
+                path_prefix: remove_string(&mut fields, "path_prefix")?,
+            },
+            "set_http_check" => DeploymentAuditLogEvent::SetHttpCheck {
+                name: remove_string(&mut fields, "name")?,
+                url: remove_string(&mut fields, "url")?,
+            },
+            "delete_http_check" => DeploymentAuditLogEvent::DeleteHttpCheck {
+                name: remove_string(&mut fields, "name")?,
+            },
+            "request_erasure" => DeploymentAuditLogEvent::RequestErasure {
+                subject_id: remove_string(&mut fields, "subject_id")?,
+            },
+            "set_data_masking_rules" => DeploymentAuditLogEvent::SetDataMaskingRules {
+                fields: remove_vec_of_strings(&mut fields, "fields")?,
+            },
             "snapshot_import" => {
                 let table_names: BTreeMap<_, _> = remove_vec(&mut fields, "table_names")?
                     .into_iter()
@@ -662,7 +1073,11 @@ mod tests {
     use serde_json::json;
     use value::ConvexObject;
 
-    use super::DeploymentAuditLogEvent;
+    use super::{
+        AuditLogActor,
+        AuditLogRequestContext,
+        DeploymentAuditLogEvent,
+    };
 
     proptest! {
         #![proptest_config(
@@ -700,6 +1115,30 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn test_serialization_of_audit_log_event_with_context() -> anyhow::Result<()> {
+        let event = DeploymentAuditLogEvent::to_log_event_with_context(
+            DeploymentAuditLogEvent::DeleteTables {
+                table_names: vec!["messages".parse()?],
+            },
+            &AuditLogActor::Member(7),
+            &AuditLogRequestContext::new(Some("10.0.0.1".parse()?)),
+            UnixTimestamp::from_millis(0),
+        )?;
+        let event_json = event.to_json_map(LogEventFormatVersion::default())?;
+        let value = serde_json::to_value(&event_json)?;
+        assert_eq!(
+            value,
+            json!({
+                "topic": "audit_log",
+                "timestamp": 0,
+                "audit_log_action": "delete_tables",
+                "audit_log_metadata": "{\"table_names\":[\"messages\"],\"actor\":\"member:7\",\"source_ip\":\"10.0.0.1\"}",
+            })
+        );
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,372 @@
+//! Erasure jobs for data subject erasure requests. An admin configures an
+//! erasure policy per table and requests the erasure of a subject document in
+//! one component. The erasure worker then finds the documents referencing the
+//! subject through indexes on their `v.id` fields, applies each table's
+//! policy, deletes the files they reference, purges the earlier revisions of
+//! everything it changed, and records a signed completion report on the job.
+
+use std::{
+    collections::BTreeSet,
+    sync::LazyLock,
+};
+
+use common::{
+    components::ComponentId,
+    document::{
+        ParseDocument,
+        ParsedDocument,
+        CREATION_TIME_FIELD_PATH,
+    },
+    maybe_val,
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+};
+use database::{
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use value::{
+    DeveloperDocumentId,
+    FieldPath,
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::{
+    ErasureAction,
+    ErasureJob,
+    ErasureJobItem,
+    ErasureJobState,
+    ErasurePolicy,
+};
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static ERASURE_POLICIES_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_erasure_policies"
+        .parse()
+        .expect("Invalid built-in erasure policies table")
+});
+
+pub static ERASURE_JOBS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_erasure_jobs"
+        .parse()
+        .expect("Invalid built-in erasure jobs table")
+});
+
+pub static ERASURE_JOB_ITEMS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_erasure_job_items"
+        .parse()
+        .expect("Invalid built-in erasure job items table")
+});
+
+/// By state and creation time. Used to find the oldest unfinished job.
+pub static ERASURE_JOBS_BY_STATE_INDEX: LazyLock<SystemIndex<ErasureJobsTable>> =
+    LazyLock::new(|| {
+        SystemIndex::new("by_state", [&STATE_FIELD, &CREATION_TIME_FIELD_PATH]).unwrap()
+    });
+static STATE_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "state".parse().expect("invalid state field"));
+
+/// By job and document. Used to skip documents a job has already erased and
+/// to page through a job's items while purging.
+pub static ERASURE_JOB_ITEMS_BY_JOB_AND_DOCUMENT_INDEX: LazyLock<
+    SystemIndex<ErasureJobItemsTable>,
+> = LazyLock::new(|| {
+    SystemIndex::new("by_job_and_document", [&JOB_ID_FIELD, &DOCUMENT_ID_FIELD]).unwrap()
+});
+static JOB_ID_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "jobId".parse().expect("invalid jobId field"));
+static DOCUMENT_ID_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "documentId".parse().expect("invalid documentId field"));
+
+const MAX_POLICIES: usize = 100;
+
+pub struct ErasurePoliciesTable;
+impl SystemTable for ErasurePoliciesTable {
+    type Metadata = ErasurePolicy;
+
+    fn table_name() -> &'static TableName {
+        &ERASURE_POLICIES_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![]
+    }
+}
+
+pub struct ErasureJobsTable;
+impl SystemTable for ErasureJobsTable {
+    type Metadata = ErasureJob;
+
+    fn table_name() -> &'static TableName {
+        &ERASURE_JOBS_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![ERASURE_JOBS_BY_STATE_INDEX.clone()]
+    }
+}
+
+pub struct ErasureJobItemsTable;
+impl SystemTable for ErasureJobItemsTable {
+    type Metadata = ErasureJobItem;
+
+    fn table_name() -> &'static TableName {
+        &ERASURE_JOB_ITEMS_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![ERASURE_JOB_ITEMS_BY_JOB_AND_DOCUMENT_INDEX.clone()]
+    }
+}
+
+pub struct ErasureModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> ErasureModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    pub async fn get_policies(&mut self) -> anyhow::Result<Vec<ParsedDocument<ErasurePolicy>>> {
+        self.tx.require_admin("get_erasure_policies")?;
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&ERASURE_POLICIES_TABLE) {
+            return Ok(vec![]);
+        }
+        let query = Query::full_table_scan(ERASURE_POLICIES_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut policies = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            policies.push(ParseDocument::<ErasurePolicy>::parse(doc)?);
+        }
+        Ok(policies)
+    }
+
+    /// Replaces the deployment's erasure policies with `policies`.
+    pub async fn set_policies(&mut self, policies: Vec<ErasurePolicy>) -> anyhow::Result<()> {
+        self.tx.require_admin("update_erasure_policies")?;
+        validate_policies(&policies)?;
+        for existing in self.get_policies().await? {
+            SystemMetadataModel::new_global(self.tx)
+                .delete(existing.id())
+                .await?;
+        }
+        for policy in policies {
+            SystemMetadataModel::new_global(self.tx)
+                .insert(&ERASURE_POLICIES_TABLE, policy.try_into()?)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Queues an erasure of the subject document in `component`. The caller
+    /// is responsible for checking the subject exists.
+    pub async fn request_erasure(
+        &mut self,
+        component: ComponentId,
+        subject_id: DeveloperDocumentId,
+        now_ms: i64,
+    ) -> anyhow::Result<ResolvedDocumentId> {
+        self.tx.require_admin("request_erasure")?;
+        let job = ErasureJob {
+            component,
+            subject_id,
+            requested_at_ms: now_ms,
+            state: ErasureJobState::Requested,
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .insert(&ERASURE_JOBS_TABLE, job.try_into()?)
+            .await
+    }
+
+    pub async fn get_job(
+        &mut self,
+        id: DeveloperDocumentId,
+    ) -> anyhow::Result<Option<ParsedDocument<ErasureJob>>> {
+        self.tx.require_admin("get_erasure_job")?;
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&ERASURE_JOBS_TABLE) {
+            return Ok(None);
+        }
+        let Ok(id) = self.tx.resolve_developer_id(&id, TableNamespace::Global) else {
+            return Ok(None);
+        };
+        if !self
+            .tx
+            .table_mapping()
+            .namespace(TableNamespace::Global)
+            .tablet_matches_name(id.tablet_id, &ERASURE_JOBS_TABLE)
+        {
+            return Ok(None);
+        }
+        self.tx
+            .get(id)
+            .await?
+            .map(ParseDocument::<ErasureJob>::parse)
+            .transpose()
+    }
+
+    /// The oldest job that hasn't completed or failed, preferring jobs that
+    /// have already started erasing. Purging jobs come last since they may
+    /// have to wait for the retention window to pass their erasure.
+    pub async fn next_unfinished_job(
+        &mut self,
+    ) -> anyhow::Result<Option<ParsedDocument<ErasureJob>>> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&ERASURE_JOBS_TABLE) {
+            return Ok(None);
+        }
+        for state in ["erasing", "requested", "purging"] {
+            let query = Query::index_range(IndexRange {
+                index_name: ERASURE_JOBS_BY_STATE_INDEX.name(),
+                range: vec![IndexRangeExpression::Eq(
+                    STATE_FIELD.clone(),
+                    maybe_val!(state.to_string()),
+                )],
+                order: Order::Asc,
+            });
+            let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+            if let Some(doc) = query_stream.next(self.tx, Some(1)).await? {
+                return Ok(Some(ParseDocument::<ErasureJob>::parse(doc)?));
+            }
+        }
+        Ok(None)
+    }
+
+    pub async fn update_job(
+        &mut self,
+        id: ResolvedDocumentId,
+        job: ErasureJob,
+    ) -> anyhow::Result<()> {
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, job.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    /// Whether the job has already erased the document.
+    pub async fn has_item(
+        &mut self,
+        job_id: DeveloperDocumentId,
+        document_id: DeveloperDocumentId,
+    ) -> anyhow::Result<bool> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&ERASURE_JOB_ITEMS_TABLE) {
+            return Ok(false);
+        }
+        let query = Query::index_range(IndexRange {
+            index_name: ERASURE_JOB_ITEMS_BY_JOB_AND_DOCUMENT_INDEX.name(),
+            range: vec![
+                IndexRangeExpression::Eq(JOB_ID_FIELD.clone(), maybe_val!(job_id.encode())),
+                IndexRangeExpression::Eq(
+                    DOCUMENT_ID_FIELD.clone(),
+                    maybe_val!(document_id.encode()),
+                ),
+            ],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        Ok(query_stream.next(self.tx, Some(1)).await?.is_some())
+    }
+
+    pub async fn insert_item(&mut self, item: ErasureJobItem) -> anyhow::Result<()> {
+        SystemMetadataModel::new_global(self.tx)
+            .insert(&ERASURE_JOB_ITEMS_TABLE, item.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    /// Up to `limit` of the job's items.
+    pub async fn items(
+        &mut self,
+        job_id: DeveloperDocumentId,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ParsedDocument<ErasureJobItem>>> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&ERASURE_JOB_ITEMS_TABLE) {
+            return Ok(vec![]);
+        }
+        let query = Query::index_range(IndexRange {
+            index_name: ERASURE_JOB_ITEMS_BY_JOB_AND_DOCUMENT_INDEX.name(),
+            range: vec![IndexRangeExpression::Eq(
+                JOB_ID_FIELD.clone(),
+                maybe_val!(job_id.encode()),
+            )],
+            order: Order::Asc,
+        })
+        .limit(limit);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut items = vec![];
+        while let Some(doc) = query_stream.next(self.tx, Some(limit)).await? {
+            items.push(ParseDocument::<ErasureJobItem>::parse(doc)?);
+        }
+        Ok(items)
+    }
+
+    pub async fn delete_item(&mut self, id: ResolvedDocumentId) -> anyhow::Result<()> {
+        SystemMetadataModel::new_global(self.tx).delete(id).await?;
+        Ok(())
+    }
+}
+
+fn validate_policies(policies: &[ErasurePolicy]) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        policies.len() <= MAX_POLICIES,
+        ErrorMetadata::bad_request(
+            "TooManyErasurePolicies",
+            format!("Deployments can have at most {MAX_POLICIES} erasure policies"),
+        )
+    );
+    let mut tables = BTreeSet::new();
+    for policy in policies {
+        let table = &policy.table_name;
+        anyhow::ensure!(
+            !table.is_system(),
+            ErrorMetadata::bad_request(
+                "InvalidErasurePolicy",
+                format!("Erasure policies can't apply to system table {table}"),
+            )
+        );
+        anyhow::ensure!(
+            tables.insert((policy.component, table)),
+            ErrorMetadata::bad_request(
+                "InvalidErasurePolicy",
+                format!("Table {table} has more than one erasure policy"),
+            )
+        );
+        match &policy.action {
+            ErasureAction::Delete => {},
+            ErasureAction::NullOut { fields } | ErasureAction::Hash { fields } => {
+                anyhow::ensure!(
+                    !fields.is_empty(),
+                    ErrorMetadata::bad_request(
+                        "InvalidErasurePolicy",
+                        format!("The erasure policy for {table} must list at least one field"),
+                    )
+                );
+                for field in fields {
+                    anyhow::ensure!(
+                        !field.fields()[0].starts_with('_'),
+                        ErrorMetadata::bad_request(
+                            "InvalidErasurePolicy",
+                            format!(
+                                "The erasure policy for {table} can't change system field {field}"
+                            ),
+                        )
+                    );
+                }
+            },
+        }
+    }
+    Ok(())
+}
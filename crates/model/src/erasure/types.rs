@@ -0,0 +1,744 @@
+use common::{
+    components::ComponentId,
+    types::{
+        IndexDescriptor,
+        ObjectKey,
+        Timestamp,
+    },
+};
+use keybroker::DataMaskingKey;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::{
+    codegen_convex_serialization,
+    ConvexObject,
+    ConvexValue,
+    DeveloperDocumentId,
+    FieldPath,
+    TableName,
+};
+
+use crate::data_masking::types::{
+    rewrite_at_path,
+    MaskingStrategy,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, strum::AsRefStr, strum::EnumString)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+#[strum(serialize_all = "camelCase")]
+pub enum ErasureActionKind {
+    Delete,
+    NullOut,
+    Hash,
+}
+
+/// What an erasure does to a document that references its subject.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum ErasureAction {
+    Delete,
+    /// Set each of the fields to `null`.
+    NullOut {
+        fields: Vec<FieldPath>,
+    },
+    /// Replace each of the fields with the deployment's keyed hash of its
+    /// value, as the `hash` data masking strategy does.
+    Hash {
+        fields: Vec<FieldPath>,
+    },
+}
+
+impl ErasureAction {
+    pub fn kind(&self) -> ErasureActionKind {
+        match self {
+            Self::Delete => ErasureActionKind::Delete,
+            Self::NullOut { .. } => ErasureActionKind::NullOut,
+            Self::Hash { .. } => ErasureActionKind::Hash,
+        }
+    }
+
+    /// The erased value of a document, or `None` if it should be deleted.
+    /// Fields that are missing, or whose parent is not an object, are left
+    /// untouched.
+    pub fn apply(
+        &self,
+        mut object: ConvexObject,
+        masking_key: &DataMaskingKey,
+    ) -> anyhow::Result<Option<ConvexObject>> {
+        match self {
+            Self::Delete => return Ok(None),
+            Self::NullOut { fields } => {
+                for field in fields {
+                    object = rewrite_at_path(object, field.fields(), &|_| Ok(ConvexValue::Null))?;
+                }
+            },
+            Self::Hash { fields } => {
+                for field in fields {
+                    object = rewrite_at_path(object, field.fields(), &|value| {
+                        MaskingStrategy::Hash.mask_value(&value, masking_key)
+                    })?;
+                }
+            },
+        }
+        Ok(Some(object))
+    }
+
+    /// Whether applying the action removes the original value at `path`.
+    pub fn erases_field(&self, path: &FieldPath) -> bool {
+        match self {
+            Self::Delete => true,
+            Self::NullOut { fields } | Self::Hash { fields } => fields
+                .iter()
+                .any(|field| path.fields().starts_with(field.fields())),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum SerializedErasureAction {
+    Delete,
+    NullOut { fields: Vec<String> },
+    Hash { fields: Vec<String> },
+}
+
+impl From<ErasureAction> for SerializedErasureAction {
+    fn from(action: ErasureAction) -> Self {
+        let serialize_fields =
+            |fields: Vec<FieldPath>| fields.into_iter().map(String::from).collect();
+        match action {
+            ErasureAction::Delete => Self::Delete,
+            ErasureAction::NullOut { fields } => Self::NullOut {
+                fields: serialize_fields(fields),
+            },
+            ErasureAction::Hash { fields } => Self::Hash {
+                fields: serialize_fields(fields),
+            },
+        }
+    }
+}
+
+impl TryFrom<SerializedErasureAction> for ErasureAction {
+    type Error = anyhow::Error;
+
+    fn try_from(action: SerializedErasureAction) -> anyhow::Result<Self> {
+        let parse_fields = |fields: Vec<String>| {
+            fields
+                .into_iter()
+                .map(|field| field.parse())
+                .collect::<anyhow::Result<Vec<FieldPath>>>()
+        };
+        Ok(match action {
+            SerializedErasureAction::Delete => Self::Delete,
+            SerializedErasureAction::NullOut { fields } => Self::NullOut {
+                fields: parse_fields(fields)?,
+            },
+            SerializedErasureAction::Hash { fields } => Self::Hash {
+                fields: parse_fields(fields)?,
+            },
+        })
+    }
+}
+
+/// How erasures treat the documents of one table in one component.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ErasurePolicy {
+    pub component: ComponentId,
+    pub table_name: TableName,
+    pub action: ErasureAction,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedErasurePolicy {
+    /// `None` for the root component.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    component_id: Option<String>,
+    table_name: String,
+    action: SerializedErasureAction,
+}
+
+impl From<ErasurePolicy> for SerializedErasurePolicy {
+    fn from(policy: ErasurePolicy) -> Self {
+        Self {
+            component_id: policy.component.serialize_to_string(),
+            table_name: policy.table_name.to_string(),
+            action: policy.action.into(),
+        }
+    }
+}
+
+impl TryFrom<SerializedErasurePolicy> for ErasurePolicy {
+    type Error = anyhow::Error;
+
+    fn try_from(policy: SerializedErasurePolicy) -> anyhow::Result<Self> {
+        Ok(Self {
+            component: ComponentId::deserialize_from_string(policy.component_id.as_deref())?,
+            table_name: policy.table_name.parse()?,
+            action: policy.action.try_into()?,
+        })
+    }
+}
+
+codegen_convex_serialization!(ErasurePolicy, SerializedErasurePolicy);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, strum::AsRefStr, strum::EnumString)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+#[strum(serialize_all = "camelCase")]
+pub enum SkippedReferenceReason {
+    /// The referencing table has no erasure policy.
+    NoPolicy,
+    /// No index starts with the referencing field, so its documents can't be
+    /// found without scanning the table.
+    NoIndex,
+}
+
+/// A field that can reference the subject but that the erasure didn't
+/// process.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct SkippedReference {
+    pub table_name: TableName,
+    pub field: FieldPath,
+    pub reason: SkippedReferenceReason,
+}
+
+/// The documents of one table that an erasure processed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ErasedTable {
+    pub table_name: TableName,
+    pub action: ErasureActionKind,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=(i64::MAX as u64)")
+    )]
+    pub documents: u64,
+}
+
+/// What the erasure changed in the live data.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ErasureSummary {
+    pub subject_table: TableName,
+    pub tables: Vec<ErasedTable>,
+    pub skipped_references: Vec<SkippedReference>,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=(i64::MAX as u64)")
+    )]
+    pub files_deleted: u64,
+}
+
+/// The completion report of an erasure.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ErasureReport {
+    pub subject_id: DeveloperDocumentId,
+    pub requested_at_ms: i64,
+    pub completed_at_ms: i64,
+    pub summary: ErasureSummary,
+    /// Earlier revisions of erased documents deleted from persistence.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=(i64::MAX as u64)")
+    )]
+    pub revisions_purged: u64,
+}
+
+impl ErasureReport {
+    /// The bytes the report's signature covers.
+    pub fn signed_content(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(serde_json::to_vec(&SerializedErasureReport::from(
+            self.clone(),
+        ))?)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedErasedTable {
+    table_name: String,
+    action: String,
+    documents: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedSkippedReference {
+    table_name: String,
+    field: String,
+    reason: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedErasureSummary {
+    subject_table: String,
+    tables: Vec<SerializedErasedTable>,
+    skipped_references: Vec<SerializedSkippedReference>,
+    files_deleted: i64,
+}
+
+impl From<ErasureSummary> for SerializedErasureSummary {
+    fn from(summary: ErasureSummary) -> Self {
+        Self {
+            subject_table: summary.subject_table.to_string(),
+            tables: summary
+                .tables
+                .into_iter()
+                .map(|table| SerializedErasedTable {
+                    table_name: table.table_name.to_string(),
+                    action: table.action.as_ref().to_string(),
+                    documents: table.documents as i64,
+                })
+                .collect(),
+            skipped_references: summary
+                .skipped_references
+                .into_iter()
+                .map(|reference| SerializedSkippedReference {
+                    table_name: reference.table_name.to_string(),
+                    field: reference.field.into(),
+                    reason: reference.reason.as_ref().to_string(),
+                })
+                .collect(),
+            files_deleted: summary.files_deleted as i64,
+        }
+    }
+}
+
+impl TryFrom<SerializedErasureSummary> for ErasureSummary {
+    type Error = anyhow::Error;
+
+    fn try_from(summary: SerializedErasureSummary) -> anyhow::Result<Self> {
+        Ok(Self {
+            subject_table: summary.subject_table.parse()?,
+            tables: summary
+                .tables
+                .into_iter()
+                .map(|table| {
+                    anyhow::Ok(ErasedTable {
+                        table_name: table.table_name.parse()?,
+                        action: table.action.parse()?,
+                        documents: table.documents.try_into()?,
+                    })
+                })
+                .collect::<anyhow::Result<_>>()?,
+            skipped_references: summary
+                .skipped_references
+                .into_iter()
+                .map(|reference| {
+                    anyhow::Ok(SkippedReference {
+                        table_name: reference.table_name.parse()?,
+                        field: reference.field.parse()?,
+                        reason: reference.reason.parse()?,
+                    })
+                })
+                .collect::<anyhow::Result<_>>()?,
+            files_deleted: summary.files_deleted.try_into()?,
+        })
+    }
+}
+
+/// The report's fields are serialized in a fixed order, so serializing a
+/// report always produces the same bytes.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedErasureReport {
+    subject_id: String,
+    requested_at_ms: i64,
+    completed_at_ms: i64,
+    summary: SerializedErasureSummary,
+    revisions_purged: i64,
+}
+
+impl From<ErasureReport> for SerializedErasureReport {
+    fn from(report: ErasureReport) -> Self {
+        Self {
+            subject_id: report.subject_id.encode(),
+            requested_at_ms: report.requested_at_ms,
+            completed_at_ms: report.completed_at_ms,
+            summary: report.summary.into(),
+            revisions_purged: report.revisions_purged as i64,
+        }
+    }
+}
+
+impl TryFrom<SerializedErasureReport> for ErasureReport {
+    type Error = anyhow::Error;
+
+    fn try_from(report: SerializedErasureReport) -> anyhow::Result<Self> {
+        Ok(Self {
+            subject_id: DeveloperDocumentId::decode(&report.subject_id)?,
+            requested_at_ms: report.requested_at_ms,
+            completed_at_ms: report.completed_at_ms,
+            summary: report.summary.try_into()?,
+            revisions_purged: report.revisions_purged.try_into()?,
+        })
+    }
+}
+
+/// A field that can reference the subject, with the index used to find the
+/// documents that do.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ErasureTarget {
+    pub table_name: TableName,
+    pub field: FieldPath,
+    pub index_descriptor: IndexDescriptor,
+    pub action: ErasureAction,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedErasureTarget {
+    table_name: String,
+    field: String,
+    index_descriptor: String,
+    action: SerializedErasureAction,
+}
+
+impl From<ErasureTarget> for SerializedErasureTarget {
+    fn from(target: ErasureTarget) -> Self {
+        Self {
+            table_name: target.table_name.to_string(),
+            field: target.field.into(),
+            index_descriptor: target.index_descriptor.into(),
+            action: target.action.into(),
+        }
+    }
+}
+
+impl TryFrom<SerializedErasureTarget> for ErasureTarget {
+    type Error = anyhow::Error;
+
+    fn try_from(target: SerializedErasureTarget) -> anyhow::Result<Self> {
+        Ok(Self {
+            table_name: target.table_name.parse()?,
+            field: target.field.parse()?,
+            index_descriptor: IndexDescriptor::new(target.index_descriptor)?,
+            action: target.action.try_into()?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum ErasureJobState {
+    Requested,
+    /// The live data is being erased in batches, each in its own transaction:
+    /// the documents found through the first of `targets`, starting after
+    /// `cursor`, and once no targets remain, the subject. Every erased
+    /// document is recorded as an `ErasureJobItem`.
+    Erasing {
+        summary: ErasureSummary,
+        subject_action: ErasureAction,
+        targets: Vec<ErasureTarget>,
+        /// Encrypted query cursor into the first target.
+        cursor: Option<String>,
+    },
+    /// The live data was erased by `erased_ts`. What remains is deleting the
+    /// content of the erased files and the earlier revisions of the erased
+    /// documents once no snapshot before `erased_ts` can be read.
+    Purging {
+        summary: ErasureSummary,
+        erased_ts: Timestamp,
+        #[cfg_attr(
+            any(test, feature = "testing"),
+            proptest(strategy = "0..=(i64::MAX as u64)")
+        )]
+        revisions_purged: u64,
+    },
+    Completed {
+        report: ErasureReport,
+        /// Hex HMAC-SHA256 of the report's signed content, keyed by the
+        /// deployment's instance secret.
+        signature: String,
+    },
+    Failed {
+        error: String,
+    },
+}
+
+impl ErasureJobState {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Requested => "requested",
+            Self::Erasing { .. } => "erasing",
+            Self::Purging { .. } => "purging",
+            Self::Completed { .. } => "completed",
+            Self::Failed { .. } => "failed",
+        }
+    }
+}
+
+/// A request to erase a subject document and the data referencing it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ErasureJob {
+    /// The component whose tables the subject and the erased data are in.
+    pub component: ComponentId,
+    pub subject_id: DeveloperDocumentId,
+    pub requested_at_ms: i64,
+    pub state: ErasureJobState,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedErasureJob {
+    component_id: Option<String>,
+    subject_id: String,
+    requested_at_ms: i64,
+    state: String,
+    summary: Option<SerializedErasureSummary>,
+    subject_action: Option<SerializedErasureAction>,
+    targets: Option<Vec<SerializedErasureTarget>>,
+    cursor: Option<String>,
+    erased_ts: Option<i64>,
+    revisions_purged: Option<i64>,
+    report: Option<SerializedErasureReport>,
+    signature: Option<String>,
+    error: Option<String>,
+}
+
+impl From<ErasureJob> for SerializedErasureJob {
+    fn from(job: ErasureJob) -> Self {
+        let mut serialized = Self {
+            component_id: job.component.serialize_to_string(),
+            subject_id: job.subject_id.encode(),
+            requested_at_ms: job.requested_at_ms,
+            state: job.state.name().to_string(),
+            summary: None,
+            subject_action: None,
+            targets: None,
+            cursor: None,
+            erased_ts: None,
+            revisions_purged: None,
+            report: None,
+            signature: None,
+            error: None,
+        };
+        match job.state {
+            ErasureJobState::Requested => {},
+            ErasureJobState::Erasing {
+                summary,
+                subject_action,
+                targets,
+                cursor,
+            } => {
+                serialized.summary = Some(summary.into());
+                serialized.subject_action = Some(subject_action.into());
+                serialized.targets = Some(targets.into_iter().map(Into::into).collect());
+                serialized.cursor = cursor;
+            },
+            ErasureJobState::Purging {
+                summary,
+                erased_ts,
+                revisions_purged,
+            } => {
+                serialized.summary = Some(summary.into());
+                serialized.erased_ts = Some(erased_ts.into());
+                serialized.revisions_purged = Some(revisions_purged as i64);
+            },
+            ErasureJobState::Completed { report, signature } => {
+                serialized.report = Some(report.into());
+                serialized.signature = Some(signature);
+            },
+            ErasureJobState::Failed { error } => {
+                serialized.error = Some(error);
+            },
+        }
+        serialized
+    }
+}
+
+impl TryFrom<SerializedErasureJob> for ErasureJob {
+    type Error = anyhow::Error;
+
+    fn try_from(job: SerializedErasureJob) -> anyhow::Result<Self> {
+        let state = match &job.state[..] {
+            "requested" => ErasureJobState::Requested,
+            "erasing" => ErasureJobState::Erasing {
+                summary: job
+                    .summary
+                    .ok_or_else(|| anyhow::anyhow!("Erasing erasure job missing summary"))?
+                    .try_into()?,
+                subject_action: job
+                    .subject_action
+                    .ok_or_else(|| anyhow::anyhow!("Erasing erasure job missing subject action"))?
+                    .try_into()?,
+                targets: job
+                    .targets
+                    .ok_or_else(|| anyhow::anyhow!("Erasing erasure job missing targets"))?
+                    .into_iter()
+                    .map(ErasureTarget::try_from)
+                    .collect::<anyhow::Result<_>>()?,
+                cursor: job.cursor,
+            },
+            "purging" => ErasureJobState::Purging {
+                summary: job
+                    .summary
+                    .ok_or_else(|| anyhow::anyhow!("Purging erasure job missing summary"))?
+                    .try_into()?,
+                erased_ts: job
+                    .erased_ts
+                    .ok_or_else(|| anyhow::anyhow!("Purging erasure job missing erased_ts"))?
+                    .try_into()?,
+                revisions_purged: job
+                    .revisions_purged
+                    .ok_or_else(|| anyhow::anyhow!("Purging erasure job missing revisions_purged"))?
+                    .try_into()?,
+            },
+            "completed" => ErasureJobState::Completed {
+                report: job
+                    .report
+                    .ok_or_else(|| anyhow::anyhow!("Completed erasure job missing report"))?
+                    .try_into()?,
+                signature: job
+                    .signature
+                    .ok_or_else(|| anyhow::anyhow!("Completed erasure job missing signature"))?,
+            },
+            "failed" => ErasureJobState::Failed {
+                error: job
+                    .error
+                    .ok_or_else(|| anyhow::anyhow!("Failed erasure job missing error"))?,
+            },
+            state => anyhow::bail!("Invalid erasure job state {state}"),
+        };
+        Ok(Self {
+            component: ComponentId::deserialize_from_string(job.component_id.as_deref())?,
+            subject_id: DeveloperDocumentId::decode(&job.subject_id)?,
+            requested_at_ms: job.requested_at_ms,
+            state,
+        })
+    }
+}
+
+codegen_convex_serialization!(ErasureJob, SerializedErasureJob);
+
+/// A document an erasure job changed, kept until the job has purged its
+/// earlier revisions. Erasing a `_storage` entry also records the file's
+/// `object_key`, whose content is deleted with the revisions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ErasureJobItem {
+    pub job_id: DeveloperDocumentId,
+    pub document_id: DeveloperDocumentId,
+    pub object_key: Option<ObjectKey>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedErasureJobItem {
+    job_id: String,
+    document_id: String,
+    object_key: Option<String>,
+}
+
+impl From<ErasureJobItem> for SerializedErasureJobItem {
+    fn from(item: ErasureJobItem) -> Self {
+        Self {
+            job_id: item.job_id.encode(),
+            document_id: item.document_id.encode(),
+            object_key: item.object_key.map(String::from),
+        }
+    }
+}
+
+impl TryFrom<SerializedErasureJobItem> for ErasureJobItem {
+    type Error = anyhow::Error;
+
+    fn try_from(item: SerializedErasureJobItem) -> anyhow::Result<Self> {
+        Ok(Self {
+            job_id: DeveloperDocumentId::decode(&item.job_id)?,
+            document_id: DeveloperDocumentId::decode(&item.document_id)?,
+            object_key: item.object_key.map(ObjectKey::try_from).transpose()?,
+        })
+    }
+}
+
+codegen_convex_serialization!(ErasureJobItem, SerializedErasureJobItem);
+
+#[cfg(test)]
+mod tests {
+    use cmd_util::env::env_config;
+    use keybroker::KeyBroker;
+    use proptest::prelude::*;
+    use sync_types::testing::assert_roundtrips;
+    use value::{
+        assert_obj,
+        ConvexObject,
+        ConvexValue,
+    };
+
+    use super::{
+        ErasureAction,
+        ErasureJob,
+        ErasureJobItem,
+        ErasurePolicy,
+    };
+
+    proptest! {
+        #![proptest_config(
+            ProptestConfig { cases: 256 * env_config("CONVEX_PROPTEST_MULTIPLIER", 1), failure_persistence: None, ..ProptestConfig::default() }
+        )]
+
+        #[test]
+        fn test_policy_roundtrips(v in any::<ErasurePolicy>()) {
+            assert_roundtrips::<ErasurePolicy, ConvexObject>(v);
+        }
+
+        #[test]
+        fn test_job_roundtrips(v in any::<ErasureJob>()) {
+            assert_roundtrips::<ErasureJob, ConvexObject>(v);
+        }
+
+        #[test]
+        fn test_job_item_roundtrips(v in any::<ErasureJobItem>()) {
+            assert_roundtrips::<ErasureJobItem, ConvexObject>(v);
+        }
+    }
+
+    #[test]
+    fn test_apply_action() -> anyhow::Result<()> {
+        let key = KeyBroker::dev().data_masking_key();
+        let document = assert_obj!(
+            "name" => "Ada",
+            "contact" => { "email" => "ada@example.com", "city" => "London" },
+        );
+        assert_eq!(ErasureAction::Delete.apply(document.clone(), &key)?, None);
+
+        let null_out = ErasureAction::NullOut {
+            fields: vec!["name".parse()?, "contact.email".parse()?],
+        };
+        assert_eq!(
+            null_out.apply(document.clone(), &key)?,
+            Some(assert_obj!(
+                "name" => null,
+                "contact" => { "email" => null, "city" => "London" },
+            ))
+        );
+        assert!(null_out.erases_field(&"contact.email".parse()?));
+        assert!(!null_out.erases_field(&"contact.city".parse()?));
+
+        let hash = ErasureAction::Hash {
+            fields: vec!["contact".parse()?],
+        };
+        let Some(hashed) = hash.apply(document, &key)? else {
+            anyhow::bail!("Hashing shouldn't delete the document");
+        };
+        assert_eq!(
+            hashed.get("name"),
+            Some(&ConvexValue::try_from("Ada".to_string())?)
+        );
+        assert!(matches!(
+            hashed.get("contact"),
+            Some(ConvexValue::String(_))
+        ));
+        assert!(hash.erases_field(&"contact.email".parse()?));
+        Ok(())
+    }
+}
@@ -0,0 +1,154 @@
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+        CREATION_TIME_FIELD_PATH,
+    },
+    maybe_val,
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+};
+use database::{
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use value::{
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::{
+    ErrorGroup,
+    ErrorOccurrence,
+};
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static ERROR_GROUPS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_error_groups"
+        .parse()
+        .expect("Invalid built-in error groups table")
+});
+
+static FINGERPRINT_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "fingerprint".parse().expect("invalid fingerprint field"));
+
+static LAST_SEEN_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "lastSeenMs".parse().expect("invalid lastSeenMs field"));
+
+pub static ERROR_GROUPS_BY_FINGERPRINT_INDEX: LazyLock<SystemIndex<ErrorGroupsTable>> =
+    LazyLock::new(|| {
+        SystemIndex::new(
+            "by_fingerprint",
+            [&FINGERPRINT_FIELD, &CREATION_TIME_FIELD_PATH],
+        )
+        .unwrap()
+    });
+
+pub static ERROR_GROUPS_BY_LAST_SEEN_INDEX: LazyLock<SystemIndex<ErrorGroupsTable>> =
+    LazyLock::new(|| {
+        SystemIndex::new(
+            "by_last_seen",
+            [&LAST_SEEN_FIELD, &CREATION_TIME_FIELD_PATH],
+        )
+        .unwrap()
+    });
+
+pub struct ErrorGroupsTable;
+impl SystemTable for ErrorGroupsTable {
+    type Metadata = ErrorGroup;
+
+    fn table_name() -> &'static TableName {
+        &ERROR_GROUPS_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![
+            ERROR_GROUPS_BY_FINGERPRINT_INDEX.clone(),
+            ERROR_GROUPS_BY_LAST_SEEN_INDEX.clone(),
+        ]
+    }
+}
+
+pub struct ErrorGroupsModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> ErrorGroupsModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    /// Adds `occurrence` to the group with its fingerprint, creating the group
+    /// if this is the first time it's been seen.
+    pub async fn record(&mut self, occurrence: ErrorOccurrence) -> anyhow::Result<()> {
+        match self.get(&occurrence.fingerprint).await? {
+            Some(existing) => {
+                let id = existing.id();
+                let mut group = existing.into_value();
+                group.add(occurrence.sample);
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(id, group.try_into()?)
+                    .await?;
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&ERROR_GROUPS_TABLE, ErrorGroup::new(occurrence).try_into()?)
+                    .await?;
+            },
+        }
+        Ok(())
+    }
+
+    pub async fn get(
+        &mut self,
+        fingerprint: &str,
+    ) -> anyhow::Result<Option<ParsedDocument<ErrorGroup>>> {
+        let query = Query::index_range(IndexRange {
+            index_name: ERROR_GROUPS_BY_FINGERPRINT_INDEX.name(),
+            range: vec![IndexRangeExpression::Eq(
+                FINGERPRINT_FIELD.clone(),
+                maybe_val!(fingerprint.to_string()),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(|doc| doc.parse())
+            .transpose()
+    }
+
+    /// Groups that most recently had an occurrence, newest first.
+    pub async fn list_recent(&mut self, limit: usize) -> anyhow::Result<Vec<ResolvedDocument>> {
+        self.tx.require_admin("list_error_groups")?;
+        let query = Query::index_range(IndexRange {
+            index_name: ERROR_GROUPS_BY_LAST_SEEN_INDEX.name(),
+            range: vec![],
+            order: Order::Desc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut result = vec![];
+        while result.len() < limit {
+            let Some(doc) = query_stream.next(self.tx, None).await? else {
+                break;
+            };
+            result.push(doc);
+        }
+        Ok(result)
+    }
+}
@@ -0,0 +1,193 @@
+use common::components::ComponentPath;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// Number of recent occurrences kept on each group.
+pub const MAX_ERROR_SAMPLES: usize = 5;
+
+/// Uncaught exceptions from one function that share a fingerprint, as
+/// recorded in `_error_groups`. See `JsError::fingerprint`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ErrorGroup {
+    pub fingerprint: String,
+    pub component_path: ComponentPath,
+    pub udf_path: String,
+    /// The most recent occurrence's message.
+    pub message: String,
+    pub count: i64,
+    pub first_seen_ms: i64,
+    pub last_seen_ms: i64,
+    /// Up to `MAX_ERROR_SAMPLES` of the most recent occurrences, oldest first.
+    pub samples: Vec<ErrorSample>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ErrorSample {
+    pub timestamp_ms: i64,
+    pub request_id: String,
+    pub message: String,
+    /// The source-mapped stack trace, one frame per line.
+    pub stack: String,
+}
+
+/// A single uncaught exception, before it's added to its group.
+#[derive(Clone, Debug)]
+pub struct ErrorOccurrence {
+    pub fingerprint: String,
+    pub component_path: ComponentPath,
+    pub udf_path: String,
+    pub sample: ErrorSample,
+}
+
+impl ErrorGroup {
+    pub fn new(occurrence: ErrorOccurrence) -> Self {
+        Self {
+            fingerprint: occurrence.fingerprint,
+            component_path: occurrence.component_path,
+            udf_path: occurrence.udf_path,
+            message: occurrence.sample.message.clone(),
+            count: 1,
+            first_seen_ms: occurrence.sample.timestamp_ms,
+            last_seen_ms: occurrence.sample.timestamp_ms,
+            samples: vec![occurrence.sample],
+        }
+    }
+
+    /// Counts another occurrence, keeping it as a sample and dropping the
+    /// oldest sample if there are too many.
+    pub fn add(&mut self, sample: ErrorSample) {
+        self.count += 1;
+        self.first_seen_ms = self.first_seen_ms.min(sample.timestamp_ms);
+        if sample.timestamp_ms >= self.last_seen_ms {
+            self.last_seen_ms = sample.timestamp_ms;
+            self.message = sample.message.clone();
+        }
+        self.samples.push(sample);
+        if self.samples.len() > MAX_ERROR_SAMPLES {
+            self.samples.remove(0);
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedErrorSample {
+    timestamp_ms: i64,
+    request_id: String,
+    message: String,
+    stack: String,
+}
+
+impl From<ErrorSample> for SerializedErrorSample {
+    fn from(value: ErrorSample) -> Self {
+        Self {
+            timestamp_ms: value.timestamp_ms,
+            request_id: value.request_id,
+            message: value.message,
+            stack: value.stack,
+        }
+    }
+}
+
+impl From<SerializedErrorSample> for ErrorSample {
+    fn from(value: SerializedErrorSample) -> Self {
+        Self {
+            timestamp_ms: value.timestamp_ms,
+            request_id: value.request_id,
+            message: value.message,
+            stack: value.stack,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedErrorGroup {
+    fingerprint: String,
+    component_path: String,
+    udf_path: String,
+    message: String,
+    count: i64,
+    first_seen_ms: i64,
+    last_seen_ms: i64,
+    samples: Vec<SerializedErrorSample>,
+}
+
+impl From<ErrorGroup> for SerializedErrorGroup {
+    fn from(value: ErrorGroup) -> Self {
+        Self {
+            fingerprint: value.fingerprint,
+            component_path: value.component_path.into(),
+            udf_path: value.udf_path,
+            message: value.message,
+            count: value.count,
+            first_seen_ms: value.first_seen_ms,
+            last_seen_ms: value.last_seen_ms,
+            samples: value.samples.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl TryFrom<SerializedErrorGroup> for ErrorGroup {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedErrorGroup) -> Result<Self, Self::Error> {
+        Ok(Self {
+            fingerprint: value.fingerprint,
+            component_path: value.component_path.parse()?,
+            udf_path: value.udf_path,
+            message: value.message,
+            count: value.count,
+            first_seen_ms: value.first_seen_ms,
+            last_seen_ms: value.last_seen_ms,
+            samples: value.samples.into_iter().map(Into::into).collect(),
+        })
+    }
+}
+
+codegen_convex_serialization!(ErrorGroup, SerializedErrorGroup);
+
+#[cfg(test)]
+mod tests {
+    use common::components::ComponentPath;
+
+    use super::{
+        ErrorGroup,
+        ErrorOccurrence,
+        ErrorSample,
+        MAX_ERROR_SAMPLES,
+    };
+
+    fn sample(timestamp_ms: i64) -> ErrorSample {
+        ErrorSample {
+            timestamp_ms,
+            request_id: format!("request-{timestamp_ms}"),
+            message: format!("Uncaught Error: failed at {timestamp_ms}"),
+            stack: "    at handler (../convex/messages.ts:10:1)".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_error_group_keeps_recent_samples() {
+        let mut group = ErrorGroup::new(ErrorOccurrence {
+            fingerprint: "abc".to_string(),
+            component_path: ComponentPath::root(),
+            udf_path: "messages:send".to_string(),
+            sample: sample(0),
+        });
+        for timestamp_ms in 1..10 {
+            group.add(sample(timestamp_ms));
+        }
+        assert_eq!(group.count, 10);
+        assert_eq!(group.first_seen_ms, 0);
+        assert_eq!(group.last_seen_ms, 9);
+        assert_eq!(group.message, "Uncaught Error: failed at 9");
+        assert_eq!(group.samples.len(), MAX_ERROR_SAMPLES);
+        assert_eq!(group.samples[0].timestamp_ms, 5);
+    }
+}
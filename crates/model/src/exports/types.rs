@@ -356,6 +356,9 @@ pub enum ExportRequestor {
     SnapshotExport,
     /// The team-level cloud backup feature
     CloudBackup,
+    /// Seeding a preview deployment with this deployment's data. Masked like
+    /// a snapshot export.
+    PreviewDeploymentClone,
 }
 
 impl ExportRequestor {
@@ -363,6 +366,7 @@ impl ExportRequestor {
         match self {
             Self::SnapshotExport => "snapshot_export",
             Self::CloudBackup => "cloud_backup",
+            Self::PreviewDeploymentClone => "preview_deployment_clone",
         }
     }
 }
@@ -0,0 +1,271 @@
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParseDocument,
+        ParsedDocument,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+};
+use database::{
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use value::{
+    ConvexValue,
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::{
+    FeatureFlag,
+    FeatureFlagRule,
+};
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static FEATURE_FLAGS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_feature_flags"
+        .parse()
+        .expect("Invalid built-in feature_flags table")
+});
+
+pub static FEATURE_FLAGS_INDEX_BY_NAME: LazyLock<SystemIndex<FeatureFlagsTable>> =
+    LazyLock::new(|| SystemIndex::new("by_name", [&NAME_FIELD]).unwrap());
+static NAME_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "name".parse().expect("invalid name field"));
+
+const MAX_FEATURE_FLAG_NAME_LEN: usize = 128;
+
+pub struct FeatureFlagsTable;
+impl SystemTable for FeatureFlagsTable {
+    type Metadata = FeatureFlag;
+
+    fn table_name() -> &'static TableName {
+        &FEATURE_FLAGS_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![FEATURE_FLAGS_INDEX_BY_NAME.clone()]
+    }
+}
+
+pub struct FeatureFlagsModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> FeatureFlagsModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    pub async fn get(&mut self, name: &str) -> anyhow::Result<Option<ParsedDocument<FeatureFlag>>> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&FEATURE_FLAGS_TABLE) {
+            return Ok(None);
+        }
+        let query = Query::index_range(IndexRange {
+            index_name: FEATURE_FLAGS_INDEX_BY_NAME.name(),
+            range: vec![IndexRangeExpression::Eq(
+                NAME_FIELD.clone(),
+                ConvexValue::try_from(name.to_string())?.into(),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParseDocument::<FeatureFlag>::parse)
+            .transpose()
+    }
+
+    /// All of the deployment's flags, ordered by name.
+    pub async fn list(&mut self) -> anyhow::Result<Vec<ParsedDocument<FeatureFlag>>> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&FEATURE_FLAGS_TABLE) {
+            return Ok(vec![]);
+        }
+        let query = Query::index_range(IndexRange {
+            index_name: FEATURE_FLAGS_INDEX_BY_NAME.name(),
+            range: vec![],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut flags = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            flags.push(doc.parse()?);
+        }
+        Ok(flags)
+    }
+
+    /// Creates the flag or changes its rule.
+    pub async fn set(
+        &mut self,
+        name: String,
+        rule: FeatureFlagRule,
+    ) -> anyhow::Result<FeatureFlag> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("set_feature_flag"));
+        }
+        validate_feature_flag(&name, &rule)?;
+        let flag = FeatureFlag {
+            name,
+            rule,
+            updated_at_ms: self.tx.runtime().unix_timestamp().as_ms_since_epoch()? as i64,
+        };
+        match self.get(&flag.name).await? {
+            Some(existing) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), flag.clone().try_into()?)
+                    .await?;
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&FEATURE_FLAGS_TABLE, flag.clone().try_into()?)
+                    .await?;
+            },
+        }
+        Ok(flag)
+    }
+
+    /// Deletes the flag, which turns it off everywhere. Returns whether it
+    /// existed.
+    pub async fn delete(&mut self, name: &str) -> anyhow::Result<bool> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("delete_feature_flag"));
+        }
+        let Some(existing) = self.get(name).await? else {
+            return Ok(false);
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .delete(existing.id())
+            .await?;
+        Ok(true)
+    }
+}
+
+fn validate_feature_flag(name: &str, rule: &FeatureFlagRule) -> anyhow::Result<()> {
+    let valid_name = !name.is_empty()
+        && name.len() <= MAX_FEATURE_FLAG_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | ':'));
+    if !valid_name {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "InvalidFeatureFlagName",
+            format!(
+                "Feature flag name {name:?} must be 1 to {MAX_FEATURE_FLAG_NAME_LEN} letters, \
+                 digits, '_', '-', '.' or ':'."
+            )
+        ));
+    }
+    match rule {
+        FeatureFlagRule::Boolean { .. } => {},
+        FeatureFlagRule::Percentage { percent } => {
+            if *percent > 100 {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "InvalidFeatureFlagRule",
+                    format!("Feature flag percentage must be between 0 and 100, not {percent}.")
+                ));
+            }
+        },
+        FeatureFlagRule::Targeted { claim, .. } => {
+            if claim.is_empty() {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "InvalidFeatureFlagRule",
+                    "Targeted feature flags need a claim to match on."
+                ));
+            }
+        },
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use database::test_helpers::DbFixtures;
+    use errors::ErrorMetadata;
+    use runtime::testing::TestRuntime;
+
+    use crate::{
+        feature_flags::{
+            types::FeatureFlagRule,
+            FeatureFlagsModel,
+        },
+        test_helpers::DbFixturesWithModel,
+    };
+
+    #[convex_macro::test_runtime]
+    async fn test_set_and_delete_feature_flags(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new_with_model(&rt).await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = FeatureFlagsModel::new(&mut tx);
+        assert!(model.get("beta").await?.is_none());
+
+        let err = model
+            .set(
+                "not a name".to_string(),
+                FeatureFlagRule::Boolean { enabled: true },
+            )
+            .await
+            .unwrap_err();
+        let err = err.downcast_ref::<ErrorMetadata>().unwrap();
+        assert_eq!(err.short_msg, "InvalidFeatureFlagName");
+        let err = model
+            .set(
+                "beta".to_string(),
+                FeatureFlagRule::Percentage { percent: 101 },
+            )
+            .await
+            .unwrap_err();
+        let err = err.downcast_ref::<ErrorMetadata>().unwrap();
+        assert_eq!(err.short_msg, "InvalidFeatureFlagRule");
+
+        model
+            .set(
+                "beta".to_string(),
+                FeatureFlagRule::Boolean { enabled: true },
+            )
+            .await?;
+        model
+            .set(
+                "alpha".to_string(),
+                FeatureFlagRule::Boolean { enabled: false },
+            )
+            .await?;
+        assert!(model.get("beta").await?.unwrap().evaluate(None));
+        assert!(!model.get("alpha").await?.unwrap().evaluate(None));
+        model
+            .set(
+                "beta".to_string(),
+                FeatureFlagRule::Boolean { enabled: false },
+            )
+            .await?;
+        assert!(!model.get("beta").await?.unwrap().evaluate(None));
+        let names: Vec<_> = model
+            .list()
+            .await?
+            .into_iter()
+            .map(|flag| flag.into_value().name)
+            .collect();
+        assert_eq!(names, vec!["alpha".to_string(), "beta".to_string()]);
+
+        assert!(model.delete("beta").await?);
+        assert!(!model.delete("beta").await?);
+        assert!(model.get("beta").await?.is_none());
+        Ok(())
+    }
+}
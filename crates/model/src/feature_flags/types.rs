@@ -0,0 +1,221 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_json::Value as JsonValue;
+use sync_types::UserIdentityAttributes;
+use value::{
+    codegen_convex_serialization,
+    sha256::Sha256,
+};
+
+/// Who a feature flag is on for.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum FeatureFlagRule {
+    /// On or off for everyone.
+    Boolean { enabled: bool },
+    /// On for a stable share of users, bucketed by their token identifier.
+    /// Calls without a user identity only see the flag on at 100%.
+    Percentage {
+        #[cfg_attr(any(test, feature = "testing"), proptest(strategy = "0..=100u32"))]
+        percent: u32,
+    },
+    /// On for users whose identity has `claim` set to one of `values`. Claims
+    /// are named as they are on `ctx.auth.getUserIdentity()`, e.g. `email` or
+    /// a custom claim, and array claims match if any element does.
+    Targeted { claim: String, values: Vec<String> },
+}
+
+impl FeatureFlagRule {
+    /// Whether evaluating the rule looks at the caller's identity, so results
+    /// can't be shared between users.
+    pub fn depends_on_identity(&self) -> bool {
+        !matches!(self, Self::Boolean { .. })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct FeatureFlag {
+    pub name: String,
+    pub rule: FeatureFlagRule,
+    pub updated_at_ms: i64,
+}
+
+impl FeatureFlag {
+    /// Whether the flag is on for a call made by `user`.
+    pub fn evaluate(&self, user: Option<&UserIdentityAttributes>) -> bool {
+        match &self.rule {
+            FeatureFlagRule::Boolean { enabled } => *enabled,
+            FeatureFlagRule::Percentage { percent } => {
+                if *percent >= 100 {
+                    return true;
+                }
+                let Some(user) = user else {
+                    return false;
+                };
+                // Hash the flag name in too so each flag rolls out to a
+                // different set of users.
+                let key = format!("{}|{}", self.name, user.token_identifier.0);
+                let digest = Sha256::hash(key.as_bytes());
+                let bucket =
+                    u64::from_le_bytes(digest[..8].try_into().expect("Digest too short")) % 100;
+                bucket < *percent as u64
+            },
+            FeatureFlagRule::Targeted { claim, values } => {
+                let Some(user) = user else {
+                    return false;
+                };
+                let Ok(JsonValue::Object(mut identity)) = JsonValue::try_from(user.clone()) else {
+                    return false;
+                };
+                let claim_values = match identity.remove(claim) {
+                    Some(JsonValue::Array(elements)) => elements,
+                    Some(value) => vec![value],
+                    None => vec![],
+                };
+                claim_values.into_iter().any(|value| {
+                    let value = match value {
+                        JsonValue::String(s) => s,
+                        value => value.to_string(),
+                    };
+                    values.contains(&value)
+                })
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SerializedFeatureFlagRule {
+    Boolean { enabled: bool },
+    Percentage { percent: i64 },
+    Targeted { claim: String, values: Vec<String> },
+}
+
+impl From<FeatureFlagRule> for SerializedFeatureFlagRule {
+    fn from(rule: FeatureFlagRule) -> Self {
+        match rule {
+            FeatureFlagRule::Boolean { enabled } => Self::Boolean { enabled },
+            FeatureFlagRule::Percentage { percent } => Self::Percentage {
+                percent: percent.into(),
+            },
+            FeatureFlagRule::Targeted { claim, values } => Self::Targeted { claim, values },
+        }
+    }
+}
+
+impl TryFrom<SerializedFeatureFlagRule> for FeatureFlagRule {
+    type Error = anyhow::Error;
+
+    fn try_from(rule: SerializedFeatureFlagRule) -> anyhow::Result<Self> {
+        Ok(match rule {
+            SerializedFeatureFlagRule::Boolean { enabled } => Self::Boolean { enabled },
+            SerializedFeatureFlagRule::Percentage { percent } => Self::Percentage {
+                percent: percent.try_into()?,
+            },
+            SerializedFeatureFlagRule::Targeted { claim, values } => {
+                Self::Targeted { claim, values }
+            },
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedFeatureFlag {
+    name: String,
+    rule: SerializedFeatureFlagRule,
+    updated_at_ms: i64,
+}
+
+impl From<FeatureFlag> for SerializedFeatureFlag {
+    fn from(flag: FeatureFlag) -> Self {
+        Self {
+            name: flag.name,
+            rule: flag.rule.into(),
+            updated_at_ms: flag.updated_at_ms,
+        }
+    }
+}
+
+impl TryFrom<SerializedFeatureFlag> for FeatureFlag {
+    type Error = anyhow::Error;
+
+    fn try_from(flag: SerializedFeatureFlag) -> anyhow::Result<Self> {
+        Ok(Self {
+            name: flag.name,
+            rule: flag.rule.try_into()?,
+            updated_at_ms: flag.updated_at_ms,
+        })
+    }
+}
+
+codegen_convex_serialization!(FeatureFlag, SerializedFeatureFlag);
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use sync_types::{
+        UserIdentifier,
+        UserIdentityAttributes,
+    };
+
+    use super::{
+        FeatureFlag,
+        FeatureFlagRule,
+    };
+
+    fn flag(rule: FeatureFlagRule) -> FeatureFlag {
+        FeatureFlag {
+            name: "new_checkout".to_string(),
+            rule,
+            updated_at_ms: 0,
+        }
+    }
+
+    fn user(subject: &str) -> UserIdentityAttributes {
+        UserIdentityAttributes {
+            token_identifier: UserIdentifier::construct("https://issuer.example", subject),
+            subject: Some(subject.to_string()),
+            email: Some(format!("{subject}@example.com")),
+            custom_claims: BTreeMap::from([(
+                "teams".to_string(),
+                r#"["billing","growth"]"#.to_string(),
+            )]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_evaluate_feature_flag() {
+        let alice = user("alice");
+        assert!(flag(FeatureFlagRule::Boolean { enabled: true }).evaluate(None));
+        assert!(!flag(FeatureFlagRule::Boolean { enabled: false }).evaluate(Some(&alice)));
+
+        assert!(flag(FeatureFlagRule::Percentage { percent: 100 }).evaluate(None));
+        assert!(!flag(FeatureFlagRule::Percentage { percent: 50 }).evaluate(None));
+        assert!(!flag(FeatureFlagRule::Percentage { percent: 0 }).evaluate(Some(&alice)));
+        let half = flag(FeatureFlagRule::Percentage { percent: 50 });
+        let users: Vec<_> = (0..200).map(|i| user(&format!("user{i}"))).collect();
+        let enabled = users.iter().filter(|u| half.evaluate(Some(u))).count();
+        assert!(enabled > 0 && enabled < users.len());
+
+        let by_email = flag(FeatureFlagRule::Targeted {
+            claim: "email".to_string(),
+            values: vec!["alice@example.com".to_string()],
+        });
+        assert!(by_email.evaluate(Some(&alice)));
+        assert!(!by_email.evaluate(Some(&user("bob"))));
+        assert!(!by_email.evaluate(None));
+
+        let by_team = flag(FeatureFlagRule::Targeted {
+            claim: "teams".to_string(),
+            values: vec!["growth".to_string()],
+        });
+        assert!(by_team.evaluate(Some(&alice)));
+    }
+}
@@ -0,0 +1,172 @@
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ResolvedDocument,
+        CREATION_TIME_FIELD_PATH,
+    },
+    maybe_val,
+    query::{
+        Expression,
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+};
+use database::{
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use value::{
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::FunctionRun;
+use crate::{
+    log_search::{
+        function_run_text,
+        types::LogSearchSource,
+        LogSearchModel,
+    },
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static FUNCTION_RUNS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_function_runs"
+        .parse()
+        .expect("Invalid built-in function runs table")
+});
+
+pub static UDF_PATH_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "udfPath".parse().expect("invalid udfPath field"));
+
+pub static OUTCOME_TYPE_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "outcome.type".parse().expect("invalid outcome.type field"));
+
+pub static FUNCTION_RUNS_BY_UDF_PATH_INDEX: LazyLock<SystemIndex<FunctionRunsTable>> =
+    LazyLock::new(|| {
+        SystemIndex::new("by_udf_path", [&UDF_PATH_FIELD, &CREATION_TIME_FIELD_PATH]).unwrap()
+    });
+
+pub static FUNCTION_RUNS_BY_OUTCOME_INDEX: LazyLock<SystemIndex<FunctionRunsTable>> =
+    LazyLock::new(|| {
+        SystemIndex::new(
+            "by_outcome",
+            [&OUTCOME_TYPE_FIELD, &CREATION_TIME_FIELD_PATH],
+        )
+        .unwrap()
+    });
+
+pub struct FunctionRunsTable;
+impl SystemTable for FunctionRunsTable {
+    type Metadata = FunctionRun;
+
+    fn table_name() -> &'static TableName {
+        &FUNCTION_RUNS_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![
+            FUNCTION_RUNS_BY_UDF_PATH_INDEX.clone(),
+            FUNCTION_RUNS_BY_OUTCOME_INDEX.clone(),
+        ]
+    }
+}
+
+/// Restricts which runs `FunctionRunsModel::list_recent` returns.
+#[derive(Clone, Debug, Default)]
+pub struct FunctionRunsFilter {
+    pub udf_path: Option<String>,
+    /// `"success"` or `"failure"`.
+    pub outcome: Option<String>,
+}
+
+pub struct FunctionRunsModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> FunctionRunsModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    pub async fn insert(&mut self, run: FunctionRun) -> anyhow::Result<()> {
+        let text = function_run_text(&run);
+        let started_at_ms = run.started_at_ms;
+        let id = SystemMetadataModel::new_global(self.tx)
+            .insert(&FUNCTION_RUNS_TABLE, run.try_into()?)
+            .await?;
+        LogSearchModel::new(self.tx)
+            .index(
+                LogSearchSource::FunctionRun,
+                id.into(),
+                started_at_ms,
+                &text,
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Most recent runs matching `filter`, newest first.
+    pub async fn list_recent(
+        &mut self,
+        filter: FunctionRunsFilter,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ResolvedDocument>> {
+        self.tx.require_admin("list_function_runs")?;
+        if let Some(outcome) = &filter.outcome
+            && !["success", "failure"].contains(&&outcome[..])
+        {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidFunctionRunOutcome",
+                format!("Expected outcome to be \"success\" or \"failure\", got {outcome:?}"),
+            ));
+        }
+        let query = match (filter.udf_path, filter.outcome) {
+            (Some(udf_path), outcome) => {
+                let query = Query::index_range(IndexRange {
+                    index_name: FUNCTION_RUNS_BY_UDF_PATH_INDEX.name(),
+                    range: vec![IndexRangeExpression::Eq(
+                        UDF_PATH_FIELD.clone(),
+                        maybe_val!(udf_path),
+                    )],
+                    order: Order::Desc,
+                });
+                match outcome {
+                    Some(outcome) => query.filter(Expression::Eq(
+                        Expression::Field(OUTCOME_TYPE_FIELD.clone()).into(),
+                        Expression::Literal(maybe_val!(outcome)).into(),
+                    )),
+                    None => query,
+                }
+            },
+            (None, Some(outcome)) => Query::index_range(IndexRange {
+                index_name: FUNCTION_RUNS_BY_OUTCOME_INDEX.name(),
+                range: vec![IndexRangeExpression::Eq(
+                    OUTCOME_TYPE_FIELD.clone(),
+                    maybe_val!(outcome),
+                )],
+                order: Order::Desc,
+            }),
+            (None, None) => Query::full_table_scan(FUNCTION_RUNS_TABLE.clone(), Order::Desc),
+        };
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut result = vec![];
+        while result.len() < limit {
+            let Some(doc) = query_stream.next(self.tx, None).await? else {
+                break;
+            };
+            result.push(doc);
+        }
+        Ok(result)
+    }
+}
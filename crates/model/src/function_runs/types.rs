@@ -0,0 +1,192 @@
+use common::{
+    components::ComponentPath,
+    types::UdfType,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// A single completed function execution, as recorded in `_function_runs`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct FunctionRun {
+    pub component_path: ComponentPath,
+    /// The function's path, or the route for HTTP actions.
+    pub udf_path: String,
+    pub udf_type: UdfType,
+    pub caller: String,
+    pub request_id: String,
+    pub execution_id: String,
+    pub started_at_ms: i64,
+    pub duration_ms: i64,
+    pub outcome: FunctionRunOutcome,
+    pub documents_read: i64,
+    pub bytes_read: i64,
+    pub documents_written: i64,
+    pub bytes_written: i64,
+    pub log_lines: Vec<String>,
+    /// Whether `log_lines` was cut short to fit the size limit.
+    pub log_lines_truncated: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum FunctionRunOutcome {
+    Success,
+    Failure { error: String },
+}
+
+impl FunctionRunOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FunctionRunOutcome::Success => "success",
+            FunctionRunOutcome::Failure { .. } => "failure",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum SerializedFunctionRunOutcome {
+    Success,
+    Failure { error: String },
+}
+
+impl From<FunctionRunOutcome> for SerializedFunctionRunOutcome {
+    fn from(value: FunctionRunOutcome) -> Self {
+        match value {
+            FunctionRunOutcome::Success => Self::Success,
+            FunctionRunOutcome::Failure { error } => Self::Failure { error },
+        }
+    }
+}
+
+impl From<SerializedFunctionRunOutcome> for FunctionRunOutcome {
+    fn from(value: SerializedFunctionRunOutcome) -> Self {
+        match value {
+            SerializedFunctionRunOutcome::Success => Self::Success,
+            SerializedFunctionRunOutcome::Failure { error } => Self::Failure { error },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedFunctionRun {
+    component_path: String,
+    udf_path: String,
+    udf_type: String,
+    caller: String,
+    request_id: String,
+    execution_id: String,
+    started_at_ms: i64,
+    duration_ms: i64,
+    outcome: SerializedFunctionRunOutcome,
+    documents_read: i64,
+    bytes_read: i64,
+    documents_written: i64,
+    bytes_written: i64,
+    log_lines: Vec<String>,
+    log_lines_truncated: bool,
+}
+
+impl From<FunctionRun> for SerializedFunctionRun {
+    fn from(value: FunctionRun) -> Self {
+        Self {
+            component_path: value.component_path.into(),
+            udf_path: value.udf_path,
+            udf_type: value.udf_type.to_lowercase_string().to_string(),
+            caller: value.caller,
+            request_id: value.request_id,
+            execution_id: value.execution_id,
+            started_at_ms: value.started_at_ms,
+            duration_ms: value.duration_ms,
+            outcome: value.outcome.into(),
+            documents_read: value.documents_read,
+            bytes_read: value.bytes_read,
+            documents_written: value.documents_written,
+            bytes_written: value.bytes_written,
+            log_lines: value.log_lines,
+            log_lines_truncated: value.log_lines_truncated,
+        }
+    }
+}
+
+impl TryFrom<SerializedFunctionRun> for FunctionRun {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedFunctionRun) -> Result<Self, Self::Error> {
+        Ok(Self {
+            component_path: value.component_path.parse()?,
+            udf_path: value.udf_path,
+            udf_type: match &value.udf_type[..] {
+                // `UdfType`'s `FromStr` doesn't accept its own lowercase form for
+                // HTTP actions.
+                "http_action" => UdfType::HttpAction,
+                udf_type => udf_type.parse()?,
+            },
+            caller: value.caller,
+            request_id: value.request_id,
+            execution_id: value.execution_id,
+            started_at_ms: value.started_at_ms,
+            duration_ms: value.duration_ms,
+            outcome: value.outcome.into(),
+            documents_read: value.documents_read,
+            bytes_read: value.bytes_read,
+            documents_written: value.documents_written,
+            bytes_written: value.bytes_written,
+            log_lines: value.log_lines,
+            log_lines_truncated: value.log_lines_truncated,
+        })
+    }
+}
+
+codegen_convex_serialization!(FunctionRun, SerializedFunctionRun);
+
+/// Keeps log lines, in order, until their total length would exceed
+/// `max_bytes`. The line that crosses the limit is cut at a character
+/// boundary. Returns whether anything was dropped.
+pub fn truncate_log_lines(lines: Vec<String>, max_bytes: usize) -> (Vec<String>, bool) {
+    let mut remaining = max_bytes;
+    let mut kept = Vec::with_capacity(lines.len());
+    for mut line in lines {
+        if line.len() > remaining {
+            let mut end = remaining;
+            while !line.is_char_boundary(end) {
+                end -= 1;
+            }
+            line.truncate(end);
+            if !line.is_empty() {
+                kept.push(line);
+            }
+            return (kept, true);
+        }
+        remaining -= line.len();
+        kept.push(line);
+    }
+    (kept, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::truncate_log_lines;
+
+    #[test]
+    fn test_truncate_log_lines() {
+        let lines = vec!["hello".to_string(), "wörld".to_string(), "!".to_string()];
+        assert_eq!(
+            truncate_log_lines(lines.clone(), 100),
+            (lines.clone(), false)
+        );
+        assert_eq!(
+            truncate_log_lines(lines.clone(), 7),
+            (vec!["hello".to_string(), "w".to_string()], true)
+        );
+        assert_eq!(
+            truncate_log_lines(lines, 5),
+            (vec!["hello".to_string()], true)
+        );
+    }
+}
@@ -0,0 +1,536 @@
+//! Scheduled HTTP checks: lightweight uptime checks of a deployment's
+//! dependencies. The check worker requests each check's URL every interval
+//! without running any functions, records the status and latency in
+//! `_http_check_results`, and sends an `http_check.failed` or
+//! `http_check.recovered` webhook event when a check's health changes.
+
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParseDocument,
+        ParsedDocument,
+        CREATION_TIME_FIELD_PATH,
+    },
+    maybe_val,
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+};
+use database::{
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use value::{
+    ConvexValue,
+    DeveloperDocumentId,
+    FieldPath,
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::{
+    HttpCheck,
+    HttpCheckConfig,
+    HttpCheckResult,
+};
+use crate::{
+    webhooks::{
+        types::WebhookEvent,
+        WebhookModel,
+    },
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static HTTP_CHECKS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_http_checks"
+        .parse()
+        .expect("Invalid built-in http checks table")
+});
+
+pub static HTTP_CHECK_RESULTS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_http_check_results"
+        .parse()
+        .expect("Invalid built-in http check results table")
+});
+
+pub static HTTP_CHECKS_BY_NAME_INDEX: LazyLock<SystemIndex<HttpChecksTable>> =
+    LazyLock::new(|| SystemIndex::new("by_name", [&NAME_FIELD]).unwrap());
+/// By next run. Used to find checks that are due.
+pub static HTTP_CHECKS_BY_NEXT_RUN_INDEX: LazyLock<SystemIndex<HttpChecksTable>> =
+    LazyLock::new(|| SystemIndex::new("by_next_run", [&NEXT_RUN_FIELD]).unwrap());
+/// By check and creation time. Used to list a check's recent results.
+pub static HTTP_CHECK_RESULTS_BY_CHECK_INDEX: LazyLock<SystemIndex<HttpCheckResultsTable>> =
+    LazyLock::new(|| {
+        SystemIndex::new("by_check", [&CHECK_ID_FIELD, &CREATION_TIME_FIELD_PATH]).unwrap()
+    });
+static NAME_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "name".parse().expect("invalid name field"));
+static NEXT_RUN_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "nextRunMs".parse().expect("invalid nextRunMs field"));
+static CHECK_ID_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "checkId".parse().expect("invalid checkId field"));
+
+const MAX_CHECK_NAME_LEN: usize = 64;
+const MAX_CHECKS: usize = 20;
+const MIN_INTERVAL_SECS: u32 = 10;
+const MAX_INTERVAL_SECS: u32 = 24 * 60 * 60;
+const MAX_TIMEOUT_SECS: u32 = 60;
+const MAX_FAILURE_THRESHOLD: u32 = 10;
+
+pub struct HttpChecksTable;
+impl SystemTable for HttpChecksTable {
+    type Metadata = HttpCheck;
+
+    fn table_name() -> &'static TableName {
+        &HTTP_CHECKS_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![
+            HTTP_CHECKS_BY_NAME_INDEX.clone(),
+            HTTP_CHECKS_BY_NEXT_RUN_INDEX.clone(),
+        ]
+    }
+}
+
+pub struct HttpCheckResultsTable;
+impl SystemTable for HttpCheckResultsTable {
+    type Metadata = HttpCheckResult;
+
+    fn table_name() -> &'static TableName {
+        &HTTP_CHECK_RESULTS_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![HTTP_CHECK_RESULTS_BY_CHECK_INDEX.clone()]
+    }
+}
+
+pub struct HttpCheckModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> HttpCheckModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    /// Creates a check, or replaces the configuration of the check with the
+    /// same name. Either way the check runs right away and starts out
+    /// healthy.
+    pub async fn set_check(&mut self, config: HttpCheckConfig, now_ms: i64) -> anyhow::Result<()> {
+        self.tx.require_admin("set_http_check")?;
+        validate_config(&config)?;
+        let existing = self.get_check_by_name(&config.name).await?;
+        let check = HttpCheck {
+            config,
+            next_run_ms: now_ms,
+            consecutive_failures: 0,
+            healthy: true,
+        };
+        match existing {
+            Some(existing) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), check.try_into()?)
+                    .await?;
+            },
+            None => {
+                anyhow::ensure!(
+                    self.list_checks().await?.len() < MAX_CHECKS,
+                    ErrorMetadata::bad_request(
+                        "TooManyHttpChecks",
+                        format!("Deployments can have at most {MAX_CHECKS} HTTP checks"),
+                    )
+                );
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&HTTP_CHECKS_TABLE, check.try_into()?)
+                    .await?;
+            },
+        }
+        Ok(())
+    }
+
+    /// Deletes a check. Its results are left to expire.
+    pub async fn delete_check(&mut self, name: &str) -> anyhow::Result<()> {
+        self.tx.require_admin("delete_http_check")?;
+        let check = self.must_get_check_by_name(name).await?;
+        SystemMetadataModel::new_global(self.tx)
+            .delete(check.id())
+            .await?;
+        Ok(())
+    }
+
+    pub async fn list_checks(&mut self) -> anyhow::Result<Vec<ParsedDocument<HttpCheck>>> {
+        self.tx.require_admin("list_http_checks")?;
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&HTTP_CHECKS_TABLE) {
+            return Ok(vec![]);
+        }
+        let query = Query::index_range(IndexRange {
+            index_name: HTTP_CHECKS_BY_NAME_INDEX.name(),
+            range: vec![],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut checks = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            checks.push(ParseDocument::<HttpCheck>::parse(doc)?);
+        }
+        Ok(checks)
+    }
+
+    pub async fn get_check_by_name(
+        &mut self,
+        name: &str,
+    ) -> anyhow::Result<Option<ParsedDocument<HttpCheck>>> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&HTTP_CHECKS_TABLE) {
+            return Ok(None);
+        }
+        let query = Query::index_range(IndexRange {
+            index_name: HTTP_CHECKS_BY_NAME_INDEX.name(),
+            range: vec![IndexRangeExpression::Eq(
+                NAME_FIELD.clone(),
+                maybe_val!(name.to_string()),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParseDocument::<HttpCheck>::parse)
+            .transpose()
+    }
+
+    async fn must_get_check_by_name(
+        &mut self,
+        name: &str,
+    ) -> anyhow::Result<ParsedDocument<HttpCheck>> {
+        self.get_check_by_name(name).await?.ok_or_else(|| {
+            ErrorMetadata::not_found("HttpCheckNotFound", format!("No HTTP check named {name}"))
+                .into()
+        })
+    }
+
+    /// Checks due to run by `now_ms`, most overdue first.
+    pub async fn list_due(
+        &mut self,
+        now_ms: i64,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ParsedDocument<HttpCheck>>> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&HTTP_CHECKS_TABLE) {
+            return Ok(vec![]);
+        }
+        let query = Query::index_range(IndexRange {
+            index_name: HTTP_CHECKS_BY_NEXT_RUN_INDEX.name(),
+            range: vec![IndexRangeExpression::Lte(
+                NEXT_RUN_FIELD.clone(),
+                ConvexValue::Int64(now_ms).into(),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut due = vec![];
+        while due.len() < limit
+            && let Some(doc) = query_stream.next(self.tx, Some(limit - due.len())).await?
+        {
+            due.push(ParseDocument::<HttpCheck>::parse(doc)?);
+        }
+        Ok(due)
+    }
+
+    /// When the next check is due, if there are any checks.
+    pub async fn next_due_ms(&mut self) -> anyhow::Result<Option<i64>> {
+        Ok(self
+            .list_due(i64::MAX, 1)
+            .await?
+            .first()
+            .map(|check| check.next_run_ms))
+    }
+
+    /// Records the result of running a check and schedules its next run.
+    /// Queues a webhook event if the result changes the check's health.
+    /// Results for checks that have been deleted or reconfigured since they
+    /// started are dropped.
+    pub async fn record_result(
+        &mut self,
+        id: ResolvedDocumentId,
+        ran: &HttpCheckConfig,
+        result: HttpCheckResult,
+        now_ms: i64,
+    ) -> anyhow::Result<()> {
+        let Some(check) = self.tx.get(id).await? else {
+            return Ok(());
+        };
+        let mut check = ParseDocument::<HttpCheck>::parse(check)?.into_value();
+        if check.config != *ran {
+            return Ok(());
+        }
+        let was_healthy = check.healthy;
+        if result.ok {
+            check.consecutive_failures = 0;
+            check.healthy = true;
+        } else {
+            check.consecutive_failures += 1;
+            if check.consecutive_failures >= check.config.failure_threshold {
+                check.healthy = false;
+            }
+        }
+        check.next_run_ms = result.started_at_ms + i64::from(check.config.interval_secs) * 1000;
+        if check.healthy != was_healthy {
+            let event = WebhookEvent::HttpCheckStatusChanged {
+                name: check.config.name.clone(),
+                url: check.config.url.clone(),
+                healthy: check.healthy,
+                status: result.status,
+                error: result.error.clone(),
+                consecutive_failures: check.consecutive_failures,
+            };
+            WebhookModel::new(self.tx)
+                .enqueue_event(&event, now_ms)
+                .await?;
+        }
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, check.try_into()?)
+            .await?;
+        SystemMetadataModel::new_global(self.tx)
+            .insert(&HTTP_CHECK_RESULTS_TABLE, result.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    /// The check's most recent results, newest first.
+    pub async fn list_results(
+        &mut self,
+        name: &str,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ParsedDocument<HttpCheckResult>>> {
+        self.tx.require_admin("list_http_check_results")?;
+        let check = self.must_get_check_by_name(name).await?;
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&HTTP_CHECK_RESULTS_TABLE) {
+            return Ok(vec![]);
+        }
+        let check_id = DeveloperDocumentId::from(check.id());
+        let query = Query::index_range(IndexRange {
+            index_name: HTTP_CHECK_RESULTS_BY_CHECK_INDEX.name(),
+            range: vec![IndexRangeExpression::Eq(
+                CHECK_ID_FIELD.clone(),
+                maybe_val!(check_id.encode()),
+            )],
+            order: Order::Desc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut results = vec![];
+        while results.len() < limit
+            && let Some(doc) = query_stream
+                .next(self.tx, Some(limit - results.len()))
+                .await?
+        {
+            results.push(ParseDocument::<HttpCheckResult>::parse(doc)?);
+        }
+        Ok(results)
+    }
+}
+
+fn validate_config(config: &HttpCheckConfig) -> anyhow::Result<()> {
+    let name = &config.name;
+    anyhow::ensure!(
+        !name.is_empty()
+            && name.len() <= MAX_CHECK_NAME_LEN
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+        ErrorMetadata::bad_request(
+            "InvalidHttpCheckName",
+            format!(
+                "HTTP check names must be 1 to {MAX_CHECK_NAME_LEN} letters, digits, dashes or \
+                 underscores"
+            ),
+        )
+    );
+    let url = &config.url;
+    let valid_url = reqwest::Url::parse(url)
+        .is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.host().is_some());
+    anyhow::ensure!(
+        valid_url,
+        ErrorMetadata::bad_request(
+            "InvalidHttpCheckUrl",
+            format!("{url} is not a valid http or https URL"),
+        )
+    );
+    anyhow::ensure!(
+        (MIN_INTERVAL_SECS..=MAX_INTERVAL_SECS).contains(&config.interval_secs),
+        ErrorMetadata::bad_request(
+            "InvalidHttpCheckInterval",
+            format!(
+                "HTTP checks must run every {MIN_INTERVAL_SECS} to {MAX_INTERVAL_SECS} seconds"
+            ),
+        )
+    );
+    anyhow::ensure!(
+        config.timeout_secs >= 1
+            && config.timeout_secs <= MAX_TIMEOUT_SECS
+            && config.timeout_secs < config.interval_secs,
+        ErrorMetadata::bad_request(
+            "InvalidHttpCheckTimeout",
+            format!(
+                "HTTP check timeouts must be 1 to {MAX_TIMEOUT_SECS} seconds, and shorter than \
+                 the interval"
+            ),
+        )
+    );
+    if let Some(status) = config.expected_status {
+        anyhow::ensure!(
+            (100..600).contains(&status),
+            ErrorMetadata::bad_request(
+                "InvalidHttpCheckStatus",
+                format!("{status} is not an HTTP status code"),
+            )
+        );
+    }
+    anyhow::ensure!(
+        (1..=MAX_FAILURE_THRESHOLD).contains(&config.failure_threshold),
+        ErrorMetadata::bad_request(
+            "InvalidHttpCheckFailureThreshold",
+            format!("HTTP check failure thresholds must be 1 to {MAX_FAILURE_THRESHOLD}"),
+        )
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use database::test_helpers::DbFixtures;
+    use keybroker::Identity;
+    use runtime::testing::TestRuntime;
+    use value::DeveloperDocumentId;
+
+    use super::{
+        types::{
+            HttpCheckConfig,
+            HttpCheckMethod,
+            HttpCheckResult,
+        },
+        HttpCheckModel,
+    };
+    use crate::{
+        test_helpers::DbFixturesWithModel,
+        webhooks::{
+            types::WebhookEventFilter,
+            WebhookModel,
+        },
+    };
+
+    fn config() -> HttpCheckConfig {
+        HttpCheckConfig {
+            name: "payments".to_string(),
+            url: "https://payments.example.com/health".to_string(),
+            method: HttpCheckMethod::Get,
+            interval_secs: 60,
+            timeout_secs: 10,
+            expected_status: None,
+            failure_threshold: 2,
+        }
+    }
+
+    fn result(check_id: DeveloperDocumentId, started_at_ms: i64, ok: bool) -> HttpCheckResult {
+        HttpCheckResult {
+            check_id,
+            started_at_ms,
+            latency_ms: 25,
+            status: Some(if ok { 200 } else { 503 }),
+            ok,
+            error: None,
+        }
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_failure_threshold_and_alerts(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new_with_model(&rt).await?.db;
+        let mut tx = db.begin(Identity::system()).await?;
+        WebhookModel::new(&mut tx)
+            .register_endpoint(
+                "alerts".to_string(),
+                "https://alerts.example.com".to_string(),
+                vec![WebhookEventFilter::HttpCheckAlerts],
+            )
+            .await?;
+        let mut model = HttpCheckModel::new(&mut tx);
+        model.set_check(config(), 1000).await?;
+        let due = model.list_due(1000, 10).await?;
+        assert_eq!(due.len(), 1);
+        let id = due[0].id();
+
+        let outcomes = [(1000, false), (61_000, false), (121_000, true)];
+        let mut health = vec![];
+        for (started_at_ms, ok) in outcomes {
+            model
+                .record_result(id, &config(), result(id.into(), started_at_ms, ok), 0)
+                .await?;
+            let check = model.get_check_by_name("payments").await?.unwrap();
+            health.push(check.healthy);
+            assert_eq!(check.next_run_ms, started_at_ms + 60_000);
+        }
+        // The first failure is under the threshold.
+        assert_eq!(health, vec![true, false, true]);
+        assert_eq!(model.list_results("payments", 10).await?.len(), 3);
+
+        // Results from a check that was reconfigured while it ran are dropped.
+        let stale = HttpCheckConfig {
+            interval_secs: 30,
+            ..config()
+        };
+        model
+            .record_result(id, &stale, result(id.into(), 200_000, false), 0)
+            .await?;
+        assert_eq!(model.list_results("payments", 10).await?.len(), 3);
+
+        // One event when it failed and one when it recovered.
+        let deliveries = WebhookModel::new(&mut tx).list_due(i64::MAX, 10).await?;
+        let mut event_types: Vec<_> = deliveries.iter().map(|d| d.event_type.as_str()).collect();
+        event_types.sort();
+        assert_eq!(
+            event_types,
+            vec!["http_check.failed", "http_check.recovered"]
+        );
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_validation(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new_with_model(&rt).await?.db;
+        let mut tx = db.begin(Identity::system()).await?;
+        let mut model = HttpCheckModel::new(&mut tx);
+        for invalid in [
+            HttpCheckConfig {
+                url: "ftp://example.com".to_string(),
+                ..config()
+            },
+            HttpCheckConfig {
+                interval_secs: 1,
+                ..config()
+            },
+            HttpCheckConfig {
+                timeout_secs: 60,
+                ..config()
+            },
+            HttpCheckConfig {
+                failure_threshold: 0,
+                ..config()
+            },
+        ] {
+            assert!(model.set_check(invalid, 0).await.is_err());
+        }
+        Ok(())
+    }
+}
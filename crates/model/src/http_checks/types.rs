@@ -0,0 +1,200 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::{
+    codegen_convex_serialization,
+    DeveloperDocumentId,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, strum::AsRefStr, strum::EnumString)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+#[strum(serialize_all = "UPPERCASE")]
+pub enum HttpCheckMethod {
+    Get,
+    Head,
+}
+
+/// What to request and how often, as configured by the developer.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct HttpCheckConfig {
+    pub name: String,
+    pub url: String,
+    pub method: HttpCheckMethod,
+    pub interval_secs: u32,
+    pub timeout_secs: u32,
+    /// The status a healthy response has, or `None` for any 2xx status.
+    pub expected_status: Option<u16>,
+    /// How many checks in a row must fail before the check is unhealthy.
+    pub failure_threshold: u32,
+}
+
+impl HttpCheckConfig {
+    pub fn is_expected_status(&self, status: u16) -> bool {
+        match self.expected_status {
+            Some(expected) => status == expected,
+            None => (200..300).contains(&status),
+        }
+    }
+}
+
+/// An outbound HTTP request the backend makes on a schedule, without running
+/// any functions, to check that a dependency is up.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct HttpCheck {
+    pub config: HttpCheckConfig,
+    pub next_run_ms: i64,
+    pub consecutive_failures: u32,
+    /// False once `failure_threshold` checks in a row have failed, until one
+    /// succeeds.
+    pub healthy: bool,
+}
+
+/// The outcome of one run of a check.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct HttpCheckResult {
+    pub check_id: DeveloperDocumentId,
+    pub started_at_ms: i64,
+    pub latency_ms: i64,
+    /// The response status, or `None` if there was no response.
+    pub status: Option<u16>,
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedHttpCheck {
+    name: String,
+    url: String,
+    method: String,
+    interval_secs: i64,
+    timeout_secs: i64,
+    expected_status: Option<i64>,
+    failure_threshold: i64,
+    next_run_ms: i64,
+    consecutive_failures: i64,
+    healthy: bool,
+}
+
+impl From<HttpCheck> for SerializedHttpCheck {
+    fn from(check: HttpCheck) -> Self {
+        let HttpCheckConfig {
+            name,
+            url,
+            method,
+            interval_secs,
+            timeout_secs,
+            expected_status,
+            failure_threshold,
+        } = check.config;
+        Self {
+            name,
+            url,
+            method: method.as_ref().to_string(),
+            interval_secs: interval_secs.into(),
+            timeout_secs: timeout_secs.into(),
+            expected_status: expected_status.map(Into::into),
+            failure_threshold: failure_threshold.into(),
+            next_run_ms: check.next_run_ms,
+            consecutive_failures: check.consecutive_failures.into(),
+            healthy: check.healthy,
+        }
+    }
+}
+
+impl TryFrom<SerializedHttpCheck> for HttpCheck {
+    type Error = anyhow::Error;
+
+    fn try_from(check: SerializedHttpCheck) -> anyhow::Result<Self> {
+        Ok(Self {
+            config: HttpCheckConfig {
+                name: check.name,
+                url: check.url,
+                method: check.method.parse()?,
+                interval_secs: check.interval_secs.try_into()?,
+                timeout_secs: check.timeout_secs.try_into()?,
+                expected_status: check.expected_status.map(TryInto::try_into).transpose()?,
+                failure_threshold: check.failure_threshold.try_into()?,
+            },
+            next_run_ms: check.next_run_ms,
+            consecutive_failures: check.consecutive_failures.try_into()?,
+            healthy: check.healthy,
+        })
+    }
+}
+
+codegen_convex_serialization!(HttpCheck, SerializedHttpCheck);
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedHttpCheckResult {
+    check_id: String,
+    started_at_ms: i64,
+    latency_ms: i64,
+    status: Option<i64>,
+    ok: bool,
+    error: Option<String>,
+}
+
+impl From<HttpCheckResult> for SerializedHttpCheckResult {
+    fn from(result: HttpCheckResult) -> Self {
+        Self {
+            check_id: result.check_id.encode(),
+            started_at_ms: result.started_at_ms,
+            latency_ms: result.latency_ms,
+            status: result.status.map(Into::into),
+            ok: result.ok,
+            error: result.error,
+        }
+    }
+}
+
+impl TryFrom<SerializedHttpCheckResult> for HttpCheckResult {
+    type Error = anyhow::Error;
+
+    fn try_from(result: SerializedHttpCheckResult) -> anyhow::Result<Self> {
+        Ok(Self {
+            check_id: DeveloperDocumentId::decode(&result.check_id)?,
+            started_at_ms: result.started_at_ms,
+            latency_ms: result.latency_ms,
+            status: result.status.map(TryInto::try_into).transpose()?,
+            ok: result.ok,
+            error: result.error,
+        })
+    }
+}
+
+codegen_convex_serialization!(HttpCheckResult, SerializedHttpCheckResult);
+
+#[cfg(test)]
+mod tests {
+    use cmd_util::env::env_config;
+    use proptest::prelude::*;
+    use sync_types::testing::assert_roundtrips;
+    use value::ConvexObject;
+
+    use super::{
+        HttpCheck,
+        HttpCheckResult,
+    };
+
+    proptest! {
+        #![proptest_config(
+            ProptestConfig { cases: 256 * env_config("CONVEX_PROPTEST_MULTIPLIER", 1), failure_persistence: None, ..ProptestConfig::default() }
+        )]
+
+        #[test]
+        fn test_check_roundtrips(v in any::<HttpCheck>()) {
+            assert_roundtrips::<HttpCheck, ConvexObject>(v);
+        }
+
+        #[test]
+        fn test_result_roundtrips(v in any::<HttpCheckResult>()) {
+            assert_roundtrips::<HttpCheckResult, ConvexObject>(v);
+        }
+    }
+}
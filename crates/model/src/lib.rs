@@ -51,7 +51,21 @@ use backend_state::{
     BackendStateTable,
     BACKEND_STATE_TABLE,
 };
+use canary::{
+    CanaryDeploymentsTable,
+    CanaryModulesTable,
+    CANARY_DEPLOYMENTS_TABLE,
+    CANARY_MODULES_INDEX_BY_PATH,
+    CANARY_MODULES_TABLE,
+};
 use canonical_urls::CANONICAL_URLS_TABLE;
+use code_versions::{
+    CodeVersionModulesTable,
+    CodeVersionsTable,
+    CODE_VERSIONS_TABLE,
+    CODE_VERSION_MODULES_INDEX_BY_VERSION,
+    CODE_VERSION_MODULES_TABLE,
+};
 use common::{
     bootstrap_model::{
         index::{
@@ -93,23 +107,44 @@ use database::{
     system_tables::ErasedSystemTable,
     BootstrapComponentsModel,
     ComponentDefinitionsTable,
+    ComponentQuotasTable,
     ComponentsTable,
     Database,
     IndexModel,
+    IndexStatisticsTable,
     IndexTable,
     IndexWorkerMetadataTable,
     SchemasTable,
+    SearchSynonymsTable,
+    TableFreezesTable,
+    TableSnapshotsTable,
     TablesTable,
     Transaction,
+    VectorEmbeddingJobsTable,
+    VectorIndexMigrationsTable,
     COMPONENTS_BY_PARENT_INDEX,
     COMPONENTS_TABLE,
     COMPONENT_DEFINITIONS_TABLE,
+    COMPONENT_QUOTAS_BY_COMPONENT_ID,
+    COMPONENT_QUOTAS_TABLE,
     INDEX_DOC_ID_INDEX,
+    INDEX_STATISTICS_BY_INDEX_ID,
+    INDEX_STATISTICS_TABLE,
     INDEX_WORKER_METADATA_TABLE,
     NUM_RESERVED_LEGACY_TABLE_NUMBERS,
     SCHEMAS_STATE_INDEX,
     SCHEMAS_TABLE,
+    SEARCH_SYNONYMS_BY_INDEX_ID,
+    SEARCH_SYNONYMS_TABLE,
     TABLES_BY_NAME_INDEX,
+    TABLE_FREEZES_BY_TABLET_ID,
+    TABLE_FREEZES_TABLE,
+    TABLE_SNAPSHOTS_BY_NAME,
+    TABLE_SNAPSHOTS_TABLE,
+    VECTOR_EMBEDDING_JOBS_BY_STATE_AND_NEXT_ATTEMPT,
+    VECTOR_EMBEDDING_JOBS_TABLE,
+    VECTOR_INDEX_MIGRATIONS_BY_SOURCE_INDEX_ID,
+    VECTOR_INDEX_MIGRATIONS_TABLE,
 };
 use database_globals::{
     DatabaseGlobalsModel,
@@ -126,13 +161,27 @@ use exports::{
     EXPORTS_TABLE,
 };
 use external_packages::ExternalPackagesTable;
+use feature_flags::{
+    FeatureFlagsTable,
+    FEATURE_FLAGS_INDEX_BY_NAME,
+    FEATURE_FLAGS_TABLE,
+};
 use file_storage::{
     FileStorageTable,
     FILE_STORAGE_ID_INDEX,
     FILE_STORAGE_TABLE,
 };
 use keybroker::Identity;
+use log_search::{
+    LogSearchTermsTable,
+    LOG_SEARCH_TERMS_INDEX_BY_TERM_AND_TS,
+    LOG_SEARCH_TERMS_TABLE,
+};
 use log_sinks::LogSinksTable;
+use maintenance_mode::{
+    MaintenanceModeTable,
+    MAINTENANCE_MODE_TABLE,
+};
 use maplit::{
     btreemap,
     btreeset,
@@ -147,10 +196,18 @@ use modules::{
     MODULE_INDEX_BY_DELETED,
     MODULE_INDEX_BY_PATH,
 };
+use queues::{
+    QueueMessagesTable,
+    QUEUE_MESSAGES_INDEX_BY_LEASE_ID,
+    QUEUE_MESSAGES_INDEX_BY_QUEUE_AND_VISIBLE_TS,
+    QUEUE_MESSAGES_TABLE,
+};
 use scheduled_jobs::{
     ScheduledJobsTable,
     SCHEDULED_JOBS_INDEX,
+    SCHEDULED_JOBS_INDEX_BY_ARGS_PENDING_STORAGE,
     SCHEDULED_JOBS_INDEX_BY_COMPLETED_TS,
+    SCHEDULED_JOBS_INDEX_BY_IDEMPOTENCY_KEY,
     SCHEDULED_JOBS_INDEX_BY_UDF_PATH,
     SCHEDULED_JOBS_TABLE,
 };
@@ -188,14 +245,97 @@ use crate::{
         CronJobsTable,
         CronNextRunTable,
     },
+    data_masking::{
+        DataMaskingRulesTable,
+        DATA_MASKING_RULES_TABLE,
+    },
     deployment_audit_log::{
         DeploymentAuditLogsTable,
+        DEPLOYMENT_AUDIT_LOG_BY_ACTION_INDEX,
         DEPLOYMENT_AUDIT_LOG_TABLE,
     },
     environment_variables::EnvironmentVariablesTable,
+    erasure::{
+        ErasureJobItemsTable,
+        ErasureJobsTable,
+        ErasurePoliciesTable,
+        ERASURE_JOBS_BY_STATE_INDEX,
+        ERASURE_JOBS_TABLE,
+        ERASURE_JOB_ITEMS_BY_JOB_AND_DOCUMENT_INDEX,
+        ERASURE_JOB_ITEMS_TABLE,
+        ERASURE_POLICIES_TABLE,
+    },
+    error_groups::{
+        ErrorGroupsTable,
+        ERROR_GROUPS_BY_FINGERPRINT_INDEX,
+        ERROR_GROUPS_BY_LAST_SEEN_INDEX,
+        ERROR_GROUPS_TABLE,
+    },
     exports::ExportsTable,
     external_packages::EXTERNAL_PACKAGES_TABLE,
+    function_runs::{
+        FunctionRunsTable,
+        FUNCTION_RUNS_BY_OUTCOME_INDEX,
+        FUNCTION_RUNS_BY_UDF_PATH_INDEX,
+        FUNCTION_RUNS_TABLE,
+    },
+    http_checks::{
+        HttpCheckResultsTable,
+        HttpChecksTable,
+        HTTP_CHECKS_BY_NAME_INDEX,
+        HTTP_CHECKS_BY_NEXT_RUN_INDEX,
+        HTTP_CHECKS_TABLE,
+        HTTP_CHECK_RESULTS_BY_CHECK_INDEX,
+        HTTP_CHECK_RESULTS_TABLE,
+    },
     log_sinks::LOG_SINKS_TABLE,
+    read_set_advice::{
+        ReadSetAdviceTable,
+        READ_SET_ADVICE_BY_LAST_WARNED_INDEX,
+        READ_SET_ADVICE_BY_UDF_PATH_INDEX,
+        READ_SET_ADVICE_TABLE,
+    },
+    schema_history::{
+        SchemaHistoryTable,
+        SCHEMA_HISTORY_BY_COMPONENT_PATH_INDEX,
+        SCHEMA_HISTORY_TABLE,
+    },
+    static_assets::{
+        StaticAssetMountsTable,
+        StaticAssetsTable,
+        STATIC_ASSETS_BY_FOLDER_AND_PATH_INDEX,
+        STATIC_ASSETS_TABLE,
+        STATIC_ASSET_MOUNTS_BY_PATH_PREFIX_INDEX,
+        STATIC_ASSET_MOUNTS_TABLE,
+    },
+    usage_metering::{
+        FunctionUsageTable,
+        TableUsageTable,
+        FUNCTION_USAGE_BY_WINDOW_INDEX,
+        FUNCTION_USAGE_TABLE,
+        TABLE_USAGE_BY_WINDOW_INDEX,
+        TABLE_USAGE_TABLE,
+    },
+    vector_index_stats::{
+        VectorIndexStatsTable,
+        VECTOR_INDEX_STATS_BY_INDEX_ID_INDEX,
+        VECTOR_INDEX_STATS_TABLE,
+    },
+    webhook_nonces::{
+        WebhookNoncesTable,
+        WEBHOOK_NONCES_INDEX_BY_EXPIRES_AT,
+        WEBHOOK_NONCES_INDEX_BY_SCOPE_AND_NONCE,
+        WEBHOOK_NONCES_TABLE,
+    },
+    webhooks::{
+        WebhookDeliveriesTable,
+        WebhookEndpointsTable,
+        WEBHOOK_DELIVERIES_BY_ENDPOINT_INDEX,
+        WEBHOOK_DELIVERIES_BY_STATE_AND_NEXT_ATTEMPT_INDEX,
+        WEBHOOK_DELIVERIES_TABLE,
+        WEBHOOK_ENDPOINTS_BY_NAME_INDEX,
+        WEBHOOK_ENDPOINTS_TABLE,
+    },
 };
 
 pub mod airbyte_import;
@@ -203,26 +343,44 @@ pub mod auth;
 pub mod aws_lambda_versions;
 pub mod backend_info;
 pub mod backend_state;
+pub mod canary;
 pub mod canonical_urls;
+pub mod code_versions;
 pub mod components;
 pub mod config;
 pub mod cron_jobs;
+pub mod data_masking;
 pub mod database_globals;
 pub mod deployment_audit_log;
 pub mod environment_variables;
+pub mod erasure;
+pub mod error_groups;
 pub mod exports;
 pub mod external_packages;
+pub mod feature_flags;
 pub mod file_storage;
 pub mod fivetran_import;
+pub mod function_runs;
+pub mod http_checks;
+pub mod log_search;
 pub mod log_sinks;
+pub mod maintenance_mode;
 mod metrics;
 pub mod migrations;
 pub mod modules;
+pub mod queues;
+pub mod read_set_advice;
 pub mod scheduled_jobs;
+pub mod schema_history;
 pub mod session_requests;
 pub mod snapshot_imports;
 pub mod source_packages;
+pub mod static_assets;
 pub mod udf_config;
+pub mod usage_metering;
+pub mod vector_index_stats;
+pub mod webhook_nonces;
+pub mod webhooks;
 
 #[cfg(any(test, feature = "testing"))]
 pub mod test_helpers;
@@ -261,9 +419,42 @@ enum DefaultTableNumber {
     FunctionHandlesTable = 33,
     CanonicalUrls = 34,
     CronNextRun = 35,
+    DataMaskingRules = 36,
+    FunctionRuns = 37,
+    FunctionUsage = 38,
+    TableUsage = 39,
+    ErrorGroups = 40,
+    SchemaHistory = 41,
+    SearchSynonyms = 42,
+    VectorIndexMigrations = 43,
+    VectorIndexStats = 44,
+    VectorEmbeddingJobs = 45,
+    IndexStatistics = 46,
+    TableFreezes = 47,
+    ComponentQuotas = 48,
+    CanaryDeployments = 49,
+    CanaryModules = 50,
+    CodeVersions = 51,
+    CodeVersionModules = 52,
+    MaintenanceMode = 53,
+    FeatureFlags = 54,
+    LogSearchTerms = 55,
+    QueueMessages = 56,
+    ReadSetAdvice = 57,
+    TableSnapshots = 58,
+    WebhookEndpoints = 59,
+    WebhookDeliveries = 60,
+    WebhookNonces = 61,
+    StaticAssetMounts = 62,
+    StaticAssets = 63,
+    HttpChecks = 64,
+    HttpCheckResults = 65,
+    ErasurePolicies = 66,
+    ErasureJobs = 67,
+    ErasureJobItems = 68,
     // Keep this number and your user name up to date. The number makes it easy to know
     // what to use next. The username on the same line detects merge conflicts
-    // Next Number - 36 - nipunn
+    // Next Number - 69 - agent
 }
 
 impl From<DefaultTableNumber> for TableNumber {
@@ -305,6 +496,39 @@ impl From<DefaultTableNumber> for &'static dyn ErasedSystemTable {
             DefaultTableNumber::FunctionHandlesTable => &FunctionHandlesTable,
             DefaultTableNumber::CanonicalUrls => &CanonicalUrlsTable,
             DefaultTableNumber::CronNextRun => &CronNextRunTable,
+            DefaultTableNumber::DataMaskingRules => &DataMaskingRulesTable,
+            DefaultTableNumber::FunctionRuns => &FunctionRunsTable,
+            DefaultTableNumber::FunctionUsage => &FunctionUsageTable,
+            DefaultTableNumber::TableUsage => &TableUsageTable,
+            DefaultTableNumber::ErrorGroups => &ErrorGroupsTable,
+            DefaultTableNumber::SchemaHistory => &SchemaHistoryTable,
+            DefaultTableNumber::SearchSynonyms => &SearchSynonymsTable,
+            DefaultTableNumber::VectorIndexMigrations => &VectorIndexMigrationsTable,
+            DefaultTableNumber::VectorIndexStats => &VectorIndexStatsTable,
+            DefaultTableNumber::VectorEmbeddingJobs => &VectorEmbeddingJobsTable,
+            DefaultTableNumber::IndexStatistics => &IndexStatisticsTable,
+            DefaultTableNumber::TableFreezes => &TableFreezesTable,
+            DefaultTableNumber::ComponentQuotas => &ComponentQuotasTable,
+            DefaultTableNumber::CanaryDeployments => &CanaryDeploymentsTable,
+            DefaultTableNumber::CanaryModules => &CanaryModulesTable,
+            DefaultTableNumber::CodeVersions => &CodeVersionsTable,
+            DefaultTableNumber::CodeVersionModules => &CodeVersionModulesTable,
+            DefaultTableNumber::MaintenanceMode => &MaintenanceModeTable,
+            DefaultTableNumber::FeatureFlags => &FeatureFlagsTable,
+            DefaultTableNumber::LogSearchTerms => &LogSearchTermsTable,
+            DefaultTableNumber::QueueMessages => &QueueMessagesTable,
+            DefaultTableNumber::ReadSetAdvice => &ReadSetAdviceTable,
+            DefaultTableNumber::TableSnapshots => &TableSnapshotsTable,
+            DefaultTableNumber::WebhookEndpoints => &WebhookEndpointsTable,
+            DefaultTableNumber::WebhookDeliveries => &WebhookDeliveriesTable,
+            DefaultTableNumber::WebhookNonces => &WebhookNoncesTable,
+            DefaultTableNumber::StaticAssetMounts => &StaticAssetMountsTable,
+            DefaultTableNumber::StaticAssets => &StaticAssetsTable,
+            DefaultTableNumber::HttpChecks => &HttpChecksTable,
+            DefaultTableNumber::HttpCheckResults => &HttpCheckResultsTable,
+            DefaultTableNumber::ErasurePolicies => &ErasurePoliciesTable,
+            DefaultTableNumber::ErasureJobs => &ErasureJobsTable,
+            DefaultTableNumber::ErasureJobItems => &ErasureJobItemsTable,
         }
     }
 }
@@ -531,6 +755,38 @@ pub fn app_system_tables() -> Vec<&'static dyn ErasedSystemTable> {
         &SnapshotImportsTable,
         &FunctionHandlesTable,
         &CanonicalUrlsTable,
+        &DataMaskingRulesTable,
+        &FunctionRunsTable,
+        &FunctionUsageTable,
+        &TableUsageTable,
+        &ErrorGroupsTable,
+        &SchemaHistoryTable,
+        &SearchSynonymsTable,
+        &VectorIndexMigrationsTable,
+        &VectorIndexStatsTable,
+        &VectorEmbeddingJobsTable,
+        &IndexStatisticsTable,
+        &TableFreezesTable,
+        &ComponentQuotasTable,
+        &CanaryDeploymentsTable,
+        &CanaryModulesTable,
+        &CodeVersionsTable,
+        &CodeVersionModulesTable,
+        &MaintenanceModeTable,
+        &FeatureFlagsTable,
+        &LogSearchTermsTable,
+        &ReadSetAdviceTable,
+        &TableSnapshotsTable,
+        &WebhookEndpointsTable,
+        &WebhookDeliveriesTable,
+        &WebhookNoncesTable,
+        &StaticAssetMountsTable,
+        &StaticAssetsTable,
+        &HttpChecksTable,
+        &HttpCheckResultsTable,
+        &ErasurePoliciesTable,
+        &ErasureJobsTable,
+        &ErasureJobItemsTable,
         &LogSinksTable,
         &AwsLambdaVersionsTable,
         &BackendInfoTable,
@@ -552,6 +808,7 @@ pub fn component_system_tables() -> Vec<&'static dyn ErasedSystemTable> {
         &ModulesTable,
         &UdfConfigTable,
         &SourcePackagesTable,
+        &QueueMessagesTable,
     ]
 }
 
@@ -569,6 +826,10 @@ static APP_TABLES_TO_LOAD_IN_MEMORY: LazyLock<BTreeSet<TableName>> = LazyLock::n
         BACKEND_INFO_TABLE.clone(),
         AWS_LAMBDA_VERSIONS_TABLE.clone(),
         SOURCE_PACKAGES_TABLE.clone(),
+        CANARY_DEPLOYMENTS_TABLE.clone(),
+        CANARY_MODULES_TABLE.clone(),
+        MAINTENANCE_MODE_TABLE.clone(),
+        FEATURE_FLAGS_TABLE.clone(),
     }
 });
 
@@ -620,6 +881,39 @@ pub static FIRST_SEEN_TABLE: LazyLock<BTreeMap<TableName, DatabaseVersion>> = La
         COMPONENT_DEFINITIONS_TABLE.clone() => 100,
         FUNCTION_HANDLES_TABLE.clone() => 102,
         CANONICAL_URLS_TABLE.clone() => 116,
+        DATA_MASKING_RULES_TABLE.clone() => 121,
+        FUNCTION_RUNS_TABLE.clone() => 122,
+        FUNCTION_USAGE_TABLE.clone() => 123,
+        TABLE_USAGE_TABLE.clone() => 123,
+        ERROR_GROUPS_TABLE.clone() => 124,
+        SCHEMA_HISTORY_TABLE.clone() => 125,
+        SEARCH_SYNONYMS_TABLE.clone() => 126,
+        VECTOR_INDEX_MIGRATIONS_TABLE.clone() => 127,
+        VECTOR_INDEX_STATS_TABLE.clone() => 128,
+        VECTOR_EMBEDDING_JOBS_TABLE.clone() => 129,
+        INDEX_STATISTICS_TABLE.clone() => 130,
+        TABLE_FREEZES_TABLE.clone() => 131,
+        COMPONENT_QUOTAS_TABLE.clone() => 132,
+        CANARY_DEPLOYMENTS_TABLE.clone() => 133,
+        CANARY_MODULES_TABLE.clone() => 133,
+        CODE_VERSIONS_TABLE.clone() => 134,
+        CODE_VERSION_MODULES_TABLE.clone() => 134,
+        MAINTENANCE_MODE_TABLE.clone() => 135,
+        FEATURE_FLAGS_TABLE.clone() => 136,
+        LOG_SEARCH_TERMS_TABLE.clone() => 137,
+        QUEUE_MESSAGES_TABLE.clone() => 139,
+        READ_SET_ADVICE_TABLE.clone() => 140,
+        TABLE_SNAPSHOTS_TABLE.clone() => 141,
+        WEBHOOK_ENDPOINTS_TABLE.clone() => 142,
+        WEBHOOK_DELIVERIES_TABLE.clone() => 142,
+        WEBHOOK_NONCES_TABLE.clone() => 143,
+        STATIC_ASSET_MOUNTS_TABLE.clone() => 144,
+        STATIC_ASSETS_TABLE.clone() => 144,
+        HTTP_CHECKS_TABLE.clone() => 145,
+        HTTP_CHECK_RESULTS_TABLE.clone() => 145,
+        ERASURE_POLICIES_TABLE.clone() => 146,
+        ERASURE_JOBS_TABLE.clone() => 146,
+        ERASURE_JOB_ITEMS_TABLE.clone() => 147,
     }
 });
 
@@ -645,6 +939,44 @@ pub static FIRST_SEEN_INDEX: LazyLock<BTreeMap<IndexName, DatabaseVersion>> = La
         COMPONENTS_BY_PARENT_INDEX.name() => 100,
         BY_COMPONENT_PATH_INDEX.name() => 102,
         EXPORTS_BY_REQUESTOR.name() => 110,
+        DEPLOYMENT_AUDIT_LOG_BY_ACTION_INDEX.name() => 120,
+        FUNCTION_RUNS_BY_UDF_PATH_INDEX.name() => 122,
+        FUNCTION_RUNS_BY_OUTCOME_INDEX.name() => 122,
+        FUNCTION_USAGE_BY_WINDOW_INDEX.name() => 123,
+        TABLE_USAGE_BY_WINDOW_INDEX.name() => 123,
+        ERROR_GROUPS_BY_FINGERPRINT_INDEX.name() => 124,
+        ERROR_GROUPS_BY_LAST_SEEN_INDEX.name() => 124,
+        SCHEMA_HISTORY_BY_COMPONENT_PATH_INDEX.name() => 125,
+        SEARCH_SYNONYMS_BY_INDEX_ID.name() => 126,
+        VECTOR_INDEX_MIGRATIONS_BY_SOURCE_INDEX_ID.name() => 127,
+        VECTOR_INDEX_STATS_BY_INDEX_ID_INDEX.name() => 128,
+        VECTOR_EMBEDDING_JOBS_BY_STATE_AND_NEXT_ATTEMPT.name() => 129,
+        INDEX_STATISTICS_BY_INDEX_ID.name() => 130,
+        TABLE_FREEZES_BY_TABLET_ID.name() => 131,
+        COMPONENT_QUOTAS_BY_COMPONENT_ID.name() => 132,
+        CANARY_MODULES_INDEX_BY_PATH.name() => 133,
+        CODE_VERSION_MODULES_INDEX_BY_VERSION.name() => 134,
+        FEATURE_FLAGS_INDEX_BY_NAME.name() => 136,
+        LOG_SEARCH_TERMS_INDEX_BY_TERM_AND_TS.name() => 137,
+        SCHEDULED_JOBS_INDEX_BY_IDEMPOTENCY_KEY.name() => 138,
+        QUEUE_MESSAGES_INDEX_BY_QUEUE_AND_VISIBLE_TS.name() => 139,
+        QUEUE_MESSAGES_INDEX_BY_LEASE_ID.name() => 139,
+        READ_SET_ADVICE_BY_UDF_PATH_INDEX.name() => 140,
+        READ_SET_ADVICE_BY_LAST_WARNED_INDEX.name() => 140,
+        TABLE_SNAPSHOTS_BY_NAME.name() => 141,
+        WEBHOOK_ENDPOINTS_BY_NAME_INDEX.name() => 142,
+        WEBHOOK_DELIVERIES_BY_STATE_AND_NEXT_ATTEMPT_INDEX.name() => 142,
+        WEBHOOK_DELIVERIES_BY_ENDPOINT_INDEX.name() => 142,
+        WEBHOOK_NONCES_INDEX_BY_SCOPE_AND_NONCE.name() => 143,
+        WEBHOOK_NONCES_INDEX_BY_EXPIRES_AT.name() => 143,
+        STATIC_ASSET_MOUNTS_BY_PATH_PREFIX_INDEX.name() => 144,
+        STATIC_ASSETS_BY_FOLDER_AND_PATH_INDEX.name() => 144,
+        HTTP_CHECKS_BY_NAME_INDEX.name() => 145,
+        HTTP_CHECKS_BY_NEXT_RUN_INDEX.name() => 145,
+        HTTP_CHECK_RESULTS_BY_CHECK_INDEX.name() => 145,
+        ERASURE_JOBS_BY_STATE_INDEX.name() => 146,
+        ERASURE_JOB_ITEMS_BY_JOB_AND_DOCUMENT_INDEX.name() => 147,
+        SCHEDULED_JOBS_INDEX_BY_ARGS_PENDING_STORAGE.name() => 148,
     }
 });
 
@@ -0,0 +1,405 @@
+//! An inverted index over `_function_runs` and `_deployment_audit_log`, so
+//! admins can find records containing some text without reading every row.
+//! Each distinct word of a record gets a posting in `_log_search_terms`,
+//! ordered by the record's timestamp so time-bounded searches only read the
+//! postings in range. Matches are then checked against the record's full
+//! text. Postings are deleted after `LOG_SEARCH_RETENTION`.
+
+use std::{
+    collections::BTreeSet,
+    sync::LazyLock,
+};
+
+use common::{
+    document::{
+        ParseDocument,
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    knobs::LOG_SEARCH_RETENTION,
+    maybe_val,
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+};
+use database::{
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use value::{
+    ConvexObject,
+    ConvexValue,
+    DeveloperDocumentId,
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::{
+    LogSearchSource,
+    LogSearchTerm,
+};
+use crate::{
+    function_runs::types::{
+        FunctionRun,
+        FunctionRunOutcome,
+    },
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static LOG_SEARCH_TERMS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_log_search_terms"
+        .parse()
+        .expect("Invalid built-in log_search_terms table")
+});
+
+static TERM_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "term".parse().expect("invalid term field"));
+static TS_MS_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "tsMs".parse().expect("invalid tsMs field"));
+
+pub static LOG_SEARCH_TERMS_INDEX_BY_TERM_AND_TS: LazyLock<SystemIndex<LogSearchTermsTable>> =
+    LazyLock::new(|| SystemIndex::new("by_term_and_ts", [&TERM_FIELD, &TS_MS_FIELD]).unwrap());
+
+/// Words shorter than this match too many records to be worth indexing.
+const MIN_TERM_LEN: usize = 2;
+/// Longer "words" are usually ids or encoded data.
+const MAX_TERM_LEN: usize = 64;
+/// Bounds the writes indexing adds to each record. The function runs writer
+/// inserts runs in batches, so this keeps a batch within the transaction's
+/// write limit. Earlier words win, which is why errors are indexed first.
+const MAX_TERMS_PER_RECORD: usize = 64;
+/// Bounds the work done by a search for a common word.
+const MAX_POSTINGS_SCANNED: usize = 10_000;
+
+pub struct LogSearchTermsTable;
+impl SystemTable for LogSearchTermsTable {
+    type Metadata = LogSearchTerm;
+
+    fn table_name() -> &'static TableName {
+        &LOG_SEARCH_TERMS_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![LOG_SEARCH_TERMS_INDEX_BY_TERM_AND_TS.clone()]
+    }
+}
+
+/// Splits `text` into lowercase words, in order of first appearance.
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut seen = BTreeSet::new();
+    text.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|word| (MIN_TERM_LEN..=MAX_TERM_LEN).contains(&word.chars().count()))
+        .map(|word| word.to_lowercase())
+        .filter(|word| seen.insert(word.clone()))
+        .collect()
+}
+
+/// The text of a function run that searches match against.
+pub fn function_run_text(run: &FunctionRun) -> String {
+    let mut parts = vec![];
+    if let FunctionRunOutcome::Failure { error } = &run.outcome {
+        parts.push(error.as_str());
+    }
+    parts.push(run.udf_path.as_str());
+    parts.extend(run.log_lines.iter().map(|line| line.as_str()));
+    parts.join("\n")
+}
+
+/// The text of a `_deployment_audit_log` entry that searches match against.
+pub fn audit_log_text(entry: &ConvexObject) -> String {
+    ["action", "actor", "metadata"]
+        .into_iter()
+        .filter_map(|field| match entry.get(field)? {
+            ConvexValue::String(s) => Some(s.to_string()),
+            value => Some(value.to_internal_json().to_string()),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[derive(Clone, Debug)]
+pub struct LogSearchQuery {
+    /// Records match if they contain this text, ignoring case. Its longest
+    /// word has to appear in the record as a whole word.
+    pub text: String,
+    pub source: Option<LogSearchSource>,
+    /// Only match function runs that failed.
+    pub failures_only: bool,
+    pub since_ms: i64,
+    pub until_ms: Option<i64>,
+    pub limit: usize,
+}
+
+pub struct LogSearchHit {
+    pub source: LogSearchSource,
+    pub ts_ms: i64,
+    pub record: ResolvedDocument,
+}
+
+pub struct LogSearchModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> LogSearchModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    /// Adds postings for the words of a record that was just inserted.
+    pub async fn index(
+        &mut self,
+        source: LogSearchSource,
+        document_id: DeveloperDocumentId,
+        ts_ms: i64,
+        text: &str,
+    ) -> anyhow::Result<()> {
+        if LOG_SEARCH_RETENTION.is_none()
+            || !SystemMetadataModel::new_global(self.tx).table_exists(&LOG_SEARCH_TERMS_TABLE)
+        {
+            return Ok(());
+        }
+        for term in tokenize(text).into_iter().take(MAX_TERMS_PER_RECORD) {
+            let posting = LogSearchTerm {
+                term,
+                source,
+                document_id,
+                ts_ms,
+            };
+            SystemMetadataModel::new_global(self.tx)
+                .insert(&LOG_SEARCH_TERMS_TABLE, posting.try_into()?)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Records matching `query`, newest first.
+    pub async fn search(&mut self, query: LogSearchQuery) -> anyhow::Result<Vec<LogSearchHit>> {
+        self.tx.require_admin("search_logs")?;
+        let needle = query.text.trim().to_lowercase();
+        // Scan the longest word's postings since it's likely the rarest.
+        let Some(term) = tokenize(&needle)
+            .into_iter()
+            .max_by_key(|term| term.chars().count())
+        else {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidLogSearchQuery",
+                format!(
+                    "Search text must contain a word of at least {MIN_TERM_LEN} letters or digits."
+                ),
+            ));
+        };
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&LOG_SEARCH_TERMS_TABLE) {
+            return Ok(vec![]);
+        }
+        let mut range = vec![
+            IndexRangeExpression::Eq(TERM_FIELD.clone(), maybe_val!(term)),
+            IndexRangeExpression::Gte(TS_MS_FIELD.clone(), maybe_val!(query.since_ms)),
+        ];
+        if let Some(until_ms) = query.until_ms {
+            range.push(IndexRangeExpression::Lte(
+                TS_MS_FIELD.clone(),
+                maybe_val!(until_ms),
+            ));
+        }
+        let index_query = Query::index_range(IndexRange {
+            index_name: LOG_SEARCH_TERMS_INDEX_BY_TERM_AND_TS.name(),
+            range,
+            order: Order::Desc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, index_query)?;
+        let mut seen = BTreeSet::new();
+        let mut hits = vec![];
+        let mut scanned = 0;
+        while hits.len() < query.limit && scanned < MAX_POSTINGS_SCANNED {
+            let Some(doc) = query_stream.next(self.tx, None).await? else {
+                break;
+            };
+            scanned += 1;
+            let posting: ParsedDocument<LogSearchTerm> = doc.parse()?;
+            let posting = posting.into_value();
+            if query.source.is_some_and(|source| source != posting.source)
+                || !seen.insert(posting.document_id)
+            {
+                continue;
+            }
+            let id = self
+                .tx
+                .resolve_developer_id(&posting.document_id, TableNamespace::Global)?;
+            // The record may have been deleted by retention before its postings.
+            let Some(record) = self.tx.get(id).await? else {
+                continue;
+            };
+            let text = match posting.source {
+                LogSearchSource::FunctionRun => {
+                    let run: ParsedDocument<FunctionRun> = record.clone().parse()?;
+                    if query.failures_only
+                        && !matches!(run.outcome, FunctionRunOutcome::Failure { .. })
+                    {
+                        continue;
+                    }
+                    function_run_text(&run)
+                },
+                LogSearchSource::AuditLog => {
+                    if query.failures_only {
+                        continue;
+                    }
+                    audit_log_text(&record.value().0)
+                },
+            };
+            if !text.to_lowercase().contains(&needle) {
+                continue;
+            }
+            hits.push(LogSearchHit {
+                source: posting.source,
+                ts_ms: posting.ts_ms,
+                record,
+            });
+        }
+        Ok(hits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::{
+        components::ComponentPath,
+        types::UdfType,
+    };
+    use database::test_helpers::DbFixtures;
+    use runtime::testing::TestRuntime;
+
+    use super::tokenize;
+    use crate::{
+        deployment_audit_log::{
+            types::DeploymentAuditLogEvent,
+            DeploymentAuditLogModel,
+        },
+        function_runs::{
+            types::{
+                FunctionRun,
+                FunctionRunOutcome,
+            },
+            FunctionRunsModel,
+        },
+        log_search::{
+            types::LogSearchSource,
+            LogSearchModel,
+            LogSearchQuery,
+        },
+        test_helpers::DbFixturesWithModel,
+    };
+
+    fn run(udf_path: &str, started_at_ms: i64, outcome: FunctionRunOutcome) -> FunctionRun {
+        FunctionRun {
+            component_path: ComponentPath::root(),
+            udf_path: udf_path.to_string(),
+            udf_type: UdfType::Action,
+            caller: "Scheduler".to_string(),
+            request_id: "request".to_string(),
+            execution_id: "execution".to_string(),
+            started_at_ms,
+            duration_ms: 10,
+            cached: false,
+            outcome,
+            documents_read: 0,
+            bytes_read: 0,
+            documents_written: 0,
+            bytes_written: 0,
+            log_lines: vec!["[LOG] 'fetching https://api.example.com'".to_string()],
+            log_lines_truncated: false,
+        }
+    }
+
+    fn query(text: &str, since_ms: i64) -> LogSearchQuery {
+        LogSearchQuery {
+            text: text.to_string(),
+            source: None,
+            failures_only: false,
+            since_ms,
+            until_ms: None,
+            limit: 10,
+        }
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("Error: read ECONNRESET at a (b.js:1) error"),
+            vec!["error", "read", "econnreset", "at", "js"]
+        );
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_search_function_runs_and_audit_log(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new_with_model(&rt).await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = FunctionRunsModel::new(&mut tx);
+        model
+            .insert(run(
+                "sync:pull",
+                1000,
+                FunctionRunOutcome::Failure {
+                    error: "Error: read ECONNRESET".to_string(),
+                },
+            ))
+            .await?;
+        model
+            .insert(run(
+                "sync:push",
+                2000,
+                FunctionRunOutcome::Failure {
+                    error: "Error: ECONNRESET while writing".to_string(),
+                },
+            ))
+            .await?;
+        model
+            .insert(run("sync:econnreset", 3000, FunctionRunOutcome::Success))
+            .await?;
+        DeploymentAuditLogModel::new(&mut tx)
+            .insert(vec![DeploymentAuditLogEvent::DeleteFeatureFlag {
+                name: "econnreset_retries".to_string(),
+            }])
+            .await?;
+
+        let mut model = LogSearchModel::new(&mut tx);
+        let hits = model.search(query("econnreset", 0)).await?;
+        // Audit log entries only match whole words.
+        assert_eq!(hits.len(), 3);
+        assert!(hits.windows(2).all(|pair| pair[0].ts_ms >= pair[1].ts_ms));
+
+        let hits = model
+            .search(LogSearchQuery {
+                failures_only: true,
+                ..query("ECONNRESET", 1500)
+            })
+            .await?;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].ts_ms, 2000);
+
+        // The whole phrase has to appear.
+        let hits = model.search(query("read ECONNRESET", 0)).await?;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].ts_ms, 1000);
+
+        let hits = model
+            .search(LogSearchQuery {
+                source: Some(LogSearchSource::AuditLog),
+                ..query("delete_feature_flag", 0)
+            })
+            .await?;
+        assert_eq!(hits.len(), 1);
+
+        assert!(model.search(query("!", 0)).await.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+use std::{
+    fmt,
+    str::FromStr,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::{
+    codegen_convex_serialization,
+    DeveloperDocumentId,
+};
+
+/// Which table a searchable record lives in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum LogSearchSource {
+    /// A row in `_function_runs`.
+    FunctionRun,
+    /// A row in `_deployment_audit_log`.
+    AuditLog,
+}
+
+impl fmt::Display for LogSearchSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FunctionRun => write!(f, "functionRun"),
+            Self::AuditLog => write!(f, "auditLog"),
+        }
+    }
+}
+
+impl FromStr for LogSearchSource {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "functionRun" => Ok(Self::FunctionRun),
+            "auditLog" => Ok(Self::AuditLog),
+            _ => anyhow::bail!("Invalid log search source: {s}"),
+        }
+    }
+}
+
+/// One word of a function run or audit log entry, in `_log_search_terms`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct LogSearchTerm {
+    pub term: String,
+    pub source: LogSearchSource,
+    pub document_id: DeveloperDocumentId,
+    /// When the record happened, so searches can be limited to a time range
+    /// without reading older postings.
+    pub ts_ms: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedLogSearchTerm {
+    term: String,
+    source: String,
+    document_id: String,
+    ts_ms: i64,
+}
+
+impl From<LogSearchTerm> for SerializedLogSearchTerm {
+    fn from(term: LogSearchTerm) -> Self {
+        Self {
+            term: term.term,
+            source: term.source.to_string(),
+            document_id: term.document_id.encode(),
+            ts_ms: term.ts_ms,
+        }
+    }
+}
+
+impl TryFrom<SerializedLogSearchTerm> for LogSearchTerm {
+    type Error = anyhow::Error;
+
+    fn try_from(term: SerializedLogSearchTerm) -> anyhow::Result<Self> {
+        Ok(Self {
+            term: term.term,
+            source: term.source.parse()?,
+            document_id: DeveloperDocumentId::decode(&term.document_id)?,
+            ts_ms: term.ts_ms,
+        })
+    }
+}
+
+codegen_convex_serialization!(LogSearchTerm, SerializedLogSearchTerm);
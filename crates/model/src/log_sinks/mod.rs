@@ -32,6 +32,7 @@ use crate::{
 
 pub mod types;
 use types::{
+    filter::LogSinkFilter,
     LogSinksRow,
     SinkConfig,
     SinkState,
@@ -130,11 +131,16 @@ impl<'a, RT: Runtime> LogSinksModel<'a, RT> {
         Ok(())
     }
 
-    pub async fn add_or_update(&mut self, config: SinkConfig) -> anyhow::Result<()> {
+    pub async fn add_or_update(
+        &mut self,
+        config: SinkConfig,
+        filter: LogSinkFilter,
+    ) -> anyhow::Result<()> {
         let sink_type = config.sink_type();
         let row = LogSinksRow {
             status: SinkState::Pending,
             config,
+            filter,
         };
 
         // Filter to non-tombstoned log sinks
@@ -173,7 +179,25 @@ impl<'a, RT: Runtime> LogSinksModel<'a, RT> {
                 .delete(sink.id())
                 .await?;
         };
-        self.add_or_update(config).await?;
+        self.add_or_update(config, LogSinkFilter::default()).await?;
+        Ok(())
+    }
+
+    /// Marks the sink of the given type for removal. The LogManager stops
+    /// sending to it and then deletes the row with `delete_tombstoned`.
+    pub async fn remove(&mut self, provider: SinkType) -> anyhow::Result<()> {
+        let Some(row) = self.get_by_provider(provider.clone()).await? else {
+            return Err(ErrorMetadata::not_found(
+                "LogSinkNotFound",
+                format!("No log sink of type {provider:?} is configured"),
+            )
+            .into());
+        };
+        self.mark_for_removal(row.id()).await
+    }
+
+    pub async fn delete_tombstoned(&mut self, id: ResolvedDocumentId) -> anyhow::Result<()> {
+        SystemMetadataModel::new_global(self.tx).delete(id).await?;
         Ok(())
     }
 
@@ -0,0 +1,192 @@
+use common::{
+    log_lines::{
+        LogFieldMatch,
+        LogLevel,
+    },
+    log_streaming::{
+        LogEvent,
+        StructuredLogEvent,
+    },
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// Restricts which events a log sink receives. The default filter lets
+/// everything through.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct LogSinkFilter {
+    /// If non-empty, events from functions are only sent if the function path
+    /// (e.g. `messages:send`) starts with one of these prefixes. Events not
+    /// tied to a function, like audit logs, are unaffected.
+    pub function_path_prefixes: Vec<String>,
+    /// If set, console logs below this level are dropped. Other topics are
+    /// unaffected.
+    pub min_log_level: Option<LogLevel>,
+    /// If non-empty, console logs are only sent if they were logged with
+    /// `ctx.log` and have all of these field values. Other topics are
+    /// unaffected.
+    pub log_fields: Vec<LogFieldMatch>,
+}
+
+impl LogSinkFilter {
+    pub fn matches(&self, event: &LogEvent) -> bool {
+        let source = match &event.event {
+            StructuredLogEvent::Console { source, log_line } => {
+                if let Some(min_log_level) = &self.min_log_level
+                    && log_line.level.severity() < min_log_level.severity()
+                {
+                    return false;
+                }
+                if !self
+                    .log_fields
+                    .iter()
+                    .all(|field_match| log_line.matches_field(field_match))
+                {
+                    return false;
+                }
+                source
+            },
+            StructuredLogEvent::FunctionExecution { source, .. }
+            | StructuredLogEvent::Exception { source, .. } => source,
+            StructuredLogEvent::Verification
+            | StructuredLogEvent::DeploymentAuditLog { .. }
+            | StructuredLogEvent::SchedulerStats { .. }
+            | StructuredLogEvent::ScheduledJobLag { .. } => return true,
+        };
+        self.function_path_prefixes.is_empty()
+            || self
+                .function_path_prefixes
+                .iter()
+                .any(|prefix| source.udf_path.starts_with(prefix))
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedLogSinkFilter {
+    pub function_path_prefixes: Vec<String>,
+    pub min_log_level: Option<String>,
+    // Filters written before structured logging don't have this field.
+    #[serde(default)]
+    pub log_fields: Vec<SerializedLogFieldMatch>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SerializedLogFieldMatch {
+    pub field: String,
+    pub value: String,
+}
+
+impl From<LogSinkFilter> for SerializedLogSinkFilter {
+    fn from(value: LogSinkFilter) -> Self {
+        Self {
+            function_path_prefixes: value.function_path_prefixes,
+            min_log_level: value.min_log_level.map(|level| level.to_string()),
+            log_fields: value
+                .log_fields
+                .into_iter()
+                .map(|LogFieldMatch { field, value }| SerializedLogFieldMatch { field, value })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<SerializedLogSinkFilter> for LogSinkFilter {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedLogSinkFilter) -> Result<Self, Self::Error> {
+        Ok(Self {
+            function_path_prefixes: value.function_path_prefixes,
+            min_log_level: value
+                .min_log_level
+                .map(|level| level.to_uppercase().parse())
+                .transpose()?,
+            log_fields: value
+                .log_fields
+                .into_iter()
+                .map(|SerializedLogFieldMatch { field, value }| LogFieldMatch { field, value })
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::{
+        log_lines::{
+            LogFieldMatch,
+            LogLevel,
+            LogLineStructured,
+        },
+        log_streaming::{
+            FunctionEventSource,
+            LogEvent,
+            StructuredLogEvent,
+        },
+        runtime::UnixTimestamp,
+    };
+    use value::assert_obj;
+
+    use super::LogSinkFilter;
+
+    fn console_event(udf_path: &str, level: LogLevel) -> LogEvent {
+        let timestamp = UnixTimestamp::from_millis(1700000000000);
+        LogEvent {
+            timestamp,
+            event: StructuredLogEvent::Console {
+                source: FunctionEventSource {
+                    udf_path: udf_path.to_string(),
+                    ..FunctionEventSource::new_for_test()
+                },
+                log_line: LogLineStructured::new_developer_log_line(
+                    level,
+                    vec!["hello".to_string()],
+                    timestamp,
+                ),
+            },
+        }
+    }
+
+    #[test]
+    fn test_log_sink_filter() {
+        assert!(LogSinkFilter::default().matches(&console_event("a:b", LogLevel::Debug)));
+
+        let filter = LogSinkFilter {
+            function_path_prefixes: vec!["payments/".to_string()],
+            min_log_level: Some(LogLevel::Warn),
+            log_fields: vec![],
+        };
+        assert!(filter.matches(&console_event("payments/stripe:charge", LogLevel::Error)));
+        assert!(!filter.matches(&console_event("payments/stripe:charge", LogLevel::Info)));
+        assert!(!filter.matches(&console_event("messages:send", LogLevel::Error)));
+        assert!(filter.matches(&LogEvent {
+            timestamp: UnixTimestamp::from_millis(1700000000000),
+            event: StructuredLogEvent::Verification,
+        }));
+
+        let filter = LogSinkFilter {
+            log_fields: vec![LogFieldMatch {
+                field: "user".to_string(),
+                value: "alice".to_string(),
+            }],
+            ..LogSinkFilter::default()
+        };
+        assert!(!filter.matches(&console_event("a:b", LogLevel::Info)));
+        let timestamp = UnixTimestamp::from_millis(1700000000000);
+        assert!(filter.matches(&LogEvent {
+            timestamp,
+            event: StructuredLogEvent::Console {
+                source: FunctionEventSource::new_for_test(),
+                log_line: LogLineStructured::new_structured_log_line(
+                    LogLevel::Info,
+                    "hello".to_string(),
+                    assert_obj!("user" => "alice"),
+                    timestamp,
+                ),
+            },
+        }));
+    }
+}
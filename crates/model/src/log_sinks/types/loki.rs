@@ -0,0 +1,92 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// Configuration for pushing logs to a Grafana Loki instance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LokiConfig {
+    /// Base URL of the Loki instance, e.g. `https://logs.example.com`. Logs
+    /// are pushed to `/loki/api/v1/push` under it.
+    pub url: reqwest::Url,
+    /// Sent as `X-Scope-OrgID` for multi-tenant Loki deployments.
+    pub tenant_id: Option<String>,
+    /// Extra stream labels attached to every log line.
+    pub labels: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedLokiConfig {
+    pub url: String,
+    pub tenant_id: Option<String>,
+    pub labels: Option<BTreeMap<String, String>>,
+}
+
+impl From<LokiConfig> for SerializedLokiConfig {
+    fn from(value: LokiConfig) -> Self {
+        Self {
+            url: value.url.to_string(),
+            tenant_id: value.tenant_id,
+            labels: Some(value.labels),
+        }
+    }
+}
+
+impl TryFrom<SerializedLokiConfig> for LokiConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedLokiConfig) -> Result<Self, Self::Error> {
+        Ok(LokiConfig {
+            url: value.url.parse()?,
+            tenant_id: value.tenant_id,
+            labels: value.labels.unwrap_or_default(),
+        })
+    }
+}
+
+impl fmt::Display for LokiConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LokiConfig {{ url: ... }}")
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+mod proptest {
+    use std::collections::BTreeMap;
+
+    use proptest::prelude::*;
+
+    use super::LokiConfig;
+
+    impl Arbitrary for LokiConfig {
+        type Parameters = ();
+
+        type Strategy = impl Strategy<Value = LokiConfig>;
+
+        fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+            (
+                any::<proptest_http::ArbitraryUri>(),
+                any::<Option<String>>(),
+                any::<BTreeMap<String, String>>(),
+            )
+                .prop_filter_map(
+                    "Invalid URL for LokiConfig",
+                    |(url, tenant_id, labels)| {
+                        reqwest::Url::parse(url.0.to_string().as_str())
+                            .ok()
+                            .map(|url| LokiConfig {
+                                url,
+                                tenant_id,
+                                labels,
+                            })
+                    },
+                )
+        }
+    }
+}
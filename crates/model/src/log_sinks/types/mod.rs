@@ -6,8 +6,15 @@ use serde::{
 };
 use value::codegen_convex_serialization;
 
+use self::filter::{
+    LogSinkFilter,
+    SerializedLogSinkFilter,
+};
+
 pub mod axiom;
 pub mod datadog;
+pub mod filter;
+pub mod loki;
 pub mod mock_sink;
 pub mod sentry;
 pub mod webhook;
@@ -21,6 +28,7 @@ pub const LOG_SINKS_LIMIT: usize = 5;
 pub struct LogSinksRow {
     pub status: SinkState,
     pub config: SinkConfig,
+    pub filter: LogSinkFilter,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -28,6 +36,9 @@ pub struct LogSinksRow {
 pub struct SerializedLogSinksRow {
     pub status: SerializedSinkState,
     pub config: SerializedSinkConfig,
+    // Rows written before sink filters were added don't have this field.
+    #[serde(default)]
+    pub filter: Option<SerializedLogSinkFilter>,
 }
 
 impl TryFrom<LogSinksRow> for SerializedLogSinksRow {
@@ -37,6 +48,7 @@ impl TryFrom<LogSinksRow> for SerializedLogSinksRow {
         Ok(Self {
             status: value.status.into(),
             config: value.config.try_into()?,
+            filter: Some(value.filter.into()),
         })
     }
 }
@@ -48,6 +60,11 @@ impl TryFrom<SerializedLogSinksRow> for LogSinksRow {
         Ok(Self {
             status: value.status.into(),
             config: value.config.try_into()?,
+            filter: value
+                .filter
+                .map(LogSinkFilter::try_from)
+                .transpose()?
+                .unwrap_or_default(),
         })
     }
 }
@@ -132,6 +149,7 @@ pub enum SinkType {
     Axiom,
     AxiomV2,
     Sentry,
+    Loki,
     #[cfg(any(test, feature = "testing"))]
     Mock,
     #[cfg(any(test, feature = "testing"))]
@@ -148,6 +166,7 @@ pub enum SinkConfig {
     Webhook(webhook::WebhookConfig),
     Axiom(axiom::AxiomConfig),
     Sentry(sentry::SentryConfig),
+    Loki(loki::LokiConfig),
     #[cfg(any(test, feature = "testing"))]
     Mock,
     #[cfg(any(test, feature = "testing"))]
@@ -165,6 +184,7 @@ pub enum SerializedSinkConfig {
     Webhook(webhook::SerializedWebhookConfig),
     Axiom(axiom::SerializedAxiomConfig),
     Sentry(sentry::SerializedSentryConfig),
+    Loki(loki::SerializedLokiConfig),
     #[cfg(any(test, feature = "testing"))]
     Mock,
     #[cfg(any(test, feature = "testing"))]
@@ -189,6 +209,9 @@ impl TryFrom<SerializedSinkConfig> for SinkConfig {
             SerializedSinkConfig::Sentry(config) => {
                 Ok(SinkConfig::Sentry(sentry::SentryConfig::try_from(config)?))
             },
+            SerializedSinkConfig::Loki(config) => {
+                Ok(SinkConfig::Loki(loki::LokiConfig::try_from(config)?))
+            },
             #[cfg(any(test, feature = "testing"))]
             SerializedSinkConfig::Mock => Ok(SinkConfig::Mock),
             #[cfg(any(test, feature = "testing"))]
@@ -215,6 +238,9 @@ impl TryFrom<SinkConfig> for SerializedSinkConfig {
             SinkConfig::Sentry(config) => Ok(SerializedSinkConfig::Sentry(
                 sentry::SerializedSentryConfig::try_from(config)?,
             )),
+            SinkConfig::Loki(config) => Ok(SerializedSinkConfig::Loki(
+                loki::SerializedLokiConfig::from(config),
+            )),
             #[cfg(any(test, feature = "testing"))]
             SinkConfig::Mock => Ok(SerializedSinkConfig::Mock),
             #[cfg(any(test, feature = "testing"))]
@@ -233,6 +259,7 @@ impl fmt::Display for SinkConfig {
             Self::Webhook(config) => write!(f, "Webhook({})", config),
             Self::Axiom(config) => write!(f, "Axiom({})", config),
             Self::Sentry(config) => write!(f, "Sentry({})", config),
+            Self::Loki(config) => write!(f, "Loki({})", config),
             #[cfg(any(test, feature = "testing"))]
             Self::Mock => write!(f, "Mock"),
             #[cfg(any(test, feature = "testing"))]
@@ -249,6 +276,7 @@ impl SinkConfig {
             Self::Webhook(_) => SinkType::Webhook,
             Self::Axiom(_) => SinkType::Axiom,
             Self::Sentry(_) => SinkType::Sentry,
+            Self::Loki(_) => SinkType::Loki,
             #[cfg(any(test, feature = "testing"))]
             Self::Mock => SinkType::Mock,
             #[cfg(any(test, feature = "testing"))]
@@ -0,0 +1,174 @@
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParseDocument,
+        ParsedDocument,
+    },
+    query::{
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+};
+use database::{
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use value::{
+    TableName,
+    TableNamespace,
+};
+
+use self::types::MaintenanceMode;
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static MAINTENANCE_MODE_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_maintenance_mode"
+        .parse()
+        .expect("Invalid built-in maintenance_mode table")
+});
+
+pub const DEFAULT_MAINTENANCE_MESSAGE: &str =
+    "This deployment is undergoing maintenance. Please try again later.";
+
+pub const DEFAULT_MAINTENANCE_STATUS_CODE: u16 = 503;
+
+pub struct MaintenanceModeTable;
+impl SystemTable for MaintenanceModeTable {
+    type Metadata = MaintenanceMode;
+
+    fn table_name() -> &'static TableName {
+        &MAINTENANCE_MODE_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![]
+    }
+}
+
+pub struct MaintenanceModeModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> MaintenanceModeModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    /// The deployment's maintenance mode, if it's in maintenance.
+    pub async fn get(&mut self) -> anyhow::Result<Option<ParsedDocument<MaintenanceMode>>> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&MAINTENANCE_MODE_TABLE) {
+            return Ok(None);
+        }
+        let query = Query::full_table_scan(MAINTENANCE_MODE_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParseDocument::<MaintenanceMode>::parse)
+            .transpose()
+    }
+
+    /// Puts the deployment in maintenance, or changes the message and status
+    /// of the current maintenance.
+    pub async fn enable(
+        &mut self,
+        message: String,
+        status_code: u16,
+    ) -> anyhow::Result<MaintenanceMode> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("enable_maintenance_mode"));
+        }
+        if !(400..600).contains(&status_code) {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidMaintenanceStatus",
+                format!("Maintenance status code must be between 400 and 599, not {status_code}.")
+            ));
+        }
+        let existing = self.get().await?;
+        let started_at_ms = match &existing {
+            Some(existing) => existing.started_at_ms,
+            None => self.tx.runtime().unix_timestamp().as_ms_since_epoch()? as i64,
+        };
+        let mode = MaintenanceMode {
+            message,
+            status_code,
+            started_at_ms,
+        };
+        match existing {
+            Some(existing) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), mode.clone().try_into()?)
+                    .await?;
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&MAINTENANCE_MODE_TABLE, mode.clone().try_into()?)
+                    .await?;
+            },
+        }
+        Ok(mode)
+    }
+
+    /// Takes the deployment out of maintenance. Returns whether it was in
+    /// maintenance.
+    pub async fn disable(&mut self) -> anyhow::Result<bool> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("disable_maintenance_mode"));
+        }
+        let Some(existing) = self.get().await? else {
+            return Ok(false);
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .delete(existing.id())
+            .await?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use database::test_helpers::DbFixtures;
+    use errors::ErrorMetadata;
+    use runtime::testing::TestRuntime;
+
+    use crate::{
+        maintenance_mode::MaintenanceModeModel,
+        test_helpers::DbFixturesWithModel,
+    };
+
+    #[convex_macro::test_runtime]
+    async fn test_enable_and_disable_maintenance_mode(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new_with_model(&rt).await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = MaintenanceModeModel::new(&mut tx);
+        assert!(model.get().await?.is_none());
+
+        let err = model
+            .enable("Back soon".to_string(), 200)
+            .await
+            .unwrap_err();
+        let err = err.downcast_ref::<ErrorMetadata>().unwrap();
+        assert_eq!(err.short_msg, "InvalidMaintenanceStatus");
+
+        let started = model.enable("Back soon".to_string(), 503).await?;
+        let updated = model.enable("Almost done".to_string(), 403).await?;
+        assert_eq!(updated.started_at_ms, started.started_at_ms);
+        let current = model.get().await?.unwrap().into_value();
+        assert_eq!(current, updated);
+
+        assert!(model.disable().await?);
+        assert!(model.get().await?.is_none());
+        assert!(!model.disable().await?);
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// A deployment-wide switch that rejects function calls made by clients,
+/// while admins can keep calling functions and managing the deployment.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct MaintenanceMode {
+    /// Shown to clients whose calls are rejected.
+    pub message: String,
+    /// The HTTP status, between 400 and 599, that rejected calls fail with.
+    #[cfg_attr(any(test, feature = "testing"), proptest(strategy = "400..600u16"))]
+    pub status_code: u16,
+    pub started_at_ms: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedMaintenanceMode {
+    message: String,
+    status_code: i64,
+    started_at_ms: i64,
+}
+
+impl From<MaintenanceMode> for SerializedMaintenanceMode {
+    fn from(mode: MaintenanceMode) -> Self {
+        Self {
+            message: mode.message,
+            status_code: mode.status_code.into(),
+            started_at_ms: mode.started_at_ms,
+        }
+    }
+}
+
+impl TryFrom<SerializedMaintenanceMode> for MaintenanceMode {
+    type Error = anyhow::Error;
+
+    fn try_from(mode: SerializedMaintenanceMode) -> anyhow::Result<Self> {
+        Ok(Self {
+            message: mode.message,
+            status_code: mode.status_code.try_into()?,
+            started_at_ms: mode.started_at_ms,
+        })
+    }
+}
+
+codegen_convex_serialization!(MaintenanceMode, SerializedMaintenanceMode);
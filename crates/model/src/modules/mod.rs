@@ -67,6 +67,7 @@ use self::{
     },
 };
 use crate::{
+    canary::CanaryModel,
     config::{
         module_loader::ModuleLoader,
         types::{
@@ -370,6 +371,45 @@ impl<'a, RT: Runtime> ModuleModel<'a, RT> {
         Ok(module_id)
     }
 
+    /// Makes `modules` the component's application modules, reusing the ids
+    /// of existing modules at the same paths and deleting the rest. Returns
+    /// the new modules' analyze results.
+    pub(crate) async fn replace_application_metadata(
+        &mut self,
+        component: ComponentId,
+        modules: Vec<ModuleMetadata>,
+    ) -> anyhow::Result<BTreeMap<CanonicalizedModulePath, AnalyzedModule>> {
+        let mut remaining: BTreeMap<_, _> = self
+            .get_application_metadata(component)
+            .await?
+            .into_iter()
+            .map(|module| (module.path.clone(), module.id()))
+            .collect();
+        let mut analyze_results = BTreeMap::new();
+        for module in modules {
+            let existing_module_id = remaining.remove(&module.path);
+            if let Some(analyze_result) = &module.analyze_result {
+                analyze_results.insert(module.path.clone(), analyze_result.clone());
+            }
+            self.put_module_metadata(
+                existing_module_id,
+                CanonicalizedComponentModulePath {
+                    component,
+                    module_path: module.path,
+                },
+                module.source_package_id,
+                module.analyze_result,
+                module.environment,
+                module.sha256,
+            )
+            .await?;
+        }
+        for (_, module_id) in remaining {
+            self.delete(component, module_id).await?;
+        }
+        Ok(analyze_results)
+    }
+
     /// Delete a module, making it inaccessible for subsequent transactions.
     pub async fn delete(
         &mut self,
@@ -391,6 +431,15 @@ impl<'a, RT: Runtime> ModuleModel<'a, RT> {
         &mut self,
         path: CanonicalizedComponentModulePath,
     ) -> anyhow::Result<Option<ParsedDocument<ModuleMetadata>>> {
+        if path.component.is_root() && !path.module_path.is_system() {
+            if let Some(canary) = CanaryModel::new(self.tx).active().await? {
+                if canary.routes_to_canary(self.tx.identity(), *self.tx.begin_timestamp()) {
+                    return CanaryModel::new(self.tx)
+                        .module_metadata(&path.module_path)
+                        .await;
+                }
+            }
+        }
         let namespace = path.component.into();
         let module_path = ConvexValue::try_from(path.module_path.as_str())?;
         let index_range = IndexRange {
@@ -0,0 +1,329 @@
+//! Work queues. Mutations enqueue messages, and workers (actions or other
+//! mutations) lease them for a visibility timeout and then ack or nack them.
+//! Messages that keep failing are moved to a dead letter queue after
+//! `max_attempts` leases.
+//!
+//! Leasing scans an index on `(queue, visibleTs)`, so workers only read the
+//! messages they're handed rather than a user table they all write to.
+
+use std::{
+    sync::LazyLock,
+    time::Duration,
+};
+
+use common::{
+    document::{
+        ParseDocument,
+        ParsedDocument,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::{
+        Runtime,
+        UnixTimestamp,
+    },
+};
+use database::{
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use sync_types::Timestamp;
+use value::{
+    ConvexValue,
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::{
+    LeasedQueueMessage,
+    QueueMessage,
+};
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static QUEUE_MESSAGES_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_queue_messages"
+        .parse()
+        .expect("Invalid built-in queue_messages table")
+});
+
+/// By queue and visible ts. Used to find messages to lease, and dead lettered
+/// messages (which have a null `visibleTs`) to redrive.
+pub static QUEUE_MESSAGES_INDEX_BY_QUEUE_AND_VISIBLE_TS: LazyLock<SystemIndex<QueueMessagesTable>> =
+    LazyLock::new(|| {
+        SystemIndex::new("by_queue_and_visible_ts", [&QUEUE_FIELD, &VISIBLE_TS_FIELD]).unwrap()
+    });
+/// By lease id. Used to find the message an ack or nack refers to.
+pub static QUEUE_MESSAGES_INDEX_BY_LEASE_ID: LazyLock<SystemIndex<QueueMessagesTable>> =
+    LazyLock::new(|| SystemIndex::new("by_lease_id", [&LEASE_ID_FIELD]).unwrap());
+static QUEUE_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "queue".parse().expect("invalid queue field"));
+static VISIBLE_TS_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "visibleTs".parse().expect("invalid visibleTs field"));
+static LEASE_ID_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "leaseId".parse().expect("invalid leaseId field"));
+
+const MAX_QUEUE_NAME_LEN: usize = 128;
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+const MAX_MAX_ATTEMPTS: u32 = 100;
+const MAX_LEASE_BATCH_SIZE: usize = 100;
+pub const DEFAULT_VISIBILITY_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_VISIBILITY_TIMEOUT: Duration = Duration::from_secs(12 * 60 * 60);
+
+pub struct QueueMessagesTable;
+impl SystemTable for QueueMessagesTable {
+    type Metadata = QueueMessage;
+
+    fn table_name() -> &'static TableName {
+        &QUEUE_MESSAGES_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![
+            QUEUE_MESSAGES_INDEX_BY_QUEUE_AND_VISIBLE_TS.clone(),
+            QUEUE_MESSAGES_INDEX_BY_LEASE_ID.clone(),
+        ]
+    }
+}
+
+pub struct QueueModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+    namespace: TableNamespace,
+}
+
+impl<'a, RT: Runtime> QueueModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>, namespace: TableNamespace) -> Self {
+        Self { tx, namespace }
+    }
+
+    /// Adds a message to `queue` that becomes visible to workers at
+    /// `visible_at`.
+    pub async fn enqueue(
+        &mut self,
+        queue: String,
+        payload: ConvexValue,
+        visible_at: UnixTimestamp,
+        max_attempts: u32,
+    ) -> anyhow::Result<()> {
+        validate_queue_name(&queue)?;
+        anyhow::ensure!(
+            (1..=MAX_MAX_ATTEMPTS).contains(&max_attempts),
+            ErrorMetadata::bad_request(
+                "InvalidMaxAttempts",
+                format!("maxAttempts must be between 1 and {MAX_MAX_ATTEMPTS}"),
+            )
+        );
+        let message = QueueMessage {
+            queue,
+            payload,
+            visible_ts: Some(visible_at.as_system_time().try_into()?),
+            lease_id: None,
+            attempts: 0,
+            max_attempts,
+        };
+        SystemMetadataModel::new(self.tx, self.namespace)
+            .insert_metadata(&QUEUE_MESSAGES_TABLE, message.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    /// Leases up to `max_messages` visible messages from `queue`, oldest
+    /// first. The messages are hidden from other workers until `now +
+    /// visibility_timeout`, after which they're redelivered unless acked.
+    /// Messages that have already been leased `max_attempts` times are moved
+    /// to the dead letter queue instead of being returned.
+    pub async fn lease(
+        &mut self,
+        queue: &str,
+        max_messages: usize,
+        visibility_timeout: Duration,
+        now: UnixTimestamp,
+    ) -> anyhow::Result<Vec<LeasedQueueMessage>> {
+        validate_queue_name(queue)?;
+        anyhow::ensure!(
+            (1..=MAX_LEASE_BATCH_SIZE).contains(&max_messages),
+            ErrorMetadata::bad_request(
+                "InvalidLeaseBatchSize",
+                format!("maxMessages must be between 1 and {MAX_LEASE_BATCH_SIZE}"),
+            )
+        );
+        validate_visibility_timeout(visibility_timeout)?;
+        if !SystemMetadataModel::new(self.tx, self.namespace).table_exists(&QUEUE_MESSAGES_TABLE) {
+            return Ok(vec![]);
+        }
+        let now_ts: Timestamp = now.as_system_time().try_into()?;
+        let index_query = Query::index_range(IndexRange {
+            index_name: QUEUE_MESSAGES_INDEX_BY_QUEUE_AND_VISIBLE_TS.name(),
+            range: vec![
+                IndexRangeExpression::Eq(
+                    QUEUE_FIELD.clone(),
+                    ConvexValue::try_from(queue.to_string())?.into(),
+                ),
+                IndexRangeExpression::Gt(VISIBLE_TS_FIELD.clone(), ConvexValue::Null.into()),
+                IndexRangeExpression::Lte(
+                    VISIBLE_TS_FIELD.clone(),
+                    ConvexValue::Int64(now_ts.into()).into(),
+                ),
+            ],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, index_query)?;
+        let mut visible = vec![];
+        while let Some(doc) = query_stream
+            .next(self.tx, Some(max_messages - visible.len()))
+            .await?
+        {
+            visible.push(ParseDocument::<QueueMessage>::parse(doc)?);
+            if visible.len() >= max_messages {
+                break;
+            }
+        }
+
+        let invisible_until: Timestamp = (now + visibility_timeout).as_system_time().try_into()?;
+        let mut leased = vec![];
+        for message in visible {
+            let (id, mut message) = message.into_id_and_value();
+            if message.attempts >= message.max_attempts {
+                message.visible_ts = None;
+                message.lease_id = None;
+            } else {
+                let lease_id = self.tx.runtime().new_uuid_v4().to_string();
+                message.attempts += 1;
+                message.visible_ts = Some(invisible_until);
+                message.lease_id = Some(lease_id.clone());
+                leased.push(LeasedQueueMessage {
+                    receipt: lease_id,
+                    payload: message.payload.clone(),
+                    attempts: message.attempts,
+                });
+            }
+            SystemMetadataModel::new(self.tx, self.namespace)
+                .replace(id, message.try_into()?)
+                .await?;
+        }
+        Ok(leased)
+    }
+
+    /// Removes a leased message from its queue.
+    pub async fn ack(&mut self, receipt: &str) -> anyhow::Result<()> {
+        let message = self.get_by_receipt(receipt).await?;
+        SystemMetadataModel::new(self.tx, self.namespace)
+            .delete(message.id())
+            .await?;
+        Ok(())
+    }
+
+    /// Returns a leased message to its queue, visible again at `visible_at`.
+    pub async fn nack(&mut self, receipt: &str, visible_at: UnixTimestamp) -> anyhow::Result<()> {
+        let (id, mut message) = self.get_by_receipt(receipt).await?.into_id_and_value();
+        message.visible_ts = Some(visible_at.as_system_time().try_into()?);
+        message.lease_id = None;
+        SystemMetadataModel::new(self.tx, self.namespace)
+            .replace(id, message.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    /// Moves `queue`'s dead lettered messages back onto the queue with their
+    /// attempts reset. Returns how many messages were moved.
+    pub async fn redrive(&mut self, queue: &str, now: UnixTimestamp) -> anyhow::Result<usize> {
+        validate_queue_name(queue)?;
+        if !SystemMetadataModel::new(self.tx, self.namespace).table_exists(&QUEUE_MESSAGES_TABLE) {
+            return Ok(0);
+        }
+        let index_query = Query::index_range(IndexRange {
+            index_name: QUEUE_MESSAGES_INDEX_BY_QUEUE_AND_VISIBLE_TS.name(),
+            range: vec![
+                IndexRangeExpression::Eq(
+                    QUEUE_FIELD.clone(),
+                    ConvexValue::try_from(queue.to_string())?.into(),
+                ),
+                IndexRangeExpression::Eq(VISIBLE_TS_FIELD.clone(), ConvexValue::Null.into()),
+            ],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, index_query)?;
+        let mut dead_lettered = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            dead_lettered.push(ParseDocument::<QueueMessage>::parse(doc)?);
+        }
+        let now_ts: Timestamp = now.as_system_time().try_into()?;
+        let count = dead_lettered.len();
+        for message in dead_lettered {
+            let (id, mut message) = message.into_id_and_value();
+            message.visible_ts = Some(now_ts);
+            message.attempts = 0;
+            SystemMetadataModel::new(self.tx, self.namespace)
+                .replace(id, message.try_into()?)
+                .await?;
+        }
+        Ok(count)
+    }
+
+    async fn get_by_receipt(
+        &mut self,
+        receipt: &str,
+    ) -> anyhow::Result<ParsedDocument<QueueMessage>> {
+        let not_found = || {
+            ErrorMetadata::bad_request(
+                "QueueLeaseNotFound",
+                "The message's lease has expired and the message was redelivered, or it was \
+                 already acked or nacked",
+            )
+        };
+        if !SystemMetadataModel::new(self.tx, self.namespace).table_exists(&QUEUE_MESSAGES_TABLE) {
+            anyhow::bail!(not_found());
+        }
+        let index_query = Query::index_range(IndexRange {
+            index_name: QUEUE_MESSAGES_INDEX_BY_LEASE_ID.name(),
+            range: vec![IndexRangeExpression::Eq(
+                LEASE_ID_FIELD.clone(),
+                ConvexValue::try_from(receipt.to_string())?.into(),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, index_query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParseDocument::<QueueMessage>::parse)
+            .transpose()?
+            .ok_or_else(|| not_found().into())
+    }
+}
+
+fn validate_queue_name(queue: &str) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        !queue.is_empty() && queue.len() <= MAX_QUEUE_NAME_LEN,
+        ErrorMetadata::bad_request(
+            "InvalidQueueName",
+            format!("Queue names must be between 1 and {MAX_QUEUE_NAME_LEN} bytes"),
+        )
+    );
+    Ok(())
+}
+
+pub fn validate_visibility_timeout(timeout: Duration) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        !timeout.is_zero() && timeout <= MAX_VISIBILITY_TIMEOUT,
+        ErrorMetadata::bad_request(
+            "InvalidVisibilityTimeout",
+            format!(
+                "visibilityTimeoutMs must be positive and at most {}",
+                MAX_VISIBILITY_TIMEOUT.as_millis()
+            ),
+        )
+    );
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_bytes::ByteBuf;
+use sync_types::Timestamp;
+use value::{
+    codegen_convex_serialization,
+    json_deserialize,
+    ConvexValue,
+};
+
+/// A message on a work queue. Messages are visible to `lease` once
+/// `visible_ts` has passed. Leasing a message pushes `visible_ts` out by the
+/// visibility timeout, so it's redelivered if the worker doesn't ack it in
+/// time.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct QueueMessage {
+    pub queue: String,
+    pub payload: ConvexValue,
+    /// `None` once the message has been moved to the dead letter queue.
+    pub visible_ts: Option<Timestamp>,
+    /// Set by the most recent lease. Acks and nacks must present it, so a
+    /// worker whose lease expired can't ack a redelivered message.
+    pub lease_id: Option<String>,
+    pub attempts: u32,
+    pub max_attempts: u32,
+}
+
+impl QueueMessage {
+    pub fn is_dead_lettered(&self) -> bool {
+        self.visible_ts.is_none()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedQueueMessage {
+    queue: String,
+    // Serialize the payload as binary since we restrict what field names can
+    // be used in system documents.
+    payload: ByteBuf,
+    visible_ts: Option<i64>,
+    lease_id: Option<String>,
+    attempts: i64,
+    max_attempts: i64,
+}
+
+impl TryFrom<QueueMessage> for SerializedQueueMessage {
+    type Error = anyhow::Error;
+
+    fn try_from(message: QueueMessage) -> anyhow::Result<Self> {
+        Ok(Self {
+            queue: message.queue,
+            payload: ByteBuf::from(message.payload.json_serialize()?.into_bytes()),
+            visible_ts: message.visible_ts.map(|ts| ts.into()),
+            lease_id: message.lease_id,
+            attempts: message.attempts.into(),
+            max_attempts: message.max_attempts.into(),
+        })
+    }
+}
+
+impl TryFrom<SerializedQueueMessage> for QueueMessage {
+    type Error = anyhow::Error;
+
+    fn try_from(message: SerializedQueueMessage) -> anyhow::Result<Self> {
+        Ok(Self {
+            queue: message.queue,
+            payload: json_deserialize(std::str::from_utf8(&message.payload)?)?,
+            visible_ts: message.visible_ts.map(|ts| ts.try_into()).transpose()?,
+            lease_id: message.lease_id,
+            attempts: message.attempts.try_into()?,
+            max_attempts: message.max_attempts.try_into()?,
+        })
+    }
+}
+
+codegen_convex_serialization!(QueueMessage, SerializedQueueMessage);
+
+/// A message handed to a worker by `lease`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LeasedQueueMessage {
+    /// Opaque handle for acking or nacking the message.
+    pub receipt: String,
+    pub payload: ConvexValue,
+    /// Number of times the message has been leased, including this one.
+    pub attempts: u32,
+}
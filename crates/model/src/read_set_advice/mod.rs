@@ -0,0 +1,164 @@
+use std::sync::LazyLock;
+
+use common::{
+    components::ComponentPath,
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+        CREATION_TIME_FIELD_PATH,
+    },
+    maybe_val,
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+};
+use database::{
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use value::{
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::ReadSetAdvice;
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static READ_SET_ADVICE_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_read_set_advice"
+        .parse()
+        .expect("Invalid built-in read set advice table")
+});
+
+static COMPONENT_PATH_FIELD: LazyLock<FieldPath> = LazyLock::new(|| {
+    "componentPath"
+        .parse()
+        .expect("invalid componentPath field")
+});
+
+static UDF_PATH_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "udfPath".parse().expect("invalid udfPath field"));
+
+static LAST_WARNED_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "lastWarnedMs".parse().expect("invalid lastWarnedMs field"));
+
+pub static READ_SET_ADVICE_BY_UDF_PATH_INDEX: LazyLock<SystemIndex<ReadSetAdviceTable>> =
+    LazyLock::new(|| {
+        SystemIndex::new(
+            "by_udf_path",
+            [
+                &COMPONENT_PATH_FIELD,
+                &UDF_PATH_FIELD,
+                &CREATION_TIME_FIELD_PATH,
+            ],
+        )
+        .unwrap()
+    });
+
+pub static READ_SET_ADVICE_BY_LAST_WARNED_INDEX: LazyLock<SystemIndex<ReadSetAdviceTable>> =
+    LazyLock::new(|| {
+        SystemIndex::new(
+            "by_last_warned",
+            [&LAST_WARNED_FIELD, &CREATION_TIME_FIELD_PATH],
+        )
+        .unwrap()
+    });
+
+pub struct ReadSetAdviceTable;
+impl SystemTable for ReadSetAdviceTable {
+    type Metadata = ReadSetAdvice;
+
+    fn table_name() -> &'static TableName {
+        &READ_SET_ADVICE_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![
+            READ_SET_ADVICE_BY_UDF_PATH_INDEX.clone(),
+            READ_SET_ADVICE_BY_LAST_WARNED_INDEX.clone(),
+        ]
+    }
+}
+
+pub struct ReadSetAdviceModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> ReadSetAdviceModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    /// Stores `advice` as the latest advice for its function, keeping when the
+    /// function was first warned about.
+    pub async fn record(&mut self, mut advice: ReadSetAdvice) -> anyhow::Result<()> {
+        match self.get(&advice.component_path, &advice.udf_path).await? {
+            Some(existing) => {
+                advice.first_warned_ms = advice.first_warned_ms.min(existing.first_warned_ms);
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), advice.try_into()?)
+                    .await?;
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&READ_SET_ADVICE_TABLE, advice.try_into()?)
+                    .await?;
+            },
+        }
+        Ok(())
+    }
+
+    pub async fn get(
+        &mut self,
+        component_path: &ComponentPath,
+        udf_path: &str,
+    ) -> anyhow::Result<Option<ParsedDocument<ReadSetAdvice>>> {
+        let query = Query::index_range(IndexRange {
+            index_name: READ_SET_ADVICE_BY_UDF_PATH_INDEX.name(),
+            range: vec![
+                IndexRangeExpression::Eq(
+                    COMPONENT_PATH_FIELD.clone(),
+                    maybe_val!(String::from(component_path.clone())),
+                ),
+                IndexRangeExpression::Eq(UDF_PATH_FIELD.clone(), maybe_val!(udf_path.to_string())),
+            ],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(|doc| doc.parse())
+            .transpose()
+    }
+
+    /// Functions that were most recently warned about, newest first.
+    pub async fn list_recent(&mut self, limit: usize) -> anyhow::Result<Vec<ResolvedDocument>> {
+        self.tx.require_admin("list_read_set_advice")?;
+        let query = Query::index_range(IndexRange {
+            index_name: READ_SET_ADVICE_BY_LAST_WARNED_INDEX.name(),
+            range: vec![],
+            order: Order::Desc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut result = vec![];
+        while result.len() < limit {
+            let Some(doc) = query_stream.next(self.tx, None).await? else {
+                break;
+            };
+            result.push(doc);
+        }
+        Ok(result)
+    }
+}
@@ -0,0 +1,146 @@
+use common::components::ComponentPath;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// Number of tables listed in `ReadSetAdvice::top_tables`.
+pub const MAX_TOP_TABLES: usize = 3;
+
+/// A query or mutation whose typical read set is close to the transaction
+/// read limits, as recorded in `_read_set_advice`. The averages are
+/// exponential moving averages over the function's recent executions.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ReadSetAdvice {
+    pub component_path: ComponentPath,
+    pub udf_path: String,
+    /// Executions observed since the backend started.
+    pub executions: i64,
+    pub average_documents_read: i64,
+    pub average_bytes_read: i64,
+    pub max_documents_read: i64,
+    pub max_bytes_read: i64,
+    pub documents_limit: i64,
+    pub bytes_limit: i64,
+    pub first_warned_ms: i64,
+    pub last_warned_ms: i64,
+    /// The tables the most recent execution read the most documents from,
+    /// most read first.
+    pub top_tables: Vec<TableReads>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct TableReads {
+    pub table_name: String,
+    pub documents_read: i64,
+}
+
+impl ReadSetAdvice {
+    /// Human readable summary, used for the warning in the function's logs.
+    pub fn message(&self) -> String {
+        let tables = self
+            .top_tables
+            .iter()
+            .map(|t| format!("{} ({} documents)", t.table_name, t.documents_read))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(
+            "{} reads an average of {} documents ({} bytes) per execution, against limits of {} \
+             documents ({} bytes). It will fail once its reads reach the limits; consider adding \
+             an index or paginating. Most reads came from: {}",
+            self.udf_path,
+            self.average_documents_read,
+            self.average_bytes_read,
+            self.documents_limit,
+            self.bytes_limit,
+            if tables.is_empty() { "none" } else { &tables },
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedTableReads {
+    table_name: String,
+    documents_read: i64,
+}
+
+impl From<TableReads> for SerializedTableReads {
+    fn from(value: TableReads) -> Self {
+        Self {
+            table_name: value.table_name,
+            documents_read: value.documents_read,
+        }
+    }
+}
+
+impl From<SerializedTableReads> for TableReads {
+    fn from(value: SerializedTableReads) -> Self {
+        Self {
+            table_name: value.table_name,
+            documents_read: value.documents_read,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedReadSetAdvice {
+    component_path: String,
+    udf_path: String,
+    executions: i64,
+    average_documents_read: i64,
+    average_bytes_read: i64,
+    max_documents_read: i64,
+    max_bytes_read: i64,
+    documents_limit: i64,
+    bytes_limit: i64,
+    first_warned_ms: i64,
+    last_warned_ms: i64,
+    top_tables: Vec<SerializedTableReads>,
+}
+
+impl From<ReadSetAdvice> for SerializedReadSetAdvice {
+    fn from(value: ReadSetAdvice) -> Self {
+        Self {
+            component_path: value.component_path.into(),
+            udf_path: value.udf_path,
+            executions: value.executions,
+            average_documents_read: value.average_documents_read,
+            average_bytes_read: value.average_bytes_read,
+            max_documents_read: value.max_documents_read,
+            max_bytes_read: value.max_bytes_read,
+            documents_limit: value.documents_limit,
+            bytes_limit: value.bytes_limit,
+            first_warned_ms: value.first_warned_ms,
+            last_warned_ms: value.last_warned_ms,
+            top_tables: value.top_tables.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl TryFrom<SerializedReadSetAdvice> for ReadSetAdvice {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedReadSetAdvice) -> Result<Self, Self::Error> {
+        Ok(Self {
+            component_path: value.component_path.parse()?,
+            udf_path: value.udf_path,
+            executions: value.executions,
+            average_documents_read: value.average_documents_read,
+            average_bytes_read: value.average_bytes_read,
+            max_documents_read: value.max_documents_read,
+            max_bytes_read: value.max_bytes_read,
+            documents_limit: value.documents_limit,
+            bytes_limit: value.bytes_limit,
+            first_warned_ms: value.first_warned_ms,
+            last_warned_ms: value.last_warned_ms,
+            top_tables: value.top_tables.into_iter().map(Into::into).collect(),
+        })
+    }
+}
+
+codegen_convex_serialization!(ReadSetAdvice, SerializedReadSetAdvice);
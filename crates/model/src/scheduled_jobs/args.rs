@@ -0,0 +1,164 @@
+//! Large scheduled function arguments are moved to file storage after the
+//! transaction scheduling the function commits, so the `_scheduled_jobs`
+//! table, which the scheduler scans continuously, only holds a reference to
+//! them. Uploading after the commit means an aborted or retried transaction
+//! never leaves an object behind.
+
+use std::sync::Arc;
+
+use bytes::Bytes;
+use common::sha256::Sha256;
+use storage::{
+    Storage,
+    StorageExt,
+    Upload,
+};
+use value::ConvexArray;
+
+use super::types::{
+    parse_args,
+    ScheduledJob,
+    StoredScheduledJobArgs,
+};
+
+/// Uploads the arguments kept in `job` to `storage`. The caller records the
+/// result with `ScheduledJob::store_args`, and deletes the object if that
+/// doesn't commit.
+pub async fn upload_scheduled_job_args(
+    job: &ScheduledJob,
+    storage: &Arc<dyn Storage>,
+) -> anyhow::Result<StoredScheduledJobArgs> {
+    anyhow::ensure!(
+        job.stored_args.is_none(),
+        "Scheduled job arguments are already in storage"
+    );
+    let bytes = Bytes::from(job.udf_args_bytes.to_vec());
+    let sha256 = Sha256::hash(&bytes);
+    let size = bytes.len().try_into()?;
+    let mut upload = storage.start_upload().await?;
+    upload.write(bytes).await?;
+    let storage_key = upload.complete().await?;
+    Ok(StoredScheduledJobArgs {
+        storage_key,
+        sha256,
+        size,
+    })
+}
+
+/// Returns the job's arguments, loading them from `storage` if they were too
+/// large to keep in the job.
+pub async fn scheduled_job_args(
+    job: &ScheduledJob,
+    storage: &Arc<dyn Storage>,
+) -> anyhow::Result<ConvexArray> {
+    let Some(stored) = &job.stored_args else {
+        return job.udf_args();
+    };
+    let bytes = storage
+        .get(&stored.storage_key)
+        .await?
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Scheduled job arguments {:?} missing from storage",
+                stored.storage_key
+            )
+        })?
+        .collect_as_bytes()
+        .await?;
+    anyhow::ensure!(
+        Sha256::hash(&bytes) == stored.sha256,
+        "Scheduled job arguments {:?} don't match their hash",
+        stored.storage_key
+    );
+    parse_args(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use common::{
+        components::{
+            CanonicalizedComponentFunctionPath,
+            ComponentPath,
+        },
+        testing::assert_contains,
+        types::Timestamp,
+    };
+    use runtime::testing::TestRuntime;
+    use storage::{
+        LocalDirStorage,
+        Storage,
+    };
+    use value::{
+        ConvexArray,
+        ConvexValue,
+    };
+
+    use super::{
+        scheduled_job_args,
+        upload_scheduled_job_args,
+    };
+    use crate::scheduled_jobs::types::{
+        ScheduledJob,
+        ScheduledJobAttempts,
+        ScheduledJobState,
+        StoredScheduledJobArgs,
+    };
+
+    fn new_job(arg: &str) -> anyhow::Result<(ScheduledJob, ConvexArray)> {
+        let args = ConvexArray::try_from(vec![ConvexValue::try_from(arg.to_owned())?])?;
+        let job = ScheduledJob::new(
+            CanonicalizedComponentFunctionPath {
+                component: ComponentPath::root(),
+                udf_path: "jobs:run".parse()?,
+            },
+            args.clone(),
+            ScheduledJobState::Pending,
+            Some(Timestamp::MIN),
+            None,
+            Timestamp::MIN,
+            ScheduledJobAttempts::default(),
+        )?;
+        Ok((job, args))
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_stored_args_roundtrip(rt: TestRuntime) -> anyhow::Result<()> {
+        let storage: Arc<dyn Storage> = Arc::new(LocalDirStorage::new(rt)?);
+        let (mut job, args) = new_job("large")?;
+        let stored = upload_scheduled_job_args(&job, &storage).await?;
+        job.store_args(stored)?;
+        assert!(job.udf_args().is_err());
+        assert_eq!(scheduled_job_args(&job, &storage).await?, args);
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_stored_args_hash_mismatch(rt: TestRuntime) -> anyhow::Result<()> {
+        let storage: Arc<dyn Storage> = Arc::new(LocalDirStorage::new(rt)?);
+        let (mut job, _) = new_job("large")?;
+        let (other_job, _) = new_job("other")?;
+        let stored = upload_scheduled_job_args(&job, &storage).await?;
+        let other_stored = upload_scheduled_job_args(&other_job, &storage).await?;
+        job.store_args(StoredScheduledJobArgs {
+            sha256: other_stored.sha256,
+            ..stored
+        })?;
+        let err = scheduled_job_args(&job, &storage).await.unwrap_err();
+        assert_contains(&err, "don't match their hash");
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_stored_args_missing(rt: TestRuntime) -> anyhow::Result<()> {
+        let storage: Arc<dyn Storage> = Arc::new(LocalDirStorage::new(rt)?);
+        let (mut job, _) = new_job("large")?;
+        let stored = upload_scheduled_job_args(&job, &storage).await?;
+        storage.delete_object(&stored.storage_key).await?;
+        job.store_args(stored)?;
+        let err = scheduled_job_args(&job, &storage).await.unwrap_err();
+        assert_contains(&err, "missing from storage");
+        Ok(())
+    }
+}
@@ -9,11 +9,16 @@ use std::{
 use common::{
     components::CanonicalizedComponentFunctionPath,
     document::{
+        timestamp_to_ms,
         ParseDocument,
         ParsedDocument,
+        CREATION_TIME_FIELD_PATH,
     },
     execution_context::ExecutionContext,
     knobs::{
+        SCHEDULED_JOB_ARGS_STORAGE_THRESHOLD_BYTES,
+        SCHEDULED_JOB_IDEMPOTENCY_WINDOW,
+        SCHEDULED_JOB_RETENTION,
         TRANSACTION_MAX_NUM_SCHEDULED,
         TRANSACTION_MAX_SCHEDULED_TOTAL_ARGUMENT_SIZE_BYTES,
     },
@@ -37,6 +42,7 @@ use common::{
 };
 use database::{
     unauthorized_error,
+    ComponentQuotasModel,
     ResolvedQuery,
     SystemMetadataModel,
     Transaction,
@@ -68,6 +74,7 @@ use crate::{
     SystemTable,
 };
 
+pub mod args;
 pub mod types;
 pub mod virtual_table;
 
@@ -109,6 +116,26 @@ pub static SCHEDULED_JOBS_INDEX_BY_UDF_PATH: LazyLock<SystemIndex<ScheduledJobsT
 /// By completed ts. Used to efficiently find jobs to garbage collect.
 pub static SCHEDULED_JOBS_INDEX_BY_COMPLETED_TS: LazyLock<SystemIndex<ScheduledJobsTable>> =
     LazyLock::new(|| SystemIndex::new("by_completed_ts", [&COMPLETED_TS_FIELD]).unwrap());
+/// By idempotency key. Used to find a job previously scheduled with the same
+/// key.
+pub static SCHEDULED_JOBS_INDEX_BY_IDEMPOTENCY_KEY: LazyLock<SystemIndex<ScheduledJobsTable>> =
+    LazyLock::new(|| {
+        SystemIndex::new(
+            "by_idempotency_key",
+            [&IDEMPOTENCY_KEY_FIELD, &CREATION_TIME_FIELD_PATH],
+        )
+        .unwrap()
+    });
+/// By whether the arguments are waiting to be moved to file storage. Used to
+/// find the jobs whose arguments to upload.
+pub static SCHEDULED_JOBS_INDEX_BY_ARGS_PENDING_STORAGE: LazyLock<SystemIndex<ScheduledJobsTable>> =
+    LazyLock::new(|| {
+        SystemIndex::new(
+            "by_args_pending_storage",
+            [&ARGS_PENDING_STORAGE_FIELD, &CREATION_TIME_FIELD_PATH],
+        )
+        .unwrap()
+    });
 pub static NEXT_TS_FIELD: LazyLock<FieldPath> =
     LazyLock::new(|| "nextTs".parse().expect("invalid nextTs field"));
 pub static COMPLETED_TS_FIELD: LazyLock<FieldPath> =
@@ -117,6 +144,20 @@ static UDF_PATH_FIELD: LazyLock<FieldPath> =
     LazyLock::new(|| "udfPath".parse().expect("invalid udfPath field"));
 static COMPONENT_PATH_FIELD: LazyLock<FieldPath> =
     LazyLock::new(|| "component".parse().expect("invalid component field"));
+static IDEMPOTENCY_KEY_FIELD: LazyLock<FieldPath> = LazyLock::new(|| {
+    "idempotencyKey"
+        .parse()
+        .expect("invalid idempotencyKey field")
+});
+
+static ARGS_PENDING_STORAGE_FIELD: LazyLock<FieldPath> = LazyLock::new(|| {
+    "argsPendingStorage"
+        .parse()
+        .expect("invalid argsPendingStorage field")
+});
+
+/// Maximum length in bytes of an idempotency key.
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 256;
 
 pub struct ScheduledJobsTable;
 impl SystemTable for ScheduledJobsTable {
@@ -131,6 +172,8 @@ impl SystemTable for ScheduledJobsTable {
             SCHEDULED_JOBS_INDEX_BY_COMPLETED_TS.clone(),
             SCHEDULED_JOBS_INDEX.clone(),
             SCHEDULED_JOBS_INDEX_BY_UDF_PATH.clone(),
+            SCHEDULED_JOBS_INDEX_BY_IDEMPOTENCY_KEY.clone(),
+            SCHEDULED_JOBS_INDEX_BY_ARGS_PENDING_STORAGE.clone(),
         ]
     }
 
@@ -192,12 +235,43 @@ impl<'a, RT: Runtime> SchedulerModel<'a, RT> {
         Ok(())
     }
 
+    /// Finds the most recent job scheduled with `idempotency_key` within
+    /// `SCHEDULED_JOB_IDEMPOTENCY_WINDOW`. Reading the index range makes
+    /// concurrent transactions scheduling with the same key conflict, so at
+    /// most one of them inserts a job.
+    async fn find_by_idempotency_key(
+        &mut self,
+        idempotency_key: &str,
+    ) -> anyhow::Result<Option<ParsedDocument<ScheduledJob>>> {
+        let index_query = Query::index_range(IndexRange {
+            index_name: SCHEDULED_JOBS_INDEX_BY_IDEMPOTENCY_KEY.name(),
+            range: vec![IndexRangeExpression::Eq(
+                IDEMPOTENCY_KEY_FIELD.clone(),
+                ConvexValue::try_from(idempotency_key.to_owned())?.into(),
+            )],
+            order: Order::Desc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, index_query)?;
+        let Some(doc) = query_stream.next(self.tx, Some(1)).await? else {
+            return Ok(None);
+        };
+        let window = (*SCHEDULED_JOB_IDEMPOTENCY_WINDOW).min(*SCHEDULED_JOB_RETENTION);
+        let cutoff = (*self.tx.begin_timestamp())
+            .sub(window)
+            .unwrap_or(Timestamp::MIN);
+        if f64::from(doc.creation_time()) < timestamp_to_ms(cutoff)? {
+            return Ok(None);
+        }
+        Ok(Some(doc.parse()?))
+    }
+
     pub async fn schedule(
         &mut self,
         path: CanonicalizedComponentFunctionPath,
         args: ConvexArray,
         ts: UnixTimestamp,
         context: ExecutionContext,
+        idempotency_key: Option<String>,
     ) -> anyhow::Result<ResolvedDocumentId> {
         if path.udf_path.is_system()
             && !(self.tx.identity().is_admin() || self.tx.identity().is_system())
@@ -205,11 +279,43 @@ impl<'a, RT: Runtime> SchedulerModel<'a, RT> {
             anyhow::bail!(unauthorized_error("schedule"))
         }
 
+        if let Some(idempotency_key) = &idempotency_key {
+            anyhow::ensure!(
+                !idempotency_key.is_empty() && idempotency_key.len() <= MAX_IDEMPOTENCY_KEY_LEN,
+                ErrorMetadata::bad_request(
+                    "InvalidIdempotencyKey",
+                    format!(
+                        "Idempotency keys must be between 1 and {MAX_IDEMPOTENCY_KEY_LEN} bytes"
+                    ),
+                )
+            );
+            if let Some(existing) = self.find_by_idempotency_key(idempotency_key).await? {
+                anyhow::ensure!(
+                    existing.matches(&path, &args)?,
+                    ErrorMetadata::bad_request(
+                        "IdempotencyKeyReused",
+                        format!(
+                            "Idempotency key {idempotency_key:?} was already used to schedule {} \
+                             with different arguments",
+                            String::from(existing.path.udf_path.clone()),
+                        ),
+                    )
+                );
+                return Ok(existing.id());
+            }
+        }
+
         self.check_scheduling_limits(&args)?;
+        ComponentQuotasModel::new(self.tx)
+            .check_scheduled_jobs_quota(self.namespace, &SCHEDULED_JOBS_TABLE)
+            .await?;
 
+        // Large arguments are moved to file storage once this transaction
+        // commits.
+        let args_pending_storage = args.size() > *SCHEDULED_JOB_ARGS_STORAGE_THRESHOLD_BYTES;
         let now: Timestamp = self.tx.runtime().generate_timestamp()?;
         let original_scheduled_ts: Timestamp = ts.as_system_time().try_into()?;
-        let scheduled_job = ScheduledJob::new(
+        let mut scheduled_job = ScheduledJob::new(
             path.clone(),
             args.clone(),
             ScheduledJobState::Pending,
@@ -221,6 +327,8 @@ impl<'a, RT: Runtime> SchedulerModel<'a, RT> {
             original_scheduled_ts,
             ScheduledJobAttempts::default(),
         )?;
+        scheduled_job.idempotency_key = idempotency_key.clone();
+        scheduled_job.args_pending_storage = args_pending_storage;
         let job = if let Some((parent_component_id, parent_scheduled_job)) =
             context.parent_scheduled_job
         {
@@ -251,7 +359,7 @@ impl<'a, RT: Runtime> SchedulerModel<'a, RT> {
                     | ScheduledJobState::Success => scheduled_job,
                     ScheduledJobState::Canceled => {
                         let scheduled_ts = self.tx.begin_timestamp();
-                        ScheduledJob::new(
+                        let mut canceled_job = ScheduledJob::new(
                             path,
                             args,
                             ScheduledJobState::Canceled,
@@ -259,7 +367,9 @@ impl<'a, RT: Runtime> SchedulerModel<'a, RT> {
                             Some(*scheduled_ts),
                             *scheduled_ts,
                             ScheduledJobAttempts::default(),
-                        )?
+                        )?;
+                        canceled_job.idempotency_key = idempotency_key;
+                        canceled_job
                     },
                 }
             } else {
@@ -329,6 +439,8 @@ impl<'a, RT: Runtime> SchedulerModel<'a, RT> {
         // job has already been processed
         job.next_ts = None;
         job.completed_ts = Some(*self.tx.begin_timestamp());
+        // The job won't run, so there's no need to move its arguments.
+        job.args_pending_storage = false;
         SystemMetadataModel::new(self.tx, self.namespace)
             .replace(id, job.try_into()?)
             .await?;
@@ -440,6 +552,29 @@ impl<'a, RT: Runtime> SchedulerModel<'a, RT> {
         Ok(count)
     }
 
+    /// Returns up to `limit` jobs whose arguments are waiting to be moved to
+    /// file storage, oldest first.
+    pub async fn list_args_pending_storage(
+        &mut self,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ParsedDocument<ScheduledJob>>> {
+        let index_query = Query::index_range(IndexRange {
+            index_name: SCHEDULED_JOBS_INDEX_BY_ARGS_PENDING_STORAGE.name(),
+            range: vec![IndexRangeExpression::Eq(
+                ARGS_PENDING_STORAGE_FIELD.clone(),
+                ConvexValue::Boolean(true).into(),
+            )],
+            order: Order::Asc,
+        })
+        .limit(limit);
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, index_query)?;
+        let mut jobs = Vec::new();
+        while let Some(job) = query_stream.next(self.tx, None).await? {
+            jobs.push(job.parse()?);
+        }
+        Ok(jobs)
+    }
+
     pub async fn list(&mut self) -> anyhow::Result<Vec<ParsedDocument<ScheduledJob>>> {
         let scheduled_query = Query::full_table_scan(SCHEDULED_JOBS_TABLE.clone(), Order::Asc);
         let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, scheduled_query)?;
@@ -486,9 +621,10 @@ impl<'a, RT: Runtime> VirtualSchedulerModel<'a, RT> {
         args: ConvexArray,
         ts: UnixTimestamp,
         context: ExecutionContext,
+        idempotency_key: Option<String>,
     ) -> anyhow::Result<DeveloperDocumentId> {
         let system_id = SchedulerModel::new(self.tx, self.namespace)
-            .schedule(path, args, ts, context)
+            .schedule(path, args, ts, context, idempotency_key)
             .await?;
         self.tx
             .virtual_system_mapping()
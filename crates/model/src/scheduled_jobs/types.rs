@@ -4,7 +4,14 @@ use common::{
         ComponentPath,
     },
     execution_context::ExecutionId,
-    types::Timestamp,
+    sha256::{
+        Sha256,
+        Sha256Digest,
+    },
+    types::{
+        ObjectKey,
+        Timestamp,
+    },
     RequestId,
 };
 #[cfg(any(test, feature = "testing"))]
@@ -35,6 +42,13 @@ pub struct ScheduledJob {
         )
     )]
     pub udf_args_bytes: ByteBuf,
+    /// Set if the arguments were too large to keep in the document, in which
+    /// case `udf_args_bytes` is an empty array.
+    pub stored_args: Option<StoredScheduledJobArgs>,
+    /// Set while the arguments are larger than
+    /// `SCHEDULED_JOB_ARGS_STORAGE_THRESHOLD_BYTES` but haven't been moved to
+    /// file storage yet.
+    pub args_pending_storage: bool,
 
     pub state: ScheduledJobState,
 
@@ -50,6 +64,11 @@ pub struct ScheduledJob {
     pub original_scheduled_ts: Timestamp,
 
     pub attempts: ScheduledJobAttempts,
+
+    /// Set when the job was scheduled with an idempotency key. Scheduling
+    /// another job with the same key within `SCHEDULED_JOB_IDEMPOTENCY_WINDOW`
+    /// returns this job instead.
+    pub idempotency_key: Option<String>,
 }
 
 fn args_to_bytes(args: ConvexArray) -> anyhow::Result<ByteBuf> {
@@ -70,18 +89,99 @@ impl ScheduledJob {
         Ok(Self {
             path,
             udf_args_bytes: args_to_bytes(udf_args)?,
+            stored_args: None,
+            args_pending_storage: false,
             state,
             next_ts,
             completed_ts,
             original_scheduled_ts,
             attempts,
+            idempotency_key: None,
         })
     }
 
+    /// Moves the job's arguments out of the document. The caller is
+    /// responsible for uploading them to `stored`.
+    pub fn store_args(&mut self, stored: StoredScheduledJobArgs) -> anyhow::Result<()> {
+        self.udf_args_bytes = args_to_bytes(ConvexArray::empty())?;
+        self.stored_args = Some(stored);
+        self.args_pending_storage = false;
+        Ok(())
+    }
+
+    /// The job's arguments, if they're kept in the document. Use
+    /// `scheduled_job_args` to also load arguments from storage.
     pub fn udf_args(&self) -> anyhow::Result<ConvexArray> {
-        let args_json: JsonValue = serde_json::from_slice(&self.udf_args_bytes)?;
-        let args = args_json.try_into()?;
-        Ok(args)
+        anyhow::ensure!(
+            self.stored_args.is_none(),
+            "Scheduled job arguments are in storage"
+        );
+        parse_args(&self.udf_args_bytes)
+    }
+
+    /// Whether scheduling `path` with `args` would schedule the same function
+    /// call as this job, without loading arguments from storage.
+    pub fn matches(
+        &self,
+        path: &CanonicalizedComponentFunctionPath,
+        args: &ConvexArray,
+    ) -> anyhow::Result<bool> {
+        if self.path != *path {
+            return Ok(false);
+        }
+        match &self.stored_args {
+            Some(stored) => Ok(stored.sha256 == Sha256::hash(&args_to_bytes(args.clone())?)),
+            None => Ok(self.udf_args()? == *args),
+        }
+    }
+}
+
+pub(crate) fn parse_args(bytes: &[u8]) -> anyhow::Result<ConvexArray> {
+    let args_json: JsonValue = serde_json::from_slice(bytes)?;
+    let args = args_json.try_into()?;
+    Ok(args)
+}
+
+/// Where a scheduled job's arguments are kept in file storage. The object
+/// isn't in `_storage`, so it's only reachable through the job and is deleted
+/// along with it.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct StoredScheduledJobArgs {
+    pub storage_key: ObjectKey,
+    pub sha256: Sha256Digest,
+    pub size: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedStoredScheduledJobArgs {
+    storage_key: String,
+    sha256: String,
+    size: i64,
+}
+
+impl TryFrom<StoredScheduledJobArgs> for SerializedStoredScheduledJobArgs {
+    type Error = anyhow::Error;
+
+    fn try_from(stored: StoredScheduledJobArgs) -> anyhow::Result<Self> {
+        Ok(Self {
+            storage_key: stored.storage_key.into(),
+            sha256: stored.sha256.as_base64(),
+            size: stored.size,
+        })
+    }
+}
+
+impl TryFrom<SerializedStoredScheduledJobArgs> for StoredScheduledJobArgs {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedStoredScheduledJobArgs) -> anyhow::Result<Self> {
+        Ok(Self {
+            storage_key: value.storage_key.try_into()?,
+            sha256: Sha256Digest::from_base64(&value.sha256)?,
+            size: value.size,
+        })
     }
 }
 
@@ -93,11 +193,14 @@ pub struct SerializedScheduledJob {
     // Serialize the udf arguments as binary since we restrict what
     // field names can be used in a `Document`'s top-level object.
     udf_args: ByteBuf,
+    stored_args: Option<SerializedStoredScheduledJobArgs>,
+    args_pending_storage: Option<bool>,
     state: SerializedScheduledJobState,
     next_ts: Option<i64>,
     completed_ts: Option<i64>,
     original_scheduled_ts: Option<i64>,
     attempts: Option<ScheduledJobAttempts>,
+    idempotency_key: Option<String>,
 }
 
 impl TryFrom<ScheduledJob> for SerializedScheduledJob {
@@ -108,11 +211,14 @@ impl TryFrom<ScheduledJob> for SerializedScheduledJob {
             component: Some(String::from(job.path.component)),
             udf_path: String::from(job.path.udf_path),
             udf_args: job.udf_args_bytes,
+            stored_args: job.stored_args.map(TryInto::try_into).transpose()?,
+            args_pending_storage: job.args_pending_storage.then_some(true),
             state: job.state.try_into()?,
             next_ts: job.next_ts.map(|ts| ts.into()),
             completed_ts: job.completed_ts.map(|ts| ts.into()),
             original_scheduled_ts: Some(job.original_scheduled_ts.into()),
             attempts: Some(job.attempts),
+            idempotency_key: job.idempotency_key,
         })
     }
 }
@@ -128,6 +234,7 @@ impl TryFrom<SerializedScheduledJob> for ScheduledJob {
             .unwrap_or_else(ComponentPath::root);
         let udf_path = value.udf_path.parse()?;
         let udf_args_bytes = value.udf_args;
+        let stored_args = value.stored_args.map(TryInto::try_into).transpose()?;
         let state = value.state.try_into()?;
         let next_ts = value.next_ts.map(|ts| ts.try_into()).transpose()?;
         let completed_ts = value.completed_ts.map(|ts| ts.try_into()).transpose()?;
@@ -150,11 +257,14 @@ impl TryFrom<SerializedScheduledJob> for ScheduledJob {
                 udf_path,
             },
             udf_args_bytes,
+            stored_args,
+            args_pending_storage: value.args_pending_storage.unwrap_or(false),
             state,
             next_ts,
             completed_ts,
             original_scheduled_ts,
             attempts: value.attempts.unwrap_or_default(),
+            idempotency_key: value.idempotency_key,
         })
     }
 }
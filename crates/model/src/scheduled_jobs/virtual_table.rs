@@ -61,11 +61,17 @@ impl VirtualSystemDocMapper for ScheduledJobsDocMapper {
 
         let job: ParsedDocument<ScheduledJob> = (&doc).parse()?;
         let job: ScheduledJob = job.into_value();
-        let udf_args = job.udf_args()?;
+        // Arguments kept in file storage aren't loaded for the virtual table, so
+        // the job is flagged instead of appearing to have no arguments.
+        let (udf_args, args_in_storage) = match job.stored_args {
+            Some(_) => (ConvexArray::empty(), true),
+            None => (job.udf_args()?, false),
+        };
         let public_job = PublicScheduledJob {
             // TODO(ENG-6920) include component (job.path.component) in virtual table.
             name: job.path.udf_path,
             args: udf_args,
+            args_in_storage,
             state: job.state,
             scheduled_time: timestamp_to_ms(job.original_scheduled_ts)?,
             completed_time: match job.completed_ts {
@@ -100,6 +106,9 @@ impl VirtualSystemDocMapper for ScheduledJobsDocMapper {
 pub struct PublicScheduledJob {
     pub name: CanonicalizedUdfPath,
     pub args: ConvexArray,
+    /// Set if the arguments are kept in file storage, in which case `args` is
+    /// empty.
+    pub args_in_storage: bool,
     pub state: ScheduledJobState,
     pub scheduled_time: f64,
     pub completed_time: Option<f64>,
@@ -115,6 +124,9 @@ impl TryFrom<PublicScheduledJob> for ConvexObject {
             ConvexValue::try_from(String::from(job.name))?,
         );
         obj.insert("args".parse()?, ConvexValue::Array(job.args));
+        if job.args_in_storage {
+            obj.insert("argsInStorage".parse()?, ConvexValue::Boolean(true));
+        }
 
         // Rename `type` -> `kind` in the scheduled job state
         let system_state: ConvexObject = job.state.try_into()?;
@@ -157,6 +169,13 @@ impl TryFrom<ConvexObject> for PublicScheduledJob {
                 anyhow::bail!("Missing or invalid `args` field for PublicScheduledJob: {args:?}")
             },
         };
+        let args_in_storage = match fields.remove("argsInStorage") {
+            None => false,
+            Some(ConvexValue::Boolean(args_in_storage)) => args_in_storage,
+            args_in_storage => anyhow::bail!(
+                "Invalid `argsInStorage` field for PublicScheduledJob: {args_in_storage:?}"
+            ),
+        };
         let public_state = match fields.remove("state") {
             Some(ConvexValue::Object(state)) => state,
             state => {
@@ -188,6 +207,7 @@ impl TryFrom<ConvexObject> for PublicScheduledJob {
         Ok(PublicScheduledJob {
             name,
             args,
+            args_in_storage,
             state,
             scheduled_time,
             completed_time,
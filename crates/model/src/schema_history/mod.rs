@@ -0,0 +1,185 @@
+use std::sync::LazyLock;
+
+use common::{
+    bootstrap_model::index::DeveloperIndexConfig,
+    components::{
+        ComponentId,
+        ComponentPath,
+    },
+    document::{
+        ResolvedDocument,
+        CREATION_TIME_FIELD_PATH,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+};
+use database::{
+    IndexModel,
+    ResolvedQuery,
+    SchemaDiff,
+    SystemMetadataModel,
+    Transaction,
+};
+use value::{
+    ConvexValue,
+    FieldPath,
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::{
+    diff_tables,
+    IndexBuild,
+    IndexBuildOutcome,
+    SchemaHistoryEntry,
+};
+use crate::{
+    deployment_audit_log::types::AuditLogIndexDiff,
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static SCHEMA_HISTORY_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_schema_history"
+        .parse()
+        .expect("Invalid built-in schema history table")
+});
+
+static COMPONENT_PATH_FIELD: LazyLock<FieldPath> = LazyLock::new(|| {
+    "componentPath"
+        .parse()
+        .expect("invalid componentPath field")
+});
+
+pub static SCHEMA_HISTORY_BY_COMPONENT_PATH_INDEX: LazyLock<SystemIndex<SchemaHistoryTable>> =
+    LazyLock::new(|| {
+        SystemIndex::new(
+            "by_component_path",
+            [&COMPONENT_PATH_FIELD, &CREATION_TIME_FIELD_PATH],
+        )
+        .unwrap()
+    });
+
+pub struct SchemaHistoryTable;
+impl SystemTable for SchemaHistoryTable {
+    type Metadata = SchemaHistoryEntry;
+
+    fn table_name() -> &'static TableName {
+        &SCHEMA_HISTORY_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![SCHEMA_HISTORY_BY_COMPONENT_PATH_INDEX.clone()]
+    }
+}
+
+pub struct SchemaHistoryModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> SchemaHistoryModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    /// Records a push's changes to a component's schema and indexes. Must be
+    /// called after the push's indexes have been applied, so the state of each
+    /// added index is its build outcome. Pushes that changed neither are not
+    /// recorded.
+    pub async fn record(
+        &mut self,
+        component_path: ComponentPath,
+        component_id: ComponentId,
+        schema_id: Option<ResolvedDocumentId>,
+        schema_diff: Option<&SchemaDiff>,
+        index_diff: &AuditLogIndexDiff,
+    ) -> anyhow::Result<()> {
+        if schema_diff.is_none()
+            && index_diff.added_indexes.is_empty()
+            && index_diff.removed_indexes.is_empty()
+        {
+            return Ok(());
+        }
+        let (previous_schema, next_schema) = match schema_diff {
+            Some(diff) => (diff.previous_schema.as_ref(), diff.next_schema.as_ref()),
+            None => (None, None),
+        };
+        let (added_tables, removed_tables, changed_validators) =
+            diff_tables(previous_schema, next_schema)?;
+
+        let indexes = IndexModel::new(self.tx)
+            .get_application_indexes(component_id.into())
+            .await?;
+        let added_indexes = index_diff
+            .added_indexes
+            .iter()
+            .map(|(name, config)| {
+                let outcome = indexes
+                    .iter()
+                    .find(|index| {
+                        index.name == *name
+                            && DeveloperIndexConfig::from(index.config.clone()) == *config
+                    })
+                    .map(|index| IndexBuildOutcome::from(&index.config));
+                IndexBuild {
+                    name: name.clone(),
+                    config: config.clone(),
+                    outcome,
+                }
+            })
+            .collect();
+
+        let entry = SchemaHistoryEntry {
+            component_path,
+            schema_id: schema_id.map(Into::into),
+            schema_validation: next_schema.is_some_and(|schema| schema.schema_validation),
+            added_tables,
+            removed_tables,
+            changed_validators,
+            added_indexes,
+            removed_indexes: index_diff.removed_indexes.clone(),
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .insert(&SCHEMA_HISTORY_TABLE, entry.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    /// Most recent schema changes, newest first, optionally restricted to a
+    /// single component.
+    pub async fn list_recent(
+        &mut self,
+        component_path: Option<&ComponentPath>,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ResolvedDocument>> {
+        self.tx.require_admin("list_schema_history")?;
+        let query = match component_path {
+            Some(component_path) => Query::index_range(IndexRange {
+                index_name: SCHEMA_HISTORY_BY_COMPONENT_PATH_INDEX.name(),
+                range: vec![IndexRangeExpression::Eq(
+                    COMPONENT_PATH_FIELD.clone(),
+                    ConvexValue::try_from(String::from(component_path.clone()))?.into(),
+                )],
+                order: Order::Desc,
+            }),
+            None => Query::full_table_scan(SCHEMA_HISTORY_TABLE.clone(), Order::Desc),
+        };
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut result = vec![];
+        while result.len() < limit {
+            let Some(doc) = query_stream.next(self.tx, None).await? else {
+                break;
+            };
+            result.push(doc);
+        }
+        Ok(result)
+    }
+}
@@ -0,0 +1,333 @@
+use std::collections::BTreeSet;
+
+use common::{
+    bootstrap_model::index::{
+        DeveloperIndexConfig,
+        IndexConfig,
+        SerializedDeveloperIndexConfig,
+        SerializedNamedDeveloperIndexConfig,
+    },
+    components::ComponentPath,
+    json::JsonSerializable,
+    schemas::{
+        validator::Validator,
+        DatabaseSchema,
+    },
+    types::IndexName,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::{
+    codegen_convex_serialization,
+    DeveloperDocumentId,
+    TableName,
+};
+
+/// One push that changed a component's schema or indexes, as recorded in
+/// `_schema_history`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchemaHistoryEntry {
+    pub component_path: ComponentPath,
+    /// The `_schemas` document that became active, or `None` if the push
+    /// removed the schema.
+    pub schema_id: Option<DeveloperDocumentId>,
+    pub schema_validation: bool,
+    pub added_tables: Vec<TableName>,
+    pub removed_tables: Vec<TableName>,
+    pub changed_validators: Vec<ValidatorChange>,
+    pub added_indexes: Vec<IndexBuild>,
+    pub removed_indexes: Vec<(IndexName, DeveloperIndexConfig)>,
+}
+
+/// A table in both the previous and next schema whose document validator
+/// changed. Validators are stored as their JSON definitions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidatorChange {
+    pub table_name: TableName,
+    /// `None` if the table had no validator.
+    pub previous: Option<String>,
+    /// `None` if the table no longer has a validator.
+    pub next: Option<String>,
+}
+
+/// An index added by a push and the state its build was in when the push
+/// finished.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexBuild {
+    pub name: IndexName,
+    pub config: DeveloperIndexConfig,
+    /// `None` if the index couldn't be found when the push finished.
+    pub outcome: Option<IndexBuildOutcome>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IndexBuildOutcome {
+    Backfilling,
+    Backfilled,
+    Enabled,
+}
+
+impl From<&IndexConfig> for IndexBuildOutcome {
+    fn from(config: &IndexConfig) -> Self {
+        if config.is_enabled() {
+            Self::Enabled
+        } else if config.is_backfilling() {
+            Self::Backfilling
+        } else {
+            Self::Backfilled
+        }
+    }
+}
+
+/// Tables added and removed between two schemas, and the tables in both whose
+/// validators changed.
+pub fn diff_tables(
+    previous: Option<&DatabaseSchema>,
+    next: Option<&DatabaseSchema>,
+) -> anyhow::Result<(Vec<TableName>, Vec<TableName>, Vec<ValidatorChange>)> {
+    let table_names = |schema: Option<&DatabaseSchema>| -> BTreeSet<TableName> {
+        schema
+            .map(|schema| schema.tables.keys().cloned().collect())
+            .unwrap_or_default()
+    };
+    let previous_tables = table_names(previous);
+    let next_tables = table_names(next);
+    let added = next_tables.difference(&previous_tables).cloned().collect();
+    let removed = previous_tables.difference(&next_tables).cloned().collect();
+
+    let mut changed_validators = vec![];
+    if let (Some(previous), Some(next)) = (previous, next) {
+        for table_name in previous_tables.intersection(&next_tables) {
+            let previous_type = &previous.tables[table_name].document_type;
+            let next_type = &next.tables[table_name].document_type;
+            if previous_type == next_type {
+                continue;
+            }
+            let to_json = |document_type: &Option<_>| {
+                document_type
+                    .clone()
+                    .map(|document_type| Validator::from(document_type).json_serialize())
+                    .transpose()
+            };
+            changed_validators.push(ValidatorChange {
+                table_name: table_name.clone(),
+                previous: to_json(previous_type)?,
+                next: to_json(next_type)?,
+            });
+        }
+    }
+    Ok((added, removed, changed_validators))
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedValidatorChange {
+    table_name: String,
+    previous: Option<String>,
+    next: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedIndexBuild {
+    index: SerializedNamedDeveloperIndexConfig,
+    outcome: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedSchemaHistoryEntry {
+    component_path: String,
+    schema_id: Option<String>,
+    schema_validation: bool,
+    added_tables: Vec<String>,
+    removed_tables: Vec<String>,
+    changed_validators: Vec<SerializedValidatorChange>,
+    added_indexes: Vec<SerializedIndexBuild>,
+    removed_indexes: Vec<SerializedNamedDeveloperIndexConfig>,
+}
+
+impl IndexBuildOutcome {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Backfilling => "backfilling",
+            Self::Backfilled => "backfilled",
+            Self::Enabled => "enabled",
+        }
+    }
+}
+
+impl TryFrom<&str> for IndexBuildOutcome {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Ok(match value {
+            "backfilling" => Self::Backfilling,
+            "backfilled" => Self::Backfilled,
+            "enabled" => Self::Enabled,
+            _ => anyhow::bail!("Invalid index build outcome: {value}"),
+        })
+    }
+}
+
+fn serialize_index(
+    name: IndexName,
+    config: DeveloperIndexConfig,
+) -> anyhow::Result<SerializedNamedDeveloperIndexConfig> {
+    Ok(SerializedNamedDeveloperIndexConfig {
+        name: name.to_string(),
+        index_config: SerializedDeveloperIndexConfig::try_from(config)?,
+    })
+}
+
+fn deserialize_index(
+    index: SerializedNamedDeveloperIndexConfig,
+) -> anyhow::Result<(IndexName, DeveloperIndexConfig)> {
+    Ok((index.name.parse()?, index.index_config.try_into()?))
+}
+
+impl TryFrom<SchemaHistoryEntry> for SerializedSchemaHistoryEntry {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SchemaHistoryEntry) -> Result<Self, Self::Error> {
+        Ok(Self {
+            component_path: value.component_path.into(),
+            schema_id: value.schema_id.map(|id| id.to_string()),
+            schema_validation: value.schema_validation,
+            added_tables: value
+                .added_tables
+                .into_iter()
+                .map(|t| t.to_string())
+                .collect(),
+            removed_tables: value
+                .removed_tables
+                .into_iter()
+                .map(|t| t.to_string())
+                .collect(),
+            changed_validators: value
+                .changed_validators
+                .into_iter()
+                .map(|change| SerializedValidatorChange {
+                    table_name: change.table_name.to_string(),
+                    previous: change.previous,
+                    next: change.next,
+                })
+                .collect(),
+            added_indexes: value
+                .added_indexes
+                .into_iter()
+                .map(|build| {
+                    anyhow::Ok(SerializedIndexBuild {
+                        index: serialize_index(build.name, build.config)?,
+                        outcome: build.outcome.map(|outcome| outcome.as_str().to_string()),
+                    })
+                })
+                .try_collect()?,
+            removed_indexes: value
+                .removed_indexes
+                .into_iter()
+                .map(|(name, config)| serialize_index(name, config))
+                .try_collect()?,
+        })
+    }
+}
+
+impl TryFrom<SerializedSchemaHistoryEntry> for SchemaHistoryEntry {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedSchemaHistoryEntry) -> Result<Self, Self::Error> {
+        Ok(Self {
+            component_path: value.component_path.parse()?,
+            schema_id: value.schema_id.map(|id| id.parse()).transpose()?,
+            schema_validation: value.schema_validation,
+            added_tables: value
+                .added_tables
+                .into_iter()
+                .map(|t| t.parse())
+                .try_collect()?,
+            removed_tables: value
+                .removed_tables
+                .into_iter()
+                .map(|t| t.parse())
+                .try_collect()?,
+            changed_validators: value
+                .changed_validators
+                .into_iter()
+                .map(|change| {
+                    anyhow::Ok(ValidatorChange {
+                        table_name: change.table_name.parse()?,
+                        previous: change.previous,
+                        next: change.next,
+                    })
+                })
+                .try_collect()?,
+            added_indexes: value
+                .added_indexes
+                .into_iter()
+                .map(|build| {
+                    let (name, config) = deserialize_index(build.index)?;
+                    anyhow::Ok(IndexBuild {
+                        name,
+                        config,
+                        outcome: build
+                            .outcome
+                            .as_deref()
+                            .map(IndexBuildOutcome::try_from)
+                            .transpose()?,
+                    })
+                })
+                .try_collect()?,
+            removed_indexes: value
+                .removed_indexes
+                .into_iter()
+                .map(deserialize_index)
+                .try_collect()?,
+        })
+    }
+}
+
+codegen_convex_serialization!(SchemaHistoryEntry, SerializedSchemaHistoryEntry);
+
+#[cfg(test)]
+mod tests {
+    use common::{
+        db_schema,
+        object_validator,
+        schemas::{
+            validator::{
+                FieldValidator,
+                Validator,
+            },
+            DocumentSchema,
+        },
+    };
+
+    use super::diff_tables;
+
+    #[test]
+    fn test_diff_tables() -> anyhow::Result<()> {
+        let previous =
+            db_schema!("messages" => DocumentSchema::Any, "users" => DocumentSchema::Any);
+        let message_validator = object_validator!(
+            "body" => FieldValidator::required_field_type(Validator::String),
+        );
+        let next = db_schema!(
+            "messages" => DocumentSchema::Union(vec![message_validator]),
+            "channels" => DocumentSchema::Any,
+        );
+        let (added, removed, changed_validators) = diff_tables(Some(&previous), Some(&next))?;
+        assert_eq!(added, vec!["channels".parse()?]);
+        assert_eq!(removed, vec!["users".parse()?]);
+        assert_eq!(changed_validators.len(), 1);
+        assert_eq!(changed_validators[0].table_name, "messages".parse()?);
+        assert!(changed_validators[0].previous.is_some());
+
+        let (added, removed, changed_validators) = diff_tables(None, Some(&next))?;
+        assert_eq!(added.len(), 2);
+        assert!(removed.is_empty());
+        assert!(changed_validators.is_empty());
+        Ok(())
+    }
+}
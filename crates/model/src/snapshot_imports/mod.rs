@@ -42,6 +42,7 @@ use self::types::{
     SnapshotImport,
 };
 use crate::{
+    deployment_audit_log::types::AuditLogRequestContext,
     SystemIndex,
     SystemTable,
 };
@@ -119,6 +120,7 @@ impl<'a, RT: Runtime> SnapshotImportModel<'a, RT> {
             member_id: self.tx.identity().member_id(),
             checkpoints: None,
             requestor,
+            source_ip: AuditLogRequestContext::current().source_ip,
         };
         let id = SystemMetadataModel::new_global(self.tx)
             .insert(
@@ -161,6 +163,14 @@ impl<'a, RT: Runtime> SnapshotImportModel<'a, RT> {
                 anyhow::bail!("invalid import state transition {current_state:?} -> {new_state:?}")
             },
         }
+        self.write_state(id, new_state).await
+    }
+
+    async fn write_state(
+        &mut self,
+        id: ResolvedDocumentId,
+        new_state: ImportState,
+    ) -> anyhow::Result<()> {
         SystemMetadataModel::new_global(self.tx)
             .patch(
                 id,
@@ -240,6 +250,42 @@ impl<'a, RT: Runtime> SnapshotImportModel<'a, RT> {
         Ok(())
     }
 
+    /// Run a failed import again, picking up from its checkpoints: tables
+    /// that were fully imported are skipped and partially imported tables
+    /// continue after their last committed chunk.
+    pub async fn resume_import(&mut self, id: ResolvedDocumentId) -> anyhow::Result<()> {
+        let import = self.get(id).await?.context(ErrorMetadata::not_found(
+            "ImportNotFound",
+            format!("import {id} not found"),
+        ))?;
+        let ImportState::Failed(_) = import.state else {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "CannotResumeImport",
+                "Only failed imports can be resumed"
+            ));
+        };
+        let made_progress =
+            import.checkpoints.iter().flatten().any(|checkpoint| {
+                checkpoint.tablet_id.is_some() || checkpoint.num_rows_written > 0
+            });
+        if !made_progress {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "CannotResumeImport",
+                "This import failed before writing any documents. Start a new import instead."
+            ));
+        }
+        // Failed -> InProgress isn't a valid transition in `update_state`, so
+        // late progress updates from a canceled import can't revive it.
+        self.write_state(
+            id,
+            ImportState::InProgress {
+                progress_message: "Resuming import".to_string(),
+                checkpoint_messages: vec![],
+            },
+        )
+        .await
+    }
+
     pub async fn complete_import(
         &mut self,
         id: ResolvedDocumentId,
@@ -279,6 +325,28 @@ impl<'a, RT: Runtime> SnapshotImportModel<'a, RT> {
         .await
     }
 
+    /// Record that the first `num_rows_written` objects in the import for a
+    /// table have been written. Call this in the transaction that writes
+    /// them so a resumed import starts exactly after them.
+    pub async fn checkpoint_rows_imported(
+        &mut self,
+        id: ResolvedDocumentId,
+        component_path: &ComponentPath,
+        display_table_name: &TableName,
+        num_rows_written: i64,
+        last_imported_id: Option<String>,
+    ) -> anyhow::Result<()> {
+        self.update_checkpoints(id, move |checkpoints| {
+            if let Some(checkpoint) = checkpoints.iter_mut().find(|c| {
+                c.component_path == *component_path && c.display_table_name == *display_table_name
+            }) {
+                checkpoint.num_rows_written = num_rows_written;
+                checkpoint.last_imported_id = last_imported_id;
+            }
+        })
+        .await
+    }
+
     pub async fn get_table_checkpoint(
         &mut self,
         id: ResolvedDocumentId,
@@ -359,8 +427,7 @@ impl<'a, RT: Runtime> SnapshotImportModel<'a, RT> {
             if let Some(checkpoint) = checkpoints.iter_mut().find(|c| {
                 c.component_path == *component_path && c.display_table_name == *display_table_name
             }) {
-                if checkpoint.num_rows_written > 0
-                    && num_rows_written <= checkpoint.num_rows_written
+                if checkpoint.num_rows_written > 0 && num_rows_written < checkpoint.num_rows_written
                 {
                     *noop_ = true;
                     return;
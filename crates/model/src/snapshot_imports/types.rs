@@ -1,3 +1,5 @@
+use std::net::IpAddr;
+
 use common::{
     components::ComponentPath,
     types::{
@@ -29,6 +31,9 @@ pub struct SnapshotImport {
     pub member_id: Option<MemberId>,
     pub checkpoints: Option<Vec<ImportTableCheckpoint>>,
     pub requestor: ImportRequestor,
+    /// The address the import was requested from, recorded in the audit log
+    /// when the import worker finishes it.
+    pub source_ip: Option<IpAddr>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -46,6 +51,9 @@ pub struct SerializedSnapshotImport {
     member_id: Option<i64>,
     checkpoints: Option<Vec<SerializedImportTableCheckpoint>>,
     requestor: SerializedImportRequestor,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    source_ip: Option<String>,
 }
 
 impl From<SnapshotImport> for SerializedSnapshotImport {
@@ -66,6 +74,7 @@ impl From<SnapshotImport> for SerializedSnapshotImport {
                 .checkpoints
                 .map(|checkpoints| checkpoints.into_iter().map(Into::into).collect()),
             requestor: import.requestor.into(),
+            source_ip: import.source_ip.map(|ip| ip.to_string()),
         }
     }
 }
@@ -92,6 +101,7 @@ impl TryFrom<SerializedSnapshotImport> for SnapshotImport {
                 .map(|checkpoints| checkpoints.into_iter().map(TryInto::try_into).try_collect())
                 .transpose()?,
             requestor: import.requestor.into(),
+            source_ip: import.source_ip.map(|ip| ip.parse()).transpose()?,
         })
     }
 }
@@ -331,6 +341,12 @@ pub struct ImportTableCheckpoint {
     // been imported by a previous attempt, which means we have to start over
     // on any transient errors.
     pub is_missing_id_field: bool,
+
+    // The `_id` of the last object written, committed along with
+    // `num_rows_written`. A resumed import checks that the object it resumes
+    // after still has this `_id`, so it doesn't resume against a different
+    // file.
+    pub last_imported_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -343,6 +359,8 @@ pub struct SerializedImportTableCheckpoint {
     pub existing_rows_in_table: i64,
     pub existing_rows_to_delete: i64,
     pub is_missing_id_field: bool,
+    #[serde(default)]
+    pub last_imported_id: Option<String>,
 }
 
 impl From<ImportTableCheckpoint> for SerializedImportTableCheckpoint {
@@ -356,6 +374,7 @@ impl From<ImportTableCheckpoint> for SerializedImportTableCheckpoint {
             existing_rows_in_table: checkpoint.existing_rows_in_table,
             existing_rows_to_delete: checkpoint.existing_rows_to_delete,
             is_missing_id_field: checkpoint.is_missing_id_field,
+            last_imported_id: checkpoint.last_imported_id,
         }
     }
 }
@@ -376,6 +395,7 @@ impl TryFrom<SerializedImportTableCheckpoint> for ImportTableCheckpoint {
             existing_rows_in_table: checkpoint.existing_rows_in_table,
             existing_rows_to_delete: checkpoint.existing_rows_to_delete,
             is_missing_id_field: checkpoint.is_missing_id_field,
+            last_imported_id: checkpoint.last_imported_id,
         })
     }
 }
@@ -0,0 +1,494 @@
+//! Static asset hosting. Files are uploaded into named folders in the root
+//! component's file storage, and a mount serves a folder under a URL prefix
+//! of the HTTP actions domain, so small sites can be hosted by the deployment
+//! itself. Requests that don't match a file fall through to HTTP actions.
+
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParseDocument,
+        ParsedDocument,
+    },
+    maybe_val,
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+};
+use database::{
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use value::{
+    DeveloperDocumentId,
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::{
+    StaticAsset,
+    StaticAssetMount,
+};
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static STATIC_ASSET_MOUNTS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_static_asset_mounts"
+        .parse()
+        .expect("Invalid built-in static asset mounts table")
+});
+
+pub static STATIC_ASSETS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_static_assets"
+        .parse()
+        .expect("Invalid built-in static assets table")
+});
+
+pub static STATIC_ASSET_MOUNTS_BY_PATH_PREFIX_INDEX: LazyLock<SystemIndex<StaticAssetMountsTable>> =
+    LazyLock::new(|| SystemIndex::new("by_path_prefix", [&PATH_PREFIX_FIELD]).unwrap());
+pub static STATIC_ASSETS_BY_FOLDER_AND_PATH_INDEX: LazyLock<SystemIndex<StaticAssetsTable>> =
+    LazyLock::new(|| SystemIndex::new("by_folder_and_path", [&FOLDER_FIELD, &PATH_FIELD]).unwrap());
+static PATH_PREFIX_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "pathPrefix".parse().expect("invalid pathPrefix field"));
+static FOLDER_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "folder".parse().expect("invalid folder field"));
+static PATH_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "path".parse().expect("invalid path field"));
+
+const MAX_MOUNTS: usize = 32;
+const MAX_FOLDER_LEN: usize = 64;
+const MAX_PATH_LEN: usize = 1024;
+/// Served for paths that end in `/`, like the mount's prefix itself.
+const INDEX_FILE: &str = "index.html";
+
+pub struct StaticAssetMountsTable;
+impl SystemTable for StaticAssetMountsTable {
+    type Metadata = StaticAssetMount;
+
+    fn table_name() -> &'static TableName {
+        &STATIC_ASSET_MOUNTS_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![STATIC_ASSET_MOUNTS_BY_PATH_PREFIX_INDEX.clone()]
+    }
+}
+
+pub struct StaticAssetsTable;
+impl SystemTable for StaticAssetsTable {
+    type Metadata = StaticAsset;
+
+    fn table_name() -> &'static TableName {
+        &STATIC_ASSETS_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![STATIC_ASSETS_BY_FOLDER_AND_PATH_INDEX.clone()]
+    }
+}
+
+pub struct StaticAssetModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> StaticAssetModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    async fn mounts(&mut self) -> anyhow::Result<Vec<ParsedDocument<StaticAssetMount>>> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&STATIC_ASSET_MOUNTS_TABLE) {
+            return Ok(vec![]);
+        }
+        let query = Query::index_range(IndexRange {
+            index_name: STATIC_ASSET_MOUNTS_BY_PATH_PREFIX_INDEX.name(),
+            range: vec![],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut mounts = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            mounts.push(ParseDocument::<StaticAssetMount>::parse(doc)?);
+        }
+        Ok(mounts)
+    }
+
+    /// All mounts, ordered by path prefix.
+    pub async fn list_mounts(&mut self) -> anyhow::Result<Vec<ParsedDocument<StaticAssetMount>>> {
+        self.tx.require_admin("list_static_asset_mounts")?;
+        self.mounts().await
+    }
+
+    /// Mounts a folder under `mount.path_prefix`, replacing any mount already
+    /// at that prefix.
+    pub async fn set_mount(&mut self, mount: StaticAssetMount) -> anyhow::Result<()> {
+        self.tx.require_admin("set_static_asset_mount")?;
+        validate_mount(&mount)?;
+        let mounts = self.mounts().await?;
+        match mounts.iter().find(|m| m.path_prefix == mount.path_prefix) {
+            Some(existing) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), mount.try_into()?)
+                    .await?;
+            },
+            None => {
+                anyhow::ensure!(
+                    mounts.len() < MAX_MOUNTS,
+                    ErrorMetadata::bad_request(
+                        "TooManyStaticAssetMounts",
+                        format!("Deployments can have at most {MAX_MOUNTS} static asset mounts"),
+                    )
+                );
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&STATIC_ASSET_MOUNTS_TABLE, mount.try_into()?)
+                    .await?;
+            },
+        }
+        Ok(())
+    }
+
+    pub async fn delete_mount(&mut self, path_prefix: &str) -> anyhow::Result<()> {
+        self.tx.require_admin("delete_static_asset_mount")?;
+        let mounts = self.mounts().await?;
+        let Some(existing) = mounts.iter().find(|m| m.path_prefix == path_prefix) else {
+            anyhow::bail!(ErrorMetadata::not_found(
+                "StaticAssetMountNotFound",
+                format!("No static asset mount at {path_prefix}"),
+            ));
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .delete(existing.id())
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_asset(
+        &mut self,
+        folder: &str,
+        path: &str,
+    ) -> anyhow::Result<Option<ParsedDocument<StaticAsset>>> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&STATIC_ASSETS_TABLE) {
+            return Ok(None);
+        }
+        let query = Query::index_range(IndexRange {
+            index_name: STATIC_ASSETS_BY_FOLDER_AND_PATH_INDEX.name(),
+            range: vec![
+                IndexRangeExpression::Eq(FOLDER_FIELD.clone(), maybe_val!(folder.to_string())),
+                IndexRangeExpression::Eq(PATH_FIELD.clone(), maybe_val!(path.to_string())),
+            ],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParseDocument::<StaticAsset>::parse)
+            .transpose()
+    }
+
+    /// The files in `folder`, ordered by path.
+    pub async fn list_assets(
+        &mut self,
+        folder: &str,
+    ) -> anyhow::Result<Vec<ParsedDocument<StaticAsset>>> {
+        self.tx.require_admin("list_static_assets")?;
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&STATIC_ASSETS_TABLE) {
+            return Ok(vec![]);
+        }
+        let query = Query::index_range(IndexRange {
+            index_name: STATIC_ASSETS_BY_FOLDER_AND_PATH_INDEX.name(),
+            range: vec![IndexRangeExpression::Eq(
+                FOLDER_FIELD.clone(),
+                maybe_val!(folder.to_string()),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut assets = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            assets.push(ParseDocument::<StaticAsset>::parse(doc)?);
+        }
+        Ok(assets)
+    }
+
+    /// Adds a file to its folder, replacing any file already at its path.
+    /// Returns the replaced file's storage id so it can be deleted.
+    pub async fn put_asset(
+        &mut self,
+        asset: StaticAsset,
+    ) -> anyhow::Result<Option<DeveloperDocumentId>> {
+        self.tx.require_admin("upload_static_asset")?;
+        validate_asset_location(&asset.folder, &asset.path)?;
+        match self.get_asset(&asset.folder, &asset.path).await? {
+            Some(existing) => {
+                let replaced = existing.storage_id;
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), asset.try_into()?)
+                    .await?;
+                Ok(Some(replaced))
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&STATIC_ASSETS_TABLE, asset.try_into()?)
+                    .await?;
+                Ok(None)
+            },
+        }
+    }
+
+    /// Removes a file from its folder, returning it so its storage can be
+    /// deleted.
+    pub async fn delete_asset(&mut self, folder: &str, path: &str) -> anyhow::Result<StaticAsset> {
+        self.tx.require_admin("delete_static_asset")?;
+        let Some(existing) = self.get_asset(folder, path).await? else {
+            anyhow::bail!(ErrorMetadata::not_found(
+                "StaticAssetNotFound",
+                format!("No static asset at {path} in folder {folder}"),
+            ));
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .delete(existing.id())
+            .await?;
+        Ok(existing.into_value())
+    }
+
+    /// The file to serve for a request to `path`, along with the mount that
+    /// matched it. The mount with the longest matching prefix wins, and
+    /// paths without a file get the mount's SPA fallback, if it has one.
+    pub async fn resolve(
+        &mut self,
+        path: &str,
+    ) -> anyhow::Result<Option<(StaticAssetMount, StaticAsset)>> {
+        let Some(mount) = self
+            .mounts()
+            .await?
+            .into_iter()
+            .map(ParsedDocument::into_value)
+            .filter(|mount| mount_relative_path(&mount.path_prefix, path).is_some())
+            .max_by_key(|mount| mount.path_prefix.len())
+        else {
+            return Ok(None);
+        };
+        let Some(relative_path) = mount_relative_path(&mount.path_prefix, path) else {
+            return Ok(None);
+        };
+        let file = if relative_path.is_empty() || relative_path.ends_with('/') {
+            format!("{relative_path}{INDEX_FILE}")
+        } else {
+            relative_path.to_string()
+        };
+        let mut asset = self.get_asset(&mount.folder, &file).await?;
+        if asset.is_none()
+            && let Some(fallback) = &mount.spa_fallback
+        {
+            asset = self.get_asset(&mount.folder, fallback).await?;
+        }
+        Ok(asset.map(|asset| (mount, asset.into_value())))
+    }
+}
+
+/// The part of `path` under `path_prefix`, without a leading `/`, or `None`
+/// if the prefix doesn't match.
+fn mount_relative_path<'a>(path_prefix: &str, path: &'a str) -> Option<&'a str> {
+    let rest = path.strip_prefix(path_prefix)?;
+    if path_prefix == "/" {
+        return Some(rest);
+    }
+    match rest.strip_prefix('/') {
+        Some(rest) => Some(rest),
+        None if rest.is_empty() => Some(rest),
+        None => None,
+    }
+}
+
+fn validate_mount(mount: &StaticAssetMount) -> anyhow::Result<()> {
+    let prefix = &mount.path_prefix;
+    let valid_prefix = prefix == "/"
+        || (prefix.starts_with('/')
+            && prefix.len() <= MAX_PATH_LEN
+            && prefix[1..].split('/').all(is_valid_path_segment));
+    anyhow::ensure!(
+        valid_prefix,
+        ErrorMetadata::bad_request(
+            "InvalidStaticAssetPathPrefix",
+            format!(
+                "Static asset path prefixes must start with / and not end with one, like /app, \
+                 but got {prefix}"
+            ),
+        )
+    );
+    validate_folder(&mount.folder)?;
+    if let Some(cache_control) = &mount.cache_control {
+        let valid_header =
+            !cache_control.is_empty() && cache_control.chars().all(|c| matches!(c, ' '..='~'));
+        anyhow::ensure!(
+            valid_header,
+            ErrorMetadata::bad_request(
+                "InvalidStaticAssetCacheControl",
+                format!("{cache_control:?} is not a valid Cache-Control header"),
+            )
+        );
+    }
+    if let Some(fallback) = &mount.spa_fallback {
+        validate_asset_path(fallback)?;
+    }
+    Ok(())
+}
+
+/// Checks that a file can be uploaded to `path` in `folder`.
+pub fn validate_asset_location(folder: &str, path: &str) -> anyhow::Result<()> {
+    validate_folder(folder)?;
+    validate_asset_path(path)
+}
+
+fn validate_folder(folder: &str) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        !folder.is_empty()
+            && folder.len() <= MAX_FOLDER_LEN
+            && folder
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+        ErrorMetadata::bad_request(
+            "InvalidStaticAssetFolder",
+            format!(
+                "Static asset folder names must be 1 to {MAX_FOLDER_LEN} letters, digits, dashes \
+                 or underscores"
+            ),
+        )
+    );
+    Ok(())
+}
+
+fn validate_asset_path(path: &str) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        path.len() <= MAX_PATH_LEN && path.split('/').all(is_valid_path_segment),
+        ErrorMetadata::bad_request(
+            "InvalidStaticAssetPath",
+            format!(
+                "Static asset paths must be relative paths like assets/main.js, but got {path}"
+            ),
+        )
+    );
+    Ok(())
+}
+
+fn is_valid_path_segment(segment: &str) -> bool {
+    !segment.is_empty()
+        && segment != "."
+        && segment != ".."
+        && !segment.contains(['\\', '?', '#'])
+        && !segment.chars().any(char::is_control)
+}
+
+#[cfg(test)]
+mod tests {
+    use database::test_helpers::DbFixtures;
+    use keybroker::Identity;
+    use runtime::testing::TestRuntime;
+    use value::{
+        sha256::Sha256,
+        DeveloperDocumentId,
+    };
+
+    use super::{
+        types::{
+            StaticAsset,
+            StaticAssetMount,
+        },
+        StaticAssetModel,
+    };
+    use crate::test_helpers::DbFixturesWithModel;
+
+    fn mount(path_prefix: &str, folder: &str, spa_fallback: Option<&str>) -> StaticAssetMount {
+        StaticAssetMount {
+            path_prefix: path_prefix.to_string(),
+            folder: folder.to_string(),
+            cache_control: None,
+            compress: true,
+            spa_fallback: spa_fallback.map(str::to_string),
+        }
+    }
+
+    fn asset(folder: &str, path: &str) -> StaticAsset {
+        StaticAsset {
+            folder: folder.to_string(),
+            path: path.to_string(),
+            storage_id: DeveloperDocumentId::MIN,
+            sha256: Sha256::hash(path.as_bytes()),
+            size: 1,
+            content_type: None,
+            cache_control: None,
+        }
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_resolve(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new_with_model(&rt).await?.db;
+        let mut tx = db.begin(Identity::system()).await?;
+        let mut model = StaticAssetModel::new(&mut tx);
+        model.set_mount(mount("/", "site", None)).await?;
+        model
+            .set_mount(mount("/app", "app", Some("index.html")))
+            .await?;
+        for (folder, path) in [
+            ("site", "index.html"),
+            ("site", "about/index.html"),
+            ("app", "index.html"),
+            ("app", "main.js"),
+        ] {
+            model.put_asset(asset(folder, path)).await?;
+        }
+
+        let resolved = |r: Option<(StaticAssetMount, StaticAsset)>| {
+            r.map(|(mount, asset)| (mount.folder, asset.path))
+        };
+        let expect = |folder: &str, path: &str| Some((folder.to_string(), path.to_string()));
+        assert_eq!(
+            resolved(model.resolve("/").await?),
+            expect("site", "index.html")
+        );
+        assert_eq!(
+            resolved(model.resolve("/about/").await?),
+            expect("site", "about/index.html")
+        );
+        // Without a fallback, missing files are left to HTTP actions.
+        assert_eq!(resolved(model.resolve("/missing.js").await?), None);
+        // The longest prefix wins, and a prefix only matches whole segments.
+        assert_eq!(
+            resolved(model.resolve("/app/main.js").await?),
+            expect("app", "main.js")
+        );
+        assert_eq!(
+            resolved(model.resolve("/app").await?),
+            expect("app", "index.html")
+        );
+        assert_eq!(
+            resolved(model.resolve("/app/settings/profile").await?),
+            expect("app", "index.html")
+        );
+        assert_eq!(resolved(model.resolve("/apple").await?), None);
+
+        model.delete_mount("/app").await?;
+        assert_eq!(resolved(model.resolve("/app/main.js").await?), None);
+
+        // Replacing a file returns the storage id of the one it replaced.
+        assert!(model
+            .put_asset(asset("site", "index.html"))
+            .await?
+            .is_some());
+        assert!(model.put_asset(asset("site", "../secret")).await.is_err());
+        assert!(model.set_mount(mount("/app/", "app", None)).await.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::{
+    codegen_convex_serialization,
+    sha256::Sha256Digest,
+    DeveloperDocumentId,
+};
+
+/// Serves the files in `folder` under the URL prefix `path_prefix` of the
+/// deployment's HTTP actions domain.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct StaticAssetMount {
+    /// Like `/` or `/app`. Never ends with `/`, except for the root prefix.
+    pub path_prefix: String,
+    pub folder: String,
+    /// The `Cache-Control` header for files that don't set their own.
+    pub cache_control: Option<String>,
+    /// Whether to gzip or brotli compress text responses for clients that
+    /// accept it.
+    pub compress: bool,
+    /// The file to serve for paths without a file, like `index.html` for a
+    /// single page app with client-side routing.
+    pub spa_fallback: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedStaticAssetMount {
+    path_prefix: String,
+    folder: String,
+    cache_control: Option<String>,
+    compress: bool,
+    spa_fallback: Option<String>,
+}
+
+impl From<StaticAssetMount> for SerializedStaticAssetMount {
+    fn from(mount: StaticAssetMount) -> Self {
+        Self {
+            path_prefix: mount.path_prefix,
+            folder: mount.folder,
+            cache_control: mount.cache_control,
+            compress: mount.compress,
+            spa_fallback: mount.spa_fallback,
+        }
+    }
+}
+
+impl TryFrom<SerializedStaticAssetMount> for StaticAssetMount {
+    type Error = anyhow::Error;
+
+    fn try_from(mount: SerializedStaticAssetMount) -> anyhow::Result<Self> {
+        Ok(Self {
+            path_prefix: mount.path_prefix,
+            folder: mount.folder,
+            cache_control: mount.cache_control,
+            compress: mount.compress,
+            spa_fallback: mount.spa_fallback,
+        })
+    }
+}
+
+codegen_convex_serialization!(StaticAssetMount, SerializedStaticAssetMount);
+
+/// A file in a static asset folder, stored in the root component's file
+/// storage.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct StaticAsset {
+    pub folder: String,
+    /// The file's path within the folder, like `assets/main.js`.
+    pub path: String,
+    pub storage_id: DeveloperDocumentId,
+    pub sha256: Sha256Digest,
+    pub size: i64,
+    pub content_type: Option<String>,
+    /// Overrides the mount's `Cache-Control` header for this file.
+    pub cache_control: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedStaticAsset {
+    folder: String,
+    path: String,
+    storage_id: String,
+    sha256: String,
+    size: i64,
+    content_type: Option<String>,
+    cache_control: Option<String>,
+}
+
+impl From<StaticAsset> for SerializedStaticAsset {
+    fn from(asset: StaticAsset) -> Self {
+        Self {
+            folder: asset.folder,
+            path: asset.path,
+            storage_id: asset.storage_id.encode(),
+            sha256: asset.sha256.as_base64(),
+            size: asset.size,
+            content_type: asset.content_type,
+            cache_control: asset.cache_control,
+        }
+    }
+}
+
+impl TryFrom<SerializedStaticAsset> for StaticAsset {
+    type Error = anyhow::Error;
+
+    fn try_from(asset: SerializedStaticAsset) -> anyhow::Result<Self> {
+        Ok(Self {
+            folder: asset.folder,
+            path: asset.path,
+            storage_id: DeveloperDocumentId::decode(&asset.storage_id)?,
+            sha256: Sha256Digest::from_base64(&asset.sha256)?,
+            size: asset.size,
+            content_type: asset.content_type,
+            cache_control: asset.cache_control,
+        })
+    }
+}
+
+codegen_convex_serialization!(StaticAsset, SerializedStaticAsset);
@@ -0,0 +1,262 @@
+//! Per-function and per-table resource consumption, rolled up into fixed
+//! windows so self-hosted deployments can attribute usage to the teams that
+//! own each function or table.
+
+use std::sync::LazyLock;
+
+use common::{
+    document::ParsedDocument,
+    maybe_val,
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+};
+use database::{
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use value::{
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::{
+    FunctionUsageRecord,
+    TableUsageRecord,
+};
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static FUNCTION_USAGE_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_function_usage"
+        .parse()
+        .expect("Invalid built-in function usage table")
+});
+
+pub static TABLE_USAGE_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_table_usage"
+        .parse()
+        .expect("Invalid built-in table usage table")
+});
+
+static WINDOW_START_FIELD: LazyLock<FieldPath> = LazyLock::new(|| {
+    "windowStartMs"
+        .parse()
+        .expect("invalid windowStartMs field")
+});
+
+static COMPONENT_PATH_FIELD: LazyLock<FieldPath> = LazyLock::new(|| {
+    "componentPath"
+        .parse()
+        .expect("invalid componentPath field")
+});
+
+static UDF_PATH_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "udfPath".parse().expect("invalid udfPath field"));
+
+static TABLE_NAME_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "tableName".parse().expect("invalid tableName field"));
+
+pub static FUNCTION_USAGE_BY_WINDOW_INDEX: LazyLock<SystemIndex<FunctionUsageTable>> =
+    LazyLock::new(|| {
+        SystemIndex::new(
+            "by_window_and_udf_path",
+            [&WINDOW_START_FIELD, &COMPONENT_PATH_FIELD, &UDF_PATH_FIELD],
+        )
+        .unwrap()
+    });
+
+pub static TABLE_USAGE_BY_WINDOW_INDEX: LazyLock<SystemIndex<TableUsageTable>> =
+    LazyLock::new(|| {
+        SystemIndex::new(
+            "by_window_and_table_name",
+            [
+                &WINDOW_START_FIELD,
+                &COMPONENT_PATH_FIELD,
+                &TABLE_NAME_FIELD,
+            ],
+        )
+        .unwrap()
+    });
+
+pub struct FunctionUsageTable;
+impl SystemTable for FunctionUsageTable {
+    type Metadata = FunctionUsageRecord;
+
+    fn table_name() -> &'static TableName {
+        &FUNCTION_USAGE_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![FUNCTION_USAGE_BY_WINDOW_INDEX.clone()]
+    }
+}
+
+pub struct TableUsageTable;
+impl SystemTable for TableUsageTable {
+    type Metadata = TableUsageRecord;
+
+    fn table_name() -> &'static TableName {
+        &TABLE_USAGE_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![TABLE_USAGE_BY_WINDOW_INDEX.clone()]
+    }
+}
+
+pub struct UsageMeteringModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> UsageMeteringModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    /// Adds `record` to the row for its window and function, creating the row
+    /// if this is the function's first usage in the window.
+    pub async fn record_function_usage(
+        &mut self,
+        record: FunctionUsageRecord,
+    ) -> anyhow::Result<()> {
+        let query = Query::index_range(IndexRange {
+            index_name: FUNCTION_USAGE_BY_WINDOW_INDEX.name(),
+            range: vec![
+                IndexRangeExpression::Eq(
+                    WINDOW_START_FIELD.clone(),
+                    maybe_val!(record.window_start_ms),
+                ),
+                IndexRangeExpression::Eq(
+                    COMPONENT_PATH_FIELD.clone(),
+                    maybe_val!(String::from(record.component_path.clone())),
+                ),
+                IndexRangeExpression::Eq(
+                    UDF_PATH_FIELD.clone(),
+                    maybe_val!(record.udf_path.clone()),
+                ),
+            ],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        match query_stream.next(self.tx, None).await? {
+            Some(doc) => {
+                let existing: ParsedDocument<FunctionUsageRecord> = doc.parse()?;
+                let id = existing.id();
+                let mut merged = existing.into_value();
+                merged.merge(&record);
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(id, merged.try_into()?)
+                    .await?;
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&FUNCTION_USAGE_TABLE, record.try_into()?)
+                    .await?;
+            },
+        }
+        Ok(())
+    }
+
+    /// Adds `record` to the row for its window and table, creating the row if
+    /// this is the table's first usage in the window.
+    pub async fn record_table_usage(&mut self, record: TableUsageRecord) -> anyhow::Result<()> {
+        let query = Query::index_range(IndexRange {
+            index_name: TABLE_USAGE_BY_WINDOW_INDEX.name(),
+            range: vec![
+                IndexRangeExpression::Eq(
+                    WINDOW_START_FIELD.clone(),
+                    maybe_val!(record.window_start_ms),
+                ),
+                IndexRangeExpression::Eq(
+                    COMPONENT_PATH_FIELD.clone(),
+                    maybe_val!(String::from(record.component_path.clone())),
+                ),
+                IndexRangeExpression::Eq(
+                    TABLE_NAME_FIELD.clone(),
+                    maybe_val!(record.table_name.clone()),
+                ),
+            ],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        match query_stream.next(self.tx, None).await? {
+            Some(doc) => {
+                let existing: ParsedDocument<TableUsageRecord> = doc.parse()?;
+                let id = existing.id();
+                let mut merged = existing.into_value();
+                merged.merge(&record);
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(id, merged.try_into()?)
+                    .await?;
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&TABLE_USAGE_TABLE, record.try_into()?)
+                    .await?;
+            },
+        }
+        Ok(())
+    }
+
+    /// Function usage rows for windows starting in `[since_ms, until_ms)`,
+    /// oldest first.
+    pub async fn list_function_usage(
+        &mut self,
+        since_ms: i64,
+        until_ms: i64,
+    ) -> anyhow::Result<Vec<FunctionUsageRecord>> {
+        self.tx.require_admin("list_function_usage")?;
+        let query = Query::index_range(IndexRange {
+            index_name: FUNCTION_USAGE_BY_WINDOW_INDEX.name(),
+            range: window_range(since_ms, until_ms),
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut result = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            let row: ParsedDocument<FunctionUsageRecord> = doc.parse()?;
+            result.push(row.into_value());
+        }
+        Ok(result)
+    }
+
+    /// Table usage rows for windows starting in `[since_ms, until_ms)`, oldest
+    /// first.
+    pub async fn list_table_usage(
+        &mut self,
+        since_ms: i64,
+        until_ms: i64,
+    ) -> anyhow::Result<Vec<TableUsageRecord>> {
+        self.tx.require_admin("list_table_usage")?;
+        let query = Query::index_range(IndexRange {
+            index_name: TABLE_USAGE_BY_WINDOW_INDEX.name(),
+            range: window_range(since_ms, until_ms),
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut result = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            let row: ParsedDocument<TableUsageRecord> = doc.parse()?;
+            result.push(row.into_value());
+        }
+        Ok(result)
+    }
+}
+
+fn window_range(since_ms: i64, until_ms: i64) -> Vec<IndexRangeExpression> {
+    vec![
+        IndexRangeExpression::Gte(WINDOW_START_FIELD.clone(), maybe_val!(since_ms)),
+        IndexRangeExpression::Lt(WINDOW_START_FIELD.clone(), maybe_val!(until_ms)),
+    ]
+}
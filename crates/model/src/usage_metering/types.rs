@@ -0,0 +1,219 @@
+use common::components::ComponentPath;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// Resources consumed by one function over one metering window, as recorded
+/// in `_function_usage`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct FunctionUsageRecord {
+    /// Start of the window this row covers, in milliseconds since the epoch.
+    pub window_start_ms: i64,
+    pub component_path: ComponentPath,
+    /// The function's path, or the route for HTTP actions.
+    pub udf_path: String,
+    pub calls: i64,
+    pub failures: i64,
+    pub duration_ms: i64,
+    /// Memory allocated to the function multiplied by its duration. Divide by
+    /// 1024 * 1000 for GB-seconds.
+    pub memory_mb_ms: i64,
+    pub database_read_bytes: i64,
+    pub database_write_bytes: i64,
+    pub documents_read: i64,
+    pub storage_ingress_bytes: i64,
+    pub storage_egress_bytes: i64,
+    pub vector_read_bytes: i64,
+    pub vector_write_bytes: i64,
+}
+
+impl FunctionUsageRecord {
+    pub fn new(window_start_ms: i64, component_path: ComponentPath, udf_path: String) -> Self {
+        Self {
+            window_start_ms,
+            component_path,
+            udf_path,
+            calls: 0,
+            failures: 0,
+            duration_ms: 0,
+            memory_mb_ms: 0,
+            database_read_bytes: 0,
+            database_write_bytes: 0,
+            documents_read: 0,
+            storage_ingress_bytes: 0,
+            storage_egress_bytes: 0,
+            vector_read_bytes: 0,
+            vector_write_bytes: 0,
+        }
+    }
+
+    /// Adds `other`'s counters to this record, keeping this record's key.
+    pub fn merge(&mut self, other: &Self) {
+        self.calls += other.calls;
+        self.failures += other.failures;
+        self.duration_ms += other.duration_ms;
+        self.memory_mb_ms += other.memory_mb_ms;
+        self.database_read_bytes += other.database_read_bytes;
+        self.database_write_bytes += other.database_write_bytes;
+        self.documents_read += other.documents_read;
+        self.storage_ingress_bytes += other.storage_ingress_bytes;
+        self.storage_egress_bytes += other.storage_egress_bytes;
+        self.vector_read_bytes += other.vector_read_bytes;
+        self.vector_write_bytes += other.vector_write_bytes;
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedFunctionUsageRecord {
+    window_start_ms: i64,
+    component_path: String,
+    udf_path: String,
+    calls: i64,
+    failures: i64,
+    duration_ms: i64,
+    memory_mb_ms: i64,
+    database_read_bytes: i64,
+    database_write_bytes: i64,
+    documents_read: i64,
+    storage_ingress_bytes: i64,
+    storage_egress_bytes: i64,
+    vector_read_bytes: i64,
+    vector_write_bytes: i64,
+}
+
+impl From<FunctionUsageRecord> for SerializedFunctionUsageRecord {
+    fn from(value: FunctionUsageRecord) -> Self {
+        Self {
+            window_start_ms: value.window_start_ms,
+            component_path: value.component_path.into(),
+            udf_path: value.udf_path,
+            calls: value.calls,
+            failures: value.failures,
+            duration_ms: value.duration_ms,
+            memory_mb_ms: value.memory_mb_ms,
+            database_read_bytes: value.database_read_bytes,
+            database_write_bytes: value.database_write_bytes,
+            documents_read: value.documents_read,
+            storage_ingress_bytes: value.storage_ingress_bytes,
+            storage_egress_bytes: value.storage_egress_bytes,
+            vector_read_bytes: value.vector_read_bytes,
+            vector_write_bytes: value.vector_write_bytes,
+        }
+    }
+}
+
+impl TryFrom<SerializedFunctionUsageRecord> for FunctionUsageRecord {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedFunctionUsageRecord) -> Result<Self, Self::Error> {
+        Ok(Self {
+            window_start_ms: value.window_start_ms,
+            component_path: value.component_path.parse()?,
+            udf_path: value.udf_path,
+            calls: value.calls,
+            failures: value.failures,
+            duration_ms: value.duration_ms,
+            memory_mb_ms: value.memory_mb_ms,
+            database_read_bytes: value.database_read_bytes,
+            database_write_bytes: value.database_write_bytes,
+            documents_read: value.documents_read,
+            storage_ingress_bytes: value.storage_ingress_bytes,
+            storage_egress_bytes: value.storage_egress_bytes,
+            vector_read_bytes: value.vector_read_bytes,
+            vector_write_bytes: value.vector_write_bytes,
+        })
+    }
+}
+
+codegen_convex_serialization!(FunctionUsageRecord, SerializedFunctionUsageRecord);
+
+/// Reads and writes against one table over one metering window, as recorded
+/// in `_table_usage`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct TableUsageRecord {
+    /// Start of the window this row covers, in milliseconds since the epoch.
+    pub window_start_ms: i64,
+    pub component_path: ComponentPath,
+    pub table_name: String,
+    pub read_bytes: i64,
+    pub write_bytes: i64,
+    pub documents_read: i64,
+    pub vector_read_bytes: i64,
+    pub vector_write_bytes: i64,
+}
+
+impl TableUsageRecord {
+    pub fn new(window_start_ms: i64, component_path: ComponentPath, table_name: String) -> Self {
+        Self {
+            window_start_ms,
+            component_path,
+            table_name,
+            read_bytes: 0,
+            write_bytes: 0,
+            documents_read: 0,
+            vector_read_bytes: 0,
+            vector_write_bytes: 0,
+        }
+    }
+
+    /// Adds `other`'s counters to this record, keeping this record's key.
+    pub fn merge(&mut self, other: &Self) {
+        self.read_bytes += other.read_bytes;
+        self.write_bytes += other.write_bytes;
+        self.documents_read += other.documents_read;
+        self.vector_read_bytes += other.vector_read_bytes;
+        self.vector_write_bytes += other.vector_write_bytes;
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedTableUsageRecord {
+    window_start_ms: i64,
+    component_path: String,
+    table_name: String,
+    read_bytes: i64,
+    write_bytes: i64,
+    documents_read: i64,
+    vector_read_bytes: i64,
+    vector_write_bytes: i64,
+}
+
+impl From<TableUsageRecord> for SerializedTableUsageRecord {
+    fn from(value: TableUsageRecord) -> Self {
+        Self {
+            window_start_ms: value.window_start_ms,
+            component_path: value.component_path.into(),
+            table_name: value.table_name,
+            read_bytes: value.read_bytes,
+            write_bytes: value.write_bytes,
+            documents_read: value.documents_read,
+            vector_read_bytes: value.vector_read_bytes,
+            vector_write_bytes: value.vector_write_bytes,
+        }
+    }
+}
+
+impl TryFrom<SerializedTableUsageRecord> for TableUsageRecord {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedTableUsageRecord) -> Result<Self, Self::Error> {
+        Ok(Self {
+            window_start_ms: value.window_start_ms,
+            component_path: value.component_path.parse()?,
+            table_name: value.table_name,
+            read_bytes: value.read_bytes,
+            write_bytes: value.write_bytes,
+            documents_read: value.documents_read,
+            vector_read_bytes: value.vector_read_bytes,
+            vector_write_bytes: value.vector_write_bytes,
+        })
+    }
+}
+
+codegen_convex_serialization!(TableUsageRecord, SerializedTableUsageRecord);
@@ -0,0 +1,117 @@
+//! Per-index health of vector indexes, recorded periodically so operators can
+//! tell when an index needs compacting or its search parameters need tuning.
+
+use std::{
+    collections::BTreeSet,
+    sync::LazyLock,
+};
+
+use common::{
+    document::{
+        ParseDocument,
+        ParsedDocument,
+    },
+    query::{
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::IndexId,
+};
+use database::{
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use value::{
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::VectorIndexStats;
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static VECTOR_INDEX_STATS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_vector_index_stats"
+        .parse()
+        .expect("Invalid built-in vector index stats table")
+});
+
+static INDEX_ID_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "indexId".parse().expect("invalid indexId field"));
+
+pub static VECTOR_INDEX_STATS_BY_INDEX_ID_INDEX: LazyLock<SystemIndex<VectorIndexStatsTable>> =
+    LazyLock::new(|| SystemIndex::new("by_index_id", [&INDEX_ID_FIELD]).unwrap());
+
+pub struct VectorIndexStatsTable;
+impl SystemTable for VectorIndexStatsTable {
+    type Metadata = VectorIndexStats;
+
+    fn table_name() -> &'static TableName {
+        &VECTOR_INDEX_STATS_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![VECTOR_INDEX_STATS_BY_INDEX_ID_INDEX.clone()]
+    }
+}
+
+pub struct VectorIndexStatsModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> VectorIndexStatsModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    pub async fn list(&mut self) -> anyhow::Result<Vec<ParsedDocument<VectorIndexStats>>> {
+        let query = Query::full_table_scan(VECTOR_INDEX_STATS_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut stats = vec![];
+        while let Some(document) = query_stream.next(self.tx, None).await? {
+            stats.push(document.parse()?);
+        }
+        Ok(stats)
+    }
+
+    /// Replaces the recorded stats of each index in `stats`, and removes the
+    /// stats of indexes that aren't in it anymore.
+    pub async fn record(&mut self, stats: Vec<VectorIndexStats>) -> anyhow::Result<()> {
+        let index_ids: BTreeSet<IndexId> = stats.iter().map(|stats| stats.index_id).collect();
+        let (existing, removed): (Vec<_>, Vec<_>) = self
+            .list()
+            .await?
+            .into_iter()
+            .partition(|document| index_ids.contains(&document.index_id));
+        for document in removed {
+            SystemMetadataModel::new_global(self.tx)
+                .delete(document.id())
+                .await?;
+        }
+        for index_stats in stats {
+            match existing
+                .iter()
+                .find(|document| document.index_id == index_stats.index_id)
+            {
+                Some(document) => {
+                    SystemMetadataModel::new_global(self.tx)
+                        .replace(document.id(), index_stats.try_into()?)
+                        .await?;
+                },
+                None => {
+                    SystemMetadataModel::new_global(self.tx)
+                        .insert(&VECTOR_INDEX_STATS_TABLE, index_stats.try_into()?)
+                        .await?;
+                },
+            }
+        }
+        Ok(())
+    }
+}
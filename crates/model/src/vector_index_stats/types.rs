@@ -0,0 +1,127 @@
+use std::str::FromStr;
+
+use common::types::IndexId;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::{
+    codegen_convex_serialization,
+    InternalId,
+};
+
+/// The latest health of one vector index, as recorded in `_vector_index_stats`
+/// by the vector index stats worker.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct VectorIndexStats {
+    pub index_id: IndexId,
+    /// The number of live vectors in each of the index's disk segments. Many
+    /// small segments slow searches down until they're compacted.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::collection::vec(0..=(i64::MAX as u64), 0..4)")
+    )]
+    pub segment_sizes: Vec<u64>,
+    /// Deleted vectors that still take up space in the disk segments.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=(i64::MAX as u64)")
+    )]
+    pub deleted_vectors: u64,
+    /// The estimated size of the disk segments' vectors.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=(i64::MAX as u64)")
+    )]
+    pub segment_bytes: u64,
+    /// The size of the vectors written since the disk segments were built,
+    /// which the backend keeps in memory.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=(i64::MAX as u64)")
+    )]
+    pub memory_index_bytes: u64,
+    /// Searches against the index since the previous stats were recorded.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=(i64::MAX as u64)")
+    )]
+    pub query_count: u64,
+    /// The average latency of those searches, if there were any.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::option::of(0.0..1e6)")
+    )]
+    pub average_query_latency_ms: Option<f64>,
+    /// The fraction of the exact nearest neighbors of sampled vectors that
+    /// the index found in the latest recall probe.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::option::of(0.0..=1.0)")
+    )]
+    pub recall: Option<f64>,
+    pub recall_sampled_at_ms: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedVectorIndexStats {
+    index_id: String,
+    segment_count: i64,
+    segment_sizes: Vec<i64>,
+    deleted_vectors: i64,
+    segment_bytes: i64,
+    memory_index_bytes: i64,
+    query_count: i64,
+    average_query_latency_ms: Option<f64>,
+    recall: Option<f64>,
+    recall_sampled_at_ms: Option<i64>,
+}
+
+impl TryFrom<VectorIndexStats> for SerializedVectorIndexStats {
+    type Error = anyhow::Error;
+
+    fn try_from(value: VectorIndexStats) -> Result<Self, Self::Error> {
+        Ok(Self {
+            index_id: value.index_id.to_string(),
+            segment_count: value.segment_sizes.len().try_into()?,
+            segment_sizes: value
+                .segment_sizes
+                .into_iter()
+                .map(i64::try_from)
+                .collect::<Result<_, _>>()?,
+            deleted_vectors: value.deleted_vectors.try_into()?,
+            segment_bytes: value.segment_bytes.try_into()?,
+            memory_index_bytes: value.memory_index_bytes.try_into()?,
+            query_count: value.query_count.try_into()?,
+            average_query_latency_ms: value.average_query_latency_ms,
+            recall: value.recall,
+            recall_sampled_at_ms: value.recall_sampled_at_ms,
+        })
+    }
+}
+
+impl TryFrom<SerializedVectorIndexStats> for VectorIndexStats {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedVectorIndexStats) -> Result<Self, Self::Error> {
+        Ok(Self {
+            index_id: InternalId::from_str(&value.index_id)?,
+            segment_sizes: value
+                .segment_sizes
+                .into_iter()
+                .map(u64::try_from)
+                .collect::<Result<_, _>>()?,
+            deleted_vectors: value.deleted_vectors.try_into()?,
+            segment_bytes: value.segment_bytes.try_into()?,
+            memory_index_bytes: value.memory_index_bytes.try_into()?,
+            query_count: value.query_count.try_into()?,
+            average_query_latency_ms: value.average_query_latency_ms,
+            recall: value.recall,
+            recall_sampled_at_ms: value.recall_sampled_at_ms,
+        })
+    }
+}
+
+codegen_convex_serialization!(VectorIndexStats, SerializedVectorIndexStats);
@@ -0,0 +1,209 @@
+//! Replay protection for webhooks sent to HTTP actions. Each verified
+//! request's nonce is recorded until it expires, and a request whose nonce is
+//! already recorded for the same route is a replay.
+
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParseDocument,
+        ParsedDocument,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+};
+use database::{
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use value::{
+    ConvexValue,
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::WebhookNonce;
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static WEBHOOK_NONCES_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_webhook_nonces"
+        .parse()
+        .expect("Invalid built-in webhook_nonces table")
+});
+
+/// By scope and nonce. Used to check whether a request is a replay.
+pub static WEBHOOK_NONCES_INDEX_BY_SCOPE_AND_NONCE: LazyLock<SystemIndex<WebhookNoncesTable>> =
+    LazyLock::new(|| SystemIndex::new("by_scope_and_nonce", [&SCOPE_FIELD, &NONCE_FIELD]).unwrap());
+/// By expiration. Used to delete nonces once they've expired.
+pub static WEBHOOK_NONCES_INDEX_BY_EXPIRES_AT: LazyLock<SystemIndex<WebhookNoncesTable>> =
+    LazyLock::new(|| SystemIndex::new("by_expires_at", [&EXPIRES_AT_MS_FIELD]).unwrap());
+static SCOPE_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "scope".parse().expect("invalid scope field"));
+static NONCE_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "nonce".parse().expect("invalid nonce field"));
+static EXPIRES_AT_MS_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "expiresAtMs".parse().expect("invalid expiresAtMs field"));
+
+pub struct WebhookNoncesTable;
+impl SystemTable for WebhookNoncesTable {
+    type Metadata = WebhookNonce;
+
+    fn table_name() -> &'static TableName {
+        &WEBHOOK_NONCES_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![
+            WEBHOOK_NONCES_INDEX_BY_SCOPE_AND_NONCE.clone(),
+            WEBHOOK_NONCES_INDEX_BY_EXPIRES_AT.clone(),
+        ]
+    }
+}
+
+pub struct WebhookNonceModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> WebhookNonceModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    async fn get(
+        &mut self,
+        scope: &str,
+        nonce: &str,
+    ) -> anyhow::Result<Option<ParsedDocument<WebhookNonce>>> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&WEBHOOK_NONCES_TABLE) {
+            return Ok(None);
+        }
+        let query = Query::index_range(IndexRange {
+            index_name: WEBHOOK_NONCES_INDEX_BY_SCOPE_AND_NONCE.name(),
+            range: vec![
+                IndexRangeExpression::Eq(
+                    SCOPE_FIELD.clone(),
+                    ConvexValue::try_from(scope.to_string())?.into(),
+                ),
+                IndexRangeExpression::Eq(
+                    NONCE_FIELD.clone(),
+                    ConvexValue::try_from(nonce.to_string())?.into(),
+                ),
+            ],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParseDocument::<WebhookNonce>::parse)
+            .transpose()
+    }
+
+    /// Records that a request with `nonce` was accepted on `scope`, until
+    /// `expires_at_ms`. Returns false if the nonce was already recorded and
+    /// hasn't expired, meaning the request is a replay.
+    pub async fn record(
+        &mut self,
+        scope: String,
+        nonce: String,
+        expires_at_ms: i64,
+        now_ms: i64,
+    ) -> anyhow::Result<bool> {
+        let existing = self.get(&scope, &nonce).await?;
+        let record = WebhookNonce {
+            scope,
+            nonce,
+            expires_at_ms,
+        };
+        match existing {
+            Some(existing) if existing.expires_at_ms > now_ms => return Ok(false),
+            Some(existing) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), record.try_into()?)
+                    .await?;
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&WEBHOOK_NONCES_TABLE, record.try_into()?)
+                    .await?;
+            },
+        }
+        Ok(true)
+    }
+
+    /// Deletes up to `limit` nonces that expired before `now_ms`, returning
+    /// how many were deleted.
+    pub async fn delete_expired(&mut self, now_ms: i64, limit: usize) -> anyhow::Result<usize> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&WEBHOOK_NONCES_TABLE) {
+            return Ok(0);
+        }
+        let query = Query::index_range(IndexRange {
+            index_name: WEBHOOK_NONCES_INDEX_BY_EXPIRES_AT.name(),
+            range: vec![IndexRangeExpression::Lte(
+                EXPIRES_AT_MS_FIELD.clone(),
+                ConvexValue::Int64(now_ms).into(),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut expired = vec![];
+        while let Some(doc) = query_stream
+            .next(self.tx, Some(limit - expired.len()))
+            .await?
+        {
+            expired.push(doc.id());
+            if expired.len() >= limit {
+                break;
+            }
+        }
+        for id in &expired {
+            SystemMetadataModel::new_global(self.tx).delete(*id).await?;
+        }
+        Ok(expired.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use database::test_helpers::DbFixtures;
+    use keybroker::Identity;
+    use runtime::testing::TestRuntime;
+
+    use super::WebhookNonceModel;
+    use crate::test_helpers::DbFixturesWithModel;
+
+    #[convex_macro::test_runtime]
+    async fn test_record_rejects_unexpired_replays(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new_with_model(&rt).await?.db;
+        let mut tx = db.begin(Identity::system()).await?;
+        let mut model = WebhookNonceModel::new(&mut tx);
+        let scope = || "root:POST /stripe".to_string();
+        assert!(model.record(scope(), "n1".into(), 2000, 1000).await?);
+        assert!(!model.record(scope(), "n1".into(), 3000, 1500).await?);
+        // The same nonce on another route isn't a replay.
+        assert!(
+            model
+                .record("root:POST /github".into(), "n1".into(), 2000, 1500)
+                .await?
+        );
+        // Once expired, the nonce can be used again.
+        assert!(model.record(scope(), "n1".into(), 4000, 2500).await?);
+
+        assert!(model.record(scope(), "n2".into(), 1000, 500).await?);
+        assert_eq!(model.delete_expired(3000, 10).await?, 2);
+        assert!(!model.record(scope(), "n1".into(), 5000, 3000).await?);
+        Ok(())
+    }
+}
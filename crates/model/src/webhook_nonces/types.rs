@@ -0,0 +1,49 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// A webhook an HTTP action route has already accepted, kept until
+/// `expires_at_ms` so a replay of the same request is rejected.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct WebhookNonce {
+    /// The component and route the webhook was sent to, so senders' nonces
+    /// don't collide across routes.
+    pub scope: String,
+    pub nonce: String,
+    pub expires_at_ms: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedWebhookNonce {
+    scope: String,
+    nonce: String,
+    expires_at_ms: i64,
+}
+
+impl From<WebhookNonce> for SerializedWebhookNonce {
+    fn from(nonce: WebhookNonce) -> Self {
+        Self {
+            scope: nonce.scope,
+            nonce: nonce.nonce,
+            expires_at_ms: nonce.expires_at_ms,
+        }
+    }
+}
+
+impl TryFrom<SerializedWebhookNonce> for WebhookNonce {
+    type Error = anyhow::Error;
+
+    fn try_from(nonce: SerializedWebhookNonce) -> anyhow::Result<Self> {
+        Ok(Self {
+            scope: nonce.scope,
+            nonce: nonce.nonce,
+            expires_at_ms: nonce.expires_at_ms,
+        })
+    }
+}
+
+codegen_convex_serialization!(WebhookNonce, SerializedWebhookNonce);
@@ -0,0 +1,523 @@
+//! Outbound webhooks. Endpoints are registered with a URL, a signing secret
+//! and filters for the events they're sent. Each matching event is queued
+//! as a delivery in `_webhook_deliveries`, in the same transaction as the
+//! change it describes where possible, so events aren't lost if the endpoint
+//! is down. The delivery worker sends due deliveries, retrying failures with
+//! exponential backoff, and the table doubles as the delivery log.
+
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParseDocument,
+        ParsedDocument,
+        CREATION_TIME_FIELD_PATH,
+    },
+    maybe_val,
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::Timestamp,
+};
+use database::{
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use keybroker::WebhookSecret;
+use value::{
+    ConvexValue,
+    DeveloperDocumentId,
+    FieldPath,
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::{
+    WebhookDelivery,
+    WebhookDeliveryState,
+    WebhookEndpoint,
+    WebhookEvent,
+    WebhookEventFilter,
+};
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static WEBHOOK_ENDPOINTS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_webhook_endpoints"
+        .parse()
+        .expect("Invalid built-in webhook endpoints table")
+});
+
+pub static WEBHOOK_DELIVERIES_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_webhook_deliveries"
+        .parse()
+        .expect("Invalid built-in webhook deliveries table")
+});
+
+static NAME_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "name".parse().expect("invalid name field"));
+static ENDPOINT_ID_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "endpointId".parse().expect("invalid endpointId field"));
+static STATE_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "state".parse().expect("invalid state field"));
+static NEXT_ATTEMPT_FIELD: LazyLock<FieldPath> = LazyLock::new(|| {
+    "nextAttemptMs"
+        .parse()
+        .expect("invalid nextAttemptMs field")
+});
+
+pub static WEBHOOK_ENDPOINTS_BY_NAME_INDEX: LazyLock<SystemIndex<WebhookEndpointsTable>> =
+    LazyLock::new(|| SystemIndex::new("by_name", [&NAME_FIELD]).unwrap());
+
+/// By state and next attempt. Used to find pending deliveries that are due.
+pub static WEBHOOK_DELIVERIES_BY_STATE_AND_NEXT_ATTEMPT_INDEX: LazyLock<
+    SystemIndex<WebhookDeliveriesTable>,
+> = LazyLock::new(|| {
+    SystemIndex::new(
+        "by_state_and_next_attempt",
+        [&STATE_FIELD, &NEXT_ATTEMPT_FIELD],
+    )
+    .unwrap()
+});
+
+/// By endpoint and creation time. Used to list an endpoint's delivery log.
+pub static WEBHOOK_DELIVERIES_BY_ENDPOINT_INDEX: LazyLock<SystemIndex<WebhookDeliveriesTable>> =
+    LazyLock::new(|| {
+        SystemIndex::new(
+            "by_endpoint",
+            [&ENDPOINT_ID_FIELD, &CREATION_TIME_FIELD_PATH],
+        )
+        .unwrap()
+    });
+
+const MAX_ENDPOINT_NAME_LEN: usize = 64;
+const MAX_ENDPOINTS: usize = 20;
+
+pub struct WebhookEndpointsTable;
+impl SystemTable for WebhookEndpointsTable {
+    type Metadata = WebhookEndpoint;
+
+    fn table_name() -> &'static TableName {
+        &WEBHOOK_ENDPOINTS_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![WEBHOOK_ENDPOINTS_BY_NAME_INDEX.clone()]
+    }
+}
+
+pub struct WebhookDeliveriesTable;
+impl SystemTable for WebhookDeliveriesTable {
+    type Metadata = WebhookDelivery;
+
+    fn table_name() -> &'static TableName {
+        &WEBHOOK_DELIVERIES_TABLE
+    }
+
+    fn indexes() -> Vec<SystemIndex<Self>> {
+        vec![
+            WEBHOOK_DELIVERIES_BY_STATE_AND_NEXT_ATTEMPT_INDEX.clone(),
+            WEBHOOK_DELIVERIES_BY_ENDPOINT_INDEX.clone(),
+        ]
+    }
+}
+
+pub struct WebhookModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> WebhookModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    /// Registers an endpoint with a new signing secret, which is returned so
+    /// it can be shown to the developer. The endpoint is only sent document
+    /// changes committed after this transaction.
+    pub async fn register_endpoint(
+        &mut self,
+        name: String,
+        url: String,
+        filters: Vec<WebhookEventFilter>,
+    ) -> anyhow::Result<(DeveloperDocumentId, WebhookSecret)> {
+        self.tx.require_admin("register_webhook_endpoint")?;
+        validate_endpoint(&name, &url, &filters)?;
+        if self.get_endpoint_by_name(&name).await?.is_some() {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "WebhookEndpointAlreadyExists",
+                format!("A webhook endpoint named {name} already exists"),
+            ));
+        }
+        anyhow::ensure!(
+            self.list_endpoints().await?.len() < MAX_ENDPOINTS,
+            ErrorMetadata::bad_request(
+                "TooManyWebhookEndpoints",
+                format!("Deployments can have at most {MAX_ENDPOINTS} webhook endpoints"),
+            )
+        );
+        let secret = WebhookSecret::random();
+        let endpoint = WebhookEndpoint {
+            name,
+            url,
+            secret: secret.clone(),
+            filters,
+            document_cursor: *self.tx.begin_timestamp(),
+        };
+        let id = SystemMetadataModel::new_global(self.tx)
+            .insert(&WEBHOOK_ENDPOINTS_TABLE, endpoint.try_into()?)
+            .await?;
+        Ok((id.into(), secret))
+    }
+
+    /// Deletes an endpoint. Its pending deliveries fail the next time the
+    /// delivery worker picks them up.
+    pub async fn delete_endpoint(&mut self, name: &str) -> anyhow::Result<()> {
+        self.tx.require_admin("delete_webhook_endpoint")?;
+        let endpoint = self.must_get_endpoint_by_name(name).await?;
+        SystemMetadataModel::new_global(self.tx)
+            .delete(endpoint.id())
+            .await?;
+        Ok(())
+    }
+
+    /// Replaces an endpoint's signing secret, returning the new one.
+    pub async fn rotate_secret(&mut self, name: &str) -> anyhow::Result<WebhookSecret> {
+        self.tx.require_admin("rotate_webhook_secret")?;
+        let (id, mut endpoint) = self
+            .must_get_endpoint_by_name(name)
+            .await?
+            .into_id_and_value();
+        let secret = WebhookSecret::random();
+        endpoint.secret = secret.clone();
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, endpoint.try_into()?)
+            .await?;
+        Ok(secret)
+    }
+
+    pub async fn list_endpoints(&mut self) -> anyhow::Result<Vec<ParsedDocument<WebhookEndpoint>>> {
+        self.tx.require_admin("list_webhook_endpoints")?;
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&WEBHOOK_ENDPOINTS_TABLE) {
+            return Ok(vec![]);
+        }
+        let query = Query::full_table_scan(WEBHOOK_ENDPOINTS_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut endpoints = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            endpoints.push(ParseDocument::<WebhookEndpoint>::parse(doc)?);
+        }
+        Ok(endpoints)
+    }
+
+    pub async fn get_endpoint_by_name(
+        &mut self,
+        name: &str,
+    ) -> anyhow::Result<Option<ParsedDocument<WebhookEndpoint>>> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&WEBHOOK_ENDPOINTS_TABLE) {
+            return Ok(None);
+        }
+        let query = Query::index_range(IndexRange {
+            index_name: WEBHOOK_ENDPOINTS_BY_NAME_INDEX.name(),
+            range: vec![IndexRangeExpression::Eq(
+                NAME_FIELD.clone(),
+                maybe_val!(name.to_string()),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParseDocument::<WebhookEndpoint>::parse)
+            .transpose()
+    }
+
+    async fn must_get_endpoint_by_name(
+        &mut self,
+        name: &str,
+    ) -> anyhow::Result<ParsedDocument<WebhookEndpoint>> {
+        self.get_endpoint_by_name(name).await?.ok_or_else(|| {
+            ErrorMetadata::not_found(
+                "WebhookEndpointNotFound",
+                format!("No webhook endpoint named {name}"),
+            )
+            .into()
+        })
+    }
+
+    pub async fn get_endpoint(
+        &mut self,
+        id: DeveloperDocumentId,
+    ) -> anyhow::Result<Option<ParsedDocument<WebhookEndpoint>>> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&WEBHOOK_ENDPOINTS_TABLE) {
+            return Ok(None);
+        }
+        let Ok(id) = self.tx.resolve_developer_id(&id, TableNamespace::Global) else {
+            return Ok(None);
+        };
+        self.tx
+            .get(id)
+            .await?
+            .map(ParseDocument::<WebhookEndpoint>::parse)
+            .transpose()
+    }
+
+    /// Queues `event` for every endpoint with a matching filter. Returns the
+    /// number of deliveries queued.
+    pub async fn enqueue_event(
+        &mut self,
+        event: &WebhookEvent,
+        now_ms: i64,
+    ) -> anyhow::Result<usize> {
+        let endpoints = self.list_endpoints().await?;
+        let mut queued = 0;
+        for endpoint in endpoints {
+            if endpoint.matches(event) {
+                self.enqueue_for_endpoint(endpoint.id().into(), event, now_ms)
+                    .await?;
+                queued += 1;
+            }
+        }
+        Ok(queued)
+    }
+
+    /// Queues `event` for one endpoint, to be sent right away.
+    pub async fn enqueue_for_endpoint(
+        &mut self,
+        endpoint_id: DeveloperDocumentId,
+        event: &WebhookEvent,
+        now_ms: i64,
+    ) -> anyhow::Result<()> {
+        let event_id = format!("evt_{}", self.tx.runtime().new_uuid_v4().simple());
+        let delivery = WebhookDelivery {
+            endpoint_id,
+            payload: event.payload(&event_id, now_ms).to_string(),
+            event_id,
+            event_type: event.event_type().to_string(),
+            attempts: 0,
+            next_attempt_ms: now_ms,
+            state: WebhookDeliveryState::Pending,
+            last_error: None,
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .insert(&WEBHOOK_DELIVERIES_TABLE, delivery.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    /// Records that an endpoint has been queued all document changes up to
+    /// and including `ts`.
+    pub async fn advance_document_cursor(
+        &mut self,
+        endpoint_id: ResolvedDocumentId,
+        ts: Timestamp,
+    ) -> anyhow::Result<()> {
+        let Some(endpoint) = self.tx.get(endpoint_id).await? else {
+            // Deleted since the change feed read it.
+            return Ok(());
+        };
+        let mut endpoint = ParseDocument::<WebhookEndpoint>::parse(endpoint)?.into_value();
+        if ts <= endpoint.document_cursor {
+            return Ok(());
+        }
+        endpoint.document_cursor = ts;
+        SystemMetadataModel::new_global(self.tx)
+            .replace(endpoint_id, endpoint.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    /// Pending deliveries whose next attempt is due by `now_ms`, oldest
+    /// first.
+    pub async fn list_due(
+        &mut self,
+        now_ms: i64,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ParsedDocument<WebhookDelivery>>> {
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&WEBHOOK_DELIVERIES_TABLE) {
+            return Ok(vec![]);
+        }
+        let query = Query::index_range(IndexRange {
+            index_name: WEBHOOK_DELIVERIES_BY_STATE_AND_NEXT_ATTEMPT_INDEX.name(),
+            range: vec![
+                IndexRangeExpression::Eq(
+                    STATE_FIELD.clone(),
+                    maybe_val!(WebhookDeliveryState::Pending.as_str()),
+                ),
+                IndexRangeExpression::Lte(
+                    NEXT_ATTEMPT_FIELD.clone(),
+                    ConvexValue::Int64(now_ms).into(),
+                ),
+            ],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut due = vec![];
+        while due.len() < limit
+            && let Some(doc) = query_stream.next(self.tx, Some(limit - due.len())).await?
+        {
+            due.push(ParseDocument::<WebhookDelivery>::parse(doc)?);
+        }
+        Ok(due)
+    }
+
+    /// The earliest next attempt of any pending delivery.
+    pub async fn next_due_ms(&mut self) -> anyhow::Result<Option<i64>> {
+        Ok(self
+            .list_due(i64::MAX, 1)
+            .await?
+            .first()
+            .map(|delivery| delivery.next_attempt_ms))
+    }
+
+    /// Stores the outcome of an attempt, unless the delivery has been
+    /// deleted or already settled in the meantime.
+    pub async fn update_delivery(
+        &mut self,
+        id: ResolvedDocumentId,
+        delivery: WebhookDelivery,
+    ) -> anyhow::Result<()> {
+        let Some(existing) = self.tx.get(id).await? else {
+            return Ok(());
+        };
+        let existing = ParseDocument::<WebhookDelivery>::parse(existing)?;
+        if existing.state != WebhookDeliveryState::Pending {
+            return Ok(());
+        }
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, delivery.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    /// Queues a failed delivery to be sent again right away, with its
+    /// attempts reset.
+    pub async fn retry_delivery(
+        &mut self,
+        id: DeveloperDocumentId,
+        now_ms: i64,
+    ) -> anyhow::Result<()> {
+        self.tx.require_admin("retry_webhook_delivery")?;
+        let not_found = || {
+            ErrorMetadata::not_found(
+                "WebhookDeliveryNotFound",
+                format!("No webhook delivery with id {id}"),
+            )
+        };
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&WEBHOOK_DELIVERIES_TABLE) {
+            anyhow::bail!(not_found());
+        }
+        let id = self
+            .tx
+            .resolve_developer_id(&id, TableNamespace::Global)
+            .map_err(|_| not_found())?;
+        if self.tx.table_mapping().tablet_name(id.tablet_id)? != *WEBHOOK_DELIVERIES_TABLE {
+            anyhow::bail!(not_found());
+        }
+        let Some(doc) = self.tx.get(id).await? else {
+            anyhow::bail!(not_found());
+        };
+        let mut delivery = ParseDocument::<WebhookDelivery>::parse(doc)?.into_value();
+        anyhow::ensure!(
+            delivery.state == WebhookDeliveryState::Failed,
+            ErrorMetadata::bad_request(
+                "WebhookDeliveryNotFailed",
+                "Only failed webhook deliveries can be retried",
+            )
+        );
+        delivery.state = WebhookDeliveryState::Pending;
+        delivery.attempts = 0;
+        delivery.next_attempt_ms = now_ms;
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, delivery.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    /// An endpoint's most recent deliveries, newest first.
+    pub async fn list_deliveries(
+        &mut self,
+        endpoint_id: DeveloperDocumentId,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ParsedDocument<WebhookDelivery>>> {
+        self.tx.require_admin("list_webhook_deliveries")?;
+        if !SystemMetadataModel::new_global(self.tx).table_exists(&WEBHOOK_DELIVERIES_TABLE) {
+            return Ok(vec![]);
+        }
+        let query = Query::index_range(IndexRange {
+            index_name: WEBHOOK_DELIVERIES_BY_ENDPOINT_INDEX.name(),
+            range: vec![IndexRangeExpression::Eq(
+                ENDPOINT_ID_FIELD.clone(),
+                maybe_val!(endpoint_id.encode()),
+            )],
+            order: Order::Desc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut deliveries = vec![];
+        while deliveries.len() < limit
+            && let Some(doc) = query_stream
+                .next(self.tx, Some(limit - deliveries.len()))
+                .await?
+        {
+            deliveries.push(ParseDocument::<WebhookDelivery>::parse(doc)?);
+        }
+        Ok(deliveries)
+    }
+}
+
+fn validate_endpoint(name: &str, url: &str, filters: &[WebhookEventFilter]) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        !name.is_empty()
+            && name.len() <= MAX_ENDPOINT_NAME_LEN
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+        ErrorMetadata::bad_request(
+            "InvalidWebhookEndpointName",
+            format!(
+                "Webhook endpoint names must be 1 to {MAX_ENDPOINT_NAME_LEN} letters, digits, \
+                 dashes or underscores"
+            ),
+        )
+    );
+    let valid_url = reqwest::Url::parse(url)
+        .is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.host().is_some());
+    anyhow::ensure!(
+        valid_url,
+        ErrorMetadata::bad_request(
+            "InvalidWebhookUrl",
+            format!("{url} is not a valid http or https URL"),
+        )
+    );
+    anyhow::ensure!(
+        !filters.is_empty(),
+        ErrorMetadata::bad_request(
+            "NoWebhookEventFilters",
+            "Webhook endpoints must subscribe to at least one kind of event",
+        )
+    );
+    for filter in filters {
+        if let WebhookEventFilter::DocumentChanges { table_names } = filter {
+            anyhow::ensure!(
+                !table_names.is_empty() && table_names.iter().all(|name| !name.is_system()),
+                ErrorMetadata::bad_request(
+                    "InvalidWebhookTables",
+                    "Document change filters must list at least one table, and can't include \
+                     system tables",
+                )
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,501 @@
+use common::{
+    components::ComponentPath,
+    types::Timestamp,
+};
+use keybroker::WebhookSecret;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+use value::{
+    codegen_convex_serialization,
+    DeveloperDocumentId,
+    TableName,
+};
+
+/// An HTTP endpoint that's sent the events matching any of its filters.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WebhookEndpoint {
+    pub name: String,
+    pub url: String,
+    /// Signs each request. See `keybroker::WebhookSecret`.
+    pub secret: WebhookSecret,
+    pub filters: Vec<WebhookEventFilter>,
+    /// Document changes committed at or before this timestamp have been
+    /// queued for delivery. Starts at the endpoint's registration, so
+    /// endpoints aren't sent changes from before they existed.
+    pub document_cursor: Timestamp,
+}
+
+impl WebhookEndpoint {
+    pub fn matches(&self, event: &WebhookEvent) -> bool {
+        self.filters.iter().any(|filter| filter.matches(event))
+    }
+
+    /// The root component tables whose document changes are sent to the
+    /// endpoint.
+    pub fn document_tables(&self) -> impl Iterator<Item = &TableName> {
+        self.filters.iter().flat_map(|filter| match filter {
+            WebhookEventFilter::DocumentChanges { table_names } => table_names.as_slice(),
+            _ => &[],
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WebhookEventFilter {
+    /// Inserts, updates and deletes of documents in these tables of the root
+    /// component.
+    DocumentChanges { table_names: Vec<TableName> },
+    /// Queries, mutations and actions that threw an error.
+    FunctionFailures,
+    /// Cron job runs, or only the ones that failed.
+    CronOutcomes { failures_only: bool },
+    /// HTTP checks becoming unhealthy or recovering.
+    HttpCheckAlerts,
+}
+
+impl WebhookEventFilter {
+    pub fn matches(&self, event: &WebhookEvent) -> bool {
+        match (self, event) {
+            (
+                WebhookEventFilter::DocumentChanges { table_names },
+                WebhookEvent::DocumentChanged {
+                    component_path,
+                    table_name,
+                    ..
+                },
+            ) => component_path.is_root() && table_names.contains(table_name),
+            (WebhookEventFilter::FunctionFailures, WebhookEvent::FunctionFailed { .. }) => true,
+            (
+                WebhookEventFilter::CronOutcomes { failures_only },
+                WebhookEvent::CronCompleted { outcome, .. },
+            ) => !failures_only || matches!(outcome, CronOutcome::Failure { .. }),
+            (WebhookEventFilter::HttpCheckAlerts, WebhookEvent::HttpCheckStatusChanged { .. }) => {
+                true
+            },
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, strum::AsRefStr)]
+#[strum(serialize_all = "camelCase")]
+pub enum DocumentChange {
+    Insert,
+    Update,
+    Delete,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CronOutcome {
+    Success,
+    Failure { error: String },
+    Canceled,
+}
+
+/// Something that happened in the deployment that endpoints can subscribe
+/// to.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WebhookEvent {
+    DocumentChanged {
+        component_path: ComponentPath,
+        table_name: TableName,
+        document_id: DeveloperDocumentId,
+        change: DocumentChange,
+        /// The document after the change, exported as clean JSON, or `None`
+        /// for deletes.
+        document: Option<JsonValue>,
+        ts: Timestamp,
+    },
+    FunctionFailed {
+        component_path: ComponentPath,
+        udf_path: String,
+        udf_type: String,
+        error: String,
+        request_id: String,
+    },
+    CronCompleted {
+        component_path: ComponentPath,
+        name: String,
+        udf_path: String,
+        outcome: CronOutcome,
+        ts: Timestamp,
+    },
+    HttpCheckStatusChanged {
+        name: String,
+        url: String,
+        healthy: bool,
+        /// The status of the response that changed the check's health, if
+        /// there was one.
+        status: Option<u16>,
+        error: Option<String>,
+        consecutive_failures: u32,
+    },
+}
+
+impl WebhookEvent {
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            WebhookEvent::DocumentChanged { .. } => "document.changed",
+            WebhookEvent::FunctionFailed { .. } => "function.failed",
+            WebhookEvent::CronCompleted { .. } => "cron.completed",
+            WebhookEvent::HttpCheckStatusChanged { healthy: false, .. } => "http_check.failed",
+            WebhookEvent::HttpCheckStatusChanged { healthy: true, .. } => "http_check.recovered",
+        }
+    }
+
+    /// The request body for the event, which is what's signed.
+    pub fn payload(&self, event_id: &str, timestamp_ms: i64) -> JsonValue {
+        let data = match self {
+            WebhookEvent::DocumentChanged {
+                component_path,
+                table_name,
+                document_id,
+                change,
+                document,
+                ts,
+            } => json!({
+                "componentPath": String::from(component_path.clone()),
+                "table": table_name.to_string(),
+                "id": document_id.encode(),
+                "change": change.as_ref(),
+                "document": document,
+                "ts": i64::from(*ts),
+            }),
+            WebhookEvent::FunctionFailed {
+                component_path,
+                udf_path,
+                udf_type,
+                error,
+                request_id,
+            } => json!({
+                "componentPath": String::from(component_path.clone()),
+                "functionPath": udf_path,
+                "functionType": udf_type,
+                "error": error,
+                "requestId": request_id,
+            }),
+            WebhookEvent::CronCompleted {
+                component_path,
+                name,
+                udf_path,
+                outcome,
+                ts,
+            } => {
+                let (status, error) = match outcome {
+                    CronOutcome::Success => ("success", None),
+                    CronOutcome::Failure { error } => ("failure", Some(error)),
+                    CronOutcome::Canceled => ("canceled", None),
+                };
+                json!({
+                    "componentPath": String::from(component_path.clone()),
+                    "name": name,
+                    "functionPath": udf_path,
+                    "status": status,
+                    "error": error,
+                    "ts": i64::from(*ts),
+                })
+            },
+            WebhookEvent::HttpCheckStatusChanged {
+                name,
+                url,
+                healthy,
+                status,
+                error,
+                consecutive_failures,
+            } => json!({
+                "name": name,
+                "url": url,
+                "healthy": healthy,
+                "status": status,
+                "error": error,
+                "consecutiveFailures": consecutive_failures,
+            }),
+        };
+        json!({
+            "id": event_id,
+            "type": self.event_type(),
+            "timestamp": timestamp_ms,
+            "data": data,
+        })
+    }
+}
+
+/// One event queued for one endpoint. Pending deliveries are due at
+/// `next_attempt_ms`; the delivery worker retries them with exponential
+/// backoff and leaves them `Failed` after too many attempts.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct WebhookDelivery {
+    pub endpoint_id: DeveloperDocumentId,
+    /// Sent as the `webhook-id` header, so receivers can drop duplicates.
+    pub event_id: String,
+    pub event_type: String,
+    /// The exact request body.
+    pub payload: String,
+    pub attempts: u32,
+    pub next_attempt_ms: i64,
+    pub state: WebhookDeliveryState,
+    /// The outcome of the latest attempt, if it failed.
+    pub last_error: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum WebhookDeliveryState {
+    Pending,
+    Delivered { status: u16 },
+    Failed,
+}
+
+impl WebhookDeliveryState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookDeliveryState::Pending => "pending",
+            WebhookDeliveryState::Delivered { .. } => "delivered",
+            WebhookDeliveryState::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SerializedWebhookEventFilter {
+    #[serde(rename_all = "camelCase")]
+    DocumentChanges {
+        table_names: Vec<String>,
+    },
+    FunctionFailures,
+    #[serde(rename_all = "camelCase")]
+    CronOutcomes {
+        failures_only: bool,
+    },
+    HttpCheckAlerts,
+}
+
+impl From<WebhookEventFilter> for SerializedWebhookEventFilter {
+    fn from(filter: WebhookEventFilter) -> Self {
+        match filter {
+            WebhookEventFilter::DocumentChanges { table_names } => Self::DocumentChanges {
+                table_names: table_names.iter().map(|name| name.to_string()).collect(),
+            },
+            WebhookEventFilter::FunctionFailures => Self::FunctionFailures,
+            WebhookEventFilter::CronOutcomes { failures_only } => {
+                Self::CronOutcomes { failures_only }
+            },
+            WebhookEventFilter::HttpCheckAlerts => Self::HttpCheckAlerts,
+        }
+    }
+}
+
+impl TryFrom<SerializedWebhookEventFilter> for WebhookEventFilter {
+    type Error = anyhow::Error;
+
+    fn try_from(filter: SerializedWebhookEventFilter) -> anyhow::Result<Self> {
+        Ok(match filter {
+            SerializedWebhookEventFilter::DocumentChanges { table_names } => {
+                Self::DocumentChanges {
+                    table_names: table_names
+                        .iter()
+                        .map(|name| name.parse())
+                        .collect::<anyhow::Result<_>>()?,
+                }
+            },
+            SerializedWebhookEventFilter::FunctionFailures => Self::FunctionFailures,
+            SerializedWebhookEventFilter::CronOutcomes { failures_only } => {
+                Self::CronOutcomes { failures_only }
+            },
+            SerializedWebhookEventFilter::HttpCheckAlerts => Self::HttpCheckAlerts,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedWebhookEndpoint {
+    name: String,
+    url: String,
+    secret: String,
+    filters: Vec<SerializedWebhookEventFilter>,
+    document_cursor: i64,
+}
+
+impl From<WebhookEndpoint> for SerializedWebhookEndpoint {
+    fn from(endpoint: WebhookEndpoint) -> Self {
+        Self {
+            name: endpoint.name,
+            url: endpoint.url,
+            secret: endpoint.secret.to_string(),
+            filters: endpoint.filters.into_iter().map(Into::into).collect(),
+            document_cursor: endpoint.document_cursor.into(),
+        }
+    }
+}
+
+impl TryFrom<SerializedWebhookEndpoint> for WebhookEndpoint {
+    type Error = anyhow::Error;
+
+    fn try_from(endpoint: SerializedWebhookEndpoint) -> anyhow::Result<Self> {
+        Ok(Self {
+            name: endpoint.name,
+            url: endpoint.url,
+            secret: endpoint.secret.parse()?,
+            filters: endpoint
+                .filters
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<anyhow::Result<_>>()?,
+            document_cursor: endpoint.document_cursor.try_into()?,
+        })
+    }
+}
+
+codegen_convex_serialization!(WebhookEndpoint, SerializedWebhookEndpoint);
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedWebhookDelivery {
+    endpoint_id: String,
+    event_id: String,
+    event_type: String,
+    payload: String,
+    attempts: i64,
+    next_attempt_ms: i64,
+    state: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_error: Option<String>,
+}
+
+impl From<WebhookDelivery> for SerializedWebhookDelivery {
+    fn from(delivery: WebhookDelivery) -> Self {
+        let status = match delivery.state {
+            WebhookDeliveryState::Delivered { status } => Some(status.into()),
+            WebhookDeliveryState::Pending | WebhookDeliveryState::Failed => None,
+        };
+        Self {
+            endpoint_id: delivery.endpoint_id.encode(),
+            event_id: delivery.event_id,
+            event_type: delivery.event_type,
+            payload: delivery.payload,
+            attempts: delivery.attempts.into(),
+            next_attempt_ms: delivery.next_attempt_ms,
+            state: delivery.state.as_str().to_string(),
+            status,
+            last_error: delivery.last_error,
+        }
+    }
+}
+
+impl TryFrom<SerializedWebhookDelivery> for WebhookDelivery {
+    type Error = anyhow::Error;
+
+    fn try_from(delivery: SerializedWebhookDelivery) -> anyhow::Result<Self> {
+        let state = match (delivery.state.as_str(), delivery.status) {
+            ("pending", None) => WebhookDeliveryState::Pending,
+            ("delivered", Some(status)) => WebhookDeliveryState::Delivered {
+                status: status.try_into()?,
+            },
+            ("failed", None) => WebhookDeliveryState::Failed,
+            (state, _) => anyhow::bail!("Invalid webhook delivery state {state}"),
+        };
+        Ok(Self {
+            endpoint_id: DeveloperDocumentId::decode(&delivery.endpoint_id)?,
+            event_id: delivery.event_id,
+            event_type: delivery.event_type,
+            payload: delivery.payload,
+            attempts: delivery.attempts.try_into()?,
+            next_attempt_ms: delivery.next_attempt_ms,
+            state,
+            last_error: delivery.last_error,
+        })
+    }
+}
+
+codegen_convex_serialization!(WebhookDelivery, SerializedWebhookDelivery);
+
+#[cfg(test)]
+mod tests {
+    use cmd_util::env::env_config;
+    use common::{
+        components::ComponentPath,
+        types::Timestamp,
+    };
+    use proptest::prelude::*;
+    use sync_types::testing::assert_roundtrips;
+    use value::{
+        ConvexObject,
+        DeveloperDocumentId,
+    };
+
+    use super::{
+        CronOutcome,
+        DocumentChange,
+        WebhookDelivery,
+        WebhookEvent,
+        WebhookEventFilter,
+    };
+
+    proptest! {
+        #![proptest_config(
+            ProptestConfig { cases: 256 * env_config("CONVEX_PROPTEST_MULTIPLIER", 1), failure_persistence: None, ..ProptestConfig::default() }
+        )]
+
+        #[test]
+        fn test_delivery_roundtrips(v in any::<WebhookDelivery>()) {
+            assert_roundtrips::<WebhookDelivery, ConvexObject>(v);
+        }
+    }
+
+    fn cron_event(outcome: CronOutcome) -> WebhookEvent {
+        WebhookEvent::CronCompleted {
+            component_path: ComponentPath::root(),
+            name: "cleanup".to_string(),
+            udf_path: "crons:cleanup".to_string(),
+            outcome,
+            ts: Timestamp::MIN,
+        }
+    }
+
+    #[test]
+    fn test_filters_match_events() -> anyhow::Result<()> {
+        let document_event = WebhookEvent::DocumentChanged {
+            component_path: ComponentPath::root(),
+            table_name: "messages".parse()?,
+            document_id: DeveloperDocumentId::MIN,
+            change: DocumentChange::Insert,
+            document: None,
+            ts: Timestamp::MIN,
+        };
+        let messages = WebhookEventFilter::DocumentChanges {
+            table_names: vec!["messages".parse()?],
+        };
+        let users = WebhookEventFilter::DocumentChanges {
+            table_names: vec!["users".parse()?],
+        };
+        assert!(messages.matches(&document_event));
+        assert!(!users.matches(&document_event));
+        assert!(!WebhookEventFilter::FunctionFailures.matches(&document_event));
+
+        let all_crons = WebhookEventFilter::CronOutcomes {
+            failures_only: false,
+        };
+        let failed_crons = WebhookEventFilter::CronOutcomes {
+            failures_only: true,
+        };
+        let failure = cron_event(CronOutcome::Failure {
+            error: "Uncaught Error".to_string(),
+        });
+        assert!(all_crons.matches(&cron_event(CronOutcome::Success)));
+        assert!(!failed_crons.matches(&cron_event(CronOutcome::Success)));
+        assert!(failed_crons.matches(&failure));
+        assert_eq!(failure.payload("evt_1", 0)["data"]["status"], "failure");
+        Ok(())
+    }
+}
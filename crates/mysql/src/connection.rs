@@ -481,7 +481,8 @@ impl<RT: Runtime> ConvexMySqlPool<RT> {
             pool: Pool::new(opts),
             use_prepared_statements,
             runtime,
-            stats: new_connection_pool_stats(cluster_name.as_str()),
+            stats: new_connection_pool_stats(cluster_name.as_str())
+                .with_capacity(*MYSQL_MAX_CONNECTIONS),
             cluster_name,
         })
     }
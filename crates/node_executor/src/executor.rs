@@ -1,5 +1,8 @@
 use std::{
-    collections::BTreeMap,
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
     future::Future,
     path::Path,
     sync::{
@@ -184,13 +187,9 @@ fn construct_js_error(
                 None => false,
             });
             JsError::from_frames(message, frames, custom_data, |specifier| {
-                if !specifier.as_str().starts_with("convex:/") {
-                    return Ok(None);
-                }
-                let Some(path) = specifier.path().strip_prefix("/user/") else {
+                let Some(module_path) = user_module_path(specifier.as_str()) else {
                     return Ok(None);
                 };
-                let module_path = path.parse()?;
                 let Some(source_map) = source_maps.get(&module_path) else {
                     return Ok(None);
                 };
@@ -202,6 +201,11 @@ fn construct_js_error(
     Ok(error)
 }
 
+/// The path of the user module a frame's `file_name` points into, if any.
+fn user_module_path(file_name: &str) -> Option<CanonicalizedModulePath> {
+    file_name.strip_prefix("convex:/user/")?.parse().ok()
+}
+
 impl<RT: Runtime> Actions<RT> {
     pub fn new(
         executor: Arc<dyn NodeExecutor>,
@@ -225,15 +229,18 @@ impl<RT: Runtime> Actions<RT> {
         self.executor.shutdown()
     }
 
+    /// On an error, `source_maps_callback` is called with the modules its
+    /// frames point into to get their source maps.
     #[rustfmt::skip]
-    pub async fn execute(
+    pub async fn execute<F>(
         &self,
         request: ExecuteRequest,
         log_line_sender: mpsc::UnboundedSender<LogLine>,
-        source_maps_callback: impl Future<Output = anyhow::Result<
-            BTreeMap<CanonicalizedModulePath, SourceMap>>>
-            + Send,
-    ) -> anyhow::Result<NodeActionOutcome> {
+        source_maps_callback: impl FnOnce(BTreeSet<CanonicalizedModulePath>) -> F + Send,
+    ) -> anyhow::Result<NodeActionOutcome>
+    where
+        F: Future<Output = anyhow::Result<BTreeMap<CanonicalizedModulePath, SourceMap>>> + Send,
+    {
         let path = request.path_and_args.path().clone();
         anyhow::ensure!(request.path_and_args.args_size() < NODE_ACTIONS_ARGS_SIZE_LIMIT, ErrorMetadata::bad_request("ArgsTooLarge", format!("Node actions arguments size is too large: {} bytes. The maximum size is {} bytes.", request.path_and_args.args_size(), NODE_ACTIONS_ARGS_SIZE_LIMIT)));
         let timer = node_executor("execute");
@@ -303,7 +310,12 @@ impl<RT: Runtime> Actions<RT> {
                 frames,
                 ..
             } => {
-                let source_maps = source_maps_callback.await?;
+                let module_paths = frames
+                    .iter()
+                    .flatten()
+                    .filter_map(|frame| user_module_path(frame.file_name.as_deref()?))
+                    .collect();
+                let source_maps = source_maps_callback(module_paths).await?;
                 let error = construct_js_error(message, name, data, frames, &source_maps)?;
                 Err(error)
             },
@@ -1014,3 +1026,48 @@ pub async fn handle_node_executor_stream(
         .ok_or_else(|| anyhow::anyhow!("Received no result from lambda response"))?;
     Ok(Ok(payload))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use common::errors::FrameData;
+
+    use super::{
+        construct_js_error,
+        user_module_path,
+    };
+
+    #[test]
+    fn test_deps_chunk_frame_symbolicated() -> anyhow::Result<()> {
+        let file_name = "convex:/user/_deps/node/chunk-ABC123.js";
+        let chunk_path = user_module_path(file_name).unwrap();
+        assert_eq!(chunk_path.as_str(), "_deps/node/chunk-ABC123.js");
+        // Maps each line of the chunk to the same line of `helpers.ts`.
+        let source_map = r#"{
+            "version": 3,
+            "sources": ["../convex/helpers.ts"],
+            "names": [],
+            "mappings": "AAAA;AACA;AACA"
+        }"#;
+        let source_maps = BTreeMap::from([(chunk_path, source_map.to_string())]);
+        let frames = vec![FrameData {
+            file_name: Some(file_name.to_string()),
+            line_number: Some(2),
+            column_number: Some(4),
+            ..Default::default()
+        }];
+        let error = construct_js_error(
+            "Oh, no!".to_string(),
+            "Error".to_string(),
+            None,
+            Some(frames),
+            &source_maps,
+        )?;
+        let frames = &error.frames.as_ref().unwrap().0;
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].file_name.as_deref(), Some("../convex/helpers.ts"));
+        assert_eq!(frames[0].line_number, Some(2));
+        Ok(())
+    }
+}
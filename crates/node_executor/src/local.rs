@@ -271,7 +271,10 @@ impl NodeExecutor for LocalNodeExecutor {
 #[cfg(test)]
 mod tests {
     use std::{
-        collections::BTreeMap,
+        collections::{
+            BTreeMap,
+            BTreeSet,
+        },
         future::Future,
         sync::{
             Arc,
@@ -402,13 +405,14 @@ mod tests {
     }
 
     #[rustfmt::skip]
-    async fn execute<RT: Runtime>(
+    async fn execute<RT: Runtime, F>(
         actions: &Actions<RT>,
         execute_request: ExecuteRequest,
-        source_maps_callback: impl Future<Output = anyhow::Result<
-            BTreeMap<CanonicalizedModulePath, SourceMap>>>
-            + Send,
-    ) -> anyhow::Result<(NodeActionOutcome, LogLines)> {
+        source_maps_callback: impl FnOnce(BTreeSet<CanonicalizedModulePath>) -> F + Send,
+    ) -> anyhow::Result<(NodeActionOutcome, LogLines)>
+    where
+        F: Future<Output = anyhow::Result<BTreeMap<CanonicalizedModulePath, SourceMap>>> + Send,
+    {
         let (log_line_sender, log_line_receiver) = mpsc::unbounded_channel();
         let execute_future = Box::pin(
             actions
@@ -437,6 +441,7 @@ mod tests {
     }
 
     async fn empty_source_maps_callback(
+        _module_paths: BTreeSet<CanonicalizedModulePath>,
     ) -> anyhow::Result<BTreeMap<CanonicalizedModulePath, SourceMap>> {
         Ok(BTreeMap::new())
     }
@@ -457,7 +462,7 @@ mod tests {
         let (response, _log_lines) = execute(
             &actions,
             execute_request(path_and_args, source_package),
-            empty_source_maps_callback(),
+            empty_source_maps_callback,
         )
         .await?;
 
@@ -479,7 +484,7 @@ mod tests {
         let (response, log_lines) = execute(
             &actions,
             execute_request(path_and_args, source_package),
-            empty_source_maps_callback(),
+            empty_source_maps_callback,
         )
         .await?;
 
@@ -518,7 +523,7 @@ mod tests {
                 context: ExecutionContext::new_for_test(),
                 encoded_parent_trace: None,
             },
-            empty_source_maps_callback(),
+            empty_source_maps_callback,
         )
         .await?;
 
@@ -554,7 +559,7 @@ mod tests {
                 ),
                 source_package,
             ),
-            empty_source_maps_callback(),
+            empty_source_maps_callback,
         )
         .await?;
 
@@ -589,7 +594,7 @@ mod tests {
                 ),
                 source_package,
             ),
-            empty_source_maps_callback(),
+            empty_source_maps_callback,
         )
         .await?;
 
@@ -616,7 +621,7 @@ mod tests {
                 )
             })
             .collect();
-        let source_maps_callback = async { Ok(source_maps) };
+        let source_maps_callback = |_| async { Ok(source_maps) };
         let path_and_args = ValidatedPathAndArgs::new_for_tests(
             "node_actions.js:logAndThrowError".parse()?,
             array![],
@@ -662,7 +667,7 @@ mod tests {
                 )
             })
             .collect();
-        let source_maps_callback = async { Ok(source_maps) };
+        let source_maps_callback = |_| async { Ok(source_maps) };
         let path_and_args = ValidatedPathAndArgs::new_for_tests(
             "node_actions.js:forgotAwait".parse()?,
             array![],
@@ -705,7 +710,7 @@ mod tests {
         let (response, _log_lines) = execute(
             &actions,
             execute_request(path_and_args, source_package),
-            empty_source_maps_callback(),
+            empty_source_maps_callback,
         )
         .await?;
 
@@ -742,7 +747,7 @@ mod tests {
                 context: ExecutionContext::new_for_test(),
                 encoded_parent_trace: None,
             },
-            empty_source_maps_callback(),
+            empty_source_maps_callback,
         )
         .await?;
         assert_eq!(response.result?, ConvexValue::try_from("TEST_VALUE")?);
@@ -763,7 +768,7 @@ mod tests {
         let (response, log_lines) = execute(
             &actions,
             execute_request(path_and_args, source_package),
-            empty_source_maps_callback(),
+            empty_source_maps_callback,
         )
         .await?;
         // This should be hitting the user timeout in executor.ts, not the Node
@@ -797,7 +802,7 @@ mod tests {
         let err = execute(
             &actions,
             execute_request(path_and_args, source_package),
-            empty_source_maps_callback(),
+            empty_source_maps_callback,
         )
         .await
         .unwrap_err();
@@ -818,7 +823,7 @@ mod tests {
         let (response, _log_lines) = execute(
             &actions,
             execute_request(path_and_args, source_package),
-            empty_source_maps_callback(),
+            empty_source_maps_callback,
         )
         .await?;
         // Since this is a busy loop, we should be hitting the process timeout.
@@ -844,7 +849,7 @@ mod tests {
         let (response, _log_lines) = execute(
             &actions,
             execute_request(path_and_args, source_package),
-            empty_source_maps_callback(),
+            empty_source_maps_callback,
         )
         .await?;
         assert_eq!(
@@ -1116,7 +1121,7 @@ export const test = {
                 ),
                 source_package.clone(),
             ),
-            empty_source_maps_callback(),
+            empty_source_maps_callback,
         )
         .await?;
         let syscall_trace = response.syscall_trace;
@@ -1139,7 +1144,7 @@ export const test = {
                 ),
                 source_package,
             ),
-            empty_source_maps_callback(),
+            empty_source_maps_callback,
         )
         .await?;
         let syscall_trace = response.syscall_trace;
@@ -71,6 +71,16 @@ where
                 map.serialize_entry("$bytes", &out[..])?;
                 map.end()?
             },
+            OpenedValue::Decimal(d) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("$decimal", &d.to_string())?;
+                map.end()?
+            },
+            OpenedValue::DateTime(d) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("$datetime", &d.to_string())?;
+                map.end()?
+            },
             OpenedValue::Array(ref values) => {
                 let mut seq = serializer.serialize_seq(Some(values.len()))?;
                 for value_r in values.iter() {
@@ -22,8 +22,10 @@ use flexbuffers::{
 use value::{
     heap_size::HeapSize,
     serde::ConvexSerializable,
+    ConvexDateTime,
     ConvexObject,
     ConvexValue,
+    Decimal128,
     FieldPath,
 };
 
@@ -162,6 +164,16 @@ impl PackedValue<ByteBuffer> {
             ConvexValue::Bytes(b) => {
                 builder.push(Blob(&b[..]));
             },
+            ConvexValue::Decimal(d) => {
+                let mut map = builder.start_map();
+                map.push("$decimal", &d.to_string()[..]);
+                map.end_map();
+            },
+            ConvexValue::DateTime(d) => {
+                let mut map = builder.start_map();
+                map.push("$datetime", &d.to_string()[..]);
+                map.end_map();
+            },
             ConvexValue::Array(ref values) => {
                 let mut vector = builder.start_vector();
                 for value in values {
@@ -214,6 +226,8 @@ where
     Boolean(bool),
     String(OpenedString<B>),
     Bytes(OpenedBytes<B>),
+    Decimal(Decimal128),
+    DateTime(ConvexDateTime),
     Array(OpenedArray<B>),
     Set(OpenedSet<B>),
     Map(OpenedMap<B>),
@@ -232,6 +246,8 @@ where
             OpenedValue::Boolean(b) => OpenedValue::Boolean(*b),
             OpenedValue::String(ref s) => OpenedValue::String(s.clone()),
             OpenedValue::Bytes(ref b) => OpenedValue::Bytes(b.clone()),
+            OpenedValue::Decimal(d) => OpenedValue::Decimal(*d),
+            OpenedValue::DateTime(d) => OpenedValue::DateTime(*d),
             OpenedValue::Array(ref a) => OpenedValue::Array(a.clone()),
             OpenedValue::Set(ref s) => OpenedValue::Set(s.clone()),
             OpenedValue::Map(ref m) => OpenedValue::Map(m.clone()),
@@ -271,6 +287,12 @@ where
                     let reader = reader.index(ix)?.get_vector()?;
                     anyhow::ensure!(reader.len() % 2 == 0);
                     OpenedValue::Map(OpenedMap { reader })
+                } else if let Some(ix) = reader.index_key("$decimal") {
+                    anyhow::ensure!(reader.len() == 1);
+                    OpenedValue::Decimal(reader.index(ix)?.get_str()?.parse()?)
+                } else if let Some(ix) = reader.index_key("$datetime") {
+                    anyhow::ensure!(reader.len() == 1);
+                    OpenedValue::DateTime(reader.index(ix)?.get_str()?.parse()?)
                 } else {
                     OpenedValue::Object(OpenedObject { reader })
                 }
@@ -532,6 +554,8 @@ where
             OpenedValue::Boolean(b) => Self::from(b),
             OpenedValue::String(s) => Self::try_from(s[..].to_owned())?,
             OpenedValue::Bytes(b) => Self::try_from(b[..].to_owned())?,
+            OpenedValue::Decimal(d) => Self::Decimal(d),
+            OpenedValue::DateTime(d) => Self::DateTime(d),
             OpenedValue::Array(packed_values) => {
                 let values = packed_values
                     .iter()
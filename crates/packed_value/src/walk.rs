@@ -41,6 +41,8 @@ where
             OpenedValue::Boolean(v) => ConvexValueType::Boolean(v),
             OpenedValue::String(string) => ConvexValueType::String(string),
             OpenedValue::Bytes(bytes) => ConvexValueType::Bytes(bytes),
+            OpenedValue::Decimal(d) => ConvexValueType::Decimal(d),
+            OpenedValue::DateTime(d) => ConvexValueType::DateTime(d),
             OpenedValue::Array(array) => ConvexValueType::Array(array),
             OpenedValue::Set(set) => ConvexValueType::Set(set),
             OpenedValue::Map(map) => ConvexValueType::Map(map),
@@ -382,7 +382,7 @@ impl ConvexPgPool {
             tls_connect,
             semaphore: Semaphore::new(max_size),
             connections: Mutex::new(VecDeque::new()),
-            stats: new_connection_pool_stats(""),
+            stats: new_connection_pool_stats("").with_capacity(max_size),
             idle_worker,
         });
         _ = this_tx.send(Arc::downgrade(&this));
@@ -3,6 +3,7 @@
 #![feature(binary_heap_drain_sorted)]
 #![feature(never_type)]
 pub mod prod;
+pub mod simulated_clock;
 
 #[cfg(any(test, feature = "testing"))]
 pub use ::common::runtime::testing;
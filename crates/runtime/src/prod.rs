@@ -52,6 +52,8 @@ use tokio::{
 };
 use tokio_metrics_collector::TaskMonitor;
 
+use crate::simulated_clock::SimulatedClock;
+
 static INSTANT_EPOCH: LazyLock<Instant> = LazyLock::new(Instant::now);
 
 pub struct ThreadHandle {
@@ -145,6 +147,9 @@ impl ThreadHandle {
 #[derive(Clone)]
 pub struct ProdRuntime {
     rt: TokioRuntimeHandle,
+    /// Replaces the wall clock for `system_time` and `wait_for_system_time`
+    /// when set. Only development deployments opt into this.
+    clock: Option<SimulatedClock>,
 }
 
 impl ProdRuntime {
@@ -189,7 +194,24 @@ impl ProdRuntime {
     pub fn new(tokio_rt: &TokioRuntime) -> Self {
         let handle = tokio_rt.handle().clone();
 
-        Self { rt: handle }
+        Self {
+            rt: handle,
+            clock: None,
+        }
+    }
+
+    /// Drive `system_time` and `wait_for_system_time` from a
+    /// [`SimulatedClock`] that can be frozen and advanced at runtime. `wait`
+    /// keeps using real time.
+    pub fn with_simulated_clock(self) -> Self {
+        Self {
+            clock: Some(SimulatedClock::new()),
+            ..self
+        }
+    }
+
+    pub fn simulated_clock(&self) -> Option<&SimulatedClock> {
+        self.clock.as_ref()
     }
 
     pub fn block_on<F: Future>(&self, name: &'static str, f: F) -> F::Output {
@@ -204,6 +226,16 @@ impl Runtime for ProdRuntime {
         Box::pin(sleep(duration).fuse())
     }
 
+    fn wait_for_system_time(
+        &self,
+        duration: Duration,
+    ) -> Pin<Box<dyn FusedFuture<Output = ()> + Send + 'static>> {
+        if let Some(clock) = &self.clock {
+            return clock.wait(duration);
+        }
+        self.wait(duration)
+    }
+
     fn spawn(
         &self,
         name: &'static str,
@@ -227,6 +259,9 @@ impl Runtime for ProdRuntime {
     }
 
     fn system_time(&self) -> SystemTime {
+        if let Some(clock) = &self.clock {
+            return clock.now();
+        }
         SystemTime::now()
     }
 
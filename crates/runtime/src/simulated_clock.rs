@@ -0,0 +1,180 @@
+//! A controllable wall clock for development deployments.
+//!
+//! When a [`ProdRuntime`](crate::prod::ProdRuntime) has a [`SimulatedClock`],
+//! `system_time()` and `wait_for_system_time()` follow it instead of the real
+//! clock. That lets developers freeze time or jump it forward and watch the
+//! scheduler and crons run the jobs that are now due, without waiting in real
+//! time. Workers that poll, like the cleanup of expired rows, see the new time
+//! on their next pass.
+//!
+//! Simulated time never moves backwards: resuming a frozen clock continues
+//! from the frozen instant, and advancing only adds to the current time.
+//! `wait()` and `monotonic_now()` are unaffected, so function timeouts,
+//! heartbeats, and rate limits keep using real time.
+
+use std::{
+    pin::Pin,
+    sync::Arc,
+    time::{
+        Duration,
+        Instant,
+        SystemTime,
+    },
+};
+
+use futures::{
+    future::FusedFuture,
+    FutureExt,
+};
+use parking_lot::Mutex;
+use tokio::{
+    sync::watch,
+    time::sleep,
+};
+
+#[derive(Clone)]
+pub struct SimulatedClock {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    state: Mutex<ClockState>,
+    // Bumped whenever the clock is frozen, resumed, or advanced so pending
+    // `wait`s can recompute their deadlines.
+    changed: watch::Sender<()>,
+}
+
+struct ClockState {
+    /// Simulated time at `anchor_real`.
+    anchor_sim: SystemTime,
+    anchor_real: Instant,
+    frozen: bool,
+}
+
+impl ClockState {
+    fn now(&self) -> SystemTime {
+        if self.frozen {
+            self.anchor_sim
+        } else {
+            self.anchor_sim + self.anchor_real.elapsed()
+        }
+    }
+
+    fn reanchor(&mut self, now: SystemTime) {
+        self.anchor_sim = now;
+        self.anchor_real = Instant::now();
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SimulatedClockStatus {
+    pub now: SystemTime,
+    pub frozen: bool,
+}
+
+impl SimulatedClock {
+    /// Start a clock that matches real time and runs until frozen.
+    pub fn new() -> Self {
+        let (changed, _) = watch::channel(());
+        Self {
+            inner: Arc::new(Inner {
+                state: Mutex::new(ClockState {
+                    anchor_sim: SystemTime::now(),
+                    anchor_real: Instant::now(),
+                    frozen: false,
+                }),
+                changed,
+            }),
+        }
+    }
+
+    pub fn now(&self) -> SystemTime {
+        self.inner.state.lock().now()
+    }
+
+    pub fn status(&self) -> SimulatedClockStatus {
+        let state = self.inner.state.lock();
+        SimulatedClockStatus {
+            now: state.now(),
+            frozen: state.frozen,
+        }
+    }
+
+    /// Stop the clock at the current instant.
+    pub fn freeze(&self) -> SimulatedClockStatus {
+        self.update(|state| {
+            let now = state.now();
+            state.reanchor(now);
+            state.frozen = true;
+        })
+    }
+
+    /// Let the clock run again from wherever it was frozen.
+    pub fn resume(&self) -> SimulatedClockStatus {
+        self.update(|state| {
+            let now = state.now();
+            state.reanchor(now);
+            state.frozen = false;
+        })
+    }
+
+    /// Jump the clock forward by `duration`. A frozen clock stays frozen at
+    /// the new time.
+    pub fn advance(&self, duration: Duration) -> SimulatedClockStatus {
+        self.update(|state| {
+            let now = state.now() + duration;
+            state.reanchor(now);
+        })
+    }
+
+    fn update(&self, f: impl FnOnce(&mut ClockState)) -> SimulatedClockStatus {
+        let status = {
+            let mut state = self.inner.state.lock();
+            f(&mut state);
+            SimulatedClockStatus {
+                now: state.now(),
+                frozen: state.frozen,
+            }
+        };
+        self.inner.changed.send_replace(());
+        status
+    }
+
+    /// Resolve once `duration` of simulated time has passed.
+    pub fn wait(&self, duration: Duration) -> Pin<Box<dyn FusedFuture<Output = ()> + Send>> {
+        let inner = self.inner.clone();
+        // Subscribe before reading the deadline so no change is missed.
+        let mut changed = inner.changed.subscribe();
+        let deadline = inner.state.lock().now() + duration;
+        let wait = async move {
+            loop {
+                let (remaining, frozen) = {
+                    let state = inner.state.lock();
+                    let remaining = deadline
+                        .duration_since(state.now())
+                        .unwrap_or(Duration::ZERO);
+                    (remaining, state.frozen)
+                };
+                if remaining.is_zero() {
+                    return;
+                }
+                if frozen {
+                    // `inner` owns the sender, so this can't fail.
+                    let _ = changed.changed().await;
+                } else {
+                    tokio::select! {
+                        _ = sleep(remaining) => {},
+                        _ = changed.changed() => {},
+                    }
+                }
+            }
+        };
+        Box::pin(wait.fuse())
+    }
+}
+
+impl Default for SimulatedClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -25,7 +25,9 @@ use common::{
     query::{
         InternalSearch,
         InternalSearchFilterExpression,
+        SearchSynonyms,
         SearchVersion,
+        TextSearchOptions,
     },
     types::{
         IndexName,
@@ -92,10 +94,7 @@ impl Dataset {
         };
         let index_name: IndexName = "messages.by_body".parse()?;
         let Ok(index_name) = index_name.map_table(&|_| Ok::<_, !>(table_id.tablet_id));
-        let config = DeveloperTextIndexConfig {
-            search_field: "body".parse()?,
-            filter_fields: BTreeSet::new(),
-        };
+        let config = DeveloperTextIndexConfig::new("body".parse()?, BTreeSet::new());
 
         let schema = TantivySearchIndexSchema::new(&config);
         let datasets = ["tweets", "wikipedia", "gutenberg"];
@@ -137,7 +136,10 @@ impl Dataset {
                 filters: vec![InternalSearchFilterExpression::Search(
                     "body".parse()?,
                     q.query,
+                    TextSearchOptions::default(),
                 )],
+                synonyms: SearchSynonyms::default(),
+                search_after: None,
             };
             let (compiled_query, _) = schema.compile(&internal_search, SearchVersion::V1)?;
             compiled.insert(q.name, compiled_query);
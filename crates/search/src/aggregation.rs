@@ -7,6 +7,7 @@ use std::{
     },
 };
 
+use common::query::SearchAfter;
 use tantivy::Term;
 
 use crate::searcher::{
@@ -17,21 +18,29 @@ use crate::searcher::{
 // Aggregate the top `max_results` posting list matches, sorted by BM25 score,
 // creation time, and internal ID in descending order. This is implemented
 // using a min-heap so we can efficiently pop the worst match when adding a new
-// candidate.
+// candidate. Matches that don't rank after `search_after` are skipped without
+// telling the caller to stop.
 pub struct PostingListMatchAggregator {
     max_results: usize,
+    search_after: Option<SearchAfter>,
     matches: BinaryHeap<Reverse<PostingListMatch>>,
 }
 
 impl PostingListMatchAggregator {
-    pub fn new(max_results: usize) -> Self {
+    pub fn new(max_results: usize, search_after: Option<SearchAfter>) -> Self {
         Self {
             max_results,
+            search_after,
             matches: BinaryHeap::with_capacity(max_results),
         }
     }
 
     pub fn insert(&mut self, m: PostingListMatch) -> bool {
+        if let Some(search_after) = &self.search_after
+            && !search_after.admits(m.bm25_score, f64::from(m.creation_time), &m.internal_id.0)
+        {
+            return true;
+        }
         let candidate = Reverse(m);
         if self.matches.len() >= self.max_results {
             assert_eq!(self.matches.len(), self.max_results);
@@ -0,0 +1,318 @@
+use common::bootstrap_model::index::text_index::{
+    AutocompleteConfig,
+    TextAnalyzerConfig,
+    TextLanguage,
+    TextSegmentation,
+};
+use tantivy::tokenizer::{
+    BoxTokenStream,
+    Language,
+    LowerCaser,
+    RemoveLongFilter,
+    SimpleTokenizer,
+    Stemmer,
+    StopWordFilter,
+    TextAnalyzer,
+    Token,
+    TokenStream,
+    Tokenizer,
+};
+
+use crate::constants::MAX_TEXT_TERM_LENGTH;
+
+/// Builds the analyzer for a text index. The default config builds the same
+/// analyzer as `convex_en`.
+pub fn text_analyzer(config: &TextAnalyzerConfig) -> TextAnalyzer {
+    let mut analyzer = match config.segmentation {
+        TextSegmentation::Words => TextAnalyzer::from(SimpleTokenizer),
+        TextSegmentation::CjkBigrams => TextAnalyzer::from(CjkBigramTokenizer),
+    }
+    .filter(RemoveLongFilter::limit(MAX_TEXT_TERM_LENGTH))
+    .filter(LowerCaser);
+    if !config.stopwords.is_empty() {
+        analyzer = analyzer.filter(StopWordFilter::remove(
+            config.stopwords.iter().cloned().collect(),
+        ));
+    }
+    if config.stemming {
+        analyzer = analyzer.filter(Stemmer::new(stemmer_language(config.language)));
+    }
+    analyzer
+}
+
+/// Builds the analyzer that splits documents into the terms a text index
+/// stores. This is `text_analyzer` plus, for autocomplete indexes, the
+/// prefixes of each word.
+pub fn index_text_analyzer(config: &TextAnalyzerConfig) -> TextAnalyzer {
+    let analyzer = text_analyzer(config);
+    match config.autocomplete {
+        None => analyzer,
+        Some(autocomplete) => TextAnalyzer::from(EdgeNgramTokenizer {
+            analyzer,
+            autocomplete,
+        }),
+    }
+}
+
+fn stemmer_language(language: TextLanguage) -> Language {
+    match language {
+        TextLanguage::Arabic => Language::Arabic,
+        TextLanguage::Danish => Language::Danish,
+        TextLanguage::Dutch => Language::Dutch,
+        TextLanguage::English => Language::English,
+        TextLanguage::Finnish => Language::Finnish,
+        TextLanguage::French => Language::French,
+        TextLanguage::German => Language::German,
+        TextLanguage::Greek => Language::Greek,
+        TextLanguage::Hungarian => Language::Hungarian,
+        TextLanguage::Italian => Language::Italian,
+        TextLanguage::Norwegian => Language::Norwegian,
+        TextLanguage::Portuguese => Language::Portuguese,
+        TextLanguage::Romanian => Language::Romanian,
+        TextLanguage::Russian => Language::Russian,
+        TextLanguage::Spanish => Language::Spanish,
+        TextLanguage::Swedish => Language::Swedish,
+        TextLanguage::Tamil => Language::Tamil,
+        TextLanguage::Turkish => Language::Turkish,
+    }
+}
+
+/// Splits text like `SimpleTokenizer`, except that runs of CJK characters,
+/// which don't separate words with spaces, become overlapping bigrams. A lone
+/// CJK character becomes a single token.
+#[derive(Clone)]
+pub struct CjkBigramTokenizer;
+
+impl Tokenizer for CjkBigramTokenizer {
+    fn token_stream<'a>(&self, text: &'a str) -> BoxTokenStream<'a> {
+        let tokens = cjk_bigram_tokens(text);
+        BoxTokenStream::from(VecTokenStream {
+            tokens: tokens.into_iter(),
+            token: Token::default(),
+        })
+    }
+}
+
+fn cjk_bigram_tokens(text: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut push = |from: usize, to: usize| {
+        tokens.push(Token {
+            offset_from: from,
+            offset_to: to,
+            position: tokens.len(),
+            text: text[from..to].to_string(),
+            position_length: 1,
+        })
+    };
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if is_cjk(c) {
+            let mut boundaries = vec![start];
+            while let Some(&(i, c)) = chars.peek()
+                && is_cjk(c)
+            {
+                boundaries.push(i);
+                chars.next();
+            }
+            boundaries.push(chars.peek().map_or(text.len(), |&(i, _)| i));
+            if boundaries.len() == 2 {
+                push(boundaries[0], boundaries[1]);
+            }
+            for window in boundaries.windows(3) {
+                push(window[0], window[2]);
+            }
+        } else if c.is_alphanumeric() {
+            let mut end = start + c.len_utf8();
+            while let Some(&(i, c)) = chars.peek()
+                && c.is_alphanumeric()
+                && !is_cjk(c)
+            {
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            push(start, end);
+        }
+    }
+    tokens
+}
+
+/// Emits each token of `analyzer` along with its prefixes of between
+/// `min_gram` and `max_gram` characters, all at the token's position so
+/// phrases still match.
+#[derive(Clone)]
+struct EdgeNgramTokenizer {
+    analyzer: TextAnalyzer,
+    autocomplete: AutocompleteConfig,
+}
+
+impl Tokenizer for EdgeNgramTokenizer {
+    fn token_stream<'a>(&self, text: &'a str) -> BoxTokenStream<'a> {
+        let mut tokens = vec![];
+        let mut token_stream = self.analyzer.token_stream(text);
+        while let Some(token) = token_stream.next() {
+            let prefix_ends = token
+                .text
+                .char_indices()
+                .map(|(i, c)| i + c.len_utf8())
+                .skip(self.autocomplete.min_gram as usize - 1)
+                .take(self.autocomplete.max_gram as usize - self.autocomplete.min_gram as usize + 1)
+                .filter(|&end| end < token.text.len());
+            for end in prefix_ends {
+                tokens.push(Token {
+                    text: token.text[..end].to_string(),
+                    ..token.clone()
+                });
+            }
+            tokens.push(token.clone());
+        }
+        BoxTokenStream::from(VecTokenStream {
+            tokens: tokens.into_iter(),
+            token: Token::default(),
+        })
+    }
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{1100}'..='\u{11FF}'     // Hangul Jamo
+        | '\u{2E80}'..='\u{2FDF}'   // CJK radicals
+        | '\u{3040}'..='\u{30FF}'   // Hiragana and Katakana
+        | '\u{3100}'..='\u{31FF}'   // Bopomofo, Hangul compatibility Jamo, etc.
+        | '\u{3400}'..='\u{4DBF}'   // CJK unified ideographs extension A
+        | '\u{4E00}'..='\u{9FFF}'   // CJK unified ideographs
+        | '\u{AC00}'..='\u{D7AF}'   // Hangul syllables
+        | '\u{F900}'..='\u{FAFF}'   // CJK compatibility ideographs
+        | '\u{FF66}'..='\u{FF9F}'   // Halfwidth Katakana
+        | '\u{20000}'..='\u{2FA1F}' // CJK unified ideographs extensions B-F
+    )
+}
+
+struct VecTokenStream {
+    tokens: std::vec::IntoIter<Token>,
+    token: Token,
+}
+
+impl TokenStream for VecTokenStream {
+    fn advance(&mut self) -> bool {
+        let Some(token) = self.tokens.next() else {
+            return false;
+        };
+        self.token = token;
+        true
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use common::bootstrap_model::index::text_index::{
+        AutocompleteConfig,
+        TextAnalyzerConfig,
+        TextLanguage,
+        TextSegmentation,
+    };
+
+    use super::{
+        index_text_analyzer,
+        text_analyzer,
+    };
+    use crate::convex_en;
+
+    fn tokens(config: &TextAnalyzerConfig, text: &str) -> Vec<(String, usize)> {
+        let analyzer = text_analyzer(config);
+        let mut token_stream = analyzer.token_stream(text);
+        let mut tokens = vec![];
+        while let Some(token) = token_stream.next() {
+            tokens.push((token.text.clone(), token.position));
+        }
+        tokens
+    }
+
+    #[test]
+    fn test_default_matches_convex_en() {
+        let text = "The QUICK brown-fox jumped over 2 lazy dogs. Ünïcödé";
+        let mut token_stream = convex_en().token_stream(text);
+        let mut expected = vec![];
+        while let Some(token) = token_stream.next() {
+            expected.push((token.text.clone(), token.position));
+        }
+        assert_eq!(tokens(&TextAnalyzerConfig::default(), text), expected);
+    }
+
+    #[test]
+    fn test_stemming_and_stopwords() {
+        let config = TextAnalyzerConfig {
+            language: TextLanguage::English,
+            stemming: true,
+            stopwords: BTreeSet::from(["the".to_string()]),
+            segmentation: TextSegmentation::Words,
+            autocomplete: None,
+        };
+        assert_eq!(
+            tokens(&config, "The runners running"),
+            vec![("runner".to_string(), 1), ("run".to_string(), 2)]
+        );
+    }
+
+    #[test]
+    fn test_cjk_bigrams() {
+        let config = TextAnalyzerConfig {
+            segmentation: TextSegmentation::CjkBigrams,
+            ..Default::default()
+        };
+        let texts = |text| {
+            tokens(&config, text)
+                .into_iter()
+                .map(|(text, _)| text)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(texts("東京都 Tokyo"), vec!["東京", "京都", "tokyo"]);
+        assert_eq!(texts("日本語abc中"), vec!["日本", "本語", "abc", "中"]);
+        assert_eq!(
+            tokens(&config, "a 東京"),
+            vec![("a".to_string(), 0), ("東京".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn test_autocomplete_prefixes() -> anyhow::Result<()> {
+        let config = TextAnalyzerConfig {
+            autocomplete: Some(AutocompleteConfig::new(Some(2), Some(4))?),
+            ..Default::default()
+        };
+        let mut token_stream = index_text_analyzer(&config).token_stream("Sofa ä couches");
+        let mut index_tokens = vec![];
+        while let Some(token) = token_stream.next() {
+            index_tokens.push((token.text.clone(), token.position));
+        }
+        let expected = [
+            ("so", 0),
+            ("sof", 0),
+            ("sofa", 0),
+            ("ä", 1),
+            ("co", 2),
+            ("cou", 2),
+            ("couc", 2),
+            ("couches", 2),
+        ];
+        assert_eq!(
+            index_tokens,
+            expected
+                .into_iter()
+                .map(|(text, position)| (text.to_string(), position))
+                .collect::<Vec<_>>()
+        );
+        // Search text isn't split into prefixes.
+        assert_eq!(tokens(&config, "Sofa"), vec![("sofa".to_string(), 0)]);
+        Ok(())
+    }
+}
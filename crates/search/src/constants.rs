@@ -8,6 +8,16 @@ use tantivy::tokenizer::{
 /// How many words (after stemming) can be in a text query?
 pub const MAX_QUERY_TERMS: usize = 16;
 
+/// How many words can be in a prefix search, which should stay cheap enough to
+/// run on every keystroke?
+pub const MAX_PREFIX_SEARCH_TERMS: usize = 8;
+
+/// How long can the text of a prefix search be, in bytes?
+pub const MAX_PREFIX_SEARCH_TEXT_LENGTH: usize = 256;
+
+/// How many synonyms can be added to a text query's terms?
+pub const MAX_SYNONYM_EXPANSIONS: usize = 16;
+
 /// What is the maximum length of a single text term? We will silently drop
 /// terms that exceed this length.
 ///
@@ -37,6 +47,14 @@ pub const MAX_POSITIONS_PER_MATCHED_TERM: usize = 16;
 
 pub const MAX_EDIT_DISTANCE: u32 = 2;
 
+/// The largest `k` allowed in a `NEAR/k` proximity operator.
+pub const MAX_NEAR_DISTANCE: u32 = 32;
+
+/// How much we multiply a document's score by when it satisfies a phrase or
+/// proximity constraint with its terms adjacent. The boost shrinks as the
+/// terms get further apart.
+pub const PROXIMITY_BOOST: f32 = 2.0;
+
 /// The maximum terms we'll return from QueryTokens. This corresponds to the
 /// maximum number of posting lists we'll want to consider in a single query.
 pub const MAX_UNIQUE_QUERY_TERMS: usize = 64;
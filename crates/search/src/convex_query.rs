@@ -6,11 +6,16 @@ use std::{
 use anyhow::Context;
 use tantivy::{
     fastfield::AliveBitSet,
+    postings::{
+        Postings,
+        SegmentPostings,
+    },
     query::{
         intersect_scorers,
         BitSetDocSet,
         BooleanQuery,
         BoostQuery,
+        ConstScorer,
         EmptyScorer,
         EnableScoring,
         Explanation,
@@ -28,18 +33,33 @@ use tantivy::{
     Term,
     TERMINATED,
 };
-use tantivy_common::ReadOnlyBitSet;
+use tantivy_common::{
+    BitSet,
+    ReadOnlyBitSet,
+};
+
+use crate::{
+    positional::{
+        positional_boost,
+        PositionalConstraint,
+    },
+    query::CompiledRangeFilter,
+};
 
 /// A query for documents that:
 /// 1. Contain at least one of the OR terms.
 /// 2. Match all of the AND terms.
+/// 3. Satisfy all of the positional constraints.
+/// 4. Have a value in each of the range filters' ranges.
 ///
 /// Unlike tantivy's BooleanQuery, this query will be scored only by the or
-/// terms.
+/// terms, boosted by how closely the positional constraints are satisfied.
 #[derive(Clone, Debug)]
 pub struct ConvexSearchQuery {
     or_query: BooleanQuery,
     and_queries: Vec<TermQuery>,
+    positional_constraints: Vec<PositionalConstraint>,
+    range_filters: Vec<CompiledRangeFilter>,
     alive_documents: AliveDocuments,
 }
 
@@ -48,6 +68,8 @@ impl ConvexSearchQuery {
     pub fn new(
         or_terms: Vec<OrTerm>,
         and_terms: Vec<Term>,
+        positional_constraints: Vec<PositionalConstraint>,
+        range_filters: Vec<CompiledRangeFilter>,
         alive_documents: AliveDocuments,
     ) -> Box<dyn Query> {
         let or_queries = or_terms
@@ -66,6 +88,8 @@ impl ConvexSearchQuery {
         Box::new(Self {
             or_query,
             and_queries,
+            positional_constraints,
+            range_filters,
             alive_documents,
         })
     }
@@ -92,6 +116,8 @@ impl Query for ConvexSearchQuery {
         Ok(Box::new(ConvexSearchWeight {
             or_weight,
             and_weights,
+            positional_constraints: self.positional_constraints.clone(),
+            range_filters: self.range_filters.clone(),
             alive_documents: self.alive_documents.clone(),
         }))
     }
@@ -107,6 +133,8 @@ impl Query for ConvexSearchQuery {
 struct ConvexSearchWeight {
     or_weight: Box<dyn Weight>,
     and_weights: Vec<Box<dyn Weight>>,
+    positional_constraints: Vec<PositionalConstraint>,
+    range_filters: Vec<CompiledRangeFilter>,
     alive_documents: AliveDocuments,
 }
 
@@ -116,11 +144,21 @@ impl Weight for ConvexSearchWeight {
         for filter_weight in &self.and_weights {
             and_scorers.push(filter_weight.scorer(reader, boost)?);
         }
+        for range_filter in &self.range_filters {
+            and_scorers.push(range_scorer(reader, range_filter)?);
+        }
         let scorer = intersect_scorers_and_use_one_for_scores(
             self.or_weight.scorer(reader, boost)?,
             intersect_scorers(and_scorers),
         );
-        Ok(scorer)
+        if self.positional_constraints.is_empty() {
+            return Ok(scorer);
+        }
+        Ok(Box::new(PositionalScorer::new(
+            scorer,
+            reader,
+            &self.positional_constraints,
+        )?))
     }
 
     fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
@@ -133,6 +171,150 @@ impl Weight for ConvexSearchWeight {
     }
 }
 
+/// The documents with a value in the range filter's range, collected from the
+/// postings of every term in the range. Hashed values are within the byte
+/// range of some ranges, so they're skipped by checking each term.
+fn range_scorer(
+    reader: &SegmentReader,
+    range_filter: &CompiledRangeFilter,
+) -> tantivy::Result<Box<dyn Scorer>> {
+    let inverted_index = reader.inverted_index(range_filter.field)?;
+    let range = &range_filter.range;
+    let mut builder = inverted_index.terms().range();
+    builder = if range.lower_inclusive {
+        builder.ge(&range.lower[..])
+    } else {
+        builder.gt(&range.lower[..])
+    };
+    builder = if range.upper_inclusive {
+        builder.le(&range.upper[..])
+    } else {
+        builder.lt(&range.upper[..])
+    };
+    let mut terms = builder.into_stream()?;
+    let mut docs = BitSet::with_max_value(reader.max_doc());
+    while terms.advance() {
+        if !range.contains(terms.key()) {
+            continue;
+        }
+        let mut postings =
+            inverted_index.read_postings_from_terminfo(terms.value(), IndexRecordOption::Basic)?;
+        let mut doc = postings.doc();
+        while doc != TERMINATED {
+            docs.insert(doc);
+            doc = postings.advance();
+        }
+    }
+    Ok(Box::new(ConstScorer::new(BitSetDocSet::from(docs), 1.0)))
+}
+
+/// Filters a scorer's documents down to the ones that satisfy a query's
+/// positional constraints, boosting their scores by how close together the
+/// constrained terms are.
+struct PositionalScorer {
+    scorer: Box<dyn Scorer>,
+    /// Postings with positions for each distinct term in the constraints, or
+    /// `None` if the term isn't in this segment.
+    postings: Vec<Option<SegmentPostings>>,
+    /// The constraints, with terms replaced by indexes into `postings`.
+    constraints: Vec<PositionalConstraint<usize>>,
+    /// The boost for the current document.
+    boost: Score,
+}
+
+impl PositionalScorer {
+    fn new(
+        scorer: Box<dyn Scorer>,
+        reader: &SegmentReader,
+        constraints: &[PositionalConstraint],
+    ) -> tantivy::Result<Self> {
+        let mut terms: Vec<Term> = vec![];
+        let constraints = constraints
+            .iter()
+            .filter_map(|constraint| {
+                constraint.try_map(|term| match terms.iter().position(|t| t == term) {
+                    Some(i) => Some(i),
+                    None => {
+                        terms.push(term.clone());
+                        Some(terms.len() - 1)
+                    },
+                })
+            })
+            .collect();
+        let postings = terms
+            .iter()
+            .map(|term| {
+                let inverted_index = reader.inverted_index(term.field())?;
+                Ok(inverted_index.read_postings(term, IndexRecordOption::WithFreqsAndPositions)?)
+            })
+            .collect::<tantivy::Result<_>>()?;
+        let mut positional_scorer = Self {
+            scorer,
+            postings,
+            constraints,
+            boost: 1.,
+        };
+        let doc = positional_scorer.scorer.doc();
+        positional_scorer.skip_to_match(doc);
+        Ok(positional_scorer)
+    }
+
+    /// Advances from `doc` to the first document that satisfies the
+    /// constraints.
+    fn skip_to_match(&mut self, mut doc: DocId) -> DocId {
+        while doc != TERMINATED {
+            let postings = &mut self.postings;
+            let boost = positional_boost(&self.constraints, |&i| {
+                Self::positions(postings[i].as_mut()?, doc)
+            });
+            if let Some(boost) = boost {
+                self.boost = boost;
+                break;
+            }
+            doc = self.scorer.advance();
+        }
+        doc
+    }
+
+    fn positions(postings: &mut SegmentPostings, doc: DocId) -> Option<Vec<u32>> {
+        if postings.doc() < doc {
+            postings.seek(doc);
+        }
+        if postings.doc() != doc {
+            return None;
+        }
+        let mut positions = vec![];
+        postings.positions(&mut positions);
+        Some(positions)
+    }
+}
+
+impl DocSet for PositionalScorer {
+    fn advance(&mut self) -> DocId {
+        let doc = self.scorer.advance();
+        self.skip_to_match(doc)
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        let doc = self.scorer.seek(target);
+        self.skip_to_match(doc)
+    }
+
+    fn doc(&self) -> DocId {
+        self.scorer.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.scorer.size_hint()
+    }
+}
+
+impl Scorer for PositionalScorer {
+    fn score(&mut self) -> Score {
+        self.scorer.score() * self.boost
+    }
+}
+
 #[derive(Clone)]
 pub struct AliveDocuments {
     pub memory_deleted: BTreeSet<DocId>,
@@ -81,6 +81,8 @@ pub async fn index_reader_for_directory<P: AsRef<Path>>(
     let directory = directory.as_ref().to_path_buf();
     let index =
         tokio_spawn_blocking("disk_index_open", move || Index::open_in_dir(directory)).await??;
+    // Search text is tokenized with the index's analyzer when compiling
+    // queries, so the reader never uses this tokenizer.
     index
         .tokenizers()
         .register(CONVEX_EN_TOKENIZER, convex_en());
@@ -101,7 +103,7 @@ pub async fn index_writer_for_directory<P: AsRef<Path>>(
     .await??;
     index
         .tokenizers()
-        .register(CONVEX_EN_TOKENIZER, convex_en());
+        .register(CONVEX_EN_TOKENIZER, tantivy_schema.index_analyzer.clone());
     Ok(index.writer(*SEARCH_INDEXING_MEMORY_ARENA_BYTES)?)
 }
 
@@ -246,6 +248,7 @@ pub async fn upload_vector_segment<RT: Runtime>(
         num_vectors: new_segment.num_vectors,
         num_deleted: new_segment.num_deleted,
         id: rt.new_uuid_v4().to_string(),
+        partitions: new_segment.partitions,
     })
 }
 
@@ -0,0 +1,130 @@
+use std::{
+    cmp::Reverse,
+    collections::BTreeMap,
+};
+
+use common::query::FacetOptions;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use value::{
+    ConvexObject,
+    ConvexValue,
+    FieldPath,
+};
+
+/// How many search results have each value of the requested filter fields,
+/// keyed by field path.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct SearchFacets(pub BTreeMap<String, Vec<FacetCount>>);
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct FacetCount {
+    pub value: JsonValue,
+    pub count: u64,
+}
+
+/// Counts the values of filter fields across a search's results. Results
+/// without a value for a field aren't counted for it.
+pub struct FacetCounter {
+    limit: usize,
+    counts: BTreeMap<FieldPath, BTreeMap<ConvexValue, u64>>,
+}
+
+impl FacetCounter {
+    pub(crate) fn new(options: FacetOptions) -> Self {
+        Self {
+            limit: options.limit as usize,
+            counts: options
+                .fields
+                .into_iter()
+                .map(|field_path| (field_path, BTreeMap::new()))
+                .collect(),
+        }
+    }
+
+    pub fn count(&mut self, document: &ConvexObject) {
+        for (field_path, counts) in &mut self.counts {
+            if let Some(value) = document.get_path(field_path) {
+                *counts.entry(value.clone()).or_default() += 1;
+            }
+        }
+    }
+
+    /// The most common values of each field, most common first. Values with
+    /// the same count are in index order.
+    pub fn into_facets(self) -> SearchFacets {
+        let facets = self
+            .counts
+            .into_iter()
+            .map(|(field_path, counts)| {
+                let mut counts: Vec<_> = counts.into_iter().collect();
+                // The sort is stable, so ties stay in value order.
+                counts.sort_by_key(|(_, count)| Reverse(*count));
+                counts.truncate(self.limit);
+                let counts = counts
+                    .into_iter()
+                    .map(|(value, count)| FacetCount {
+                        value: value.to_internal_json(),
+                        count,
+                    })
+                    .collect();
+                (String::from(field_path), counts)
+            })
+            .collect();
+        SearchFacets(facets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::query::FacetOptions;
+    use maplit::btreeset;
+    use serde_json::json;
+    use value::assert_obj;
+
+    use super::{
+        FacetCount,
+        FacetCounter,
+    };
+
+    #[test]
+    fn test_facet_counts() -> anyhow::Result<()> {
+        let options = FacetOptions::new(btreeset! {"author".parse()?, "tag".parse()?}, Some(2))?;
+        let mut counter = FacetCounter::new(options);
+        counter.count(&assert_obj!("author" => "ada", "tag" => "rust"));
+        counter.count(&assert_obj!("author" => "bob", "tag" => "go"));
+        counter.count(&assert_obj!("author" => "ada", "tag" => "c"));
+        counter.count(&assert_obj!("author" => "cy"));
+
+        let facets = counter.into_facets().0;
+        assert_eq!(
+            facets["author"],
+            vec![
+                FacetCount {
+                    value: json!("ada"),
+                    count: 2
+                },
+                FacetCount {
+                    value: json!("bob"),
+                    count: 1
+                },
+            ]
+        );
+        // Ties are broken by value, and documents without a tag aren't counted.
+        assert_eq!(
+            facets["tag"],
+            vec![
+                FacetCount {
+                    value: json!("c"),
+                    count: 1
+                },
+                FacetCount {
+                    value: json!("go"),
+                    count: 1
+                },
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,306 @@
+use std::ops::Range;
+
+use anyhow::Context;
+use common::query::HighlightOptions;
+use levenshtein_automata::{
+    Distance,
+    DFA,
+};
+use serde::Serialize;
+use tantivy::tokenizer::TextAnalyzer;
+use value::{
+    ConvexObject,
+    ConvexValue,
+    FieldPath,
+};
+
+use crate::{
+    levenshtein_dfa::{
+        build_fuzzy_dfa,
+        shares_first_char,
+    },
+    query::QueryTerm,
+};
+
+/// Where a search matched a result's search field, along with snippets of the
+/// field around the matches. Offsets are in UTF-16 code units so they can
+/// index JavaScript strings directly.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct SearchHighlights {
+    /// Every match in the search field, in order.
+    pub matches: Vec<HighlightRange>,
+    /// The snippets with the most matches, best first.
+    pub snippets: Vec<Snippet>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct HighlightRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Snippet {
+    pub text: String,
+    /// The matches in the snippet, relative to the start of `text`.
+    pub matches: Vec<HighlightRange>,
+}
+
+struct HighlightTerm {
+    text: String,
+    fuzzy: bool,
+    dfa: DFA,
+}
+
+/// Finds the terms of a compiled query in search results by tokenizing their
+/// search field with the index's analyzer. Every term is highlighted wherever
+/// it matches, including terms of phrases that didn't match as a phrase.
+pub struct Highlighter {
+    analyzer: TextAnalyzer,
+    search_field: FieldPath,
+    terms: Vec<HighlightTerm>,
+    options: HighlightOptions,
+}
+
+impl Highlighter {
+    pub(crate) fn new(
+        analyzer: TextAnalyzer,
+        search_field: FieldPath,
+        text_query: &[QueryTerm],
+        options: HighlightOptions,
+    ) -> anyhow::Result<Self> {
+        let terms = text_query
+            .iter()
+            .map(|query_term| {
+                let text = query_term
+                    .term()
+                    .as_str()
+                    .context("Term was not valid UTF8")?
+                    .to_string();
+                let dfa = build_fuzzy_dfa(
+                    &text,
+                    query_term.max_distance().try_into()?,
+                    query_term.prefix(),
+                );
+                Ok(HighlightTerm {
+                    text,
+                    fuzzy: query_term.max_distance() > 0,
+                    dfa,
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self {
+            analyzer,
+            search_field,
+            terms,
+            options,
+        })
+    }
+
+    fn is_match(&self, token: &str) -> bool {
+        self.terms.iter().any(|term| {
+            matches!(term.dfa.eval(token), Distance::Exact(_))
+                && (!term.fuzzy || shares_first_char(&term.text, token))
+        })
+    }
+
+    pub fn highlight(&self, document: &ConvexObject) -> SearchHighlights {
+        let Some(ConvexValue::String(text)) = document.get_path(&self.search_field) else {
+            return SearchHighlights::default();
+        };
+        let mut byte_matches: Vec<Range<usize>> = vec![];
+        let mut token_stream = self.analyzer.token_stream(text);
+        while let Some(token) = token_stream.next() {
+            if !self.is_match(&token.text) {
+                continue;
+            }
+            // CJK bigrams overlap, so merge their matches.
+            match byte_matches.last_mut() {
+                Some(last) if token.offset_from <= last.end => {
+                    last.end = last.end.max(token.offset_to);
+                },
+                _ => byte_matches.push(token.offset_from..token.offset_to),
+            }
+        }
+        highlights(text, &byte_matches, self.options)
+    }
+}
+
+fn highlights(
+    text: &str,
+    byte_matches: &[Range<usize>],
+    options: HighlightOptions,
+) -> SearchHighlights {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    // The UTF-16 offset of each character, plus the end of the text.
+    let mut utf16_offsets = Vec::with_capacity(chars.len() + 1);
+    let mut offset = 0;
+    for (_, c) in &chars {
+        utf16_offsets.push(offset);
+        offset += c.len_utf16();
+    }
+    utf16_offsets.push(offset);
+    let char_index = |byte: usize| chars.partition_point(|&(i, _)| i < byte);
+    let matches: Vec<Range<usize>> = byte_matches
+        .iter()
+        .map(|m| char_index(m.start)..char_index(m.end))
+        .collect();
+
+    let mut fragments = vec![];
+    let mut i = 0;
+    while i < matches.len() && options.max_fragments > 0 {
+        let range = fragment_around(&chars, &matches[i], options.fragment_size as usize);
+        // This includes at least the first match, since the fragment does.
+        let count = matches[i..].partition_point(|m| m.end <= range.end);
+        fragments.push((range, i..i + count));
+        i += count;
+    }
+    fragments
+        .sort_by_key(|(range, in_fragment)| (std::cmp::Reverse(in_fragment.len()), range.start));
+    fragments.truncate(options.max_fragments as usize);
+
+    let to_utf16 = |m: &Range<usize>, base: usize| HighlightRange {
+        start: utf16_offsets[m.start] - base,
+        end: utf16_offsets[m.end] - base,
+    };
+    let snippets = fragments
+        .into_iter()
+        .map(|(range, in_fragment)| {
+            let base = utf16_offsets[range.start];
+            Snippet {
+                text: chars[range].iter().map(|&(_, c)| c).collect(),
+                matches: matches[in_fragment]
+                    .iter()
+                    .map(|m| to_utf16(m, base))
+                    .collect(),
+            }
+        })
+        .collect();
+    SearchHighlights {
+        matches: matches.iter().map(|m| to_utf16(m, 0)).collect(),
+        snippets,
+    }
+}
+
+/// Picks a fragment of about `fragment_size` characters that starts a little
+/// before `first_match`, trimmed to whole words where possible.
+fn fragment_around(
+    chars: &[(usize, char)],
+    first_match: &Range<usize>,
+    fragment_size: usize,
+) -> Range<usize> {
+    let is_space = |i: usize| chars[i].1.is_whitespace();
+    let mut start = first_match.start.saturating_sub(fragment_size / 4);
+    if start > 0
+        && !is_space(start - 1)
+        && let Some(space) = (start..first_match.start).find(|&i| is_space(i))
+    {
+        start = space + 1;
+    }
+    let mut end = (start + fragment_size)
+        .min(chars.len())
+        .max(first_match.end);
+    if end < chars.len()
+        && !is_space(end)
+        && let Some(space) = (first_match.end..end).rev().find(|&i| is_space(i))
+    {
+        end = space;
+    }
+    start..end
+}
+
+#[cfg(test)]
+mod tests {
+    use common::query::HighlightOptions;
+    use tantivy::Term;
+    use value::assert_obj;
+
+    use super::{
+        HighlightRange,
+        Highlighter,
+    };
+    use crate::{
+        convex_en,
+        query::QueryTerm,
+    };
+
+    fn highlighter(terms: &[(&str, u32, bool)], options: HighlightOptions) -> Highlighter {
+        let field = tantivy::schema::Field::from_field_id(0);
+        let terms: Vec<_> = terms
+            .iter()
+            .map(|&(text, max_distance, prefix)| {
+                QueryTerm::new(Term::from_field_text(field, text), max_distance, prefix)
+            })
+            .collect();
+        Highlighter::new(convex_en(), "body".parse().unwrap(), &terms, options).unwrap()
+    }
+
+    fn range(start: usize, end: usize) -> HighlightRange {
+        HighlightRange { start, end }
+    }
+
+    #[test]
+    fn test_highlight_matches() -> anyhow::Result<()> {
+        let highlighter = highlighter(
+            &[("quick", 0, false), ("brwn", 1, false), ("fo", 0, true)],
+            HighlightOptions::default(),
+        );
+        let document = assert_obj!("body" => "The Quick brown fox, quickly!");
+        let highlights = highlighter.highlight(&document);
+        assert_eq!(
+            highlights.matches,
+            vec![range(4, 9), range(10, 15), range(16, 19)]
+        );
+        assert_eq!(highlights.snippets.len(), 1);
+        assert_eq!(highlights.snippets[0].text, "The Quick brown fox, quickly!");
+
+        // Offsets are in UTF-16 code units.
+        let document = assert_obj!("body" => "😀 quick");
+        assert_eq!(highlighter.highlight(&document).matches, vec![range(3, 8)]);
+
+        let document = assert_obj!("other" => "quick");
+        assert!(highlighter.highlight(&document).matches.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_snippets() -> anyhow::Result<()> {
+        let highlighter = highlighter(
+            &[("needle", 0, false)],
+            HighlightOptions {
+                fragment_size: 20,
+                max_fragments: 2,
+            },
+        );
+        let filler = "lorem ipsum dolor sit amet ".repeat(4);
+        let text = format!("{filler}needle {filler}needle needle {filler}needle");
+        let document = assert_obj!("body" => text.as_str());
+        let highlights = highlighter.highlight(&document);
+        assert_eq!(highlights.matches.len(), 4);
+        assert_eq!(highlights.snippets.len(), 2);
+
+        // The snippet with two matches comes first.
+        let best = &highlights.snippets[0];
+        assert_eq!(best.text, "amet needle needle");
+        assert_eq!(best.matches, vec![range(5, 11), range(12, 18)]);
+        for snippet in &highlights.snippets {
+            assert!(snippet.text.chars().count() <= 20);
+            assert!(!snippet.text.starts_with(' ') && !snippet.text.ends_with(' '));
+            for m in &snippet.matches {
+                assert_eq!(&snippet.text[m.start..m.end], "needle");
+            }
+        }
+
+        let highlighter = highlighter(
+            &[("needle", 0, false)],
+            HighlightOptions {
+                fragment_size: 20,
+                max_fragments: 0,
+            },
+        );
+        let highlights = highlighter.highlight(&document);
+        assert_eq!(highlights.matches.len(), 4);
+        assert!(highlights.snippets.is_empty());
+        Ok(())
+    }
+}
@@ -53,7 +53,6 @@ use value::InternalId;
 use crate::{
     archive::cache::ArchiveCacheManager,
     constants::CONVEX_EN_TOKENIZER,
-    convex_en,
     disk_index::{
         download_single_file_zip,
         upload_single_file,
@@ -349,7 +348,7 @@ pub async fn build_new_segment(
         .create_in_dir(&index_path)?;
     index
         .tokenizers()
-        .register(CONVEX_EN_TOKENIZER, convex_en());
+        .register(CONVEX_EN_TOKENIZER, tantivy_schema.index_analyzer.clone());
     let mut segment_writer = SingleSegmentIndexWriter::new(index, SEGMENT_MAX_SIZE_BYTES)?;
     let mut new_id_tracker = SearchMemoryIdTracker::default();
     futures::pin_mut!(revision_stream);
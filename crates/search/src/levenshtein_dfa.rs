@@ -76,3 +76,9 @@ pub fn build_fuzzy_dfa(query: &str, distance: u8, prefix: bool) -> DFA {
         dfa_builder.build_dfa(query)
     }
 }
+
+/// Fuzzy matches must keep the query's first character. Typos there are rare
+/// and would otherwise match many unrelated terms.
+pub fn shares_first_char(query: &str, term: &str) -> bool {
+    query.chars().next() == term.chars().next()
+}
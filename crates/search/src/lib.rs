@@ -8,16 +8,20 @@
 #![feature(trait_alias)]
 
 mod aggregation;
+mod analyzer;
 mod archive;
 mod constants;
 mod convex_query;
 pub mod disk_index;
+mod facets;
 pub mod fragmented_segment;
+mod highlight;
 mod incremental_index;
 mod intersection;
 mod levenshtein_dfa;
 mod memory_index;
 pub mod metrics;
+mod positional;
 pub mod query;
 pub mod scoring;
 pub mod searcher;
@@ -30,23 +34,33 @@ use std::{
         BTreeMap,
         BTreeSet,
     },
+    iter,
     sync::Arc,
 };
 
 use aggregation::PostingListMatchAggregator;
+pub use analyzer::{
+    index_text_analyzer,
+    text_analyzer,
+    CjkBigramTokenizer,
+};
 use anyhow::Context;
 use common::{
     bootstrap_model::index::{
-        text_index::DeveloperTextIndexConfig,
+        text_index::{
+            DeveloperTextIndexConfig,
+            TextAnalyzerConfig,
+        },
         IndexConfig,
     },
     document::ResolvedDocument,
-    index::IndexKeyBytes,
     query::{
         search_value_to_bytes,
         InternalSearch,
         InternalSearchFilterExpression,
+        SearchSynonyms,
         SearchVersion,
+        TextSearchOptions,
     },
     runtime::{
         block_in_place,
@@ -63,14 +77,29 @@ pub use constants::{
     EXACT_SEARCH_MAX_WORD_LENGTH,
     MAX_CANDIDATE_REVISIONS,
     MAX_FILTER_CONDITIONS,
+    MAX_PREFIX_SEARCH_TERMS,
+    MAX_PREFIX_SEARCH_TEXT_LENGTH,
     MAX_QUERY_TERMS,
+    MAX_SYNONYM_EXPANSIONS,
     SINGLE_TYPO_SEARCH_MAX_WORD_LENGTH,
 };
 use convex_query::OrTerm;
 use errors::ErrorMetadata;
+pub use facets::{
+    FacetCount,
+    FacetCounter,
+    SearchFacets,
+};
+pub use highlight::{
+    HighlightRange,
+    Highlighter,
+    SearchHighlights,
+    Snippet,
+};
 use indexing::index_registry::Index;
 use itertools::Itertools;
 use metrics::log_search_token_limit_exceeded;
+use positional::ParsedSearchText;
 pub use query::{
     CandidateRevision,
     FilterConditionRead,
@@ -95,15 +124,11 @@ use tantivy::{
         TextOptions,
         FAST,
     },
-    tokenizer::{
-        TextAnalyzer,
-        Token,
-    },
+    tokenizer::TextAnalyzer,
     Term,
 };
 pub use tantivy_query::SearchQueryResult;
 use value::{
-    values_to_bytes,
     ConvexValue,
     FieldPath,
 };
@@ -111,6 +136,7 @@ use value::{
 use self::query::{
     CompiledFilterCondition,
     CompiledQuery,
+    CompiledRangeFilter,
     QueryTerm,
 };
 pub use self::{
@@ -218,17 +244,14 @@ impl From<FieldPosition> for u32 {
     }
 }
 
-impl TryFrom<&Token> for FieldPosition {
-    type Error = anyhow::Error;
-
-    fn try_from(value: &Token) -> Result<Self, Self::Error> {
-        Ok(Self(u32::try_from(value.position)?))
-    }
-}
-
 #[derive(Clone)]
 pub struct TantivySearchIndexSchema {
-    analyzer: TextAnalyzer,
+    analyzer_config: TextAnalyzerConfig,
+    // Analyzes search text.
+    pub(crate) analyzer: TextAnalyzer,
+    // Analyzes documents, which for autocomplete indexes also yields the
+    // prefixes of each word.
+    pub(crate) index_analyzer: TextAnalyzer,
 
     internal_id_field: Field,
     ts_field: Field,
@@ -236,6 +259,9 @@ pub struct TantivySearchIndexSchema {
 
     search_field_path: FieldPath,
     pub search_field: Field,
+    // Every search field is indexed into `search_field`.
+    extra_search_fields: BTreeSet<FieldPath>,
+    search_field_weights: BTreeMap<FieldPath, u32>,
 
     pub filter_fields: BTreeMap<FieldPath, Field>,
 
@@ -244,21 +270,15 @@ pub struct TantivySearchIndexSchema {
 
 impl From<&TantivySearchIndexSchema> for pb::searchlight::SearchIndexConfig {
     fn from(schema: &TantivySearchIndexSchema) -> Self {
-        pb::searchlight::SearchIndexConfig {
-            search_field_path: Some(schema.search_field_path.clone().into()),
-            filter_fields: schema
-                .filter_fields
-                .keys()
-                .cloned()
-                .map(|p| p.into())
-                .collect::<Vec<_>>(),
-        }
+        schema.to_index_config().into()
     }
 }
 
 impl TantivySearchIndexSchema {
     pub fn new(index_config: &DeveloperTextIndexConfig) -> Self {
-        let analyzer = convex_en();
+        let analyzer_config = index_config.analyzer.clone();
+        let analyzer = text_analyzer(&analyzer_config);
+        let index_analyzer = index_text_analyzer(&analyzer_config);
 
         let mut schema_builder = Schema::builder();
 
@@ -288,13 +308,17 @@ impl TantivySearchIndexSchema {
         }
         let schema = schema_builder.build();
         Self {
+            analyzer_config,
             analyzer,
+            index_analyzer,
             internal_id_field,
             ts_field,
             creation_time_field,
 
             search_field_path,
             search_field,
+            extra_search_fields: index_config.extra_search_fields.clone(),
+            search_field_weights: index_config.search_field_weights.clone(),
 
             filter_fields,
             schema,
@@ -321,10 +345,39 @@ impl TantivySearchIndexSchema {
     pub fn to_index_config(&self) -> DeveloperTextIndexConfig {
         DeveloperTextIndexConfig {
             search_field: self.search_field_path.clone(),
+            extra_search_fields: self.extra_search_fields.clone(),
+            search_field_weights: self.search_field_weights.clone(),
             filter_fields: self.filter_fields.keys().cloned().collect(),
+            analyzer: self.analyzer_config.clone(),
         }
     }
 
+    /// The paths of the fields this index searches, starting with the
+    /// `searchField` that search queries name.
+    pub fn search_field_paths(&self) -> impl Iterator<Item = &FieldPath> {
+        std::iter::once(&self.search_field_path).chain(&self.extra_search_fields)
+    }
+
+    /// The text to index into `search_field` for a document: the value of
+    /// each of its search fields, repeated by the field's weight so that
+    /// matches in it count that many times when scoring. Repeating a value
+    /// also counts it toward the document's length that many times, which is
+    /// how BM25F weights fields.
+    fn search_texts<'a>(&self, document: &'a ResolvedDocument) -> Vec<&'a str> {
+        let mut texts = vec![];
+        for field_path in self.search_field_paths() {
+            if let Some(ConvexValue::String(ref s)) = document.value().get_path(field_path) {
+                let weight = self
+                    .search_field_weights
+                    .get(field_path)
+                    .copied()
+                    .unwrap_or(1);
+                texts.extend(iter::repeat(&s[..]).take(weight as usize));
+            }
+        }
+        texts
+    }
+
     fn filter_field_bytes(document: &ResolvedDocument, field_path: &FieldPath) -> Vec<u8> {
         let value = document.value().get_path(field_path);
         search_value_to_bytes(value)
@@ -335,13 +388,11 @@ impl TantivySearchIndexSchema {
     /// when a super rough estimate is sufficient (e.g. capping the maximum
     /// size of a new segment).
     pub fn estimate_size(&self, document: &ResolvedDocument) -> u64 {
-        let document_size = if let Some(ConvexValue::String(ref s)) =
-            document.value().get_path(&self.search_field_path)
-        {
-            s.len()
-        } else {
-            0
-        };
+        let document_size: usize = self
+            .search_texts(document)
+            .into_iter()
+            .map(|s| s.len())
+            .sum();
         let mut filter_field_sizes = 0;
         for field_path in self.filter_fields.keys() {
             let value = TantivySearchIndexSchema::filter_field_bytes(document, field_path);
@@ -357,18 +408,24 @@ impl TantivySearchIndexSchema {
         let _timer = metrics::index_into_terms_timer();
 
         let mut doc_terms = vec![];
-        if let Some(ConvexValue::String(ref s)) = document.value().get_path(&self.search_field_path)
-        {
-            let mut token_stream = self.analyzer.token_stream(&s[..]);
+        // Like tantivy does for fields with several values, leave a gap between
+        // the positions of each text so phrases can't span them.
+        let mut start_position = 0;
+        for text in self.search_texts(document) {
+            let mut end_position = start_position;
+            let mut token_stream = self.index_analyzer.token_stream(text);
 
             while let Some(token) = token_stream.next() {
                 metrics::log_text_term(&token.text);
 
+                let position = start_position + u32::try_from(token.position)?;
+                end_position = end_position.max(position + token.position_length as u32);
                 doc_terms.push(DocumentTerm::Search {
                     term: Term::from_field_text(self.search_field, &token.text),
-                    pos: FieldPosition::try_from(token)?,
+                    pos: FieldPosition(position),
                 });
             }
+            start_position = end_position + 1;
         }
         for (field_path, tantivy_field) in &self.filter_fields {
             let value = TantivySearchIndexSchema::filter_field_bytes(document, field_path);
@@ -396,9 +453,8 @@ impl TantivySearchIndexSchema {
         let creation_time = document.creation_time();
         tantivy_document.add_f64(self.creation_time_field, creation_time.into());
 
-        if let Some(ConvexValue::String(ref s)) = document.value().get_path(&self.search_field_path)
-        {
-            tantivy_document.add_text(self.search_field, s);
+        for text in self.search_texts(document) {
+            tantivy_document.add_text(self.search_field, text);
         }
         for (field_path, tantivy_field) in &self.filter_fields {
             let value = TantivySearchIndexSchema::filter_field_bytes(document, field_path);
@@ -410,8 +466,10 @@ impl TantivySearchIndexSchema {
 
     pub fn document_lengths(&self, document: &TantivyDocument) -> DocumentLengths {
         let mut search_field = 0;
-        if let Some(tantivy::schema::Value::Str(ref s)) = document.get_first(self.search_field) {
-            search_field += s.len();
+        for value in document.get_all(self.search_field) {
+            if let tantivy::schema::Value::Str(ref s) = value {
+                search_field += s.len();
+            }
         }
         let mut filter_fields = BTreeMap::new();
         for (field_path, tantivy_field) in &self.filter_fields {
@@ -590,8 +648,13 @@ impl TantivySearchIndexSchema {
         // to know which `InternalId`s to exclude when querying the disk
         // indexes.
         let (prepared_memory_query, query) = block_in_place(|| {
-            let prepared_memory_query =
-                memory_index.prepare_posting_list_query(&and_terms, &or_terms, &bm25_stats)?;
+            let prepared_memory_query = memory_index.prepare_posting_list_query(
+                &and_terms,
+                &or_terms,
+                &compiled_query.positional_constraints,
+                &compiled_query.range_filters,
+                &bm25_stats,
+            )?;
             let mut deleted_internal_ids = BTreeSet::new();
             if let Some(ref prepared_query) = prepared_memory_query {
                 deleted_internal_ids =
@@ -603,6 +666,9 @@ impl TantivySearchIndexSchema {
                 num_documents: bm25_stats.num_documents,
                 or_terms,
                 and_terms,
+                positional_constraints: compiled_query.positional_constraints,
+                range_filters: compiled_query.range_filters,
+                search_after: compiled_query.search_after,
                 max_results: MAX_CANDIDATE_REVISIONS,
             };
             anyhow::Ok((prepared_memory_query, query))
@@ -622,7 +688,8 @@ impl TantivySearchIndexSchema {
                     .await
             });
         }
-        let mut match_aggregator = PostingListMatchAggregator::new(MAX_CANDIDATE_REVISIONS);
+        let mut match_aggregator =
+            PostingListMatchAggregator::new(MAX_CANDIDATE_REVISIONS, query.search_after.clone());
         if let Some(ref prepared_query) = prepared_memory_query {
             block_in_place(|| {
                 memory_index.query_posting_lists(
@@ -651,18 +718,8 @@ impl TantivySearchIndexSchema {
                     ts: m.ts,
                     creation_time: m.creation_time,
                 };
-                let index_fields = vec![
-                    Some(ConvexValue::Float64(-f64::from(m.bm25_score))),
-                    Some(ConvexValue::Float64(-f64::from(m.creation_time))),
-                    Some(ConvexValue::Bytes(
-                        Vec::<u8>::from(m.internal_id)
-                            .try_into()
-                            .expect("Could not convert internal ID to value"),
-                    )),
-                ];
-                let bytes = values_to_bytes(&index_fields);
-                let index_key_bytes = IndexKeyBytes(bytes);
-                result.push((candidate, index_key_bytes));
+                let index_key = candidate.index_key(f64::from(candidate.score));
+                result.push((candidate, index_key));
             }
             result
         });
@@ -671,21 +728,120 @@ impl TantivySearchIndexSchema {
 
     fn compile_tokens_with_typo_tolerance(
         search_field: Field,
-        tokens: &Vec<String>,
+        parsed: &ParsedSearchText,
+        options: &TextSearchOptions,
     ) -> anyhow::Result<Vec<QueryTerm>> {
         let mut res = vec![];
 
-        let mut it = tokens.iter().peekable();
-        while let Some(text) = it.next() {
+        // Tokens in phrases and proximity constraints have to match exactly.
+        let constrained = parsed.constrained_tokens();
+        let mut it = parsed.tokens.iter().enumerate().peekable();
+        while let Some((i, text)) = it.next() {
             let term = Term::from_field_text(search_field, text);
             anyhow::ensure!(term.as_str().is_some(), "Term was not valid UTF8");
 
+            if constrained.binary_search(&i).is_ok() {
+                res.push(QueryTerm::new(term, 0, false));
+                continue;
+            }
             let is_prefix = it.peek().is_none();
-            res.push(QueryTerm::new(term, is_prefix))
+            let max_distance = max_typos_for_token(text).min(options.max_typos as u32);
+            res.push(QueryTerm::new(term, max_distance, is_prefix))
         }
         Ok(res)
     }
 
+    /// Adds the synonyms of the query's unconstrained tokens as exact terms.
+    /// Synonyms are analyzed like the search text, and synonyms that don't
+    /// analyze to a single token are ignored.
+    fn expand_synonyms(
+        &self,
+        text_query: &mut Vec<QueryTerm>,
+        parsed: &ParsedSearchText,
+        synonyms: &SearchSynonyms,
+    ) {
+        if synonyms.is_empty() {
+            return;
+        }
+        let analyze = |word: &str| {
+            let mut token_stream = self.analyzer.token_stream(word);
+            let token = token_stream.next()?.text.clone();
+            token_stream.next().is_none().then_some(token)
+        };
+        let groups: Vec<Vec<String>> = synonyms
+            .groups
+            .iter()
+            .map(|group| group.iter().filter_map(|word| analyze(word)).collect())
+            .collect();
+        let constrained = parsed.constrained_tokens();
+        let mut seen: BTreeSet<&str> = parsed.tokens.iter().map(|t| t.as_str()).collect();
+        let mut num_expansions = 0;
+        for (i, token) in parsed.tokens.iter().enumerate() {
+            if constrained.binary_search(&i).is_ok() {
+                continue;
+            }
+            for group in groups.iter().filter(|group| group.contains(token)) {
+                for synonym in group {
+                    if num_expansions == MAX_SYNONYM_EXPANSIONS {
+                        return;
+                    }
+                    if seen.insert(synonym.as_str()) {
+                        let term = Term::from_field_text(self.search_field, synonym);
+                        text_query.push(QueryTerm::new(term, 0, false));
+                        num_expansions += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Splits the text of a prefix search into the word prefixes an
+    /// autocomplete index stores. Words longer than the longest stored prefix
+    /// are cut to it, and quotes don't make phrases.
+    fn parse_prefix_search(
+        &self,
+        query: &InternalSearch,
+        search_text: &str,
+    ) -> anyhow::Result<ParsedSearchText> {
+        let Some(autocomplete) = self.analyzer_config.autocomplete else {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "PrefixSearchRequiresAutocompleteIndex",
+                format!(
+                    "Search query against {} is a prefix search, but the index doesn't store \
+                     prefixes. Add `analyzer: {{ autocomplete: {{}} }}` to the index to use \
+                     `prefixSearch`.",
+                    query.printable_index_name()?,
+                )
+            ))
+        };
+        if search_text.len() > MAX_PREFIX_SEARCH_TEXT_LENGTH {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "PrefixSearchTextTooLong",
+                format!(
+                    "Prefix search against {} has {} bytes of text, but at most \
+                     {MAX_PREFIX_SEARCH_TEXT_LENGTH} are allowed.",
+                    query.printable_index_name()?,
+                    search_text.len(),
+                )
+            ))
+        }
+        let mut parsed = ParsedSearchText::default();
+        let mut token_stream = self.analyzer.token_stream(search_text);
+        while let Some(token) = token_stream.next() {
+            if parsed.tokens.len() == MAX_PREFIX_SEARCH_TERMS {
+                parsed.truncated = true;
+                break;
+            }
+            let end = token
+                .text
+                .char_indices()
+                .nth(autocomplete.max_gram as usize)
+                .map_or(token.text.len(), |(i, _)| i);
+            parsed.tokens.push(token.text[..end].to_string());
+        }
+        Ok(parsed)
+    }
+
     pub fn compile(
         &self,
         query: &InternalSearch,
@@ -693,12 +849,13 @@ impl TantivySearchIndexSchema {
     ) -> anyhow::Result<(CompiledQuery, QueryReads)> {
         let timer = metrics::compile_timer();
 
-        let mut search_text: Option<&str> = None;
+        let mut search_text: Option<(&str, &TextSearchOptions)> = None;
         let mut filter_conditions = Vec::new();
+        let mut range_filters = Vec::new();
         let mut filter_reads = Vec::new();
         for filter in query.filters.iter() {
             match filter {
-                InternalSearchFilterExpression::Search(field_path, text_query) => {
+                InternalSearchFilterExpression::Search(field_path, text_query, options) => {
                     if *field_path != self.search_field_path {
                         anyhow::bail!(ErrorMetadata::bad_request(
                             "IncorrectSearchField",
@@ -721,7 +878,7 @@ impl TantivySearchIndexSchema {
                             )
                         ))
                     }
-                    search_text = Some(text_query)
+                    search_text = Some((text_query, options))
                 },
                 InternalSearchFilterExpression::Eq(field_path, value) => {
                     let Some(field) = self.filter_fields.get(field_path) else {
@@ -739,10 +896,31 @@ impl TantivySearchIndexSchema {
                     filter_conditions.push(CompiledFilterCondition::Must(term));
                     filter_reads.push(FilterConditionRead::Must(field_path.clone(), value.clone()));
                 },
+                InternalSearchFilterExpression::Range(field_path, range) => {
+                    let Some(field) = self.filter_fields.get(field_path) else {
+                        anyhow::bail!(ErrorMetadata::bad_request(
+                            "IncorrectFilterFieldError",
+                            format!(
+                                "Search query against {} contains a range filter on \
+                                 {field_path:?} but that field isn't indexed for filtering in \
+                                 `filterFields`.",
+                                query.printable_index_name()?,
+                            )
+                        ))
+                    };
+                    range_filters.push(CompiledRangeFilter {
+                        field: *field,
+                        range: range.clone(),
+                    });
+                    filter_reads.push(FilterConditionRead::Range(
+                        field_path.clone(),
+                        range.clone(),
+                    ));
+                },
             }
         }
 
-        let Some(search_text) = search_text else {
+        let Some((search_text, options)) = search_text else {
             anyhow::bail!(ErrorMetadata::bad_request(
                 "MissingSearchFilterError",
                 format!(
@@ -754,65 +932,159 @@ impl TantivySearchIndexSchema {
             ))
         };
 
-        let mut token_stream = self.analyzer.token_stream(search_text);
-        let mut tokens = vec![];
+        let parsed = if options.prefix_search {
+            self.parse_prefix_search(query, search_text)?
+        } else {
+            ParsedSearchText::parse(&self.analyzer, search_text, MAX_QUERY_TERMS)?
+        };
         // TODO(CX-5693): Consider how/if we should surface this to developers.
-        while tokens.len() < MAX_QUERY_TERMS
-            && let Some(token) = token_stream.next()
-        {
-            tokens.push(token.text.clone());
-        }
-        if tokens.len() == MAX_QUERY_TERMS && token_stream.next().is_some() {
+        if parsed.truncated {
             log_search_token_limit_exceeded();
         }
 
-        let text_query = match version {
-            SearchVersion::V1 => tokens
+        let mut text_query = match version {
+            // Only the V2 search codepath can generate QueryTerm::Fuzzy
+            SearchVersion::V2 if !options.prefix_search => {
+                Self::compile_tokens_with_typo_tolerance(self.search_field, &parsed, options)?
+            },
+            // Prefix searches match the prefixes an autocomplete index stores
+            // exactly.
+            SearchVersion::V1 | SearchVersion::V2 => parsed
+                .tokens
                 .iter()
                 .map(|text| {
                     let term = Term::from_field_text(self.search_field, text);
                     anyhow::ensure!(term.as_str().is_some(), "Term was not valid UTF8");
-                    Ok(QueryTerm::new(term, false))
+                    Ok(QueryTerm::new(term, 0, false))
                 })
                 .collect::<anyhow::Result<Vec<_>>>()?,
-            // Only the V2 search codepath can generate QueryTerm::Fuzzy
-            SearchVersion::V2 => {
-                Self::compile_tokens_with_typo_tolerance(self.search_field, &tokens)?
-            },
         };
-
-        let text_reads = text_query
-            .clone()
-            .into_iter()
-            .map(|t| {
-                anyhow::Ok(TextQueryTermRead::new(
-                    self.search_field_path.clone(),
-                    TextQueryTerm::try_from(t)?,
-                ))
+        self.expand_synonyms(&mut text_query, &parsed, &query.synonyms);
+        let positional_constraints = parsed
+            .constraints
+            .iter()
+            .map(|constraint| {
+                constraint
+                    .try_map(|&i| Some(Term::from_field_text(self.search_field, &parsed.tokens[i])))
+                    .context("Positional constraint refers to a missing token")
             })
-            .collect::<anyhow::Result<_>>()?;
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        // A document overlaps the query if its text in any of the search fields
+        // does.
+        let mut text_reads = vec![];
+        for field_path in self.search_field_paths() {
+            for t in &text_query {
+                text_reads.push(TextQueryTermRead::new(
+                    field_path.clone(),
+                    TextQueryTerm::try_from(t.clone())?,
+                ));
+            }
+        }
 
-        if filter_conditions.len() > MAX_FILTER_CONDITIONS {
+        let num_filter_conditions = filter_conditions.len() + range_filters.len();
+        if num_filter_conditions > MAX_FILTER_CONDITIONS {
             anyhow::bail!(ErrorMetadata::bad_request(
                 "TooManyFilterConditionsInSearchQueryError",
                 format!(
                     "Search query against {} has too many filter conditions. Max: {} Actual: {}",
                     query.printable_index_name()?,
                     MAX_FILTER_CONDITIONS,
-                    filter_conditions.len()
+                    num_filter_conditions
                 )
             ))
         }
         let query = CompiledQuery {
             text_query,
             filter_conditions,
+            positional_constraints,
+            range_filters,
+            search_after: query.search_after.clone(),
         };
-        let reads = QueryReads::new(text_reads, filter_reads.into());
+        let reads = QueryReads::new(text_reads.into(), filter_reads.into())
+            .with_analyzer(self.analyzer_config.clone());
         metrics::log_compiled_query(&query);
 
         timer.finish();
         Ok((query, reads))
     }
+
+    /// Builds a highlighter for the query's results if its search filter asked
+    /// for highlights.
+    pub fn highlighter(
+        &self,
+        query: &InternalSearch,
+        version: SearchVersion,
+    ) -> anyhow::Result<Option<Highlighter>> {
+        let highlight = query.filters.iter().find_map(|filter| match filter {
+            InternalSearchFilterExpression::Search(_, _, options) => options
+                .highlight
+                .map(|highlight| (highlight, options.prefix_search)),
+            InternalSearchFilterExpression::Eq(..) | InternalSearchFilterExpression::Range(..) => {
+                None
+            },
+        });
+        let Some((options, prefix_search)) = highlight else {
+            return Ok(None);
+        };
+        let (compiled, _) = self.compile(query, version)?;
+        // Prefix searches match the prefixes of words in documents, which
+        // the index analyzer yields with the offsets of the whole word.
+        let analyzer = if prefix_search {
+            self.index_analyzer.clone()
+        } else {
+            self.analyzer.clone()
+        };
+        let highlighter = Highlighter::new(
+            analyzer,
+            self.search_field_path.clone(),
+            &compiled.text_query,
+            options,
+        )?;
+        Ok(Some(highlighter))
+    }
+
+    /// Builds a counter for the values of filter fields in the query's results
+    /// if its search filter asked for facets.
+    pub fn facet_counter(&self, query: &InternalSearch) -> anyhow::Result<Option<FacetCounter>> {
+        let facets = query.filters.iter().find_map(|filter| match filter {
+            InternalSearchFilterExpression::Search(_, _, options) => options.facets.clone(),
+            InternalSearchFilterExpression::Eq(..) | InternalSearchFilterExpression::Range(..) => {
+                None
+            },
+        });
+        let Some(options) = facets else {
+            return Ok(None);
+        };
+        if let Some(field_path) = options
+            .fields
+            .iter()
+            .find(|field_path| !self.filter_fields.contains_key(field_path))
+        {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "IncorrectFilterFieldError",
+                format!(
+                    "Search query against {} asks for facet counts of {field_path:?} but that \
+                     field isn't indexed for filtering in `filterFields`.",
+                    query.printable_index_name()?,
+                )
+            ))
+        }
+        Ok(Some(FacetCounter::new(options)))
+    }
+}
+
+/// The most typos we'll tolerate for a query token of this length, since short
+/// tokens with typos match too many unrelated terms.
+fn max_typos_for_token(token: &str) -> u32 {
+    let char_count = token.chars().count();
+    if char_count <= EXACT_SEARCH_MAX_WORD_LENGTH {
+        0
+    } else if char_count <= SINGLE_TYPO_SEARCH_MAX_WORD_LENGTH {
+        1
+    } else {
+        2
+    }
 }
 
 pub struct DocumentLengths {
@@ -848,10 +1120,10 @@ mod test {
     /// tantivy.
     #[test]
     fn test_field_ids_dont_change() -> anyhow::Result<()> {
-        let schema = TantivySearchIndexSchema::new(&DeveloperTextIndexConfig {
-            search_field: "mySearchField".parse()?,
-            filter_fields: BTreeSet::new(),
-        });
+        let schema = TantivySearchIndexSchema::new(&DeveloperTextIndexConfig::new(
+            "mySearchField".parse()?,
+            BTreeSet::new(),
+        ));
         assert_eq!(schema.internal_id_field.field_id(), 0);
         assert_eq!(schema.ts_field.field_id(), 1);
         assert_eq!(schema.creation_time_field.field_id(), 2);
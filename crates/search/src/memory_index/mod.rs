@@ -59,11 +59,16 @@ use crate::{
         term_table::TermTable,
     },
     metrics,
+    positional::{
+        positional_boost,
+        PositionalConstraint,
+    },
     query::{
         shortlist_and_id_mapping,
         CandidateRevisionPositions,
         CompiledFilterCondition,
         CompiledQuery,
+        CompiledRangeFilter,
         QueryTerm,
         ShortlistId,
         TermListBitsetQuery,
@@ -504,6 +509,8 @@ impl MemoryTextIndex {
         &self,
         and_terms: &[Term],
         or_terms: &[OrTerm],
+        positional_constraints: &[PositionalConstraint],
+        range_filters: &[CompiledRangeFilter],
         stats: &Bm25Stats,
     ) -> anyhow::Result<Option<PreparedMemoryPostingListQuery>> {
         let _timer = metrics::index_prepare_posting_list_query_timer();
@@ -538,6 +545,25 @@ impl MemoryTextIndex {
         if weights_by_union_id.is_empty() {
             return Ok(None);
         }
+        // A document can only satisfy a constraint if it has all of its terms.
+        let Some(positional_constraints) = positional_constraints
+            .iter()
+            .map(|c| c.try_map(|term| self.term_table.get(term)))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return Ok(None);
+        };
+        // Like AND terms, a range without any terms can't match any documents.
+        let mut range_terms = Vec::with_capacity(range_filters.len());
+        for range_filter in range_filters {
+            let term_ids = self
+                .term_table
+                .terms_in_range(range_filter.field, &range_filter.range);
+            if term_ids.is_empty() {
+                return Ok(None);
+            }
+            range_terms.push(term_ids);
+        }
 
         anyhow::ensure!(all_term_ids.len() <= MAX_UNIQUE_QUERY_TERMS);
         let mut intersection_terms = Bitset64::new();
@@ -557,6 +583,8 @@ impl MemoryTextIndex {
             intersection_terms,
             union_terms,
             union_weights,
+            positional_constraints,
+            range_terms,
         };
         Ok(Some(prepared))
     }
@@ -608,11 +636,16 @@ impl MemoryTextIndex {
             let Some(bm25_score) = maybe_score else {
                 continue;
             };
+            let Some(boost) = positional_boost(&query.positional_constraints, |&term_id| {
+                document.term_list.term_positions(term_id)
+            }) else {
+                continue;
+            };
             let m = PostingListMatch {
                 internal_id,
                 ts: document.ts,
                 creation_time: document.creation_time,
-                bm25_score,
+                bm25_score: bm25_score * boost,
             };
             // NB: Since we're scanning over all of `self.documents` and they're not in BM25
             // score order, we can't early return if we've filled up `results` and
@@ -937,6 +970,12 @@ pub struct PreparedMemoryPostingListQuery {
 
     // BM25 weights corresponding to each element in `union_terms`.
     pub union_weights: Vec<Bm25Weight>,
+
+    pub positional_constraints: Vec<PositionalConstraint<TermId>>,
+
+    /// The terms in each range filter's range. Documents must have at least
+    /// one term from each.
+    pub range_terms: Vec<BTreeSet<TermId>>,
 }
 
 impl PreparedMemoryPostingListQuery {
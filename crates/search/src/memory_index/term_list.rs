@@ -275,7 +275,7 @@ impl TermList {
         let Some(ref inner) = self.inner else {
             return false;
         };
-        if !inner.term_filter_matches2(query) {
+        if !inner.term_filter_matches2(query) || !self.range_filters_match2(query) {
             return false;
         }
         // Build up a bitset of which terms match.
@@ -297,7 +297,7 @@ impl TermList {
         num_search_tokens: u32,
     ) -> Option<Score> {
         let inner = self.inner.as_ref()?;
-        if !inner.term_filter_matches2(query) {
+        if !inner.term_filter_matches2(query) || !self.range_filters_match2(query) {
             return None;
         }
 
@@ -325,6 +325,30 @@ impl TermList {
         (all_intersection && any_union).then_some(score)
     }
 
+    // Check that the document has a term in each of the query's range filters.
+    fn range_filters_match2(&self, query: &PreparedMemoryPostingListQuery) -> bool {
+        query.range_terms.iter().all(|range_terms| {
+            self.iter_terms()
+                .any(|term_id| range_terms.contains(&term_id))
+        })
+    }
+
+    /// The sorted positions of a term in the document, or `None` if the
+    /// document doesn't contain it.
+    pub fn term_positions(&self, term_id: TermId) -> Option<Vec<u32>> {
+        let inner = self.inner.as_ref()?;
+        if !inner.term_filter.contains(&(term_id as u64)) {
+            return None;
+        }
+        let (_, pos) = inner.term_matches(&[term_id]).next()?;
+        let positions_end = inner.cumulative_freqs.select(pos)?;
+        let positions_start = positions_end - inner.cumulative_freqs.delta(pos)?;
+        let positions = (positions_start..positions_end)
+            .map(|i| inner.positions.access(i).unwrap() as u32)
+            .collect();
+        Some(positions)
+    }
+
     // Check if a query matches the given document, and compute its BM25 score if
     // so.
     //
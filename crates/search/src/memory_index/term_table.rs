@@ -8,19 +8,26 @@ use std::{
     },
 };
 
+use common::query::FilterRange;
 use imbl_slab::{
     Slab,
     SlabKey,
 };
 use ref_cast::RefCast;
 use tantivy::{
-    schema::Type,
+    schema::{
+        Field,
+        Type,
+    },
     Term,
 };
 
 use crate::{
     aggregation::TokenMatchAggregator,
-    levenshtein_dfa::build_fuzzy_dfa,
+    levenshtein_dfa::{
+        build_fuzzy_dfa,
+        shares_first_char,
+    },
     memory_index::{
         art::ART,
         small_slice::SmallSlice,
@@ -119,6 +126,18 @@ impl TermTable {
         self.index.get(TermRef::ref_cast(term)).cloned()
     }
 
+    /// The terms of `field` whose values are in `range`.
+    pub fn terms_in_range(&self, field: Field, range: &FilterRange) -> BTreeSet<TermId> {
+        self.terms
+            .iter()
+            .filter(|(_, entry)| {
+                let term = Term::wrap(&entry.term[..]);
+                term.field() == field && range.contains(term.value_bytes())
+            })
+            .map(|(term_id, _)| term_id)
+            .collect()
+    }
+
     pub fn get_fuzzy(
         &self,
         term: &Term,
@@ -167,7 +186,7 @@ impl TermTable {
                 } else {
                     // TODO: There's a bug here where skipping a prefix allows
                     // matching terms for other fields!
-                    assert!(query.term.as_str().is_some());
+                    let term_str = query.term.as_str().expect("Fuzzy query term not a string");
                     for (_, match_distance, match_term) in
                         self.get_fuzzy(&query.term, distance as u8, prefix)
                     {
@@ -178,6 +197,13 @@ impl TermTable {
                         if distance != match_distance {
                             continue;
                         }
+                        if distance > 0
+                            && !match_term
+                                .as_str()
+                                .is_some_and(|m| shares_first_char(term_str, m))
+                        {
+                            continue;
+                        }
                         seen_terms.insert(match_term.clone());
                         let m = TokenMatch {
                             distance,
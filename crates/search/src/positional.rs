@@ -0,0 +1,371 @@
+use anyhow::Context;
+use errors::ErrorMetadata;
+use itertools::Itertools;
+use pb::searchlight::{
+    positional_constraint::Constraint as ConstraintProto,
+    NearConstraint,
+    PhraseConstraint,
+};
+use tantivy::{
+    tokenizer::TextAnalyzer,
+    Score,
+    Term,
+};
+
+use crate::constants::{
+    MAX_NEAR_DISTANCE,
+    PROXIMITY_BOOST,
+};
+
+/// A requirement on where a query's terms appear relative to each other in a
+/// document, from a quoted phrase or a `NEAR/k` operator in the search text.
+/// Documents that don't satisfy it don't match, and the ones that do have
+/// their score boosted by how close together the terms are.
+///
+/// Constraints are generic over how terms are represented so the memory
+/// index can use its `TermId`s.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PositionalConstraint<T = Term> {
+    /// The terms appear at consecutive positions, in order.
+    Phrase(Vec<T>),
+    /// The two terms appear in either order with at most `max_distance`
+    /// positions between them, so `NEAR/1` matches adjacent terms.
+    Near {
+        left: T,
+        right: T,
+        max_distance: u32,
+    },
+}
+
+impl<T> PositionalConstraint<T> {
+    pub fn terms(&self) -> Vec<&T> {
+        match self {
+            Self::Phrase(terms) => terms.iter().collect(),
+            Self::Near { left, right, .. } => vec![left, right],
+        }
+    }
+
+    /// Maps each term, returning `None` if any term can't be mapped.
+    pub fn try_map<U>(
+        &self,
+        mut f: impl FnMut(&T) -> Option<U>,
+    ) -> Option<PositionalConstraint<U>> {
+        let constraint = match self {
+            Self::Phrase(terms) => {
+                PositionalConstraint::Phrase(terms.iter().map(f).collect::<Option<_>>()?)
+            },
+            Self::Near {
+                left,
+                right,
+                max_distance,
+            } => PositionalConstraint::Near {
+                left: f(left)?,
+                right: f(right)?,
+                max_distance: *max_distance,
+            },
+        };
+        Some(constraint)
+    }
+
+    /// Checks the constraint against a document, where `positions` returns
+    /// the sorted positions of a term in the document's search field or `None`
+    /// if the term isn't present. Returns the score multiplier if the document
+    /// satisfies the constraint.
+    pub fn boost(&self, mut positions: impl FnMut(&T) -> Option<Vec<u32>>) -> Option<Score> {
+        let slack = match self {
+            Self::Phrase(terms) => {
+                let term_positions = terms
+                    .iter()
+                    .map(&mut positions)
+                    .collect::<Option<Vec<_>>>()?;
+                let (first, rest) = term_positions.split_first()?;
+                let is_match = first.iter().any(|&start| {
+                    rest.iter()
+                        .enumerate()
+                        .all(|(i, p)| p.binary_search(&(start + i as u32 + 1)).is_ok())
+                });
+                if !is_match {
+                    return None;
+                }
+                0
+            },
+            Self::Near {
+                left,
+                right,
+                max_distance,
+            } => {
+                let gap = min_gap(&positions(left)?, &positions(right)?)?;
+                if gap > *max_distance {
+                    return None;
+                }
+                gap - 1
+            },
+        };
+        Some(1. + PROXIMITY_BOOST / (1 + slack) as f32)
+    }
+}
+
+/// Checks all of a query's constraints against a document, returning the
+/// product of their score multipliers if they're all satisfied.
+pub fn positional_boost<T>(
+    constraints: &[PositionalConstraint<T>],
+    mut positions: impl FnMut(&T) -> Option<Vec<u32>>,
+) -> Option<Score> {
+    let mut boost = 1.;
+    for constraint in constraints {
+        boost *= constraint.boost(&mut positions)?;
+    }
+    Some(boost)
+}
+
+/// The smallest nonzero distance between a position in `left` and one in
+/// `right`. Both must be sorted.
+fn min_gap(left: &[u32], right: &[u32]) -> Option<u32> {
+    left.iter()
+        .filter_map(|&a| {
+            let after = right.partition_point(|&b| b <= a);
+            let before = right.partition_point(|&b| b < a);
+            let gap_after = right.get(after).map(|&b| b - a);
+            let gap_before = before.checked_sub(1).map(|i| a - right[i]);
+            gap_after.into_iter().chain(gap_before).min()
+        })
+        .min()
+}
+
+impl TryFrom<pb::searchlight::PositionalConstraint> for PositionalConstraint {
+    type Error = anyhow::Error;
+
+    fn try_from(value: pb::searchlight::PositionalConstraint) -> Result<Self, Self::Error> {
+        let constraint = match value.constraint.context("Missing constraint")? {
+            ConstraintProto::Phrase(PhraseConstraint { terms }) => {
+                PositionalConstraint::Phrase(terms.into_iter().map(Term::wrap).collect())
+            },
+            ConstraintProto::Near(NearConstraint {
+                left,
+                right,
+                max_distance,
+            }) => PositionalConstraint::Near {
+                left: Term::wrap(left.context("Missing left")?),
+                right: Term::wrap(right.context("Missing right")?),
+                max_distance: max_distance.context("Missing max_distance")?,
+            },
+        };
+        Ok(constraint)
+    }
+}
+
+impl From<PositionalConstraint> for pb::searchlight::PositionalConstraint {
+    fn from(value: PositionalConstraint) -> Self {
+        let constraint = match value {
+            PositionalConstraint::Phrase(terms) => ConstraintProto::Phrase(PhraseConstraint {
+                terms: terms.into_iter().map(|t| t.as_slice().to_vec()).collect(),
+            }),
+            PositionalConstraint::Near {
+                left,
+                right,
+                max_distance,
+            } => ConstraintProto::Near(NearConstraint {
+                left: Some(left.as_slice().to_vec()),
+                right: Some(right.as_slice().to_vec()),
+                max_distance: Some(max_distance),
+            }),
+        };
+        Self {
+            constraint: Some(constraint),
+        }
+    }
+}
+
+/// Search text split into tokens, along with the positional constraints
+/// between them.
+#[derive(Debug, Default)]
+pub struct ParsedSearchText {
+    pub tokens: Vec<String>,
+    /// Constraints on `tokens`, by index.
+    pub constraints: Vec<PositionalConstraint<usize>>,
+    /// Whether we dropped tokens past the limit.
+    pub truncated: bool,
+}
+
+impl ParsedSearchText {
+    /// Parses search text where `"quoted phrases"` must match exactly and
+    /// `a NEAR/k b` requires `a` and `b` to be within `k` positions of each
+    /// other. Everything else is tokenized as plain search terms, and at most
+    /// `max_tokens` tokens are kept.
+    pub fn parse(analyzer: &TextAnalyzer, text: &str, max_tokens: usize) -> anyhow::Result<Self> {
+        let mut parsed = Self::default();
+        let mut pending_near = None;
+        // Splitting on quotes alternates between unquoted and quoted text. An
+        // unterminated quote runs to the end of the text.
+        for (i, segment) in text.split('"').enumerate() {
+            if i % 2 == 1 {
+                let start = parsed.tokens.len();
+                parsed.push_tokens(analyzer, segment, max_tokens, &mut pending_near);
+                if parsed.tokens.len() - start > 1 {
+                    let phrase = (start..parsed.tokens.len()).collect();
+                    parsed
+                        .constraints
+                        .push(PositionalConstraint::Phrase(phrase));
+                }
+                continue;
+            }
+            for word in segment.split_whitespace() {
+                let Some(max_distance) = parse_near_operator(word)? else {
+                    parsed.push_tokens(analyzer, word, max_tokens, &mut pending_near);
+                    continue;
+                };
+                pending_near = parsed
+                    .tokens
+                    .len()
+                    .checked_sub(1)
+                    .map(|left| (left, max_distance));
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn push_tokens(
+        &mut self,
+        analyzer: &TextAnalyzer,
+        text: &str,
+        max_tokens: usize,
+        pending_near: &mut Option<(usize, u32)>,
+    ) {
+        let mut token_stream = analyzer.token_stream(text);
+        while let Some(token) = token_stream.next() {
+            if self.tokens.len() == max_tokens {
+                self.truncated = true;
+                return;
+            }
+            if let Some((left, max_distance)) = pending_near.take() {
+                self.constraints.push(PositionalConstraint::Near {
+                    left,
+                    right: self.tokens.len(),
+                    max_distance,
+                });
+            }
+            self.tokens.push(token.text.clone());
+        }
+    }
+
+    /// The indexes of tokens that are part of a constraint. These need to
+    /// match exactly.
+    pub fn constrained_tokens(&self) -> Vec<usize> {
+        self.constraints
+            .iter()
+            .flat_map(|c| c.terms())
+            .copied()
+            .sorted()
+            .dedup()
+            .collect()
+    }
+}
+
+/// Parses a `NEAR/k` operator, which must be uppercase like in SQLite's FTS.
+fn parse_near_operator(word: &str) -> anyhow::Result<Option<u32>> {
+    let Some(distance) = word.strip_prefix("NEAR/") else {
+        return Ok(None);
+    };
+    let max_distance = distance
+        .parse::<u32>()
+        .ok()
+        .filter(|k| (1..=MAX_NEAR_DISTANCE).contains(k));
+    let Some(max_distance) = max_distance else {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "InvalidSearchQuery",
+            format!(
+                "Invalid proximity operator {word:?} in search query. Use NEAR/k where k is \
+                 between 1 and {MAX_NEAR_DISTANCE}."
+            ),
+        ));
+    };
+    Ok(Some(max_distance))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        min_gap,
+        positional_boost,
+        ParsedSearchText,
+        PositionalConstraint,
+    };
+    use crate::convex_en;
+
+    fn parse(text: &str) -> anyhow::Result<ParsedSearchText> {
+        ParsedSearchText::parse(&convex_en(), text, 16)
+    }
+
+    #[test]
+    fn test_parse_phrases_and_near() -> anyhow::Result<()> {
+        let parsed = parse(r#"the "Quick brown" fox NEAR/3 dog "lazy""#)?;
+        assert_eq!(
+            parsed.tokens,
+            vec!["the", "quick", "brown", "fox", "dog", "lazy"]
+        );
+        assert_eq!(
+            parsed.constraints,
+            vec![
+                PositionalConstraint::Phrase(vec![1, 2]),
+                PositionalConstraint::Near {
+                    left: 3,
+                    right: 4,
+                    max_distance: 3
+                },
+            ]
+        );
+        assert_eq!(parsed.constrained_tokens(), vec![1, 2, 3, 4]);
+
+        // Plain text parses the same way it tokenizes.
+        let parsed = parse("near/3 NEAR quick-brown")?;
+        assert_eq!(parsed.tokens, vec!["near", "3", "near", "quick", "brown"]);
+        assert!(parsed.constraints.is_empty());
+
+        // A dangling operator is ignored.
+        assert!(parse("NEAR/2 fox")?.constraints.is_empty());
+        assert!(parse("NEAR/0 fox").is_err());
+        assert!(parse("a NEAR/x b").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_truncates_tokens() -> anyhow::Result<()> {
+        let parsed = ParsedSearchText::parse(&convex_en(), r#"a b "c d""#, 3)?;
+        assert_eq!(parsed.tokens, vec!["a", "b", "c"]);
+        assert!(parsed.truncated);
+        assert!(parsed.constraints.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_positional_boost() {
+        let positions = |term: &&str| match *term {
+            "quick" => Some(vec![1, 7]),
+            "brown" => Some(vec![2]),
+            "fox" => Some(vec![5]),
+            _ => None,
+        };
+        let phrase = PositionalConstraint::Phrase(vec!["quick", "brown"]);
+        let reversed = PositionalConstraint::Phrase(vec!["brown", "quick"]);
+        let near = |max_distance| PositionalConstraint::Near {
+            left: "fox",
+            right: "quick",
+            max_distance,
+        };
+        assert!(positional_boost(&[phrase.clone()], positions).is_some());
+        assert!(positional_boost(&[reversed], positions).is_none());
+        assert!(positional_boost(&[near(1)], positions).is_none());
+        let near_boost = positional_boost(&[near(2)], positions).unwrap();
+        let phrase_boost = positional_boost(&[phrase], positions).unwrap();
+        assert!(phrase_boost > near_boost);
+        assert!(near_boost > 1.);
+    }
+
+    #[test]
+    fn test_min_gap() {
+        assert_eq!(min_gap(&[5, 6], &[5, 9]), Some(1));
+        assert_eq!(min_gap(&[1, 3], &[1, 3]), Some(2));
+        assert_eq!(min_gap(&[4], &[4]), None);
+        assert_eq!(min_gap(&[10], &[1, 20]), Some(9));
+    }
+}
@@ -10,12 +10,18 @@ use std::{
 use anyhow::Context;
 use bitvec::vec::BitVec;
 use common::{
+    bootstrap_model::index::text_index::TextAnalyzerConfig,
     document::{
         CreationTime,
         PackedDocument,
     },
     index::IndexKeyBytes,
-    query::FilterValue,
+    query::{
+        search_result_index_key,
+        FilterRange,
+        FilterValue,
+        SearchAfter,
+    },
     types::{
         SubscriberId,
         TabletIndexName,
@@ -52,12 +58,15 @@ use value::{
 };
 
 use crate::{
-    convex_en,
+    analyzer::index_text_analyzer,
+    constants::MAX_EDIT_DISTANCE,
+    levenshtein_dfa::build_fuzzy_dfa,
     memory_index::{
         art::ART,
         TermId,
     },
     metrics,
+    positional::PositionalConstraint,
     scoring::term_from_str,
     EditDistance,
 };
@@ -67,6 +76,9 @@ use crate::{
 pub struct CompiledQuery {
     pub text_query: Vec<QueryTerm>,
     pub filter_conditions: Vec<CompiledFilterCondition>,
+    pub positional_constraints: Vec<PositionalConstraint>,
+    pub range_filters: Vec<CompiledRangeFilter>,
+    pub search_after: Option<SearchAfter>,
 }
 
 impl CompiledQuery {
@@ -90,6 +102,19 @@ impl CompiledQuery {
                 // TODO(CX-5481): get rid of this `Term::wrap` call. Need to propagate the Field for these.
                 .map(|bytes| CompiledFilterCondition::Must(Term::wrap(bytes)))
                 .collect_vec(),
+            positional_constraints: value
+                .positional_constraints
+                .into_iter()
+                .map(PositionalConstraint::try_from)
+                .collect::<anyhow::Result<Vec<_>>>()?,
+            range_filters: value
+                .range_filters
+                .into_iter()
+                .map(CompiledRangeFilter::try_from)
+                .collect::<anyhow::Result<Vec<_>>>()?,
+            search_after: value
+                .search_after
+                .map(|index_key| SearchAfter::new(IndexKeyBytes(index_key))),
         })
     }
 }
@@ -107,6 +132,19 @@ impl From<CompiledQuery> for pb::searchlight::TextQuery {
                 .into_iter()
                 .map(|CompiledFilterCondition::Must(term)| term.as_slice().to_vec())
                 .collect_vec(),
+            positional_constraints: value
+                .positional_constraints
+                .into_iter()
+                .map(pb::searchlight::PositionalConstraint::from)
+                .collect_vec(),
+            range_filters: value
+                .range_filters
+                .into_iter()
+                .map(pb::searchlight::RangeFilter::from)
+                .collect_vec(),
+            search_after: value
+                .search_after
+                .map(|search_after| search_after.index_key().0.clone()),
         }
     }
 }
@@ -114,14 +152,20 @@ impl From<CompiledQuery> for pb::searchlight::TextQuery {
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct QueryTerm {
     term: Term,
+    /// The maximum edit distance of terms this term matches.
+    max_distance: u32,
     /// If the term is the last in a search query, it can be a prefix match for
     /// typeahead suggestions.
     prefix: bool,
 }
 
 impl QueryTerm {
-    pub fn new(term: Term, prefix: bool) -> Self {
-        QueryTerm { term, prefix }
+    pub fn new(term: Term, max_distance: u32, prefix: bool) -> Self {
+        QueryTerm {
+            term,
+            max_distance,
+            prefix,
+        }
     }
 
     pub fn term(&self) -> &Term {
@@ -133,7 +177,7 @@ impl QueryTerm {
     }
 
     pub fn max_distance(&self) -> u32 {
-        0
+        self.max_distance
     }
 
     pub fn prefix(&self) -> bool {
@@ -148,11 +192,16 @@ impl QueryTerm {
             None => anyhow::bail!("No TermType in QueryTerm"),
             Some(pb::searchlight::text_query_term::TermType::Exact(exact)) => QueryTerm {
                 term: Term::from_field_text(search_field, &exact.token),
+                max_distance: 0,
                 prefix: false,
             },
-            Some(pb::searchlight::text_query_term::TermType::Fuzzy(fuzzy)) => QueryTerm {
-                term: Term::from_field_text(search_field, &fuzzy.token),
-                prefix: fuzzy.prefix,
+            Some(pb::searchlight::text_query_term::TermType::Fuzzy(fuzzy)) => {
+                anyhow::ensure!(fuzzy.max_distance <= MAX_EDIT_DISTANCE);
+                QueryTerm {
+                    term: Term::from_field_text(search_field, &fuzzy.token),
+                    max_distance: fuzzy.max_distance,
+                    prefix: fuzzy.prefix,
+                }
             },
         };
         Ok(qterm)
@@ -168,10 +217,10 @@ impl TryFrom<QueryTerm> for TextQueryTerm {
             .as_str()
             .context("Term was not a string")?
             .to_string();
-        let text_query_term = if value.prefix {
+        let text_query_term = if value.prefix || value.max_distance > 0 {
             TextQueryTerm::Fuzzy {
                 token: term,
-                max_distance: 0.try_into()?,
+                max_distance: u8::try_from(value.max_distance)?.try_into()?,
                 prefix: value.prefix,
             }
         } else {
@@ -186,10 +235,10 @@ impl From<QueryTerm> for pb::searchlight::TextQueryTerm {
         let term = value.term();
         let term_str = term.as_str().expect("QueryTerm not a string").to_string();
 
-        let term_type = if value.prefix {
+        let term_type = if value.prefix || value.max_distance > 0 {
             pb::searchlight::text_query_term::TermType::Fuzzy(pb::searchlight::FuzzyTextTerm {
                 token: term_str,
-                max_distance: 0,
+                max_distance: value.max_distance,
                 prefix: value.prefix,
             })
         } else {
@@ -482,6 +531,43 @@ pub enum CompiledFilterCondition {
     Must(Term),
 }
 
+/// Matches documents whose value for a filter field is in a range. Unlike
+/// `CompiledFilterCondition`s, which match a single term, these match every
+/// term of the field in the range.
+#[derive(Debug, Clone)]
+pub struct CompiledRangeFilter {
+    pub field: Field,
+    pub range: FilterRange,
+}
+
+impl TryFrom<pb::searchlight::RangeFilter> for CompiledRangeFilter {
+    type Error = anyhow::Error;
+
+    fn try_from(value: pb::searchlight::RangeFilter) -> anyhow::Result<Self> {
+        Ok(Self {
+            field: Field::from_field_id(value.field.context("Missing field")?),
+            range: FilterRange {
+                lower: value.lower.context("Missing lower")?.into(),
+                lower_inclusive: value.lower_inclusive.context("Missing lower_inclusive")?,
+                upper: value.upper.context("Missing upper")?.into(),
+                upper_inclusive: value.upper_inclusive.context("Missing upper_inclusive")?,
+            },
+        })
+    }
+}
+
+impl From<CompiledRangeFilter> for pb::searchlight::RangeFilter {
+    fn from(value: CompiledRangeFilter) -> Self {
+        Self {
+            field: Some(value.field.field_id()),
+            lower: Some(value.range.lower.into()),
+            lower_inclusive: Some(value.range.lower_inclusive),
+            upper: Some(value.range.upper.into()),
+            upper_inclusive: Some(value.range.upper_inclusive),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
 pub struct CandidateRevision {
@@ -491,6 +577,13 @@ pub struct CandidateRevision {
     pub creation_time: CreationTime,
 }
 
+impl CandidateRevision {
+    /// The index key of this result if it had the given score.
+    pub fn index_key(&self, score: f64) -> IndexKeyBytes {
+        search_result_index_key(score, f64::from(self.creation_time), &self.id.0)
+    }
+}
+
 impl From<CandidateRevision> for pb::searchlight::CandidateRevision {
     fn from(revision: CandidateRevision) -> Self {
         let ts: Option<u64> = match revision.ts {
@@ -715,25 +808,61 @@ impl HeapSize for TextQueryTermRead {
 #[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
 pub enum FilterConditionRead {
     Must(FieldPath, FilterValue),
+    Range(FieldPath, FilterRange),
+}
+
+impl FilterConditionRead {
+    fn matches(&self, document: &PackedDocument) -> bool {
+        let document_value = |field_path: &FieldPath| {
+            let document_value = document.value().get_path(field_path);
+            FilterValue::from_search_value(document_value.as_ref())
+        };
+        match self {
+            FilterConditionRead::Must(field_path, filter_value) => {
+                document_value(field_path) == *filter_value
+            },
+            FilterConditionRead::Range(field_path, range) => {
+                range.contains(&document_value(field_path))
+            },
+        }
+    }
 }
 
 impl HeapSize for FilterConditionRead {
     fn heap_size(&self) -> usize {
         match self {
             FilterConditionRead::Must(p, v) => p.heap_size() + v.heap_size(),
+            FilterConditionRead::Range(p, r) => p.heap_size() + r.heap_size(),
         }
     }
 }
 
+/// The reads of the text searches a transaction ran against one index.
+///
+/// A write overlaps the reads if the old or new document could be in the
+/// results of one of the searches: it has to match every filter condition of
+/// that search and, if the search had any terms, match one of its terms (with
+/// the same typo tolerance and prefix matching as the search). This is exact
+/// for whether a document matches a search, but writes that only change the
+/// index's BM25 statistics (like inserting a document that doesn't match) don't
+/// overlap, even though they can slightly change the scores of the results.
 #[derive(Debug, Clone)]
 pub struct QueryReads {
     pub text_queries: WithHeapSize<Vec<TextQueryTermRead>>,
     pub filter_conditions: WithHeapSize<Vec<FilterConditionRead>>,
+    /// Where each search merged into these reads ends in `text_queries` and
+    /// `filter_conditions`, so we only combine terms and filter conditions
+    /// from the same search.
+    search_ends: Vec<(usize, usize)>,
+    /// The analyzer of the index that was searched, which we need to tokenize
+    /// documents the same way when checking them against the reads.
+    analyzer: TextAnalyzerConfig,
 
     // State derived from text_queries for more efficient matching with many
-    // fuzzy text subscriptions. Because this is strictly derived, it can always
-    // be reconstructed from the simpler text_queries / filter_conditions.
-    fuzzy_terms: SearchTermTries<()>,
+    // fuzzy text subscriptions, mapping terms to the searches they're from.
+    // Because this is strictly derived, it can always be reconstructed from
+    // the simpler text_queries / filter_conditions.
+    fuzzy_terms: SearchTermTries<usize>,
 }
 
 impl QueryReads {
@@ -741,14 +870,42 @@ impl QueryReads {
         text_queries: WithHeapSize<Vec<TextQueryTermRead>>,
         filter_conditions: WithHeapSize<Vec<FilterConditionRead>>,
     ) -> Self {
+        let analyzer = TextAnalyzerConfig::default();
         let mut fuzzy_terms = SearchTermTries::new();
-        fuzzy_terms.extend((), &text_queries);
+        fuzzy_terms.extend(0, &analyzer, &text_queries);
         Self {
+            search_ends: vec![(text_queries.len(), filter_conditions.len())],
             text_queries,
             filter_conditions,
+            analyzer,
             fuzzy_terms,
         }
     }
+
+    pub fn with_analyzer(self, analyzer: TextAnalyzerConfig) -> Self {
+        let mut fuzzy_terms = SearchTermTries::new();
+        for (i, (text_queries, _)) in self.searches().enumerate() {
+            fuzzy_terms.extend(i, &analyzer, text_queries);
+        }
+        Self {
+            analyzer,
+            fuzzy_terms,
+            ..self
+        }
+    }
+
+    /// The terms and filter conditions of each search in these reads.
+    fn searches(&self) -> impl Iterator<Item = (&[TextQueryTermRead], &[FilterConditionRead])> {
+        let starts = [(0, 0)].into_iter().chain(self.search_ends.iter().copied());
+        starts.zip(self.search_ends.iter()).map(
+            move |((text_start, filter_start), &(text_end, filter_end))| {
+                (
+                    &self.text_queries[text_start..text_end],
+                    &self.filter_conditions[filter_start..filter_end],
+                )
+            },
+        )
+    }
 }
 
 #[cfg(any(test, feature = "testing"))]
@@ -761,16 +918,20 @@ impl Arbitrary for QueryReads {
         any::<(
             WithHeapSize<Vec<TextQueryTermRead>>,
             WithHeapSize<Vec<FilterConditionRead>>,
+            TextAnalyzerConfig,
         )>()
-        .prop_map(|(text_queries, filter_conditions)| {
-            QueryReads::new(text_queries, filter_conditions)
+        .prop_map(|(text_queries, filter_conditions, analyzer)| {
+            QueryReads::new(text_queries, filter_conditions).with_analyzer(analyzer)
         })
     }
 }
 
 impl PartialEq for QueryReads {
     fn eq(&self, other: &Self) -> bool {
-        self.text_queries == other.text_queries && self.filter_conditions == other.filter_conditions
+        self.text_queries == other.text_queries
+            && self.filter_conditions == other.filter_conditions
+            && self.search_ends == other.search_ends
+            && self.analyzer == other.analyzer
     }
 }
 
@@ -785,7 +946,7 @@ impl HeapSize for QueryReads {
 
 #[derive(Debug, Clone)]
 struct SearchTermTries<T: Clone + Ord> {
-    terms: BTreeMap<FieldPath, Tries<T>>,
+    terms: BTreeMap<(TextAnalyzerConfig, FieldPath), Tries<T>>,
 }
 
 impl<T: Clone + Ord> SearchTermTries<T> {
@@ -795,36 +956,27 @@ impl<T: Clone + Ord> SearchTermTries<T> {
         }
     }
 
+    /// Adds the values of the terms that match a token in `document` to
+    /// `result`.
     #[fastrace::trace]
-    fn overlaps_document<'a>(
-        &'a self,
-        document: &'a PackedDocument,
-        analyzer: &'a TextAnalyzer,
-    ) -> bool {
-        let mut result = BTreeSet::new();
-
-        for (path, tries) in self.terms.iter() {
+    fn matching_values(&self, document: &PackedDocument, result: &mut BTreeSet<T>) {
+        for ((analyzer, path), tries) in self.terms.iter() {
             let Some(ConvexValue::String(document_text)) = document.value().get_path(path) else {
                 continue;
             };
 
-            let mut tokens = ValueTokens::new(analyzer, &document_text);
-            tries.matching_values(&mut tokens, &mut result);
-            if !result.is_empty() {
-                return true;
-            }
+            let mut tokens = ValueTokens::new(&index_text_analyzer(analyzer), &document_text);
+            tries.matching_values(&mut tokens, result);
         }
-
-        false
     }
 
-    fn extend(&mut self, value: T, queries: &WithHeapSize<Vec<TextQueryTermRead>>) {
+    fn extend(&mut self, value: T, analyzer: &TextAnalyzerConfig, queries: &[TextQueryTermRead]) {
         for text_query in queries {
             let path = &text_query.field_path;
             let (token, max_distance, prefix) = text_query.term.fuzzy_params();
             let art = self
                 .terms
-                .entry(path.clone())
+                .entry((analyzer.clone(), path.clone()))
                 .or_insert_with(Tries::new)
                 .tries
                 .entry((prefix, max_distance))
@@ -838,14 +990,14 @@ impl<T: Clone + Ord> SearchTermTries<T> {
         }
     }
 
-    fn remove(&mut self, value: T, queries: &WithHeapSize<Vec<TextQueryTermRead>>) {
+    fn remove(&mut self, value: T, analyzer: &TextAnalyzerConfig, queries: &[TextQueryTermRead]) {
         for text_query in queries {
             let path = &text_query.field_path;
             let (token, max_distance, prefix) = text_query.term.fuzzy_params();
             let value = value.clone();
             let tries = self
                 .terms
-                .get_mut(path)
+                .get_mut(&(analyzer.clone(), path.clone()))
                 .unwrap_or_else(|| panic!("Missing tries for {}", path));
             let trie = tries
                 .tries
@@ -890,12 +1042,23 @@ impl<T: Clone> Tries<T> {
 
 impl<T: Clone + Ord> Tries<T> {
     fn matching_values(&self, tokens: &mut ValueTokens, result: &mut BTreeSet<T>) {
-        for ((prefix, _max_distance), trie) in self.tries.iter() {
+        for ((prefix, max_distance), trie) in self.tries.iter() {
             // Prefixing is handled by constructing prefix tokens in ValueTokens (see the
             // notes there), so we can get away with a symmetric search where the dfa's
             // prefix is always set to false.
             tokens.for_each_token(*prefix, |token| {
-                if let Some(value) = trie.get(token) {
+                if *max_distance == 0 {
+                    if let Some(value) = trie.get(token) {
+                        result.extend(value.keys().cloned());
+                    }
+                    return;
+                }
+                // Edit distance is symmetric, so the terms within the distance
+                // of the document's token are the terms that match it with
+                // typos. This ignores that typos never change the first
+                // character, which only makes us overlap more often.
+                let dfa = build_fuzzy_dfa(token, *max_distance, false);
+                for (value, ..) in trie.intersect(dfa, None) {
                     result.extend(value.keys().cloned());
                 }
             });
@@ -908,13 +1071,32 @@ impl QueryReads {
         QueryReads {
             text_queries: WithHeapSize::default(),
             filter_conditions: WithHeapSize::default(),
+            search_ends: vec![],
+            analyzer: TextAnalyzerConfig::default(),
             fuzzy_terms: SearchTermTries::new(),
         }
     }
 
     pub fn merge(&mut self, other: Self) {
-        self.fuzzy_terms.extend((), &other.text_queries);
-
+        // Reads are merged per index, so they all share the index's analyzer.
+        // `empty()` doesn't know it yet, so take it from the first reads.
+        if self.search_ends.is_empty() {
+            self.analyzer = other.analyzer.clone();
+        }
+        let num_searches = self.search_ends.len();
+        for (i, (text_queries, _)) in other.searches().enumerate() {
+            self.fuzzy_terms
+                .extend(num_searches + i, &other.analyzer, text_queries);
+        }
+        let (num_text_queries, num_filter_conditions) =
+            (self.text_queries.len(), self.filter_conditions.len());
+        self.search_ends
+            .extend(other.search_ends.iter().map(|(text_end, filter_end)| {
+                (
+                    num_text_queries + text_end,
+                    num_filter_conditions + filter_end,
+                )
+            }));
         self.text_queries.extend(other.text_queries);
         self.filter_conditions.extend(other.filter_conditions);
     }
@@ -923,100 +1105,127 @@ impl QueryReads {
     pub fn overlaps_document(&self, document: &PackedDocument) -> bool {
         let _timer = metrics::query_reads_overlaps_timer();
 
-        for filter_condition in &self.filter_conditions {
-            let FilterConditionRead::Must(field_path, filter_value) = filter_condition;
-            let document_value = document.value().get_path(field_path);
-            let document_value = FilterValue::from_search_value(document_value.as_ref());
-            // If the document doesn't match the filter condition, we can skip checking
-            // fuzzy terms
-            if document_value != *filter_value {
-                metrics::log_query_reads_outcome(false);
-                return false;
+        // Tokenizing the document is the expensive part, so only do it once a
+        // search's filter conditions match.
+        let mut text_matches = None;
+        for (i, (text_queries, filter_conditions)) in self.searches().enumerate() {
+            if !filter_conditions
+                .iter()
+                .all(|filter_condition| filter_condition.matches(document))
+            {
+                continue;
+            }
+            // If there are no text queries and all filters match, this counts as an
+            // overlap.
+            let is_match = text_queries.is_empty()
+                || text_matches
+                    .get_or_insert_with(|| {
+                        let mut matches = BTreeSet::new();
+                        self.fuzzy_terms.matching_values(document, &mut matches);
+                        matches
+                    })
+                    .contains(&i);
+            if is_match {
+                metrics::log_query_reads_outcome(true);
+                return true;
             }
         }
-        // If there are no text queries and all filters match, this counts as an
-        // overlap.
-        if self.text_queries.is_empty() {
-            metrics::log_query_reads_outcome(true);
-            return true;
-        }
-        // If all the filter conditions match and there are text queries, we then check
-        // for fuzzy matches.
-        let analyzer = convex_en();
-        let is_fuzzy_match = self.fuzzy_terms.overlaps_document(document, &analyzer);
-        metrics::log_query_reads_outcome(is_fuzzy_match);
-        is_fuzzy_match
+        metrics::log_query_reads_outcome(false);
+        false
     }
 }
 
+/// The text searches of every subscription, for finding the subscriptions a
+/// write invalidates. A write invalidates a subscription under the same
+/// conditions as it overlaps the subscription's [`QueryReads`].
 pub struct TextSearchSubscriptions {
-    fuzzy_searches: BTreeMap<TabletIndexName, SearchTermTries<SubscriberId>>,
-    // TODO: Filter conditions are inefficiently searched, especially in conjunction with text
-    // searches. We should eventually optimize this simpler implementation as well.
-    filter_conditions: BTreeMap<TabletIndexName, BTreeMap<SubscriberId, Vec<FilterConditionRead>>>,
+    /// The terms of each subscribed search, mapped to the subscriber and the
+    /// search's position in its reads.
+    fuzzy_searches: BTreeMap<TabletIndexName, SearchTermTries<(SubscriberId, usize)>>,
+    // TODO: Filter conditions are inefficiently searched, especially for searches without
+    // terms. We should eventually optimize this simpler implementation as well.
+    searches: BTreeMap<TabletIndexName, BTreeMap<SubscriberId, Vec<SubscribedSearch>>>,
+}
+
+struct SubscribedSearch {
+    filter_conditions: Vec<FilterConditionRead>,
+    has_text_queries: bool,
 }
 
 impl TextSearchSubscriptions {
     pub fn new() -> Self {
         Self {
             fuzzy_searches: BTreeMap::new(),
-            filter_conditions: BTreeMap::new(),
+            searches: BTreeMap::new(),
         }
     }
 
     pub fn insert(&mut self, id: SubscriberId, index: &TabletIndexName, reads: &QueryReads) {
-        self.filter_conditions
+        let fuzzy_terms = self
+            .fuzzy_searches
             .entry(index.clone())
-            .or_default()
-            .entry(id)
-            .or_default()
-            .extend(reads.filter_conditions.to_vec());
-        self.fuzzy_searches
+            .or_insert_with(SearchTermTries::new);
+        let mut searches = vec![];
+        for (i, (text_queries, filter_conditions)) in reads.searches().enumerate() {
+            fuzzy_terms.extend((id, i), &reads.analyzer, text_queries);
+            searches.push(SubscribedSearch {
+                filter_conditions: filter_conditions.to_vec(),
+                has_text_queries: !text_queries.is_empty(),
+            });
+        }
+        let existing = self
+            .searches
             .entry(index.clone())
-            .or_insert_with(SearchTermTries::new)
-            .extend(id, &reads.text_queries)
+            .or_default()
+            .insert(id, searches);
+        assert!(
+            existing.is_none(),
+            "Subscriber already has searches on {}",
+            index
+        );
     }
 
     pub fn remove(&mut self, id: SubscriberId, index: &TabletIndexName, reads: &QueryReads) {
-        let conditions = self
-            .filter_conditions
+        let searches = self
+            .searches
             .get_mut(index)
-            .unwrap_or_else(|| panic!("Missing condition index entry for {}", index));
-        assert!(conditions.remove(&id).is_some());
-        if conditions.is_empty() {
-            self.filter_conditions.remove(index);
+            .unwrap_or_else(|| panic!("Missing search index entry for {}", index));
+        assert!(searches.remove(&id).is_some());
+        if searches.is_empty() {
+            self.searches.remove(index);
         }
         let terms = self
             .fuzzy_searches
             .get_mut(index)
             .unwrap_or_else(|| panic!("Missing fuzzy search index entry for {}", index));
-        terms.remove(id, &reads.text_queries);
+        for (i, (text_queries, _)) in reads.searches().enumerate() {
+            terms.remove((id, i), &reads.analyzer, text_queries);
+        }
     }
 
     pub fn add_matches(&self, document: &PackedDocument, to_notify: &mut BTreeSet<SubscriberId>) {
-        self.add_filter_conditions_matches(document, to_notify);
-        self.add_fuzzy_matches(document, to_notify);
-    }
-
-    fn add_filter_conditions_matches(
-        &self,
-        document: &PackedDocument,
-        to_notify: &mut BTreeSet<SubscriberId>,
-    ) {
-        for (index, filter_conditions_map) in &self.filter_conditions {
-            if *index.table() != document.id().tablet_id {
-                continue;
-            }
-
-            for (subscriber_id, filter_conditions) in filter_conditions_map {
-                for filter_condition in filter_conditions {
-                    let FilterConditionRead::Must(field_path, filter_value) = filter_condition;
-                    let document_value = document.value().get_path(field_path);
-                    let document_value = FilterValue::from_search_value(document_value.as_ref());
-
-                    if document_value == *filter_value {
+        for (index, searches) in self
+            .searches
+            .iter()
+            .filter(|(index, _)| *index.table() == document.id().tablet_id)
+        {
+            // Searches with terms only match documents that match one of their
+            // terms, which we can find without checking every search.
+            let mut text_matches = BTreeSet::new();
+            self.add_fuzzy_matches(index, document, &mut text_matches);
+            for (subscriber_id, searches) in searches {
+                for (i, search) in searches.iter().enumerate() {
+                    if search.has_text_queries && !text_matches.contains(&(*subscriber_id, i)) {
+                        continue;
+                    }
+                    if search
+                        .filter_conditions
+                        .iter()
+                        .all(|filter_condition| filter_condition.matches(document))
+                    {
                         metrics::log_query_reads_outcome(true);
                         to_notify.insert(*subscriber_id);
+                        break;
                     }
                 }
             }
@@ -1030,20 +1239,14 @@ impl TextSearchSubscriptions {
     /// This inverse looking search optimizes for cases where the number of
     /// reads/subscriptions is significantly larger than the number of
     /// tokens in the document.
-    fn add_fuzzy_matches(&self, document: &PackedDocument, matches: &mut BTreeSet<SubscriberId>) {
-        let analyzer = convex_en();
-        for (_, fuzzy_terms) in self
-            .fuzzy_searches
-            .iter()
-            .filter(|(index, _)| *index.table() == document.id().tablet_id)
-        {
-            for (field, tries) in fuzzy_terms.terms.iter() {
-                let Some(ConvexValue::String(value)) = document.value().get_path(field) else {
-                    continue;
-                };
-                let mut tokens = ValueTokens::new(&analyzer, &value);
-                tries.matching_values(&mut tokens, matches);
-            }
+    fn add_fuzzy_matches(
+        &self,
+        index: &TabletIndexName,
+        document: &PackedDocument,
+        matches: &mut BTreeSet<(SubscriberId, usize)>,
+    ) {
+        if let Some(fuzzy_terms) = self.fuzzy_searches.get(index) {
+            fuzzy_terms.matching_values(document, matches);
         }
     }
 }
@@ -1138,9 +1341,14 @@ mod tests {
 
     use super::*;
 
+    fn overlaps(tries: &SearchTermTries<()>, document: &PackedDocument) -> bool {
+        let mut matches = BTreeSet::new();
+        tries.matching_values(document, &mut matches);
+        !matches.is_empty()
+    }
+
     #[test]
     fn test_search_term_tries_overlaps() -> anyhow::Result<()> {
-        let analyzer = convex_en();
         let mut tries = SearchTermTries::new();
 
         // Create a document with a text field
@@ -1162,10 +1370,10 @@ mod tests {
             TextQueryTerm::Exact("hello".to_string()),
         );
         let text_queries = WithHeapSize::from(vec![text_query]);
-        tries.extend((), &text_queries);
+        tries.extend((), &TextAnalyzerConfig::default(), &text_queries);
 
         // Test that the document matches
-        assert!(tries.overlaps_document(&doc, &analyzer));
+        assert!(overlaps(&tries, &doc));
 
         // Add a non-matching term
         let text_query = TextQueryTermRead::new(
@@ -1173,10 +1381,10 @@ mod tests {
             TextQueryTerm::Exact("goodbye".to_string()),
         );
         let text_queries = WithHeapSize::from(vec![text_query]);
-        tries.extend((), &text_queries);
+        tries.extend((), &TextAnalyzerConfig::default(), &text_queries);
 
         // Document should still match because it matches at least one term
-        assert!(tries.overlaps_document(&doc, &analyzer));
+        assert!(overlaps(&tries, &doc));
 
         // Create a document that doesn't match any terms
         let mut map = BTreeMap::new();
@@ -1192,21 +1400,20 @@ mod tests {
         )?);
 
         // Document should not match
-        assert!(!tries.overlaps_document(&doc, &analyzer));
+        assert!(!overlaps(&tries, &doc));
         Ok(())
     }
 
     #[test]
     fn test_search_term_tries_overlaps_returns_false_if_the_field_does_not_exist(
     ) -> anyhow::Result<()> {
-        let analyzer = convex_en();
         let mut tries = SearchTermTries::new();
         let text_query = TextQueryTermRead::new(
             FieldPath::from_str("title")?,
             TextQueryTerm::Exact("hello".to_string()),
         );
         let text_queries = WithHeapSize::from(vec![text_query]);
-        tries.extend((), &text_queries);
+        tries.extend((), &TextAnalyzerConfig::default(), &text_queries);
 
         let doc = PackedDocument::pack(&ResolvedDocument::new(
             ResolvedDocumentId::MIN,
@@ -1214,7 +1421,7 @@ mod tests {
             ConvexObject::try_from(btreemap! {})?,
         )?);
 
-        assert!(!tries.overlaps_document(&doc, &analyzer));
+        assert!(!overlaps(&tries, &doc));
         Ok(())
     }
 
@@ -1248,7 +1455,7 @@ mod tests {
 
         // Test matching
         let mut matches = BTreeSet::new();
-        subscriptions.add_fuzzy_matches(&doc, &mut matches);
+        subscriptions.add_matches(&doc, &mut matches);
         assert!(matches.contains(&subscriber_id));
 
         // Test non-matching
@@ -1261,12 +1468,51 @@ mod tests {
         )?);
 
         let mut matches = BTreeSet::new();
-        subscriptions.add_fuzzy_matches(&non_matching_doc, &mut matches);
+        subscriptions.add_matches(&non_matching_doc, &mut matches);
         assert!(matches.is_empty());
 
         Ok(())
     }
 
+    #[test]
+    fn test_fuzzy_matches_use_index_analyzer() -> anyhow::Result<()> {
+        let mut subscriptions = TextSearchSubscriptions::new();
+        let index = TabletIndexName::new(TabletId::MIN, IndexDescriptor::new("test_index")?)?;
+        let subscriber_id = SubscriberId::MIN;
+
+        // With stemming, searching for "running" reads the stem "run".
+        let query_reads = QueryReads::new(
+            WithHeapSize::from(vec![TextQueryTermRead::new(
+                FieldPath::from_str("text")?,
+                TextQueryTerm::Exact("run".to_string()),
+            )]),
+            WithHeapSize::default(),
+        )
+        .with_analyzer(TextAnalyzerConfig {
+            stemming: true,
+            ..Default::default()
+        });
+        subscriptions.insert(subscriber_id, &index, &query_reads);
+
+        let doc = PackedDocument::pack(&ResolvedDocument::new(
+            ResolvedDocumentId::MIN,
+            CreationTime::ONE,
+            ConvexObject::try_from(btreemap! {
+                FieldName::from_str("text")? => ConvexValue::String(ConvexString::try_from("she runs")?)
+            })?,
+        )?);
+        assert!(query_reads.overlaps_document(&doc));
+        let mut matches = BTreeSet::new();
+        subscriptions.add_matches(&doc, &mut matches);
+        assert!(matches.contains(&subscriber_id));
+
+        subscriptions.remove(subscriber_id, &index, &query_reads);
+        let mut matches = BTreeSet::new();
+        subscriptions.add_matches(&doc, &mut matches);
+        assert!(matches.is_empty());
+        Ok(())
+    }
+
     #[test]
     fn test_add_fuzzy_matches_returns_false_if_the_field_does_not_exist() -> anyhow::Result<()> {
         let mut subscriptions = TextSearchSubscriptions::new();
@@ -1291,9 +1537,98 @@ mod tests {
         subscriptions.insert(subscriber_id, &index, &query_reads);
 
         let mut matches = BTreeSet::new();
-        subscriptions.add_fuzzy_matches(&doc, &mut matches);
+        subscriptions.add_matches(&doc, &mut matches);
+        assert!(matches.is_empty());
+
+        Ok(())
+    }
+
+    fn channel_document(text: &str, channel: &str) -> anyhow::Result<PackedDocument> {
+        Ok(PackedDocument::pack(&ResolvedDocument::new(
+            ResolvedDocumentId::MIN,
+            CreationTime::ONE,
+            ConvexObject::try_from(btreemap! {
+                FieldName::from_str("text")? => ConvexValue::String(ConvexString::try_from(text)?),
+                FieldName::from_str("channel")? => ConvexValue::String(ConvexString::try_from(channel)?),
+            })?,
+        )?))
+    }
+
+    fn channel_search(term: TextQueryTerm, channel: &str) -> anyhow::Result<QueryReads> {
+        let channel = ConvexValue::String(ConvexString::try_from(channel)?);
+        Ok(QueryReads::new(
+            WithHeapSize::from(vec![TextQueryTermRead::new(
+                FieldPath::from_str("text")?,
+                term,
+            )]),
+            WithHeapSize::from(vec![FilterConditionRead::Must(
+                FieldPath::from_str("channel")?,
+                FilterValue::from_search_value(Some(&channel)),
+            )]),
+        ))
+    }
+
+    #[test]
+    fn test_matches_require_terms_and_filters_of_the_same_search() -> anyhow::Result<()> {
+        let mut subscriptions = TextSearchSubscriptions::new();
+        let index = TabletIndexName::new(TabletId::MIN, IndexDescriptor::new("test_index")?)?;
+        let subscriber_id = SubscriberId::MIN;
+
+        let mut query_reads = channel_search(TextQueryTerm::Exact("hello".to_string()), "general")?;
+        query_reads.merge(channel_search(
+            TextQueryTerm::Exact("goodbye".to_string()),
+            "random",
+        )?);
+        subscriptions.insert(subscriber_id, &index, &query_reads);
+
+        let overlaps = |text: &str, channel: &str| -> anyhow::Result<bool> {
+            let doc = channel_document(text, channel)?;
+            let mut matches = BTreeSet::new();
+            subscriptions.add_matches(&doc, &mut matches);
+            assert_eq!(
+                matches.contains(&subscriber_id),
+                query_reads.overlaps_document(&doc)
+            );
+            Ok(!matches.is_empty())
+        };
+        assert!(overlaps("hello world", "general")?);
+        assert!(overlaps("goodbye world", "random")?);
+        // Matching the filters or the terms alone isn't enough, and neither is
+        // matching the terms of one search and the filters of another.
+        assert!(!overlaps("something else", "general")?);
+        assert!(!overlaps("hello world", "other")?);
+        assert!(!overlaps("hello world", "random")?);
+
+        subscriptions.remove(subscriber_id, &index, &query_reads);
+        let mut matches = BTreeSet::new();
+        subscriptions.add_matches(&channel_document("hello world", "general")?, &mut matches);
         assert!(matches.is_empty());
+        Ok(())
+    }
 
+    #[test]
+    fn test_matches_with_typos() -> anyhow::Result<()> {
+        let mut subscriptions = TextSearchSubscriptions::new();
+        let index = TabletIndexName::new(TabletId::MIN, IndexDescriptor::new("test_index")?)?;
+        let subscriber_id = SubscriberId::MIN;
+
+        let query_reads = channel_search(
+            TextQueryTerm::Fuzzy {
+                token: "hello".to_string(),
+                max_distance: FuzzyDistance::One,
+                prefix: false,
+            },
+            "general",
+        )?;
+        subscriptions.insert(subscriber_id, &index, &query_reads);
+
+        for (text, expected) in [("helo world", true), ("hellooo world", false)] {
+            let doc = channel_document(text, "general")?;
+            assert_eq!(query_reads.overlaps_document(&doc), expected);
+            let mut matches = BTreeSet::new();
+            subscriptions.add_matches(&doc, &mut matches);
+            assert_eq!(matches.contains(&subscriber_id), expected);
+        }
         Ok(())
     }
 }
@@ -0,0 +1,166 @@
+//! A `Searcher` wrapper for resilience tests. See
+//! `common::testing::fault_injection`.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use common::{
+    bootstrap_model::index::{
+        text_index::FragmentedTextSegment,
+        vector_index::FragmentedVectorSegment,
+    },
+    runtime::Runtime,
+    testing::fault_injection::{
+        with_fault,
+        FaultOp,
+        FaultSchedule,
+    },
+};
+use pb::searchlight::FragmentedVectorSegmentPaths;
+use storage::Storage;
+use vector::{
+    CompiledVectorSearch,
+    QdrantSchema,
+    VectorSearchQueryResult,
+    VectorSearcher,
+};
+
+use super::{
+    searcher::{
+        Bm25Stats,
+        PostingListMatch,
+        PostingListQuery,
+        Term,
+        TokenMatch,
+        TokenQuery,
+    },
+    FragmentedTextStorageKeys,
+    Searcher,
+};
+
+/// A `Searcher` that injects faults from a [`FaultSchedule`] into the calls it
+/// forwards. Queries use [`FaultOp::SearchQuery`] and compactions use
+/// [`FaultOp::SearchCompaction`].
+pub struct FaultInjectingSearcher<RT: Runtime> {
+    inner: Arc<dyn Searcher>,
+    rt: RT,
+    schedule: FaultSchedule,
+}
+
+impl<RT: Runtime> FaultInjectingSearcher<RT> {
+    pub fn new(inner: Arc<dyn Searcher>, rt: RT, schedule: FaultSchedule) -> Self {
+        Self {
+            inner,
+            rt,
+            schedule,
+        }
+    }
+}
+
+#[async_trait]
+impl<RT: Runtime> Searcher for FaultInjectingSearcher<RT> {
+    async fn query_tokens(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        storage_keys: FragmentedTextStorageKeys,
+        queries: Vec<TokenQuery>,
+        max_results: usize,
+    ) -> anyhow::Result<Vec<TokenMatch>> {
+        with_fault(
+            &self.rt,
+            &self.schedule,
+            FaultOp::SearchQuery,
+            self.inner
+                .query_tokens(search_storage, storage_keys, queries, max_results),
+        )
+        .await
+    }
+
+    async fn query_bm25_stats(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        storage_keys: FragmentedTextStorageKeys,
+        terms: Vec<Term>,
+    ) -> anyhow::Result<Bm25Stats> {
+        with_fault(
+            &self.rt,
+            &self.schedule,
+            FaultOp::SearchQuery,
+            self.inner
+                .query_bm25_stats(search_storage, storage_keys, terms),
+        )
+        .await
+    }
+
+    async fn query_posting_lists(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        storage_keys: FragmentedTextStorageKeys,
+        query: PostingListQuery,
+    ) -> anyhow::Result<Vec<PostingListMatch>> {
+        with_fault(
+            &self.rt,
+            &self.schedule,
+            FaultOp::SearchQuery,
+            self.inner
+                .query_posting_lists(search_storage, storage_keys, query),
+        )
+        .await
+    }
+
+    async fn execute_text_compaction(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        segments: Vec<FragmentedTextStorageKeys>,
+    ) -> anyhow::Result<FragmentedTextSegment> {
+        with_fault(
+            &self.rt,
+            &self.schedule,
+            FaultOp::SearchCompaction,
+            self.inner.execute_text_compaction(search_storage, segments),
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl<RT: Runtime> VectorSearcher for FaultInjectingSearcher<RT> {
+    async fn execute_multi_segment_vector_query(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        segments: Vec<FragmentedVectorSegmentPaths>,
+        schema: QdrantSchema,
+        search: CompiledVectorSearch,
+        overfetch_delta: u32,
+    ) -> anyhow::Result<Vec<VectorSearchQueryResult>> {
+        with_fault(
+            &self.rt,
+            &self.schedule,
+            FaultOp::SearchQuery,
+            self.inner.execute_multi_segment_vector_query(
+                search_storage,
+                segments,
+                schema,
+                search,
+                overfetch_delta,
+            ),
+        )
+        .await
+    }
+
+    async fn execute_vector_compaction(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        segments: Vec<FragmentedVectorSegmentPaths>,
+        dimension: usize,
+    ) -> anyhow::Result<FragmentedVectorSegment> {
+        with_fault(
+            &self.rt,
+            &self.schedule,
+            FaultOp::SearchCompaction,
+            self.inner
+                .execute_vector_compaction(search_storage, segments, dimension),
+        )
+        .await
+    }
+}
@@ -1,10 +1,16 @@
+#[cfg(any(test, feature = "testing"))]
+mod fault_injection;
 mod in_process;
 mod metrics;
 #[allow(clippy::module_inception)]
 mod searcher;
+mod searchlight_client;
 mod searchlight_knobs;
+mod searchlight_server;
 mod segment_cache;
 
+#[cfg(any(test, feature = "testing"))]
+pub use fault_injection::FaultInjectingSearcher;
 pub use in_process::{
     InProcessSearcher,
     SearcherStub,
@@ -24,4 +30,9 @@ pub use searcher::{
     TokenMatch,
     TokenQuery,
 };
+pub use searchlight_client::SearchlightClient;
+pub use searchlight_server::{
+    SearchStorageResolver,
+    SearchlightService,
+};
 pub use text_search::tracker::SegmentTermMetadata;
@@ -24,6 +24,8 @@ use common::{
     bootstrap_model::index::text_index::FragmentedTextSegment,
     bounded_thread_pool::BoundedThreadPool,
     document::CreationTime,
+    index::IndexKeyBytes,
+    query::SearchAfter,
     runtime::Runtime,
     types::{
         ObjectKey,
@@ -59,7 +61,9 @@ use tantivy::{
     },
     schema::Field,
     termdict::TermOrdinal,
+    DocId,
     InvertedIndexReader,
+    Score,
     SegmentReader,
     TantivyError,
 };
@@ -109,8 +113,11 @@ use crate::{
     incremental_index::fetch_compact_and_upload_text_segment,
     levenshtein_dfa::{
         build_fuzzy_dfa,
+        shares_first_char,
         LevenshteinDfaWrapper,
     },
+    positional::PositionalConstraint,
+    query::CompiledRangeFilter,
     searcher::{
         metrics::{
             text_compaction_searcher_latency_seconds,
@@ -784,6 +791,9 @@ impl<RT: Runtime> SearcherImpl<RT> {
                         if distance != match_distance {
                             continue;
                         }
+                        if distance > 0 && !shares_first_char(term_str, match_str) {
+                            continue;
+                        }
 
                         seen_terms.insert(match_term.clone());
                         let m = TokenMatch {
@@ -914,23 +924,56 @@ impl<RT: Runtime> SearcherImpl<RT> {
                     segment_alive_bitset: deletion_tracker.alive_bitset().clone(),
                 };
 
-                let search_query =
-                    ConvexSearchQuery::new(query.or_terms, query.and_terms, alive_documents);
+                let search_query = ConvexSearchQuery::new(
+                    query.or_terms,
+                    query.and_terms,
+                    query.positional_constraints,
+                    query.range_filters,
+                    alive_documents,
+                );
                 let enable_scoring =
                     EnableScoring::enabled_from_statistics_provider(&stats_provider, searcher);
                 let search_weight = search_query.weight(enable_scoring)?;
 
-                let collector = TopDocs::with_limit(query.max_results);
                 let segment = searcher.segment_reader(*segment_ord);
-                let segment_results = collector.collect_segment(&*search_weight, 0, segment)?;
-
                 let fast_fields = segment.fast_fields();
                 let internal_ids = fast_fields.bytes(INTERNAL_ID_FIELD_NAME)?;
                 let timestamps = fast_fields.u64(TS_FIELD_NAME)?;
                 let creation_times = fast_fields.f64(CREATION_TIME_FIELD_NAME)?;
 
+                // Rank documents by score and then creation time, like
+                // `PostingListMatch`, so results are the same however ties on
+                // score fall. Documents that don't rank after `search_after`
+                // sort last and are dropped below.
+                let (tweak_ids, tweak_creation_times) =
+                    (internal_ids.clone(), creation_times.clone());
+                let search_after = query.search_after;
+                let collector =
+                    TopDocs::with_limit(query.max_results).tweak_score(move |_: &SegmentReader| {
+                        let internal_ids = tweak_ids.clone();
+                        let creation_times = tweak_creation_times.clone();
+                        let search_after = search_after.clone();
+                        move |doc_id: DocId, bm25_score: Score| {
+                            let creation_time = creation_times.get_val(doc_id);
+                            if let Some(search_after) = &search_after
+                                && !search_after.admits(
+                                    bm25_score,
+                                    creation_time,
+                                    internal_ids.get_bytes(doc_id),
+                                )
+                            {
+                                return None;
+                            }
+                            Some((bm25_score, creation_time))
+                        }
+                    });
+                let segment_results = collector.collect_segment(&*search_weight, 0, segment)?;
+
                 let mut results = Vec::with_capacity(segment_results.len());
-                for (bm25_score, doc_address) in segment_results {
+                for (rank, doc_address) in segment_results {
+                    let Some((bm25_score, _)) = rank else {
+                        continue;
+                    };
                     let internal_id = internal_ids.get_bytes(doc_address.doc_id).try_into()?;
 
                     let ts = Timestamp::try_from(timestamps.get_val(doc_address.doc_id))?;
@@ -947,9 +990,8 @@ impl<RT: Runtime> SearcherImpl<RT> {
 
                 anyhow::ensure!(results.len() <= query.max_results);
 
-                // TODO: The collector sorts only on score, unlike PostingListMatchAggregator,
-                // so we have to resort the results here, sweeping this nondeterminism
-                // under the rug.
+                // The collector breaks ties on creation time but not internal ID, unlike
+                // PostingListMatchAggregator, so sort the results the same way it does.
                 results.sort_by(|a, b| a.cmp(b).reverse());
 
                 Ok(results)
@@ -1255,6 +1297,9 @@ pub struct PostingListQuery {
 
     pub or_terms: Vec<OrTerm>,
     pub and_terms: Vec<Term>,
+    pub positional_constraints: Vec<PositionalConstraint>,
+    pub range_filters: Vec<CompiledRangeFilter>,
+    pub search_after: Option<SearchAfter>,
 
     pub max_results: usize,
 }
@@ -1270,6 +1315,9 @@ impl TryFrom<PostingListQueryProto> for PostingListQuery {
             or_terms,
             and_terms,
             max_results,
+            positional_constraints,
+            range_filters,
+            search_after,
         }: PostingListQueryProto,
     ) -> Result<Self, Self::Error> {
         let num_terms_by_field = num_terms_by_field
@@ -1287,12 +1335,23 @@ impl TryFrom<PostingListQueryProto> for PostingListQuery {
             .collect::<anyhow::Result<_>>()?;
         let or_terms = or_terms.into_iter().map(|t| t.try_into()).try_collect()?;
         let and_terms = and_terms.into_iter().map(Term::wrap).collect();
+        let positional_constraints = positional_constraints
+            .into_iter()
+            .map(PositionalConstraint::try_from)
+            .try_collect()?;
+        let range_filters = range_filters
+            .into_iter()
+            .map(CompiledRangeFilter::try_from)
+            .try_collect()?;
         Ok(PostingListQuery {
             deleted_internal_ids,
             num_terms_by_field,
             num_documents: num_documents.context("Missing num_documents")?,
             or_terms,
             and_terms,
+            positional_constraints,
+            range_filters,
+            search_after: search_after.map(|index_key| SearchAfter::new(IndexKeyBytes(index_key))),
             max_results: max_results.context("Missing max_results")? as usize,
         })
     }
@@ -1308,6 +1367,9 @@ impl TryFrom<PostingListQuery> for PostingListQueryProto {
             num_documents,
             or_terms,
             and_terms,
+            positional_constraints,
+            range_filters,
+            search_after,
             max_results,
         }: PostingListQuery,
     ) -> Result<Self, Self::Error> {
@@ -1334,6 +1396,15 @@ impl TryFrom<PostingListQuery> for PostingListQueryProto {
             or_terms,
             and_terms,
             max_results: Some(max_results as u32),
+            positional_constraints: positional_constraints
+                .into_iter()
+                .map(pb::searchlight::PositionalConstraint::from)
+                .collect(),
+            range_filters: range_filters
+                .into_iter()
+                .map(pb::searchlight::RangeFilter::from)
+                .collect(),
+            search_after: search_after.map(|search_after| search_after.index_key().0.clone()),
         })
     }
 }
@@ -1524,10 +1595,10 @@ mod tests {
 
         let mut id_generator = TestIdGenerator::new();
         let field_path: FieldPath = "mySearchField".parse()?;
-        let schema = TantivySearchIndexSchema::new(&DeveloperTextIndexConfig {
-            search_field: field_path.clone(),
-            filter_fields: BTreeSet::new(),
-        });
+        let schema = TantivySearchIndexSchema::new(&DeveloperTextIndexConfig::new(
+            field_path.clone(),
+            BTreeSet::new(),
+        ));
 
         #[derive(serde::Deserialize)]
         struct SearchDocument {
@@ -1675,6 +1746,9 @@ mod tests {
             deleted_internal_ids: BTreeSet::new(),
             or_terms,
             and_terms: vec![],
+            positional_constraints: vec![],
+            range_filters: vec![],
+            search_after: None,
             num_terms_by_field: stats.num_terms_by_field,
             num_documents: stats.num_documents,
             max_results,
@@ -1700,10 +1774,7 @@ mod tests {
 
     fn test_schema() -> TantivySearchIndexSchema {
         let field_path: FieldPath = "mySearchField".parse().unwrap();
-        TantivySearchIndexSchema::new(&DeveloperTextIndexConfig {
-            search_field: field_path.clone(),
-            filter_fields: BTreeSet::new(),
-        })
+        TantivySearchIndexSchema::new(&DeveloperTextIndexConfig::new(field_path, BTreeSet::new()))
     }
 
     #[derive(Clone)]
@@ -1924,6 +1995,9 @@ mod tests {
             deleted_internal_ids: BTreeSet::new(),
             or_terms,
             and_terms: vec![],
+            positional_constraints: vec![],
+            range_filters: vec![],
+            search_after: None,
             num_terms_by_field: stats.num_terms_by_field,
             num_documents: stats.num_documents,
             max_results,
@@ -0,0 +1,303 @@
+//! A [`Searcher`] that runs searches and compactions on a pool of searchlight
+//! processes over gRPC. See [`super::SearchlightService`].
+
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use common::{
+    bootstrap_model::index::{
+        text_index::FragmentedTextSegment,
+        vector_index::FragmentedVectorSegment,
+    },
+    fastrace_helpers::interceptor::TraceparentPopulatingInterceptor,
+    grpc::handle_response,
+    knobs::{
+        SEARCHLIGHT_CONNECT_TIMEOUT,
+        SEARCHLIGHT_MAX_MESSAGE_SIZE,
+        SEARCHLIGHT_REQUEST_TIMEOUT,
+    },
+    types::ObjectKey,
+};
+use itertools::Itertools;
+use pb::searchlight::{
+    searchlight_client::SearchlightClient as SearchlightGrpcClient,
+    FetchTermOrdinalsRequest,
+    FieldAndTermValues,
+    FragmentedTextSegmentPaths,
+    FragmentedVectorSegmentPaths,
+    FragmentedVectorSegmentPathsList,
+    QueryBm25StatsRequest,
+    QueryPostingListsRequest,
+    QueryTokensRequest,
+    StorageKey,
+    TextCompactionRequest,
+    VectorCompactionRequest,
+    VectorQueryRequest,
+};
+use storage::Storage;
+use tantivy::{
+    schema::Field,
+    termdict::TermOrdinal,
+    Term,
+};
+use tonic::{
+    codegen::InterceptedService,
+    metadata::{
+        Ascii,
+        MetadataValue,
+    },
+    service::Interceptor,
+    transport::{
+        Channel,
+        Endpoint,
+    },
+};
+use vector::{
+    CompiledVectorSearch,
+    QdrantSchema,
+    VectorSearchQueryResult,
+    VectorSearcher,
+};
+
+use super::{
+    searchlight_server::{
+        bearer_token,
+        AUTHORIZATION_METADATA_KEY,
+    },
+    Bm25Stats,
+    FragmentedTextStorageKeys,
+    PostingListMatch,
+    PostingListQuery,
+    Searcher,
+    SegmentTermMetadataFetcher,
+    TermValue,
+    TokenMatch,
+    TokenQuery,
+};
+
+type GrpcClient = SearchlightGrpcClient<InterceptedService<Channel, SearchlightInterceptor>>;
+
+/// Propagates the trace and authenticates with the searchlight's shared
+/// secret.
+#[derive(Clone)]
+struct SearchlightInterceptor {
+    authorization: MetadataValue<Ascii>,
+}
+
+impl Interceptor for SearchlightInterceptor {
+    fn call(&mut self, request: tonic::Request<()>) -> Result<tonic::Request<()>, tonic::Status> {
+        let mut request = TraceparentPopulatingInterceptor.call(request)?;
+        request
+            .metadata_mut()
+            .insert(AUTHORIZATION_METADATA_KEY, self.authorization.clone());
+        Ok(request)
+    }
+}
+
+/// Requests are balanced across all of the given searchlight URLs. Each
+/// searchlight caches the segments it has fetched, so the pool can be scaled
+/// independently of the backend.
+#[derive(Clone)]
+pub struct SearchlightClient {
+    client: GrpcClient,
+}
+
+impl SearchlightClient {
+    /// Must be called from within a tokio runtime, which balances requests in
+    /// the background. `secret` must match the searchlights' `--secret`.
+    pub fn new(urls: Vec<String>, secret: &str) -> anyhow::Result<Self> {
+        anyhow::ensure!(!urls.is_empty(), "At least one searchlight URL is required");
+        anyhow::ensure!(!secret.is_empty(), "The searchlight secret can't be empty");
+        let authorization = bearer_token(secret)
+            .parse()
+            .context("The searchlight secret must be printable ASCII")?;
+        let endpoints: Vec<_> = urls
+            .into_iter()
+            .map(|url| {
+                let endpoint = Endpoint::from_shared(url.clone())
+                    .with_context(|| format!("Invalid searchlight URL {url}"))?
+                    .connect_timeout(*SEARCHLIGHT_CONNECT_TIMEOUT)
+                    .timeout(*SEARCHLIGHT_REQUEST_TIMEOUT)
+                    .tcp_nodelay(true);
+                anyhow::Ok(endpoint)
+            })
+            .try_collect()?;
+        let channel = Channel::balance_list(endpoints.into_iter());
+        let client = SearchlightGrpcClient::with_interceptor(
+            channel,
+            SearchlightInterceptor { authorization },
+        )
+        .max_decoding_message_size(*SEARCHLIGHT_MAX_MESSAGE_SIZE)
+        .max_encoding_message_size(*SEARCHLIGHT_MAX_MESSAGE_SIZE);
+        Ok(Self { client })
+    }
+
+    fn client(&self) -> GrpcClient {
+        // Cloning shares the underlying channel.
+        self.client.clone()
+    }
+}
+
+fn text_segment_paths(storage_keys: FragmentedTextStorageKeys) -> FragmentedTextSegmentPaths {
+    storage_keys.into()
+}
+
+fn vector_segment_paths(
+    segments: Vec<FragmentedVectorSegmentPaths>,
+) -> Option<FragmentedVectorSegmentPathsList> {
+    Some(FragmentedVectorSegmentPathsList { segments })
+}
+
+#[async_trait]
+impl Searcher for SearchlightClient {
+    async fn query_tokens(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        storage_keys: FragmentedTextStorageKeys,
+        queries: Vec<TokenQuery>,
+        max_results: usize,
+    ) -> anyhow::Result<Vec<TokenMatch>> {
+        let request = QueryTokensRequest {
+            storage_type: Some(search_storage.storage_type_proto()),
+            segment: Some(text_segment_paths(storage_keys)),
+            token_queries: queries.into_iter().map(TryInto::try_into).try_collect()?,
+            max_results: Some(max_results.try_into()?),
+        };
+        let response = handle_response(self.client().query_tokens(request).await)?;
+        response
+            .token_matches
+            .into_iter()
+            .map(TokenMatch::try_from)
+            .try_collect()
+    }
+
+    async fn query_bm25_stats(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        storage_keys: FragmentedTextStorageKeys,
+        terms: Vec<Term>,
+    ) -> anyhow::Result<Bm25Stats> {
+        let request = QueryBm25StatsRequest {
+            storage_type: Some(search_storage.storage_type_proto()),
+            segment: Some(text_segment_paths(storage_keys)),
+            terms: terms
+                .into_iter()
+                .map(|term| term.as_slice().to_vec())
+                .collect(),
+        };
+        let response = handle_response(self.client().query_bm25_stats(request).await)?;
+        response.try_into()
+    }
+
+    async fn query_posting_lists(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        storage_keys: FragmentedTextStorageKeys,
+        query: PostingListQuery,
+    ) -> anyhow::Result<Vec<PostingListMatch>> {
+        let request = QueryPostingListsRequest {
+            storage_type: Some(search_storage.storage_type_proto()),
+            segment: Some(text_segment_paths(storage_keys)),
+            query: Some(query.try_into()?),
+        };
+        let response = handle_response(self.client().query_posting_lists(request).await)?;
+        response
+            .matches
+            .into_iter()
+            .map(PostingListMatch::try_from)
+            .try_collect()
+    }
+
+    async fn execute_text_compaction(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        segments: Vec<FragmentedTextStorageKeys>,
+    ) -> anyhow::Result<FragmentedTextSegment> {
+        let request = TextCompactionRequest {
+            segments: segments.into_iter().map(text_segment_paths).collect(),
+            storage_type: Some(search_storage.storage_type_proto()),
+        };
+        let response = handle_response(self.client().execute_text_compaction(request).await)?;
+        response.segment.context("Missing segment")?.try_into()
+    }
+}
+
+#[async_trait]
+impl VectorSearcher for SearchlightClient {
+    async fn execute_multi_segment_vector_query(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        segments: Vec<FragmentedVectorSegmentPaths>,
+        schema: QdrantSchema,
+        search: CompiledVectorSearch,
+        overfetch_delta: u32,
+    ) -> anyhow::Result<Vec<VectorSearchQueryResult>> {
+        let request = VectorQueryRequest {
+            index_config: Some(schema.into()),
+            query: Some(search.into()),
+            overfetch_delta,
+            segments: vector_segment_paths(segments),
+            storage_type: Some(search_storage.storage_type_proto()),
+        };
+        let response = handle_response(self.client().execute_vector_query(request).await)?;
+        response
+            .results
+            .into_iter()
+            .map(VectorSearchQueryResult::try_from)
+            .try_collect()
+    }
+
+    async fn execute_vector_compaction(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        segments: Vec<FragmentedVectorSegmentPaths>,
+        dimension: usize,
+    ) -> anyhow::Result<FragmentedVectorSegment> {
+        let request = VectorCompactionRequest {
+            segments: vector_segment_paths(segments),
+            dimension: dimension.try_into()?,
+            storage_type: Some(search_storage.storage_type_proto()),
+        };
+        let response = handle_response(self.client().execute_vector_compaction(request).await)?;
+        response.segment.context("Missing segment")?.try_into()
+    }
+}
+
+#[async_trait]
+impl SegmentTermMetadataFetcher for SearchlightClient {
+    async fn fetch_term_ordinals(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        segment: ObjectKey,
+        field_to_term_values: BTreeMap<Field, Vec<TermValue>>,
+    ) -> anyhow::Result<BTreeMap<Field, Vec<TermOrdinal>>> {
+        let request = FetchTermOrdinalsRequest {
+            storage_type: Some(search_storage.storage_type_proto()),
+            segment: Some(StorageKey {
+                storage_key: segment.into(),
+            }),
+            field_and_term_values: field_to_term_values
+                .into_iter()
+                .map(|(field, term_values)| FieldAndTermValues {
+                    field: Some(field.field_id()),
+                    term_values,
+                })
+                .collect(),
+        };
+        let response = handle_response(self.client().fetch_term_ordinals(request).await)?;
+        response
+            .field_and_term_ordinals
+            .into_iter()
+            .map(|field_and_term_ordinals| {
+                anyhow::Ok((
+                    Field::from_field_id(field_and_term_ordinals.field.context("Missing field")?),
+                    field_and_term_ordinals.term_ordinals,
+                ))
+            })
+            .try_collect()
+    }
+}
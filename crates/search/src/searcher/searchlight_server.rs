@@ -0,0 +1,361 @@
+//! Serves a [`SearcherImpl`] over gRPC, so text and vector search can run in a
+//! separate pool of searchlight processes, each with its own segment cache,
+//! instead of competing with the committer for CPU in the backend.
+//!
+//! Every request must carry the shared secret the searchlight was started
+//! with, since compactions write to search storage.
+
+use std::sync::Arc;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use common::{
+    bootstrap_model::index::text_index::FragmentedTextSegment,
+    runtime::Runtime,
+    types::ObjectKey,
+};
+use itertools::Itertools;
+use pb::{
+    error_metadata::ErrorMetadataStatusExt,
+    searchlight::{
+        searchlight_server::Searchlight,
+        FetchTermOrdinalsRequest,
+        FetchTermOrdinalsResponse,
+        FieldAndTermOrdinals,
+        QueryBm25StatsRequest,
+        QueryBm25StatsResponse,
+        QueryPostingListsRequest,
+        QueryPostingListsResponse,
+        QueryTokensRequest,
+        QueryTokensResponse,
+        StorageType,
+        TextCompactionRequest,
+        TextCompactionResponse,
+        VectorCompactionRequest,
+        VectorCompactionResponse,
+        VectorPrefetchRequest,
+        VectorPrefetchResponse,
+        VectorQueryRequest,
+        VectorQueryResponse,
+    },
+};
+use storage::Storage;
+use tantivy::{
+    schema::Field,
+    Term,
+};
+use tonic::{
+    Request,
+    Response,
+    Status,
+};
+use vector::{
+    CompiledVectorSearch,
+    QdrantSchema,
+    VectorSearcher,
+};
+
+use super::{
+    FragmentedTextStorageKeys,
+    PostingListQuery,
+    Searcher,
+    SearcherImpl,
+    SegmentTermMetadataFetcher,
+    TokenQuery,
+};
+
+/// Finds the search storage a request's segments are in. Every request names
+/// its storage, since one searchlight pool can serve many deployments.
+#[async_trait]
+pub trait SearchStorageResolver: Send + Sync + 'static {
+    /// Fails if `storage_type` isn't storage this searchlight may read.
+    async fn resolve(&self, storage_type: StorageType) -> anyhow::Result<Arc<dyn Storage>>;
+}
+
+/// The metadata key requests carry the shared secret in, as `Bearer
+/// <secret>`.
+pub(crate) const AUTHORIZATION_METADATA_KEY: &str = "authorization";
+
+pub(crate) fn bearer_token(secret: &str) -> String {
+    format!("Bearer {secret}")
+}
+
+pub struct SearchlightService<RT: Runtime> {
+    searcher: Arc<SearcherImpl<RT>>,
+    storage_resolver: Arc<dyn SearchStorageResolver>,
+    authorization: String,
+}
+
+impl<RT: Runtime> SearchlightService<RT> {
+    /// Only serves requests from clients configured with `secret`.
+    pub fn new(
+        searcher: Arc<SearcherImpl<RT>>,
+        storage_resolver: Arc<dyn SearchStorageResolver>,
+        secret: &str,
+    ) -> anyhow::Result<Self> {
+        anyhow::ensure!(!secret.is_empty(), "The searchlight secret can't be empty");
+        Ok(Self {
+            searcher,
+            storage_resolver,
+            authorization: bearer_token(secret),
+        })
+    }
+
+    fn authorize<T>(&self, request: &Request<T>) -> Result<(), Status> {
+        let provided = request
+            .metadata()
+            .get(AUTHORIZATION_METADATA_KEY)
+            .map(|value| value.as_bytes())
+            .unwrap_or_default();
+        // Compare in constant time so the secret can't be guessed byte by byte.
+        let matches = provided.len() == self.authorization.len()
+            && provided
+                .iter()
+                .zip(self.authorization.as_bytes())
+                .fold(0, |acc, (a, b)| acc | (a ^ b))
+                == 0;
+        if !matches {
+            return Err(Status::unauthenticated(
+                "Missing or invalid searchlight secret",
+            ));
+        }
+        Ok(())
+    }
+
+    async fn storage(&self, storage_type: Option<StorageType>) -> anyhow::Result<Arc<dyn Storage>> {
+        self.storage_resolver
+            .resolve(storage_type.context("Missing storage_type")?)
+            .await
+    }
+
+    async fn execute_vector_query_impl(
+        &self,
+        request: VectorQueryRequest,
+    ) -> anyhow::Result<VectorQueryResponse> {
+        let storage = self.storage(request.storage_type).await?;
+        let schema = QdrantSchema::try_from(request.index_config.context("Missing index_config")?)?;
+        let search = CompiledVectorSearch::try_from(request.query.context("Missing query")?)?;
+        let segments = request.segments.context("Missing segments")?.segments;
+        let results = self
+            .searcher
+            .execute_multi_segment_vector_query(
+                storage,
+                segments,
+                schema,
+                search,
+                request.overfetch_delta,
+            )
+            .await?;
+        Ok(VectorQueryResponse {
+            results: results.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    async fn execute_vector_compaction_impl(
+        &self,
+        request: VectorCompactionRequest,
+    ) -> anyhow::Result<VectorCompactionResponse> {
+        let storage = self.storage(request.storage_type).await?;
+        let segments = request.segments.context("Missing segments")?.segments;
+        let segment = self
+            .searcher
+            .execute_vector_compaction(storage, segments, request.dimension as usize)
+            .await?;
+        Ok(VectorCompactionResponse {
+            segment: Some(segment.into()),
+        })
+    }
+
+    async fn queue_vector_prefetch_impl(
+        &self,
+        request: VectorPrefetchRequest,
+    ) -> anyhow::Result<VectorPrefetchResponse> {
+        let storage = self.storage(request.storage_type).await?;
+        let segments = request.segments.context("Missing segments")?.segments;
+        self.searcher.queue_prefetch_segments(storage, segments)?;
+        Ok(VectorPrefetchResponse {})
+    }
+
+    async fn fetch_term_ordinals_impl(
+        &self,
+        request: FetchTermOrdinalsRequest,
+    ) -> anyhow::Result<FetchTermOrdinalsResponse> {
+        let storage = self.storage(request.storage_type).await?;
+        let segment = ObjectKey::try_from(request.segment.context("Missing segment")?.storage_key)?;
+        let field_to_term_values = request
+            .field_and_term_values
+            .into_iter()
+            .map(|field_and_term_values| {
+                anyhow::Ok((
+                    Field::from_field_id(field_and_term_values.field.context("Missing field")?),
+                    field_and_term_values.term_values,
+                ))
+            })
+            .try_collect()?;
+        let field_and_term_ordinals = self
+            .searcher
+            .fetch_term_ordinals(storage, segment, field_to_term_values)
+            .await?
+            .into_iter()
+            .map(|(field, term_ordinals)| FieldAndTermOrdinals {
+                field: Some(field.field_id()),
+                term_ordinals,
+            })
+            .collect();
+        Ok(FetchTermOrdinalsResponse {
+            field_and_term_ordinals,
+        })
+    }
+
+    async fn query_tokens_impl(
+        &self,
+        request: QueryTokensRequest,
+    ) -> anyhow::Result<QueryTokensResponse> {
+        let storage = self.storage(request.storage_type).await?;
+        let storage_keys =
+            FragmentedTextStorageKeys::try_from(request.segment.context("Missing segment")?)?;
+        let queries = request
+            .token_queries
+            .into_iter()
+            .map(TokenQuery::try_from)
+            .try_collect()?;
+        let max_results = request.max_results.context("Missing max_results")? as usize;
+        let token_matches = self
+            .searcher
+            .query_tokens(storage, storage_keys, queries, max_results)
+            .await?
+            .into_iter()
+            .map(TryInto::try_into)
+            .try_collect()?;
+        Ok(QueryTokensResponse { token_matches })
+    }
+
+    async fn query_bm25_stats_impl(
+        &self,
+        request: QueryBm25StatsRequest,
+    ) -> anyhow::Result<QueryBm25StatsResponse> {
+        let storage = self.storage(request.storage_type).await?;
+        let storage_keys =
+            FragmentedTextStorageKeys::try_from(request.segment.context("Missing segment")?)?;
+        let terms = request.terms.into_iter().map(Term::wrap).collect();
+        let stats = self
+            .searcher
+            .query_bm25_stats(storage, storage_keys, terms)
+            .await?;
+        Ok(stats.into())
+    }
+
+    async fn query_posting_lists_impl(
+        &self,
+        request: QueryPostingListsRequest,
+    ) -> anyhow::Result<QueryPostingListsResponse> {
+        let storage = self.storage(request.storage_type).await?;
+        let storage_keys =
+            FragmentedTextStorageKeys::try_from(request.segment.context("Missing segment")?)?;
+        let query = PostingListQuery::try_from(request.query.context("Missing query")?)?;
+        let matches = self
+            .searcher
+            .query_posting_lists(storage, storage_keys, query)
+            .await?
+            .into_iter()
+            .map(TryInto::try_into)
+            .try_collect()?;
+        Ok(QueryPostingListsResponse { matches })
+    }
+
+    async fn execute_text_compaction_impl(
+        &self,
+        request: TextCompactionRequest,
+    ) -> anyhow::Result<TextCompactionResponse> {
+        let storage = self.storage(request.storage_type).await?;
+        let segments = request
+            .segments
+            .into_iter()
+            .map(FragmentedTextStorageKeys::try_from)
+            .try_collect()?;
+        let segment: FragmentedTextSegment = self
+            .searcher
+            .execute_text_compaction(storage, segments)
+            .await?;
+        Ok(TextCompactionResponse {
+            segment: Some(segment.into()),
+        })
+    }
+}
+
+fn to_response<T>(result: anyhow::Result<T>) -> Result<Response<T>, Status> {
+    result.map(Response::new).map_err(Status::from_anyhow)
+}
+
+#[tonic::async_trait]
+impl<RT: Runtime> Searchlight for SearchlightService<RT> {
+    async fn execute_vector_query(
+        &self,
+        request: Request<VectorQueryRequest>,
+    ) -> Result<Response<VectorQueryResponse>, Status> {
+        self.authorize(&request)?;
+        to_response(self.execute_vector_query_impl(request.into_inner()).await)
+    }
+
+    async fn execute_vector_compaction(
+        &self,
+        request: Request<VectorCompactionRequest>,
+    ) -> Result<Response<VectorCompactionResponse>, Status> {
+        self.authorize(&request)?;
+        to_response(
+            self.execute_vector_compaction_impl(request.into_inner())
+                .await,
+        )
+    }
+
+    async fn queue_vector_prefetch(
+        &self,
+        request: Request<VectorPrefetchRequest>,
+    ) -> Result<Response<VectorPrefetchResponse>, Status> {
+        self.authorize(&request)?;
+        to_response(self.queue_vector_prefetch_impl(request.into_inner()).await)
+    }
+
+    async fn fetch_term_ordinals(
+        &self,
+        request: Request<FetchTermOrdinalsRequest>,
+    ) -> Result<Response<FetchTermOrdinalsResponse>, Status> {
+        self.authorize(&request)?;
+        to_response(self.fetch_term_ordinals_impl(request.into_inner()).await)
+    }
+
+    async fn query_tokens(
+        &self,
+        request: Request<QueryTokensRequest>,
+    ) -> Result<Response<QueryTokensResponse>, Status> {
+        self.authorize(&request)?;
+        to_response(self.query_tokens_impl(request.into_inner()).await)
+    }
+
+    async fn query_bm25_stats(
+        &self,
+        request: Request<QueryBm25StatsRequest>,
+    ) -> Result<Response<QueryBm25StatsResponse>, Status> {
+        self.authorize(&request)?;
+        to_response(self.query_bm25_stats_impl(request.into_inner()).await)
+    }
+
+    async fn query_posting_lists(
+        &self,
+        request: Request<QueryPostingListsRequest>,
+    ) -> Result<Response<QueryPostingListsResponse>, Status> {
+        self.authorize(&request)?;
+        to_response(self.query_posting_lists_impl(request.into_inner()).await)
+    }
+
+    async fn execute_text_compaction(
+        &self,
+        request: Request<TextCompactionRequest>,
+    ) -> Result<Response<TextCompactionResponse>, Status> {
+        self.authorize(&request)?;
+        to_response(
+            self.execute_text_compaction_impl(request.into_inner())
+                .await,
+        )
+    }
+}
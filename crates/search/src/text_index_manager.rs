@@ -196,7 +196,7 @@ impl TextIndexManager {
         // Ignore empty searches to avoid failures due to transient search issues (e.g.
         // bootstrapping). Do this after validating the query above.
         if search.filters.iter().any(|filter| {
-            let InternalSearchFilterExpression::Search(_, query_string) = filter else {
+            let InternalSearchFilterExpression::Search(_, query_string, _) = filter else {
                 return false;
             };
             query_string.trim().is_empty()
@@ -42,6 +42,8 @@ impl<C: ShapeConfig> CountedShape<C> {
             (ConvexValue::String(ref s), ShapeEnum::FieldName) => s.parse::<FieldName>().is_ok(),
             (ConvexValue::String(..), ShapeEnum::String) => true,
             (ConvexValue::Bytes(..), ShapeEnum::Bytes) => true,
+            (ConvexValue::Decimal(..), ShapeEnum::Decimal) => true,
+            (ConvexValue::DateTime(..), ShapeEnum::DateTime) => true,
             (ConvexValue::Array(ref array), ShapeEnum::Array(ref array_shape)) => array
                 .iter()
                 .all(|value| array_shape.element().contains(value)),
@@ -26,8 +26,10 @@ use serde_json::{
 };
 use value::{
     id_v6::DeveloperDocumentId,
+    ConvexDateTime,
     ConvexObject,
     ConvexValue,
+    Decimal128,
     FieldName,
     IdentifierFieldName,
 };
@@ -75,6 +77,8 @@ pub enum ExportContext {
     },
     Float64Inf,
     Bytes,
+    Decimal,
+    DateTime,
     Array(Vec<ExportContext>),
     Set,
     Map,
@@ -246,6 +250,20 @@ impl ExportContext {
                     ExportContext::Bytes
                 }
             },
+            ConvexValue::Decimal(_) => {
+                if Self::inferred_context_for_string(shape).is_some() {
+                    ExportContext::Infer
+                } else {
+                    ExportContext::Decimal
+                }
+            },
+            ConvexValue::DateTime(_) => {
+                if Self::inferred_context_for_string(shape).is_some() {
+                    ExportContext::Infer
+                } else {
+                    ExportContext::DateTime
+                }
+            },
             ConvexValue::Array(elements) => {
                 let inner_shape = shape
                     .iter()
@@ -389,6 +407,8 @@ impl ExportContext {
                         | ShapeEnum::FieldName
                         | ShapeEnum::String => yield ExportContext::Infer,
                         ShapeEnum::Bytes => yield ExportContext::Bytes,
+                        ShapeEnum::Decimal => yield ExportContext::Decimal,
+                        ShapeEnum::DateTime => yield ExportContext::DateTime,
                         // Unknown could have any ExportContext that can be a string.
                         ShapeEnum::Unknown => {
                             yield ExportContext::Infer;
@@ -398,6 +418,8 @@ impl ExportContext {
                             };
                             yield ExportContext::Int64;
                             yield ExportContext::Bytes;
+                            yield ExportContext::Decimal;
+                            yield ExportContext::DateTime;
                         },
                         // coroutine cannot be recursive, so unions are already handled by
                         // union_options() above.
@@ -479,6 +501,14 @@ impl ExportContext {
                         _ => anyhow::bail!("Unexpected string for f64"),
                     },
                     Self::Bytes => ConvexValue::try_from(base64::decode(value)?),
+                    Self::Decimal => value
+                        .parse::<Decimal128>()
+                        .map(ConvexValue::from)
+                        .context("Unexpected string for decimal"),
+                    Self::DateTime => value
+                        .parse::<ConvexDateTime>()
+                        .map(ConvexValue::from)
+                        .context("Unexpected string for datetime"),
                     Self::Array(_) | Self::Map | Self::Set | Self::Object(_) => {
                         anyhow::bail!("unexpected shape hint for string")
                     },
@@ -513,6 +543,8 @@ impl ExportContext {
                     ConvexValue::try_from(values)
                 },
                 Self::Bytes
+                | Self::Decimal
+                | Self::DateTime
                 | Self::Float64NaN { .. }
                 | Self::Float64Inf
                 | Self::Int64
@@ -559,6 +591,8 @@ impl ExportContext {
                     | Self::Float64NaN { .. }
                     | Self::Float64Inf
                     | Self::Bytes
+                    | Self::Decimal
+                    | Self::DateTime
                     | Self::Array(_) => anyhow::bail!("unsupported shape hint for object value"),
                 }
             },
@@ -573,6 +607,8 @@ impl From<ExportContext> for JsonValue {
             ExportContext::Int64 => json!("int64"),
             ExportContext::Float64Inf => json!("float64inf"),
             ExportContext::Bytes => json!("bytes"),
+            ExportContext::Decimal => json!("decimal"),
+            ExportContext::DateTime => json!("datetime"),
             ExportContext::Set => json!("set"),
             ExportContext::Map => json!("map"),
             ExportContext::Float64NaN { nan_le_bytes } => {
@@ -599,6 +635,8 @@ impl TryFrom<JsonValue> for ExportContext {
                 "int64" => Self::Int64,
                 "float64inf" => Self::Float64Inf,
                 "bytes" => Self::Bytes,
+                "decimal" => Self::Decimal,
+                "datetime" => Self::DateTime,
                 "set" => Self::Set,
                 "map" => Self::Map,
                 _ => anyhow::bail!("invalid export context {s}"),
@@ -90,6 +90,8 @@ impl<C: ShapeConfig> TryFrom<JsonValue> for CountedShapeEnum<C> {
             FieldName,
             String,
             Bytes,
+            Decimal,
+            DateTime,
             #[serde(rename_all = "camelCase")]
             Array {
                 element_type: JsonValue,
@@ -140,6 +142,8 @@ impl<C: ShapeConfig> TryFrom<JsonValue> for CountedShapeEnum<C> {
             ShapeEnumJson::FieldName => ShapeEnum::FieldName,
             ShapeEnumJson::String => ShapeEnum::String,
             ShapeEnumJson::Bytes => ShapeEnum::Bytes,
+            ShapeEnumJson::Decimal => ShapeEnum::Decimal,
+            ShapeEnumJson::DateTime => ShapeEnum::DateTime,
             ShapeEnumJson::Array { element_type } => {
                 ShapeEnum::Array(ArrayShape::new(Shape::try_from(element_type)?))
             },
@@ -253,6 +257,8 @@ impl<C: ShapeConfig> CountedShapeEnum<C> {
             ShapeEnum::FieldName => json!({"kind": "FieldName"}),
             ShapeEnum::String => json!({"kind": "String"}),
             ShapeEnum::Bytes => json!({"kind": "Bytes"}),
+            ShapeEnum::Decimal => json!({"kind": "Decimal"}),
+            ShapeEnum::DateTime => json!({"kind": "DateTime"}),
             ShapeEnum::Array(array_shape) => {
                 json!({"kind": "Array", "elementType": array_shape.element().to_json(include_pii)})
             },
@@ -163,6 +163,12 @@ pub enum ShapeEnum<C: ShapeConfig, S: ShapeCounter> {
     /// The set of all `Value::Bytes`s.
     Bytes,
 
+    /// The set of all `Value::Decimal`s.
+    Decimal,
+
+    /// The set of all `Value::DateTime`s.
+    DateTime,
+
     /// The set of all `Value::Array`s with elements within a particular shape.
     /// Note that there are two multisets involved here: This shape
     /// represents a multiset of arrays, where the inner element shape
@@ -269,6 +275,8 @@ impl<C: ShapeConfig> CountedShape<C> {
             ConvexValue::Boolean(..) => ShapeEnum::Boolean,
             ConvexValue::String(ref s) => StringLiteralShape::shape_of(s),
             ConvexValue::Bytes(..) => ShapeEnum::Bytes,
+            ConvexValue::Decimal(..) => ShapeEnum::Decimal,
+            ConvexValue::DateTime(..) => ShapeEnum::DateTime,
             ConvexValue::Array(ref array) => ArrayShape::shape_of(array),
             ConvexValue::Set(ref set) => SetShape::shape_of(set),
             ConvexValue::Map(ref map) => MapShape::shape_of(map),
@@ -387,6 +395,8 @@ impl<C: ShapeConfig> CountedShape<C> {
             },
             (ConvexValue::String(..), ShapeEnum::String) => ShapeEnum::String,
             (ConvexValue::Bytes(..), ShapeEnum::Bytes) => ShapeEnum::Bytes,
+            (ConvexValue::Decimal(..), ShapeEnum::Decimal) => ShapeEnum::Decimal,
+            (ConvexValue::DateTime(..), ShapeEnum::DateTime) => ShapeEnum::DateTime,
             (ConvexValue::Array(ref array), ShapeEnum::Array(ref array_shape)) => {
                 let mut element_shape = array_shape.element().clone();
                 for value in array {
@@ -535,6 +545,8 @@ impl<C: ShapeConfig, S: ShapeCounter> ShapeEnum<C, S> {
             | ShapeEnum::FieldName
             | ShapeEnum::String
             | ShapeEnum::Bytes
+            | ShapeEnum::Decimal
+            | ShapeEnum::DateTime
             | ShapeEnum::Array(_)
             | ShapeEnum::Set(_)
             | ShapeEnum::Map(_)
@@ -577,6 +589,8 @@ impl<C: ShapeConfig> From<&CountedShapeEnum<C>> for StructuralShapeEnum<C> {
             ShapeEnum::FieldName => Self::FieldName,
             ShapeEnum::String => Self::String,
             ShapeEnum::Bytes => Self::Bytes,
+            ShapeEnum::Decimal => Self::Decimal,
+            ShapeEnum::DateTime => Self::DateTime,
             ShapeEnum::Array(array) => Self::Array(ArrayShape::new(array.element().into())),
             ShapeEnum::Set(set) => Self::Set(SetShape::new(set.element().into())),
             ShapeEnum::Map(map) => Self::Map(MapShape::new(map.key().into(), map.value().into())),
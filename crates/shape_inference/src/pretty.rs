@@ -54,6 +54,8 @@ impl<C: ShapeConfig, S: ShapeCounter> fmt::Display for ShapeEnum<C, S> {
             ShapeEnum::FieldName => write!(f, "field_name"),
             ShapeEnum::String => write!(f, "string"),
             ShapeEnum::Bytes => write!(f, "bytes"),
+            ShapeEnum::Decimal => write!(f, "decimal"),
+            ShapeEnum::DateTime => write!(f, "datetime"),
             ShapeEnum::Array(ref array) => write!(f, "array<{}>", array.element()),
             ShapeEnum::Set(ref set) => write!(f, "set<{}>", set.element()),
             ShapeEnum::Map(ref map) => write!(f, "map<{}, {}>", map.key(), map.value()),
@@ -191,6 +193,8 @@ impl<C: ShapeConfig> StructuralShape<C> {
             ("field_name", ShapeEnum::FieldName),
             ("string", ShapeEnum::String),
             ("bytes", ShapeEnum::Bytes),
+            ("decimal", ShapeEnum::Decimal),
+            ("datetime", ShapeEnum::DateTime),
             ("unknown", ShapeEnum::Unknown),
         ];
         for (unit_str, unit_enum) in units {
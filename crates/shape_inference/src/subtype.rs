@@ -38,6 +38,8 @@ impl<C: ShapeConfig, S: ShapeCounter> ShapeEnum<C, S> {
             (ShapeEnum::Int64, ShapeEnum::Int64) => true,
             (ShapeEnum::Boolean, ShapeEnum::Boolean) => true,
             (ShapeEnum::Bytes, ShapeEnum::Bytes) => true,
+            (ShapeEnum::Decimal, ShapeEnum::Decimal) => true,
+            (ShapeEnum::DateTime, ShapeEnum::DateTime) => true,
 
             // Two string literal types are subtypes if they're equal.
             (ShapeEnum::StringLiteral(ref s), ShapeEnum::StringLiteral(ref other_s)) => {
@@ -229,6 +231,8 @@ impl<C: ShapeConfig> CountedShape<C> {
             (ShapeEnum::Int64, ShapeEnum::Int64) => ShapeEnum::Int64,
             (ShapeEnum::Boolean, ShapeEnum::Boolean) => ShapeEnum::Boolean,
             (ShapeEnum::Bytes, ShapeEnum::Bytes) => ShapeEnum::Bytes,
+            (ShapeEnum::Decimal, ShapeEnum::Decimal) => ShapeEnum::Decimal,
+            (ShapeEnum::DateTime, ShapeEnum::DateTime) => ShapeEnum::DateTime,
 
             (ShapeEnum::StringLiteral(ref s), ShapeEnum::StringLiteral(ref other_s)) => {
                 if s[..] != other_s[..] {
@@ -25,6 +25,8 @@ impl Arbitrary for ExportContext {
             (any::<[u8; 8]>()).prop_map(|nan_le_bytes| ExportContext::Float64NaN { nan_le_bytes }),
             Just(ExportContext::Float64Inf),
             Just(ExportContext::Bytes),
+            Just(ExportContext::Decimal),
+            Just(ExportContext::DateTime),
             Just(ExportContext::Set),
             Just(ExportContext::Map),
         ];
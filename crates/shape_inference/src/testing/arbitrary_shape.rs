@@ -156,6 +156,10 @@ pub fn nonempty_shape_strategy<C: ShapeConfig>() -> impl Strategy<Value = Counte
             .prop_map(|num_values| CountedShape::new(ShapeEnum::FieldName, num_values)),
         (1..MAX_NUM_VALUES).prop_map(|num_values| CountedShape::new(ShapeEnum::String, num_values)),
         (1..MAX_NUM_VALUES).prop_map(|num_values| CountedShape::new(ShapeEnum::Bytes, num_values)),
+        (1..MAX_NUM_VALUES)
+            .prop_map(|num_values| CountedShape::new(ShapeEnum::Decimal, num_values)),
+        (1..MAX_NUM_VALUES)
+            .prop_map(|num_values| CountedShape::new(ShapeEnum::DateTime, num_values)),
     ];
     nonempty_leaf.prop_recursive(2, 16, branching, move |inner| {
         // When generating non-leaf shapes, we need to be sure to adjust the number of
@@ -58,6 +58,12 @@ pub fn shape_member_strategy<C: ShapeConfig>(t: &CountedShape<C>) -> BoxedStrate
         ShapeEnum::Bytes => any::<value::ConvexBytes>()
             .prop_map(ConvexValue::Bytes)
             .boxed(),
+        ShapeEnum::Decimal => any::<value::Decimal128>()
+            .prop_map(ConvexValue::Decimal)
+            .boxed(),
+        ShapeEnum::DateTime => any::<value::ConvexDateTime>()
+            .prop_map(ConvexValue::DateTime)
+            .boxed(),
         ShapeEnum::Array(ref array) => {
             prop::collection::vec(shape_member_strategy(array.element()), 0..BRANCHING)
                 .prop_map(|values| ConvexValue::Array(ConvexArray::try_from(values).unwrap()))
@@ -19,6 +19,11 @@ use common::{
         InternalId,
         ResolvedDocument,
     },
+    document_chunks::{
+        chunk_document,
+        has_chunked_fields,
+        unchunk_document,
+    },
     index::{
         IndexEntry,
         IndexKeyBytes,
@@ -88,6 +93,7 @@ impl SqlitePersistence {
         let connection = Connection::open(path)?;
         // Execute create tables unconditionally since they are idempotent.
         connection.execute_batch(DOCUMENTS_INIT)?;
+        connection.execute_batch(DOCUMENT_CHUNKS_INIT)?;
         connection.execute_batch(INDEXES_INIT)?;
         connection.execute_batch(READ_ONLY_INIT)?;
         connection.execute_batch(PERSISTENCE_GLOBALS_INIT)?;
@@ -126,11 +132,10 @@ impl SqlitePersistence {
     fn _index_scan_inner(
         &self,
         index_id: IndexId,
-        tablet_id: TabletId,
         read_timestamp: Timestamp,
         interval: &Interval,
         order: Order,
-    ) -> anyhow::Result<Vec<anyhow::Result<(IndexKeyBytes, LatestDocument)>>> {
+    ) -> anyhow::Result<Vec<IndexRow>> {
         let interval = interval.clone();
         let index_id = &index_id[..];
         let read_timestamp: u64 = read_timestamp.into();
@@ -195,30 +200,38 @@ ORDER BY B.key {order}
 
             Ok((key, ts, document_id, table, json_value, prev_ts))
         })?;
-        let mut triples = vec![];
+        let mut rows = vec![];
         for row in row_iter {
             let (key, ts, document_id, table, json_value, prev_ts) = row?;
             let table = table.ok_or_else(|| {
                 anyhow::anyhow!("Dangling index reference for {:?} {:?}", key, ts)
             })?;
             let table = TabletId(table.try_into()?);
-            let _document_id = InternalDocumentId::new(table, InternalId::try_from(document_id)?);
+            let document_id = InternalDocumentId::new(table, InternalId::try_from(document_id)?);
             let json_value = json_value.ok_or_else(|| {
                 anyhow::anyhow!("Index reference to deleted document {:?} {:?}", key, ts)
             })?;
-            let json_value: serde_json::Value = serde_json::from_str(&json_value)?;
-            let value: ConvexValue = json_value.try_into()?;
-            let document = ResolvedDocument::from_database(tablet_id, value)?;
-            triples.push(Ok((
-                key,
-                LatestDocument {
-                    ts,
-                    value: document,
-                    prev_ts,
-                },
-            )));
+            rows.push((key, ts, document_id, json_value, prev_ts));
         }
-        Ok(triples)
+        Ok(rows)
+    }
+
+    /// Parses a row from `_index_scan_inner`, loading its chunks.
+    fn index_row_to_document(
+        &self,
+        tablet_id: TabletId,
+        (key, ts, document_id, json_value, prev_ts): IndexRow,
+    ) -> anyhow::Result<(IndexKeyBytes, LatestDocument)> {
+        let value = document_value(&self.inner.lock().connection, document_id, ts, &json_value)?;
+        let document = ResolvedDocument::from_database(tablet_id, value)?;
+        Ok((
+            key,
+            LatestDocument {
+                ts,
+                value: document,
+                prev_ts,
+            },
+        ))
     }
 
     fn _get_persistence_global(
@@ -273,12 +286,26 @@ impl Persistence for SqlitePersistence {
             ConflictStrategy::Error => tx.prepare_cached(INSERT_DOCUMENT)?,
             ConflictStrategy::Overwrite => tx.prepare_cached(INSERT_OVERWRITE_DOCUMENT)?,
         };
+        let mut insert_chunk_query = match conflict_strategy {
+            ConflictStrategy::Error => tx.prepare_cached(INSERT_DOCUMENT_CHUNK)?,
+            ConflictStrategy::Overwrite => tx.prepare_cached(INSERT_OVERWRITE_DOCUMENT_CHUNK)?,
+        };
 
         for update in documents {
             let (json_value, deleted) = if let Some(document) = update.value {
                 assert_eq!(update.id, document.id_with_table_id());
-                let json_value = document.value().json_serialize()?;
-                (Some(json_value), 0)
+                let chunked = chunk_document(document.value())?;
+                for chunk in chunked.chunks {
+                    insert_chunk_query.execute(params![
+                        &update.id.table().0[..],
+                        &update.id.internal_id()[..],
+                        &u64::from(update.ts),
+                        &chunk.field,
+                        &chunk.index,
+                        &chunk.json,
+                    ])?;
+                }
+                (Some(chunked.json_value), 0)
             } else {
                 (None, 1)
             };
@@ -292,6 +319,7 @@ impl Persistence for SqlitePersistence {
             ])?;
         }
         drop(insert_document_query);
+        drop(insert_chunk_query);
 
         let mut insert_index_query = if conflict_strategy == ConflictStrategy::Overwrite {
             tx.prepare_cached(INSERT_OVERWRITE_INDEX)?
@@ -422,6 +450,7 @@ impl Persistence for SqlitePersistence {
         let mut inner = self.inner.lock();
         let tx = inner.connection.transaction()?;
         let mut delete_document_query = tx.prepare_cached(DELETE_DOCUMENT)?;
+        let mut delete_chunks_query = tx.prepare_cached(DELETE_DOCUMENT_CHUNKS)?;
         let mut count_deleted = 0;
 
         for (ts, internal_id) in documents {
@@ -432,8 +461,10 @@ impl Persistence for SqlitePersistence {
                 &id[..],
                 &u64::from(ts),
             ])?;
+            delete_chunks_query.execute(params![&tablet_id.0[..], &id[..], &u64::from(ts)])?;
         }
         drop(delete_document_query);
+        drop(delete_chunks_query);
         tx.commit()?;
         Ok(count_deleted)
     }
@@ -448,29 +479,32 @@ impl PersistenceReader for SqlitePersistence {
         _page_size: u32,
         retention_validator: Arc<dyn RetentionValidator>,
     ) -> DocumentStream<'_> {
-        let triples = try {
+        let rows: anyhow::Result<Vec<DocumentRow>> = try {
             let connection = &self.inner.lock().connection;
             let load_docs_query = load_docs(range, order);
             let mut stmt = connection.prepare(load_docs_query.as_str())?;
-
-            let mut entries = vec![];
-            for row in stmt.query_map([], load_document_row)? {
-                let (document_id, ts, document, prev_ts) = row_to_document(row)?;
-                entries.push(Ok(DocumentLogEntry {
-                    ts,
-                    id: document_id,
-                    value: document,
-                    prev_ts,
-                }));
-            }
-            entries
+            stmt.query_map([], load_document_row)?
+                .collect::<rusqlite::Result<_>>()?
         };
         // load_documents isn't async so we have to validate snapshot as part of the
         // stream.
         let validate =
             self.validate_document_snapshot(range.min_timestamp_inclusive(), retention_validator);
-        match triples {
-            Ok(s) => (validate.chain(stream::iter(s))).boxed(),
+        match rows {
+            // Chunked fields are loaded as the stream is consumed, so only the
+            // documents being processed are held in memory whole.
+            Ok(rows) => validate
+                .chain(stream::iter(rows).map(move |row| {
+                    let (document_id, ts, document, prev_ts) =
+                        row_to_document(&self.inner.lock().connection, Ok(row))?;
+                    anyhow::Ok(DocumentLogEntry {
+                        ts,
+                        id: document_id,
+                        value: document,
+                        prev_ts,
+                    })
+                }))
+                .boxed(),
             Err(e) => stream::once(async { Err(e) }).boxed(),
         }
     }
@@ -491,7 +525,8 @@ impl PersistenceReader for SqlitePersistence {
                 let params = params![&id.table().0[..], &internal_id[..], &u64::from(ts)];
                 let mut row_iter = stmt.query_map(params, load_document_row)?;
                 if let Some(row) = row_iter.next() {
-                    let (document_id, prev_ts, document, prev_prev_ts) = row_to_document(row)?;
+                    let (document_id, prev_ts, document, prev_prev_ts) =
+                        row_to_document(&inner.connection, row)?;
                     out.insert(
                         (document_id, ts),
                         DocumentLogEntry {
@@ -527,7 +562,8 @@ impl PersistenceReader for SqlitePersistence {
                 let params = params![&id.table().0[..], &internal_id[..], &u64::from(prev_ts)];
                 let mut row_iter = stmt.query_map(params, load_document_row)?;
                 if let Some(row) = row_iter.next() {
-                    let (document_id, prev_ts, document, prev_prev_ts) = row_to_document(row)?;
+                    let (document_id, prev_ts, document, prev_prev_ts) =
+                        row_to_document(&inner.connection, row)?;
                     out.insert(
                         DocumentPrevTsQuery {
                             id: document_id,
@@ -562,11 +598,16 @@ impl PersistenceReader for SqlitePersistence {
         _size_hint: usize,
         retention_validator: Arc<dyn RetentionValidator>,
     ) -> IndexStream<'_> {
-        let triples = self._index_scan_inner(index_id, tablet_id, read_timestamp, interval, order);
+        let rows = self._index_scan_inner(index_id, read_timestamp, interval, order);
         // index_scan isn't async so we have to validate snapshot as part of the stream.
         let validate = self.validate_snapshot(read_timestamp, retention_validator);
-        match triples {
-            Ok(s) => (validate.chain(stream::iter(s))).boxed(),
+        match rows {
+            // As in `load_documents`, chunks are loaded as the stream is consumed.
+            Ok(rows) => validate
+                .chain(
+                    stream::iter(rows).map(move |row| self.index_row_to_document(tablet_id, row)),
+                )
+                .boxed(),
             Err(e) => stream::once(async { Err(e) }).boxed(),
         }
     }
@@ -581,6 +622,10 @@ impl PersistenceReader for SqlitePersistence {
     fn version(&self) -> PersistenceVersion {
         PersistenceVersion::V5
     }
+
+    fn supports_chunked_documents(&self) -> bool {
+        true
+    }
 }
 
 const DOCUMENTS_INIT: &str = r#"
@@ -600,6 +645,22 @@ CREATE TABLE IF NOT EXISTS documents (
 CREATE INDEX IF NOT EXISTS documents_by_table_and_id ON documents (table_id, id, ts);
 "#;
 
+// Chunks of large top-level fields, stored outside of their document's row.
+// See `common::document_chunks`.
+const DOCUMENT_CHUNKS_INIT: &str = r#"
+CREATE TABLE IF NOT EXISTS document_chunks (
+    table_id BLOB NOT NULL,
+    id BLOB NOT NULL,
+    ts INTEGER NOT NULL,
+
+    field TEXT NOT NULL,
+    chunk_index INTEGER NOT NULL,
+    json_chunk TEXT NOT NULL,
+
+    PRIMARY KEY (table_id, id, ts, field, chunk_index)
+);
+"#;
+
 const INDEXES_INIT: &str = r#"
 CREATE TABLE IF NOT EXISTS indexes (
     index_id BLOB NOT NULL,
@@ -633,8 +694,39 @@ CREATE TABLE IF NOT EXISTS persistence_globals (
 );
 "#;
 
+/// Parses a document row's JSON, loading any chunked fields from
+/// `document_chunks`. Callers parse rows one at a time as their stream is
+/// consumed, so a scan never holds all of its chunked documents at once.
+fn document_value(
+    connection: &Connection,
+    document_id: InternalDocumentId,
+    ts: Timestamp,
+    json_value: &str,
+) -> anyhow::Result<ConvexValue> {
+    let json_value: JsonValue = serde_json::from_str(json_value)?;
+    if !has_chunked_fields(&json_value) {
+        return json_value.try_into();
+    }
+    let mut stmt = connection.prepare_cached(LOAD_DOCUMENT_CHUNKS)?;
+    let json_value = unchunk_document(json_value, |field, count| {
+        let params = params![
+            &document_id.table().0[..],
+            &document_id.internal_id()[..],
+            &u64::from(ts),
+            field,
+            &(count as u64),
+        ];
+        let chunks = stmt
+            .query_map(params, |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(chunks)
+    })?;
+    json_value.try_into()
+}
+
 fn row_to_document(
-    row: rusqlite::Result<(Vec<u8>, u64, Vec<u8>, Option<String>, bool, Option<u64>)>,
+    connection: &Connection,
+    row: rusqlite::Result<DocumentRow>,
 ) -> anyhow::Result<(
     InternalDocumentId,
     Timestamp,
@@ -649,8 +741,7 @@ fn row_to_document(
     let document = if !deleted {
         let json_value = json_value
             .ok_or_else(|| anyhow::anyhow!("Unexpected NULL json_value at {} {}", id, prev_ts))?;
-        let json_value: serde_json::Value = serde_json::from_str(&json_value)?;
-        let value: ConvexValue = json_value.try_into()?;
+        let value = document_value(connection, document_id, prev_ts, &json_value)?;
         Some(ResolvedDocument::from_database(table, value)?)
     } else {
         None
@@ -677,9 +768,21 @@ WHERE ts >= {} AND ts < {}
     )
 }
 
-fn load_document_row(
-    row: &Row<'_>,
-) -> rusqlite::Result<(Vec<u8>, u64, Vec<u8>, Option<String>, bool, Option<u64>)> {
+/// `(id, ts, table_id, json_value, deleted, prev_ts)`, with chunked fields not
+/// yet loaded.
+type DocumentRow = (Vec<u8>, u64, Vec<u8>, Option<String>, bool, Option<u64>);
+
+/// `(key, ts, document_id, json_value, prev_ts)` of an index entry's document,
+/// with chunked fields not yet loaded.
+type IndexRow = (
+    IndexKeyBytes,
+    Timestamp,
+    InternalDocumentId,
+    String,
+    Option<Timestamp>,
+);
+
+fn load_document_row(row: &Row<'_>) -> rusqlite::Result<DocumentRow> {
     let id = row.get::<_, Vec<u8>>(0)?;
     let ts = row.get::<_, u64>(1)?;
     let table: Vec<u8> = row.get(2)?;
@@ -695,6 +798,12 @@ const INSERT_DOCUMENT: &str = "INSERT INTO documents (id, ts, table_id, json_val
                                prev_ts) VALUES (?, ?, ?, ?, ?, ?)";
 const INSERT_OVERWRITE_DOCUMENT: &str = "INSERT OR REPLACE INTO documents (id, ts, table_id, \
                                          json_value, deleted, prev_ts) VALUES (?, ?, ?, ?, ?, ?)";
+const INSERT_DOCUMENT_CHUNK: &str = "INSERT INTO document_chunks VALUES (?, ?, ?, ?, ?, ?)";
+const INSERT_OVERWRITE_DOCUMENT_CHUNK: &str =
+    "INSERT OR REPLACE INTO document_chunks VALUES (?, ?, ?, ?, ?, ?)";
+const LOAD_DOCUMENT_CHUNKS: &str = "SELECT json_chunk FROM document_chunks WHERE table_id = ? AND \
+                                    id = ? AND ts = ? AND field = ? AND chunk_index < ? ORDER BY \
+                                    chunk_index ASC";
 const INSERT_INDEX: &str = "INSERT INTO indexes VALUES (?, ?, ?, ?, ?, ?)";
 const INSERT_OVERWRITE_INDEX: &str = "INSERT OR REPLACE INTO indexes VALUES (?, ?, ?, ?, ?, ?)";
 const WRITE_PERSISTENCE_GLOBAL: &str = "INSERT OR REPLACE INTO persistence_globals VALUES (?, ?)";
@@ -705,6 +814,8 @@ const WALK_INDEXES: &str =
 const DELETE_INDEX: &str = "DELETE FROM indexes WHERE index_id = ? AND ts <= ? AND key = ?";
 
 const DELETE_DOCUMENT: &str = "DELETE FROM documents WHERE table_id = ? AND id = ? AND ts <= ?";
+const DELETE_DOCUMENT_CHUNKS: &str =
+    "DELETE FROM document_chunks WHERE table_id = ? AND id = ? AND ts <= ?";
 
 const CHECK_IS_READ_ONLY: &str = "SELECT 1 FROM read_only LIMIT 1";
 const SET_READ_ONLY: &str = "INSERT INTO read_only (id) VALUES (1)";
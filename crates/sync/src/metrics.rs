@@ -85,6 +85,27 @@ pub fn log_query_set_size(num_queries: usize) {
     log_distribution(&SYNC_QUERY_SET_TOTAL, num_queries as f64);
 }
 
+register_convex_histogram!(
+    SYNC_SESSION_JOURNAL_BYTES,
+    "Total size of the query journals kept for a sync session"
+);
+pub fn log_session_journal_size(bytes: usize) {
+    log_distribution(&SYNC_SESSION_JOURNAL_BYTES, bytes as f64);
+}
+
+register_convex_counter!(
+    SYNC_JOURNALS_COMPACTED_TOTAL,
+    "Number of query journals dropped to keep a sync session under its journal size limit"
+);
+register_convex_counter!(
+    SYNC_JOURNAL_COMPACTED_BYTES_TOTAL,
+    "Bytes of query journals dropped to keep a sync session under its journal size limit"
+);
+pub fn log_journals_compacted(num_journals: usize, bytes: usize) {
+    log_counter(&SYNC_JOURNALS_COMPACTED_TOTAL, num_journals as u64);
+    log_counter(&SYNC_JOURNAL_COMPACTED_BYTES_TOTAL, bytes as u64);
+}
+
 register_convex_counter!(
     SYNC_QUERY_RESULT_DEDUP_TOTAL,
     "Number of deduplicated query results"
@@ -1,4 +1,5 @@
 use std::{
+    cmp::Reverse,
     collections::BTreeMap,
     hash::Hash,
     mem,
@@ -13,12 +14,17 @@ use application::{
     },
 };
 use common::{
+    errors::JsError,
     sha256::{
         Sha256,
         Sha256Digest,
     },
     types::SessionId,
-    value::JsonPackedValue,
+    value::{
+        ConvexValue,
+        JsonPackedValue,
+    },
+    RequestId,
 };
 use errors::ErrorMetadata;
 use futures::{
@@ -33,6 +39,7 @@ use futures::{
     StreamExt,
 };
 use keybroker::Identity;
+use serde_json::json;
 use sync_types::{
     IdentityVersion,
     Query,
@@ -74,6 +81,12 @@ pub struct SyncedQuery {
     /// when `self.subscription` is no longer valid and the query should be
     /// rerun.
     invalidation_future: Option<AbortHandle>,
+
+    /// Set when `SyncState::compact_journals` drops the query's journal. The
+    /// query can't be rerun to the same end cursor without it, so the next
+    /// time it's invalidated it fails with [`compacted_journal_error`] instead,
+    /// which makes the client restart pagination.
+    journal_compacted: bool,
 }
 
 /// The client issues modifications to sync state predicated on a client
@@ -143,6 +156,10 @@ pub struct SyncState {
     pending_identity: Option<Identity>,
     /// These are the query set version and identity according to the client.
     received_client_version: ClientVersion,
+
+    /// Total size of the serialized journals across `queries` and
+    /// `in_progress_queries`, bounded by `SyncState::compact_journals`.
+    journal_bytes: usize,
 }
 
 impl SyncState {
@@ -160,6 +177,7 @@ impl SyncState {
             pending_query_updates: vec![],
             pending_identity: None,
             received_client_version: ClientVersion::initial(),
+            journal_bytes: 0,
         }
     }
 
@@ -307,9 +325,11 @@ impl SyncState {
     /// fill out these fields.
     pub fn insert(&mut self, query: Query) -> anyhow::Result<()> {
         let query_id = query.query_id;
+        let journal_size = journal_size(&query);
         if self.in_progress_queries.insert(query_id, query).is_some() {
             anyhow::bail!("Duplicate query ID: {}", query_id);
         }
+        self.journal_bytes += journal_size;
         self.refill_needed = true;
         Ok(())
     }
@@ -320,8 +340,9 @@ impl SyncState {
             if let Some(handle) = query.invalidation_future.take() {
                 handle.abort();
             }
-        } else if self.in_progress_queries.remove(&query_id).is_some() {
-            // Removed in-progress query.
+            self.journal_bytes -= journal_size(&query.query);
+        } else if let Some(query) = self.in_progress_queries.remove(&query_id) {
+            self.journal_bytes -= journal_size(&query);
         } else {
             anyhow::bail!("Nonexistent query id: {}", query_id);
         }
@@ -388,6 +409,7 @@ impl SyncState {
                 subscription: None,
                 result_hash: None,
                 invalidation_future: None,
+                journal_compacted: false,
             };
             if self.queries.insert(query_id, sq).is_some() {
                 anyhow::bail!("Duplicate query ID: {}", query_id);
@@ -406,7 +428,10 @@ impl SyncState {
 
         // Save the new query journal so any recomputations will be done with it
         // present.
+        self.journal_bytes -= journal_size(&query.query);
         query.query.journal = Some(journal.clone());
+        query.journal_compacted = false;
+        self.journal_bytes += journal_size(&query.query);
 
         // Cancel the query's (now out-of-date) subscription so we resubscribe in the
         // next call to `fill_subscriptions`.
@@ -470,6 +495,94 @@ impl SyncState {
     pub fn num_queries(&self) -> usize {
         self.queries.len() + self.in_progress_queries.len()
     }
+
+    /// Whether the query's journal was dropped by `compact_journals`, so it
+    /// must fail with [`compacted_journal_error`] instead of being rerun.
+    pub fn journal_compacted(&self, query_id: QueryId) -> bool {
+        self.queries
+            .get(&query_id)
+            .is_some_and(|sq| sq.journal_compacted)
+    }
+
+    /// Drop the largest journals until the session's journals fit in
+    /// `max_bytes`. Only queries with an up-to-date result are compacted: their
+    /// journal isn't needed until they're invalidated. Rerunning one without
+    /// its journal would silently start its page over, so it fails with
+    /// [`compacted_journal_error`] instead and the client restarts pagination.
+    pub fn compact_journals(&mut self, max_bytes: usize) {
+        let compacted = if self.journal_bytes > max_bytes {
+            let candidates = self
+                .queries
+                .iter()
+                .filter(|(_, sq)| sq.subscription.is_some())
+                .map(|(query_id, sq)| (*query_id, journal_size(&sq.query)))
+                .filter(|(_, size)| *size > 0)
+                .collect();
+            select_journals_to_compact(candidates, self.journal_bytes - max_bytes)
+        } else {
+            vec![]
+        };
+        let mut compacted_bytes = 0;
+        for query_id in &compacted {
+            let sq = self
+                .queries
+                .get_mut(query_id)
+                .expect("Compacting nonexistent query");
+            compacted_bytes += journal_size(&sq.query);
+            sq.query.journal = None;
+            sq.journal_compacted = true;
+        }
+        self.journal_bytes -= compacted_bytes;
+        if !compacted.is_empty() {
+            metrics::log_journals_compacted(compacted.len(), compacted_bytes);
+        }
+        metrics::log_session_journal_size(self.journal_bytes);
+    }
+}
+
+/// The result of a paginated query whose journal was compacted. The client
+/// treats an `InvalidCursor` error like a cursor from a different query, and
+/// restarts pagination from the first page with new queries.
+pub fn compacted_journal_error() -> RedactedJsError {
+    let message = "InvalidCursor: This page's position was dropped to bound the session's memory. \
+                   Restart pagination from the first page.";
+    let data = ConvexValue::try_from(json!({
+        "isConvexSystemError": true,
+        "paginationError": "InvalidCursor",
+    }))
+    .expect("InvalidCursor data should be a valid Value");
+    RedactedJsError::from_js_error(
+        JsError::convex_error(message.to_string(), data),
+        false,
+        RequestId::new(),
+    )
+}
+
+fn journal_size(query: &Query) -> usize {
+    query
+        .journal
+        .as_ref()
+        .and_then(Option::as_ref)
+        .map_or(0, |journal| journal.len())
+}
+
+/// Picks the largest journals until at least `excess` bytes are freed, or all
+/// of them if that's not enough.
+fn select_journals_to_compact(
+    mut candidates: Vec<(QueryId, usize)>,
+    excess: usize,
+) -> Vec<QueryId> {
+    candidates.sort_by_key(|(query_id, size)| (Reverse(*size), *query_id));
+    let mut freed = 0;
+    candidates
+        .into_iter()
+        .take_while(|(_, size)| {
+            let needed = freed < excess;
+            freed += size;
+            needed
+        })
+        .map(|(query_id, _)| query_id)
+        .collect()
 }
 
 fn hash_result(
@@ -511,7 +624,11 @@ fn hash_log_lines(hasher: &mut Sha256, log_lines: &RedactedLogLines) {
 
 #[cfg(test)]
 mod tests {
-    use application::redaction::RedactedLogLines;
+    use application::{
+        api::SubscriptionTrait,
+        redaction::RedactedLogLines,
+    };
+    use async_trait::async_trait;
     use cmd_util::env::env_config;
     use common::{
         log_lines::{
@@ -520,14 +637,40 @@ mod tests {
             LogLines,
         },
         runtime::UnixTimestamp,
+        types::Timestamp,
         value::{
             ConvexValue,
             JsonPackedValue,
         },
     };
+    use futures::{
+        future::BoxFuture,
+        FutureExt,
+    };
     use proptest::prelude::*;
+    use sync_types::{
+        Query,
+        QueryId,
+    };
+
+    use crate::state::{
+        select_journals_to_compact,
+        udf_result_sha256,
+        SyncState,
+    };
+
+    struct NeverInvalidated;
 
-    use crate::state::udf_result_sha256;
+    #[async_trait]
+    impl SubscriptionTrait for NeverInvalidated {
+        fn wait_for_invalidation(&self) -> BoxFuture<'static, anyhow::Result<()>> {
+            futures::future::pending().boxed()
+        }
+
+        async fn extend_validity(&self, _new_ts: Timestamp) -> anyhow::Result<bool> {
+            Ok(true)
+        }
+    }
 
     proptest! {
         #![proptest_config(
@@ -586,4 +729,67 @@ mod tests {
             udf_result_sha256(&v, &v2_logs)
         );
     }
+
+    #[test]
+    fn test_select_journals_to_compact() {
+        let candidates = vec![
+            (QueryId::new(0), 100),
+            (QueryId::new(1), 300),
+            (QueryId::new(2), 200),
+        ];
+        assert_eq!(select_journals_to_compact(candidates.clone(), 0), vec![]);
+        assert_eq!(
+            select_journals_to_compact(candidates.clone(), 300),
+            vec![QueryId::new(1)]
+        );
+        assert_eq!(
+            select_journals_to_compact(candidates.clone(), 301),
+            vec![QueryId::new(1), QueryId::new(2)]
+        );
+        assert_eq!(
+            select_journals_to_compact(candidates, 1000),
+            vec![QueryId::new(1), QueryId::new(2), QueryId::new(0)]
+        );
+    }
+
+    #[test]
+    fn test_compacted_journal_is_not_rerun() -> anyhow::Result<()> {
+        let mut state = SyncState::new();
+        let query_id = QueryId::new(0);
+        state.insert(Query {
+            query_id,
+            udf_path: "messages:list".parse()?,
+            args: vec![],
+            journal: None,
+            component_path: None,
+        })?;
+        let journal = Some("x".repeat(100));
+        state.complete_fetch(
+            query_id,
+            Ok(JsonPackedValue::pack(ConvexValue::Null)),
+            RedactedLogLines::empty(),
+            journal.clone(),
+            Box::new(NeverInvalidated),
+        )?;
+        state.compact_journals(1000);
+        assert!(!state.journal_compacted(query_id));
+
+        state.compact_journals(0);
+        assert!(state.journal_compacted(query_id));
+        state.take_subscriptions();
+        let queries: Vec<_> = state.need_fetch().collect();
+        assert_eq!(queries.len(), 1);
+        assert_eq!(queries[0].journal, None);
+
+        // A new result comes with a new journal.
+        state.complete_fetch(
+            query_id,
+            Err(super::compacted_journal_error()),
+            RedactedLogLines::empty(),
+            journal,
+            Box::new(NeverInvalidated),
+        )?;
+        assert!(!state.journal_compacted(query_id));
+        Ok(())
+    }
 }
@@ -1,5 +1,6 @@
 use std::{
     collections::BTreeMap,
+    mem,
     sync::{
         atomic::{
             AtomicUsize,
@@ -18,10 +19,19 @@ use application::{
         SubscriptionClient,
         SubscriptionTrait,
     },
+    identity_quotas::{
+        IdentityQuotaTracker,
+        SessionSubscriptionQuota,
+    },
     redaction::{
         RedactedJsError,
         RedactedLogLines,
     },
+    subscription_stats::{
+        ActiveSubscriptionGuard,
+        SubscribedQueryPath,
+        SubscriptionStatsTracker,
+    },
     RedactedActionError,
     RedactedMutationError,
 };
@@ -33,7 +43,10 @@ use common::{
     },
     fastrace_helpers::get_sampled_span,
     http::ResolvedHostname,
-    knobs::SYNC_MAX_SEND_TRANSITION_COUNT,
+    knobs::{
+        SYNC_MAX_SEND_TRANSITION_COUNT,
+        SYNC_MAX_SESSION_JOURNAL_BYTES,
+    },
     runtime::{
         try_join_buffer_unordered,
         Runtime,
@@ -47,6 +60,10 @@ use common::{
     version::ClientVersion,
     RequestId,
 };
+use database::{
+    InvalidationCause,
+    Token,
+};
 use errors::{
     ErrorMetadata,
     ErrorMetadataAnyhowExt,
@@ -60,6 +77,7 @@ use futures::{
     },
     select_biased,
     stream::{
+        self,
         Buffered,
         FuturesUnordered,
     },
@@ -100,7 +118,10 @@ use crate::{
         mutation_queue_timer,
         TypedClientEvent,
     },
-    state::SyncState,
+    state::{
+        compacted_journal_error,
+        SyncState,
+    },
     ServerMessage,
 };
 
@@ -237,6 +258,16 @@ pub struct SyncWorker<RT: Runtime> {
     modify_query_to_transition_timers: BTreeMap<QuerySetVersion, StatusTimer>,
 
     on_connect: Option<(StatusTimer, Box<dyn FnOnce(SessionId) + Send>)>,
+
+    subscription_stats: SubscriptionStatsTracker,
+    /// Counts each query in the query set as an active subscription from when
+    /// it first returns a result until it's removed or the session ends.
+    active_subscriptions: BTreeMap<QueryId, ActiveSubscriptionGuard>,
+
+    identity_quotas: IdentityQuotaTracker,
+    /// Counts the query set against the session identity's subscription
+    /// limit.
+    subscription_quota: SessionSubscriptionQuota,
 }
 
 enum QueryResult {
@@ -248,8 +279,20 @@ enum QueryResult {
     Refresh,
 }
 
+/// A run of a query function, counted once it returns.
+struct QueryExecution {
+    path: SubscribedQueryPath,
+    /// `Some` if the run was caused by an invalidation.
+    invalidation: Option<Option<InvalidationCause>>,
+}
+
 struct TransitionState {
-    udf_results: Vec<(QueryId, QueryResult, Box<dyn SubscriptionTrait>)>,
+    udf_results: Vec<(
+        QueryId,
+        QueryResult,
+        Box<dyn SubscriptionTrait>,
+        Option<QueryExecution>,
+    )>,
     state_modifications: BTreeMap<QueryId, StateModification<JsonPackedValue>>,
     current_version: StateVersion,
     new_version: StateVersion,
@@ -268,6 +311,9 @@ impl<RT: Runtime> SyncWorker<RT> {
     ) -> Self {
         let (mutation_sender, receiver) = mpsc::channel(OPERATION_QUEUE_BUFFER_SIZE);
         let mutation_futures = ReceiverStream::new(receiver).buffered(1); // Execute at most one operation at a time.
+        let subscription_stats = api.subscription_stats(&host);
+        let identity_quotas = api.identity_quotas(&host);
+        let subscription_quota = identity_quotas.session_subscriptions();
         SyncWorker {
             api,
             config,
@@ -283,6 +329,10 @@ impl<RT: Runtime> SyncWorker<RT> {
             update_scheduled: false,
             modify_query_to_transition_timers: BTreeMap::new(),
             on_connect: Some((connect_timer(), on_connect)),
+            subscription_stats,
+            active_subscriptions: BTreeMap::new(),
+            identity_quotas,
+            subscription_quota,
         }
     }
 
@@ -296,6 +346,8 @@ impl<RT: Runtime> SyncWorker<RT> {
     pub async fn go(&mut self) -> anyhow::Result<()> {
         let mut ping_timeout = self.rt.wait(HEARTBEAT_INTERVAL);
         let mut pending = future::pending().boxed().fuse();
+        // Ready once the session's identity has transition bandwidth again.
+        let mut bandwidth_wait = future::pending().boxed().fuse();
 
         // Create a new subscription client for every sync socket. Thus we don't require
         // the subscription client to auto-recover on connection failures.
@@ -349,6 +401,7 @@ impl<RT: Runtime> SyncWorker<RT> {
                     // in case update_scheduled is True.
                     None
                 }
+                _ = bandwidth_wait => None,
                 _ = ping_timeout => Some(ServerMessage::Ping {}),
             };
             // If there is a message to return to the client, send it.
@@ -370,6 +423,16 @@ impl<RT: Runtime> SyncWorker<RT> {
                 && self.tx.transition_count() < *SYNC_MAX_SEND_TRANSITION_COUNT
                 && self.transition_future.is_none()
             {
+                // Hold the update back, leaving it scheduled, while the session's
+                // identity is over its transition bandwidth.
+                let identity = self.state.identity(self.rt.system_time())?;
+                if let Some(wait) = self
+                    .identity_quotas
+                    .transition_wait(&identity, self.rt.monotonic_now())
+                {
+                    bandwidth_wait = self.rt.wait(wait).boxed().fuse();
+                    continue;
+                }
                 // Always transition to the latest timestamp. In the future,
                 // when we have Sync Worker running on the edge, we can remove this
                 // call by making self.update_scheduled to be a Option<Timestamp>,
@@ -400,6 +463,24 @@ impl<RT: Runtime> SyncWorker<RT> {
         Ok(())
     }
 
+    /// Runs the mutations and actions the client already sent to completion
+    /// and sends their responses, without handling any new messages. Used to
+    /// drain the session before the backend shuts down, so the client learns
+    /// the outcome of its operations instead of retrying them elsewhere.
+    pub async fn finish_pending_operations(&mut self) -> anyhow::Result<()> {
+        // Replace the sender so the mutation stream ends once the queued
+        // mutations have run.
+        let (closed_sender, _) = mpsc::channel(1);
+        drop(mem::replace(&mut self.mutation_sender, closed_sender));
+        let mut pending = stream::select(&mut self.mutation_futures, &mut self.action_futures);
+        while let Some(result) = pending.next().await {
+            if self.tx.send((result?, self.rt.monotonic_now())).is_err() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
     pub fn identity_version(&self) -> IdentityVersion {
         self.state.current_version().identity
     }
@@ -476,6 +557,8 @@ impl<RT: Runtime> SyncWorker<RT> {
                 component_path,
             } => {
                 let identity = self.state.identity(self.rt.system_time())?;
+                self.identity_quotas
+                    .check_function_call(&identity, self.rt.monotonic_now())?;
                 let mutation_identifier =
                     self.state.session_id().map(|id| SessionRequestIdentifier {
                         session_id: id,
@@ -583,6 +666,8 @@ impl<RT: Runtime> SyncWorker<RT> {
                 component_path,
             } => {
                 let identity = self.state.identity(self.rt.system_time())?;
+                self.identity_quotas
+                    .check_function_call(&identity, self.rt.monotonic_now())?;
 
                 let api = self.api.clone();
                 let host = self.host.clone();
@@ -748,12 +833,18 @@ impl<RT: Runtime> SyncWorker<RT> {
                     self.state.insert(query)?;
                 },
                 QuerySetModification::Remove { query_id } => {
+                    self.active_subscriptions.remove(&query_id);
                     self.state.remove(query_id)?;
                     state_modifications
                         .insert(query_id, StateModification::QueryRemoved { query_id });
                 },
             }
         }
+        self.subscription_quota.update(
+            &identity,
+            self.state.num_queries(),
+            self.rt.monotonic_now(),
+        )?;
 
         // Step 3: Take all remaining subscriptions.
         let mut remaining_subscriptions = self.state.take_subscriptions();
@@ -761,99 +852,137 @@ impl<RT: Runtime> SyncWorker<RT> {
         // Step 4: Refresh subscriptions up to new_ts and run queries which
         // subscriptions are no longer current.
         let api = self.api.clone();
-        let need_fetch: Vec<_> = self.state.need_fetch().collect();
+        let need_fetch: Vec<_> = self
+            .state
+            .need_fetch()
+            .map(|query| {
+                let journal_compacted = self.state.journal_compacted(query.query_id);
+                (query, journal_compacted)
+            })
+            .collect();
         let host = self.host.clone();
         let client_version = self.config.client_version.clone();
         Ok(async move {
             let future_results: anyhow::Result<Vec<_>> = try_join_buffer_unordered(
                 "update_query",
-                need_fetch.into_iter().map(move |query| {
-                    let api = api.clone();
-                    let host = host.clone();
-                    let identity_ = identity.clone();
-                    let client_version = client_version.clone();
-                    let current_subscription = remaining_subscriptions.remove(&query.query_id);
-                    let subscriptions_client = subscriptions_client.clone();
-                    async move {
-                        LocalSpan::add_property(|| ("udf_path", query.udf_path.to_string()));
-                        let new_subscription = match current_subscription {
-                            Some(subscription) => {
-                                if subscription.extend_validity(new_ts).await? {
-                                    Some(subscription)
-                                } else {
-                                    None
-                                }
-                            },
-                            None => None,
-                        };
-                        let (query_result, subscription) = match new_subscription {
-                            Some(subscription) => (QueryResult::Refresh, subscription),
-                            None => {
-                                // We failed to refresh the subscription or it was invalid to start
-                                // with. Rerun the query.
-                                let caller = FunctionCaller::SyncWorker(client_version);
-                                let ts = ExecuteQueryTimestamp::At(new_ts);
-
-                                // This query run might have been triggered due to invalidation
-                                // of a subscription. The sync worker is effectively the owner
-                                // of the query so we do not want to re-use the original query
-                                // request id.
-                                let request_id = RequestId::new();
-                                let udf_return = match query.component_path {
-                                    None => {
-                                        api.execute_public_query(
-                                            &host,
-                                            request_id,
-                                            identity_,
-                                            ExportPath::from(query.udf_path.canonicalize()),
-                                            query.args,
-                                            caller,
-                                            ts,
-                                            query.journal,
-                                        )
-                                        .await?
-                                    },
-                                    Some(ref p) => {
-                                        let path = Self::parse_admin_component_path(
-                                            p,
-                                            &query.udf_path,
-                                            &identity_,
-                                        )?;
-                                        api.execute_admin_query(
-                                            &host,
-                                            request_id,
-                                            identity_,
-                                            path,
-                                            query.args,
-                                            caller,
-                                            ts,
-                                            query.journal,
-                                        )
-                                        .await?
-                                    },
-                                };
-                                let subscription =
-                                    subscriptions_client.subscribe(udf_return.token).await?;
-                                (
-                                    QueryResult::Rerun {
-                                        result: udf_return.result,
-                                        log_lines: udf_return.log_lines,
-                                        journal: udf_return.journal,
-                                    },
-                                    subscription,
-                                )
-                            },
-                        };
-                        Ok::<_, anyhow::Error>((query.query_id, query_result, subscription))
-                    }
-                }),
+                need_fetch
+                    .into_iter()
+                    .map(move |(query, journal_compacted)| {
+                        let api = api.clone();
+                        let host = host.clone();
+                        let identity_ = identity.clone();
+                        let client_version = client_version.clone();
+                        let current_subscription = remaining_subscriptions.remove(&query.query_id);
+                        let subscriptions_client = subscriptions_client.clone();
+                        async move {
+                            LocalSpan::add_property(|| ("udf_path", query.udf_path.to_string()));
+                            let mut invalidation = None;
+                            let new_subscription = match current_subscription {
+                                Some(subscription) => {
+                                    if subscription.extend_validity(new_ts).await? {
+                                        Some(subscription)
+                                    } else {
+                                        invalidation = Some(subscription.invalidation_cause());
+                                        None
+                                    }
+                                },
+                                None => None,
+                            };
+                            let (query_result, subscription, execution) = match new_subscription {
+                                Some(subscription) => (QueryResult::Refresh, subscription, None),
+                                None if journal_compacted => {
+                                    // The client removes the failed query when it restarts
+                                    // pagination, so it doesn't need to be invalidated again.
+                                    let subscription = subscriptions_client
+                                        .subscribe(Token::empty(new_ts))
+                                        .await?;
+                                    (
+                                        QueryResult::Rerun {
+                                            result: Err(compacted_journal_error()),
+                                            log_lines: RedactedLogLines::empty(),
+                                            journal: None,
+                                        },
+                                        subscription,
+                                        None,
+                                    )
+                                },
+                                None => {
+                                    // We failed to refresh the subscription or it was invalid to
+                                    // start with. Rerun the
+                                    // query.
+                                    let execution = QueryExecution {
+                                        path: SubscribedQueryPath::from(&query),
+                                        invalidation,
+                                    };
+                                    let caller = FunctionCaller::SyncWorker(client_version);
+                                    let ts = ExecuteQueryTimestamp::At(new_ts);
+
+                                    // This query run might have been triggered due to invalidation
+                                    // of a subscription. The sync worker is effectively the owner
+                                    // of the query so we do not want to re-use the original query
+                                    // request id.
+                                    let request_id = RequestId::new();
+                                    let udf_return = match query.component_path {
+                                        None => {
+                                            api.execute_public_query(
+                                                &host,
+                                                request_id,
+                                                identity_,
+                                                ExportPath::from(query.udf_path.canonicalize()),
+                                                query.args,
+                                                caller,
+                                                ts,
+                                                query.journal,
+                                            )
+                                            .await?
+                                        },
+                                        Some(ref p) => {
+                                            let path = Self::parse_admin_component_path(
+                                                p,
+                                                &query.udf_path,
+                                                &identity_,
+                                            )?;
+                                            api.execute_admin_query(
+                                                &host,
+                                                request_id,
+                                                identity_,
+                                                path,
+                                                query.args,
+                                                caller,
+                                                ts,
+                                                query.journal,
+                                            )
+                                            .await?
+                                        },
+                                    };
+                                    let subscription =
+                                        subscriptions_client.subscribe(udf_return.token).await?;
+                                    (
+                                        QueryResult::Rerun {
+                                            result: udf_return.result,
+                                            log_lines: udf_return.log_lines,
+                                            journal: udf_return.journal,
+                                        },
+                                        subscription,
+                                        Some(execution),
+                                    )
+                                },
+                            };
+                            Ok::<_, anyhow::Error>((
+                                query.query_id,
+                                query_result,
+                                subscription,
+                                execution,
+                            ))
+                        }
+                    }),
             )
             .await;
 
             let mut udf_results = vec![];
             for result in future_results? {
-                let (query_id, result, subscription) = result;
-                udf_results.push((query_id, result, subscription));
+                let (query_id, result, subscription, execution) = result;
+                udf_results.push((query_id, result, subscription, execution));
             }
 
             Ok(TransitionState {
@@ -877,13 +1006,15 @@ impl<RT: Runtime> SyncWorker<RT> {
             timer,
         }: TransitionState,
     ) -> anyhow::Result<ServerMessage> {
-        for (query_id, result, subscription) in udf_results {
+        let mut transition_bytes = 0;
+        for (query_id, result, subscription, execution) in udf_results {
             match result {
                 QueryResult::Rerun {
                     result,
                     log_lines,
                     journal,
                 } => {
+                    let succeeded = result.is_ok();
                     let modification = self.state.complete_fetch(
                         query_id,
                         result,
@@ -894,6 +1025,23 @@ impl<RT: Runtime> SyncWorker<RT> {
                     let Some(modification) = modification else {
                         continue;
                     };
+                    let size = modification_size(&modification) as u64;
+                    if let Some(execution) = execution {
+                        // Only queries that ran successfully are tracked, so a
+                        // client can't add entries for functions that don't
+                        // exist.
+                        if succeeded && !self.active_subscriptions.contains_key(&query_id) {
+                            let guard = self
+                                .subscription_stats
+                                .subscribe(execution.path, self.rt.unix_timestamp());
+                            self.active_subscriptions.insert(query_id, guard);
+                        }
+                        if let Some(guard) = self.active_subscriptions.get(&query_id) {
+                            guard.record_execution(execution.invalidation);
+                            guard.record_bytes_pushed(size);
+                        }
+                    }
+                    transition_bytes += size;
                     state_modifications.insert(query_id, modification);
                 },
                 QueryResult::Refresh => {
@@ -902,9 +1050,17 @@ impl<RT: Runtime> SyncWorker<RT> {
             }
         }
 
+        let identity = self.state.identity(self.rt.system_time())?;
+        self.identity_quotas.record_transition_bytes(
+            &identity,
+            transition_bytes,
+            self.rt.monotonic_now(),
+        );
+
         // Resubscribe for queries that don't have an active invalidation
         // future.
         self.state.fill_invalidation_futures()?;
+        self.state.compact_journals(*SYNC_MAX_SESSION_JOURNAL_BYTES);
 
         // Step 6: Send our transition to the client and update our version.
         self.state.advance_version(new_version)?;
@@ -926,3 +1082,23 @@ impl<RT: Runtime> SyncWorker<RT> {
         Ok(transition)
     }
 }
+
+/// Approximate size of a query result sent to the client.
+fn modification_size(modification: &StateModification<JsonPackedValue>) -> usize {
+    match modification {
+        StateModification::QueryUpdated {
+            value, log_lines, ..
+        } => value.as_str().len() + log_lines.0.iter().map(|line| line.len()).sum::<usize>(),
+        StateModification::QueryFailed {
+            error_message,
+            log_lines,
+            error_data,
+            ..
+        } => {
+            error_message.len()
+                + log_lines.0.iter().map(|line| line.len()).sum::<usize>()
+                + error_data.as_ref().map_or(0, |data| data.as_str().len())
+        },
+        StateModification::QueryRemoved { .. } => 0,
+    }
+}
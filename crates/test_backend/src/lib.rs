@@ -0,0 +1,177 @@
+//! In-process backend for integration tests.
+//!
+//! [`TestBackend`] runs an [`Application`] on top of in-memory persistence and
+//! a [`TestRuntime`], so crates building on the backend can push modules, call
+//! functions, and inspect the resulting tables without starting the server
+//! binary or going through HTTP.
+
+use std::time::Duration;
+
+use application::{
+    deploy_config::StartPushRequest,
+    test_helpers::ApplicationTestExt,
+    Application,
+};
+use common::{
+    bootstrap_model::index::DeveloperIndexMetadata,
+    components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentPath,
+        PublicFunctionPath,
+    },
+    query::{
+        Order,
+        Query,
+    },
+    types::FunctionCaller,
+    RequestId,
+};
+use database::{
+    IndexModel,
+    ResolvedQuery,
+};
+use keybroker::Identity;
+use model::config::types::ModuleConfig;
+use runtime::testing::TestRuntime;
+use serde_json::Value as JsonValue;
+use value::{
+    TableName,
+    TableNamespace,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// A backend running in the current process against in-memory persistence.
+///
+/// Functions are called as the system identity on the root component. Time
+/// only moves forward when [`TestBackend::advance_time`] is called, so
+/// scheduled functions and crons run deterministically.
+pub struct TestBackend {
+    rt: TestRuntime,
+    application: Application<TestRuntime>,
+}
+
+impl TestBackend {
+    pub async fn new(rt: TestRuntime) -> anyhow::Result<Self> {
+        let application = Application::new_for_tests(&rt).await?;
+        Ok(Self { rt, application })
+    }
+
+    /// The underlying application, for anything not covered by this API.
+    pub fn application(&self) -> &Application<TestRuntime> {
+        &self.application
+    }
+
+    pub fn runtime(&self) -> &TestRuntime {
+        &self.rt
+    }
+
+    /// Replace the root component's functions with `modules`.
+    pub async fn push_modules(&self, modules: Vec<ModuleConfig>) -> anyhow::Result<()> {
+        self.application.load_modules(modules).await
+    }
+
+    /// Run a full push, including components and schema, as `npx convex
+    /// deploy` would.
+    pub async fn push(&self, request: StartPushRequest) -> anyhow::Result<()> {
+        self.application.run_test_push(request).await?;
+        Ok(())
+    }
+
+    pub async fn query(&self, path: &str, args: JsonValue) -> anyhow::Result<JsonValue> {
+        let result = self
+            .application
+            .read_only_udf(
+                RequestId::new(),
+                Self::function_path(path)?,
+                vec![args],
+                Identity::system(),
+                FunctionCaller::Test,
+            )
+            .await?;
+        match result.result {
+            Ok(value) => Ok(value.json_value()),
+            Err(e) => anyhow::bail!("Query {path} failed: {e}"),
+        }
+    }
+
+    pub async fn mutation(&self, path: &str, args: JsonValue) -> anyhow::Result<JsonValue> {
+        let result = self
+            .application
+            .mutation_udf(
+                RequestId::new(),
+                Self::function_path(path)?,
+                vec![args],
+                Identity::system(),
+                None,
+                FunctionCaller::Test,
+                None,
+            )
+            .await?;
+        match result {
+            Ok(result) => Ok(result.value.json_value()),
+            Err(e) => anyhow::bail!("Mutation {path} failed: {}", e.error),
+        }
+    }
+
+    pub async fn action(&self, path: &str, args: JsonValue) -> anyhow::Result<JsonValue> {
+        let result = self
+            .application
+            .action_udf(
+                RequestId::new(),
+                Self::function_path(path)?,
+                vec![args],
+                Identity::system(),
+                FunctionCaller::Test,
+            )
+            .await?;
+        match result {
+            Ok(result) => Ok(result.value.json_value()),
+            Err(e) => anyhow::bail!("Action {path} failed: {}", e.error),
+        }
+    }
+
+    /// Move the clock forward, letting any scheduled functions and crons that
+    /// come due run.
+    pub async fn advance_time(&self, duration: Duration) {
+        self.rt.advance_time(duration).await;
+    }
+
+    /// All documents in a root component table, in `_creationTime` order.
+    pub async fn documents(&self, table_name: &str) -> anyhow::Result<Vec<JsonValue>> {
+        let table_name: TableName = table_name.parse()?;
+        let mut tx = self.application.begin(Identity::system()).await?;
+        let mut query = ResolvedQuery::new(
+            &mut tx,
+            TableNamespace::root_component(),
+            Query::full_table_scan(table_name, Order::Asc),
+        )?;
+        let mut documents = vec![];
+        while let Some(document) = query.next(&mut tx, None).await? {
+            documents.push(JsonValue::from(document.into_value().0));
+        }
+        Ok(documents)
+    }
+
+    /// The indexes currently registered on the root component's tables.
+    pub async fn indexes(&self) -> anyhow::Result<Vec<DeveloperIndexMetadata>> {
+        let mut tx = self.application.begin(Identity::system()).await?;
+        let indexes = IndexModel::new(&mut tx)
+            .get_application_indexes(TableNamespace::root_component())
+            .await?;
+        Ok(indexes
+            .into_iter()
+            .map(|index| index.into_value())
+            .collect())
+    }
+
+    fn function_path(path: &str) -> anyhow::Result<PublicFunctionPath> {
+        Ok(PublicFunctionPath::Component(
+            CanonicalizedComponentFunctionPath {
+                component: ComponentPath::root(),
+                udf_path: path.parse()?,
+            },
+        ))
+    }
+}
@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+use isolate::test_helpers::TEST_SOURCE_ISOLATE_ONLY;
+use runtime::testing::TestRuntime;
+use serde_json::json;
+
+use crate::TestBackend;
+
+#[convex_macro::test_runtime]
+async fn test_push_call_and_inspect(rt: TestRuntime) -> anyhow::Result<()> {
+    let backend = TestBackend::new(rt).await?;
+    backend
+        .push_modules(TEST_SOURCE_ISOLATE_ONLY.clone())
+        .await?;
+
+    backend
+        .mutation("basic:insertObject", json!({"an": "object"}))
+        .await?;
+    backend.advance_time(Duration::from_secs(1)).await;
+
+    let documents = backend.documents("objects").await?;
+    assert_eq!(documents.len(), 1);
+    assert_eq!(documents[0]["an"], json!("object"));
+    assert_eq!(backend.query("basic:count", json!({})).await?, json!(1.0));
+
+    // `objects` isn't in the schema, so it only has the system indexes.
+    let indexes = backend.indexes().await?;
+    assert!(!indexes
+        .iter()
+        .any(|index| index.name.table().as_ref() == "objects"));
+
+    let err = backend
+        .mutation("basic:doesNotExist", json!({}))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("basic:doesNotExist"), "{err}");
+    Ok(())
+}
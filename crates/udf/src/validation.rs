@@ -9,6 +9,7 @@ use common::{
     },
     errors::JsError,
     identity::InertIdentity,
+    knobs::VALIDATE_SCHEDULED_FUNCTION_ARGS,
     log_lines::LogLines,
     query_journal::QueryJournal,
     runtime::{
@@ -180,11 +181,11 @@ pub async fn validate_schedule_args<RT: Runtime>(
     // the interface and make AnalyzedResult non-optional in the future.
     let function_name = canonicalized.udf_path.function_name();
     if let Some(analyze_result) = &module.analyze_result {
-        let found = analyze_result
+        let Some(analyzed_function) = analyze_result
             .functions
             .iter()
-            .any(|f| &f.name == function_name);
-        if !found {
+            .find(|f| &f.name == function_name)
+        else {
             anyhow::bail!(ErrorMetadata::bad_request(
                 "InvalidScheduledFunction",
                 format!(
@@ -195,6 +196,28 @@ pub async fn validate_schedule_args<RT: Runtime>(
                     path.component.in_component_str(),
                 ),
             ));
+        };
+        // The function may still change before the job runs, in which case
+        // it's validated again then.
+        if *VALIDATE_SCHEDULED_FUNCTION_ARGS {
+            let (_, component) =
+                BootstrapComponentsModel::new(tx).must_component_path_to_ids(&path.component)?;
+            let table_mapping = &tx.table_mapping().namespace(component.into());
+            if let Some(error) = analyzed_function.args()?.check_args(
+                &udf_args,
+                table_mapping,
+                virtual_system_mapping(),
+            )? {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "InvalidScheduledFunctionArgs",
+                    format!(
+                        "Attempted to schedule {}{} with invalid arguments. \
+                         ArgumentValidationError: {error}",
+                        String::from(path.udf_path.clone().strip()),
+                        path.component.in_component_str(),
+                    ),
+                ));
+            }
         }
     }
 
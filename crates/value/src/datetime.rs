@@ -0,0 +1,229 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    str::FromStr,
+};
+
+use chrono::{
+    DateTime,
+    FixedOffset,
+    SecondsFormat,
+    Utc,
+};
+use errors::ErrorMetadata;
+
+use crate::{
+    heap_size::HeapSize,
+    size::Size,
+};
+
+/// Largest UTC offset a [`ConvexDateTime`] can carry, in minutes.
+pub const MAX_OFFSET_MINUTES: i16 = 23 * 60 + 59;
+
+/// An instant with nanosecond precision, along with the UTC offset it was
+/// written in.
+///
+/// Datetimes order by instant, so `2024-01-01T12:00:00+02:00` sorts before
+/// `2024-01-01T11:00:00Z`. Two datetimes for the same instant in different
+/// offsets are distinct values, ordered by offset.
+///
+/// The instant is stored as nanoseconds since the Unix epoch, which covers
+/// 1677-09-21 through 2262-04-11.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ConvexDateTime {
+    nanos_since_epoch: i64,
+    offset_minutes: i16,
+}
+
+fn out_of_range(message: impl Into<String>) -> ErrorMetadata {
+    ErrorMetadata::bad_request("DateTimeOutOfRange", message.into())
+}
+
+impl ConvexDateTime {
+    pub fn new(nanos_since_epoch: i64, offset_minutes: i16) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            offset_minutes.abs() <= MAX_OFFSET_MINUTES,
+            out_of_range(format!(
+                "UTC offset of {offset_minutes} minutes is outside of [-{MAX_OFFSET_MINUTES}, \
+                 {MAX_OFFSET_MINUTES}]"
+            ))
+        );
+        Ok(Self {
+            nanos_since_epoch,
+            offset_minutes,
+        })
+    }
+
+    /// A datetime in UTC.
+    pub fn from_nanos(nanos_since_epoch: i64) -> Self {
+        Self {
+            nanos_since_epoch,
+            offset_minutes: 0,
+        }
+    }
+
+    pub fn nanos_since_epoch(&self) -> i64 {
+        self.nanos_since_epoch
+    }
+
+    pub fn offset_minutes(&self) -> i16 {
+        self.offset_minutes
+    }
+
+    /// The same instant in UTC.
+    pub fn to_utc(self) -> Self {
+        Self::from_nanos(self.nanos_since_epoch)
+    }
+
+    fn to_chrono(self) -> DateTime<FixedOffset> {
+        let offset = FixedOffset::east_opt(i32::from(self.offset_minutes) * 60)
+            .expect("offset checked in constructor");
+        DateTime::<Utc>::from_timestamp_nanos(self.nanos_since_epoch).with_timezone(&offset)
+    }
+}
+
+impl Ord for ConvexDateTime {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.nanos_since_epoch
+            .cmp(&other.nanos_since_epoch)
+            .then(self.offset_minutes.cmp(&other.offset_minutes))
+    }
+}
+
+impl PartialOrd for ConvexDateTime {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl FromStr for ConvexDateTime {
+    type Err = anyhow::Error;
+
+    /// Parses an RFC 3339 timestamp like `2024-01-02T03:04:05.123456789+02:00`.
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let datetime = DateTime::parse_from_rfc3339(s).map_err(|e| {
+            ErrorMetadata::bad_request(
+                "InvalidDateTime",
+                format!("{s:?} isn't an RFC 3339 datetime like \"2024-01-02T03:04:05Z\": {e}"),
+            )
+        })?;
+        let nanos_since_epoch = datetime
+            .timestamp_nanos_opt()
+            .ok_or_else(|| out_of_range(format!("{s:?} is outside of years 1677 through 2262")))?;
+        let offset_seconds = datetime.offset().local_minus_utc();
+        anyhow::ensure!(
+            offset_seconds % 60 == 0,
+            ErrorMetadata::bad_request(
+                "InvalidDateTime",
+                format!("{s:?} has a UTC offset that isn't a whole number of minutes"),
+            )
+        );
+        Self::new(nanos_since_epoch, (offset_seconds / 60) as i16)
+    }
+}
+
+impl fmt::Display for ConvexDateTime {
+    /// Formats as RFC 3339 with as many fractional digits (0, 3, 6 or 9) as
+    /// the value needs, and `Z` for UTC.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.to_chrono()
+                .to_rfc3339_opts(SecondsFormat::AutoSi, true)
+        )
+    }
+}
+
+impl Size for ConvexDateTime {
+    fn size(&self) -> usize {
+        1 + 8 + 2
+    }
+
+    fn nesting(&self) -> usize {
+        0
+    }
+}
+
+impl HeapSize for ConvexDateTime {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl proptest::arbitrary::Arbitrary for ConvexDateTime {
+    type Parameters = ();
+
+    type Strategy = impl proptest::strategy::Strategy<Value = ConvexDateTime>;
+
+    fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+        use proptest::prelude::*;
+        // Stay clear of the edges of the `i64` range, where an offset could push
+        // the local time past what chrono can represent.
+        (
+            -(1i64 << 62)..(1i64 << 62),
+            prop_oneof![Just(0), -MAX_OFFSET_MINUTES..=MAX_OFFSET_MINUTES],
+        )
+            .prop_map(|(nanos_since_epoch, offset_minutes)| ConvexDateTime {
+                nanos_since_epoch,
+                offset_minutes,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cmd_util::env::env_config;
+    use proptest::prelude::*;
+
+    use super::ConvexDateTime;
+
+    #[test]
+    fn test_parse_and_format() -> anyhow::Result<()> {
+        for (input, expected) in [
+            ("2024-01-02T03:04:05Z", "2024-01-02T03:04:05Z"),
+            ("2024-01-02T03:04:05+00:00", "2024-01-02T03:04:05Z"),
+            ("2024-01-02T03:04:05.5Z", "2024-01-02T03:04:05.500Z"),
+            (
+                "2024-01-02T03:04:05.123456789-07:30",
+                "2024-01-02T03:04:05.123456789-07:30",
+            ),
+            ("1970-01-01T00:00:00.000001Z", "1970-01-01T00:00:00.000001Z"),
+        ] {
+            assert_eq!(input.parse::<ConvexDateTime>()?.to_string(), expected);
+        }
+        for invalid in [
+            "",
+            "2024-01-02",
+            "2024-01-02T03:04:05",
+            "2024-13-02T03:04:05Z",
+            "3000-01-01T00:00:00Z",
+        ] {
+            assert!(invalid.parse::<ConvexDateTime>().is_err(), "{invalid}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_orders_by_instant() -> anyhow::Result<()> {
+        let d = |s: &str| s.parse::<ConvexDateTime>().unwrap();
+        assert!(d("2024-01-01T12:00:00+02:00") < d("2024-01-01T11:00:00Z"));
+        assert!(d("2024-01-01T10:00:00Z") < d("2024-01-01T12:00:00+02:00"));
+        assert_ne!(d("2024-01-01T10:00:00Z"), d("2024-01-01T12:00:00+02:00"));
+        assert_eq!(
+            d("2024-01-01T10:00:00Z"),
+            d("2024-01-01T12:00:00+02:00").to_utc()
+        );
+        Ok(())
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig { cases: 256 * env_config("CONVEX_PROPTEST_MULTIPLIER", 1), failure_persistence: None, .. ProptestConfig::default() })]
+
+        #[test]
+        fn test_display_roundtrips(d in any::<ConvexDateTime>()) {
+            prop_assert_eq!(d.to_string().parse::<ConvexDateTime>().unwrap(), d);
+        }
+    }
+}
@@ -0,0 +1,374 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    str::FromStr,
+};
+
+use errors::ErrorMetadata;
+
+use crate::{
+    heap_size::HeapSize,
+    size::Size,
+};
+
+/// Maximum number of significant digits in a [`Decimal128`].
+pub const MAX_DECIMAL_DIGITS: u32 = 34;
+/// Smallest exponent of a [`Decimal128`]'s least significant digit.
+pub const MIN_DECIMAL_EXPONENT: i32 = -6176;
+/// Largest exponent of a [`Decimal128`]'s least significant digit.
+pub const MAX_DECIMAL_EXPONENT: i32 = 6111;
+
+const MAX_COEFFICIENT: u128 = 10u128.pow(MAX_DECIMAL_DIGITS) - 1;
+
+/// An exact base-10 number `coefficient * 10^exponent` with the precision and
+/// range of IEEE 754 decimal128, but without NaNs, infinities or negative
+/// zero.
+///
+/// Decimals are kept normalized, without trailing zeros in the coefficient, so
+/// `1.50` and `1.5` are the same value and the derived `Eq` and `Hash` compare
+/// numeric values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Decimal128 {
+    coefficient: i128,
+    exponent: i32,
+}
+
+fn out_of_range(message: impl Into<String>) -> ErrorMetadata {
+    ErrorMetadata::bad_request("DecimalOutOfRange", message.into())
+}
+
+fn num_digits(n: u128) -> u32 {
+    n.checked_ilog10().unwrap_or(0) + 1
+}
+
+impl Decimal128 {
+    pub const ZERO: Self = Self {
+        coefficient: 0,
+        exponent: 0,
+    };
+
+    pub fn new(mut coefficient: i128, mut exponent: i32) -> anyhow::Result<Self> {
+        if coefficient == 0 {
+            return Ok(Self::ZERO);
+        }
+        while coefficient % 10 == 0 {
+            coefficient /= 10;
+            exponent = exponent
+                .checked_add(1)
+                .ok_or_else(|| out_of_range("Decimal exponent is too large"))?;
+        }
+        anyhow::ensure!(
+            coefficient.unsigned_abs() <= MAX_COEFFICIENT,
+            out_of_range(format!(
+                "Decimal has more than {MAX_DECIMAL_DIGITS} significant digits"
+            ))
+        );
+        anyhow::ensure!(
+            (MIN_DECIMAL_EXPONENT..=MAX_DECIMAL_EXPONENT).contains(&exponent),
+            out_of_range(format!(
+                "Decimal exponent {exponent} is outside of [{MIN_DECIMAL_EXPONENT}, \
+                 {MAX_DECIMAL_EXPONENT}]"
+            ))
+        );
+        Ok(Self {
+            coefficient,
+            exponent,
+        })
+    }
+
+    pub fn coefficient(&self) -> i128 {
+        self.coefficient
+    }
+
+    pub fn exponent(&self) -> i32 {
+        self.exponent
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coefficient == 0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.coefficient < 0
+    }
+
+    /// The significant digits of the absolute value, without trailing zeros.
+    pub fn digits(&self) -> String {
+        self.coefficient.unsigned_abs().to_string()
+    }
+
+    /// The exponent of the most significant digit, e.g. 2 for `123` and -2 for
+    /// `0.0123`.
+    pub fn adjusted_exponent(&self) -> i32 {
+        self.exponent + num_digits(self.coefficient.unsigned_abs()) as i32 - 1
+    }
+
+    pub fn checked_add(self, other: Self) -> anyhow::Result<Self> {
+        let exponent = self.exponent.min(other.exponent);
+        let aligned = |d: Self| {
+            10i128
+                .checked_pow((d.exponent - exponent) as u32)
+                .and_then(|scale| d.coefficient.checked_mul(scale))
+        };
+        let sum = aligned(self)
+            .zip(aligned(other))
+            .and_then(|(l, r)| l.checked_add(r))
+            .ok_or_else(|| {
+                out_of_range(format!(
+                    "{self} + {other} needs more than {MAX_DECIMAL_DIGITS} significant digits"
+                ))
+            })?;
+        Self::new(sum, exponent)
+    }
+
+    pub fn checked_sub(self, other: Self) -> anyhow::Result<Self> {
+        self.checked_add(-other)
+    }
+
+    pub fn checked_mul(self, other: Self) -> anyhow::Result<Self> {
+        let product = self
+            .coefficient
+            .checked_mul(other.coefficient)
+            .ok_or_else(|| {
+                out_of_range(format!(
+                    "{self} * {other} needs more than {MAX_DECIMAL_DIGITS} significant digits"
+                ))
+            })?;
+        Self::new(product, self.exponent.saturating_add(other.exponent))
+    }
+
+    fn cmp_magnitude(&self, other: &Self) -> Ordering {
+        let (l, r) = (
+            self.coefficient.unsigned_abs(),
+            other.coefficient.unsigned_abs(),
+        );
+        let (l_digits, r_digits) = (num_digits(l), num_digits(r));
+        self.adjusted_exponent()
+            .cmp(&other.adjusted_exponent())
+            .then_with(|| {
+                // Same order of magnitude, so pad the shorter coefficient with zeros.
+                if l_digits < r_digits {
+                    (l * 10u128.pow(r_digits - l_digits)).cmp(&r)
+                } else {
+                    l.cmp(&(r * 10u128.pow(l_digits - r_digits)))
+                }
+            })
+    }
+}
+
+impl std::ops::Neg for Decimal128 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            coefficient: -self.coefficient,
+            exponent: self.exponent,
+        }
+    }
+}
+
+impl Ord for Decimal128 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let sign_cmp = self.coefficient.signum().cmp(&other.coefficient.signum());
+        if !sign_cmp.is_eq() || self.is_zero() {
+            return sign_cmp;
+        }
+        let magnitude_cmp = self.cmp_magnitude(other);
+        if self.is_negative() {
+            magnitude_cmp.reverse()
+        } else {
+            magnitude_cmp
+        }
+    }
+}
+
+impl PartialOrd for Decimal128 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl FromStr for Decimal128 {
+    type Err = anyhow::Error;
+
+    /// Parses plain (`-12.50`) or scientific (`1.25e-3`) notation.
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let invalid = || {
+            ErrorMetadata::bad_request(
+                "InvalidDecimal",
+                format!("{s:?} isn't a decimal number like \"-12.50\" or \"1.25e-3\""),
+            )
+        };
+        let (negative, unsigned) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let (mantissa, exponent) = match unsigned.split_once(['e', 'E']) {
+            Some((mantissa, exponent)) => {
+                (mantissa, exponent.parse::<i32>().map_err(|_| invalid())?)
+            },
+            None => (unsigned, 0),
+        };
+        let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        anyhow::ensure!(
+            !(integer.is_empty() && fraction.is_empty())
+                && integer
+                    .bytes()
+                    .chain(fraction.bytes())
+                    .all(|b| b.is_ascii_digit()),
+            invalid()
+        );
+        let mut exponent = exponent
+            .checked_sub(fraction.len() as i32)
+            .ok_or_else(|| out_of_range("Decimal exponent is too small"))?;
+        let digits = format!("{integer}{fraction}");
+        let digits = digits.trim_start_matches('0');
+        let significant = digits.trim_end_matches('0');
+        if significant.is_empty() {
+            return Ok(Self::ZERO);
+        }
+        exponent = exponent
+            .checked_add((digits.len() - significant.len()) as i32)
+            .ok_or_else(|| out_of_range("Decimal exponent is too large"))?;
+        anyhow::ensure!(
+            significant.len() <= MAX_DECIMAL_DIGITS as usize,
+            out_of_range(format!(
+                "{s:?} has more than {MAX_DECIMAL_DIGITS} significant digits"
+            ))
+        );
+        let coefficient: i128 = significant.parse()?;
+        Self::new(if negative { -coefficient } else { coefficient }, exponent)
+    }
+}
+
+impl fmt::Display for Decimal128 {
+    /// Formats in plain notation unless the number is very large or very
+    /// small, in which case it uses scientific notation like `1.5e40`. The
+    /// output is canonical: equal decimals format the same way.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_negative() {
+            write!(f, "-")?;
+        }
+        let digits = self.digits();
+        let adjusted_exponent = self.adjusted_exponent();
+        if !(-7..MAX_DECIMAL_DIGITS as i32).contains(&adjusted_exponent) {
+            let (first, rest) = digits.split_at(1);
+            write!(f, "{first}")?;
+            if !rest.is_empty() {
+                write!(f, ".{rest}")?;
+            }
+            return write!(f, "e{adjusted_exponent}");
+        }
+        if self.exponent >= 0 {
+            return write!(f, "{digits}{}", "0".repeat(self.exponent as usize));
+        }
+        let point = digits.len() as i32 + self.exponent;
+        if point > 0 {
+            let (integer, fraction) = digits.split_at(point as usize);
+            write!(f, "{integer}.{fraction}")
+        } else {
+            write!(f, "0.{}{digits}", "0".repeat(-point as usize))
+        }
+    }
+}
+
+impl Size for Decimal128 {
+    fn size(&self) -> usize {
+        1 + 16 + 4
+    }
+
+    fn nesting(&self) -> usize {
+        0
+    }
+}
+
+impl HeapSize for Decimal128 {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl proptest::arbitrary::Arbitrary for Decimal128 {
+    type Parameters = ();
+
+    type Strategy = impl proptest::strategy::Strategy<Value = Decimal128>;
+
+    fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+        use proptest::prelude::*;
+        let max = MAX_COEFFICIENT as i128;
+        prop_oneof![
+            (-1_000_000i128..1_000_000, -10i32..10),
+            (-max..=max, -60i32..60),
+        ]
+        .prop_filter_map("Decimal wasn't in range", |(coefficient, exponent)| {
+            Decimal128::new(coefficient, exponent).ok()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cmd_util::env::env_config;
+    use proptest::prelude::*;
+
+    use super::Decimal128;
+
+    #[test]
+    fn test_parse_and_format() -> anyhow::Result<()> {
+        for (input, expected) in [
+            ("0", "0"),
+            ("-0.00", "0"),
+            ("12.50", "12.5"),
+            ("100", "100"),
+            ("-0.001", "-0.001"),
+            ("1.25e-3", "0.00125"),
+            ("1e40", "1e40"),
+            ("0.000000001", "1e-9"),
+            (
+                "9999999999999999999999999999999999",
+                "9999999999999999999999999999999999",
+            ),
+        ] {
+            assert_eq!(input.parse::<Decimal128>()?.to_string(), expected);
+        }
+        for invalid in [
+            "",
+            ".",
+            "1.2.3",
+            "abc",
+            "1e",
+            "99999999999999999999999999999999999",
+        ] {
+            assert!(invalid.parse::<Decimal128>().is_err(), "{invalid}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_arithmetic() -> anyhow::Result<()> {
+        let d = |s: &str| s.parse::<Decimal128>().unwrap();
+        // The classic float64 failure is exact with decimals.
+        assert_eq!(d("0.1").checked_add(d("0.2"))?, d("0.3"));
+        assert_eq!(d("10.00").checked_sub(d("0.01"))?, d("9.99"));
+        assert_eq!(d("1.5").checked_mul(d("-2"))?, d("-3"));
+        assert!(d("1e30").checked_add(d("1e-30")).is_err());
+        Ok(())
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig { cases: 256 * env_config("CONVEX_PROPTEST_MULTIPLIER", 1), failure_persistence: None, .. ProptestConfig::default() })]
+
+        #[test]
+        fn test_display_roundtrips(d in any::<Decimal128>()) {
+            prop_assert_eq!(d.to_string().parse::<Decimal128>().unwrap(), d);
+        }
+
+        #[test]
+        fn test_ord_matches_subtraction(l in any::<Decimal128>(), r in any::<Decimal128>()) {
+            if let Ok(difference) = l.checked_sub(r) {
+                prop_assert_eq!(l.cmp(&r), difference.cmp(&Decimal128::ZERO));
+            }
+        }
+    }
+}
@@ -113,6 +113,8 @@ impl ConvexValue {
                 let bytes: Vec<u8> = value.into();
                 JsonValue::String(base64::encode(bytes))
             },
+            ConvexValue::Decimal(value) => JsonValue::String(value.to_string()),
+            ConvexValue::DateTime(value) => JsonValue::String(value.to_string()),
             ConvexValue::Array(values) => {
                 JsonValue::Array(values.into_iter().map(|x| x.export_clean()).collect())
             },
@@ -130,6 +130,16 @@ pub mod value {
                 obj.serialize_entry("$bytes", &JsonBytes::encode(b))?;
                 obj.end()
             },
+            ConvexValue::Decimal(d) => {
+                let mut obj = serializer.serialize_map(Some(1))?;
+                obj.serialize_entry("$decimal", &d.to_string())?;
+                obj.end()
+            },
+            ConvexValue::DateTime(d) => {
+                let mut obj = serializer.serialize_map(Some(1))?;
+                obj.serialize_entry("$datetime", &d.to_string())?;
+                obj.end()
+            },
             ConvexValue::Array(a) => super::array::serialize(a, serializer),
             ConvexValue::Set(s) => {
                 crate::metrics::log_serialized_set();
@@ -252,6 +262,14 @@ impl TryFrom<JsonValue> for ConvexValue {
                             }
                             Self::from(n)
                         },
+                        "$decimal" => {
+                            let d: String = serde_json::from_value(value)?;
+                            Self::Decimal(d.parse()?)
+                        },
+                        "$datetime" => {
+                            let d: String = serde_json::from_value(value)?;
+                            Self::DateTime(d.parse()?)
+                        },
                         "$set" => {
                             metrics::log_deserialized_set();
                             let items = match value {
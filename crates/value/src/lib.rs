@@ -11,6 +11,8 @@ mod array;
 pub mod base32;
 pub mod base64;
 mod bytes;
+pub mod datetime;
+pub mod decimal;
 mod document_id;
 pub mod export;
 mod field_name;
@@ -66,6 +68,8 @@ use walk::ConvexValueWalker;
 pub use crate::{
     array::ConvexArray,
     bytes::ConvexBytes,
+    datetime::ConvexDateTime,
+    decimal::Decimal128,
     document_id::{
         DeveloperDocumentId,
         InternalDocumentId,
@@ -106,9 +110,12 @@ pub use crate::{
     set::ConvexSet,
     size::{
         check_nesting_for_documents,
+        check_user_document_size,
         check_user_size,
         Size,
+        MAX_CHUNKED_DOCUMENT_SIZE,
         MAX_DOCUMENT_NESTING,
+        MAX_INLINE_FIELD_SIZE,
         MAX_NESTING,
         MAX_SIZE,
         MAX_USER_SIZE,
@@ -162,6 +169,15 @@ pub enum ConvexValue {
     /// Arbitrary binary data.
     Bytes(ConvexBytes),
 
+    /// Exact base-10 number, for values like money that can't be rounded.
+    /// Decimals sort after every other type so that adding them didn't change
+    /// the order of existing index keys.
+    Decimal(Decimal128),
+
+    /// An instant with nanosecond precision and the UTC offset it was written
+    /// in. Datetimes order by instant and sort after decimals.
+    DateTime(ConvexDateTime),
+
     /// Arrays of (potentially heterogeneous) [`ConvexValue`]s.
     Array(ConvexArray),
 
@@ -231,6 +247,18 @@ impl From<f64> for ConvexValue {
     }
 }
 
+impl From<Decimal128> for ConvexValue {
+    fn from(d: Decimal128) -> Self {
+        Self::Decimal(d)
+    }
+}
+
+impl From<ConvexDateTime> for ConvexValue {
+    fn from(d: ConvexDateTime) -> Self {
+        Self::DateTime(d)
+    }
+}
+
 impl From<bool> for ConvexValue {
     fn from(i: bool) -> Self {
         Self::Boolean(i)
@@ -376,6 +404,8 @@ impl Display for ConvexValue {
             ConvexValue::Boolean(b) => write!(f, "{:?}", b),
             ConvexValue::String(s) => write!(f, "{:?}", s),
             ConvexValue::Bytes(b) => write!(f, "{}", b),
+            ConvexValue::Decimal(d) => write!(f, "decimal(\"{}\")", d),
+            ConvexValue::DateTime(d) => write!(f, "datetime(\"{}\")", d),
             ConvexValue::Array(arr) => write!(f, "{}", arr),
             ConvexValue::Set(set) => write!(f, "{}", set),
             ConvexValue::Map(map) => write!(f, "{}", map),
@@ -393,6 +423,8 @@ impl Size for ConvexValue {
             ConvexValue::Boolean(_) => 1,
             ConvexValue::String(s) => s.size(),
             ConvexValue::Bytes(b) => b.size(),
+            ConvexValue::Decimal(d) => d.size(),
+            ConvexValue::DateTime(d) => d.size(),
             ConvexValue::Array(arr) => arr.size(),
             ConvexValue::Set(set) => set.size(),
             ConvexValue::Map(map) => map.size(),
@@ -408,6 +440,8 @@ impl Size for ConvexValue {
             ConvexValue::Boolean(_) => 0,
             ConvexValue::String(_) => 0,
             ConvexValue::Bytes(_) => 0,
+            ConvexValue::Decimal(_) => 0,
+            ConvexValue::DateTime(_) => 0,
             ConvexValue::Array(arr) => arr.nesting(),
             ConvexValue::Set(set) => set.nesting(),
             ConvexValue::Map(map) => map.nesting(),
@@ -425,6 +459,8 @@ impl HeapSize for ConvexValue {
             ConvexValue::Boolean(_) => 0,
             ConvexValue::String(s) => s.heap_size(),
             ConvexValue::Bytes(b) => b.heap_size(),
+            ConvexValue::Decimal(d) => d.heap_size(),
+            ConvexValue::DateTime(d) => d.heap_size(),
             ConvexValue::Array(arr) => arr.heap_size(),
             ConvexValue::Set(set) => set.heap_size(),
             ConvexValue::Map(map) => map.heap_size(),
@@ -477,6 +513,14 @@ impl Hash for ConvexValue {
                 h.write_u8(11);
                 o.hash(h);
             },
+            ConvexValue::Decimal(d) => {
+                h.write_u8(12);
+                d.hash(h);
+            },
+            ConvexValue::DateTime(d) => {
+                h.write_u8(13);
+                d.hash(h);
+            },
         }
     }
 }
@@ -568,6 +612,8 @@ pub mod proptest {
 
     use super::{
         bytes::ConvexBytes,
+        datetime::ConvexDateTime,
+        decimal::Decimal128,
         string::ConvexString,
         ConvexValue,
     };
@@ -682,6 +728,8 @@ pub mod proptest {
                 Err(_) => Some(ConvexValue::String(s))
             }),
             1 => any::<ConvexBytes>().prop_map(ConvexValue::Bytes),
+            1 => any::<Decimal128>().prop_map(ConvexValue::Decimal),
+            1 => any::<ConvexDateTime>().prop_map(ConvexValue::DateTime),
         ];
         let map_set_weight = if exclude_sets_and_maps.0 { 0 } else { 1 };
         let ValueBranching {
@@ -105,6 +105,8 @@ impl<'de, W: ConvexValueWalker> serde::Deserializer<'de> for Deserialize<W> {
             ConvexValueType::Boolean(b) => visitor.visit_bool(b),
             ConvexValueType::String(s) => visitor.visit_str(s.as_str()),
             ConvexValueType::Bytes(b) => visitor.visit_bytes(b.as_bytes()),
+            ConvexValueType::Decimal(d) => visitor.visit_string(d.to_string()),
+            ConvexValueType::DateTime(d) => visitor.visit_string(d.to_string()),
             ConvexValueType::Array(v) => visit_array(v, visitor),
             ConvexValueType::Object(v) => visit_object(v, visitor),
             ConvexValueType::Set(_) => Err(anyhow::anyhow!("Unsupported Set").into()),
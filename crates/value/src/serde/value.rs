@@ -30,6 +30,8 @@ impl Serialize for ConvexValue {
             ConvexValue::Boolean(b) => serializer.serialize_bool(*b),
             ConvexValue::String(s) => serializer.serialize_str(s),
             ConvexValue::Bytes(b) => serializer.serialize_bytes(b),
+            ConvexValue::Decimal(d) => serializer.collect_str(d),
+            ConvexValue::DateTime(d) => serializer.collect_str(d),
             ConvexValue::Array(a) => a.serialize(serializer),
             ConvexValue::Set(_) => Err(S::Error::custom("Set serialization not supported")),
             ConvexValue::Map(_) => Err(S::Error::custom("Map serialization not supported")),
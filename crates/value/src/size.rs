@@ -4,8 +4,17 @@ use humansize::{
     BINARY,
 };
 
+use crate::ConvexObject;
+
 pub const MAX_SIZE: usize = 1 << 25; // 32 MB
 pub const MAX_USER_SIZE: usize = 1 << 20; // 1MB
+/// Top-level document fields larger than this are stored out of line in
+/// chunks and can't be indexed.
+pub const MAX_INLINE_FIELD_SIZE: usize = 1 << 18; // 256KB
+/// Documents with chunked fields can grow up to this size on persistence that
+/// supports chunking, and are limited to `MAX_USER_SIZE` elsewhere. The fields
+/// stored inline are still limited to `MAX_USER_SIZE`.
+pub const MAX_CHUNKED_DOCUMENT_SIZE: usize = 1 << 24; // 16MB
 pub const MAX_NESTING: usize = 64;
 pub const MAX_DOCUMENT_NESTING: usize = 16;
 pub const VALUE_TOO_LARGE_SHORT_MSG: &str = "ValueTooLargeError";
@@ -52,6 +61,44 @@ pub fn check_user_size(size: usize) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Checks a user document's size, allowing documents over `MAX_USER_SIZE` as
+/// long as the excess is in fields large enough to be chunked.
+pub fn check_user_document_size(object: &ConvexObject) -> anyhow::Result<()> {
+    let size = object.size();
+    if size <= MAX_USER_SIZE {
+        return Ok(());
+    }
+    if size > MAX_CHUNKED_DOCUMENT_SIZE {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            VALUE_TOO_LARGE_SHORT_MSG,
+            format!(
+                "Value is too large ({} > maximum size {})",
+                size.format_size(BINARY),
+                MAX_CHUNKED_DOCUMENT_SIZE.format_size(BINARY),
+            )
+        ));
+    }
+    let chunked_size: usize = object
+        .values()
+        .map(|value| value.size())
+        .filter(|size| *size > MAX_INLINE_FIELD_SIZE)
+        .sum();
+    let inline_size = size - chunked_size;
+    if inline_size > MAX_USER_SIZE {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            VALUE_TOO_LARGE_SHORT_MSG,
+            format!(
+                "Value is too large ({} stored inline > maximum size {}). Only top-level fields \
+                 larger than {} are stored separately.",
+                inline_size.format_size(BINARY),
+                MAX_USER_SIZE.format_size(BINARY),
+                MAX_INLINE_FIELD_SIZE.format_size(BINARY),
+            )
+        ));
+    }
+    Ok(())
+}
+
 pub fn check_nesting(nesting: usize) -> anyhow::Result<()> {
     if nesting > MAX_NESTING {
         anyhow::bail!(ErrorMetadata::bad_request(
@@ -19,11 +19,19 @@
 //!    for an explanation of the algorithm.
 //! 5) Compound types, like arrays, are stored sequentially, with a null
 //!    terminator at the end.
+//! 6) Decimals are stored as a sign byte, the exponent of their most
+//!    significant digit and then their digits, with the exponent and digits
+//!    complemented for negative numbers. Their tag sorts after every other
+//!    type's since decimals were added after the other types.
+//! 7) Datetimes are stored as their nanoseconds since the epoch and then their
+//!    UTC offset in minutes, both big-endian with the sign bit flipped. Their
+//!    tag sorts after decimals'.
 use std::cmp::Ordering;
 
 use bytes::BufMut;
 
 use crate::{
+    decimal::MIN_DECIMAL_EXPONENT,
     walk::{
         ConvexArrayWalker,
         ConvexBytesWalker,
@@ -34,7 +42,9 @@ use crate::{
         ConvexValueType,
         ConvexValueWalker,
     },
+    ConvexDateTime,
     ConvexValue,
+    Decimal128,
 };
 
 const UNDEFINED_TAG: u8 = 0x1;
@@ -72,6 +82,12 @@ const ARRAY_TAG: u8 = 0x12;
 const SET_TAG: u8 = 0x13;
 const MAP_TAG: u8 = 0x14;
 const OBJECT_TAG: u8 = 0x15;
+const DECIMAL_TAG: u8 = 0x16;
+const DATETIME_TAG: u8 = 0x17;
+
+const NEGATIVE_DECIMAL: u8 = 0x1;
+const ZERO_DECIMAL: u8 = 0x2;
+const POSITIVE_DECIMAL: u8 = 0x3;
 
 pub const TERMINATOR_BYTE: u8 = 0x0;
 const ESCAPE_BYTE: u8 = 0xFF;
@@ -114,6 +130,37 @@ fn write_tagged_int(n: i64, writer: &mut impl BufMut) {
     writer.put(&buf[(8 - num_bytes)..]);
 }
 
+fn write_decimal(d: Decimal128, writer: &mut impl BufMut) {
+    writer.put_u8(DECIMAL_TAG);
+    if d.is_zero() {
+        writer.put_u8(ZERO_DECIMAL);
+        return;
+    }
+    // Numbers with larger exponents have larger magnitudes. With equal exponents,
+    // the digits compare like strings since neither has trailing zeros.
+    let biased_exponent = (d.adjusted_exponent() - MIN_DECIMAL_EXPONENT) as u16;
+    let digits = d.digits();
+    if d.is_negative() {
+        writer.put_u8(NEGATIVE_DECIMAL);
+        writer.put_u16(!biased_exponent);
+        for digit in digits.bytes() {
+            writer.put_u8(!digit);
+        }
+        writer.put_u8(!TERMINATOR_BYTE);
+    } else {
+        writer.put_u8(POSITIVE_DECIMAL);
+        writer.put_u16(biased_exponent);
+        writer.put_slice(digits.as_bytes());
+        writer.put_u8(TERMINATOR_BYTE);
+    }
+}
+
+fn write_datetime(d: ConvexDateTime, writer: &mut impl BufMut) {
+    writer.put_u8(DATETIME_TAG);
+    writer.put_u64(d.nanos_since_epoch() as u64 ^ (1 << 63));
+    writer.put_u16(d.offset_minutes() as u16 ^ (1 << 15));
+}
+
 /// Generate the sort key for a sequence of `Value`s.
 pub fn values_to_bytes(values: &[Option<ConvexValue>]) -> Vec<u8> {
     let mut out = vec![];
@@ -170,6 +217,35 @@ pub mod sorting_decode {
         Ok(())
     }
 
+    fn read_decimal<R: Read>(reader: &mut R) -> anyhow::Result<Decimal128> {
+        let sign = reader.read_u8()?;
+        if sign == ZERO_DECIMAL {
+            return Ok(Decimal128::ZERO);
+        }
+        let negative = sign == NEGATIVE_DECIMAL;
+        let complement = |byte: u8| if negative { !byte } else { byte };
+        let biased_exponent = reader.read_u16::<BigEndian>()?;
+        let biased_exponent = if negative {
+            !biased_exponent
+        } else {
+            biased_exponent
+        };
+        let mut digits = String::new();
+        loop {
+            let byte = complement(reader.read_u8()?);
+            if byte == TERMINATOR_BYTE {
+                break;
+            }
+            digits.push(byte as char);
+        }
+        let coefficient: i128 = digits.parse()?;
+        let adjusted_exponent = biased_exponent as i32 + MIN_DECIMAL_EXPONENT;
+        Decimal128::new(
+            if negative { -coefficient } else { coefficient },
+            adjusted_exponent - digits.len() as i32 + 1,
+        )
+    }
+
     fn read_tagged_int<R: Read>(tag: u8, reader: &mut R) -> io::Result<i64> {
         let is_negative = tag < ZERO_INT64_TAG;
         let tag_diff = cmp::max(tag, ZERO_INT64_TAG) - cmp::min(tag, ZERO_INT64_TAG);
@@ -331,6 +407,13 @@ pub mod sorting_decode {
                     ConvexValue::Object(ConvexObject::try_from(elements)?)
                 },
 
+                DECIMAL_TAG => ConvexValue::Decimal(read_decimal(reader)?),
+                DATETIME_TAG => {
+                    let nanos_since_epoch = (reader.read_u64::<BigEndian>()? ^ (1 << 63)) as i64;
+                    let offset_minutes = (reader.read_u16::<BigEndian>()? ^ (1 << 15)) as i16;
+                    ConvexValue::DateTime(ConvexDateTime::new(nanos_since_epoch, offset_minutes)?)
+                },
+
                 ESCAPE_BYTE => bail!("Escape code used as tag"),
                 _ => bail!("Unrecognized tag: {}", tag),
             };
@@ -394,6 +477,8 @@ pub fn write_sort_key<V: ConvexValueWalker>(
             writer.put_u8(BYTES_TAG);
             write_escaped_bytes(b.as_bytes(), writer);
         },
+        ConvexValueType::Decimal(d) => write_decimal(d, writer),
+        ConvexValueType::DateTime(d) => write_datetime(d, writer),
         ConvexValueType::Array(array) => {
             writer.put_u8(ARRAY_TAG);
             for element in array.walk() {
@@ -463,6 +548,8 @@ impl Ord for ConvexValue {
                 ConvexValue::Set(..) => 8,
                 ConvexValue::Map(..) => 9,
                 ConvexValue::Object(..) => 10,
+                ConvexValue::Decimal(..) => 11,
+                ConvexValue::DateTime(..) => 12,
             }
         }
         let tag_cmp = type_tag(self).cmp(&type_tag(other));
@@ -506,6 +593,18 @@ impl Ord for ConvexValue {
                 };
                 self_.cmp(other_)
             },
+            ConvexValue::Decimal(self_) => {
+                let ConvexValue::Decimal(other_) = other else {
+                    panic!("Invalid value: {other:?}");
+                };
+                self_.cmp(other_)
+            },
+            ConvexValue::DateTime(self_) => {
+                let ConvexValue::DateTime(other_) = other else {
+                    panic!("Invalid value: {other:?}");
+                };
+                self_.cmp(other_)
+            },
             ConvexValue::Array(self_) => {
                 let ConvexValue::Array(other_) = other else {
                     panic!("Invalid value: {other:?}");
@@ -607,11 +706,13 @@ mod tests {
         values_to_bytes,
         ConvexArray,
         ConvexBytes,
+        ConvexDateTime,
         ConvexMap,
         ConvexObject,
         ConvexSet,
         ConvexString,
         ConvexValue,
+        Decimal128,
         InternalId,
         ResolvedDocumentId,
         TableNumber,
@@ -703,6 +804,19 @@ mod tests {
             test_compatible_with_ord(String::from(l), String::from(r))
         }
 
+        #[test]
+        fn test_compatible_with_decimal(l in any::<Decimal128>(), r in any::<Decimal128>())  {
+            test_compatible_with_ord(l, r)
+        }
+
+        #[test]
+        fn test_compatible_with_datetime(
+            l in any::<ConvexDateTime>(),
+            r in any::<ConvexDateTime>(),
+        ) {
+            test_compatible_with_ord(l, r)
+        }
+
         #[test]
         fn test_compatible_with_bytes(l in any::<ConvexBytes>(), r in any::<ConvexBytes>())  {
             test_compatible_with_ord(Vec::from(l), Vec::from(r))
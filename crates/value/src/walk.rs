@@ -8,11 +8,13 @@ use std::fmt::{
 use crate::{
     ConvexArray,
     ConvexBytes,
+    ConvexDateTime,
     ConvexMap,
     ConvexObject,
     ConvexSet,
     ConvexString,
     ConvexValue,
+    Decimal128,
     FieldName,
 };
 
@@ -38,6 +40,8 @@ pub enum ConvexValueType<V: ConvexValueWalker + ?Sized> {
     Boolean(bool),
     String(V::String),
     Bytes(V::Bytes),
+    Decimal(Decimal128),
+    DateTime(ConvexDateTime),
     Array(V::Array),
     Set(V::Set),
     Map(V::Map),
@@ -53,6 +57,8 @@ impl<V: ConvexValueWalker + ?Sized> ConvexValueType<V> {
             ConvexValueType::Boolean(_) => "Boolean",
             ConvexValueType::String(_) => "String",
             ConvexValueType::Bytes(_) => "Bytes",
+            ConvexValueType::Decimal(_) => "Decimal",
+            ConvexValueType::DateTime(_) => "DateTime",
             ConvexValueType::Array(_) => "Array",
             ConvexValueType::Set(_) => "Set",
             ConvexValueType::Map(_) => "Map",
@@ -123,6 +129,8 @@ impl ConvexValueWalker for ConvexValue {
             ConvexValue::Boolean(b) => ConvexValueType::Boolean(b),
             ConvexValue::String(string) => ConvexValueType::String(string),
             ConvexValue::Bytes(bytes) => ConvexValueType::Bytes(bytes),
+            ConvexValue::Decimal(d) => ConvexValueType::Decimal(d),
+            ConvexValue::DateTime(d) => ConvexValueType::DateTime(d),
             ConvexValue::Array(array) => ConvexValueType::Array(array),
             ConvexValue::Set(set) => ConvexValueType::Set(set),
             ConvexValue::Map(map) => ConvexValueType::Map(map),
@@ -149,6 +157,8 @@ impl<'a> ConvexValueWalker for &'a ConvexValue {
             ConvexValue::Boolean(b) => ConvexValueType::Boolean(*b),
             ConvexValue::String(string) => ConvexValueType::String(string),
             ConvexValue::Bytes(bytes) => ConvexValueType::Bytes(bytes),
+            ConvexValue::Decimal(d) => ConvexValueType::Decimal(*d),
+            ConvexValue::DateTime(d) => ConvexValueType::DateTime(*d),
             ConvexValue::Array(array) => ConvexValueType::Array(array),
             ConvexValue::Set(set) => ConvexValueType::Set(set),
             ConvexValue::Map(map) => ConvexValueType::Map(map),
@@ -297,6 +307,8 @@ impl<V: ConvexValueWalker> ConvexValueWalker for ConvexValueType<V> {
             ConvexValueType::Boolean(b) => ConvexValueType::Boolean(b),
             ConvexValueType::String(string) => ConvexValueType::String(string),
             ConvexValueType::Bytes(bytes) => ConvexValueType::Bytes(bytes),
+            ConvexValueType::Decimal(d) => ConvexValueType::Decimal(d),
+            ConvexValueType::DateTime(d) => ConvexValueType::DateTime(d),
             ConvexValueType::Array(array) => ConvexValueType::Array(array),
             ConvexValueType::Set(set) => ConvexValueType::Set(set),
             ConvexValueType::Map(map) => ConvexValueType::Map(map),
@@ -0,0 +1,177 @@
+//! Compares the recall and latency of the segment layouts a vector index can
+//! use: scanning every vector, an HNSW graph memory-mapped from disk (large
+//! `segmented` indexes), an HNSW graph loaded into memory (`hnsw` indexes) and
+//! HNSW graphs over quantized vectors.
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::Path,
+    sync::{
+        atomic::AtomicBool,
+        Arc,
+    },
+};
+
+use atomic_refcell::AtomicRefCell;
+use common::bootstrap_model::index::vector_index::VectorQuantization;
+use criterion::{
+    black_box,
+    criterion_group,
+    criterion_main,
+    Criterion,
+};
+use qdrant_segment::{
+    data_types::{
+        named_vectors::NamedVectors,
+        vectors::{
+            QueryVector,
+            Vector,
+        },
+    },
+    entry::entry_point::SegmentEntry,
+    segment::Segment,
+    types::{
+        Distance,
+        ExtendedPointId,
+        QuantizationSearchParams,
+        SearchParams,
+        WithPayload,
+        WithVector,
+    },
+};
+use rand::Rng;
+use tempfile::TempDir;
+use uuid::Uuid;
+use vector::{
+    id_tracker::{
+        VectorMemoryIdTracker,
+        OP_NUM,
+    },
+    qdrant_segments::{
+        build_disk_segment,
+        create_mutable_segment,
+        in_memory_graph,
+        load_disk_segment,
+        quantized,
+        restore_segment_from_tar,
+        segment_config,
+        UntarredVectorDiskSegmentPaths,
+        DEFAULT_VECTOR_NAME,
+    },
+};
+
+const NUM_VECTORS: usize = 20000;
+const DIMENSIONS: usize = 128;
+const NUM_QUERIES: usize = 100;
+const LIMIT: usize = 10;
+
+fn random_vector(rng: &mut impl Rng) -> Vec<f32> {
+    (0..DIMENSIONS)
+        .map(|_| rng.random_range(-1.0..1.0))
+        .collect()
+}
+
+fn build_segment(
+    dir: &Path,
+    vectors: &[Vec<f32>],
+    hnsw: bool,
+    in_memory: bool,
+    quantization: VectorQuantization,
+) -> anyhow::Result<Segment> {
+    let id_tracker = Arc::new(AtomicRefCell::new(VectorMemoryIdTracker::new()));
+    let memory_config = segment_config(DIMENSIONS, Distance::Cosine, true, 4);
+    let mut memory_segment =
+        create_mutable_segment(&dir.join("memory"), id_tracker, DIMENSIONS, memory_config)?;
+    for vector in vectors {
+        let point_id = ExtendedPointId::Uuid(Uuid::new_v4());
+        let vector = Vector::Dense(vector.clone());
+        let named_vector = NamedVectors::from_ref(DEFAULT_VECTOR_NAME, vector.to_vec_ref());
+        memory_segment.upsert_point(OP_NUM, point_id, named_vector)?;
+    }
+    let indexing_path = dir.join("indexing");
+    let disk_path = dir.join("disk");
+    fs::create_dir_all(&indexing_path)?;
+    fs::create_dir_all(&disk_path)?;
+    // A mutable config scans every vector, like small segments of segmented
+    // indexes.
+    let mut disk_config = segment_config(DIMENSIONS, Distance::Cosine, !hnsw, 4);
+    if in_memory {
+        disk_config = in_memory_graph(disk_config);
+    }
+    let disk_config = quantized(disk_config, quantization);
+    let paths = build_disk_segment(&memory_segment, &indexing_path, &disk_path, disk_config)?.paths;
+    let runtime = tokio::runtime::Runtime::new()?;
+    let untarred = runtime.block_on(restore_segment_from_tar(&paths.segment))?;
+    load_disk_segment(UntarredVectorDiskSegmentPaths::from(untarred, paths))
+}
+
+fn search(segment: &Segment, query: &[f32], exact: bool) -> Vec<ExtendedPointId> {
+    segment
+        .search(
+            DEFAULT_VECTOR_NAME,
+            &QueryVector::Nearest(Vector::Dense(query.to_vec())),
+            &WithPayload::from(false),
+            &WithVector::Bool(false),
+            None,
+            LIMIT,
+            Some(&SearchParams {
+                hnsw_ef: None,
+                exact,
+                // Exact searches are the ground truth, so they skip the
+                // quantized vectors.
+                quantization: Some(QuantizationSearchParams {
+                    ignore: exact,
+                    rescore: Some(true),
+                    oversampling: Some(3.0),
+                }),
+                indexed_only: false,
+            }),
+            &AtomicBool::new(false),
+        )
+        .unwrap()
+        .into_iter()
+        .map(|result| result.id)
+        .collect()
+}
+
+/// The fraction of the exact top `LIMIT` results that the approximate search
+/// also returns, averaged over the queries.
+fn recall(segment: &Segment, queries: &[Vec<f32>]) -> f64 {
+    let mut found = 0;
+    for query in queries {
+        let exact: BTreeSet<_> = search(segment, query, true).into_iter().collect();
+        found += search(segment, query, false)
+            .into_iter()
+            .filter(|id| exact.contains(id))
+            .count();
+    }
+    found as f64 / (queries.len() * LIMIT) as f64
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let mut rng = rand::rng();
+    let vectors: Vec<_> = (0..NUM_VECTORS).map(|_| random_vector(&mut rng)).collect();
+    let queries: Vec<_> = (0..NUM_QUERIES).map(|_| random_vector(&mut rng)).collect();
+
+    for (name, hnsw, in_memory, quantization) in [
+        ("full_scan", false, false, VectorQuantization::None),
+        ("hnsw_mmap", true, false, VectorQuantization::None),
+        ("hnsw_in_memory", true, true, VectorQuantization::None),
+        ("hnsw_scalar", true, true, VectorQuantization::Scalar),
+        ("hnsw_product", true, true, VectorQuantization::Product),
+    ] {
+        let dir = TempDir::new().unwrap();
+        let segment = build_segment(dir.path(), &vectors, hnsw, in_memory, quantization).unwrap();
+        println!("{name} recall@{LIMIT}: {:.3}", recall(&segment, &queries));
+        c.bench_function(name, |b| {
+            let mut i = 0;
+            b.iter(|| {
+                i = (i + 1) % queries.len();
+                search(&segment, black_box(&queries[i]), false)
+            })
+        });
+    }
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -27,7 +27,7 @@ pub fn criterion_benchmark(c: &mut Criterion) {
 
     let ts = Timestamp::must(1);
 
-    let mut index = MemoryVectorIndex::new(WriteTimestamp::Committed(ts));
+    let mut index = MemoryVectorIndex::new(WriteTimestamp::Committed(ts), Default::default());
     let mut next_id = 1u128;
 
     for _ in 0..n {
@@ -41,6 +41,7 @@ pub fn criterion_benchmark(c: &mut Criterion) {
                 .try_into()
                 .unwrap(),
             filter_fields: BTreeMap::new(),
+            filter_numbers: BTreeMap::new(),
         };
         index
             .update(id, WriteTimestamp::Committed(ts), None, Some(document))
@@ -55,7 +56,9 @@ pub fn criterion_benchmark(c: &mut Criterion) {
             .try_into()
             .unwrap(),
         limit: k,
+        ef_search: None,
         filter_conditions: BTreeMap::new(),
+        exact: false,
     };
     c.bench_function("query", |b| b.iter(|| index.query(ts, black_box(&search))));
 }
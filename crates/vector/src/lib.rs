@@ -26,11 +26,15 @@ pub mod metrics;
 mod qdrant_index;
 pub mod qdrant_segments;
 mod query;
+mod rerank;
 mod searcher;
 mod vector_index_manager;
 
 #[cfg(any(test, feature = "testing"))]
-pub use self::qdrant_index::cosine_similarity;
+pub use self::qdrant_index::{
+    cosine_similarity,
+    vector_similarity,
+};
 pub use self::{
     memory_index::MemoryVectorIndex,
     metrics::{
@@ -39,6 +43,7 @@ pub use self::{
         VECTOR_INDEX_TYPE_LABEL,
     },
     qdrant_index::{
+        FilterNumber,
         PreviousVectorSegmentsHack,
         QdrantDocument,
         QdrantExternalId,
@@ -49,16 +54,24 @@ pub use self::{
         CompiledVectorSearch,
         InternalVectorSearch,
         PublicVectorSearchQueryResult,
+        VectorFilterBound,
+        VectorFilterRange,
+        VectorMmr,
         VectorSearch,
         VectorSearchExpression,
         VectorSearchJson,
         VectorSearchQueryResult,
         VectorSearchRequest,
     },
+    rerank::{
+        maximal_marginal_relevance,
+        vector_distance,
+    },
     searcher::VectorSearcher,
     vector_index_manager::{
         IndexState,
         VectorIndexManager,
+        VectorIndexSegmentStats,
     },
 };
 
@@ -3,23 +3,25 @@ use std::{
     mem,
 };
 
-use common::types::{
-    Timestamp,
-    WriteTimestamp,
+use common::{
+    bootstrap_model::index::vector_index::VectorDistanceMetric,
+    types::{
+        Timestamp,
+        WriteTimestamp,
+    },
 };
 use imbl::{
     OrdMap,
     OrdSet,
     Vector,
 };
-use qdrant_segment::spaces::{
-    metric::Metric,
-    simple::CosineMetric,
-};
 use value::InternalId;
 
 use crate::{
     qdrant_index::{
+        preprocess_vector,
+        similarity,
+        FilterNumber,
         NormalizedQdrantDocument,
         QdrantDocument,
     },
@@ -32,6 +34,8 @@ use crate::{
 
 #[derive(Clone)]
 pub struct MemoryVectorIndex {
+    distance_metric: VectorDistanceMetric,
+
     min_ts: WriteTimestamp,
     max_ts: WriteTimestamp,
 
@@ -45,8 +49,10 @@ pub struct MemoryVectorIndex {
 }
 
 impl MemoryVectorIndex {
-    pub fn new(base_ts: WriteTimestamp) -> Self {
+    pub fn new(base_ts: WriteTimestamp, distance_metric: VectorDistanceMetric) -> Self {
         Self {
+            distance_metric,
+
             min_ts: base_ts,
             max_ts: base_ts,
 
@@ -110,7 +116,7 @@ impl MemoryVectorIndex {
             }
         }
         if let Some(old_value) = old_value {
-            let normalized = NormalizedQdrantDocument::from(old_value);
+            let normalized = NormalizedQdrantDocument::new(old_value, self.distance_metric);
             self.tombstones_size += normalized.size();
             self.tombstones.push_back((ts, normalized));
         }
@@ -119,7 +125,7 @@ impl MemoryVectorIndex {
             self.documents_size -= old_value.document.size();
         }
         if let Some(new_value) = new_value {
-            let normalized = NormalizedQdrantDocument::from(new_value);
+            let normalized = NormalizedQdrantDocument::new(new_value, self.distance_metric);
             self.documents_size += normalized.size();
             let revision = Revision {
                 ts,
@@ -203,12 +209,16 @@ impl MemoryVectorIndex {
             self.min_ts,
         );
         let query_vector = Vec::from(query.vector.clone());
-        let query_vector = CosineMetric::preprocess(query_vector);
+        let query_vector = preprocess_vector(self.distance_metric, query_vector);
         let mut candidates = vec![];
 
         for (&id, revision) in &self.documents {
             if revision.document.matches(query) {
-                let distance = CosineMetric::similarity(&query_vector, &revision.document.vector);
+                let distance = similarity(
+                    self.distance_metric,
+                    &query_vector,
+                    &revision.document.vector,
+                );
                 candidates.push(VectorSearchQueryResult {
                     score: distance,
                     id,
@@ -242,6 +252,14 @@ impl NormalizedQdrantDocument {
             let condition_result = match filter_condition {
                 CompiledVectorFilter::Eq(ref term) => term == value,
                 CompiledVectorFilter::In(ref terms) => terms.iter().any(|t| t == value),
+                CompiledVectorFilter::NotIn(ref terms) => terms.iter().all(|t| t != value),
+                CompiledVectorFilter::Range(ref range) => {
+                    match self.filter_numbers.get(field_path) {
+                        Some(FilterNumber::Int64(i)) => range.int64 && range.contains(*i as f64),
+                        Some(FilterNumber::Float64(f)) => !range.int64 && range.contains(*f),
+                        None => false,
+                    }
+                },
             };
             if condition_result {
                 return true;
@@ -156,6 +156,8 @@ pub fn log_compiled_query(query: &CompiledVectorSearch) {
                     log_vector_search_total("in");
                     log_distribution(&VECTOR_SEARCH_COMPILE_FILTER_IN_TOTAL, vec.len() as f64);
                 },
+                CompiledVectorFilter::NotIn(_) => log_vector_search_total("not_in"),
+                CompiledVectorFilter::Range(_) => log_vector_search_total("range"),
             }
         }
     } else {
@@ -23,9 +23,19 @@ use std::{
 
 use atomic_refcell::AtomicRefCell;
 use common::{
-    bootstrap_model::index::vector_index::DeveloperVectorIndexConfig,
+    bootstrap_model::index::vector_index::{
+        DeveloperVectorIndexConfig,
+        VectorDistanceMetric,
+        VectorIndexKind,
+        VectorQuantization,
+    },
     document::ResolvedDocument,
-    knobs::VECTOR_INDEX_THREADS,
+    knobs::{
+        VECTOR_INDEX_THREADS,
+        VECTOR_MAX_EF_SEARCH,
+        VECTOR_MAX_SEGMENT_PARTITIONS,
+        VECTOR_QUANTIZATION_OVERSAMPLING,
+    },
     persistence::DocumentStream,
     query::search_value_to_bytes,
     types::{
@@ -53,11 +63,16 @@ use qdrant_segment::{
     segment::Segment,
     spaces::{
         metric::Metric,
-        simple::CosineMetric,
+        simple::{
+            CosineMetric,
+            DotProductMetric,
+            EuclidMetric,
+        },
     },
     types::{
         AnyVariants,
         Condition,
+        Distance,
         ExtendedPointId,
         FieldCondition,
         Filter,
@@ -69,6 +84,8 @@ use qdrant_segment::{
         PayloadSelector,
         PayloadSelectorInclude,
         PointIdType,
+        QuantizationSearchParams,
+        Range,
         SearchParams,
         ValueVariants,
         WithPayload,
@@ -97,6 +114,8 @@ use crate::{
     qdrant_segments::{
         build_disk_segment,
         create_mutable_segment,
+        in_memory_graph,
+        quantized,
         segment_config,
         snapshot_segment,
         VectorDiskSegmentValues,
@@ -104,6 +123,7 @@ use crate::{
     },
     query::{
         CompiledVectorFilter,
+        CompiledVectorRange,
         CompiledVectorSearch,
         InternalVectorSearch,
         VectorSearchExpression,
@@ -117,12 +137,20 @@ use crate::{
 };
 
 const TIMESTAMP_FIELD: &str = "_ts";
+// Numeric filter field values are also stored under these fields (user field
+// names can't start with underscores) as numbers that range filters can use.
+const FLOAT64_FIELDS: &str = "_f64";
+const INT64_FIELDS: &str = "_i64";
 
 #[derive(Clone, Debug)]
 pub struct QdrantSchema {
     dimension: usize,
     vector_field: FieldPath,
     filter_fields: BTreeSet<FieldPath>,
+    partition_field: Option<FieldPath>,
+    distance_metric: VectorDistanceMetric,
+    index_kind: VectorIndexKind,
+    quantization: VectorQuantization,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -137,6 +165,10 @@ impl QdrantSchema {
             dimension: u32::from(index_config.dimensions) as usize,
             vector_field: index_config.vector_field.clone(),
             filter_fields: index_config.filter_fields.clone(),
+            partition_field: index_config.partition_field.clone(),
+            distance_metric: index_config.distance_metric,
+            index_kind: index_config.index_kind,
+            quantization: index_config.quantization,
         }
     }
 
@@ -169,6 +201,11 @@ impl QdrantSchema {
                 .iter()
                 .map(|f| (f.clone(), search_value_to_bytes(object.get_path(f))))
                 .collect(),
+            filter_numbers: self
+                .filter_fields
+                .iter()
+                .filter_map(|f| Some((f.clone(), FilterNumber::new(object.get_path(f)?)?)))
+                .collect(),
         };
         Some(document)
     }
@@ -177,6 +214,17 @@ impl QdrantSchema {
         self.dimension * VECTOR_ELEMENT_SIZE
     }
 
+    /// The partitions that `query` is restricted to by an equality or `in`
+    /// filter on the index's partition field, if any.
+    pub fn query_partitions(&self, query: &CompiledVectorSearch) -> Option<BTreeSet<Vec<u8>>> {
+        let partition_field = self.partition_field.as_ref()?;
+        match query.filter_conditions.get(partition_field)? {
+            CompiledVectorFilter::Eq(value) => Some(BTreeSet::from([value.clone()])),
+            CompiledVectorFilter::In(values) => Some(values.iter().cloned().collect()),
+            CompiledVectorFilter::NotIn(_) | CompiledVectorFilter::Range(_) => None,
+        }
+    }
+
     pub fn compile(&self, query: InternalVectorSearch) -> anyhow::Result<CompiledVectorSearch> {
         let timer = metrics::compile_timer();
 
@@ -193,6 +241,18 @@ impl QdrantSchema {
                 )
             )
         );
+        if let Some(ef_search) = query.ef_search {
+            anyhow::ensure!(
+                ef_search >= 1 && ef_search <= *VECTOR_MAX_EF_SEARCH,
+                ErrorMetadata::bad_request(
+                    "VectorEfSearchOutOfRange",
+                    format!(
+                        "Vector queries must have an efSearch between 1 and {}, requested {}.",
+                        *VECTOR_MAX_EF_SEARCH, ef_search,
+                    )
+                )
+            );
+        }
         let mut filter_conditions = BTreeMap::new();
         // Each equality expression contributes to this, so an `In` with N elements
         // increments this by N
@@ -231,6 +291,39 @@ impl QdrantSchema {
                     filter_length += values_bytes.len();
                     filter_conditions.insert(field_path, CompiledVectorFilter::In(values_bytes));
                 },
+                VectorSearchExpression::NotIn(field_path, values) => {
+                    if !self.filter_fields.contains(&field_path) {
+                        anyhow::bail!(incorrect_vector_filter_field_error(
+                            &index_name,
+                            &field_path
+                        ))
+                    }
+                    let values_bytes: Vec<_> = values
+                        .into_iter()
+                        .map(|v| search_value_to_bytes(v.as_ref()))
+                        .collect();
+                    if filter_conditions.contains_key(&field_path) {
+                        anyhow::bail!("Found multiple filters for the same field?")
+                    }
+                    filter_length += values_bytes.len();
+                    filter_conditions.insert(field_path, CompiledVectorFilter::NotIn(values_bytes));
+                },
+                VectorSearchExpression::Range(field_path, range) => {
+                    if !self.filter_fields.contains(&field_path) {
+                        anyhow::bail!(incorrect_vector_filter_field_error(
+                            &index_name,
+                            &field_path
+                        ))
+                    }
+                    if filter_conditions.contains_key(&field_path) {
+                        anyhow::bail!("Found multiple filters for the same field?")
+                    }
+                    filter_length += 1;
+                    filter_conditions.insert(
+                        field_path,
+                        CompiledVectorFilter::Range(CompiledVectorRange::from(&range)),
+                    );
+                },
             }
         }
         anyhow::ensure!(
@@ -250,13 +343,33 @@ impl QdrantSchema {
         let result = CompiledVectorSearch {
             vector: query_vector,
             limit: query_limit,
+            ef_search: query.ef_search,
             filter_conditions,
+            exact: query.exact,
         };
         metrics::log_compiled_query(&result);
         timer.finish();
         Ok(result)
     }
 
+    fn search_params(&self, query: &CompiledVectorSearch, require_exact: bool) -> SearchParams {
+        // Quantized searches overfetch candidates using the compressed vectors
+        // and then re-rank them against the original ones.
+        let quantization = (!self.quantization.is_default()).then(|| QuantizationSearchParams {
+            ignore: false,
+            rescore: Some(true),
+            oversampling: Some(*VECTOR_QUANTIZATION_OVERSAMPLING),
+        });
+        // qdrant never explores fewer candidates than the number of results it
+        // returns, so a small `ef_search` can't truncate the results.
+        SearchParams {
+            hnsw_ef: query.ef_search.map(|ef| ef as usize),
+            exact: require_exact || query.exact,
+            quantization,
+            indexed_only: false,
+        }
+    }
+
     pub fn search(
         &self,
         segment: &Segment,
@@ -265,15 +378,31 @@ impl QdrantSchema {
         slow_vector_query_threshold_millis: u64,
         require_exact: bool,
     ) -> anyhow::Result<Vec<VectorSearchQueryResult>> {
+        let indexed_fields = segment.get_indexed_fields();
         let qdrant_conditions = query
             .filter_conditions
             .iter()
             .map(|(field_path, condition)| {
-                let field_condition = FieldCondition::new_match(
-                    encode_user_field_path(field_path)?,
-                    qdrant_filter_condition(condition),
-                );
-                Ok(Some(Condition::Field(field_condition)))
+                let condition = qdrant_filter_condition(field_path, condition)?;
+                // Segments built before range filters were supported don't store
+                // numbers, so ranges would silently match nothing in them.
+                if let Condition::Field(FieldCondition {
+                    key,
+                    range: Some(_),
+                    ..
+                }) = &condition
+                    && !indexed_fields.contains_key(key)
+                {
+                    anyhow::bail!(ErrorMetadata::bad_request(
+                        "VectorIndexNeedsRebuild",
+                        format!(
+                            "This vector index was built before range filters were supported, so \
+                             it can't filter {field_path:?} with `q.gt`, `q.gte`, `q.lt` or \
+                             `q.lte`. Rename the index to rebuild it."
+                        )
+                    ));
+                }
+                Ok(Some(condition))
             })
             .collect::<anyhow::Result<Option<Vec<_>>>>()?;
         let qdrant_filter = Filter {
@@ -282,12 +411,7 @@ impl QdrantSchema {
             must: None,
             must_not: None,
         };
-        let search_params = SearchParams {
-            hnsw_ef: None,
-            exact: require_exact,
-            quantization: None,
-            indexed_only: false,
-        };
+        let search_params = self.search_params(&query, require_exact);
         let payload_selector = PayloadSelectorInclude {
             include: vec![json_path_from_str(TIMESTAMP_FIELD)?],
         };
@@ -357,7 +481,13 @@ impl QdrantSchema {
         // upfront, always set up the more complex directory.
         let memory_dir: PathBuf = tmpdir.path().join("memory");
         let id_tracker = Arc::new(AtomicRefCell::new(VectorMemoryIdTracker::new()));
-        let mutable_config = segment_config(self.dimension, true, *VECTOR_INDEX_THREADS);
+        let distance = qdrant_distance(self.distance_metric);
+        // Plain segments keep the quantization config so that compaction can
+        // carry it over when merging them into an HNSW segment.
+        let mutable_config = quantized(
+            segment_config(self.dimension, distance, true, *VECTOR_INDEX_THREADS),
+            self.quantization,
+        );
         let mut memory_segment = create_mutable_segment(
             &memory_dir,
             id_tracker.clone(),
@@ -365,6 +495,10 @@ impl QdrantSchema {
             mutable_config,
         )?;
 
+        // Record which partitions the segment's vectors are in so that searches
+        // within other partitions can skip it. Vectors deleted later in the
+        // batch may leave extra partitions behind, which is harmless.
+        let mut partitions = self.partition_field.as_ref().map(|_| BTreeSet::new());
         let op_num = 1;
         futures::pin_mut!(revision_stream);
         while let Some(entry) = revision_stream.try_next().await? {
@@ -374,6 +508,18 @@ impl QdrantSchema {
                     tracing::trace!("Skipping an invalid doc: {:?}", document);
                     continue;
                 };
+                let too_many_partitions = match (&self.partition_field, &mut partitions) {
+                    (Some(partition_field), Some(partitions)) => {
+                        partitions.insert(search_value_to_bytes(
+                            document.value().get_path(partition_field),
+                        ));
+                        partitions.len() > *VECTOR_MAX_SEGMENT_PARTITIONS
+                    },
+                    _ => false,
+                };
+                if too_many_partitions {
+                    partitions = None;
+                }
                 memory_segment.upsert_point(op_num, *point_id, qdrant_doc.qdrant_vector())?;
                 let payload = qdrant_doc.encode_payload(entry.ts)?;
                 memory_segment.set_payload(op_num, *point_id, &payload.into(), &None)?;
@@ -401,14 +547,27 @@ impl QdrantSchema {
             // consistency, but it's faster and simpler.
             previous_segments.maybe_delete_qdrant(*point_id)?;
         }
-        // We encode all of our index values as strings.
+        // We encode all of our index values as strings, along with numbers for
+        // range filters.
         let field_schema = Some(&PayloadFieldSchema::FieldType(PayloadSchemaType::Keyword));
+        let float_schema = Some(&PayloadFieldSchema::FieldType(PayloadSchemaType::Float));
+        let integer_schema = Some(&PayloadFieldSchema::FieldType(PayloadSchemaType::Integer));
         for field in self.filter_fields.iter() {
             memory_segment.create_field_index(
                 op_num,
                 &encode_user_field_path(field)?,
                 field_schema,
             )?;
+            memory_segment.create_field_index(
+                op_num,
+                &encode_number_field_path(field, false)?,
+                float_schema,
+            )?;
+            memory_segment.create_field_index(
+                op_num,
+                &encode_number_field_path(field, true)?,
+                integer_schema,
+            )?;
         }
         memory_timer.finish();
 
@@ -432,7 +591,9 @@ impl QdrantSchema {
         let estimated_size_bytes =
             memory_segment.total_point_count() * self.dimension * VECTOR_ELEMENT_SIZE;
         let estmated_size_kb = estimated_size_bytes / 1024;
-        let index_type = if estmated_size_kb >= hnsw_threshold_bytes {
+        let index_type = if self.index_kind == VectorIndexKind::Hnsw
+            || estmated_size_kb >= hnsw_threshold_bytes
+        {
             QdrantVectorIndexType::HNSW
         } else {
             QdrantVectorIndexType::Plain
@@ -456,6 +617,7 @@ impl QdrantSchema {
                     )?,
                     num_vectors,
                     num_deleted,
+                    partitions: None,
                 })
             },
             QdrantVectorIndexType::HNSW => {
@@ -463,7 +625,12 @@ impl QdrantSchema {
                 fs::create_dir_all(&indexing_path)?;
                 let disk_path = index_path.join("disk");
                 fs::create_dir_all(&disk_path)?;
-                let disk_config = segment_config(self.dimension, false, *VECTOR_INDEX_THREADS);
+                let mut disk_config =
+                    segment_config(self.dimension, distance, false, *VECTOR_INDEX_THREADS);
+                if self.index_kind == VectorIndexKind::Hnsw {
+                    disk_config = in_memory_graph(disk_config);
+                }
+                let disk_config = quantized(disk_config, self.quantization);
                 build_disk_segment(&memory_segment, &indexing_path, &disk_path, disk_config)
             },
         }?;
@@ -471,7 +638,10 @@ impl QdrantSchema {
 
         tracing::debug!("Built a {index_type:?} vector index for {index_path:?}",);
 
-        Ok(Some(result))
+        Ok(Some(VectorDiskSegmentValues {
+            partitions,
+            ..result
+        }))
     }
 }
 
@@ -480,6 +650,25 @@ pub struct QdrantDocument {
     pub internal_id: InternalId,
     pub vector: IndexedVector,
     pub filter_fields: BTreeMap<FieldPath, Vec<u8>>,
+    /// The filter fields with numeric values, for range filters.
+    pub filter_numbers: BTreeMap<FieldPath, FilterNumber>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FilterNumber {
+    Float64(f64),
+    Int64(i64),
+}
+
+impl FilterNumber {
+    /// Payloads are JSON, so only finite floats can be compared with ranges.
+    fn new(value: &ConvexValue) -> Option<Self> {
+        match value {
+            ConvexValue::Float64(f) if f.is_finite() => Some(Self::Float64(*f)),
+            ConvexValue::Int64(i) => Some(Self::Int64(*i)),
+            _ => None,
+        }
+    }
 }
 
 impl QdrantDocument {
@@ -490,24 +679,24 @@ impl QdrantDocument {
     pub fn encode_payload(&self, ts: Timestamp) -> anyhow::Result<JsonValue> {
         let mut map = serde_json::Map::new();
         for (field_path, field_value) in &self.filter_fields {
-            let mut current = &mut map;
-            // The path should consist of nested json objects.
-            for i in 0..field_path.fields().len() - 1 {
-                let field: String = field_path.fields()[i].clone().into();
-                let JsonValue::Object(inner) = current
-                    .entry(field)
-                    .or_insert_with(|| JsonValue::Object(serde_json::Map::new()))
-                else {
-                    // This means one filter field path is a prefix of another. We should
-                    // prevent the developer from defining such index. Throw a system error here.
-                    anyhow::bail!("Conflicting field path: {:?} {:?}", field_path, map);
-                };
-                current = inner;
-            }
-            current.insert(
-                field_path.last().clone().into(),
+            insert_at_path(
+                &mut map,
+                field_path,
                 JsonValue::String(base64::encode_urlsafe(&field_value[..])),
-            );
+            )?;
+        }
+        for (field_path, number) in &self.filter_numbers {
+            let (numbers_field, value) = match number {
+                FilterNumber::Float64(f) => (FLOAT64_FIELDS, JsonValue::from(*f)),
+                FilterNumber::Int64(i) => (INT64_FIELDS, JsonValue::from(*i)),
+            };
+            let JsonValue::Object(numbers) = map
+                .entry(numbers_field)
+                .or_insert_with(|| JsonValue::Object(serde_json::Map::new()))
+            else {
+                anyhow::bail!("{numbers_field} isn't an object in {map:?}");
+            };
+            insert_at_path(numbers, field_path, value)?;
         }
         map.insert(
             TIMESTAMP_FIELD.to_string(),
@@ -524,32 +713,68 @@ impl QdrantDocument {
 
 #[cfg(any(test, feature = "testing"))]
 pub fn cosine_similarity(v1: &[f32], v2: &[f32]) -> f32 {
-    let v1 = CosineMetric::preprocess(v1.to_vec());
-    let v2 = CosineMetric::preprocess(v2.to_vec());
-    CosineMetric::similarity(&v1, &v2)
+    vector_similarity(VectorDistanceMetric::Cosine, v1, v2)
+}
+
+/// The score of `v2` as a result for the query `v1` under `metric`, matching
+/// the scores of both memory and disk indexes.
+#[cfg(any(test, feature = "testing"))]
+pub fn vector_similarity(metric: VectorDistanceMetric, v1: &[f32], v2: &[f32]) -> f32 {
+    let v1 = preprocess_vector(metric, v1.to_vec());
+    let v2 = preprocess_vector(metric, v2.to_vec());
+    similarity(metric, &v1, &v2)
+}
+
+pub(crate) fn qdrant_distance(metric: VectorDistanceMetric) -> Distance {
+    match metric {
+        VectorDistanceMetric::Cosine => Distance::Cosine,
+        VectorDistanceMetric::DotProduct => Distance::Dot,
+        VectorDistanceMetric::Euclidean => Distance::Euclid,
+    }
+}
+
+/// Qdrant applies the same preprocessing to vectors in disk segments based on
+/// the segment's `Distance`. Only cosine changes the vector, normalizing it to
+/// unit length.
+pub(crate) fn preprocess_vector(metric: VectorDistanceMetric, vector: Vec<f32>) -> Vec<f32> {
+    match metric {
+        VectorDistanceMetric::Cosine => CosineMetric::preprocess(vector),
+        VectorDistanceMetric::DotProduct => DotProductMetric::preprocess(vector),
+        VectorDistanceMetric::Euclidean => EuclidMetric::preprocess(vector),
+    }
+}
+
+/// Scores two vectors that have already been through [`preprocess_vector`].
+pub(crate) fn similarity(metric: VectorDistanceMetric, v1: &[f32], v2: &[f32]) -> f32 {
+    match metric {
+        VectorDistanceMetric::Cosine => CosineMetric::similarity(v1, v2),
+        VectorDistanceMetric::DotProduct => DotProductMetric::similarity(v1, v2),
+        VectorDistanceMetric::Euclidean => EuclidMetric::similarity(v1, v2),
+    }
 }
 
-// NB: For cosine similarity, we need to normalize vectors before indexing them.
+// NB: Vectors are preprocessed for the index's distance metric before indexing
+// them, which for cosine similarity normalizes them.
 #[derive(Clone, Debug)]
 pub struct NormalizedQdrantDocument {
     pub internal_id: InternalId,
     pub vector: Vec<f32>,
     pub filter_fields: BTreeMap<FieldPath, Vec<u8>>,
+    pub filter_numbers: BTreeMap<FieldPath, FilterNumber>,
 }
 
-impl From<QdrantDocument> for NormalizedQdrantDocument {
-    fn from(value: QdrantDocument) -> Self {
+impl NormalizedQdrantDocument {
+    pub fn new(value: QdrantDocument, metric: VectorDistanceMetric) -> Self {
         let vector = Vec::from(value.vector);
-        let vector = CosineMetric::preprocess(vector);
+        let vector = preprocess_vector(metric, vector);
         Self {
             internal_id: value.internal_id,
             vector,
             filter_fields: value.filter_fields,
+            filter_numbers: value.filter_numbers,
         }
     }
-}
 
-impl NormalizedQdrantDocument {
     pub fn size(&self) -> usize {
         let mut size = 0;
         size += self.vector.len() * mem::size_of::<f32>();
@@ -558,35 +783,111 @@ impl NormalizedQdrantDocument {
             size += field_path.fields().iter().map(|f| f.len()).sum::<usize>();
             size += maybe_value.len();
         }
+        size += self.filter_numbers.len() * mem::size_of::<(FieldPath, FilterNumber)>();
+        for field_path in self.filter_numbers.keys() {
+            size += field_path.fields().iter().map(|f| f.len()).sum::<usize>();
+        }
         size
     }
 }
 
+/// Inserts `value` into `map` at `field_path`, which should consist of nested
+/// JSON objects.
+fn insert_at_path(
+    map: &mut serde_json::Map<String, JsonValue>,
+    field_path: &FieldPath,
+    value: JsonValue,
+) -> anyhow::Result<()> {
+    let mut current = &mut *map;
+    for i in 0..field_path.fields().len() - 1 {
+        let field: String = field_path.fields()[i].clone().into();
+        let JsonValue::Object(inner) = current
+            .entry(field)
+            .or_insert_with(|| JsonValue::Object(serde_json::Map::new()))
+        else {
+            // This means one filter field path is a prefix of another. We should
+            // prevent the developer from defining such index. Throw a system error here.
+            anyhow::bail!("Conflicting field path: {:?}", field_path);
+        };
+        current = inner;
+    }
+    current.insert(field_path.last().clone().into(), value);
+    Ok(())
+}
+
 fn encode_user_field_path(field_path: &FieldPath) -> anyhow::Result<JsonPath> {
     let key = String::from(field_path.clone());
     json_path_from_str(key.as_str())
 }
 
-fn qdrant_filter_condition(condition: &CompiledVectorFilter) -> Match {
-    match condition {
+fn encode_number_field_path(field_path: &FieldPath, int64: bool) -> anyhow::Result<JsonPath> {
+    let numbers_field = if int64 { INT64_FIELDS } else { FLOAT64_FIELDS };
+    let key = String::from(field_path.clone());
+    json_path_from_str(&format!("{numbers_field}.{key}"))
+}
+
+fn qdrant_filter_condition(
+    field_path: &FieldPath,
+    condition: &CompiledVectorFilter,
+) -> anyhow::Result<Condition> {
+    let condition = match condition {
         CompiledVectorFilter::Eq(value) => {
             let value_b64 = base64::encode_urlsafe(&value[..]);
             let match_value = MatchValue {
                 value: ValueVariants::Keyword(value_b64),
             };
-            Match::Value(match_value)
+            Condition::Field(FieldCondition::new_match(
+                encode_user_field_path(field_path)?,
+                Match::Value(match_value),
+            ))
         },
-        CompiledVectorFilter::In(values) => {
-            let values_b64 = values
-                .iter()
-                .map(|v| base64::encode_urlsafe(&v[..]))
-                .collect();
-            let match_value = MatchAny {
-                any: AnyVariants::Keywords(values_b64),
-            };
-            Match::Any(match_value)
-        },
-    }
+        CompiledVectorFilter::In(values) => Condition::Field(FieldCondition::new_match(
+            encode_user_field_path(field_path)?,
+            match_any(values),
+        )),
+        CompiledVectorFilter::NotIn(values) => Condition::Filter(Filter {
+            should: None,
+            min_should: None,
+            must: None,
+            must_not: Some(vec![Condition::Field(FieldCondition::new_match(
+                encode_user_field_path(field_path)?,
+                match_any(values),
+            ))]),
+        }),
+        CompiledVectorFilter::Range(range) => Condition::Field(FieldCondition::new_range(
+            encode_number_field_path(field_path, range.int64)?,
+            Range {
+                gt: range
+                    .lower
+                    .filter(|(_, inclusive)| !inclusive)
+                    .map(|(v, _)| v),
+                gte: range
+                    .lower
+                    .filter(|(_, inclusive)| *inclusive)
+                    .map(|(v, _)| v),
+                lt: range
+                    .upper
+                    .filter(|(_, inclusive)| !inclusive)
+                    .map(|(v, _)| v),
+                lte: range
+                    .upper
+                    .filter(|(_, inclusive)| *inclusive)
+                    .map(|(v, _)| v),
+            },
+        )),
+    };
+    Ok(condition)
+}
+
+fn match_any(values: &[Vec<u8>]) -> Match {
+    let values_b64 = values
+        .iter()
+        .map(|v| base64::encode_urlsafe(&v[..]))
+        .collect();
+    let match_value = MatchAny {
+        any: AnyVariants::Keywords(values_b64),
+    };
+    Match::Any(match_value)
 }
 
 impl From<QdrantSchema> for proto::VectorIndexConfig {
@@ -595,6 +896,10 @@ impl From<QdrantSchema> for proto::VectorIndexConfig {
             dimension: value.dimension as u32,
             vector_field_path: Some(value.vector_field.into()),
             filter_fields: value.filter_fields.into_iter().map(|f| f.into()).collect(),
+            distance_metric: Some(value.distance_metric.to_string()),
+            index_kind: Some(value.index_kind.to_string()),
+            quantization: Some(value.quantization.to_string()),
+            partition_field_path: value.partition_field.map(|f| f.into()),
         }
     }
 }
@@ -612,10 +917,18 @@ impl TryFrom<proto::VectorIndexConfig> for QdrantSchema {
             .into_iter()
             .map(|f| f.try_into())
             .collect::<Result<_, _>>()?;
+        let partition_field = value
+            .partition_field_path
+            .map(|f| f.try_into())
+            .transpose()?;
         Ok(QdrantSchema {
             dimension: value.dimension as usize,
             vector_field,
             filter_fields,
+            partition_field,
+            distance_metric: VectorDistanceMetric::parse(value.distance_metric.as_deref())?,
+            index_kind: VectorIndexKind::parse(value.index_kind.as_deref())?,
+            quantization: VectorQuantization::parse(value.quantization.as_deref())?,
         })
     }
 }
@@ -676,13 +989,90 @@ fn json_path_from_str(s: &str) -> anyhow::Result<JsonPath> {
 
 #[cfg(test)]
 mod tests {
-    use common::types::Timestamp;
+    use std::collections::BTreeSet;
+
+    use common::{
+        bootstrap_model::index::vector_index::{
+            VectorDistanceMetric,
+            VectorIndexKind,
+            VectorQuantization,
+        },
+        knobs::VECTOR_MAX_EF_SEARCH,
+        types::{
+            IndexDescriptor,
+            TabletIndexName,
+            Timestamp,
+        },
+    };
+    use errors::ErrorMetadataAnyhowExt;
     use maplit::btreemap;
     use rand::Rng;
     use serde_json::json;
-    use value::InternalId;
+    use value::{
+        InternalId,
+        TabletId,
+    };
 
-    use crate::QdrantDocument;
+    use crate::{
+        qdrant_index::{
+            FilterNumber,
+            QdrantSchema,
+        },
+        query::InternalVectorSearch,
+        QdrantDocument,
+    };
+
+    fn test_schema() -> anyhow::Result<QdrantSchema> {
+        Ok(QdrantSchema {
+            dimension: 2,
+            vector_field: "embedding".parse()?,
+            filter_fields: BTreeSet::new(),
+            partition_field: None,
+            distance_metric: VectorDistanceMetric::Cosine,
+            index_kind: VectorIndexKind::Segmented,
+            quantization: VectorQuantization::None,
+        })
+    }
+
+    fn test_search(ef_search: Option<u32>) -> anyhow::Result<InternalVectorSearch> {
+        Ok(InternalVectorSearch {
+            index_name: TabletIndexName::new(TabletId::MIN, IndexDescriptor::new("by_embedding")?)?,
+            limit: Some(10),
+            ef_search,
+            vector: vec![1.0, 0.0],
+            expressions: vec![],
+            original_table_name: "documents".parse()?,
+            exact: false,
+        })
+    }
+
+    #[test]
+    fn test_ef_search_out_of_range() -> anyhow::Result<()> {
+        let schema = test_schema()?;
+        for ef_search in [0, *VECTOR_MAX_EF_SEARCH + 1] {
+            let err = schema.compile(test_search(Some(ef_search))?).unwrap_err();
+            assert_eq!(err.short_msg(), "VectorEfSearchOutOfRange");
+        }
+        for ef_search in [None, Some(1), Some(*VECTOR_MAX_EF_SEARCH)] {
+            let compiled = schema.compile(test_search(ef_search)?)?;
+            assert_eq!(compiled.ef_search, ef_search);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_ef_search_sets_hnsw_ef() -> anyhow::Result<()> {
+        let schema = test_schema()?;
+        let compiled = schema.compile(test_search(Some(64))?)?;
+        let params = schema.search_params(&compiled, false);
+        assert_eq!(params.hnsw_ef, Some(64));
+        assert!(!params.exact);
+
+        // Without `ef_search`, qdrant uses the `ef` the segment was built with.
+        let compiled = schema.compile(test_search(None)?)?;
+        assert_eq!(schema.search_params(&compiled, false).hnsw_ef, None);
+        Ok(())
+    }
 
     #[test]
     fn test_encode_payload() -> anyhow::Result<()> {
@@ -697,6 +1087,7 @@ mod tests {
                 .try_into()
                 .unwrap(),
             filter_fields: btreemap!(),
+            filter_numbers: btreemap!(),
         };
         let payload = document.encode_payload(Timestamp::MIN)?;
         assert_eq!(payload, json!({ "_ts": "AAAAAAAAAAA"}));
@@ -713,6 +1104,7 @@ mod tests {
                 "def.ghi".parse()? => vec![98],
                 "def.xyz".parse()? => vec![99],
             ),
+            filter_numbers: btreemap!(),
         };
         let payload = document.encode_payload(Timestamp::MIN)?;
         assert_eq!(
@@ -730,9 +1122,38 @@ mod tests {
             filter_fields: btreemap!(
                 "zzz".parse()? => vec![97],
             ),
+            filter_numbers: btreemap!(),
         };
         let payload = document.encode_payload(Timestamp::MIN)?;
         assert_eq!(payload, json!({ "zzz": "YQ", "_ts": "AAAAAAAAAAA"}));
+
+        let document = QdrantDocument {
+            internal_id: InternalId(1u128.to_le_bytes()),
+            vector: (0..d)
+                .map(|_| rng.random())
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap(),
+            filter_fields: btreemap!(
+                "price".parse()? => vec![97],
+                "stock.count".parse()? => vec![98],
+            ),
+            filter_numbers: btreemap!(
+                "price".parse()? => FilterNumber::Float64(2.5),
+                "stock.count".parse()? => FilterNumber::Int64(3),
+            ),
+        };
+        let payload = document.encode_payload(Timestamp::MIN)?;
+        assert_eq!(
+            payload,
+            json!({
+                "price": "YQ",
+                "stock": { "count": "Yg" },
+                "_f64": { "price": 2.5 },
+                "_i64": { "stock": { "count": 3 } },
+                "_ts": "AAAAAAAAAAA",
+            })
+        );
         Ok(())
     }
 }
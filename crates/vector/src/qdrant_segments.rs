@@ -1,5 +1,8 @@
 use std::{
-    collections::HashMap,
+    collections::{
+        BTreeSet,
+        HashMap,
+    },
     fs::{
         self,
         File,
@@ -15,8 +18,10 @@ use std::{
     },
 };
 
+use anyhow::Context;
 use atomic_refcell::AtomicRefCell;
 use common::{
+    bootstrap_model::index::vector_index::VectorQuantization,
     deleted_bitset::DeletedBitset,
     id_tracker::StaticIdTracker,
     runtime::tokio_spawn_blocking,
@@ -29,6 +34,7 @@ use qdrant_common::cpu::CpuPermit;
 use qdrant_segment::vector_storage::{
     appendable_mmap_dense_vector_storage::open_appendable_memmap_vector_storage,
     memmap_dense_vector_storage::open_memmap_vector_storage,
+    quantized::quantized_vectors::QuantizedVectors,
 };
 use qdrant_segment::{
     common::{
@@ -46,7 +52,10 @@ use qdrant_segment::{
     id_tracker::IdTracker,
     index::{
         hnsw_index::{
-            graph_links::GraphLinksMmap,
+            graph_links::{
+                GraphLinksMmap,
+                GraphLinksRam,
+            },
             hnsw::HNSWIndex,
         },
         plain_payload_index::PlainIndex,
@@ -66,10 +75,17 @@ use qdrant_segment::{
         PAYLOAD_INDEX_PATH,
     },
     types::{
+        CompressionRatio,
         Distance,
         HnswConfig,
         Indexes,
         PayloadStorageType,
+        ProductQuantization,
+        ProductQuantizationConfig,
+        QuantizationConfig,
+        ScalarQuantization,
+        ScalarQuantizationConfig,
+        ScalarType,
         SegmentConfig,
         SegmentType,
         VectorDataConfig,
@@ -88,10 +104,11 @@ use crate::id_tracker::{
 
 const UUID_TABLE_FILENAME: &str = "uuids.table";
 const DELETED_BITSET_FILENAME: &str = "deleted.bitset";
-pub(crate) const DEFAULT_VECTOR_NAME: &str = "default_vector";
+pub const DEFAULT_VECTOR_NAME: &str = "default_vector";
 
-pub(crate) fn segment_config(
+pub fn segment_config(
     dimension: usize,
+    distance: Distance,
     mutable: bool,
     max_indexing_threads: usize,
 ) -> SegmentConfig {
@@ -128,7 +145,7 @@ pub(crate) fn segment_config(
     };
     let vector_data_config = VectorDataConfig {
         size: dimension,
-        distance: Distance::Cosine,
+        distance,
         storage_type: vector_storage_type,
         index,
         quantization_config: None,
@@ -140,6 +157,56 @@ pub(crate) fn segment_config(
     }
 }
 
+/// Loads the HNSW graph of segments built with `config` into memory when
+/// they're opened, rather than memory-mapping it from disk.
+pub fn in_memory_graph(mut config: SegmentConfig) -> SegmentConfig {
+    for vector_data_config in config.vector_data.values_mut() {
+        if let Indexes::Hnsw(ref mut hnsw_config) = vector_data_config.index {
+            hnsw_config.on_disk = Some(false);
+        }
+    }
+    config
+}
+
+/// Compresses the vectors of segments built with `config`. Only segments with
+/// an HNSW graph build quantized vectors, since scanning every vector is only
+/// done for segments small enough not to need them.
+pub fn quantized(mut config: SegmentConfig, quantization: VectorQuantization) -> SegmentConfig {
+    let quantization_config = match quantization {
+        VectorQuantization::None => None,
+        VectorQuantization::Scalar => Some(QuantizationConfig::Scalar(ScalarQuantization {
+            scalar: ScalarQuantizationConfig {
+                r#type: ScalarType::Int8,
+                // Ignore outliers when picking the range to quantize to.
+                quantile: Some(0.99),
+                always_ram: Some(true),
+            },
+        })),
+        VectorQuantization::Product => Some(QuantizationConfig::Product(ProductQuantization {
+            product: ProductQuantizationConfig {
+                compression: CompressionRatio::X16,
+                always_ram: Some(true),
+            },
+        })),
+    };
+    for vector_data_config in config.vector_data.values_mut() {
+        vector_data_config.quantization_config = quantization_config.clone();
+    }
+    config
+}
+
+fn is_in_memory_graph(config: &SegmentConfig) -> bool {
+    config.vector_data.values().any(|vector_data_config| {
+        matches!(
+            vector_data_config.index,
+            Indexes::Hnsw(HnswConfig {
+                on_disk: Some(false),
+                ..
+            })
+        )
+    })
+}
+
 pub fn create_mutable_segment(
     path: &Path,
     id_tracker: Arc<AtomicRefCell<VectorMemoryIdTracker>>,
@@ -163,12 +230,13 @@ pub fn create_mutable_segment(
 
     let stopped = AtomicBool::new(false);
     let vector_storage_path = get_vector_storage_path(path, DEFAULT_VECTOR_NAME);
-    let vector_storage = open_appendable_memmap_vector_storage(
-        &vector_storage_path,
-        dimension,
-        Distance::Cosine,
-        &stopped,
-    )?;
+    let distance = segment_config
+        .vector_data
+        .get(DEFAULT_VECTOR_NAME)
+        .context("Missing vector data config")?
+        .distance;
+    let vector_storage =
+        open_appendable_memmap_vector_storage(&vector_storage_path, dimension, distance, &stopped)?;
     let point_count = id_tracker.borrow().total_point_count();
     let vector_count = vector_storage.borrow().total_vector_count();
     anyhow::ensure!(point_count == vector_count);
@@ -244,6 +312,8 @@ pub struct VectorDiskSegmentValues {
     pub paths: VectorDiskSegmentPaths,
     pub num_vectors: u32,
     pub num_deleted: u32,
+    /// See `FragmentedVectorSegment::partitions`.
+    pub partitions: Option<BTreeSet<Vec<u8>>>,
 }
 
 pub fn build_disk_segment(
@@ -261,7 +331,24 @@ pub fn merge_disk_segments_hnsw(
     tmp_path: &Path,
     disk_path: &Path,
 ) -> anyhow::Result<VectorDiskSegmentValues> {
-    let segment_config = segment_config(dimension, false, 4);
+    // The segments were all built for the same index, so they share the
+    // index's distance metric, quantization and where it keeps HNSW graphs.
+    let first_config = &segments
+        .first()
+        .context("No segments to merge")?
+        .1
+        .segment_config;
+    let first_vector_config = first_config
+        .vector_data
+        .get(DEFAULT_VECTOR_NAME)
+        .context("Missing vector data config")?;
+    let mut segment_config = segment_config(dimension, first_vector_config.distance, false, 4);
+    if is_in_memory_graph(first_config) {
+        segment_config = in_memory_graph(segment_config);
+    }
+    for vector_data_config in segment_config.vector_data.values_mut() {
+        vector_data_config.quantization_config = first_vector_config.quantization_config.clone();
+    }
     merge_disk_segments(segments, tmp_path, disk_path, segment_config)
 }
 
@@ -339,6 +426,7 @@ pub fn merge_disk_segments(
         paths: segment,
         num_vectors: total_point_count as u32,
         num_deleted: num_deleted as u32,
+        partitions: None,
     })
 }
 
@@ -487,25 +575,46 @@ pub fn load_disk_segment(paths: UntarredVectorDiskSegmentPaths) -> anyhow::Resul
     let vector_count = vector_storage.borrow().total_vector_count();
     anyhow::ensure!(vector_count == point_count);
 
+    // Only segments with an HNSW graph have quantized vectors, even if the
+    // config asks for them.
+    let quantized_vectors = if vector_config.quantization_config.is_some()
+        && QuantizedVectors::config_exists(&vector_storage_path)
+    {
+        Some(QuantizedVectors::load(
+            &vector_storage.borrow(),
+            &vector_storage_path,
+        )?)
+    } else {
+        None
+    };
+    let quantized_vectors = Arc::new(AtomicRefCell::new(quantized_vectors));
+
     let vector_index = match vector_config.index {
         qdrant_segment::types::Indexes::Plain {} => VectorIndexEnum::Plain(PlainIndex::new(
             id_tracker.clone(),
             vector_storage.clone(),
             payload_index.clone(),
         )),
-        qdrant_segment::types::Indexes::Hnsw(ref hnsw_config) => {
-            anyhow::ensure!(
-                hnsw_config.on_disk.unwrap_or(false),
-                "HNSW indexes are only supported when they're written to disk!"
-            );
-            VectorIndexEnum::HnswMmap(HNSWIndex::<GraphLinksMmap>::open(
+        qdrant_segment::types::Indexes::Hnsw(ref hnsw_config) => match hnsw_config.on_disk {
+            Some(true) => VectorIndexEnum::HnswMmap(HNSWIndex::<GraphLinksMmap>::open(
+                &vector_index_path,
+                id_tracker.clone(),
+                vector_storage.clone(),
+                quantized_vectors.clone(),
+                payload_index.clone(),
+                hnsw_config.clone(),
+            )?),
+            // Segments of `hnsw` vector indexes read their whole graph into
+            // memory.
+            Some(false) => VectorIndexEnum::HnswRam(HNSWIndex::<GraphLinksRam>::open(
                 &vector_index_path,
                 id_tracker.clone(),
                 vector_storage.clone(),
-                Arc::new(AtomicRefCell::new(None)),
+                quantized_vectors.clone(),
                 payload_index.clone(),
                 hnsw_config.clone(),
-            )?)
+            )?),
+            None => anyhow::bail!("HNSW indexes must say whether their graph is on disk!"),
         },
     };
     let vector_index = Arc::new(AtomicRefCell::new(vector_index));
@@ -513,7 +622,7 @@ pub fn load_disk_segment(paths: UntarredVectorDiskSegmentPaths) -> anyhow::Resul
     let vector_data = VectorData {
         vector_storage,
         vector_index,
-        quantized_vectors: Arc::new(AtomicRefCell::new(None)),
+        quantized_vectors,
     };
     let segment = Segment {
         version: segment_state.version,
@@ -580,6 +689,7 @@ mod tests {
         segment::Segment,
         types::{
             Condition,
+            Distance,
             ExtendedPointId,
             FieldCondition,
             Filter,
@@ -657,7 +767,7 @@ mod tests {
     ) -> anyhow::Result<(Segment, Arc<AtomicRefCell<VectorMemoryIdTracker>>)> {
         let memory_path = test_dir.path().join("memory");
         let id_tracker = Arc::new(AtomicRefCell::new(VectorMemoryIdTracker::new()));
-        let mutable_config = segment_config(dimensions, true, 4);
+        let mutable_config = segment_config(dimensions, Distance::Cosine, true, 4);
         let mut memory_segment =
             create_mutable_segment(&memory_path, id_tracker.clone(), dimensions, mutable_config)?;
 
@@ -677,7 +787,7 @@ mod tests {
     ) -> anyhow::Result<(Segment, Arc<AtomicRefCell<VectorMemoryIdTracker>>)> {
         let memory_path = test_dir.path().join("memory");
         let id_tracker = Arc::new(AtomicRefCell::new(VectorMemoryIdTracker::new()));
-        let mutable_config = segment_config(dimensions, true, 4);
+        let mutable_config = segment_config(dimensions, Distance::Cosine, true, 4);
         let mut memory_segment =
             create_mutable_segment(&memory_path, id_tracker.clone(), dimensions, mutable_config)?;
 
@@ -705,7 +815,7 @@ mod tests {
         let disk_path = test_dir.path().join("disk");
         fs::create_dir_all(&disk_path)?;
 
-        let disk_config = segment_config(dimensions, false, 4);
+        let disk_config = segment_config(dimensions, Distance::Cosine, false, 4);
         Ok(build_disk_segment(&memory_segment, &indexing_path, &disk_path, disk_config)?.paths)
     }
 
@@ -773,7 +883,7 @@ mod tests {
         let disk_path = test_dir.path().join("disk");
         fs::create_dir_all(&disk_path)?;
 
-        let disk_config = segment_config(DIMENSIONS, false, 4);
+        let disk_config = segment_config(DIMENSIONS, Distance::Cosine, false, 4);
         Ok(build_disk_segment(memory_segment, &indexing_path, &disk_path, disk_config)?.paths)
     }
 
@@ -1349,7 +1459,7 @@ mod tests {
         let new_paths = create_test_disk_segment(DIMENSIONS, &new_dir, vector.into_iter())?;
         let new_segment = unsafe_load_disk_segment(&new_paths).await?;
 
-        let config = segment_config(DIMENSIONS, false, 4);
+        let config = segment_config(DIMENSIONS, Distance::Cosine, false, 4);
         let merged_dir = tempfile::tempdir()?;
         let result =
             merge_disk_segments_tmpdir(vec![&initial_segment, &new_segment], &merged_dir, config)
@@ -1375,7 +1485,7 @@ mod tests {
         let new_paths = create_test_disk_segment(DIMENSIONS, &new_dir, vectors.into_iter())?;
         let new_segment = unsafe_load_disk_segment(&new_paths).await?;
 
-        let config = segment_config(DIMENSIONS, false, 4);
+        let config = segment_config(DIMENSIONS, Distance::Cosine, false, 4);
         let merged_dir = tempfile::tempdir()?;
         let VectorDiskSegmentValues { paths, .. } =
             merge_disk_segments_tmpdir(vec![&initial_segment, &new_segment], &merged_dir, config)?;
@@ -1414,7 +1524,7 @@ mod tests {
         let new_paths = create_test_disk_segment(DIMENSIONS, &new_dir, vector.clone().into_iter())?;
         let new_segment = unsafe_load_disk_segment(&new_paths).await?;
 
-        let config = segment_config(DIMENSIONS, false, 4);
+        let config = segment_config(DIMENSIONS, Distance::Cosine, false, 4);
         let merged_dir = tempfile::tempdir()?;
         let VectorDiskSegmentValues {
             paths: merged_paths,
@@ -1453,7 +1563,7 @@ mod tests {
             .map(|(segment, ..)| segment)
             .collect();
 
-        let config = segment_config(DIMENSIONS, false, 4);
+        let config = segment_config(DIMENSIONS, Distance::Cosine, false, 4);
         let merged_dir = tempfile::tempdir()?;
         let VectorDiskSegmentValues {
             paths: merged_paths,
@@ -1496,7 +1606,7 @@ mod tests {
             create_test_disk_segment(DIMENSIONS, &other_dir, other_vectors.clone().into_iter())?;
         let other_segment = unsafe_load_disk_segment(&other_paths).await?;
 
-        let config = segment_config(DIMENSIONS, false, 4);
+        let config = segment_config(DIMENSIONS, Distance::Cosine, false, 4);
         let merged_dir = tempfile::tempdir()?;
         let VectorDiskSegmentValues {
             paths: merged_paths,
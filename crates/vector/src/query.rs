@@ -46,7 +46,10 @@ use value::{
     TabletId,
 };
 
-use crate::IndexedVector;
+use crate::{
+    IndexedVector,
+    MAX_VECTOR_RESULTS,
+};
 
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -59,14 +62,262 @@ pub struct VectorSearch {
     pub index_name: IndexName,
     pub component_id: ComponentId,
     pub limit: Option<u32>,
+    /// How many candidates to explore in the HNSW graph. Higher values trade
+    /// latency for recall. Defaults to the index's own setting if unset.
+    pub ef_search: Option<u32>,
     pub vector: Vec<f32>,
     pub expressions: BTreeSet<VectorSearchExpression>,
+    /// Whether to return each result's stored vector and its exact distance
+    /// from the query vector.
+    pub include_vectors: bool,
+    pub mmr: Option<VectorMmr>,
+}
+
+/// Re-ranks a vector search's nearest neighbors by maximal marginal relevance,
+/// trading some relevance for results that are less similar to each other.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VectorMmr {
+    /// Between 0 and 1, where 1 ranks purely by relevance and smaller values
+    /// favor diverse results.
+    pub lambda: f64,
+    /// How many nearest neighbors to re-rank. Defaults to a multiple of the
+    /// query's limit.
+    pub fetch_limit: Option<u32>,
+}
+
+impl VectorMmr {
+    const DEFAULT_FETCH_MULTIPLIER: u32 = 4;
+    const DEFAULT_LAMBDA: f64 = 0.5;
+
+    /// Checks the options against the query's `limit`, returning how many
+    /// nearest neighbors to fetch for re-ranking.
+    pub fn fetch_limit(&self, limit: u32) -> anyhow::Result<u32> {
+        anyhow::ensure!(
+            (0.0..=1.0).contains(&self.lambda),
+            invalid_vector_search_mmr(format!(
+                "mmr.lambda must be between 0 and 1, got {}",
+                self.lambda
+            ))
+        );
+        let fetch_limit = self.fetch_limit.unwrap_or_else(|| {
+            (limit.saturating_mul(Self::DEFAULT_FETCH_MULTIPLIER)).min(MAX_VECTOR_RESULTS as u32)
+        });
+        anyhow::ensure!(
+            fetch_limit >= limit && fetch_limit as usize <= MAX_VECTOR_RESULTS,
+            invalid_vector_search_mmr(format!(
+                "mmr.fetchLimit must be between the limit ({limit}) and {MAX_VECTOR_RESULTS}, got \
+                 {fetch_limit}"
+            ))
+        );
+        Ok(fetch_limit)
+    }
+}
+
+fn invalid_vector_search_mmr(msg: impl Into<std::borrow::Cow<'static, str>>) -> ErrorMetadata {
+    ErrorMetadata::bad_request("InvalidVectorSearchMmr", msg)
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum VectorSearchExpression {
     Eq(FieldPath, Option<ConvexValue>),
     In(FieldPath, BTreeSet<Option<ConvexValue>>),
+    /// Matches documents where the field isn't any of the values.
+    NotIn(FieldPath, BTreeSet<Option<ConvexValue>>),
+    Range(FieldPath, VectorFilterRange),
+}
+
+/// Bounds on a numeric filter field. At least one end is bounded and the
+/// bounds are numbers of the same type. Like text search range filters, only
+/// values of that type match, so a `float64` range doesn't match `int64`s.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct VectorFilterRange {
+    pub lower: Option<VectorFilterBound>,
+    pub upper: Option<VectorFilterBound>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct VectorFilterBound {
+    pub value: ConvexValue,
+    pub inclusive: bool,
+}
+
+/// The conditions on a single field that a vector search filter can express.
+#[derive(Clone, Debug, PartialEq)]
+enum FieldFilter {
+    Values(BTreeSet<Option<ConvexValue>>),
+    NotIn(BTreeSet<Option<ConvexValue>>),
+    Range(VectorFilterRange),
+}
+
+fn invalid_vector_search_filter(msg: impl Into<std::borrow::Cow<'static, str>>) -> ErrorMetadata {
+    ErrorMetadata::bad_request("InvalidVectorSearchFilter", msg)
+}
+
+impl VectorFilterRange {
+    fn from_comparison(expression: Expression) -> anyhow::Result<(FieldPath, Self)> {
+        let (left, right, lower, inclusive) = match expression {
+            Expression::Gt(left, right) => (left, right, true, false),
+            Expression::Gte(left, right) => (left, right, true, true),
+            Expression::Lt(left, right) => (left, right, false, false),
+            Expression::Lte(left, right) => (left, right, false, true),
+            _ => anyhow::bail!(invalid_vector_search_filter(
+                "`q.and` can only combine `q.gt`, `q.gte`, `q.lt` and `q.lte` on the same field."
+            )),
+        };
+        let (Expression::Field(field_path), Expression::Literal(MaybeValue(Some(value)))) =
+            (*left, *right)
+        else {
+            anyhow::bail!(invalid_vector_search_filter(
+                "Comparisons must take a field path as their first argument and a value as their \
+                 second"
+            ));
+        };
+        if !matches!(value, ConvexValue::Float64(_) | ConvexValue::Int64(_)) {
+            anyhow::bail!(invalid_vector_search_filter(format!(
+                "Vector search filters can only compare numbers with `gt`, `gte`, `lt` and `lte`, \
+                 but got a value of type {}",
+                value.type_name()
+            )));
+        }
+        let bound = Some(VectorFilterBound { value, inclusive });
+        let range = if lower {
+            Self {
+                lower: bound,
+                upper: None,
+            }
+        } else {
+            Self {
+                lower: None,
+                upper: bound,
+            }
+        };
+        Ok((field_path, range))
+    }
+
+    /// Combines the bounds of two ranges on `field_path`, which must bound
+    /// different ends with numbers of the same type.
+    fn intersect(self, other: Self, field_path: &FieldPath) -> anyhow::Result<Self> {
+        if (self.lower.is_some() && other.lower.is_some())
+            || (self.upper.is_some() && other.upper.is_some())
+        {
+            anyhow::bail!(invalid_vector_search_filter(format!(
+                "Vector search filter has more than one lower or upper bound on {field_path:?}"
+            )));
+        }
+        let range = Self {
+            lower: self.lower.or(other.lower),
+            upper: self.upper.or(other.upper),
+        };
+        if let (Some(lower), Some(upper)) = (&range.lower, &range.upper)
+            && lower.value.type_name() != upper.value.type_name()
+        {
+            anyhow::bail!(invalid_vector_search_filter(format!(
+                "The bounds on {field_path:?} must be numbers of the same type"
+            )));
+        }
+        Ok(range)
+    }
+
+    /// The type of the numbers in the range.
+    pub fn is_int64(&self) -> bool {
+        let bound = self.lower.as_ref().or(self.upper.as_ref());
+        matches!(
+            bound,
+            Some(VectorFilterBound {
+                value: ConvexValue::Int64(_),
+                ..
+            })
+        )
+    }
+
+    fn to_expression(&self, field_path: &FieldPath) -> Expression {
+        let comparison = |bound: &VectorFilterBound, lower: bool| {
+            let field = Box::new(Expression::Field(field_path.clone()));
+            let value = Box::new(Expression::Literal(MaybeValue(Some(bound.value.clone()))));
+            match (lower, bound.inclusive) {
+                (true, false) => Expression::Gt(field, value),
+                (true, true) => Expression::Gte(field, value),
+                (false, false) => Expression::Lt(field, value),
+                (false, true) => Expression::Lte(field, value),
+            }
+        };
+        match (&self.lower, &self.upper) {
+            (Some(lower), Some(upper)) => {
+                Expression::And(vec![comparison(lower, true), comparison(upper, false)])
+            },
+            (Some(lower), None) => comparison(lower, true),
+            (None, Some(upper)) => comparison(upper, false),
+            (None, None) => Expression::And(vec![]),
+        }
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl Arbitrary for VectorFilterRange {
+    type Parameters = ();
+
+    type Strategy = impl Strategy<Value = VectorFilterRange>;
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        use proptest::prelude::*;
+
+        let bound = |int64: bool| {
+            let value = if int64 {
+                any::<i64>().prop_map(ConvexValue::Int64).boxed()
+            } else {
+                any::<f64>().prop_map(ConvexValue::Float64).boxed()
+            };
+            (value, any::<bool>())
+                .prop_map(|(value, inclusive)| VectorFilterBound { value, inclusive })
+        };
+        any::<bool>()
+            .prop_flat_map(move |int64| {
+                (
+                    proptest::option::of(bound(int64)),
+                    proptest::option::of(bound(int64)),
+                )
+            })
+            .prop_filter("Ranges must be bounded", |(lower, upper)| {
+                lower.is_some() || upper.is_some()
+            })
+            .prop_map(|(lower, upper)| VectorFilterRange { lower, upper })
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl Arbitrary for FieldFilter {
+    type Parameters = ();
+
+    type Strategy = impl Strategy<Value = FieldFilter>;
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        use proptest::prelude::*;
+
+        prop_oneof![
+            proptest::collection::btree_set(any::<Option<ConvexValue>>(), 1..5)
+                .prop_map(FieldFilter::Values),
+            proptest::collection::btree_set(any::<Option<ConvexValue>>(), 1..5)
+                .prop_map(FieldFilter::NotIn),
+            any::<VectorFilterRange>().prop_map(FieldFilter::Range),
+        ]
+    }
+}
+
+impl FieldFilter {
+    /// Combines two filters on `field_path` in a `q.or`, which is only
+    /// possible for equalities.
+    fn union(self, other: Self, field_path: &FieldPath) -> anyhow::Result<Self> {
+        match (self, other) {
+            (FieldFilter::Values(mut values), FieldFilter::Values(other)) => {
+                values.extend(other);
+                Ok(FieldFilter::Values(values))
+            },
+            _ => anyhow::bail!(invalid_vector_search_filter(format!(
+                "Vector search filters can only combine `q.eq`s with `q.or` on the same field, \
+                 but {field_path:?} has other conditions"
+            ))),
+        }
+    }
 }
 
 #[cfg(any(test, feature = "testing"))]
@@ -81,25 +332,41 @@ impl Arbitrary for VectorSearch {
             any::<IndexName>(),
             any::<ComponentId>(),
             any::<Option<u32>>(),
+            any::<Option<u32>>(),
             any::<Vec<f32>>(),
             // There's an invariant that there's at most one `VectorSearchExpression` for a given
-            // field. To ensure this, generate a map from FieldPath to filtered values
+            // field. To ensure this, generate a map from FieldPath to filters
             // and construct the `VectorSearchExpression` from that.
-            proptest::collection::btree_map(
-                any::<FieldPath>(),
-                proptest::collection::btree_set(any::<Option<ConvexValue>>(), 1..5),
-                1..5,
-            ),
+            proptest::collection::btree_map(any::<FieldPath>(), any::<FieldFilter>(), 1..5),
+            any::<bool>(),
+            proptest::option::of((0.0..=1.0f64, any::<Option<u32>>()).prop_map(
+                |(lambda, fetch_limit)| VectorMmr {
+                    lambda,
+                    fetch_limit,
+                },
+            )),
         )
-            .prop_map(|(index_name, component_id, limit, vector, field_map)| {
-                VectorSearch {
+            .prop_map(
+                |(
                     index_name,
                     component_id,
                     limit,
+                    ef_search,
+                    vector,
+                    field_map,
+                    include_vectors,
+                    mmr,
+                )| VectorSearch {
+                    index_name,
+                    component_id,
+                    limit,
+                    ef_search,
                     vector,
                     expressions: VectorSearchExpression::from_field_map(field_map),
-                }
-            })
+                    include_vectors,
+                    mmr,
+                },
+            )
     }
 }
 
@@ -122,23 +389,33 @@ impl Arbitrary for VectorSearchExpression {
             )
                 .prop_map(|(field_path, elements)| {
                     VectorSearchExpression::In(field_path, elements)
-                })
+                }),
+            (
+                any::<FieldPath>(),
+                prop::collection::btree_set(any::<Option<ConvexValue>>(), 1..5),
+            )
+                .prop_map(|(field_path, elements)| {
+                    VectorSearchExpression::NotIn(field_path, elements)
+                }),
+            any::<(FieldPath, VectorFilterRange)>()
+                .prop_map(|(field_path, range)| VectorSearchExpression::Range(field_path, range)),
         ]
     }
 }
 
 impl VectorSearchExpression {
-    /// Vector filters use a subset of the `Expression` syntax -- `q.or` and
-    /// `q.eq`.
+    /// Vector filters use a subset of the `Expression` syntax: `q.eq`, `q.neq`,
+    /// `q.not` of equalities on one field, comparisons of numbers (combined
+    /// with `q.and` for both bounds) and `q.or` of any of those.
     ///
     /// We massage these into a list of Vec<VectorSearchExpression> (or error if
     /// this is impossible). As an intermediate step, we create a map from
-    /// FieldPath to a Vec of Values so we can create
+    /// FieldPath to the field's condition so we can merge equalities into
     /// `VectorSearchExpression::In` or `VectorSearchExpression::Eq`
     /// accordingly.
     fn assemble_filter_map(
         expression: Expression,
-    ) -> anyhow::Result<BTreeMap<FieldPath, BTreeSet<Option<ConvexValue>>>> {
+    ) -> anyhow::Result<BTreeMap<FieldPath, FieldFilter>> {
         match expression {
             Expression::Eq(left, right) => {
                 if let (Expression::Field(field_path), Expression::Literal(value)) = (*left, *right)
@@ -146,45 +423,84 @@ impl VectorSearchExpression {
                     let mut field_map = BTreeMap::new();
                     let mut values = BTreeSet::new();
                     values.insert(value.0);
-                    field_map.insert(field_path, values);
+                    field_map.insert(field_path, FieldFilter::Values(values));
                     Ok(field_map)
                 } else {
-                    anyhow::bail!(ErrorMetadata::bad_request(
-                        "InvalidVectorSearchFilter",
+                    anyhow::bail!(invalid_vector_search_filter(
                         "`q.eq` must take a field path as its first argument and a value as its \
                          second"
                     ))
                 }
             },
+            Expression::Neq(left, right) => {
+                Self::assemble_filter_map(Expression::Not(Box::new(Expression::Eq(left, right))))
+            },
+            Expression::Not(inner) => {
+                let field_map = Self::assemble_filter_map(*inner)?;
+                let mut fields = field_map.into_iter();
+                match (fields.next(), fields.next()) {
+                    (Some((field_path, FieldFilter::Values(values))), None) => {
+                        Ok(BTreeMap::from([(field_path, FieldFilter::NotIn(values))]))
+                    },
+                    _ => anyhow::bail!(invalid_vector_search_filter(
+                        "`q.not` can only negate `q.eq` or a `q.or` of `q.eq`s on the same field."
+                    )),
+                }
+            },
+            Expression::Gt(..) | Expression::Gte(..) | Expression::Lt(..) | Expression::Lte(..) => {
+                let (field_path, range) = VectorFilterRange::from_comparison(expression)?;
+                Ok(BTreeMap::from([(field_path, FieldFilter::Range(range))]))
+            },
+            Expression::And(expressions) => {
+                let mut combined: Option<(FieldPath, VectorFilterRange)> = None;
+                for e in expressions {
+                    let (field_path, range) = VectorFilterRange::from_comparison(e)?;
+                    combined = match combined {
+                        None => Some((field_path, range)),
+                        Some((combined_path, combined_range)) => {
+                            if combined_path != field_path {
+                                anyhow::bail!(invalid_vector_search_filter(
+                                    "`q.and` can only combine `q.gt`, `q.gte`, `q.lt` and `q.lte` \
+                                     on the same field."
+                                ));
+                            }
+                            let range = combined_range.intersect(range, &field_path)?;
+                            Some((field_path, range))
+                        },
+                    };
+                }
+                let Some((field_path, range)) = combined else {
+                    anyhow::bail!(invalid_vector_search_filter(
+                        "`q.and` must have at least one argument."
+                    ));
+                };
+                Ok(BTreeMap::from([(field_path, FieldFilter::Range(range))]))
+            },
             Expression::Or(expressions) => {
-                let mut full_field_map = BTreeMap::new();
+                let mut full_field_map: BTreeMap<FieldPath, FieldFilter> = BTreeMap::new();
                 for e in expressions {
                     let field_map = Self::assemble_filter_map(e)?;
-                    for (key, values) in field_map {
-                        let merged_values = full_field_map.entry(key).or_insert(BTreeSet::new());
-                        merged_values.extend(values);
+                    for (key, filter) in field_map {
+                        let filter = match full_field_map.remove(&key) {
+                            Some(existing) => existing.union(filter, &key)?,
+                            None => filter,
+                        };
+                        full_field_map.insert(key, filter);
                     }
                 }
                 Ok(full_field_map)
             },
             Expression::Literal(_)
-            | Expression::Neq(..)
-            | Expression::Lt(..)
-            | Expression::Lte(..)
-            | Expression::Gt(..)
-            | Expression::Gte(..)
             | Expression::Add(..)
             | Expression::Sub(..)
             | Expression::Mul(..)
             | Expression::Div(..)
             | Expression::Mod(..)
             | Expression::Neg(_)
-            | Expression::And(_)
-            | Expression::Not(_)
             | Expression::Field(_) => {
-                anyhow::bail!(ErrorMetadata::bad_request(
-                    "InvalidVectorSearchFilter",
-                    "Filters should be a combination of `q.eq` and `q.or`."
+                anyhow::bail!(invalid_vector_search_filter(
+                    "Filters should be a combination of `q.eq`, `q.neq`, `q.not`, `q.gt`, \
+                     `q.gte`, `q.lt`, `q.lte`, `q.and` and `q.or`."
                 ))
             },
         }
@@ -195,43 +511,51 @@ impl VectorSearchExpression {
         Ok(Self::from_field_map(field_map))
     }
 
-    fn from_field_map(
-        field_map: BTreeMap<FieldPath, BTreeSet<Option<ConvexValue>>>,
-    ) -> BTreeSet<Self> {
+    fn from_field_map(field_map: BTreeMap<FieldPath, FieldFilter>) -> BTreeSet<Self> {
         let mut filters = BTreeSet::new();
-        for (key, values) in field_map {
-            if values.len() == 1 {
-                filters.insert(VectorSearchExpression::Eq(
+        for (key, filter) in field_map {
+            let expression = match filter {
+                FieldFilter::Values(values) if values.len() == 1 => VectorSearchExpression::Eq(
                     key,
                     values
-                        .iter()
+                        .into_iter()
                         .next()
-                        .expect("Set does not have a single element")
-                        .clone(),
-                ));
-            } else {
-                filters.insert(VectorSearchExpression::In(key, values));
-            }
+                        .expect("Set does not have a single element"),
+                ),
+                FieldFilter::Values(values) => VectorSearchExpression::In(key, values),
+                FieldFilter::NotIn(values) => VectorSearchExpression::NotIn(key, values),
+                FieldFilter::Range(range) => VectorSearchExpression::Range(key, range),
+            };
+            filters.insert(expression);
         }
         filters
     }
 
     fn to_expression(filter_expressions: BTreeSet<Self>) -> Expression {
+        let eq = |field_path: &FieldPath, value: Option<ConvexValue>| {
+            Expression::Eq(
+                Box::new(Expression::Field(field_path.clone())),
+                Box::new(Expression::Literal(MaybeValue(value))),
+            )
+        };
         let mut expressions = vec![];
         for filter in filter_expressions {
             match filter {
-                VectorSearchExpression::Eq(field_path, value) => expressions.push(Expression::Eq(
-                    Box::new(Expression::Field(field_path)),
-                    Box::new(Expression::Literal(MaybeValue(value))),
-                )),
+                VectorSearchExpression::Eq(field_path, value) => {
+                    expressions.push(eq(&field_path, value))
+                },
                 VectorSearchExpression::In(field_path, values) => {
                     for value in values {
-                        expressions.push(Expression::Eq(
-                            Box::new(Expression::Field(field_path.clone())),
-                            Box::new(Expression::Literal(MaybeValue(value))),
-                        ))
+                        expressions.push(eq(&field_path, value))
                     }
                 },
+                VectorSearchExpression::NotIn(field_path, values) => {
+                    let values: Vec<_> = values.into_iter().map(|v| eq(&field_path, v)).collect();
+                    expressions.push(Expression::Not(Box::new(Expression::Or(values))))
+                },
+                VectorSearchExpression::Range(field_path, range) => {
+                    expressions.push(range.to_expression(&field_path))
+                },
             }
         }
         Expression::Or(expressions)
@@ -244,8 +568,21 @@ pub struct VectorSearchJson {
     index_name: String,
     component_id: Option<String>,
     limit: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ef_search: Option<u32>,
     vector: Vec<f32>,
     expressions: Option<JsonExpression>,
+    #[serde(default)]
+    include_vectors: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mmr: Option<VectorMmrJson>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VectorMmrJson {
+    lambda: Option<f64>,
+    fetch_limit: Option<u32>,
 }
 
 impl VectorSearchJson {
@@ -270,6 +607,18 @@ enum VectorSearchExpressionJson {
         path: String,
         values: Vec<JsonValue>,
     },
+    NotIn {
+        path: String,
+        values: Vec<JsonValue>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Range {
+        path: String,
+        lower: Option<JsonValue>,
+        lower_inclusive: bool,
+        upper: Option<JsonValue>,
+        upper_inclusive: bool,
+    },
 }
 
 impl TryFrom<JsonValue> for VectorSearch {
@@ -291,7 +640,13 @@ impl TryFrom<JsonValue> for VectorSearch {
             component_id,
             expressions,
             limit: search.limit,
+            ef_search: search.ef_search,
             vector: search.vector,
+            include_vectors: search.include_vectors,
+            mmr: search.mmr.map(|mmr| VectorMmr {
+                lambda: mmr.lambda.unwrap_or(VectorMmr::DEFAULT_LAMBDA),
+                fetch_limit: mmr.fetch_limit,
+            }),
         };
         Ok(result)
     }
@@ -313,7 +668,13 @@ impl TryFrom<VectorSearch> for JsonValue {
             component_id: value.component_id.serialize_to_string(),
             expressions: expression_json,
             limit: value.limit,
+            ef_search: value.ef_search,
             vector: value.vector,
+            include_vectors: value.include_vectors,
+            mmr: value.mmr.map(|mmr| VectorMmrJson {
+                lambda: Some(mmr.lambda),
+                fetch_limit: mmr.fetch_limit,
+            }),
         };
         Ok(serde_json::to_value(search)?)
     }
@@ -335,6 +696,22 @@ impl TryFrom<VectorSearchExpression> for VectorSearchExpressionJson {
                     .map(|v| MaybeValue(v).to_internal_json())
                     .collect(),
             },
+            VectorSearchExpression::NotIn(path, values) => VectorSearchExpressionJson::NotIn {
+                path: path.into(),
+                values: values
+                    .into_iter()
+                    .map(|v| MaybeValue(v).to_internal_json())
+                    .collect(),
+            },
+            VectorSearchExpression::Range(path, VectorFilterRange { lower, upper }) => {
+                VectorSearchExpressionJson::Range {
+                    path: path.into(),
+                    lower_inclusive: lower.as_ref().is_some_and(|b| b.inclusive),
+                    lower: lower.map(|b| b.value.to_internal_json()),
+                    upper_inclusive: upper.as_ref().is_some_and(|b| b.inclusive),
+                    upper: upper.map(|b| b.value.to_internal_json()),
+                }
+            },
         };
         Ok(result)
     }
@@ -355,6 +732,38 @@ impl TryFrom<VectorSearchExpressionJson> for VectorSearchExpression {
                     .map(|v| anyhow::Ok(MaybeValue::try_from(v)?.0))
                     .try_collect()?,
             ),
+            VectorSearchExpressionJson::NotIn { path, values } => VectorSearchExpression::NotIn(
+                path.parse()?,
+                values
+                    .into_iter()
+                    .map(|v| anyhow::Ok(MaybeValue::try_from(v)?.0))
+                    .try_collect()?,
+            ),
+            VectorSearchExpressionJson::Range {
+                path,
+                lower,
+                lower_inclusive,
+                upper,
+                upper_inclusive,
+            } => {
+                let bound = |value: Option<JsonValue>, inclusive| {
+                    value
+                        .map(|v| {
+                            anyhow::Ok(VectorFilterBound {
+                                value: ConvexValue::try_from(v)?,
+                                inclusive,
+                            })
+                        })
+                        .transpose()
+                };
+                VectorSearchExpression::Range(
+                    path.parse()?,
+                    VectorFilterRange {
+                        lower: bound(lower, lower_inclusive)?,
+                        upper: bound(upper, upper_inclusive)?,
+                    },
+                )
+            },
         };
         Ok(result)
     }
@@ -381,8 +790,10 @@ impl VectorSearch {
             index_name,
             vector: self.vector,
             limit: self.limit,
+            ef_search: self.ef_search,
             expressions: self.expressions.into_iter().collect(),
             original_table_name,
+            exact: false,
         };
         Ok(result)
     }
@@ -391,9 +802,13 @@ impl VectorSearch {
 pub struct InternalVectorSearch {
     pub index_name: GenericIndexName<TabletId>,
     pub limit: Option<u32>,
+    pub ef_search: Option<u32>,
     pub vector: Vec<f32>,
     pub expressions: Vec<VectorSearchExpression>,
     pub original_table_name: TableName,
+    /// Whether to compare the query against every vector instead of walking the
+    /// HNSW graph. Only used internally, to measure an index's recall.
+    pub exact: bool,
 }
 
 impl InternalVectorSearch {
@@ -409,17 +824,22 @@ impl InternalVectorSearch {
 pub struct CompiledVectorSearch {
     pub vector: IndexedVector,
     pub limit: u32,
+    pub ef_search: Option<u32>,
     pub filter_conditions: BTreeMap<FieldPath, CompiledVectorFilter>,
+    pub exact: bool,
 }
 
 impl Debug for CompiledVectorSearch {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "CompiledVectorSearch {{ vector_size: {}, limit: {}, filter_conditions: {:?} }}",
+            "CompiledVectorSearch {{ vector_size: {}, limit: {}, ef_search: {:?}, \
+             filter_conditions: {:?}, exact: {} }}",
             self.vector.len(),
             self.limit,
+            self.ef_search,
             &self.filter_conditions,
+            self.exact,
         )
     }
 }
@@ -428,6 +848,56 @@ impl Debug for CompiledVectorSearch {
 pub enum CompiledVectorFilter {
     Eq(Vec<u8>),
     In(Vec<Vec<u8>>),
+    NotIn(Vec<Vec<u8>>),
+    Range(CompiledVectorRange),
+}
+
+/// A range over a filter field's numbers, which disk segments index as
+/// floats and integers separately from the field's values.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompiledVectorRange {
+    pub int64: bool,
+    pub lower: Option<(f64, bool)>,
+    pub upper: Option<(f64, bool)>,
+}
+
+impl CompiledVectorRange {
+    pub fn contains(&self, value: f64) -> bool {
+        let above_lower = match self.lower {
+            Some((lower, true)) => value >= lower,
+            Some((lower, false)) => value > lower,
+            None => true,
+        };
+        let below_upper = match self.upper {
+            Some((upper, true)) => value <= upper,
+            Some((upper, false)) => value < upper,
+            None => true,
+        };
+        above_lower && below_upper
+    }
+}
+
+impl From<&VectorFilterRange> for CompiledVectorRange {
+    fn from(range: &VectorFilterRange) -> Self {
+        // Disk segments compare integers as floats too, so int64 bounds beyond
+        // 2^53 are rounded.
+        let number = |value: &ConvexValue| match value {
+            ConvexValue::Int64(i) => *i as f64,
+            ConvexValue::Float64(f) => *f,
+            _ => f64::NAN,
+        };
+        Self {
+            int64: range.is_int64(),
+            lower: range
+                .lower
+                .as_ref()
+                .map(|b| (number(&b.value), b.inclusive)),
+            upper: range
+                .upper
+                .as_ref()
+                .map(|b| (number(&b.value), b.inclusive)),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -466,6 +936,8 @@ impl VectorSearchQueryResult {
         PublicVectorSearchQueryResult {
             id: DeveloperDocumentId::new(table_number, self.id),
             score: self.score,
+            vector: None,
+            distance: None,
         }
     }
 }
@@ -475,6 +947,7 @@ impl From<CompiledVectorSearch> for proto::CompiledVectorQuery {
         Self {
             vector: value.vector.into(),
             limit: value.limit,
+            ef_search: value.ef_search,
             filter_conditions: value
                 .filter_conditions
                 .into_iter()
@@ -485,6 +958,7 @@ impl From<CompiledVectorSearch> for proto::CompiledVectorQuery {
                     },
                 )
                 .collect(),
+            exact: value.exact,
         }
     }
 }
@@ -511,7 +985,9 @@ impl TryFrom<proto::CompiledVectorQuery> for CompiledVectorSearch {
         Ok(Self {
             vector: value.vector.try_into()?,
             limit: value.limit,
+            ef_search: value.ef_search,
             filter_conditions: filter_conditions.into_iter().collect(),
+            exact: value.exact,
         })
     }
 }
@@ -525,6 +1001,20 @@ impl From<CompiledVectorFilter> for proto::compiled_vector_query_filter_conditio
                     eq_conditions: values,
                 })
             },
+            CompiledVectorFilter::NotIn(values) => {
+                Self::NotInCondition(proto::CompiledVectorQueryFilterInCondition {
+                    eq_conditions: values,
+                })
+            },
+            CompiledVectorFilter::Range(range) => {
+                Self::RangeCondition(proto::CompiledVectorQueryFilterRangeCondition {
+                    int64: range.int64,
+                    lower: range.lower.map(|(lower, _)| lower),
+                    lower_inclusive: range.lower.is_some_and(|(_, inclusive)| inclusive),
+                    upper: range.upper.map(|(upper, _)| upper),
+                    upper_inclusive: range.upper.is_some_and(|(_, inclusive)| inclusive),
+                })
+            },
         }
     }
 }
@@ -542,6 +1032,16 @@ impl TryFrom<proto::compiled_vector_query_filter_condition::Filter> for Compiled
             proto::compiled_vector_query_filter_condition::Filter::InCondition(value) => {
                 Ok(Self::In(value.eq_conditions))
             },
+            proto::compiled_vector_query_filter_condition::Filter::NotInCondition(value) => {
+                Ok(Self::NotIn(value.eq_conditions))
+            },
+            proto::compiled_vector_query_filter_condition::Filter::RangeCondition(value) => {
+                Ok(Self::Range(CompiledVectorRange {
+                    int64: value.int64,
+                    lower: value.lower.map(|lower| (lower, value.lower_inclusive)),
+                    upper: value.upper.map(|upper| (upper, value.upper_inclusive)),
+                }))
+            },
         }
     }
 }
@@ -580,11 +1080,22 @@ impl TryFrom<proto::VectorQueryResult> for VectorSearchQueryResult {
 pub struct PublicVectorSearchQueryResult {
     pub score: f32,
     pub id: DeveloperDocumentId,
+    /// The stored vector, if the query asked for it.
+    pub vector: Option<Vec<f32>>,
+    /// The exact distance from the query vector under the index's metric, if
+    /// the query asked for vectors.
+    pub distance: Option<f32>,
 }
 
 impl Size for PublicVectorSearchQueryResult {
     fn size(&self) -> usize {
-        self.id.size() + std::mem::size_of::<f32>()
+        self.id.size()
+            + std::mem::size_of::<f32>()
+            + self
+                .vector
+                .as_ref()
+                .map_or(0, |vector| vector.len() * std::mem::size_of::<f32>())
+            + self.distance.map_or(0, |_| std::mem::size_of::<f32>())
     }
 
     fn nesting(&self) -> usize {
@@ -602,10 +1113,17 @@ impl PartialEq for PublicVectorSearchQueryResult {
 
 impl From<PublicVectorSearchQueryResult> for JsonValue {
     fn from(value: PublicVectorSearchQueryResult) -> Self {
-        json!({
+        let mut result = json!({
             "_id": String::from(value.id),
             "_score": value.score,
-        })
+        });
+        if let Some(vector) = value.vector {
+            result["_vector"] = json!(vector);
+        }
+        if let Some(distance) = value.distance {
+            result["_distance"] = json!(distance);
+        }
+        result
     }
 }
 
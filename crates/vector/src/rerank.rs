@@ -0,0 +1,115 @@
+use common::bootstrap_model::index::vector_index::VectorDistanceMetric;
+
+/// The exact distance between two vectors under `metric`, where smaller is
+/// closer. Cosine distance is `1 - cosine similarity`, dot product distance is
+/// the negated dot product, and Euclidean distance is the L2 norm of the
+/// difference.
+pub fn vector_distance(metric: VectorDistanceMetric, v1: &[f32], v2: &[f32]) -> f32 {
+    match metric {
+        VectorDistanceMetric::Cosine => 1.0 - cosine(v1, v2),
+        VectorDistanceMetric::DotProduct => -dot(v1, v2),
+        VectorDistanceMetric::Euclidean => v1
+            .iter()
+            .zip(v2)
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum::<f32>()
+            .sqrt(),
+    }
+}
+
+/// Orders `candidates` by maximal marginal relevance to `query`, returning the
+/// indexes of at most `limit` of them.
+///
+/// Each step picks the candidate maximizing `lambda * sim(query, candidate) -
+/// (1 - lambda) * max(sim(candidate, picked))`, so a `lambda` of 1 ranks purely
+/// by relevance and smaller values increasingly penalize candidates that are
+/// similar to ones already picked. Similarity is always cosine, regardless of
+/// the index's metric, so that both terms are on the same scale.
+pub fn maximal_marginal_relevance(
+    query: &[f32],
+    candidates: &[&[f32]],
+    lambda: f64,
+    limit: usize,
+) -> Vec<usize> {
+    let relevance: Vec<f64> = candidates
+        .iter()
+        .map(|candidate| f64::from(cosine(query, candidate)))
+        .collect();
+    // The highest similarity of each candidate to any picked candidate.
+    let mut redundancy: Vec<Option<f64>> = vec![None; candidates.len()];
+    let mut picked = Vec::with_capacity(limit.min(candidates.len()));
+    while picked.len() < limit {
+        let best = (0..candidates.len())
+            .filter(|i| !picked.contains(i))
+            .map(|i| {
+                let score = lambda * relevance[i] - (1.0 - lambda) * redundancy[i].unwrap_or(0.0);
+                (i, score)
+            })
+            .max_by(|(i, a), (j, b)| a.total_cmp(b).then(j.cmp(i)));
+        let Some((best, _)) = best else {
+            break;
+        };
+        picked.push(best);
+        for (i, candidate) in candidates.iter().enumerate() {
+            let similarity = f64::from(cosine(candidates[best], candidate));
+            redundancy[i] = Some(redundancy[i].map_or(similarity, |r| r.max(similarity)));
+        }
+    }
+    picked
+}
+
+fn dot(v1: &[f32], v2: &[f32]) -> f32 {
+    v1.iter().zip(v2).map(|(a, b)| a * b).sum()
+}
+
+fn cosine(v1: &[f32], v2: &[f32]) -> f32 {
+    let norms = dot(v1, v1).sqrt() * dot(v2, v2).sqrt();
+    if norms == 0.0 {
+        return 0.0;
+    }
+    dot(v1, v2) / norms
+}
+
+#[cfg(test)]
+mod tests {
+    use common::bootstrap_model::index::vector_index::VectorDistanceMetric;
+
+    use super::{
+        maximal_marginal_relevance,
+        vector_distance,
+    };
+
+    #[test]
+    fn test_vector_distance() {
+        let (v1, v2) = ([3.0, 0.0], [0.0, 4.0]);
+        assert_eq!(vector_distance(VectorDistanceMetric::Cosine, &v1, &v2), 1.0);
+        assert_eq!(
+            vector_distance(VectorDistanceMetric::DotProduct, &v1, &v1),
+            -9.0
+        );
+        assert_eq!(
+            vector_distance(VectorDistanceMetric::Euclidean, &v1, &v2),
+            5.0
+        );
+    }
+
+    #[test]
+    fn test_mmr_diversifies() {
+        let query = [1.0, 0.0];
+        let near = [1.0, 0.1];
+        let near_duplicate = [1.0, 0.11];
+        let different = [0.7, -0.7];
+        let candidates: [&[f32]; 3] = [&near, &near_duplicate, &different];
+
+        // Ranking purely by relevance keeps the near duplicate second.
+        assert_eq!(
+            maximal_marginal_relevance(&query, &candidates, 1.0, 3),
+            vec![0, 1, 2]
+        );
+        // Penalizing redundancy moves the different vector ahead of it.
+        assert_eq!(
+            maximal_marginal_relevance(&query, &candidates, 0.5, 2),
+            vec![0, 2]
+        );
+    }
+}
@@ -69,6 +69,23 @@ pub struct VectorIndexManager {
     pub indexes: IndexState,
 }
 
+/// The segments of a vector index that the backend has loaded, for operators
+/// to tell when it needs compacting.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VectorIndexSegmentStats {
+    /// The number of live vectors in each disk segment.
+    pub segment_sizes: Vec<u64>,
+    /// Vectors that are deleted but still take up space in disk segments
+    /// until they're compacted.
+    pub num_deleted: u64,
+    /// The estimated size of the disk segments' vectors, including deleted
+    /// ones. See `FragmentedVectorSegment::total_size_bytes`.
+    pub segment_bytes: u64,
+    /// The size of the vectors written since the disk segments were built,
+    /// which the backend keeps in memory.
+    pub memory_index_bytes: usize,
+}
+
 #[derive(Clone)]
 pub enum IndexState {
     Bootstrapping(OrdMap<IndexId, VectorIndexState>),
@@ -170,6 +187,42 @@ impl VectorIndexManager {
             .map(|(id, (_, idx))| (*id, idx.size())))
     }
 
+    /// Returns `None` if the index isn't a vector index or is still
+    /// backfilling.
+    pub fn segment_stats(&self, index: &Index) -> anyhow::Result<Option<VectorIndexSegmentStats>> {
+        let IndexConfig::Vector {
+            ref developer_config,
+            ..
+        } = index.metadata.config
+        else {
+            return Ok(None);
+        };
+        let Some((state, memory_index)) = self.require_ready_index(&index.id())? else {
+            return Ok(None);
+        };
+        let VectorIndexState::SnapshottedAt(ref snapshot) = state else {
+            return Ok(None);
+        };
+        let VectorIndexSnapshotData::MultiSegment(ref segments) = snapshot.data else {
+            return Ok(None);
+        };
+        Ok(Some(VectorIndexSegmentStats {
+            segment_sizes: segments
+                .iter()
+                .map(|segment| segment.non_deleted_vectors())
+                .try_collect()?,
+            num_deleted: segments
+                .iter()
+                .map(|segment| u64::from(segment.num_deleted))
+                .sum(),
+            segment_bytes: segments
+                .iter()
+                .map(|segment| segment.total_size_bytes(developer_config.dimensions))
+                .sum::<anyhow::Result<u64>>()?,
+            memory_index_bytes: memory_index.size(),
+        }))
+    }
+
     pub fn num_transactions(&self, index_id: IndexId) -> anyhow::Result<Option<usize>> {
         let Some((_, index)) = self.require_ready_indexes()?.get(&index_id) else {
             return Ok(None);
@@ -268,7 +321,8 @@ impl VectorIndexManager {
             (None, Some(insertion)) => {
                 let metadata = IndexMetadata::try_from(insertion.value().clone().0)?;
                 if let IndexConfig::Vector {
-                    ref on_disk_state, ..
+                    ref developer_config,
+                    ref on_disk_state,
                 } = metadata.config
                 {
                     let VectorIndexState::Backfilling(state) = on_disk_state else {
@@ -280,7 +334,7 @@ impl VectorIndexManager {
                     self.indexes.insert(
                         insertion.id().internal_id(),
                         index,
-                        MemoryVectorIndex::new(ts),
+                        MemoryVectorIndex::new(ts, developer_config.distance_metric),
                     );
 
                     metrics::log_index_created()
@@ -479,15 +533,24 @@ impl VectorIndexManager {
                         VectorIndexType::MultiSegment,
                         &SEARCHLIGHT_CLUSTER_NAME,
                     );
+                    // Skip the segments without vectors in the partitions the
+                    // query is restricted to.
+                    let partitions = qdrant_schema.query_partitions(&compiled_query);
+                    let segments: Vec<_> = segments
+                        .iter()
+                        .filter(|segment| {
+                            partitions
+                                .as_ref()
+                                .is_none_or(|partitions| segment.may_contain_partitions(partitions))
+                        })
+                        .cloned()
+                        .map(|segment| segment.to_paths_proto())
+                        .try_collect()?;
                     let total_segments = segments.len();
                     let results = searcher
                         .execute_multi_segment_vector_query(
                             search_storage.clone(),
-                            segments
-                                .iter()
-                                .cloned()
-                                .map(|segment| segment.to_paths_proto())
-                                .try_collect()?,
+                            segments,
                             qdrant_schema,
                             compiled_query.clone(),
                             overfetch_delta as u32,
@@ -20,6 +20,18 @@ export const insert = mutation(
   },
 );
 
+// The component's write shares this mutation's transaction, so it's rolled
+// back when the mutation fails.
+export const insertThenFail = mutation(
+  async (ctx, { channel, text }: { channel: string; text: string }) => {
+    await ctx.runMutation(components.component.messages.insertMessage, {
+      channel,
+      text,
+    });
+    throw new Error("failing after writing to the component");
+  },
+);
+
 export const hello = action(async (ctx) => {
   return await ctx.runAction(components.component.messages.hello, {});
 });
@@ -5,11 +5,19 @@ import { GenericActionCtx, PublicHttpAction } from "convex/server";
 import { httpAction } from "./ratelimiter/_generated/server.js";
 
 // A version of httpAction that typechecks when used in other components.
-type ClientHttpCtx = Omit<GenericActionCtx<any>, "vectorSearch"> & {
+type ClientHttpCtx = Omit<
+  GenericActionCtx<any>,
+  "vectorSearch" | "hybridSearch"
+> & {
   vectorSearch: unknown;
+  hybridSearch: unknown;
 };
-type ClientExportedHttpCtx = Omit<GenericActionCtx<any>, "vectorSearch"> & {
+type ClientExportedHttpCtx = Omit<
+  GenericActionCtx<any>,
+  "vectorSearch" | "hybridSearch"
+> & {
   vectorSearch: any;
+  hybridSearch: any;
 };
 type OmitCallSignature<T> = T extends {
   (...args: any[]): any;
@@ -172,3 +172,40 @@ test("failed mutations don't block the queue", async () => {
   await expect(firstPromise).rejects.toThrow("First mutation failed");
   await expect(secondPromise).resolves.toBe("second result");
 });
+
+test("queries read the writes of earlier mutations", async () => {
+  const client = new ConvexHttpClient("http://test");
+  const apiQueryFunc = makeFunctionReference<"query", {}, string>(
+    "test:query",
+  );
+
+  const fetchMock = vi.fn();
+  fetchMock.mockImplementation((url, options) => {
+    const body = JSON.parse(options.body);
+    if (body.path === "test:mutation") {
+      return Promise.resolve({
+        ok: true,
+        json: () =>
+          Promise.resolve({
+            status: "success",
+            value: "done",
+            ts: "AQAAAAAAAAA=",
+          }),
+      });
+    }
+    return Promise.resolve({
+      ok: true,
+      json: () => Promise.resolve({ status: "success", value: "result" }),
+    });
+  });
+  setFetch(fetchMock);
+
+  await client.query(apiQueryFunc, {});
+  expect(JSON.parse(fetchMock.mock.calls[0][1].body).minTs).toBeUndefined();
+
+  await client.mutation(apiMutationFunc, { value: "write" });
+  await client.query(apiQueryFunc, {});
+  expect(JSON.parse(fetchMock.mock.calls[2][1].body).minTs).toBe(
+    "AQAAAAAAAAA=",
+  );
+});
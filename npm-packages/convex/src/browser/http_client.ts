@@ -63,6 +63,9 @@ export class ConvexHttpClient {
   private auth?: string;
   private adminAuth?: string;
   private encodedTsPromise?: Promise<string>;
+  // The commit timestamp of the last mutation this client ran, so later
+  // queries read its writes.
+  private lastMutationTs?: string;
   private debug: boolean;
   private fetchOptions?: FetchOptions;
   private logger: Logger;
@@ -291,6 +294,9 @@ export class ConvexHttpClient {
       format: "convex_encoded_json",
       args,
       ...(timestamp ? { ts: timestamp } : {}),
+      ...(!timestamp && this.lastMutationTs
+        ? { minTs: this.lastMutationTs }
+        : {}),
     });
     const endpoint = timestamp
       ? `${this.address}/api/query_at_ts`
@@ -365,6 +371,9 @@ export class ConvexHttpClient {
     }
     switch (respJSON.status) {
       case "success":
+        if (respJSON.ts !== undefined) {
+          this.lastMutationTs = respJSON.ts;
+        }
         return jsonToConvex(respJSON.value);
       case "error":
         if (respJSON.errorData !== undefined) {
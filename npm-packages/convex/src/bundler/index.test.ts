@@ -55,6 +55,34 @@ test("bundle finds JavaScript functions", async () => {
   expect(bundles[1].path).toEqual("foo.js");
 });
 
+test("bundle emits a bundle per exported function", async () => {
+  const fixtureDir = dirname + "/test_fixtures/js/project01";
+  const ctx = await getDefaultCtx();
+  const entryPoints = await entryPointsByEnvironment(ctx, fixtureDir);
+  const { modules } = await bundle(
+    ctx,
+    fixtureDir,
+    entryPoints.isolate,
+    false,
+    "browser",
+    undefined,
+    undefined,
+    undefined,
+    true,
+  );
+  const functionBundles = sorted(
+    modules.filter((m) => m.path.startsWith("_deps/functions/")),
+    (b) => b.path,
+  );
+  expect(functionBundles.map((b) => b.path)).toEqual([
+    "_deps/functions/foo/default.js",
+    "_deps/functions/foo/exported.js",
+    "_deps/functions/foo/notAFunction.js",
+  ]);
+  // The modules themselves are still bundled.
+  expect(modules.map((m) => m.path)).toContain("foo.js");
+});
+
 test("returns true when simple import httpRouter found", async () => {
   const result = await doesImportConvexHttpRouter(`
     import { httpRouter } from "convex/server";
@@ -62,10 +62,78 @@ type EsBuildResult = esbuild.BuildResult & {
   bundledModuleNames: Set<string>;
 };
 
+// Per-function bundles live at `_deps/functions/<module>/<function>.js`. The
+// backend runs a function from its bundle when there is one, so the isolate
+// only evaluates code reachable from that function.
+const FUNCTION_BUNDLES_DIR = path.join("_deps", "functions");
+const FUNCTION_BUNDLE_PREFIX = "convex-function-bundle:";
+
+// Resolves `convex-function-bundle:<path>#<export>` entry points to a module
+// that only re-exports `<export>` from `<path>`, so esbuild tree-shakes
+// everything else in the module away.
+const functionBundlePlugin: esbuild.Plugin = {
+  name: "convex-function-bundle",
+  setup(build) {
+    build.onResolve(
+      { filter: new RegExp(`^${FUNCTION_BUNDLE_PREFIX}`) },
+      (args) => ({
+        path: args.path.slice(FUNCTION_BUNDLE_PREFIX.length),
+        namespace: "convex-function-bundle",
+      }),
+    );
+    build.onLoad(
+      { filter: /.*/, namespace: "convex-function-bundle" },
+      (args) => {
+        const separator = args.path.lastIndexOf("#");
+        const modulePath = args.path.slice(0, separator);
+        const exportName = args.path.slice(separator + 1);
+        return {
+          contents: `export { ${exportName} } from ${JSON.stringify(modulePath)};`,
+          resolveDir: path.dirname(modulePath),
+        };
+      },
+    );
+  },
+};
+
+type EntryPoint = string | { in: string; out: string };
+
+// One entry point per exported function of each bundled module, named by the
+// exports esbuild found when bundling the modules themselves.
+function functionEntryPoints(
+  dir: string,
+  result: EsBuildResult,
+): EntryPoint[] {
+  const entryPoints: EntryPoint[] = [];
+  for (const output of Object.values(result.metafile!.outputs)) {
+    if (!output.entryPoint) {
+      continue;
+    }
+    const modulePath = path.resolve(output.entryPoint);
+    const relPath = path.relative(dir, modulePath);
+    const moduleName = path.join(
+      path.dirname(relPath),
+      path.basename(relPath, path.extname(relPath)),
+    );
+    for (const exportName of output.exports) {
+      // The backend only looks for bundles of functions whose names are
+      // valid path components.
+      if (!/^[A-Za-z0-9_]+$/.test(exportName)) {
+        continue;
+      }
+      entryPoints.push({
+        in: `${FUNCTION_BUNDLE_PREFIX}${modulePath}#${exportName}`,
+        out: path.join(FUNCTION_BUNDLES_DIR, moduleName, exportName),
+      });
+    }
+  }
+  return entryPoints;
+}
+
 async function doEsbuild(
   ctx: Context,
   dir: string,
-  entryPoints: string[],
+  entryPoints: EntryPoint[],
   generateSourceMaps: boolean,
   platform: esbuild.Platform,
   chunksFolder: string,
@@ -85,7 +153,7 @@ async function doEsbuild(
       outbase: dir,
       conditions: ["convex", "module", ...extraConditions],
       // The wasmPlugin should be last so it doesn't run on external modules.
-      plugins: [external.plugin, wasmPlugin],
+      plugins: [external.plugin, functionBundlePlugin, wasmPlugin],
       write: false,
       sourcemap: generateSourceMaps,
       splitting: true,
@@ -111,7 +179,8 @@ async function doEsbuild(
       if (
         relPath.indexOf("(disabled):") !== -1 ||
         relPath.startsWith("wasm-binary:") ||
-        relPath.startsWith("wasm-stub:")
+        relPath.startsWith("wasm-stub:") ||
+        relPath.startsWith("convex-function-bundle:")
       ) {
         continue;
       }
@@ -192,6 +261,7 @@ export async function bundle(
   chunksFolder = "_deps",
   externalPackagesAllowList: string[] = [],
   extraConditions: string[] = [],
+  functionBundles = false,
 ): Promise<{
   modules: Bundle[];
   externalDependencies: Map<string, string>;
@@ -201,7 +271,7 @@ export async function bundle(
     ctx,
     externalPackagesAllowList,
   );
-  const result = await doEsbuild(
+  let result = await doEsbuild(
     ctx,
     dir,
     entryPoints,
@@ -211,6 +281,20 @@ export async function bundle(
     availableExternalPackages,
     extraConditions,
   );
+  if (functionBundles && result.errors.length === 0) {
+    // Bundle the modules again together with their functions so code shared
+    // between modules and functions is deduplicated into the same chunks.
+    result = await doEsbuild(
+      ctx,
+      dir,
+      [...entryPoints, ...functionEntryPoints(dir, result)],
+      generateSourceMaps,
+      platform,
+      chunksFolder,
+      availableExternalPackages,
+      extraConditions,
+    );
+  }
   if (result.errors.length) {
     const errorMessage = result.errors
       .map((e) => `esbuild error: ${e.text}`)
@@ -0,0 +1,64 @@
+import { Command } from "@commander-js/extra-typings";
+import chalk from "chalk";
+import { oneoffContext } from "../bundler/context.js";
+import {
+  deploymentSelectionWithinProjectFromOptions,
+  loadSelectedDeploymentCredentials,
+} from "./lib/api.js";
+import { readBenchConfig, runBenchInDeployment } from "./lib/bench.js";
+import { actionDescription } from "./lib/command.js";
+import { getDeploymentSelection } from "./lib/deploymentSelection.js";
+
+export const bench = new Command("bench")
+  .summary("Measure a deployment's write and subscription performance")
+  .description(
+    "Run a synthetic workload against a deployment and print commit latency, " +
+      "index write amplification, and subscription fan-out as JSON.\n\n" +
+      "  Run the default workload: `npx convex bench`\n" +
+      "  Run a workload from a file: `npx convex bench --config bench.json`\n\n" +
+      "The workload only writes to a table that is new or empty (`bench_documents` by default), " +
+      "and empties it again afterwards. Runs with the same seed issue the same operations.",
+  )
+  .allowExcessArguments(false)
+  .option("--config <path>", "Path to a JSON bench config")
+  .option("--seed <seed>", "Seed for the generated workload")
+  .option("--transactions <count>", "Number of write transactions to run")
+  .option(
+    "--subscriptions <count>",
+    "Number of subscriptions to keep open during the run",
+  )
+  .addDeploymentSelectionOptions(actionDescription("Run the bench against"))
+  .showHelpAfterError()
+  .action(async (options) => {
+    const ctx = await oneoffContext(options);
+    const config = await readBenchConfig(ctx, options.config);
+    if (options.seed !== undefined) {
+      config.seed = parseInt(options.seed);
+    }
+    if (options.transactions !== undefined) {
+      config.transactions = parseInt(options.transactions);
+    }
+    if (options.subscriptions !== undefined) {
+      config.subscriptions = parseInt(options.subscriptions);
+    }
+    const selectionWithinProject =
+      await deploymentSelectionWithinProjectFromOptions(ctx, options);
+    const deploymentSelection = await getDeploymentSelection(ctx, options);
+    const deployment = await loadSelectedDeploymentCredentials(
+      ctx,
+      deploymentSelection,
+      selectionWithinProject,
+    );
+    const deploymentNotice = deployment.deploymentFields?.deploymentName
+      ? ` against ${chalk.bold(deployment.deploymentFields.deploymentName)}`
+      : "";
+    await runBenchInDeployment(
+      ctx,
+      {
+        deploymentUrl: deployment.url,
+        adminKey: deployment.adminKey,
+        deploymentNotice,
+      },
+      config,
+    );
+  });
@@ -30,6 +30,10 @@ export function validatorToType(
     return "string";
   } else if (validator.type === "bytes") {
     return "ArrayBuffer";
+  } else if (validator.type === "decimal") {
+    return 'import("convex/values").Decimal';
+  } else if (validator.type === "datetime") {
+    return 'import("convex/values").DateTime';
   } else if (validator.type === "any") {
     return "any";
   } else if (validator.type === "literal") {
@@ -0,0 +1,48 @@
+import { Command } from "@commander-js/extra-typings";
+import chalk from "chalk";
+import { oneoffContext } from "../bundler/context.js";
+import {
+  deploymentSelectionWithinProjectFromOptions,
+  loadSelectedDeploymentCredentials,
+} from "./lib/api.js";
+import { actionDescription } from "./lib/command.js";
+import { getDeploymentSelection } from "./lib/deploymentSelection.js";
+import { loadFixturesInDeployment } from "./lib/fixtures.js";
+
+export const fixtures = new Command("fixtures")
+  .summary("Seed a fresh dev deployment from a fixtures file")
+  .description(
+    "Load documents, files, and scheduled jobs from a JSON fixtures file into empty tables, in a single transaction.\n\n" +
+      "  Load fixtures: `npx convex fixtures fixtures.json`\n\n" +
+      'Documents need a `_key`, and can refer to each other with `{ "$ref": "table/key" }` ' +
+      'and to files with `{ "$file": "key" }`. IDs are derived from keys, so they stay the same ' +
+      "across fresh deployments with the same schema.\n\n" +
+      "Only local deployments allow loading fixtures.",
+  )
+  .argument("<path>", "Path to the fixtures JSON file")
+  .allowExcessArguments(false)
+  .addDeploymentSelectionOptions(actionDescription("Load fixtures into"))
+  .showHelpAfterError()
+  .action(async (fixturesPath, options) => {
+    const ctx = await oneoffContext(options);
+    const selectionWithinProject =
+      await deploymentSelectionWithinProjectFromOptions(ctx, options);
+    const deploymentSelection = await getDeploymentSelection(ctx, options);
+    const deployment = await loadSelectedDeploymentCredentials(
+      ctx,
+      deploymentSelection,
+      selectionWithinProject,
+    );
+    const deploymentNotice = deployment.deploymentFields?.deploymentName
+      ? ` into ${chalk.bold(deployment.deploymentFields.deploymentName)}`
+      : "";
+    await loadFixturesInDeployment(
+      ctx,
+      {
+        deploymentUrl: deployment.url,
+        adminKey: deployment.adminKey,
+        deploymentNotice,
+      },
+      fixturesPath,
+    );
+  });
@@ -23,10 +23,13 @@ import { convexExport } from "./convexExport.js";
 import { convexImport } from "./convexImport.js";
 import { env } from "./env.js";
 import { data } from "./data.js";
+import { fixtures } from "./fixtures.js";
+import { bench } from "./bench.js";
 import inquirer from "inquirer";
 import inquirerSearchList from "inquirer-search-list";
 import { format } from "util";
 import { functionSpec } from "./functionSpec.js";
+import { schemaExport } from "./schemaExport.js";
 import { disableLocalDeployments } from "./disableLocalDev.js";
 import { mcp } from "./mcp.js";
 import dns from "node:dns";
@@ -102,11 +105,14 @@ async function main() {
     .addCommand(convexExport)
     .addCommand(env)
     .addCommand(data)
+    .addCommand(fixtures)
+    .addCommand(bench)
     .addCommand(codegen)
     .addCommand(update)
     .addCommand(logout)
     .addCommand(networkTest, { hidden: true })
     .addCommand(functionSpec)
+    .addCommand(schemaExport)
     .addCommand(disableLocalDeployments)
     .addCommand(mcp)
     .addHelpCommand("help <command>", "Show help for given <command>")
@@ -0,0 +1,70 @@
+import chalk from "chalk";
+import {
+  Context,
+  logFinishedStep,
+  logOutput,
+  showSpinner,
+} from "../../bundler/context.js";
+import { deploymentFetch, logAndHandleFetchError } from "./utils/utils.js";
+
+export type BenchConfig = {
+  seed?: number;
+  tableName?: string;
+  transactions?: number;
+  writesPerTransaction?: number;
+  writeMix?: { inserts: number; updates: number; deletes: number };
+  documentSizeBytes?: number;
+  queryShape?: { type: "pointGet" } | { type: "scan"; limit: number };
+  queriesPerTransaction?: number;
+  subscriptions?: number;
+};
+
+export async function readBenchConfig(
+  ctx: Context,
+  configPath: string | undefined,
+): Promise<BenchConfig> {
+  if (configPath === undefined) {
+    return {};
+  }
+  if (!ctx.fs.exists(configPath)) {
+    return await ctx.crash({
+      exitCode: 1,
+      errorType: "invalid filesystem data",
+      printedMessage: `Bench config ${chalk.bold(configPath)} not found.`,
+    });
+  }
+  try {
+    return JSON.parse(ctx.fs.readUtf8File(configPath));
+  } catch (e: any) {
+    return await ctx.crash({
+      exitCode: 1,
+      errorType: "invalid filesystem data",
+      printedMessage: `Bench config ${chalk.bold(configPath)} isn't valid JSON: ${e.message}`,
+    });
+  }
+}
+
+export async function runBenchInDeployment(
+  ctx: Context,
+  deployment: {
+    deploymentUrl: string;
+    adminKey: string;
+    deploymentNotice: string;
+  },
+  config: BenchConfig,
+) {
+  const fetch = deploymentFetch(ctx, deployment);
+  showSpinner(ctx, `Running bench${deployment.deploymentNotice}...`);
+  let report: unknown;
+  try {
+    const response = await fetch("/api/run_bench", {
+      body: JSON.stringify({ config }),
+      method: "POST",
+    });
+    report = await response.json();
+  } catch (e) {
+    return await logAndHandleFetchError(ctx, e);
+  }
+  logFinishedStep(ctx, `Finished bench${deployment.deploymentNotice}`);
+  logOutput(ctx, JSON.stringify(report, null, 2));
+}
@@ -73,6 +73,10 @@ export interface ProjectConfig {
     staticApi: boolean;
     staticDataModel: boolean;
   };
+
+  // Also upload a bundle per function, so running a function only evaluates
+  // the code it needs instead of its whole module.
+  functionBundles?: boolean;
 }
 
 export interface Config {
@@ -200,6 +204,18 @@ export async function parseProjectConfig(
     });
   }
 
+  if (
+    obj.functionBundles !== undefined &&
+    typeof obj.functionBundles !== "boolean"
+  ) {
+    return await ctx.crash({
+      exitCode: 1,
+      errorType: "invalid filesystem data",
+      printedMessage:
+        "Expected `functionBundles` in `convex.json` to be true or false",
+    });
+  }
+
   return obj;
 }
 
@@ -381,6 +397,10 @@ export async function configFromProjectConfig(
     entryPoints.isolate,
     true,
     "browser",
+    undefined,
+    undefined,
+    undefined,
+    projectConfig.functionBundles ?? false,
   );
   if (verbose) {
     logMessage(
@@ -599,6 +619,9 @@ function stripDefaults(projectConfig: ProjectConfig): any {
   if (Object.keys(stripped.codegen).length === 0) {
     delete stripped.codegen;
   }
+  if (stripped.functionBundles === false) {
+    delete stripped.functionBundles;
+  }
   return stripped;
 }
 
@@ -10,8 +10,10 @@ import {
 import { spawnSync } from "child_process";
 import { deploymentFetch, logAndHandleFetchError } from "./utils/utils.js";
 import {
+  MigrationStep,
   schemaStatus,
   SchemaStatus,
+  SchemaViolationReport,
   StartPushRequest,
   startPushResponse,
   StartPushResponse,
@@ -130,6 +132,7 @@ export async function waitForSchema(
           schemaChange: startPush.schemaChange,
           timeoutMs: SCHEMA_TIMEOUT_MS,
           dryRun: options.dryRun,
+          includeMigrationPlan: true,
         }),
         method: "POST",
         headers: {
@@ -176,6 +179,12 @@ export async function waitForSchema(
         msg += ".";
         logFailure(ctx, msg);
         logError(ctx, chalk.red(`${currentStatus.error}`));
+        if (currentStatus.report) {
+          logSchemaViolationReport(ctx, currentStatus.report);
+        }
+        if (currentStatus.migrationPlan) {
+          logMigrationPlan(ctx, currentStatus.migrationPlan.steps);
+        }
         return await ctx.crash({
           exitCode: 1,
           errorType: {
@@ -204,6 +213,39 @@ export async function waitForSchema(
   }
 }
 
+function logSchemaViolationReport(
+  ctx: Context,
+  report: SchemaViolationReport,
+) {
+  logError(ctx, "Documents that don't match the schema:");
+  for (const table of report.tables) {
+    logError(
+      ctx,
+      `  ${table.tableName}: ${table.count} document${table.count === 1 ? "" : "s"} (e.g. ${table.sampleIds.join(", ")})`,
+    );
+  }
+}
+
+function logMigrationPlan(ctx: Context, steps: MigrationStep[]) {
+  logError(ctx, "To migrate these documents without downtime:");
+  steps.forEach((step, i) => {
+    let description: string;
+    switch (step.type) {
+      case "widenSchema":
+        description =
+          "Push a schema that accepts both the existing and the new document shapes.";
+        break;
+      case "backfill":
+        description = `Run a migration that rewrites the ${step.documentCount} document${step.documentCount === 1 ? "" : "s"} in "${step.tableName}" to match the new schema.`;
+        break;
+      case "narrowSchema":
+        description = "Push the new schema again.";
+        break;
+    }
+    logError(ctx, `  ${i + 1}. ${description}`);
+  });
+}
+
 export async function finishPush(
   ctx: Context,
   span: Span,
@@ -51,6 +51,35 @@ export const componentSchemaStatus = looseObject({
 });
 export type ComponentSchemaStatus = z.infer<typeof componentSchemaStatus>;
 
+export const schemaViolationReport = looseObject({
+  tables: z.array(
+    looseObject({
+      tableName: z.string(),
+      count: z.number(),
+      sampleIds: z.array(z.string()),
+      firstError: z.string(),
+    }),
+  ),
+});
+export type SchemaViolationReport = z.infer<typeof schemaViolationReport>;
+
+export const migrationStep = z.union([
+  looseObject({
+    type: z.literal("widenSchema"),
+    schema: z.any(),
+  }),
+  looseObject({
+    type: z.literal("backfill"),
+    tableName: z.string(),
+    documentCount: z.number(),
+  }),
+  looseObject({
+    type: z.literal("narrowSchema"),
+    schema: z.any(),
+  }),
+]);
+export type MigrationStep = z.infer<typeof migrationStep>;
+
 export const schemaStatus = z.union([
   looseObject({
     type: z.literal("inProgress"),
@@ -61,6 +90,10 @@ export const schemaStatus = z.union([
     error: z.string(),
     componentPath,
     tableName: z.nullable(z.string()),
+    report: z.optional(z.nullable(schemaViolationReport)),
+    migrationPlan: z.optional(
+      z.nullable(looseObject({ steps: z.array(migrationStep) })),
+    ),
   }),
   looseObject({
     type: z.literal("raceDetected"),
@@ -8,6 +8,8 @@ const baseConvexValidator = z.discriminatedUnion("type", [
   looseObject({ type: z.literal("boolean") }),
   looseObject({ type: z.literal("string") }),
   looseObject({ type: z.literal("bytes") }),
+  looseObject({ type: z.literal("decimal") }),
+  looseObject({ type: z.literal("datetime") }),
   looseObject({ type: z.literal("any") }),
   looseObject({ type: z.literal("literal"), value: z.any() }),
   looseObject({ type: z.literal("id"), tableName: z.string() }),
@@ -0,0 +1,54 @@
+import chalk from "chalk";
+import { Context, logFinishedStep } from "../../bundler/context.js";
+import { deploymentFetch, logAndHandleFetchError } from "./utils/utils.js";
+
+type LoadFixturesResponse = {
+  documentIds: Record<string, string>;
+  fileIds: Record<string, string>;
+  scheduledJobs: number;
+};
+
+export async function loadFixturesInDeployment(
+  ctx: Context,
+  deployment: {
+    deploymentUrl: string;
+    adminKey: string;
+    deploymentNotice: string;
+  },
+  fixturesPath: string,
+) {
+  if (!ctx.fs.exists(fixturesPath)) {
+    return await ctx.crash({
+      exitCode: 1,
+      errorType: "invalid filesystem data",
+      printedMessage: `Fixtures file ${chalk.bold(fixturesPath)} not found.`,
+    });
+  }
+  let fixtures: unknown;
+  try {
+    fixtures = JSON.parse(ctx.fs.readUtf8File(fixturesPath));
+  } catch (e: any) {
+    return await ctx.crash({
+      exitCode: 1,
+      errorType: "invalid filesystem data",
+      printedMessage: `Fixtures file ${chalk.bold(fixturesPath)} isn't valid JSON: ${e.message}`,
+    });
+  }
+  const fetch = deploymentFetch(ctx, deployment);
+  let loaded: LoadFixturesResponse;
+  try {
+    const response = await fetch("/api/load_fixtures", {
+      body: JSON.stringify({ fixtures }),
+      method: "POST",
+    });
+    loaded = await response.json();
+  } catch (e) {
+    return await logAndHandleFetchError(ctx, e);
+  }
+  const documents = Object.keys(loaded.documentIds).length;
+  const files = Object.keys(loaded.fileIds).length;
+  logFinishedStep(
+    ctx,
+    `Loaded ${documents} documents, ${files} files, and ${loaded.scheduledJobs} scheduled jobs${deployment.deploymentNotice}`,
+  );
+}
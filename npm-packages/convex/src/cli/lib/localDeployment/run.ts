@@ -56,6 +56,8 @@ export async function runLocalBackend(
   if (args.isLatestVersion) {
     // CLI args that were added in later versions of backend go here instead of above
     // since the CLI may run older versions of backend (e.g. when upgrading).
+    commandArgs.push("--enable-fixture-loading");
+    commandArgs.push("--enable-simulated-time");
     if (args.deploymentKind === "anonymous") {
       const uuid = loadUuidForAnonymousUser(ctx);
       if (uuid !== null) {
@@ -131,6 +131,8 @@ type StructuredLogLine = {
   level: "LOG" | "DEBUG" | "INFO" | "WARN" | "ERROR";
   timestamp: number;
   isTruncated: boolean;
+  // Convex JSON encoded fields of lines logged with `ctx.log`.
+  fields?: Record<string, any>;
 };
 type LogLine = string | StructuredLogLine;
 
@@ -270,7 +272,9 @@ function logToTerminal(
     }
   } else {
     const level = message.level;
-    const formattedMessage = `${message.messages.join(" ")}${message.isTruncated ? " (truncated due to length)" : ""}`;
+    const fields =
+      message.fields === undefined ? [] : [JSON.stringify(message.fields)];
+    const formattedMessage = `${[...message.messages, ...fields].join(" ")}${message.isTruncated ? " (truncated due to length)" : ""}`;
     logToDestination(
       ctx,
       dest,
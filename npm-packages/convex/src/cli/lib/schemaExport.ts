@@ -0,0 +1,42 @@
+import chalk from "chalk";
+import {
+  Context,
+  logFinishedStep,
+  logOutput,
+} from "../../bundler/context.js";
+import { deploymentFetch, logAndHandleFetchError } from "./utils/utils.js";
+
+export type SchemaExportFormat = "jsonSchema" | "openapi";
+
+export async function schemaExportForDeployment(
+  ctx: Context,
+  options: {
+    deploymentUrl: string;
+    adminKey: string;
+    format: SchemaExportFormat;
+    output: string | undefined;
+  },
+) {
+  const fetch = deploymentFetch(ctx, {
+    deploymentUrl: options.deploymentUrl,
+    adminKey: options.adminKey,
+  });
+  let exported: unknown;
+  try {
+    const response = await fetch(
+      `/api/export_schema?format=${options.format}&valueFormat=json`,
+      { method: "GET" },
+    );
+    exported = await response.json();
+  } catch (e) {
+    return await logAndHandleFetchError(ctx, e);
+  }
+
+  const output = JSON.stringify(exported, null, 2);
+  if (options.output !== undefined) {
+    ctx.fs.writeUtf8File(options.output, output);
+    logFinishedStep(ctx, `Wrote schema to ${chalk.bold(options.output)}`);
+  } else {
+    logOutput(ctx, output);
+  }
+}
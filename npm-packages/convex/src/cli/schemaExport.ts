@@ -0,0 +1,49 @@
+import { oneoffContext } from "../bundler/context.js";
+import {
+  deploymentSelectionWithinProjectFromOptions,
+  loadSelectedDeploymentCredentials,
+} from "./lib/api.js";
+import { Command, Option } from "@commander-js/extra-typings";
+import { actionDescription } from "./lib/command.js";
+import { schemaExportForDeployment } from "./lib/schemaExport.js";
+import { getDeploymentSelection } from "./lib/deploymentSelection.js";
+
+export const schemaExport = new Command("schema-export")
+  .summary("Export your deployed schema as JSON Schema or OpenAPI")
+  .description(
+    "Convert the schema deployed to your Convex deployment into JSON Schema or OpenAPI component definitions, " +
+      "so other services and languages can validate and generate types for your documents.\n\n" +
+      "  JSON Schema: `npx convex schema-export`\n" +
+      "  OpenAPI: `npx convex schema-export --format openapi`\n\n" +
+      "By default, this exports from your dev deployment.",
+  )
+  .allowExcessArguments(false)
+  .addOption(
+    new Option("--format <format>", "Output format.")
+      .choices(["json-schema", "openapi"] as const)
+      .default("json-schema" as const),
+  )
+  .addOption(
+    new Option("--output <path>", "Write to a file instead of stdout."),
+  )
+  .addDeploymentSelectionOptions(actionDescription("Export the schema of"))
+  .showHelpAfterError()
+  .action(async (options) => {
+    const ctx = await oneoffContext(options);
+    const deploymentSelection = await getDeploymentSelection(ctx, options);
+    const selectionWithinProject =
+      await deploymentSelectionWithinProjectFromOptions(ctx, options);
+    const { adminKey, url: deploymentUrl } =
+      await loadSelectedDeploymentCredentials(
+        ctx,
+        deploymentSelection,
+        selectionWithinProject,
+      );
+
+    await schemaExportForDeployment(ctx, {
+      deploymentUrl,
+      adminKey,
+      format: options.format === "openapi" ? "openapi" : "jsonSchema",
+      output: options.output,
+    });
+  });
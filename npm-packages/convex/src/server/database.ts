@@ -1,10 +1,12 @@
-import { GenericId } from "../values/index.js";
+import { GenericId, Value } from "../values/index.js";
 import {
   DocumentByName,
   GenericDataModel,
+  GenericTableInfo,
   NamedTableInfo,
   TableNamesInDataModel,
 } from "./data_model.js";
+import { ExpressionOrValue, FilterBuilder } from "./filter_builder.js";
 import { QueryInitializer } from "./query.js";
 import { SystemDataModel } from "./schema.js";
 import {
@@ -23,6 +25,25 @@ interface BaseDatabaseReader<DataModel extends GenericDataModel> {
     id: GenericId<TableName>,
   ): Promise<DocumentByName<DataModel, TableName> | null>;
 
+  /**
+   * Fetch documents the function will read later along with the next read
+   * it awaits.
+   *
+   * That read waits for the prefetched documents too, but later calls to
+   * {@link GenericDatabaseReader.get} for them don't go back to the database.
+   * This helps chains of reads that can't be awaited together with
+   * `Promise.all`, e.g. when one read decides which documents the next ones
+   * need.
+   *
+   * Prefetched documents aren't part of the function's read set, so changes
+   * to them only invalidate a query once it fetches them with `get`.
+   *
+   * @param ids - The ids of the documents to fetch, at most 1024.
+   */
+  prefetch<TableName extends TableNamesInDataModel<DataModel>>(
+    ids: GenericId<TableName>[],
+  ): void;
+
   /**
    * Begin a query for the given table name.
    *
@@ -114,6 +135,23 @@ export interface GenericDatabaseReader<DataModel extends GenericDataModel>
    * @public
    */
   system: BaseDatabaseReader<SystemDataModel>;
+
+  /**
+   * Fetch the version of a document, which increases every time the document
+   * is written.
+   *
+   * Pass it to {@link GenericDatabaseWriter.replaceIfVersion} to only replace
+   * the document if it hasn't been written since, for example when applying an
+   * update computed outside of Convex.
+   *
+   * @param id - The {@link values.GenericId} of the document.
+   * @returns - The document's version, or `null` if it doesn't exist or was
+   * written earlier in the same mutation, since it has no version until the
+   * mutation commits.
+   */
+  getVersion<TableName extends TableNamesInDataModel<DataModel>>(
+    id: GenericId<TableName>,
+  ): Promise<bigint | null>;
 }
 
 export interface GenericDatabaseReaderWithTable<
@@ -176,6 +214,33 @@ export interface GenericDatabaseWriter<DataModel extends GenericDataModel>
     value: Partial<DocumentByName<DataModel, TableName>>,
   ): Promise<void>;
 
+  /**
+   * Update nested fields of an existing document in place.
+   *
+   * Each key is a `.`-separated path to a field. Setting a path creates any
+   * missing objects along it, and setting it to `undefined` removes the field.
+   * Use {@link arrayPatch} to add or remove array elements without reading
+   * the array first.
+   *
+   * ```ts
+   * await ctx.db.deepPatch(id, {
+   *   "profile.settings.theme": "dark",
+   *   tags: arrayPatch.push("new"),
+   *   items: arrayPatch.pullWhere((q) => q.eq(q.field("done"), true)),
+   * });
+   * ```
+   *
+   * Only the top-level fields that the paths start with are checked against
+   * the schema.
+   *
+   * @param id - The {@link values.GenericId} of the document to patch.
+   * @param updates - The new value for each field path.
+   */
+  deepPatch<TableName extends TableNamesInDataModel<DataModel>>(
+    id: GenericId<TableName>,
+    updates: Record<string, DeepPatchValue>,
+  ): Promise<void>;
+
   /**
    * Replace the value of an existing document, overwriting its old value.
    *
@@ -188,6 +253,34 @@ export interface GenericDatabaseWriter<DataModel extends GenericDataModel>
     value: WithOptionalSystemFields<DocumentByName<DataModel, TableName>>,
   ): Promise<void>;
 
+  /**
+   * Replace the value of an existing document if it hasn't been written since
+   * its version was read.
+   *
+   * Pass a version from {@link GenericDatabaseReader.getVersion} to only
+   * replace the document if it's still at that version, for example when
+   * applying an update computed outside of Convex:
+   *
+   * ```ts
+   * const replaced = await ctx.db.replaceIfVersion(id, args.version, args.value);
+   * if (!replaced) {
+   *   throw new ConvexError("The document was updated concurrently.");
+   * }
+   * ```
+   *
+   * @param id - The {@link values.GenericId} of the document to replace.
+   * @param version - The version from `db.getVersion`.
+   * @param value - The new {@link GenericDocument} for the document. This value can omit the system fields,
+   * and the database will fill them in.
+   * @returns `true` if the document was replaced, or `false` if it has been
+   * written since its version was read.
+   */
+  replaceIfVersion<TableName extends TableNamesInDataModel<DataModel>>(
+    id: GenericId<TableName>,
+    version: bigint,
+    value: WithOptionalSystemFields<DocumentByName<DataModel, TableName>>,
+  ): Promise<boolean>;
+
   /**
    * Delete an existing document.
    *
@@ -251,6 +344,33 @@ export interface BaseTableWriter<
     value: Partial<DocumentByName<DataModel, TableName>>,
   ): Promise<void>;
 
+  /**
+   * Update nested fields of an existing document in place.
+   *
+   * Each key is a `.`-separated path to a field. Setting a path creates any
+   * missing objects along it, and setting it to `undefined` removes the field.
+   * Use {@link arrayPatch} to add or remove array elements without reading
+   * the array first.
+   *
+   * ```ts
+   * await ctx.db.table("tasks").deepPatch(id, {
+   *   "profile.settings.theme": "dark",
+   *   tags: arrayPatch.push("new"),
+   *   items: arrayPatch.pullWhere((q) => q.eq(q.field("done"), true)),
+   * });
+   * ```
+   *
+   * Only the top-level fields that the paths start with are checked against
+   * the schema.
+   *
+   * @param id - The {@link values.GenericId} of the document to patch.
+   * @param updates - The new value for each field path.
+   */
+  deepPatch(
+    id: GenericId<TableName>,
+    updates: Record<string, DeepPatchValue>,
+  ): Promise<void>;
+
   /**
    * Replace the value of an existing document, overwriting its old value.
    *
@@ -263,6 +383,34 @@ export interface BaseTableWriter<
     value: WithOptionalSystemFields<DocumentByName<DataModel, TableName>>,
   ): Promise<void>;
 
+  /**
+   * Replace the value of an existing document if it hasn't been written since
+   * its version was read.
+   *
+   * Pass a version from {@link GenericDatabaseReader.getVersion} to only
+   * replace the document if it's still at that version, for example when
+   * applying an update computed outside of Convex:
+   *
+   * ```ts
+   * const replaced = await ctx.db.table("tasks").replaceIfVersion(id, args.version, args.value);
+   * if (!replaced) {
+   *   throw new ConvexError("The document was updated concurrently.");
+   * }
+   * ```
+   *
+   * @param id - The {@link values.GenericId} of the document to replace.
+   * @param version - The version from `db.getVersion`.
+   * @param value - The new {@link GenericDocument} for the document. This value can omit the system fields,
+   * and the database will fill them in.
+   * @returns `true` if the document was replaced, or `false` if it has been
+   * written since its version was read.
+   */
+  replaceIfVersion(
+    id: GenericId<TableName>,
+    version: bigint,
+    value: WithOptionalSystemFields<DocumentByName<DataModel, TableName>>,
+  ): Promise<boolean>;
+
   /**
    * Delete an existing document.
    *
@@ -270,3 +418,60 @@ export interface BaseTableWriter<
    */
   delete(id: GenericId<TableName>): Promise<void>;
 }
+
+/**
+ * An update to an array field in {@link GenericDatabaseWriter.deepPatch},
+ * created with {@link arrayPatch}.
+ *
+ * @public
+ */
+export class ArrayPatch {
+  /**
+   * @internal
+   */
+  constructor(
+    readonly kind: "push" | "pull",
+    readonly values: Value[],
+    readonly predicate?: (
+      q: FilterBuilder<GenericTableInfo>,
+    ) => ExpressionOrValue<boolean>,
+  ) {}
+}
+
+/**
+ * The new value for a field path in {@link GenericDatabaseWriter.deepPatch}.
+ *
+ * @public
+ */
+export type DeepPatchValue = Value | undefined | ArrayPatch;
+
+/**
+ * Array operations for {@link GenericDatabaseWriter.deepPatch}.
+ *
+ * @public
+ */
+export const arrayPatch = {
+  /**
+   * Append values to the array, creating it if the field is unset.
+   */
+  push(...values: Value[]): ArrayPatch {
+    return new ArrayPatch("push", values);
+  },
+  /**
+   * Remove every element equal to `value` from the array.
+   */
+  pull(value: Value): ArrayPatch {
+    return new ArrayPatch("pull", [value]);
+  },
+  /**
+   * Remove the object elements of the array that match a filter. Fields in
+   * the filter refer to fields of each element.
+   */
+  pullWhere(
+    predicate: (
+      q: FilterBuilder<GenericTableInfo>,
+    ) => ExpressionOrValue<boolean>,
+  ): ArrayPatch {
+    return new ArrayPatch("pull", [], predicate);
+  },
+};
@@ -0,0 +1,23 @@
+/**
+ * An interface to read the deployment's feature flags within Convex query and
+ * mutation functions.
+ *
+ * Flags are managed from the dashboard or the deployment's admin API. A flag
+ * can be on or off for everyone, on for a percentage of users, or on for users
+ * whose identity has a claim set to one of a list of values.
+ *
+ * @public
+ */
+export interface FeatureFlags {
+  /**
+   * Check whether a feature flag is on for the current user.
+   *
+   * Queries that check a flag rerun when the flag is changed, so subscribed
+   * clients see the change without reloading.
+   *
+   * @param name - The name of the flag.
+   * @returns A promise that resolves to `true` if the flag is on for the
+   * current user, and `false` if it's off or doesn't exist.
+   */
+  get(name: string): Promise<boolean>;
+}
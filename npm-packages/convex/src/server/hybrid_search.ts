@@ -0,0 +1,106 @@
+import { Id } from "../values/value.js";
+import {
+  DocumentByInfo,
+  GenericDataModel,
+  GenericTableInfo,
+  NamedSearchIndex,
+  NamedTableInfo,
+  NamedVectorIndex,
+  SearchIndexNames,
+  TableNamesInDataModel,
+  VectorIndexNames,
+} from "./data_model.js";
+import { SearchFilter, SearchFilterBuilder } from "./search_filter_builder.js";
+import { FilterExpression, VectorFilterBuilder } from "./vector_search.js";
+
+/**
+ * How {@link GenericActionCtx.hybridSearch} combines the text and vector
+ * rankings.
+ *
+ * - `{ type: "rrf", k?: number }` scores each result by the sum of
+ *   `1 / (k + rank)` over the rankings it appears in. Only the positions of
+ *   the results matter, not their scores. `k` defaults to 60.
+ * - `{ type: "weighted", textWeight?: number, vectorWeight?: number }` scales
+ *   each search's scores to between 0 and 1 and adds them up with the given
+ *   weights, which default to 0.5.
+ *
+ * @public
+ */
+export type HybridSearchFusion =
+  | { type: "rrf"; k?: number }
+  | { type: "weighted"; textWeight?: number; vectorWeight?: number };
+
+/**
+ * An object with parameters for a hybrid search, which combines a text search
+ * and a vector search against indexes on the same table.
+ * @public
+ */
+export interface HybridSearchQuery<
+  TableInfo extends GenericTableInfo,
+  TextIndexName extends SearchIndexNames<TableInfo>,
+  VectorIndexName extends VectorIndexNames<TableInfo>,
+> {
+  /**
+   * The name of the search index to run the text search against.
+   */
+  textIndex: TextIndexName;
+  /**
+   * The text search, like `q => q.search("body", "hello").eq("channel", id)`.
+   *
+   * The `highlight`, `facets` and `score` search options aren't supported.
+   */
+  textSearch: (
+    q: SearchFilterBuilder<
+      DocumentByInfo<TableInfo>,
+      NamedSearchIndex<TableInfo, TextIndexName>
+    >,
+  ) => SearchFilter;
+  /**
+   * The name of the vector index to run the vector search against.
+   */
+  vectorIndex: VectorIndexName;
+  /**
+   * The query vector. This must have the same length as the `dimensions` of
+   * the vector index.
+   */
+  vector: number[];
+  /**
+   * Optional filter expression for the vector search, like the `filter` of
+   * {@link VectorSearchQuery}.
+   */
+  vectorFilter?: (
+    q: VectorFilterBuilder<
+      DocumentByInfo<TableInfo>,
+      NamedVectorIndex<TableInfo, VectorIndexName>
+    >,
+  ) => FilterExpression<boolean>;
+  /**
+   * The number of results to return. If specified, must be between 1 and 256
+   * inclusive.
+   *
+   * @default 10
+   */
+  limit?: number;
+  /**
+   * How to combine the two rankings. See {@link HybridSearchFusion}.
+   *
+   * @default { type: "rrf" }
+   */
+  fusion?: HybridSearchFusion;
+}
+
+export type HybridSearch<
+  DataModel extends GenericDataModel,
+  TableName extends TableNamesInDataModel<DataModel>,
+  TextIndexName extends SearchIndexNames<NamedTableInfo<DataModel, TableName>>,
+  VectorIndexName extends VectorIndexNames<
+    NamedTableInfo<DataModel, TableName>
+  >,
+> = (
+  tableName: TableName,
+  query: HybridSearchQuery<
+    NamedTableInfo<DataModel, TableName>,
+    TextIndexName,
+    VectorIndexName
+  >,
+) => Promise<Array<{ _id: Id<TableName>; _score: number }>>;
@@ -6,11 +6,17 @@ import {
 } from "../../values/index.js";
 import { performAsyncSyscall, performSyscall } from "./syscall.js";
 import {
+  ArrayPatch,
+  DeepPatchValue,
   GenericDatabaseReader,
   GenericDatabaseReaderWithTable,
   GenericDatabaseWriter,
   GenericDatabaseWriterWithTable,
 } from "../database.js";
+import {
+  filterBuilderImpl,
+  serializeExpression,
+} from "./filter_builder_impl.js";
 import { QueryInitializerImpl } from "./query_impl.js";
 import { GenericDataModel, GenericDocument } from "../data_model.js";
 import { validateArg } from "./validate.js";
@@ -36,6 +42,24 @@ async function get(id: GenericId<string>, isSystem: boolean) {
   return jsonToConvex(syscallJSON) as GenericDocument;
 }
 
+function prefetch(ids: GenericId<string>[], isSystem: boolean) {
+  validateArg(ids, 1, "prefetch", "ids");
+  if (!Array.isArray(ids) || ids.some((id) => typeof id !== "string")) {
+    throw new Error(
+      "Invalid argument `ids` for `db.prefetch`, expected an array of strings",
+    );
+  }
+  void performAsyncSyscall("1.0/prefetch", { ids, isSystem, version });
+}
+
+async function getVersion(id: GenericId<string>) {
+  validateArg(id, 1, "getVersion", "id");
+  const syscallJSON = await performAsyncSyscall("1.0/getVersion", {
+    id: convexToJson(id),
+  });
+  return jsonToConvex(syscallJSON) as bigint | null;
+}
+
 export function setupReader(): GenericDatabaseReader<GenericDataModel> {
   const reader = (
     isSystem = false,
@@ -45,6 +69,12 @@ export function setupReader(): GenericDatabaseReader<GenericDataModel> {
       get: async (id: GenericId<string>) => {
         return await get(id, isSystem);
       },
+      prefetch: (ids: GenericId<string>[]) => {
+        prefetch(ids, isSystem);
+      },
+      getVersion: async (id: GenericId<string>) => {
+        return await getVersion(id);
+      },
       query: (tableName: string) => {
         return new TableReader(tableName, isSystem).query();
       },
@@ -107,6 +137,34 @@ async function patch(id: any, value: any) {
   });
 }
 
+async function deepPatch(id: any, updates: Record<string, DeepPatchValue>) {
+  validateArg(id, 1, "deepPatch", "id");
+  validateArg(updates, 2, "deepPatch", "updates");
+  const operations = Object.entries(updates).map(([path, update]) => {
+    if (update === undefined) {
+      return { op: "unset", path };
+    }
+    if (!(update instanceof ArrayPatch)) {
+      return { op: "set", path, value: convexToJson(update) };
+    }
+    if (update.kind === "push") {
+      return { op: "push", path, values: update.values.map(convexToJson) };
+    }
+    if (update.predicate !== undefined) {
+      return {
+        op: "pull",
+        path,
+        filter: serializeExpression(update.predicate(filterBuilderImpl)),
+      };
+    }
+    return { op: "pull", path, value: convexToJson(update.values[0]) };
+  });
+  await performAsyncSyscall("1.0/deepPatch", {
+    id: convexToJson(id),
+    operations,
+  });
+}
+
 async function replace(id: any, value: any) {
   validateArg(id, 1, "replace", "id");
   validateArg(value, 2, "replace", "value");
@@ -116,6 +174,17 @@ async function replace(id: any, value: any) {
   });
 }
 
+async function replaceIfVersion(id: any, version: bigint, value: any) {
+  validateArg(id, 1, "replaceIfVersion", "id");
+  validateArg(version, 2, "replaceIfVersion", "version");
+  validateArg(value, 3, "replaceIfVersion", "value");
+  return await performAsyncSyscall("1.0/replaceIfVersion", {
+    id: convexToJson(id),
+    version: convexToJson(version),
+    value: convexToJson(value),
+  });
+}
+
 async function delete_(id: any) {
   validateArg(id, 1, "delete", "id");
   await performAsyncSyscall("1.0/remove", { id: convexToJson(id) });
@@ -126,6 +195,8 @@ export function setupWriter(): GenericDatabaseWriter<GenericDataModel> &
   const reader = setupReader();
   return {
     get: reader.get,
+    prefetch: reader.prefetch,
+    getVersion: reader.getVersion,
     query: reader.query,
     normalizeId: reader.normalizeId,
     system: reader.system as any,
@@ -135,9 +206,15 @@ export function setupWriter(): GenericDatabaseWriter<GenericDataModel> &
     patch: async (id, value) => {
       return await patch(id, value);
     },
+    deepPatch: async (id, updates) => {
+      return await deepPatch(id, updates);
+    },
     replace: async (id, value) => {
       return await replace(id, value);
     },
+    replaceIfVersion: async (id, version, value) => {
+      return await replaceIfVersion(id, version, value);
+    },
     delete: async (id) => {
       return await delete_(id);
     },
@@ -179,9 +256,15 @@ class TableWriter extends TableReader {
   async patch(id: any, value: any) {
     return patch(id, value);
   }
+  async deepPatch(id: any, updates: Record<string, DeepPatchValue>) {
+    return deepPatch(id, updates);
+  }
   async replace(id: any, value: any) {
     return replace(id, value);
   }
+  async replaceIfVersion(id: any, version: bigint, value: any) {
+    return replaceIfVersion(id, version, value);
+  }
   async delete(id: any) {
     return delete_(id);
   }
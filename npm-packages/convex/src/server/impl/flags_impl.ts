@@ -0,0 +1,12 @@
+import { FeatureFlags } from "../flags.js";
+import { performAsyncSyscall } from "./syscall.js";
+import { validateArg } from "./validate.js";
+
+export function setupFlags(): FeatureFlags {
+  return {
+    get: async (name: string) => {
+      validateArg(name, 1, "get", "name");
+      return await performAsyncSyscall("1.0/getFeatureFlag", { name });
+    },
+  };
+}
@@ -0,0 +1,34 @@
+import { convexToJson } from "../../values/index.js";
+import { LogFields, Logger } from "../logger.js";
+import { performJsSyscall } from "./syscall.js";
+
+function structuredLog(
+  level: string,
+  messageOrFields: string | LogFields,
+  fields?: LogFields,
+) {
+  const [message, logFields] =
+    typeof messageOrFields === "string"
+      ? [messageOrFields, fields ?? {}]
+      : ["", messageOrFields];
+  performJsSyscall("console/structuredLog", {
+    level,
+    message,
+    fields: convexToJson(logFields),
+  });
+}
+
+export function setupLogger(): Logger {
+  return {
+    debug: (messageOrFields: any, fields?: LogFields) =>
+      structuredLog("DEBUG", messageOrFields, fields),
+    info: (messageOrFields: any, fields?: LogFields) =>
+      structuredLog("INFO", messageOrFields, fields),
+    log: (messageOrFields: any, fields?: LogFields) =>
+      structuredLog("LOG", messageOrFields, fields),
+    warn: (messageOrFields: any, fields?: LogFields) =>
+      structuredLog("WARN", messageOrFields, fields),
+    error: (messageOrFields: any, fields?: LogFields) =>
+      structuredLog("ERROR", messageOrFields, fields),
+  };
+}
@@ -1,4 +1,4 @@
-import { QueryImpl } from "./query_impl.js";
+import { QueryImpl, QueryInitializerImpl } from "./query_impl.js";
 import { test, expect } from "vitest";
 
 // Mock to prevent
@@ -44,3 +44,31 @@ test("take throws a TypeError if passed a negative integer", async () => {
   await expect(t).rejects.toThrow(TypeError);
   await expect(t).rejects.toThrow(/must be a non-negative integer/);
 });
+
+test("estimatedCount consumes the query", async () => {
+  const query = newQuery();
+  await query.estimatedCount();
+  await expect(() => query.take(1)).rejects.toThrow(/can only be chained once/);
+});
+
+test("approxDistinct requires a field", async () => {
+  const t = () => {
+    return newQuery().approxDistinct(undefined as any);
+  };
+  await expect(t).rejects.toThrow(/Must provide arg 1 `field`/);
+});
+
+test("groupBy requires aggregates", async () => {
+  const t = () => {
+    return newQuery().groupBy([], undefined as any);
+  };
+  await expect(t).rejects.toThrow(/Must provide arg 2 `aggregates`/);
+});
+
+test("sample throws a TypeError if passed a float", async () => {
+  const t = () => {
+    return new QueryInitializerImpl("messages").sample(1.5);
+  };
+  await expect(t).rejects.toThrow(TypeError);
+  await expect(t).rejects.toThrow(/must be a non-negative integer/);
+});
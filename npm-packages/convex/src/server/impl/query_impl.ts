@@ -5,7 +5,12 @@ import {
   filterBuilderImpl,
   serializeExpression,
 } from "./filter_builder_impl.js";
-import { Query, QueryInitializer } from "../query.js";
+import {
+  GroupByAggregate,
+  GroupByResult,
+  Query,
+  QueryInitializer,
+} from "../query.js";
 import { ExpressionOrValue, FilterBuilder } from "../filter_builder.js";
 import { GenericTableInfo } from "../data_model.js";
 import {
@@ -17,10 +22,26 @@ import {
   SerializedSearchFilter,
 } from "./search_filter_builder_impl.js";
 import { validateArg, validateArgIsNonNegativeInteger } from "./validate.js";
+import {
+  SearchHighlights,
+  setSearchHighlights,
+} from "../search_highlights.js";
+import { SearchFacets, setSearchFacets } from "../search_facets.js";
 import { version } from "../../index.js";
 
 const MAX_QUERY_OPERATORS = 256;
 
+function facetsFromJson(
+  facets: Record<string, { value: JSONValue; count: number }[]>,
+): SearchFacets {
+  return Object.fromEntries(
+    Object.entries(facets).map(([field, counts]) => [
+      field,
+      counts.map(({ value, count }) => ({ value: jsonToConvex(value), count })),
+    ]),
+  );
+}
+
 type QueryOperator = { filter: JSONValue } | { limit: number };
 type Source =
   | { type: "FullTableScan"; tableName: string; order: "asc" | "desc" | null }
@@ -102,6 +123,33 @@ export class QueryInitializerImpl
     return this.fullTableScan().order(order);
   }
 
+  estimatedCount(): Promise<number> {
+    return this.fullTableScan().estimatedCount();
+  }
+
+  approxDistinct(field: string): Promise<number> {
+    return this.fullTableScan().approxDistinct(field);
+  }
+
+  groupBy(
+    fields: string[],
+    aggregates: Record<string, GroupByAggregate<GenericTableInfo>>,
+  ): Promise<GroupByResult<Record<string, any>>[]> {
+    return this.fullTableScan().groupBy(fields, aggregates);
+  }
+
+  async sample(n: number): Promise<any[]> {
+    validateArgIsNonNegativeInteger(n, 1, "sample", "n");
+    // Seeding the sample from `Math.random()` keeps it deterministic.
+    const seed = Math.floor(Math.random() * 2 ** 32);
+    const documents: JSONValue[] = await performAsyncSyscall("1.0/sample", {
+      table: this.tableName,
+      n,
+      seed,
+    });
+    return documents.map((document) => jsonToConvex(document));
+  }
+
   // This is internal API and should not be exposed to developers yet.
   async count(): Promise<number> {
     const syscallJSON = await performAsyncSyscall("1.0/count", {
@@ -139,6 +187,10 @@ export class QueryInitializerImpl
     return this.fullTableScan().first();
   }
 
+  prefetch(numItems?: number): void {
+    this.fullTableScan().prefetch(numItems);
+  }
+
   unique(): Promise<any> {
     return this.fullTableScan().unique();
   }
@@ -167,6 +219,8 @@ export class QueryImpl implements Query<GenericTableInfo> {
     | { type: "closed" }
     | { type: "consumed" };
   private tableNameForErrorMessages: string;
+  // Set by the last result of a search query with the `facets` option.
+  private facets: SearchFacets | undefined;
 
   constructor(query: SerializedQuery) {
     this.state = { type: "preparing", query };
@@ -249,6 +303,46 @@ export class QueryImpl implements Query<GenericTableInfo> {
     return new QueryImpl(query);
   }
 
+  async estimatedCount(): Promise<number> {
+    const query = this.takeQuery();
+    const syscallJSON = await performAsyncSyscall("1.0/estimatedCount", {
+      query,
+    });
+    return jsonToConvex(syscallJSON) as number;
+  }
+
+  async approxDistinct(field: string): Promise<number> {
+    validateArg(field, 1, "approxDistinct", "field");
+    const query = this.takeQuery();
+    const syscallJSON = await performAsyncSyscall("1.0/approxDistinct", {
+      query,
+      field,
+    });
+    return jsonToConvex(syscallJSON) as number;
+  }
+
+  async groupBy(
+    fields: string[],
+    aggregates: Record<string, GroupByAggregate<GenericTableInfo>>,
+  ): Promise<GroupByResult<Record<string, any>>[]> {
+    validateArg(fields, 1, "groupBy", "fields");
+    validateArg(aggregates, 2, "groupBy", "aggregates");
+    const names = Object.keys(aggregates);
+    const query = this.takeQuery();
+    const groups: { key: JSONValue[]; values: JSONValue[] }[] =
+      await performAsyncSyscall("1.0/groupBy", {
+        query,
+        groupBy: fields,
+        aggregates: names.map((name) => aggregates[name]),
+      });
+    return groups.map(({ key, values }) => ({
+      key: key.map((value) => jsonToConvex(value)),
+      values: Object.fromEntries(
+        names.map((name, i) => [name, jsonToConvex(values[i])]),
+      ),
+    }));
+  }
+
   [Symbol.asyncIterator](): AsyncIterableIterator<any> {
     this.startQuery();
     return this;
@@ -263,13 +357,20 @@ export class QueryImpl implements Query<GenericTableInfo> {
     // a `for await` statement.
     const queryId =
       this.state.type === "preparing" ? this.startQuery() : this.state.queryId;
-    const { value, done } = await performAsyncSyscall("1.0/queryStreamNext", {
-      queryId,
-    });
+    const { value, done, highlights, facets } = await performAsyncSyscall(
+      "1.0/queryStreamNext",
+      { queryId },
+    );
     if (done) {
       this.closeQuery();
     }
+    if (facets !== undefined) {
+      this.facets = facetsFromJson(facets);
+    }
     const convexValue = jsonToConvex(value);
+    if (highlights !== undefined) {
+      setSearchHighlights(convexValue, highlights);
+    }
     return { value: convexValue, done };
   }
 
@@ -278,6 +379,20 @@ export class QueryImpl implements Query<GenericTableInfo> {
     return Promise.resolve({ done: true, value: undefined });
   }
 
+  prefetch(numItems?: number): void {
+    if (numItems !== undefined) {
+      validateArgIsNonNegativeInteger(numItems, 1, "prefetch", "numItems");
+    }
+    if (this.state.type !== "preparing") {
+      throw new Error("A query can't be prefetched after iteration begins.");
+    }
+    void performAsyncSyscall("1.0/prefetch", {
+      query: this.state.query,
+      numItems,
+      version,
+    });
+  }
+
   async paginate(
     paginationOpts: PaginationOptions,
   ): Promise<PaginationResult<any>> {
@@ -295,23 +410,41 @@ export class QueryImpl implements Query<GenericTableInfo> {
     const cursor = paginationOpts.cursor;
     const endCursor = paginationOpts?.endCursor ?? null;
     const maximumRowsRead = paginationOpts.maximumRowsRead ?? null;
-    const { page, isDone, continueCursor, splitCursor, pageStatus } =
-      await performAsyncSyscall("1.0/queryPage", {
-        query,
-        cursor,
-        endCursor,
-        pageSize,
-        maximumRowsRead,
-        maximumBytesRead: paginationOpts.maximumBytesRead,
-        version,
-      });
-    return {
-      page: page.map((json: string) => jsonToConvex(json)),
+    const {
+      page,
+      isDone,
+      continueCursor,
+      splitCursor,
+      pageStatus,
+      pageHighlights,
+      facets,
+    } = await performAsyncSyscall("1.0/queryPage", {
+      query,
+      cursor,
+      endCursor,
+      pageSize,
+      maximumRowsRead,
+      maximumBytesRead: paginationOpts.maximumBytesRead,
+      version,
+    });
+    const result = {
+      page: page.map((json: string, i: number) => {
+        const convexValue = jsonToConvex(json);
+        const highlights: SearchHighlights | undefined = pageHighlights?.[i];
+        if (highlights !== undefined) {
+          setSearchHighlights(convexValue, highlights);
+        }
+        return convexValue;
+      }),
       isDone,
       continueCursor,
       splitCursor,
       pageStatus,
     };
+    if (facets !== undefined) {
+      setSearchFacets(result, facetsFromJson(facets));
+    }
+    return result;
   }
 
   async collect(): Promise<Array<any>> {
@@ -319,6 +452,9 @@ export class QueryImpl implements Query<GenericTableInfo> {
     for await (const item of this) {
       out.push(item);
     }
+    if (this.facets !== undefined) {
+      setSearchFacets(out, this.facets);
+    }
     return out;
   }
 
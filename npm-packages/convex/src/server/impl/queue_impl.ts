@@ -0,0 +1,70 @@
+import { convexToJson, jsonToConvex, Value } from "../../values/index.js";
+import { version } from "../../index.js";
+import { LeasedMessage, Queue, QueueWorker } from "../queue.js";
+import { performAsyncSyscall } from "./syscall.js";
+import { validateArg } from "./validate.js";
+
+export function setupMutationQueue(): Queue {
+  return {
+    ...setupQueueWorker("1.0/queue"),
+    enqueue: async (
+      queue: string,
+      payload: Value,
+      options?: { delayMs?: number; maxAttempts?: number },
+    ) => {
+      validateArg(queue, 1, "enqueue", "queue");
+      validateArg(payload, 2, "enqueue", "payload");
+      await performAsyncSyscall("1.0/queue/enqueue", {
+        queue,
+        payload: convexToJson(payload),
+        delayMs: options?.delayMs,
+        maxAttempts: options?.maxAttempts,
+        version,
+      });
+    },
+    redrive: async (queue: string) => {
+      validateArg(queue, 1, "redrive", "queue");
+      return await performAsyncSyscall("1.0/queue/redrive", {
+        queue,
+        version,
+      });
+    },
+  };
+}
+
+export function setupActionQueue(): QueueWorker {
+  return setupQueueWorker("1.0/actions/queue");
+}
+
+function setupQueueWorker(prefix: string): QueueWorker {
+  return {
+    lease: async (
+      queue: string,
+      options?: { maxMessages?: number; visibilityTimeoutMs?: number },
+    ): Promise<LeasedMessage[]> => {
+      validateArg(queue, 1, "lease", "queue");
+      const messages = await performAsyncSyscall(`${prefix}/lease`, {
+        queue,
+        maxMessages: options?.maxMessages,
+        visibilityTimeoutMs: options?.visibilityTimeoutMs,
+        version,
+      });
+      return messages.map((message: any) => ({
+        ...message,
+        payload: jsonToConvex(message.payload),
+      }));
+    },
+    ack: async (receipt: string) => {
+      validateArg(receipt, 1, "ack", "receipt");
+      await performAsyncSyscall(`${prefix}/ack`, { receipt, version });
+    },
+    nack: async (receipt: string, options?: { delayMs?: number }) => {
+      validateArg(receipt, 1, "nack", "receipt");
+      await performAsyncSyscall(`${prefix}/nack`, {
+        receipt,
+        delayMs: options?.delayMs,
+        version,
+      });
+    },
+  };
+}
@@ -20,11 +20,16 @@ import {
   RegisteredAction,
   RegisteredMutation,
   RegisteredQuery,
+  RunFunctionOptions,
 } from "../registration.js";
 import { setupActionCalls } from "./actions_impl.js";
 import { setupActionVectorSearch } from "./vector_search_impl.js";
+import { setupActionHybridSearch } from "./hybrid_search_impl.js";
 import { setupAuth } from "./authentication_impl.js";
 import { setupReader, setupWriter } from "./database_impl.js";
+import { setupFlags } from "./flags_impl.js";
+import { setupLogger } from "./logger_impl.js";
+import { setupActionQueue, setupMutationQueue } from "./queue_impl.js";
 import { QueryImpl, QueryInitializerImpl } from "./query_impl.js";
 import {
   setupActionScheduler,
@@ -39,6 +44,7 @@ import { parseArgs } from "../../common/index.js";
 import { performAsyncSyscall } from "./syscall.js";
 import { asObjectValidator } from "../../values/validator.js";
 import { getFunctionAddress } from "../components/paths.js";
+import type { HttpMiddleware } from "../middleware.js";
 
 async function invokeMutation<
   F extends (ctx: GenericMutationCtx<GenericDataModel>, ...args: any) => any,
@@ -51,11 +57,15 @@ async function invokeMutation<
     db: setupWriter(),
     auth: setupAuth(requestId),
     storage: setupStorageWriter(requestId),
+    flags: setupFlags(),
+    log: setupLogger(),
     scheduler: setupMutationScheduler(),
+    queue: setupMutationQueue(),
 
-    runQuery: (reference: any, args?: any) => runUdf("query", reference, args),
-    runMutation: (reference: any, args?: any) =>
-      runUdf("mutation", reference, args),
+    runQuery: (reference: any, args?: any, options?: RunFunctionOptions) =>
+      runUdf("query", reference, args, options),
+    runMutation: (reference: any, args?: any, options?: RunFunctionOptions) =>
+      runUdf("mutation", reference, args, options),
   };
   const result = await invokeFunction(func, mutationCtx, args as any);
   validateReturnValue(result);
@@ -259,7 +269,10 @@ async function invokeQuery<
     db: setupReader(),
     auth: setupAuth(requestId),
     storage: setupStorageReader(requestId),
-    runQuery: (reference: any, args?: any) => runUdf("query", reference, args),
+    flags: setupFlags(),
+    log: setupLogger(),
+    runQuery: (reference: any, args?: any, options?: RunFunctionOptions) =>
+      runUdf("query", reference, args, options),
   };
   const result = await invokeFunction(func, queryCtx, args as any);
   validateReturnValue(result);
@@ -349,8 +362,11 @@ async function invokeAction<
     ...calls,
     auth: setupAuth(requestId),
     scheduler: setupActionScheduler(requestId),
+    queue: setupActionQueue(),
+    log: setupLogger(),
     storage: setupStorageActionWriter(requestId),
     vectorSearch: setupActionVectorSearch(requestId) as any,
+    hybridSearch: setupActionHybridSearch(requestId) as any,
   };
   const result = await invokeFunction(func, ctx, args as any);
   return JSON.stringify(convexToJson(result === undefined ? null : result));
@@ -430,7 +446,7 @@ export const internalActionGeneric: ActionBuilder<any, "internal"> = ((
 
 async function invokeHttpAction<
   F extends (ctx: GenericActionCtx<GenericDataModel>, request: Request) => any,
->(func: F, request: Request) {
+>(func: F, request: Request, middleware: HttpMiddleware[]) {
   // TODO(presley): Change the function signature and propagate the requestId from Rust.
   // Ok, to mock it out for now, since http endpoints are only running in V8.
   const requestId = "";
@@ -440,9 +456,19 @@ async function invokeHttpAction<
     auth: setupAuth(requestId),
     storage: setupStorageActionWriter(requestId),
     scheduler: setupActionScheduler(requestId),
+    queue: setupActionQueue(),
+    log: setupLogger(),
     vectorSearch: setupActionVectorSearch(requestId) as any,
+    hybridSearch: setupActionHybridSearch(requestId) as any,
   };
-  return await invokeFunction(func, ctx, [request]);
+  // Each middleware's `next` runs the middleware after it, and the last one's
+  // runs the handler.
+  const handler = middleware.reduceRight(
+    (next, m) => (ctx: any, request: Request) =>
+      m(ctx, request, () => next(ctx, request)),
+    func as (ctx: any, request: Request) => Promise<Response>,
+  );
+  return await invokeFunction(handler, ctx, [request]);
 }
 
 /**
@@ -463,7 +489,8 @@ export const httpActionGeneric = (
   const q = dontCallDirectly("httpAction", func) as PublicHttpAction;
   assertNotBrowser();
   q.isHttp = true;
-  q.invokeHttpAction = (request) => invokeHttpAction(func as any, request);
+  q.invokeHttpAction = (request, middleware) =>
+    invokeHttpAction(func as any, request, middleware ?? []);
   q._handler = func;
   return q;
 };
@@ -472,11 +499,13 @@ async function runUdf(
   udfType: "query" | "mutation",
   f: any,
   args?: Record<string, Value>,
+  options?: RunFunctionOptions,
 ): Promise<any> {
   const queryArgs = parseArgs(args);
   const syscallArgs = {
     udfType,
     args: convexToJson(queryArgs),
+    validateReturns: options?.validateReturns,
     ...getFunctionAddress(f),
   };
   const result = await performAsyncSyscall("1.0/runUdf", syscallArgs);
@@ -8,13 +8,16 @@ import { validateArg } from "./validate.js";
 import { getFunctionAddress } from "../components/paths.js";
 
 export function setupMutationScheduler(): Scheduler {
-  return {
+  const scheduler = (idempotencyKey?: string) => ({
     runAfter: async (
       delayMs: number,
       functionReference: SchedulableFunctionReference,
       args?: Record<string, Value>,
     ) => {
-      const syscallArgs = runAfterSyscallArgs(delayMs, functionReference, args);
+      const syscallArgs = {
+        ...runAfterSyscallArgs(delayMs, functionReference, args),
+        idempotencyKey,
+      };
       return await performAsyncSyscall("1.0/schedule", syscallArgs);
     },
     runAt: async (
@@ -22,23 +25,29 @@ export function setupMutationScheduler(): Scheduler {
       functionReference: SchedulableFunctionReference,
       args?: Record<string, Value>,
     ) => {
-      const syscallArgs = runAtSyscallArgs(
-        ms_since_epoch_or_date,
-        functionReference,
-        args,
-      );
+      const syscallArgs = {
+        ...runAtSyscallArgs(ms_since_epoch_or_date, functionReference, args),
+        idempotencyKey,
+      };
       return await performAsyncSyscall("1.0/schedule", syscallArgs);
     },
+  });
+  return {
+    ...scheduler(),
     cancel: async (id: Id<"_scheduled_functions">) => {
       validateArg(id, 1, "cancel", "id");
       const args = { id: convexToJson(id) };
       await performAsyncSyscall("1.0/cancel_job", args);
     },
+    withIdempotencyKey: (idempotencyKey: string) => {
+      validateIdempotencyKey(idempotencyKey);
+      return scheduler(idempotencyKey);
+    },
   };
 }
 
 export function setupActionScheduler(requestId: string): Scheduler {
-  return {
+  const scheduler = (idempotencyKey?: string) => ({
     runAfter: async (
       delayMs: number,
       functionReference: SchedulableFunctionReference,
@@ -47,6 +56,7 @@ export function setupActionScheduler(requestId: string): Scheduler {
       const syscallArgs = {
         requestId,
         ...runAfterSyscallArgs(delayMs, functionReference, args),
+        idempotencyKey,
       };
       return await performAsyncSyscall("1.0/actions/schedule", syscallArgs);
     },
@@ -58,9 +68,17 @@ export function setupActionScheduler(requestId: string): Scheduler {
       const syscallArgs = {
         requestId,
         ...runAtSyscallArgs(ms_since_epoch_or_date, functionReference, args),
+        idempotencyKey,
       };
       return await performAsyncSyscall("1.0/actions/schedule", syscallArgs);
     },
+  });
+  return {
+    ...scheduler(),
+    withIdempotencyKey: (idempotencyKey: string) => {
+      validateIdempotencyKey(idempotencyKey);
+      return scheduler(idempotencyKey);
+    },
     cancel: async (id: Id<"_scheduled_functions">) => {
       validateArg(id, 1, "cancel", "id");
       const syscallArgs = { id: convexToJson(id) };
@@ -69,6 +87,12 @@ export function setupActionScheduler(requestId: string): Scheduler {
   };
 }
 
+function validateIdempotencyKey(idempotencyKey: string) {
+  if (typeof idempotencyKey !== "string" || idempotencyKey.length === 0) {
+    throw new Error("`idempotencyKey` must be a non-empty string");
+  }
+}
+
 function runAfterSyscallArgs(
   delayMs: number,
   functionReference: SchedulableFunctionReference,
@@ -1,4 +1,8 @@
-import { JSONValue, convexOrUndefinedToJson } from "../../values/value.js";
+import {
+  JSONValue,
+  convexOrUndefinedToJson,
+  convexToJson,
+} from "../../values/value.js";
 import {
   FieldTypeFromFieldPath,
   GenericDocument,
@@ -8,6 +12,7 @@ import {
   SearchFilter,
   SearchFilterBuilder,
   SearchFilterFinalizer,
+  SearchOptions,
 } from "../search_filter_builder.js";
 import { validateArg } from "./validate.js";
 
@@ -16,9 +21,14 @@ export type SerializedSearchFilter =
       type: "Search";
       fieldPath: string;
       value: string;
+      maxTypos?: number;
+      highlight?: { fragmentSize?: number; maxFragments?: number };
+      facets?: { fields: string[]; limit?: number };
+      score?: string;
+      prefixSearch?: boolean;
     }
   | {
-      type: "Eq";
+      type: "Eq" | "Gt" | "Gte" | "Lt" | "Lte";
       fieldPath: string;
       value: JSONValue;
     };
@@ -53,15 +63,62 @@ export class SearchFilterBuilderImpl
   search(
     fieldName: string,
     query: string,
+    options?: SearchOptions,
   ): SearchFilterFinalizer<GenericDocument, GenericSearchIndexConfig> {
     validateArg(fieldName, 1, "search", "fieldName");
     validateArg(query, 2, "search", "query");
+    const maxTypos = options?.maxTypos ?? 0;
+    if (maxTypos !== 0 && maxTypos !== 1 && maxTypos !== 2) {
+      throw new Error(
+        `\`maxTypos\` must be 0, 1, or 2, but received ${maxTypos}.`,
+      );
+    }
+    return this.searchWithOptions(fieldName, query, maxTypos, false, options);
+  }
+
+  prefixSearch(
+    fieldName: string,
+    query: string,
+    options?: Omit<SearchOptions, "maxTypos">,
+  ): SearchFilterFinalizer<GenericDocument, GenericSearchIndexConfig> {
+    validateArg(fieldName, 1, "prefixSearch", "fieldName");
+    validateArg(query, 2, "prefixSearch", "query");
+    return this.searchWithOptions(fieldName, query, 0, true, options);
+  }
+
+  private searchWithOptions(
+    fieldName: string,
+    query: string,
+    maxTypos: number,
+    prefixSearch: boolean,
+    options?: Omit<SearchOptions, "maxTypos">,
+  ): SearchFilterFinalizer<GenericDocument, GenericSearchIndexConfig> {
+    const highlight = options?.highlight;
+    const facets = options?.facets;
+    if (facets !== undefined && !Array.isArray(facets.fields)) {
+      throw new Error(
+        `\`facets.fields\` must be an array of field names, but received ${facets.fields}.`,
+      );
+    }
+    const score = options?.score;
+    if (score !== undefined && typeof score !== "string") {
+      throw new Error(
+        `\`score\` must be a string expression, but received ${score}.`,
+      );
+    }
     this.consume();
     return new SearchFilterBuilderImpl(
       this.filters.concat({
         type: "Search",
         fieldPath: fieldName,
         value: query,
+        ...(maxTypos > 0 ? { maxTypos } : {}),
+        ...(highlight
+          ? { highlight: highlight === true ? {} : highlight }
+          : {}),
+        ...(facets !== undefined ? { facets } : {}),
+        ...(score !== undefined ? { score } : {}),
+        ...(prefixSearch ? { prefixSearch } : {}),
       }),
     );
   }
@@ -84,6 +141,53 @@ export class SearchFilterBuilderImpl
     );
   }
 
+  gt<FieldName extends string>(
+    fieldName: FieldName,
+    value: FieldTypeFromFieldPath<GenericDocument, FieldName>,
+  ): SearchFilterFinalizer<GenericDocument, GenericSearchIndexConfig> {
+    return this.range("Gt", fieldName, value);
+  }
+  gte<FieldName extends string>(
+    fieldName: FieldName,
+    value: FieldTypeFromFieldPath<GenericDocument, FieldName>,
+  ): SearchFilterFinalizer<GenericDocument, GenericSearchIndexConfig> {
+    return this.range("Gte", fieldName, value);
+  }
+  lt<FieldName extends string>(
+    fieldName: FieldName,
+    value: FieldTypeFromFieldPath<GenericDocument, FieldName>,
+  ): SearchFilterFinalizer<GenericDocument, GenericSearchIndexConfig> {
+    return this.range("Lt", fieldName, value);
+  }
+  lte<FieldName extends string>(
+    fieldName: FieldName,
+    value: FieldTypeFromFieldPath<GenericDocument, FieldName>,
+  ): SearchFilterFinalizer<GenericDocument, GenericSearchIndexConfig> {
+    return this.range("Lte", fieldName, value);
+  }
+
+  private range(
+    type: "Gt" | "Gte" | "Lt" | "Lte",
+    fieldName: string,
+    value: unknown,
+  ): SearchFilterFinalizer<GenericDocument, GenericSearchIndexConfig> {
+    const method = type.toLowerCase();
+    validateArg(fieldName, 1, method, "fieldName");
+    if (typeof value !== "number" && typeof value !== "bigint") {
+      throw new Error(
+        `\`${method}\` can only compare numbers and bigints, but received ${typeof value}.`,
+      );
+    }
+    this.consume();
+    return new SearchFilterBuilderImpl(
+      this.filters.concat({
+        type,
+        fieldPath: fieldName,
+        value: convexToJson(value),
+      }),
+    );
+  }
+
   export() {
     this.consume();
     return this.filters;
@@ -35,6 +35,12 @@ export function setupActionVectorSearch(
     ) {
       throw Error("`vector` must be a non-empty Array in vectorSearch");
     }
+    if (
+      query.efSearch !== undefined &&
+      (!Number.isInteger(query.efSearch) || query.efSearch < 1)
+    ) {
+      throw Error("`efSearch` must be a positive integer in vectorSearch");
+    }
 
     return await new VectorQueryImpl(
       requestId,
@@ -65,8 +71,11 @@ export class VectorQueryImpl {
       query: {
         indexName,
         limit: query.limit,
+        efSearch: query.efSearch,
         vector: query.vector,
         expressions: filters,
+        includeVectors: query.includeVectors,
+        mmr: query.mmr,
       },
     };
   }
@@ -90,8 +99,11 @@ export class VectorQueryImpl {
 type SerializedVectorQuery = {
   indexName: string;
   limit?: number;
+  efSearch?: number;
   vector: Array<number>;
   expressions: JSONValue;
+  includeVectors?: boolean;
+  mmr?: { lambda?: number; fetchLimit?: number };
 };
 
 type ExpressionOrValue<T extends Value | undefined> = FilterExpression<T> | T;
@@ -143,9 +155,71 @@ export const filterBuilderImpl: VectorFilterBuilder<
     });
   },
 
+  neq<FieldName extends GenericVectorIndexConfig["filterFields"]>(
+    fieldName: FieldName,
+    value: FieldTypeFromFieldPath<GenericDocument, FieldName>,
+  ): FilterExpression<boolean> {
+    return serializeComparison("$neq", "neq", fieldName, value);
+  },
+
+  gt<FieldName extends GenericVectorIndexConfig["filterFields"]>(
+    fieldName: FieldName,
+    value: FieldTypeFromFieldPath<GenericDocument, FieldName>,
+  ): FilterExpression<boolean> {
+    return serializeComparison("$gt", "gt", fieldName, value);
+  },
+
+  gte<FieldName extends GenericVectorIndexConfig["filterFields"]>(
+    fieldName: FieldName,
+    value: FieldTypeFromFieldPath<GenericDocument, FieldName>,
+  ): FilterExpression<boolean> {
+    return serializeComparison("$gte", "gte", fieldName, value);
+  },
+
+  lt<FieldName extends GenericVectorIndexConfig["filterFields"]>(
+    fieldName: FieldName,
+    value: FieldTypeFromFieldPath<GenericDocument, FieldName>,
+  ): FilterExpression<boolean> {
+    return serializeComparison("$lt", "lt", fieldName, value);
+  },
+
+  lte<FieldName extends GenericVectorIndexConfig["filterFields"]>(
+    fieldName: FieldName,
+    value: FieldTypeFromFieldPath<GenericDocument, FieldName>,
+  ): FilterExpression<boolean> {
+    return serializeComparison("$lte", "lte", fieldName, value);
+  },
+
   //  Logic  ///////////////////////////////////////////////////////////////////
 
+  and(...exprs: Array<ExpressionOrValue<boolean>>): FilterExpression<boolean> {
+    return new ExpressionImpl({ $and: exprs.map(serializeExpression) });
+  },
+
   or(...exprs: Array<ExpressionOrValue<boolean>>): FilterExpression<boolean> {
     return new ExpressionImpl({ $or: exprs.map(serializeExpression) });
   },
+
+  not(x: ExpressionOrValue<boolean>): FilterExpression<boolean> {
+    return new ExpressionImpl({ $not: serializeExpression(x) });
+  },
 };
+
+function serializeComparison(
+  op: "$neq" | "$gt" | "$gte" | "$lt" | "$lte",
+  method: string,
+  fieldName: string,
+  value: Value | undefined,
+): FilterExpression<boolean> {
+  if (typeof fieldName !== "string") {
+    throw new Error(
+      `The first argument to \`q.${method}\` must be a field name.`,
+    );
+  }
+  return new ExpressionImpl({
+    [op]: [
+      serializeExpression(new ExpressionImpl({ $field: fieldName })),
+      serializeExpression(value),
+    ],
+  });
+}
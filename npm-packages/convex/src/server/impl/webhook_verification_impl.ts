@@ -0,0 +1,49 @@
+import * as Base64 from "../../values/base64.js";
+import type { WebhookVerification } from "../router.js";
+import { performAsyncSyscall } from "./syscall.js";
+
+type VerifyWebhookResult =
+  | { status: "verified" }
+  | { status: "replayed" }
+  | { status: "invalid"; reason: string };
+
+/**
+ * Checks the request's webhook signature, returning the response to send
+ * instead of running the route's handler, or null if the handler should run.
+ */
+export async function verifyWebhookRequest(
+  request: Request,
+  verification: WebhookVerification,
+  route: string,
+): Promise<Response | null> {
+  const secret = process.env[verification.secretEnvVar];
+  if (!secret) {
+    throw new Error(
+      `Can't verify webhooks for ${route}: environment variable ${verification.secretEnvVar} isn't set`,
+    );
+  }
+  // Read a clone so the handler can still read the body.
+  const body = new Uint8Array(await request.clone().arrayBuffer());
+  const result: VerifyWebhookResult = await performAsyncSyscall(
+    "1.0/actions/verifyWebhook",
+    {
+      scheme: verification.scheme,
+      secret,
+      route,
+      headers: [...request.headers.entries()],
+      body: Base64.fromByteArray(body),
+      toleranceSeconds: verification.toleranceSeconds,
+      replayProtection: verification.replayProtection ?? true,
+    },
+  );
+  switch (result.status) {
+    case "verified":
+      return null;
+    case "replayed":
+      return new Response("Webhook already received", { status: 200 });
+    case "invalid":
+      return new Response(`Webhook verification failed: ${result.reason}`, {
+        status: 401,
+      });
+  }
+}
@@ -56,6 +56,9 @@ export type {
   UserIdentity,
   UserIdentityAttributes,
 } from "./authentication.js";
+export type { FeatureFlags } from "./flags.js";
+export type { LogFields, Logger } from "./logger.js";
+export type { LeasedMessage, Queue, QueueWorker } from "./queue.js";
 export * from "./database.js";
 export type {
   GenericDocument,
@@ -103,7 +106,13 @@ export {
 } from "./impl/registration_impl.js";
 export type { IndexRange, IndexRangeBuilder } from "./index_range_builder.js";
 export * from "./pagination.js";
-export type { OrderedQuery, Query, QueryInitializer } from "./query.js";
+export type {
+  GroupByAggregate,
+  GroupByResult,
+  OrderedQuery,
+  Query,
+  QueryInitializer,
+} from "./query.js";
 export type {
   ArgsArray,
   DefaultFunctionArgs,
@@ -119,6 +128,7 @@ export type {
   GenericMutationCtxWithTable,
   GenericQueryCtx,
   GenericQueryCtxWithTable,
+  RunFunctionOptions,
   RegisteredAction,
   RegisteredMutation,
   RegisteredQuery,
@@ -131,6 +141,10 @@ export type {
   DefaultArgsForOptionalValidator,
 } from "./registration.js";
 export * from "./search_filter_builder.js";
+export { searchHighlights } from "./search_highlights.js";
+export type { HighlightRange, SearchHighlights } from "./search_highlights.js";
+export { searchFacets } from "./search_facets.js";
+export type { SearchFacets } from "./search_facets.js";
 export * from "./storage.js";
 export type { Scheduler, SchedulableFunctionReference } from "./scheduler.js";
 export { cronJobs } from "./cron.js";
@@ -143,13 +157,27 @@ export type {
   SystemIndexes,
   IndexTiebreakerField,
 } from "./system_fields.js";
-export { httpRouter, HttpRouter, ROUTABLE_HTTP_METHODS } from "./router.js";
+export {
+  getPathParams,
+  httpRouter,
+  HttpRouter,
+  ROUTABLE_HTTP_METHODS,
+} from "./router.js";
 export type {
+  PathParams,
   RoutableMethod,
   RouteSpec,
   RouteSpecWithPath,
   RouteSpecWithPathPrefix,
+  WebhookScheme,
+  WebhookVerification,
 } from "./router.js";
+export { cors, rateLimit, requireAuth } from "./middleware.js";
+export type {
+  CorsOptions,
+  HttpMiddleware,
+  RateLimitOptions,
+} from "./middleware.js";
 export {
   anyApi,
   getFunctionName,
@@ -192,8 +220,13 @@ export type {
 export type { Index, SearchIndex, VectorIndex } from "./schema.js";
 
 export type {
+  SearchIndexAnalyzer,
   SearchIndexConfig,
+  VectorDistanceMetric,
+  VectorEmbedderConfig,
   VectorIndexConfig,
+  VectorIndexKind,
+  VectorQuantization,
   TableDefinition,
   SchemaDefinition,
   DefineSchemaOptions,
@@ -202,7 +235,12 @@ export type {
   SystemDataModel,
   SystemTableNames,
 } from "./schema.js";
-export { defineTable, defineSchema } from "./schema.js";
+export {
+  defineTable,
+  defineSchema,
+  serverDefaults,
+  ServerDefault,
+} from "./schema.js";
 
 export type {
   VectorSearch,
@@ -211,6 +249,12 @@ export type {
   FilterExpression,
 } from "./vector_search.js";
 
+export type {
+  HybridSearch,
+  HybridSearchQuery,
+  HybridSearchFusion,
+} from "./hybrid_search.js";
+
 /**
  * @public
  */
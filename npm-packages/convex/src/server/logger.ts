@@ -0,0 +1,56 @@
+import { Value } from "../values/index.js";
+
+/**
+ * Fields attached to a structured log line. Values keep their Convex types, so
+ * an `Int64` field stays distinct from a `number`.
+ *
+ * @public
+ */
+export type LogFields = Record<string, Value>;
+
+/**
+ * A structured alternative to `console` within Convex functions.
+ *
+ * Each call produces one log line with a level, an optional message and a set
+ * of fields. Log streams receive the fields as structured data alongside the
+ * request and execution ids, and the function logs can be filtered by field
+ * value.
+ *
+ * ```js
+ * ctx.log.info("Charged customer", { customerId, amountCents: 500 });
+ * ctx.log.warn({ retries: 3 });
+ * ```
+ *
+ * @public
+ */
+export interface Logger {
+  /**
+   * Log a line at the `DEBUG` level.
+   */
+  debug(message: string, fields?: LogFields): void;
+  debug(fields: LogFields): void;
+
+  /**
+   * Log a line at the `INFO` level.
+   */
+  info(message: string, fields?: LogFields): void;
+  info(fields: LogFields): void;
+
+  /**
+   * Log a line at the `LOG` level.
+   */
+  log(message: string, fields?: LogFields): void;
+  log(fields: LogFields): void;
+
+  /**
+   * Log a line at the `WARN` level.
+   */
+  warn(message: string, fields?: LogFields): void;
+  warn(fields: LogFields): void;
+
+  /**
+   * Log a line at the `ERROR` level.
+   */
+  error(message: string, fields?: LogFields): void;
+  error(fields: LogFields): void;
+}
@@ -0,0 +1,282 @@
+import type { GenericActionCtx } from "./registration.js";
+import { ROUTABLE_HTTP_METHODS, type RoutableMethod } from "./router.js";
+import { performAsyncSyscall } from "./impl/syscall.js";
+
+/**
+ * Middleware that runs before a route's HTTP action.
+ *
+ * Middleware receives the same `ctx` and `request` as the handler, and calls
+ * `next()` to run the rest of the route: the following middleware and then the
+ * handler. It can return a response without calling `next()` to reject the
+ * request, or change the response `next()` returns.
+ *
+ * ```js
+ * const logTiming: HttpMiddleware = async (ctx, request, next) => {
+ *   const start = Date.now();
+ *   const response = await next();
+ *   console.log(`${request.method} ${request.url} took ${Date.now() - start}ms`);
+ *   return response;
+ * };
+ * ```
+ *
+ * @public
+ */
+export type HttpMiddleware = (
+  ctx: GenericActionCtx<any>,
+  request: Request,
+  next: () => Promise<Response>,
+) => Promise<Response>;
+
+/**
+ * Middleware functions that answer CORS preflight requests themselves. An `OPTIONS`
+ * request to a path without an `OPTIONS` route runs the middleware of a route
+ * on that path that includes one of these.
+ */
+const preflightMiddleware = new WeakSet<HttpMiddleware>();
+
+export function handlesPreflight(middleware: HttpMiddleware[]): boolean {
+  return middleware.some((m) => preflightMiddleware.has(m));
+}
+
+/**
+ * Options for the {@link cors} middleware.
+ *
+ * @public
+ */
+export type CorsOptions = {
+  /**
+   * Origins allowed to make requests, like `"https://example.com"`, or `"*"`
+   * to allow any origin.
+   */
+  allowedOrigins: string[] | "*";
+  /**
+   * Methods allowed in preflight requests. Defaults to all routable methods.
+   */
+  allowedMethods?: RoutableMethod[];
+  /**
+   * Request headers allowed in preflight requests. Defaults to the headers
+   * the preflight request asks for.
+   */
+  allowedHeaders?: string[];
+  /**
+   * Response headers the browser exposes to the page.
+   */
+  exposedHeaders?: string[];
+  /**
+   * Whether requests may include credentials like cookies. Defaults to false.
+   */
+  allowCredentials?: boolean;
+  /**
+   * How long browsers may cache a preflight response. Defaults to 86400.
+   */
+  maxAgeSeconds?: number;
+};
+
+/**
+ * Middleware that adds CORS headers to a route's responses and answers its
+ * preflight requests, so the route doesn't also need an `OPTIONS` handler.
+ *
+ * Put `cors` first in a route's middleware, since preflight requests don't
+ * carry credentials and would be rejected by middleware like
+ * {@link requireAuth}.
+ *
+ * ```js
+ * http.route({
+ *   path: "/items/:id",
+ *   method: "GET",
+ *   handler: getItem,
+ *   middleware: [cors({ allowedOrigins: ["https://example.com"] })],
+ * });
+ * ```
+ *
+ * @public
+ */
+export function cors(options: CorsOptions): HttpMiddleware {
+  const allowedMethods = options.allowedMethods ?? ROUTABLE_HTTP_METHODS;
+  const allowCredentials = options.allowCredentials ?? false;
+  const maxAgeSeconds = options.maxAgeSeconds ?? 86400;
+  const allowOrigin = (origin: string | null): string | null => {
+    if (origin === null) {
+      return null;
+    }
+    if (options.allowedOrigins === "*") {
+      // Browsers reject `*` on requests with credentials.
+      return allowCredentials ? origin : "*";
+    }
+    return options.allowedOrigins.includes(origin) ? origin : null;
+  };
+  const middleware: HttpMiddleware = async (_ctx, request, next) => {
+    const origin = allowOrigin(request.headers.get("Origin"));
+    const requestedMethod = request.headers.get("Access-Control-Request-Method");
+    if (request.method === "OPTIONS" && requestedMethod !== null) {
+      const headers = new Headers({ Vary: "Origin" });
+      if (origin !== null) {
+        headers.set("Access-Control-Allow-Origin", origin);
+        headers.set("Access-Control-Allow-Methods", allowedMethods.join(", "));
+        const allowedHeaders =
+          options.allowedHeaders?.join(", ") ??
+          request.headers.get("Access-Control-Request-Headers");
+        if (allowedHeaders) {
+          headers.set("Access-Control-Allow-Headers", allowedHeaders);
+        }
+        if (allowCredentials) {
+          headers.set("Access-Control-Allow-Credentials", "true");
+        }
+        headers.set("Access-Control-Max-Age", `${maxAgeSeconds}`);
+      }
+      return new Response(null, { status: 204, headers });
+    }
+    const response = await next();
+    if (origin === null) {
+      return response;
+    }
+    // Copy the response since the handler's may have immutable headers.
+    const withCors = new Response(response.body, response);
+    withCors.headers.set("Access-Control-Allow-Origin", origin);
+    withCors.headers.append("Vary", "Origin");
+    if (allowCredentials) {
+      withCors.headers.set("Access-Control-Allow-Credentials", "true");
+    }
+    if (options.exposedHeaders !== undefined) {
+      withCors.headers.set(
+        "Access-Control-Expose-Headers",
+        options.exposedHeaders.join(", "),
+      );
+    }
+    return withCors;
+  };
+  preflightMiddleware.add(middleware);
+  return middleware;
+}
+
+/**
+ * Middleware that responds 401 to requests without a valid auth token,
+ * without running the rest of the route.
+ *
+ * @public
+ */
+export function requireAuth(): HttpMiddleware {
+  return async (ctx, _request, next) => {
+    const identity = await ctx.auth.getUserIdentity();
+    if (identity === null) {
+      return new Response("Unauthenticated", {
+        status: 401,
+        headers: { "WWW-Authenticate": "Bearer" },
+      });
+    }
+    return await next();
+  };
+}
+
+/**
+ * Options for the {@link rateLimit} middleware.
+ *
+ * @public
+ */
+export type RateLimitOptions = {
+  /**
+   * How many requests each key may make per period. Requests are spread out
+   * over the period, with bursts of up to this many requests allowed.
+   */
+  requests: number;
+  /**
+   * The length of the period, in seconds.
+   */
+  periodSeconds: number;
+  /**
+   * Which bucket a request counts against. Defaults to the authenticated
+   * user's token identifier, or the client's IP address from the
+   * `X-Forwarded-For` header for unauthenticated requests.
+   */
+  key?: (
+    ctx: GenericActionCtx<any>,
+    request: Request,
+  ) => string | Promise<string>;
+};
+
+type RateLimitResult = { ok: true } | { ok: false; retryAfterMs: number };
+
+/**
+ * Middleware that responds 429 to requests over a rate limit, without running
+ * the rest of the route.
+ *
+ * Limits are tracked by the Convex backend per route, so they hold across
+ * requests without storing anything in your tables.
+ *
+ * ```js
+ * http.route({
+ *   path: "/search",
+ *   method: "GET",
+ *   handler: search,
+ *   middleware: [rateLimit({ requests: 10, periodSeconds: 60 })],
+ * });
+ * ```
+ *
+ * @public
+ */
+export function rateLimit(options: RateLimitOptions): HttpMiddleware {
+  if (!Number.isInteger(options.requests) || options.requests < 1) {
+    throw new Error(
+      `rateLimit requests must be a positive integer, not ${options.requests}`,
+    );
+  }
+  if (!(options.periodSeconds > 0 && Number.isFinite(options.periodSeconds))) {
+    throw new Error(
+      `rateLimit periodSeconds must be a positive number, not ${options.periodSeconds}`,
+    );
+  }
+  const key = options.key ?? defaultRateLimitKey;
+  return async (ctx, request, next) => {
+    const result: RateLimitResult = await performAsyncSyscall(
+      "1.0/actions/rateLimit",
+      {
+        route: routeOf(request),
+        key: await key(ctx, request),
+        requests: options.requests,
+        periodSeconds: options.periodSeconds,
+      },
+    );
+    if (!result.ok) {
+      return new Response("Too many requests", {
+        status: 429,
+        headers: {
+          "Retry-After": `${Math.ceil(result.retryAfterMs / 1000)}`,
+        },
+      });
+    }
+    return await next();
+  };
+}
+
+async function defaultRateLimitKey(
+  ctx: GenericActionCtx<any>,
+  request: Request,
+): Promise<string> {
+  const identity = await ctx.auth.getUserIdentity();
+  if (identity !== null) {
+    return `user:${identity.tokenIdentifier}`;
+  }
+  const forwardedFor = request.headers.get("X-Forwarded-For");
+  const ip = forwardedFor?.split(",")[0].trim();
+  return `ip:${ip || "unknown"}`;
+}
+
+/**
+ * The route each request was routed to, as `"METHOD path"`. Rate limits are
+ * tracked per route.
+ */
+const requestRoutes = new WeakMap<Request, string>();
+
+export function setRequestRoute(request: Request, route: string) {
+  requestRoutes.set(request, route);
+}
+
+function routeOf(request: Request): string {
+  const route = requestRoutes.get(request);
+  if (route === undefined) {
+    throw new Error(
+      "Middleware must be called with the Request passed to the route",
+    );
+  }
+  return route;
+}
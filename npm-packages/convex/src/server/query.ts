@@ -1,11 +1,13 @@
 import {
   DocumentByInfo,
+  FieldPaths,
   GenericTableInfo,
   IndexNames,
   NamedIndex,
   NamedSearchIndex,
   SearchIndexNames,
 } from "./data_model.js";
+import { Value } from "../values/index.js";
 import { ExpressionOrValue, FilterBuilder } from "./filter_builder.js";
 import { IndexRange, IndexRangeBuilder } from "./index_range_builder.js";
 import { PaginationResult, PaginationOptions } from "./pagination.js";
@@ -76,6 +78,12 @@ export interface QueryInitializer<TableInfo extends GenericTableInfo>
    * Documents will be returned in relevance order based on how well they
    * match the search text.
    *
+   * Reactive queries that search only rerun when a write adds, changes, or
+   * removes a document matching one of the search's words (including with
+   * typos or as a prefix, when the search allows them) and all of its
+   * filters. Writes to other documents can shift relevance scores slightly,
+   * but don't rerun the query.
+   *
    * To learn about full text search, see [Indexes](https://docs.convex.dev/text-search).
    *
    * @param indexName - The name of the search index to query.
@@ -95,6 +103,22 @@ export interface QueryInitializer<TableInfo extends GenericTableInfo>
     ) => SearchFilter,
   ): OrderedQuery<TableInfo>;
 
+  /**
+   * Select up to `n` documents from the table at random.
+   *
+   * Documents are drawn by picking random points in the table's ID space, so
+   * this reads about `n` documents no matter how large the table is. Useful
+   * for previews and spot checks where reading the whole table would be too
+   * expensive. Tables with at most `n` documents are returned in full.
+   *
+   * The sample is drawn from `Math.random()`, so rerunning a function with
+   * the same random seed returns the same sample.
+   *
+   * @param n - The number of documents to select, at most 1024.
+   * @returns - Up to `n` distinct documents, in no particular order.
+   */
+  sample(n: number): Promise<DocumentByInfo<TableInfo>[]>;
+
   /**
    * The number of documents in the table.
    *
@@ -147,8 +171,96 @@ export interface Query<TableInfo extends GenericTableInfo>
    * @param order - The order to return results in.
    */
   order(order: "asc" | "desc"): OrderedQuery<TableInfo>;
+
+  /**
+   * Estimate how many documents the query would return, without reading them.
+   *
+   * Full table scans return the table's exact document count. Queries using
+   * {@link QueryInitializer.withIndex} with an index range scale it by the
+   * fraction of the index the range covers, which is estimated from
+   * statistics computed in the background, so it can lag behind recent
+   * writes.
+   *
+   * This can't be called after {@link OrderedQuery.filter}.
+   *
+   * @returns - The estimated number of documents.
+   */
+  estimatedCount(): Promise<number>;
+
+  /**
+   * Estimate how many distinct values one of the query's index fields has
+   * across the whole index, without reading its documents.
+   *
+   * The query must use {@link QueryInitializer.withIndex} without an index
+   * range. The estimate comes from a sketch computed in the background, and
+   * is typically within 2% of the number of distinct values when the sketch
+   * was computed.
+   *
+   * @param field - The indexed field to count the distinct values of.
+   * @returns - The estimated number of distinct values.
+   */
+  approxDistinct(field: FieldPaths<TableInfo>): Promise<number>;
+
+  /**
+   * Group the query's documents by a prefix of its index's fields and
+   * compute aggregates over each group, in a single scan of the index.
+   *
+   * This avoids loading every document into the function just to summarize
+   * it. Documents scanned don't count towards the function's read limits;
+   * each group returned counts as one document read instead.
+   *
+   * The query must use {@link QueryInitializer.withIndex}, or be a full table
+   * scan grouped by no fields, and can't be called after
+   * {@link OrderedQuery.filter}. Groups are returned in index order.
+   *
+   * ```typescript
+   * const totals = await db
+   *   .query("orders")
+   *   .withIndex("by_customer")
+   *   .groupBy(["customer"], {
+   *     orders: { op: "count" },
+   *     spent: { op: "sum", field: "amount" },
+   *   });
+   * ```
+   *
+   * @param fields - The fields to group by, which must be the first fields
+   * of the index in order.
+   * @param aggregates - The aggregates to compute for each group, by name.
+   * @returns - One result per group, with the group's values for `fields`
+   * and its aggregates. Missing fields and aggregates with no values are
+   * `null`.
+   */
+  groupBy<Aggregates extends Record<string, GroupByAggregate<TableInfo>>>(
+    fields: FieldPaths<TableInfo>[],
+    aggregates: Aggregates,
+  ): Promise<GroupByResult<Aggregates>[]>;
 }
 
+/**
+ * An aggregate computed for each group by {@link Query.groupBy}.
+ *
+ * `count` counts the group's documents. `sum` adds up a field's numeric
+ * values, and `min` and `max` find its smallest and largest values in Convex's
+ * value ordering, skipping documents where the field is missing.
+ *
+ * @public
+ */
+export type GroupByAggregate<TableInfo extends GenericTableInfo> =
+  | { op: "count" }
+  | { op: "sum" | "min" | "max"; field: FieldPaths<TableInfo> };
+
+/**
+ * One group returned by {@link Query.groupBy}.
+ *
+ * @public
+ */
+export type GroupByResult<Aggregates extends Record<string, any>> = {
+  /** The group's values for the grouped fields, in order. */
+  key: Value[];
+  /** The group's value for each aggregate. */
+  values: { [Name in keyof Aggregates]: Value };
+};
+
 /**
  * A {@link Query} with an order that has already been defined.
  *
@@ -221,6 +333,20 @@ export interface OrderedQuery<TableInfo extends GenericTableInfo>
    * */
   first(): Promise<DocumentByInfo<TableInfo> | null>;
 
+  /**
+   * Fetch the query's first results along with the next read the function
+   * awaits.
+   *
+   * That read waits for the results too, but running the query later doesn't
+   * go back to the database for them. This doesn't run the query: it can
+   * still be chained and run as usual. Prefetched results aren't part of the
+   * function's read set until the query returns them.
+   *
+   * @param numItems - How many results to fetch. Defaults to the size of the
+   * first page the query would fetch anyway.
+   */
+  prefetch(numItems?: number): void;
+
   /**
    * Execute the query and return the singular result if there is one.
    *
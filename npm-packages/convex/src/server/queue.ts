@@ -0,0 +1,107 @@
+import { Value } from "../values/value.js";
+
+/**
+ * A message handed to a worker by {@link QueueWorker.lease}.
+ *
+ * @public
+ */
+export interface LeasedMessage {
+  /**
+   * Opaque handle to pass to {@link QueueWorker.ack} or
+   * {@link QueueWorker.nack}.
+   */
+  receipt: string;
+  /**
+   * The payload the message was enqueued with.
+   */
+  payload: Value;
+  /**
+   * How many times the message has been leased, including this time.
+   */
+  attempts: number;
+}
+
+/**
+ * An interface to consume messages from the deployment's work queues, available
+ * in mutations and actions.
+ *
+ * A leased message is hidden from other workers until its visibility timeout
+ * passes. Ack it once it's processed, or nack it to make it visible again.
+ * Messages that aren't acked in time are redelivered, and a message that has
+ * been leased `maxAttempts` times without being acked is moved to the queue's
+ * dead letter queue.
+ *
+ * @public
+ */
+export interface QueueWorker {
+  /**
+   * Lease the oldest visible messages on a queue.
+   *
+   * @param queue - The name of the queue.
+   * @param options.maxMessages - The most messages to lease, between 1 and
+   * 100. Defaults to 1.
+   * @param options.visibilityTimeoutMs - How long the messages are hidden
+   * from other workers. Defaults to 30 seconds, and can be at most 12 hours.
+   * @returns A promise that resolves to the leased messages, which is empty if
+   * no messages are visible.
+   */
+  lease(
+    queue: string,
+    options?: { maxMessages?: number; visibilityTimeoutMs?: number },
+  ): Promise<LeasedMessage[]>;
+
+  /**
+   * Remove a leased message from its queue.
+   *
+   * Throws if the lease has expired and the message was leased again.
+   *
+   * @param receipt - The message's {@link LeasedMessage.receipt}.
+   */
+  ack(receipt: string): Promise<void>;
+
+  /**
+   * Return a leased message to its queue.
+   *
+   * @param receipt - The message's {@link LeasedMessage.receipt}.
+   * @param options.delayMs - How long to wait before the message is visible
+   * again. Defaults to 0.
+   */
+  nack(receipt: string, options?: { delayMs?: number }): Promise<void>;
+}
+
+/**
+ * An interface to the deployment's work queues within Convex mutations.
+ *
+ * Queues are created by enqueuing a message on them. Enqueuing is part of the
+ * mutation's transaction, so the message is only visible to workers if the
+ * mutation commits.
+ *
+ * @public
+ */
+export interface Queue extends QueueWorker {
+  /**
+   * Add a message to a queue.
+   *
+   * @param queue - The name of the queue.
+   * @param payload - The message, which can be any Convex value.
+   * @param options.delayMs - How long to wait before the message is visible
+   * to workers. Defaults to 0.
+   * @param options.maxAttempts - How many times the message can be leased
+   * before it's moved to the dead letter queue, between 1 and 100. Defaults
+   * to 5.
+   */
+  enqueue(
+    queue: string,
+    payload: Value,
+    options?: { delayMs?: number; maxAttempts?: number },
+  ): Promise<void>;
+
+  /**
+   * Move a queue's dead lettered messages back onto the queue, with their
+   * attempts reset.
+   *
+   * @param queue - The name of the queue.
+   * @returns A promise that resolves to the number of messages moved.
+   */
+  redrive(queue: string): Promise<number>;
+}
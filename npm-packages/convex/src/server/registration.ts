@@ -9,6 +9,7 @@ import {
   StorageWriter,
 } from "./index.js";
 import {
+  ArgsAndOptions,
   FunctionReference,
   FunctionReturnType,
   OptionalRestArgs,
@@ -24,14 +25,36 @@ import { Id } from "../values/value.js";
 import {
   GenericDataModel,
   NamedTableInfo,
+  SearchIndexNames,
   TableNamesInDataModel,
   VectorIndexNames,
 } from "./data_model.js";
 import { Scheduler } from "./scheduler.js";
+import { FeatureFlags } from "./flags.js";
+import { Logger } from "./logger.js";
+import { Queue, QueueWorker } from "./queue.js";
 import { VectorSearchQuery } from "./vector_search.js";
+import { HybridSearchQuery } from "./hybrid_search.js";
+import type { HttpMiddleware } from "./middleware.js";
 import { Expand } from "../type_utils.js";
 import { Validator } from "../values/validators.js";
 
+/**
+ * Options for calling a query or mutation with `ctx.runQuery` or
+ * `ctx.runMutation` from within a query or mutation.
+ *
+ * @public
+ */
+export type RunFunctionOptions = {
+  /**
+   * Whether to check the called function's return value against its
+   * `returns` validator. Its arguments are always validated.
+   *
+   * Defaults to `true`.
+   */
+  validateReturns?: boolean;
+};
+
 /**
  * A set of services for use within Convex mutation functions.
  *
@@ -59,21 +82,37 @@ export interface GenericMutationCtx<DataModel extends GenericDataModel> {
    */
   storage: StorageWriter;
 
+  /**
+   * The deployment's feature flags.
+   */
+  flags: FeatureFlags;
+
+  /**
+   * A utility for writing structured log lines.
+   */
+  log: Logger;
+
   /**
    * A utility for scheduling Convex functions to run in the future.
    */
   scheduler: Scheduler;
 
+  /**
+   * A utility for enqueuing and consuming messages on work queues.
+   */
+  queue: Queue;
+
   /**
    * Call a query function within the same transaction.
    *
    * NOTE: often you can call the query's function directly instead of using this.
    * `runQuery` incurs overhead of running argument and return value validation,
-   * and creating a new isolated JS context.
+   * and creating a new isolated JS context. Pass `{ validateReturns: false }`
+   * as the last argument to skip the return value validation.
    */
   runQuery: <Query extends FunctionReference<"query", "public" | "internal">>(
     query: Query,
-    ...args: OptionalRestArgs<Query>
+    ...args: ArgsAndOptions<Query, RunFunctionOptions>
   ) => Promise<FunctionReturnType<Query>>;
 
   /**
@@ -81,7 +120,8 @@ export interface GenericMutationCtx<DataModel extends GenericDataModel> {
    *
    * NOTE: often you can call the mutation's function directly instead of using this.
    * `runMutation` incurs overhead of running argument and return value validation,
-   * and creating a new isolated JS context.
+   * and creating a new isolated JS context. Pass `{ validateReturns: false }`
+   * as the last argument to skip the return value validation.
    *
    * The mutation runs in a sub-transaction, so if the mutation throws an error,
    * all of its writes will be rolled back. Additionally, a successful mutation's
@@ -91,7 +131,7 @@ export interface GenericMutationCtx<DataModel extends GenericDataModel> {
     Mutation extends FunctionReference<"mutation", "public" | "internal">,
   >(
     mutation: Mutation,
-    ...args: OptionalRestArgs<Mutation>
+    ...args: ArgsAndOptions<Mutation, RunFunctionOptions>
   ) => Promise<FunctionReturnType<Mutation>>;
 }
 
@@ -139,16 +179,28 @@ export interface GenericQueryCtx<DataModel extends GenericDataModel> {
    */
   storage: StorageReader;
 
+  /**
+   * The deployment's feature flags. Queries that check a flag rerun when it
+   * changes.
+   */
+  flags: FeatureFlags;
+
+  /**
+   * A utility for writing structured log lines.
+   */
+  log: Logger;
+
   /**
    * Call a query function within the same transaction.
    *
    * NOTE: often you can call the query's function directly instead of using this.
    * `runQuery` incurs overhead of running argument and return value validation,
-   * and creating a new isolated JS context.
+   * and creating a new isolated JS context. Pass `{ validateReturns: false }`
+   * as the last argument to skip the return value validation.
    */
   runQuery: <Query extends FunctionReference<"query", "public" | "internal">>(
     query: Query,
-    ...args: OptionalRestArgs<Query>
+    ...args: ArgsAndOptions<Query, RunFunctionOptions>
   ) => Promise<FunctionReturnType<Query>>;
 }
 
@@ -235,6 +287,16 @@ export interface GenericActionCtx<DataModel extends GenericDataModel> {
    */
   scheduler: Scheduler;
 
+  /**
+   * A utility for consuming messages from work queues.
+   */
+  queue: QueueWorker;
+
+  /**
+   * A utility for writing structured log lines.
+   */
+  log: Logger;
+
   /**
    * Information about the currently authenticated user.
    */
@@ -265,6 +327,36 @@ export interface GenericActionCtx<DataModel extends GenericDataModel> {
       VectorSearchQuery<NamedTableInfo<DataModel, TableName>, IndexName>
     >,
   ): Promise<Array<{ _id: Id<TableName>; _score: number }>>;
+
+  /**
+   * Run a text search and a vector search on the given table, and combine
+   * their rankings into one list of results.
+   *
+   * Both searches read the table at the same point in time, and each
+   * document appears at most once in the results. Each search contributes its
+   * 256 best matches to the combined ranking.
+   *
+   * @param tableName - The name of the table to query.
+   * @param query - A {@link HybridSearchQuery} with the text search, the
+   * vector to query, the number of results to return, and how to combine the
+   * rankings.
+   * @returns A promise of IDs and combined scores for the best matching
+   * documents, best first.
+   */
+  hybridSearch<
+    TableName extends TableNamesInDataModel<DataModel>,
+    TextIndexName extends SearchIndexNames<NamedTableInfo<DataModel, TableName>>,
+    VectorIndexName extends VectorIndexNames<
+      NamedTableInfo<DataModel, TableName>
+    >,
+  >(
+    tableName: TableName,
+    query: HybridSearchQuery<
+      NamedTableInfo<DataModel, TableName>,
+      TextIndexName,
+      VectorIndexName
+    >,
+  ): Promise<Array<{ _id: Id<TableName>; _score: number }>>;
 }
 
 /**
@@ -435,7 +527,10 @@ export type PublicHttpAction = {
   isHttp: true;
 
   /** @internal */
-  invokeHttpAction(request: Request): Promise<Response>;
+  invokeHttpAction(
+    request: Request,
+    middleware?: HttpMiddleware[],
+  ): Promise<Response>;
   /** @internal */
   _handler: (ctx: GenericActionCtx<any>, request: Request) => Promise<Response>;
 };
@@ -1,5 +1,6 @@
 import { httpActionGeneric } from "./impl/registration_impl.js";
 import { HttpActionBuilder } from "./registration.js";
+import { cors } from "./middleware.js";
 import { httpRouter } from "./router.js";
 import { expect, test } from "vitest";
 
@@ -133,3 +134,148 @@ test("HttpRouter pathPrefix", () => {
   // Not shadowed: last path segment is different
   http.route({ pathPrefix: "/path11/", method: "GET", handler: action1 });
 });
+
+test("HttpRouter records webhook verification per route", () => {
+  const http = httpRouter();
+  http.route({
+    path: "/stripe",
+    method: "POST",
+    handler: action1,
+    verify: { scheme: "stripe", secretEnvVar: "STRIPE_WEBHOOK_SECRET" },
+  });
+  http.route({
+    pathPrefix: "/github/",
+    method: "POST",
+    handler: action2,
+    verify: {
+      scheme: "github",
+      secretEnvVar: "GITHUB_WEBHOOK_SECRET",
+      replayProtection: false,
+    },
+  });
+  http.route({ path: "/open", method: "POST", handler: action3 });
+
+  expect(http.verifications.get("POST /stripe")?.scheme).toBe("stripe");
+  expect(http.verifications.get("POST /github/*")?.replayProtection).toBe(
+    false,
+  );
+  expect(http.verifications.has("POST /open")).toBe(false);
+
+  expect(() => {
+    http.route({
+      path: "/made-up",
+      method: "POST",
+      handler: action4,
+      // @ts-expect-error  // not a valid scheme
+      verify: { scheme: "made-up", secretEnvVar: "SECRET" },
+    });
+  }).toThrow();
+  expect(() => {
+    http.route({
+      path: "/no-secret",
+      method: "POST",
+      handler: action4,
+      verify: { scheme: "svix", secretEnvVar: "" },
+    });
+  }).toThrow();
+  expect(() => {
+    http.route({
+      path: "/negative",
+      method: "POST",
+      handler: action4,
+      verify: { scheme: "svix", secretEnvVar: "SECRET", toleranceSeconds: -1 },
+    });
+  }).toThrow();
+});
+
+test("HttpRouter path parameters", () => {
+  const http = httpRouter();
+  http.route({ path: "/items/:id", method: "GET", handler: action1 });
+  http.route({ path: "/items/new", method: "GET", handler: action2 });
+  http.route({
+    path: "/items/:id/:field",
+    method: "GET",
+    handler: action3,
+  });
+  http.route({ pathPrefix: "/orgs/:org/", method: "GET", handler: action4 });
+  http.route({ pathPrefix: "/orgs/", method: "GET", handler: action1 });
+
+  // Exact paths win over paths with parameters.
+  expect(http.lookup("/items/new", "GET")).toEqual([
+    action2,
+    "GET",
+    "/items/new",
+  ]);
+  expect(http.lookup("/items/abc", "GET")).toEqual([
+    action1,
+    "GET",
+    "/items/:id",
+  ]);
+  expect(http.lookup("/items/abc/name", "GET")).toEqual([
+    action3,
+    "GET",
+    "/items/:id/:field",
+  ]);
+  // Parameters don't match empty segments.
+  expect(http.lookup("/items/", "GET")).toEqual(null);
+
+  // Prefixes with more segments win.
+  expect(http.lookup("/orgs/acme/files/a", "GET")).toEqual([
+    action4,
+    "GET",
+    "/orgs/:org/*",
+  ]);
+  expect(http.lookup("/orgs/acme", "GET")).toEqual([
+    action1,
+    "GET",
+    "/orgs/*",
+  ]);
+
+  expect(http.getRoutes()).toEqual([
+    ["/items/:id", "GET", action1],
+    ["/items/:id/:field", "GET", action3],
+    ["/items/new", "GET", action2],
+    ["/orgs/*", "GET", action1],
+    ["/orgs/:org/*", "GET", action4],
+  ]);
+
+  // Routes that only differ in parameter names conflict.
+  expect(() => {
+    http.route({ path: "/items/:itemId", method: "GET", handler: action1 });
+  }).toThrow();
+  expect(() => {
+    http.route({ path: "/bad/:a-b", method: "GET", handler: action1 });
+  }).toThrow();
+  expect(() => {
+    http.route({ path: "/twice/:a/:a", method: "GET", handler: action1 });
+  }).toThrow();
+});
+
+test("HttpRouter routes preflight requests to routes with cors", () => {
+  const http = httpRouter();
+  http.route({
+    path: "/items/:id",
+    method: "PUT",
+    handler: action1,
+    middleware: [cors({ allowedOrigins: "*" })],
+  });
+  http.route({ path: "/private", method: "PUT", handler: action2 });
+
+  expect(http.middleware.get("PUT /items/:id")).toHaveLength(1);
+  expect(http.lookup("/items/abc", "OPTIONS")).toEqual([
+    action1,
+    "OPTIONS",
+    "/items/:id",
+  ]);
+  expect(http.lookup("/private", "OPTIONS")).toEqual(null);
+
+  expect(() => {
+    http.route({
+      path: "/bad",
+      method: "GET",
+      handler: action1,
+      // @ts-expect-error  // not an array of middleware
+      middleware: cors({ allowedOrigins: "*" }),
+    });
+  }).toThrow();
+});
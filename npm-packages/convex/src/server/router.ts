@@ -1,4 +1,10 @@
 import { performJsSyscall } from "./impl/syscall.js";
+import { verifyWebhookRequest } from "./impl/webhook_verification_impl.js";
+import {
+  handlesPreflight,
+  HttpMiddleware,
+  setRequestRoute,
+} from "./middleware.js";
 import { PublicHttpAction } from "./registration.js";
 
 // Note: this list is duplicated in the dashboard.
@@ -46,6 +52,64 @@ export function normalizeMethod(
  */
 export const httpRouter = () => new HttpRouter();
 
+/**
+ * The webhook signature schemes HTTP action routes can verify.
+ *
+ * - `"stripe"`: the `Stripe-Signature` header.
+ * - `"github"`: the `X-Hub-Signature-256` header.
+ * - `"svix"`: the `svix-*` (or `webhook-*`) headers from Standard Webhooks
+ *   senders like Svix, Clerk and Resend, and Convex's own webhooks.
+ *
+ * @public
+ */
+export type WebhookScheme = "stripe" | "github" | "svix";
+
+const WEBHOOK_SCHEMES: readonly WebhookScheme[] = ["stripe", "github", "svix"];
+
+/**
+ * Verifies that requests to a route were signed by a webhook sender before
+ * running its handler. Requests with a missing or invalid signature get a 401
+ * response without running the handler.
+ *
+ * @public
+ */
+export type WebhookVerification = {
+  /**
+   * How the sender signs its requests.
+   */
+  scheme: WebhookScheme;
+  /**
+   * The name of the environment variable holding the signing secret, e.g.
+   * `"STRIPE_WEBHOOK_SECRET"`.
+   */
+  secretEnvVar: string;
+  /**
+   * How many seconds a signed timestamp may be from the current time.
+   * Defaults to 300. GitHub doesn't sign a timestamp, so this has no effect
+   * for `"github"`.
+   */
+  toleranceSeconds?: number;
+  /**
+   * Whether to accept each delivery only once. Repeats of a delivery that was
+   * already accepted get a 200 response without running the handler, so
+   * senders stop retrying them. Defaults to true.
+   */
+  replayProtection?: boolean;
+};
+
+/**
+ * The path parameters of a route path like `"/items/:id"`, as an object like
+ * `{ id: string }`.
+ *
+ * @public
+ */
+export type PathParams<Path extends string> =
+  Path extends `${string}:${infer Param}/${infer Rest}`
+    ? { [K in Param]: string } & PathParams<`/${Rest}`>
+    : Path extends `${string}:${infer Param}`
+      ? { [K in Param]: string }
+      : Record<never, string>;
+
 /**
  * A type representing a route to an HTTP action using an exact request URL path match.
  *
@@ -55,7 +119,9 @@ export const httpRouter = () => new HttpRouter();
  */
 export type RouteSpecWithPath = {
   /**
-   * Exact HTTP request path to route.
+   * HTTP request path to route. Segments starting with `:`, like `:id` in
+   * `"/items/:id"`, match any non-empty segment and are available to the
+   * handler through {@link getPathParams}.
    */
   path: string;
   /**
@@ -66,6 +132,14 @@ export type RouteSpecWithPath = {
    * The HTTP action to execute.
    */
   handler: PublicHttpAction;
+  /**
+   * Verify webhook signatures before running the handler.
+   */
+  verify?: WebhookVerification;
+  /**
+   * Middleware to run before the handler, in order.
+   */
+  middleware?: HttpMiddleware[];
 };
 
 /**
@@ -78,7 +152,8 @@ export type RouteSpecWithPath = {
 export type RouteSpecWithPathPrefix = {
   /**
    * An HTTP request path prefix to route. Requests with a path starting with this value
-   * will be routed to the HTTP action.
+   * will be routed to the HTTP action. Like `path`, it may contain `:param`
+   * segments.
    */
   pathPrefix: string;
   /**
@@ -89,6 +164,14 @@ export type RouteSpecWithPathPrefix = {
    * The HTTP action to execute.
    */
   handler: PublicHttpAction;
+  /**
+   * Verify webhook signatures before running the handler.
+   */
+  verify?: WebhookVerification;
+  /**
+   * Middleware to run before the handler, in order.
+   */
+  middleware?: HttpMiddleware[];
 };
 
 /**
@@ -142,6 +225,19 @@ export type RouteSpec = RouteSpecWithPath | RouteSpecWithPathPrefix;
 export class HttpRouter {
   exactRoutes: Map<string, Map<RoutableMethod, PublicHttpAction>> = new Map();
   prefixRoutes: Map<RoutableMethod, Map<string, PublicHttpAction>> = new Map();
+  /**
+   * Webhook verification for routes that have it, keyed by `"METHOD path"`
+   * with the path as returned by `lookup`.
+   */
+  verifications: Map<string, WebhookVerification> = new Map();
+  /**
+   * Routes whose path or path prefix has `:param` segments.
+   */
+  patternRoutes: Map<RoutableMethod, PatternRoute[]> = new Map();
+  /**
+   * Middleware for routes that have it, keyed like `verifications`.
+   */
+  middleware: Map<string, HttpMiddleware[]> = new Map();
   isRouter: true = true;
 
   /**
@@ -149,6 +245,13 @@ export class HttpRouter {
    * for an HTTP method (e.g. "GET") and a path or pathPrefix.
    *
    * Paths must begin with a slash. Path prefixes must also end in a slash.
+   * Either may contain `:param` segments, which match any non-empty path
+   * segment.
+   *
+   * When several routes match a request, the most specific one runs: an
+   * exact path without parameters, then paths with parameters, then path
+   * prefixes with the most segments. Between otherwise equal routes, the one
+   * with a literal segment where the other has a parameter wins.
    *
    * ```js
    * // matches `/profile` (but not `/profile/`)
@@ -156,6 +259,25 @@ export class HttpRouter {
    *
    * // matches `/profiles/`, `/profiles/abc`, and `/profiles/a/c/b` (but not `/profile`)
    * http.route({ pathPrefix: "/profile/", method: "GET", handler: getProfile})
+   *
+   * // matches `/items/abc`, with `getPathParams(request).id` set to "abc"
+   * http.route({ path: "/items/:id", method: "GET", handler: getItem})
+   *
+   * // runs `requireAuth` and then `rateLimit` before `createItem`
+   * http.route({
+   *   path: "/items",
+   *   method: "POST",
+   *   handler: createItem,
+   *   middleware: [requireAuth(), rateLimit({ requests: 10, periodSeconds: 60 })],
+   * })
+   *
+   * // only runs `handleStripe` for requests signed by Stripe
+   * http.route({
+   *   path: "/stripe",
+   *   method: "POST",
+   *   handler: handleStripe,
+   *   verify: { scheme: "stripe", secretEnvVar: "STRIPE_WEBHOOK_SECRET" },
+   * })
    * ```
    */
   route = (spec: RouteSpec) => {
@@ -167,7 +289,20 @@ export class HttpRouter {
         `'${method}' is not an allowed HTTP method (like GET, POST, PUT etc.)`,
       );
     }
+    if (spec.verify !== undefined) {
+      validateWebhookVerification(spec.verify);
+    }
+    if (
+      spec.middleware !== undefined &&
+      !(
+        Array.isArray(spec.middleware) &&
+        spec.middleware.every((m) => typeof m === "function")
+      )
+    ) {
+      throw new Error(`route middleware must be an array of functions`);
+    }
 
+    let route: string;
     if ("path" in spec) {
       if ("pathPrefix" in spec) {
         throw new Error(
@@ -177,17 +312,28 @@ export class HttpRouter {
       if (!spec.path.startsWith("/")) {
         throw new Error(`path '${spec.path}' does not start with a /`);
       }
-      const methods: Map<RoutableMethod, PublicHttpAction> =
-        this.exactRoutes.has(spec.path)
-          ? this.exactRoutes.get(spec.path)!
-          : new Map();
-      if (methods.has(method)) {
-        throw new Error(
-          `Path '${spec.path}' for method ${method} already in use`,
-        );
+      const segments = parsePathPattern(spec.path, false);
+      if (segments !== null) {
+        addPatternRoute(this.patternRoutes, method, {
+          route: spec.path,
+          segments,
+          isPrefix: false,
+          handler,
+        });
+      } else {
+        const methods: Map<RoutableMethod, PublicHttpAction> =
+          this.exactRoutes.has(spec.path)
+            ? this.exactRoutes.get(spec.path)!
+            : new Map();
+        if (methods.has(method)) {
+          throw new Error(
+            `Path '${spec.path}' for method ${method} already in use`,
+          );
+        }
+        methods.set(method, handler);
+        this.exactRoutes.set(spec.path, methods);
       }
-      methods.set(method, handler);
-      this.exactRoutes.set(spec.path, methods);
+      route = `${method} ${spec.path}`;
     } else if ("pathPrefix" in spec) {
       if (!spec.pathPrefix.startsWith("/")) {
         throw new Error(
@@ -197,20 +343,37 @@ export class HttpRouter {
       if (!spec.pathPrefix.endsWith("/")) {
         throw new Error(`pathPrefix ${spec.pathPrefix} must end with a /`);
       }
-      const prefixes =
-        this.prefixRoutes.get(method) || new Map<string, PublicHttpAction>();
-      if (prefixes.has(spec.pathPrefix)) {
-        throw new Error(
-          `${spec.method} pathPrefix ${spec.pathPrefix} is already defined`,
-        );
+      const segments = parsePathPattern(spec.pathPrefix, true);
+      if (segments !== null) {
+        addPatternRoute(this.patternRoutes, method, {
+          route: `${spec.pathPrefix}*`,
+          segments,
+          isPrefix: true,
+          handler,
+        });
+      } else {
+        const prefixes =
+          this.prefixRoutes.get(method) || new Map<string, PublicHttpAction>();
+        if (prefixes.has(spec.pathPrefix)) {
+          throw new Error(
+            `${spec.method} pathPrefix ${spec.pathPrefix} is already defined`,
+          );
+        }
+        prefixes.set(spec.pathPrefix, handler);
+        this.prefixRoutes.set(method, prefixes);
       }
-      prefixes.set(spec.pathPrefix, handler);
-      this.prefixRoutes.set(method, prefixes);
+      route = `${method} ${spec.pathPrefix}*`;
     } else {
       throw new Error(
         `Invalid httpRouter route entry: must contain either field 'path' or 'pathPrefix'`,
       );
     }
+    if (spec.verify !== undefined) {
+      this.verifications.set(route, spec.verify);
+    }
+    if (spec.middleware !== undefined && spec.middleware.length > 0) {
+      this.middleware.set(route, [...spec.middleware]);
+    }
   };
 
   /**
@@ -247,7 +410,30 @@ export class HttpRouter {
         ),
     );
 
-    return [...exact, ...prefixes];
+    // Routes with parameters are listed alongside the paths or prefixes
+    // without them, in the same order.
+    const patterns = [...this.patternRoutes.entries()].flatMap(
+      ([method, routes]) =>
+        routes.map((r) => [r.route, method, r.handler, r.isPrefix] as const),
+    );
+    const exactPatterns = patterns
+      .filter(([, , , isPrefix]) => !isPrefix)
+      .map(([path, method, handler]) => [path, method, handler] as const);
+    const prefixPatterns = patterns
+      .filter(([, , , isPrefix]) => isPrefix)
+      .map(([path, method, handler]) => [path, method, handler] as const);
+    const compare = (a: string, b: string) => (a < b ? -1 : a > b ? 1 : 0);
+
+    return [
+      ...[...exact, ...exactPatterns].sort(
+        ([pathA, methodA], [pathB, methodB]) =>
+          compare(pathA, pathB) || compare(methodA, methodB),
+      ),
+      ...[...prefixes, ...prefixPatterns].sort(
+        ([pathA, methodA], [pathB, methodB]) =>
+          compare(methodA, methodB) || compare(pathA, pathB),
+      ),
+    ];
   };
 
   /**
@@ -264,26 +450,19 @@ export class HttpRouter {
    * http.lookup("/profile/abc", "GET") // returns [getProfile, "GET", "/profile/*"]
    *```
    *
+   * An `OPTIONS` request to a path without an `OPTIONS` route is routed to
+   * another method's route on that path whose middleware answers CORS
+   * preflight requests, like {@link cors}.
+   *
    * @returns - a tuple [{@link PublicHttpAction}, method, path] or null.
    */
   lookup = (
     path: string,
     method: RoutableMethod | "HEAD",
   ): Readonly<[PublicHttpAction, RoutableMethod, string]> | null => {
-    method = normalizeMethod(method);
-    const exactMatch = this.exactRoutes.get(path)?.get(method);
-    if (exactMatch) return [exactMatch, method, path];
-
-    const prefixes = this.prefixRoutes.get(method) || new Map();
-    const prefixesSorted = [...prefixes.entries()].sort(
-      ([prefixA, _a], [prefixB, _b]) => prefixB.length - prefixA.length,
-    );
-    for (const [pathPrefix, endpoint] of prefixesSorted) {
-      if (path.startsWith(pathPrefix)) {
-        return [endpoint, method, `${pathPrefix}*`];
-      }
-    }
-    return null;
+    const match = matchRoute(this, path, method);
+    if (match === null) return null;
+    return [match.handler, match.method, match.path];
   };
 
   /**
@@ -309,7 +488,7 @@ export class HttpRouter {
     }
 
     const method = request.method;
-    const match = this.lookup(pathname, method as RoutableMethod);
+    const match = matchRoute(this, pathname, method as RoutableMethod);
     if (!match) {
       const response = new Response(`No HttpAction routed for ${pathname}`, {
         status: 404,
@@ -318,10 +497,301 @@ export class HttpRouter {
         performJsSyscall("convexJsonFromResponse", { response }),
       );
     }
-    const [endpoint, _method, _path] = match;
-    const response = await endpoint.invokeHttpAction(request);
+    const route = `${match.method} ${match.path}`;
+    requestPathParams.set(request, match.params);
+    setRequestRoute(request, route);
+    // Preflight requests routed to another method's route only run its
+    // middleware, which answers them.
+    const verification = match.isPreflight
+      ? undefined
+      : this.verifications.get(route);
+    if (verification !== undefined) {
+      const rejection = await verifyWebhookRequest(
+        request,
+        verification,
+        route,
+      );
+      if (rejection !== null) {
+        return JSON.stringify(
+          performJsSyscall("convexJsonFromResponse", { response: rejection }),
+        );
+      }
+    }
+    const middleware = this.middleware.get(match.registeredRoute) ?? [];
+    const response = await match.handler.invokeHttpAction(request, middleware);
     return JSON.stringify(
       performJsSyscall("convexJsonFromResponse", { response }),
     );
   };
 }
+
+const requestPathParams = new WeakMap<Request, Record<string, string>>();
+
+/**
+ * Returns the values of the path parameters, like `:id` in `"/items/:id"`, in
+ * the path of the route `request` was routed to.
+ *
+ * ```js
+ * export const getItem = httpAction(async (ctx, request) => {
+ *   const { id } = getPathParams<"/items/:id">(request);
+ *   ...
+ * });
+ * ```
+ *
+ * @param request - The `Request` passed to the HTTP action.
+ * @returns An object mapping each parameter's name to its URL-decoded value.
+ *
+ * @public
+ */
+export function getPathParams<Path extends string = string>(
+  request: Request,
+): string extends Path ? Record<string, string> : PathParams<Path> {
+  const params = requestPathParams.get(request);
+  if (params === undefined) {
+    throw new Error(
+      "getPathParams must be called with the Request passed to an HTTP action",
+    );
+  }
+  return params as any;
+}
+
+type PathSegment = { literal: string } | { param: string };
+
+/**
+ * A route whose path or path prefix has `:param` segments.
+ */
+type PatternRoute = {
+  /**
+   * The route's path as returned by `getRoutes`, ending in `*` for prefixes.
+   */
+  route: string;
+  segments: PathSegment[];
+  isPrefix: boolean;
+  handler: PublicHttpAction;
+};
+
+type RouteMatch = {
+  handler: PublicHttpAction;
+  method: RoutableMethod;
+  path: string;
+  params: Record<string, string>;
+  /**
+   * The `"METHOD path"` the handler was registered on, which differs from
+   * the request's route for preflight requests.
+   */
+  registeredRoute: string;
+  isPreflight: boolean;
+};
+
+function matchRoute(
+  router: HttpRouter,
+  path: string,
+  requestMethod: RoutableMethod | "HEAD",
+): RouteMatch | null {
+  const method = normalizeMethod(requestMethod);
+  const match = matchMethodRoute(router, path, method);
+  if (match !== null || method !== "OPTIONS") {
+    return match;
+  }
+  for (const otherMethod of ROUTABLE_HTTP_METHODS) {
+    const other = matchMethodRoute(router, path, otherMethod);
+    if (other === null) continue;
+    const middleware = router.middleware.get(other.registeredRoute) ?? [];
+    if (handlesPreflight(middleware)) {
+      return { ...other, method, isPreflight: true };
+    }
+  }
+  return null;
+}
+
+function matchMethodRoute(
+  router: HttpRouter,
+  path: string,
+  method: RoutableMethod,
+): RouteMatch | null {
+  const found = (
+    handler: PublicHttpAction,
+    routePath: string,
+    params: Record<string, string>,
+  ): RouteMatch => ({
+    handler,
+    method,
+    path: routePath,
+    params,
+    registeredRoute: `${method} ${routePath}`,
+    isPreflight: false,
+  });
+
+  const exactMatch = router.exactRoutes.get(path)?.get(method);
+  if (exactMatch) return found(exactMatch, path, {});
+
+  const patterns = router.patternRoutes.get(method) ?? [];
+  const pathSegments = path.slice(1).split("/");
+  const exactPatterns = patterns
+    .filter((r) => !r.isPrefix)
+    .sort(bySpecificity);
+  for (const route of exactPatterns) {
+    const params = matchSegments(route, pathSegments);
+    if (params !== null) return found(route.handler, route.route, params);
+  }
+
+  // Static prefixes are matched by string, so they keep matching exactly
+  // what they did before parameters were supported.
+  const staticPrefixes: PatternRoute[] = [
+    ...(router.prefixRoutes.get(method) ?? new Map()).entries(),
+  ].map(([pathPrefix, handler]) => ({
+    route: `${pathPrefix}*`,
+    segments: prefixSegments(pathPrefix).map((literal) => ({ literal })),
+    isPrefix: true,
+    handler,
+  }));
+  const prefixes = [
+    ...staticPrefixes,
+    ...patterns.filter((r) => r.isPrefix),
+  ].sort(bySpecificity);
+  for (const route of prefixes) {
+    const isStatic = route.segments.every((s) => "literal" in s);
+    if (isStatic) {
+      if (path.startsWith(route.route.slice(0, -1))) {
+        return found(route.handler, route.route, {});
+      }
+      continue;
+    }
+    const params = matchSegments(route, pathSegments);
+    if (params !== null) return found(route.handler, route.route, params);
+  }
+  return null;
+}
+
+/**
+ * Orders more specific routes first: those with more segments, then those
+ * with a literal segment at the first position where the other has a
+ * parameter.
+ */
+function bySpecificity(a: PatternRoute, b: PatternRoute): number {
+  if (a.segments.length !== b.segments.length) {
+    return b.segments.length - a.segments.length;
+  }
+  for (let i = 0; i < a.segments.length; i++) {
+    const aIsLiteral = "literal" in a.segments[i];
+    const bIsLiteral = "literal" in b.segments[i];
+    if (aIsLiteral !== bIsLiteral) {
+      return aIsLiteral ? -1 : 1;
+    }
+  }
+  return 0;
+}
+
+function matchSegments(
+  route: PatternRoute,
+  pathSegments: string[],
+): Record<string, string> | null {
+  // Prefixes end in a slash, so the path needs at least one more segment,
+  // even if it's empty.
+  if (
+    route.isPrefix
+      ? pathSegments.length <= route.segments.length
+      : pathSegments.length !== route.segments.length
+  ) {
+    return null;
+  }
+  const params: Record<string, string> = {};
+  for (let i = 0; i < route.segments.length; i++) {
+    const segment = route.segments[i];
+    const value = pathSegments[i];
+    if ("literal" in segment) {
+      if (segment.literal !== value) return null;
+    } else {
+      if (value === "") return null;
+      params[segment.param] = decodePathSegment(value);
+    }
+  }
+  return params;
+}
+
+function decodePathSegment(segment: string): string {
+  try {
+    return decodeURIComponent(segment);
+  } catch {
+    return segment;
+  }
+}
+
+function prefixSegments(pathPrefix: string): string[] {
+  // Drop the empty segment after the trailing slash.
+  return pathPrefix.slice(1).split("/").slice(0, -1);
+}
+
+/**
+ * Parses a path or path prefix's `:param` segments, returning null if it has
+ * none.
+ */
+function parsePathPattern(
+  path: string,
+  isPrefix: boolean,
+): PathSegment[] | null {
+  const segments = isPrefix ? prefixSegments(path) : path.slice(1).split("/");
+  if (!segments.some((segment) => segment.startsWith(":"))) {
+    return null;
+  }
+  const names = new Set<string>();
+  return segments.map((segment) => {
+    if (!segment.startsWith(":")) {
+      return { literal: segment };
+    }
+    const param = segment.slice(1);
+    if (!/^[A-Za-z_][A-Za-z0-9_]*$/.test(param)) {
+      throw new Error(
+        `Invalid path parameter '${segment}' in '${path}': names must be identifiers like ':id'`,
+      );
+    }
+    if (names.has(param)) {
+      throw new Error(
+        `Path parameter '${segment}' appears more than once in '${path}'`,
+      );
+    }
+    names.add(param);
+    return { param };
+  });
+}
+
+function addPatternRoute(
+  patternRoutes: Map<RoutableMethod, PatternRoute[]>,
+  method: RoutableMethod,
+  patternRoute: PatternRoute,
+) {
+  const routes = patternRoutes.get(method) ?? [];
+  // Routes that only differ in parameter names match the same requests.
+  const shape = (r: PatternRoute) =>
+    `${r.isPrefix}:` +
+    r.segments.map((s) => ("literal" in s ? `/${s.literal}` : "/:")).join("");
+  const conflict = routes.find((r) => shape(r) === shape(patternRoute));
+  if (conflict !== undefined) {
+    throw new Error(
+      `${method} ${patternRoute.route} conflicts with ${method} ${conflict.route}`,
+    );
+  }
+  routes.push(patternRoute);
+  patternRoutes.set(method, routes);
+}
+
+function validateWebhookVerification(verify: WebhookVerification) {
+  if (!WEBHOOK_SCHEMES.includes(verify.scheme)) {
+    throw new Error(
+      `Unknown webhook scheme '${verify.scheme}', expected one of ${WEBHOOK_SCHEMES.join(", ")}`,
+    );
+  }
+  if (typeof verify.secretEnvVar !== "string" || verify.secretEnvVar === "") {
+    throw new Error(
+      `Webhook verification requires secretEnvVar, the name of the environment variable holding the signing secret`,
+    );
+  }
+  if (
+    verify.toleranceSeconds !== undefined &&
+    !(verify.toleranceSeconds >= 0 && Number.isFinite(verify.toleranceSeconds))
+  ) {
+    throw new Error(
+      `toleranceSeconds must be a non-negative number, not ${verify.toleranceSeconds}`,
+    );
+  }
+}
@@ -69,4 +69,24 @@ export interface Scheduler {
    * @param id
    */
   cancel(id: Id<"_scheduled_functions">): Promise<void>;
+
+  /**
+   * Returns a scheduler that schedules each function at most once for the
+   * given key.
+   *
+   * If a function was already scheduled with the same key within the
+   * deployment's idempotency window (one day by default), `runAfter` and
+   * `runAt` return the ID of that scheduled function instead of scheduling
+   * another. This makes it safe to retry a mutation or action that schedules
+   * work, for example after a network error.
+   *
+   * Reusing a key to schedule a different function, or the same function
+   * with different arguments, throws an error.
+   *
+   * @param idempotencyKey - A string of at most 256 bytes identifying the
+   * scheduled function, e.g. `"send-receipt:" + orderId`.
+   */
+  withIdempotencyKey(
+    idempotencyKey: string,
+  ): Pick<Scheduler, "runAfter" | "runAt">;
 }
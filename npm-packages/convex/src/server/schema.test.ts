@@ -587,6 +587,142 @@ test("defineTable collects indexes", () => {
   ]);
 });
 
+test("defineTable collects search indexes over several fields", () => {
+  const table = defineTable({
+    title: v.string(),
+    body: v.string(),
+    tags: v.string(),
+  })
+    .searchIndex("search_title", { searchField: "title" })
+    .searchIndex("search_all", {
+      searchField: "body",
+      extraSearchFields: ["title", "tags"],
+      searchFieldWeights: { title: 3 },
+    });
+
+  expect(table.export().searchIndexes).toEqual([
+    { indexDescriptor: "search_title", searchField: "title", filterFields: [] },
+    {
+      indexDescriptor: "search_all",
+      searchField: "body",
+      extraSearchFields: ["title", "tags"],
+      searchFieldWeights: { title: 3 },
+      filterFields: [],
+    },
+  ]);
+});
+
+test("defineTable collects vector index distance metrics", () => {
+  const table = defineTable({
+    embedding: v.array(v.float64()),
+  })
+    .vectorIndex("by_cosine", { vectorField: "embedding", dimensions: 4 })
+    .vectorIndex("by_dot_product", {
+      vectorField: "embedding",
+      dimensions: 4,
+      distanceMetric: "dotProduct",
+    });
+
+  expect(table.export().vectorIndexes).toEqual([
+    {
+      indexDescriptor: "by_cosine",
+      vectorField: "embedding",
+      dimensions: 4,
+      filterFields: [],
+    },
+    {
+      indexDescriptor: "by_dot_product",
+      vectorField: "embedding",
+      dimensions: 4,
+      filterFields: [],
+      distanceMetric: "dotProduct",
+    },
+  ]);
+});
+
+test("defineTable collects vector index kinds", () => {
+  const table = defineTable({
+    embedding: v.array(v.float64()),
+  }).vectorIndex("by_embedding", {
+    vectorField: "embedding",
+    dimensions: 4,
+    indexKind: "hnsw",
+  });
+
+  expect(table.export().vectorIndexes).toEqual([
+    {
+      indexDescriptor: "by_embedding",
+      vectorField: "embedding",
+      dimensions: 4,
+      filterFields: [],
+      indexKind: "hnsw",
+    },
+  ]);
+});
+
+test("defineTable collects vector index quantization", () => {
+  const table = defineTable({
+    embedding: v.array(v.float64()),
+  }).vectorIndex("by_embedding", {
+    vectorField: "embedding",
+    dimensions: 4,
+    quantization: "scalar",
+  });
+
+  expect(table.export().vectorIndexes).toEqual([
+    {
+      indexDescriptor: "by_embedding",
+      vectorField: "embedding",
+      dimensions: 4,
+      filterFields: [],
+      quantization: "scalar",
+    },
+  ]);
+});
+
+test("defineTable collects vector index partition fields", () => {
+  const table = defineTable({
+    embedding: v.array(v.float64()),
+    tenantId: v.string(),
+  }).vectorIndex("by_embedding", {
+    vectorField: "embedding",
+    dimensions: 4,
+    filterFields: ["tenantId"],
+    partitionField: "tenantId",
+  });
+
+  expect(table.export().vectorIndexes).toEqual([
+    {
+      indexDescriptor: "by_embedding",
+      vectorField: "embedding",
+      dimensions: 4,
+      filterFields: ["tenantId"],
+      partitionField: "tenantId",
+    },
+  ]);
+});
+
+test("defineTable collects vector index embedders", () => {
+  const table = defineTable({
+    text: v.string(),
+    embedding: v.optional(v.array(v.float64())),
+  }).vectorIndex("by_embedding", {
+    vectorField: "embedding",
+    dimensions: 4,
+    embedder: { sourceField: "text", action: "embeddings:embed" },
+  });
+
+  expect(table.export().vectorIndexes).toEqual([
+    {
+      indexDescriptor: "by_embedding",
+      vectorField: "embedding",
+      dimensions: 4,
+      filterFields: [],
+      embedder: { sourceField: "text", action: "embeddings:embed" },
+    },
+  ]);
+});
+
 test("Experimental API table.[' indexes']() returns indexes", () => {
   const table = defineTable({
     a: v.string(),
@@ -42,6 +42,11 @@ import {
   SystemIndexes,
 } from "../server/system_fields.js";
 import { Expand } from "../type_utils.js";
+import { ExpressionOrValue, FilterBuilder } from "./filter_builder.js";
+import {
+  filterBuilderImpl,
+  serializeExpression,
+} from "./impl/filter_builder_impl.js";
 import {
   GenericValidator,
   ObjectType,
@@ -49,6 +54,7 @@ import {
   v,
 } from "../values/validator.js";
 import { VObject, Validator } from "../values/validators.js";
+import { convexToJson, JSONValue, Value } from "../values/value.js";
 
 /**
  * Extract all of the index field paths within a {@link Validator}.
@@ -82,6 +88,7 @@ type ExtractDocument<T extends Validator<any, any, any>> =
 export interface SearchIndexConfig<
   SearchField extends string,
   FilterFields extends string,
+  ExtraSearchFields extends string = never,
 > {
   /**
    * The field to index for full text search.
@@ -90,10 +97,94 @@ export interface SearchIndexConfig<
    */
   searchField: SearchField;
 
+  /**
+   * More `string` fields to search along with `searchField`, like a title
+   * and tags along with a body. Search queries still name `searchField`,
+   * and match text in any of the search fields.
+   */
+  extraSearchFields?: ExtraSearchFields[];
+
+  /**
+   * How many times a match in each search field counts when scoring
+   * results, as a whole number between 1 and 10. Search fields without a
+   * weight count once.
+   *
+   * For example `{ title: 3 }` makes matches in `title` count three times as
+   * much as matches in other search fields.
+   */
+  searchFieldWeights?: Partial<Record<SearchField | ExtraSearchFields, number>>;
+
   /**
    * Additional fields to index for fast filtering when running search queries.
    */
   filterFields?: FilterFields[];
+
+  /**
+   * How to split the search field into terms. Search text is split the same
+   * way, and changing this rebuilds the index.
+   *
+   * Defaults to splitting into lowercase words without stemming or
+   * stopwords.
+   */
+  analyzer?: SearchIndexAnalyzer;
+}
+
+/**
+ * How a full text search index splits text into terms.
+ *
+ * @public
+ */
+export interface SearchIndexAnalyzer {
+  /**
+   * Also store the prefixes of each word, from `minGram` to `maxGram`
+   * characters long (defaulting to 2 and 15), so the index can serve
+   * {@link SearchFilterBuilder.prefixSearch}. This makes the index larger.
+   */
+  autocomplete?: { minGram?: number; maxGram?: number };
+
+  /**
+   * The language of the text, which picks the stemmer. Defaults to
+   * `"english"`.
+   */
+  language?:
+    | "arabic"
+    | "danish"
+    | "dutch"
+    | "english"
+    | "finnish"
+    | "french"
+    | "german"
+    | "greek"
+    | "hungarian"
+    | "italian"
+    | "norwegian"
+    | "portuguese"
+    | "romanian"
+    | "russian"
+    | "spanish"
+    | "swedish"
+    | "tamil"
+    | "turkish";
+
+  /**
+   * Reduce words to their stems so that e.g. "running" matches "runs".
+   * Defaults to `false`.
+   */
+  stemming?: boolean;
+
+  /**
+   * Words to leave out of the index and search text, like "the" or "a".
+   * Matching is case insensitive.
+   */
+  stopwords?: string[];
+
+  /**
+   * How to split text into words. `"words"` splits on anything that isn't a
+   * letter or number. `"cjkBigrams"` also splits runs of Chinese, Japanese
+   * or Korean characters into overlapping pairs, since those languages don't
+   * separate words with spaces. Defaults to `"words"`.
+   */
+  segmentation?: "words" | "cjkBigrams";
 }
 
 /**
@@ -119,8 +210,113 @@ export interface VectorIndexConfig<
    * Additional fields to index for fast filtering when running vector searches.
    */
   filterFields?: FilterFields[];
+  /**
+   * One of the `filterFields`, like a tenant ID, to partition the index by.
+   *
+   * The index tracks which partitions each of its segments contains, so
+   * searches that filter this field with `q.eq` (or an `or` of `q.eq`s)
+   * skip the segments of other partitions instead of filtering after
+   * searching the whole index. Changing this rebuilds the index.
+   */
+  partitionField?: FilterFields;
+  /**
+   * How to compare vectors, which determines the `_score` of vector search
+   * results. Higher scores are always closer, and changing this rebuilds the
+   * index.
+   *
+   * - `"cosine"`: the cosine similarity, between -1 and 1.
+   * - `"dotProduct"`: the dot product, for embeddings whose magnitude is
+   *   meaningful.
+   * - `"euclidean"`: the negated squared Euclidean distance, so identical
+   *   vectors score 0.
+   *
+   * Defaults to `"cosine"`.
+   */
+  distanceMetric?: VectorDistanceMetric;
+  /**
+   * How to lay out the index on disk.
+   *
+   * - `"segmented"`: small segments are scanned in full and large ones use
+   *   HNSW graphs read from disk on demand. Uses little memory.
+   * - `"hnsw"`: every segment uses an HNSW graph that is loaded into memory.
+   *   Searches are faster and more consistent at the cost of memory and
+   *   slower builds.
+   *
+   * To migrate an existing index, change this option and push: the index is
+   * rebuilt in the background and keeps serving the old layout until the
+   * new one is ready.
+   *
+   * Defaults to `"segmented"`.
+   */
+  indexKind?: VectorIndexKind;
+  /**
+   * How to compress the vectors that searches compare against, to fit large
+   * indexes in less memory. Searches re-rank their top candidates against
+   * the full-precision vectors, so `_score`s are unaffected.
+   *
+   * - `"none"`: keep full-precision vectors only.
+   * - `"scalar"`: store each component as an 8-bit integer, using a quarter
+   *   of the memory with little loss of recall.
+   * - `"product"`: product quantization, using a sixteenth of the memory at
+   *   a larger cost to recall.
+   *
+   * Only segments large enough to get an HNSW graph, or all segments of
+   * `"hnsw"` indexes, are quantized. Changing this rebuilds the index.
+   *
+   * Defaults to `"none"`.
+   */
+  quantization?: VectorQuantization;
+  /**
+   * Have the backend compute the vector field from a text field.
+   *
+   * Whenever a document is inserted or updated with a new string in
+   * `sourceField`, the backend queues it and calls `action` with a batch of
+   * `{ texts: string[] }`. The action must return one vector of `dimensions`
+   * numbers per text, which the backend writes to `vectorField` unless the
+   * text has changed again in the meantime. Failed calls are retried with
+   * backoff. Changing this doesn't rebuild the index.
+   */
+  embedder?: VectorEmbedderConfig;
 }
 
+/**
+ * An action that computes a vector index's vectors from a text field.
+ *
+ * @public
+ */
+export type VectorEmbedderConfig = {
+  /**
+   * The text field to embed.
+   */
+  sourceField: string;
+  /**
+   * The path of the action that computes the vectors, like
+   * `"embeddings:embed"`, in the same component as the table.
+   */
+  action: string;
+};
+
+/**
+ * How a vector index compresses its vectors.
+ *
+ * @public
+ */
+export type VectorQuantization = "none" | "scalar" | "product";
+
+/**
+ * How a vector index lays out its data.
+ *
+ * @public
+ */
+export type VectorIndexKind = "segmented" | "hnsw";
+
+/**
+ * How a vector index compares vectors.
+ *
+ * @public
+ */
+export type VectorDistanceMetric = "cosine" | "dotProduct" | "euclidean";
+
 /**
  * @internal
  */
@@ -129,6 +325,11 @@ export type VectorIndex = {
   vectorField: string;
   dimensions: number;
   filterFields: string[];
+  partitionField?: string;
+  distanceMetric?: VectorDistanceMetric;
+  indexKind?: VectorIndexKind;
+  quantization?: VectorQuantization;
+  embedder?: VectorEmbedderConfig;
 };
 
 /**
@@ -137,6 +338,7 @@ export type VectorIndex = {
 export type Index = {
   indexDescriptor: string;
   fields: string[];
+  variant?: string | number | boolean;
 };
 
 /**
@@ -145,8 +347,45 @@ export type Index = {
 export type SearchIndex = {
   indexDescriptor: string;
   searchField: string;
+  extraSearchFields?: string[];
+  searchFieldWeights?: Record<string, number>;
   filterFields: string[];
+  analyzer?: SearchIndexAnalyzer;
+};
+/**
+ * A default the server computes when a document is inserted without the
+ * field. Create one with {@link serverDefaults}.
+ * @public
+ */
+export class ServerDefault {
+  /**
+   * @internal
+   */
+  constructor(readonly kind: "now" | "tokenIdentifier") {}
+}
+
+/**
+ * Server-computed values for {@link TableDefinition.defaults}.
+ * @public
+ */
+export const serverDefaults = {
+  /** The time of the insert in milliseconds, which is the document's `_creationTime`. */
+  now: () => new ServerDefault("now"),
+  /**
+   * The `tokenIdentifier` of the authenticated user. The field is left unset
+   * if the insert isn't made by a user.
+   */
+  tokenIdentifier: () => new ServerDefault("tokenIdentifier"),
 };
+
+/**
+ * @internal
+ */
+export type FieldDefault =
+  | { type: "value"; value: JSONValue }
+  | { type: "now" }
+  | { type: "tokenIdentifier" };
+
 /**
  * The definition of a table within a schema.
  *
@@ -162,6 +401,10 @@ export class TableDefinition<
   private indexes: Index[];
   private searchIndexes: SearchIndex[];
   private vectorIndexes: VectorIndex[];
+  private fieldDefaults: Record<string, FieldDefault> | undefined;
+  private immutableFields: string[] = [];
+  private writeOnceFields: string[] = [];
+  private checks: { name: string; expression: JSONValue }[] = [];
   // The type of documents stored in this table.
   validator: DocumentType;
 
@@ -195,6 +438,10 @@ export class TableDefinition<
    * @param name - The name of the index.
    * @param fields - The fields to index, in order. Must specify at least one
    * field.
+   * @param options - For a table whose documents are a discriminated union,
+   * `variant` is the discriminant of the variant this index is for. The index
+   * must start with the discriminant field and may only reference fields of
+   * that variant.
    * @returns A {@link TableDefinition} with this index included.
    */
   index<
@@ -204,6 +451,7 @@ export class TableDefinition<
   >(
     name: IndexName,
     fields: [FirstFieldPath, ...RestFieldPaths],
+    options?: { variant: string | number | boolean },
   ): TableDefinition<
     DocumentType,
     // Update `Indexes` to include the new index and use `Expand` to make the
@@ -218,7 +466,106 @@ export class TableDefinition<
     SearchIndexes,
     VectorIndexes
   > {
-    this.indexes.push({ indexDescriptor: name, fields });
+    this.indexes.push({
+      indexDescriptor: name,
+      fields,
+      ...(options !== undefined ? { variant: options.variant } : {}),
+    });
+    return this;
+  }
+
+  /**
+   * Declare values the server fills in for top-level fields that an inserted
+   * document doesn't set.
+   *
+   * ```ts
+   * defineTable({ status: v.string(), createdBy: v.string() }).defaults({
+   *   status: "draft",
+   *   createdBy: serverDefaults.tokenIdentifier(),
+   * });
+   * ```
+   *
+   * @param defaults - A constant value or a {@link ServerDefault} for each
+   * field.
+   * @returns A {@link TableDefinition} with these defaults.
+   */
+  defaults(defaults: Record<string, Value | ServerDefault>): this {
+    this.fieldDefaults = Object.fromEntries(
+      Object.entries(defaults).map(([field, value]) => [
+        field,
+        value instanceof ServerDefault
+          ? { type: value.kind }
+          : { type: "value", value: convexToJson(value) },
+      ]),
+    );
+    return this;
+  }
+
+  /**
+   * Declare top-level fields that keep the value they were inserted with.
+   * Patches and replaces that change or remove them fail.
+   *
+   * ```ts
+   * defineTable({ ownerId: v.id("users"), title: v.string() }).immutable(
+   *   "ownerId",
+   * );
+   * ```
+   *
+   * @param fields - The fields that can't change after insertion.
+   * @returns A {@link TableDefinition} with these fields immutable.
+   */
+  immutable(...fields: ExtractFieldPaths<DocumentType>[]): this {
+    this.immutableFields.push(...fields);
+    return this;
+  }
+
+  /**
+   * Declare top-level fields that can be set once, either on insert or by a
+   * later write, and then keep that value.
+   *
+   * @param fields - The fields that can't change once set.
+   * @returns A {@link TableDefinition} with these fields write-once.
+   */
+  writeOnce(...fields: ExtractFieldPaths<DocumentType>[]): this {
+    this.writeOnceFields.push(...fields);
+    return this;
+  }
+
+  /**
+   * Declare an invariant that every document in this table must satisfy.
+   * Inserts, patches and replaces that break it fail, and pushing a schema
+   * with a new check fails if existing documents break it.
+   *
+   * ```ts
+   * defineTable({ startTime: v.number(), endTime: v.number() }).check(
+   *   "endsAfterStart",
+   *   (q) => q.gt(q.field("endTime"), q.field("startTime")),
+   * );
+   * ```
+   *
+   * A check is only satisfied when its expression is `true`, so comparisons
+   * against a missing optional field fail unless the expression allows it.
+   *
+   * @param name - The name of the check, shown in violation errors.
+   * @param predicate - A filter expression over the document's fields.
+   * @returns A {@link TableDefinition} with this check.
+   */
+  check(
+    name: string,
+    predicate: (
+      q: FilterBuilder<{
+        document: ExtractDocument<DocumentType>;
+        fieldPaths: ExtractFieldPaths<DocumentType>;
+        indexes: {};
+        searchIndexes: {};
+        vectorIndexes: {};
+      }>,
+    ) => ExpressionOrValue<boolean>,
+  ): this {
+    this.checks.push({
+      name,
+      expression: serializeExpression(predicate(filterBuilderImpl as any)),
+    });
     return this;
   }
 
@@ -235,9 +582,12 @@ export class TableDefinition<
     IndexName extends string,
     SearchField extends ExtractFieldPaths<DocumentType>,
     FilterFields extends ExtractFieldPaths<DocumentType> = never,
+    ExtraSearchFields extends ExtractFieldPaths<DocumentType> = never,
   >(
     name: IndexName,
-    indexConfig: Expand<SearchIndexConfig<SearchField, FilterFields>>,
+    indexConfig: Expand<
+      SearchIndexConfig<SearchField, FilterFields, ExtraSearchFields>
+    >,
   ): TableDefinition<
     DocumentType,
     Indexes,
@@ -258,7 +608,21 @@ export class TableDefinition<
     this.searchIndexes.push({
       indexDescriptor: name,
       searchField: indexConfig.searchField,
+      ...(indexConfig.extraSearchFields !== undefined
+        ? { extraSearchFields: indexConfig.extraSearchFields }
+        : {}),
+      ...(indexConfig.searchFieldWeights !== undefined
+        ? {
+            searchFieldWeights: indexConfig.searchFieldWeights as Record<
+              string,
+              number
+            >,
+          }
+        : {}),
       filterFields: indexConfig.filterFields || [],
+      ...(indexConfig.analyzer !== undefined
+        ? { analyzer: indexConfig.analyzer }
+        : {}),
     });
     return this;
   }
@@ -300,6 +664,21 @@ export class TableDefinition<
       vectorField: indexConfig.vectorField,
       dimensions: indexConfig.dimensions,
       filterFields: indexConfig.filterFields || [],
+      ...(indexConfig.partitionField !== undefined
+        ? { partitionField: indexConfig.partitionField }
+        : {}),
+      ...(indexConfig.distanceMetric !== undefined
+        ? { distanceMetric: indexConfig.distanceMetric }
+        : {}),
+      ...(indexConfig.indexKind !== undefined
+        ? { indexKind: indexConfig.indexKind }
+        : {}),
+      ...(indexConfig.quantization !== undefined
+        ? { quantization: indexConfig.quantization }
+        : {}),
+      ...(indexConfig.embedder !== undefined
+        ? { embedder: indexConfig.embedder }
+        : {}),
     });
     return this;
   }
@@ -334,6 +713,16 @@ export class TableDefinition<
       searchIndexes: this.searchIndexes,
       vectorIndexes: this.vectorIndexes,
       documentType,
+      ...(this.fieldDefaults !== undefined
+        ? { defaults: this.fieldDefaults }
+        : {}),
+      ...(this.immutableFields.length > 0
+        ? { immutableFields: this.immutableFields }
+        : {}),
+      ...(this.writeOnceFields.length > 0
+        ? { writeOnceFields: this.writeOnceFields }
+        : {}),
+      ...(this.checks.length > 0 ? { checks: this.checks } : {}),
     };
   }
 }
@@ -584,6 +973,7 @@ const _systemSchema = defineSchema({
   _scheduled_functions: defineTable({
     name: v.string(),
     args: v.array(v.any()),
+    argsInStorage: v.optional(v.boolean()),
     scheduledTime: v.float64(),
     completedTime: v.optional(v.float64()),
     state: v.union(
@@ -599,6 +989,24 @@ const _systemSchema = defineSchema({
     size: v.float64(),
     contentType: v.optional(v.string()),
   }),
+  _indexes: defineTable({
+    table: v.string(),
+    name: v.string(),
+    componentId: v.union(v.string(), v.null()),
+    type: v.union(
+      v.literal("database"),
+      v.literal("search"),
+      v.literal("vector"),
+    ),
+    fields: v.array(v.string()),
+    state: v.union(
+      v.literal("backfilling"),
+      v.literal("backfilled"),
+      v.literal("enabled"),
+    ),
+    documentsIndexed: v.union(v.float64(), v.null()),
+    sizeBytes: v.union(v.float64(), v.null()),
+  }),
 });
 
 export interface SystemDataModel
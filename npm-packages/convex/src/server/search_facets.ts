@@ -0,0 +1,50 @@
+import { Value } from "../values/index.js";
+import { PaginationResult } from "./pagination.js";
+
+/**
+ * How many results of a text search have each value of the filter fields
+ * listed in its `facets` option, keyed by field name. Each field's values are
+ * ordered from most to least common.
+ *
+ * Results without a value for a field aren't counted for it.
+ *
+ * @public
+ */
+export type SearchFacets = Record<string, { value: Value; count: number }[]>;
+
+const facetsByResults = new WeakMap<object, SearchFacets>();
+
+/**
+ * @internal
+ */
+export function setSearchFacets(results: object, facets: SearchFacets) {
+  facetsByResults.set(results, facets);
+}
+
+/**
+ * Get the facet counts of a text search.
+ *
+ * ```ts
+ * const results = await ctx.db
+ *   .query("messages")
+ *   .withSearchIndex("search_body", (q) =>
+ *     q.search("body", "hello", { facets: { fields: ["channel"] } }),
+ *   )
+ *   .take(10);
+ * const channels = searchFacets(results)?.channel;
+ * ```
+ *
+ * The counts cover every result of the search, not just the ones returned, so
+ * every page of a paginated search has the same counts.
+ *
+ * @param results - The array returned by `collect` or `take`, or the result
+ * of `paginate`, for a search query.
+ * @returns The facet counts, or `undefined` if the results didn't come from a
+ * search query with the `facets` option.
+ * @public
+ */
+export function searchFacets(
+  results: unknown[] | PaginationResult<unknown>,
+): SearchFacets | undefined {
+  return facetsByResults.get(results);
+}
@@ -35,18 +35,116 @@ export interface SearchFilterBuilder<
    * - How many times do they appear?
    * - How long is the text field?
    *
+   * Pass `{ maxTypos: 1 }` or `{ maxTypos: 2 }` to also match words that are
+   * that many typos away from the query's words. Matches with typos rank
+   * below exact matches.
+   *
+   * Wrap words in double quotes, like `"quick brown fox"`, to only match text
+   * containing them next to each other in that order. Write `fox NEAR/3 dog`
+   * to only match text where "fox" and "dog" are at most 3 words apart, in
+   * either order. Closer matches rank higher, and quoted or `NEAR` words never
+   * match with typos.
+   *
    * @param fieldName - The name of the field to search in. This must be listed
    * as the index's `searchField`.
    * @param query - The query text to search for.
+   * @param options - See {@link SearchOptions}.
    */
   search(
     fieldName: SearchIndexConfig["searchField"],
     query: string,
+    options?: SearchOptions<SearchIndexConfig["filterFields"]>,
+  ): SearchFilterFinalizer<Document, SearchIndexConfig>;
+
+  /**
+   * Search for words in `doc[fieldName]` that start with the words of `query`,
+   * for autocomplete as the user types.
+   *
+   * The index must store word prefixes, by setting
+   * `analyzer: { autocomplete: {} }` in its definition. Query words longer
+   * than the index's `maxGram` are cut to it, at most 8 words are used, and
+   * typos and quoted phrases aren't supported.
+   *
+   * @param fieldName - The name of the field to search in. This must be listed
+   * as the index's `searchField`.
+   * @param query - The partial query text to search for.
+   * @param options - See {@link SearchOptions}. `maxTypos` isn't supported.
+   */
+  prefixSearch(
+    fieldName: SearchIndexConfig["searchField"],
+    query: string,
+    options?: Omit<
+      SearchOptions<SearchIndexConfig["filterFields"]>,
+      "maxTypos"
+    >,
   ): SearchFilterFinalizer<Document, SearchIndexConfig>;
 }
 
 /**
- * Builder to define equality expressions as part of a search filter.
+ * Options for {@link SearchFilterBuilder.search}.
+ *
+ * @public
+ */
+export type SearchOptions<FilterFields extends string = string> = {
+  /**
+   * The most typos (insertions, deletions, or substitutions) to tolerate in
+   * each word of the query. Defaults to 0.
+   *
+   * Words of 4 or fewer characters are always matched exactly, and words of 8
+   * or fewer characters tolerate at most one typo. Typos never change a
+   * word's first character.
+   */
+  maxTypos?: 0 | 1 | 2;
+
+  /**
+   * Find where the search matched each result, along with snippets of the
+   * search field around the matches. Read them with {@link searchHighlights}.
+   *
+   * `fragmentSize` is the approximate length of each snippet in characters,
+   * between 10 and 1000, and defaults to 100. `maxFragments` is the most
+   * snippets to return for each result, up to 10, and defaults to 3.
+   */
+  highlight?: boolean | { fragmentSize?: number; maxFragments?: number };
+
+  /**
+   * Count how many results have each value of some of the index's
+   * `filterFields`, for example to show category filters next to the
+   * results. Read the counts with {@link searchFacets}.
+   *
+   * `fields` lists up to 8 filter fields. `limit` is the most values to
+   * count for each field, up to 100, and defaults to 10. The most common
+   * values come first. Counting reads every document the search matches, up
+   * to 1024 documents, even if the query returns fewer of them.
+   */
+  facets?: { fields: FilterFields[]; limit?: number };
+
+  /**
+   * Rank results by an expression instead of their relevance alone, like
+   * `"bm25 * log(1 + popularity)"`.
+   *
+   * `bm25` is the result's relevance score, and other names are fields of
+   * the document, like `likes` or `stats.views`. Fields that are missing or
+   * aren't numbers count as 0. Expressions can use numbers, `+`, `-`, `*`,
+   * `/`, parentheses, and the functions `log`, `log10`, `sqrt`, `abs`, `exp`,
+   * `pow`, `min`, and `max`.
+   *
+   * The expression reorders the 1024 most relevant matches, and reads each
+   * of them even if the query returns fewer.
+   */
+  score?: string;
+};
+
+/**
+ * Builder to define equality and range expressions as part of a search
+ * filter.
+ *
+ * Range expressions compare numbers, like prices or timestamps from
+ * `Date.now()`. Each one is a separate condition, so combine them to find
+ * values between two bounds:
+ *
+ * ```ts
+ * q.search("body", "lamp").gte("price", 10).lte("price", 50)
+ * ```
  *
  * See {@link SearchFilterBuilder}.
  *
@@ -67,6 +165,58 @@ export interface SearchFilterFinalizer<
     fieldName: FieldName,
     value: FieldTypeFromFieldPath<Document, FieldName>,
   ): SearchFilterFinalizer<Document, SearchIndexConfig>;
+
+  /**
+   * Restrict this query to documents where `doc[fieldName] > value`.
+   *
+   * @param fieldName - The name of the field to compare. This must be listed in
+   * the search index's `filterFields`.
+   * @param value - The number to compare against. Only values of the same
+   * type match, so a `float64` range doesn't match `int64`s.
+   */
+  gt<FieldName extends SearchIndexConfig["filterFields"]>(
+    fieldName: FieldName,
+    value: FieldTypeFromFieldPath<Document, FieldName>,
+  ): SearchFilterFinalizer<Document, SearchIndexConfig>;
+
+  /**
+   * Restrict this query to documents where `doc[fieldName] >= value`.
+   *
+   * @param fieldName - The name of the field to compare. This must be listed in
+   * the search index's `filterFields`.
+   * @param value - The number to compare against. Only values of the same
+   * type match, so a `float64` range doesn't match `int64`s.
+   */
+  gte<FieldName extends SearchIndexConfig["filterFields"]>(
+    fieldName: FieldName,
+    value: FieldTypeFromFieldPath<Document, FieldName>,
+  ): SearchFilterFinalizer<Document, SearchIndexConfig>;
+
+  /**
+   * Restrict this query to documents where `doc[fieldName] < value`.
+   *
+   * @param fieldName - The name of the field to compare. This must be listed in
+   * the search index's `filterFields`.
+   * @param value - The number to compare against. Only values of the same
+   * type match, so a `float64` range doesn't match `int64`s.
+   */
+  lt<FieldName extends SearchIndexConfig["filterFields"]>(
+    fieldName: FieldName,
+    value: FieldTypeFromFieldPath<Document, FieldName>,
+  ): SearchFilterFinalizer<Document, SearchIndexConfig>;
+
+  /**
+   * Restrict this query to documents where `doc[fieldName] <= value`.
+   *
+   * @param fieldName - The name of the field to compare. This must be listed in
+   * the search index's `filterFields`.
+   * @param value - The number to compare against. Only values of the same
+   * type match, so a `float64` range doesn't match `int64`s.
+   */
+  lte<FieldName extends SearchIndexConfig["filterFields"]>(
+    fieldName: FieldName,
+    value: FieldTypeFromFieldPath<Document, FieldName>,
+  ): SearchFilterFinalizer<Document, SearchIndexConfig>;
 }
 
 /**
@@ -0,0 +1,68 @@
+import { GenericDocument } from "./data_model.js";
+
+/**
+ * A range of a string, from `start` up to but not including `end`, in the
+ * same units as JavaScript string indexes.
+ *
+ * @public
+ */
+export type HighlightRange = {
+  start: number;
+  end: number;
+};
+
+/**
+ * Where a text search matched one of its results.
+ *
+ * @public
+ */
+export type SearchHighlights = {
+  /**
+   * Every match in the search field, in order.
+   */
+  matches: HighlightRange[];
+  /**
+   * Snippets of the search field around the matches, with the snippets
+   * containing the most matches first. Match ranges are relative to the
+   * start of the snippet's `text`.
+   */
+  snippets: { text: string; matches: HighlightRange[] }[];
+};
+
+const highlightsByDocument = new WeakMap<object, SearchHighlights>();
+
+/**
+ * @internal
+ */
+export function setSearchHighlights(
+  document: unknown,
+  highlights: SearchHighlights,
+) {
+  if (typeof document === "object" && document !== null) {
+    highlightsByDocument.set(document, highlights);
+  }
+}
+
+/**
+ * Get where a text search matched a document it returned.
+ *
+ * ```ts
+ * const results = await ctx.db
+ *   .query("messages")
+ *   .withSearchIndex("search_body", (q) =>
+ *     q.search("body", "hello", { highlight: true }),
+ *   )
+ *   .take(10);
+ * const snippets = results.map((doc) => searchHighlights(doc)?.snippets);
+ * ```
+ *
+ * @param document - A document returned by a search query.
+ * @returns The highlights, or `undefined` if the document didn't come from a
+ * search query with the `highlight` option.
+ * @public
+ */
+export function searchHighlights(
+  document: GenericDocument,
+): SearchHighlights | undefined {
+  return highlightsByDocument.get(document);
+}
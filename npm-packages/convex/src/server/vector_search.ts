@@ -36,10 +36,44 @@ export interface VectorSearchQuery<
    */
   limit?: number;
   /**
-   * Optional filter expression made up of `q.or` and `q.eq` operating
-   * over the filter fields of the index.
+   * How many candidates the search explores in the index before picking the
+   * closest `limit` results. Higher values improve recall at the cost of
+   * latency, so offline jobs may want a large value while interactive
+   * requests may want a small one. Must be a positive integer no larger than
+   * the deployment's limit.
+   *
+   * Defaults to the index's own setting.
+   */
+  efSearch?: number;
+  /**
+   * Whether to return each result's stored vector as `_vector` and its exact
+   * distance from the query vector as `_distance`, so the documents don't need
+   * to be fetched again.
+   *
+   * Distances are smaller for closer vectors: `1 - cosine similarity` for
+   * cosine indexes, the negated dot product for dot product indexes and the
+   * straight-line distance for Euclidean indexes.
+   *
+   * @default false
+   */
+  includeVectors?: boolean;
+  /**
+   * Re-rank the nearest `fetchLimit` results by maximal marginal relevance
+   * before returning the best `limit` of them, favoring results that aren't
+   * similar to each other.
+   *
+   * `lambda` is between 0 and 1, where 1 ranks purely by relevance and smaller
+   * values favor diversity. It defaults to 0.5. `fetchLimit` must be between
+   * `limit` and 256, and defaults to four times `limit`.
+   */
+  mmr?: { lambda?: number; fetchLimit?: number };
+  /**
+   * Optional filter expression made up of `q.or`, `q.eq`, `q.neq`, `q.not`,
+   * `q.and` and the range comparisons `q.gt`, `q.gte`, `q.lt` and `q.lte`
+   * operating over the filter fields of the index.
    *
    * e.g. `filter: q => q.or(q.eq("genre", "comedy"), q.eq("genre", "drama"))`
+   * or `filter: q => q.and(q.gte("year", 1990), q.lt("year", 2000))`
    *
    * @param q
    * @returns
@@ -60,7 +94,14 @@ export type VectorSearch<
   tableName: TableName,
   indexName: IndexName,
   query: VectorSearchQuery<NamedTableInfo<DataModel, TableName>, IndexName>,
-) => Promise<Array<{ _id: Id<TableName>; _score: number }>>;
+) => Promise<
+  Array<{
+    _id: Id<TableName>;
+    _score: number;
+    _vector?: number[];
+    _distance?: number;
+  }>
+>;
 
 /**
  * Expressions are evaluated to produce a {@link values.Value} in the course of executing a query.
@@ -112,12 +153,93 @@ export interface VectorFilterBuilder<
     value: FieldTypeFromFieldPath<Document, FieldName>,
   ): FilterExpression<boolean>;
 
+  /**
+   * Is the field at `fieldName` not equal to `value`
+   *
+   * @public
+   * */
+  neq<FieldName extends VectorIndexConfig["filterFields"]>(
+    fieldName: FieldName,
+    value: FieldTypeFromFieldPath<Document, FieldName>,
+  ): FilterExpression<boolean>;
+
+  /**
+   * Is the field at `fieldName` greater than `value`
+   *
+   * Range comparisons only support numbers, and only match documents where
+   * the field has the same type (`float64` or `int64`) as `value`.
+   *
+   * @public
+   * */
+  gt<FieldName extends VectorIndexConfig["filterFields"]>(
+    fieldName: FieldName,
+    value: FieldTypeFromFieldPath<Document, FieldName>,
+  ): FilterExpression<boolean>;
+
+  /**
+   * Is the field at `fieldName` greater than or equal to `value`
+   *
+   * Range comparisons only support numbers, and only match documents where
+   * the field has the same type (`float64` or `int64`) as `value`.
+   *
+   * @public
+   * */
+  gte<FieldName extends VectorIndexConfig["filterFields"]>(
+    fieldName: FieldName,
+    value: FieldTypeFromFieldPath<Document, FieldName>,
+  ): FilterExpression<boolean>;
+
+  /**
+   * Is the field at `fieldName` less than `value`
+   *
+   * Range comparisons only support numbers, and only match documents where
+   * the field has the same type (`float64` or `int64`) as `value`.
+   *
+   * @public
+   * */
+  lt<FieldName extends VectorIndexConfig["filterFields"]>(
+    fieldName: FieldName,
+    value: FieldTypeFromFieldPath<Document, FieldName>,
+  ): FilterExpression<boolean>;
+
+  /**
+   * Is the field at `fieldName` less than or equal to `value`
+   *
+   * Range comparisons only support numbers, and only match documents where
+   * the field has the same type (`float64` or `int64`) as `value`.
+   *
+   * @public
+   * */
+  lte<FieldName extends VectorIndexConfig["filterFields"]>(
+    fieldName: FieldName,
+    value: FieldTypeFromFieldPath<Document, FieldName>,
+  ): FilterExpression<boolean>;
+
   //  Logic  ///////////////////////////////////////////////////////////////////
 
+  /**
+   * `exprs[0] && exprs[1]`
+   *
+   * This can only combine a lower and an upper bound on the same field, e.g.
+   * `q.and(q.gte("year", 1990), q.lt("year", 2000))`.
+   *
+   * @public
+   */
+  and(...exprs: Array<FilterExpression<boolean>>): FilterExpression<boolean>;
+
   /**
    * `exprs[0] || exprs[1] || ... || exprs[n]`
    *
    * @public
    */
   or(...exprs: Array<FilterExpression<boolean>>): FilterExpression<boolean>;
+
+  /**
+   * `!x`
+   *
+   * This can only negate `q.eq` or a `q.or` of `q.eq`s on a single field.
+   *
+   * @public
+   */
+  not(x: FilterExpression<boolean>): FilterExpression<boolean>;
 }
@@ -1,5 +1,7 @@
 import { Value } from "./value.js";
 import { compareUTF8 } from "./compare_utf8.js";
+import { Decimal } from "./decimal.js";
+import { DateTime } from "./datetime.js";
 
 export function compareValues(k1: Value | undefined, k2: Value | undefined) {
   return compareAsTuples(makeComparable(k1), makeComparable(k2));
@@ -30,6 +32,18 @@ function compareSameTypeValues<T>(v1: T, v2: T): number {
     }
     return compareUTF8(v1, v2);
   }
+  if (v1 instanceof Decimal) {
+    if (!(v2 instanceof Decimal)) {
+      throw new Error(`Unexpected type ${v2 as any}`);
+    }
+    return v1.cmp(v2);
+  }
+  if (v1 instanceof DateTime) {
+    if (!(v2 instanceof DateTime)) {
+      throw new Error(`Unexpected type ${v2 as any}`);
+    }
+    return v1.cmp(v2);
+  }
   if (
     typeof v1 === "bigint" ||
     typeof v1 === "boolean" ||
@@ -137,6 +151,14 @@ function makeComparable(v: Value | undefined): [number, any] {
   if (Array.isArray(v)) {
     return [7, v.map(makeComparable)];
   }
+  // Decimals sort after every other type.
+  if (v instanceof Decimal) {
+    return [9, v];
+  }
+  // Datetimes sort after decimals.
+  if (v instanceof DateTime) {
+    return [10, v];
+  }
   // Otherwise, it's an POJO.
   const keys = Object.keys(v).sort();
   const pojo: Value[] = keys.map((k) => [k, v[k]!]);
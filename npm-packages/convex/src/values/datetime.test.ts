@@ -0,0 +1,72 @@
+import { test, expect, describe } from "vitest";
+
+import { DateTime, datetime } from "./datetime.js";
+import { convexToJson, jsonToConvex } from "./value.js";
+import { compareValues } from "./compare.js";
+
+describe("DateTime", () => {
+  test("formats canonically", () => {
+    expect(datetime("2024-01-02T03:04:05Z").toString()).toEqual(
+      "2024-01-02T03:04:05Z",
+    );
+    expect(datetime("2024-01-02T03:04:05+00:00").toString()).toEqual(
+      "2024-01-02T03:04:05Z",
+    );
+    expect(datetime("2024-01-02T03:04:05.5Z").toString()).toEqual(
+      "2024-01-02T03:04:05.500Z",
+    );
+    expect(datetime("2024-01-02T03:04:05.123456789-07:30").toString()).toEqual(
+      "2024-01-02T03:04:05.123456789-07:30",
+    );
+    expect(datetime("1970-01-01T00:00:00.000001Z").toString()).toEqual(
+      "1970-01-01T00:00:00.000001Z",
+    );
+    expect(datetime("1969-12-31T23:59:59.999Z").toString()).toEqual(
+      "1969-12-31T23:59:59.999Z",
+    );
+  });
+
+  test("rejects invalid input", () => {
+    expect(() => datetime("")).toThrow();
+    expect(() => datetime("2024-01-02")).toThrow();
+    expect(() => datetime("2024-01-02T03:04:05")).toThrow();
+    expect(() => datetime("2024-13-02T03:04:05Z")).toThrow();
+    expect(() => datetime("2023-02-29T00:00:00Z")).toThrow();
+    expect(() => datetime("3000-01-01T00:00:00Z")).toThrow();
+  });
+
+  test("converts to and from Date", () => {
+    const date = new Date("2024-01-02T03:04:05.678Z");
+    const d = datetime(date);
+    expect(d.epochNanoseconds).toEqual(BigInt(date.getTime()) * BigInt(1e6));
+    expect(d.toDate().getTime()).toEqual(date.getTime());
+    expect(
+      datetime("1969-12-31T23:59:59.9999Z").toEpochMilliseconds(),
+    ).toEqual(-1);
+  });
+
+  test("compares by instant", () => {
+    const a = datetime("2024-01-01T12:00:00+02:00");
+    const b = datetime("2024-01-01T11:00:00Z");
+    expect(a.isBefore(b)).toBe(true);
+    expect(b.isAfter(a)).toBe(true);
+    expect(a.cmp(b)).toEqual(-1);
+    expect(a.equals("2024-01-01T10:00:00Z")).toBe(false);
+    expect(a.toUTC().equals("2024-01-01T10:00:00Z")).toBe(true);
+  });
+
+  test("roundtrips through JSON", () => {
+    const value = { at: datetime("2024-01-02T03:04:05.123+05:30") };
+    const json = convexToJson(value);
+    expect(json).toEqual({ at: { $datetime: "2024-01-02T03:04:05.123+05:30" } });
+    const roundtripped = jsonToConvex(json) as { at: DateTime };
+    expect(roundtripped.at).toBeInstanceOf(DateTime);
+    expect(roundtripped.at.equals(value.at)).toBe(true);
+  });
+
+  test("sorts after every other type", () => {
+    const d = datetime("1970-01-01T00:00:00Z");
+    expect(compareValues(d, {})).toEqual(1);
+    expect(compareValues(d, datetime("2024-01-01T00:00:00Z"))).toEqual(-1);
+  });
+});
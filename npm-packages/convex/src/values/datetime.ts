@@ -0,0 +1,230 @@
+// This code is used by code that may not have bigint literals.
+const ZERO = BigInt("0");
+const NANOS_PER_MILLI = BigInt("1000000");
+const NANOS_PER_SECOND = BigInt("1000000000");
+const NANOS_PER_MINUTE = BigInt("60000000000");
+const MIN_NANOS = BigInt("-9223372036854775808");
+const MAX_NANOS = BigInt("9223372036854775807");
+
+const MAX_OFFSET_MINUTES = 23 * 60 + 59;
+
+const RFC_3339_REGEX =
+  /^(\d{4})-(\d{2})-(\d{2})[Tt ](\d{2}):(\d{2}):(\d{2})(?:\.(\d+))?([Zz]|[+-]\d{2}:\d{2})$/;
+
+/**
+ * An instant with nanosecond precision, along with the UTC offset it was
+ * written in, stored in Convex as a `datetime` value.
+ *
+ * Use datetimes instead of millisecond `number`s for timestamps you want to
+ * index and query by range: datetimes sort by instant in indexes, after every
+ * other type of value, so `2024-01-01T12:00:00+02:00` sorts before
+ * `2024-01-01T11:00:00Z`.
+ *
+ * Datetimes are immutable and can represent instants between the years 1677
+ * and 2262.
+ *
+ * @public
+ */
+export class DateTime {
+  /** Nanoseconds since the Unix epoch. */
+  readonly epochNanoseconds: bigint;
+  /** The UTC offset this datetime was written in, in minutes. */
+  readonly offsetMinutes: number;
+
+  private constructor(epochNanoseconds: bigint, offsetMinutes: number) {
+    if (epochNanoseconds < MIN_NANOS || epochNanoseconds > MAX_NANOS) {
+      throw new Error("DateTime is outside of years 1677 through 2262");
+    }
+    if (
+      !Number.isInteger(offsetMinutes) ||
+      Math.abs(offsetMinutes) > MAX_OFFSET_MINUTES
+    ) {
+      throw new Error(
+        `UTC offset of ${offsetMinutes} minutes is outside of ` +
+          `[-${MAX_OFFSET_MINUTES}, ${MAX_OFFSET_MINUTES}]`,
+      );
+    }
+    this.epochNanoseconds = epochNanoseconds;
+    this.offsetMinutes = offsetMinutes;
+  }
+
+  /**
+   * Create a datetime from an RFC 3339 string like
+   * `"2024-01-02T03:04:05.123456789+02:00"`, or from a `Date`.
+   */
+  static from(value: string | Date | DateTime): DateTime {
+    if (value instanceof DateTime) {
+      return value;
+    }
+    if (value instanceof Date) {
+      return DateTime.fromEpochMilliseconds(value.getTime());
+    }
+    return parse(value);
+  }
+
+  /** Create a datetime in UTC from nanoseconds since the Unix epoch. */
+  static fromEpochNanoseconds(
+    epochNanoseconds: bigint,
+    offsetMinutes = 0,
+  ): DateTime {
+    return new DateTime(epochNanoseconds, offsetMinutes);
+  }
+
+  /** Create a datetime in UTC from milliseconds since the Unix epoch. */
+  static fromEpochMilliseconds(epochMilliseconds: number): DateTime {
+    if (!Number.isInteger(epochMilliseconds)) {
+      throw new Error(`${epochMilliseconds} isn't a whole number of ms`);
+    }
+    return new DateTime(BigInt(epochMilliseconds) * NANOS_PER_MILLI, 0);
+  }
+
+  /** The current time in UTC, with millisecond precision. */
+  static now(): DateTime {
+    return DateTime.fromEpochMilliseconds(Date.now());
+  }
+
+  /**
+   * Returns a negative number, zero, or a positive number. Datetimes compare
+   * by instant, then by offset.
+   */
+  cmp(other: DateTime | string | Date): number {
+    const rhs = DateTime.from(other);
+    if (this.epochNanoseconds !== rhs.epochNanoseconds) {
+      return this.epochNanoseconds < rhs.epochNanoseconds ? -1 : 1;
+    }
+    return Math.sign(this.offsetMinutes - rhs.offsetMinutes);
+  }
+
+  /** Whether this datetime is an earlier instant than `other`. */
+  isBefore(other: DateTime | string | Date): boolean {
+    return this.epochNanoseconds < DateTime.from(other).epochNanoseconds;
+  }
+
+  /** Whether this datetime is a later instant than `other`. */
+  isAfter(other: DateTime | string | Date): boolean {
+    return this.epochNanoseconds > DateTime.from(other).epochNanoseconds;
+  }
+
+  /**
+   * Whether `other` is the same instant in the same offset. Use
+   * `a.toUTC().equals(b.toUTC())` to ignore offsets.
+   */
+  equals(other: DateTime | string | Date): boolean {
+    return this.cmp(other) === 0;
+  }
+
+  /** The same instant in UTC. */
+  toUTC(): DateTime {
+    return new DateTime(this.epochNanoseconds, 0);
+  }
+
+  /** Milliseconds since the Unix epoch, rounded down. */
+  toEpochMilliseconds(): number {
+    return Number(floorDiv(this.epochNanoseconds, NANOS_PER_MILLI));
+  }
+
+  /** Convert to a `Date`. This drops sub-millisecond precision. */
+  toDate(): Date {
+    return new Date(this.toEpochMilliseconds());
+  }
+
+  /**
+   * Format as RFC 3339 in this datetime's offset, with as many fractional
+   * digits (0, 3, 6 or 9) as needed and `Z` for UTC. Equal datetimes format
+   * the same way.
+   */
+  toString(): string {
+    const local =
+      this.epochNanoseconds + BigInt(this.offsetMinutes) * NANOS_PER_MINUTE;
+    const seconds = floorDiv(local, NANOS_PER_SECOND);
+    const subsecond = Number(local - seconds * NANOS_PER_SECOND);
+    const date = new Date(Number(seconds) * 1000);
+    const ymd =
+      `${pad(date.getUTCFullYear(), 4)}-${pad(date.getUTCMonth() + 1, 2)}` +
+      `-${pad(date.getUTCDate(), 2)}`;
+    const hms =
+      `${pad(date.getUTCHours(), 2)}:${pad(date.getUTCMinutes(), 2)}` +
+      `:${pad(date.getUTCSeconds(), 2)}`;
+    let fraction = "";
+    if (subsecond % 1_000_000 === 0 && subsecond !== 0) {
+      fraction = `.${pad(subsecond / 1_000_000, 3)}`;
+    } else if (subsecond % 1000 === 0 && subsecond !== 0) {
+      fraction = `.${pad(subsecond / 1000, 6)}`;
+    } else if (subsecond !== 0) {
+      fraction = `.${pad(subsecond, 9)}`;
+    }
+    let offset = "Z";
+    if (this.offsetMinutes !== 0) {
+      const sign = this.offsetMinutes < 0 ? "-" : "+";
+      const minutes = Math.abs(this.offsetMinutes);
+      offset = `${sign}${pad(Math.floor(minutes / 60), 2)}:${pad(minutes % 60, 2)}`;
+    }
+    return `${ymd}T${hms}${fraction}${offset}`;
+  }
+
+  toJSON(): string {
+    return this.toString();
+  }
+}
+
+/**
+ * Shorthand for {@link DateTime.from}.
+ *
+ * ```js
+ * const start = datetime("2024-01-01T00:00:00Z");
+ * ```
+ *
+ * @public
+ */
+export function datetime(value: string | Date | DateTime): DateTime {
+  return DateTime.from(value);
+}
+
+function parse(value: string): DateTime {
+  const match = RFC_3339_REGEX.exec(value);
+  if (match === null) {
+    throw new Error(
+      `${JSON.stringify(value)} isn't an RFC 3339 datetime like ` +
+        `"2024-01-02T03:04:05Z"`,
+    );
+  }
+  const [, year, month, day, hour, minute, second, fraction = "", offset] =
+    match;
+  const [y, mo, d, h, mi, s] = [year, month, day, hour, minute, second].map(
+    Number,
+  );
+  const ms = Date.UTC(y, mo - 1, d, h, mi, s);
+  const check = new Date(ms);
+  if (
+    check.getUTCFullYear() !== y ||
+    check.getUTCMonth() !== mo - 1 ||
+    check.getUTCDate() !== d ||
+    check.getUTCHours() !== h ||
+    check.getUTCMinutes() !== mi ||
+    check.getUTCSeconds() !== s
+  ) {
+    throw new Error(`${JSON.stringify(value)} isn't a valid datetime`);
+  }
+  let offsetMinutes = 0;
+  if (offset !== "Z" && offset !== "z") {
+    const sign = offset[0] === "-" ? -1 : 1;
+    offsetMinutes =
+      sign * (Number(offset.slice(1, 3)) * 60 + Number(offset.slice(4, 6)));
+  }
+  const subsecond = BigInt(fraction.slice(0, 9).padEnd(9, "0"));
+  return new DateTime(
+    BigInt(ms) * NANOS_PER_MILLI +
+      subsecond -
+      BigInt(offsetMinutes) * NANOS_PER_MINUTE,
+    offsetMinutes,
+  );
+}
+
+function floorDiv(n: bigint, d: bigint): bigint {
+  const q = n / d;
+  return n % d < ZERO ? q - BigInt(1) : q;
+}
+
+function pad(n: number, width: number): string {
+  return n.toString().padStart(width, "0");
+}
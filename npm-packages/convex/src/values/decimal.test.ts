@@ -0,0 +1,60 @@
+import { test, expect, describe } from "vitest";
+
+import { Decimal, decimal } from "./decimal.js";
+import { convexToJson, jsonToConvex } from "./value.js";
+import { compareValues } from "./compare.js";
+
+describe("Decimal", () => {
+  test("formats canonically", () => {
+    expect(decimal("1.50").toString()).toEqual("1.5");
+    expect(decimal("-0.00").toString()).toEqual("0");
+    expect(decimal("1200").toString()).toEqual("1200");
+    expect(decimal("0.00000012").toString()).toEqual("0.00000012");
+    expect(decimal("1.2e-8").toString()).toEqual("1.2e-8");
+    expect(decimal("1.5e40").toString()).toEqual("1.5e40");
+    expect(decimal(0.1).toString()).toEqual("0.1");
+    expect(decimal(BigInt("-42")).toString()).toEqual("-42");
+  });
+
+  test("rejects invalid input", () => {
+    expect(() => decimal("")).toThrow();
+    expect(() => decimal("1.2.3")).toThrow();
+    expect(() => decimal("abc")).toThrow();
+    expect(() => decimal(NaN)).toThrow();
+    expect(() => decimal("1".repeat(35))).toThrow();
+    expect(() => decimal("1e7000")).toThrow();
+  });
+
+  test("arithmetic is exact", () => {
+    expect(decimal("0.1").add("0.2").equals("0.3")).toBe(true);
+    expect(decimal("19.99").mul(3).toString()).toEqual("59.97");
+    expect(decimal("10").sub("0.01").toString()).toEqual("9.99");
+    expect(decimal("1").div(3, 4).toString()).toEqual("0.3333");
+    expect(decimal("2").div(3, 2).toString()).toEqual("0.67");
+    expect(decimal("-2").div(3, 2, "down").toString()).toEqual("-0.66");
+    expect(decimal("2.5").round(0).toString()).toEqual("2");
+    expect(decimal("3.5").round(0).toString()).toEqual("4");
+    expect(decimal("2.5").round(0, "halfUp").toString()).toEqual("3");
+    expect(() => decimal("1").div(0, 2)).toThrow();
+  });
+
+  test("compares numerically", () => {
+    expect(decimal("1.5").cmp("1.50")).toEqual(0);
+    expect(decimal("-2").cmp("1")).toEqual(-1);
+    expect(decimal("10").cmp("9.99")).toEqual(1);
+  });
+
+  test("roundtrips through JSON", () => {
+    const value = { price: decimal("12.50") };
+    const json = convexToJson(value);
+    expect(json).toEqual({ price: { $decimal: "12.5" } });
+    const roundtripped = jsonToConvex(json) as { price: Decimal };
+    expect(roundtripped.price).toBeInstanceOf(Decimal);
+    expect(roundtripped.price.equals(value.price)).toBe(true);
+  });
+
+  test("sorts after every other type", () => {
+    expect(compareValues(decimal("-1"), {})).toEqual(1);
+    expect(compareValues(decimal("-1"), decimal("0.5"))).toEqual(-1);
+  });
+});
@@ -0,0 +1,278 @@
+// This code is used by code that may not have bigint literals.
+const ZERO = BigInt("0");
+const ONE = BigInt("1");
+const TWO = BigInt("2");
+const TEN = BigInt("10");
+
+const MAX_DIGITS = 34;
+const MIN_EXPONENT = -6176;
+const MAX_EXPONENT = 6111;
+
+const DECIMAL_REGEX = /^([+-])?(\d*)(?:\.(\d*))?(?:[eE]([+-]?\d+))?$/;
+
+/**
+ * How {@link Decimal.div} rounds a quotient that doesn't fit in the requested
+ * number of decimal places.
+ *
+ * @public
+ */
+export type DecimalRounding = "halfEven" | "halfUp" | "down" | "up";
+
+/**
+ * An exact base-10 number with up to 34 significant digits, stored in Convex
+ * as a `decimal` value.
+ *
+ * Use decimals instead of `number` for money and other quantities where
+ * floating-point rounding is unacceptable: `0.1 + 0.2` is exactly `0.3`.
+ * Decimals sort numerically in indexes, after every other type of value.
+ *
+ * Decimals are immutable. `1.50` and `1.5` are the same decimal and both
+ * format as `"1.5"`.
+ *
+ * @public
+ */
+export class Decimal {
+  /** @internal */
+  readonly coefficient: bigint;
+  /** @internal */
+  readonly exponent: number;
+
+  private constructor(coefficient: bigint, exponent: number) {
+    if (coefficient === ZERO) {
+      exponent = 0;
+    }
+    while (coefficient !== ZERO && coefficient % TEN === ZERO) {
+      coefficient /= TEN;
+      exponent += 1;
+    }
+    if (digitCount(abs(coefficient)) > MAX_DIGITS) {
+      throw new Error(`Decimal has more than ${MAX_DIGITS} significant digits`);
+    }
+    if (exponent < MIN_EXPONENT || exponent > MAX_EXPONENT) {
+      throw new Error(
+        `Decimal exponent ${exponent} is outside of [${MIN_EXPONENT}, ${MAX_EXPONENT}]`,
+      );
+    }
+    this.coefficient = coefficient;
+    this.exponent = exponent;
+  }
+
+  /**
+   * Create a decimal from a string like `"-12.50"` or `"1.25e-3"`, an integer
+   * `bigint`, or a `number`. Numbers are converted through their shortest
+   * string representation, so `Decimal.from(0.1)` is exactly `0.1`.
+   */
+  static from(value: string | number | bigint | Decimal): Decimal {
+    if (value instanceof Decimal) {
+      return value;
+    }
+    if (typeof value === "bigint") {
+      return new Decimal(value, 0);
+    }
+    if (typeof value === "number") {
+      if (!Number.isFinite(value)) {
+        throw new Error(`${value} can't be represented as a decimal`);
+      }
+      return Decimal.from(value.toString());
+    }
+    const match = DECIMAL_REGEX.exec(value);
+    if (match === null) {
+      throw new Error(
+        `${JSON.stringify(value)} isn't a decimal number like "-12.50" or "1.25e-3"`,
+      );
+    }
+    const [, sign, integer, fraction = "", exponent = "0"] = match;
+    if (integer === "" && fraction === "") {
+      throw new Error(
+        `${JSON.stringify(value)} isn't a decimal number like "-12.50" or "1.25e-3"`,
+      );
+    }
+    const coefficient = BigInt(integer + fraction || "0");
+    return new Decimal(
+      sign === "-" ? -coefficient : coefficient,
+      Number(exponent) - fraction.length,
+    );
+  }
+
+  /** Returns `this + other`. */
+  add(other: Decimal | string | number | bigint): Decimal {
+    const rhs = Decimal.from(other);
+    const exponent = Math.min(this.exponent, rhs.exponent);
+    return new Decimal(
+      this.scaledTo(exponent) + rhs.scaledTo(exponent),
+      exponent,
+    );
+  }
+
+  /** Returns `this - other`. */
+  sub(other: Decimal | string | number | bigint): Decimal {
+    return this.add(Decimal.from(other).neg());
+  }
+
+  /** Returns `this * other`. */
+  mul(other: Decimal | string | number | bigint): Decimal {
+    const rhs = Decimal.from(other);
+    return new Decimal(
+      this.coefficient * rhs.coefficient,
+      this.exponent + rhs.exponent,
+    );
+  }
+
+  /**
+   * Returns `this / other` rounded to `places` digits after the decimal
+   * point. Division isn't exact in general, so the scale is required.
+   *
+   * @param rounding - Defaults to `"halfEven"` (banker's rounding).
+   */
+  div(
+    other: Decimal | string | number | bigint,
+    places: number,
+    rounding: DecimalRounding = "halfEven",
+  ): Decimal {
+    const rhs = Decimal.from(other);
+    if (rhs.coefficient === ZERO) {
+      throw new Error("Division by zero");
+    }
+    // this / rhs = (c1 / c2) * 10^(e1 - e2); rescale so the quotient is an
+    // integer count of 10^-places.
+    const shift = this.exponent - rhs.exponent + places;
+    let numerator = this.coefficient;
+    let denominator = rhs.coefficient;
+    if (shift >= 0) {
+      numerator *= TEN ** BigInt(shift);
+    } else {
+      denominator *= TEN ** BigInt(-shift);
+    }
+    return new Decimal(
+      roundedQuotient(numerator, denominator, rounding),
+      -places,
+    );
+  }
+
+  /**
+   * Returns this decimal rounded to `places` digits after the decimal point.
+   *
+   * @param rounding - Defaults to `"halfEven"` (banker's rounding).
+   */
+  round(places: number, rounding: DecimalRounding = "halfEven"): Decimal {
+    return this.div(ONE, places, rounding);
+  }
+
+  /** Returns `-this`. */
+  neg(): Decimal {
+    return new Decimal(-this.coefficient, this.exponent);
+  }
+
+  /** Returns the absolute value of this decimal. */
+  abs(): Decimal {
+    return new Decimal(abs(this.coefficient), this.exponent);
+  }
+
+  /** Returns a negative number, zero, or a positive number. */
+  cmp(other: Decimal | string | number | bigint): number {
+    const rhs = Decimal.from(other);
+    const exponent = Math.min(this.exponent, rhs.exponent);
+    const diff = this.scaledTo(exponent) - rhs.scaledTo(exponent);
+    return diff < ZERO ? -1 : diff > ZERO ? 1 : 0;
+  }
+
+  equals(other: Decimal | string | number | bigint): boolean {
+    return this.cmp(other) === 0;
+  }
+
+  isZero(): boolean {
+    return this.coefficient === ZERO;
+  }
+
+  isNegative(): boolean {
+    return this.coefficient < ZERO;
+  }
+
+  /**
+   * Format this decimal in plain notation, or in scientific notation like
+   * `"1.5e40"` if it's very large or very small. Equal decimals format the
+   * same way.
+   */
+  toString(): string {
+    const sign = this.coefficient < ZERO ? "-" : "";
+    const digits = abs(this.coefficient).toString();
+    const adjustedExponent = this.exponent + digits.length - 1;
+    if (adjustedExponent < -7 || adjustedExponent >= MAX_DIGITS) {
+      const rest = digits.slice(1);
+      return `${sign}${digits[0]}${rest ? `.${rest}` : ""}e${adjustedExponent}`;
+    }
+    if (this.exponent >= 0) {
+      return `${sign}${digits}${"0".repeat(this.exponent)}`;
+    }
+    const point = digits.length + this.exponent;
+    if (point > 0) {
+      return `${sign}${digits.slice(0, point)}.${digits.slice(point)}`;
+    }
+    return `${sign}0.${"0".repeat(-point)}${digits}`;
+  }
+
+  /**
+   * Convert to the nearest `number`. This can lose precision.
+   */
+  toNumber(): number {
+    return Number(this.toString());
+  }
+
+  toJSON(): string {
+    return this.toString();
+  }
+
+  private scaledTo(exponent: number): bigint {
+    return this.coefficient * TEN ** BigInt(this.exponent - exponent);
+  }
+}
+
+/**
+ * Shorthand for {@link Decimal.from}.
+ *
+ * ```js
+ * const total = decimal("19.99").mul(3); // 59.97
+ * ```
+ *
+ * @public
+ */
+export function decimal(value: string | number | bigint | Decimal): Decimal {
+  return Decimal.from(value);
+}
+
+function abs(n: bigint): bigint {
+  return n < ZERO ? -n : n;
+}
+
+function digitCount(n: bigint): number {
+  return n.toString().length;
+}
+
+function roundedQuotient(
+  numerator: bigint,
+  denominator: bigint,
+  rounding: DecimalRounding,
+): bigint {
+  const quotient = numerator / denominator;
+  const remainder = numerator % denominator;
+  if (remainder === ZERO) {
+    return quotient;
+  }
+  const negative = numerator < ZERO !== denominator < ZERO;
+  const awayFromZero = negative ? quotient - ONE : quotient + ONE;
+  const twiceRemainder = abs(remainder) * TWO;
+  const absDenominator = abs(denominator);
+  switch (rounding) {
+    case "down":
+      return quotient;
+    case "up":
+      return awayFromZero;
+    case "halfUp":
+      return twiceRemainder >= absDenominator ? awayFromZero : quotient;
+    case "halfEven":
+      if (twiceRemainder === absDenominator) {
+        return quotient % TWO === ZERO ? quotient : awayFromZero;
+      }
+      return twiceRemainder > absDenominator ? awayFromZero : quotient;
+  }
+}
@@ -7,6 +7,9 @@
  */
 
 export { convexToJson, jsonToConvex } from "./value.js";
+export { Decimal, decimal } from "./decimal.js";
+export type { DecimalRounding } from "./decimal.js";
+export { DateTime, datetime } from "./datetime.js";
 export type {
   Id as GenericId,
   JSONValue,
@@ -32,6 +35,8 @@ export type {
   VInt64,
   VBoolean,
   VBytes,
+  VDecimal,
+  VDatetime,
   VString,
   VNull,
   VAny,
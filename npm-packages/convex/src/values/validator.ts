@@ -6,6 +6,8 @@ import {
   VArray,
   VBoolean,
   VBytes,
+  VDecimal,
+  VDatetime,
   VFloat64,
   VId,
   VInt64,
@@ -147,6 +149,22 @@ export const v = {
     return new VBytes({ isOptional: "required" });
   },
 
+  /**
+   * Validates that the value is of Convex type Decimal (constructed in JS via
+   * `decimal("12.50")`).
+   */
+  decimal: () => {
+    return new VDecimal({ isOptional: "required" });
+  },
+
+  /**
+   * Validates that the value is of Convex type DateTime (constructed in JS via
+   * `datetime("2024-01-02T03:04:05Z")`).
+   */
+  datetime: () => {
+    return new VDatetime({ isOptional: "required" });
+  },
+
   /**
    * Validates that the value is equal to the given literal value.
    * @param literal The literal value to compare against.
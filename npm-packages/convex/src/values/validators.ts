@@ -1,5 +1,7 @@
 import { GenericId } from "./index.js";
 import { GenericValidator } from "./validator.js";
+import { Decimal } from "./decimal.js";
+import { DateTime } from "./datetime.js";
 import { JSONValue, convexToJson } from "./value.js";
 
 type TableNameFromType<T> =
@@ -186,6 +188,54 @@ export class VBytes<
   }
 }
 
+/**
+ * The type of the `v.decimal()` validator.
+ */
+export class VDecimal<
+  Type = Decimal,
+  IsOptional extends OptionalProperty = "required",
+> extends BaseValidator<Type, IsOptional> {
+  /**
+   * The kind of validator, `"decimal"`.
+   */
+  readonly kind = "decimal" as const;
+
+  /** @internal */
+  get json(): ValidatorJSON {
+    return { type: this.kind };
+  }
+  /** @internal */
+  asOptional() {
+    return new VDecimal<Type | undefined, "optional">({
+      isOptional: "optional",
+    });
+  }
+}
+
+/**
+ * The type of the `v.datetime()` validator.
+ */
+export class VDatetime<
+  Type = DateTime,
+  IsOptional extends OptionalProperty = "required",
+> extends BaseValidator<Type, IsOptional> {
+  /**
+   * The kind of validator, `"datetime"`.
+   */
+  readonly kind = "datetime" as const;
+
+  /** @internal */
+  get json(): ValidatorJSON {
+    return { type: this.kind };
+  }
+  /** @internal */
+  asOptional() {
+    return new VDatetime<Type | undefined, "optional">({
+      isOptional: "optional",
+    });
+  }
+}
+
 /**
  * The type of the `v.string()` validator.
  */
@@ -537,6 +587,10 @@ export type VOptional<T extends Validator<any, OptionalProperty, any>> =
     ? VLiteral<Type | undefined, "optional">
   : T extends VBytes<infer Type, OptionalProperty>
     ? VBytes<Type | undefined, "optional">
+  : T extends VDecimal<infer Type, OptionalProperty>
+    ? VDecimal<Type | undefined, "optional">
+  : T extends VDatetime<infer Type, OptionalProperty>
+    ? VDatetime<Type | undefined, "optional">
   : T extends VObject< infer Type, infer Fields, OptionalProperty, infer FieldPaths>
     ? VObject<Type | undefined, Fields, "optional", FieldPaths>
   : T extends VArray<infer Type, infer Element, OptionalProperty>
@@ -591,6 +645,8 @@ export type Validator<
   | VAny<Type, IsOptional>
   | VLiteral<Type, IsOptional>
   | VBytes<Type, IsOptional>
+  | VDecimal<Type, IsOptional>
+  | VDatetime<Type, IsOptional>
   | VObject<
       Type,
       Record<string, Validator<any, OptionalProperty, any>>,
@@ -627,6 +683,8 @@ export type ValidatorJSON =
   | { type: "boolean" }
   | { type: "string" }
   | { type: "bytes" }
+  | { type: "decimal" }
+  | { type: "datetime" }
   | { type: "any" }
   | { type: "literal"; value: JSONValue }
   | { type: "id"; tableName: string }
@@ -6,6 +6,8 @@
  * @module
  */
 import * as Base64 from "./base64.js";
+import { Decimal } from "./decimal.js";
+import { DateTime } from "./datetime.js";
 import { isSimpleObject } from "../common/index.js";
 
 const LITTLE_ENDIAN = true;
@@ -70,6 +72,8 @@ export type Value =
   | boolean
   | string
   | ArrayBuffer
+  | Decimal
+  | DateTime
   | Value[]
   | { [key: string]: undefined | Value };
 
@@ -235,6 +239,18 @@ export function jsonToConvex(value: JSONValue): Value {
       }
       return float;
     }
+    if (key === "$decimal") {
+      if (typeof value.$decimal !== "string") {
+        throw new Error(`Malformed $decimal field on ${value as any}`);
+      }
+      return Decimal.from(value.$decimal);
+    }
+    if (key === "$datetime") {
+      if (typeof value.$datetime !== "string") {
+        throw new Error(`Malformed $datetime field on ${value as any}`);
+      }
+      return DateTime.from(value.$datetime);
+    }
     if (key === "$set") {
       throw new Error(
         `Received a Set which is no longer supported as a Convex type.`,
@@ -317,6 +333,12 @@ function convexToJsonInternal(
   if (value instanceof ArrayBuffer) {
     return { $bytes: Base64.fromByteArray(new Uint8Array(value)) };
   }
+  if (value instanceof Decimal) {
+    return { $decimal: value.toString() };
+  }
+  if (value instanceof DateTime) {
+    return { $datetime: value.toString() };
+  }
   if (Array.isArray(value)) {
     return value.map((value, i) =>
       convexToJsonInternal(value, originalValue, context + `[${i}]`, false),
@@ -1,4 +1,4 @@
-import { ValidatorJSON, Value } from "convex/values";
+import { DateTime, Decimal, ValidatorJSON, Value } from "convex/values";
 import isPlainObject from "lodash/isPlainObject";
 import { UNDEFINED_PLACEHOLDER } from "system-udfs/convex/_system/frontend/lib/values";
 import * as IdEncoding from "id-encoding";
@@ -21,6 +21,10 @@ export const isValidValue = (
       return typeof value === "bigint";
     case "bytes":
       return value instanceof Uint8Array;
+    case "decimal":
+      return value instanceof Decimal;
+    case "datetime":
+      return value instanceof DateTime;
     case "any":
       return true;
     case "literal":
@@ -107,6 +111,12 @@ export const typeForValue = (value: Value): ValidatorJSON["type"] => {
   if (value instanceof Uint8Array) {
     return "bytes";
   }
+  if (value instanceof Decimal) {
+    return "decimal";
+  }
+  if (value instanceof DateTime) {
+    return "datetime";
+  }
   if (isPlainObject(value)) {
     return "object";
   }
@@ -71,6 +71,8 @@ export const defaultValueForShape = (shape: Shape): Value | undefined => {
     case "Null":
       return null;
     case "Bytes":
+    case "Decimal":
+    case "DateTime":
     case "Map":
     case "Never":
     case "Set":
@@ -63,6 +63,8 @@ const validateDocumentShape = (shape: Shape | undefined) => {
     case "Array":
     case "Boolean":
     case "Bytes":
+    case "Decimal":
+    case "DateTime":
     case "Float64":
     case "Int64":
     case "Id":
@@ -82,6 +82,10 @@ export function displaySchemaFromShape({
       return `v.boolean()`;
     case "Bytes":
       return `v.bytes()`;
+    case "Decimal":
+      return `v.decimal()`;
+    case "DateTime":
+      return `v.datetime()`;
     case "Float64":
       return `v.float64()`;
     case "Id":
@@ -193,6 +197,10 @@ function displayValidator(validator: ValidatorJSON): string {
       return `v.string()`;
     case "bytes":
       return `v.bytes()`;
+    case "decimal":
+      return `v.decimal()`;
+    case "datetime":
+      return `v.datetime()`;
     case "any":
       return `v.any()`;
     case "literal":
@@ -408,6 +408,9 @@ export class PaginatorDatabaseReader<DataModel extends GenericDataModel>
   get(_id: any): any {
     throw new Error("get() not supported for `paginator`");
   }
+  prefetch(_ids: any[]): void {
+    throw new Error("prefetch() not supported for `paginator`");
+  }
   normalizeId(_tableName: any, _id: any): any {
     throw new Error("normalizeId() not supported for `paginator`.");
   }
@@ -471,6 +474,11 @@ export class PaginatorQueryInitializer<
       ".first() not supported for `paginator`. Use .paginate() instead.",
     );
   }
+  prefetch(_numItems?: number): void {
+    throw new Error(
+      ".prefetch() not supported for `paginator`. Use .paginate() instead.",
+    );
+  }
   unique(): any {
     throw new Error(
       ".unique() not supported for `paginator`. Use .paginate() instead.",
@@ -486,6 +494,26 @@ export class PaginatorQueryInitializer<
       ".count() not supported for `paginator`. Use .paginate() instead.",
     );
   }
+  sample(_n: number): any {
+    throw new Error(
+      ".sample() not supported for `paginator`. Use .paginate() instead.",
+    );
+  }
+  estimatedCount(): any {
+    throw new Error(
+      ".estimatedCount() not supported for `paginator`. Use .paginate() instead.",
+    );
+  }
+  approxDistinct(_field: string): any {
+    throw new Error(
+      ".approxDistinct() not supported for `paginator`. Use .paginate() instead.",
+    );
+  }
+  groupBy(_fields: string[], _aggregates: any): any {
+    throw new Error(
+      ".groupBy() not supported for `paginator`. Use .paginate() instead.",
+    );
+  }
   limit(_n: number): any {
     throw new Error(
       ".limit() not supported for `paginator`. Use .paginate() instead.",
@@ -531,6 +559,11 @@ export class PaginatorQuery<
       ".first() not supported for `paginator`. Use .paginate() instead.",
     );
   }
+  prefetch(_numItems?: number): void {
+    throw new Error(
+      ".prefetch() not supported for `paginator`. Use .paginate() instead.",
+    );
+  }
   unique(): any {
     throw new Error(
       ".unique() not supported for `paginator`. Use .paginate() instead.",
@@ -541,6 +574,21 @@ export class PaginatorQuery<
       ".take() not supported for `paginator`. Use .paginate() instead.",
     );
   }
+  estimatedCount(): any {
+    throw new Error(
+      ".estimatedCount() not supported for `paginator`. Use .paginate() instead.",
+    );
+  }
+  approxDistinct(_field: string): any {
+    throw new Error(
+      ".approxDistinct() not supported for `paginator`. Use .paginate() instead.",
+    );
+  }
+  groupBy(_fields: string[], _aggregates: any): any {
+    throw new Error(
+      ".groupBy() not supported for `paginator`. Use .paginate() instead.",
+    );
+  }
   limit(_n: number): any {
     throw new Error(
       ".limit() not supported for `paginator`. Use .paginate() instead.",
@@ -655,6 +703,11 @@ export class OrderedPaginatorQuery<
       ".first() not supported for `paginator`. Use .paginate() instead.",
     );
   }
+  prefetch(_numItems?: number): void {
+    throw new Error(
+      ".prefetch() not supported for `paginator`. Use .paginate() instead.",
+    );
+  }
   unique(): any {
     throw new Error(
       ".unique() not supported for `paginator`. Use .paginate() instead.",
@@ -596,6 +596,10 @@ let globalSyscalls: Syscalls | null = null;
     return globalSyscalls.asyncSyscall(op, jsonArgs);
   },
   jsSyscall: (op: string, args: Record<string, any>) => {
+    // `ctx.log` writes straight to the log stream without a round trip.
+    if (op === "console/structuredLog") {
+      return globalStructuredLog(args as StructuredLogArgs);
+    }
     if (!globalSyscalls) {
       throw new Error(`Cannot invoke syscall during module imports`);
     }
@@ -620,6 +624,15 @@ type ConsoleState = {
 
 let globalConsoleState: ConsoleState;
 
+type StructuredLogArgs = {
+  level: string;
+  message: string;
+  // Convex JSON encoded.
+  fields: Record<string, any>;
+};
+
+let globalStructuredLog: (args: StructuredLogArgs) => void = () => {};
+
 function defaultConsoleState(): ConsoleState {
   return {
     sentLines: 0,
@@ -664,8 +677,13 @@ export function setupConsole(responseStream: Writable) {
         customInspect: true,
       }),
     );
-
-    let messages = serializedArgs;
+    writeLogLine(level, serializedArgs);
+  }
+  function writeLogLine(
+    level: string,
+    messages: string[],
+    fields?: Record<string, any>,
+  ) {
     // Requirements:
     // - 6MB limit on AWS lambda response size, so only collect
     //   maximum 2MB of logs, one ~million UTF16 code units (UTF16
@@ -675,7 +693,9 @@ export function setupConsole(responseStream: Writable) {
       return;
     }
     const totalMessageLength =
-      messages.reduce((acc, current) => acc + current.length + 1, 0) - 1;
+      messages.reduce((acc, current) => acc + current.length + 1, 0) -
+      1 +
+      (fields === undefined ? 0 : JSON.stringify(fields).length);
     if (
       globalConsoleState.totalSentLineLength + totalMessageLength >
       1_048_576
@@ -698,12 +718,15 @@ export function setupConsole(responseStream: Writable) {
           isTruncated: false,
           timestamp: Date.now(),
           level,
+          fields: globalConsoleState.logLimitHit ? undefined : fields,
         },
       }) + "\n",
     );
     globalConsoleState.totalSentLineLength += totalMessageLength;
     globalConsoleState.sentLines += 1;
   }
+  globalStructuredLog = ({ level, message, fields }) =>
+    writeLogLine(level, message === "" ? [] : [message], fields);
   devConsole.debug = function (...args) {
     consoleMessage("DEBUG", ...args);
   };
@@ -46,6 +46,7 @@ const scheduleSchema = z.object({
   functionHandle: z.optional(z.string()),
   ts: z.number(),
   args: z.any(),
+  idempotencyKey: z.optional(z.string()),
   version: z.string(),
 });
 
@@ -301,6 +302,9 @@ export class SyscallsImpl {
         case "1.0/actions/vectorSearch": {
           return JSON.stringify(await this.syscallVectorSearch(jsonArgs));
         }
+        case "1.0/actions/hybridSearch": {
+          return JSON.stringify(await this.syscallHybridSearch(jsonArgs));
+        }
         case "1.0/schedule":
           throw new Error(
             "The mutation scheduler is being used outside of a Convex mutation. Did" +
@@ -311,6 +315,12 @@ export class SyscallsImpl {
           return JSON.stringify(await this.syscallSchedule(jsonArgs));
         case "1.0/actions/cancel_job":
           return JSON.stringify(await this.syscallCancelJob(jsonArgs));
+        case "1.0/actions/queue/lease":
+          return JSON.stringify(await this.syscallQueueLease(jsonArgs));
+        case "1.0/actions/queue/ack":
+          return JSON.stringify(await this.syscallQueueAck(jsonArgs));
+        case "1.0/actions/queue/nack":
+          return JSON.stringify(await this.syscallQueueNack(jsonArgs));
         case "1.0/getUserIdentity":
           return JSON.stringify(this.syscallGetUserIdentity(jsonArgs));
         case "1.0/storageGenerateUploadUrl": {
@@ -512,6 +522,29 @@ export class SyscallsImpl {
     });
   }
 
+  async syscallHybridSearch(rawArgs: string): Promise<JSONValue> {
+    const hybridSearchSchema = z.object({
+      query: z.any(),
+      version: z.string(),
+    });
+    const hybridSearchReturn = z.object({
+      results: z.array(z.any()),
+    });
+    const operationName = "hybrid search";
+    const hybridSearchArgs = this.validateArgs(
+      rawArgs,
+      hybridSearchSchema,
+      operationName,
+    );
+    return this.actionCallback({
+      version: hybridSearchArgs.version,
+      body: { query: hybridSearchArgs.query },
+      path: "/api/actions/hybrid_search",
+      operationName,
+      responseValidator: hybridSearchReturn,
+    });
+  }
+
   async syscallSchedule(rawArgs: string): Promise<JSONValue> {
     const scheduleReturn = z.object({
       jobId: z.string(),
@@ -530,6 +563,7 @@ export class SyscallsImpl {
         udfPath: scheduleArgs.name,
         udfArgs: scheduleArgs.args,
         scheduledTs: scheduleArgs.ts,
+        idempotencyKey: scheduleArgs.idempotencyKey,
       },
       path: "/api/actions/schedule_job",
       operationName,
@@ -557,6 +591,69 @@ export class SyscallsImpl {
     return null;
   }
 
+  async syscallQueueLease(rawArgs: string): Promise<JSONValue> {
+    const queueLeaseSchema = z.object({
+      queue: z.string(),
+      maxMessages: z.optional(z.number()),
+      visibilityTimeoutMs: z.optional(z.number()),
+      version: z.string(),
+    });
+    const operationName = "lease queue messages";
+    const args = this.validateArgs(rawArgs, queueLeaseSchema, operationName);
+    return await this.actionCallback({
+      version: args.version,
+      body: {
+        queue: args.queue,
+        maxMessages: args.maxMessages,
+        visibilityTimeoutMs: args.visibilityTimeoutMs,
+      },
+      path: "/api/actions/queue_lease",
+      operationName,
+      responseValidator: z.array(
+        z.object({
+          receipt: z.string(),
+          payload: z.any(),
+          attempts: z.number(),
+        }),
+      ),
+    });
+  }
+
+  async syscallQueueAck(rawArgs: string): Promise<JSONValue> {
+    const queueAckSchema = z.object({
+      receipt: z.string(),
+      version: z.string(),
+    });
+    const operationName = "ack queue message";
+    const args = this.validateArgs(rawArgs, queueAckSchema, operationName);
+    await this.actionCallback({
+      version: args.version,
+      body: { receipt: args.receipt },
+      path: "/api/actions/queue_ack",
+      operationName,
+      responseValidator: z.any(),
+    });
+    return null;
+  }
+
+  async syscallQueueNack(rawArgs: string): Promise<JSONValue> {
+    const queueNackSchema = z.object({
+      receipt: z.string(),
+      delayMs: z.optional(z.number()),
+      version: z.string(),
+    });
+    const operationName = "nack queue message";
+    const args = this.validateArgs(rawArgs, queueNackSchema, operationName);
+    await this.actionCallback({
+      version: args.version,
+      body: { receipt: args.receipt, delayMs: args.delayMs },
+      path: "/api/actions/queue_nack",
+      operationName,
+      responseValidator: z.any(),
+    });
+    return null;
+  }
+
   syscallGetUserIdentity(rawArgs: string): JSONValue {
     this.validateArgs(rawArgs, z.any(), "get user identity");
     return this.userIdentity as JSONValue;
@@ -4,8 +4,11 @@ import {
   DocumentByInfo,
   DocumentByName,
   Expression,
+  FieldPaths,
   FilterBuilder,
   GenericDataModel,
+  GroupByAggregate,
+  GroupByResult,
   GenericTableInfo,
   IndexRange,
   IndexRangeBuilder,
@@ -163,6 +166,20 @@ class WrapQuery<T extends GenericTableInfo> implements Query<T> {
   order(order: "asc" | "desc"): WrapQuery<T> {
     return new WrapQuery(this.q.order(order), this.p);
   }
+  estimatedCount(): Promise<number> {
+    return this.q.estimatedCount();
+  }
+  approxDistinct(field: FieldPaths<T>): Promise<number> {
+    return this.q.approxDistinct(field);
+  }
+  async groupBy<Aggregates extends Record<string, GroupByAggregate<T>>>(
+    _fields: FieldPaths<T>[],
+    _aggregates: Aggregates,
+  ): Promise<GroupByResult<Aggregates>[]> {
+    // Aggregates are computed without reading documents into the function,
+    // so the read rule can't be applied to them.
+    throw new Error("groupBy() isn't supported with row level security");
+  }
   async paginate(
     paginationOpts: PaginationOptions,
   ): Promise<PaginationResult<DocumentByInfo<T>>> {
@@ -190,6 +207,9 @@ class WrapQuery<T extends GenericTableInfo> implements Query<T> {
     }
     return null;
   }
+  prefetch(numItems?: number): void {
+    this.q.prefetch(numItems);
+  }
   async unique(): Promise<DocumentByInfo<T> | null> {
     let uniqueResult = null;
     for await (const result of this) {
@@ -268,6 +288,21 @@ class WrapQueryInitializer<T extends GenericTableInfo>
   order(order: "asc" | "desc"): OrderedQuery<T> {
     return this.fullTableScan().order(order);
   }
+  async sample(n: number): Promise<DocumentByInfo<T>[]> {
+    return await asyncFilter(await this.q.sample(n), this.p);
+  }
+  estimatedCount(): Promise<number> {
+    return this.fullTableScan().estimatedCount();
+  }
+  approxDistinct(field: FieldPaths<T>): Promise<number> {
+    return this.fullTableScan().approxDistinct(field);
+  }
+  groupBy<Aggregates extends Record<string, GroupByAggregate<T>>>(
+    fields: FieldPaths<T>[],
+    aggregates: Aggregates,
+  ): Promise<GroupByResult<Aggregates>[]> {
+    return this.fullTableScan().groupBy(fields, aggregates);
+  }
   async paginate(
     paginationOpts: PaginationOptions,
   ): Promise<PaginationResult<DocumentByInfo<T>>> {
@@ -282,6 +317,9 @@ class WrapQueryInitializer<T extends GenericTableInfo>
   first(): Promise<DocumentByInfo<T> | null> {
     return this.fullTableScan().first();
   }
+  prefetch(numItems?: number): void {
+    this.fullTableScan().prefetch(numItems);
+  }
   unique(): Promise<DocumentByInfo<T> | null> {
     return this.fullTableScan().unique();
   }
@@ -329,6 +367,10 @@ class WrapReader<Ctx, DataModel extends GenericDataModel>
     return null;
   }
 
+  prefetch<TableName extends string>(ids: GenericId<TableName>[]): void {
+    this.db.prefetch(ids);
+  }
+
   query<TableName extends string>(
     tableName: TableName,
   ): QueryInitializer<NamedTableInfo<DataModel, TableName>> {
@@ -435,6 +477,9 @@ class WrapWriter<Ctx, DataModel extends GenericDataModel>
   get<TableName extends string>(id: GenericId<TableName>): Promise<any> {
     return this.reader.get(id);
   }
+  prefetch<TableName extends string>(ids: GenericId<TableName>[]): void {
+    this.reader.prefetch(ids);
+  }
   query<TableName extends string>(tableName: TableName): QueryInitializer<any> {
     return this.reader.query(tableName);
   }
@@ -15,6 +15,8 @@ export type Shape =
   | { type: "Boolean" }
   | { type: "String" }
   | { type: "Bytes" }
+  | { type: "Decimal" }
+  | { type: "DateTime" }
   | {
       type: "Object";
       fields: Array<{ fieldName: string; optional: boolean; shape: Shape }>;
@@ -48,6 +50,8 @@ export const shapeSchema: z.ZodType<Shape> = z.lazy(() =>
     z.object({ type: z.literal("Boolean") }),
     z.object({ type: z.literal("String") }),
     z.object({ type: z.literal("Bytes") }),
+    z.object({ type: z.literal("Decimal") }),
+    z.object({ type: z.literal("DateTime") }),
     z.object({
       type: z.literal("Object"),
       fields: z.array(
@@ -95,6 +99,10 @@ export function stringifyShape(shape: Shape): string {
       return "boolean";
     case "Bytes":
       return "ArrayBuffer";
+    case "Decimal":
+      return "Decimal";
+    case "DateTime":
+      return "DateTime";
     case "Float64":
       return "number";
     case "Id":
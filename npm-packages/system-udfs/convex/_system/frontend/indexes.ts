@@ -0,0 +1,19 @@
+import { v } from "convex/values";
+import { queryGeneric } from "../secretSystemTables";
+
+/**
+ * Lists the indexes on user tables across all components, optionally only
+ * those on one table. `_indexes` is read from the root component since it
+ * covers every component's tables.
+ */
+export default queryGeneric({
+  args: { tableName: v.optional(v.string()) },
+  handler: async ({ db }, { tableName }) => {
+    const indexes = await db.system.query("_indexes").collect();
+    return indexes.filter(
+      (index) =>
+        !index.table.startsWith("_") &&
+        (tableName === undefined || index.table === tableName),
+    );
+  },
+});
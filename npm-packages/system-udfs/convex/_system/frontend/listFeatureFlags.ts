@@ -0,0 +1,13 @@
+import { Doc } from "../../_generated/dataModel";
+import { queryPrivateSystem } from "../secretSystemTables";
+
+export default queryPrivateSystem({
+  args: {},
+  handler: async ({ db }): Promise<Doc<"_feature_flags">[]> => {
+    return await db
+      .query("_feature_flags")
+      .withIndex("by_name")
+      .order("asc")
+      .collect();
+  },
+});
@@ -0,0 +1,38 @@
+import { v } from "convex/values";
+import { Doc } from "../../_generated/dataModel";
+import { queryPrivateSystem } from "../secretSystemTables";
+
+const DEFAULT_LIMIT = 100;
+const MAX_LIMIT = 1000;
+
+export default queryPrivateSystem({
+  args: {
+    udfPath: v.optional(v.string()),
+    outcome: v.optional(v.union(v.literal("success"), v.literal("failure"))),
+    limit: v.optional(v.number()),
+  },
+  handler: async (
+    { db },
+    { udfPath, outcome, limit },
+  ): Promise<Doc<"_function_runs">[]> => {
+    const take = Math.min(limit ?? DEFAULT_LIMIT, MAX_LIMIT);
+    if (udfPath !== undefined) {
+      let runs = db
+        .query("_function_runs")
+        .withIndex("by_udf_path", (q) => q.eq("udfPath", udfPath))
+        .order("desc");
+      if (outcome !== undefined) {
+        runs = runs.filter((q) => q.eq(q.field("outcome.type"), outcome));
+      }
+      return await runs.take(take);
+    }
+    if (outcome !== undefined) {
+      return await db
+        .query("_function_runs")
+        .withIndex("by_outcome", (q) => q.eq("outcome.type", outcome))
+        .order("desc")
+        .take(take);
+    }
+    return await db.query("_function_runs").order("desc").take(take);
+  },
+});
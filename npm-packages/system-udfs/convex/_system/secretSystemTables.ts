@@ -15,6 +15,7 @@ import { Id } from "../_generated/dataModel";
 const VIRTUAL_TABLES: Set<TableNamesInDataModel<SystemDataModel>> = new Set([
   "_storage",
   "_scheduled_functions",
+  "_indexes",
 ]);
 
 function isValidVirtualTable(table: string) {
@@ -2,6 +2,7 @@ import { defineSchema, defineTable } from "convex/server";
 import { v } from "convex/values";
 import deploymentAuditLogTable, {
   deploymentState,
+  indexMetadata,
 } from "./tableDefs/deploymentAuditLogTable";
 import { snapshotImportsTable } from "./tableDefs/snapshotImport";
 
@@ -346,6 +347,354 @@ export default defineSchema({
     }),
     executionTime: v.number(),
   }).index("by_name_and_ts", ["name", "ts"]),
+  _function_runs: defineTable({
+    componentPath: v.string(),
+    udfPath: v.string(),
+    udfType: v.union(
+      v.literal("query"),
+      v.literal("mutation"),
+      v.literal("action"),
+      v.literal("http_action"),
+    ),
+    caller: v.string(),
+    requestId: v.string(),
+    executionId: v.string(),
+    startedAtMs: v.int64(),
+    durationMs: v.int64(),
+    outcome: v.union(
+      v.object({ type: v.literal("success") }),
+      v.object({ type: v.literal("failure"), error: v.string() }),
+    ),
+    documentsRead: v.int64(),
+    bytesRead: v.int64(),
+    documentsWritten: v.int64(),
+    bytesWritten: v.int64(),
+    logLines: v.array(v.string()),
+    logLinesTruncated: v.boolean(),
+  })
+    .index("by_udf_path", ["udfPath"])
+    .index("by_outcome", ["outcome.type"]),
+  _function_usage: defineTable({
+    windowStartMs: v.int64(),
+    componentPath: v.string(),
+    udfPath: v.string(),
+    calls: v.int64(),
+    failures: v.int64(),
+    durationMs: v.int64(),
+    memoryMbMs: v.int64(),
+    databaseReadBytes: v.int64(),
+    databaseWriteBytes: v.int64(),
+    documentsRead: v.int64(),
+    storageIngressBytes: v.int64(),
+    storageEgressBytes: v.int64(),
+    vectorReadBytes: v.int64(),
+    vectorWriteBytes: v.int64(),
+  }).index("by_window_and_udf_path", [
+    "windowStartMs",
+    "componentPath",
+    "udfPath",
+  ]),
+  _table_usage: defineTable({
+    windowStartMs: v.int64(),
+    componentPath: v.string(),
+    tableName: v.string(),
+    readBytes: v.int64(),
+    writeBytes: v.int64(),
+    documentsRead: v.int64(),
+    vectorReadBytes: v.int64(),
+    vectorWriteBytes: v.int64(),
+  }).index("by_window_and_table_name", [
+    "windowStartMs",
+    "componentPath",
+    "tableName",
+  ]),
+  _error_groups: defineTable({
+    fingerprint: v.string(),
+    componentPath: v.string(),
+    udfPath: v.string(),
+    message: v.string(),
+    count: v.int64(),
+    firstSeenMs: v.int64(),
+    lastSeenMs: v.int64(),
+    samples: v.array(
+      v.object({
+        timestampMs: v.int64(),
+        requestId: v.string(),
+        message: v.string(),
+        stack: v.string(),
+      }),
+    ),
+  })
+    .index("by_fingerprint", ["fingerprint"])
+    .index("by_last_seen", ["lastSeenMs"]),
+  _schema_history: defineTable({
+    componentPath: v.string(),
+    schemaId: v.union(v.string(), v.null()),
+    schemaValidation: v.boolean(),
+    addedTables: v.array(v.string()),
+    removedTables: v.array(v.string()),
+    changedValidators: v.array(
+      v.object({
+        tableName: v.string(),
+        previous: v.union(v.string(), v.null()),
+        next: v.union(v.string(), v.null()),
+      }),
+    ),
+    addedIndexes: v.array(
+      v.object({
+        index: indexMetadata,
+        outcome: v.union(
+          v.literal("backfilling"),
+          v.literal("backfilled"),
+          v.literal("enabled"),
+          v.null(),
+        ),
+      }),
+    ),
+    removedIndexes: v.array(indexMetadata),
+  }).index("by_component_path", ["componentPath"]),
+  _search_synonyms: defineTable({
+    indexId: v.string(),
+    synonyms: v.array(v.array(v.string())),
+  }).index("by_index_id", ["indexId"]),
+  _vector_index_migrations: defineTable({
+    sourceIndexId: v.string(),
+    targetIndexId: v.string(),
+    reembedAction: v.string(),
+    batchSize: v.int64(),
+    state: v.union(
+      v.literal("backfilling"),
+      v.literal("readyForCutover"),
+      v.literal("cutOver"),
+      v.literal("failed"),
+      v.literal("canceled"),
+    ),
+    error: v.optional(v.string()),
+    cursor: v.union(v.float64(), v.null()),
+    documentsScanned: v.int64(),
+    documentsReembedded: v.int64(),
+  }).index("by_source_index_id", ["sourceIndexId"]),
+  _vector_index_stats: defineTable({
+    indexId: v.string(),
+    segmentCount: v.int64(),
+    segmentSizes: v.array(v.int64()),
+    deletedVectors: v.int64(),
+    segmentBytes: v.int64(),
+    memoryIndexBytes: v.int64(),
+    queryCount: v.int64(),
+    averageQueryLatencyMs: v.union(v.float64(), v.null()),
+    recall: v.union(v.float64(), v.null()),
+    recallSampledAtMs: v.union(v.int64(), v.null()),
+  }).index("by_index_id", ["indexId"]),
+  _vector_embedding_jobs: defineTable({
+    tabletId: v.string(),
+    documentId: v.string(),
+    indexDescriptor: v.string(),
+    attempts: v.int64(),
+    nextAttemptMs: v.int64(),
+    state: v.union(v.literal("pending"), v.literal("failed")),
+    error: v.optional(v.string()),
+  }).index("by_state_and_next_attempt", ["state", "nextAttemptMs"]),
+  _index_statistics: defineTable({
+    indexId: v.string(),
+    count: v.int64(),
+    samples: v.array(v.bytes()),
+    distinct: v.array(v.object({ field: v.string(), sketch: v.bytes() })),
+    computedAtMs: v.int64(),
+  }).index("by_index_id", ["indexId"]),
+  _table_freezes: defineTable({
+    tabletId: v.string(),
+    reason: v.string(),
+    frozenAtMs: v.int64(),
+    expiresAtMs: v.int64(),
+  }).index("by_tablet_id", ["tabletId"]),
+  _component_quotas: defineTable({
+    componentId: v.string(),
+    maxDocuments: v.union(v.int64(), v.null()),
+    maxDocumentBytes: v.union(v.int64(), v.null()),
+    maxFunctionCallsPerMinute: v.union(v.int64(), v.null()),
+    maxScheduledJobs: v.union(v.int64(), v.null()),
+  }).index("by_component_id", ["componentId"]),
+  _canary_deployments: defineTable({
+    sourcePackageId: v.string(),
+    trafficPercent: v.int64(),
+    routing: v.union(v.literal("invocation"), v.literal("identity")),
+    rollbackPolicy: v.object({
+      minInvocations: v.int64(),
+      maxErrorRateIncrease: v.float64(),
+    }),
+    state: v.union(
+      v.object({ type: v.literal("active") }),
+      v.object({ type: v.literal("rolledBack"), reason: v.string() }),
+    ),
+    startedAtMs: v.int64(),
+  }),
+  _canary_modules: defineTable({
+    path: v.string(),
+    sourcePackageId: v.string(),
+    environment: v.string(),
+    analyzeResult: v.union(analyzedModule, v.null()),
+    sha256: v.string(),
+  }).index("by_path", ["path"]),
+  _code_versions: defineTable({
+    sourcePackageId: v.string(),
+    rawSchema: v.union(v.string(), v.null()),
+    authInfo: v.array(v.any()),
+    udfServerVersion: v.string(),
+    pushedAtMs: v.int64(),
+  }),
+  _code_version_modules: defineTable({
+    versionId: v.string(),
+    module: v.object({
+      path: v.string(),
+      sourcePackageId: v.string(),
+      environment: v.string(),
+      analyzeResult: v.union(analyzedModule, v.null()),
+      sha256: v.string(),
+    }),
+  }).index("by_version", ["versionId"]),
+  _maintenance_mode: defineTable({
+    message: v.string(),
+    statusCode: v.int64(),
+    startedAtMs: v.int64(),
+  }),
+  _feature_flags: defineTable({
+    name: v.string(),
+    rule: v.union(
+      v.object({ type: v.literal("boolean"), enabled: v.boolean() }),
+      v.object({ type: v.literal("percentage"), percent: v.int64() }),
+      v.object({
+        type: v.literal("targeted"),
+        claim: v.string(),
+        values: v.array(v.string()),
+      }),
+    ),
+    updatedAtMs: v.int64(),
+  }).index("by_name", ["name"]),
+  _log_search_terms: defineTable({
+    term: v.string(),
+    source: v.union(v.literal("functionRun"), v.literal("auditLog")),
+    documentId: v.string(),
+    tsMs: v.int64(),
+  }).index("by_term_and_ts", ["term", "tsMs"]),
+  _queue_messages: defineTable({
+    queue: v.string(),
+    payload: v.bytes(),
+    visibleTs: v.union(v.int64(), v.null()),
+    leaseId: v.union(v.string(), v.null()),
+    attempts: v.int64(),
+    maxAttempts: v.int64(),
+  })
+    .index("by_queue_and_visible_ts", ["queue", "visibleTs"])
+    .index("by_lease_id", ["leaseId"]),
+  _read_set_advice: defineTable({
+    componentPath: v.string(),
+    udfPath: v.string(),
+    executions: v.int64(),
+    averageDocumentsRead: v.int64(),
+    averageBytesRead: v.int64(),
+    maxDocumentsRead: v.int64(),
+    maxBytesRead: v.int64(),
+    documentsLimit: v.int64(),
+    bytesLimit: v.int64(),
+    firstWarnedMs: v.int64(),
+    lastWarnedMs: v.int64(),
+    topTables: v.array(
+      v.object({ tableName: v.string(), documentsRead: v.int64() }),
+    ),
+  })
+    .index("by_udf_path", ["componentPath", "udfPath"])
+    .index("by_last_warned", ["lastWarnedMs"]),
+  _table_snapshots: defineTable({
+    componentId: v.union(v.string(), v.null()),
+    name: v.string(),
+    tableName: v.string(),
+    tabletId: v.string(),
+    snapshotTs: v.int64(),
+    createdAtMs: v.int64(),
+  }).index("by_name", ["componentId", "name"]),
+  _webhook_endpoints: defineTable({
+    name: v.string(),
+    url: v.string(),
+    secret: v.string(),
+    filters: v.array(
+      v.union(
+        v.object({
+          type: v.literal("documentChanges"),
+          tableNames: v.array(v.string()),
+        }),
+        v.object({ type: v.literal("functionFailures") }),
+        v.object({
+          type: v.literal("cronOutcomes"),
+          failuresOnly: v.boolean(),
+        }),
+        v.object({ type: v.literal("httpCheckAlerts") }),
+      ),
+    ),
+    documentCursor: v.int64(),
+  }).index("by_name", ["name"]),
+  _webhook_deliveries: defineTable({
+    endpointId: v.id("_webhook_endpoints"),
+    eventId: v.string(),
+    eventType: v.string(),
+    payload: v.string(),
+    attempts: v.int64(),
+    nextAttemptMs: v.int64(),
+    state: v.union(
+      v.literal("pending"),
+      v.literal("delivered"),
+      v.literal("failed"),
+    ),
+    status: v.optional(v.int64()),
+    lastError: v.optional(v.string()),
+  })
+    .index("by_state_and_next_attempt", ["state", "nextAttemptMs"])
+    .index("by_endpoint", ["endpointId"]),
+  _webhook_nonces: defineTable({
+    scope: v.string(),
+    nonce: v.string(),
+    expiresAtMs: v.int64(),
+  })
+    .index("by_scope_and_nonce", ["scope", "nonce"])
+    .index("by_expires_at", ["expiresAtMs"]),
+  _static_asset_mounts: defineTable({
+    pathPrefix: v.string(),
+    folder: v.string(),
+    cacheControl: v.union(v.string(), v.null()),
+    compress: v.boolean(),
+    spaFallback: v.union(v.string(), v.null()),
+  }).index("by_path_prefix", ["pathPrefix"]),
+  _static_assets: defineTable({
+    folder: v.string(),
+    path: v.string(),
+    storageId: v.string(),
+    sha256: v.string(),
+    size: v.int64(),
+    contentType: v.union(v.string(), v.null()),
+    cacheControl: v.union(v.string(), v.null()),
+  }).index("by_folder_and_path", ["folder", "path"]),
+  _http_checks: defineTable({
+    name: v.string(),
+    url: v.string(),
+    method: v.union(v.literal("GET"), v.literal("HEAD")),
+    intervalSecs: v.int64(),
+    timeoutSecs: v.int64(),
+    expectedStatus: v.union(v.int64(), v.null()),
+    failureThreshold: v.int64(),
+    nextRunMs: v.int64(),
+    consecutiveFailures: v.int64(),
+    healthy: v.boolean(),
+  })
+    .index("by_name", ["name"])
+    .index("by_next_run", ["nextRunMs"]),
+  _http_check_results: defineTable({
+    checkId: v.id("_http_checks"),
+    startedAtMs: v.int64(),
+    latencyMs: v.int64(),
+    status: v.union(v.int64(), v.null()),
+    ok: v.boolean(),
+    error: v.union(v.string(), v.null()),
+  }).index("by_check", ["checkId"]),
   _udf_config: defineTable({ serverVersion: v.string() }),
   _schemas: defineTable(schemaMetadata).index("by_state", ["state"]),
   _log_sinks: logSinksTable,
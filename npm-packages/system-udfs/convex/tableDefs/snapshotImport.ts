@@ -73,6 +73,7 @@ export const snapshotImportsTable = defineTable({
           existing_rows_in_table: v.int64(),
           existing_rows_to_delete: v.int64(),
           is_missing_id_field: v.boolean(),
+          last_imported_id: v.optional(v.union(v.string(), v.null())),
         }),
       ),
     ),
@@ -27,6 +27,20 @@ function toString(value: unknown, defaultValue: string) {
 function consoleMessage(level, args) {
   performOp("console/message", level, getMessage(args));
 }
+// Backs `ctx.log`. `fields` is already Convex JSON encoded.
+export function structuredLog(args: {
+  level: string;
+  message: string;
+  fields: Record<string, any>;
+}) {
+  performOp(
+    "console/structuredMessage",
+    args.level,
+    args.message,
+    args.fields,
+  );
+}
+
 const consoleImpl = {
   debug: function (...args) {
     consoleMessage("DEBUG", args);
@@ -11,7 +11,45 @@ import { performAsyncOp, performOp } from "./syscall";
 
 export { ReadableStream };
 
+type RustStreamSource = { streamId: string; state: "unread" | "read" | "taken" };
+
+// Streams backed by a stream in Rust (storage blobs, fetch response bodies).
+// While JS hasn't read from one, handing it back to Rust passes the Rust
+// stream by reference instead of copying every chunk through JS.
+const rustStreamSources = new WeakMap<ReadableStream, RustStreamSource>();
+
+/**
+ * Take the id of the Rust stream backing `stream`, if JS hasn't read from it.
+ * The stream can't be read from JS afterwards.
+ */
+export const takeRustStreamId = (stream: ReadableStream): string | null => {
+  const source = rustStreamSources.get(stream);
+  if (source === undefined || source.state !== "unread") {
+    return null;
+  }
+  source.state = "taken";
+  return source.streamId;
+};
+
+/**
+ * Record that `wrapper` only reads from `source`, so taking the Rust stream
+ * behind `wrapper` is equivalent to taking it from `source`.
+ */
+export const forwardRustStream = (
+  source: ReadableStream,
+  wrapper: ReadableStream,
+) => {
+  const rustSource = rustStreamSources.get(source);
+  if (rustSource !== undefined && rustSource.state === "unread") {
+    rustStreamSources.set(wrapper, rustSource);
+  }
+};
+
 export const constructStreamId = (stream: ReadableStream | null): string => {
+  const rustStreamId = stream !== null ? takeRustStreamId(stream) : null;
+  if (rustStreamId !== null) {
+    return rustStreamId;
+  }
   const streamId = performOp("stream/create");
   const reader = stream?.getReader();
   void populateStream();
@@ -31,9 +69,18 @@ export const constructStreamId = (stream: ReadableStream | null): string => {
 };
 
 export const extractStream = (streamId: string): ReadableStream => {
-  return new ReadableStream({
+  const source: RustStreamSource = { streamId, state: "unread" };
+  const stream = new ReadableStream({
     type: "bytes",
     async pull(controller) {
+      if (source.state === "taken") {
+        return controller.error(
+          new TypeError(
+            "Stream was passed to Convex by reference and can no longer be read",
+          ),
+        );
+      }
+      source.state = "read";
       // eslint-disable-next-line no-constant-condition
       while (true) {
         const { value, done } = await performAsyncOp(
@@ -48,6 +95,8 @@ export const extractStream = (streamId: string): ReadableStream => {
       }
     },
   });
+  rustStreamSources.set(stream, source);
+  return stream;
 };
 
 // For testing.
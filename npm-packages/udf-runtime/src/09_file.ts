@@ -4,7 +4,7 @@
 
 import { throwNotImplementedMethodError } from "./helpers";
 import { performOp } from "./syscall";
-import { ReadableStream } from "./06_streams";
+import { ReadableStream, forwardRustStream } from "./06_streams";
 
 async function* toIterator(
   parts: (BlobReference | BlobStreamReference | Blob)[],
@@ -251,7 +251,27 @@ export class Blob {
 
   stream(): ReadableStream<Uint8Array> {
     const partIterator = toIterator(this._parts);
-    return iteratorToReadableStream(partIterator);
+    const stream = iteratorToReadableStream(partIterator);
+    const source = this._singleSourceStream();
+    if (source !== null) {
+      forwardRustStream(source, stream);
+    }
+    return stream;
+  }
+
+  // The stream this blob's bytes come from, if they all come from one stream.
+  private _singleSourceStream(): ReadableStream<Uint8Array> | null {
+    if (this._parts.length !== 1) {
+      return null;
+    }
+    const part = this._parts[0];
+    if (part instanceof Blob) {
+      return part._singleSourceStream();
+    }
+    if (part instanceof BlobStreamReference && part.size === this._size) {
+      return part.stream();
+    }
+    return null;
   }
 
   private static _processBlobParts(
@@ -330,6 +350,7 @@ export class Blob {
     // so `Request.body.locked` is true, while still retaining the ability to return
     // an unlocked stream from `Blob.stream()`.
     const newStream = iteratorToReadableStream(stream[Symbol.asyncIterator]());
+    forwardRustStream(stream, newStream);
     blob._parts = [new BlobStreamReference(newStream, size)];
     blob._size = size;
     return blob;
@@ -1,7 +1,7 @@
 import { setupURL } from "./00_url.js";
 import { setupCrypto } from "./00_crypto.js";
 import { setupDOMException } from "./01_dom_exception.js";
-import { setupConsole } from "./02_console";
+import { setupConsole, structuredLog } from "./02_console";
 import { setupEvent } from "./02_event";
 import { setupTimers } from "./02_timers.js";
 import { setupAbortSignal } from "./03_abort_signal.js";
@@ -62,6 +62,8 @@ export function setup(global: any) {
         return storeBlob(args as any);
       case "storage/getBlob":
         return getBlob(args as any);
+      case "console/structuredLog":
+        return structuredLog(args as any);
       // Deprecated APIs, used prior to Convex 0.13.0
       case "storage/storeFile":
         return storeRequest(args as any);
@@ -1,3 +1,4 @@
+import { arrayPatch } from "convex/server";
 import { Id } from "./_generated/dataModel";
 import { mutation, query, action } from "./_generated/server";
 
@@ -53,6 +54,24 @@ export const patchObject = mutation(
   },
 );
 
+export const deepPatchObject = mutation(
+  async ({ db }, { id }: { id: Id<any> }) => {
+    await db.deepPatch(id, {
+      "profile.settings.theme": "dark",
+      tags: arrayPatch.push("c"),
+      items: arrayPatch.pullWhere((q) => q.eq(q.field("done"), true)),
+      stale: undefined,
+    });
+    return await db.get(id);
+  },
+);
+
+export const deepPatchCreationTime = mutation(
+  async ({ db }, { id }: { id: Id<any> }) => {
+    await db.deepPatch(id, { _creationTime: 1017 });
+  },
+);
+
 export const deleteObjectField = mutation(
   async ({ db }, { id, fieldName }: { id: Id<any>; fieldName: string }) => {
     const patchValue: any = {};
@@ -69,6 +88,27 @@ export const replaceObject = mutation(
   },
 );
 
+export const replaceObjectIfVersion = mutation(
+  async (
+    { db },
+    { id, version, obj }: { id: Id<any>; version: bigint; obj: any },
+  ) => {
+    return await db.replaceIfVersion(id, version, obj);
+  },
+);
+
+export const getObjectVersion = query(
+  async ({ db }, { id }: { id: Id<any> }) => {
+    return await db.getVersion(id);
+  },
+);
+
+// Reads a document and writes it back unchanged.
+export const rewriteObject = mutation(async ({ db }, { id }: { id: Id<any> }) => {
+  const doc = await db.get(id);
+  await db.replace(id, doc!);
+});
+
 // Add and deletes the same object in the single mutation.
 export const insertAndDeleteObject = mutation(async ({ db }, obj: any) => {
   const id = await db.insert("objects", obj);
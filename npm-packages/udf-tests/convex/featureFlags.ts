@@ -0,0 +1,16 @@
+import { v } from "convex/values";
+import { mutation, query } from "./_generated/server";
+
+export const isEnabled = query({
+  args: { name: v.string() },
+  handler: async (ctx, { name }) => {
+    return await ctx.flags.get(name);
+  },
+});
+
+export const isEnabledInMutation = mutation({
+  args: { name: v.string() },
+  handler: async (ctx, { name }) => {
+    return await ctx.flags.get(name);
+  },
+});
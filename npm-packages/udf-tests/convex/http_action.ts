@@ -1,4 +1,9 @@
-import { httpRouter } from "convex/server";
+import {
+  cors,
+  getPathParams,
+  httpRouter,
+  rateLimit,
+} from "convex/server";
 import { imported } from "./http_no_default";
 import { api } from "./_generated/api";
 import { httpAction, query } from "./_generated/server";
@@ -72,6 +77,14 @@ const echo = httpAction(async (_ctx, request) => {
   return new Response(await request.blob());
 });
 
+// Serves a file from storage, which passes the stored bytes to the response
+// without reading them in JS.
+const storeAndServe = httpAction(async (ctx, request) => {
+  const storageId = await ctx.storage.store(await request.blob());
+  const blob = await ctx.storage.get(storageId);
+  return new Response(blob);
+});
+
 const schedule = httpAction(async ({ scheduler }, _request) => {
   await scheduler.runAfter(2000, api.basic.insertObject, { foo: "bar" });
   return new Response();
@@ -144,6 +157,11 @@ http.route({
   path: "/echo",
   handler: echo,
 });
+http.route({
+  method: "POST",
+  path: "/store_and_serve",
+  handler: storeAndServe,
+});
 http.route({
   method: "GET",
   path: "/schedule",
@@ -258,6 +276,29 @@ http.route({
   }),
 });
 
+http.route({
+  method: "POST",
+  path: "/githubWebhook",
+  handler: httpAction(async (_, request: Request) => {
+    // Verification reads a clone, so the body is still readable here.
+    return new Response(`Received ${await request.text()}`);
+  }),
+  verify: { scheme: "github", secretEnvVar: "GITHUB_WEBHOOK_SECRET" },
+});
+
+http.route({
+  method: "GET",
+  path: "/items/:id",
+  handler: httpAction(async (_, request: Request) => {
+    const { id } = getPathParams<"/items/:id">(request);
+    return new Response(`Item ${id}`);
+  }),
+  middleware: [
+    cors({ allowedOrigins: ["https://example.com"] }),
+    rateLimit({ requests: 2, periodSeconds: 60, key: () => "everyone" }),
+  ],
+});
+
 export const erroringQuery = query(() => {
   throw new Error("Oh no! Called erroring query");
 });
@@ -30,6 +30,10 @@ export const insert = mutation(
   },
 );
 
+export const listIndexes = query(async ({ db }) => {
+  return await db.system.query("_indexes").collect();
+});
+
 export const insertMissingField = mutation(
   async ({ db }, { a }: { a: number }) => {
     await db.insert("myTable", {
@@ -6,6 +6,11 @@ export const logString = query(() => {
   console.log("myString");
 });
 
+export const logStructured = query((ctx) => {
+  ctx.log.info("charged", { amount: 42, user: "alice" });
+  ctx.log.warn({ retries: 3n });
+});
+
 export const logNumber = query(() => {
   console.log(42);
 });
@@ -70,6 +70,18 @@ export const parallelGetAndQuery = query(
   },
 );
 
+export const prefetchThenGet = query(
+  async ({ db }, { ids }: { ids: Id<"test">[] }) => {
+    db.prefetch(ids);
+    db.query("test").prefetch(ids.length);
+    const documents = [];
+    for (const id of ids) {
+      documents.push(await db.get(id));
+    }
+    return documents;
+  },
+);
+
 export const explicitScan = query(({ db }, { number }: { number: number }) => {
   return db
     .query("test")
@@ -100,6 +112,19 @@ export const falseLiteralFilter = query(({ db }) =>
     .collect(),
 );
 
+export const parallelGroupBy = query(async ({ db }) => {
+  return await Promise.all([
+    db
+      .query("test")
+      .withIndex("by_hello", (q) => q.lt("hello", 2))
+      .groupBy(["hello"], { count: { op: "count" } }),
+    db
+      .query("test")
+      .withIndex("by_hello", (q) => q.gt("hello", 3))
+      .groupBy(["hello"], { count: { op: "count" } }),
+  ]);
+});
+
 export const paginateTableScan = query({
   args: { paginationOpts: paginationOptsValidator },
   handler: async ({ db }, { paginationOpts }) => {
@@ -0,0 +1,48 @@
+import { v } from "convex/values";
+import { action, mutation } from "./_generated/server";
+
+export const enqueue = mutation({
+  args: { queue: v.string(), payload: v.any(), maxAttempts: v.optional(v.number()) },
+  handler: async (ctx, { queue, payload, maxAttempts }) => {
+    await ctx.queue.enqueue(queue, payload, { maxAttempts });
+  },
+});
+
+export const lease = mutation({
+  args: { queue: v.string(), maxMessages: v.optional(v.number()) },
+  handler: async (ctx, { queue, maxMessages }) => {
+    return await ctx.queue.lease(queue, { maxMessages });
+  },
+});
+
+export const ack = mutation({
+  args: { receipt: v.string() },
+  handler: async (ctx, { receipt }) => {
+    await ctx.queue.ack(receipt);
+  },
+});
+
+export const nack = mutation({
+  args: { receipt: v.string() },
+  handler: async (ctx, { receipt }) => {
+    await ctx.queue.nack(receipt);
+  },
+});
+
+export const redrive = mutation({
+  args: { queue: v.string() },
+  handler: async (ctx, { queue }) => {
+    return await ctx.queue.redrive(queue);
+  },
+});
+
+export const leaseAndAckInAction = action({
+  args: { queue: v.string() },
+  handler: async (ctx, { queue }) => {
+    const messages = await ctx.queue.lease(queue, { maxMessages: 10 });
+    for (const message of messages) {
+      await ctx.queue.ack(message.receipt);
+    }
+    return messages.map((message) => message.payload);
+  },
+});
@@ -1,4 +1,5 @@
 import { v } from "convex/values";
+import { api } from "./_generated/api";
 import { action, mutation, query } from "./_generated/server";
 
 export const extraOutputFields = query({
@@ -56,3 +57,44 @@ export const stringOutputReturnsStringAction = action({
     return "hello";
   },
 });
+
+export const runQueryWithBadOutput = query({
+  args: { validateReturns: v.boolean() },
+  handler: async (ctx, { validateReturns }): Promise<any> => {
+    return await ctx.runQuery(
+      api.returns_validation.stringOutputReturnsNumberQuery,
+      {},
+      { validateReturns },
+    );
+  },
+});
+
+export const runMutationWithBadOutput = mutation({
+  args: { validateReturns: v.boolean() },
+  handler: async (ctx, { validateReturns }): Promise<any> => {
+    return await ctx.runMutation(
+      api.returns_validation.stringOutputReturnsNumberMutation,
+      {},
+      { validateReturns },
+    );
+  },
+});
+
+export const insertDocument = mutation({
+  args: {},
+  handler: async (ctx) => {
+    await ctx.db.insert("objects", { field: "value" });
+  },
+});
+
+export const readDocument = query({
+  args: {},
+  returns: v.object({
+    _id: v.id("objects"),
+    _creationTime: v.number(),
+    field: v.string(),
+  }),
+  handler: async (ctx) => {
+    return (await ctx.db.query("objects").first())!;
+  },
+});
@@ -48,6 +48,17 @@ export const scheduleByName = mutation(
   },
 );
 
+export const scheduleWithIdempotencyKey = mutation(
+  async (
+    { scheduler },
+    { idempotencyKey, obj }: { idempotencyKey: string; obj: any },
+  ) => {
+    return await scheduler
+      .withIdempotencyKey(idempotencyKey)
+      .runAfter(1000, api.basic.insertObject, obj);
+  },
+);
+
 export const scheduleMany = mutation(
   async ({ scheduler }, { limit, obj }: { limit: number; obj: any }) => {
     for (let i = 0; i < limit; i++) {
@@ -97,3 +108,15 @@ export const scheduleByString = action({
     return jobPath;
   },
 });
+
+export const incrementBy = mutation({
+  args: { amount: v.number() },
+  handler: async () => {},
+});
+
+export const scheduleWithInvalidArgs = mutation(async ({ scheduler }) => {
+  const incrementBy = makeFunctionReference<"mutation">(
+    "scheduler:incrementBy",
+  );
+  await scheduler.runAfter(1000, incrementBy, { amount: "one" });
+});
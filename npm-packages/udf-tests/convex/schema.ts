@@ -33,9 +33,14 @@ export default defineSchema({
     filterA: v.string(),
     filterB: v.boolean(),
     id: v.string(),
-  }).vectorIndex("vector", {
-    vectorField: "vector",
-    dimensions: 4,
-    filterFields: ["filterA", "filterB"],
-  }),
+  })
+    .vectorIndex("vector", {
+      vectorField: "vector",
+      dimensions: 4,
+      filterFields: ["filterA", "filterB"],
+    })
+    .searchIndex("text", {
+      searchField: "id",
+      filterFields: ["filterA"],
+    }),
 });
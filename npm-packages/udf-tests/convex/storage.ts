@@ -16,6 +16,23 @@ export const getFile = action({
   },
 });
 
+export const copyFile = action({
+  args: { id: v.id("_storage") },
+  handler: async (ctx, { id }) => {
+    const blob = await ctx.storage.get(id);
+    return ctx.storage.store(blob!);
+  },
+});
+
+export const readCopiedBlob = action({
+  args: { id: v.id("_storage") },
+  handler: async (ctx, { id }) => {
+    const blob = await ctx.storage.get(id);
+    await ctx.storage.store(blob!);
+    return await blob!.arrayBuffer();
+  },
+});
+
 export const getFileUrl = query({
   args: { id: v.id("_storage") },
   handler: async (ctx, { id }) => {
@@ -130,3 +130,46 @@ export const noFilter = action({
     return "success";
   },
 });
+
+export const hybridSearch = action({
+  args: {},
+  handler: async (ctx) => {
+    const result = await ctx.hybridSearch("vectorTable", {
+      textIndex: "text",
+      textSearch: (q) => q.search("id", "doc2"),
+      vectorIndex: "vector",
+      vector: [1, 2, 3, 4],
+      vectorFilter: (q) => q.eq("filterB", true),
+      limit: 3,
+    });
+    const docs = await ctx.runQuery(api.vector_search.getDocuments, {
+      ids: result.map((r) => r._id),
+    });
+    // doc2 is the only document in both rankings, and doc3 doesn't match the
+    // vector filter or the text search.
+    assert.equal(docs[0].id, "doc2");
+    assert.deepEqual(["doc1", "doc2", "doc4"], docs.map((d) => d.id).sort());
+    return "success";
+  },
+});
+
+export const hybridSearchWeighted = action({
+  args: {},
+  handler: async (ctx) => {
+    const result = await ctx.hybridSearch("vectorTable", {
+      textIndex: "text",
+      textSearch: (q) => q.search("id", "doc3"),
+      vectorIndex: "vector",
+      vector: [1, 2, 3, 4],
+      fusion: { type: "weighted", textWeight: 1, vectorWeight: 0 },
+    });
+    const docs = await ctx.runQuery(api.vector_search.getDocuments, {
+      ids: result.map((r) => r._id),
+    });
+    assert.equal(docs.length, 4);
+    assert.equal(docs[0].id, "doc3");
+    assert.equal(result[0]._score, 1);
+    assert.equal(result[1]._score, 0);
+    return "success";
+  },
+});
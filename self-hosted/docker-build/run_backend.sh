@@ -68,6 +68,12 @@ exec ./convex-local-backend "$@" \
     ${DISABLE_BEACON:+--disable-beacon} \
     ${REDACT_LOGS_TO_CLIENT:+--redact-logs-to-client} \
     ${DO_NOT_REQUIRE_SSL:+--do-not-require-ssl} \
+    ${ADMIN_IP_ALLOWLIST:+--admin-ip-allowlist "$ADMIN_IP_ALLOWLIST"} \
+    ${TRUSTED_PROXY_CIDRS:+--trusted-proxy-cidrs "$TRUSTED_PROXY_CIDRS"} \
+    ${CORS_ALLOWED_ORIGINS:+--cors-allowed-origins "$CORS_ALLOWED_ORIGINS"} \
+    ${OTEL_EXPORTER_OTLP_ENDPOINT:+--otlp-endpoint "$OTEL_EXPORTER_OTLP_ENDPOINT"} \
+    ${OTEL_EXPORTER_OTLP_HEADERS:+--otlp-headers "$OTEL_EXPORTER_OTLP_HEADERS"} \
+    ${OTEL_TRACES_SAMPLER_ARG:+--otlp-trace-sample-ratio "$OTEL_TRACES_SAMPLER_ARG"} \
     "${DB_FLAGS[@]}" \
     "${STORAGE_FLAGS[@]}" \
     "$DB_SPEC"